@@ -0,0 +1,39 @@
+-- Candles, trading signals and notification delivery status
+
+CREATE TABLE IF NOT EXISTS candles (
+    exchange VARCHAR(50) NOT NULL,
+    symbol VARCHAR(50) NOT NULL,
+    interval VARCHAR(10) NOT NULL,
+    open_time TIMESTAMPTZ NOT NULL,
+    open DECIMAL(20, 8) NOT NULL,
+    high DECIMAL(20, 8) NOT NULL,
+    low DECIMAL(20, 8) NOT NULL,
+    close DECIMAL(20, 8) NOT NULL,
+    volume DECIMAL(20, 8) NOT NULL,
+    trades BIGINT NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    
+    PRIMARY KEY (exchange, symbol, interval, open_time)
+);
+
+CREATE INDEX IF NOT EXISTS idx_candles_open_time ON candles (open_time DESC);
+
+CREATE TABLE IF NOT EXISTS trading_signals (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    exchange VARCHAR(50) NOT NULL,
+    symbol VARCHAR(50) NOT NULL,
+    signal_type VARCHAR(10) NOT NULL,
+    pattern_kind VARCHAR(50),
+    status VARCHAR(20) NOT NULL DEFAULT 'pending',
+    price DECIMAL(20, 8) NOT NULL,
+    reason TEXT,
+    anomaly_id UUID,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_trading_signals_exchange_symbol ON trading_signals (exchange, symbol, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_trading_signals_status ON trading_signals (status);
+
+ALTER TABLE alerts ADD COLUMN IF NOT EXISTS status VARCHAR(20) NOT NULL DEFAULT 'pending';
+
+CREATE INDEX IF NOT EXISTS idx_alerts_status ON alerts (status);
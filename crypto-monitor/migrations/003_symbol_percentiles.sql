@@ -0,0 +1,15 @@
+-- Trailing 30-day daily distributions per symbol, recomputed nightly from candles
+
+CREATE TABLE IF NOT EXISTS symbol_percentiles (
+    exchange VARCHAR(50) NOT NULL,
+    symbol VARCHAR(50) NOT NULL,
+    volatility DOUBLE PRECISION[] NOT NULL,
+    volume DOUBLE PRECISION[] NOT NULL,
+    range DOUBLE PRECISION[] NOT NULL,
+    days_available INTEGER NOT NULL,
+    span_start DATE NOT NULL,
+    span_end DATE NOT NULL,
+    computed_at TIMESTAMPTZ NOT NULL,
+    
+    PRIMARY KEY (exchange, symbol)
+);
@@ -0,0 +1,7 @@
+-- Notification routing rules edited through the alerts config API, a single row
+
+CREATE TABLE IF NOT EXISTS notification_routing (
+    id SMALLINT PRIMARY KEY DEFAULT 1 CHECK (id = 1),
+    config JSONB NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
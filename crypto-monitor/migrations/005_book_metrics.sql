@@ -0,0 +1,14 @@
+-- Top-of-book metrics sampled at the book_metrics cadence, for spread and imbalance charts
+
+CREATE TABLE IF NOT EXISTS book_metrics (
+    exchange VARCHAR(50) NOT NULL,
+    symbol VARCHAR(50) NOT NULL,
+    timestamp TIMESTAMPTZ NOT NULL,
+    spread_bps DOUBLE PRECISION NOT NULL,
+    imbalance DOUBLE PRECISION NOT NULL,
+    microprice DOUBLE PRECISION NOT NULL,
+    
+    PRIMARY KEY (exchange, symbol, timestamp)
+);
+
+CREATE INDEX IF NOT EXISTS idx_book_metrics_timestamp ON book_metrics (timestamp DESC);
@@ -0,0 +1,14 @@
+-- Leader election leases, one row per coordination group
+CREATE TABLE IF NOT EXISTS coordination_leases (
+    name VARCHAR(100) PRIMARY KEY,
+    holder VARCHAR(100) NOT NULL,
+    acquired_at TIMESTAMPTZ NOT NULL,
+    renewed_at TIMESTAMPTZ NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+-- Detections persisted per instance; followers record theirs as shadow detections
+ALTER TABLE anomalies ADD COLUMN IF NOT EXISTS instance_id VARCHAR(100);
+ALTER TABLE anomalies ADD COLUMN IF NOT EXISTS shadow BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE INDEX IF NOT EXISTS idx_anomalies_instance ON anomalies (instance_id, detected_at DESC);
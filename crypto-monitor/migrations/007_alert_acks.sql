@@ -0,0 +1,7 @@
+-- Acknowledgement state of sent alerts, set through the alerts ack API
+
+ALTER TABLE alerts ADD COLUMN IF NOT EXISTS acked_by VARCHAR(255);
+ALTER TABLE alerts ADD COLUMN IF NOT EXISTS acked_at TIMESTAMPTZ;
+ALTER TABLE alerts ADD COLUMN IF NOT EXISTS ack_note TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_alerts_unacked ON alerts (created_at DESC) WHERE acked_at IS NULL;
@@ -0,0 +1,6 @@
+-- External system that published a detection through the ingest API or the anomalies topic,
+-- NULL for detections made by the monitor itself
+
+ALTER TABLE anomalies ADD COLUMN IF NOT EXISTS source VARCHAR(100);
+
+CREATE INDEX IF NOT EXISTS idx_anomalies_source ON anomalies (source, detected_at DESC) WHERE source IS NOT NULL;
@@ -0,0 +1,8 @@
+-- Anomaly detector windows, saved periodically so a restart resumes detection without a new
+-- warm-up. One row per saved state, overwritten on each save
+
+CREATE TABLE IF NOT EXISTS detector_state (
+    name VARCHAR(100) PRIMARY KEY,
+    saved_at TIMESTAMPTZ NOT NULL,
+    state JSONB NOT NULL
+);
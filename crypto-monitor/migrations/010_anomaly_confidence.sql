@@ -0,0 +1,3 @@
+-- Detector confidence from 0 to 1, NULL for anomalies recorded before it was reported
+
+ALTER TABLE anomalies ADD COLUMN IF NOT EXISTS confidence DOUBLE PRECISION;
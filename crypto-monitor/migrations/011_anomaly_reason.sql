@@ -0,0 +1,4 @@
+-- Structured form of the description (kind, observed, baseline, threshold, window), NULL for
+-- external anomalies and those recorded before detectors reported it
+
+ALTER TABLE anomalies ADD COLUMN IF NOT EXISTS reason JSONB;
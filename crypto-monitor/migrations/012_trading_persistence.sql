@@ -0,0 +1,24 @@
+-- Open positions are reloaded at startup so a restart keeps managing them. Every executed
+-- order is recorded in `trades` against the position it opened, grew or closed
+
+ALTER TABLE positions ADD COLUMN IF NOT EXISTS adds INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS trades (
+    id UUID PRIMARY KEY,
+    position_id UUID NOT NULL REFERENCES positions(id),
+    order_id VARCHAR(255),
+    exchange VARCHAR(50) NOT NULL,
+    symbol VARCHAR(50) NOT NULL,
+    side VARCHAR(10) NOT NULL,
+    -- entry | add | close
+    kind VARCHAR(10) NOT NULL,
+    quantity DECIMAL(20, 8) NOT NULL,
+    price DECIMAL(20, 8) NOT NULL,
+    -- Set on closes
+    realized_pnl DECIMAL(20, 8),
+    executed_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_trades_position_id ON trades (position_id);
+CREATE INDEX IF NOT EXISTS idx_trades_executed_at ON trades (executed_at DESC);
+CREATE INDEX IF NOT EXISTS idx_positions_open ON positions (status) WHERE status = 'open';
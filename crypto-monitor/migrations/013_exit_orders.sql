@@ -0,0 +1,5 @@
+-- Exit orders resting on the exchange for a position, kept so a restart still recognizes
+-- their fills and can cancel the one left over
+
+ALTER TABLE positions ADD COLUMN IF NOT EXISTS stop_order_id VARCHAR(255);
+ALTER TABLE positions ADD COLUMN IF NOT EXISTS take_profit_order_id VARCHAR(255);
@@ -0,0 +1,4 @@
+-- Fees paid on each fill and in total per position, realized PnL is net of them
+
+ALTER TABLE positions ADD COLUMN IF NOT EXISTS fees DECIMAL(20, 8) NOT NULL DEFAULT 0;
+ALTER TABLE trades ADD COLUMN IF NOT EXISTS fee DECIMAL(20, 8) NOT NULL DEFAULT 0;
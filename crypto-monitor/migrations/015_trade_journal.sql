@@ -0,0 +1,13 @@
+-- Trade journal records evicted from the trader's in-memory journal. `record` is the record
+-- as exported, `event` the signal outcome or lifecycle event it describes
+
+CREATE TABLE IF NOT EXISTS trade_journal (
+    id UUID PRIMARY KEY,
+    recorded_at TIMESTAMPTZ NOT NULL,
+    exchange VARCHAR(50) NOT NULL,
+    symbol VARCHAR(50) NOT NULL,
+    event VARCHAR(50) NOT NULL,
+    record JSONB NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_trade_journal_recorded_at ON trade_journal (recorded_at);
@@ -0,0 +1,4 @@
+-- The anomaly list pages newest first by (detected_at, id), the id breaking ties between
+-- detections made at the same instant
+
+CREATE INDEX IF NOT EXISTS idx_anomalies_detected_at_id ON anomalies (detected_at DESC, id DESC);
@@ -0,0 +1,8 @@
+-- Trader state other than positions that a restart must not lose, such as a circuit breaker
+-- halt. One row per named document, overwritten on each save
+
+CREATE TABLE IF NOT EXISTS trader_state (
+    name VARCHAR(100) PRIMARY KEY,
+    saved_at TIMESTAMPTZ NOT NULL,
+    state JSONB NOT NULL
+);
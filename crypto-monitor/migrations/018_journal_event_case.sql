@@ -0,0 +1,8 @@
+-- Signal outcomes were stored as RejectedByRisk while lifecycle events were order_filled,
+-- both are snake case from here on
+
+UPDATE trade_journal
+SET event = lower(regexp_replace(event, '([a-z])([A-Z])', '\1_\2', 'g'))
+WHERE event ~ '[A-Z]';
+
+CREATE INDEX IF NOT EXISTS idx_trade_journal_recorded_at_id ON trade_journal (recorded_at, id);
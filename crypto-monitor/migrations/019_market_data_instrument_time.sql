@@ -0,0 +1,4 @@
+-- Market stats read each instrument's first and last price in the window with an ordered
+-- LIMIT 1, which this index serves in both directions
+
+CREATE INDEX IF NOT EXISTS idx_market_data_instrument_time ON market_data (exchange, symbol, timestamp);
@@ -0,0 +1,70 @@
+// Trades from several threads through one AnomalyDetectorManager: every thread on its own pair
+// with per-pair locking, the same behind one global lock as `process_trade` used to be, and every
+// thread on a single pair, which still serializes.
+// Run with `cargo bench -p monitor-anomaly --bench manager_contention`.
+use chrono::{Duration, TimeZone, Utc};
+use monitor_anomaly::{
+    detector::AnomalyDetectorManager, PriceAnomalyConfig, TradeSample, VolumeAnomalyConfig,
+};
+use monitor_core::instrument::InstrumentKey;
+use parking_lot::Mutex;
+use std::{hint::black_box, time::Instant};
+
+const TRADES_PER_THREAD: usize = 50_000;
+
+fn manager() -> AnomalyDetectorManager {
+    AnomalyDetectorManager::new(VolumeAnomalyConfig::default(), PriceAnomalyConfig::default())
+}
+
+// Trades per second across all threads, thread `t` trading on `keys[t]`
+fn measure(threads: usize, keys: &[InstrumentKey], global: Option<&Mutex<()>>) -> f64 {
+    let manager = manager();
+    let start = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+    let started = Instant::now();
+    std::thread::scope(|scope| {
+        for key in keys.iter().take(threads) {
+            let manager = &manager;
+            scope.spawn(move || {
+                for i in 0..TRADES_PER_THREAD {
+                    let trade = TradeSample {
+                        timestamp: start + Duration::milliseconds(i as i64),
+                        price: 100.0 + (i % 13) as f64 * 0.01,
+                        volume: 1.0 + (i % 5) as f64,
+                    };
+                    let _serialized = global.map(|lock| lock.lock());
+                    black_box(manager.process_trade_keyed(key, &trade));
+                }
+            });
+        }
+    });
+    (threads * TRADES_PER_THREAD) as f64 / started.elapsed().as_secs_f64()
+}
+
+fn main() {
+    let cores = std::thread::available_parallelism().map_or(4, |n| n.get());
+    let distinct: Vec<InstrumentKey> = (0..cores)
+        .map(|i| InstrumentKey::new("binance", &format!("SYM{}/USDT", i)))
+        .collect();
+    let shared = vec![distinct[0].clone(); cores];
+    let global = Mutex::new(());
+
+    println!("{} trades per thread", TRADES_PER_THREAD);
+    let single = measure(1, &distinct, None);
+    println!(" 1 thread:                          {:>12.0} trades/s", single);
+    let mut counts = vec![2, 4, cores];
+    counts.retain(|threads| *threads <= cores);
+    counts.dedup();
+    for threads in counts {
+        let per_pair = measure(threads, &distinct, None);
+        let locked = measure(threads, &distinct, Some(&global));
+        let one_pair = measure(threads, &shared, None);
+        println!(
+            "{:>2} threads, distinct pairs:       {:>12.0} trades/s ({:.2}x one thread)",
+            threads,
+            per_pair,
+            per_pair / single
+        );
+        println!("{:>2} threads, one global lock:      {:>12.0} trades/s", threads, locked);
+        println!("{:>2} threads, all on one pair:      {:>12.0} trades/s", threads, one_pair);
+    }
+}
@@ -0,0 +1,110 @@
+// Trades through `AnomalyDetectorManager::process_trade`, which takes the pair as `&str`s and
+// looks its key up per trade, against `process_trade_keyed` with keys built up front. Matching
+// rates and allocations mean the lookup neither allocates nor contends once a thread has seen
+// the pair.
+// Run with `cargo bench -p monitor-anomaly --bench manager_keys`.
+use chrono::{Duration, TimeZone, Utc};
+use monitor_anomaly::{
+    detector::AnomalyDetectorManager, PriceAnomalyConfig, TradeSample, VolumeAnomalyConfig,
+};
+use monitor_core::instrument::InstrumentKey;
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    hint::black_box,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+const TRADES_PER_THREAD: usize = 200_000;
+const SYMBOLS_PER_THREAD: usize = 4;
+
+// Counts every allocation the benchmark makes
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn trade(i: usize) -> TradeSample {
+    let start = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+    TradeSample {
+        timestamp: start + Duration::milliseconds(i as i64),
+        price: 100.0 + (i % 13) as f64 * 0.01,
+        volume: 1.0 + (i % 5) as f64,
+    }
+}
+
+fn thread_symbols(thread: usize) -> Vec<String> {
+    (0..SYMBOLS_PER_THREAD).map(|s| format!("SYM{}-{}/USDT", thread, s)).collect()
+}
+
+// Trades per second across all threads and allocations per trade, thread `t` cycling through
+// its own symbols. The pairs' detectors are built before the clock starts, each thread's key
+// cache fills on its first trade per pair
+fn measure(threads: usize, keyed: bool) -> (f64, f64) {
+    let manager =
+        AnomalyDetectorManager::new(VolumeAnomalyConfig::default(), PriceAnomalyConfig::default());
+    let symbols: Vec<Vec<String>> = (0..threads).map(thread_symbols).collect();
+    for symbols in &symbols {
+        for symbol in symbols {
+            manager.process_trade(symbol, "binance", &trade(0));
+        }
+    }
+
+    let trades: Vec<TradeSample> = (1..=TRADES_PER_THREAD).map(trade).collect();
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let started = Instant::now();
+    std::thread::scope(|scope| {
+        for symbols in &symbols {
+            let (manager, trades) = (&manager, &trades);
+            scope.spawn(move || {
+                let keys: Vec<InstrumentKey> =
+                    symbols.iter().map(|s| InstrumentKey::new("binance", s)).collect();
+                for (i, trade) in trades.iter().enumerate() {
+                    let n = i % SYMBOLS_PER_THREAD;
+                    let detections = if keyed {
+                        manager.process_trade_keyed(&keys[n], trade)
+                    } else {
+                        manager.process_trade(&symbols[n], "binance", trade)
+                    };
+                    black_box(detections);
+                }
+            });
+        }
+    });
+    let elapsed = started.elapsed().as_secs_f64();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    let total = threads * TRADES_PER_THREAD;
+    (total as f64 / elapsed, allocations as f64 / total as f64)
+}
+
+fn main() {
+    let cores = std::thread::available_parallelism().map_or(4, |n| n.get());
+    println!("{} trades per thread over {} pairs each", TRADES_PER_THREAD, SYMBOLS_PER_THREAD);
+    let mut counts = vec![1, cores];
+    counts.dedup();
+    for threads in counts {
+        let (str_rate, str_allocs) = measure(threads, false);
+        let (key_rate, key_allocs) = measure(threads, true);
+        println!(
+            "{:>2} threads, process_trade(&str):  {:>12.0} trades/s, {:.2} allocations/trade",
+            threads, str_rate, str_allocs
+        );
+        println!(
+            "{:>2} threads, process_trade_keyed:  {:>12.0} trades/s, {:.2} allocations/trade",
+            threads, key_rate, key_allocs
+        );
+    }
+}
@@ -0,0 +1,74 @@
+// Replays a recorded `timestamp,value` series through the volume or price detector and prints
+// the report as JSON.
+//
+//   cargo run -p monitor-anomaly --example backtest -- tests/fixtures/volume_spikes.csv \
+//       --labels tests/fixtures/volume_labels.csv
+
+use monitor_anomaly::{
+    backtest::{load_csv, load_labels, run_backtest, BacktestConfig, BacktestDetector},
+    PriceAnomalyConfig, VolumeAnomalyConfig,
+};
+use std::path::PathBuf;
+
+const USAGE: &str = "usage: backtest <series.csv> [--kind volume|price] [--z <threshold>] \
+                     [--window <samples>] [--labels <labels.csv>] [--tolerance <secs>]";
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let mut series: Option<PathBuf> = None;
+    let mut kind = "volume".to_string();
+    let mut z: Option<f64> = None;
+    let mut window: Option<usize> = None;
+    let mut labels: Option<PathBuf> = None;
+    let mut tolerance: Option<u64> = None;
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(USAGE);
+        match arg.as_str() {
+            "--kind" => kind = value()?,
+            "--z" => z = Some(value()?.parse()?),
+            "--window" => window = Some(value()?.parse()?),
+            "--labels" => labels = Some(value()?.into()),
+            "--tolerance" => tolerance = Some(value()?.parse()?),
+            _ if series.is_none() && !arg.starts_with("--") => series = Some(arg.into()),
+            _ => return Err(USAGE.into()),
+        }
+    }
+    let series = series.ok_or(USAGE)?;
+
+    let detector = match kind.as_str() {
+        "volume" => {
+            let mut config = VolumeAnomalyConfig::default();
+            if let Some(z) = z {
+                config.z_score_threshold = z;
+            }
+            if let Some(window) = window {
+                config.window_size = window;
+            }
+            BacktestDetector::Volume(config)
+        }
+        "price" => {
+            let mut config = PriceAnomalyConfig::default();
+            if let Some(z) = z {
+                config.z_score_threshold = z;
+            }
+            if let Some(window) = window {
+                config.window_size = window;
+            }
+            BacktestDetector::Price(config)
+        }
+        _ => return Err(USAGE.into()),
+    };
+
+    let mut config = BacktestConfig::new(detector);
+    if let Some(labels) = labels {
+        config = config.with_labels(load_labels(&labels)?);
+    }
+    if let Some(tolerance) = tolerance {
+        config.match_tolerance_secs = tolerance;
+    }
+
+    let report = run_backtest(&config, load_csv(&series)?.into_iter());
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
@@ -0,0 +1,196 @@
+/// 实时加密货币监控系统演示
+/// 
+/// 功能：
+/// 1. 实时监控多个交易所的价格和成交量
+/// 2. 异常检测（价格突变、成交量异常）
+/// 3. 实时统计和报告
+/// 4. 自动告警功能
+
+use barter_data::{
+    exchange::{
+        binance::{futures::BinanceFuturesUsd, spot::BinanceSpot},
+        bybit::spot::BybitSpot,
+        okx::Okx,
+    },
+    streams::{Streams, reconnect::stream::ReconnectingStream},
+    subscription::trade::PublicTrades,
+};
+use barter_instrument::instrument::market_data::kind::MarketDataInstrumentKind;
+use chrono::Utc;
+use monitor_anomaly::threshold::{SimpleThresholdConfig, SimpleThresholdDetector, ThresholdReport};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio_stream::StreamExt;
+use tracing::{error, info, warn};
+
+/// 统计报告间隔（秒）
+const REPORT_INTERVAL_SECS: u64 = 10;
+
+/// 基础版配置：固定阈值，不启用动态调整
+fn monitor_config() -> SimpleThresholdConfig {
+    SimpleThresholdConfig {
+        price_change_threshold: 2.0,     // 2% 价格变化触发告警
+        volume_multiplier_base: 3.0,     // 3倍平均成交量触发告警
+        volume_multiplier_max: 3.0,
+        window_size: 100,                // 保留最近100条记录
+        min_samples: 10,
+        dynamic_threshold: false,
+        alert_cooldown_secs: 0,
+        ..Default::default()
+    }
+}
+
+fn print_report(report: &ThresholdReport) {
+    println!("\n================== 监控系统报告 ==================");
+    println!("运行时间: {} 秒 | 总事件数: {} | 速率: {:.1} 事件/秒",
+             report.elapsed_secs, report.total_events, report.events_per_sec);
+    println!("--------------------------------------------------");
+    
+    for stats in &report.symbols {
+        println!(
+            "📈 {} {} - 价格: ${:.2} | 成交量: {:.4} | 交易数: {} | 波动率: {:.3}% | 异常: {}",
+            stats.exchange, stats.symbol, stats.last_price, stats.total_volume,
+            stats.trade_count, stats.price_volatility, stats.anomalies_detected
+        );
+    }
+    
+    println!("==================================================\n");
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // 初始化日志
+    init_logging();
+    
+    println!("\n🚀 启动加密货币实时监控系统");
+    println!("===================================");
+    println!("监控交易所: Binance, OKX, Bybit");
+    println!("监控币种: BTC/USDT, ETH/USDT");
+    println!("功能: 价格监控, 成交量分析, 异常检测");
+    println!("===================================\n");
+    
+    // 创建监控系统
+    let detector = Arc::new(Mutex::new(SimpleThresholdDetector::new(monitor_config())));
+    
+    // 构建数据流
+    info!("初始化交易所数据流...");
+    let streams = Streams::<PublicTrades>::builder()
+        // Binance
+        .subscribe([
+            (BinanceSpot::default(), "btc", "usdt", MarketDataInstrumentKind::Spot, PublicTrades),
+            (BinanceSpot::default(), "eth", "usdt", MarketDataInstrumentKind::Spot, PublicTrades),
+        ])
+        .subscribe([
+            (BinanceFuturesUsd::default(), "btc", "usdt", MarketDataInstrumentKind::Perpetual, PublicTrades),
+            (BinanceFuturesUsd::default(), "eth", "usdt", MarketDataInstrumentKind::Perpetual, PublicTrades),
+        ])
+        // OKX
+        .subscribe([
+            (Okx, "btc", "usdt", MarketDataInstrumentKind::Spot, PublicTrades),
+            (Okx, "eth", "usdt", MarketDataInstrumentKind::Spot, PublicTrades),
+        ])
+        // Bybit
+        .subscribe([
+            (BybitSpot::default(), "btc", "usdt", MarketDataInstrumentKind::Spot, PublicTrades),
+            (BybitSpot::default(), "eth", "usdt", MarketDataInstrumentKind::Spot, PublicTrades),
+        ])
+        .init()
+        .await?;
+    
+    info!("✅ 数据流初始化成功，开始监控...\n");
+    
+    // 合并流
+    let mut joined_stream = streams
+        .select_all()
+        .with_error_handler(|error| {
+            error!("流错误: {:?}", error);
+        });
+    
+    // 启动定期报告任务
+    let report_detector = Arc::clone(&detector);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(REPORT_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            print_report(&report_detector.lock().await.report(Utc::now()));
+        }
+    });
+    
+    // 主监控循环
+    let test_duration = Duration::from_secs(60); // 运行60秒
+    let timeout = tokio::time::sleep(test_duration);
+    tokio::pin!(timeout);
+    
+    loop {
+        tokio::select! {
+            _ = &mut timeout => {
+                info!("\n监控时间结束");
+                break;
+            }
+            event = joined_stream.next() => {
+                if let Some(event) = event {
+                    match event {
+                        barter_data::streams::reconnect::Event::Item(market_event) => {
+                            // 提取交易所信息
+                            let debug_str = format!("{:?}", market_event);
+                            let exchange = if debug_str.contains("Binance") {
+                                "Binance"
+                            } else if debug_str.contains("Okx") {
+                                "OKX"
+                            } else if debug_str.contains("Bybit") {
+                                "Bybit"
+                            } else {
+                                "Unknown"
+                            };
+                            
+                            let symbol = format!("{}/{}",
+                                market_event.instrument.base,
+                                market_event.instrument.quote
+                            ).to_uppercase();
+                            
+                            let market_type = match market_event.instrument.kind {
+                                MarketDataInstrumentKind::Spot => "Spot",
+                                MarketDataInstrumentKind::Perpetual => "Futures",
+                                _ => "Unknown",
+                            };
+                            
+                            // 处理交易数据
+                            let detections = detector.lock().await.process_trade(
+                                &format!("{} {}", exchange, market_type),
+                                &symbol,
+                                market_event.kind.price,
+                                market_event.kind.amount,
+                                Utc::now(),
+                            );
+                            for detection in detections {
+                                warn!("⚠️ {:?}: {}", detection.anomaly_type, detection.description);
+                            }
+                        },
+                        barter_data::streams::reconnect::Event::Reconnecting(exchange_id) => {
+                            warn!("交易所重连中: {:?}", exchange_id);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    
+    // 生成最终报告
+    println!("\n🏁 监控系统关闭，生成最终报告...");
+    print_report(&detector.lock().await.report(Utc::now()));
+    
+    Ok(())
+}
+
+fn init_logging() {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::filter::EnvFilter::builder()
+                .with_default_directive(tracing_subscriber::filter::LevelFilter::INFO.into())
+                .from_env_lossy(),
+        )
+        .with_ansi(true)
+        .compact()
+        .init()
+}
\ No newline at end of file
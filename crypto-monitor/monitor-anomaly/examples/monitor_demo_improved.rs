@@ -0,0 +1,244 @@
+/// 改进版实时加密货币监控系统
+/// 
+/// 改进内容：
+/// 1. 优化异常检测敏感度
+/// 2. 动态调整阈值
+/// 3. 过滤 WebSocket ping/pong 错误
+/// 4. 更好的统计展示
+
+use barter_data::{
+    exchange::{
+        binance::{futures::BinanceFuturesUsd, spot::BinanceSpot},
+        bybit::spot::BybitSpot,
+        okx::Okx,
+    },
+    streams::{Streams, reconnect::stream::ReconnectingStream},
+    subscription::trade::PublicTrades,
+};
+use barter_instrument::instrument::market_data::kind::MarketDataInstrumentKind;
+use chrono::Utc;
+use monitor_anomaly::{
+    threshold::{SimpleThresholdConfig, SimpleThresholdDetector},
+    AnomalyDetection,
+};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio_stream::StreamExt;
+use tracing::{debug, error, info, warn};
+
+/// 统计报告间隔（秒）
+const REPORT_INTERVAL_SECS: u64 = 10;
+
+/// 改进的监控系统：检测逻辑由 SimpleThresholdDetector 提供，这里只负责错误过滤和输出
+struct MonitoringSystem {
+    detector: Mutex<SimpleThresholdDetector>,
+    error_count: AtomicU64,
+    filtered_errors: AtomicU64,
+}
+
+impl MonitoringSystem {
+    fn new(config: SimpleThresholdConfig) -> Self {
+        Self {
+            detector: Mutex::new(SimpleThresholdDetector::new(config)),
+            error_count: AtomicU64::new(0),
+            filtered_errors: AtomicU64::new(0),
+        }
+    }
+    
+    async fn process_trade(&self, exchange: &str, symbol: &str, price: f64, volume: f64) -> Vec<AnomalyDetection> {
+        self.detector
+            .lock()
+            .await
+            .process_trade(exchange, symbol, price, volume, Utc::now())
+    }
+    
+    fn handle_error(&self, error_msg: &str) {
+        // 过滤已知的 ping/pong 错误
+        if error_msg.contains("pong") || error_msg.contains("ping") || error_msg.contains("subscription_id") {
+            self.filtered_errors.fetch_add(1, Ordering::Relaxed);
+            debug!("Filtered known error: {}", error_msg);
+        } else {
+            self.error_count.fetch_add(1, Ordering::Relaxed);
+            error!("Stream error: {}", error_msg);
+        }
+    }
+    
+    async fn generate_report(&self) {
+        let detector = self.detector.lock().await;
+        let report = detector.report(Utc::now());
+        let config = detector.config();
+        
+        println!("\n================== 监控系统报告 ==================");
+        println!("运行时间: {} 秒 | 总事件: {} | 速率: {:.1} 事件/秒",
+                 report.elapsed_secs, report.total_events, report.events_per_sec);
+        println!("错误统计: {} 个错误 | {} 个已过滤",
+                 self.error_count.load(Ordering::Relaxed),
+                 self.filtered_errors.load(Ordering::Relaxed));
+        println!("--------------------------------------------------");
+        
+        for stats in &report.symbols {
+            println!(
+                "📈 {} {} - 价格: ${:.2} | 均量: {:.4} | 总量: {:.2} | 交易: {} | 波动: {:.3}% | 异常: {} ({:.2}%)",
+                stats.exchange, stats.symbol, stats.last_price, stats.avg_volume, stats.total_volume,
+                stats.trade_count, stats.price_volatility, stats.anomalies_detected, stats.detection_rate_pct
+            );
+        }
+        
+        // 显示监控配置
+        println!("--------------------------------------------------");
+        println!("监控配置: 价格阈值 {:.1}% | 成交量倍数 {:.1}-{:.1}x | 动态阈值: {}",
+                 config.price_change_threshold,
+                 config.volume_multiplier_base,
+                 config.volume_multiplier_max,
+                 if config.dynamic_threshold { "启用" } else { "禁用" });
+        println!("==================================================\n");
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // 初始化日志（降低日志级别）
+    init_logging();
+    
+    println!("\n🚀 启动改进版加密货币实时监控系统 v2.0");
+    println!("=========================================");
+    println!("监控交易所: Binance, OKX, Bybit");
+    println!("监控币种: BTC/USDT, ETH/USDT");
+    println!("改进功能:");
+    println!("  ✅ 优化异常检测敏感度");
+    println!("  ✅ 动态阈值调整");
+    println!("  ✅ 过滤 WebSocket 噪音");
+    println!("  ✅ 改进统计显示");
+    println!("=========================================\n");
+    
+    // 创建监控系统
+    let monitoring_system = Arc::new(MonitoringSystem::new(SimpleThresholdConfig::default()));
+    
+    // 构建数据流
+    info!("初始化交易所数据流...");
+    let streams = Streams::<PublicTrades>::builder()
+        // Binance
+        .subscribe([
+            (BinanceSpot::default(), "btc", "usdt", MarketDataInstrumentKind::Spot, PublicTrades),
+            (BinanceSpot::default(), "eth", "usdt", MarketDataInstrumentKind::Spot, PublicTrades),
+        ])
+        .subscribe([
+            (BinanceFuturesUsd::default(), "btc", "usdt", MarketDataInstrumentKind::Perpetual, PublicTrades),
+            (BinanceFuturesUsd::default(), "eth", "usdt", MarketDataInstrumentKind::Perpetual, PublicTrades),
+        ])
+        // OKX
+        .subscribe([
+            (Okx, "btc", "usdt", MarketDataInstrumentKind::Spot, PublicTrades),
+            (Okx, "eth", "usdt", MarketDataInstrumentKind::Spot, PublicTrades),
+        ])
+        // Bybit
+        .subscribe([
+            (BybitSpot::default(), "btc", "usdt", MarketDataInstrumentKind::Spot, PublicTrades),
+            (BybitSpot::default(), "eth", "usdt", MarketDataInstrumentKind::Spot, PublicTrades),
+        ])
+        .init()
+        .await?;
+    
+    info!("✅ 数据流初始化成功，开始监控...\n");
+    
+    // 合并流（改进错误处理）
+    let error_handler = Arc::clone(&monitoring_system);
+    let mut joined_stream = streams
+        .select_all()
+        .with_error_handler(move |error| {
+            error_handler.handle_error(&format!("{:?}", error));
+        });
+    
+    // 启动定期报告任务
+    let report_system = Arc::clone(&monitoring_system);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(REPORT_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            report_system.generate_report().await;
+        }
+    });
+    
+    // 主监控循环
+    let test_duration = Duration::from_secs(60); // 运行60秒
+    let timeout = tokio::time::sleep(test_duration);
+    tokio::pin!(timeout);
+    
+    info!("监控系统运行中... (运行时间: {} 秒)", test_duration.as_secs());
+    
+    loop {
+        tokio::select! {
+            _ = &mut timeout => {
+                info!("\n⏰ 监控时间结束");
+                break;
+            }
+            event = joined_stream.next() => {
+                if let Some(event) = event {
+                    match event {
+                        barter_data::streams::reconnect::Event::Item(market_event) => {
+                            // 提取交易所信息
+                            let debug_str = format!("{:?}", market_event);
+                            let exchange = if debug_str.contains("Binance") {
+                                "Binance"
+                            } else if debug_str.contains("Okx") {
+                                "OKX"
+                            } else if debug_str.contains("Bybit") {
+                                "Bybit"
+                            } else {
+                                "Unknown"
+                            };
+                            
+                            let symbol = format!("{}/{}",
+                                market_event.instrument.base,
+                                market_event.instrument.quote
+                            ).to_uppercase();
+                            
+                            let market_type = match market_event.instrument.kind {
+                                MarketDataInstrumentKind::Spot => "Spot",
+                                MarketDataInstrumentKind::Perpetual => "Futures",
+                                _ => "Unknown",
+                            };
+                            
+                            // 处理交易数据
+                            let detections = monitoring_system.process_trade(
+                                &format!("{} {}", exchange, market_type),
+                                &symbol,
+                                market_event.kind.price,
+                                market_event.kind.amount,
+                            ).await;
+                            for detection in detections {
+                                warn!("⚠️ {:?} [{:?}]: {}", detection.anomaly_type, detection.severity, detection.description);
+                            }
+                        },
+                        barter_data::streams::reconnect::Event::Reconnecting(exchange_id) => {
+                            warn!("交易所重连中: {:?}", exchange_id);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    
+    // 生成最终报告
+    println!("\n🏁 监控系统关闭，生成最终报告...");
+    monitoring_system.generate_report().await;
+    
+    println!("\n✨ 监控系统已优雅关闭");
+    
+    Ok(())
+}
+
+fn init_logging() {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::filter::EnvFilter::builder()
+                .with_default_directive(tracing_subscriber::filter::LevelFilter::INFO.into())
+                .with_env_var("RUST_LOG")
+                .from_env_lossy(),
+        )
+        .with_ansi(true)
+        .compact()
+        .init()
+}
\ No newline at end of file
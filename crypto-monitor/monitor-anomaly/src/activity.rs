@@ -0,0 +1,338 @@
+use crate::warmup::WarmupTracker;
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use monitor_core::{
+    event::EventBuilder, exchange_status::ExchangeStatus, AlertType, EventSource, EventType,
+    ExpectedActivityConfig, MaintenanceWindow, MonitorEvent,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::VecDeque, sync::Arc};
+use tracing::warn;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum ActivityAlert {
+    // Nothing at all received from the exchange feed
+    FeedStale {
+        exchange: String,
+        last_seen: Option<DateTime<Utc>>,
+    },
+    // Feed is alive but no trades reached the pipeline for this symbol
+    NoActivity {
+        exchange: String,
+        symbol: String,
+        last_trade: Option<DateTime<Utc>>,
+        trades_last_hour: usize,
+        consumer_lag_ms: Option<i64>,
+    },
+}
+
+impl ActivityAlert {
+    pub fn to_event(&self) -> Option<MonitorEvent> {
+        let message = match self {
+            ActivityAlert::FeedStale { exchange, .. } => {
+                format!("No data received from {} feed", exchange)
+            }
+            ActivityAlert::NoActivity { exchange, symbol, trades_last_hour, .. } => format!(
+                "Feed for {} is up but only {} trades for {} were routed in the last hour",
+                exchange, trades_last_hour, symbol
+            ),
+        };
+
+        let mut data = serde_json::to_value(self).ok()?;
+        data["message"] = serde_json::Value::String(message);
+
+        EventBuilder::new()
+            .with_source(EventSource::Monitor)
+            .with_type(EventType::Alert(AlertType::Warning))
+            .with_data(data)
+            .build()
+    }
+}
+
+pub struct ActivityMonitor {
+    expectations: Vec<ExpectedActivityConfig>,
+    maintenance: Vec<MaintenanceWindow>,
+    // Latest self-reported exchange status and when it stops being trusted
+    exchange_status: DashMap<String, (ExchangeStatus, DateTime<Utc>)>,
+    feed_stale_after: Duration,
+    started_at: DateTime<Utc>,
+    trades: DashMap<String, VecDeque<DateTime<Utc>>>,
+    feeds: DashMap<String, DateTime<Utc>>,
+    consumer_lag_ms: DashMap<String, i64>,
+    // Keys currently in breach, so each breach alerts once until activity resumes
+    breached: DashMap<String, ()>,
+    warmup: Option<Arc<WarmupTracker>>,
+}
+
+impl ActivityMonitor {
+    pub fn new(
+        expectations: Vec<ExpectedActivityConfig>,
+        maintenance: Vec<MaintenanceWindow>,
+        feed_stale_after: Duration,
+    ) -> Self {
+        Self {
+            expectations,
+            maintenance,
+            exchange_status: DashMap::new(),
+            feed_stale_after,
+            started_at: Utc::now(),
+            trades: DashMap::new(),
+            feeds: DashMap::new(),
+            consumer_lag_ms: DashMap::new(),
+            breached: DashMap::new(),
+            warmup: None,
+        }
+    }
+
+    pub fn with_warmup(mut self, warmup: Arc<WarmupTracker>) -> Self {
+        self.warmup = Some(warmup);
+        self
+    }
+
+    // A degraded or down exchange is treated as in maintenance until `valid_for` after the poll,
+    // so a status feed that stops answering cannot suppress alerts forever
+    pub fn set_exchange_status(&self, status: &ExchangeStatus, valid_for: Duration) {
+        self.exchange_status.insert(
+            status.exchange.to_lowercase(),
+            (status.clone(), status.checked_at + valid_for),
+        );
+    }
+
+    pub fn exchange_status(&self, exchange: &str) -> Option<ExchangeStatus> {
+        self.exchange_status.get(&exchange.to_lowercase()).map(|s| s.0.clone())
+    }
+
+    // Any message from the exchange, routed or not
+    pub fn record_feed_message(&self, exchange: &str, at: DateTime<Utc>) {
+        self.feeds.insert(exchange.to_lowercase(), at);
+        self.breached.remove(&format!("feed:{}", exchange.to_lowercase()));
+    }
+
+    // A trade that reached the detection pipeline, `lag` is processing time minus event time
+    pub fn record_trade(&self, exchange: &str, symbol: &str, at: DateTime<Utc>, now: DateTime<Utc>) {
+        let key = Self::key(exchange, symbol);
+        {
+            let mut trades = self.trades.entry(key.clone()).or_default();
+            trades.push_back(at);
+            while trades.front().is_some_and(|t| now - *t > Duration::hours(1)) {
+                trades.pop_front();
+            }
+        }
+        self.consumer_lag_ms
+            .insert(exchange.to_lowercase(), (now - at).num_milliseconds());
+        self.breached.remove(&key);
+    }
+
+    pub fn evaluate(&self, now: DateTime<Utc>) -> Vec<ActivityAlert> {
+        let mut alerts = Vec::new();
+
+        for expectation in &self.expectations {
+            let exchange = expectation.exchange.to_lowercase();
+            let key = Self::key(&exchange, &expectation.symbol);
+
+            if self.in_maintenance(&exchange, now) {
+                continue;
+            }
+            if let Some(warmup) = &self.warmup {
+                if warmup.is_warming_up(&key, now) {
+                    continue;
+                }
+            }
+
+            // A silent feed is reported as feed staleness, never as missing activity
+            let feed_seen = self.feeds.get(&exchange).map(|t| *t);
+            let feed_reference = feed_seen.unwrap_or(self.started_at);
+            if now - feed_reference > self.feed_stale_after {
+                let feed_key = format!("feed:{}", exchange);
+                if self.breached.insert(feed_key, ()).is_none() {
+                    alerts.push(ActivityAlert::FeedStale {
+                        exchange: exchange.clone(),
+                        last_seen: feed_seen,
+                    });
+                }
+                continue;
+            }
+
+            let (last_trade, trades_last_hour) = self
+                .trades
+                .get(&key)
+                .map(|trades| {
+                    let recent = trades.iter().filter(|t| now - **t <= Duration::hours(1)).count();
+                    (trades.back().copied(), recent)
+                })
+                .unwrap_or((None, 0));
+
+            let silent_for = now - last_trade.unwrap_or(self.started_at);
+            let silent = silent_for > Duration::seconds(expectation.max_silent_secs as i64);
+            // The hourly rate is only meaningful once an hour of history can exist
+            let too_few = now - self.started_at >= Duration::hours(1)
+                && trades_last_hour < expectation.min_trades_per_hour;
+
+            if (silent || too_few) && self.breached.insert(key.clone(), ()).is_none() {
+                warn!("Expected activity breached for {}", key);
+                alerts.push(ActivityAlert::NoActivity {
+                    exchange: exchange.clone(),
+                    symbol: expectation.symbol.clone(),
+                    last_trade,
+                    trades_last_hour,
+                    consumer_lag_ms: self.consumer_lag_ms.get(&exchange).map(|l| *l),
+                });
+            }
+        }
+
+        alerts
+    }
+
+    fn in_maintenance(&self, exchange: &str, now: DateTime<Utc>) -> bool {
+        let reported = self
+            .exchange_status
+            .get(exchange)
+            .is_some_and(|s| s.0.is_incident() && now < s.1);
+
+        reported || self.maintenance.iter().any(|window| {
+            window
+                .exchange
+                .as_deref()
+                .is_none_or(|e| e.eq_ignore_ascii_case(exchange))
+                && window.start <= now
+                && now < window.end
+        })
+    }
+
+    fn key(exchange: &str, symbol: &str) -> String {
+        format!("{}:{}", exchange.to_lowercase(), symbol)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use monitor_core::{exchange_status::ExchangeHealth, WarmupConfig};
+
+    // Drives the monitor the way the market data simulator would: `feed` controls whether the
+    // exchange keeps sending messages, `route` whether trades reach the pipeline
+    fn simulate(monitor: &ActivityMonitor, start: DateTime<Utc>, secs: i64, feed: bool, route: bool) {
+        for s in 0..secs {
+            let at = start + Duration::seconds(s);
+            if feed {
+                monitor.record_feed_message("binance", at);
+                if route {
+                    monitor.record_trade("binance", "BTC/USDT", at, at + Duration::milliseconds(20));
+                }
+            }
+        }
+    }
+
+    fn monitor(maintenance: Vec<MaintenanceWindow>) -> ActivityMonitor {
+        let mut monitor = ActivityMonitor::new(
+            vec![ExpectedActivityConfig {
+                exchange: "binance".to_string(),
+                symbol: "BTC/USDT".to_string(),
+                min_trades_per_hour: 10,
+                max_silent_secs: 60,
+            }],
+            maintenance,
+            Duration::seconds(30),
+        );
+        monitor.started_at = Utc::now();
+        monitor
+    }
+
+    #[test]
+    fn test_feed_down_raises_staleness_not_activity_alert() {
+        let monitor = monitor(vec![]);
+        let start = monitor.started_at;
+        simulate(&monitor, start, 10, true, true);
+        simulate(&monitor, start + Duration::seconds(10), 120, false, false);
+
+        let alerts = monitor.evaluate(start + Duration::seconds(130));
+        assert_eq!(alerts.len(), 1);
+        assert!(matches!(alerts[0], ActivityAlert::FeedStale { .. }));
+    }
+
+    #[test]
+    fn test_feed_up_without_routed_trades_raises_activity_alert() {
+        let monitor = monitor(vec![]);
+        let start = monitor.started_at;
+        simulate(&monitor, start, 10, true, true);
+        simulate(&monitor, start + Duration::seconds(10), 120, true, false);
+
+        let alerts = monitor.evaluate(start + Duration::seconds(130));
+        assert_eq!(alerts.len(), 1);
+        match &alerts[0] {
+            ActivityAlert::NoActivity { last_trade, consumer_lag_ms, .. } => {
+                assert_eq!(*last_trade, Some(start + Duration::seconds(9)));
+                assert_eq!(*consumer_lag_ms, Some(20));
+            }
+            other => panic!("unexpected alert {:?}", other),
+        }
+
+        // Alerts once per breach, and clears when trades flow again
+        assert!(monitor.evaluate(start + Duration::seconds(131)).is_empty());
+        simulate(&monitor, start + Duration::seconds(131), 1, true, true);
+        assert!(monitor.evaluate(start + Duration::seconds(132)).is_empty());
+    }
+
+    #[test]
+    fn test_symbol_that_never_traded_alerts_once_warmed_up() {
+        let monitor = monitor(vec![]);
+        let start = monitor.started_at;
+        let warmup = WarmupConfig {
+            duration_secs: 60,
+            min_samples_per_symbol: 60,
+        };
+        let monitor = monitor.with_warmup(Arc::new(WarmupTracker::starting_at(warmup, start)));
+        simulate(&monitor, start, 120, true, false);
+
+        // Its warm-up runs on time alone, it will never have the samples
+        assert!(monitor.evaluate(start + Duration::seconds(30)).is_empty());
+        let alerts = monitor.evaluate(start + Duration::seconds(120));
+        assert!(matches!(
+            alerts[..],
+            [ActivityAlert::NoActivity { last_trade: None, trades_last_hour: 0, .. }]
+        ));
+    }
+
+    #[test]
+    fn test_maintenance_window_suppresses_alerts() {
+        let now = Utc::now();
+        let monitor = monitor(vec![MaintenanceWindow {
+            exchange: Some("binance".to_string()),
+            start: now,
+            end: now + Duration::hours(1),
+        }]);
+        let start = monitor.started_at;
+        simulate(&monitor, start, 120, true, false);
+
+        assert!(monitor.evaluate(start + Duration::seconds(120)).is_empty());
+    }
+
+    #[test]
+    fn test_reported_incident_suppresses_alerts_until_it_expires() {
+        let monitor = monitor(vec![]);
+        let start = monitor.started_at;
+        let status = |health, checked_at| ExchangeStatus {
+            exchange: "binance".to_string(),
+            health,
+            vendor_state: "1".to_string(),
+            message: Some("system maintenance".to_string()),
+            checked_at,
+        };
+        simulate(&monitor, start, 10, true, true);
+        monitor.set_exchange_status(&status(ExchangeHealth::Down, start), Duration::seconds(180));
+
+        assert!(monitor.evaluate(start + Duration::seconds(120)).is_empty());
+
+        // No fresh poll since, the last status is no longer trusted
+        let alerts = monitor.evaluate(start + Duration::seconds(181));
+        assert!(matches!(alerts[..], [ActivityAlert::FeedStale { .. }]));
+
+        // A healthy report never suppresses
+        let monitor = self::monitor(vec![]);
+        simulate(&monitor, start, 10, true, true);
+        monitor.set_exchange_status(&status(ExchangeHealth::Healthy, start), Duration::seconds(180));
+        assert_eq!(monitor.evaluate(start + Duration::seconds(120)).len(), 1);
+    }
+}
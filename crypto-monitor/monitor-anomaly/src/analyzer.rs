@@ -1,19 +1,53 @@
 use crate::{
-    AnomalyDetection, AnomalyMetrics, AnomalySeverity, TimeSeriesData,
+    confidence_from_exceedance, graded_severity, AnomalyDetection, AnomalyMetrics, AnomalySeverity,
+    TimeSeriesData, TradeSample,
     metrics::{MetricsCalculator, TrendDirection},
+    reason::{AnomalyReason, ReasonKind},
+    suppression::AlertSuppressor,
+    warmup::WarmupTracker,
 };
-use chrono::Utc;
-use monitor_core::AnomalyType;
-use std::collections::VecDeque;
+use chrono::{DateTime, Duration, Utc};
+use monitor_core::{AnomalyType, MarketAnalyzerConfig};
+use parking_lot::RwLock;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
 use tracing::{debug, info};
 
+pub use monitor_core::model::TradeSide;
+
+// Thresholds of the checks below, confidence grows with how far each is exceeded
+const UNUSUAL_VOLUME_RATIO: f64 = 5.0;
+const MANIPULATION_PRICE_CHANGE_PCT: f64 = 3.0;
+const FLASH_CRASH_DROP_PCT: f64 = 10.0;
+const PUMP_PCT: f64 = 20.0;
+const DUMP_PCT: f64 = 15.0;
+
+// Trades alternating sides this many times in a row look like wash trading
+const WASH_MIN_REPETITIONS: usize = 6;
+const WASH_WINDOW_SECS: i64 = 60;
+// Sizes within this fraction of each other count as the same size
+const WASH_SIZE_TOLERANCE: f64 = 0.01;
+// Largest price move across the pattern, as a fraction of the first price
+const WASH_MAX_PRICE_IMPACT: f64 = 0.001;
+
+struct SidedTrade {
+    timestamp: DateTime<Utc>,
+    price: f64,
+    volume: f64,
+    side: TradeSide,
+}
+
 pub struct MarketAnalyzer {
     symbol: String,
     exchange: String,
     metrics: MetricsCalculator,
     price_history: VecDeque<f64>,
     volume_history: VecDeque<f64>,
+    // Only trades whose aggressor side is known
+    sided_trades: VecDeque<SidedTrade>,
     max_history_size: usize,
+    // Event time of the latest trade, detections are stamped with it
+    last_trade_at: DateTime<Utc>,
 }
 
 impl MarketAnalyzer {
@@ -24,7 +58,9 @@ impl MarketAnalyzer {
             metrics: MetricsCalculator::new(),
             price_history: VecDeque::new(),
             volume_history: VecDeque::new(),
+            sided_trades: VecDeque::new(),
             max_history_size: 1000,
+            last_trade_at: Utc::now(),
         }
     }
     
@@ -32,14 +68,38 @@ impl MarketAnalyzer {
         &mut self,
         price: f64,
         volume: f64,
+        side: Option<TradeSide>,
+    ) -> Vec<AnomalyDetection> {
+        let trade = TradeSample {
+            timestamp: Utc::now(),
+            price,
+            volume,
+        };
+        self.analyze_trade(&trade, side)
+    }
+    
+    // Windows and detections run on the trade's event time, so a replay sees what live did
+    pub fn analyze_trade(
+        &mut self,
+        trade: &TradeSample,
+        side: Option<TradeSide>,
     ) -> Vec<AnomalyDetection> {
         let mut anomalies = Vec::new();
+        let (timestamp, price, volume) = (trade.timestamp, trade.price, trade.volume);
+        self.last_trade_at = timestamp;
         
         // Update history
         self.update_history(price, volume);
+        if let Some(side) = side {
+            self.record_sided_trade(SidedTrade {
+                timestamp,
+                price,
+                volume,
+                side,
+            });
+        }
         
         // Update metrics
-        let timestamp = Utc::now();
         self.metrics.add_data(
             "price",
             TimeSeriesData { timestamp, value: price },
@@ -68,9 +128,26 @@ impl MarketAnalyzer {
             anomalies.push(anomaly);
         }
         
+        if side.is_some() {
+            if let Some(anomaly) = self.check_wash_trading() {
+                anomalies.push(anomaly);
+            }
+        }
+        
         anomalies
     }
     
+    fn record_sided_trade(&mut self, trade: SidedTrade) {
+        let cutoff = trade.timestamp - Duration::seconds(WASH_WINDOW_SECS);
+        while self.sided_trades.front().is_some_and(|t| t.timestamp < cutoff) {
+            self.sided_trades.pop_front();
+        }
+        self.sided_trades.push_back(trade);
+        if self.sided_trades.len() > self.max_history_size {
+            self.sided_trades.pop_front();
+        }
+    }
+    
     fn update_history(&mut self, price: f64, volume: f64) {
         self.price_history.push_back(price);
         if self.price_history.len() > self.max_history_size {
@@ -94,25 +171,28 @@ impl MarketAnalyzer {
         
         let volume_ratio = current_volume / avg_volume;
         
-        if volume_ratio > 5.0 {
-            let description = format!(
-                "Unusual volume detected for {}/{}: current volume is {:.1}x average",
-                self.exchange, self.symbol, volume_ratio
-            );
+        if volume_ratio > UNUSUAL_VOLUME_RATIO {
+            let confidence = confidence_from_exceedance(volume_ratio, UNUSUAL_VOLUME_RATIO);
+            let reason = AnomalyReason::new(
+                &self.exchange,
+                &self.symbol,
+                current_volume,
+                ReasonKind::UnusualVolume { ratio: volume_ratio },
+            )
+            .with_baseline(avg_volume)
+            .with_threshold(UNUSUAL_VOLUME_RATIO);
+            let description = reason.to_string();
             
             info!("{}", description);
             
             return Some(AnomalyDetection {
                 id: uuid::Uuid::new_v4(),
-                timestamp: Utc::now(),
+                timestamp: self.last_trade_at,
                 symbol: self.symbol.clone(),
                 exchange: self.exchange.clone(),
                 anomaly_type: AnomalyType::UnusualActivity,
-                severity: if volume_ratio > 10.0 {
-                    AnomalySeverity::Critical
-                } else {
-                    AnomalySeverity::High
-                },
+                severity: graded_severity("unusual volume", AnomalySeverity::High, confidence),
+                confidence,
                 metrics: AnomalyMetrics {
                     current_value: current_volume,
                     expected_value: avg_volume,
@@ -121,8 +201,11 @@ impl MarketAnalyzer {
                     percentage_change: Some((volume_ratio - 1.0) * 100.0),
                     historical_avg: Some(avg_volume),
                     historical_std: None,
+                    ..Default::default()
                 },
                 description,
+                reason: Some(reason),
+                ..Default::default()
             });
         }
         
@@ -153,21 +236,35 @@ impl MarketAnalyzer {
             / self.volume_history.len() as f64;
         
         // Suspicious if large price change with below-average volume
-        if price_change.abs() > 3.0 && avg_volume < historical_avg_volume * 0.5 {
-            let description = format!(
-                "Potential price manipulation for {}/{}: {:.2}% price change with low volume",
-                self.exchange, self.symbol, price_change
-            );
+        if price_change.abs() > MANIPULATION_PRICE_CHANGE_PCT
+            && avg_volume < historical_avg_volume * 0.5
+        {
+            let confidence =
+                confidence_from_exceedance(price_change.abs(), MANIPULATION_PRICE_CHANGE_PCT);
+            let reason = AnomalyReason::new(
+                &self.exchange,
+                &self.symbol,
+                recent_prices[0],
+                ReasonKind::LowVolumeMove { change_pct: price_change },
+            )
+            .with_baseline(recent_prices[9])
+            .with_threshold(MANIPULATION_PRICE_CHANGE_PCT);
+            let description = reason.to_string();
             
             info!("{}", description);
             
             return Some(AnomalyDetection {
                 id: uuid::Uuid::new_v4(),
-                timestamp: Utc::now(),
+                timestamp: self.last_trade_at,
                 symbol: self.symbol.clone(),
                 exchange: self.exchange.clone(),
                 anomaly_type: AnomalyType::UnusualActivity,
-                severity: AnomalySeverity::High,
+                severity: graded_severity(
+                    "price manipulation",
+                    AnomalySeverity::High,
+                    confidence,
+                ),
+                confidence,
                 metrics: AnomalyMetrics {
                     current_value: recent_prices[0],
                     expected_value: recent_prices[9],
@@ -176,8 +273,11 @@ impl MarketAnalyzer {
                     percentage_change: Some(price_change),
                     historical_avg: Some(historical_avg_volume),
                     historical_std: None,
+                    ..Default::default()
                 },
                 description,
+                reason: Some(reason),
+                ..Default::default()
             });
         }
         
@@ -202,21 +302,28 @@ impl MarketAnalyzer {
         
         let drop_percentage = ((max_price - min_price) / max_price) * 100.0;
         
-        if drop_percentage > 10.0 && current_price < max_price * 0.9 {
-            let description = format!(
-                "Flash crash detected for {}/{}: {:.2}% drop in 5 periods",
-                self.exchange, self.symbol, drop_percentage
-            );
+        if drop_percentage > FLASH_CRASH_DROP_PCT && current_price < max_price * 0.9 {
+            let confidence = confidence_from_exceedance(drop_percentage, FLASH_CRASH_DROP_PCT);
+            let reason = AnomalyReason::new(
+                &self.exchange,
+                &self.symbol,
+                current_price,
+                ReasonKind::FlashCrash { drop_pct: drop_percentage, periods: recent_prices.len() },
+            )
+            .with_baseline(max_price)
+            .with_threshold(FLASH_CRASH_DROP_PCT);
+            let description = reason.to_string();
             
             info!("{}", description);
             
             return Some(AnomalyDetection {
                 id: uuid::Uuid::new_v4(),
-                timestamp: Utc::now(),
+                timestamp: self.last_trade_at,
                 symbol: self.symbol.clone(),
                 exchange: self.exchange.clone(),
                 anomaly_type: AnomalyType::PriceSpike,
-                severity: AnomalySeverity::Critical,
+                severity: graded_severity("flash crash", AnomalySeverity::Critical, confidence),
+                confidence,
                 metrics: AnomalyMetrics {
                     current_value: current_price,
                     expected_value: max_price,
@@ -225,8 +332,11 @@ impl MarketAnalyzer {
                     percentage_change: Some(-drop_percentage),
                     historical_avg: None,
                     historical_std: None,
+                    ..Default::default()
                 },
                 description,
+                reason: Some(reason),
+                ..Default::default()
             });
         }
         
@@ -268,21 +378,37 @@ impl MarketAnalyzer {
             let pump_percentage = ((peak_price - price_before) / price_before) * 100.0;
             let dump_percentage = ((peak_price - price_after) / peak_price) * 100.0;
             
-            if pump_percentage > 20.0 && dump_percentage > 15.0 {
-                let description = format!(
-                    "Pump and dump pattern detected for {}/{}: +{:.1}% pump, -{:.1}% dump",
-                    self.exchange, self.symbol, pump_percentage, dump_percentage
-                );
+            if pump_percentage > PUMP_PCT && dump_percentage > DUMP_PCT {
+                // Only as strong as the weaker leg of the pattern
+                let confidence = confidence_from_exceedance(pump_percentage, PUMP_PCT)
+                    .min(confidence_from_exceedance(dump_percentage, DUMP_PCT));
+                let reason = AnomalyReason::new(
+                    &self.exchange,
+                    &self.symbol,
+                    price_after,
+                    ReasonKind::PumpAndDump {
+                        pump_pct: pump_percentage,
+                        dump_pct: dump_percentage,
+                    },
+                )
+                .with_baseline(price_before)
+                .with_threshold(PUMP_PCT);
+                let description = reason.to_string();
                 
                 info!("{}", description);
                 
                 return Some(AnomalyDetection {
                     id: uuid::Uuid::new_v4(),
-                    timestamp: Utc::now(),
+                    timestamp: self.last_trade_at,
                     symbol: self.symbol.clone(),
                     exchange: self.exchange.clone(),
                     anomaly_type: AnomalyType::UnusualActivity,
-                    severity: AnomalySeverity::Critical,
+                    severity: graded_severity(
+                        "pump and dump",
+                        AnomalySeverity::Critical,
+                        confidence,
+                    ),
+                    confidence,
                     metrics: AnomalyMetrics {
                         current_value: price_after,
                         expected_value: price_before,
@@ -291,12 +417,326 @@ impl MarketAnalyzer {
                         percentage_change: Some(pump_percentage),
                         historical_avg: None,
                         historical_std: None,
+                        ..Default::default()
                     },
                     description,
+                    reason: Some(reason),
+                    ..Default::default()
                 });
             }
         }
         
         None
     }
-}
\ No newline at end of file
+    
+    // Counts back from the latest trade while sides keep alternating at near-identical sizes
+    // without moving the price, the signature of one party trading with itself
+    fn check_wash_trading(&self) -> Option<AnomalyDetection> {
+        let latest = self.sided_trades.back()?;
+        
+        let mut repetitions = 1;
+        let mut next_side = latest.side;
+        for trade in self.sided_trades.iter().rev().skip(1) {
+            let same_size =
+                (trade.volume - latest.volume).abs() <= latest.volume * WASH_SIZE_TOLERANCE;
+            let no_impact =
+                (trade.price - latest.price).abs() <= latest.price * WASH_MAX_PRICE_IMPACT;
+            if trade.side == next_side || !same_size || !no_impact {
+                break;
+            }
+            next_side = trade.side;
+            repetitions += 1;
+        }
+        
+        // Reported when the pattern first qualifies and again if it runs on twice as long
+        if repetitions != WASH_MIN_REPETITIONS && repetitions != WASH_MIN_REPETITIONS * 2 {
+            return None;
+        }
+        
+        let confidence =
+            confidence_from_exceedance(repetitions as f64, WASH_MIN_REPETITIONS as f64);
+        let pattern: Vec<&SidedTrade> = self.sided_trades.iter().rev().take(repetitions).collect();
+        let first = pattern[repetitions - 1];
+        let avg_size = pattern.iter().map(|t| t.volume).sum::<f64>() / repetitions as f64;
+        let price_impact = (latest.price - first.price) / first.price * 100.0;
+        let duration = (latest.timestamp - first.timestamp).num_milliseconds() as f64 / 1000.0;
+        
+        let reason = AnomalyReason::new(
+            &self.exchange,
+            &self.symbol,
+            repetitions as f64,
+            ReasonKind::WashTrading {
+                repetitions,
+                avg_size,
+                price_impact_pct: price_impact,
+            },
+        )
+        .with_threshold(WASH_MIN_REPETITIONS as f64)
+        .with_window_secs(duration);
+        let description = reason.to_string();
+        
+        info!("{}", description);
+        
+        Some(AnomalyDetection {
+            id: uuid::Uuid::new_v4(),
+            timestamp: latest.timestamp,
+            symbol: self.symbol.clone(),
+            exchange: self.exchange.clone(),
+            anomaly_type: AnomalyType::UnusualActivity,
+            severity: graded_severity("wash trading", AnomalySeverity::Medium, confidence),
+            confidence,
+            metrics: AnomalyMetrics {
+                current_value: latest.volume,
+                expected_value: avg_size,
+                deviation: latest.volume - avg_size,
+                z_score: None,
+                percentage_change: Some(price_impact),
+                historical_avg: Some(avg_size),
+                historical_std: None,
+                repetitions: Some(repetitions),
+                ..Default::default()
+            },
+            description,
+            reason: Some(reason),
+            ..Default::default()
+        })
+    }
+}
+
+// One `MarketAnalyzer` per exchange and symbol pair, created with the pair's first trade
+pub struct AnalyzerManager {
+    config: MarketAnalyzerConfig,
+    analyzers: RwLock<HashMap<String, MarketAnalyzer>>,
+    warmup: Option<Arc<WarmupTracker>>,
+    suppressor: Option<Arc<AlertSuppressor>>,
+}
+
+impl AnalyzerManager {
+    pub fn new(config: MarketAnalyzerConfig) -> Self {
+        Self {
+            config,
+            analyzers: RwLock::new(HashMap::new()),
+            warmup: None,
+            suppressor: None,
+        }
+    }
+
+    pub fn with_warmup(mut self, warmup: Arc<WarmupTracker>) -> Self {
+        self.warmup = Some(warmup);
+        self
+    }
+
+    pub fn with_suppressor(mut self, suppressor: Arc<AlertSuppressor>) -> Self {
+        self.suppressor = Some(suppressor);
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    pub fn process(
+        &self,
+        symbol: &str,
+        exchange: &str,
+        trade: &TradeSample,
+        side: Option<TradeSide>,
+    ) -> Vec<AnomalyDetection> {
+        let valid = trade.price.is_finite() && trade.price > 0.0 && trade.volume.is_finite();
+        if !self.config.enabled || !valid {
+            return Vec::new();
+        }
+
+        let key = format!("{}:{}", exchange, symbol);
+        let mut detections = self
+            .analyzers
+            .write()
+            .entry(key.clone())
+            .or_insert_with(|| MarketAnalyzer::new(symbol.to_string(), exchange.to_string()))
+            .analyze_trade(trade, side);
+
+        if let Some(warmup) = &self.warmup {
+            if warmup.is_warming_up(&key, trade.timestamp) {
+                for detection in &mut detections {
+                    detection.warmup = true;
+                }
+            }
+        }
+        if let Some(suppressor) = &self.suppressor {
+            detections.retain(|detection| suppressor.admit(detection));
+        }
+        detections
+    }
+
+    pub fn reset(&self, symbol: &str, exchange: &str) {
+        self.analyzers.write().remove(&format!("{}:{}", exchange, symbol));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn analyzer() -> MarketAnalyzer {
+        MarketAnalyzer::new("BTC/USDT".to_string(), "binance".to_string())
+    }
+
+    fn trade(volume: f64) -> TradeSample {
+        trade_at(Utc::now(), volume)
+    }
+
+    fn trade_at(timestamp: DateTime<Utc>, volume: f64) -> TradeSample {
+        TradeSample {
+            timestamp,
+            price: 100.0,
+            volume,
+        }
+    }
+
+    fn wash_detections(anomalies: &[AnomalyDetection]) -> Vec<&AnomalyDetection> {
+        anomalies.iter().filter(|a| a.metrics.repetitions.is_some()).collect()
+    }
+
+    #[test]
+    fn test_alternating_identical_trades_are_flagged() {
+        let mut analyzer = analyzer();
+        let mut anomalies = Vec::new();
+        for i in 0..14 {
+            let side = if i % 2 == 0 { TradeSide::Buy } else { TradeSide::Sell };
+            let size = 1.0 + (i % 3) as f64 * 0.002;
+            let price = 100.0 + (i % 2) as f64 * 0.01;
+            anomalies.extend(analyzer.analyze_market_data(price, size, Some(side)));
+        }
+
+        let wash = wash_detections(&anomalies);
+        assert_eq!(wash.len(), 2);
+        assert_eq!(wash[0].metrics.repetitions, Some(6));
+        assert_eq!(wash[0].anomaly_type, AnomalyType::UnusualActivity);
+        assert_eq!(wash[0].severity, AnomalySeverity::Medium);
+        assert!(wash[0].description.starts_with("Possible wash trading"));
+        assert_eq!(wash[1].metrics.repetitions, Some(12));
+        assert_eq!(wash[1].severity, AnomalySeverity::High);
+    }
+
+    #[test]
+    fn test_unusual_volume_confidence_sets_severity() {
+        let mut analyzer = analyzer();
+        for _ in 0..29 {
+            assert!(analyzer.analyze_market_data(100.0, 1.0, None).is_empty());
+        }
+
+        // 29 against an average of 1.93 including itself, 15x
+        let anomalies = analyzer.analyze_market_data(100.0, 29.0, None);
+        assert_eq!(anomalies.len(), 1);
+        assert!((anomalies[0].confidence - (1.0 - 5.0 / 30.0)).abs() < 1e-9);
+        assert_eq!(anomalies[0].severity, AnomalySeverity::High);
+        assert!(anomalies[0].description.contains("15.0x"));
+    }
+
+    #[test]
+    fn test_organic_trades_are_not_flagged() {
+        let mut analyzer = analyzer();
+        let mut state: u64 = 42;
+        let mut next = || {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            (state >> 11) as f64 / (1u64 << 53) as f64
+        };
+
+        let mut price = 100.0;
+        let mut anomalies = Vec::new();
+        for _ in 0..500 {
+            let side = if next() < 0.5 { TradeSide::Buy } else { TradeSide::Sell };
+            let size = 0.1 + next() * 4.9;
+            price *= 1.0 + (next() - 0.5) * 0.002;
+            anomalies.extend(analyzer.analyze_market_data(price, size, Some(side)));
+        }
+
+        assert!(wash_detections(&anomalies).is_empty());
+    }
+
+    #[test]
+    fn test_pattern_broken_by_size_price_or_missing_side() {
+        let mut analyzer = analyzer();
+        let mut anomalies = Vec::new();
+        for i in 0..12 {
+            let side = if i % 2 == 0 { TradeSide::Buy } else { TradeSide::Sell };
+            // A different size every fifth trade
+            let size = if i % 5 == 4 { 2.0 } else { 1.0 };
+            anomalies.extend(analyzer.analyze_market_data(100.0, size, Some(side)));
+        }
+        for i in 0..12 {
+            // Same sizes but the price walks away
+            let side = if i % 2 == 0 { TradeSide::Buy } else { TradeSide::Sell };
+            let price = 100.0 * (1.0 + i as f64 * 0.0015);
+            anomalies.extend(analyzer.analyze_market_data(price, 1.0, Some(side)));
+        }
+        for _ in 0..12 {
+            anomalies.extend(analyzer.analyze_market_data(100.0, 1.0, None));
+        }
+
+        assert!(wash_detections(&anomalies).is_empty());
+    }
+
+    #[test]
+    fn test_manager_keeps_one_analyzer_per_pair() {
+        let manager = AnalyzerManager::new(MarketAnalyzerConfig { enabled: true });
+        for _ in 0..29 {
+            assert!(manager.process("BTC/USDT", "binance", &trade(1.0), None).is_empty());
+            assert!(manager.process("BTC/USDT", "okx", &trade(29.0), None).is_empty());
+        }
+
+        // Only the pair whose own history is quiet sees the volume as unusual
+        assert!(manager.process("BTC/USDT", "okx", &trade(29.0), None).is_empty());
+        let anomalies = manager.process("BTC/USDT", "binance", &trade(29.0), None);
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].exchange, "binance");
+
+        // Starting over forgets the history
+        manager.reset("BTC/USDT", "binance");
+        assert!(manager.process("BTC/USDT", "binance", &trade(29.0), None).is_empty());
+
+        let disabled = AnalyzerManager::new(MarketAnalyzerConfig::default());
+        for _ in 0..40 {
+            assert!(disabled.process("BTC/USDT", "binance", &trade(29.0), None).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_manager_warmup_follows_event_time() {
+        use chrono::TimeZone;
+        use monitor_core::WarmupConfig;
+
+        // Started long before the wall clock, only the trades' own timestamps decide
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let warmup = Arc::new(WarmupTracker::starting_at(
+            WarmupConfig {
+                duration_secs: 60,
+                min_samples_per_symbol: 0,
+            },
+            start,
+        ));
+        let manager =
+            AnalyzerManager::new(MarketAnalyzerConfig { enabled: true }).with_warmup(warmup);
+
+        let spike = |offset: i64| {
+            let at = start + Duration::seconds(offset);
+            for _ in 0..29 {
+                manager.process("BTC/USDT", "binance", &trade_at(at, 1.0), None);
+            }
+            let anomalies = manager.process("BTC/USDT", "binance", &trade_at(at, 29.0), None);
+            manager.reset("BTC/USDT", "binance");
+            anomalies
+        };
+
+        let early = spike(10);
+        assert_eq!(early.len(), 1);
+        assert!(early[0].warmup);
+        assert_eq!(early[0].timestamp, start + Duration::seconds(10));
+
+        let late = spike(120);
+        assert_eq!(late.len(), 1);
+        assert!(!late[0].warmup);
+    }
+}
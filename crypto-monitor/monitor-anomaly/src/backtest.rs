@@ -0,0 +1,313 @@
+use crate::{
+    detector::AnomalyDetectorManager, AnomalyDetection, AnomalyDetector, AnomalySeverity,
+    PriceAnomalyConfig, TimeSeriesData, VolumeAnomalyConfig,
+};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use monitor_core::{MonitorError, Result};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::Path};
+
+const BACKTEST_SYMBOL: &str = "BACKTEST";
+const BACKTEST_EXCHANGE: &str = "backtest";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BacktestDetector {
+    Volume(VolumeAnomalyConfig),
+    Price(PriceAnomalyConfig),
+}
+
+impl BacktestDetector {
+    // The detector the live manager would run for this config on a window baseline
+    fn build(&self) -> Box<dyn AnomalyDetector> {
+        match self {
+            BacktestDetector::Volume(config) => {
+                AnomalyDetectorManager::new(config.clone(), PriceAnomalyConfig::default())
+                    .volume_detector(BACKTEST_SYMBOL, BACKTEST_EXCHANGE)
+            }
+            BacktestDetector::Price(config) => {
+                AnomalyDetectorManager::new(VolumeAnomalyConfig::default(), config.clone())
+                    .price_detector(BACKTEST_SYMBOL, BACKTEST_EXCHANGE)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestConfig {
+    pub detector: BacktestDetector,
+    // Known anomalies to score the detections against, none skips scoring
+    #[serde(default)]
+    pub labels: Vec<DateTime<Utc>>,
+    // A detection this close to a label counts as finding it
+    #[serde(default = "default_match_tolerance_secs")]
+    pub match_tolerance_secs: u64,
+}
+
+fn default_match_tolerance_secs() -> u64 {
+    5
+}
+
+impl BacktestConfig {
+    pub fn new(detector: BacktestDetector) -> Self {
+        Self {
+            detector,
+            labels: Vec::new(),
+            match_tolerance_secs: default_match_tolerance_secs(),
+        }
+    }
+
+    pub fn with_labels(mut self, labels: Vec<DateTime<Utc>>) -> Self {
+        self.labels = labels;
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BacktestDetection {
+    pub timestamp: DateTime<Utc>,
+    pub value: f64,
+    pub severity: AnomalySeverity,
+    pub confidence: f64,
+    pub z_score: Option<f64>,
+    // Whether a label lies within the match tolerance, `None` without labels
+    pub labeled: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LabelScore {
+    pub labels: usize,
+    // Detections within the tolerance of some label
+    pub true_positives: usize,
+    pub false_positives: usize,
+    // Labels with no detection within the tolerance
+    pub missed: usize,
+    pub precision: f64,
+    pub recall: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestReport {
+    pub samples: usize,
+    pub first_sample: Option<DateTime<Utc>>,
+    pub last_sample: Option<DateTime<Utc>>,
+    pub detections: Vec<BacktestDetection>,
+    pub by_severity: BTreeMap<String, usize>,
+    pub score: Option<LabelScore>,
+}
+
+impl BacktestReport {
+    pub fn detection_count(&self) -> usize {
+        self.detections.len()
+    }
+
+    pub fn detection_timestamps(&self) -> Vec<DateTime<Utc>> {
+        self.detections.iter().map(|d| d.timestamp).collect()
+    }
+}
+
+// Feeds the series through a fresh detector in order, exactly as live samples would arrive
+pub fn run_backtest(
+    config: &BacktestConfig,
+    data: impl Iterator<Item = TimeSeriesData>,
+) -> BacktestReport {
+    let mut detector = config.detector.build();
+    let mut report = BacktestReport {
+        samples: 0,
+        first_sample: None,
+        last_sample: None,
+        detections: Vec::new(),
+        by_severity: BTreeMap::new(),
+        score: None,
+    };
+
+    let mut found: Vec<AnomalyDetection> = Vec::new();
+    for sample in data {
+        report.samples += 1;
+        report.first_sample.get_or_insert(sample.timestamp);
+        report.last_sample = Some(sample.timestamp);
+        found.extend(detector.detect(&sample));
+    }
+
+    let tolerance = Duration::seconds(config.match_tolerance_secs as i64);
+    let near_label = |at: DateTime<Utc>| config.labels.iter().any(|l| (*l - at).abs() <= tolerance);
+    for detection in found {
+        *report.by_severity.entry(format!("{:?}", detection.severity)).or_default() += 1;
+        report.detections.push(BacktestDetection {
+            timestamp: detection.timestamp,
+            value: detection.metrics.current_value,
+            severity: detection.severity,
+            confidence: detection.confidence,
+            z_score: detection.metrics.z_score,
+            labeled: (!config.labels.is_empty()).then(|| near_label(detection.timestamp)),
+        });
+    }
+
+    if !config.labels.is_empty() {
+        let true_positives = report.detections.iter().filter(|d| d.labeled == Some(true)).count();
+        let found_labels = config
+            .labels
+            .iter()
+            .filter(|l| report.detections.iter().any(|d| (d.timestamp - **l).abs() <= tolerance))
+            .count();
+        report.score = Some(LabelScore {
+            labels: config.labels.len(),
+            true_positives,
+            false_positives: report.detections.len() - true_positives,
+            missed: config.labels.len() - found_labels,
+            precision: if report.detections.is_empty() {
+                0.0
+            } else {
+                true_positives as f64 / report.detections.len() as f64
+            },
+            recall: found_labels as f64 / config.labels.len() as f64,
+        });
+    }
+
+    report
+}
+
+// `timestamp,value` rows, timestamps as RFC 3339 or Unix milliseconds. A header row and blank
+// lines are skipped.
+pub fn load_csv(path: &Path) -> Result<Vec<TimeSeriesData>> {
+    read_rows(path)?
+        .into_iter()
+        .map(|(line, fields)| {
+            let [timestamp, value] = fields.as_slice() else {
+                return Err(row_error(path, line, "expected timestamp,value"));
+            };
+            let value = value
+                .parse::<f64>()
+                .map_err(|e| row_error(path, line, &format!("invalid value: {}", e)))?;
+            Ok(TimeSeriesData {
+                timestamp: parse_timestamp(timestamp).ok_or_else(|| {
+                    row_error(path, line, &format!("invalid timestamp {}", timestamp))
+                })?,
+                value,
+            })
+        })
+        .collect()
+}
+
+// One labeled anomaly per row, its timestamp in the first column
+pub fn load_labels(path: &Path) -> Result<Vec<DateTime<Utc>>> {
+    read_rows(path)?
+        .into_iter()
+        .map(|(line, fields)| {
+            parse_timestamp(&fields[0])
+                .ok_or_else(|| row_error(path, line, &format!("invalid timestamp {}", fields[0])))
+        })
+        .collect()
+}
+
+// Non-empty rows split on commas with their 1-based line numbers, minus a header
+fn read_rows(path: &Path) -> Result<Vec<(usize, Vec<String>)>> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        MonitorError::Other(format!("Failed to read {}: {}", path.display(), e))
+    })?;
+    let mut rows: Vec<(usize, Vec<String>)> = content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| (i + 1, line.split(',').map(|f| f.trim().to_string()).collect()))
+        .collect();
+    if rows.first().is_some_and(|(_, fields)| fields[0].eq_ignore_ascii_case("timestamp")) {
+        rows.remove(0);
+    }
+    Ok(rows)
+}
+
+fn parse_timestamp(field: &str) -> Option<DateTime<Utc>> {
+    if let Ok(at) = DateTime::parse_from_rfc3339(field) {
+        return Some(at.with_timezone(&Utc));
+    }
+    field
+        .parse::<i64>()
+        .ok()
+        .and_then(|ms| Utc.timestamp_millis_opt(ms).single())
+}
+
+fn row_error(path: &Path, line: usize, message: &str) -> MonitorError {
+    MonitorError::Other(format!("{} line {}: {}", path.display(), line, message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)
+    }
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap() + Duration::seconds(secs)
+    }
+
+    #[test]
+    fn test_volume_backtest_finds_fixture_spikes() {
+        let data = load_csv(&fixture("volume_spikes.csv")).unwrap();
+        assert_eq!(data.len(), 180);
+
+        let config = BacktestConfig::new(BacktestDetector::Volume(VolumeAnomalyConfig::default()));
+        let report = run_backtest(&config, data.into_iter());
+
+        assert_eq!(report.samples, 180);
+        assert_eq!(report.first_sample, Some(at(0)));
+        assert_eq!(report.detection_timestamps(), vec![at(45), at(90), at(150)]);
+        assert!(report.detections.iter().all(|d| d.labeled.is_none()));
+        assert_eq!(report.by_severity.get("Critical"), Some(&1));
+        assert_eq!(report.by_severity.get("High"), Some(&2));
+        assert!(report.score.is_none());
+
+        // A stricter threshold keeps only the largest spike
+        let strict = BacktestConfig::new(BacktestDetector::Volume(VolumeAnomalyConfig {
+            z_score_threshold: 10.0,
+            ..Default::default()
+        }));
+        let data = load_csv(&fixture("volume_spikes.csv")).unwrap();
+        assert_eq!(run_backtest(&strict, data.into_iter()).detection_timestamps(), vec![at(45)]);
+    }
+
+    #[test]
+    fn test_labels_score_precision_and_recall() {
+        let data = load_csv(&fixture("volume_spikes.csv")).unwrap();
+        let labels = load_labels(&fixture("volume_labels.csv")).unwrap();
+        assert_eq!(labels, vec![at(91), at(120), at(150)]);
+
+        let config = BacktestConfig::new(BacktestDetector::Volume(VolumeAnomalyConfig::default()))
+            .with_labels(labels);
+        let report = run_backtest(&config, data.into_iter());
+
+        // The spike at 45s is unlabeled and nothing happens at the 120s label
+        let score = report.score.clone().unwrap();
+        assert_eq!((score.labels, score.true_positives, score.false_positives), (3, 2, 1));
+        assert_eq!(score.missed, 1);
+        assert!((score.precision - 2.0 / 3.0).abs() < 1e-12);
+        assert!((score.recall - 2.0 / 3.0).abs() < 1e-12);
+        let labeled: Vec<_> = report.detections.iter().map(|d| d.labeled).collect();
+        assert_eq!(labeled, vec![Some(false), Some(true), Some(true)]);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["score"]["missed"], 1);
+    }
+
+    #[test]
+    fn test_csv_loader_accepts_epoch_millis_and_reports_bad_rows() {
+        let dir = std::env::temp_dir().join(format!("backtest-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let good = dir.join("good.csv");
+        std::fs::write(&good, "1709251200000,1.5\n\n1709251201000, 2.5\n").unwrap();
+        let data = load_csv(&good).unwrap();
+        assert_eq!(data.len(), 2);
+        assert_eq!(data[1].timestamp, at(1));
+        assert_eq!(data[1].value, 2.5);
+
+        let bad = dir.join("bad.csv");
+        std::fs::write(&bad, "timestamp,value\n2024-03-01T00:00:00Z,1.0\nyesterday,2.0\n").unwrap();
+        let error = load_csv(&bad).unwrap_err().to_string();
+        assert!(error.contains("line 3"), "{}", error);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
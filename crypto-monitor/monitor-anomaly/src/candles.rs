@@ -0,0 +1,569 @@
+use crate::{
+    reason::{AnomalyReason, ReasonKind},
+    confidence_from_exceedance, interval_label, severity_from_confidence, AnomalyDetection,
+    AnomalyMetrics, AnomalySeverity, CandleAnomalyDetector, CandleConfig, CandleRangeConfig,
+    CandleVolumeConfig, TradeSample,
+};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use monitor_core::{model::Candle, AnomalyType};
+use std::collections::{BTreeMap, VecDeque};
+use tracing::{debug, info};
+
+struct OpenCandle {
+    candle: Candle,
+    first_at: DateTime<Utc>,
+    last_at: DateTime<Utc>,
+}
+
+impl OpenCandle {
+    fn merge(&mut self, trade: &TradeSample) {
+        let candle = &mut self.candle;
+        candle.high = candle.high.max(trade.price);
+        candle.low = candle.low.min(trade.price);
+        candle.volume += trade.volume;
+        candle.trades += 1;
+        // Late trades only move the open or close if they really came first or last
+        if trade.timestamp < self.first_at {
+            candle.open = trade.price;
+            self.first_at = trade.timestamp;
+        }
+        if trade.timestamp >= self.last_at {
+            candle.close = trade.price;
+            self.last_at = trade.timestamp;
+        }
+    }
+}
+
+struct CandleSeries {
+    interval: Duration,
+    label: String,
+    // Keyed by open time, usually just the current candle and the one still taking late trades
+    open: BTreeMap<DateTime<Utc>, OpenCandle>,
+    // Trades before this belong to candles already emitted
+    closed_until: Option<DateTime<Utc>>,
+    detectors: Vec<Box<dyn CandleAnomalyDetector>>,
+}
+
+impl CandleSeries {
+    fn bucket(&self, at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let length = self.interval.num_milliseconds().max(1);
+        let millis = at.timestamp_millis();
+        Utc.timestamp_millis_opt(millis - millis.rem_euclid(length)).single()
+    }
+}
+
+// Builds candles per interval for one exchange and symbol from its trades and passes each
+// completed one to the interval's detectors. A candle completes once a trade arrives
+// `late_tolerance_secs` past its end, so slightly out of order trades are still merged in;
+// later ones are dropped. The candle the first trade falls in is only emitted if that trade
+// opened it exactly, otherwise it missed whatever traded before the aggregator started.
+pub struct CandleAggregator {
+    exchange: String,
+    symbol: String,
+    tolerance: Duration,
+    started_at: Option<DateTime<Utc>>,
+    latest: Option<DateTime<Utc>>,
+    series: Vec<CandleSeries>,
+    late_dropped: u64,
+}
+
+impl CandleAggregator {
+    pub fn new(config: &CandleConfig, symbol: String, exchange: String) -> Self {
+        Self {
+            exchange,
+            symbol,
+            tolerance: Duration::seconds(config.late_tolerance_secs as i64),
+            started_at: None,
+            latest: None,
+            series: config
+                .intervals_secs
+                .iter()
+                .filter(|secs| **secs > 0)
+                .map(|&secs| CandleSeries {
+                    interval: Duration::seconds(secs as i64),
+                    label: interval_label(secs),
+                    open: BTreeMap::new(),
+                    closed_until: None,
+                    detectors: Vec::new(),
+                })
+                .collect(),
+            late_dropped: 0,
+        }
+    }
+
+    // The volume and range detectors on every configured interval
+    pub fn with_default_detectors(
+        config: &CandleConfig,
+        symbol: String,
+        exchange: String,
+    ) -> Self {
+        let mut aggregator = Self::new(config, symbol.clone(), exchange.clone());
+        for &secs in &config.intervals_secs {
+            aggregator.add_detector(
+                secs,
+                Box::new(CandleVolumeDetector::new(
+                    config.volume.clone(),
+                    symbol.clone(),
+                    exchange.clone(),
+                )),
+            );
+            aggregator.add_detector(
+                secs,
+                Box::new(CandleRangeDetector::new(
+                    config.range.clone(),
+                    symbol.clone(),
+                    exchange.clone(),
+                )),
+            );
+        }
+        aggregator
+    }
+
+    // Registers a detector for the candles of one configured interval
+    pub fn add_detector(&mut self, interval_secs: u64, detector: Box<dyn CandleAnomalyDetector>) {
+        let interval = Duration::seconds(interval_secs as i64);
+        match self.series.iter_mut().find(|s| s.interval == interval) {
+            Some(series) => series.detectors.push(detector),
+            None => debug!("No {}s candles for {}/{}", interval_secs, self.exchange, self.symbol),
+        }
+    }
+
+    pub fn push_trade(&mut self, trade: &TradeSample) -> Vec<AnomalyDetection> {
+        if !trade.price.is_finite() || trade.price <= 0.0 {
+            return Vec::new();
+        }
+        self.started_at.get_or_insert(trade.timestamp);
+        let latest = self.latest.map_or(trade.timestamp, |l| l.max(trade.timestamp));
+        self.latest = Some(latest);
+
+        let mut dropped = false;
+        for series in &mut self.series {
+            let Some(start) = series.bucket(trade.timestamp) else {
+                continue;
+            };
+            if series.closed_until.is_some_and(|closed| start < closed) {
+                dropped = true;
+                continue;
+            }
+            match series.open.get_mut(&start) {
+                Some(open) => open.merge(trade),
+                None => {
+                    series.open.insert(
+                        start,
+                        OpenCandle {
+                            candle: Candle {
+                                exchange: self.exchange.clone(),
+                                symbol: self.symbol.clone(),
+                                timestamp: start,
+                                interval: series.label.clone(),
+                                open: trade.price,
+                                high: trade.price,
+                                low: trade.price,
+                                close: trade.price,
+                                volume: trade.volume,
+                                trades: 1,
+                            },
+                            first_at: trade.timestamp,
+                            last_at: trade.timestamp,
+                        },
+                    );
+                }
+            }
+        }
+        if dropped {
+            self.late_dropped += 1;
+            debug!(
+                "Dropped trade at {} for {}/{}, its candle already closed",
+                trade.timestamp, self.exchange, self.symbol
+            );
+        }
+
+        self.close_until(latest)
+    }
+
+    // Completes candles by the clock, for quiet markets where no later trade arrives
+    pub fn flush(&mut self, now: DateTime<Utc>) -> Vec<AnomalyDetection> {
+        self.close_until(now)
+    }
+
+    // Trades left out of at least one interval because its candle had already completed
+    pub fn late_dropped(&self) -> u64 {
+        self.late_dropped
+    }
+
+    pub fn reset(&mut self) {
+        self.started_at = None;
+        self.latest = None;
+        self.late_dropped = 0;
+        for series in &mut self.series {
+            series.open.clear();
+            series.closed_until = None;
+            for detector in &mut series.detectors {
+                detector.reset();
+            }
+        }
+    }
+
+    fn close_until(&mut self, now: DateTime<Utc>) -> Vec<AnomalyDetection> {
+        let Some(started_at) = self.started_at else {
+            return Vec::new();
+        };
+        let mut detections = Vec::new();
+        for series in &mut self.series {
+            while let Some(start) = series.open.keys().next().copied() {
+                let end = start + series.interval;
+                if end + self.tolerance > now {
+                    break;
+                }
+                let Some(open) = series.open.remove(&start) else {
+                    break;
+                };
+                series.closed_until = Some(end);
+                if start < started_at {
+                    continue;
+                }
+                for detector in &mut series.detectors {
+                    detections.extend(detector.detect_candle(&open.candle));
+                }
+            }
+        }
+        detections
+    }
+}
+
+fn average(values: &VecDeque<f64>) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+// Candle volume against the average of the previous candles
+pub struct CandleVolumeDetector {
+    config: CandleVolumeConfig,
+    symbol: String,
+    exchange: String,
+    volumes: VecDeque<f64>,
+}
+
+impl CandleVolumeDetector {
+    pub fn new(config: CandleVolumeConfig, symbol: String, exchange: String) -> Self {
+        Self {
+            volumes: VecDeque::with_capacity(config.lookback),
+            config,
+            symbol,
+            exchange,
+        }
+    }
+}
+
+impl CandleAnomalyDetector for CandleVolumeDetector {
+    fn detect_candle(&mut self, candle: &Candle) -> Option<AnomalyDetection> {
+        let ready = self.volumes.len() >= self.config.min_candles.max(1);
+        let mean = if ready { average(&self.volumes) } else { 0.0 };
+        if self.volumes.len() >= self.config.lookback.max(1) {
+            self.volumes.pop_front();
+        }
+        self.volumes.push_back(candle.volume);
+
+        if mean <= 0.0 || candle.volume < mean * self.config.volume_multiple {
+            return None;
+        }
+
+        let multiple = candle.volume / mean;
+        let confidence = confidence_from_exceedance(multiple, self.config.volume_multiple);
+        let severity = severity_from_confidence(confidence);
+        let reason = AnomalyReason::new(
+            &self.exchange,
+            &self.symbol,
+            candle.volume,
+            ReasonKind::CandleVolume { interval: candle.interval.clone(), multiple },
+        )
+        .with_baseline(mean)
+        .with_threshold(self.config.volume_multiple);
+
+        info!("{}", reason);
+
+        Some(candle_detection(
+            candle,
+            AnomalyType::VolumeSpike,
+            severity,
+            confidence,
+            AnomalyMetrics {
+                current_value: candle.volume,
+                expected_value: mean,
+                deviation: candle.volume - mean,
+                z_score: None,
+                percentage_change: Some((multiple - 1.0) * 100.0),
+                historical_avg: Some(mean),
+                historical_std: None,
+                ..Default::default()
+            },
+            reason,
+        ))
+    }
+
+    fn reset(&mut self) {
+        self.volumes.clear();
+    }
+}
+
+// High to low range, as a percentage of the open, against the average of the previous candles
+pub struct CandleRangeDetector {
+    config: CandleRangeConfig,
+    symbol: String,
+    exchange: String,
+    ranges: VecDeque<f64>,
+}
+
+impl CandleRangeDetector {
+    pub fn new(config: CandleRangeConfig, symbol: String, exchange: String) -> Self {
+        Self {
+            ranges: VecDeque::with_capacity(config.lookback),
+            config,
+            symbol,
+            exchange,
+        }
+    }
+}
+
+impl CandleAnomalyDetector for CandleRangeDetector {
+    fn detect_candle(&mut self, candle: &Candle) -> Option<AnomalyDetection> {
+        if candle.open <= 0.0 {
+            return None;
+        }
+        let range_pct = (candle.high - candle.low) / candle.open * 100.0;
+        let ready = self.ranges.len() >= self.config.min_candles.max(1);
+        let mean = if ready { average(&self.ranges) } else { 0.0 };
+        if self.ranges.len() >= self.config.lookback.max(1) {
+            self.ranges.pop_front();
+        }
+        self.ranges.push_back(range_pct);
+
+        if mean <= 0.0 || range_pct < mean * self.config.range_multiple {
+            return None;
+        }
+
+        let multiple = range_pct / mean;
+        let confidence = confidence_from_exceedance(multiple, self.config.range_multiple);
+        let severity = severity_from_confidence(confidence);
+        let reason = AnomalyReason::new(
+            &self.exchange,
+            &self.symbol,
+            range_pct,
+            ReasonKind::CandleRange {
+                interval: candle.interval.clone(),
+                multiple,
+                open: candle.open,
+                close: candle.close,
+            },
+        )
+        .with_baseline(mean)
+        .with_threshold(self.config.range_multiple);
+
+        info!("{}", reason);
+
+        Some(candle_detection(
+            candle,
+            AnomalyType::PriceSpike,
+            severity,
+            confidence,
+            AnomalyMetrics {
+                current_value: candle.close,
+                expected_value: candle.open,
+                deviation: candle.close - candle.open,
+                z_score: None,
+                percentage_change: Some((candle.close - candle.open) / candle.open * 100.0),
+                historical_avg: Some(mean),
+                historical_std: None,
+                ..Default::default()
+            },
+            reason,
+        ))
+    }
+
+    fn reset(&mut self) {
+        self.ranges.clear();
+    }
+}
+
+fn candle_detection(
+    candle: &Candle,
+    anomaly_type: AnomalyType,
+    severity: AnomalySeverity,
+    confidence: f64,
+    metrics: AnomalyMetrics,
+    reason: AnomalyReason,
+) -> AnomalyDetection {
+    AnomalyDetection {
+        id: uuid::Uuid::new_v4(),
+        timestamp: candle.timestamp,
+        symbol: candle.symbol.clone(),
+        exchange: candle.exchange.clone(),
+        anomaly_type,
+        severity,
+        confidence,
+        metrics,
+        description: reason.to_string(),
+        reason: Some(reason),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+    use std::sync::Arc;
+
+    fn at(h: u32, m: u32, s: u32, ms: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 1, h, m, s).unwrap() + Duration::milliseconds(ms)
+    }
+
+    fn trade(timestamp: DateTime<Utc>, price: f64, volume: f64) -> TradeSample {
+        TradeSample {
+            timestamp,
+            price,
+            volume,
+        }
+    }
+
+    // Keeps every candle it is handed
+    struct Recorder(Arc<Mutex<Vec<Candle>>>);
+
+    impl CandleAnomalyDetector for Recorder {
+        fn detect_candle(&mut self, candle: &Candle) -> Option<AnomalyDetection> {
+            self.0.lock().push(candle.clone());
+            None
+        }
+
+        fn reset(&mut self) {}
+    }
+
+    fn candle(open: f64, high: f64, low: f64, close: f64, volume: f64) -> Candle {
+        Candle {
+            exchange: "binance".to_string(),
+            symbol: "BTC/USDT".to_string(),
+            timestamp: at(12, 0, 0, 0),
+            interval: "1m".to_string(),
+            open,
+            high,
+            low,
+            close,
+            volume,
+            trades: 10,
+        }
+    }
+
+    #[test]
+    fn test_scripted_trades_build_known_candles() {
+        let mut aggregator = CandleAggregator::new(
+            &CandleConfig::default(),
+            "BTC/USDT".to_string(),
+            "binance".to_string(),
+        );
+        let recorded = Arc::new(Mutex::new(Vec::new()));
+        aggregator.add_detector(60, Box::new(Recorder(recorded.clone())));
+
+        let script = [
+            // Started half way through 12:00, which is never emitted
+            trade(at(12, 0, 30, 0), 100.0, 1.0),
+            trade(at(12, 0, 45, 0), 101.0, 1.0),
+            trade(at(12, 1, 0, 0), 102.0, 1.0),
+            trade(at(12, 1, 10, 0), 105.0, 2.0),
+            trade(at(12, 1, 20, 0), 99.0, 1.0),
+            trade(at(12, 1, 59, 0), 103.0, 1.0),
+            trade(at(12, 2, 1, 0), 104.0, 1.0),
+            // Out of order but within the 2s tolerance, it sets the low without moving the close
+            trade(at(12, 1, 30, 0), 98.0, 0.5),
+            // Completes 12:01
+            trade(at(12, 2, 3, 0), 106.0, 1.0),
+            // Too late for 12:01
+            trade(at(12, 1, 40, 0), 90.0, 1.0),
+        ];
+        for trade in &script {
+            assert!(aggregator.push_trade(trade).is_empty());
+        }
+        assert_eq!(aggregator.late_dropped(), 1);
+        assert_eq!(recorded.lock().len(), 1);
+
+        // Nothing trades after 12:02, the clock completes it
+        aggregator.flush(at(12, 3, 1, 999));
+        assert_eq!(recorded.lock().len(), 1);
+        aggregator.flush(at(12, 3, 2, 0));
+
+        let candles = recorded.lock().clone();
+        let summary: Vec<_> = candles
+            .iter()
+            .map(|c| (c.timestamp, c.open, c.high, c.low, c.close, c.volume, c.trades))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (at(12, 1, 0, 0), 102.0, 105.0, 98.0, 103.0, 5.5, 5),
+                (at(12, 2, 0, 0), 104.0, 106.0, 104.0, 106.0, 2.0, 2),
+            ]
+        );
+        assert!(candles.iter().all(|c| c.interval == "1m" && c.exchange == "binance"));
+    }
+
+    #[test]
+    fn test_trade_on_the_boundary_starts_a_full_candle() {
+        let config = CandleConfig {
+            intervals_secs: vec![60, 300],
+            ..Default::default()
+        };
+        let mut aggregator =
+            CandleAggregator::new(&config, "BTC/USDT".to_string(), "binance".to_string());
+        let minutes = Arc::new(Mutex::new(Vec::new()));
+        let fives = Arc::new(Mutex::new(Vec::new()));
+        aggregator.add_detector(60, Box::new(Recorder(minutes.clone())));
+        aggregator.add_detector(300, Box::new(Recorder(fives.clone())));
+
+        for i in 0..6 * 60 {
+            aggregator.push_trade(&trade(at(12, 0, 0, 0) + Duration::seconds(i), 100.0, 1.0));
+        }
+        aggregator.flush(at(12, 11, 0, 0));
+
+        // The first trade lands exactly on 12:00, so that candle counts as complete. The 12:05
+        // five minute candle only saw its first minute of trades before going quiet
+        assert_eq!(minutes.lock().len(), 6);
+        assert!(minutes.lock().iter().all(|c| c.volume == 60.0 && c.trades == 60));
+        let fives = fives.lock();
+        assert_eq!(fives.len(), 2);
+        assert_eq!((fives[0].interval.as_str(), fives[0].volume), ("5m", 300.0));
+        assert_eq!(fives[1].volume, 60.0);
+    }
+
+    #[test]
+    fn test_volume_and_range_detectors() {
+        let mut volume = CandleVolumeDetector::new(
+            CandleVolumeConfig::default(),
+            "BTC/USDT".to_string(),
+            "binance".to_string(),
+        );
+        let mut range = CandleRangeDetector::new(
+            CandleRangeConfig::default(),
+            "BTC/USDT".to_string(),
+            "binance".to_string(),
+        );
+        for _ in 0..10 {
+            let quiet = candle(100.0, 101.0, 100.0, 100.5, 10.0);
+            assert!(volume.detect_candle(&quiet).is_none());
+            assert!(range.detect_candle(&quiet).is_none());
+        }
+
+        // 6x the volume inside the usual 1% range
+        let heavy = candle(100.0, 101.0, 100.0, 100.8, 60.0);
+        let anomaly = volume.detect_candle(&heavy).unwrap();
+        assert_eq!(anomaly.anomaly_type, AnomalyType::VolumeSpike);
+        assert!(anomaly.description.starts_with("Candle volume anomaly [1m] for binance/BTC/USDT"));
+        assert_eq!(anomaly.metrics.expected_value, 10.0);
+        assert!(range.detect_candle(&heavy).is_none());
+
+        // A 4% range on ordinary volume
+        let wide = candle(100.0, 100.0, 96.0, 96.5, 10.0);
+        assert!(volume.detect_candle(&wide).is_none());
+        let anomaly = range.detect_candle(&wide).unwrap();
+        assert_eq!(anomaly.anomaly_type, AnomalyType::PriceSpike);
+        assert!(anomaly.description.starts_with("Candle range anomaly [1m]"));
+        assert!((anomaly.metrics.percentage_change.unwrap() + 3.5).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,356 @@
+use crate::{
+    metrics::{align_series, pearson},
+    reason::{AnomalyReason, ReasonKind},
+    warmup::WarmupTracker,
+    confidence_from_exceedance, severity_from_confidence, AnomalyDetection, AnomalyMetrics,
+    DetectionDetails, TimeSeriesData,
+};
+use chrono::{DateTime, Duration, Utc};
+use monitor_core::{AnomalyType, CorrelationBreakConfig};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+};
+use tracing::info;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CorrelationBreakDetails {
+    // "exchange:symbol" of the series the follower decoupled from
+    pub leader: String,
+    pub correlation: f64,
+    pub historical_correlation: f64,
+    pub returns: usize,
+    pub duration_secs: f64,
+}
+
+struct PairState {
+    leader: String,
+    follower: String,
+    // Readings taken outside a break, oldest first
+    readings: VecDeque<f64>,
+    last_reading: Option<DateTime<Utc>>,
+    // When readings first fell below `break_below`, cleared once one is back above it
+    breaking_since: Option<DateTime<Utc>>,
+    reported: bool,
+}
+
+impl PairState {
+    fn historical(&self, min_readings: usize) -> Option<f64> {
+        (self.readings.len() >= min_readings.max(1))
+            .then(|| self.readings.iter().sum::<f64>() / self.readings.len() as f64)
+    }
+}
+
+#[derive(Default)]
+struct State {
+    // Trade prices of every series in a configured pair, keyed by "exchange:symbol"
+    series: HashMap<String, VecDeque<TimeSeriesData>>,
+    pairs: Vec<PairState>,
+}
+
+// Watches configured pairs of series that normally move together, e.g. BTC and ETH, and flags
+// the follower when their correlation breaks down. Trade prices of the two are paired with
+// `align_series` and each reading is the Pearson correlation of the log returns between
+// consecutive pairs; raw prices would read as correlated whenever both trend. The historical
+// correlation is the mean of past readings, frozen during a break so it doesn't follow the
+// break down. A break alerts once it has lasted `min_duration_secs`, and again only after a
+// reading back above `break_below`.
+pub struct CorrelationBreakDetector {
+    config: CorrelationBreakConfig,
+    state: Mutex<State>,
+    warmup: Option<Arc<WarmupTracker>>,
+}
+
+impl CorrelationBreakDetector {
+    pub fn new(config: CorrelationBreakConfig) -> Self {
+        let mut state = State::default();
+        for pair in &config.pairs {
+            for key in [&pair.leader, &pair.follower] {
+                state.series.entry(key.clone()).or_default();
+            }
+            state.pairs.push(PairState {
+                leader: pair.leader.clone(),
+                follower: pair.follower.clone(),
+                readings: VecDeque::new(),
+                last_reading: None,
+                breaking_since: None,
+                reported: false,
+            });
+        }
+        Self {
+            config,
+            state: Mutex::new(state),
+            warmup: None,
+        }
+    }
+
+    pub fn with_warmup(mut self, warmup: Arc<WarmupTracker>) -> Self {
+        self.warmup = Some(warmup);
+        self
+    }
+
+    pub fn record_trade(
+        &self,
+        exchange: &str,
+        symbol: &str,
+        price: f64,
+        timestamp: DateTime<Utc>,
+    ) -> Vec<AnomalyDetection> {
+        if !self.config.enabled || !price.is_finite() || price <= 0.0 {
+            return Vec::new();
+        }
+        let key = format!("{}:{}", exchange, symbol);
+
+        let mut state = self.state.lock();
+        let State { series, pairs } = &mut *state;
+        let Some(samples) = series.get_mut(&key) else {
+            return Vec::new();
+        };
+        // Pairing needs each series in time order, late trades are dropped
+        if samples.back().is_some_and(|last| last.timestamp > timestamp) {
+            return Vec::new();
+        }
+        if samples.len() >= self.config.max_samples.max(1) {
+            samples.pop_front();
+        }
+        samples.push_back(TimeSeriesData {
+            timestamp,
+            value: price.ln(),
+        });
+
+        let interval = Duration::seconds(self.config.reading_interval_secs as i64);
+        let mut detections = Vec::new();
+        for pair in pairs.iter_mut() {
+            if pair.leader != key && pair.follower != key {
+                continue;
+            }
+            if pair.last_reading.is_some_and(|last| timestamp - last < interval) {
+                continue;
+            }
+            let Some((correlation, returns)) = self.correlation(series, pair) else {
+                continue;
+            };
+            pair.last_reading = Some(timestamp);
+            detections.extend(self.judge(pair, correlation, returns, timestamp));
+        }
+        detections
+    }
+
+    // Correlation of the latest `window` returns and how many there were
+    fn correlation(
+        &self,
+        series: &HashMap<String, VecDeque<TimeSeriesData>>,
+        pair: &PairState,
+    ) -> Option<(f64, usize)> {
+        let tolerance = Duration::milliseconds(self.config.alignment_tolerance_ms as i64);
+        let (leader, follower) = (series.get(&pair.leader)?, series.get(&pair.follower)?);
+        let aligned = align_series(leader, follower, tolerance);
+        let window = self.config.window.max(2);
+        if aligned.len() <= window {
+            return None;
+        }
+        let returns: Vec<(f64, f64)> = aligned[aligned.len() - window - 1..]
+            .windows(2)
+            .map(|w| (w[1].0 - w[0].0, w[1].1 - w[0].1))
+            .collect();
+        Some((pearson(&returns)?, returns.len()))
+    }
+
+    fn judge(
+        &self,
+        pair: &mut PairState,
+        correlation: f64,
+        returns: usize,
+        timestamp: DateTime<Utc>,
+    ) -> Option<AnomalyDetection> {
+        let historical = pair
+            .historical(self.config.min_baseline_readings)
+            .filter(|h| *h >= self.config.min_historical_correlation);
+        let Some(historical) = historical.filter(|_| correlation < self.config.break_below) else {
+            pair.breaking_since = None;
+            pair.reported = false;
+            if pair.readings.len() >= self.config.baseline_readings.max(1) {
+                pair.readings.pop_front();
+            }
+            pair.readings.push_back(correlation);
+            return None;
+        };
+
+        let since = *pair.breaking_since.get_or_insert(timestamp);
+        let duration = timestamp - since;
+        if pair.reported || duration < Duration::seconds(self.config.min_duration_secs as i64) {
+            return None;
+        }
+        pair.reported = true;
+
+        let details = CorrelationBreakDetails {
+            leader: pair.leader.clone(),
+            correlation,
+            historical_correlation: historical,
+            returns,
+            duration_secs: duration.num_milliseconds() as f64 / 1000.0,
+        };
+        Some(self.detection(&pair.follower, details, timestamp))
+    }
+
+    fn detection(
+        &self,
+        follower: &str,
+        details: CorrelationBreakDetails,
+        timestamp: DateTime<Utc>,
+    ) -> AnomalyDetection {
+        let (exchange, symbol) = follower.split_once(':').unwrap_or((follower, ""));
+        let (leader_exchange, leader_symbol) =
+            details.leader.split_once(':').unwrap_or((&details.leader, ""));
+
+        // Half confidence for a drop right to `break_below`, more the further it fell
+        let confidence = confidence_from_exceedance(
+            details.historical_correlation - details.correlation,
+            details.historical_correlation - self.config.break_below,
+        );
+        let severity = severity_from_confidence(confidence);
+        let reason = AnomalyReason::new(
+            exchange,
+            symbol,
+            details.correlation,
+            ReasonKind::CorrelationBreak {
+                leader_exchange: leader_exchange.to_string(),
+                leader_symbol: leader_symbol.to_string(),
+                returns: details.returns,
+            },
+        )
+        .with_baseline(details.historical_correlation)
+        .with_threshold(self.config.break_below)
+        .with_window_secs(details.duration_secs);
+        let description = reason.to_string();
+        info!("{}", description);
+
+        AnomalyDetection {
+            id: uuid::Uuid::new_v4(),
+            timestamp,
+            symbol: symbol.to_string(),
+            exchange: exchange.to_string(),
+            anomaly_type: AnomalyType::CorrelationBreak,
+            severity,
+            confidence,
+            metrics: AnomalyMetrics {
+                current_value: details.correlation,
+                expected_value: details.historical_correlation,
+                deviation: details.correlation - details.historical_correlation,
+                z_score: None,
+                percentage_change: None,
+                historical_avg: Some(details.historical_correlation),
+                historical_std: None,
+                ..Default::default()
+            },
+            description,
+            reason: Some(reason),
+            warmup: self
+                .warmup
+                .as_ref()
+                .is_some_and(|w| w.is_warming_up(follower, timestamp)),
+            details: Some(DetectionDetails::CorrelationBreak(details)),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use monitor_core::CorrelationPairConfig;
+
+    fn config() -> CorrelationBreakConfig {
+        CorrelationBreakConfig {
+            enabled: true,
+            pairs: vec![CorrelationPairConfig {
+                leader: "binance:BTC/USDT".to_string(),
+                follower: "binance:ETH/USDT".to_string(),
+            }],
+            alignment_tolerance_ms: 500,
+            max_samples: 1000,
+            window: 20,
+            reading_interval_secs: 5,
+            baseline_readings: 60,
+            min_baseline_readings: 5,
+            min_historical_correlation: 0.6,
+            break_below: 0.2,
+            min_duration_secs: 30,
+        }
+    }
+
+    // BTC trades every second on a fixed pseudo-random walk, ETH every two seconds 300ms later.
+    // ETH follows BTC's moves until `decouple_at` seconds, then mirrors them
+    fn run(
+        detector: &CorrelationBreakDetector,
+        seconds: i64,
+        decouple_at: i64,
+    ) -> Vec<(i64, AnomalyDetection)> {
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let (mut btc, mut eth) = (0.0_f64, 0.0_f64);
+        let mut detections = Vec::new();
+        for second in 0..seconds {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            let step = ((state >> 11) as f64 / (1u64 << 53) as f64 - 0.5) * 0.002;
+            btc += step;
+            eth += if second < decouple_at { step } else { -step };
+
+            let at = start + Duration::seconds(second);
+            let mut found = detector.record_trade("binance", "BTC/USDT", 50_000.0 * btc.exp(), at);
+            if second % 2 == 0 {
+                let at = at + Duration::milliseconds(300);
+                found.extend(detector.record_trade("binance", "ETH/USDT", 3_000.0 * eth.exp(), at));
+            }
+            detections.extend(found.into_iter().map(|d| (second, d)));
+        }
+        detections
+    }
+
+    #[test]
+    fn test_sustained_decoupling_alerts_once() {
+        let detector = CorrelationBreakDetector::new(config());
+        let detections = run(&detector, 600, 300);
+
+        assert_eq!(detections.len(), 1);
+        let (second, detection) = &detections[0];
+        assert!(*second > 330 && *second < 400, "alerted at {}s", second);
+        assert_eq!(detection.anomaly_type, AnomalyType::CorrelationBreak);
+        assert_eq!(detection.exchange, "binance");
+        assert_eq!(detection.symbol, "ETH/USDT");
+        let Some(DetectionDetails::CorrelationBreak(details)) = &detection.details else {
+            panic!("missing correlation break details");
+        };
+        assert_eq!(details.leader, "binance:BTC/USDT");
+        assert!(details.historical_correlation > 0.9);
+        assert!(details.correlation < 0.2);
+        assert!(details.duration_secs >= 30.0);
+        assert!(detection
+            .description
+            .starts_with("Correlation break for binance/ETH/USDT against binance/BTC/USDT"));
+    }
+
+    #[test]
+    fn test_pairs_never_correlated_do_not_alert() {
+        let detector = CorrelationBreakDetector::new(config());
+        assert!(run(&detector, 600, 0).is_empty());
+    }
+
+    #[test]
+    fn test_unconfigured_series_and_disabled_detector_are_ignored() {
+        let detector = CorrelationBreakDetector::new(config());
+        assert!(detector.record_trade("okx", "BTC/USDT", 50_000.0, Utc::now()).is_empty());
+        assert!(detector.state.lock().series.get("okx:BTC/USDT").is_none());
+
+        let detector = CorrelationBreakDetector::new(CorrelationBreakConfig {
+            enabled: false,
+            ..config()
+        });
+        assert!(run(&detector, 600, 300).is_empty());
+    }
+}
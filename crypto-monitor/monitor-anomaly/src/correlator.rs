@@ -0,0 +1,327 @@
+use crate::{AnomalyDetection, AnomalySeverity};
+use chrono::{DateTime, Duration, Utc};
+use monitor_core::CorrelationConfig;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+// Detections on one exchange and symbol that arrived within the correlation window, the first
+// of them already released on its own when it arrived
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnomalyGroup {
+    pub id: Uuid,
+    pub exchange: String,
+    pub symbol: String,
+    pub first_at: DateTime<Utc>,
+    pub last_at: DateTime<Utc>,
+    // Highest among the members
+    pub severity: AnomalySeverity,
+    pub description: String,
+    pub member_ids: Vec<Uuid>,
+    // In arrival order
+    pub members: Vec<AnomalyDetection>,
+}
+
+impl AnomalyGroup {
+    fn new(members: Vec<AnomalyDetection>, window_secs: u64) -> Self {
+        let first = &members[0];
+        let severity = members
+            .iter()
+            .map(|a| a.severity.clone())
+            .fold(first.severity.clone(), |max, s| max.max(s));
+        let description = format!(
+            "{} correlated anomalies on {}/{} within {}s: {}",
+            members.len(),
+            first.exchange,
+            first.symbol,
+            window_secs,
+            members
+                .iter()
+                .map(|a| format!("[{:?} {:?}] {}", a.anomaly_type, a.severity, a.description))
+                .collect::<Vec<_>>()
+                .join(" | ")
+        );
+
+        Self {
+            id: Uuid::new_v4(),
+            exchange: first.exchange.clone(),
+            symbol: first.symbol.clone(),
+            first_at: members.iter().map(|a| a.timestamp).min().unwrap_or(first.timestamp),
+            last_at: members.iter().map(|a| a.timestamp).max().unwrap_or(first.timestamp),
+            severity,
+            description,
+            member_ids: members.iter().map(|a| a.id).collect(),
+            members,
+        }
+    }
+
+    // The most severe member, the earliest on ties, carrying the group's description. Keeps the
+    // member's id so signals traded on it still point at a stored detection.
+    pub fn primary(&self) -> AnomalyDetection {
+        let mut primary = self
+            .members
+            .iter()
+            .fold(&self.members[0], |best, a| if a.severity > best.severity { a } else { best })
+            .clone();
+        primary.description = self.description.clone();
+        primary.confidence = self.members.iter().map(|a| a.confidence).fold(0.0, f64::max);
+        primary.warmup = self.members.iter().all(|a| a.warmup);
+        primary
+    }
+
+    // The detection that opened the window, already notified and traded on by itself
+    pub fn leader(&self) -> &AnomalyDetection {
+        &self.members[0]
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Correlated {
+    Single(AnomalyDetection),
+    Group(AnomalyGroup),
+}
+
+struct Pending {
+    // When the leader arrived, not its event time, so windows close on the same clock `flush`
+    // is called with however far behind the feed runs
+    opened_at: DateTime<Utc>,
+    members: Vec<AnomalyDetection>,
+}
+
+// Releases the first detection on an exchange and symbol at once and holds those following it
+// within the correlation window, releasing them with it as one group when the window closes. A
+// flash crash's price, volume and activity alerts then make one immediate and one follow-up
+// notification rather than three, and nothing waits on the window by itself.
+pub struct AnomalyCorrelator {
+    config: CorrelationConfig,
+    pending: Mutex<HashMap<String, Pending>>,
+}
+
+impl AnomalyCorrelator {
+    pub fn new(config: CorrelationConfig) -> Self {
+        Self {
+            config,
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled && self.config.window_secs > 0
+    }
+
+    // Takes the detection as it arrives at `now` and returns what is released: the pair's
+    // previous window when it has passed, and the detection itself when it opens a new one.
+    // Disabled, the detection comes straight back.
+    pub fn push(&self, anomaly: AnomalyDetection, now: DateTime<Utc>) -> Vec<Correlated> {
+        if !self.is_enabled() {
+            return vec![Correlated::Single(anomaly)];
+        }
+
+        let key = format!("{}:{}", anomaly.exchange, anomaly.symbol);
+        let mut pending = self.pending.lock();
+        let mut released = Vec::new();
+        if pending.get(&key).is_some_and(|p| now - p.opened_at >= self.window()) {
+            released.extend(pending.remove(&key).and_then(|p| self.close(p)));
+        }
+        match pending.get_mut(&key) {
+            Some(open) => open.members.push(anomaly),
+            None => {
+                released.push(Correlated::Single(anomaly.clone()));
+                pending.insert(
+                    key,
+                    Pending {
+                        opened_at: now,
+                        members: vec![anomaly],
+                    },
+                );
+            }
+        }
+        released
+    }
+
+    // Releases the group of every pair whose window has passed by `now`
+    pub fn flush(&self, now: DateTime<Utc>) -> Vec<Correlated> {
+        let window = self.window();
+        let mut pending = self.pending.lock();
+        let due: Vec<String> = pending
+            .iter()
+            .filter(|(_, p)| now - p.opened_at >= window)
+            .map(|(key, _)| key.clone())
+            .collect();
+        due.iter()
+            .filter_map(|key| pending.remove(key))
+            .filter_map(|p| self.close(p))
+            .collect()
+    }
+
+    // Releases every group still open, on shutdown
+    pub fn drain(&self) -> Vec<Correlated> {
+        self.pending.lock().drain().filter_map(|(_, p)| self.close(p)).collect()
+    }
+
+    // Detections held behind their window's leader
+    pub fn pending(&self) -> usize {
+        self.pending.lock().values().map(|p| p.members.len() - 1).sum()
+    }
+
+    // A window whose leader came alone has nothing left to release
+    fn close(&self, pending: Pending) -> Option<Correlated> {
+        (pending.members.len() > 1).then(|| {
+            Correlated::Group(AnomalyGroup::new(pending.members, self.config.window_secs))
+        })
+    }
+
+    fn window(&self) -> Duration {
+        Duration::seconds(self.config.window_secs as i64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AnomalyMetrics;
+    use chrono::TimeZone;
+    use monitor_core::AnomalyType;
+
+    fn start() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap()
+    }
+
+    fn detection(
+        symbol: &str,
+        anomaly_type: AnomalyType,
+        severity: AnomalySeverity,
+        at: DateTime<Utc>,
+    ) -> AnomalyDetection {
+        let description = format!("{:?}", anomaly_type);
+        AnomalyDetection {
+            id: Uuid::new_v4(),
+            timestamp: at,
+            symbol: symbol.to_string(),
+            exchange: "binance".to_string(),
+            anomaly_type,
+            confidence: severity.confidence_floor(),
+            severity,
+            metrics: AnomalyMetrics {
+                current_value: 1.0,
+                expected_value: 1.0,
+                deviation: 0.0,
+                z_score: None,
+                percentage_change: None,
+                historical_avg: None,
+                historical_std: None,
+                ..Default::default()
+            },
+            description,
+            ..Default::default()
+        }
+    }
+
+    fn correlator() -> AnomalyCorrelator {
+        AnomalyCorrelator::new(CorrelationConfig::default())
+    }
+
+    #[test]
+    fn test_co_occurring_detections_are_grouped() {
+        let correlator = correlator();
+        let crash = [
+            (AnomalyType::PriceSpike, AnomalySeverity::High, 0),
+            (AnomalyType::VolumeSpike, AnomalySeverity::Critical, 300),
+            (AnomalyType::UnusualActivity, AnomalySeverity::Medium, 1_500),
+        ];
+        let mut ids = Vec::new();
+        for (anomaly_type, severity, offset_ms) in crash {
+            let at = start() + Duration::milliseconds(offset_ms);
+            let anomaly = detection("BTC/USDT", anomaly_type, severity, at);
+            ids.push(anomaly.id);
+            let released = correlator.push(anomaly, at);
+            // The leader goes out as it arrives, the rest wait for the window
+            if offset_ms == 0 {
+                assert!(matches!(&released[..], [Correlated::Single(a)] if a.id == ids[0]));
+            } else {
+                assert!(released.is_empty());
+            }
+        }
+        assert_eq!(correlator.pending(), 2);
+
+        // Still inside the window
+        assert!(correlator.flush(start() + Duration::milliseconds(1_999)).is_empty());
+        let released = correlator.flush(start() + Duration::seconds(2));
+        assert_eq!(released.len(), 1);
+        let Correlated::Group(group) = &released[0] else {
+            panic!("expected a group");
+        };
+        assert_eq!(group.member_ids, ids);
+        assert_eq!(group.leader().id, ids[0]);
+        assert_eq!(group.severity, AnomalySeverity::Critical);
+        assert_eq!(group.last_at - group.first_at, Duration::milliseconds(1_500));
+        assert!(group.description.starts_with("3 correlated anomalies on binance/BTC/USDT"));
+        assert!(group.description.contains("[UnusualActivity Medium] UnusualActivity"));
+
+        let primary = group.primary();
+        assert_eq!(primary.id, ids[1]);
+        assert_eq!(primary.anomaly_type, AnomalyType::VolumeSpike);
+        assert_eq!(primary.description, group.description);
+        assert_eq!(correlator.pending(), 0);
+    }
+
+    #[test]
+    fn test_isolated_detections_pass_through() {
+        let correlator = correlator();
+        let first = detection("BTC/USDT", AnomalyType::PriceSpike, AnomalySeverity::High, start());
+        let other_symbol =
+            detection("ETH/USDT", AnomalyType::VolumeSpike, AnomalySeverity::Low, start());
+        let later = detection(
+            "BTC/USDT",
+            AnomalyType::VolumeSpike,
+            AnomalySeverity::Medium,
+            start() + Duration::seconds(5),
+        );
+        let (first_id, other_id, later_id) = (first.id, other_symbol.id, later.id);
+
+        let released = correlator.push(first, start());
+        assert!(matches!(&released[..], [Correlated::Single(a)] if a.id == first_id));
+        let released = correlator.push(other_symbol, start());
+        assert!(matches!(&released[..], [Correlated::Single(a)] if a.id == other_id));
+        // Past the BTC window, so it opens a new one
+        let released = correlator.push(later, start() + Duration::seconds(5));
+        assert!(matches!(&released[..], [Correlated::Single(a)] if a.id == later_id));
+
+        // Leaders alone in their windows have nothing left to release
+        assert!(correlator.flush(start() + Duration::seconds(3)).is_empty());
+        assert!(correlator.drain().is_empty());
+        assert_eq!(correlator.pending(), 0);
+    }
+
+    #[test]
+    fn test_window_runs_on_arrival_time() {
+        let correlator = correlator();
+        // Detections from a feed running an hour behind, their event times 10s apart
+        let arrived = start() + Duration::hours(1);
+        let lagging = |offset: i64| {
+            let at = start() + Duration::seconds(offset);
+            detection("BTC/USDT", AnomalyType::PriceSpike, AnomalySeverity::High, at)
+        };
+
+        assert_eq!(correlator.push(lagging(0), arrived).len(), 1);
+        assert!(correlator.push(lagging(10), arrived + Duration::seconds(1)).is_empty());
+        // Stale event times alone don't close the window
+        assert!(correlator.flush(arrived + Duration::seconds(1)).is_empty());
+        let released = correlator.flush(arrived + Duration::seconds(2));
+        assert!(matches!(&released[..], [Correlated::Group(g)] if g.members.len() == 2));
+    }
+
+    #[test]
+    fn test_disabled_correlator_releases_immediately() {
+        let correlator = AnomalyCorrelator::new(CorrelationConfig {
+            enabled: false,
+            ..Default::default()
+        });
+        let anomaly =
+            detection("BTC/USDT", AnomalyType::PriceSpike, AnomalySeverity::High, start());
+
+        assert!(matches!(&correlator.push(anomaly, start())[..], [Correlated::Single(_)]));
+        assert_eq!(correlator.pending(), 0);
+    }
+}
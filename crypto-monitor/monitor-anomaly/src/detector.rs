@@ -1,11 +1,35 @@
 use crate::{
-    AnomalyDetection, AnomalyDetector, AnomalyMetrics, AnomalySeverity,
-    PriceAnomalyConfig, TimeSeriesData, TimeSeriesWindow, VolumeAnomalyConfig,
+    candles::CandleAggregator,
+    iceberg::BookSide,
+    metrics::MetricsCalculator,
+    momentum::RateOfChangeDetector,
+    reason::{AnomalyReason, ReasonKind},
+    seasonal::SeasonalBaseline,
+    snapshot::{DetectorSnapshot, DetectorState, EwmaState, PairSnapshot, TimeframeState},
+    stats::{DetectorStats, DetectorStatsEntry, Tracked},
+    suppression::{AlertSuppressor, SuppressionStats},
+    update_rate::UpdateRateDetector,
+    volatility::VolatilityRegimeDetector,
+    warmup::WarmupTracker,
+    confidence_from_exceedance, severity_from_confidence, severity_from_thresholds,
+    AnomalyDetection, AnomalyDetector, AnomalyMetrics, AnomalySeverity, BollingerConfig,
+    BookAnomalyDetector, CandleConfig, CrossExchangeDivergenceConfig, DepthImbalanceConfig,
+    DetectionDetails, EwmaAnomalyConfig, LargeTradeConfig, PriceAnomalyConfig,
+    QuoteAnomalyDetector, QuoteSample, RateOfChangeConfig, RollingOrderStats, Series,
+    SpreadAnomalyConfig, TimeSeriesData, TimeSeriesWindow, TimeframeZScore, TradeAnomalyDetector,
+    TradeSample, UpdateRateConfig, VolatilityRegimeConfig, VolumeAnomalyConfig, VolumeTrigger,
+    WindowEstimator,
 };
-use chrono::Utc;
-use monitor_core::AnomalyType;
-use parking_lot::RwLock;
-use std::collections::HashMap;
+use chrono::{DateTime, Duration, Timelike, Utc};
+use monitor_core::{
+    instrument::InstrumentKey,
+    model::{OrderBook, OrderBookLevel},
+    AnomalyType, BaselineKind,
+};
+use dashmap::{mapref::entry::Entry, DashMap};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 use tracing::{debug, info, warn};
 
@@ -14,30 +38,53 @@ pub struct VolumeAnomalyDetector {
     symbol: String,
     exchange: String,
     window: TimeSeriesWindow,
+    seasonal: Option<SeasonalBaseline>,
 }
 
 impl VolumeAnomalyDetector {
     pub fn new(config: VolumeAnomalyConfig, symbol: String, exchange: String) -> Self {
         Self {
-            window: TimeSeriesWindow::new(config.window_size),
+            window: Self::new_window(&config),
+            seasonal: config
+                .seasonality
+                .enabled
+                .then(|| SeasonalBaseline::new(config.seasonality.clone())),
             config,
             symbol,
             exchange,
         }
     }
+    
+    // Quantiles are only tracked when a percentile trigger needs them
+    fn new_window(config: &VolumeAnomalyConfig) -> TimeSeriesWindow {
+        if config.trigger.uses_percentile() {
+            TimeSeriesWindow::with_quantiles(config.window_size)
+        } else {
+            TimeSeriesWindow::new(config.window_size)
+        }
+    }
 }
 
 impl AnomalyDetector for VolumeAnomalyDetector {
     fn detect(&mut self, data: &TimeSeriesData) -> Option<AnomalyDetection> {
+        // Taken before the volume joins the window, so a spike doesn't dampen its own z-score
+        // and a record volume can still pass p100
+        let ready = self.window.len() >= self.config.min_samples;
+        let window_baseline = ready.then(|| (self.window.mean(), self.window.std_dev()));
+        let percentile_volume = self
+            .window
+            .quantile(self.config.percentile / 100.0)
+            .filter(|_| ready);
         self.window.push(data.clone());
         
-        if self.window.len() < self.config.min_samples {
-            return None;
+        // The same hour on previous days once there are enough of them, else the window
+        let seasonal = self.seasonal.as_ref().and_then(|s| s.baseline(data.timestamp));
+        if let Some(history) = &mut self.seasonal {
+            history.push(data.timestamp, data.value);
         }
         
-        let mean = self.window.mean();
-        let std_dev = self.window.std_dev();
-        let z_score = self.window.z_score(data.value);
+        let (mean, std_dev) = seasonal.or(window_baseline)?;
+        let z_score = if std_dev > 0.0 { (data.value - mean) / std_dev } else { 0.0 };
         
         let percentage_change = if mean > 0.0 {
             ((data.value - mean) / mean) * 100.0
@@ -45,26 +92,49 @@ impl AnomalyDetector for VolumeAnomalyDetector {
             0.0
         };
         
-        if z_score.abs() >= self.config.z_score_threshold
-            && percentage_change.abs() >= self.config.min_percentage_change
-        {
-            let severity = match z_score.abs() {
-                z if z >= 5.0 => AnomalySeverity::Critical,
-                z if z >= 4.0 => AnomalySeverity::High,
-                z if z >= 3.0 => AnomalySeverity::Medium,
-                _ => AnomalySeverity::Low,
+        let z_triggered = z_score.abs() >= self.config.z_score_threshold;
+        let percentile_triggered = percentile_volume.is_some_and(|p| data.value > p);
+        let triggered = match self.config.trigger {
+            VolumeTrigger::ZScore => z_triggered,
+            VolumeTrigger::Percentile => percentile_triggered,
+            VolumeTrigger::Either => z_triggered || percentile_triggered,
+            VolumeTrigger::Both => z_triggered && percentile_triggered,
+        };
+        
+        if triggered && percentage_change.abs() >= self.config.min_percentage_change {
+            // Graded on the z-score whenever it passed, else on the distance past the percentile
+            let (confidence, severity, threshold) = match percentile_volume {
+                Some(p) if !z_triggered => {
+                    let confidence = confidence_from_exceedance(data.value, p);
+                    (confidence, severity_from_confidence(confidence), p)
+                }
+                _ => {
+                    let severity = severity_from_thresholds(z_score, &self.config.z_score_severity);
+                    let confidence = severity.floor_confidence(confidence_from_exceedance(
+                        z_score.abs(),
+                        self.config.z_score_threshold,
+                    ));
+                    (confidence, severity, self.config.z_score_threshold)
+                }
             };
+            let percentile_volume =
+                percentile_volume.filter(|_| self.config.trigger.uses_percentile());
             
-            let description = format!(
-                "Volume anomaly detected for {}/{}: current volume {:.2} is {:.1}% {} average ({:.2}), Z-score: {:.2}",
-                self.exchange,
-                self.symbol,
+            let reason = AnomalyReason::new(
+                &self.exchange,
+                &self.symbol,
                 data.value,
-                percentage_change.abs(),
-                if percentage_change > 0.0 { "above" } else { "below" },
-                mean,
-                z_score
-            );
+                ReasonKind::Volume {
+                    change_pct: percentage_change,
+                    z_score,
+                    seasonal_hour: seasonal.map(|_| data.timestamp.hour()),
+                    percentile: percentile_volume.map(|_| self.config.percentile),
+                    percentile_volume,
+                },
+            )
+            .with_baseline(mean)
+            .with_threshold(threshold);
+            let description = reason.to_string();
             
             info!("{}", description);
             
@@ -75,6 +145,7 @@ impl AnomalyDetector for VolumeAnomalyDetector {
                 exchange: self.exchange.clone(),
                 anomaly_type: AnomalyType::VolumeSpike,
                 severity,
+                confidence,
                 metrics: AnomalyMetrics {
                     current_value: data.value,
                     expected_value: mean,
@@ -83,8 +154,11 @@ impl AnomalyDetector for VolumeAnomalyDetector {
                     percentage_change: Some(percentage_change),
                     historical_avg: Some(mean),
                     historical_std: Some(std_dev),
+                    ..Default::default()
                 },
                 description,
+                reason: Some(reason),
+                ..Default::default()
             })
         } else {
             None
@@ -92,7 +166,41 @@ impl AnomalyDetector for VolumeAnomalyDetector {
     }
     
     fn reset(&mut self) {
-        self.window = TimeSeriesWindow::new(self.config.window_size);
+        self.window = Self::new_window(&self.config);
+        if let Some(history) = &mut self.seasonal {
+            history.clear();
+        }
+    }
+    
+    fn kind(&self) -> String {
+        "volume".to_string()
+    }
+    
+    fn window_fill(&self) -> Option<f64> {
+        Some(self.window.fill())
+    }
+    
+    fn series(&self) -> Series {
+        Series::Volume
+    }
+    
+    fn state_name(&self) -> Option<&'static str> {
+        Some("volume")
+    }
+    
+    fn snapshot(&self) -> DetectorState {
+        DetectorState {
+            window: self.window.values(),
+            seasonal: self.seasonal.as_ref().map(|s| s.moments()).unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+    
+    fn restore(&mut self, state: &DetectorState, saved_at: DateTime<Utc>) {
+        self.window.restore(&state.window, saved_at);
+        if let Some(history) = &mut self.seasonal {
+            history.restore(&state.seasonal);
+        }
     }
 }
 
@@ -119,49 +227,51 @@ impl PriceAnomalyDetector {
 impl AnomalyDetector for PriceAnomalyDetector {
     fn detect(&mut self, data: &TimeSeriesData) -> Option<AnomalyDetection> {
         let current_price = data.value;
+        let previous_price = self.last_price.replace(current_price);
         
+        // Against the window before the price joins it, so a jump doesn't dampen its own z-score
+        let ready = self.window.len() >= self.config.min_samples;
+        let mean = self.window.mean();
+        let std_dev = self.window.std_dev();
+        let z_score = self.window.z_score(current_price);
         self.window.push(data.clone());
         
-        if self.window.len() < self.config.min_samples {
-            self.last_price = Some(current_price);
+        if !ready {
             return None;
         }
         
-        let mean = self.window.mean();
-        let std_dev = self.window.std_dev();
-        let z_score = self.window.z_score(current_price);
-        
-        let percentage_change = if let Some(last) = self.last_price {
-            if last > 0.0 {
-                ((current_price - last) / last) * 100.0
-            } else {
-                0.0
-            }
-        } else {
-            0.0
+        let percentage_change = match previous_price {
+            Some(previous) if previous > 0.0 => ((current_price - previous) / previous) * 100.0,
+            _ => 0.0,
         };
-        
-        self.last_price = Some(current_price);
+        // The move is measured from the previous price, without one the window mean
+        let expected = previous_price.unwrap_or(mean);
         
         if percentage_change.abs() >= self.config.percentage_threshold
             || z_score.abs() >= self.config.z_score_threshold
         {
-            let severity = match (percentage_change.abs(), z_score.abs()) {
-                (p, z) if p >= 10.0 || z >= 5.0 => AnomalySeverity::Critical,
-                (p, z) if p >= 7.0 || z >= 4.0 => AnomalySeverity::High,
-                (p, z) if p >= 5.0 || z >= 3.0 => AnomalySeverity::Medium,
-                _ => AnomalySeverity::Low,
-            };
-            
-            let description = format!(
-                "Price anomaly detected for {}/{}: price moved {:.2}% from {:.4} to {:.4}, Z-score: {:.2}",
-                self.exchange,
-                self.symbol,
+            // Whichever of the two triggers the move went furthest past
+            let confidence = confidence_from_exceedance(
+                percentage_change.abs(),
+                self.config.percentage_threshold,
+            )
+            .max(confidence_from_exceedance(z_score.abs(), self.config.z_score_threshold));
+            let severity = severity_from_thresholds(
                 percentage_change,
-                self.last_price.unwrap_or(0.0),
+                &self.config.percentage_severity,
+            )
+            .max(severity_from_thresholds(z_score, &self.config.z_score_severity));
+            let confidence = severity.floor_confidence(confidence);
+            
+            let reason = AnomalyReason::new(
+                &self.exchange,
+                &self.symbol,
                 current_price,
-                z_score
-            );
+                ReasonKind::Price { change_pct: percentage_change, z_score },
+            )
+            .with_baseline(expected)
+            .with_threshold(self.config.percentage_threshold);
+            let description = reason.to_string();
             
             info!("{}", description);
             
@@ -172,16 +282,20 @@ impl AnomalyDetector for PriceAnomalyDetector {
                 exchange: self.exchange.clone(),
                 anomaly_type: AnomalyType::PriceSpike,
                 severity,
+                confidence,
                 metrics: AnomalyMetrics {
                     current_value: current_price,
-                    expected_value: mean,
-                    deviation: current_price - mean,
+                    expected_value: expected,
+                    deviation: current_price - expected,
                     z_score: Some(z_score),
                     percentage_change: Some(percentage_change),
                     historical_avg: Some(mean),
                     historical_std: Some(std_dev),
+                    ..Default::default()
                 },
                 description,
+                reason: Some(reason),
+                ..Default::default()
             })
         } else {
             None
@@ -192,126 +306,3726 @@ impl AnomalyDetector for PriceAnomalyDetector {
         self.window = TimeSeriesWindow::new(self.config.window_size);
         self.last_price = None;
     }
+    
+    fn kind(&self) -> String {
+        "price".to_string()
+    }
+    
+    fn window_fill(&self) -> Option<f64> {
+        Some(self.window.fill())
+    }
+    
+    fn state_name(&self) -> Option<&'static str> {
+        Some("price")
+    }
+    
+    fn snapshot(&self) -> DetectorState {
+        DetectorState {
+            window: self.window.values(),
+            last_price: self.last_price,
+            ..Default::default()
+        }
+    }
+    
+    fn restore(&mut self, state: &DetectorState, saved_at: DateTime<Utc>) {
+        self.window.restore(&state.window, saved_at);
+        self.last_price = state.last_price;
+    }
 }
 
-pub struct CompositeAnomalyDetector {
-    detectors: Vec<Box<dyn AnomalyDetector>>,
+// One timeframe of a multi-timeframe detector: the mean of the samples in the trailing
+// `secs`, judged against that mean as it stood at each earlier bar close
+struct Timeframe {
+    secs: u64,
+    samples: VecDeque<(DateTime<Utc>, f64)>,
+    sum: f64,
+    bar: Option<i64>,
+    bars: TimeSeriesWindow,
 }
 
-impl CompositeAnomalyDetector {
-    pub fn new() -> Self {
+impl Timeframe {
+    fn new(secs: u64, window_size: usize) -> Self {
         Self {
-            detectors: Vec::new(),
+            secs: secs.max(1),
+            samples: VecDeque::new(),
+            sum: 0.0,
+            bar: None,
+            bars: TimeSeriesWindow::new(window_size),
         }
     }
-    
-    pub fn add_detector(&mut self, detector: Box<dyn AnomalyDetector>) {
-        self.detectors.push(detector);
+
+    fn trailing_mean(&self) -> f64 {
+        self.sum / self.samples.len() as f64
     }
-    
-    pub fn detect_all(&mut self, data: &TimeSeriesData) -> Vec<AnomalyDetection> {
-        self.detectors
+
+    // Z-score of the trailing mean including `data`, once `min_bars` bars have closed
+    fn push(&mut self, data: &TimeSeriesData, min_bars: usize) -> Option<f64> {
+        let bar = data.timestamp.timestamp_millis().div_euclid(self.secs as i64 * 1000);
+        if self.bar.is_some_and(|last| bar > last) && !self.samples.is_empty() {
+            self.bars.push(TimeSeriesData {
+                timestamp: data.timestamp,
+                value: self.trailing_mean(),
+            });
+            // Recomputed once a bar so removals don't accumulate rounding
+            self.sum = self.samples.iter().map(|(_, v)| v).sum();
+        }
+        self.bar = Some(bar);
+
+        self.samples.push_back((data.timestamp, data.value));
+        self.sum += data.value;
+        let cutoff = data.timestamp - Duration::seconds(self.secs as i64);
+        while self.samples.len() > 1 && self.samples.front().is_some_and(|(t, _)| *t <= cutoff) {
+            if let Some((_, value)) = self.samples.pop_front() {
+                self.sum -= value;
+            }
+        }
+
+        (self.bars.len() >= min_bars && self.bars.std_dev() > 0.0)
+            .then(|| self.bars.z_score(self.trailing_mean()))
+    }
+}
+
+// Price detector that only alerts when enough timeframes agree. A single-tick spike moves the
+// 1s mean but barely registers over a minute, where a sustained move shows on every timeframe.
+pub struct MultiTimeframePriceDetector {
+    config: PriceAnomalyConfig,
+    symbol: String,
+    exchange: String,
+    timeframes: Vec<Timeframe>,
+    last_price: Option<f64>,
+}
+
+impl MultiTimeframePriceDetector {
+    pub fn new(config: PriceAnomalyConfig, symbol: String, exchange: String) -> Self {
+        let mut secs = config.timeframes_secs.clone();
+        secs.sort_unstable();
+        secs.dedup();
+        Self {
+            timeframes: secs
+                .into_iter()
+                .map(|s| Timeframe::new(s, config.window_size))
+                .collect(),
+            config,
+            symbol,
+            exchange,
+            last_price: None,
+        }
+    }
+}
+
+impl AnomalyDetector for MultiTimeframePriceDetector {
+    fn detect(&mut self, data: &TimeSeriesData) -> Option<AnomalyDetection> {
+        let min_bars = self.config.min_samples;
+        let scores: Vec<TimeframeZScore> = self
+            .timeframes
             .iter_mut()
-            .filter_map(|d| d.detect(data))
-            .collect()
+            .map(|tf| TimeframeZScore {
+                timeframe_secs: tf.secs,
+                z_score: tf.push(data, min_bars),
+            })
+            .collect();
+        let previous = self.last_price.replace(data.value);
+
+        let threshold = self.config.z_score_threshold;
+        let agreeing: Vec<(usize, f64)> = scores
+            .iter()
+            .enumerate()
+            .filter_map(|(i, s)| s.z_score.filter(|z| z.abs() >= threshold).map(|z| (i, z)))
+            .collect();
+        let required = self.config.required_confirmations.clamp(1, self.timeframes.len().max(1));
+        if agreeing.len() < required {
+            return None;
+        }
+
+        // Reported against the finest agreeing timeframe
+        let (finest, z_score) = agreeing[0];
+        let expected = self.timeframes[finest].bars.mean();
+        let std_dev = self.timeframes[finest].bars.std_dev();
+        let strongest = agreeing.iter().map(|(_, z)| z.abs()).fold(0.0, f64::max);
+        let confidence = confidence_from_exceedance(strongest, threshold);
+        let percentage_change = previous
+            .filter(|last| *last > 0.0)
+            .map(|last| (data.value - last) / last * 100.0);
+
+        let reason = AnomalyReason::new(
+            &self.exchange,
+            &self.symbol,
+            data.value,
+            ReasonKind::PriceTimeframes { agreeing: agreeing.len(), z_scores: scores.clone() },
+        )
+        .with_baseline(expected)
+        .with_threshold(threshold)
+        .with_window_secs(self.timeframes[finest].secs as f64);
+        let description = reason.to_string();
+
+        info!("{}", description);
+
+        Some(AnomalyDetection {
+            id: uuid::Uuid::new_v4(),
+            timestamp: data.timestamp,
+            symbol: self.symbol.clone(),
+            exchange: self.exchange.clone(),
+            anomaly_type: AnomalyType::PriceSpike,
+            severity: severity_from_confidence(confidence),
+            confidence,
+            metrics: AnomalyMetrics {
+                current_value: data.value,
+                expected_value: expected,
+                deviation: data.value - expected,
+                z_score: Some(z_score),
+                percentage_change,
+                historical_avg: Some(expected),
+                historical_std: Some(std_dev),
+                timeframe_z_scores: scores,
+                ..Default::default()
+            },
+            description,
+            reason: Some(reason),
+            ..Default::default()
+        })
     }
-    
-    pub fn reset_all(&mut self) {
-        for detector in &mut self.detectors {
-            detector.reset();
+
+    fn reset(&mut self) {
+        for tf in &mut self.timeframes {
+            *tf = Timeframe::new(tf.secs, self.config.window_size);
+        }
+        self.last_price = None;
+    }
+
+    fn kind(&self) -> String {
+        "price".to_string()
+    }
+
+    // The least filled timeframe, usually the longest
+    fn window_fill(&self) -> Option<f64> {
+        self.timeframes.iter().map(|tf| tf.bars.fill()).reduce(f64::min)
+    }
+
+    fn state_name(&self) -> Option<&'static str> {
+        Some("price")
+    }
+
+    // Closed bars only, the samples of the open bar are seconds old at most
+    fn snapshot(&self) -> DetectorState {
+        DetectorState {
+            last_price: self.last_price,
+            timeframes: self
+                .timeframes
+                .iter()
+                .map(|tf| TimeframeState {
+                    secs: tf.secs,
+                    bars: tf.bars.values(),
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    // Timeframes no longer configured are dropped, new ones start empty
+    fn restore(&mut self, state: &DetectorState, saved_at: DateTime<Utc>) {
+        self.reset();
+        self.last_price = state.last_price;
+        for saved in &state.timeframes {
+            if let Some(tf) = self.timeframes.iter_mut().find(|tf| tf.secs == saved.secs) {
+                tf.bars.restore(&saved.bars, saved_at);
+            }
         }
     }
 }
 
-pub struct AnomalyDetectorManager {
-    detectors: Arc<RwLock<HashMap<String, CompositeAnomalyDetector>>>,
-    volume_config: VolumeAnomalyConfig,
-    price_config: PriceAnomalyConfig,
+// Judges a series against an exponentially weighted mean and variance. Every sample moves the
+// baseline, so a sustained shift stops alerting once the baseline has caught up with it rather
+// than for a whole window length
+pub struct EwmaAnomalyDetector {
+    config: EwmaAnomalyConfig,
+    anomaly_type: AnomalyType,
+    // Series name in descriptions and saved state, e.g. "volume"
+    metric: &'static str,
+    symbol: String,
+    exchange: String,
+    alpha: f64,
+    mean: f64,
+    variance: f64,
+    samples: u64,
 }
 
-impl AnomalyDetectorManager {
+impl EwmaAnomalyDetector {
     pub fn new(
-        volume_config: VolumeAnomalyConfig,
-        price_config: PriceAnomalyConfig,
+        config: EwmaAnomalyConfig,
+        anomaly_type: AnomalyType,
+        metric: &'static str,
+        symbol: String,
+        exchange: String,
     ) -> Self {
         Self {
-            detectors: Arc::new(RwLock::new(HashMap::new())),
-            volume_config,
-            price_config,
+            alpha: 1.0 - 0.5f64.powf(1.0 / config.half_life_samples.max(f64::EPSILON)),
+            config,
+            anomaly_type,
+            metric,
+            symbol,
+            exchange,
+            mean: 0.0,
+            variance: 0.0,
+            samples: 0,
         }
     }
-    
-    pub fn get_or_create_detector(
-        &self,
-        symbol: &str,
-        exchange: &str,
-    ) -> CompositeAnomalyDetector {
-        let key = format!("{}:{}", exchange, symbol);
-        
-        let mut detectors = self.detectors.write();
-        
-        detectors.entry(key).or_insert_with(|| {
-            let mut composite = CompositeAnomalyDetector::new();
-            
-            composite.add_detector(Box::new(VolumeAnomalyDetector::new(
-                self.volume_config.clone(),
-                symbol.to_string(),
-                exchange.to_string(),
-            )));
-            
-            composite.add_detector(Box::new(PriceAnomalyDetector::new(
-                self.price_config.clone(),
-                symbol.to_string(),
-                exchange.to_string(),
-            )));
-            
-            composite
-        }).clone()
+}
+
+impl AnomalyDetector for EwmaAnomalyDetector {
+    fn detect(&mut self, data: &TimeSeriesData) -> Option<AnomalyDetection> {
+        if self.samples == 0 {
+            self.mean = data.value;
+            self.samples = 1;
+            return None;
+        }
+
+        // Judged against the baseline before this sample, which is then folded in
+        let expected = self.mean;
+        let std_dev = self.variance.sqrt();
+        let deviation = data.value - expected;
+        let z_score = if std_dev > 0.0 { deviation / std_dev } else { 0.0 };
+        let ready = self.samples as usize >= self.config.min_samples;
+
+        self.mean += self.alpha * deviation;
+        self.variance = (1.0 - self.alpha) * (self.variance + self.alpha * deviation * deviation);
+        self.samples += 1;
+
+        if !ready || z_score.abs() < self.config.k_sigma {
+            return None;
+        }
+
+        let severity = severity_from_thresholds(z_score, &self.config.severity);
+        let percentage_change = (expected != 0.0).then(|| deviation / expected * 100.0);
+
+        let reason = AnomalyReason::new(
+            &self.exchange,
+            &self.symbol,
+            data.value,
+            ReasonKind::Ewma { metric: self.metric.to_string(), z_score },
+        )
+        .with_baseline(expected)
+        .with_threshold(self.config.k_sigma);
+        let description = reason.to_string();
+
+        info!("{}", description);
+
+        Some(AnomalyDetection {
+            id: uuid::Uuid::new_v4(),
+            timestamp: data.timestamp,
+            symbol: self.symbol.clone(),
+            exchange: self.exchange.clone(),
+            anomaly_type: self.anomaly_type.clone(),
+            confidence: severity.confidence_floor(),
+            severity,
+            metrics: AnomalyMetrics {
+                current_value: data.value,
+                expected_value: expected,
+                deviation,
+                z_score: Some(z_score),
+                percentage_change,
+                historical_avg: Some(expected),
+                historical_std: Some(std_dev),
+                ..Default::default()
+            },
+            description,
+            reason: Some(reason),
+            ..Default::default()
+        })
     }
-    
-    pub fn process_data(
-        &self,
-        symbol: &str,
-        exchange: &str,
-        data: &TimeSeriesData,
-    ) -> Vec<AnomalyDetection> {
-        let key = format!("{}:{}", exchange, symbol);
-        
-        let mut detectors = self.detectors.write();
-        
-        let composite = detectors.entry(key).or_insert_with(|| {
-            let mut composite = CompositeAnomalyDetector::new();
-            
-            composite.add_detector(Box::new(VolumeAnomalyDetector::new(
-                self.volume_config.clone(),
-                symbol.to_string(),
-                exchange.to_string(),
-            )));
-            
-            composite.add_detector(Box::new(PriceAnomalyDetector::new(
-                self.price_config.clone(),
-                symbol.to_string(),
-                exchange.to_string(),
-            )));
-            
-            composite
-        });
-        
-        composite.detect_all(data)
+
+    fn reset(&mut self) {
+        self.mean = 0.0;
+        self.variance = 0.0;
+        self.samples = 0;
     }
-    
-    pub fn reset(&self, symbol: &str, exchange: &str) {
-        let key = format!("{}:{}", exchange, symbol);
-        
-        if let Some(detector) = self.detectors.write().get_mut(&key) {
-            detector.reset_all();
+
+    fn kind(&self) -> String {
+        self.metric.to_string()
+    }
+
+    fn series(&self) -> Series {
+        match self.anomaly_type {
+            AnomalyType::VolumeSpike => Series::Volume,
+            _ => Series::Price,
         }
     }
-    
-    pub fn reset_all(&self) {
-        let mut detectors = self.detectors.write();
-        for detector in detectors.values_mut() {
-            detector.reset_all();
+
+    fn state_name(&self) -> Option<&'static str> {
+        Some(self.metric)
+    }
+
+    fn snapshot(&self) -> DetectorState {
+        DetectorState {
+            ewma: Some(EwmaState {
+                mean: self.mean,
+                variance: self.variance,
+                samples: self.samples,
+            }),
+            ..Default::default()
+        }
+    }
+
+    // Windows saved while the metric used the window baseline are left alone
+    fn restore(&mut self, state: &DetectorState, _saved_at: DateTime<Utc>) {
+        if let Some(ewma) = state.ewma {
+            self.mean = ewma.mean;
+            self.variance = ewma.variance;
+            self.samples = ewma.samples;
+        }
+    }
+}
+
+// Judges a series by modified z-score, 0.6745 * (value - median) / MAD over a rolling window.
+// A single extreme value shifts the median and MAD by at most one rank, where it would drag a
+// mean and inflate a standard deviation enough to hide the next genuine spike.
+pub struct RobustAnomalyDetector {
+    threshold: f64,
+    min_samples: usize,
+    anomaly_type: AnomalyType,
+    // Series name in descriptions and saved state, e.g. "volume"
+    metric: &'static str,
+    symbol: String,
+    exchange: String,
+    window: RollingOrderStats,
+}
+
+impl RobustAnomalyDetector {
+    pub fn for_volume(config: &VolumeAnomalyConfig, symbol: String, exchange: String) -> Self {
+        Self {
+            threshold: config.modified_z_threshold,
+            min_samples: config.min_samples,
+            anomaly_type: AnomalyType::VolumeSpike,
+            metric: "volume",
+            symbol,
+            exchange,
+            window: RollingOrderStats::new(config.window_size),
+        }
+    }
+
+    pub fn for_price(config: &PriceAnomalyConfig, symbol: String, exchange: String) -> Self {
+        Self {
+            threshold: config.modified_z_threshold,
+            min_samples: config.min_samples,
+            anomaly_type: AnomalyType::PriceSpike,
+            metric: "price",
+            symbol,
+            exchange,
+            window: RollingOrderStats::new(config.window_size),
+        }
+    }
+}
+
+impl AnomalyDetector for RobustAnomalyDetector {
+    fn detect(&mut self, data: &TimeSeriesData) -> Option<AnomalyDetection> {
+        // Judged against the window before this sample
+        let ready = self.window.len() >= self.min_samples;
+        let median = self.window.median();
+        let mad = self.window.mad();
+        self.window.push(data.value);
+
+        // A window of mostly identical values has no spread to measure against
+        if !ready || mad <= 0.0 {
+            return None;
+        }
+        let deviation = data.value - median;
+        let modified_z = 0.6745 * deviation / mad;
+        if modified_z.abs() < self.threshold {
+            return None;
+        }
+        let confidence = confidence_from_exceedance(modified_z.abs(), self.threshold);
+
+        let reason = AnomalyReason::new(
+            &self.exchange,
+            &self.symbol,
+            data.value,
+            ReasonKind::Robust {
+                metric: self.metric.to_string(),
+                mad,
+                modified_z_score: modified_z,
+            },
+        )
+        .with_baseline(median)
+        .with_threshold(self.threshold);
+        let description = reason.to_string();
+
+        info!("{}", description);
+
+        Some(AnomalyDetection {
+            id: uuid::Uuid::new_v4(),
+            timestamp: data.timestamp,
+            symbol: self.symbol.clone(),
+            exchange: self.exchange.clone(),
+            anomaly_type: self.anomaly_type.clone(),
+            severity: severity_from_confidence(confidence),
+            confidence,
+            metrics: AnomalyMetrics {
+                current_value: data.value,
+                expected_value: median,
+                deviation,
+                z_score: Some(modified_z),
+                percentage_change: (median != 0.0).then(|| deviation / median * 100.0),
+                historical_avg: Some(median),
+                historical_std: Some(mad),
+                ..Default::default()
+            },
+            description,
+            reason: Some(reason),
+            ..Default::default()
+        })
+    }
+
+    fn reset(&mut self) {
+        self.window.clear();
+    }
+
+    fn kind(&self) -> String {
+        self.metric.to_string()
+    }
+
+    fn series(&self) -> Series {
+        match self.anomaly_type {
+            AnomalyType::VolumeSpike => Series::Volume,
+            _ => Series::Price,
+        }
+    }
+
+    fn window_fill(&self) -> Option<f64> {
+        Some(self.window.fill())
+    }
+
+    fn state_name(&self) -> Option<&'static str> {
+        Some(self.metric)
+    }
+
+    fn snapshot(&self) -> DetectorState {
+        DetectorState {
+            window: self.window.values(),
+            ..Default::default()
         }
     }
-}
\ No newline at end of file
+
+    fn restore(&mut self, state: &DetectorState, _saved_at: DateTime<Utc>) {
+        self.window.clear();
+        for &value in &state.window {
+            self.window.push(value);
+        }
+    }
+}
+
+// Gives the EWMA and median/MAD volume estimators the hour-of-day baselines the window
+// estimator has built in. The wrapped detector keeps its own baseline current and decides
+// until the hour has `min_days` of history, then the z-score against that hour does
+pub struct SeasonalVolumeDetector {
+    inner: Box<dyn AnomalyDetector>,
+    config: VolumeAnomalyConfig,
+    symbol: String,
+    exchange: String,
+    seasonal: SeasonalBaseline,
+}
+
+impl SeasonalVolumeDetector {
+    pub fn new(
+        inner: Box<dyn AnomalyDetector>,
+        config: VolumeAnomalyConfig,
+        symbol: String,
+        exchange: String,
+    ) -> Self {
+        Self {
+            seasonal: SeasonalBaseline::new(config.seasonality.clone()),
+            inner,
+            config,
+            symbol,
+            exchange,
+        }
+    }
+}
+
+impl AnomalyDetector for SeasonalVolumeDetector {
+    fn detect(&mut self, data: &TimeSeriesData) -> Option<AnomalyDetection> {
+        let seasonal = self.seasonal.baseline(data.timestamp);
+        self.seasonal.push(data.timestamp, data.value);
+        let own = self.inner.detect(data);
+        let Some((mean, std_dev)) = seasonal else {
+            return own;
+        };
+
+        let deviation = data.value - mean;
+        let z_score = if std_dev > 0.0 { deviation / std_dev } else { 0.0 };
+        let percentage_change = if mean > 0.0 { deviation / mean * 100.0 } else { 0.0 };
+        if z_score.abs() < self.config.z_score_threshold
+            || percentage_change.abs() < self.config.min_percentage_change
+        {
+            return None;
+        }
+        let severity = severity_from_thresholds(z_score, &self.config.z_score_severity);
+        let confidence = severity.floor_confidence(confidence_from_exceedance(
+            z_score.abs(),
+            self.config.z_score_threshold,
+        ));
+
+        let reason = AnomalyReason::new(
+            &self.exchange,
+            &self.symbol,
+            data.value,
+            ReasonKind::Volume {
+                change_pct: percentage_change,
+                z_score,
+                seasonal_hour: Some(data.timestamp.hour()),
+                percentile: None,
+                percentile_volume: None,
+            },
+        )
+        .with_baseline(mean)
+        .with_threshold(self.config.z_score_threshold);
+        let description = reason.to_string();
+
+        info!("{}", description);
+
+        Some(AnomalyDetection {
+            id: uuid::Uuid::new_v4(),
+            timestamp: data.timestamp,
+            symbol: self.symbol.clone(),
+            exchange: self.exchange.clone(),
+            anomaly_type: AnomalyType::VolumeSpike,
+            severity,
+            confidence,
+            metrics: AnomalyMetrics {
+                current_value: data.value,
+                expected_value: mean,
+                deviation,
+                z_score: Some(z_score),
+                percentage_change: Some(percentage_change),
+                historical_avg: Some(mean),
+                historical_std: Some(std_dev),
+                ..Default::default()
+            },
+            description,
+            reason: Some(reason),
+            ..Default::default()
+        })
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+        self.seasonal.clear();
+    }
+
+    fn kind(&self) -> String {
+        self.inner.kind()
+    }
+
+    fn window_fill(&self) -> Option<f64> {
+        self.inner.window_fill()
+    }
+
+    fn series(&self) -> Series {
+        Series::Volume
+    }
+
+    fn state_name(&self) -> Option<&'static str> {
+        self.inner.state_name()
+    }
+
+    fn snapshot(&self) -> DetectorState {
+        DetectorState {
+            seasonal: self.seasonal.moments(),
+            ..self.inner.snapshot()
+        }
+    }
+
+    fn restore(&mut self, state: &DetectorState, saved_at: DateTime<Utc>) {
+        self.inner.restore(state, saved_at);
+        self.seasonal.restore(&state.seasonal);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BandSide {
+    Upper,
+    Lower,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BollingerDetails {
+    pub side: BandSide,
+    pub lower: f64,
+    pub middle: f64,
+    pub upper: f64,
+    // Upper minus lower band over the middle one
+    pub band_width: f64,
+    // Share of past band widths at or below this one, low when the breach left a squeeze
+    pub band_width_percentile: Option<f64>,
+    pub consecutive: usize,
+}
+
+// Key of the price series inside the detector's own MetricsCalculator
+const BOLLINGER_KEY: &str = "price";
+
+// Flags prices that keep closing outside the Bollinger bands drawn by a MetricsCalculator. A
+// single print outside is usually a wick, so only `consecutive` prices in a row beyond the same
+// band alert, once per excursion. Each alert ranks the band width against past widths: a breach
+// out of a squeeze reads differently from one in an already wide market.
+pub struct BollingerBreachDetector {
+    config: BollingerConfig,
+    symbol: String,
+    exchange: String,
+    metrics: MetricsCalculator,
+    widths: TimeSeriesWindow,
+    // Side of the current run of prices outside the bands and its length
+    run: Option<(BandSide, usize)>,
+}
+
+impl BollingerBreachDetector {
+    pub fn new(config: BollingerConfig, symbol: String, exchange: String) -> Self {
+        Self {
+            widths: TimeSeriesWindow::with_quantiles(config.width_history.max(1)),
+            config,
+            symbol,
+            exchange,
+            metrics: MetricsCalculator::new(),
+            run: None,
+        }
+    }
+}
+
+impl AnomalyDetector for BollingerBreachDetector {
+    fn detect(&mut self, data: &TimeSeriesData) -> Option<AnomalyDetection> {
+        let period = self.config.period.max(2);
+        self.metrics.add_data(BOLLINGER_KEY, data.clone(), period);
+        if self.metrics.sample_count(BOLLINGER_KEY) < period {
+            return None;
+        }
+        let (lower, middle, upper) = self
+            .metrics
+            .calculate_bollinger_bands_with(BOLLINGER_KEY, self.config.multiplier)?;
+        let band_width = if middle != 0.0 { (upper - lower) / middle } else { 0.0 };
+        // Ranked against the widths before this one
+        let band_width_percentile = self.widths.percentile_rank(band_width);
+        self.widths.push(TimeSeriesData {
+            timestamp: data.timestamp,
+            value: band_width,
+        });
+
+        let (side, band) = if data.value > upper {
+            (BandSide::Upper, upper)
+        } else if data.value < lower {
+            (BandSide::Lower, lower)
+        } else {
+            self.run = None;
+            return None;
+        };
+        let consecutive = match self.run {
+            Some((run_side, count)) if run_side == side => count + 1,
+            _ => 1,
+        };
+        self.run = Some((side, consecutive));
+        if consecutive != self.config.consecutive.max(1) {
+            return None;
+        }
+
+        let deviation = data.value - middle;
+        let std_dev = (upper - middle) / self.config.multiplier.max(f64::EPSILON);
+        // Half confidence for a close right on the band, more the further past it
+        let confidence = confidence_from_exceedance(deviation.abs(), (band - middle).abs());
+
+        let reason = AnomalyReason::new(
+            &self.exchange,
+            &self.symbol,
+            data.value,
+            ReasonKind::BollingerBreach {
+                side,
+                consecutive,
+                band_width_percentile,
+            },
+        )
+        .with_baseline(middle)
+        .with_threshold(band);
+        let description = reason.to_string();
+
+        info!("{}", description);
+
+        Some(AnomalyDetection {
+            id: uuid::Uuid::new_v4(),
+            timestamp: data.timestamp,
+            symbol: self.symbol.clone(),
+            exchange: self.exchange.clone(),
+            anomaly_type: AnomalyType::BollingerBreach,
+            severity: severity_from_confidence(confidence),
+            confidence,
+            metrics: AnomalyMetrics {
+                current_value: data.value,
+                expected_value: middle,
+                deviation,
+                z_score: (std_dev > 0.0).then(|| deviation / std_dev),
+                percentage_change: (middle != 0.0).then(|| deviation / middle * 100.0),
+                historical_avg: Some(middle),
+                historical_std: Some(std_dev),
+                ..Default::default()
+            },
+            description,
+            reason: Some(reason),
+            details: Some(DetectionDetails::Bollinger(BollingerDetails {
+                side,
+                lower,
+                middle,
+                upper,
+                band_width,
+                band_width_percentile,
+                consecutive,
+            })),
+            ..Default::default()
+        })
+    }
+
+    fn reset(&mut self) {
+        self.metrics.reset_key(BOLLINGER_KEY);
+        self.widths = TimeSeriesWindow::with_quantiles(self.config.width_history.max(1));
+        self.run = None;
+    }
+
+    fn kind(&self) -> String {
+        "bollinger".to_string()
+    }
+
+    fn window_fill(&self) -> Option<f64> {
+        let period = self.config.period.max(2);
+        Some(self.metrics.sample_count(BOLLINGER_KEY) as f64 / period as f64)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LargeTradeTrigger {
+    Absolute,
+    Percentile,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LargeTradeDetails {
+    pub trigger: LargeTradeTrigger,
+    pub notional: f64,
+    pub median: f64,
+    pub p95: f64,
+    // Notional at the configured percentile, the percentile trigger's threshold
+    pub percentile_threshold: f64,
+    pub multiple_of_median: f64,
+}
+
+pub struct LargeTradeDetector {
+    config: LargeTradeConfig,
+    symbol: String,
+    exchange: String,
+    // Recent notionals in arrival order, and the same values kept sorted for percentiles
+    window: VecDeque<f64>,
+    sorted: Vec<f64>,
+}
+
+impl LargeTradeDetector {
+    pub fn new(config: LargeTradeConfig, symbol: String, exchange: String) -> Self {
+        Self {
+            window: VecDeque::with_capacity(config.window_size),
+            sorted: Vec::with_capacity(config.window_size),
+            config,
+            symbol,
+            exchange,
+        }
+    }
+    
+    // Nearest-rank percentile over the current window
+    fn percentile(&self, pct: f64) -> f64 {
+        if self.sorted.is_empty() {
+            return 0.0;
+        }
+        let rank = ((pct / 100.0) * self.sorted.len() as f64).ceil() as usize;
+        self.sorted[rank.clamp(1, self.sorted.len()) - 1]
+    }
+    
+    fn push(&mut self, notional: f64) {
+        if self.window.len() >= self.config.window_size.max(1) {
+            if let Some(oldest) = self.window.pop_front() {
+                let index = self.sorted.partition_point(|v| *v < oldest);
+                self.sorted.remove(index);
+            }
+        }
+        self.window.push_back(notional);
+        let index = self.sorted.partition_point(|v| *v < notional);
+        self.sorted.insert(index, notional);
+    }
+}
+
+impl TradeAnomalyDetector for LargeTradeDetector {
+    fn detect_trade(&mut self, trade: &TradeSample) -> Option<AnomalyDetection> {
+        let notional = trade.price * trade.volume;
+        if !notional.is_finite() || notional <= 0.0 {
+            return None;
+        }
+        
+        // The trade is judged against the distribution before it
+        let warmed_up = self.window.len() >= self.config.min_samples;
+        let median = self.percentile(50.0);
+        let p95 = self.percentile(95.0);
+        let percentile_threshold = self.percentile(self.config.percentile);
+        self.push(notional);
+        
+        if !warmed_up {
+            return None;
+        }
+        
+        let absolute =
+            self.config.absolute_threshold > 0.0 && notional >= self.config.absolute_threshold;
+        let relative = percentile_threshold > 0.0 && notional > percentile_threshold;
+        if !absolute && !relative {
+            return None;
+        }
+        
+        let multiple_of_median = if median > 0.0 { notional / median } else { 0.0 };
+        let excess = [
+            (self.config.absolute_threshold, absolute),
+            (percentile_threshold, relative),
+        ]
+        .iter()
+        .filter(|(threshold, hit)| *hit && *threshold > 0.0)
+        .map(|(threshold, _)| notional / threshold)
+        .fold(0.0, f64::max);
+        let mut severity = severity_from_thresholds(excess, &self.config.severity);
+        if absolute {
+            severity = severity.max(AnomalySeverity::Medium);
+        }
+        let trigger = if absolute {
+            LargeTradeTrigger::Absolute
+        } else {
+            LargeTradeTrigger::Percentile
+        };
+        
+        let reason = AnomalyReason::new(
+            &self.exchange,
+            &self.symbol,
+            notional,
+            ReasonKind::LargeTrade {
+                volume: trade.volume,
+                price: trade.price,
+                multiple_of_median,
+                percentile: self.config.percentile,
+            },
+        )
+        .with_baseline(median)
+        .with_threshold(percentile_threshold);
+        let description = reason.to_string();
+        
+        info!("{}", description);
+        
+        Some(AnomalyDetection {
+            id: uuid::Uuid::new_v4(),
+            timestamp: trade.timestamp,
+            symbol: self.symbol.clone(),
+            exchange: self.exchange.clone(),
+            anomaly_type: AnomalyType::LargeOrder,
+            confidence: severity.confidence_floor(),
+            severity,
+            metrics: AnomalyMetrics {
+                current_value: notional,
+                expected_value: p95,
+                deviation: notional - p95,
+                z_score: None,
+                // Relative to the median, so 400% is five times the typical trade
+                percentage_change: (median > 0.0).then(|| (multiple_of_median - 1.0) * 100.0),
+                historical_avg: Some(median),
+                historical_std: None,
+                ..Default::default()
+            },
+            description,
+            reason: Some(reason),
+            details: Some(DetectionDetails::LargeTrade(LargeTradeDetails {
+                trigger,
+                notional,
+                median,
+                p95,
+                percentile_threshold,
+                multiple_of_median,
+            })),
+            ..Default::default()
+        })
+    }
+    
+    fn reset(&mut self) {
+        self.window.clear();
+        self.sorted.clear();
+    }
+    
+    fn kind(&self) -> String {
+        "large_trade".to_string()
+    }
+    
+    fn window_fill(&self) -> Option<f64> {
+        Some(self.window.len() as f64 / self.config.window_size.max(1) as f64)
+    }
+    
+    fn state_name(&self) -> Option<&'static str> {
+        Some("large_trade")
+    }
+    
+    fn snapshot(&self) -> DetectorState {
+        DetectorState {
+            window: self.window.iter().copied().collect(),
+            ..Default::default()
+        }
+    }
+    
+    fn restore(&mut self, state: &DetectorState, _saved_at: DateTime<Utc>) {
+        self.reset();
+        for &notional in &state.window {
+            self.push(notional);
+        }
+    }
+}
+
+pub struct DepthImbalanceDetector {
+    config: DepthImbalanceConfig,
+    symbol: String,
+    exchange: String,
+    // Severity of the imbalance currently in progress, so a persisting skew only
+    // fires again when it gets worse
+    active: Option<AnomalySeverity>,
+}
+
+impl DepthImbalanceDetector {
+    pub fn new(config: DepthImbalanceConfig, symbol: String, exchange: String) -> Self {
+        Self {
+            config,
+            symbol,
+            exchange,
+            active: None,
+        }
+    }
+
+    // Quantity and notional over the best `levels` levels of one side
+    fn side_depth(
+        &self,
+        levels: &[OrderBookLevel],
+        best_first: impl Fn(f64, f64) -> std::cmp::Ordering,
+    ) -> (f64, f64) {
+        let mut sorted: Vec<&OrderBookLevel> = levels
+            .iter()
+            .filter(|l| l.price > 0.0 && l.quantity > 0.0)
+            .collect();
+        sorted.sort_by(|a, b| best_first(a.price, b.price));
+        sorted
+            .iter()
+            .take(self.config.levels)
+            .fold((0.0, 0.0), |(qty, notional), l| {
+                (qty + l.quantity, notional + l.price * l.quantity)
+            })
+    }
+
+    fn severity(&self, skew: f64) -> AnomalySeverity {
+        severity_from_thresholds(skew / self.config.ratio_threshold, &self.config.severity)
+    }
+}
+
+impl BookAnomalyDetector for DepthImbalanceDetector {
+    fn detect_book(&mut self, book: &OrderBook) -> Option<AnomalyDetection> {
+        let (bid_volume, bid_notional) = self.side_depth(&book.bids, |a, b| b.total_cmp(&a));
+        let (ask_volume, ask_notional) = self.side_depth(&book.asks, |a, b| a.total_cmp(&b));
+
+        // A missing side is a feed problem rather than an imbalance
+        if bid_volume <= 0.0
+            || ask_volume <= 0.0
+            || bid_notional + ask_notional < self.config.min_notional
+        {
+            return None;
+        }
+
+        let ratio = bid_volume / ask_volume;
+        let skew = ratio.max(1.0 / ratio);
+        if skew < self.config.ratio_threshold {
+            self.active = None;
+            return None;
+        }
+
+        let severity = self.severity(skew);
+        if self.active.as_ref().is_some_and(|active| *active >= severity) {
+            return None;
+        }
+        self.active = Some(severity.clone());
+
+        let heavy_side = if ratio >= 1.0 { BookSide::Bid } else { BookSide::Ask };
+        let reason = AnomalyReason::new(
+            &self.exchange,
+            &self.symbol,
+            skew,
+            ReasonKind::DepthImbalance {
+                heavy_side,
+                levels: self.config.levels,
+                bid_volume,
+                ask_volume,
+            },
+        )
+        .with_threshold(self.config.ratio_threshold);
+        let description = reason.to_string();
+
+        info!("{}", description);
+
+        Some(AnomalyDetection {
+            id: uuid::Uuid::new_v4(),
+            timestamp: book.timestamp,
+            symbol: self.symbol.clone(),
+            exchange: self.exchange.clone(),
+            anomaly_type: AnomalyType::DepthImbalance,
+            confidence: severity.confidence_floor(),
+            severity,
+            metrics: AnomalyMetrics {
+                current_value: ratio,
+                expected_value: 1.0,
+                deviation: ratio - 1.0,
+                z_score: None,
+                percentage_change: Some(
+                    (bid_volume - ask_volume) / (bid_volume + ask_volume) * 100.0,
+                ),
+                historical_avg: None,
+                historical_std: None,
+                ..Default::default()
+            },
+            description,
+            reason: Some(reason),
+            ..Default::default()
+        })
+    }
+
+    fn reset(&mut self) {
+        self.active = None;
+    }
+
+    fn kind(&self) -> String {
+        "depth_imbalance".to_string()
+    }
+}
+
+pub struct SpreadAnomalyDetector {
+    config: SpreadAnomalyConfig,
+    symbol: String,
+    exchange: String,
+    // Relative spreads in basis points of the mid price
+    window: TimeSeriesWindow,
+    // Severity of the widening currently in progress, so a spread that stays wide only
+    // fires again when it gets worse
+    active: Option<AnomalySeverity>,
+}
+
+impl SpreadAnomalyDetector {
+    pub fn new(config: SpreadAnomalyConfig, symbol: String, exchange: String) -> Self {
+        Self {
+            window: TimeSeriesWindow::new(config.window_size),
+            config,
+            symbol,
+            exchange,
+            active: None,
+        }
+    }
+}
+
+impl QuoteAnomalyDetector for SpreadAnomalyDetector {
+    fn detect_quote(&mut self, quote: &QuoteSample) -> Option<AnomalyDetection> {
+        // Empty, locked or crossed quotes are a feed problem rather than a wide spread
+        if quote.bid_price <= 0.0 || quote.ask_price <= quote.bid_price {
+            return None;
+        }
+
+        let mid = (quote.bid_price + quote.ask_price) / 2.0;
+        let spread_bps = (quote.ask_price - quote.bid_price) / mid * 10_000.0;
+
+        // Judged against the history before this quote
+        let ready = self.window.len() >= self.config.min_samples;
+        let mean = self.window.mean();
+        let std_dev = self.window.std_dev();
+        let z_score = self.window.z_score(spread_bps);
+        self.window.push(TimeSeriesData {
+            timestamp: quote.timestamp,
+            value: spread_bps,
+        });
+
+        let over_cap =
+            self.config.max_spread_bps > 0.0 && spread_bps >= self.config.max_spread_bps;
+        let over_z = ready && z_score >= self.config.z_score_threshold;
+        if !over_cap && !over_z {
+            self.active = None;
+            return None;
+        }
+
+        let multiple = if ready && mean > 0.0 {
+            spread_bps / mean
+        } else {
+            spread_bps / self.config.max_spread_bps
+        };
+        // Scales with how many times the historical mean the spread is
+        let severity = severity_from_thresholds(multiple, &self.config.severity);
+        if self.active.as_ref().is_some_and(|active| *active >= severity) {
+            return None;
+        }
+        self.active = Some(severity.clone());
+
+        let reason = AnomalyReason::new(
+            &self.exchange,
+            &self.symbol,
+            spread_bps,
+            ReasonKind::SpreadWidening {
+                bid_price: quote.bid_price,
+                ask_price: quote.ask_price,
+                multiple,
+                z_score: ready.then_some(z_score),
+            },
+        );
+        // Judged against the history once there is enough of it, else against the cap alone
+        let reason = if ready {
+            reason.with_baseline(mean).with_threshold(self.config.z_score_threshold)
+        } else {
+            reason.with_threshold(self.config.max_spread_bps)
+        };
+        let description = reason.to_string();
+
+        info!("{}", description);
+
+        let expected = if ready { mean } else { self.config.max_spread_bps };
+        Some(AnomalyDetection {
+            id: uuid::Uuid::new_v4(),
+            timestamp: quote.timestamp,
+            symbol: self.symbol.clone(),
+            exchange: self.exchange.clone(),
+            anomaly_type: AnomalyType::SpreadWidening,
+            confidence: severity.confidence_floor(),
+            severity,
+            metrics: AnomalyMetrics {
+                current_value: spread_bps,
+                expected_value: expected,
+                deviation: spread_bps - expected,
+                z_score: ready.then_some(z_score),
+                percentage_change: (ready && mean > 0.0)
+                    .then(|| (spread_bps - mean) / mean * 100.0),
+                historical_avg: ready.then_some(mean),
+                historical_std: ready.then_some(std_dev),
+                ..Default::default()
+            },
+            description,
+            reason: Some(reason),
+            ..Default::default()
+        })
+    }
+
+    fn reset(&mut self) {
+        self.window = TimeSeriesWindow::new(self.config.window_size);
+        self.active = None;
+    }
+
+    fn kind(&self) -> String {
+        "spread".to_string()
+    }
+
+    fn window_fill(&self) -> Option<f64> {
+        Some(self.window.fill())
+    }
+
+    fn state_name(&self) -> Option<&'static str> {
+        Some("spread")
+    }
+
+    fn snapshot(&self) -> DetectorState {
+        DetectorState {
+            window: self.window.values(),
+            ..Default::default()
+        }
+    }
+
+    fn restore(&mut self, state: &DetectorState, saved_at: DateTime<Utc>) {
+        self.window.restore(&state.window, saved_at);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DivergenceDetails {
+    pub high_exchange: String,
+    pub high_price: f64,
+    pub low_exchange: String,
+    pub low_price: f64,
+    pub spread_bps: f64,
+    pub duration_secs: f64,
+    // Every exchange price that took part in the comparison
+    pub prices: Vec<(String, f64)>,
+}
+
+// Tracks the latest price of one symbol on every exchange and flags spreads that stay wide
+pub struct CrossExchangeDivergenceDetector {
+    config: CrossExchangeDivergenceConfig,
+    symbol: String,
+    prices: HashMap<String, (f64, DateTime<Utc>)>,
+    // When the spread last crossed the threshold, cleared as soon as it converges
+    diverging_since: Option<DateTime<Utc>>,
+    // Set once the current divergence has been reported
+    reported: bool,
+}
+
+impl CrossExchangeDivergenceDetector {
+    pub fn new(config: CrossExchangeDivergenceConfig, symbol: String) -> Self {
+        Self {
+            config,
+            symbol,
+            prices: HashMap::new(),
+            diverging_since: None,
+            reported: false,
+        }
+    }
+
+    fn severity(&self, spread_bps: f64) -> AnomalySeverity {
+        severity_from_thresholds(spread_bps / self.config.threshold_bps, &self.config.severity)
+    }
+
+    pub fn update(
+        &mut self,
+        exchange: &str,
+        price: f64,
+        timestamp: DateTime<Utc>,
+    ) -> Option<AnomalyDetection> {
+        if price <= 0.0 || !price.is_finite() {
+            return None;
+        }
+        match self.prices.get(exchange) {
+            Some((_, seen)) if *seen > timestamp => {}
+            _ => {
+                self.prices.insert(exchange.to_string(), (price, timestamp));
+            }
+        }
+
+        let max_age = Duration::seconds(self.config.max_price_age_secs as i64);
+        let mut fresh: Vec<(String, f64)> = self
+            .prices
+            .iter()
+            .filter(|(_, (_, seen))| timestamp - *seen <= max_age)
+            .map(|(exchange, (price, _))| (exchange.clone(), *price))
+            .collect();
+        fresh.sort_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+
+        let (low_exchange, low_price) = fresh.first()?.clone();
+        let (high_exchange, high_price) = fresh.last()?.clone();
+        let spread_bps = (high_price - low_price) / ((high_price + low_price) / 2.0) * 10_000.0;
+
+        if fresh.len() < 2 || spread_bps < self.config.threshold_bps {
+            self.diverging_since = None;
+            self.reported = false;
+            return None;
+        }
+
+        let since = *self.diverging_since.get_or_insert(timestamp);
+        let duration = timestamp - since;
+        if self.reported || duration < Duration::seconds(self.config.min_duration_secs as i64) {
+            return None;
+        }
+        self.reported = true;
+
+        // Attribute the alert to whichever side sits further from the rest of the market
+        let mean = fresh.iter().map(|(_, price)| price).sum::<f64>() / fresh.len() as f64;
+        let exchange = if high_price - mean >= mean - low_price {
+            high_exchange.clone()
+        } else {
+            low_exchange.clone()
+        };
+
+        let duration_secs = duration.num_milliseconds() as f64 / 1000.0;
+        let reason = AnomalyReason::new(
+            &exchange,
+            &self.symbol,
+            spread_bps,
+            ReasonKind::Divergence {
+                high_exchange: high_exchange.clone(),
+                high_price,
+                low_exchange: low_exchange.clone(),
+                low_price,
+            },
+        )
+        .with_threshold(self.config.threshold_bps)
+        .with_window_secs(duration_secs);
+        let description = reason.to_string();
+
+        info!("{}", description);
+
+        let severity = self.severity(spread_bps);
+        Some(AnomalyDetection {
+            id: uuid::Uuid::new_v4(),
+            timestamp,
+            symbol: self.symbol.clone(),
+            exchange,
+            anomaly_type: AnomalyType::PriceDivergence,
+            confidence: severity.confidence_floor(),
+            severity,
+            metrics: AnomalyMetrics {
+                current_value: spread_bps,
+                expected_value: self.config.threshold_bps,
+                deviation: spread_bps - self.config.threshold_bps,
+                z_score: None,
+                percentage_change: Some(spread_bps / 100.0),
+                historical_avg: Some(mean),
+                historical_std: None,
+                ..Default::default()
+            },
+            description,
+            reason: Some(reason),
+            details: Some(DetectionDetails::Divergence(DivergenceDetails {
+                high_exchange,
+                high_price,
+                low_exchange,
+                low_price,
+                spread_bps,
+                duration_secs,
+                prices: fresh,
+            })),
+            ..Default::default()
+        })
+    }
+
+    // Drops one exchange from the comparison, e.g. when its feed is reset
+    pub fn remove_exchange(&mut self, exchange: &str) {
+        self.prices.remove(exchange);
+    }
+
+    pub fn reset(&mut self) {
+        self.prices.clear();
+        self.diverging_since = None;
+        self.reported = false;
+    }
+}
+
+pub struct CompositeAnomalyDetector {
+    detectors: Vec<Tracked<Box<dyn AnomalyDetector>>>,
+    trade_detectors: Vec<Tracked<Box<dyn TradeAnomalyDetector>>>,
+    book_detectors: Vec<Tracked<Box<dyn BookAnomalyDetector>>>,
+    quote_detectors: Vec<Tracked<Box<dyn QuoteAnomalyDetector>>>,
+    // Every trade counts as a candle sample
+    candles: Option<Tracked<CandleAggregator>>,
+}
+
+impl CompositeAnomalyDetector {
+    pub fn new() -> Self {
+        Self {
+            detectors: Vec::new(),
+            trade_detectors: Vec::new(),
+            book_detectors: Vec::new(),
+            quote_detectors: Vec::new(),
+            candles: None,
+        }
+    }
+    
+    pub fn add_detector(&mut self, detector: Box<dyn AnomalyDetector>) {
+        self.detectors.push(Tracked::new(detector));
+    }
+    
+    pub fn add_trade_detector(&mut self, detector: Box<dyn TradeAnomalyDetector>) {
+        self.trade_detectors.push(Tracked::new(detector));
+    }
+    
+    pub fn add_book_detector(&mut self, detector: Box<dyn BookAnomalyDetector>) {
+        self.book_detectors.push(Tracked::new(detector));
+    }
+    
+    pub fn add_quote_detector(&mut self, detector: Box<dyn QuoteAnomalyDetector>) {
+        self.quote_detectors.push(Tracked::new(detector));
+    }
+    
+    pub fn set_candle_aggregator(&mut self, candles: CandleAggregator) {
+        self.candles = Some(Tracked::new(candles));
+    }
+    
+    pub fn detect_all(&mut self, data: &TimeSeriesData) -> Vec<AnomalyDetection> {
+        self.detectors
+            .iter_mut()
+            .filter_map(|d| d.stats.record(d.detector.detect(data)))
+            .collect()
+    }
+    
+    // Each detector fed the trade's price or volume, whichever series it watches
+    pub fn detect_series(&mut self, trade: &TradeSample) -> Vec<AnomalyDetection> {
+        self.detectors
+            .iter_mut()
+            .filter_map(|d| {
+                let sample = d.detector.series().sample(trade);
+                d.stats.record(d.detector.detect(&sample))
+            })
+            .collect()
+    }
+    
+    pub fn detect_trade(&mut self, trade: &TradeSample) -> Vec<AnomalyDetection> {
+        let mut detections: Vec<_> = self
+            .trade_detectors
+            .iter_mut()
+            .filter_map(|d| d.stats.record(d.detector.detect_trade(trade)))
+            .collect();
+        if let Some(candles) = &mut self.candles {
+            let closed = candles.detector.push_trade(trade);
+            candles.stats.samples_seen += 1;
+            for detection in &closed {
+                candles.stats.record_anomaly(detection);
+            }
+            detections.extend(closed);
+        }
+        detections
+    }
+    
+    // Closes candles whose end has passed `now`, so a pair that stops trading still gets its
+    // last candles judged
+    pub fn flush_candles(&mut self, now: DateTime<Utc>) -> Vec<AnomalyDetection> {
+        let Some(candles) = &mut self.candles else {
+            return Vec::new();
+        };
+        let closed = candles.detector.flush(now);
+        for detection in &closed {
+            candles.stats.record_anomaly(detection);
+        }
+        closed
+    }
+    
+    pub fn detect_book(&mut self, book: &OrderBook) -> Vec<AnomalyDetection> {
+        self.book_detectors
+            .iter_mut()
+            .filter_map(|d| d.stats.record(d.detector.detect_book(book)))
+            .collect()
+    }
+    
+    pub fn detect_quote(&mut self, quote: &QuoteSample) -> Vec<AnomalyDetection> {
+        self.quote_detectors
+            .iter_mut()
+            .filter_map(|d| d.stats.record(d.detector.detect_quote(quote)))
+            .collect()
+    }
+    
+    // Runtime statistics of every detector, by kind
+    pub fn stats(&self) -> Vec<(String, DetectorStats)> {
+        let with_fill = |stats: &DetectorStats, fill: Option<f64>| DetectorStats {
+            window_fill: fill,
+            ..stats.clone()
+        };
+        let mut entries = Vec::new();
+        for Tracked { detector, stats } in &self.detectors {
+            entries.push((detector.kind(), with_fill(stats, detector.window_fill())));
+        }
+        for Tracked { detector, stats } in &self.trade_detectors {
+            entries.push((detector.kind(), with_fill(stats, detector.window_fill())));
+        }
+        for Tracked { detector, stats } in &self.book_detectors {
+            entries.push((detector.kind(), with_fill(stats, detector.window_fill())));
+        }
+        for Tracked { detector, stats } in &self.quote_detectors {
+            entries.push((detector.kind(), with_fill(stats, detector.window_fill())));
+        }
+        if let Some(candles) = &self.candles {
+            entries.push(("candles".to_string(), candles.stats.clone()));
+        }
+        entries
+    }
+    
+    // Saved history of every detector that keeps one, by state name
+    pub fn snapshot(&self) -> HashMap<String, DetectorState> {
+        let mut states = HashMap::new();
+        for Tracked { detector, .. } in &self.detectors {
+            if let Some(name) = detector.state_name() {
+                states.insert(name.to_string(), detector.snapshot());
+            }
+        }
+        for Tracked { detector, .. } in &self.trade_detectors {
+            if let Some(name) = detector.state_name() {
+                states.insert(name.to_string(), detector.snapshot());
+            }
+        }
+        for Tracked { detector, .. } in &self.quote_detectors {
+            if let Some(name) = detector.state_name() {
+                states.insert(name.to_string(), detector.snapshot());
+            }
+        }
+        states
+    }
+    
+    pub fn restore(&mut self, states: &HashMap<String, DetectorState>, saved_at: DateTime<Utc>) {
+        for Tracked { detector, .. } in &mut self.detectors {
+            if let Some(state) = detector.state_name().and_then(|name| states.get(name)) {
+                detector.restore(state, saved_at);
+            }
+        }
+        for Tracked { detector, .. } in &mut self.trade_detectors {
+            if let Some(state) = detector.state_name().and_then(|name| states.get(name)) {
+                detector.restore(state, saved_at);
+            }
+        }
+        for Tracked { detector, .. } in &mut self.quote_detectors {
+            if let Some(state) = detector.state_name().and_then(|name| states.get(name)) {
+                detector.restore(state, saved_at);
+            }
+        }
+    }
+    
+    pub fn reset_all(&mut self) {
+        for Tracked { detector, .. } in &mut self.detectors {
+            detector.reset();
+        }
+        for Tracked { detector, .. } in &mut self.trade_detectors {
+            detector.reset();
+        }
+        for Tracked { detector, .. } in &mut self.book_detectors {
+            detector.reset();
+        }
+        for Tracked { detector, .. } in &mut self.quote_detectors {
+            detector.reset();
+        }
+        if let Some(candles) = &mut self.candles {
+            candles.detector.reset();
+        }
+    }
+}
+
+pub struct AnomalyDetectorManager {
+    // A lock per pair: the map's shards are only held to look a pair up, detection runs under
+    // the pair's own mutex, so trades on different pairs never wait on each other. The map is
+    // never locked while holding the mutex of a pair already in it
+    detectors: Arc<DashMap<InstrumentKey, Arc<Mutex<CompositeAnomalyDetector>>>>,
+    // Keyed by symbol alone, each one compares every exchange quoting the symbol
+    divergence: Arc<DashMap<String, CrossExchangeDivergenceDetector>>,
+    volume_config: VolumeAnomalyConfig,
+    price_config: PriceAnomalyConfig,
+    volume_baseline: BaselineKind,
+    price_baseline: BaselineKind,
+    ewma_config: EwmaAnomalyConfig,
+    depth_config: DepthImbalanceConfig,
+    large_trade_config: LargeTradeConfig,
+    divergence_config: CrossExchangeDivergenceConfig,
+    spread_config: SpreadAnomalyConfig,
+    // Volatility regimes are only watched once configured
+    volatility_config: Option<VolatilityRegimeConfig>,
+    // One detector per horizon, only once configured
+    rate_of_change_config: Option<RateOfChangeConfig>,
+    // Candles are only built from trades once configured
+    candle_config: Option<CandleConfig>,
+    // Order book update rates are only watched once configured
+    update_rate_config: Option<UpdateRateConfig>,
+    // Bollinger band breaches are only watched once configured
+    bollinger_config: Option<BollingerConfig>,
+    // Hour-of-day baselines from a snapshot too old to restore whole, seeded into each pair's
+    // detectors as they are created so a warm-up from stored ticks still runs
+    long_horizon: DashMap<InstrumentKey, (HashMap<String, DetectorState>, DateTime<Utc>)>,
+    warmup: Option<Arc<WarmupTracker>>,
+    suppressor: Option<Arc<AlertSuppressor>>,
+}
+
+impl AnomalyDetectorManager {
+    pub fn new(
+        volume_config: VolumeAnomalyConfig,
+        price_config: PriceAnomalyConfig,
+    ) -> Self {
+        Self {
+            detectors: Arc::new(DashMap::new()),
+            divergence: Arc::new(DashMap::new()),
+            volume_config,
+            price_config,
+            volume_baseline: BaselineKind::Window,
+            price_baseline: BaselineKind::Window,
+            ewma_config: EwmaAnomalyConfig::default(),
+            depth_config: DepthImbalanceConfig::default(),
+            large_trade_config: LargeTradeConfig::default(),
+            divergence_config: CrossExchangeDivergenceConfig::default(),
+            spread_config: SpreadAnomalyConfig::default(),
+            volatility_config: None,
+            rate_of_change_config: None,
+            candle_config: None,
+            update_rate_config: None,
+            bollinger_config: None,
+            long_horizon: DashMap::new(),
+            warmup: None,
+            suppressor: None,
+        }
+    }
+    
+    pub fn with_baselines(mut self, volume: BaselineKind, price: BaselineKind) -> Self {
+        self.volume_baseline = volume;
+        self.price_baseline = price;
+        self
+    }
+    
+    pub fn with_ewma_config(mut self, ewma_config: EwmaAnomalyConfig) -> Self {
+        self.ewma_config = ewma_config;
+        self
+    }
+    
+    pub fn with_depth_config(mut self, depth_config: DepthImbalanceConfig) -> Self {
+        self.depth_config = depth_config;
+        self
+    }
+    
+    pub fn with_large_trade_config(mut self, large_trade_config: LargeTradeConfig) -> Self {
+        self.large_trade_config = large_trade_config;
+        self
+    }
+    
+    pub fn with_divergence_config(mut self, divergence_config: CrossExchangeDivergenceConfig) -> Self {
+        self.divergence_config = divergence_config;
+        self
+    }
+    
+    pub fn with_spread_config(mut self, spread_config: SpreadAnomalyConfig) -> Self {
+        self.spread_config = spread_config;
+        self
+    }
+    
+    pub fn with_volatility_config(mut self, volatility_config: VolatilityRegimeConfig) -> Self {
+        self.volatility_config = Some(volatility_config);
+        self
+    }
+    
+    pub fn with_rate_of_change_config(mut self, rate_of_change_config: RateOfChangeConfig) -> Self {
+        self.rate_of_change_config = Some(rate_of_change_config);
+        self
+    }
+    
+    pub fn with_candle_config(mut self, candle_config: CandleConfig) -> Self {
+        self.candle_config = Some(candle_config);
+        self
+    }
+    
+    pub fn with_update_rate_config(mut self, update_rate_config: UpdateRateConfig) -> Self {
+        self.update_rate_config = Some(update_rate_config);
+        self
+    }
+    
+    pub fn with_bollinger_config(mut self, bollinger_config: BollingerConfig) -> Self {
+        self.bollinger_config = Some(bollinger_config);
+        self
+    }
+    
+    pub fn with_warmup(mut self, warmup: Arc<WarmupTracker>) -> Self {
+        self.warmup = Some(warmup);
+        self
+    }
+    
+    pub fn warmup(&self) -> Option<&Arc<WarmupTracker>> {
+        self.warmup.as_ref()
+    }
+    
+    // Drops repeats of detections already sent for the same exchange, symbol and type
+    pub fn with_suppressor(mut self, suppressor: Arc<AlertSuppressor>) -> Self {
+        self.suppressor = Some(suppressor);
+        self
+    }
+    
+    pub fn suppression_stats(&self) -> SuppressionStats {
+        self.suppressor
+            .as_ref()
+            .map(|suppressor| suppressor.stats())
+            .unwrap_or_default()
+    }
+    
+    fn suppress_repeats(&self, detections: &mut Vec<AnomalyDetection>) {
+        if let Some(suppressor) = &self.suppressor {
+            detections.retain(|detection| suppressor.admit(detection));
+        }
+    }
+    
+    // The volume series' detector on the configured baseline, as each pair's composite runs it
+    pub(crate) fn volume_detector(&self, symbol: &str, exchange: &str) -> Box<dyn AnomalyDetector> {
+        let volume: Box<dyn AnomalyDetector> = match self.volume_baseline {
+            BaselineKind::Window if self.volume_config.estimator == WindowEstimator::MedianMad => {
+                Box::new(RobustAnomalyDetector::for_volume(
+                    &self.volume_config,
+                    symbol.to_string(),
+                    exchange.to_string(),
+                ))
+            }
+            BaselineKind::Window => Box::new(VolumeAnomalyDetector::new(
+                self.volume_config.clone(),
+                symbol.to_string(),
+                exchange.to_string(),
+            )),
+            BaselineKind::Ewma => Box::new(EwmaAnomalyDetector::new(
+                self.ewma_config.clone(),
+                AnomalyType::VolumeSpike,
+                "volume",
+                symbol.to_string(),
+                exchange.to_string(),
+            )),
+        };
+        // The mean and standard deviation window has its hour-of-day baselines built in
+        let built_in = self.volume_baseline == BaselineKind::Window
+            && self.volume_config.estimator == WindowEstimator::MeanStd;
+        let wrap_seasonal = self.volume_config.seasonality.enabled && !built_in;
+        if wrap_seasonal {
+            Box::new(SeasonalVolumeDetector::new(
+                volume,
+                self.volume_config.clone(),
+                symbol.to_string(),
+                exchange.to_string(),
+            ))
+        } else {
+            volume
+        }
+    }
+    
+    // The price series' detector on the configured baseline, as each pair's composite runs it
+    pub(crate) fn price_detector(&self, symbol: &str, exchange: &str) -> Box<dyn AnomalyDetector> {
+        match self.price_baseline {
+            BaselineKind::Window if self.price_config.estimator == WindowEstimator::MedianMad => {
+                Box::new(RobustAnomalyDetector::for_price(
+                    &self.price_config,
+                    symbol.to_string(),
+                    exchange.to_string(),
+                ))
+            }
+            BaselineKind::Window if !self.price_config.timeframes_secs.is_empty() => {
+                Box::new(MultiTimeframePriceDetector::new(
+                    self.price_config.clone(),
+                    symbol.to_string(),
+                    exchange.to_string(),
+                ))
+            }
+            BaselineKind::Window => Box::new(PriceAnomalyDetector::new(
+                self.price_config.clone(),
+                symbol.to_string(),
+                exchange.to_string(),
+            )),
+            BaselineKind::Ewma => Box::new(EwmaAnomalyDetector::new(
+                self.ewma_config.clone(),
+                AnomalyType::PriceSpike,
+                "price",
+                symbol.to_string(),
+                exchange.to_string(),
+            )),
+        }
+    }
+    
+    fn create_composite(&self, symbol: &str, exchange: &str) -> CompositeAnomalyDetector {
+        let mut composite = CompositeAnomalyDetector::new();
+        
+        composite.add_detector(self.volume_detector(symbol, exchange));
+        composite.add_detector(self.price_detector(symbol, exchange));
+        
+        if let Some(config) = &self.volatility_config {
+            composite.add_detector(Box::new(VolatilityRegimeDetector::new(
+                config.clone(),
+                symbol.to_string(),
+                exchange.to_string(),
+            )));
+        }
+        
+        if let Some(config) = &self.bollinger_config {
+            composite.add_detector(Box::new(BollingerBreachDetector::new(
+                config.clone(),
+                symbol.to_string(),
+                exchange.to_string(),
+            )));
+        }
+        
+        if let Some(config) = &self.rate_of_change_config {
+            for &horizon_secs in &config.horizons_secs {
+                composite.add_detector(Box::new(RateOfChangeDetector::new(
+                    config.clone(),
+                    horizon_secs,
+                    symbol.to_string(),
+                    exchange.to_string(),
+                )));
+            }
+        }
+        
+        composite.add_trade_detector(Box::new(LargeTradeDetector::new(
+            self.large_trade_config.clone(),
+            symbol.to_string(),
+            exchange.to_string(),
+        )));
+        
+        composite.add_book_detector(Box::new(DepthImbalanceDetector::new(
+            self.depth_config.clone(),
+            symbol.to_string(),
+            exchange.to_string(),
+        )));
+        
+        if let Some(config) = &self.update_rate_config {
+            composite.add_book_detector(Box::new(UpdateRateDetector::new(
+                config.clone(),
+                symbol.to_string(),
+                exchange.to_string(),
+            )));
+        }
+        
+        composite.add_quote_detector(Box::new(SpreadAnomalyDetector::new(
+            self.spread_config.clone(),
+            symbol.to_string(),
+            exchange.to_string(),
+        )));
+        
+        if let Some(config) = &self.candle_config {
+            composite.set_candle_aggregator(CandleAggregator::with_default_detectors(
+                config,
+                symbol.to_string(),
+                exchange.to_string(),
+            ));
+        }
+        
+        if !self.long_horizon.is_empty() {
+            let key = InstrumentKey::new(exchange, symbol);
+            if let Some((_, (states, saved_at))) = self.long_horizon.remove(&key) {
+                composite.restore(&states, saved_at);
+            }
+        }
+        
+        composite
+    }
+    
+    // A handle on the pair's stored detectors, created on first use. Not a copy: samples fed
+    // through it are in the windows the next `process_data` judges against
+    pub fn get_or_create_detector(
+        &self,
+        symbol: &str,
+        exchange: &str,
+    ) -> Arc<Mutex<CompositeAnomalyDetector>> {
+        self.composite(&InstrumentKey::new(exchange, symbol))
+    }
+    
+    pub fn process_data(
+        &self,
+        symbol: &str,
+        exchange: &str,
+        data: &TimeSeriesData,
+    ) -> Vec<AnomalyDetection> {
+        self.process_data_keyed(&InstrumentKey::new(exchange, symbol), data)
+    }
+    
+    // The `_keyed` variants take a key built once per subscription, the `&str` ones look it up
+    pub fn process_data_keyed(
+        &self,
+        key: &InstrumentKey,
+        data: &TimeSeriesData,
+    ) -> Vec<AnomalyDetection> {
+        let mut detections = self.composite(key).lock().detect_all(data);
+        
+        if let Some(warmup) = &self.warmup {
+            if warmup.observe(key.as_str(), data.timestamp) {
+                for detection in &mut detections {
+                    detection.warmup = true;
+                }
+            }
+        }
+        
+        self.suppress_repeats(&mut detections);
+        detections
+    }
+    
+    // Runs the price series and trade-size detectors, then compares the price across exchanges
+    pub fn process_trade(
+        &self,
+        symbol: &str,
+        exchange: &str,
+        trade: &TradeSample,
+    ) -> Vec<AnomalyDetection> {
+        self.process_trade_keyed(&InstrumentKey::new(exchange, symbol), trade)
+    }
+    
+    pub fn process_trade_keyed(
+        &self,
+        key: &InstrumentKey,
+        trade: &TradeSample,
+    ) -> Vec<AnomalyDetection> {
+        let mut detections = {
+            let composite = self.composite(key);
+            let mut composite = composite.lock();
+            let mut detections = composite.detect_series(trade);
+            detections.extend(composite.detect_trade(trade));
+            detections
+        };
+        
+        // Only the symbol's first trade allocates its entry
+        let mut divergence = match self.divergence.get_mut(key.symbol()) {
+            Some(divergence) => divergence,
+            None => self.divergence.entry(key.symbol().to_string()).or_insert_with(|| {
+                CrossExchangeDivergenceDetector::new(
+                    self.divergence_config.clone(),
+                    key.symbol().to_string(),
+                )
+            }),
+        };
+        detections.extend(divergence.update(key.exchange(), trade.price, trade.timestamp));
+        drop(divergence);
+        
+        if let Some(warmup) = &self.warmup {
+            if warmup.observe(key.as_str(), trade.timestamp) {
+                for detection in &mut detections {
+                    detection.warmup = true;
+                }
+            }
+        }
+        
+        self.suppress_repeats(&mut detections);
+        detections
+    }
+    
+    // Whether pairs build candles, which then need `flush_candles` called periodically
+    pub fn has_candles(&self) -> bool {
+        self.candle_config.is_some()
+    }
+    
+    // Closes every pair's candles that ended before `now`, including those of pairs with no
+    // trade since to close them
+    pub fn flush_candles(&self, now: DateTime<Utc>) -> Vec<AnomalyDetection> {
+        let mut detections = Vec::new();
+        for (key, composite) in self.composites() {
+            let mut closed = composite.lock().flush_candles(now);
+            if closed.is_empty() {
+                continue;
+            }
+            if let Some(warmup) = &self.warmup {
+                if warmup.is_warming_up(key.as_str(), now) {
+                    for detection in &mut closed {
+                        detection.warmup = true;
+                    }
+                }
+            }
+            detections.append(&mut closed);
+        }
+        
+        self.suppress_repeats(&mut detections);
+        detections
+    }
+    
+    pub fn process_order_book(&self, book: &OrderBook) -> Vec<AnomalyDetection> {
+        let key = InstrumentKey::new(&book.exchange, &book.symbol);
+        
+        let mut detections = {
+            let composite = self.composite(&key);
+            let mut composite = composite.lock();
+            let mut detections = composite.detect_book(book);
+            if let Some(quote) = QuoteSample::from_book(book) {
+                detections.extend(composite.detect_quote(&quote));
+            }
+            detections
+        };
+        
+        // Book updates don't count as warm-up samples, trades drive the sample count
+        if let Some(warmup) = &self.warmup {
+            if warmup.is_warming_up(key.as_str(), book.timestamp) {
+                for detection in &mut detections {
+                    detection.warmup = true;
+                }
+            }
+        }
+        
+        self.suppress_repeats(&mut detections);
+        detections
+    }
+    
+    // Top-of-book updates from feeds that don't send depth
+    pub fn process_quote(
+        &self,
+        symbol: &str,
+        exchange: &str,
+        quote: &QuoteSample,
+    ) -> Vec<AnomalyDetection> {
+        self.process_quote_keyed(&InstrumentKey::new(exchange, symbol), quote)
+    }
+    
+    pub fn process_quote_keyed(
+        &self,
+        key: &InstrumentKey,
+        quote: &QuoteSample,
+    ) -> Vec<AnomalyDetection> {
+        let mut detections = self.composite(key).lock().detect_quote(quote);
+        
+        if let Some(warmup) = &self.warmup {
+            if warmup.is_warming_up(key.as_str(), quote.timestamp) {
+                for detection in &mut detections {
+                    detection.warmup = true;
+                }
+            }
+        }
+        
+        self.suppress_repeats(&mut detections);
+        detections
+    }
+    
+    // The pair's detectors, created on its first sample. The shard lock is released before
+    // the handle is returned, callers lock the pair themselves
+    fn composite(&self, key: &InstrumentKey) -> Arc<Mutex<CompositeAnomalyDetector>> {
+        if let Some(composite) = self.detectors.get(key) {
+            return composite.clone();
+        }
+        self.detectors
+            .entry(key.clone())
+            .or_insert_with(|| {
+                Arc::new(Mutex::new(self.create_composite(key.symbol(), key.exchange())))
+            })
+            .clone()
+    }
+    
+    // Whether the pair has seen data, or had its windows restored or warmed up
+    pub fn has_detectors(&self, key: &InstrumentKey) -> bool {
+        self.detectors.contains_key(key)
+    }
+    
+    // Handles of every pair, so they can be locked one at a time without holding the map
+    fn composites(&self) -> Vec<(InstrumentKey, Arc<Mutex<CompositeAnomalyDetector>>)> {
+        self.detectors
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect()
+    }
+    
+    // Runtime statistics of one pair's detectors, empty until the pair has seen data
+    pub fn get_stats(&self, symbol: &str, exchange: &str) -> Vec<DetectorStatsEntry> {
+        let key = InstrumentKey::new(exchange, symbol);
+        
+        let composite = self.detectors.get(&key).map(|composite| composite.clone());
+        composite
+            .map(|composite| Self::stats_entries(symbol, exchange, &composite.lock()))
+            .unwrap_or_default()
+    }
+    
+    pub fn get_all_stats(&self) -> Vec<DetectorStatsEntry> {
+        let mut entries: Vec<DetectorStatsEntry> = self
+            .composites()
+            .into_iter()
+            .flat_map(|(key, composite)| {
+                Self::stats_entries(key.symbol(), key.exchange(), &composite.lock())
+            })
+            .collect();
+        entries.sort_by(|a, b| (&a.exchange, &a.symbol).cmp(&(&b.exchange, &b.symbol)));
+        
+        entries
+    }
+    
+    fn stats_entries(
+        symbol: &str,
+        exchange: &str,
+        composite: &CompositeAnomalyDetector,
+    ) -> Vec<DetectorStatsEntry> {
+        composite
+            .stats()
+            .into_iter()
+            .map(|(detector, stats)| DetectorStatsEntry {
+                exchange: exchange.to_string(),
+                symbol: symbol.to_string(),
+                detector,
+                stats,
+            })
+            .collect()
+    }
+    
+    // Detector windows of every exchange and symbol pair, to carry across a restart
+    pub fn snapshot(&self, taken_at: DateTime<Utc>) -> DetectorSnapshot {
+        let mut pairs: Vec<PairSnapshot> = self
+            .composites()
+            .into_iter()
+            .map(|(key, composite)| PairSnapshot {
+                exchange: key.exchange().to_string(),
+                symbol: key.symbol().to_string(),
+                detectors: composite.lock().snapshot(),
+            })
+            .collect();
+        pairs.sort_by(|a, b| (&a.exchange, &a.symbol).cmp(&(&b.exchange, &b.symbol)));
+        
+        DetectorSnapshot { taken_at, pairs }
+    }
+    
+    // Replaces the windows of every pair in the snapshot, returning how many pairs it held.
+    // The restored samples count towards each pair's warm-up as a warm-up from ticks does
+    pub fn restore(&self, snapshot: &DetectorSnapshot) -> usize {
+        for pair in &snapshot.pairs {
+            let key = InstrumentKey::new(&pair.exchange, &pair.symbol);
+            self.composite(&key).lock().restore(&pair.detectors, snapshot.taken_at);
+            if let Some(warmup) = &self.warmup {
+                let samples = pair.detectors.values().map(DetectorState::samples).max();
+                warmup.restore(key.as_str(), samples.unwrap_or(0));
+            }
+        }
+        
+        snapshot.pairs.len()
+    }
+    
+    // Keeps only what a stale snapshot still says about the days before the gap, returning the
+    // number of pairs that had any
+    pub fn restore_long_horizon(&self, snapshot: &DetectorSnapshot) -> usize {
+        let kept = snapshot.long_horizon();
+        for pair in &kept.pairs {
+            self.long_horizon.insert(
+                InstrumentKey::new(&pair.exchange, &pair.symbol),
+                (pair.detectors.clone(), snapshot.taken_at),
+            );
+        }
+        kept.pairs.len()
+    }
+    
+    // Feeds stored trades, oldest first, through a pair's fresh detectors so live samples are
+    // judged from the first one, each detector on the series it watches. Whatever the history
+    // itself would have flagged is dropped. Pairs that already have detectors, e.g. restored
+    // from a snapshot, are left alone. Returns the number of trades fed.
+    pub fn warm_up(&self, symbol: &str, exchange: &str, history: &[TradeSample]) -> usize {
+        let key = InstrumentKey::new(exchange, symbol);
+        if history.is_empty() {
+            return 0;
+        }
+        
+        // Locked before it is published, so a live sample arriving meanwhile waits for the
+        // history instead of running ahead of it
+        let composite = Arc::new(Mutex::new(self.create_composite(symbol, exchange)));
+        let mut warming = composite.lock();
+        match self.detectors.entry(key.clone()) {
+            Entry::Occupied(_) => return 0,
+            Entry::Vacant(vacant) => {
+                vacant.insert(composite.clone());
+            }
+        }
+        for trade in history {
+            warming.detect_series(trade);
+        }
+        drop(warming);
+        
+        if let Some(warmup) = &self.warmup {
+            warmup.restore(key.as_str(), history.len());
+        }
+        
+        debug!("Warmed up {} detectors with {} samples", key, history.len());
+        history.len()
+    }
+    
+    pub fn reset(&self, symbol: &str, exchange: &str) {
+        let key = InstrumentKey::new(exchange, symbol);
+        
+        let composite = self.detectors.get(&key).map(|composite| composite.clone());
+        if let Some(composite) = composite {
+            composite.lock().reset_all();
+        }
+        if let Some(mut detector) = self.divergence.get_mut(symbol) {
+            detector.remove_exchange(exchange);
+        }
+    }
+    
+    pub fn reset_all(&self) {
+        for (_, composite) in self.composites() {
+            composite.lock().reset_all();
+        }
+        for mut detector in self.divergence.iter_mut() {
+            detector.reset();
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Duration, TimeZone};
+    use monitor_core::SeverityThresholds;
+
+    fn level(price: f64, quantity: f64) -> OrderBookLevel {
+        OrderBookLevel { price, quantity }
+    }
+
+    // Ten levels a side around 100, with bids scaled by `skew`
+    fn book(skew: f64, at: DateTime<Utc>) -> OrderBook {
+        OrderBook {
+            exchange: "binance".to_string(),
+            symbol: "BTC/USDT".to_string(),
+            timestamp: at,
+            bids: (0..10).map(|i| level(99.9 - i as f64 * 0.1, 10.0 * skew)).collect(),
+            asks: (0..10).rev().map(|i| level(100.1 + i as f64 * 0.1, 10.0)).collect(),
+        }
+    }
+
+    fn trade(notional: f64, at: DateTime<Utc>) -> TradeSample {
+        TradeSample {
+            timestamp: at,
+            price: 1000.0,
+            volume: notional / 1000.0,
+        }
+    }
+
+    fn large_trades(absolute_threshold: f64) -> LargeTradeDetector {
+        LargeTradeDetector::new(
+            LargeTradeConfig {
+                absolute_threshold,
+                percentile: 99.0,
+                window_size: 100,
+                min_samples: 50,
+                ..Default::default()
+            },
+            "BTC/USDT".to_string(),
+            "binance".to_string(),
+        )
+    }
+
+    fn details(anomaly: &AnomalyDetection) -> &LargeTradeDetails {
+        match &anomaly.details {
+            Some(DetectionDetails::LargeTrade(details)) => details,
+            other => panic!("unexpected details {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_large_trade_silent_during_warmup() {
+        let mut detector = large_trades(1_000_000.0);
+        let start = Utc::now();
+        for i in 0..49 {
+            let notional = if i == 30 { 50_000_000.0 } else { 10_000.0 };
+            assert!(detector.detect_trade(&trade(notional, start + Duration::seconds(i))).is_none());
+        }
+        assert!(detector.detect_trade(&trade(50_000_000.0, start + Duration::seconds(49))).is_none());
+
+        // With fifty samples in the window trades are judged
+        let anomaly = detector
+            .detect_trade(&trade(50_000_000.0, start + Duration::seconds(50)))
+            .unwrap();
+        assert_eq!(anomaly.anomaly_type, AnomalyType::LargeOrder);
+
+        detector.reset();
+        assert!(detector.detect_trade(&trade(50_000_000.0, start)).is_none());
+    }
+
+    #[test]
+    fn test_large_trade_absolute_threshold() {
+        // Whales are routine on this symbol, the percentile stays above the absolute threshold
+        let mut detector = large_trades(1_000_000.0);
+        let start = Utc::now();
+        for i in 0..100 {
+            let notional = if i % 10 == 0 { 3_000_000.0 } else { 10_000.0 };
+            detector.detect_trade(&trade(notional, start + Duration::seconds(i)));
+        }
+
+        assert!(detector.detect_trade(&trade(900_000.0, start)).is_none());
+        let anomaly = detector.detect_trade(&trade(1_500_000.0, start)).unwrap();
+        let details = details(&anomaly);
+        assert_eq!(details.trigger, LargeTradeTrigger::Absolute);
+        assert_eq!(anomaly.severity, AnomalySeverity::Medium);
+        assert!((anomaly.metrics.current_value - 1_500_000.0).abs() < 1e-6);
+        assert!((details.multiple_of_median - 150.0).abs() < 1e-9);
+        assert_eq!(anomaly.metrics.expected_value, 3_000_000.0);
+    }
+
+    #[test]
+    fn test_large_trade_percentile_threshold() {
+        let mut detector = large_trades(0.0);
+        let start = Utc::now();
+        for i in 1..=100 {
+            detector.detect_trade(&trade(i as f64 * 1000.0, start + Duration::seconds(i)));
+        }
+
+        assert!(detector.detect_trade(&trade(99_000.0, start)).is_none());
+        // That pushed the 1k trade out, p99 is now 99k again
+        let anomaly = detector.detect_trade(&trade(150_000.0, start)).unwrap();
+        let details = details(&anomaly);
+        assert_eq!(details.trigger, LargeTradeTrigger::Percentile);
+        assert_eq!(
+            (details.median, details.p95, details.percentile_threshold),
+            (51_000.0, 96_000.0, 99_000.0)
+        );
+        assert_eq!(anomaly.metrics.expected_value, 96_000.0);
+        assert_eq!(anomaly.metrics.historical_avg, Some(51_000.0));
+        assert_eq!(anomaly.severity, AnomalySeverity::Low);
+
+        let anomaly = detector.detect_trade(&trade(1_500_000.0, start)).unwrap();
+        assert_eq!(anomaly.severity, AnomalySeverity::Critical);
+    }
+
+    fn divergence() -> CrossExchangeDivergenceDetector {
+        CrossExchangeDivergenceDetector::new(
+            CrossExchangeDivergenceConfig {
+                threshold_bps: 50.0,
+                min_duration_secs: 30,
+                max_price_age_secs: 10,
+                ..Default::default()
+            },
+            "BTC/USDT".to_string(),
+        )
+    }
+
+    // One price per exchange per second, round robin over binance, okx and bybit
+    fn interleave(
+        detector: &mut CrossExchangeDivergenceDetector,
+        start: DateTime<Utc>,
+        seconds: std::ops::Range<i64>,
+        price: impl Fn(&str, i64) -> f64,
+    ) -> Vec<AnomalyDetection> {
+        let mut anomalies = Vec::new();
+        for second in seconds {
+            for exchange in ["binance", "okx", "bybit"] {
+                let at = start + Duration::seconds(second);
+                anomalies.extend(detector.update(exchange, price(exchange, second), at));
+            }
+        }
+        anomalies
+    }
+
+    #[test]
+    fn test_divergence_ignores_spreads_that_converge_quickly() {
+        let mut detector = divergence();
+        let start = Utc::now();
+
+        // OKX runs 1% rich for 20s at a time, then snaps back, over and over
+        let anomalies = interleave(&mut detector, start, 0..300, |exchange, second| {
+            match (exchange, second % 40 < 20) {
+                ("okx", true) => 50_500.0,
+                _ => 50_000.0 + (second % 7) as f64,
+            }
+        });
+        assert!(anomalies.is_empty());
+    }
+
+    #[test]
+    fn test_divergence_reports_sustained_spread_once() {
+        let mut detector = divergence();
+        let start = Utc::now();
+
+        let anomalies = interleave(&mut detector, start, 0..120, |exchange, second| {
+            match (exchange, second >= 10) {
+                ("bybit", true) => 49_700.0,
+                ("okx", _) => 50_010.0,
+                _ => 50_000.0,
+            }
+        });
+        assert_eq!(anomalies.len(), 1);
+
+        let anomaly = &anomalies[0];
+        assert_eq!(anomaly.anomaly_type, AnomalyType::PriceDivergence);
+        assert_eq!(anomaly.exchange, "bybit");
+        assert_eq!(anomaly.timestamp, start + Duration::seconds(40));
+        assert!(anomaly.description.contains("okx at 50010"));
+        assert!(anomaly.description.contains("bybit at 49700"));
+        let spread_bps = 310.0 / 49_855.0 * 10_000.0;
+        assert!((anomaly.metrics.current_value - spread_bps).abs() < 1e-9);
+        assert_eq!(anomaly.severity, AnomalySeverity::Low);
+        match &anomaly.details {
+            Some(DetectionDetails::Divergence(details)) => {
+                assert_eq!(
+                    (details.high_exchange.as_str(), details.low_exchange.as_str()),
+                    ("okx", "bybit")
+                );
+                assert_eq!(details.prices.len(), 3);
+                assert_eq!(details.duration_secs, 30.0);
+            }
+            other => panic!("unexpected details {:?}", other),
+        }
+
+        // Once bybit comes back in line the next divergence is reported again
+        let anomalies = interleave(&mut detector, start, 120..125, |_, _| 50_000.0);
+        assert!(anomalies.is_empty());
+        let anomalies = interleave(&mut detector, start, 125..160, |exchange, _| {
+            if exchange == "binance" {
+                51_000.0
+            } else {
+                50_000.0
+            }
+        });
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].exchange, "binance");
+    }
+
+    #[test]
+    fn test_divergence_skips_stale_exchanges() {
+        let mut detector = divergence();
+        let start = Utc::now();
+
+        // Bybit's last price is far off but it stopped updating long ago
+        assert!(detector.update("bybit", 45_000.0, start).is_none());
+        for second in 20..120 {
+            let at = start + Duration::seconds(second);
+            assert!(detector.update("binance", 50_000.0, at).is_none());
+            assert!(detector.update("okx", 50_005.0, at).is_none());
+        }
+
+        // Fresh prices bring it back into the comparison
+        let anomalies = interleave(&mut detector, start, 120..160, |exchange, _| {
+            if exchange == "bybit" {
+                45_000.0
+            } else {
+                50_000.0
+            }
+        });
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].exchange, "bybit");
+        assert_eq!(anomalies[0].severity, AnomalySeverity::Critical);
+    }
+
+    fn detector() -> DepthImbalanceDetector {
+        DepthImbalanceDetector::new(
+            DepthImbalanceConfig::default(),
+            "BTC/USDT".to_string(),
+            "binance".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_depth_imbalance_escalates_with_skew() {
+        let mut detector = detector();
+        let start = Utc::now();
+
+        assert!(detector.detect_book(&book(1.5, start)).is_none());
+
+        let mut severities = Vec::new();
+        for (i, skew) in [3.5, 3.5, 5.0, 7.0, 10.0].into_iter().enumerate() {
+            let at = start + Duration::seconds(i as i64);
+            if let Some(anomaly) = detector.detect_book(&book(skew, at)) {
+                assert_eq!(anomaly.anomaly_type, AnomalyType::DepthImbalance);
+                assert!((anomaly.metrics.current_value - skew).abs() < 1e-9);
+                severities.push(anomaly.severity);
+            }
+        }
+        // The repeated 3.5x book stays quiet until the skew grows
+        assert_eq!(
+            severities,
+            [
+                AnomalySeverity::Low,
+                AnomalySeverity::Medium,
+                AnomalySeverity::High,
+                AnomalySeverity::Critical,
+            ]
+        );
+
+        // Back to balance re-arms the detector, ask-heavy books count too
+        assert!(detector.detect_book(&book(1.0, start)).is_none());
+        let anomaly = detector.detect_book(&book(1.0 / 4.0, start)).unwrap();
+        assert_eq!(anomaly.severity, AnomalySeverity::Low);
+        assert!(anomaly.description.contains("ask side"));
+    }
+
+    #[test]
+    fn test_depth_imbalance_ignores_thin_and_one_sided_books() {
+        let mut detector = DepthImbalanceDetector::new(
+            DepthImbalanceConfig {
+                levels: 3,
+                ..Default::default()
+            },
+            "BTC/USDT".to_string(),
+            "binance".to_string(),
+        );
+        let now = Utc::now();
+
+        let mut thin = book(10.0, now);
+        for l in thin.bids.iter_mut().chain(thin.asks.iter_mut()) {
+            l.quantity /= 100.0;
+        }
+        assert!(detector.detect_book(&thin).is_none());
+
+        let mut one_sided = book(10.0, now);
+        one_sided.asks.clear();
+        assert!(detector.detect_book(&one_sided).is_none());
+
+        // Only the best three levels count, however deep the book is behind them
+        let mut deep = book(1.0, now);
+        for l in deep.bids.iter_mut().chain(deep.asks.iter_mut()) {
+            l.quantity *= 10.0;
+        }
+        deep.bids.push(level(90.0, 10_000.0));
+        assert!(detector.detect_book(&deep).is_none());
+    }
+
+    // Mid price stays at 100 so the relative spread is exactly `spread_bps`
+    fn quote(spread_bps: f64, at: DateTime<Utc>) -> QuoteSample {
+        let half = spread_bps / 200.0;
+        QuoteSample {
+            timestamp: at,
+            bid_price: 100.0 - half,
+            bid_size: 1.0,
+            ask_price: 100.0 + half,
+            ask_size: 1.0,
+        }
+    }
+
+    fn spreads() -> SpreadAnomalyDetector {
+        SpreadAnomalyDetector::new(
+            SpreadAnomalyConfig::default(),
+            "BTC/USDT".to_string(),
+            "binance".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_spread_widening_scales_with_historical_mean() {
+        let mut detector = spreads();
+        let start = Utc::now();
+
+        for i in 0..100 {
+            let spread = if i % 2 == 0 { 1.0 } else { 1.2 };
+            let at = start + Duration::seconds(i);
+            assert!(detector.detect_quote(&quote(spread, at)).is_none());
+        }
+
+        let anomaly = detector.detect_quote(&quote(10.0, start)).unwrap();
+        assert_eq!(anomaly.anomaly_type, AnomalyType::SpreadWidening);
+        assert_eq!(anomaly.severity, AnomalySeverity::High);
+        assert!((anomaly.metrics.current_value - 10.0).abs() < 1e-6);
+        assert!((anomaly.metrics.expected_value - 1.1).abs() < 1e-6);
+        assert!(anomaly.metrics.z_score.unwrap() > 4.0);
+
+        // A spread that stays wide is quiet until it gets worse
+        assert!(detector.detect_quote(&quote(10.0, start)).is_none());
+        let anomaly = detector.detect_quote(&quote(15.0, start)).unwrap();
+        assert_eq!(anomaly.severity, AnomalySeverity::Critical);
+
+        // Back to normal re-arms the detector
+        assert!(detector.detect_quote(&quote(1.1, start)).is_none());
+        assert!(detector.detect_quote(&quote(10.0, start)).is_some());
+    }
+
+    #[test]
+    fn test_spread_cap_applies_without_history() {
+        let mut detector = spreads();
+        let now = Utc::now();
+
+        // Too few samples for a z-score, and under the cap
+        assert!(detector.detect_quote(&quote(1.0, now)).is_none());
+        assert!(detector.detect_quote(&quote(50.0, now)).is_none());
+
+        let anomaly = detector.detect_quote(&quote(150.0, now)).unwrap();
+        assert_eq!(anomaly.severity, AnomalySeverity::Low);
+        assert!(anomaly.metrics.z_score.is_none());
+        assert!(anomaly.description.contains("cap"));
+
+        // Crossed and empty quotes are ignored
+        let mut crossed = quote(1.0, now);
+        std::mem::swap(&mut crossed.bid_price, &mut crossed.ask_price);
+        assert!(detector.detect_quote(&crossed).is_none());
+        assert!(detector.detect_quote(&quote(0.0, now)).is_none());
+    }
+
+    #[test]
+    fn test_quote_sample_from_book_top() {
+        let now = Utc::now();
+        let quote = QuoteSample::from_book(&book(2.0, now)).unwrap();
+        assert!((quote.bid_price - 99.9).abs() < 1e-9);
+        assert!((quote.ask_price - 100.1).abs() < 1e-9);
+        assert!((quote.bid_size - 20.0).abs() < 1e-9);
+
+        let mut one_sided = book(2.0, now);
+        one_sided.bids.clear();
+        assert!(QuoteSample::from_book(&one_sided).is_none());
+    }
+
+    #[test]
+    fn test_spike_is_judged_against_the_window_before_it() {
+        let start = Utc::now();
+        let sample = |i: i64, value: f64| TimeSeriesData {
+            timestamp: start + Duration::seconds(i),
+            value,
+        };
+        let mut volume = VolumeAnomalyDetector::new(
+            VolumeAnomalyConfig {
+                window_size: 30,
+                ..Default::default()
+            },
+            "BTC/USDT".to_string(),
+            "binance".to_string(),
+        );
+        for i in 0..30 {
+            let odd = (i % 2) as f64;
+            assert!(volume.detect(&sample(i, 10.0 + odd * 10.0)).is_none());
+        }
+
+        // Ten times the mean of 15. Inside the window it would inflate its own deviation
+        let spike = sample(30, 150.0);
+        let mut polluted = volume.window.clone();
+        polluted.push(spike.clone());
+        let anomaly = volume.detect(&spike).unwrap();
+        let z = anomaly.metrics.z_score.unwrap();
+        assert!((z - 27.0).abs() < 1e-9);
+        assert!(polluted.z_score(150.0) < 5.5);
+        assert!((anomaly.metrics.expected_value - 15.0).abs() < 1e-9);
+        assert_eq!(anomaly.severity, AnomalySeverity::Critical);
+
+        // The spike still joins the window, and raises the baseline of what follows
+        assert_eq!(volume.window.len(), 30);
+        assert_eq!(volume.window.data.back().map(|p| p.value), Some(150.0));
+        assert_eq!(volume.window.mean(), polluted.mean());
+        assert_eq!(volume.window.std_dev(), polluted.std_dev());
+        assert!(volume.detect(&sample(31, 20.0)).is_none());
+        assert_eq!(volume.window.len(), 30);
+
+        // Prices the same, the 6% jump against a window of 100.1 +- 0.1
+        let mut price = PriceAnomalyDetector::new(
+            PriceAnomalyConfig::default(),
+            "BTC/USDT".to_string(),
+            "binance".to_string(),
+        );
+        for i in 0..30 {
+            assert!(price.detect(&sample(i, 100.0 + (i % 2) as f64 * 0.2)).is_none());
+        }
+        let anomaly = price.detect(&sample(30, 106.1)).unwrap();
+        assert!((anomaly.metrics.z_score.unwrap() - 60.0).abs() < 1e-6);
+        assert!((anomaly.metrics.historical_avg.unwrap() - 100.1).abs() < 1e-9);
+        assert_eq!(price.window.len(), 31);
+    }
+
+    fn volume_detector(window_size: usize) -> VolumeAnomalyDetector {
+        VolumeAnomalyDetector::new(
+            VolumeAnomalyConfig {
+                window_size,
+                ..Default::default()
+            },
+            "BTC/USDT".to_string(),
+            "binance".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_window_restore_keeps_mean_and_std_dev() {
+        let start = Utc::now();
+        let mut original = volume_detector(60);
+        for i in 0..45 {
+            let value = 97_000.0 + (i % 7) as f64 * 3.5;
+            original.detect(&TimeSeriesData { timestamp: start, value });
+        }
+
+        let state: DetectorState =
+            serde_json::from_str(&serde_json::to_string(&original.snapshot()).unwrap()).unwrap();
+        let mut restored = volume_detector(60);
+        restored.restore(&state, start);
+
+        assert_eq!(restored.window.len(), 45);
+        assert_eq!(restored.window.mean(), original.window.mean());
+        assert_eq!(restored.window.std_dev(), original.window.std_dev());
+
+        // Both judge the next value the same way
+        let spike = TimeSeriesData { timestamp: start, value: 300_000.0 };
+        let (a, b) = (original.detect(&spike).unwrap(), restored.detect(&spike).unwrap());
+        assert_eq!(a.metrics.z_score, b.metrics.z_score);
+        assert_eq!(a.severity, b.severity);
+    }
+
+    #[test]
+    fn test_volume_and_price_confidence_follow_exceedance() {
+        let start = Utc::now();
+        let mut volume = volume_detector(60);
+        let mut price = PriceAnomalyDetector::new(
+            PriceAnomalyConfig::default(),
+            "BTC/USDT".to_string(),
+            "binance".to_string(),
+        );
+        let sample = |i: i64, value: f64| TimeSeriesData {
+            timestamp: start + Duration::seconds(i),
+            value,
+        };
+        for i in 0..40 {
+            let odd = (i % 2) as f64;
+            assert!(volume.detect(&sample(i, 10.0 + odd * 10.0)).is_none());
+            assert!(price.detect(&sample(i, 100.0 + odd * 0.2)).is_none());
+        }
+
+        // Z-score 20 against a threshold of 3, far enough past it to clear the Critical floor
+        let anomaly = volume.detect(&sample(40, 115.0)).unwrap();
+        let z = anomaly.metrics.z_score.unwrap();
+        assert_eq!(anomaly.confidence, confidence_from_exceedance(z, 3.0));
+        assert_eq!(anomaly.severity, AnomalySeverity::Critical);
+
+        // The z-score of 59 goes further past its threshold than the 5.8% move past 5%
+        let anomaly = price.detect(&sample(40, 106.0)).unwrap();
+        let z = anomaly.metrics.z_score.unwrap();
+        let pct = anomaly.metrics.percentage_change.unwrap();
+        assert!(confidence_from_exceedance(pct, 5.0) < confidence_from_exceedance(z, 3.0));
+        assert_eq!(anomaly.confidence, confidence_from_exceedance(z, 3.0));
+        assert_eq!(anomaly.severity, AnomalySeverity::Critical);
+    }
+
+    #[test]
+    fn test_seasonal_baseline_quiets_daily_open() {
+        use chrono::Datelike;
+        use monitor_core::SeasonalityConfig;
+
+        let config = VolumeAnomalyConfig {
+            seasonality: SeasonalityConfig {
+                enabled: true,
+                min_days: 3,
+                max_days: 28,
+            },
+            ..Default::default()
+        };
+        let detector = || {
+            VolumeAnomalyDetector::new(
+                config.clone(),
+                "BTC/USDT".to_string(),
+                "binance".to_string(),
+            )
+        };
+        // A sample a minute from noon on the 1st, ten times busier every day from 00:00 UTC
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let sample = |i: i64| {
+            let timestamp = start + Duration::minutes(i);
+            let odd = (i % 2) as f64;
+            let base = if timestamp.hour() == 0 { 100.0 } else { 10.0 };
+            TimeSeriesData {
+                timestamp,
+                value: base + odd * 10.0,
+            }
+        };
+        let fifth = (Utc.with_ymd_and_hms(2024, 3, 5, 0, 0, 0).unwrap() - start).num_minutes();
+
+        // The window alone flags each open while fewer than three previous days have the hour
+        let mut seasonal = detector();
+        let mut alerts = Vec::new();
+        for i in 0..fifth {
+            alerts.extend(seasonal.detect(&sample(i)).map(|a| a.timestamp));
+        }
+        let opens: Vec<u32> = alerts.iter().map(|at| at.day()).collect();
+        assert!(!alerts.is_empty());
+        assert!(alerts.iter().all(|at| at.hour() == 0));
+        assert_eq!(opens.first(), Some(&2));
+        assert_eq!(opens.last(), Some(&4));
+
+        // From the 5th the open is judged against earlier opens, kept across a restore
+        let mut restored = detector();
+        restored.restore(&seasonal.snapshot(), sample(fifth - 1).timestamp);
+        for i in fifth..fifth + 2 * 1440 {
+            assert!(restored.detect(&sample(i)).is_none(), "{}", sample(i).timestamp);
+        }
+
+        // A real spike at the open still stands out from the opens before it
+        let mut spike = sample(fifth + 2 * 1440);
+        spike.value = 1_000.0;
+        let anomaly = restored.detect(&spike).unwrap();
+        assert_eq!(anomaly.metrics.expected_value, 105.0);
+        assert!(anomaly.description.contains("above the 00:00 UTC average (105.00)"));
+
+        // Without seasonality the same open alerts every day
+        let mut plain = VolumeAnomalyDetector::new(
+            VolumeAnomalyConfig::default(),
+            "BTC/USDT".to_string(),
+            "binance".to_string(),
+        );
+        assert!((0..fifth + 1440)
+            .filter_map(|i| plain.detect(&sample(i)))
+            .any(|a| a.timestamp.day() == 5));
+    }
+
+    #[test]
+    fn test_ewma_and_robust_volume_use_seasonal_baselines() {
+        use monitor_core::SeasonalityConfig;
+
+        let config = VolumeAnomalyConfig {
+            seasonality: SeasonalityConfig {
+                enabled: true,
+                min_days: 3,
+                max_days: 28,
+            },
+            ..Default::default()
+        };
+        let wrap = |inner: Box<dyn AnomalyDetector>| {
+            SeasonalVolumeDetector::new(
+                inner,
+                config.clone(),
+                "BTC/USDT".to_string(),
+                "binance".to_string(),
+            )
+        };
+        let estimators = [
+            wrap(Box::new(EwmaAnomalyDetector::new(
+                EwmaAnomalyConfig::default(),
+                AnomalyType::VolumeSpike,
+                "volume",
+                "BTC/USDT".to_string(),
+                "binance".to_string(),
+            ))),
+            wrap(Box::new(RobustAnomalyDetector::for_volume(
+                &config,
+                "BTC/USDT".to_string(),
+                "binance".to_string(),
+            ))),
+        ];
+        // Ten times busier every day from 00:00 UTC, as in the window estimator's test
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let sample = |i: i64| {
+            let timestamp = start + Duration::minutes(i);
+            let base = if timestamp.hour() == 0 { 100.0 } else { 10.0 };
+            TimeSeriesData {
+                timestamp,
+                value: base + (i % 2) as f64 * 10.0,
+            }
+        };
+        let fifth = (Utc.with_ymd_and_hms(2024, 3, 5, 0, 0, 0).unwrap() - start).num_minutes();
+
+        for mut detector in estimators {
+            // The estimator's own baseline flags the opens until the hour has three days
+            let early = (0..fifth).filter_map(|i| detector.detect(&sample(i))).count();
+            assert!(early > 0, "{}", detector.kind());
+            for i in fifth..fifth + 2 * 1440 {
+                assert!(detector.detect(&sample(i)).is_none(), "{}", sample(i).timestamp);
+            }
+
+            let mut spike = sample(fifth + 2 * 1440);
+            spike.value = 1_000.0;
+            let anomaly = detector.detect(&spike).unwrap();
+            assert_eq!(anomaly.metrics.expected_value, 105.0);
+            assert!(anomaly.description.contains("above the 00:00 UTC average (105.00)"));
+            assert!(!detector.snapshot().seasonal.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_stale_snapshot_keeps_seasonal_baselines() {
+        use monitor_core::SeasonalityConfig;
+
+        let config = VolumeAnomalyConfig {
+            seasonality: SeasonalityConfig {
+                enabled: true,
+                min_days: 1,
+                max_days: 28,
+            },
+            ..Default::default()
+        };
+        let manager = AnomalyDetectorManager::new(config.clone(), PriceAnomalyConfig::default())
+            .with_baselines(BaselineKind::Ewma, BaselineKind::Window);
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+        for i in 0..3 * 24 {
+            let sample = trade(1_000.0, start + Duration::hours(i));
+            manager.process_trade("BTC/USDT", "binance", &sample);
+        }
+        let snapshot = manager.snapshot(start + Duration::days(3));
+        let seasonal = snapshot.pairs[0].detectors["volume"].seasonal.clone();
+        assert_eq!(seasonal.len(), 72);
+
+        // Only the hour-of-day moments survive, the short windows start over
+        let restored = AnomalyDetectorManager::new(config, PriceAnomalyConfig::default())
+            .with_baselines(BaselineKind::Ewma, BaselineKind::Window);
+        assert_eq!(restored.restore_long_horizon(&snapshot), 1);
+        assert!(!restored.has_detectors(&InstrumentKey::new("binance", "BTC/USDT")));
+        let later = start + Duration::days(10);
+        restored.process_trade("BTC/USDT", "binance", &trade(1_000.0, later));
+        let pair = &restored.snapshot(later).pairs[0];
+        assert_eq!(pair.detectors["volume"].seasonal.len(), 73);
+        assert_eq!(pair.detectors["volume"].ewma.map(|e| e.samples), Some(1));
+        assert_eq!(pair.detectors["price"].window.len(), 1);
+    }
+
+    #[test]
+    fn test_configured_severity_bands() {
+        let start = Utc::now();
+        let mut volume = VolumeAnomalyDetector::new(
+            VolumeAnomalyConfig::default(),
+            "BTC/USDT".to_string(),
+            "binance".to_string(),
+        );
+        let mut lenient = VolumeAnomalyDetector::new(
+            VolumeAnomalyConfig {
+                z_score_severity: SeverityThresholds::new(3.0, 10.0, 20.0).unwrap(),
+                ..Default::default()
+            },
+            "BTC/USDT".to_string(),
+            "binance".to_string(),
+        );
+        let mut price = PriceAnomalyDetector::new(
+            PriceAnomalyConfig {
+                percentage_severity: SeverityThresholds::new(5.0, 5.5, 5.7).unwrap(),
+                z_score_severity: SeverityThresholds::new(100.0, 200.0, 300.0).unwrap(),
+                ..Default::default()
+            },
+            "BTC/USDT".to_string(),
+            "binance".to_string(),
+        );
+        let sample = |i: i64, value: f64| TimeSeriesData {
+            timestamp: start + Duration::seconds(i),
+            value,
+        };
+        for i in 0..40 {
+            let odd = (i % 2) as f64;
+            assert!(volume.detect(&sample(i, 10.0 + odd * 10.0)).is_none());
+            assert!(lenient.detect(&sample(i, 10.0 + odd * 10.0)).is_none());
+            assert!(price.detect(&sample(i, 100.0 + odd * 0.2)).is_none());
+        }
+
+        // A z-score of 9 is Critical on the default bands, confidence raised to the Critical
+        // floor it would otherwise fall short of
+        let anomaly = volume.detect(&sample(40, 60.0)).unwrap();
+        assert_eq!(anomaly.severity, AnomalySeverity::Critical);
+        let z = anomaly.metrics.z_score.unwrap();
+        assert!(confidence_from_exceedance(z, 3.0) < AnomalySeverity::Critical.confidence_floor());
+        assert_eq!(anomaly.confidence, AnomalySeverity::Critical.confidence_floor());
+
+        // Only Medium on wider bands, where the confidence from exceedance already clears the floor
+        let anomaly = lenient.detect(&sample(40, 60.0)).unwrap();
+        assert_eq!(anomaly.severity, AnomalySeverity::Medium);
+        assert_eq!(anomaly.confidence, confidence_from_exceedance(z, 3.0));
+
+        // The 5.8% move is Critical on its own bands, whatever the z-score bands say
+        let anomaly = price.detect(&sample(40, 106.0)).unwrap();
+        assert_eq!(anomaly.severity, AnomalySeverity::Critical);
+        assert!(anomaly.confidence >= AnomalySeverity::Critical.confidence_floor());
+    }
+
+    #[test]
+    fn test_price_description_reports_prior_price() {
+        let start = Utc::now();
+        let mut price = PriceAnomalyDetector::new(
+            PriceAnomalyConfig::default(),
+            "BTC/USDT".to_string(),
+            "binance".to_string(),
+        );
+        for i in 0..40 {
+            let sample = TimeSeriesData {
+                timestamp: start + Duration::seconds(i),
+                value: 100.0 + (i % 2) as f64 * 0.2,
+            };
+            assert!(price.detect(&sample).is_none());
+        }
+
+        // 6% up from the last price of 100.2
+        let anomaly = price
+            .detect(&TimeSeriesData {
+                timestamp: start + Duration::seconds(40),
+                value: 106.212,
+            })
+            .unwrap();
+        assert!(
+            anomaly.description.contains("moved 6.00% from 100.2000 to 106.2120"),
+            "{}",
+            anomaly.description
+        );
+        assert!((anomaly.metrics.expected_value - 100.2).abs() < 1e-9);
+        assert!((anomaly.metrics.deviation - 6.012).abs() < 1e-9);
+        assert!((anomaly.metrics.percentage_change.unwrap() - 6.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_warm_up_lets_first_live_sample_detect() {
+        let warmup = Arc::new(WarmupTracker::new(monitor_core::WarmupConfig {
+            duration_secs: 300,
+            min_samples_per_symbol: 30,
+        }));
+        let manager = AnomalyDetectorManager::new(
+            VolumeAnomalyConfig::default(),
+            PriceAnomalyConfig::default(),
+        )
+        .with_warmup(warmup.clone());
+        let cold = AnomalyDetectorManager::new(
+            VolumeAnomalyConfig::default(),
+            PriceAnomalyConfig::default(),
+        );
+        let start = Utc::now() - Duration::minutes(5);
+        // Exactly min_samples trades, the last one a spike the warm-up must not report
+        let history: Vec<TradeSample> = (0..30)
+            .map(|i| TradeSample {
+                timestamp: start + Duration::seconds(i),
+                price: if i == 29 { 1_100.0 } else { 1_000.0 + (i % 5) as f64 },
+                volume: if i == 29 { 10.0 } else { 1.0 + (i % 5) as f64 * 0.1 },
+            })
+            .collect();
+
+        assert_eq!(manager.warm_up("BTC/USDT", "binance", &history), 30);
+        // Already has detectors, so a second warm-up is ignored
+        assert_eq!(manager.warm_up("BTC/USDT", "binance", &history), 0);
+
+        let live = TradeSample {
+            timestamp: Utc::now(),
+            price: 1_200.0,
+            volume: 20.0,
+        };
+        let detections = manager.process_trade("BTC/USDT", "binance", &live);
+        let spike = detections
+            .iter()
+            .find(|d| d.anomaly_type == AnomalyType::PriceSpike)
+            .expect("first live sample should be judged");
+        assert!(!spike.warmup);
+        // The volume window was seeded with volumes, not prices
+        let volume = detections
+            .iter()
+            .find(|d| d.anomaly_type == AnomalyType::VolumeSpike)
+            .expect("first live volume should be judged");
+        assert!(volume.metrics.expected_value < 2.0);
+
+        let detections = cold.process_trade("BTC/USDT", "binance", &live);
+        assert!(detections.iter().all(|d| {
+            d.anomaly_type != AnomalyType::PriceSpike && d.anomaly_type != AnomalyType::VolumeSpike
+        }));
+    }
+
+    #[test]
+    fn test_manager_rate_of_change_per_horizon() {
+        let manager = AnomalyDetectorManager::new(
+            VolumeAnomalyConfig::default(),
+            PriceAnomalyConfig::default(),
+        )
+        .with_rate_of_change_config(RateOfChangeConfig::default());
+        let start = Utc::now();
+        let rate_of_change = |detections: Vec<AnomalyDetection>| -> Vec<String> {
+            detections
+                .into_iter()
+                .map(|d| d.description)
+                .filter(|d| d.starts_with("Price rate of change"))
+                .collect()
+        };
+
+        // Trades further apart than every horizon, each 10% above the last
+        let mut price = 1_000.0;
+        for i in 0..10 {
+            let sample = TradeSample {
+                timestamp: start + Duration::seconds(i * 1_000),
+                price,
+                volume: 1.0,
+            };
+            let detections = manager.process_trade("ETH/USDT", "binance", &sample);
+            assert!(rate_of_change(detections).is_empty());
+            price *= 1.1;
+        }
+
+        // Steady for 400s then 5% up, which the 15m horizon can't judge yet
+        let mut descriptions = Vec::new();
+        for i in 0..=400 {
+            let sample = TradeSample {
+                timestamp: start + Duration::seconds(i),
+                price: if i == 400 { 1_050.0 } else { 1_000.0 },
+                volume: 1.0,
+            };
+            let detections = manager.process_trade("BTC/USDT", "binance", &sample);
+            descriptions.extend(rate_of_change(detections));
+        }
+        assert_eq!(descriptions.len(), 2);
+        assert!(descriptions[0].starts_with("Price rate of change [1m] for binance/BTC/USDT"));
+        assert!(descriptions[1].starts_with("Price rate of change [5m] for binance/BTC/USDT"));
+    }
+
+    #[test]
+    fn test_manager_candles_from_trades() {
+        use chrono::TimeZone;
+        
+        let manager = AnomalyDetectorManager::new(
+            VolumeAnomalyConfig::default(),
+            PriceAnomalyConfig::default(),
+        )
+        .with_candle_config(CandleConfig::default());
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        
+        // A trade every 5s, ten times the size through 12:12, and one more to close that minute
+        let mut candle_alerts = Vec::new();
+        for i in 0..158 {
+            let sample = TradeSample {
+                timestamp: start + Duration::seconds(i * 5),
+                price: 100.0,
+                volume: if (144..156).contains(&i) { 10.0 } else { 1.0 },
+            };
+            let detections = manager.process_trade("BTC/USDT", "binance", &sample);
+            candle_alerts.extend(
+                detections
+                    .into_iter()
+                    .filter(|d| d.description.starts_with("Candle"))
+                    .map(|d| (i, d)),
+            );
+        }
+        
+        assert_eq!(candle_alerts.len(), 1);
+        let (at, anomaly) = &candle_alerts[0];
+        assert_eq!(*at, 157);
+        assert_eq!(anomaly.timestamp, start + Duration::minutes(12));
+        assert!(anomaly.description.starts_with("Candle volume anomaly [1m] for binance/BTC/USDT"));
+        assert_eq!(anomaly.metrics.current_value, 120.0);
+    }
+
+    #[test]
+    fn test_manager_flush_closes_candles_of_quiet_pairs() {
+        use chrono::TimeZone;
+        
+        let manager = AnomalyDetectorManager::new(
+            VolumeAnomalyConfig::default(),
+            PriceAnomalyConfig::default(),
+        )
+        .with_candle_config(CandleConfig::default());
+        assert!(manager.has_candles());
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        
+        // Ten times the size through 12:12, then the pair goes quiet
+        for i in 0..156 {
+            let sample = TradeSample {
+                timestamp: start + Duration::seconds(i * 5),
+                price: 100.0,
+                volume: if i >= 144 { 10.0 } else { 1.0 },
+            };
+            let detections = manager.process_trade("BTC/USDT", "binance", &sample);
+            assert!(detections.iter().all(|d| !d.description.starts_with("Candle")));
+        }
+        
+        // Still within the late tolerance, then past it
+        assert!(manager.flush_candles(start + Duration::seconds(781)).is_empty());
+        let flushed = manager.flush_candles(start + Duration::seconds(783));
+        assert_eq!(flushed.len(), 1);
+        assert!(flushed[0]
+            .description
+            .starts_with("Candle volume anomaly [1m] for binance/BTC/USDT"));
+        assert_eq!(flushed[0].metrics.current_value, 120.0);
+        assert!(manager.flush_candles(start + Duration::seconds(900)).is_empty());
+        
+        let candle_stats = manager
+            .get_stats("BTC/USDT", "binance")
+            .into_iter()
+            .find(|entry| entry.detector == "candles")
+            .unwrap()
+            .stats;
+        assert_eq!(candle_stats.anomalies_emitted, 1);
+    }
+
+    #[test]
+    fn test_manager_stats_count_samples_and_survive_reset() {
+        let manager = AnomalyDetectorManager::new(
+            VolumeAnomalyConfig::default(),
+            PriceAnomalyConfig::default(),
+        );
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let sample = |i: i64, value: f64| TimeSeriesData {
+            timestamp: start + Duration::seconds(i),
+            value,
+        };
+        let volume_stats = |manager: &AnomalyDetectorManager| {
+            manager
+                .get_stats("BTC/USDT", "binance")
+                .into_iter()
+                .find(|entry| entry.detector == "volume")
+                .unwrap()
+                .stats
+        };
+        assert!(manager.get_stats("BTC/USDT", "binance").is_empty());
+
+        // A prebuilt key reaches the same detectors as the `&str` form
+        let key = InstrumentKey::new("binance", "BTC/USDT");
+        for i in 0..40 {
+            let odd = (i % 2) as f64;
+            if i % 2 == 0 {
+                manager.process_data_keyed(&key, &sample(i, 10.0 + odd * 10.0));
+            } else {
+                manager.process_data("BTC/USDT", "binance", &sample(i, 10.0 + odd * 10.0));
+            }
+        }
+        let detections = manager.process_data("BTC/USDT", "binance", &sample(40, 60.0));
+        assert!(detections.iter().any(|d| d.anomaly_type == AnomalyType::VolumeSpike));
+
+        let volume = volume_stats(&manager);
+        assert_eq!(volume.samples_seen, 41);
+        assert_eq!(volume.anomalies_emitted, 1);
+        assert_eq!(volume.last_anomaly_at, Some(start + Duration::seconds(40)));
+        assert_eq!(volume.window_fill, Some(41.0 / 60.0));
+        let stats = manager.get_stats("BTC/USDT", "binance");
+        let spread = stats.iter().find(|entry| entry.detector == "spread").unwrap();
+        assert_eq!(spread.stats.samples_seen, 0);
+
+        // Resetting empties the windows, the totals are kept
+        manager.reset("BTC/USDT", "binance");
+        let volume = volume_stats(&manager);
+        assert_eq!(volume.samples_seen, 41);
+        assert_eq!(volume.anomalies_emitted, 1);
+        assert_eq!(volume.window_fill, Some(0.0));
+
+        manager.process_data("ETH/USDT", "okx", &sample(41, 10.0));
+        let all = manager.get_all_stats();
+        assert_eq!(all.len(), 2 * stats.len());
+        assert_eq!(all[0].exchange, "binance");
+        assert_eq!(all[all.len() - 1].exchange, "okx");
+        assert_eq!(all[all.len() - 1].stats.samples_seen, 0);
+    }
+
+    #[test]
+    fn test_detector_handle_shares_state_with_process_data() {
+        let manager = AnomalyDetectorManager::new(
+            VolumeAnomalyConfig::default(),
+            PriceAnomalyConfig::default(),
+        );
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let sample = |i: i64, value: f64| TimeSeriesData {
+            timestamp: start + Duration::seconds(i),
+            value,
+        };
+
+        let handle = manager.get_or_create_detector("BTC/USDT", "binance");
+        for i in 0..40 {
+            let odd = (i % 2) as f64;
+            handle.lock().detect_all(&sample(i, 10.0 + odd * 10.0));
+        }
+        assert!(Arc::ptr_eq(&handle, &manager.get_or_create_detector("BTC/USDT", "binance")));
+
+        // Judged against the 40 samples fed through the handle, alone it would be too early
+        let detections = manager.process_data("BTC/USDT", "binance", &sample(40, 60.0));
+        assert!(detections.iter().any(|d| d.anomaly_type == AnomalyType::VolumeSpike));
+        let stats = manager.get_stats("BTC/USDT", "binance");
+        let volume = stats.iter().find(|entry| entry.detector == "volume").unwrap();
+        assert_eq!(volume.stats.samples_seen, 41);
+
+        // Resets through the manager are seen through the handle
+        manager.reset("BTC/USDT", "binance");
+        let fill = handle.lock().stats().into_iter().find(|(kind, _)| kind == "volume");
+        assert_eq!(fill.unwrap().1.window_fill, Some(0.0));
+    }
+
+    #[test]
+    fn test_manager_concurrent_pairs_keep_their_own_state() {
+        const THREADS: usize = 8;
+        const TRADES: usize = 500;
+        let manager = AnomalyDetectorManager::new(
+            VolumeAnomalyConfig::default(),
+            PriceAnomalyConfig::default(),
+        );
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        // The last two threads share a pair, every other thread has its own
+        let symbol = |thread: usize| format!("SYM{}/USDT", thread.min(THREADS - 2));
+
+        std::thread::scope(|scope| {
+            for thread in 0..THREADS {
+                let (manager, key) = (&manager, InstrumentKey::new("binance", &symbol(thread)));
+                scope.spawn(move || {
+                    for i in 0..TRADES {
+                        let trade = TradeSample {
+                            timestamp: start + Duration::milliseconds(i as i64),
+                            price: 100.0 + (i % 7) as f64 * 0.01,
+                            volume: 1.0,
+                        };
+                        manager.process_trade_keyed(&key, &trade);
+                    }
+                });
+            }
+        });
+
+        for thread in 0..THREADS - 1 {
+            let stats = manager.get_stats(&symbol(thread), "binance");
+            let price = stats.iter().find(|entry| entry.detector == "price").unwrap();
+            let expected = if thread == THREADS - 2 { 2 * TRADES } else { TRADES };
+            assert_eq!(price.stats.samples_seen, expected as u64, "{}", symbol(thread));
+        }
+        assert_eq!(manager.snapshot(start).pairs.len(), THREADS - 1);
+    }
+
+    #[test]
+    fn test_manager_snapshot_round_trip() {
+        let manager = AnomalyDetectorManager::new(
+            VolumeAnomalyConfig::default(),
+            PriceAnomalyConfig::default(),
+        );
+        let start = Utc::now();
+        for i in 0..80 {
+            let at = start + Duration::seconds(i);
+            for exchange in ["binance", "okx"] {
+                let sample = trade(1_000.0 + (i % 5) as f64 * 100.0, at);
+                manager.process_trade("BTC/USDT", exchange, &sample);
+            }
+        }
+
+        let snapshot = manager.snapshot(start + Duration::seconds(80));
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let decoded: DetectorSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, snapshot);
+
+        let restored = AnomalyDetectorManager::new(
+            VolumeAnomalyConfig::default(),
+            PriceAnomalyConfig::default(),
+        );
+        assert_eq!(restored.restore(&decoded), 2);
+        assert_eq!(restored.snapshot(snapshot.taken_at), snapshot);
+
+        let pair = &snapshot.pairs[0];
+        assert_eq!(pair.detectors["price"].window.len(), 60);
+        assert!(pair.detectors["price"].last_price.is_some());
+        assert_eq!(pair.detectors["large_trade"].window.len(), 80);
+        assert!(pair.detectors["spread"].window.is_empty());
+    }
+
+    #[test]
+    fn test_restore_counts_towards_warmup() {
+        use monitor_core::WarmupConfig;
+
+        let source = AnomalyDetectorManager::new(
+            VolumeAnomalyConfig::default(),
+            PriceAnomalyConfig::default(),
+        );
+        let start = Utc::now();
+        for i in 0..80 {
+            let sample = trade(1_000.0 + (i % 5) as f64 * 100.0, start + Duration::seconds(i));
+            source.process_trade("BTC/USDT", "binance", &sample);
+        }
+
+        // Restored windows carry the history a fresh start would still be collecting
+        let warmup = Arc::new(WarmupTracker::new(WarmupConfig {
+            duration_secs: 300,
+            min_samples_per_symbol: 60,
+        }));
+        let restored = AnomalyDetectorManager::new(
+            VolumeAnomalyConfig::default(),
+            PriceAnomalyConfig::default(),
+        )
+        .with_warmup(warmup.clone());
+        let key = InstrumentKey::new("binance", "BTC/USDT");
+        assert!(warmup.is_warming_up(key.as_str(), start));
+        restored.restore(&source.snapshot(start + Duration::seconds(80)));
+        assert!(!warmup.is_warming_up(key.as_str(), start));
+    }
+
+    #[test]
+    fn test_multi_timeframe_snapshot_round_trip() {
+        let config = PriceAnomalyConfig {
+            timeframes_secs: vec![1, 10],
+            min_samples: 5,
+            ..Default::default()
+        };
+        let detector = || {
+            MultiTimeframePriceDetector::new(
+                config.clone(),
+                "BTC/USDT".to_string(),
+                "binance".to_string(),
+            )
+        };
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+        let mut live = detector();
+        for i in 0..120 {
+            let value = 100.0 + (i % 7) as f64 * 0.1;
+            live.detect(&TimeSeriesData {
+                timestamp: start + Duration::milliseconds(500 * i),
+                value,
+            });
+        }
+
+        let state = live.snapshot();
+        assert_eq!(state.timeframes.iter().map(|tf| tf.secs).collect::<Vec<_>>(), [1, 10]);
+        assert_eq!(state.timeframes[0].bars.len(), 59);
+        assert_eq!(state.timeframes[1].bars.len(), 5);
+
+        let mut restored = detector();
+        restored.restore(&state, start + Duration::seconds(60));
+        assert_eq!(restored.snapshot(), state);
+        assert_eq!(restored.window_fill(), live.window_fill());
+    }
+
+    #[test]
+    fn test_snapshot_older_than_lookback_is_stale() {
+        let now = Utc::now();
+        let snapshot = DetectorSnapshot {
+            taken_at: now - Duration::minutes(61),
+            pairs: Vec::new(),
+        };
+        assert!(snapshot.is_stale(now, Duration::minutes(60)));
+        assert!(!snapshot.is_stale(now, Duration::minutes(90)));
+    }
+
+    // Flat at 100 then flat at 300, both with a repeating +-5% ripple
+    fn step_series() -> Vec<f64> {
+        (0..400)
+            .map(|i| {
+                let level = if i < 200 { 100.0 } else { 300.0 };
+                let ripple = (((i * 7) % 11) as f64 - 5.0) / 5.0 * 0.05;
+                level * (1.0 + ripple)
+            })
+            .collect()
+    }
+
+    fn alert_indices(detector: &mut dyn AnomalyDetector, series: &[f64]) -> Vec<usize> {
+        let start = Utc::now();
+        series
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &value)| {
+                let at = start + Duration::seconds(i as i64);
+                detector.detect(&TimeSeriesData { timestamp: at, value }).map(|_| i)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ewma_adapts_to_step_change_sooner_than_window() {
+        let series = step_series();
+        let mut window = VolumeAnomalyDetector::new(
+            VolumeAnomalyConfig {
+                min_percentage_change: 0.0,
+                ..Default::default()
+            },
+            "BTC/USDT".to_string(),
+            "binance".to_string(),
+        );
+        let mut ewma = EwmaAnomalyDetector::new(
+            EwmaAnomalyConfig::default(),
+            AnomalyType::VolumeSpike,
+            "volume",
+            "BTC/USDT".to_string(),
+            "binance".to_string(),
+        );
+
+        let window_alerts = alert_indices(&mut window, &series);
+        let ewma_alerts = alert_indices(&mut ewma, &series);
+
+        // Both catch the step and stay quiet on the ripple before it
+        assert_eq!(window_alerts.first(), Some(&200));
+        assert_eq!(ewma_alerts.first(), Some(&200));
+        // The window keeps alerting while its mean catches up, the EWMA baseline is there first
+        assert!(ewma_alerts.len() < window_alerts.len());
+        assert!(ewma_alerts.iter().all(|&i| i < 205));
+    }
+
+    #[test]
+    fn test_ewma_state_round_trip() {
+        let series = step_series();
+        let detector = || {
+            EwmaAnomalyDetector::new(
+                EwmaAnomalyConfig::default(),
+                AnomalyType::PriceSpike,
+                "price",
+                "BTC/USDT".to_string(),
+                "binance".to_string(),
+            )
+        };
+        let mut original = detector();
+        alert_indices(&mut original, &series[..150]);
+
+        let mut restored = detector();
+        restored.restore(&original.snapshot(), Utc::now());
+        assert_eq!(restored.snapshot(), original.snapshot());
+        assert_eq!(restored.state_name(), Some("price"));
+        assert_eq!(
+            alert_indices(&mut restored, &series[150..]),
+            alert_indices(&mut original, &series[150..])
+        );
+
+        // A window saved under the same name doesn't touch the baseline
+        let before = restored.snapshot();
+        let window = DetectorState {
+            window: vec![1.0, 2.0],
+            ..Default::default()
+        };
+        restored.restore(&window, Utc::now());
+        assert_eq!(restored.snapshot(), before);
+    }
+
+    #[test]
+    fn test_volume_percentile_trigger_catches_spike_hidden_by_outliers() {
+        // Regular 1000 prints inflate the standard deviation, so 300 is only 0.77 sigma out
+        let ripple = |i: usize| 100.0 * (1.0 + (((i * 7) % 11) as f64 - 5.0) / 5.0 * 0.05);
+        let mut series: Vec<f64> = (0..120)
+            .map(|i| if i > 0 && i % 15 == 0 { 1000.0 } else { ripple(i) })
+            .collect();
+        series.push(300.0);
+
+        let detector = |trigger| {
+            VolumeAnomalyDetector::new(
+                VolumeAnomalyConfig {
+                    min_percentage_change: 0.0,
+                    trigger,
+                    percentile: 90.0,
+                    ..Default::default()
+                },
+                "BTC/USDT".to_string(),
+                "binance".to_string(),
+            )
+        };
+        let outliers = vec![30, 45, 60, 75, 90, 105];
+        let with_spike: Vec<usize> = outliers.iter().copied().chain([120]).collect();
+        for (trigger, expected) in [
+            (VolumeTrigger::ZScore, &outliers),
+            (VolumeTrigger::Percentile, &with_spike),
+            (VolumeTrigger::Either, &with_spike),
+            (VolumeTrigger::Both, &outliers),
+        ] {
+            assert_eq!(&alert_indices(&mut detector(trigger), &series), expected, "{:?}", trigger);
+        }
+
+        let mut percentile = detector(VolumeTrigger::Percentile);
+        let start = Utc::now();
+        let mut last = None;
+        for (i, &value) in series.iter().enumerate() {
+            let at = start + Duration::seconds(i as i64);
+            last = percentile.detect(&TimeSeriesData { timestamp: at, value });
+        }
+        let detection = last.unwrap();
+        // 300 against a p90 of 105 grades on the distance past the percentile
+        assert_eq!(detection.severity, AnomalySeverity::High);
+        assert!(detection.description.ends_with(", p90 105.00"));
+        let Some(ReasonKind::Volume { percentile_volume, .. }) =
+            detection.reason.as_ref().map(|r| &r.kind)
+        else {
+            panic!("missing volume reason");
+        };
+        assert!((percentile_volume.unwrap() - 105.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_median_mad_sees_spike_after_extreme_outlier() {
+        let ripple = |i: usize| 100.0 * (1.0 + (((i * 7) % 11) as f64 - 5.0) / 5.0 * 0.05);
+        let mut series: Vec<f64> = (0..100).map(ripple).collect();
+        series.push(10_000.0);
+        series.extend((101..111).map(ripple));
+        series.push(130.0);
+
+        let config = VolumeAnomalyConfig {
+            min_percentage_change: 0.0,
+            estimator: WindowEstimator::MedianMad,
+            ..Default::default()
+        };
+        let (symbol, exchange) = ("BTC/USDT".to_string(), "binance".to_string());
+        let mut mean_std =
+            VolumeAnomalyDetector::new(config.clone(), symbol.clone(), exchange.clone());
+        let mut robust = RobustAnomalyDetector::for_volume(&config, symbol, exchange);
+
+        // The outlier inflates the standard deviation enough to hide the later spike
+        assert_eq!(alert_indices(&mut mean_std, &series), vec![100]);
+        assert_eq!(alert_indices(&mut robust, &series), vec![100, 111]);
+    }
+
+    #[test]
+    fn test_manager_uses_configured_estimator() {
+        let manager = AnomalyDetectorManager::new(
+            VolumeAnomalyConfig {
+                estimator: WindowEstimator::MedianMad,
+                ..Default::default()
+            },
+            PriceAnomalyConfig::default(),
+        );
+        let start = Utc::now();
+        let mut detections = Vec::new();
+        for i in 0..41 {
+            let sample = TradeSample {
+                timestamp: start + Duration::seconds(i),
+                price: 100.0 + (i % 5) as f64,
+                volume: if i < 40 { 1.0 + (i % 5) as f64 * 0.1 } else { 5.0 },
+            };
+            detections.extend(manager.process_trade("BTC/USDT", "binance", &sample));
+        }
+
+        let volume: Vec<_> = detections
+            .iter()
+            .filter(|d| d.anomaly_type == AnomalyType::VolumeSpike)
+            .collect();
+        assert_eq!(volume.len(), 1);
+        assert!(volume[0].description.starts_with("Robust volume anomaly"));
+        // The price series keeps the default estimator
+        assert!(manager.snapshot(start).pairs[0].detectors["price"].last_price.is_some());
+    }
+
+    // Around 100 with uniform noise of up to +-0.5, one sample a second from a bar boundary
+    fn noisy_prices(count: usize, level: f64, state: &mut u64) -> Vec<f64> {
+        (0..count)
+            .map(|_| {
+                *state = state
+                    .wrapping_mul(6_364_136_223_846_793_005)
+                    .wrapping_add(1_442_695_040_888_963_407);
+                level + ((*state >> 11) as f64 / (1u64 << 53) as f64 - 0.5)
+            })
+            .collect()
+    }
+
+    fn multi_timeframe_alerts(series: &[f64]) -> Vec<AnomalyDetection> {
+        let mut detector = MultiTimeframePriceDetector::new(
+            PriceAnomalyConfig {
+                min_samples: 5,
+                timeframes_secs: vec![1, 60, 300],
+                ..Default::default()
+            },
+            "BTC/USDT".to_string(),
+            "binance".to_string(),
+        );
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        series
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &value)| {
+                let at = start + Duration::seconds(i as i64);
+                detector.detect(&TimeSeriesData { timestamp: at, value })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_single_tick_spike_needs_confirmation() {
+        let mut state = 0x2545_f491_4f6c_dd1d;
+        let mut series = noisy_prices(2_400, 100.0, &mut state);
+        series.push(103.0);
+        series.extend(noisy_prices(30, 100.0, &mut state));
+
+        assert!(multi_timeframe_alerts(&series).is_empty());
+
+        // The single-timeframe detector does flag it
+        let mut single = PriceAnomalyDetector::new(
+            PriceAnomalyConfig::default(),
+            "BTC/USDT".to_string(),
+            "binance".to_string(),
+        );
+        assert_eq!(alert_indices(&mut single, &series), vec![2_400]);
+    }
+
+    #[test]
+    fn test_sustained_move_is_confirmed() {
+        let mut state = 0x2545_f491_4f6c_dd1d;
+        let mut series = noisy_prices(2_400, 100.0, &mut state);
+        series.extend(noisy_prices(60, 103.0, &mut state));
+
+        let alerts = multi_timeframe_alerts(&series);
+        assert!(!alerts.is_empty());
+        let first = &alerts[0];
+        assert_eq!(first.timestamp.timestamp() % 3_600, 2_402);
+        let scores = &first.metrics.timeframe_z_scores;
+        let frames: Vec<_> = scores.iter().map(|s| s.timeframe_secs).collect();
+        assert_eq!(frames, vec![1, 60, 300]);
+        let confirmed = scores.iter().filter(|s| s.z_score.is_some_and(|z| z >= 3.0)).count();
+        assert!(confirmed >= 2);
+    }
+
+    fn bollinger(consecutive: usize) -> BollingerBreachDetector {
+        BollingerBreachDetector::new(
+            BollingerConfig {
+                consecutive,
+                ..Default::default()
+            },
+            "BTC/USDT".to_string(),
+            "binance".to_string(),
+        )
+    }
+
+    fn bollinger_ripple(i: usize, amplitude: f64) -> f64 {
+        100.0 * (1.0 + (((i * 7) % 11) as f64 - 5.0) / 5.0 * amplitude)
+    }
+
+    #[test]
+    fn test_bollinger_single_tick_breach_is_suppressed() {
+        let mut series: Vec<f64> = (0..60).map(|i| bollinger_ripple(i, 0.005)).collect();
+        series.push(103.0);
+        series.extend((61..90).map(|i| bollinger_ripple(i, 0.005)));
+
+        assert!(alert_indices(&mut bollinger(3), &series).is_empty());
+        // Without the confirmation the wick alone alerts
+        assert_eq!(alert_indices(&mut bollinger(1), &series), vec![60]);
+    }
+
+    #[test]
+    fn test_bollinger_sustained_breach_alerts_once() {
+        let flat: Vec<f64> = (0..60).map(|i| bollinger_ripple(i, 0.005)).collect();
+        let up: Vec<f64> =
+            flat.iter().copied().chain((1..15).map(|j| 100.5 + 0.8 * j as f64)).collect();
+        let down: Vec<f64> =
+            flat.iter().copied().chain((1..15).map(|j| 99.5 - 0.8 * j as f64)).collect();
+
+        assert_eq!(alert_indices(&mut bollinger(3), &up), vec![62]);
+        assert_eq!(alert_indices(&mut bollinger(3), &down), vec![62]);
+
+        let mut detector = bollinger(3);
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let detections: Vec<_> = up
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &value)| {
+                let at = start + Duration::seconds(i as i64);
+                detector.detect(&TimeSeriesData { timestamp: at, value })
+            })
+            .collect();
+        let detection = &detections[0];
+        assert_eq!(detection.anomaly_type, AnomalyType::BollingerBreach);
+        let Some(DetectionDetails::Bollinger(details)) = &detection.details else {
+            panic!("missing bollinger details");
+        };
+        assert_eq!(details.side, BandSide::Upper);
+        assert_eq!(details.consecutive, 3);
+        assert!(detection.metrics.current_value > details.upper);
+        // The breakout widened the bands past every earlier width
+        assert_eq!(details.band_width_percentile, Some(100.0));
+        assert!(detection
+            .description
+            .starts_with("Price closed above the upper Bollinger band for binance/BTC/USDT"));
+    }
+
+    #[test]
+    fn test_bollinger_breach_out_of_squeeze_ranks_width_low() {
+        let mut series: Vec<f64> = (0..120).map(|i| bollinger_ripple(i, 0.02)).collect();
+        series.extend((120..200).map(|i| bollinger_ripple(i, 0.002)));
+        series.extend((1..10).map(|j| 100.2 + 0.3 * j as f64));
+
+        let mut detector = bollinger(3);
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let mut detections = Vec::new();
+        for (i, &value) in series.iter().enumerate() {
+            let at = start + Duration::seconds(i as i64);
+            detections.extend(detector.detect(&TimeSeriesData { timestamp: at, value }));
+        }
+        assert_eq!(detections.len(), 1);
+        let Some(DetectionDetails::Bollinger(details)) = &detections[0].details else {
+            panic!("missing bollinger details");
+        };
+        assert!(details.band_width_percentile.unwrap() < 50.0);
+    }
+}
@@ -0,0 +1,229 @@
+use crate::{AnomalyDetection, AnomalySeverity};
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use monitor_core::EscalationConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Escalation {
+    pub original_severity: AnomalySeverity,
+    pub tiers: usize,
+    // Detections of the same type on the symbol within the window, this one included
+    pub recent_count: usize,
+    pub window_secs: u64,
+}
+
+// Raises the severity of detections that keep recurring on a symbol. Counts decay as
+// detections age out of the window, and each pair keeps at most `second_tier_count` timestamps.
+pub struct SeverityEscalator {
+    config: EscalationConfig,
+    recent: DashMap<String, VecDeque<DateTime<Utc>>>,
+}
+
+impl SeverityEscalator {
+    pub fn new(config: EscalationConfig) -> Self {
+        Self {
+            config,
+            recent: DashMap::new(),
+        }
+    }
+
+    // Counts the detection at its own timestamp and escalates it in place when the count
+    // crosses a tier. Warm-up detections are neither counted nor escalated.
+    pub fn escalate(&self, anomaly: &mut AnomalyDetection) {
+        if !self.config.enabled || anomaly.warmup {
+            return;
+        }
+
+        let key = format!("{}:{:?}", anomaly.symbol, anomaly.anomaly_type);
+        let recent_count = self.record(&key, anomaly.timestamp);
+        let tiers = if recent_count >= self.config.second_tier_count {
+            2
+        } else if recent_count >= self.config.first_tier_count {
+            1
+        } else {
+            0
+        };
+
+        let escalated = anomaly.severity.escalated(tiers);
+        if escalated == anomaly.severity {
+            return;
+        }
+
+        anomaly.description = format!(
+            "{} [escalated from {:?}: {} {:?} detections on {} in {}s]",
+            anomaly.description,
+            anomaly.severity,
+            recent_count,
+            anomaly.anomaly_type,
+            anomaly.symbol,
+            self.config.window_secs
+        );
+        anomaly.escalation = Some(Escalation {
+            original_severity: std::mem::replace(&mut anomaly.severity, escalated),
+            tiers,
+            recent_count,
+            window_secs: self.config.window_secs,
+        });
+    }
+
+    fn record(&self, key: &str, at: DateTime<Utc>) -> usize {
+        if !self.recent.contains_key(key) && self.recent.len() >= self.config.max_tracked {
+            self.prune(at);
+            if self.recent.len() >= self.config.max_tracked {
+                self.evict_oldest();
+            }
+        }
+
+        let window = self.window();
+        let mut recent = self.recent.entry(key.to_string()).or_default();
+        while recent.front().is_some_and(|t| at - *t >= window) {
+            recent.pop_front();
+        }
+        // Nothing beyond the top tier changes the outcome
+        if recent.len() >= self.config.second_tier_count.max(1) {
+            recent.pop_front();
+        }
+        recent.push_back(at);
+        recent.len()
+    }
+
+    // Drops pairs with no detection left in the window
+    pub fn prune(&self, now: DateTime<Utc>) {
+        let window = self.window();
+        self.recent
+            .retain(|_, recent| recent.back().is_some_and(|t| now - *t < window));
+    }
+
+    fn evict_oldest(&self) {
+        let oldest = self
+            .recent
+            .iter()
+            .min_by_key(|entry| entry.value().back().copied())
+            .map(|entry| entry.key().clone());
+        if let Some(key) = oldest {
+            self.recent.remove(&key);
+        }
+    }
+
+    pub fn tracked(&self) -> usize {
+        self.recent.len()
+    }
+
+    fn window(&self) -> Duration {
+        Duration::seconds(self.config.window_secs as i64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AnomalyMetrics;
+    use monitor_core::AnomalyType;
+
+    fn detection(symbol: &str, severity: AnomalySeverity, at: DateTime<Utc>) -> AnomalyDetection {
+        AnomalyDetection {
+            id: uuid::Uuid::new_v4(),
+            timestamp: at,
+            symbol: symbol.to_string(),
+            exchange: "binance".to_string(),
+            anomaly_type: AnomalyType::VolumeSpike,
+            confidence: severity.confidence_floor(),
+            severity,
+            metrics: AnomalyMetrics {
+                current_value: 5.0,
+                expected_value: 1.0,
+                deviation: 4.0,
+                z_score: Some(4.0),
+                percentage_change: None,
+                historical_avg: None,
+                historical_std: None,
+                ..Default::default()
+            },
+            description: "Volume spike".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn escalator() -> SeverityEscalator {
+        SeverityEscalator::new(EscalationConfig {
+            max_tracked: 2,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_two_tier_escalation() {
+        let escalator = escalator();
+        let start = Utc::now();
+        let mut severities = Vec::new();
+        for i in 0..6 {
+            let mut anomaly = detection("BTC/USDT", AnomalySeverity::Medium, start + Duration::minutes(i));
+            escalator.escalate(&mut anomaly);
+            severities.push(anomaly.severity.clone());
+            if i == 4 {
+                let escalation = anomaly.escalation.unwrap();
+                assert_eq!(escalation.original_severity, AnomalySeverity::Medium);
+                assert_eq!((escalation.tiers, escalation.recent_count), (2, 5));
+                assert!(anomaly.description.contains("escalated from Medium"));
+            }
+        }
+        assert_eq!(
+            severities,
+            [
+                AnomalySeverity::Medium,
+                AnomalySeverity::Medium,
+                AnomalySeverity::High,
+                AnomalySeverity::High,
+                AnomalySeverity::Critical,
+                AnomalySeverity::Critical,
+            ]
+        );
+
+        // Already critical, nothing to record
+        let mut critical = detection("BTC/USDT", AnomalySeverity::Critical, start + Duration::minutes(6));
+        escalator.escalate(&mut critical);
+        assert!(critical.escalation.is_none());
+    }
+
+    #[test]
+    fn test_counts_decay_and_stay_bounded() {
+        let escalator = escalator();
+        let start = Utc::now();
+        for i in 0..3 {
+            escalator.escalate(&mut detection("BTC/USDT", AnomalySeverity::Medium, start + Duration::minutes(i)));
+        }
+
+        // Ten minutes after the first, only two are left in the window
+        let mut later = detection("BTC/USDT", AnomalySeverity::Medium, start + Duration::minutes(11));
+        escalator.escalate(&mut later);
+        assert_eq!(later.severity, AnomalySeverity::Medium);
+
+        let mut burst = detection("BTC/USDT", AnomalySeverity::Medium, start + Duration::minutes(12));
+        escalator.escalate(&mut burst);
+        assert_eq!(burst.severity, AnomalySeverity::High);
+
+        // Once idle for a full window a symbol starts from zero, and no more than
+        // `max_tracked` pairs are held
+        escalator.escalate(&mut detection("ETH/USDT", AnomalySeverity::Low, start + Duration::minutes(12)));
+        escalator.escalate(&mut detection("SOL/USDT", AnomalySeverity::Low, start + Duration::minutes(30)));
+        assert_eq!(escalator.tracked(), 1);
+        escalator.escalate(&mut detection("XRP/USDT", AnomalySeverity::Low, start + Duration::minutes(31)));
+        assert_eq!(escalator.tracked(), 2);
+        escalator.escalate(&mut detection("ADA/USDT", AnomalySeverity::Low, start + Duration::minutes(32)));
+        assert_eq!(escalator.tracked(), 2);
+
+        let mut again = detection("BTC/USDT", AnomalySeverity::Medium, start + Duration::minutes(33));
+        escalator.escalate(&mut again);
+        assert!(again.escalation.is_none());
+
+        // Warm-up detections are not counted
+        let mut warmup = detection("DOT/USDT", AnomalySeverity::Medium, start + Duration::minutes(33));
+        warmup.warmup = true;
+        for _ in 0..5 {
+            escalator.escalate(&mut warmup);
+        }
+        assert!(warmup.escalation.is_none());
+    }
+}
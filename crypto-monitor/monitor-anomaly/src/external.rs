@@ -0,0 +1,507 @@
+use crate::{AnomalyDetection, AnomalyMetrics, AnomalySeverity, DetectionDetails};
+use chrono::{DateTime, Duration, Utc};
+use monitor_core::{AnomalyType, IngestConfig, IngestSourceConfig};
+use parking_lot::Mutex;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt,
+};
+use uuid::Uuid;
+
+const FIELDS: &[&str] = &[
+    "id",
+    "source",
+    "timestamp",
+    "symbol",
+    "exchange",
+    "anomaly_type",
+    "severity",
+    "metrics",
+    "description",
+    "context",
+];
+const REQUIRED: &[&str] = &[
+    "source",
+    "timestamp",
+    "symbol",
+    "exchange",
+    "anomaly_type",
+    "severity",
+    "metrics",
+    "description",
+];
+
+// Publishers' clocks may run a little ahead of ours, but not by more than this
+const MAX_CLOCK_SKEW_SECS: i64 = 60;
+const MAX_DESCRIPTION_LEN: usize = 2000;
+
+// The contract for detections published by other systems, over POST /api/v1/anomalies/ingest
+// or as the data of Anomaly events on the anomalies topic. Mirrors AnomalyDetection, with the
+// publishing system named in `source`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExternalAnomaly {
+    // Publishers that retry should set it, redeliveries of an id are dropped
+    #[serde(default = "Uuid::new_v4")]
+    pub id: Uuid,
+    pub source: String,
+    pub timestamp: DateTime<Utc>,
+    pub symbol: String,
+    pub exchange: String,
+    pub anomaly_type: AnomalyType,
+    pub severity: AnomalySeverity,
+    pub metrics: AnomalyMetrics,
+    pub description: String,
+    // Free-form detector context, stored and notified as is
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExternalDetails {
+    pub context: serde_json::Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    fn new(field: &str, message: impl Into<String>) -> Self {
+        Self {
+            field: field.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+fn check<T: DeserializeOwned>(
+    object: &serde_json::Map<String, serde_json::Value>,
+    field: &str,
+    errors: &mut Vec<FieldError>,
+) {
+    if let Some(value) = object.get(field) {
+        if let Err(e) = serde_json::from_value::<T>(value.clone()) {
+            errors.push(FieldError::new(field, e.to_string()));
+        }
+    }
+}
+
+impl ExternalAnomaly {
+    // Reports every problem with the payload rather than the first one serde hits
+    pub fn parse(value: &serde_json::Value, now: DateTime<Utc>) -> Result<Self, Vec<FieldError>> {
+        let Some(object) = value.as_object() else {
+            return Err(vec![FieldError::new("", "expected a JSON object")]);
+        };
+
+        let mut errors: Vec<FieldError> = REQUIRED
+            .iter()
+            .filter(|field| !object.contains_key(**field))
+            .map(|field| FieldError::new(field, "missing field"))
+            .collect();
+        errors.extend(
+            object
+                .keys()
+                .filter(|key| !FIELDS.contains(&key.as_str()))
+                .map(|key| FieldError::new(key, "unknown field")),
+        );
+        check::<Uuid>(object, "id", &mut errors);
+        check::<String>(object, "source", &mut errors);
+        check::<DateTime<Utc>>(object, "timestamp", &mut errors);
+        check::<String>(object, "symbol", &mut errors);
+        check::<String>(object, "exchange", &mut errors);
+        check::<AnomalyType>(object, "anomaly_type", &mut errors);
+        check::<AnomalySeverity>(object, "severity", &mut errors);
+        check::<AnomalyMetrics>(object, "metrics", &mut errors);
+        check::<String>(object, "description", &mut errors);
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        let anomaly: ExternalAnomaly = serde_json::from_value(value.clone())
+            .map_err(|e| vec![FieldError::new("", e.to_string())])?;
+        anomaly.validate(now)?;
+        Ok(anomaly)
+    }
+
+    fn validate(&self, now: DateTime<Utc>) -> Result<(), Vec<FieldError>> {
+        let mut errors = Vec::new();
+        for (field, value) in [
+            ("source", &self.source),
+            ("symbol", &self.symbol),
+            ("exchange", &self.exchange),
+            ("description", &self.description),
+        ] {
+            if value.trim().is_empty() {
+                errors.push(FieldError::new(field, "must not be empty"));
+            }
+        }
+        if self.description.chars().count() > MAX_DESCRIPTION_LEN {
+            errors.push(FieldError::new(
+                "description",
+                format!("longer than {} characters", MAX_DESCRIPTION_LEN),
+            ));
+        }
+        if self.timestamp > now + Duration::seconds(MAX_CLOCK_SKEW_SECS) {
+            errors.push(FieldError::new("timestamp", "is in the future"));
+        }
+
+        let metrics = &self.metrics;
+        for (field, value) in [
+            ("metrics.current_value", Some(metrics.current_value)),
+            ("metrics.expected_value", Some(metrics.expected_value)),
+            ("metrics.deviation", Some(metrics.deviation)),
+            ("metrics.z_score", metrics.z_score),
+            ("metrics.percentage_change", metrics.percentage_change),
+            ("metrics.historical_avg", metrics.historical_avg),
+            ("metrics.historical_std", metrics.historical_std),
+        ] {
+            if value.is_some_and(|v| !v.is_finite()) {
+                errors.push(FieldError::new(field, "must be a finite number"));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn into_detection(self) -> AnomalyDetection {
+        AnomalyDetection {
+            id: self.id,
+            timestamp: self.timestamp,
+            symbol: self.symbol,
+            exchange: self.exchange,
+            anomaly_type: self.anomaly_type,
+            confidence: self.severity.confidence_floor(),
+            severity: self.severity,
+            metrics: self.metrics,
+            description: self.description,
+            details: self
+                .context
+                .map(|context| DetectionDetails::External(ExternalDetails { context })),
+            source: Some(self.source),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IngestReceipt {
+    pub id: Uuid,
+    pub source: String,
+    // Already ingested under this id, nothing was dispatched
+    pub duplicate: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum IngestRejection {
+    Disabled,
+    UnknownSource(String),
+    Unauthorized(String),
+    RateLimited { source: String, retry_after_secs: u64 },
+}
+
+impl fmt::Display for IngestRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IngestRejection::Disabled => write!(f, "External detection ingest is disabled"),
+            IngestRejection::UnknownSource(source) => {
+                write!(f, "Source '{}' is not allowed to publish detections", source)
+            }
+            IngestRejection::Unauthorized(source) => {
+                write!(f, "API key is not valid for source '{}'", source)
+            }
+            IngestRejection::RateLimited { source, retry_after_secs } => write!(
+                f,
+                "Rate limit exceeded for source '{}', retry after {}s",
+                source, retry_after_secs
+            ),
+        }
+    }
+}
+
+struct SourceBucket {
+    tokens: f64,
+    last_refill: DateTime<Utc>,
+}
+
+#[derive(Default)]
+struct SeenIds {
+    ids: HashSet<Uuid>,
+    order: VecDeque<Uuid>,
+}
+
+// Allowlist, API keys, dedup and per-source rate limits shared by the HTTP and bus paths
+pub struct IngestGate {
+    config: IngestConfig,
+    buckets: Mutex<HashMap<String, SourceBucket>>,
+    seen: Mutex<SeenIds>,
+}
+
+impl IngestGate {
+    pub fn new(config: IngestConfig) -> Self {
+        Self {
+            config,
+            buckets: Mutex::new(HashMap::new()),
+            seen: Mutex::new(SeenIds::default()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    fn source(&self, name: &str) -> Result<&IngestSourceConfig, IngestRejection> {
+        if !self.config.enabled {
+            return Err(IngestRejection::Disabled);
+        }
+        self.config
+            .sources
+            .iter()
+            .find(|source| source.name == name)
+            .ok_or_else(|| IngestRejection::UnknownSource(name.to_string()))
+    }
+
+    // API keys are tied to one source, a key can't publish under another source's name
+    pub fn authorize(&self, api_key: Option<&str>, source: &str) -> Result<(), IngestRejection> {
+        let config = self.source(source)?;
+        match api_key {
+            Some(key) if config.api_keys.iter().any(|k| k == key) => Ok(()),
+            _ => Err(IngestRejection::Unauthorized(source.to_string())),
+        }
+    }
+
+    // Redeliveries are acknowledged without using the source's quota, and a rate-limited
+    // detection isn't remembered so its retry goes through
+    pub fn admit(
+        &self,
+        anomaly: &ExternalAnomaly,
+        now: DateTime<Utc>,
+    ) -> Result<IngestReceipt, IngestRejection> {
+        let config = self.source(&anomaly.source)?;
+        let receipt = |duplicate| IngestReceipt {
+            id: anomaly.id,
+            source: anomaly.source.clone(),
+            duplicate,
+        };
+
+        let mut seen = self.seen.lock();
+        if seen.ids.contains(&anomaly.id) {
+            return Ok(receipt(true));
+        }
+
+        self.take_token(config, now)?;
+
+        seen.ids.insert(anomaly.id);
+        seen.order.push_back(anomaly.id);
+        while seen.order.len() > self.config.dedup_capacity {
+            if let Some(id) = seen.order.pop_front() {
+                seen.ids.remove(&id);
+            }
+        }
+        Ok(receipt(false))
+    }
+
+    // Token bucket holding a minute's allowance, refilled continuously
+    fn take_token(
+        &self,
+        config: &IngestSourceConfig,
+        now: DateTime<Utc>,
+    ) -> Result<(), IngestRejection> {
+        let capacity = config.max_per_minute as f64;
+        let per_sec = capacity / 60.0;
+
+        let mut buckets = self.buckets.lock();
+        let bucket = buckets.entry(config.name.clone()).or_insert(SourceBucket {
+            tokens: capacity,
+            last_refill: now,
+        });
+        let elapsed = (now - bucket.last_refill).num_milliseconds().max(0) as f64 / 1000.0;
+        bucket.tokens = (bucket.tokens + elapsed * per_sec).min(capacity);
+        bucket.last_refill = now;
+
+        if bucket.tokens < 1.0 {
+            let retry_after_secs = if capacity > 0.0 {
+                ((1.0 - bucket.tokens) * 60.0 / capacity).ceil() as u64
+            } else {
+                60
+            };
+            return Err(IngestRejection::RateLimited {
+                source: config.name.clone(),
+                retry_after_secs,
+            });
+        }
+        bucket.tokens -= 1.0;
+        Ok(())
+    }
+}
+
+// Implemented by the monitor facade, so the API can hand detections to the pipeline without
+// depending on it
+#[async_trait::async_trait]
+pub trait ExternalIngest: Send + Sync {
+    fn gate(&self) -> &IngestGate;
+
+    async fn ingest(&self, anomaly: ExternalAnomaly) -> Result<IngestReceipt, IngestRejection>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> IngestConfig {
+        IngestConfig {
+            enabled: true,
+            sources: vec![
+                IngestSourceConfig {
+                    name: "ml-research".to_string(),
+                    api_keys: vec!["ml-key".to_string()],
+                    max_per_minute: 3,
+                },
+                IngestSourceConfig {
+                    name: "risk".to_string(),
+                    api_keys: vec!["risk-key".to_string()],
+                    max_per_minute: 60,
+                },
+            ],
+            dedup_capacity: 100,
+        }
+    }
+
+    fn payload(source: &str) -> serde_json::Value {
+        serde_json::json!({
+            "source": source,
+            "timestamp": "2024-03-01T12:00:00Z",
+            "symbol": "BTC/USDT",
+            "exchange": "binance",
+            "anomaly_type": "VolumeSpike",
+            "severity": "High",
+            "metrics": {
+                "current_value": 120.0,
+                "expected_value": 40.0,
+                "deviation": 80.0,
+                "z_score": 4.5,
+                "percentage_change": null,
+                "historical_avg": null,
+                "historical_std": null
+            },
+            "description": "Model flagged a volume regime change",
+            "context": { "model": "regime-v3", "score": 0.97 }
+        })
+    }
+
+    fn now() -> DateTime<Utc> {
+        "2024-03-01T12:00:05Z".parse().unwrap()
+    }
+
+    fn anomaly(source: &str) -> ExternalAnomaly {
+        ExternalAnomaly::parse(&payload(source), now()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_reports_every_field_error() {
+        let detection = anomaly("ml-research").into_detection();
+        assert_eq!(detection.source.as_deref(), Some("ml-research"));
+        assert!(matches!(detection.details, Some(DetectionDetails::External(_))));
+
+        let mut value = payload("ml-research");
+        let object = value.as_object_mut().unwrap();
+        object.remove("symbol");
+        object.insert("severity".to_string(), "Extreme".into());
+        object.insert("confidence".to_string(), 0.9.into());
+        object["metrics"].as_object_mut().unwrap().remove("deviation");
+
+        let errors = ExternalAnomaly::parse(&value, now()).unwrap_err();
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, ["symbol", "confidence", "severity", "metrics"]);
+        assert!(errors[2].message.contains("Extreme"));
+        assert!(errors[3].message.contains("deviation"));
+
+        // Well-formed but unusable values are caught after deserialization
+        let mut value = payload(" ");
+        value["timestamp"] = "2024-03-01T13:00:00Z".into();
+        let errors = ExternalAnomaly::parse(&value, now()).unwrap_err();
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, ["source", "timestamp"]);
+    }
+
+    #[test]
+    fn test_authorize_ties_keys_to_sources() {
+        let gate = IngestGate::new(config());
+        assert!(gate.authorize(Some("ml-key"), "ml-research").is_ok());
+        assert_eq!(
+            gate.authorize(Some("risk-key"), "ml-research"),
+            Err(IngestRejection::Unauthorized("ml-research".to_string()))
+        );
+        assert_eq!(
+            gate.authorize(None, "risk"),
+            Err(IngestRejection::Unauthorized("risk".to_string()))
+        );
+        assert_eq!(
+            gate.authorize(Some("ml-key"), "vendor"),
+            Err(IngestRejection::UnknownSource("vendor".to_string()))
+        );
+
+        let disabled = IngestGate::new(IngestConfig::default());
+        assert_eq!(
+            disabled.authorize(Some("ml-key"), "ml-research"),
+            Err(IngestRejection::Disabled)
+        );
+    }
+
+    #[test]
+    fn test_rate_limit_is_per_source() {
+        let gate = IngestGate::new(config());
+        let now = now();
+
+        for _ in 0..3 {
+            assert!(!gate.admit(&anomaly("ml-research"), now).unwrap().duplicate);
+        }
+        let limited = anomaly("ml-research");
+        assert_eq!(
+            gate.admit(&limited, now).unwrap_err(),
+            IngestRejection::RateLimited {
+                source: "ml-research".to_string(),
+                retry_after_secs: 20,
+            }
+        );
+
+        // Another source keeps its own allowance
+        for _ in 0..10 {
+            assert!(gate.admit(&anomaly("risk"), now).is_ok());
+        }
+
+        // One token back every 20s, and the rejected detection wasn't remembered as seen
+        assert!(gate.admit(&limited, now + Duration::seconds(19)).is_err());
+        assert!(!gate.admit(&limited, now + Duration::seconds(40)).unwrap().duplicate);
+    }
+
+    #[test]
+    fn test_redeliveries_are_deduplicated() {
+        let gate = IngestGate::new(IngestConfig {
+            dedup_capacity: 2,
+            ..config()
+        });
+        let now = now();
+        let first = anomaly("risk");
+
+        assert!(!gate.admit(&first, now).unwrap().duplicate);
+        assert!(gate.admit(&first, now).unwrap().duplicate);
+
+        gate.admit(&anomaly("risk"), now).unwrap();
+        gate.admit(&anomaly("risk"), now).unwrap();
+        assert!(!gate.admit(&first, now).unwrap().duplicate);
+    }
+}
@@ -0,0 +1,229 @@
+use crate::{
+    reason::{AnomalyReason, ReasonKind},
+    warmup::WarmupTracker,
+    severity_from_thresholds, AnomalyDetection, AnomalyMetrics, DetectionDetails,
+};
+use dashmap::DashMap;
+use monitor_core::{model::FundingRateUpdate, AnomalyType, FundingRateConfig};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::info;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum FundingTrigger {
+    AboveMax,
+    BelowMin,
+    Change,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FundingDetails {
+    pub trigger: FundingTrigger,
+    pub predicted_rate: f64,
+    pub previous_rate: Option<f64>,
+    pub annualized_pct: f64,
+    pub interval_hours: f64,
+    // Longs and shorts swapped which side pays
+    pub sign_flip: bool,
+}
+
+struct LastFunding {
+    rate: f64,
+    out_of_bounds: bool,
+}
+
+// Flags perpetual funding as the predicted rate leaves its configured bounds or moves sharply
+// between two updates. Staying out of bounds alerts once, when the rate first crosses.
+pub struct FundingRateDetector {
+    config: FundingRateConfig,
+    last: DashMap<String, LastFunding>,
+    warmup: Option<Arc<WarmupTracker>>,
+}
+
+impl FundingRateDetector {
+    pub fn new(config: FundingRateConfig) -> Self {
+        Self {
+            config,
+            last: DashMap::new(),
+            warmup: None,
+        }
+    }
+
+    pub fn with_warmup(mut self, warmup: Arc<WarmupTracker>) -> Self {
+        self.warmup = Some(warmup);
+        self
+    }
+
+    pub fn record(&self, update: &FundingRateUpdate) -> Option<AnomalyDetection> {
+        let rate = update.predicted_rate;
+        if !self.config.enabled || !rate.is_finite() || update.interval_hours <= 0.0 {
+            return None;
+        }
+
+        let key = format!("{}:{}", update.exchange, update.symbol);
+        let out_of_bounds = rate > self.config.max_rate || rate < self.config.min_rate;
+        let previous = self.last.insert(key.clone(), LastFunding { rate, out_of_bounds });
+
+        let crossed = out_of_bounds && !previous.as_ref().is_some_and(|p| p.out_of_bounds);
+        let previous_rate = previous.map(|p| p.rate);
+        let changed = previous_rate.is_some_and(|p| (rate - p).abs() > self.config.max_change);
+
+        let trigger = if crossed && rate > self.config.max_rate {
+            FundingTrigger::AboveMax
+        } else if crossed {
+            FundingTrigger::BelowMin
+        } else if changed {
+            FundingTrigger::Change
+        } else {
+            return None;
+        };
+
+        let details = FundingDetails {
+            trigger,
+            predicted_rate: rate,
+            previous_rate,
+            annualized_pct: update.annualized_pct(),
+            interval_hours: update.interval_hours,
+            sign_flip: previous_rate.is_some_and(|p| p * rate < 0.0),
+        };
+        Some(self.detection(update, &key, details))
+    }
+
+    fn detection(
+        &self,
+        update: &FundingRateUpdate,
+        key: &str,
+        details: FundingDetails,
+    ) -> AnomalyDetection {
+        let severity = severity_from_thresholds(details.annualized_pct, &self.config.severity);
+
+        let threshold = match details.trigger {
+            FundingTrigger::AboveMax => self.config.max_rate,
+            FundingTrigger::BelowMin => self.config.min_rate,
+            FundingTrigger::Change => self.config.max_change,
+        };
+        let mut reason = AnomalyReason::new(
+            &update.exchange,
+            &update.symbol,
+            details.predicted_rate,
+            ReasonKind::Funding {
+                trigger: details.trigger,
+                sign_flip: details.sign_flip,
+                interval_hours: details.interval_hours,
+                annualized_pct: details.annualized_pct,
+            },
+        )
+        .with_threshold(threshold)
+        .with_window_secs(details.interval_hours * 3600.0);
+        reason.baseline = details.previous_rate;
+        let description = reason.to_string();
+        info!("{}", description);
+
+        let expected = details.previous_rate.unwrap_or(0.0);
+        AnomalyDetection {
+            id: uuid::Uuid::new_v4(),
+            timestamp: update.timestamp,
+            symbol: update.symbol.clone(),
+            exchange: update.exchange.clone(),
+            anomaly_type: AnomalyType::FundingRate,
+            confidence: severity.confidence_floor(),
+            severity,
+            metrics: AnomalyMetrics {
+                current_value: details.predicted_rate,
+                expected_value: expected,
+                deviation: details.predicted_rate - expected,
+                z_score: None,
+                percentage_change: None,
+                historical_avg: None,
+                historical_std: None,
+                ..Default::default()
+            },
+            description,
+            reason: Some(reason),
+            warmup: self
+                .warmup
+                .as_ref()
+                .is_some_and(|w| w.is_warming_up(key, update.timestamp)),
+            details: Some(DetectionDetails::Funding(details)),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AnomalySeverity;
+    use chrono::Utc;
+
+    fn update(predicted_rate: f64) -> FundingRateUpdate {
+        FundingRateUpdate {
+            exchange: "binance".to_string(),
+            symbol: "BTC/USDT:USDT".to_string(),
+            timestamp: Utc::now(),
+            predicted_rate,
+            interval_hours: 8.0,
+            next_funding_at: None,
+        }
+    }
+
+    fn details(detection: &AnomalyDetection) -> &FundingDetails {
+        let Some(DetectionDetails::Funding(details)) = &detection.details else {
+            panic!("missing funding details");
+        };
+        details
+    }
+
+    #[test]
+    fn test_extreme_positive_rate_alerts_once() {
+        let detector = FundingRateDetector::new(FundingRateConfig::default());
+
+        assert!(detector.record(&update(0.0001)).is_none());
+        assert!(detector.record(&update(0.0004)).is_none());
+        // 0.11% per 8h is 120.45% a year
+        let detection = detector.record(&update(0.0011)).unwrap();
+        assert_eq!(detection.anomaly_type, AnomalyType::FundingRate);
+        assert_eq!(detection.severity, AnomalySeverity::Critical);
+        assert_eq!(details(&detection).trigger, FundingTrigger::AboveMax);
+        assert!((details(&detection).annualized_pct - 120.45).abs() < 1e-6);
+
+        // Still above the bound, and only drifting
+        assert!(detector.record(&update(0.0013)).is_none());
+        // Back inside, then crossing again alerts again
+        assert!(detector.record(&update(0.0009)).is_none());
+        assert!(detector.record(&update(0.0012)).is_some());
+    }
+
+    #[test]
+    fn test_sign_flip_is_a_change() {
+        let detector = FundingRateDetector::new(FundingRateConfig::default());
+
+        assert!(detector.record(&update(0.0004)).is_none());
+        let detection = detector.record(&update(-0.0003)).unwrap();
+        let details = details(&detection);
+        assert_eq!(details.trigger, FundingTrigger::Change);
+        assert!(details.sign_flip);
+        assert_eq!(details.previous_rate, Some(0.0004));
+        assert!(detection.description.contains("flipped sign"));
+        // About 33% a year
+        assert_eq!(detection.severity, AnomalySeverity::Medium);
+
+        // A small flip around zero is not
+        assert!(detector.record(&update(-0.00001)).is_none());
+        assert!(detector.record(&update(0.00001)).is_none());
+    }
+
+    #[test]
+    fn test_first_update_below_min_alerts_with_hourly_interval() {
+        let detector = FundingRateDetector::new(FundingRateConfig::default());
+
+        let mut hourly = update(-0.0015);
+        hourly.interval_hours = 1.0;
+        let detection = detector.record(&hourly).unwrap();
+        assert_eq!(details(&detection).trigger, FundingTrigger::BelowMin);
+        assert!(!details(&detection).sign_flip);
+        assert_eq!(detection.severity, AnomalySeverity::Critical);
+    }
+}
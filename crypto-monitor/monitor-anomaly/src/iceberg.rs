@@ -0,0 +1,337 @@
+use crate::{
+    reason::{AnomalyReason, ReasonKind},
+    warmup::WarmupTracker,
+    AnomalyDetection, AnomalyMetrics, AnomalySeverity, DetectionDetails,
+};
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use monitor_core::{AnomalyType, IcebergConfig};
+use serde::{Deserialize, Serialize};
+use std::{collections::VecDeque, sync::Arc};
+use tracing::info;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BookSide {
+    Bid,
+    Ask,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TopOfBook {
+    pub bid_price: f64,
+    pub bid_size: f64,
+    pub ask_price: f64,
+    pub ask_size: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IcebergDetails {
+    pub side: BookSide,
+    pub level_price: f64,
+    pub executed_size: f64,
+    pub displayed_size: f64,
+    pub hidden_size_estimate: f64,
+    pub fills: usize,
+    pub duration_secs: f64,
+}
+
+struct LevelTracker {
+    price: f64,
+    fills: VecDeque<(DateTime<Utc>, f64)>,
+    // Largest size the book showed at the level while it was tracked
+    displayed: f64,
+    flagged: bool,
+}
+
+impl LevelTracker {
+    fn new(price: f64) -> Self {
+        Self {
+            price,
+            fills: VecDeque::new(),
+            displayed: 0.0,
+            flagged: false,
+        }
+    }
+}
+
+// Flags price levels that keep absorbing same-side fills well beyond what the book displays.
+// Trades are attributed to a side by matching their price against the latest top of book.
+pub struct IcebergDetector {
+    config: IcebergConfig,
+    books: DashMap<String, TopOfBook>,
+    levels: DashMap<(String, BookSide), LevelTracker>,
+    warmup: Option<Arc<WarmupTracker>>,
+}
+
+impl IcebergDetector {
+    pub fn new(config: IcebergConfig) -> Self {
+        Self {
+            config,
+            books: DashMap::new(),
+            levels: DashMap::new(),
+            warmup: None,
+        }
+    }
+
+    pub fn with_warmup(mut self, warmup: Arc<WarmupTracker>) -> Self {
+        self.warmup = Some(warmup);
+        self
+    }
+
+    fn tolerance(&self, symbol: &str, price: f64) -> f64 {
+        self.config
+            .tick_sizes
+            .get(symbol)
+            .copied()
+            .unwrap_or(price * self.config.price_tolerance_bps / 10_000.0)
+    }
+
+    pub fn update_book(&self, exchange: &str, symbol: &str, book: TopOfBook) {
+        let key = format!("{}:{}", exchange, symbol);
+
+        for (side, price, size) in [
+            (BookSide::Bid, book.bid_price, book.bid_size),
+            (BookSide::Ask, book.ask_price, book.ask_size),
+        ] {
+            let level_key = (key.clone(), side);
+            let moved_away = match self.levels.get_mut(&level_key) {
+                Some(mut level) if (level.price - price).abs() <= self.tolerance(symbol, price) => {
+                    level.displayed = level.displayed.max(size);
+                    false
+                }
+                Some(_) => true,
+                None => false,
+            };
+            if moved_away {
+                self.levels.remove(&level_key);
+            }
+        }
+
+        self.books.insert(key, book);
+    }
+
+    pub fn record_trade(
+        &self,
+        exchange: &str,
+        symbol: &str,
+        price: f64,
+        size: f64,
+        at: DateTime<Utc>,
+    ) -> Option<AnomalyDetection> {
+        if !self.config.enabled {
+            return None;
+        }
+
+        let key = format!("{}:{}", exchange, symbol);
+        let book = *self.books.get(&key)?;
+        let tolerance = self.tolerance(symbol, price);
+
+        // Only fills at the touch can be attributed to a visible level
+        let (side, level_price, displayed) = if (price - book.ask_price).abs() <= tolerance {
+            (BookSide::Ask, book.ask_price, book.ask_size)
+        } else if (price - book.bid_price).abs() <= tolerance {
+            (BookSide::Bid, book.bid_price, book.bid_size)
+        } else {
+            return None;
+        };
+
+        let mut level = self
+            .levels
+            .entry((key.clone(), side))
+            .or_insert_with(|| LevelTracker::new(level_price));
+        if (level.price - level_price).abs() > tolerance {
+            *level = LevelTracker::new(level_price);
+        }
+
+        let window_start = at - Duration::seconds(self.config.window_secs as i64);
+        level.fills.push_back((at, size));
+        while level.fills.front().is_some_and(|(t, _)| *t < window_start) {
+            level.fills.pop_front();
+        }
+        level.displayed = level.displayed.max(displayed);
+
+        let executed: f64 = level.fills.iter().map(|(_, size)| size).sum();
+        if level.flagged
+            || level.fills.len() < self.config.min_fills
+            || level.displayed <= 0.0
+            || executed <= level.displayed * self.config.hidden_size_multiple
+        {
+            return None;
+        }
+        level.flagged = true;
+
+        let first_fill = level.fills.front().map(|(t, _)| *t).unwrap_or(at);
+        let details = IcebergDetails {
+            side,
+            level_price: level.price,
+            executed_size: executed,
+            displayed_size: level.displayed,
+            hidden_size_estimate: executed - level.displayed,
+            fills: level.fills.len(),
+            duration_secs: (at - first_fill).num_milliseconds() as f64 / 1000.0,
+        };
+        drop(level);
+
+        Some(self.detection(exchange, symbol, &key, at, details))
+    }
+
+    fn detection(
+        &self,
+        exchange: &str,
+        symbol: &str,
+        key: &str,
+        at: DateTime<Utc>,
+        details: IcebergDetails,
+    ) -> AnomalyDetection {
+        let multiple = details.executed_size / details.displayed_size;
+        let severity = if multiple >= self.config.hidden_size_multiple * 3.0 {
+            AnomalySeverity::High
+        } else {
+            AnomalySeverity::Medium
+        };
+
+        let reason = AnomalyReason::new(
+            exchange,
+            symbol,
+            details.executed_size,
+            ReasonKind::Iceberg {
+                side: details.side,
+                level_price: details.level_price,
+                fills: details.fills,
+                displayed_size: details.displayed_size,
+                hidden_size_estimate: details.hidden_size_estimate,
+            },
+        )
+        .with_baseline(details.displayed_size)
+        .with_threshold(details.displayed_size * self.config.hidden_size_multiple)
+        .with_window_secs(details.duration_secs);
+        let description = reason.to_string();
+        info!("{}", description);
+
+        AnomalyDetection {
+            id: uuid::Uuid::new_v4(),
+            timestamp: at,
+            symbol: symbol.to_string(),
+            exchange: exchange.to_string(),
+            anomaly_type: AnomalyType::LargeOrder,
+            confidence: severity.confidence_floor(),
+            severity,
+            metrics: AnomalyMetrics {
+                current_value: details.executed_size,
+                expected_value: details.displayed_size,
+                deviation: details.hidden_size_estimate,
+                z_score: None,
+                percentage_change: Some((multiple - 1.0) * 100.0),
+                historical_avg: None,
+                historical_std: None,
+                ..Default::default()
+            },
+            description,
+            reason: Some(reason),
+            warmup: self
+                .warmup
+                .as_ref()
+                .is_some_and(|w| w.is_warming_up(key, at)),
+            details: Some(DetectionDetails::Iceberg(details)),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn book(bid: f64, ask: f64, size: f64) -> TopOfBook {
+        TopOfBook {
+            bid_price: bid,
+            bid_size: size,
+            ask_price: ask,
+            ask_size: size,
+        }
+    }
+
+    #[test]
+    fn test_flags_refilling_level_once() {
+        let detector = IcebergDetector::new(IcebergConfig::default());
+        let start = Utc::now();
+        let mut detections = Vec::new();
+
+        // The ask keeps showing 1.0 at 100.0 while buyers lift it again and again
+        for i in 0..10 {
+            detector.update_book("binance", "BTC/USDT", book(99.9, 100.0, 1.0));
+            let at = start + Duration::seconds(i * 2);
+            detections.extend(detector.record_trade("binance", "BTC/USDT", 100.0, 0.9, at));
+        }
+
+        assert_eq!(detections.len(), 1);
+        let detection = &detections[0];
+        assert!(matches!(detection.anomaly_type, AnomalyType::LargeOrder));
+        let Some(DetectionDetails::Iceberg(details)) = &detection.details else {
+            panic!("missing iceberg details");
+        };
+        assert_eq!(details.side, BookSide::Ask);
+        assert_eq!(details.level_price, 100.0);
+        assert_eq!(details.fills, 5);
+        assert!((details.hidden_size_estimate - 3.5).abs() < 1e-9);
+        assert_eq!(details.duration_secs, 8.0);
+    }
+
+    #[test]
+    fn test_ignores_sweep_through_levels() {
+        let detector = IcebergDetector::new(IcebergConfig::default());
+        let start = Utc::now();
+        let mut detections = Vec::new();
+
+        // Each level is consumed once and the ask moves up, so nothing is ever hidden
+        for i in 0..10 {
+            let ask = 100.0 + i as f64 * 0.5;
+            detector.update_book("binance", "BTC/USDT", book(ask - 0.1, ask, 1.0));
+            for j in 0..2 {
+                let at = start + Duration::milliseconds(i * 200 + j * 50);
+                detections.extend(detector.record_trade("binance", "BTC/USDT", ask, 0.5, at));
+            }
+        }
+
+        assert!(detections.is_empty());
+    }
+
+    #[test]
+    fn test_resets_when_price_moves_away() {
+        let detector = IcebergDetector::new(IcebergConfig::default());
+        let start = Utc::now();
+
+        detector.update_book("binance", "BTC/USDT", book(99.9, 100.0, 1.0));
+        for i in 0..4 {
+            let at = start + Duration::seconds(i);
+            assert!(detector.record_trade("binance", "BTC/USDT", 100.0, 1.0, at).is_none());
+        }
+
+        // Ask lifts away and comes back, the earlier fills no longer count
+        detector.update_book("binance", "BTC/USDT", book(100.0, 100.5, 1.0));
+        detector.update_book("binance", "BTC/USDT", book(99.9, 100.0, 1.0));
+        for i in 4..8 {
+            let at = start + Duration::seconds(i);
+            assert!(detector.record_trade("binance", "BTC/USDT", 100.0, 1.0, at).is_none());
+        }
+        let at = start + Duration::seconds(8);
+        assert!(detector.record_trade("binance", "BTC/USDT", 100.0, 1.0, at).is_some());
+    }
+
+    #[test]
+    fn test_fills_outside_window_expire() {
+        let config = IcebergConfig {
+            window_secs: 10,
+            ..Default::default()
+        };
+        let detector = IcebergDetector::new(config);
+        let start = Utc::now();
+
+        detector.update_book("binance", "BTC/USDT", book(99.9, 100.0, 1.0));
+        for i in 0..10 {
+            let at = start + Duration::seconds(i * 5);
+            assert!(detector.record_trade("binance", "BTC/USDT", 99.9, 1.0, at).is_none());
+        }
+    }
+}
@@ -1,13 +1,48 @@
+pub mod activity;
+pub mod candles;
+pub mod correlation_break;
+pub mod correlator;
 pub mod detector;
+pub mod escalation;
+pub mod external;
+pub mod funding;
+pub mod iceberg;
+pub mod liquidation;
 pub mod metrics;
+pub mod momentum;
+pub mod open_interest;
+pub mod reason;
+pub mod seasonal;
+pub mod snapshot;
+pub mod stats;
+pub mod suppression;
+pub mod threshold;
+pub mod update_rate;
 pub mod analyzer;
+pub mod backtest;
+pub mod volatility;
+pub mod warmup;
 
 use chrono::{DateTime, Utc};
-use monitor_core::{AnomalyType, MonitorError, Result};
+use monitor_core::{
+    model::{Candle, OrderBook},
+    AnomalyType, MonitorError, Result, SeasonalityConfig, SeverityThresholds,
+};
+// Lives with AnomalyType so the notification routing config can name it
+pub use monitor_core::AnomalySeverity;
+// Detector settings read from the monitor config
+pub use monitor_core::{
+    BollingerConfig, CandleConfig, CandleRangeConfig, CandleVolumeConfig, EwmaAnomalyConfig,
+    RateOfChangeConfig, UpdateRateConfig, VolatilityRegimeConfig, WindowEstimator,
+};
 use serde::{Deserialize, Serialize};
+use snapshot::DetectorState;
 use std::collections::VecDeque;
+use tracing::debug;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+// Defaults let detectors spell out only the fields they set, a new optional field then needs
+// no edit to every literal
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AnomalyDetection {
     pub id: uuid::Uuid,
     pub timestamp: DateTime<Utc>,
@@ -15,19 +50,99 @@ pub struct AnomalyDetection {
     pub exchange: String,
     pub anomaly_type: AnomalyType,
     pub severity: AnomalySeverity,
+    // 0.0 to 1.0, how far past its threshold the observation was
+    #[serde(default)]
+    pub confidence: f64,
     pub metrics: AnomalyMetrics,
     pub description: String,
+    // What the detector rendered `description` from, `None` for external detections
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<reason::AnomalyReason>,
+    // Detected while the system was still warming up, not acted on
+    #[serde(default)]
+    pub warmup: bool,
+    // Detector-specific context beyond the common metrics
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<DetectionDetails>,
+    // Set when repeated detections raised `severity` above what the detector reported
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub escalation: Option<escalation::Escalation>,
+    // External system that published the detection, `None` for our own detectors
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum AnomalySeverity {
-    Low,
-    Medium,
-    High,
-    Critical,
+#[serde(tag = "kind", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum DetectionDetails {
+    Iceberg(iceberg::IcebergDetails),
+    LargeTrade(detector::LargeTradeDetails),
+    Divergence(detector::DivergenceDetails),
+    External(external::ExternalDetails),
+    Liquidation(liquidation::LiquidationDetails),
+    Funding(funding::FundingDetails),
+    OpenInterest(open_interest::OpenInterestDetails),
+    CorrelationBreak(correlation_break::CorrelationBreakDetails),
+    Bollinger(detector::BollingerDetails),
+    RateOfChange(momentum::RateOfChangeDetails),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+// Half confidence right at the threshold, approaching 1.0 as the observation moves past it:
+// 0.75 at twice the threshold and 0.9 at five times
+pub fn confidence_from_exceedance(value: f64, threshold: f64) -> f64 {
+    if threshold <= 0.0 || !value.is_finite() || value <= 0.0 {
+        return 0.0;
+    }
+    (1.0 - threshold / (2.0 * value)).clamp(0.0, 1.0)
+}
+
+// Short form of a length of time for descriptions: 60 -> "1m", 3600 -> "1h", 90 -> "90s"
+pub(crate) fn interval_label(secs: u64) -> String {
+    match secs {
+        0 => "0s".to_string(),
+        _ if secs % 3600 == 0 => format!("{}h", secs / 3600),
+        _ if secs % 60 == 0 => format!("{}m", secs / 60),
+        _ => format!("{}s", secs),
+    }
+}
+
+// For detectors graded on configured bands of their own measure rather than on confidence
+pub fn severity_from_thresholds(value: f64, thresholds: &SeverityThresholds) -> AnomalySeverity {
+    let value = value.abs();
+    if value >= thresholds.critical() {
+        AnomalySeverity::Critical
+    } else if value >= thresholds.high() {
+        AnomalySeverity::High
+    } else if value >= thresholds.medium() {
+        AnomalySeverity::Medium
+    } else {
+        AnomalySeverity::Low
+    }
+}
+
+// For checks that used to report a fixed severity: graded from confidence like the rest, and
+// logged when that comes out below the severity the check used to report
+pub fn graded_severity(check: &str, nominal: AnomalySeverity, confidence: f64) -> AnomalySeverity {
+    let severity = severity_from_confidence(confidence);
+    if severity < nominal {
+        debug!(
+            "{} graded {:?} rather than {:?} at confidence {:.2}",
+            check, severity, nominal, confidence
+        );
+    }
+    severity
+}
+
+// Shared by every detector that reports a confidence, so severities compare across detectors
+pub fn severity_from_confidence(confidence: f64) -> AnomalySeverity {
+    [AnomalySeverity::Critical, AnomalySeverity::High, AnomalySeverity::Medium]
+        .into_iter()
+        .find(|severity| confidence >= severity.confidence_floor())
+        .unwrap_or(AnomalySeverity::Low)
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AnomalyMetrics {
     pub current_value: f64,
     pub expected_value: f64,
@@ -36,6 +151,22 @@ pub struct AnomalyMetrics {
     pub percentage_change: Option<f64>,
     pub historical_avg: Option<f64>,
     pub historical_std: Option<f64>,
+    // Set by detectors that judge the series on several timeframes, finest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub timeframe_z_scores: Vec<TimeframeZScore>,
+    // Trades in a repeated pattern, set by the wash-trading check
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repetitions: Option<usize>,
+    // Set by the volatility regime detector
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regime: Option<volatility::VolatilityRegime>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimeframeZScore {
+    pub timeframe_secs: u64,
+    // `None` while the timeframe has too few bars to judge
+    pub z_score: Option<f64>,
 }
 
 #[derive(Debug, Clone)]
@@ -44,12 +175,77 @@ pub struct TimeSeriesData {
     pub value: f64,
 }
 
+#[derive(Debug, Clone)]
+pub struct TradeSample {
+    pub timestamp: DateTime<Utc>,
+    pub price: f64,
+    pub volume: f64,
+}
+
+// The side of a trade a detector watches, when it is fed from trades
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Series {
+    Price,
+    Volume,
+}
+
+impl Series {
+    pub fn sample(self, trade: &TradeSample) -> TimeSeriesData {
+        TimeSeriesData {
+            timestamp: trade.timestamp,
+            value: match self {
+                Series::Price => trade.price,
+                Series::Volume => trade.volume,
+            },
+        }
+    }
+}
+
+// Best bid and ask, from an L1 update or the top of a depth snapshot
+#[derive(Debug, Clone)]
+pub struct QuoteSample {
+    pub timestamp: DateTime<Utc>,
+    pub bid_price: f64,
+    pub bid_size: f64,
+    pub ask_price: f64,
+    pub ask_size: f64,
+}
+
+impl QuoteSample {
+    pub fn from_book(book: &OrderBook) -> Option<Self> {
+        let bid = book
+            .bids
+            .iter()
+            .filter(|l| l.quantity > 0.0)
+            .max_by(|a, b| a.price.total_cmp(&b.price))?;
+        let ask = book
+            .asks
+            .iter()
+            .filter(|l| l.quantity > 0.0)
+            .min_by(|a, b| a.price.total_cmp(&b.price))?;
+        Some(Self {
+            timestamp: book.timestamp,
+            bid_price: bid.price,
+            bid_size: bid.quantity,
+            ask_price: ask.price,
+            ask_size: ask.quantity,
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TimeSeriesWindow {
     pub data: VecDeque<TimeSeriesData>,
     pub max_size: usize,
-    pub sum: f64,
-    pub sum_squared: f64,
+    // Welford's running mean and sum of squared deviations, a plain sum of squares cancels
+    // catastrophically for prices in the tens of thousands
+    pub running_mean: f64,
+    pub m2: f64,
+    // Removals slowly accumulate rounding error, so the moments are recomputed from the
+    // window once per full turnover
+    evictions: usize,
+    // The same values kept sorted, only for windows built `with_quantiles`
+    sorted: Option<SortedBlocks>,
 }
 
 impl TimeSeriesWindow {
@@ -57,29 +253,76 @@ impl TimeSeriesWindow {
         Self {
             data: VecDeque::with_capacity(max_size),
             max_size,
-            sum: 0.0,
-            sum_squared: 0.0,
+            running_mean: 0.0,
+            m2: 0.0,
+            evictions: 0,
+            sorted: None,
+        }
+    }
+    
+    // Also keeps the values sorted for `quantile`, updates and lookups cost about the square
+    // root of the window rather than its size
+    pub fn with_quantiles(max_size: usize) -> Self {
+        Self {
+            sorted: Some(SortedBlocks::default()),
+            ..Self::new(max_size)
         }
     }
     
     pub fn push(&mut self, point: TimeSeriesData) {
         if self.data.len() >= self.max_size {
             if let Some(old) = self.data.pop_front() {
-                self.sum -= old.value;
-                self.sum_squared -= old.value * old.value;
+                self.remove(old.value);
+                self.evictions += 1;
+                if let Some(sorted) = &mut self.sorted {
+                    sorted.remove(old.value);
+                }
             }
         }
         
-        self.sum += point.value;
-        self.sum_squared += point.value * point.value;
+        let value = point.value;
+        if let Some(sorted) = &mut self.sorted {
+            sorted.insert(value);
+        }
         self.data.push_back(point);
+        let delta = value - self.running_mean;
+        self.running_mean += delta / self.data.len() as f64;
+        self.m2 += delta * (value - self.running_mean);
+        
+        if self.evictions >= self.max_size {
+            self.recompute();
+        }
+    }
+    
+    // Reverses one Welford step, `data` already holds the remaining points
+    fn remove(&mut self, value: f64) {
+        let n = self.data.len();
+        if n == 0 {
+            self.running_mean = 0.0;
+            self.m2 = 0.0;
+            return;
+        }
+        let delta = value - self.running_mean;
+        self.running_mean -= delta / n as f64;
+        self.m2 = (self.m2 - delta * (value - self.running_mean)).max(0.0);
+    }
+    
+    fn recompute(&mut self) {
+        let n = self.data.len() as f64;
+        self.running_mean = self.data.iter().map(|p| p.value).sum::<f64>() / n;
+        self.m2 = self
+            .data
+            .iter()
+            .map(|p| (p.value - self.running_mean).powi(2))
+            .sum();
+        self.evictions = 0;
     }
     
     pub fn mean(&self) -> f64 {
         if self.data.is_empty() {
             0.0
         } else {
-            self.sum / self.data.len() as f64
+            self.running_mean
         }
     }
     
@@ -87,9 +330,22 @@ impl TimeSeriesWindow {
         if self.data.len() < 2 {
             0.0
         } else {
-            let mean = self.mean();
-            let variance = (self.sum_squared / self.data.len() as f64) - (mean * mean);
-            variance.max(0.0).sqrt()
+            (self.m2 / self.data.len() as f64).max(0.0).sqrt()
+        }
+    }
+    
+    pub fn values(&self) -> Vec<f64> {
+        self.data.iter().map(|p| p.value).collect()
+    }
+    
+    // Replaces the window with saved values, oldest first, all stamped with the save time
+    pub fn restore(&mut self, values: &[f64], at: DateTime<Utc>) {
+        *self = match self.sorted {
+            Some(_) => Self::with_quantiles(self.max_size),
+            None => Self::new(self.max_size),
+        };
+        for &value in values {
+            self.push(TimeSeriesData { timestamp: at, value });
         }
     }
     
@@ -109,11 +365,314 @@ impl TimeSeriesWindow {
     pub fn is_empty(&self) -> bool {
         self.data.is_empty()
     }
+    
+    // Share of the window holding points, 1.0 once full
+    pub fn fill(&self) -> f64 {
+        self.data.len() as f64 / self.max_size.max(1) as f64
+    }
+    
+    // Nearest-rank quantile, `q` from 0.0 to 1.0. `None` for an empty window or one built
+    // without `with_quantiles`
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        let sorted = self.sorted.as_ref().filter(|s| !s.is_empty())?;
+        if !(0.0..=1.0).contains(&q) {
+            return None;
+        }
+        let rank = (q * sorted.len() as f64).ceil() as usize;
+        Some(sorted.nth(rank.clamp(1, sorted.len()) - 1))
+    }
+    
+    // Percentage of the window at or below `value`, the inverse of `quantile`. Same `None`
+    // cases as `quantile`
+    pub fn percentile_rank(&self, value: f64) -> Option<f64> {
+        let sorted = self.sorted.as_ref().filter(|s| !s.is_empty())?;
+        Some(sorted.at_or_below(value) as f64 / sorted.len() as f64 * 100.0)
+    }
+}
+
+// Values kept sorted in consecutive blocks of up to `2 * SORTED_BLOCK`. An update shifts within
+// one block and a rank lookup steps over whole blocks, instead of shifting or scanning them all
+const SORTED_BLOCK: usize = 64;
+
+#[derive(Debug, Clone, Default)]
+struct SortedBlocks {
+    // Never empty, each block's values sort before the next block's
+    blocks: Vec<Vec<f64>>,
+    len: usize,
+}
+
+impl SortedBlocks {
+    // The first block ending at or above `value`, where its first copy is or would go
+    fn block_for(&self, value: f64) -> usize {
+        let index = self
+            .blocks
+            .partition_point(|block| block[block.len() - 1].total_cmp(&value).is_lt());
+        index.min(self.blocks.len().saturating_sub(1))
+    }
+
+    fn insert(&mut self, value: f64) {
+        if self.blocks.is_empty() {
+            self.blocks.push(Vec::with_capacity(2 * SORTED_BLOCK));
+        }
+        let index = self.block_for(value);
+        let block = &mut self.blocks[index];
+        let at = block.partition_point(|v| v.total_cmp(&value).is_lt());
+        block.insert(at, value);
+        if block.len() >= 2 * SORTED_BLOCK {
+            let upper = block.split_off(SORTED_BLOCK);
+            self.blocks.insert(index + 1, upper);
+        }
+        self.len += 1;
+    }
+
+    fn remove(&mut self, value: f64) {
+        if self.blocks.is_empty() {
+            return;
+        }
+        let index = self.block_for(value);
+        let block = &mut self.blocks[index];
+        let at = block.partition_point(|v| v.total_cmp(&value).is_lt());
+        if block.get(at).is_some_and(|v| v.total_cmp(&value).is_eq()) {
+            block.remove(at);
+            self.len -= 1;
+            if block.is_empty() {
+                self.blocks.remove(index);
+            }
+        }
+    }
+
+    // The value at 0-based `rank` in ascending order, `rank` below `len`
+    fn nth(&self, mut rank: usize) -> f64 {
+        for block in &self.blocks {
+            if rank < block.len() {
+                return block[rank];
+            }
+            rank -= block.len();
+        }
+        f64::NAN
+    }
+
+    fn at_or_below(&self, value: f64) -> usize {
+        let mut count = 0;
+        for block in &self.blocks {
+            if block[block.len() - 1].total_cmp(&value).is_le() {
+                count += block.len();
+            } else {
+                return count + block.partition_point(|v| v.total_cmp(&value).is_le());
+            }
+        }
+        count
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+// Rolling window kept sorted alongside arrival order, for median and MAD without re-sorting.
+// Updates are a binary search plus a shift, MAD is one merge walk out from the median.
+#[derive(Debug, Clone)]
+pub struct RollingOrderStats {
+    window: VecDeque<f64>,
+    sorted: Vec<f64>,
+    max_size: usize,
+}
+
+impl RollingOrderStats {
+    pub fn new(max_size: usize) -> Self {
+        Self {
+            window: VecDeque::with_capacity(max_size),
+            sorted: Vec::with_capacity(max_size),
+            max_size: max_size.max(1),
+        }
+    }
+
+    pub fn push(&mut self, value: f64) {
+        if self.window.len() >= self.max_size {
+            if let Some(oldest) = self.window.pop_front() {
+                let index = self.sorted.partition_point(|v| *v < oldest);
+                self.sorted.remove(index);
+            }
+        }
+        self.window.push_back(value);
+        let index = self.sorted.partition_point(|v| *v < value);
+        self.sorted.insert(index, value);
+    }
+
+    pub fn median(&self) -> f64 {
+        let n = self.sorted.len();
+        match n {
+            0 => 0.0,
+            _ if n % 2 == 1 => self.sorted[n / 2],
+            _ => (self.sorted[n / 2 - 1] + self.sorted[n / 2]) / 2.0,
+        }
+    }
+
+    // Median absolute deviation from the median. Deviations grow walking outward from the
+    // median on either side, so merging the two walks visits them in ascending order.
+    pub fn mad(&self) -> f64 {
+        let n = self.sorted.len();
+        if n == 0 {
+            return 0.0;
+        }
+        let median = self.median();
+        let split = self.sorted.partition_point(|v| *v < median);
+        let (mut left, mut right) = (split, split);
+        let (lower, upper) = ((n - 1) / 2, n / 2);
+        let mut lower_value = 0.0;
+        for seen in 0..=upper {
+            let deviation = if left > 0
+                && (right == n || median - self.sorted[left - 1] <= self.sorted[right] - median)
+            {
+                left -= 1;
+                median - self.sorted[left]
+            } else {
+                right += 1;
+                self.sorted[right - 1] - median
+            };
+            if seen == lower {
+                lower_value = deviation;
+            }
+            if seen == upper {
+                return (lower_value + deviation) / 2.0;
+            }
+        }
+        0.0
+    }
+
+    pub fn values(&self) -> Vec<f64> {
+        self.window.iter().copied().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.window.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.window.is_empty()
+    }
+
+    pub fn fill(&self) -> f64 {
+        self.window.len() as f64 / self.max_size.max(1) as f64
+    }
+
+    pub fn clear(&mut self) {
+        self.window.clear();
+        self.sorted.clear();
+    }
 }
 
 pub trait AnomalyDetector: Send + Sync {
     fn detect(&mut self, data: &TimeSeriesData) -> Option<AnomalyDetection>;
     fn reset(&mut self);
+
+    // Name the detector's history is saved under across restarts, `None` when it keeps none
+    fn state_name(&self) -> Option<&'static str> {
+        None
+    }
+    fn snapshot(&self) -> DetectorState {
+        DetectorState::default()
+    }
+    fn restore(&mut self, _state: &DetectorState, _saved_at: DateTime<Utc>) {}
+    // Label the detector's runtime statistics are reported under
+    fn kind(&self) -> String;
+    // Share of the detector's window holding samples, `None` when it keeps none
+    fn window_fill(&self) -> Option<f64> {
+        None
+    }
+    fn series(&self) -> Series {
+        Series::Price
+    }
+}
+
+// Detectors that need each trade's size as well as its price
+pub trait TradeAnomalyDetector: Send + Sync {
+    fn detect_trade(&mut self, trade: &TradeSample) -> Option<AnomalyDetection>;
+    fn reset(&mut self);
+
+    fn state_name(&self) -> Option<&'static str> {
+        None
+    }
+    fn snapshot(&self) -> DetectorState {
+        DetectorState::default()
+    }
+    fn restore(&mut self, _state: &DetectorState, _saved_at: DateTime<Utc>) {}
+    fn kind(&self) -> String;
+    fn window_fill(&self) -> Option<f64> {
+        None
+    }
+}
+
+// Detectors that judge completed candles, fed by a `candles::CandleAggregator`
+pub trait CandleAnomalyDetector: Send + Sync {
+    fn detect_candle(&mut self, candle: &Candle) -> Option<AnomalyDetection>;
+    fn reset(&mut self);
+}
+
+// Detectors that look at full order book snapshots rather than a single series
+pub trait BookAnomalyDetector: Send + Sync {
+    fn detect_book(&mut self, book: &OrderBook) -> Option<AnomalyDetection>;
+    fn reset(&mut self);
+
+    fn kind(&self) -> String;
+    fn window_fill(&self) -> Option<f64> {
+        None
+    }
+}
+
+// Detectors that only need the best bid and ask
+pub trait QuoteAnomalyDetector: Send + Sync {
+    fn detect_quote(&mut self, quote: &QuoteSample) -> Option<AnomalyDetection>;
+    fn reset(&mut self);
+
+    fn state_name(&self) -> Option<&'static str> {
+        None
+    }
+    fn snapshot(&self) -> DetectorState {
+        DetectorState::default()
+    }
+    fn restore(&mut self, _state: &DetectorState, _saved_at: DateTime<Utc>) {}
+    fn kind(&self) -> String;
+    fn window_fill(&self) -> Option<f64> {
+        None
+    }
+}
+
+fn default_modified_z_threshold() -> f64 {
+    3.5
+}
+
+fn default_required_confirmations() -> usize {
+    2
+}
+
+// The cutoffs each detector graded on before they were configurable
+fn default_z_score_severity() -> SeverityThresholds {
+    SeverityThresholds::new(3.0, 4.0, 5.0).expect("z-score bands increase")
+}
+
+fn default_percentage_severity() -> SeverityThresholds {
+    SeverityThresholds::new(5.0, 7.0, 10.0).expect("percentage bands increase")
+}
+
+fn default_large_trade_severity() -> SeverityThresholds {
+    SeverityThresholds::new(2.0, 5.0, 10.0).expect("large trade bands increase")
+}
+
+fn default_depth_imbalance_severity() -> SeverityThresholds {
+    SeverityThresholds::new(1.5, 2.0, 3.0).expect("depth imbalance bands increase")
+}
+
+fn default_divergence_severity() -> SeverityThresholds {
+    SeverityThresholds::new(1.5, 2.0, 4.0).expect("divergence bands increase")
+}
+
+fn default_spread_severity() -> SeverityThresholds {
+    SeverityThresholds::new(3.0, 5.0, 10.0).expect("spread bands increase")
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -122,6 +681,25 @@ pub struct VolumeAnomalyConfig {
     pub min_percentage_change: f64,
     pub window_size: usize,
     pub min_samples: usize,
+    #[serde(default)]
+    pub estimator: WindowEstimator,
+    // Threshold used instead of `z_score_threshold` with the median_mad estimator
+    #[serde(default = "default_modified_z_threshold")]
+    pub modified_z_threshold: f64,
+    // Severity by z-score
+    #[serde(default = "default_z_score_severity")]
+    pub z_score_severity: SeverityThresholds,
+    // Baselines per UTC hour of day, used over the window once they have enough days
+    #[serde(default)]
+    pub seasonality: SeasonalityConfig,
+    // What flags a volume, the z-score, the window percentile or a combination, with the
+    // mean_std estimator
+    #[serde(default)]
+    pub trigger: VolumeTrigger,
+    // Volumes above this percentile of the window, before they join it, pass the percentile
+    // trigger
+    #[serde(default = "default_volume_percentile")]
+    pub percentile: f64,
 }
 
 impl Default for VolumeAnomalyConfig {
@@ -131,16 +709,60 @@ impl Default for VolumeAnomalyConfig {
             min_percentage_change: 200.0,
             window_size: 60,
             min_samples: 30,
+            estimator: WindowEstimator::MeanStd,
+            modified_z_threshold: default_modified_z_threshold(),
+            z_score_severity: default_z_score_severity(),
+            seasonality: SeasonalityConfig::default(),
+            trigger: VolumeTrigger::ZScore,
+            percentile: default_volume_percentile(),
         }
     }
 }
 
+// `min_percentage_change` applies whichever trigger is used
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VolumeTrigger {
+    #[default]
+    ZScore,
+    // For volumes too heavy-tailed for a z-score to mean much
+    Percentile,
+    Either,
+    Both,
+}
+
+impl VolumeTrigger {
+    pub fn uses_percentile(&self) -> bool {
+        !matches!(self, VolumeTrigger::ZScore)
+    }
+}
+
+fn default_volume_percentile() -> f64 {
+    99.0
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PriceAnomalyConfig {
     pub percentage_threshold: f64,
     pub z_score_threshold: f64,
     pub window_size: usize,
     pub min_samples: usize,
+    #[serde(default)]
+    pub estimator: WindowEstimator,
+    #[serde(default = "default_modified_z_threshold")]
+    pub modified_z_threshold: f64,
+    // Bar lengths in seconds, e.g. [1, 60, 300]. When set, a price only alerts once
+    // `required_confirmations` of them judge it anomalous; `window_size` and `min_samples` then
+    // count bars of each timeframe.
+    #[serde(default)]
+    pub timeframes_secs: Vec<u64>,
+    #[serde(default = "default_required_confirmations")]
+    pub required_confirmations: usize,
+    // Severity by percent move and by z-score, the higher of the two applies
+    #[serde(default = "default_percentage_severity")]
+    pub percentage_severity: SeverityThresholds,
+    #[serde(default = "default_z_score_severity")]
+    pub z_score_severity: SeverityThresholds,
 }
 
 impl Default for PriceAnomalyConfig {
@@ -150,6 +772,344 @@ impl Default for PriceAnomalyConfig {
             z_score_threshold: 3.0,
             window_size: 60,
             min_samples: 30,
+            estimator: WindowEstimator::MeanStd,
+            modified_z_threshold: default_modified_z_threshold(),
+            timeframes_secs: Vec::new(),
+            required_confirmations: default_required_confirmations(),
+            percentage_severity: default_percentage_severity(),
+            z_score_severity: default_z_score_severity(),
         }
     }
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LargeTradeConfig {
+    // Trade notional (price * volume) flagged regardless of the symbol's history, 0 disables
+    pub absolute_threshold: f64,
+    // Percentile of recent trade notionals a trade must exceed
+    pub percentile: f64,
+    pub window_size: usize,
+    pub min_samples: usize,
+    // Severity by multiples of the threshold the trade passed, never under Medium past the
+    // absolute one
+    #[serde(default = "default_large_trade_severity")]
+    pub severity: SeverityThresholds,
+}
+
+impl Default for LargeTradeConfig {
+    fn default() -> Self {
+        Self {
+            absolute_threshold: 1_000_000.0,
+            percentile: 99.9,
+            window_size: 2000,
+            min_samples: 200,
+            severity: default_large_trade_severity(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DepthImbalanceConfig {
+    // Levels per side summed from the top of the book
+    pub levels: usize,
+    // Larger side volume over smaller side volume that counts as imbalanced
+    pub ratio_threshold: f64,
+    // Books with less notional over both sides are too thin to judge
+    pub min_notional: f64,
+    // Severity by multiples of `ratio_threshold`
+    #[serde(default = "default_depth_imbalance_severity")]
+    pub severity: SeverityThresholds,
+}
+
+impl Default for DepthImbalanceConfig {
+    fn default() -> Self {
+        Self {
+            levels: 10,
+            ratio_threshold: 3.0,
+            min_notional: 10_000.0,
+            severity: default_depth_imbalance_severity(),
+        }
+    }
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrossExchangeDivergenceConfig {
+    // Spread between the highest and lowest exchange price, in basis points of their midpoint
+    pub threshold_bps: f64,
+    // How long the spread has to stay above the threshold before it's reported
+    pub min_duration_secs: u64,
+    // Prices older than this are left out of the comparison
+    pub max_price_age_secs: u64,
+    // Severity by multiples of `threshold_bps`
+    #[serde(default = "default_divergence_severity")]
+    pub severity: SeverityThresholds,
+}
+
+impl Default for CrossExchangeDivergenceConfig {
+    fn default() -> Self {
+        Self {
+            threshold_bps: 50.0,
+            min_duration_secs: 30,
+            max_price_age_secs: 10,
+            severity: default_divergence_severity(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpreadAnomalyConfig {
+    // Z-score of the relative spread against its recent history
+    pub z_score_threshold: f64,
+    // Spread in basis points of the mid price flagged regardless of history, 0 disables
+    pub max_spread_bps: f64,
+    pub window_size: usize,
+    pub min_samples: usize,
+    // Severity by multiples of the mean spread over the window, or of `max_spread_bps` until the
+    // window has `min_samples`
+    #[serde(default = "default_spread_severity")]
+    pub severity: SeverityThresholds,
+}
+
+impl Default for SpreadAnomalyConfig {
+    fn default() -> Self {
+        Self {
+            z_score_threshold: 4.0,
+            max_spread_bps: 100.0,
+            window_size: 300,
+            min_samples: 60,
+            severity: default_spread_severity(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Prices around 97_000 with up to 0.01% jitter, from a fixed LCG so runs are repeatable
+    fn jittered_prices(count: usize) -> Vec<f64> {
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        (0..count)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6_364_136_223_846_793_005)
+                    .wrapping_add(1_442_695_040_888_963_407);
+                let unit = (state >> 11) as f64 / (1u64 << 53) as f64;
+                97_000.0 * (1.0 + (unit * 2.0 - 1.0) * 1e-4)
+            })
+            .collect()
+    }
+
+    fn reference_std_dev(values: &[f64]) -> f64 {
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        let variance =
+            values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+        variance.sqrt()
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        let relative = ((actual - expected) / expected).abs();
+        assert!(relative < 1e-6, "{} vs {} (relative error {:e})", actual, expected, relative);
+    }
+
+    #[test]
+    fn test_std_dev_is_stable_for_large_prices() {
+        let prices = jittered_prices(10_000);
+        let now = Utc::now();
+        let mut window = TimeSeriesWindow::new(prices.len());
+        for &value in &prices {
+            window.push(TimeSeriesData { timestamp: now, value });
+        }
+
+        assert_close(window.std_dev(), reference_std_dev(&prices));
+        assert_close(window.mean(), prices.iter().sum::<f64>() / prices.len() as f64);
+    }
+
+    #[test]
+    fn test_std_dev_is_stable_across_evictions() {
+        let prices = jittered_prices(10_000);
+        let now = Utc::now();
+        let mut window = TimeSeriesWindow::new(300);
+        for (i, &value) in prices.iter().enumerate() {
+            window.push(TimeSeriesData { timestamp: now, value });
+            if i >= 300 && i % 997 == 0 {
+                assert_close(window.std_dev(), reference_std_dev(&prices[i + 1 - 300..=i]));
+            }
+        }
+
+        assert_eq!(window.len(), 300);
+        assert_close(window.std_dev(), reference_std_dev(&prices[prices.len() - 300..]));
+    }
+
+    fn reference_quantile(values: &[f64], q: f64) -> f64 {
+        let mut sorted = values.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let rank = (q * sorted.len() as f64).ceil() as usize;
+        sorted[rank.clamp(1, sorted.len()) - 1]
+    }
+
+    #[test]
+    fn test_quantiles_match_sorted_window() {
+        // Rounded to a few distinct values so evictions have duplicates to remove
+        let prices: Vec<f64> = jittered_prices(5_000).iter().map(|p| p.round()).collect();
+        let now = Utc::now();
+        let mut window = TimeSeriesWindow::with_quantiles(250);
+        for (i, &value) in prices.iter().enumerate() {
+            window.push(TimeSeriesData { timestamp: now, value });
+            if i % 173 == 0 {
+                let raw = &prices[(i + 1).saturating_sub(250)..=i];
+                for q in [0.0, 0.01, 0.25, 0.5, 0.95, 0.99, 1.0] {
+                    assert_eq!(window.quantile(q), Some(reference_quantile(raw, q)));
+                }
+            }
+        }
+
+        let values = window.values();
+        window.restore(&values, now);
+        assert_eq!(window.quantile(0.99), Some(reference_quantile(&values, 0.99)));
+        assert_eq!(window.quantile(1.5), None);
+        let median = window.quantile(0.5).unwrap();
+        let below = values.iter().filter(|v| **v <= median).count();
+        assert_eq!(window.percentile_rank(median), Some(below as f64 / 250.0 * 100.0));
+        assert_eq!(window.percentile_rank(f64::MAX), Some(100.0));
+        assert_eq!(window.percentile_rank(0.0), Some(0.0));
+
+        let mut plain = TimeSeriesWindow::new(250);
+        plain.push(TimeSeriesData { timestamp: now, value: 1.0 });
+        assert_eq!(plain.quantile(0.5), None);
+        assert_eq!(plain.percentile_rank(1.0), None);
+        assert_eq!(TimeSeriesWindow::with_quantiles(10).quantile(0.5), None);
+    }
+
+    fn reference_median(values: &[f64]) -> f64 {
+        let mut sorted = values.to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let n = sorted.len();
+        if n % 2 == 1 {
+            sorted[n / 2]
+        } else {
+            (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0
+        }
+    }
+
+    #[test]
+    fn test_rolling_median_and_mad_match_sorting() {
+        let prices = jittered_prices(2_000);
+        for size in [1, 2, 7, 60] {
+            let mut stats = RollingOrderStats::new(size);
+            for (i, &value) in prices.iter().enumerate() {
+                stats.push(value);
+                let window = &prices[(i + 1).saturating_sub(size)..=i];
+                let median = reference_median(window);
+                let deviations: Vec<f64> = window.iter().map(|v| (v - median).abs()).collect();
+
+                assert_eq!(stats.len(), window.len());
+                assert_eq!(stats.median(), median);
+                assert_eq!(stats.mad(), reference_median(&deviations));
+            }
+        }
+    }
+
+    #[test]
+    fn test_severity_from_confidence_boundaries() {
+        let cases = [
+            (0.0, AnomalySeverity::Low),
+            (0.4999, AnomalySeverity::Low),
+            (0.5, AnomalySeverity::Medium),
+            (0.7499, AnomalySeverity::Medium),
+            (0.75, AnomalySeverity::High),
+            (0.8999, AnomalySeverity::High),
+            (0.9, AnomalySeverity::Critical),
+            (1.0, AnomalySeverity::Critical),
+        ];
+        for (confidence, severity) in cases {
+            assert_eq!(severity_from_confidence(confidence), severity, "{}", confidence);
+            assert!(severity.confidence_floor() <= confidence);
+            assert_eq!(severity_from_confidence(severity.confidence_floor()), severity);
+        }
+    }
+
+    #[test]
+    fn test_floor_confidence_only_raises() {
+        assert_eq!(AnomalySeverity::High.floor_confidence(0.6), 0.75);
+        assert_eq!(AnomalySeverity::High.floor_confidence(0.8), 0.8);
+        assert_eq!(AnomalySeverity::Low.floor_confidence(0.1), 0.1);
+        let downgraded = graded_severity("test", AnomalySeverity::Critical, 0.6);
+        assert_eq!(downgraded, AnomalySeverity::Medium);
+        let upgraded = graded_severity("test", AnomalySeverity::Low, 0.95);
+        assert_eq!(upgraded, AnomalySeverity::Critical);
+    }
+
+    #[test]
+    fn test_severity_from_thresholds_boundaries() {
+        let bands = SeverityThresholds::new(3.0, 4.0, 5.0).unwrap();
+        let cases = [
+            (0.0, AnomalySeverity::Low),
+            (2.999, AnomalySeverity::Low),
+            (3.0, AnomalySeverity::Medium),
+            (-3.5, AnomalySeverity::Medium),
+            (4.0, AnomalySeverity::High),
+            (4.999, AnomalySeverity::High),
+            (5.0, AnomalySeverity::Critical),
+            (-50.0, AnomalySeverity::Critical),
+        ];
+        for (value, severity) in cases {
+            assert_eq!(severity_from_thresholds(value, &bands), severity, "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_severity_thresholds_must_increase() {
+        assert!(SeverityThresholds::new(3.0, 3.0, 5.0).is_err());
+        assert!(SeverityThresholds::new(3.0, 6.0, 5.0).is_err());
+        assert!(SeverityThresholds::new(-1.0, 6.0, 15.0).is_err());
+        assert!(SeverityThresholds::new(3.0, f64::NAN, 15.0).is_err());
+
+        // Configs are checked as they are read
+        let config: VolumeAnomalyConfig = serde_json::from_value(serde_json::json!({
+            "z_score_threshold": 3.0,
+            "min_percentage_change": 200.0,
+            "window_size": 60,
+            "min_samples": 30,
+            "z_score_severity": { "medium": 3.0, "high": 4.0, "critical": 5.0 },
+        }))
+        .unwrap();
+        assert_eq!(config.z_score_severity.critical(), 5.0);
+        let result = serde_json::from_value::<PriceAnomalyConfig>(serde_json::json!({
+            "percentage_threshold": 5.0,
+            "z_score_threshold": 3.0,
+            "window_size": 60,
+            "min_samples": 30,
+            "percentage_severity": { "medium": 5.0, "high": 10.0, "critical": 7.0 },
+        }));
+        assert!(result.unwrap_err().to_string().contains("must increase"));
+    }
+
+    #[test]
+    fn test_confidence_from_exceedance() {
+        assert_eq!(confidence_from_exceedance(3.0, 3.0), 0.5);
+        assert_eq!(confidence_from_exceedance(6.0, 3.0), 0.75);
+        assert_eq!(confidence_from_exceedance(15.0, 3.0), 0.9);
+        assert_eq!(confidence_from_exceedance(1.0, 3.0), 0.0);
+        assert_eq!(confidence_from_exceedance(3.0, 0.0), 0.0);
+        assert_eq!(confidence_from_exceedance(f64::NAN, 3.0), 0.0);
+        assert!(confidence_from_exceedance(1e12, 3.0) < 1.0);
+
+        // Just under a band boundary stays in the band below
+        assert_eq!(
+            severity_from_confidence(confidence_from_exceedance(5.999, 3.0)),
+            AnomalySeverity::Medium
+        );
+        assert_eq!(
+            severity_from_confidence(confidence_from_exceedance(14.999, 3.0)),
+            AnomalySeverity::High
+        );
+    }
+
+    #[test]
+    fn test_interval_labels() {
+        assert_eq!(interval_label(60), "1m");
+        assert_eq!(interval_label(900), "15m");
+        assert_eq!(interval_label(7200), "2h");
+        assert_eq!(interval_label(45), "45s");
+    }
+}
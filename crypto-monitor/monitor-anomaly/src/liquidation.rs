@@ -0,0 +1,327 @@
+use crate::{
+    reason::{AnomalyReason, ReasonKind},
+    warmup::WarmupTracker,
+    severity_from_thresholds, AnomalyDetection, AnomalyMetrics, DetectionDetails,
+    TimeSeriesData, TimeSeriesWindow,
+};
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use monitor_core::{
+    model::{Liquidation, LiquidationSide},
+    AnomalyType, LiquidationConfig,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::info;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LiquidationDetails {
+    // Side that made up most of the bucket's notional
+    pub direction: LiquidationSide,
+    pub long_notional: f64,
+    pub short_notional: f64,
+    pub liquidations: usize,
+    pub bucket_start: DateTime<Utc>,
+    pub bucket_secs: u64,
+}
+
+struct Bucket {
+    index: i64,
+    start: DateTime<Utc>,
+    long: f64,
+    short: f64,
+    count: usize,
+    flagged: bool,
+}
+
+impl Bucket {
+    fn new(index: i64, start: DateTime<Utc>) -> Self {
+        Self {
+            index,
+            start,
+            long: 0.0,
+            short: 0.0,
+            count: 0,
+            flagged: false,
+        }
+    }
+
+    fn total(&self) -> f64 {
+        self.long + self.short
+    }
+}
+
+struct SymbolBuckets {
+    current: Option<Bucket>,
+    // Totals of completed buckets, quiet ones as zero
+    history: TimeSeriesWindow,
+}
+
+// Sums liquidation notional per exchange and symbol over fixed time buckets and flags a bucket
+// once it passes the notional threshold or stands out against the buckets before it
+pub struct LiquidationAnomalyDetector {
+    config: LiquidationConfig,
+    symbols: DashMap<String, SymbolBuckets>,
+    warmup: Option<Arc<WarmupTracker>>,
+}
+
+impl LiquidationAnomalyDetector {
+    pub fn new(config: LiquidationConfig) -> Self {
+        Self {
+            config,
+            symbols: DashMap::new(),
+            warmup: None,
+        }
+    }
+
+    pub fn with_warmup(mut self, warmup: Arc<WarmupTracker>) -> Self {
+        self.warmup = Some(warmup);
+        self
+    }
+
+    pub fn record(&self, liquidation: &Liquidation) -> Option<AnomalyDetection> {
+        let notional = liquidation.notional();
+        if !self.config.enabled || !notional.is_finite() || notional <= 0.0 {
+            return None;
+        }
+
+        let key = format!("{}:{}", liquidation.exchange, liquidation.symbol);
+        let at = liquidation.timestamp;
+        let bucket_ms = self.config.bucket_secs.max(1) as i64 * 1000;
+        let index = at.timestamp_millis().div_euclid(bucket_ms);
+
+        let mut entry = self.symbols.entry(key.clone()).or_insert_with(|| SymbolBuckets {
+            current: None,
+            history: TimeSeriesWindow::new(self.config.history_buckets),
+        });
+        let state = &mut *entry;
+
+        match &state.current {
+            // Late arrivals for a bucket already closed are dropped
+            Some(bucket) if index < bucket.index => return None,
+            Some(bucket) if index > bucket.index => {
+                let (closed, closed_start) = (bucket.index, bucket.start);
+                state.history.push(TimeSeriesData {
+                    timestamp: closed_start,
+                    value: bucket.total(),
+                });
+                let quiet = (index - closed - 1).min(self.config.history_buckets as i64);
+                for i in 0..quiet {
+                    state.history.push(TimeSeriesData {
+                        timestamp: closed_start + Duration::milliseconds((i + 1) * bucket_ms),
+                        value: 0.0,
+                    });
+                }
+                state.current = None;
+            }
+            _ => {}
+        }
+        let bucket = state.current.get_or_insert_with(|| {
+            let start = at - Duration::milliseconds(at.timestamp_millis().rem_euclid(bucket_ms));
+            Bucket::new(index, start)
+        });
+
+        match liquidation.side {
+            LiquidationSide::Long => bucket.long += notional,
+            LiquidationSide::Short => bucket.short += notional,
+        }
+        bucket.count += 1;
+
+        let total = bucket.total();
+        let history = &state.history;
+        let z_score = (history.len() >= self.config.min_buckets && history.std_dev() > 0.0)
+            .then(|| history.z_score(total));
+        let over_cap = total >= self.config.notional_threshold;
+        let z_hit = z_score.is_some_and(|z| z >= self.config.z_score_threshold);
+        if bucket.flagged || !(over_cap || z_hit) {
+            return None;
+        }
+        bucket.flagged = true;
+
+        let details = LiquidationDetails {
+            direction: if bucket.long >= bucket.short {
+                LiquidationSide::Long
+            } else {
+                LiquidationSide::Short
+            },
+            long_notional: bucket.long,
+            short_notional: bucket.short,
+            liquidations: bucket.count,
+            bucket_start: bucket.start,
+            bucket_secs: self.config.bucket_secs,
+        };
+        let baseline = (!history.is_empty()).then(|| (history.mean(), history.std_dev()));
+        drop(entry);
+
+        Some(self.detection(liquidation, &key, z_score, baseline, details))
+    }
+
+    fn detection(
+        &self,
+        liquidation: &Liquidation,
+        key: &str,
+        z_score: Option<f64>,
+        baseline: Option<(f64, f64)>,
+        details: LiquidationDetails,
+    ) -> AnomalyDetection {
+        let total = details.long_notional + details.short_notional;
+        let expected = baseline.map_or(0.0, |(mean, _)| mean);
+
+        // How far past whichever trigger fired, in multiples of it
+        let multiple = (total / self.config.notional_threshold)
+            .max(z_score.map_or(0.0, |z| z / self.config.z_score_threshold));
+        let severity = severity_from_thresholds(multiple, &self.config.severity);
+
+        let reason = AnomalyReason::new(
+            &liquidation.exchange,
+            &liquidation.symbol,
+            total,
+            ReasonKind::Liquidation {
+                direction: details.direction,
+                long_notional: details.long_notional,
+                short_notional: details.short_notional,
+                liquidations: details.liquidations,
+                z_score,
+            },
+        )
+        .with_baseline(expected)
+        .with_threshold(self.config.notional_threshold)
+        .with_window_secs(details.bucket_secs as f64);
+        let description = reason.to_string();
+        info!("{}", description);
+
+        AnomalyDetection {
+            id: uuid::Uuid::new_v4(),
+            timestamp: liquidation.timestamp,
+            symbol: liquidation.symbol.clone(),
+            exchange: liquidation.exchange.clone(),
+            anomaly_type: AnomalyType::LiquidationCascade,
+            confidence: severity.confidence_floor(),
+            severity,
+            metrics: AnomalyMetrics {
+                current_value: total,
+                expected_value: expected,
+                deviation: total - expected,
+                z_score,
+                percentage_change: (expected > 0.0).then(|| (total - expected) / expected * 100.0),
+                historical_avg: baseline.map(|(mean, _)| mean),
+                historical_std: baseline.map(|(_, std_dev)| std_dev),
+                ..Default::default()
+            },
+            description,
+            reason: Some(reason),
+            warmup: self
+                .warmup
+                .as_ref()
+                .is_some_and(|w| w.is_warming_up(key, liquidation.timestamp)),
+            details: Some(DetectionDetails::Liquidation(details)),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AnomalySeverity;
+    use chrono::TimeZone;
+
+    fn liquidation(side: LiquidationSide, notional: f64, at: DateTime<Utc>) -> Liquidation {
+        Liquidation {
+            exchange: "binance".to_string(),
+            symbol: "BTC/USDT".to_string(),
+            timestamp: at,
+            side,
+            price: 50_000.0,
+            quantity: notional / 50_000.0,
+        }
+    }
+
+    fn start() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap()
+    }
+
+    fn bucket(i: i64) -> DateTime<Utc> {
+        start() + Duration::seconds(i * 60)
+    }
+
+    #[test]
+    fn test_burst_after_quiet_periods_flags_bucket_once() {
+        let detector = LiquidationAnomalyDetector::new(LiquidationConfig::default());
+
+        // A few small liquidations every fifth minute, nothing in between
+        for b in (0..31).step_by(5) {
+            for (i, side) in [LiquidationSide::Long, LiquidationSide::Short, LiquidationSide::Long]
+                .into_iter()
+                .enumerate()
+            {
+                let at = bucket(b) + Duration::seconds(i as i64 * 10);
+                assert!(detector.record(&liquidation(side, 5_000.0, at)).is_none());
+            }
+        }
+
+        // Then longs get flushed within a single minute
+        let detections: Vec<_> = (0..20)
+            .filter_map(|i| {
+                let at = bucket(31) + Duration::seconds(i * 2);
+                detector.record(&liquidation(LiquidationSide::Long, 20_000.0, at))
+            })
+            .collect();
+
+        assert_eq!(detections.len(), 1);
+        let detection = &detections[0];
+        assert_eq!(detection.anomaly_type, AnomalyType::LiquidationCascade);
+        assert!(detection.description.starts_with("Long liquidations"));
+        assert!(detection.metrics.z_score.unwrap() >= 4.0);
+        let Some(DetectionDetails::Liquidation(details)) = &detection.details else {
+            panic!("missing liquidation details");
+        };
+        assert_eq!(details.direction, LiquidationSide::Long);
+        assert_eq!(details.liquidations, 2);
+        assert_eq!(details.short_notional, 0.0);
+        assert_eq!(details.bucket_start, bucket(31));
+    }
+
+    #[test]
+    fn test_notional_threshold_applies_without_history() {
+        let detector = LiquidationAnomalyDetector::new(LiquidationConfig::default());
+
+        let first = liquidation(LiquidationSide::Short, 3_000_000.0, bucket(0));
+        assert!(detector.record(&first).is_none());
+        let second = liquidation(LiquidationSide::Short, 3_000_000.0, bucket(0));
+        let detection = detector.record(&second).unwrap();
+
+        assert!(detection.description.starts_with("Short liquidations"));
+        assert_eq!(detection.metrics.current_value, 6_000_000.0);
+        assert_eq!(detection.metrics.z_score, None);
+        assert_eq!(detection.severity, AnomalySeverity::Medium);
+
+        // The next bucket starts from zero
+        let third = liquidation(LiquidationSide::Short, 3_000_000.0, bucket(1));
+        assert!(detector.record(&third).is_none());
+    }
+
+    #[test]
+    fn test_late_and_other_symbol_liquidations_are_separate() {
+        let detector = LiquidationAnomalyDetector::new(LiquidationConfig {
+            notional_threshold: 100_000.0,
+            ..Default::default()
+        });
+
+        assert!(detector
+            .record(&liquidation(LiquidationSide::Long, 60_000.0, bucket(2)))
+            .is_none());
+        // Late for a closed bucket, not added to the current one
+        assert!(detector
+            .record(&liquidation(LiquidationSide::Long, 60_000.0, bucket(1)))
+            .is_none());
+
+        let mut other = liquidation(LiquidationSide::Long, 60_000.0, bucket(2));
+        other.symbol = "ETH/USDT".to_string();
+        assert!(detector.record(&other).is_none());
+
+        assert!(detector
+            .record(&liquidation(LiquidationSide::Long, 60_000.0, bucket(2)))
+            .is_some());
+    }
+}
@@ -1,23 +1,111 @@
 use crate::{TimeSeriesData, TimeSeriesWindow};
-use chrono::{DateTime, Utc};
-use std::collections::HashMap;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use tracing::debug;
 
+const DEFAULT_RSI_PERIOD: usize = 14;
+const DEFAULT_ATR_PERIOD: usize = 14;
+const DEFAULT_ALIGNMENT_TOLERANCE_MS: i64 = 1000;
+const DEFAULT_TICK_SIZE: f64 = 0.01;
+const DEFAULT_MIN_TREND_R_SQUARED: f64 = 0.5;
+// Share of traded volume inside the value area
+const VALUE_AREA_SHARE: f64 = 0.7;
+
 pub struct MetricsCalculator {
     windows: HashMap<String, TimeSeriesWindow>,
+    rsi: HashMap<String, RsiState>,
+    rsi_period: usize,
+    atr: HashMap<String, AtrState>,
+    atr_periods: Vec<usize>,
+    vwap: HashMap<String, VwapState>,
+    obv: HashMap<String, ObvState>,
+    alignment_tolerance: Duration,
+    trend_mode: TrendMode,
+    min_trend_r_squared: f64,
 }
 
 impl MetricsCalculator {
     pub fn new() -> Self {
         Self {
             windows: HashMap::new(),
+            rsi: HashMap::new(),
+            rsi_period: DEFAULT_RSI_PERIOD,
+            atr: HashMap::new(),
+            atr_periods: vec![DEFAULT_ATR_PERIOD],
+            vwap: HashMap::new(),
+            obv: HashMap::new(),
+            alignment_tolerance: Duration::milliseconds(DEFAULT_ALIGNMENT_TOLERANCE_MS),
+            trend_mode: TrendMode::Regression,
+            min_trend_r_squared: DEFAULT_MIN_TREND_R_SQUARED,
         }
     }
     
+    pub fn with_rsi_period(mut self, period: usize) -> Self {
+        self.rsi_period = period.max(1);
+        self
+    }
+    
+    // The ATR periods every bar updates, replacing the default 14. Each is its own Wilder
+    // average, so `calculate_atr` answers only for these
+    pub fn with_atr_periods(mut self, periods: impl IntoIterator<Item = usize>) -> Self {
+        self.atr_periods = periods.into_iter().map(|period| period.max(1)).collect();
+        self.atr_periods.sort_unstable();
+        self.atr_periods.dedup();
+        self
+    }
+    
+    // How far apart two samples may be and still be paired by `calculate_correlation`
+    pub fn with_alignment_tolerance(mut self, tolerance: Duration) -> Self {
+        self.alignment_tolerance = tolerance;
+        self
+    }
+    
+    pub fn with_trend_mode(mut self, mode: TrendMode) -> Self {
+        self.trend_mode = mode;
+        self
+    }
+    
+    // How much of the variance the fitted line must explain before a trend reads as Up or Down
+    pub fn with_min_trend_r_squared(mut self, r_squared: f64) -> Self {
+        self.min_trend_r_squared = r_squared;
+        self
+    }
+    
     pub fn add_data(&mut self, key: &str, data: TimeSeriesData, window_size: usize) {
-        let window = self.windows.entry(key.to_string())
-            .or_insert_with(|| TimeSeriesWindow::new(window_size));
-        window.push(data);
+        let period = self.rsi_period;
+        slot(&mut self.rsi, key, || RsiState::new(period)).update(data.value);
+        slot(&mut self.windows, key, || TimeSeriesWindow::new(window_size)).push(data);
+    }
+    
+    pub fn add_bar(&mut self, key: &str, high: f64, low: f64, close: f64) {
+        let periods = &self.atr_periods;
+        slot(&mut self.atr, key, || AtrState::new(periods)).update(high, low, close);
+    }
+    
+    // Feeds the session VWAP; call `reset_key` at the session boundary
+    pub fn add_trade(&mut self, key: &str, price: f64, volume: f64) {
+        let vwap = slot(&mut self.vwap, key, VwapState::default);
+        vwap.price_volume += price * volume;
+        vwap.volume += volume;
+    }
+    
+    pub fn add_close(&mut self, key: &str, close: f64, volume: f64) {
+        let obv = slot(&mut self.obv, key, ObvState::default);
+        if let Some(previous) = obv.previous_close.replace(close) {
+            if close > previous {
+                obv.value += volume;
+            } else if close < previous {
+                obv.value -= volume;
+            }
+        }
+    }
+    
+    pub fn reset_key(&mut self, key: &str) {
+        self.windows.remove(key);
+        self.rsi.remove(key);
+        self.atr.remove(key);
+        self.vwap.remove(key);
+        self.obv.remove(key);
     }
     
     pub fn calculate_sma(&self, key: &str) -> Option<f64> {
@@ -39,79 +127,396 @@ impl MetricsCalculator {
     }
     
     pub fn calculate_bollinger_bands(&self, key: &str) -> Option<(f64, f64, f64)> {
+        self.calculate_bollinger_bands_with(key, 2.0)
+    }
+    
+    // Bands `multiplier` standard deviations either side of the window mean
+    pub fn calculate_bollinger_bands_with(
+        &self,
+        key: &str,
+        multiplier: f64,
+    ) -> Option<(f64, f64, f64)> {
         self.windows.get(key).map(|w| {
             let mean = w.mean();
             let std_dev = w.std_dev();
-            let upper = mean + 2.0 * std_dev;
-            let lower = mean - 2.0 * std_dev;
+            let upper = mean + multiplier * std_dev;
+            let lower = mean - multiplier * std_dev;
             (lower, mean, upper)
         })
     }
     
-    pub fn calculate_rsi(&self, key: &str, period: usize) -> Option<f64> {
-        self.windows.get(key).and_then(|w| {
-            if w.data.len() < period + 1 {
-                return None;
-            }
-            
-            let mut gains = 0.0;
-            let mut losses = 0.0;
-            
-            for i in 1..=period {
-                let change = w.data[i].value - w.data[i-1].value;
-                if change > 0.0 {
-                    gains += change;
-                } else {
-                    losses -= change;
-                }
-            }
-            
-            let avg_gain = gains / period as f64;
-            let avg_loss = losses / period as f64;
-            
-            if avg_loss == 0.0 {
-                return Some(100.0);
-            }
-            
-            let rs = avg_gain / avg_loss;
-            Some(100.0 - (100.0 / (1.0 + rs)))
+    // Samples currently in the window of `key`
+    pub fn sample_count(&self, key: &str) -> usize {
+        self.windows.get(key).map_or(0, |w| w.len())
+    }
+    
+    pub fn calculate_rsi(&self, key: &str) -> Option<f64> {
+        self.rsi.get(key).and_then(|state| state.value())
+    }
+    
+    // None until `period` bars are in, and for periods not given to `with_atr_periods`
+    pub fn calculate_atr(&self, key: &str, period: usize) -> Option<f64> {
+        self.atr.get(key).and_then(|state| state.value(period))
+    }
+    
+    pub fn calculate_vwap(&self, key: &str) -> Option<f64> {
+        self.vwap
+            .get(key)
+            .filter(|state| state.volume > 0.0)
+            .map(|state| state.price_volume / state.volume)
+    }
+    
+    pub fn calculate_obv(&self, key: &str) -> Option<f64> {
+        self.obv.get(key).map(|state| state.value)
+    }
+    
+    // The standard 12/26/9 MACD
+    pub fn calculate_macd(&self, key: &str) -> Option<MacdOutput> {
+        self.calculate_macd_with_params(key, 12, 26, 9)
+    }
+    
+    // MACD over the key's window: the fast minus the slow EMA at every point both exist, with
+    // the signal line an EMA of that MACD series. Each EMA is seeded with the simple average of
+    // its first `period` inputs, so the window needs `slow + signal - 1` samples.
+    pub fn calculate_macd_with_params(
+        &self,
+        key: &str,
+        fast: usize,
+        slow: usize,
+        signal: usize,
+    ) -> Option<MacdOutput> {
+        if fast == 0 || signal == 0 || fast >= slow {
+            return None;
+        }
+        let values: Vec<f64> = self.windows.get(key)?.data.iter().map(|d| d.value).collect();
+        if values.len() < slow + signal - 1 {
+            return None;
+        }
+        
+        let fast_ema = ema_series(&values, fast);
+        let slow_ema = ema_series(&values, slow);
+        // Both series end at the latest sample, the fast one starts `slow - fast` samples earlier
+        let history: Vec<f64> = slow_ema
+            .iter()
+            .zip(&fast_ema[slow - fast..])
+            .map(|(slow, fast)| fast - slow)
+            .collect();
+        let macd = *history.last()?;
+        let signal = *ema_series(&history, signal).last()?;
+        
+        Some(MacdOutput {
+            macd,
+            signal,
+            histogram: macd - signal,
         })
     }
     
-    pub fn calculate_macd(&self, key: &str) -> Option<(f64, f64, f64)> {
-        let ema12 = self.calculate_ema(key, 2.0 / 13.0)?;
-        let ema26 = self.calculate_ema(key, 2.0 / 27.0)?;
-        let macd = ema12 - ema26;
-        let signal = macd * 0.2; // Simplified signal line
-        let histogram = macd - signal;
-        Some((macd, signal, histogram))
+    // Pearson correlation of two keys' values over their latest `window` aligned pairs, see
+    // `align_series` for how samples are paired
+    pub fn calculate_correlation(&self, key_a: &str, key_b: &str, window: usize) -> Option<f64> {
+        let a = self.windows.get(key_a)?;
+        let b = self.windows.get(key_b)?;
+        let pairs = align_series(&a.data, &b.data, self.alignment_tolerance);
+        pearson(&pairs[pairs.len().saturating_sub(window)..])
     }
     
     pub fn calculate_volatility(&self, key: &str) -> Option<f64> {
         self.windows.get(key).map(|w| w.std_dev())
     }
     
-    pub fn detect_trend(&self, key: &str) -> Option<TrendDirection> {
-        self.windows.get(key).and_then(|w| {
-            if w.data.len() < 3 {
-                return None;
+    // Trend over the key's latest `window` values, at least three
+    pub fn detect_trend(&self, key: &str, window: usize) -> Option<Trend> {
+        let w = self.windows.get(key)?;
+        let n = window.min(w.data.len());
+        if n < 3 {
+            return None;
+        }
+        let values: Vec<f64> = w.data.iter().skip(w.data.len() - n).map(|d| d.value).collect();
+        let (slope, r_squared) = linear_fit(&values);
+        
+        let direction = match self.trend_mode {
+            TrendMode::Regression if r_squared >= self.min_trend_r_squared && slope > 0.0 => {
+                TrendDirection::Up
             }
-            
-            let recent = &w.data[w.data.len() - 3..];
-            let first = recent[0].value;
-            let last = recent[recent.len() - 1].value;
-            
-            if last > first * 1.01 {
-                Some(TrendDirection::Up)
-            } else if last < first * 0.99 {
-                Some(TrendDirection::Down)
-            } else {
-                Some(TrendDirection::Sideways)
+            TrendMode::Regression if r_squared >= self.min_trend_r_squared && slope < 0.0 => {
+                TrendDirection::Down
+            }
+            TrendMode::Regression => TrendDirection::Sideways,
+            TrendMode::Simple => {
+                let (first, last) = (values[0], values[n - 1]);
+                if last > first * 1.01 {
+                    TrendDirection::Up
+                } else if last < first * 0.99 {
+                    TrendDirection::Down
+                } else {
+                    TrendDirection::Sideways
+                }
             }
+        };
+        Some(Trend {
+            direction,
+            slope,
+            r_squared,
         })
     }
 }
 
+// Least-squares slope per sample and R² of `values` against their index. A flat series has
+// nothing to explain and reads as R² 0.
+fn linear_fit(values: &[f64]) -> (f64, f64) {
+    let n = values.len() as f64;
+    let x_mean = (n - 1.0) / 2.0;
+    let y_mean = values.iter().sum::<f64>() / n;
+    let (mut sxy, mut sxx, mut syy) = (0.0, 0.0, 0.0);
+    for (i, value) in values.iter().enumerate() {
+        let (dx, dy) = (i as f64 - x_mean, value - y_mean);
+        sxy += dx * dy;
+        sxx += dx * dx;
+        syy += dy * dy;
+    }
+    if sxx == 0.0 {
+        return (0.0, 0.0);
+    }
+    let r_squared = if syy > 0.0 { sxy * sxy / (sxx * syy) } else { 0.0 };
+    (sxy / sxx, r_squared)
+}
+
+// Wilder-smoothed RSI, updated per sample. It keeps its own previous value and averages, so
+// the oldest samples falling out of the window don't change the result.
+struct RsiState {
+    period: usize,
+    previous: Option<f64>,
+    changes: usize,
+    avg_gain: f64,
+    avg_loss: f64,
+}
+
+impl RsiState {
+    fn new(period: usize) -> Self {
+        Self {
+            period,
+            previous: None,
+            changes: 0,
+            avg_gain: 0.0,
+            avg_loss: 0.0,
+        }
+    }
+
+    fn update(&mut self, value: f64) {
+        let Some(previous) = self.previous.replace(value) else {
+            return;
+        };
+        let change = value - previous;
+        let (gain, loss) = (change.max(0.0), (-change).max(0.0));
+        let period = self.period as f64;
+
+        if self.changes < self.period {
+            // The first `period` changes seed the averages with a simple mean
+            self.changes += 1;
+            self.avg_gain += gain / period;
+            self.avg_loss += loss / period;
+        } else {
+            self.avg_gain = (self.avg_gain * (period - 1.0) + gain) / period;
+            self.avg_loss = (self.avg_loss * (period - 1.0) + loss) / period;
+        }
+    }
+
+    fn value(&self) -> Option<f64> {
+        if self.changes < self.period {
+            return None;
+        }
+        if self.avg_loss == 0.0 {
+            return Some(100.0);
+        }
+        let rs = self.avg_gain / self.avg_loss;
+        Some(100.0 - (100.0 / (1.0 + rs)))
+    }
+}
+
+// Wilder-smoothed average true ranges, each seeded with the mean of its first `period` ranges.
+// The first bar has no previous close, so its true range is just high - low.
+struct AtrState {
+    previous_close: Option<f64>,
+    averages: Vec<WilderAverage>,
+}
+
+struct WilderAverage {
+    period: usize,
+    bars: usize,
+    atr: f64,
+}
+
+impl AtrState {
+    fn new(periods: &[usize]) -> Self {
+        Self {
+            previous_close: None,
+            averages: periods
+                .iter()
+                .map(|&period| WilderAverage { period, bars: 0, atr: 0.0 })
+                .collect(),
+        }
+    }
+
+    fn update(&mut self, high: f64, low: f64, close: f64) {
+        let range = high - low;
+        let true_range = match self.previous_close.replace(close) {
+            Some(previous) => range.max((high - previous).abs()).max((low - previous).abs()),
+            None => range,
+        };
+
+        for average in &mut self.averages {
+            let period = average.period as f64;
+            if average.bars < average.period {
+                average.bars += 1;
+                average.atr += true_range / period;
+            } else {
+                average.atr = (average.atr * (period - 1.0) + true_range) / period;
+            }
+        }
+    }
+
+    fn value(&self, period: usize) -> Option<f64> {
+        self.averages
+            .iter()
+            .find(|average| average.period == period)
+            .filter(|average| average.bars >= average.period)
+            .map(|average| average.atr)
+    }
+}
+
+#[derive(Default)]
+struct VwapState {
+    price_volume: f64,
+    volume: f64,
+}
+
+#[derive(Default)]
+struct ObvState {
+    previous_close: Option<f64>,
+    value: f64,
+}
+
+// The state under `key`, created on first use. Looked up before inserting so the per-sample
+// updates of a known key don't allocate it again
+fn slot<'a, V>(
+    map: &'a mut HashMap<String, V>,
+    key: &str,
+    init: impl FnOnce() -> V,
+) -> &'a mut V {
+    if !map.contains_key(key) {
+        map.insert(key.to_string(), init());
+    }
+    map.get_mut(key).expect("state inserted above")
+}
+
+// Pairs the samples of two time-ordered series. The series with fewer samples leads: each of
+// its samples, oldest first, takes the nearest sample of the other series within `tolerance`,
+// the earlier one on a tie. Pairs never cross and no sample is used twice, so a sparse series
+// against a dense one pairs every sparse sample at most once and the dense samples between
+// them go unused. A sample with nothing within tolerance, e.g. across a gap in the other
+// series, is dropped. Pairs are returned oldest first as (a, b).
+pub fn align_series(
+    a: &VecDeque<TimeSeriesData>,
+    b: &VecDeque<TimeSeriesData>,
+    tolerance: Duration,
+) -> Vec<(f64, f64)> {
+    let swapped = b.len() < a.len();
+    let (lead, other) = if swapped { (b, a) } else { (a, b) };
+    let distance = |i: usize, at: DateTime<Utc>| (other[i].timestamp - at).abs();
+
+    let mut pairs = Vec::with_capacity(lead.len());
+    // First sample of `other` not yet paired or passed over
+    let mut next = 0;
+    for point in lead {
+        if next >= other.len() {
+            break;
+        }
+        // Samples before the last one at or before `point` are further from it and from
+        // every later lead sample
+        while next + 1 < other.len() && other[next + 1].timestamp <= point.timestamp {
+            next += 1;
+        }
+        let nearest = if next + 1 < other.len()
+            && distance(next + 1, point.timestamp) < distance(next, point.timestamp)
+        {
+            next + 1
+        } else {
+            next
+        };
+        if distance(nearest, point.timestamp) > tolerance {
+            continue;
+        }
+        let (lead_value, other_value) = (point.value, other[nearest].value);
+        pairs.push(if swapped {
+            (other_value, lead_value)
+        } else {
+            (lead_value, other_value)
+        });
+        next = nearest + 1;
+    }
+    pairs
+}
+
+// `None` below three pairs or when either side is flat
+pub fn pearson(pairs: &[(f64, f64)]) -> Option<f64> {
+    if pairs.len() < 3 {
+        return None;
+    }
+    let n = pairs.len() as f64;
+    let mean_a = pairs.iter().map(|(a, _)| a).sum::<f64>() / n;
+    let mean_b = pairs.iter().map(|(_, b)| b).sum::<f64>() / n;
+    let (mut covariance, mut var_a, mut var_b) = (0.0, 0.0, 0.0);
+    for (a, b) in pairs {
+        covariance += (a - mean_a) * (b - mean_b);
+        var_a += (a - mean_a).powi(2);
+        var_b += (b - mean_b).powi(2);
+    }
+    if var_a <= 0.0 || var_b <= 0.0 {
+        return None;
+    }
+    Some((covariance / (var_a * var_b).sqrt()).clamp(-1.0, 1.0))
+}
+
+// EMA of `values` with smoothing 2 / (period + 1), from the `period`-th value on
+fn ema_series(values: &[f64], period: usize) -> Vec<f64> {
+    if period == 0 || values.len() < period {
+        return Vec::new();
+    }
+    let alpha = 2.0 / (period as f64 + 1.0);
+    let mut ema = values[..period].iter().sum::<f64>() / period as f64;
+    let mut series = Vec::with_capacity(values.len() - period + 1);
+    series.push(ema);
+    for value in &values[period..] {
+        ema = alpha * value + (1.0 - alpha) * ema;
+        series.push(ema);
+    }
+    series
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MacdOutput {
+    pub macd: f64,
+    pub signal: f64,
+    pub histogram: f64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TrendMode {
+    // Least-squares line over the window, Up or Down only once R² clears the minimum
+    #[default]
+    Regression,
+    // The window's last value against its first, a 1% move either way
+    Simple,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Trend {
+    pub direction: TrendDirection,
+    // Change per sample and goodness of fit of the least-squares line, in both modes
+    pub slope: f64,
+    pub r_squared: f64,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum TrendDirection {
     Up,
@@ -119,7 +524,9 @@ pub enum TrendDirection {
     Sideways,
 }
 
+#[derive(Debug, Clone)]
 pub struct VolumeProfile {
+    // Ascending by price
     pub levels: Vec<PriceLevel>,
     pub poc: f64, // Point of Control
     pub vah: f64, // Value Area High
@@ -134,53 +541,495 @@ pub struct PriceLevel {
 
 impl VolumeProfile {
     pub fn calculate(trades: &[(f64, f64)]) -> Option<Self> {
-        if trades.is_empty() {
-            return None;
+        VolumeProfileBuilder::from_trades(trades, DEFAULT_TICK_SIZE).build()
+    }
+}
+
+// Traded volume bucketed by price, updated per trade. Each trade counts towards the tick
+// nearest its price.
+#[derive(Debug, Clone)]
+pub struct VolumeProfileBuilder {
+    tick_size: f64,
+    // Volume per bucket, keyed by the bucket's price in ticks
+    buckets: BTreeMap<i64, f64>,
+}
+
+impl VolumeProfileBuilder {
+    pub fn new(tick_size: f64) -> Self {
+        Self {
+            tick_size: if tick_size.is_finite() && tick_size > 0.0 {
+                tick_size
+            } else {
+                DEFAULT_TICK_SIZE
+            },
+            buckets: BTreeMap::new(),
         }
-        
-        // Simplified volume profile calculation
-        let mut price_volumes: HashMap<i64, f64> = HashMap::new();
-        
-        for (price, volume) in trades {
-            let price_level = (*price * 100.0) as i64;
-            *price_volumes.entry(price_level).or_insert(0.0) += volume;
+    }
+    
+    pub fn from_trades(trades: &[(f64, f64)], tick_size: f64) -> Self {
+        let mut builder = Self::new(tick_size);
+        for &(price, volume) in trades {
+            builder.add_trade(price, volume);
         }
-        
-        let mut levels: Vec<PriceLevel> = price_volumes
-            .into_iter()
-            .map(|(p, v)| PriceLevel {
-                price: p as f64 / 100.0,
-                volume: v,
+        builder
+    }
+    
+    pub fn add_trade(&mut self, price: f64, volume: f64) {
+        if !price.is_finite() || !volume.is_finite() || price <= 0.0 || volume <= 0.0 {
+            return;
+        }
+        let tick = (price / self.tick_size).round() as i64;
+        *self.buckets.entry(tick).or_insert(0.0) += volume;
+    }
+    
+    pub fn clear(&mut self) {
+        self.buckets.clear();
+    }
+    
+    // The value area grows from the POC one level at a time, taking whichever neighbour
+    // traded more, both on a tie, until it holds 70% of the volume
+    pub fn build(&self) -> Option<VolumeProfile> {
+        let levels: Vec<PriceLevel> = self
+            .buckets
+            .iter()
+            .map(|(&tick, &volume)| PriceLevel {
+                price: tick as f64 * self.tick_size,
+                volume,
             })
             .collect();
+        // Highest volume, the lowest price on a tie
+        let poc = (0..levels.len()).reduce(|best, i| {
+            if levels[i].volume > levels[best].volume {
+                i
+            } else {
+                best
+            }
+        })?;
         
-        levels.sort_by(|a, b| b.volume.partial_cmp(&a.volume).unwrap());
-        
-        if levels.is_empty() {
-            return None;
-        }
-        
-        let poc = levels[0].price;
-        let total_volume: f64 = levels.iter().map(|l| l.volume).sum();
-        let value_area_volume = total_volume * 0.7;
-        
-        let mut accumulated_volume = 0.0;
-        let mut vah = poc;
-        let mut val = poc;
-        
-        for level in &levels {
-            accumulated_volume += level.volume;
-            if accumulated_volume <= value_area_volume {
-                vah = vah.max(level.price);
-                val = val.min(level.price);
+        let target = levels.iter().map(|l| l.volume).sum::<f64>() * VALUE_AREA_SHARE;
+        let (mut low, mut high) = (poc, poc);
+        let mut covered = levels[poc].volume;
+        while covered < target {
+            let below = low.checked_sub(1).map(|i| levels[i].volume);
+            let above = levels.get(high + 1).map(|l| l.volume);
+            match (below, above) {
+                (Some(below), Some(above)) if below == above => {
+                    low -= 1;
+                    high += 1;
+                    covered += below + above;
+                }
+                (Some(below), Some(above)) if above > below => {
+                    high += 1;
+                    covered += above;
+                }
+                (Some(below), _) => {
+                    low -= 1;
+                    covered += below;
+                }
+                (None, Some(above)) => {
+                    high += 1;
+                    covered += above;
+                }
+                (None, None) => break,
             }
         }
         
         Some(VolumeProfile {
+            poc: levels[poc].price,
+            vah: levels[high].price,
+            val: levels[low].price,
             levels,
-            poc,
-            vah,
-            val,
         })
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn calculator(values: impl IntoIterator<Item = f64>) -> MetricsCalculator {
+        let mut calculator = MetricsCalculator::new();
+        for value in values {
+            let data = TimeSeriesData {
+                timestamp: Utc::now(),
+                value,
+            };
+            calculator.add_data("price", data, 400);
+        }
+        calculator
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-6, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn test_macd_matches_reference_values() {
+        let series = (0..40).map(|i| {
+            let i = i as f64;
+            100.0 + 5.0 * (i / 3.0).sin() + 0.5 * i
+        });
+        let calculator = calculator(series);
+
+        let macd = calculator.calculate_macd("price").unwrap();
+        assert_close(macd.macd, 3.1597945472);
+        assert_close(macd.signal, 3.0143559310);
+        assert_close(macd.histogram, 0.1454386161);
+
+        let macd = calculator.calculate_macd_with_params("price", 5, 10, 4).unwrap();
+        assert_close(macd.macd, 1.9542935441);
+        assert_close(macd.signal, 1.2634210831);
+        assert_close(macd.histogram, 0.6908724611);
+    }
+
+    #[test]
+    fn test_macd_of_linear_trend_is_constant() {
+        // Both EMAs lag a ramp by slope * (period - 1) / 2, so MACD settles at slope * 7
+        let calculator = calculator((0..50).map(|i| 100.0 + 0.5 * i as f64));
+        let macd = calculator.calculate_macd("price").unwrap();
+        assert_close(macd.macd, 3.5);
+        assert_close(macd.signal, 3.5);
+        assert_close(macd.histogram, 0.0);
+    }
+
+    #[test]
+    fn test_signal_lags_then_converges_to_macd() {
+        let values: Vec<f64> = (0..30).map(|i| 100.0 + i as f64).chain([129.0; 300]).collect();
+
+        // Ten bars into the plateau MACD is falling and the signal line still trails above it
+        let early = calculator(values[..40].iter().copied());
+        let macd = early.calculate_macd("price").unwrap();
+        assert!(macd.histogram < -0.5);
+
+        let settled = calculator(values);
+        let macd = settled.calculate_macd("price").unwrap();
+        assert!(macd.histogram.abs() < 1e-6);
+        assert!(macd.macd.abs() < 1e-6);
+    }
+
+    // Closes and RSI-14 from the StockCharts RSI worksheet; the sheet carries unrounded closes,
+    // so its published values differ from these closes in the second decimal
+    const RSI_CLOSES: [f64; 33] = [
+        44.34, 44.09, 44.15, 43.61, 44.33, 44.83, 45.10, 45.42, 45.84, 46.08, 45.89, 46.03, 45.61,
+        46.28, 46.28, 46.00, 46.03, 46.41, 46.22, 45.64, 46.21, 46.25, 45.71, 46.45, 45.78, 45.35,
+        44.03, 44.18, 44.22, 44.57, 43.42, 42.66, 43.13,
+    ];
+    const RSI_14: [f64; 19] = [
+        70.53, 66.32, 66.55, 69.41, 66.36, 57.97, 62.93, 63.26, 56.06, 62.38, 54.71, 50.42, 39.99,
+        41.46, 41.87, 45.46, 37.30, 33.08, 37.77,
+    ];
+
+    #[test]
+    fn test_rsi_matches_published_reference() {
+        let mut calculator = MetricsCalculator::new();
+        let mut rsi = Vec::new();
+        for value in RSI_CLOSES {
+            let data = TimeSeriesData {
+                timestamp: Utc::now(),
+                value,
+            };
+            calculator.add_data("price", data, 400);
+            rsi.extend(calculator.calculate_rsi("price"));
+        }
+
+        assert_eq!(rsi.len(), RSI_14.len());
+        for (actual, expected) in rsi.iter().zip(RSI_14) {
+            assert!((actual - expected).abs() < 0.1, "{} != {}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_rsi_follows_latest_data_past_window_eviction() {
+        // A window far shorter than the series still yields the full-history value
+        let mut calculator = MetricsCalculator::new();
+        for value in RSI_CLOSES {
+            let data = TimeSeriesData {
+                timestamp: Utc::now(),
+                value,
+            };
+            calculator.add_data("price", data, 5);
+        }
+        let rsi = calculator.calculate_rsi("price").unwrap();
+        assert!((rsi - RSI_14[RSI_14.len() - 1]).abs() < 0.1);
+
+        // Ten samples are nine changes, short of the fourteen RSI-14 is seeded from
+        let rising = calculator((0..10).map(|i| i as f64));
+        assert!(rising.calculate_rsi("price").is_none());
+
+        let mut short = MetricsCalculator::new().with_rsi_period(3);
+        for value in [1.0, 2.0, 3.0, 4.0] {
+            let data = TimeSeriesData {
+                timestamp: Utc::now(),
+                value,
+            };
+            short.add_data("price", data, 2);
+        }
+        assert_eq!(short.calculate_rsi("price"), Some(100.0));
+    }
+
+    #[test]
+    fn test_atr_uses_true_range_and_wilder_smoothing() {
+        let mut calculator = MetricsCalculator::new().with_atr_periods([3, 2]);
+        let bars = [(10.0, 8.0, 9.0), (11.0, 9.0, 10.0), (12.0, 9.0, 11.0)];
+        for (high, low, close) in bars {
+            assert!(calculator.calculate_atr("btc", 3).is_none());
+            calculator.add_bar("btc", high, low, close);
+        }
+        // True ranges 2, 2, 3
+        assert_close(calculator.calculate_atr("btc", 3).unwrap(), 7.0 / 3.0);
+        // Seeded from 2 and 2, then (2 * 1 + 3) / 2
+        assert_close(calculator.calculate_atr("btc", 2).unwrap(), 2.5);
+        assert!(calculator.calculate_atr("btc", 14).is_none());
+
+        // A range of 1 against the previous close of 11, then a gap up from 10.5 to a 13 high
+        calculator.add_bar("btc", 11.0, 10.0, 10.5);
+        assert_close(calculator.calculate_atr("btc", 3).unwrap(), 17.0 / 9.0);
+        calculator.add_bar("btc", 13.0, 10.0, 12.0);
+        assert_close(calculator.calculate_atr("btc", 3).unwrap(), 61.0 / 27.0);
+    }
+
+    #[test]
+    fn test_vwap_weights_by_volume_until_reset() {
+        let mut calculator = MetricsCalculator::new();
+        assert!(calculator.calculate_vwap("btc").is_none());
+        calculator.add_trade("btc", 100.0, 1.0);
+        calculator.add_trade("btc", 102.0, 3.0);
+        assert_close(calculator.calculate_vwap("btc").unwrap(), 101.5);
+
+        calculator.reset_key("btc");
+        assert!(calculator.calculate_vwap("btc").is_none());
+        calculator.add_trade("btc", 90.0, 2.0);
+        assert_close(calculator.calculate_vwap("btc").unwrap(), 90.0);
+    }
+
+    #[test]
+    fn test_obv_adds_up_volume_and_subtracts_down_volume() {
+        let mut calculator = MetricsCalculator::new();
+        let closes = [(10.0, 5.0), (11.0, 3.0), (10.5, 2.0), (10.5, 4.0), (12.0, 1.0)];
+        let mut obv = Vec::new();
+        for (close, volume) in closes {
+            calculator.add_close("btc", close, volume);
+            obv.extend(calculator.calculate_obv("btc"));
+        }
+        assert_eq!(obv, vec![0.0, 3.0, 1.0, 1.0, 2.0]);
+
+        calculator.reset_key("btc");
+        assert!(calculator.calculate_obv("btc").is_none());
+    }
+
+    // Samples at millisecond offsets from a fixed start
+    fn samples(points: &[(i64, f64)]) -> VecDeque<TimeSeriesData> {
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        points
+            .iter()
+            .map(|&(ms, value)| TimeSeriesData {
+                timestamp: start + Duration::milliseconds(ms),
+                value,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_correlation_of_linearly_related_series() {
+        let tolerance = Duration::milliseconds(500);
+        let mut calculator = MetricsCalculator::new().with_alignment_tolerance(tolerance);
+        let start = Utc::now();
+        for i in 0..50 {
+            let timestamp = start + Duration::seconds(i);
+            let value = (i as f64 / 4.0).sin();
+            calculator.add_data("btc", TimeSeriesData { timestamp, value }, 100);
+            let eth = TimeSeriesData {
+                timestamp: timestamp + Duration::milliseconds(200),
+                value: 3.0 * value + 1.0,
+            };
+            calculator.add_data("eth", eth, 100);
+            let inverse = TimeSeriesData {
+                timestamp,
+                value: -value,
+            };
+            calculator.add_data("inverse", inverse, 100);
+        }
+
+        assert_close(calculator.calculate_correlation("btc", "eth", 20).unwrap(), 1.0);
+        assert_close(calculator.calculate_correlation("btc", "inverse", 20).unwrap(), -1.0);
+        assert!(calculator.calculate_correlation("btc", "sol", 20).is_none());
+        assert!(calculator.calculate_correlation("btc", "eth", 2).is_none());
+    }
+
+    #[test]
+    fn test_sparse_series_pairs_each_sample_with_nearest_dense_sample() {
+        // One sample a second against one every three seconds, 100ms after a dense sample
+        let dense: Vec<(i64, f64)> = (0..30).map(|i| (i * 1000, i as f64)).collect();
+        let sparse: Vec<(i64, f64)> =
+            (0..10).map(|i| (100 + i * 3000, 1000.0 + i as f64)).collect();
+        let (dense, sparse) = (samples(&dense), samples(&sparse));
+
+        let pairs = align_series(&dense, &sparse, Duration::milliseconds(500));
+        let expected: Vec<(f64, f64)> =
+            (0..10).map(|i| (3.0 * i as f64, 1000.0 + i as f64)).collect();
+        assert_eq!(pairs, expected);
+
+        // Either order gives the same pairs, oriented as the arguments
+        let swapped = align_series(&sparse, &dense, Duration::milliseconds(500));
+        let flipped: Vec<(f64, f64)> = expected.iter().map(|&(a, b)| (b, a)).collect();
+        assert_eq!(swapped, flipped);
+    }
+
+    #[test]
+    fn test_samples_across_gaps_are_dropped() {
+        // A sample a second, silent from 10s to 19s, against one every 2.5s
+        let dense: Vec<(i64, f64)> = (0..30)
+            .filter(|i| !(10..20).contains(i))
+            .map(|i| (i * 1000, i as f64))
+            .collect();
+        let sparse: Vec<(i64, f64)> = (0..12).map(|i| (i * 2500, 100.0 + i as f64)).collect();
+
+        let pairs = align_series(&samples(&dense), &samples(&sparse), Duration::milliseconds(400));
+        // Samples at the half seconds are 500ms from the nearest, 10s and 15s fall in the gap
+        assert_eq!(pairs, vec![(0.0, 100.0), (5.0, 102.0), (20.0, 108.0), (25.0, 110.0)]);
+    }
+
+    #[test]
+    fn test_ties_take_the_earlier_sample_and_samples_are_not_reused() {
+        // 1s sits halfway between 0.5s and 1.5s
+        let a = samples(&[(1000, 1.0), (11_000, 2.0)]);
+        let b = samples(&[(500, 10.0), (1500, 20.0), (2500, 30.0)]);
+        assert_eq!(align_series(&a, &b, Duration::milliseconds(600)), vec![(1.0, 10.0)]);
+
+        // Both samples of `a` are nearest to 50ms, the second one gets nothing
+        let a = samples(&[(0, 1.0), (100, 2.0)]);
+        let b = samples(&[(50, 10.0), (1000, 20.0), (5000, 30.0)]);
+        assert_eq!(align_series(&a, &b, Duration::milliseconds(600)), vec![(1.0, 10.0)]);
+    }
+
+    #[test]
+    fn test_value_area_expands_from_poc() {
+        // 140 traded, so the value area needs 98. From the 50 at 102 it takes 103 (30 against
+        // 20 below), then 101 (20 against 15 above) to reach 100
+        let trades = [
+            (99.0, 10.0),
+            (100.0, 10.0),
+            (101.0, 20.0),
+            (102.0, 30.0),
+            (102.2, 20.0),
+            (103.0, 30.0),
+            (104.0, 15.0),
+            (105.0, 5.0),
+        ];
+        let profile = VolumeProfileBuilder::from_trades(&trades, 1.0).build().unwrap();
+        assert_eq!(profile.poc, 102.0);
+        assert_eq!((profile.val, profile.vah), (101.0, 103.0));
+        let prices: Vec<f64> = profile.levels.iter().map(|l| l.price).collect();
+        assert_eq!(prices, vec![99.0, 100.0, 101.0, 102.0, 103.0, 104.0, 105.0]);
+
+        // Equal neighbours join together
+        let trades = [(99.0, 10.0), (100.0, 40.0), (101.0, 10.0)];
+        let profile = VolumeProfileBuilder::from_trades(&trades, 1.0).build().unwrap();
+        assert_eq!((profile.val, profile.poc, profile.vah), (99.0, 100.0, 101.0));
+    }
+
+    #[test]
+    fn test_profile_builds_incrementally_with_sub_cent_ticks() {
+        let trades = [(0.0012, 500.0), (0.00131, 800.0), (0.00129, 400.0), (0.0014, 300.0)];
+        let mut builder = VolumeProfileBuilder::new(0.0001);
+        assert!(builder.build().is_none());
+        for &(price, volume) in &trades {
+            builder.add_trade(price, volume);
+        }
+        builder.add_trade(f64::NAN, 1.0);
+
+        let profile = builder.build().unwrap();
+        assert_eq!(profile.levels.len(), 3);
+        assert_close(profile.poc, 0.0013);
+        assert_close(profile.levels[1].volume, 1200.0);
+        // 1200 of 2000 at the POC, then the 500 below
+        assert_close(profile.val, 0.0012);
+        assert_close(profile.vah, 0.0013);
+
+        let rebuilt = VolumeProfileBuilder::from_trades(&trades, 0.0001).build().unwrap();
+        assert_close(rebuilt.vah, profile.vah);
+
+        // The default cent tick lumps every one of these prices together
+        assert_eq!(VolumeProfile::calculate(&trades).unwrap().levels.len(), 1);
+    }
+
+    // Uniform noise in [-1, 1), from a fixed LCG so runs are repeatable
+    fn noise(count: usize) -> Vec<f64> {
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        (0..count)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6_364_136_223_846_793_005)
+                    .wrapping_add(1_442_695_040_888_963_407);
+                (state >> 11) as f64 / (1u64 << 53) as f64 * 2.0 - 1.0
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_regression_trend_ignores_noise_around_a_flat_line() {
+        let flat = calculator(noise(50).into_iter().map(|n| 100.0 + n));
+        let trend = flat.detect_trend("price", 50).unwrap();
+        assert_eq!(trend.direction, TrendDirection::Sideways);
+        assert!(trend.r_squared < 0.1);
+
+        // The three-point rule calls some of the same stretches Up or Down
+        let directions: Vec<TrendDirection> = (3..=50)
+            .map(|end| {
+                let values = noise(end).into_iter().map(|n| 100.0 + n);
+                let partial = calculator(values).with_trend_mode(TrendMode::Simple);
+                partial.detect_trend("price", 3).unwrap().direction
+            })
+            .collect();
+        assert!(directions.contains(&TrendDirection::Up));
+        assert!(directions.contains(&TrendDirection::Down));
+    }
+
+    #[test]
+    fn test_regression_trend_finds_noisy_uptrend() {
+        let values = noise(50).into_iter().enumerate().map(|(i, n)| 100.0 + 0.2 * i as f64 + n);
+        let rising = calculator(values);
+        let trend = rising.detect_trend("price", 50).unwrap();
+        assert_eq!(trend.direction, TrendDirection::Up);
+        assert!(trend.r_squared > 0.9);
+        assert!((trend.slope - 0.2).abs() < 0.01);
+
+        // Demanding more fit than the noise allows reads as sideways
+        let strict = calculator(
+            noise(50).into_iter().enumerate().map(|(i, n)| 100.0 + 0.2 * i as f64 + n),
+        )
+        .with_min_trend_r_squared(0.99);
+        assert_eq!(strict.detect_trend("price", 50).unwrap().direction, TrendDirection::Sideways);
+
+        let falling = calculator((0..10).map(|i| 100.0 - i as f64));
+        let trend = falling.detect_trend("price", 10).unwrap();
+        assert_eq!(trend.direction, TrendDirection::Down);
+        assert_close(trend.r_squared, 1.0);
+        assert!(falling.detect_trend("price", 2).is_none());
+    }
+
+    #[test]
+    fn test_simple_trend_keeps_the_one_percent_rule() {
+        let simple = |values: [f64; 3]| {
+            calculator(values)
+                .with_trend_mode(TrendMode::Simple)
+                .detect_trend("price", 3)
+                .unwrap()
+                .direction
+        };
+        assert_eq!(simple([100.0, 100.5, 102.0]), TrendDirection::Up);
+        assert_eq!(simple([100.0, 101.0, 100.5]), TrendDirection::Sideways);
+        assert_eq!(simple([100.0, 99.5, 98.5]), TrendDirection::Down);
+    }
+
+    #[test]
+    fn test_macd_needs_enough_samples_and_valid_periods() {
+        let calculator = calculator((0..33).map(|i| i as f64));
+        assert!(calculator.calculate_macd("price").is_none());
+        assert!(calculator.calculate_macd("volume").is_none());
+        assert!(calculator.calculate_macd_with_params("price", 10, 10, 3).is_none());
+        assert!(calculator.calculate_macd_with_params("price", 5, 10, 0).is_none());
+        assert!(calculator.calculate_macd_with_params("price", 5, 10, 4).is_some());
+    }
+}
@@ -0,0 +1,271 @@
+use crate::{
+    reason::{AnomalyReason, ReasonKind},
+    confidence_from_exceedance, interval_label, severity_from_confidence, AnomalyDetection,
+    AnomalyDetector, AnomalyMetrics, DetectionDetails, RateOfChangeConfig, TimeSeriesData,
+};
+use chrono::{DateTime, Duration, Utc};
+use monitor_core::AnomalyType;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use tracing::info;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RateOfChangeDetails {
+    // Which of the configured horizons the change was measured over
+    pub horizon_secs: u64,
+    // When the reference price was traded
+    pub reference_at: DateTime<Utc>,
+}
+
+// Price change over one fixed horizon, measured against the price the horizon ago rather than
+// the previous tick. The reference is the last sample at or before that moment, and must lie
+// within `reference_tolerance_pct` of the horizon of it, so a gap in the data never stretches
+// the horizon. A move past the threshold alerts once until it falls back within it.
+pub struct RateOfChangeDetector {
+    config: RateOfChangeConfig,
+    horizon: Duration,
+    tolerance: Duration,
+    // Closest two kept prices may be, so the window holds at most about `max_samples`
+    spacing: Duration,
+    label: String,
+    symbol: String,
+    exchange: String,
+    window: VecDeque<TimeSeriesData>,
+    triggered: bool,
+}
+
+impl RateOfChangeDetector {
+    pub fn new(
+        config: RateOfChangeConfig,
+        horizon_secs: u64,
+        symbol: String,
+        exchange: String,
+    ) -> Self {
+        let tolerance_ms = (horizon_secs as f64 * config.reference_tolerance_pct * 10.0) as i64;
+        let tolerance_ms = tolerance_ms.max(1_000);
+        let span_ms = horizon_secs as i64 * 1_000 + tolerance_ms;
+        Self {
+            horizon: Duration::seconds(horizon_secs as i64),
+            tolerance: Duration::milliseconds(tolerance_ms),
+            spacing: Duration::milliseconds(span_ms / config.max_samples.max(1) as i64),
+            label: interval_label(horizon_secs),
+            config,
+            symbol,
+            exchange,
+            window: VecDeque::new(),
+            triggered: false,
+        }
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+}
+
+impl AnomalyDetector for RateOfChangeDetector {
+    fn detect(&mut self, data: &TimeSeriesData) -> Option<AnomalyDetection> {
+        let price = data.value;
+        if !price.is_finite() || price <= 0.0 {
+            return None;
+        }
+
+        let target = data.timestamp - self.horizon;
+        // Anything older can't be a reference for this sample or any later one
+        while self
+            .window
+            .front()
+            .is_some_and(|oldest| oldest.timestamp < target - self.tolerance)
+        {
+            self.window.pop_front();
+        }
+        let index = self.window.partition_point(|d| d.timestamp <= target);
+        let reference = index.checked_sub(1).map(|i| self.window[i].clone());
+        // The newest price replaces one that came too soon after the price before it
+        let len = self.window.len();
+        if len >= 2 && data.timestamp - self.window[len - 2].timestamp < self.spacing {
+            self.window.pop_back();
+        }
+        self.window.push_back(data.clone());
+
+        let reference = reference?;
+        let percentage_change = (price - reference.value) / reference.value * 100.0;
+        if percentage_change.abs() < self.config.percentage_threshold {
+            self.triggered = false;
+            return None;
+        }
+        if self.triggered {
+            return None;
+        }
+        self.triggered = true;
+
+        let confidence =
+            confidence_from_exceedance(percentage_change.abs(), self.config.percentage_threshold);
+        let severity = severity_from_confidence(confidence);
+        let reason = AnomalyReason::new(
+            &self.exchange,
+            &self.symbol,
+            price,
+            ReasonKind::RateOfChange {
+                label: self.label.clone(),
+                change_pct: percentage_change,
+                since: reference.timestamp,
+            },
+        )
+        .with_baseline(reference.value)
+        .with_threshold(self.config.percentage_threshold)
+        .with_window_secs(self.horizon.num_seconds() as f64);
+        let description = reason.to_string();
+
+        info!("{}", description);
+
+        Some(AnomalyDetection {
+            id: uuid::Uuid::new_v4(),
+            timestamp: data.timestamp,
+            symbol: self.symbol.clone(),
+            exchange: self.exchange.clone(),
+            anomaly_type: AnomalyType::RateOfChange,
+            severity,
+            confidence,
+            metrics: AnomalyMetrics {
+                current_value: price,
+                expected_value: reference.value,
+                deviation: price - reference.value,
+                z_score: None,
+                percentage_change: Some(percentage_change),
+                historical_avg: None,
+                historical_std: None,
+                ..Default::default()
+            },
+            description,
+            reason: Some(reason),
+            details: Some(DetectionDetails::RateOfChange(RateOfChangeDetails {
+                horizon_secs: self.horizon.num_seconds() as u64,
+                reference_at: reference.timestamp,
+            })),
+            ..Default::default()
+        })
+    }
+
+    fn reset(&mut self) {
+        self.window.clear();
+        self.triggered = false;
+    }
+
+    fn kind(&self) -> String {
+        format!("rate_of_change_{}", self.label)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn start() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap()
+    }
+
+    fn sample(secs: i64, value: f64) -> TimeSeriesData {
+        TimeSeriesData {
+            timestamp: start() + Duration::seconds(secs),
+            value,
+        }
+    }
+
+    fn detector(horizon_secs: u64) -> RateOfChangeDetector {
+        RateOfChangeDetector::new(
+            RateOfChangeConfig::default(),
+            horizon_secs,
+            "BTC/USDT".to_string(),
+            "binance".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_change_over_horizon_alerts_once() {
+        let mut one = detector(60);
+        let mut five = detector(300);
+        // Creeping up 0.012 a second never moves 3% within a minute, but does within five
+        let (mut one_alerts, mut five_alerts) = (Vec::new(), Vec::new());
+        for i in 0..400 {
+            let data = sample(i, 100.0 + 0.012 * i as f64);
+            one_alerts.extend(one.detect(&data));
+            five_alerts.extend(five.detect(&data).map(|a| (i, a)));
+        }
+        assert!(one_alerts.is_empty());
+
+        // Alerts as soon as the horizon is covered, then stays quiet while still past it
+        assert_eq!(five_alerts.len(), 1);
+        let (at, anomaly) = &five_alerts[0];
+        assert_eq!(*at, 300);
+        assert!(anomaly.description.starts_with("Price rate of change [5m] for binance/BTC/USDT"));
+        assert_eq!(anomaly.anomaly_type, AnomalyType::RateOfChange);
+        let Some(DetectionDetails::RateOfChange(details)) = &anomaly.details else {
+            panic!("unexpected details {:?}", anomaly.details);
+        };
+        assert_eq!(details.horizon_secs, 300);
+        assert_eq!(details.reference_at, start());
+        assert_eq!(anomaly.metrics.expected_value, 100.0);
+        assert!((anomaly.metrics.percentage_change.unwrap() - 3.6).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_busy_symbols_keep_a_bounded_window() {
+        let mut detector = RateOfChangeDetector::new(
+            RateOfChangeConfig { max_samples: 100, ..Default::default() },
+            60,
+            "BTC/USDT".to_string(),
+            "binance".to_string(),
+        );
+        // A tick every 10ms for five minutes, creeping up 3.6% a minute
+        let mut alerts = Vec::new();
+        for i in 0..30_000 {
+            let at = start() + Duration::milliseconds(i * 10);
+            let data = TimeSeriesData { timestamp: at, value: 100.0 + 0.0006 * i as f64 };
+            alerts.extend(detector.detect(&data));
+            assert!(detector.window.len() <= 102, "{}", detector.window.len());
+        }
+
+        // The thinned window still finds a reference within a spacing of the horizon
+        assert_eq!(alerts.len(), 1);
+        let Some(DetectionDetails::RateOfChange(details)) = &alerts[0].details else {
+            panic!("unexpected details {:?}", alerts[0].details);
+        };
+        let lag = alerts[0].timestamp - details.reference_at - Duration::seconds(60);
+        assert!(lag >= Duration::zero() && lag < Duration::milliseconds(700), "{}", lag);
+    }
+
+    #[test]
+    fn test_jump_rearms_once_horizon_passes() {
+        let mut detector = detector(60);
+        let mut alerts = Vec::new();
+        for i in 0..300 {
+            let price = if (120..240).contains(&i) { 104.0 } else { 100.0 };
+            alerts.extend(detector.detect(&sample(i, price)).map(|_| i));
+        }
+        // Up at 120, then down 3.8% from 104 at 240
+        assert_eq!(alerts, vec![120, 240]);
+    }
+
+    #[test]
+    fn test_gaps_longer_than_horizon_do_not_alert() {
+        let mut sparse = detector(60);
+        // Every two minutes, each 10% above the last
+        let mut price = 100.0;
+        for i in 0..10 {
+            assert!(sparse.detect(&sample(i * 120, price)).is_none());
+            price *= 1.1;
+        }
+
+        // A reference up to 10% of the horizon before it still counts, 6s for a minute
+        let mut late = detector(60);
+        assert!(late.detect(&sample(0, 100.0)).is_none());
+        let anomaly = late.detect(&sample(66, 110.0)).unwrap();
+        assert!(anomaly.description.contains("[1m]"));
+        assert_eq!(anomaly.metrics.expected_value, 100.0);
+
+        let mut gap = detector(60);
+        assert!(gap.detect(&sample(0, 100.0)).is_none());
+        assert!(gap.detect(&sample(70, 110.0)).is_none());
+    }
+}
@@ -0,0 +1,315 @@
+use crate::{
+    reason::{AnomalyReason, ReasonKind},
+    warmup::WarmupTracker,
+    confidence_from_exceedance, severity_from_confidence, AnomalyDetection, AnomalyMetrics,
+    DetectionDetails,
+};
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use monitor_core::{model::OpenInterestUpdate, AnomalyType, OpenInterestConfig};
+use serde::{Deserialize, Serialize};
+use std::{collections::VecDeque, fmt, sync::Arc};
+use tracing::info;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum OpenInterestPattern {
+    // Open interest and price rising together, new longs
+    LongBuildUp,
+    // Open interest rising into a falling price, new shorts
+    ShortBuildUp,
+    // Longs closing as the price falls
+    LongUnwind,
+    // Shorts closing as the price rises
+    ShortCovering,
+    // Without a price move to tell the side
+    BuildUp,
+    Unwind,
+}
+
+impl fmt::Display for OpenInterestPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            OpenInterestPattern::LongBuildUp => "long build-up",
+            OpenInterestPattern::ShortBuildUp => "short build-up",
+            OpenInterestPattern::LongUnwind => "long unwind",
+            OpenInterestPattern::ShortCovering => "short covering",
+            OpenInterestPattern::BuildUp => "build-up",
+            OpenInterestPattern::Unwind => "unwind",
+        };
+        f.write_str(label)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpenInterestDetails {
+    pub pattern: OpenInterestPattern,
+    pub open_interest: f64,
+    pub previous_open_interest: f64,
+    pub change_pct: f64,
+    // Over the same span, when prices were known at both ends
+    pub price_change_pct: Option<f64>,
+    pub span_secs: i64,
+}
+
+struct Reading {
+    timestamp: DateTime<Utc>,
+    open_interest: f64,
+    price: Option<f64>,
+}
+
+#[derive(Default)]
+struct InstrumentState {
+    readings: VecDeque<Reading>,
+    triggered: bool,
+}
+
+// Flags open interest building up or unwinding by more than `change_pct` within `window_secs`,
+// against the oldest reading still inside the window. The price move over the same span tells
+// longs from shorts, taken from the update itself or else the last trade seen for the pair.
+// A move alerts once until open interest is back within the threshold.
+pub struct OpenInterestDetector {
+    config: OpenInterestConfig,
+    state: DashMap<String, InstrumentState>,
+    prices: DashMap<String, f64>,
+    warmup: Option<Arc<WarmupTracker>>,
+}
+
+impl OpenInterestDetector {
+    pub fn new(config: OpenInterestConfig) -> Self {
+        Self {
+            config,
+            state: DashMap::new(),
+            prices: DashMap::new(),
+            warmup: None,
+        }
+    }
+
+    pub fn with_warmup(mut self, warmup: Arc<WarmupTracker>) -> Self {
+        self.warmup = Some(warmup);
+        self
+    }
+
+    // Latest traded price, the context for readings that carry none
+    pub fn observe_price(&self, exchange: &str, symbol: &str, price: f64) {
+        if price.is_finite() && price > 0.0 {
+            self.prices.insert(format!("{}:{}", exchange, symbol), price);
+        }
+    }
+
+    pub fn record(&self, update: &OpenInterestUpdate) -> Option<AnomalyDetection> {
+        let open_interest = update.open_interest;
+        if !self.config.enabled || !open_interest.is_finite() || open_interest <= 0.0 {
+            return None;
+        }
+
+        let key = format!("{}:{}", update.exchange, update.symbol);
+        let price = update
+            .price
+            .filter(|p| p.is_finite() && *p > 0.0)
+            .or_else(|| self.prices.get(&key).map(|p| *p));
+
+        let mut state = self.state.entry(key.clone()).or_default();
+        let window_start = update.timestamp - Duration::seconds(self.config.window_secs as i64);
+        while state
+            .readings
+            .front()
+            .is_some_and(|oldest| oldest.timestamp < window_start)
+        {
+            state.readings.pop_front();
+        }
+        let reference = state
+            .readings
+            .front()
+            .map(|r| (r.timestamp, r.open_interest, r.price));
+        state.readings.push_back(Reading {
+            timestamp: update.timestamp,
+            open_interest,
+            price,
+        });
+
+        let (since, previous, previous_price) = reference?;
+        let change_pct = (open_interest - previous) / previous * 100.0;
+        if change_pct.abs() < self.config.change_pct {
+            state.triggered = false;
+            return None;
+        }
+        if state.triggered {
+            return None;
+        }
+        state.triggered = true;
+        drop(state);
+
+        let price_change_pct = match (previous_price, price) {
+            (Some(before), Some(now)) => Some((now - before) / before * 100.0),
+            _ => None,
+        };
+        let price_direction = price_change_pct
+            .filter(|c| c.abs() >= self.config.price_change_pct)
+            .map(|c| c > 0.0);
+        let pattern = match (change_pct > 0.0, price_direction) {
+            (true, Some(true)) => OpenInterestPattern::LongBuildUp,
+            (true, Some(false)) => OpenInterestPattern::ShortBuildUp,
+            (true, None) => OpenInterestPattern::BuildUp,
+            (false, Some(false)) => OpenInterestPattern::LongUnwind,
+            (false, Some(true)) => OpenInterestPattern::ShortCovering,
+            (false, None) => OpenInterestPattern::Unwind,
+        };
+
+        let details = OpenInterestDetails {
+            pattern,
+            open_interest,
+            previous_open_interest: previous,
+            change_pct,
+            price_change_pct,
+            span_secs: (update.timestamp - since).num_seconds(),
+        };
+        Some(self.detection(update, &key, details))
+    }
+
+    fn detection(
+        &self,
+        update: &OpenInterestUpdate,
+        key: &str,
+        details: OpenInterestDetails,
+    ) -> AnomalyDetection {
+        let confidence =
+            confidence_from_exceedance(details.change_pct.abs(), self.config.change_pct);
+        let severity = severity_from_confidence(confidence);
+        let reason = AnomalyReason::new(
+            &update.exchange,
+            &update.symbol,
+            details.open_interest,
+            ReasonKind::OpenInterest {
+                pattern: details.pattern,
+                change_pct: details.change_pct,
+                price_change_pct: details.price_change_pct,
+            },
+        )
+        .with_baseline(details.previous_open_interest)
+        .with_threshold(self.config.change_pct)
+        .with_window_secs(details.span_secs as f64);
+        let description = reason.to_string();
+        info!("{}", description);
+
+        AnomalyDetection {
+            id: uuid::Uuid::new_v4(),
+            timestamp: update.timestamp,
+            symbol: update.symbol.clone(),
+            exchange: update.exchange.clone(),
+            anomaly_type: AnomalyType::OpenInterest,
+            severity,
+            confidence,
+            metrics: AnomalyMetrics {
+                current_value: details.open_interest,
+                expected_value: details.previous_open_interest,
+                deviation: details.open_interest - details.previous_open_interest,
+                z_score: None,
+                percentage_change: Some(details.change_pct),
+                historical_avg: None,
+                historical_std: None,
+                ..Default::default()
+            },
+            description,
+            reason: Some(reason),
+            warmup: self
+                .warmup
+                .as_ref()
+                .is_some_and(|w| w.is_warming_up(key, update.timestamp)),
+            details: Some(DetectionDetails::OpenInterest(details)),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn update(minute: i64, open_interest: f64, price: Option<f64>) -> OpenInterestUpdate {
+        OpenInterestUpdate {
+            exchange: "binance".to_string(),
+            symbol: "BTC/USDT".to_string(),
+            timestamp: Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap()
+                + Duration::minutes(minute),
+            open_interest,
+            price,
+        }
+    }
+
+    fn details(detection: &AnomalyDetection) -> &OpenInterestDetails {
+        let Some(DetectionDetails::OpenInterest(details)) = &detection.details else {
+            panic!("missing open interest details");
+        };
+        details
+    }
+
+    #[test]
+    fn test_rising_with_price_is_long_build_up() {
+        let detector = OpenInterestDetector::new(OpenInterestConfig::default());
+
+        // 1% more contracts a minute while the price climbs 0.5 a minute
+        let mut detections = Vec::new();
+        for i in 0..12 {
+            let reading = update(i, 10_000.0 + 100.0 * i as f64, Some(100.0 + 0.5 * i as f64));
+            detections.extend(detector.record(&reading).map(|d| (i, d)));
+        }
+
+        // 5% after five minutes, then quiet while it keeps building
+        assert_eq!(detections.len(), 1);
+        let (at, detection) = &detections[0];
+        assert_eq!(*at, 5);
+        assert_eq!(detection.anomaly_type, AnomalyType::OpenInterest);
+        let details = details(detection);
+        assert_eq!(details.pattern, OpenInterestPattern::LongBuildUp);
+        assert_eq!(details.previous_open_interest, 10_000.0);
+        assert_eq!(details.span_secs, 300);
+        assert!((details.price_change_pct.unwrap() - 2.5).abs() < 1e-9);
+        assert!(detection
+            .description
+            .starts_with("Open interest long build-up for binance/BTC/USDT: +5.00% in 5m"));
+    }
+
+    #[test]
+    fn test_rising_into_falling_trades_is_short_build_up() {
+        let detector = OpenInterestDetector::new(OpenInterestConfig::default());
+
+        // Readings carry no price, the last trade supplies it
+        detector.observe_price("binance", "BTC/USDT", 100.0);
+        assert!(detector.record(&update(0, 10_000.0, None)).is_none());
+        assert!(detector.record(&update(1, 10_100.0, None)).is_none());
+        detector.observe_price("binance", "BTC/USDT", 98.0);
+        let detection = detector.record(&update(2, 10_600.0, None)).unwrap();
+
+        let details = details(&detection);
+        assert_eq!(details.pattern, OpenInterestPattern::ShortBuildUp);
+        assert!((details.change_pct - 6.0).abs() < 1e-9);
+        assert!((details.price_change_pct.unwrap() + 2.0).abs() < 1e-9);
+        assert!(detection.description.contains("price -2.00%"));
+    }
+
+    #[test]
+    fn test_unwinds_and_flat_price() {
+        let detector = OpenInterestDetector::new(OpenInterestConfig::default());
+
+        // Longs closing into a falling price
+        assert!(detector.record(&update(0, 10_000.0, Some(100.0))).is_none());
+        let detection = detector.record(&update(3, 9_000.0, Some(97.0))).unwrap();
+        assert_eq!(details(&detection).pattern, OpenInterestPattern::LongUnwind);
+
+        // Back within 5% of the oldest reading re-arms, and a 0.2% price move reads as flat
+        assert!(detector.record(&update(4, 9_800.0, Some(100.0))).is_none());
+        let detection = detector.record(&update(5, 9_200.0, Some(100.2))).unwrap();
+        assert_eq!(details(&detection).pattern, OpenInterestPattern::Unwind);
+
+        // Readings older than the window are never a reference
+        let other = OpenInterestDetector::new(OpenInterestConfig::default());
+        assert!(other.record(&update(0, 10_000.0, None)).is_none());
+        assert!(other.record(&update(16, 12_000.0, None)).is_none());
+        let detection = other.record(&update(17, 13_000.0, None)).unwrap();
+        assert_eq!(details(&detection).pattern, OpenInterestPattern::BuildUp);
+        assert_eq!(details(&detection).price_change_pct, None);
+    }
+}
@@ -0,0 +1,734 @@
+use crate::{
+    detector::BandSide, funding::FundingTrigger, iceberg::BookSide, interval_label,
+    open_interest::OpenInterestPattern, volatility::VolatilityRegime, TimeframeZScore,
+};
+use chrono::{DateTime, Utc};
+use monitor_core::model::LiquidationSide;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// Why a detector fired, as numbers rather than prose. `AnomalyDetection::description` is this
+// rendered through Display, so notifiers and strategies can read the values directly and a
+// translation only has to replace the rendering.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnomalyReason {
+    pub exchange: String,
+    pub symbol: String,
+    // The reading that was judged, in the unit of the check named by `kind`
+    pub observed: f64,
+    // What it was judged against: a mean, median, previous reading or reference price
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub baseline: Option<f64>,
+    // The limit that was crossed, in whatever the check compares: a percentage move, a Z-score,
+    // a multiple of the baseline or the reading itself
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<f64>,
+    // Span the reading covers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window_secs: Option<f64>,
+    #[serde(flatten)]
+    pub kind: ReasonKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum ReasonKind {
+    Volume {
+        change_pct: f64,
+        z_score: f64,
+        // Set when judged against the same UTC hour on previous days
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        seasonal_hour: Option<u32>,
+        // Set when the window percentile took part, with the volume at it
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        percentile: Option<f64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        percentile_volume: Option<f64>,
+    },
+    Price {
+        change_pct: f64,
+        z_score: f64,
+    },
+    PriceTimeframes {
+        agreeing: usize,
+        z_scores: Vec<TimeframeZScore>,
+    },
+    Ewma {
+        metric: String,
+        z_score: f64,
+    },
+    Robust {
+        metric: String,
+        mad: f64,
+        modified_z_score: f64,
+    },
+    LargeTrade {
+        volume: f64,
+        price: f64,
+        multiple_of_median: f64,
+        percentile: f64,
+    },
+    DepthImbalance {
+        heavy_side: BookSide,
+        levels: usize,
+        bid_volume: f64,
+        ask_volume: f64,
+    },
+    SpreadWidening {
+        bid_price: f64,
+        ask_price: f64,
+        multiple: f64,
+        // `None` when judged against the absolute cap alone
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        z_score: Option<f64>,
+    },
+    Divergence {
+        high_exchange: String,
+        high_price: f64,
+        low_exchange: String,
+        low_price: f64,
+    },
+    CandleVolume {
+        interval: String,
+        multiple: f64,
+    },
+    CandleRange {
+        interval: String,
+        multiple: f64,
+        open: f64,
+        close: f64,
+    },
+    UpdateRate,
+    Volatility {
+        regime: VolatilityRegime,
+        ratio: f64,
+    },
+    RateOfChange {
+        label: String,
+        change_pct: f64,
+        since: DateTime<Utc>,
+    },
+    Iceberg {
+        side: BookSide,
+        level_price: f64,
+        fills: usize,
+        displayed_size: f64,
+        hidden_size_estimate: f64,
+    },
+    Liquidation {
+        direction: LiquidationSide,
+        long_notional: f64,
+        short_notional: f64,
+        liquidations: usize,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        z_score: Option<f64>,
+    },
+    Funding {
+        trigger: FundingTrigger,
+        sign_flip: bool,
+        interval_hours: f64,
+        annualized_pct: f64,
+    },
+    OpenInterest {
+        pattern: OpenInterestPattern,
+        change_pct: f64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        price_change_pct: Option<f64>,
+    },
+    CorrelationBreak {
+        leader_exchange: String,
+        leader_symbol: String,
+        returns: usize,
+    },
+    BollingerBreach {
+        side: BandSide,
+        consecutive: usize,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        band_width_percentile: Option<f64>,
+    },
+    PriceMove {
+        change_pct: f64,
+    },
+    WindowVolume {
+        multiple: f64,
+    },
+    UnusualVolume {
+        ratio: f64,
+    },
+    LowVolumeMove {
+        change_pct: f64,
+    },
+    FlashCrash {
+        drop_pct: f64,
+        periods: usize,
+    },
+    PumpAndDump {
+        pump_pct: f64,
+        dump_pct: f64,
+    },
+    WashTrading {
+        repetitions: usize,
+        avg_size: f64,
+        price_impact_pct: f64,
+    },
+}
+
+impl AnomalyReason {
+    pub fn new(exchange: &str, symbol: &str, observed: f64, kind: ReasonKind) -> Self {
+        Self {
+            exchange: exchange.to_string(),
+            symbol: symbol.to_string(),
+            observed,
+            baseline: None,
+            threshold: None,
+            window_secs: None,
+            kind,
+        }
+    }
+
+    pub fn with_baseline(mut self, baseline: f64) -> Self {
+        self.baseline = Some(baseline);
+        self
+    }
+
+    pub fn with_threshold(mut self, threshold: f64) -> Self {
+        self.threshold = Some(threshold);
+        self
+    }
+
+    pub fn with_window_secs(mut self, window_secs: f64) -> Self {
+        self.window_secs = Some(window_secs);
+        self
+    }
+}
+
+impl fmt::Display for AnomalyReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (exchange, symbol, observed) = (&self.exchange, &self.symbol, self.observed);
+        let baseline = self.baseline.unwrap_or_default();
+        let threshold = self.threshold.unwrap_or_default();
+        let window = self.window_secs.unwrap_or_default();
+        match &self.kind {
+            ReasonKind::Volume {
+                change_pct,
+                z_score,
+                seasonal_hour,
+                percentile,
+                percentile_volume,
+            } => write!(
+                f,
+                "Volume anomaly detected for {}/{}: current volume {:.2} is {:.1}% {}{} average ({:.2}), Z-score: {:.2}{}",
+                exchange,
+                symbol,
+                observed,
+                change_pct.abs(),
+                if *change_pct > 0.0 { "above" } else { "below" },
+                seasonal_hour.map_or(String::new(), |h| format!(" the {:02}:00 UTC", h)),
+                baseline,
+                z_score,
+                match (percentile, percentile_volume) {
+                    (Some(p), Some(volume)) => format!(", p{} {:.2}", p, volume),
+                    _ => String::new(),
+                }
+            ),
+            ReasonKind::Price { change_pct, z_score } => write!(
+                f,
+                "Price anomaly detected for {}/{}: price moved {:.2}% from {:.4} to {:.4}, Z-score: {:.2}",
+                exchange, symbol, change_pct, baseline, observed, z_score
+            ),
+            ReasonKind::PriceTimeframes { agreeing, z_scores } => {
+                let frames = z_scores
+                    .iter()
+                    .map(|s| match s.z_score {
+                        Some(z) => format!("{}s {:.2}", s.timeframe_secs, z),
+                        None => format!("{}s n/a", s.timeframe_secs),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(
+                    f,
+                    "Price anomaly confirmed on {}/{} timeframes for {}/{}: price {:.4}, Z-scores {}",
+                    agreeing,
+                    z_scores.len(),
+                    exchange,
+                    symbol,
+                    observed,
+                    frames
+                )
+            }
+            ReasonKind::Ewma { metric, z_score } => write!(
+                f,
+                "EWMA {} anomaly detected for {}/{}: {:.2} is {:.1} sigma from the adaptive \
+                 baseline {:.2}",
+                metric, exchange, symbol, observed, z_score, baseline
+            ),
+            ReasonKind::Robust { metric, mad, modified_z_score } => write!(
+                f,
+                "Robust {} anomaly detected for {}/{}: {:.2} against median {:.2} (MAD {:.4}), \
+                 modified Z-score: {:.2}",
+                metric, exchange, symbol, observed, baseline, mad, modified_z_score
+            ),
+            ReasonKind::LargeTrade { volume, price, multiple_of_median, percentile } => write!(
+                f,
+                "Large trade detected for {}/{}: notional {:.2} ({:.4} @ {:.4}) is {:.1}x the median, p{} {:.2}",
+                exchange, symbol, observed, volume, price, multiple_of_median, percentile, threshold
+            ),
+            ReasonKind::DepthImbalance { heavy_side, levels, bid_volume, ask_volume } => write!(
+                f,
+                "Depth imbalance detected for {}/{}: {} side holds {:.1}x the opposite volume over top {} levels \
+                 (bids {:.4}, asks {:.4})",
+                exchange,
+                symbol,
+                match heavy_side {
+                    BookSide::Bid => "bid",
+                    BookSide::Ask => "ask",
+                },
+                observed,
+                levels,
+                bid_volume,
+                ask_volume
+            ),
+            ReasonKind::SpreadWidening {
+                bid_price,
+                ask_price,
+                multiple,
+                z_score: Some(z),
+            } => write!(
+                f,
+                "Spread widening detected for {}/{}: spread {:.2} bps is {:.1}x the average {:.2} bps \
+                 (bid {:.4}, ask {:.4}), Z-score: {:.2}",
+                exchange, symbol, observed, multiple, baseline, bid_price, ask_price, z
+            ),
+            ReasonKind::SpreadWidening { bid_price, ask_price, z_score: None, .. } => write!(
+                f,
+                "Spread widening detected for {}/{}: spread {:.2} bps exceeds the {:.2} bps cap \
+                 (bid {:.4}, ask {:.4})",
+                exchange, symbol, observed, threshold, bid_price, ask_price
+            ),
+            ReasonKind::Divergence { high_exchange, high_price, low_exchange, low_price } => write!(
+                f,
+                "Price divergence detected for {}: {} at {:.4} vs {} at {:.4}, spread {:.1} bps for {:.0}s",
+                symbol, high_exchange, high_price, low_exchange, low_price, observed, window
+            ),
+            ReasonKind::CandleVolume { interval, multiple } => write!(
+                f,
+                "Candle volume anomaly [{}] for {}/{}: volume {:.2} is {:.1}x the average {:.2}",
+                interval, exchange, symbol, observed, multiple, baseline
+            ),
+            ReasonKind::CandleRange { interval, multiple, open, close } => write!(
+                f,
+                "Candle range anomaly [{}] for {}/{}: range {:.2}% is {:.1}x the average {:.2}%, \
+                 {:.4} to {:.4}",
+                interval, exchange, symbol, observed, multiple, baseline, open, close
+            ),
+            ReasonKind::UpdateRate => write!(
+                f,
+                "Order book update rate spike for {}/{}: {:.0} updates/s against a baseline of {:.1}/s",
+                exchange, symbol, observed, baseline
+            ),
+            ReasonKind::Volatility { regime, ratio } => write!(
+                f,
+                "Volatility {} for {}/{}: realized volatility {:.4}% is {:.2}x its median {:.4}%",
+                regime.as_str(),
+                exchange,
+                symbol,
+                observed * 100.0,
+                ratio,
+                baseline * 100.0
+            ),
+            ReasonKind::RateOfChange { label, change_pct, since } => write!(
+                f,
+                "Price rate of change [{}] for {}/{}: {:.2}% from {:.4} to {:.4} since {}",
+                label,
+                exchange,
+                symbol,
+                change_pct,
+                baseline,
+                observed,
+                since.format("%H:%M:%S")
+            ),
+            ReasonKind::Iceberg {
+                side,
+                level_price,
+                fills,
+                displayed_size,
+                hidden_size_estimate,
+            } => write!(
+                f,
+                "Possible iceberg on {}/{} {:?} at {:.8}: {:.4} executed over {} fills in {:.0}s against {:.4} displayed (~{:.4} hidden)",
+                exchange,
+                symbol,
+                side,
+                level_price,
+                observed,
+                fills,
+                window,
+                displayed_size,
+                hidden_size_estimate
+            ),
+            ReasonKind::Liquidation {
+                direction,
+                long_notional,
+                short_notional,
+                liquidations,
+                z_score,
+            } => write!(
+                f,
+                "{} liquidations on {}/{}: {:.2} notional in {}s ({:.2} long, {:.2} short over {} liquidations) against {:.2} per bucket, Z-score: {}",
+                match direction {
+                    LiquidationSide::Long => "Long",
+                    LiquidationSide::Short => "Short",
+                },
+                exchange,
+                symbol,
+                observed,
+                window,
+                long_notional,
+                short_notional,
+                liquidations,
+                baseline,
+                z_score.map_or("n/a".to_string(), |z| format!("{:.2}", z))
+            ),
+            ReasonKind::Funding { trigger, sign_flip, interval_hours, annualized_pct } => {
+                let crossed = match trigger {
+                    FundingTrigger::AboveMax => format!("above max {:.4}%", threshold * 100.0),
+                    FundingTrigger::BelowMin => format!("below min {:.4}%", threshold * 100.0),
+                    FundingTrigger::Change if *sign_flip => "flipped sign".to_string(),
+                    FundingTrigger::Change => "jumped".to_string(),
+                };
+                write!(
+                    f,
+                    "Funding rate for {}/{} {}: predicted {:.4}% per {}h (previous {}), {:.1}% annualized",
+                    exchange,
+                    symbol,
+                    crossed,
+                    observed * 100.0,
+                    interval_hours,
+                    self.baseline
+                        .map_or("n/a".to_string(), |p| format!("{:.4}%", p * 100.0)),
+                    annualized_pct
+                )
+            }
+            ReasonKind::OpenInterest { pattern, change_pct, price_change_pct } => write!(
+                f,
+                "Open interest {} for {}/{}: {:+.2}% in {} to {:.2} from {:.2}, {}",
+                pattern,
+                exchange,
+                symbol,
+                change_pct,
+                interval_label(window.max(0.0) as u64),
+                observed,
+                baseline,
+                match price_change_pct {
+                    Some(change) => format!("price {:+.2}%", change),
+                    None => "price unknown".to_string(),
+                }
+            ),
+            ReasonKind::CorrelationBreak { leader_exchange, leader_symbol, returns } => write!(
+                f,
+                "Correlation break for {}/{} against {}/{}: {:.2} over the last {} returns \
+                 against {:.2} historically, below {:.2} for {}",
+                exchange,
+                symbol,
+                leader_exchange,
+                leader_symbol,
+                observed,
+                returns,
+                baseline,
+                threshold,
+                interval_label(window.max(0.0) as u64)
+            ),
+            ReasonKind::BollingerBreach { side, consecutive, band_width_percentile } => write!(
+                f,
+                "Price closed {} Bollinger band for {}/{}: {:.4} against {:.4} (mean {:.4}) for \
+                 {} prices in a row, band width {}",
+                match side {
+                    BandSide::Upper => "above the upper",
+                    BandSide::Lower => "below the lower",
+                },
+                exchange,
+                symbol,
+                observed,
+                threshold,
+                baseline,
+                consecutive,
+                match band_width_percentile {
+                    Some(percentile) => format!("p{:.0} of its history", percentile),
+                    None => "percentile n/a".to_string(),
+                }
+            ),
+            ReasonKind::PriceMove { change_pct } => write!(
+                f,
+                "Price moved {:.2}% on {}/{} ({:.2} -> {:.2}), threshold {:.2}%",
+                change_pct, exchange, symbol, baseline, observed, threshold
+            ),
+            ReasonKind::WindowVolume { multiple } => write!(
+                f,
+                "Volume {:.4} on {}/{} is {:.1}x the window mean {:.4}",
+                observed, exchange, symbol, multiple, baseline
+            ),
+            ReasonKind::UnusualVolume { ratio } => write!(
+                f,
+                "Unusual volume detected for {}/{}: current volume is {:.1}x average",
+                exchange, symbol, ratio
+            ),
+            ReasonKind::LowVolumeMove { change_pct } => write!(
+                f,
+                "Potential price manipulation for {}/{}: {:.2}% price change with low volume",
+                exchange, symbol, change_pct
+            ),
+            ReasonKind::FlashCrash { drop_pct, periods } => write!(
+                f,
+                "Flash crash detected for {}/{}: {:.2}% drop in {} periods",
+                exchange, symbol, drop_pct, periods
+            ),
+            ReasonKind::PumpAndDump { pump_pct, dump_pct } => write!(
+                f,
+                "Pump and dump pattern detected for {}/{}: +{:.1}% pump, -{:.1}% dump",
+                exchange, symbol, pump_pct, dump_pct
+            ),
+            ReasonKind::WashTrading { repetitions, avg_size, price_impact_pct } => write!(
+                f,
+                "Possible wash trading for {}/{}: {} trades alternating buy and sell at ~{:.4} size within {:.0}s, {:.3}% price impact",
+                exchange, symbol, repetitions, avg_size, window, price_impact_pct
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn reason(observed: f64, kind: ReasonKind) -> AnomalyReason {
+        AnomalyReason::new("binance", "BTC/USDT", observed, kind)
+    }
+
+    fn samples() -> Vec<(AnomalyReason, &'static str)> {
+        vec![
+            (
+                reason(
+                    250.0,
+                    ReasonKind::Volume {
+                        change_pct: 150.0,
+                        z_score: 4.5,
+                        seasonal_hour: Some(9),
+                        percentile: None,
+                        percentile_volume: None,
+                    },
+                )
+                .with_baseline(100.0)
+                .with_threshold(3.0),
+                "Volume anomaly detected for binance/BTC/USDT: current volume 250.00 is 150.0% \
+                 above the 09:00 UTC average (100.00), Z-score: 4.50",
+            ),
+            (
+                reason(
+                    250.0,
+                    ReasonKind::Volume {
+                        change_pct: 150.0,
+                        z_score: 1.8,
+                        seasonal_hour: None,
+                        percentile: Some(99.0),
+                        percentile_volume: Some(180.0),
+                    },
+                )
+                .with_baseline(100.0)
+                .with_threshold(180.0),
+                "Volume anomaly detected for binance/BTC/USDT: current volume 250.00 is 150.0% \
+                 above average (100.00), Z-score: 1.80, p99 180.00",
+            ),
+            (
+                reason(95.0, ReasonKind::Price { change_pct: -5.0, z_score: -3.2 })
+                    .with_baseline(100.0)
+                    .with_threshold(2.0),
+                "Price anomaly detected for binance/BTC/USDT: price moved -5.00% from 100.0000 \
+                 to 95.0000, Z-score: -3.20",
+            ),
+            (
+                reason(
+                    101.5,
+                    ReasonKind::PriceTimeframes {
+                        agreeing: 2,
+                        z_scores: vec![
+                            TimeframeZScore { timeframe_secs: 1, z_score: Some(3.5) },
+                            TimeframeZScore { timeframe_secs: 60, z_score: Some(3.1) },
+                            TimeframeZScore { timeframe_secs: 300, z_score: None },
+                        ],
+                    },
+                )
+                .with_baseline(100.0)
+                .with_threshold(3.0)
+                .with_window_secs(1.0),
+                "Price anomaly confirmed on 2/3 timeframes for binance/BTC/USDT: price 101.5000, \
+                 Z-scores 1s 3.50, 60s 3.10, 300s n/a",
+            ),
+            (
+                reason(
+                    80.0,
+                    ReasonKind::SpreadWidening {
+                        bid_price: 99.6,
+                        ask_price: 100.4,
+                        multiple: 1.6,
+                        z_score: None,
+                    },
+                )
+                .with_threshold(50.0),
+                "Spread widening detected for binance/BTC/USDT: spread 80.00 bps exceeds the \
+                 50.00 bps cap (bid 99.6000, ask 100.4000)",
+            ),
+            (
+                AnomalyReason::new(
+                    "okx",
+                    "BTC/USDT",
+                    20.0,
+                    ReasonKind::Divergence {
+                        high_exchange: "okx".to_string(),
+                        high_price: 50100.0,
+                        low_exchange: "binance".to_string(),
+                        low_price: 50000.0,
+                    },
+                )
+                .with_threshold(15.0)
+                .with_window_secs(45.0),
+                "Price divergence detected for BTC/USDT: okx at 50100.0000 vs binance at \
+                 50000.0000, spread 20.0 bps for 45s",
+            ),
+            (
+                reason(25.0, ReasonKind::UpdateRate)
+                    .with_baseline(5.0)
+                    .with_threshold(25.0)
+                    .with_window_secs(1.0),
+                "Order book update rate spike for binance/BTC/USDT: 25 updates/s against a \
+                 baseline of 5.0/s",
+            ),
+            (
+                reason(
+                    102.5,
+                    ReasonKind::RateOfChange {
+                        label: "5m".to_string(),
+                        change_pct: 2.5,
+                        since: Utc.with_ymd_and_hms(2024, 3, 1, 11, 55, 0).unwrap(),
+                    },
+                )
+                .with_baseline(100.0)
+                .with_threshold(2.0)
+                .with_window_secs(300.0),
+                "Price rate of change [5m] for binance/BTC/USDT: 2.50% from 100.0000 to \
+                 102.5000 since 11:55:00",
+            ),
+            (
+                reason(
+                    6_000_000.0,
+                    ReasonKind::Liquidation {
+                        direction: LiquidationSide::Long,
+                        long_notional: 5_500_000.0,
+                        short_notional: 500_000.0,
+                        liquidations: 42,
+                        z_score: None,
+                    },
+                )
+                .with_baseline(1_000_000.0)
+                .with_threshold(5_000_000.0)
+                .with_window_secs(60.0),
+                "Long liquidations on binance/BTC/USDT: 6000000.00 notional in 60s (5500000.00 \
+                 long, 500000.00 short over 42 liquidations) against 1000000.00 per bucket, \
+                 Z-score: n/a",
+            ),
+            (
+                reason(
+                    0.0015,
+                    ReasonKind::Funding {
+                        trigger: FundingTrigger::AboveMax,
+                        sign_flip: false,
+                        interval_hours: 8.0,
+                        annualized_pct: 164.4,
+                    },
+                )
+                .with_threshold(0.001)
+                .with_window_secs(28_800.0),
+                "Funding rate for binance/BTC/USDT above max 0.1000%: predicted 0.1500% per 8h \
+                 (previous n/a), 164.4% annualized",
+            ),
+            (
+                reason(
+                    1125.0,
+                    ReasonKind::OpenInterest {
+                        pattern: OpenInterestPattern::LongBuildUp,
+                        change_pct: 12.5,
+                        price_change_pct: Some(2.0),
+                    },
+                )
+                .with_baseline(1000.0)
+                .with_threshold(10.0)
+                .with_window_secs(900.0),
+                "Open interest long build-up for binance/BTC/USDT: +12.50% in 15m to 1125.00 \
+                 from 1000.00, price +2.00%",
+            ),
+            (
+                AnomalyReason::new(
+                    "binance",
+                    "ETH/USDT",
+                    0.05,
+                    ReasonKind::CorrelationBreak {
+                        leader_exchange: "binance".to_string(),
+                        leader_symbol: "BTC/USDT".to_string(),
+                        returns: 120,
+                    },
+                )
+                .with_baseline(0.85)
+                .with_threshold(0.2)
+                .with_window_secs(300.0),
+                "Correlation break for binance/ETH/USDT against binance/BTC/USDT: 0.05 over the \
+                 last 120 returns against 0.85 historically, below 0.20 for 5m",
+            ),
+            (
+                reason(
+                    103.5,
+                    ReasonKind::BollingerBreach {
+                        side: BandSide::Upper,
+                        consecutive: 3,
+                        band_width_percentile: Some(12.0),
+                    },
+                )
+                .with_baseline(100.5)
+                .with_threshold(102.25),
+                "Price closed above the upper Bollinger band for binance/BTC/USDT: 103.5000 \
+                 against 102.2500 (mean 100.5000) for 3 prices in a row, band width p12 of its \
+                 history",
+            ),
+            (
+                reason(105.0, ReasonKind::PumpAndDump { pump_pct: 40.0, dump_pct: 25.0 })
+                    .with_baseline(100.0)
+                    .with_threshold(20.0),
+                "Pump and dump pattern detected for binance/BTC/USDT: +40.0% pump, -25.0% dump",
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_descriptions_keep_detector_wording() {
+        for (reason, expected) in samples() {
+            assert_eq!(reason.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn test_reason_round_trips_through_json() {
+        for (reason, _) in samples() {
+            let json = serde_json::to_string(&reason).unwrap();
+            let parsed: AnomalyReason = serde_json::from_str(&json).unwrap();
+            assert_eq!(parsed, reason);
+        }
+
+        // The kind's fields sit next to the common ones, unset figures are left out
+        let (volume, _) = samples().remove(0);
+        let value = serde_json::to_value(&volume).unwrap();
+        assert_eq!(value["kind"], "volume");
+        assert_eq!(value["observed"], 250.0);
+        assert_eq!(value["seasonal_hour"], 9);
+        assert!(value.get("window_secs").is_none());
+    }
+}
@@ -0,0 +1,150 @@
+use crate::snapshot::HourMoments;
+use chrono::{DateTime, Timelike, Utc};
+use monitor_core::SeasonalityConfig;
+use std::collections::VecDeque;
+
+const SECS_PER_DAY: i64 = 86_400;
+
+// Mean and standard deviation of a series for each UTC hour of day, pooled over the previous
+// `max_days` days. An hour's baseline leaves out the current day, so today's samples are judged
+// against earlier days only, and is only given once `min_days` of them have samples.
+#[derive(Debug, Clone)]
+pub struct SeasonalBaseline {
+    config: SeasonalityConfig,
+    // Per hour of day, oldest day first
+    hours: Vec<VecDeque<HourMoments>>,
+}
+
+impl SeasonalBaseline {
+    pub fn new(config: SeasonalityConfig) -> Self {
+        Self {
+            config,
+            hours: vec![VecDeque::new(); 24],
+        }
+    }
+
+    fn slot(at: DateTime<Utc>) -> (i64, u32) {
+        (at.timestamp().div_euclid(SECS_PER_DAY), at.hour())
+    }
+
+    pub fn push(&mut self, at: DateTime<Utc>, value: f64) {
+        let (day, hour) = Self::slot(at);
+        let days = &mut self.hours[hour as usize];
+        if days.back().is_none_or(|last| last.day < day) {
+            days.push_back(HourMoments {
+                day,
+                hour,
+                count: 0,
+                sum: 0.0,
+                sum_sq: 0.0,
+            });
+        }
+        // Late samples only count towards a day already started
+        let Some(moments) = days.iter_mut().rev().find(|m| m.day == day) else {
+            return;
+        };
+        moments.count += 1;
+        moments.sum += value;
+        moments.sum_sq += value * value;
+
+        let oldest = day - self.config.max_days as i64;
+        while days.front().is_some_and(|first| first.day < oldest) {
+            days.pop_front();
+        }
+    }
+
+    // Mean and standard deviation for the hour `at` falls in, from the days before it
+    pub fn baseline(&self, at: DateTime<Utc>) -> Option<(f64, f64)> {
+        let (day, hour) = Self::slot(at);
+        let earliest = day - self.config.max_days as i64;
+        let past = self.hours[hour as usize]
+            .iter()
+            .filter(|m| m.day < day && m.day >= earliest && m.count > 0);
+        let (days, count, sum, sum_sq) = past.fold((0, 0, 0.0, 0.0), |acc, m| {
+            (acc.0 + 1, acc.1 + m.count, acc.2 + m.sum, acc.3 + m.sum_sq)
+        });
+        if days < self.config.min_days.max(1) {
+            return None;
+        }
+        let mean = sum / count as f64;
+        let variance = (sum_sq / count as f64 - mean * mean).max(0.0);
+        Some((mean, variance.sqrt()))
+    }
+
+    pub fn moments(&self) -> Vec<HourMoments> {
+        self.hours.iter().flatten().copied().collect()
+    }
+
+    pub fn restore(&mut self, moments: &[HourMoments]) {
+        self.clear();
+        let mut sorted = moments.to_vec();
+        sorted.sort_by_key(|m| (m.hour, m.day));
+        for m in sorted.into_iter().filter(|m| m.hour < 24) {
+            let days = &mut self.hours[m.hour as usize];
+            if days.back().is_none_or(|last| last.day < m.day) {
+                days.push_back(m);
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        for days in &mut self.hours {
+            days.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn baseline(min_days: usize, max_days: usize) -> SeasonalBaseline {
+        SeasonalBaseline::new(SeasonalityConfig {
+            enabled: true,
+            min_days,
+            max_days,
+        })
+    }
+
+    #[test]
+    fn test_hour_baseline_from_previous_days() {
+        let mut seasonal = baseline(2, 3);
+        let midnight = Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+
+        // 00:xx gets 100 and 110 a day, 01:xx 10
+        for day in 0..2 {
+            let start = midnight + Duration::days(day);
+            seasonal.push(start + Duration::minutes(5), 100.0);
+            seasonal.push(start + Duration::minutes(35), 110.0);
+            seasonal.push(start + Duration::minutes(65), 10.0);
+        }
+        assert_eq!(seasonal.baseline(midnight + Duration::hours(25)), None);
+
+        let third = midnight + Duration::days(2);
+        assert_eq!(seasonal.baseline(third), Some((105.0, 5.0)));
+        assert_eq!(seasonal.baseline(third + Duration::minutes(90)), Some((10.0, 0.0)));
+
+        // Today's samples stay out of today's baseline
+        seasonal.push(third + Duration::minutes(1), 1_000.0);
+        assert_eq!(seasonal.baseline(third + Duration::minutes(2)), Some((105.0, 5.0)));
+
+        // Days past `max_days` age out
+        assert_eq!(seasonal.baseline(midnight + Duration::days(5)), None);
+    }
+
+    #[test]
+    fn test_moments_round_trip() {
+        let mut seasonal = baseline(1, 28);
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+        for i in 0..100 {
+            seasonal.push(start + Duration::minutes(37 * i), i as f64);
+        }
+
+        let mut restored = baseline(1, 28);
+        restored.restore(&seasonal.moments());
+        assert_eq!(restored.moments(), seasonal.moments());
+        let later = start + Duration::days(3);
+        assert_eq!(restored.baseline(later), seasonal.baseline(later));
+    }
+}
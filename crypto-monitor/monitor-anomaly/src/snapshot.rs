@@ -0,0 +1,182 @@
+use crate::detector::AnomalyDetectorManager;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use monitor_core::{
+    coordination::LeaderElection, scheduler::PeriodicTask,
+    storage::repository::DetectorStateRepository, Result,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{debug, info};
+
+// Saved under this name in the detector_state table
+const STATE_NAME: &str = "anomaly_detectors";
+
+// History of one detector, oldest value first
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DetectorState {
+    pub window: Vec<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_price: Option<f64>,
+    // Exponentially weighted baselines keep moments instead of a window
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ewma: Option<EwmaState>,
+    // Seasonal baselines keep moments per day and hour of day
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub seasonal: Vec<HourMoments>,
+    // Multi-timeframe detectors keep the closed bars of each timeframe
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub timeframes: Vec<TimeframeState>,
+}
+
+impl DetectorState {
+    // Samples behind the state, counted towards a restored pair's warm-up
+    pub fn samples(&self) -> usize {
+        let bars = self.timeframes.iter().map(|tf| tf.bars.len()).max().unwrap_or(0);
+        let ewma = self.ewma.map(|e| e.samples as usize).unwrap_or(0);
+        self.window.len().max(bars).max(ewma)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimeframeState {
+    pub secs: u64,
+    // Trailing means at each bar close, oldest first
+    pub bars: Vec<f64>,
+}
+
+// Samples seen in one UTC hour of one day, days counted from the Unix epoch
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HourMoments {
+    pub day: i64,
+    pub hour: u32,
+    pub count: u64,
+    pub sum: f64,
+    pub sum_sq: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EwmaState {
+    pub mean: f64,
+    pub variance: f64,
+    pub samples: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PairSnapshot {
+    pub exchange: String,
+    pub symbol: String,
+    // Keyed by each detector's state name
+    pub detectors: HashMap<String, DetectorState>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DetectorSnapshot {
+    pub taken_at: DateTime<Utc>,
+    pub pairs: Vec<PairSnapshot>,
+}
+
+impl DetectorSnapshot {
+    pub fn is_stale(&self, now: DateTime<Utc>, max_age: Duration) -> bool {
+        now - self.taken_at > max_age
+    }
+
+    // Only the hour-of-day baselines, which span days and outlive any restart gap, of the
+    // pairs that have them
+    pub fn long_horizon(&self) -> DetectorSnapshot {
+        let pairs = self
+            .pairs
+            .iter()
+            .filter_map(|pair| {
+                let detectors: HashMap<String, DetectorState> = pair
+                    .detectors
+                    .iter()
+                    .filter(|(_, state)| !state.seasonal.is_empty())
+                    .map(|(name, state)| {
+                        let seasonal = DetectorState {
+                            seasonal: state.seasonal.clone(),
+                            ..Default::default()
+                        };
+                        (name.clone(), seasonal)
+                    })
+                    .collect();
+                (!detectors.is_empty()).then(|| PairSnapshot {
+                    exchange: pair.exchange.clone(),
+                    symbol: pair.symbol.clone(),
+                    detectors,
+                })
+            })
+            .collect();
+        DetectorSnapshot {
+            taken_at: self.taken_at,
+            pairs,
+        }
+    }
+}
+
+// Saves the manager's detector windows on the scheduler. With coordination every instance
+// keeps windows but only the leader saves them, so instances don't overwrite each other
+pub struct DetectorSnapshotWriter {
+    repository: DetectorStateRepository,
+    manager: Arc<AnomalyDetectorManager>,
+    leadership: Option<Arc<LeaderElection>>,
+}
+
+impl DetectorSnapshotWriter {
+    pub fn new(repository: DetectorStateRepository, manager: Arc<AnomalyDetectorManager>) -> Self {
+        Self {
+            repository,
+            manager,
+            leadership: None,
+        }
+    }
+
+    pub fn with_leadership(mut self, leadership: Arc<LeaderElection>) -> Self {
+        self.leadership = Some(leadership);
+        self
+    }
+}
+
+#[async_trait]
+impl PeriodicTask for DetectorSnapshotWriter {
+    fn name(&self) -> &str {
+        "detector_state"
+    }
+
+    async fn run(&self) -> Result<()> {
+        if self.leadership.as_ref().is_some_and(|l| !l.is_leader()) {
+            debug!("Not the leader, leaving detector windows to it");
+            return Ok(());
+        }
+        let snapshot = self.manager.snapshot(Utc::now());
+        let state = serde_json::to_string(&snapshot)?;
+        self.repository.save(STATE_NAME, snapshot.taken_at, &state).await?;
+        debug!("Saved detector windows for {} pairs", snapshot.pairs.len());
+        Ok(())
+    }
+}
+
+// Restores the last saved windows into the manager unless they are older than `max_age`, in
+// which case only the hour-of-day baselines are kept. Returns the number of exchange and symbol
+// pairs restored
+pub async fn restore_saved(
+    repository: &DetectorStateRepository,
+    manager: &AnomalyDetectorManager,
+    max_age: Duration,
+    now: DateTime<Utc>,
+) -> Result<usize> {
+    let Some(record) = repository.load(STATE_NAME).await? else {
+        return Ok(0);
+    };
+    let snapshot: DetectorSnapshot = serde_json::from_str(&record.state)?;
+    if snapshot.is_stale(now, max_age) {
+        info!(
+            "Discarding detector windows saved at {}, older than {}s, keeping seasonal baselines",
+            snapshot.taken_at,
+            max_age.num_seconds()
+        );
+        return Ok(manager.restore_long_horizon(&snapshot));
+    }
+    Ok(manager.restore(&snapshot))
+}
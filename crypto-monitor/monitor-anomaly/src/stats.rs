@@ -0,0 +1,56 @@
+use crate::AnomalyDetection;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DetectorStats {
+    pub samples_seen: u64,
+    pub anomalies_emitted: u64,
+    pub last_anomaly_at: Option<DateTime<Utc>>,
+    // Share of the detector's window holding samples, for detectors that keep one
+    pub window_fill: Option<f64>,
+}
+
+impl DetectorStats {
+    // Counts one sample and passes the detector's verdict on it through
+    pub fn record(&mut self, detection: Option<AnomalyDetection>) -> Option<AnomalyDetection> {
+        self.samples_seen += 1;
+        if let Some(detection) = &detection {
+            self.record_anomaly(detection);
+        }
+        detection
+    }
+
+    pub fn record_anomaly(&mut self, detection: &AnomalyDetection) {
+        self.anomalies_emitted += 1;
+        self.last_anomaly_at = Some(
+            self.last_anomaly_at
+                .map_or(detection.timestamp, |last| last.max(detection.timestamp)),
+        );
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DetectorStatsEntry {
+    pub exchange: String,
+    pub symbol: String,
+    pub detector: String,
+    #[serde(flatten)]
+    pub stats: DetectorStats,
+}
+
+// A detector and what it has done since it was created. Counters are lifetime totals and
+// survive resets, only the window fill follows the detector's state.
+pub(crate) struct Tracked<D> {
+    pub(crate) detector: D,
+    pub(crate) stats: DetectorStats,
+}
+
+impl<D> Tracked<D> {
+    pub(crate) fn new(detector: D) -> Self {
+        Self {
+            detector,
+            stats: DetectorStats::default(),
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use crate::{AnomalyDetection, AnomalySeverity, DetectionDetails};
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use monitor_core::AlertSuppressionConfig;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SuppressionStats {
+    // Dropped inside the minimum interval
+    pub cooldown: u64,
+    // Dropped past the interval as too close to the last detection sent
+    pub duplicate: u64,
+    // Critical detections let through that would otherwise have been dropped
+    pub critical_bypassed: u64,
+    pub tracked: usize,
+}
+
+struct LastSent {
+    at: DateTime<Utc>,
+    severity: AnomalySeverity,
+    value: f64,
+}
+
+// Drops detections that repeat one already sent for the same exchange, symbol and type, so a
+// volatile market doesn't produce a stream of near-identical alerts and trades
+pub struct AlertSuppressor {
+    config: AlertSuppressionConfig,
+    last: DashMap<String, LastSent>,
+    cooldown: AtomicU64,
+    duplicate: AtomicU64,
+    critical_bypassed: AtomicU64,
+}
+
+impl AlertSuppressor {
+    pub fn new(config: AlertSuppressionConfig) -> Self {
+        Self {
+            config,
+            last: DashMap::new(),
+            cooldown: AtomicU64::new(0),
+            duplicate: AtomicU64::new(0),
+            critical_bypassed: AtomicU64::new(0),
+        }
+    }
+
+    // Whether the detection goes out, recording it as the last one sent when it does. Warm-up
+    // detections are never suppressed and don't start a cooldown.
+    pub fn admit(&self, anomaly: &AnomalyDetection) -> bool {
+        if !self.config.enabled || anomaly.warmup {
+            return true;
+        }
+
+        let mut key =
+            format!("{}:{}:{:?}", anomaly.exchange, anomaly.symbol, anomaly.anomaly_type);
+        // Each rate of change horizon cools down on its own
+        if let Some(DetectionDetails::RateOfChange(details)) = &anomaly.details {
+            key.push_str(&format!(":{}", details.horizon_secs));
+        }
+        let suppressed_by = self.last.get(&key).and_then(|last| self.check(&last, anomaly));
+        if let Some(counter) = suppressed_by {
+            if !self.config.critical_bypass || anomaly.severity != AnomalySeverity::Critical {
+                counter.fetch_add(1, Ordering::Relaxed);
+                return false;
+            }
+            self.critical_bypassed.fetch_add(1, Ordering::Relaxed);
+        }
+
+        self.record(key, anomaly);
+        true
+    }
+
+    // The counter the detection would be suppressed under, if any
+    fn check(&self, last: &LastSent, anomaly: &AnomalyDetection) -> Option<&AtomicU64> {
+        let elapsed = anomaly.timestamp - last.at;
+        if elapsed < Duration::seconds(self.config.min_interval_secs as i64) {
+            return Some(&self.cooldown);
+        }
+
+        let current = anomaly.metrics.current_value;
+        let moved_pct = if last.value != 0.0 {
+            ((current - last.value) / last.value).abs() * 100.0
+        } else if current == 0.0 {
+            0.0
+        } else {
+            f64::INFINITY
+        };
+        let duplicate = self.config.min_metric_delta_pct > 0.0
+            && elapsed < Duration::seconds(self.config.dedup_window_secs as i64)
+            && anomaly.severity <= last.severity
+            && moved_pct < self.config.min_metric_delta_pct;
+        duplicate.then_some(&self.duplicate)
+    }
+
+    fn record(&self, key: String, anomaly: &AnomalyDetection) {
+        if !self.last.contains_key(&key) && self.last.len() >= self.config.max_tracked {
+            self.prune(anomaly.timestamp);
+            if self.last.len() >= self.config.max_tracked {
+                self.evict_oldest();
+            }
+        }
+
+        self.last.insert(
+            key,
+            LastSent {
+                at: anomaly.timestamp,
+                severity: anomaly.severity.clone(),
+                value: anomaly.metrics.current_value,
+            },
+        );
+    }
+
+    // Drops triples whose last detection no longer suppresses anything
+    pub fn prune(&self, now: DateTime<Utc>) {
+        let horizon = Duration::seconds(
+            self.config.min_interval_secs.max(self.config.dedup_window_secs) as i64,
+        );
+        self.last.retain(|_, last| now - last.at < horizon);
+    }
+
+    fn evict_oldest(&self) {
+        let oldest = self
+            .last
+            .iter()
+            .min_by_key(|entry| entry.value().at)
+            .map(|entry| entry.key().clone());
+        if let Some(key) = oldest {
+            self.last.remove(&key);
+        }
+    }
+
+    pub fn stats(&self) -> SuppressionStats {
+        SuppressionStats {
+            cooldown: self.cooldown.load(Ordering::Relaxed),
+            duplicate: self.duplicate.load(Ordering::Relaxed),
+            critical_bypassed: self.critical_bypassed.load(Ordering::Relaxed),
+            tracked: self.last.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{momentum::RateOfChangeDetails, AnomalyMetrics};
+    use monitor_core::AnomalyType;
+
+    fn detection(severity: AnomalySeverity, value: f64, at: DateTime<Utc>) -> AnomalyDetection {
+        AnomalyDetection {
+            id: uuid::Uuid::new_v4(),
+            timestamp: at,
+            symbol: "BTC/USDT".to_string(),
+            exchange: "binance".to_string(),
+            anomaly_type: AnomalyType::VolumeSpike,
+            confidence: severity.confidence_floor(),
+            severity,
+            metrics: AnomalyMetrics {
+                current_value: value,
+                expected_value: 1.0,
+                deviation: value - 1.0,
+                z_score: Some(4.0),
+                percentage_change: None,
+                historical_avg: None,
+                historical_std: None,
+                ..Default::default()
+            },
+            description: "Volume spike".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_minimum_interval_boundary() {
+        let suppressor = AlertSuppressor::new(AlertSuppressionConfig {
+            min_metric_delta_pct: 0.0,
+            ..Default::default()
+        });
+        let start = Utc::now();
+
+        assert!(suppressor.admit(&detection(AnomalySeverity::Medium, 5.0, start)));
+        let just_before = start + Duration::seconds(60) - Duration::milliseconds(1);
+        assert!(!suppressor.admit(&detection(AnomalySeverity::Medium, 5.0, just_before)));
+        assert!(suppressor.admit(&detection(
+            AnomalySeverity::Medium,
+            5.0,
+            start + Duration::seconds(60)
+        )));
+
+        // Other types and exchanges have their own interval
+        let mut other = detection(AnomalySeverity::Medium, 5.0, start + Duration::seconds(61));
+        other.anomaly_type = AnomalyType::PriceSpike;
+        assert!(suppressor.admit(&other));
+        other.exchange = "okx".to_string();
+        assert!(suppressor.admit(&other));
+
+        // and so does each rate of change horizon
+        let horizon = |horizon_secs: u64| {
+            let mut detection = detection(AnomalySeverity::Medium, 5.0, start);
+            detection.anomaly_type = AnomalyType::RateOfChange;
+            detection.details = Some(DetectionDetails::RateOfChange(RateOfChangeDetails {
+                horizon_secs,
+                reference_at: start - Duration::seconds(horizon_secs as i64),
+            }));
+            detection
+        };
+        assert!(suppressor.admit(&horizon(60)));
+        assert!(suppressor.admit(&horizon(300)));
+        assert!(!suppressor.admit(&horizon(60)));
+
+        assert_eq!(
+            suppressor.stats(),
+            SuppressionStats {
+                cooldown: 2,
+                duplicate: 0,
+                critical_bypassed: 0,
+                tracked: 5,
+            }
+        );
+    }
+
+    #[test]
+    fn test_near_identical_metrics_are_deduplicated() {
+        let suppressor = AlertSuppressor::new(AlertSuppressionConfig::default());
+        let start = Utc::now();
+        let at = |secs: i64| start + Duration::seconds(secs);
+
+        assert!(suppressor.admit(&detection(AnomalySeverity::Medium, 10.0, at(0))));
+        assert!(!suppressor.admit(&detection(AnomalySeverity::Medium, 10.2, at(90))));
+        // A real move, or a more severe detection, still goes out
+        assert!(suppressor.admit(&detection(AnomalySeverity::Medium, 11.0, at(120))));
+        assert!(suppressor.admit(&detection(AnomalySeverity::High, 11.0, at(200))));
+        // and so does the same detection once the dedup window has passed
+        assert!(!suppressor.admit(&detection(AnomalySeverity::High, 11.0, at(499))));
+        assert!(suppressor.admit(&detection(AnomalySeverity::High, 11.0, at(500))));
+
+        assert_eq!(suppressor.stats().duplicate, 2);
+    }
+
+    #[test]
+    fn test_critical_bypass_is_optional() {
+        let start = Utc::now();
+        let later = start + Duration::seconds(5);
+
+        let suppressor = AlertSuppressor::new(AlertSuppressionConfig::default());
+        assert!(suppressor.admit(&detection(AnomalySeverity::High, 5.0, start)));
+        assert!(suppressor.admit(&detection(AnomalySeverity::Critical, 5.0, later)));
+        assert_eq!(suppressor.stats().critical_bypassed, 1);
+
+        let suppressor = AlertSuppressor::new(AlertSuppressionConfig {
+            critical_bypass: false,
+            ..Default::default()
+        });
+        assert!(suppressor.admit(&detection(AnomalySeverity::High, 5.0, start)));
+        assert!(!suppressor.admit(&detection(AnomalySeverity::Critical, 5.0, later)));
+        assert_eq!(suppressor.stats().cooldown, 1);
+    }
+
+    #[test]
+    fn test_warmup_detections_pass_without_starting_a_cooldown() {
+        let suppressor = AlertSuppressor::new(AlertSuppressionConfig::default());
+        let start = Utc::now();
+
+        let mut warming = detection(AnomalySeverity::Medium, 5.0, start);
+        warming.warmup = true;
+        assert!(suppressor.admit(&warming));
+        assert!(suppressor.admit(&warming));
+        assert!(suppressor.admit(&detection(AnomalySeverity::Medium, 5.0, start)));
+        assert_eq!(suppressor.stats().tracked, 1);
+    }
+}
@@ -0,0 +1,446 @@
+use crate::{
+    reason::{AnomalyReason, ReasonKind},
+    severity_from_thresholds, AnomalyDetection, AnomalyMetrics,
+};
+use chrono::{DateTime, Duration, Utc};
+use monitor_core::{AnomalyType, SeverityThresholds};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimpleThresholdConfig {
+    // Absolute trade-to-trade price change, in percent
+    pub price_change_threshold: f64,
+    // Volume multiple of the window mean, raised towards the max for noisy symbols
+    pub volume_multiplier_base: f64,
+    pub volume_multiplier_max: f64,
+    // Trades kept per symbol for the price change and volume statistics
+    pub window_size: usize,
+    // Trades seen before anything is flagged
+    pub min_samples: usize,
+    // Price threshold follows mean + 2 std of recent changes, volume multiple follows dispersion
+    pub dynamic_threshold: bool,
+    pub alert_cooldown_secs: u64,
+    // Severity by how many times its threshold the change was
+    #[serde(default = "default_threshold_severity")]
+    pub severity: SeverityThresholds,
+}
+
+fn default_threshold_severity() -> SeverityThresholds {
+    SeverityThresholds::new(1.5, 2.0, 3.0).expect("threshold bands increase")
+}
+
+impl Default for SimpleThresholdConfig {
+    fn default() -> Self {
+        Self {
+            price_change_threshold: 3.0,
+            volume_multiplier_base: 5.0,
+            volume_multiplier_max: 20.0,
+            window_size: 200,
+            min_samples: 20,
+            dynamic_threshold: true,
+            alert_cooldown_secs: 2,
+            severity: default_threshold_severity(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WindowStatistics {
+    pub mean: f64,
+    pub std_dev: f64,
+    pub min: f64,
+    pub max: f64,
+    pub percentile_95: f64,
+}
+
+impl WindowStatistics {
+    pub fn calculate<'a>(values: impl IntoIterator<Item = &'a f64>) -> Option<Self> {
+        let mut sorted: Vec<f64> = values.into_iter().copied().collect();
+        if sorted.is_empty() {
+            return None;
+        }
+        sorted.sort_by(|a, b| a.total_cmp(b));
+
+        let len = sorted.len() as f64;
+        let mean = sorted.iter().sum::<f64>() / len;
+        let variance = sorted.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / len;
+
+        Some(Self {
+            mean,
+            std_dev: variance.sqrt(),
+            min: sorted[0],
+            max: sorted[sorted.len() - 1],
+            percentile_95: sorted[((len * 0.95) as usize).min(sorted.len() - 1)],
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolStatistics {
+    pub exchange: String,
+    pub symbol: String,
+    pub last_price: f64,
+    pub avg_volume: f64,
+    pub total_volume: f64,
+    pub trade_count: u64,
+    // Standard deviation of the windowed price changes, in percent
+    pub price_volatility: f64,
+    pub anomalies_detected: u64,
+    pub detection_rate_pct: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThresholdReport {
+    pub generated_at: DateTime<Utc>,
+    pub elapsed_secs: i64,
+    pub total_events: u64,
+    pub events_per_sec: f64,
+    pub symbols: Vec<SymbolStatistics>,
+}
+
+struct SymbolMonitor {
+    exchange: String,
+    symbol: String,
+    last_price: f64,
+    total_volume: f64,
+    trade_count: u64,
+    price_changes: VecDeque<f64>,
+    volumes: VecDeque<f64>,
+    anomalies_detected: u64,
+    last_alert_at: Option<DateTime<Utc>>,
+    volume_multiplier: f64,
+}
+
+impl SymbolMonitor {
+    fn new(exchange: &str, symbol: &str, config: &SimpleThresholdConfig) -> Self {
+        Self {
+            exchange: exchange.to_string(),
+            symbol: symbol.to_string(),
+            last_price: 0.0,
+            total_volume: 0.0,
+            trade_count: 0,
+            price_changes: VecDeque::with_capacity(config.window_size),
+            volumes: VecDeque::with_capacity(config.window_size),
+            anomalies_detected: 0,
+            last_alert_at: None,
+            volume_multiplier: config.volume_multiplier_base,
+        }
+    }
+
+    fn push_bounded(window: &mut VecDeque<f64>, value: f64, max: usize) {
+        if window.len() >= max.max(1) {
+            window.pop_front();
+        }
+        window.push_back(value);
+    }
+
+    fn observe(
+        &mut self,
+        price: f64,
+        volume: f64,
+        at: DateTime<Utc>,
+        config: &SimpleThresholdConfig,
+    ) -> Vec<AnomalyDetection> {
+        self.trade_count += 1;
+        self.total_volume += volume;
+        Self::push_bounded(&mut self.volumes, volume, config.window_size);
+
+        let previous = std::mem::replace(&mut self.last_price, price);
+        if self.volumes.len() < config.min_samples {
+            return Vec::new();
+        }
+        let cooldown = Duration::seconds(config.alert_cooldown_secs as i64);
+        if self.last_alert_at.is_some_and(|last| at - last < cooldown) {
+            return Vec::new();
+        }
+
+        let mut detections = Vec::new();
+        if previous > 0.0 {
+            let change_pct = ((price - previous) / previous * 100.0).abs();
+            Self::push_bounded(&mut self.price_changes, change_pct, config.window_size);
+
+            if let Some(stats) = WindowStatistics::calculate(&self.price_changes) {
+                let threshold = if config.dynamic_threshold {
+                    (stats.mean + 2.0 * stats.std_dev).max(config.price_change_threshold)
+                } else {
+                    config.price_change_threshold
+                };
+
+                if change_pct > threshold && change_pct > stats.percentile_95 {
+                    detections.push(self.detection(
+                        config,
+                        AnomalyType::PriceSpike,
+                        at,
+                        change_pct / threshold,
+                        AnomalyMetrics {
+                            current_value: price,
+                            expected_value: previous,
+                            deviation: price - previous,
+                            z_score: None,
+                            percentage_change: Some((price - previous) / previous * 100.0),
+                            historical_avg: Some(stats.mean),
+                            historical_std: Some(stats.std_dev),
+                            ..Default::default()
+                        },
+                        AnomalyReason::new(
+                            &self.exchange,
+                            &self.symbol,
+                            price,
+                            ReasonKind::PriceMove { change_pct },
+                        )
+                        .with_baseline(previous)
+                        .with_threshold(threshold),
+                    ));
+                }
+            }
+        }
+
+        if let Some(stats) = WindowStatistics::calculate(&self.volumes) {
+            if config.dynamic_threshold && stats.mean > 0.0 {
+                let dispersion = 1.5 + (stats.std_dev / stats.mean).min(2.0);
+                self.volume_multiplier =
+                    (config.volume_multiplier_base * dispersion).min(config.volume_multiplier_max);
+            }
+
+            let threshold = stats.mean * self.volume_multiplier;
+            if volume > threshold && volume > stats.percentile_95 * 1.5 {
+                detections.push(self.detection(
+                    config,
+                    AnomalyType::VolumeSpike,
+                    at,
+                    volume / threshold,
+                    AnomalyMetrics {
+                        current_value: volume,
+                        expected_value: stats.mean,
+                        deviation: volume - stats.mean,
+                        z_score: (stats.std_dev > 0.0).then(|| (volume - stats.mean) / stats.std_dev),
+                        percentage_change: Some((volume - stats.mean) / stats.mean * 100.0),
+                        historical_avg: Some(stats.mean),
+                        historical_std: Some(stats.std_dev),
+                        ..Default::default()
+                    },
+                    AnomalyReason::new(
+                        &self.exchange,
+                        &self.symbol,
+                        volume,
+                        ReasonKind::WindowVolume { multiple: volume / stats.mean },
+                    )
+                    .with_baseline(stats.mean)
+                    .with_threshold(threshold),
+                ));
+            }
+        }
+
+        if !detections.is_empty() {
+            self.anomalies_detected += detections.len() as u64;
+            self.last_alert_at = Some(at);
+        }
+        detections
+    }
+
+    fn detection(
+        &self,
+        config: &SimpleThresholdConfig,
+        anomaly_type: AnomalyType,
+        at: DateTime<Utc>,
+        // How far past the threshold, 1.0 being right on it
+        ratio: f64,
+        metrics: AnomalyMetrics,
+        reason: AnomalyReason,
+    ) -> AnomalyDetection {
+        let severity = severity_from_thresholds(ratio, &config.severity);
+
+        AnomalyDetection {
+            id: uuid::Uuid::new_v4(),
+            timestamp: at,
+            symbol: self.symbol.clone(),
+            exchange: self.exchange.clone(),
+            anomaly_type,
+            confidence: severity.confidence_floor(),
+            severity,
+            metrics,
+            description: reason.to_string(),
+            reason: Some(reason),
+            ..Default::default()
+        }
+    }
+
+    fn statistics(&self) -> SymbolStatistics {
+        let avg_volume = if self.volumes.is_empty() {
+            0.0
+        } else {
+            self.volumes.iter().sum::<f64>() / self.volumes.len() as f64
+        };
+
+        SymbolStatistics {
+            exchange: self.exchange.clone(),
+            symbol: self.symbol.clone(),
+            last_price: self.last_price,
+            avg_volume,
+            total_volume: self.total_volume,
+            trade_count: self.trade_count,
+            price_volatility: WindowStatistics::calculate(&self.price_changes)
+                .map_or(0.0, |s| s.std_dev),
+            anomalies_detected: self.anomalies_detected,
+            detection_rate_pct: if self.trade_count > 0 {
+                self.anomalies_detected as f64 / self.trade_count as f64 * 100.0
+            } else {
+                0.0
+            },
+        }
+    }
+}
+
+// Trade-by-trade price change and volume checks against fixed or dynamic thresholds, with
+// bounded per-symbol windows so memory stays flat however long it runs
+pub struct SimpleThresholdDetector {
+    config: SimpleThresholdConfig,
+    monitors: HashMap<String, SymbolMonitor>,
+    started_at: DateTime<Utc>,
+    total_events: u64,
+}
+
+impl SimpleThresholdDetector {
+    pub fn new(config: SimpleThresholdConfig) -> Self {
+        Self {
+            config,
+            monitors: HashMap::new(),
+            started_at: Utc::now(),
+            total_events: 0,
+        }
+    }
+
+    pub fn with_start(mut self, started_at: DateTime<Utc>) -> Self {
+        self.started_at = started_at;
+        self
+    }
+
+    pub fn config(&self) -> &SimpleThresholdConfig {
+        &self.config
+    }
+
+    pub fn process_trade(
+        &mut self,
+        exchange: &str,
+        symbol: &str,
+        price: f64,
+        volume: f64,
+        at: DateTime<Utc>,
+    ) -> Vec<AnomalyDetection> {
+        self.total_events += 1;
+
+        let config = &self.config;
+        self.monitors
+            .entry(format!("{}:{}", exchange, symbol))
+            .or_insert_with(|| SymbolMonitor::new(exchange, symbol, config))
+            .observe(price, volume, at, config)
+    }
+
+    pub fn report(&self, now: DateTime<Utc>) -> ThresholdReport {
+        let elapsed_secs = (now - self.started_at).num_seconds();
+        let mut symbols: Vec<SymbolStatistics> =
+            self.monitors.values().map(SymbolMonitor::statistics).collect();
+        symbols.sort_by(|a, b| (&a.exchange, &a.symbol).cmp(&(&b.exchange, &b.symbol)));
+
+        ThresholdReport {
+            generated_at: now,
+            elapsed_secs,
+            total_events: self.total_events,
+            events_per_sec: self.total_events as f64 / elapsed_secs.max(1) as f64,
+            symbols,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.monitors.clear();
+        self.total_events = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> Vec<Vec<String>> {
+        std::fs::read_to_string(format!(
+            "{}/tests/fixtures/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            name
+        ))
+        .unwrap()
+        .lines()
+        .skip(1)
+        .map(|line| line.split(',').map(str::to_string).collect())
+        .collect()
+    }
+
+    fn timestamp(field: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(field).unwrap().with_timezone(&Utc)
+    }
+
+    // A noisy random walk with a 6% jump every 97 trades and a volume burst every 61, 700ms
+    // apart
+    fn tape() -> Vec<(f64, f64, DateTime<Utc>)> {
+        fixture("threshold_tape.csv")
+            .iter()
+            .map(|row| (row[1].parse().unwrap(), row[2].parse().unwrap(), timestamp(&row[0])))
+            .collect()
+    }
+
+    // The alerts are what the improved monitor_demo example's `SymbolMonitor::add_data_point`
+    // raised on the tape before the port, its wall-clock cooldown read off the trade timestamps
+    #[test]
+    fn test_alerts_match_example_behaviour() {
+        for (config, recorded) in [
+            (
+                SimpleThresholdConfig { window_size: 50, ..Default::default() },
+                "threshold_alerts_dynamic.csv",
+            ),
+            (
+                SimpleThresholdConfig {
+                    price_change_threshold: 2.0,
+                    dynamic_threshold: false,
+                    ..Default::default()
+                },
+                "threshold_alerts_fixed.csv",
+            ),
+        ] {
+            let expected: Vec<(DateTime<Utc>, AnomalyType)> = fixture(recorded)
+                .iter()
+                .map(|row| (timestamp(&row[0]), AnomalyType::from_name(&row[1]).unwrap()))
+                .collect();
+
+            let mut detector = SimpleThresholdDetector::new(config);
+            let mut alerts = Vec::new();
+            for (price, volume, at) in tape() {
+                let detections = detector.process_trade("binance", "BTC/USDT", price, volume, at);
+                alerts.extend(detections.into_iter().map(|d| (d.timestamp, d.anomaly_type)));
+            }
+            assert!(!expected.is_empty());
+            assert_eq!(alerts, expected, "{}", recorded);
+        }
+    }
+
+    #[test]
+    fn test_windows_stay_bounded() {
+        let config = SimpleThresholdConfig { window_size: 64, ..Default::default() };
+        let tape = tape();
+        let mut detector = SimpleThresholdDetector::new(config).with_start(tape[0].2);
+        for i in 0..200_000 {
+            let (price, volume, _) = tape[i % tape.len()];
+            let at = tape[0].2 + Duration::milliseconds(i as i64 * 100);
+            detector.process_trade("binance", "BTC/USDT", price, volume, at);
+        }
+
+        let monitor = &detector.monitors["binance:BTC/USDT"];
+        assert_eq!(monitor.price_changes.len(), 64);
+        assert_eq!(monitor.volumes.len(), 64);
+        assert!(monitor.price_changes.capacity() < 256);
+
+        let report = detector.report(tape[0].2 + Duration::seconds(20_000));
+        assert_eq!(report.total_events, 200_000);
+        assert_eq!(report.symbols[0].trade_count, 200_000);
+        assert_eq!(report.events_per_sec, 10.0);
+    }
+}
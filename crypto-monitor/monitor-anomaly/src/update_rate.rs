@@ -0,0 +1,245 @@
+use crate::{
+    reason::{AnomalyReason, ReasonKind},
+    confidence_from_exceedance, severity_from_confidence, AnomalyDetection, AnomalyMetrics,
+    BookAnomalyDetector, UpdateRateConfig,
+};
+use monitor_core::{model::OrderBook, AnomalyType};
+use std::collections::VecDeque;
+use tracing::info;
+
+// Counts order book updates per second and flags a second whose count runs past the mean of
+// the last `baseline_secs` seconds times `spike_multiple`, as soon as it does. Seconds without
+// updates count as zero. A burst alerts once, until a second closes back under the threshold.
+pub struct UpdateRateDetector {
+    config: UpdateRateConfig,
+    symbol: String,
+    exchange: String,
+    // Closed per-second counts, oldest first
+    buckets: VecDeque<u32>,
+    sum: u64,
+    // Second being counted, as a Unix timestamp, and its updates so far
+    current: Option<(i64, u32)>,
+    triggered: bool,
+}
+
+impl UpdateRateDetector {
+    pub fn new(config: UpdateRateConfig, symbol: String, exchange: String) -> Self {
+        Self {
+            buckets: VecDeque::with_capacity(config.baseline_secs),
+            config,
+            symbol,
+            exchange,
+            sum: 0,
+            current: None,
+            triggered: false,
+        }
+    }
+
+    // Mean updates per second over the closed seconds
+    pub fn baseline_rate(&self) -> f64 {
+        if self.buckets.is_empty() {
+            0.0
+        } else {
+            self.sum as f64 / self.buckets.len() as f64
+        }
+    }
+
+    fn threshold(&self) -> f64 {
+        (self.baseline_rate() * self.config.spike_multiple).max(self.config.min_updates_per_sec)
+    }
+
+    fn push(&mut self, count: u32) {
+        if self.buckets.len() >= self.config.baseline_secs.max(1) {
+            if let Some(oldest) = self.buckets.pop_front() {
+                self.sum -= oldest as u64;
+            }
+        }
+        self.buckets.push_back(count);
+        self.sum += count as u64;
+    }
+
+    // Closes the open second and any silent ones after it, then starts counting `second`
+    fn roll_to(&mut self, second: i64) {
+        if let Some((open, count)) = self.current {
+            if (count as f64) < self.threshold() {
+                self.triggered = false;
+            }
+            self.push(count);
+            let silent = (second - open - 1).clamp(0, self.config.baseline_secs as i64);
+            for _ in 0..silent {
+                self.push(0);
+            }
+        }
+        self.current = Some((second, 0));
+    }
+}
+
+impl BookAnomalyDetector for UpdateRateDetector {
+    fn detect_book(&mut self, book: &OrderBook) -> Option<AnomalyDetection> {
+        // Late updates count towards the second already open
+        let second = book.timestamp.timestamp();
+        if self.current.is_none_or(|(open, _)| second > open) {
+            self.roll_to(second);
+        }
+        let (_, count) = self.current.as_mut()?;
+        *count += 1;
+        let rate = *count as f64;
+
+        if self.triggered || self.buckets.len() < self.config.min_baseline_secs {
+            return None;
+        }
+        let threshold = self.threshold();
+        if rate < threshold {
+            return None;
+        }
+        self.triggered = true;
+
+        let baseline = self.baseline_rate();
+        let confidence = confidence_from_exceedance(rate, threshold);
+        let severity = severity_from_confidence(confidence);
+        let reason =
+            AnomalyReason::new(&self.exchange, &self.symbol, rate, ReasonKind::UpdateRate)
+                .with_baseline(baseline)
+                .with_threshold(threshold)
+                .with_window_secs(1.0);
+        let description = reason.to_string();
+        info!("{}", description);
+
+        Some(AnomalyDetection {
+            id: uuid::Uuid::new_v4(),
+            timestamp: book.timestamp,
+            symbol: self.symbol.clone(),
+            exchange: self.exchange.clone(),
+            anomaly_type: AnomalyType::UnusualActivity,
+            severity,
+            confidence,
+            metrics: AnomalyMetrics {
+                current_value: rate,
+                expected_value: baseline,
+                deviation: rate - baseline,
+                z_score: None,
+                percentage_change: (baseline > 0.0)
+                    .then(|| (rate - baseline) / baseline * 100.0),
+                historical_avg: Some(baseline),
+                historical_std: None,
+                ..Default::default()
+            },
+            description,
+            reason: Some(reason),
+            ..Default::default()
+        })
+    }
+
+    fn reset(&mut self) {
+        self.buckets.clear();
+        self.sum = 0;
+        self.current = None;
+        self.triggered = false;
+    }
+
+    fn kind(&self) -> String {
+        "update_rate".to_string()
+    }
+
+    fn window_fill(&self) -> Option<f64> {
+        Some(self.buckets.len() as f64 / self.config.baseline_secs.max(1) as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+
+    fn book(at: DateTime<Utc>) -> OrderBook {
+        OrderBook {
+            exchange: "binance".to_string(),
+            symbol: "BTC/USDT".to_string(),
+            timestamp: at,
+            bids: Vec::new(),
+            asks: Vec::new(),
+        }
+    }
+
+    fn detector() -> UpdateRateDetector {
+        UpdateRateDetector::new(
+            UpdateRateConfig::default(),
+            "BTC/USDT".to_string(),
+            "binance".to_string(),
+        )
+    }
+
+    // `per_sec` evenly spaced updates in each of `secs` seconds from `from`, with the
+    // detections they raised
+    fn feed(
+        detector: &mut UpdateRateDetector,
+        from: DateTime<Utc>,
+        secs: i64,
+        per_sec: i64,
+    ) -> Vec<(DateTime<Utc>, AnomalyDetection)> {
+        let step = Duration::milliseconds(1000 / per_sec);
+        (0..secs * per_sec)
+            .filter_map(|i| {
+                let at = from + step * i as i32;
+                detector.detect_book(&book(at)).map(|d| (at, d))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_burst_against_steady_baseline() {
+        let mut detector = detector();
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+
+        assert!(feed(&mut detector, start, 120, 5).is_empty());
+        assert_eq!(detector.baseline_rate(), 5.0);
+
+        // Two seconds at 50/s alert once, on the 25th update of the first
+        let burst = start + Duration::seconds(120);
+        let detections = feed(&mut detector, burst, 2, 50);
+        assert_eq!(detections.len(), 1);
+        let (at, anomaly) = &detections[0];
+        assert_eq!(*at, burst + Duration::milliseconds(24 * 20));
+        assert_eq!(anomaly.anomaly_type, AnomalyType::UnusualActivity);
+        assert_eq!(anomaly.metrics.current_value, 25.0);
+        assert_eq!(anomaly.metrics.expected_value, 5.0);
+        assert_eq!(anomaly.confidence, 0.5);
+        assert!(anomaly.description.contains("25 updates/s against a baseline of 5.0/s"));
+
+        // Back at 5/s for a while re-arms it for the next burst
+        assert!(feed(&mut detector, burst + Duration::seconds(2), 30, 5).is_empty());
+        let detections = feed(&mut detector, burst + Duration::seconds(32), 1, 50);
+        assert_eq!(detections.len(), 1);
+    }
+
+    #[test]
+    fn test_quiet_until_baseline_and_floor() {
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+
+        // Not enough history yet
+        let mut young = detector();
+        assert!(feed(&mut young, start, 30, 5).is_empty());
+        assert!(feed(&mut young, start + Duration::seconds(30), 2, 50).is_empty());
+
+        // Ten times a baseline of 1/s stays under the absolute floor of 20/s
+        let mut slow = detector();
+        assert!(feed(&mut slow, start, 120, 1).is_empty());
+        assert!(feed(&mut slow, start + Duration::seconds(120), 2, 10).is_empty());
+    }
+
+    #[test]
+    fn test_silent_seconds_count_as_zero() {
+        let mut detector = detector();
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+
+        // A feed gone quiet for 200s leaves a baseline of 5/s over a third of the window
+        assert!(feed(&mut detector, start, 100, 5).is_empty());
+        let resumed = start + Duration::seconds(300);
+        let detections = feed(&mut detector, resumed, 1, 20);
+
+        assert_eq!(detections.len(), 1);
+        let (_, anomaly) = &detections[0];
+        assert_eq!(anomaly.metrics.current_value, 20.0);
+        assert!((anomaly.metrics.expected_value - 500.0 / 300.0).abs() < 1e-9);
+    }
+}
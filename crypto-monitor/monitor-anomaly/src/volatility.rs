@@ -0,0 +1,258 @@
+use crate::{
+    reason::{AnomalyReason, ReasonKind},
+    confidence_from_exceedance, severity_from_confidence, AnomalyDetection, AnomalyDetector,
+    AnomalyMetrics, RollingOrderStats, TimeSeriesData, VolatilityRegimeConfig,
+};
+use monitor_core::AnomalyType;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use tracing::info;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum VolatilityRegime {
+    Expansion,
+    Contraction,
+}
+
+impl VolatilityRegime {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            VolatilityRegime::Expansion => "expansion",
+            VolatilityRegime::Contraction => "contraction",
+        }
+    }
+}
+
+// Realized volatility of the last `window_size` log returns, judged against the median of its
+// own past readings. Alerts once on entering a regime; leaving it needs the ratio back within
+// the square root of `change_multiple`, so a reading hovering at the threshold alerts once.
+pub struct VolatilityRegimeDetector {
+    config: VolatilityRegimeConfig,
+    symbol: String,
+    exchange: String,
+    last_price: Option<f64>,
+    returns: VecDeque<f64>,
+    readings: RollingOrderStats,
+    regime: Option<VolatilityRegime>,
+}
+
+impl VolatilityRegimeDetector {
+    pub fn new(config: VolatilityRegimeConfig, symbol: String, exchange: String) -> Self {
+        Self {
+            readings: RollingOrderStats::new(config.baseline_size),
+            returns: VecDeque::with_capacity(config.window_size),
+            config,
+            symbol,
+            exchange,
+            last_price: None,
+            regime: None,
+        }
+    }
+
+    pub fn regime(&self) -> Option<VolatilityRegime> {
+        self.regime
+    }
+
+    // Root mean square of the log returns, per sample and not annualized
+    fn realized_volatility(&self) -> f64 {
+        (self.returns.iter().map(|r| r * r).sum::<f64>() / self.returns.len() as f64).sqrt()
+    }
+}
+
+impl AnomalyDetector for VolatilityRegimeDetector {
+    fn detect(&mut self, data: &TimeSeriesData) -> Option<AnomalyDetection> {
+        let price = data.value;
+        if !price.is_finite() || price <= 0.0 {
+            return None;
+        }
+        let previous = self.last_price.replace(price)?;
+
+        if self.returns.len() >= self.config.window_size.max(1) {
+            self.returns.pop_front();
+        }
+        self.returns.push_back((price / previous).ln());
+        if self.returns.len() < self.config.window_size {
+            return None;
+        }
+
+        let volatility = self.realized_volatility();
+        let median = (self.readings.len() >= self.config.min_baseline.max(1))
+            .then(|| self.readings.median());
+        self.readings.push(volatility);
+        let median = median.filter(|m| *m > 0.0)?;
+
+        let multiple = self.config.change_multiple;
+        let ratio = volatility / median;
+        match self.regime {
+            Some(VolatilityRegime::Expansion) if ratio < multiple.sqrt() => self.regime = None,
+            Some(VolatilityRegime::Contraction) if ratio > 1.0 / multiple.sqrt() => {
+                self.regime = None
+            }
+            _ => {}
+        }
+
+        let (regime, change) = if ratio >= multiple {
+            (VolatilityRegime::Expansion, ratio)
+        } else if ratio <= 1.0 / multiple {
+            (VolatilityRegime::Contraction, (1.0 / ratio).min(f64::MAX))
+        } else {
+            return None;
+        };
+        if self.regime == Some(regime) {
+            return None;
+        }
+        self.regime = Some(regime);
+
+        let confidence = confidence_from_exceedance(change, multiple);
+        let severity = severity_from_confidence(confidence);
+        let reason = AnomalyReason::new(
+            &self.exchange,
+            &self.symbol,
+            volatility,
+            ReasonKind::Volatility { regime, ratio },
+        )
+        .with_baseline(median)
+        .with_threshold(multiple);
+        let description = reason.to_string();
+
+        info!("{}", description);
+
+        Some(AnomalyDetection {
+            id: uuid::Uuid::new_v4(),
+            timestamp: data.timestamp,
+            symbol: self.symbol.clone(),
+            exchange: self.exchange.clone(),
+            anomaly_type: AnomalyType::VolatilityRegime,
+            severity,
+            confidence,
+            metrics: AnomalyMetrics {
+                current_value: volatility,
+                expected_value: median,
+                deviation: volatility - median,
+                z_score: None,
+                percentage_change: Some((ratio - 1.0) * 100.0),
+                historical_avg: Some(median),
+                historical_std: None,
+                regime: Some(regime),
+                ..Default::default()
+            },
+            description,
+            reason: Some(reason),
+            ..Default::default()
+        })
+    }
+
+    fn reset(&mut self) {
+        self.last_price = None;
+        self.returns.clear();
+        self.readings.clear();
+        self.regime = None;
+    }
+
+    fn kind(&self) -> String {
+        "volatility_regime".to_string()
+    }
+
+    fn window_fill(&self) -> Option<f64> {
+        Some(self.readings.fill())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AnomalySeverity;
+    use chrono::{Duration, TimeZone, Utc};
+
+    // Prices from 100 whose log returns are uniform within +/- each phase's bound, from a fixed
+    // LCG so runs are repeatable
+    fn phased_prices(phases: &[(usize, f64)]) -> Vec<TimeSeriesData> {
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut price = 100.0;
+        let mut prices = vec![price];
+        for &(count, bound) in phases {
+            for _ in 0..count {
+                state = state
+                    .wrapping_mul(6_364_136_223_846_793_005)
+                    .wrapping_add(1_442_695_040_888_963_407);
+                let unit = (state >> 11) as f64 / (1u64 << 53) as f64;
+                price *= ((unit * 2.0 - 1.0) * bound).exp();
+                prices.push(price);
+            }
+        }
+        prices
+            .into_iter()
+            .enumerate()
+            .map(|(i, value)| TimeSeriesData {
+                timestamp: start + Duration::seconds(i as i64),
+                value,
+            })
+            .collect()
+    }
+
+    fn detector() -> VolatilityRegimeDetector {
+        VolatilityRegimeDetector::new(
+            VolatilityRegimeConfig::default(),
+            "BTC/USDT".to_string(),
+            "binance".to_string(),
+        )
+    }
+
+    fn run(
+        detector: &mut VolatilityRegimeDetector,
+        data: &[TimeSeriesData],
+    ) -> Vec<(usize, AnomalyDetection)> {
+        data.iter()
+            .enumerate()
+            .filter_map(|(i, sample)| detector.detect(sample).map(|d| (i, d)))
+            .collect()
+    }
+
+    #[test]
+    fn test_calm_to_volatile_alerts_once_at_transition() {
+        let mut detector = detector();
+        let data = phased_prices(&[(200, 0.0005), (100, 0.005)]);
+
+        let alerts = run(&mut detector, &data);
+        assert_eq!(alerts.len(), 1);
+        let (index, detection) = &alerts[0];
+        // The second volatile return is enough to double the realized volatility
+        assert_eq!(*index, 202);
+        assert_eq!(detection.anomaly_type, AnomalyType::VolatilityRegime);
+        assert_eq!(detection.metrics.regime, Some(VolatilityRegime::Expansion));
+        assert!(detection.description.starts_with("Volatility expansion for binance/BTC/USDT"));
+        assert!(detection.metrics.current_value > 2.0 * detection.metrics.expected_value);
+        assert_eq!(detection.severity, AnomalySeverity::Medium);
+        assert_eq!(detector.regime(), Some(VolatilityRegime::Expansion));
+    }
+
+    #[test]
+    fn test_volatile_to_calm_alerts_contraction_once() {
+        let mut detector = detector();
+        let data = phased_prices(&[(200, 0.005), (100, 0.0005)]);
+
+        let alerts = run(&mut detector, &data);
+        assert_eq!(alerts.len(), 1);
+        let (index, detection) = &alerts[0];
+        // Calm returns have to push most of the volatile ones out of the window first
+        assert_eq!(*index, 226);
+        assert_eq!(detection.metrics.regime, Some(VolatilityRegime::Contraction));
+        assert!(detection.description.starts_with("Volatility contraction"));
+        assert!(detection.metrics.percentage_change.unwrap() < -50.0);
+    }
+
+    #[test]
+    fn test_steady_volatility_never_alerts() {
+        let mut detector = detector();
+        let data = phased_prices(&[(400, 0.002)]);
+
+        assert!(run(&mut detector, &data).is_empty());
+        assert_eq!(detector.regime(), None);
+
+        detector.reset();
+        assert!(detector.detect(&data[0]).is_none());
+    }
+}
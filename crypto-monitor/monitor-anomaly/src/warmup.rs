@@ -0,0 +1,230 @@
+use chrono::{DateTime, Duration, Utc};
+use dashmap::{DashMap, DashSet};
+use monitor_core::{
+    event::EventBuilder, EventSource, EventType, MonitorEvent, SystemEventType, WarmupConfig,
+};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::info;
+
+pub struct WarmupTracker {
+    config: WarmupConfig,
+    started_at: DateTime<Utc>,
+    keys: DashMap<String, KeyProgress>,
+    restored: DashSet<String>,
+    completed: AtomicBool,
+}
+
+// How far one key is through its warm-up, by samples and by the time of its own events
+#[derive(Debug, Clone, Copy)]
+struct KeyProgress {
+    samples: usize,
+    first_seen: Option<DateTime<Utc>>,
+}
+
+impl KeyProgress {
+    fn new(first_seen: DateTime<Utc>) -> Self {
+        Self {
+            samples: 0,
+            first_seen: Some(first_seen),
+        }
+    }
+}
+
+impl WarmupTracker {
+    pub fn new(config: WarmupConfig) -> Self {
+        Self::starting_at(config, Utc::now())
+    }
+
+    pub fn starting_at(config: WarmupConfig, started_at: DateTime<Utc>) -> Self {
+        Self {
+            config,
+            started_at,
+            keys: DashMap::new(),
+            restored: DashSet::new(),
+            completed: AtomicBool::new(false),
+        }
+    }
+
+    // Counts a sample for `key` and returns whether detections for it are still warm-up only
+    pub fn observe(&self, key: &str, now: DateTime<Utc>) -> bool {
+        // Only a key's first sample allocates it
+        let counted = self.keys.get_mut(key).map(|mut progress| {
+            progress.samples = progress.samples.saturating_add(1);
+            progress.first_seen.get_or_insert(now);
+            *progress
+        });
+        let progress = counted.unwrap_or_else(|| {
+            let mut progress = self.keys.entry(key.to_string()).or_insert(KeyProgress::new(now));
+            progress.samples = progress.samples.saturating_add(1);
+            *progress
+        });
+
+        self.is_key_warming(key, progress, now)
+    }
+
+    // Detector windows restored from a snapshot already carry history, so the symbol skips
+    // the startup duration and counts the restored samples towards its threshold
+    pub fn restore(&self, key: &str, restored_samples: usize) {
+        let mut progress = self.keys.entry(key.to_string()).or_insert(KeyProgress {
+            samples: 0,
+            first_seen: None,
+        });
+        progress.samples = progress.samples.max(restored_samples);
+        self.restored.insert(key.to_string());
+    }
+
+    // Whether detections for `key` are still warm-up only, without counting a sample. Funding,
+    // liquidations and other sparse events land here, so the key's clock starts the first time
+    // it is asked about, and it warms up once `duration_secs` of its own events have passed
+    // even if it never trades `min_samples_per_symbol` times
+    pub fn is_warming_up(&self, key: &str, now: DateTime<Utc>) -> bool {
+        let seen = self.keys.get(key).map(|progress| *progress);
+        let progress = seen.unwrap_or_else(|| {
+            *self.keys.entry(key.to_string()).or_insert(KeyProgress::new(now))
+        });
+        self.is_key_warming(key, progress, now)
+    }
+
+    fn is_key_warming(&self, key: &str, progress: KeyProgress, now: DateTime<Utc>) -> bool {
+        let within_startup = !self.restored.contains(key) && self.remaining(now) > Duration::zero();
+        within_startup || self.is_key_pending(progress, now)
+    }
+
+    // Short of its samples, and not yet seen for the warm-up duration
+    fn is_key_pending(&self, progress: KeyProgress, now: DateTime<Utc>) -> bool {
+        let duration = Duration::seconds(self.config.duration_secs as i64);
+        progress.samples < self.config.min_samples_per_symbol
+            && progress.first_seen.is_none_or(|first_seen| now - first_seen < duration)
+    }
+
+    pub fn remaining(&self, now: DateTime<Utc>) -> Duration {
+        let end = self.started_at + Duration::seconds(self.config.duration_secs as i64);
+        (end - now).max(Duration::zero())
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.completed.load(Ordering::Relaxed)
+    }
+
+    // Returns the completion event exactly once, after the startup duration has elapsed and
+    // every symbol seen so far has reached its sample threshold or been seen for that long
+    pub fn poll_completion(&self, now: DateTime<Utc>) -> Option<MonitorEvent> {
+        if self.is_complete() || self.remaining(now) > Duration::zero() {
+            return None;
+        }
+
+        let pending = self
+            .keys
+            .iter()
+            .filter(|progress| self.is_key_pending(*progress.value(), now))
+            .count();
+        if pending > 0 {
+            return None;
+        }
+
+        if self.completed.swap(true, Ordering::Relaxed) {
+            return None;
+        }
+
+        info!("Warm-up completed after {}s", (now - self.started_at).num_seconds());
+
+        EventBuilder::new()
+            .with_source(EventSource::Monitor)
+            .with_type(EventType::System(SystemEventType::WarmupCompleted))
+            .with_data(serde_json::json!({
+                "started_at": self.started_at,
+                "completed_at": now,
+                "symbols": self.keys.len(),
+            }))
+            .build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker(start: DateTime<Utc>) -> WarmupTracker {
+        WarmupTracker::starting_at(
+            WarmupConfig {
+                duration_secs: 60,
+                min_samples_per_symbol: 3,
+            },
+            start,
+        )
+    }
+
+    #[test]
+    fn test_detections_flagged_until_duration_and_samples_met() {
+        let start = Utc::now();
+        let tracker = tracker(start);
+
+        // Enough samples but still inside the startup window
+        for _ in 0..3 {
+            assert!(tracker.observe("binance:BTC/USDT", start + Duration::seconds(10)));
+        }
+
+        let after = start + Duration::seconds(61);
+        assert!(!tracker.observe("binance:BTC/USDT", after));
+        // A symbol first seen after the window still needs its own samples
+        assert!(tracker.observe("okx:ETH/USDT", after));
+    }
+
+    #[test]
+    fn test_completion_event_emitted_once() {
+        let start = Utc::now();
+        let tracker = tracker(start);
+        tracker.observe("binance:BTC/USDT", start + Duration::seconds(30));
+
+        let after = start + Duration::seconds(61);
+        assert!(tracker.poll_completion(after).is_none());
+
+        tracker.observe("binance:BTC/USDT", after);
+        tracker.observe("binance:BTC/USDT", after);
+
+        let event = tracker.poll_completion(after).unwrap();
+        assert!(matches!(
+            event.event_type,
+            EventType::System(SystemEventType::WarmupCompleted)
+        ));
+        assert!(tracker.poll_completion(after).is_none());
+        assert!(tracker.is_complete());
+    }
+
+    #[test]
+    fn test_thin_symbol_warms_up_on_elapsed_event_time() {
+        let start = Utc::now();
+        let tracker = tracker(start);
+
+        // One trade, then nothing until the warm-up duration has passed since it
+        let first = start + Duration::seconds(100);
+        assert!(tracker.observe("binance:XYZ/USDT", first));
+        assert!(tracker.observe("binance:XYZ/USDT", first + Duration::seconds(59)));
+        assert!(!tracker.observe("binance:XYZ/USDT", first + Duration::seconds(60)));
+
+        // Events that count no samples, like funding updates, start the clock too
+        assert!(tracker.is_warming_up("bybit:XYZ/USDT", first));
+        assert!(tracker.is_warming_up("bybit:XYZ/USDT", first + Duration::seconds(30)));
+        assert!(!tracker.is_warming_up("bybit:XYZ/USDT", first + Duration::seconds(60)));
+    }
+
+    #[test]
+    fn test_completion_waits_for_thin_symbols_by_time() {
+        let start = Utc::now();
+        let tracker = tracker(start);
+        tracker.observe("binance:XYZ/USDT", start + Duration::seconds(50));
+
+        assert!(tracker.poll_completion(start + Duration::seconds(61)).is_none());
+        assert!(tracker.poll_completion(start + Duration::seconds(110)).is_some());
+    }
+
+    #[test]
+    fn test_restored_symbol_skips_warmup() {
+        let start = Utc::now();
+        let tracker = tracker(start);
+        tracker.restore("binance:BTC/USDT", 50);
+
+        assert!(!tracker.is_warming_up("binance:BTC/USDT", start + Duration::seconds(1)));
+        assert!(tracker.is_warming_up("okx:ETH/USDT", start + Duration::seconds(1)));
+    }
+}
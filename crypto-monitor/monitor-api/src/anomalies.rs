@@ -0,0 +1,109 @@
+use crate::{cache::QueryCache, AnomalyStats};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use monitor_core::storage::repository::{BucketCount, GroupCount};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+// Polled like the market stats, the six queries behind a stats response run once per TTL
+pub const ANOMALY_STATS_TTL: std::time::Duration = std::time::Duration::from_secs(5);
+
+// Stored counts per stats query, `suppressed` is filled in fresh on every response
+pub type AnomalyStatsCache = QueryCache<AnomalyStats>;
+
+impl Default for AnomalyStatsCache {
+    fn default() -> Self {
+        Self::new("anomaly_stats", ANOMALY_STATS_TTL)
+    }
+}
+
+// How far back the anomaly stats look, hourly buckets over a day and daily ones over a month
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StatsRange {
+    #[default]
+    #[serde(rename = "24h")]
+    Day,
+    #[serde(rename = "30d")]
+    Month,
+}
+
+impl StatsRange {
+    pub fn window(self) -> Duration {
+        match self {
+            StatsRange::Day => Duration::hours(24),
+            StatsRange::Month => Duration::days(30),
+        }
+    }
+
+    pub fn bucket_secs(self) -> i64 {
+        match self {
+            StatsRange::Day => 3600,
+            StatsRange::Month => 86_400,
+        }
+    }
+}
+
+pub fn group_counts(counts: Vec<GroupCount>) -> BTreeMap<String, i64> {
+    counts.into_iter().map(|group| (group.key, group.count)).collect()
+}
+
+// Every bucket from the one holding `from` to the one holding `to`, aligned like
+// `CompiledQuery::histogram`, with the counts it returned and zero for the rest
+pub fn fill_histogram(
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    bucket_secs: i64,
+    counts: &[BucketCount],
+) -> Vec<BucketCount> {
+    let counts: HashMap<i64, i64> = counts
+        .iter()
+        .map(|bucket| (bucket.bucket.timestamp(), bucket.count))
+        .collect();
+    let first = from.timestamp().div_euclid(bucket_secs) * bucket_secs;
+
+    (first..=to.timestamp())
+        .step_by(bucket_secs as usize)
+        .filter_map(|start| {
+            Some(BucketCount {
+                bucket: Utc.timestamp_opt(start, 0).single()?,
+                count: counts.get(&start).copied().unwrap_or(0),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_range_parses_from_the_query() {
+        let range: StatsRange = serde_json::from_str("\"30d\"").unwrap();
+        assert_eq!(range, StatsRange::Month);
+        assert_eq!(StatsRange::default().bucket_secs(), 3600);
+        assert!(serde_json::from_str::<StatsRange>("\"7d\"").is_err());
+    }
+
+    #[test]
+    fn test_histogram_lists_empty_buckets() {
+        let to = Utc.with_ymd_and_hms(2024, 3, 1, 12, 30, 0).unwrap();
+        let from = to - StatsRange::Day.window();
+        let counts = vec![
+            BucketCount {
+                bucket: Utc.with_ymd_and_hms(2024, 2, 29, 12, 0, 0).unwrap(),
+                count: 2,
+            },
+            BucketCount {
+                bucket: Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap(),
+                count: 5,
+            },
+        ];
+
+        let histogram = fill_histogram(from, to, StatsRange::Day.bucket_secs(), &counts);
+        // The partial hours at both ends included
+        assert_eq!(histogram.len(), 25);
+        assert_eq!(histogram[0], counts[0]);
+        assert_eq!(histogram[21], counts[1]);
+        assert_eq!(histogram[24].bucket, Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap());
+        assert_eq!(histogram.iter().map(|bucket| bucket.count).sum::<i64>(), 7);
+    }
+}
@@ -0,0 +1,147 @@
+use dashmap::DashMap;
+use monitor_core::{
+    cache::{CacheCounters, CacheStats, InstrumentedCache},
+    Result,
+};
+use std::{
+    future::Future,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::Mutex;
+
+// Results of a database query per request key, so polling dashboards don't run the same
+// aggregates on every request
+pub struct QueryCache<V> {
+    name: &'static str,
+    ttl: Duration,
+    entries: DashMap<String, (Instant, Arc<V>)>,
+    // One lock per key being loaded, so concurrent misses share a single query
+    in_flight: DashMap<String, Arc<Mutex<()>>>,
+    counters: CacheCounters,
+}
+
+impl<V> QueryCache<V> {
+    pub fn new(name: &'static str, ttl: Duration) -> Self {
+        Self {
+            name,
+            ttl,
+            entries: DashMap::new(),
+            in_flight: DashMap::new(),
+            counters: CacheCounters::new(),
+        }
+    }
+
+    // The value cached under `key` while fresh, otherwise the one `load` returns. Callers
+    // missing the same key meanwhile wait for that load instead of running their own, a failed
+    // load caches nothing and the next waiter tries again
+    pub async fn get_or_load<F, Fut>(&self, key: String, load: F) -> Result<Arc<V>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<V>>,
+    {
+        if let Some(value) = self.counters.observe(self.fresh(&key)) {
+            return Ok(value);
+        }
+
+        let flight = self.in_flight.entry(key.clone()).or_default().clone();
+        let _loading = flight.lock().await;
+        if let Some(value) = self.fresh(&key) {
+            return Ok(value);
+        }
+
+        let loaded = load().await.map(Arc::new);
+        if let Ok(value) = &loaded {
+            // Queries change with the dashboards, expired ones are dropped rather than kept around
+            let now = Instant::now();
+            self.entries.retain(|_, entry| now.duration_since(entry.0) < self.ttl);
+            self.entries.insert(key.clone(), (now, value.clone()));
+        }
+        self.in_flight.remove(&key);
+        loaded
+    }
+
+    fn fresh(&self, key: &str) -> Option<Arc<V>> {
+        let now = Instant::now();
+        self.entries
+            .get(key)
+            .filter(|entry| now.duration_since(entry.0) < self.ttl)
+            .map(|entry| entry.1.clone())
+    }
+}
+
+impl<V: Send + Sync> InstrumentedCache for QueryCache<V> {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn stats(&self) -> CacheStats {
+        self.counters.stats(
+            self.name,
+            self.entries.len(),
+            std::mem::size_of::<V>(),
+            Some(self.ttl.as_secs()),
+        )
+    }
+
+    fn clear(&self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_loads_once_per_ttl() {
+        let cache = QueryCache::new("test", Duration::from_millis(50));
+        let loads = AtomicUsize::new(0);
+        let load = || async {
+            loads.fetch_add(1, Ordering::SeqCst);
+            Ok(vec![51_000.0])
+        };
+
+        for _ in 0..3 {
+            let values = cache.get_or_load("btc".to_string(), load).await.unwrap();
+            assert_eq!(values.len(), 1);
+        }
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+        cache.get_or_load("eth".to_string(), load).await.unwrap();
+        assert_eq!(loads.load(Ordering::SeqCst), 2);
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        cache.get_or_load("btc".to_string(), load).await.unwrap();
+        assert_eq!(loads.load(Ordering::SeqCst), 3);
+        // The expired query went with the refresh
+        assert_eq!(cache.stats().entries, 1);
+        assert_eq!(cache.stats().hits, 2);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_misses_share_one_load() {
+        let cache = QueryCache::new("test", Duration::from_secs(5));
+        let loads = AtomicUsize::new(0);
+        let load = || async {
+            loads.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Ok(vec![51_000.0])
+        };
+
+        let (a, b, c) = tokio::join!(
+            cache.get_or_load("btc".to_string(), load),
+            cache.get_or_load("btc".to_string(), load),
+            cache.get_or_load("btc".to_string(), load),
+        );
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+        assert!(Arc::ptr_eq(&a.unwrap(), &b.unwrap()));
+        assert_eq!(c.unwrap().len(), 1);
+
+        // A failed load leaves the next caller to load again
+        let failing = || async { Err(monitor_core::MonitorError::Other("down".to_string())) };
+        assert!(cache.get_or_load("eth".to_string(), failing).await.is_err());
+        cache.get_or_load("eth".to_string(), load).await.unwrap();
+        assert_eq!(loads.load(Ordering::SeqCst), 2);
+    }
+}
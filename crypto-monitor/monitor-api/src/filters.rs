@@ -0,0 +1,400 @@
+use crate::{AnomalyQuery, AnomalyStatsQuery, ApiError, BookMetricsQuery, EvidenceQuery, MarketDataQuery};
+use axum::http::StatusCode;
+use monitor_core::{
+    book_metrics::parse_resolution,
+    storage::{
+        evidence::{DEFAULT_WINDOW_MINUTES, MAX_WINDOW_MINUTES},
+        query::{AnomalyFilter, BookMetricsFilter, FilterSet, Page, TickFilter, DEFAULT_LIMIT, MAX_LIMIT},
+    },
+    AnomalyType,
+};
+use chrono::{DateTime, TimeZone, Utc};
+use monitor_core::storage::repository::AnomalyRecord;
+use std::fmt;
+
+const SEVERITIES: &[&str] = &["Low", "Medium", "High", "Critical"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryValidationError {
+    pub parameter: &'static str,
+    pub message: String,
+}
+
+impl QueryValidationError {
+    fn new(parameter: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            parameter,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for QueryValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid query parameter '{}': {}", self.parameter, self.message)
+    }
+}
+
+impl std::error::Error for QueryValidationError {}
+
+impl From<QueryValidationError> for ApiError {
+    fn from(err: QueryValidationError) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            message: err.to_string(),
+        }
+    }
+}
+
+fn non_empty(parameter: &'static str, value: &Option<String>) -> Result<Option<String>, QueryValidationError> {
+    match value.as_deref().map(str::trim) {
+        None => Ok(None),
+        Some("") => Err(QueryValidationError::new(parameter, "must not be empty")),
+        Some(v) => Ok(Some(v.to_string())),
+    }
+}
+
+// Accepts a comma separated list, each entry must be one of `allowed`
+fn one_of(
+    parameter: &'static str,
+    value: &Option<String>,
+    allowed: &[&str],
+) -> Result<Option<Vec<String>>, QueryValidationError> {
+    let Some(value) = non_empty(parameter, value)? else {
+        return Ok(None);
+    };
+    
+    value
+        .split(',')
+        .map(str::trim)
+        .map(|v| {
+            allowed
+                .iter()
+                .find(|a| a.eq_ignore_ascii_case(v))
+                .map(|a| a.to_string())
+                .ok_or_else(|| {
+                    QueryValidationError::new(
+                        parameter,
+                        format!("unknown value '{}', expected one of {}", v, allowed.join(", ")),
+                    )
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(Some)
+}
+
+fn page(limit: Option<i64>) -> Result<Page, QueryValidationError> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT);
+    if !(1..=MAX_LIMIT).contains(&limit) {
+        return Err(QueryValidationError::new(
+            "limit",
+            format!("must be between 1 and {}", MAX_LIMIT),
+        ));
+    }
+    
+    Ok(Page { limit, offset: 0 })
+}
+
+fn time_range<T>(
+    from: Option<chrono::DateTime<chrono::Utc>>,
+    to: Option<chrono::DateTime<chrono::Utc>>,
+    filter: impl FnOnce(Option<chrono::DateTime<chrono::Utc>>, Option<chrono::DateTime<chrono::Utc>>) -> T,
+) -> Result<Option<T>, QueryValidationError> {
+    match (from, to) {
+        (None, None) => Ok(None),
+        (Some(from), Some(to)) if from >= to => {
+            Err(QueryValidationError::new("from", "must be earlier than 'to'"))
+        }
+        (from, to) => Ok(Some(filter(from, to))),
+    }
+}
+
+impl TryFrom<&AnomalyQuery> for FilterSet<AnomalyFilter> {
+    type Error = QueryValidationError;
+    
+    fn try_from(query: &AnomalyQuery) -> Result<Self, Self::Error> {
+        // Detections made while following shadow the leader's, only the leader's copy is listed
+        let mut filters = vec![AnomalyFilter::ShadowEq(false)];
+        
+        if let Some(symbol) = non_empty("symbol", &query.symbol)? {
+            filters.push(AnomalyFilter::SymbolEq(symbol));
+        }
+        if let Some(exchange) = non_empty("exchange", &query.exchange)? {
+            filters.push(AnomalyFilter::ExchangeEq(exchange.to_lowercase()));
+        }
+        let anomaly_types: Vec<&str> = AnomalyType::ALL.iter().map(AnomalyType::name).collect();
+        if let Some(types) = one_of("anomaly_type", &query.anomaly_type, &anomaly_types)? {
+            filters.push(AnomalyFilter::TypeIn(types));
+        }
+        if let Some(severities) = one_of("severity", &query.severity, SEVERITIES)? {
+            filters.push(AnomalyFilter::SeverityIn(severities));
+        }
+        if let Some(range) = time_range(query.from, query.to, AnomalyFilter::TimeRange)? {
+            filters.push(range);
+        }
+        if let Some(cursor) = non_empty("cursor", &query.cursor)? {
+            let cursor = AnomalyCursor::parse(&cursor)?;
+            filters.push(AnomalyFilter::Before(cursor.detected_at, cursor.id));
+        }
+        
+        Ok(FilterSet::new(filters, page(query.limit)?))
+    }
+}
+
+// Where a page of anomalies ended, as `<detected_at in microseconds>_<id>`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnomalyCursor {
+    pub detected_at: DateTime<Utc>,
+    pub id: uuid::Uuid,
+}
+
+impl AnomalyCursor {
+    pub fn after(record: &AnomalyRecord) -> Self {
+        Self {
+            detected_at: record.detected_at,
+            id: record.id,
+        }
+    }
+    
+    pub fn parse(value: &str) -> Result<Self, QueryValidationError> {
+        let invalid = || QueryValidationError::new("cursor", "not a cursor from a previous page");
+        let (micros, id) = value.split_once('_').ok_or_else(invalid)?;
+        let micros: i64 = micros.parse().map_err(|_| invalid())?;
+        let nanos = (micros.rem_euclid(1_000_000) * 1_000) as u32;
+        let detected_at = Utc
+            .timestamp_opt(micros.div_euclid(1_000_000), nanos)
+            .single()
+            .ok_or_else(invalid)?;
+        let id = id.parse().map_err(|_| invalid())?;
+        Ok(Self { detected_at, id })
+    }
+}
+
+impl fmt::Display for AnomalyCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}_{}", self.detected_at.timestamp_micros(), self.id)
+    }
+}
+
+impl TryFrom<&MarketDataQuery> for FilterSet<TickFilter> {
+    type Error = QueryValidationError;
+    
+    fn try_from(query: &MarketDataQuery) -> Result<Self, Self::Error> {
+        let mut filters = Vec::new();
+        
+        if let Some(symbol) = non_empty("symbol", &query.symbol)? {
+            filters.push(TickFilter::SymbolEq(symbol));
+        }
+        if let Some(exchange) = non_empty("exchange", &query.exchange)? {
+            filters.push(TickFilter::ExchangeEq(exchange.to_lowercase()));
+        }
+        if let Some(range) = time_range(query.from, query.to, TickFilter::TimeRange)? {
+            filters.push(range);
+        }
+        
+        Ok(FilterSet::new(filters, page(query.limit)?))
+    }
+}
+
+// A series is charted per instrument, so both are required
+impl TryFrom<&BookMetricsQuery> for FilterSet<BookMetricsFilter> {
+    type Error = QueryValidationError;
+    
+    fn try_from(query: &BookMetricsQuery) -> Result<Self, Self::Error> {
+        let symbol = non_empty("symbol", &query.symbol)?
+            .ok_or_else(|| QueryValidationError::new("symbol", "is required"))?;
+        let exchange = non_empty("exchange", &query.exchange)?
+            .ok_or_else(|| QueryValidationError::new("exchange", "is required"))?;
+        
+        let mut filters = vec![
+            BookMetricsFilter::SymbolEq(symbol),
+            BookMetricsFilter::ExchangeEq(exchange.to_lowercase()),
+        ];
+        if let Some(range) = time_range(query.from, query.to, BookMetricsFilter::TimeRange)? {
+            filters.push(range);
+        }
+        
+        Ok(FilterSet::new(filters, page(query.limit)?))
+    }
+}
+
+impl BookMetricsQuery {
+    pub fn downsample_secs(&self) -> Result<Option<i64>, QueryValidationError> {
+        non_empty("downsample", &self.downsample)?
+            .map(|value| {
+                parse_resolution(&value)
+                    .map_err(|_| QueryValidationError::new("downsample", "expected e.g. 30s, 5m, 1h or 1d"))
+            })
+            .transpose()
+    }
+}
+
+impl AnomalyStatsQuery {
+    // Stored detections since `from`, leaving out shadows so a coordinated deployment
+    // counts each once
+    pub fn filters(&self, from: DateTime<Utc>) -> Result<Vec<AnomalyFilter>, QueryValidationError> {
+        let mut filters = vec![
+            AnomalyFilter::ShadowEq(false),
+            AnomalyFilter::TimeRange(Some(from), None),
+        ];
+        if let Some(symbol) = non_empty("symbol", &self.symbol)? {
+            filters.push(AnomalyFilter::SymbolEq(symbol));
+        }
+        if let Some(exchange) = non_empty("exchange", &self.exchange)? {
+            filters.push(AnomalyFilter::ExchangeEq(exchange.to_lowercase()));
+        }
+        Ok(filters)
+    }
+}
+
+impl EvidenceQuery {
+    pub fn window(&self) -> Result<chrono::Duration, QueryValidationError> {
+        let minutes = self.window_minutes.unwrap_or(DEFAULT_WINDOW_MINUTES);
+        if !(1..=MAX_WINDOW_MINUTES).contains(&minutes) {
+            return Err(QueryValidationError::new(
+                "window_minutes",
+                format!("must be between 1 and {}", MAX_WINDOW_MINUTES),
+            ));
+        }
+        Ok(chrono::Duration::minutes(minutes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    fn anomaly_query() -> AnomalyQuery {
+        AnomalyQuery {
+            symbol: None,
+            exchange: None,
+            anomaly_type: None,
+            severity: None,
+            from: None,
+            to: None,
+            limit: None,
+            cursor: None,
+        }
+    }
+    
+    #[test]
+    fn test_anomaly_query_converts_to_filters() {
+        let query = AnomalyQuery {
+            symbol: Some("BTC/USDT".to_string()),
+            exchange: Some("Binance".to_string()),
+            severity: Some("high,critical".to_string()),
+            ..anomaly_query()
+        };
+        
+        let filters = FilterSet::<AnomalyFilter>::try_from(&query).unwrap();
+        assert_eq!(
+            filters.filters,
+            vec![
+                AnomalyFilter::ShadowEq(false),
+                AnomalyFilter::SymbolEq("BTC/USDT".to_string()),
+                AnomalyFilter::ExchangeEq("binance".to_string()),
+                AnomalyFilter::SeverityIn(vec!["High".to_string(), "Critical".to_string()]),
+            ]
+        );
+    }
+    
+    #[test]
+    fn test_validation_errors_name_the_parameter() {
+        let bad_severity = AnomalyQuery {
+            severity: Some("Extreme".to_string()),
+            ..anomaly_query()
+        };
+        assert_eq!(FilterSet::<AnomalyFilter>::try_from(&bad_severity).unwrap_err().parameter, "severity");
+        
+        let bad_limit = AnomalyQuery {
+            limit: Some(0),
+            ..anomaly_query()
+        };
+        assert_eq!(FilterSet::<AnomalyFilter>::try_from(&bad_limit).unwrap_err().parameter, "limit");
+        
+        let now = Utc::now();
+        let bad_range = AnomalyQuery {
+            from: Some(now),
+            to: Some(now - Duration::hours(1)),
+            ..anomaly_query()
+        };
+        assert_eq!(FilterSet::<AnomalyFilter>::try_from(&bad_range).unwrap_err().parameter, "from");
+    }
+    
+    #[test]
+    fn test_types_are_case_insensitive_and_cursors_round_trip() {
+        let query = AnomalyQuery {
+            anomaly_type: Some("volumespike, PRICESPIKE".to_string()),
+            ..anomaly_query()
+        };
+        let filters = FilterSet::<AnomalyFilter>::try_from(&query).unwrap();
+        assert_eq!(
+            filters.filters,
+            vec![
+                AnomalyFilter::ShadowEq(false),
+                AnomalyFilter::TypeIn(vec!["VolumeSpike".to_string(), "PriceSpike".to_string()]),
+            ]
+        );
+        // Accepted names are the ones detections are stored under
+        for anomaly_type in AnomalyType::ALL {
+            assert_eq!(serde_json::to_value(anomaly_type).unwrap(), anomaly_type.name());
+        }
+        
+        let cursor = AnomalyCursor {
+            detected_at: Utc.with_ymd_and_hms(2024, 3, 1, 12, 30, 0).unwrap()
+                + Duration::microseconds(123_456),
+            id: uuid::Uuid::new_v4(),
+        };
+        let query = AnomalyQuery {
+            cursor: Some(cursor.to_string()),
+            ..anomaly_query()
+        };
+        let filters = FilterSet::<AnomalyFilter>::try_from(&query).unwrap();
+        assert_eq!(
+            filters.filters,
+            vec![
+                AnomalyFilter::ShadowEq(false),
+                AnomalyFilter::Before(cursor.detected_at, cursor.id),
+            ]
+        );
+        
+        for bad in ["", "123", "abc_def", "1709296200000000_not-a-uuid"] {
+            let query = AnomalyQuery {
+                cursor: Some(bad.to_string()),
+                ..anomaly_query()
+            };
+            let error = FilterSet::<AnomalyFilter>::try_from(&query).unwrap_err();
+            assert_eq!(error.parameter, "cursor");
+        }
+    }
+    
+    #[test]
+    fn test_book_metrics_query_requires_instrument() {
+        let query = BookMetricsQuery {
+            symbol: Some("BTC/USDT".to_string()),
+            exchange: None,
+            from: None,
+            to: None,
+            downsample: Some("5m".to_string()),
+            limit: None,
+        };
+        assert_eq!(FilterSet::<BookMetricsFilter>::try_from(&query).unwrap_err().parameter, "exchange");
+        assert_eq!(query.downsample_secs().unwrap(), Some(300));
+        
+        let bad = BookMetricsQuery {
+            downsample: Some("fortnightly".to_string()),
+            ..query
+        };
+        assert_eq!(bad.downsample_secs().unwrap_err().parameter, "downsample");
+    }
+    
+    #[test]
+    fn test_evidence_window_is_bounded() {
+        let default = EvidenceQuery { window_minutes: None };
+        assert_eq!(default.window().unwrap(), Duration::minutes(DEFAULT_WINDOW_MINUTES));
+        
+        let too_wide = EvidenceQuery { window_minutes: Some(MAX_WINDOW_MINUTES + 1) };
+        assert_eq!(too_wide.window().unwrap_err().parameter, "window_minutes");
+    }
+}
@@ -1,12 +1,45 @@
 use crate::{
-    ApiResponse, ApiResult, MarketDataQuery, AnomalyQuery, TradingConfig,
-    AlertConfig, MarketStats, SystemStatus, state::AppState,
+    ApiError, ApiResponse, ApiResult, MarketDataQuery, AnomalyQuery, BookMetricsQuery, EvidenceQuery,
+    AnomalyPage, AnomalyStats, AnomalyStatsQuery, DetectorStatsQuery, JournalQuery,
+    AlertConfig, AlertHistoryQuery, AckRequest, HaltRequest, BulkAckRequest, MarketStats, SystemStatus,
+    ValidationErrorResponse, anomalies, filters::AnomalyCursor, market::MarketSnapshot,
+    state::AppState, websocket,
 };
 use axum::{
+    body::{Body, Bytes},
     extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
-use monitor_core::Result;
+use monitor_anomaly::{
+    external::{ExternalAnomaly, ExternalIngest, IngestRejection},
+    stats::DetectorStatsEntry,
+};
+use monitor_core::{
+    percentiles::{trailing_volatility, TodayMetrics},
+    storage::{
+        evidence::{EvidenceCollector, MAX_BUNDLE_BYTES},
+        query::{AnomalyFilter, BookMetricsFilter, FilterSet, TickFilter},
+        repository::{AnomalyRepository, BookMetricsBucket, BookMetricsRepository, TickRepository},
+    },
+    instrument::InstrumentKey,
+    JournalFormat, Result, TradingConfig,
+};
+use monitor_notifier::{
+    ack::{Acknowledgement, AlertHistoryEntry, BulkAck},
+    manager::NotificationManager,
+    routing::RoutingConfig,
+    slo::DeliveryMetrics,
+};
+use monitor_trader::{
+    circuit_breaker::{BreakerStatus, CircuitBreaker},
+    dca::{DcaPlan, DcaPlans},
+    executor::AutoTrader,
+    journal,
+    orders::PendingOrder,
+    Position, TradingStats,
+};
 use std::sync::Arc;
 use tracing::info;
 
@@ -17,27 +50,91 @@ pub async fn health_check() -> ApiResult<String> {
 pub async fn get_system_status(
     State(state): State<AppState>,
 ) -> ApiResult<SystemStatus> {
+    let messaging = state.messaging.read().as_ref().map(|m| m.status());
     let status = SystemStatus {
-        status: "running".to_string(),
+        status: if messaging.as_ref().is_some_and(|m| m.is_degraded()) {
+            "degraded".to_string()
+        } else {
+            "running".to_string()
+        },
         uptime_seconds: 0, // TODO: Track actual uptime
         connected_exchanges: vec![],
         active_monitors: 0,
         anomalies_detected_24h: 0,
         trades_executed_24h: 0,
+        warmup_remaining_seconds: state
+            .warmup
+            .read()
+            .as_ref()
+            .filter(|w| !w.is_complete())
+            .map(|w| w.remaining(chrono::Utc::now()).num_seconds()),
+        messaging,
+        leadership: state.leadership.read().as_ref().map(|l| l.status()),
     };
     
     Ok(Json(ApiResponse::success(status)))
 }
 
+// Summaries of the stored ticks per instrument, over the last 24h unless `from`/`to` say
+// otherwise. Served from `state.market_stats` for a few seconds after each query
 pub async fn get_market_stats(
     Query(query): Query<MarketDataQuery>,
     State(state): State<AppState>,
 ) -> ApiResult<Vec<MarketStats>> {
-    // TODO: Implement actual market stats retrieval
-    let stats = vec![];
+    let mut filters = FilterSet::<TickFilter>::try_from(&query)?;
+    if query.from.is_none() && query.to.is_none() {
+        let since = chrono::Utc::now() - chrono::Duration::hours(24);
+        filters = filters.with(TickFilter::TimeRange(Some(since), None));
+    }
+    
+    let key = format!(
+        "{:?}|{:?}|{:?}|{:?}|{:?}",
+        query.symbol, query.exchange, query.from, query.to, query.limit
+    );
+    let pool = state.db.clone();
+    let snapshot = state
+        .market_stats
+        .get_or_load(key, move || async move {
+            let summaries = TickRepository::new(pool.clone()).summarize(&filters).await?;
+            let instruments: Vec<(String, String)> = summaries
+                .iter()
+                .map(|s| (s.exchange.clone(), s.symbol.clone()))
+                .collect();
+            let volatility = trailing_volatility(&pool, &instruments, chrono::Utc::now()).await?;
+            Ok(MarketSnapshot { summaries, volatility })
+        })
+        .await?;
+    if snapshot.summaries.is_empty() {
+        if let Some(symbol) = &query.symbol {
+            return Err(ApiError {
+                status: StatusCode::NOT_FOUND,
+                message: format!("No market data for {}", symbol.trim()),
+            });
+        }
+    }
+    
+    let mut stats: Vec<MarketStats> = snapshot.summaries.iter().map(MarketStats::from).collect();
+    for stat in &mut stats {
+        let volatility = snapshot.volatility.get(&format!("{}:{}", stat.exchange, stat.symbol));
+        attach_percentiles(&state, stat, volatility.copied());
+    }
     Ok(Json(ApiResponse::success(stats)))
 }
 
+// `realized_volatility` covers the same trailing 24h as the summary, `None` without candles
+fn attach_percentiles(state: &AppState, stat: &mut MarketStats, realized_volatility: Option<f64>) {
+    let Some(percentiles) = state.percentiles.get(&stat.exchange, &stat.symbol) else {
+        return;
+    };
+    let open = stat.current_price - stat.price_change_24h;
+    let today = TodayMetrics {
+        realized_volatility,
+        volume: stat.volume_24h,
+        range: if open > 0.0 { (stat.high_24h - stat.low_24h) / open } else { 0.0 },
+    };
+    stat.percentiles = Some(percentiles.context(&today, chrono::Utc::now()));
+}
+
 pub async fn get_market_history(
     Query(query): Query<MarketDataQuery>,
     State(state): State<AppState>,
@@ -47,6 +144,26 @@ pub async fn get_market_history(
     Ok(Json(ApiResponse::success(history)))
 }
 
+pub async fn get_book_metrics(
+    Query(query): Query<BookMetricsQuery>,
+    State(state): State<AppState>,
+) -> ApiResult<Vec<BookMetricsBucket>> {
+    let filters = FilterSet::<BookMetricsFilter>::try_from(&query)?;
+    let repository = BookMetricsRepository::new(state.db.clone());
+    
+    let series = match query.downsample_secs()? {
+        Some(bucket_secs) => repository.downsample(&filters.filters, bucket_secs).await?,
+        None => {
+            // Raw samples come back newest first, charts want them in time order
+            let mut records = repository.find_book_metrics(&filters).await?;
+            records.reverse();
+            records.into_iter().map(BookMetricsBucket::from).collect()
+        }
+    };
+    
+    Ok(Json(ApiResponse::success(series)))
+}
+
 pub async fn get_orderbook(
     Query(query): Query<MarketDataQuery>,
     State(state): State<AppState>,
@@ -59,83 +176,427 @@ pub async fn get_orderbook(
     Ok(Json(ApiResponse::success(orderbook)))
 }
 
+// Stored detections newest first, a page at a time
 pub async fn get_anomalies(
     Query(query): Query<AnomalyQuery>,
     State(state): State<AppState>,
-) -> ApiResult<Vec<monitor_anomaly::AnomalyDetection>> {
-    // TODO: Implement anomaly retrieval
-    let anomalies = vec![];
-    Ok(Json(ApiResponse::success(anomalies)))
+) -> ApiResult<AnomalyPage> {
+    let filters = FilterSet::<AnomalyFilter>::try_from(&query)?;
+    let anomalies = AnomalyRepository::new(state.db.clone()).find_anomalies(&filters).await?;
+    // A short page is the last one
+    let next_cursor = match anomalies.last() {
+        Some(last) if anomalies.len() as i64 == filters.page.limit => {
+            Some(AnomalyCursor::after(last).to_string())
+        }
+        _ => None,
+    };
+    
+    Ok(Json(ApiResponse::success(AnomalyPage { anomalies, next_cursor })))
 }
 
+// Downloadable JSON bundle of what was stored around an anomaly, capped at MAX_BUNDLE_BYTES and
+// streamed out as it is serialized
+pub async fn get_anomaly_evidence(
+    Path(id): Path<uuid::Uuid>,
+    Query(query): Query<EvidenceQuery>,
+    State(state): State<AppState>,
+) -> std::result::Result<Response, ApiError> {
+    let window = query.window()?;
+    let bundle = EvidenceCollector::new(state.db.clone())
+        .collect(id, window, MAX_BUNDLE_BYTES)
+        .await?
+        .ok_or_else(|| ApiError {
+            status: StatusCode::NOT_FOUND,
+            message: format!("Anomaly {} not found", id),
+        })?;
+    let body = Body::from_stream(futures::stream::iter(bundle.into_json_chunks()));
+    
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/json".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"anomaly-{}-evidence.json\"", id),
+            ),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+const API_KEY_HEADER: &str = "x-api-key";
+
+// Detections published by other systems. The API key must be one of those configured for the
+// payload's source, schema problems come back as a 422 listing every invalid field
+pub async fn ingest_anomaly(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> std::result::Result<Response, ApiError> {
+    let ingest = state.external_ingest.read().clone().ok_or_else(|| ApiError {
+        status: StatusCode::SERVICE_UNAVAILABLE,
+        message: "External detection ingest is not available".to_string(),
+    })?;
+    let Some(api_key) = headers.get(API_KEY_HEADER).and_then(|v| v.to_str().ok()) else {
+        return Err(ApiError {
+            status: StatusCode::UNAUTHORIZED,
+            message: format!("Missing {} header", API_KEY_HEADER),
+        });
+    };
+    let value: serde_json::Value = serde_json::from_slice(&body).map_err(|e| ApiError {
+        status: StatusCode::BAD_REQUEST,
+        message: format!("Invalid JSON: {}", e),
+    })?;
+    
+    // Authorize before validating, so only a source's own key sees its payload errors
+    if let Some(source) = value.get("source").and_then(|s| s.as_str()) {
+        if let Err(rejection) = ingest.gate().authorize(Some(api_key), source) {
+            return Ok(rejection_response(rejection));
+        }
+    }
+    let anomaly = match ExternalAnomaly::parse(&value, chrono::Utc::now()) {
+        Ok(anomaly) => anomaly,
+        Err(errors) => {
+            return Ok((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(ValidationErrorResponse::new(errors)),
+            )
+                .into_response())
+        }
+    };
+    
+    match ingest.ingest(anomaly).await {
+        Ok(receipt) => Ok(Json(ApiResponse::success(receipt)).into_response()),
+        Err(rejection) => Ok(rejection_response(rejection)),
+    }
+}
+
+fn rejection_response(rejection: IngestRejection) -> Response {
+    let status = match &rejection {
+        IngestRejection::Disabled => StatusCode::SERVICE_UNAVAILABLE,
+        IngestRejection::UnknownSource(_) => StatusCode::FORBIDDEN,
+        IngestRejection::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+        IngestRejection::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+    };
+    let body = Json(ApiResponse::<()>::error(rejection.to_string()));
+    match rejection {
+        IngestRejection::RateLimited { retry_after_secs, .. } => {
+            (status, [(header::RETRY_AFTER, retry_after_secs.to_string())], body).into_response()
+        }
+        _ => (status, body).into_response(),
+    }
+}
+
+// Totals, breakdowns and a histogram of the stored detections over the last day or month.
+// Served from `state.anomaly_stats` for a few seconds after each query
 pub async fn get_anomaly_stats(
+    Query(query): Query<AnomalyStatsQuery>,
     State(state): State<AppState>,
-) -> ApiResult<serde_json::Value> {
-    // TODO: Implement anomaly statistics
-    let stats = serde_json::json!({
-        "total": 0,
-        "by_type": {},
-        "by_severity": {}
+) -> ApiResult<AnomalyStats> {
+    let range = query.range.unwrap_or_default();
+    let to = chrono::Utc::now();
+    let from = to - range.window();
+    let filters = query.filters(from)?;
+    
+    let key = format!("{:?}|{:?}|{:?}", range, query.symbol, query.exchange);
+    let repository = AnomalyRepository::new(state.db.clone());
+    let stored = state
+        .anomaly_stats
+        .get_or_load(key, move || async move {
+            let (total, by_type, by_severity, by_exchange, by_symbol, buckets) = tokio::try_join!(
+                repository.count(&filters),
+                repository.count_by(&filters, "anomaly_type"),
+                repository.count_by(&filters, "severity"),
+                repository.count_by(&filters, "exchange"),
+                repository.count_by(&filters, "symbol"),
+                repository.histogram(&filters, range.bucket_secs()),
+            )?;
+            Ok(AnomalyStats {
+                range,
+                from,
+                to,
+                total,
+                by_type: anomalies::group_counts(by_type),
+                by_severity: anomalies::group_counts(by_severity),
+                by_exchange: anomalies::group_counts(by_exchange),
+                by_symbol: anomalies::group_counts(by_symbol),
+                histogram: anomalies::fill_histogram(from, to, range.bucket_secs(), &buckets),
+                suppressed: Default::default(),
+            })
+        })
+        .await?;
+    
+    let mut stats = AnomalyStats::clone(&stored);
+    stats.suppressed = state
+        .suppression
+        .read()
+        .as_ref()
+        .map(|suppressor| suppressor.stats())
+        .unwrap_or_default();
+    Ok(Json(ApiResponse::success(stats)))
+}
+
+// Runtime statistics of every detector, narrowed to a symbol and/or exchange when given
+pub async fn get_detector_stats(
+    Query(query): Query<DetectorStatsQuery>,
+    State(state): State<AppState>,
+) -> ApiResult<Vec<DetectorStatsEntry>> {
+    let mut stats = state
+        .anomaly_manager
+        .read()
+        .as_ref()
+        .map(|manager| manager.get_all_stats())
+        .unwrap_or_default();
+    stats.retain(|entry| {
+        query.symbol.as_ref().is_none_or(|symbol| &entry.symbol == symbol)
+            && query.exchange.as_ref().is_none_or(|exchange| &entry.exchange == exchange)
     });
     Ok(Json(ApiResponse::success(stats)))
 }
 
+fn trader(state: &AppState) -> std::result::Result<Arc<AutoTrader>, ApiError> {
+    state.trader.read().clone().ok_or_else(|| ApiError {
+        status: StatusCode::SERVICE_UNAVAILABLE,
+        message: "Auto trading is disabled".to_string(),
+    })
+}
+
+// Every route that changes trading needs one of `api.trading_api_keys`, checked before
+// anything else so an unauthorized caller learns nothing about the trader
+fn authorize_trading(state: &AppState, headers: &HeaderMap) -> std::result::Result<(), ApiError> {
+    let keys = state.trading_api_keys.read();
+    if keys.is_empty() {
+        return Err(ApiError {
+            status: StatusCode::FORBIDDEN,
+            message: "Trading changes are disabled, no trading API keys are configured".to_string(),
+        });
+    }
+    match headers.get(API_KEY_HEADER).and_then(|v| v.to_str().ok()) {
+        Some(key) if keys.iter().any(|k| k == key) => Ok(()),
+        Some(_) => Err(ApiError {
+            status: StatusCode::UNAUTHORIZED,
+            message: "Invalid API key".to_string(),
+        }),
+        None => Err(ApiError {
+            status: StatusCode::UNAUTHORIZED,
+            message: format!("Missing {} header", API_KEY_HEADER),
+        }),
+    }
+}
+
+// Sizes must be positive and percentages within 0-100, the trader takes them as they are
+fn validate_trading_config(config: &TradingConfig) -> std::result::Result<(), ApiError> {
+    let invalid = |message: String| ApiError {
+        status: StatusCode::BAD_REQUEST,
+        message,
+    };
+    if !(config.max_position_size.is_finite() && config.max_position_size > 0.0) {
+        return Err(invalid(format!(
+            "max_position_size must be positive, got {}",
+            config.max_position_size
+        )));
+    }
+    for (name, value) in [
+        ("risk_percentage", config.risk_percentage),
+        ("stop_loss_percentage", config.stop_loss_percentage),
+        ("take_profit_percentage", config.take_profit_percentage),
+    ] {
+        if !(0.0..=100.0).contains(&value) {
+            return Err(invalid(format!("{} must be between 0 and 100, got {}", name, value)));
+        }
+    }
+    Ok(())
+}
+
 pub async fn get_trading_config(
     State(state): State<AppState>,
 ) -> ApiResult<TradingConfig> {
-    // TODO: Implement config retrieval
-    let config = TradingConfig {
-        enabled: false,
-        symbol: "BTC/USDT".to_string(),
-        exchange: "binance".to_string(),
-        max_position_size: 1000.0,
-        stop_loss_percentage: 3.0,
-        take_profit_percentage: 6.0,
-    };
-    Ok(Json(ApiResponse::success(config)))
+    let trader = trader(&state)?;
+    Ok(Json(ApiResponse::success(trader.get_config())))
 }
 
+// Replaces the whole trading config, taking effect from the next signal. Behind the API key
+// like every trading change, so auto trading is only ever switched on by a caller holding one
 pub async fn update_trading_config(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Json(config): Json<TradingConfig>,
 ) -> ApiResult<TradingConfig> {
-    // TODO: Implement config update
+    authorize_trading(&state, &headers)?;
+    let trader = trader(&state)?;
+    validate_trading_config(&config)?;
     info!("Updating trading config: {:?}", config);
-    Ok(Json(ApiResponse::success(config)))
+    trader.update_config(config);
+    Ok(Json(ApiResponse::success(trader.get_config())))
 }
 
 pub async fn get_positions(
     State(state): State<AppState>,
-) -> ApiResult<Vec<monitor_trader::Position>> {
-    // TODO: Implement position retrieval
-    let positions = vec![];
-    Ok(Json(ApiResponse::success(positions)))
+) -> ApiResult<Vec<Position>> {
+    let trader = trader(&state)?;
+    Ok(Json(ApiResponse::success(trader.get_positions())))
 }
 
-pub async fn get_orders(
+pub async fn get_trading_stats(
     State(state): State<AppState>,
-) -> ApiResult<Vec<serde_json::Value>> {
-    // TODO: Implement order retrieval
-    let orders = vec![];
-    Ok(Json(ApiResponse::success(orders)))
+) -> ApiResult<TradingStats> {
+    let trader = trader(&state)?;
+    Ok(Json(ApiResponse::success(trader.get_stats())))
 }
 
-pub async fn place_order(
+// Closes the position at market, `key` is "exchange:symbol" with the slash URL-encoded
+pub async fn close_position(
     State(state): State<AppState>,
-    Json(order): Json<serde_json::Value>,
-) -> ApiResult<serde_json::Value> {
-    // TODO: Implement order placement
-    info!("Placing order: {:?}", order);
-    Ok(Json(ApiResponse::success(order)))
+    headers: HeaderMap,
+    Path(key): Path<String>,
+) -> ApiResult<Position> {
+    authorize_trading(&state, &headers)?;
+    let trader = trader(&state)?;
+    let key = InstrumentKey::parse(&key).ok_or_else(|| ApiError {
+        status: StatusCode::BAD_REQUEST,
+        message: format!("Expected an exchange:symbol key, got {}", key),
+    })?;
+    info!("Closing the position on {} on request", key);
+    let closed = trader.close_position_manually(&key).await?.ok_or_else(|| ApiError {
+        status: StatusCode::NOT_FOUND,
+        message: format!("No open position on {}", key),
+    })?;
+    Ok(Json(ApiResponse::success(closed)))
 }
 
-pub async fn cancel_order(
+pub async fn get_cooldowns(
     State(state): State<AppState>,
-    Path(id): Path<String>,
-) -> ApiResult<String> {
-    // TODO: Implement order cancellation
-    info!("Cancelling order: {}", id);
-    Ok(Json(ApiResponse::success("Order cancelled".to_string())))
+) -> ApiResult<Vec<monitor_trader::cooldown::CooldownStatus>> {
+    let cooldowns = state
+        .cooldowns
+        .read()
+        .as_ref()
+        .map(|cooldowns| cooldowns.active(chrono::Utc::now()))
+        .unwrap_or_default();
+    Ok(Json(ApiResponse::success(cooldowns)))
+}
+
+fn circuit_breaker(state: &AppState) -> std::result::Result<Arc<CircuitBreaker>, ApiError> {
+    state.circuit_breaker.read().clone().ok_or_else(|| ApiError {
+        status: StatusCode::SERVICE_UNAVAILABLE,
+        message: "Auto trading is disabled".to_string(),
+    })
+}
+
+pub async fn get_trading_halt(
+    State(state): State<AppState>,
+) -> ApiResult<BreakerStatus> {
+    let breaker = circuit_breaker(&state)?;
+    Ok(Json(ApiResponse::success(breaker.status(chrono::Utc::now()))))
+}
+
+// Stops new entries until resumed, open positions keep their stop-loss and take-profit. Goes
+// through the trader, which cancels working entries and saves the halt
+pub async fn halt_trading(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<HaltRequest>,
+) -> ApiResult<BreakerStatus> {
+    authorize_trading(&state, &headers)?;
+    let trader = trader(&state)?;
+    trader.halt_trading(request.note).await;
+    Ok(Json(ApiResponse::success(trader.get_breaker_status())))
+}
+
+pub async fn resume_trading(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> ApiResult<BreakerStatus> {
+    authorize_trading(&state, &headers)?;
+    let trader = trader(&state)?;
+    if !trader.resume_trading().await {
+        return Err(ApiError {
+            status: StatusCode::CONFLICT,
+            message: "Trading is not halted".to_string(),
+        });
+    }
+    Ok(Json(ApiResponse::success(trader.get_breaker_status())))
+}
+
+fn dca_plans(state: &AppState) -> std::result::Result<Arc<DcaPlans>, ApiError> {
+    state.dca_plans.read().clone().ok_or_else(|| ApiError {
+        status: StatusCode::SERVICE_UNAVAILABLE,
+        message: "Auto trading is disabled".to_string(),
+    })
+}
+
+// Entries still going out in clips, oldest first
+pub async fn get_dca_plans(
+    State(state): State<AppState>,
+) -> ApiResult<Vec<DcaPlan>> {
+    let plans = dca_plans(&state)?;
+    Ok(Json(ApiResponse::success(plans.list())))
+}
+
+// Stops the remaining clips, the ones already filled stay in the position
+pub async fn cancel_dca_plan(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<uuid::Uuid>,
+) -> ApiResult<DcaPlan> {
+    authorize_trading(&state, &headers)?;
+    let plans = dca_plans(&state)?;
+    let plan = plans.cancel(id).ok_or_else(|| ApiError {
+        status: StatusCode::NOT_FOUND,
+        message: format!("DCA plan {} not found", id),
+    })?;
+    info!("Cancelled DCA plan {} after {} of {} clips", plan.id, plan.clips_sent, plan.clips);
+    Ok(Json(ApiResponse::success(plan)))
+}
+
+// Signals, orders, fills and position changes between `from` and `to` as a CSV or JSON download
+pub async fn get_trading_journal(
+    Query(query): Query<JournalQuery>,
+    State(state): State<AppState>,
+) -> std::result::Result<Response, ApiError> {
+    let trader = trader(&state)?;
+    let format = query.format.unwrap_or_default();
+    let records = trader.export_journal(query.from, query.to).await?;
+    let body = journal::export(&records, format)?;
+    let (content_type, extension) = match format {
+        JournalFormat::Csv => ("text/csv", "csv"),
+        JournalFormat::Json => ("application/json", "json"),
+    };
+    
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"trade-journal.{}\"", extension),
+            ),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+// Entry orders resting on the venue until they fill or time out
+pub async fn get_orders(
+    State(state): State<AppState>,
+) -> ApiResult<Vec<PendingOrder>> {
+    let trader = trader(&state)?;
+    Ok(Json(ApiResponse::success(trader.get_pending_orders())))
+}
+
+// Manual orders are not supported yet, entries only come from signals
+pub async fn place_order() -> ApiResult<serde_json::Value> {
+    Err(ApiError {
+        status: StatusCode::NOT_IMPLEMENTED,
+        message: "Manual order placement is not implemented".to_string(),
+    })
+}
+
+pub async fn cancel_order(Path(id): Path<String>) -> ApiResult<String> {
+    Err(ApiError {
+        status: StatusCode::NOT_IMPLEMENTED,
+        message: format!("Manual cancellation of order {} is not implemented", id),
+    })
 }
 
 pub async fn get_alert_config(
@@ -159,10 +620,434 @@ pub async fn update_alert_config(
     Ok(Json(ApiResponse::success(config)))
 }
 
+fn notifier(state: &AppState) -> std::result::Result<Arc<NotificationManager>, ApiError> {
+    state.notifier.read().clone().ok_or_else(|| ApiError {
+        status: StatusCode::SERVICE_UNAVAILABLE,
+        message: "Notifications are disabled".to_string(),
+    })
+}
+
+pub async fn get_alert_routing(
+    State(state): State<AppState>,
+) -> ApiResult<RoutingConfig> {
+    let notifier = notifier(&state)?;
+    Ok(Json(ApiResponse::success(notifier.routing().await)))
+}
+
+pub async fn update_alert_routing(
+    State(state): State<AppState>,
+    Json(routing): Json<RoutingConfig>,
+) -> ApiResult<RoutingConfig> {
+    let notifier = notifier(&state)?;
+    routing.validate().map_err(|e| ApiError {
+        status: StatusCode::BAD_REQUEST,
+        message: e.to_string(),
+    })?;
+    
+    // Persist first so a restart never reverts to rules the API already reported as applied
+    routing.save(&state.db).await?;
+    notifier.set_routing(routing.clone()).await?;
+    
+    info!("Updated notification routing ({} rules)", routing.rules.len());
+    Ok(Json(ApiResponse::success(routing)))
+}
+
+pub async fn get_alert_slo(
+    State(state): State<AppState>,
+) -> ApiResult<DeliveryMetrics> {
+    let notifier = notifier(&state)?;
+    Ok(Json(ApiResponse::success(notifier.delivery_tracker().metrics(chrono::Utc::now()))))
+}
+
+// The delivery histograms and SLO gauges for Prometheus to scrape
+pub async fn get_metrics(State(state): State<AppState>) -> std::result::Result<Response, ApiError> {
+    let notifier = notifier(&state)?;
+    let metrics = notifier.delivery_tracker().metrics(chrono::Utc::now());
+    Ok((
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        metrics.to_prometheus(),
+    )
+        .into_response())
+}
+
 pub async fn get_alert_history(
+    Query(query): Query<AlertHistoryQuery>,
     State(state): State<AppState>,
-) -> ApiResult<Vec<serde_json::Value>> {
-    // TODO: Implement alert history retrieval
-    let alerts = vec![];
+) -> ApiResult<Vec<AlertHistoryEntry>> {
+    let notifier = notifier(&state)?;
+    let alerts = notifier
+        .alert_log()
+        .history(query.limit.unwrap_or(100).min(1000), query.acked);
     Ok(Json(ApiResponse::success(alerts)))
-}
\ No newline at end of file
+}
+
+fn acker(acked_by: &str) -> std::result::Result<&str, ApiError> {
+    match acked_by.trim() {
+        "" => Err(ApiError {
+            status: StatusCode::BAD_REQUEST,
+            message: "acked_by is required".to_string(),
+        }),
+        acked_by => Ok(acked_by),
+    }
+}
+
+pub async fn ack_alert(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+    Json(request): Json<AckRequest>,
+) -> ApiResult<Acknowledgement> {
+    let notifier = notifier(&state)?;
+    let ack = notifier
+        .acknowledge(id, acker(&request.acked_by)?, request.note)
+        .await?
+        .ok_or_else(|| ApiError {
+            status: StatusCode::NOT_FOUND,
+            message: format!("Alert {} not found", id),
+        })?;
+    
+    websocket::broadcast_alert_acked(&state, std::slice::from_ref(&ack));
+    Ok(Json(ApiResponse::success(ack)))
+}
+
+// Clears an alert storm in one call, capped at the configured bulk limit and audit-logged
+pub async fn bulk_ack_alerts(
+    State(state): State<AppState>,
+    Json(request): Json<BulkAckRequest>,
+) -> ApiResult<BulkAck> {
+    let notifier = notifier(&state)?;
+    let acked_by = acker(&request.acked_by)?;
+    if request.filter.is_empty() {
+        return Err(ApiError {
+            status: StatusCode::BAD_REQUEST,
+            message: "Bulk ack needs at least one filter criterion".to_string(),
+        });
+    }
+    
+    let result = notifier
+        .bulk_acknowledge(&request.filter, acked_by, request.note)
+        .await?;
+    if !result.acknowledgements.is_empty() {
+        websocket::broadcast_alert_acked(&state, &result.acknowledgements);
+    }
+    Ok(Json(ApiResponse::success(result)))
+}
+
+pub async fn get_consumers(
+    State(state): State<AppState>,
+) -> ApiResult<Vec<monitor_core::router::ConsumerStatus>> {
+    let consumers = state
+        .topic_router
+        .read()
+        .as_ref()
+        .map(|router| router.consumer_statuses())
+        .unwrap_or_default();
+    Ok(Json(ApiResponse::success(consumers)))
+}
+
+pub async fn get_caches(
+    State(state): State<AppState>,
+) -> ApiResult<Vec<monitor_core::cache::CacheStats>> {
+    Ok(Json(ApiResponse::success(state.caches.stats())))
+}
+
+pub async fn clear_cache(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> ApiResult<String> {
+    if !state.caches.clear(&name) {
+        return Err(ApiError {
+            status: StatusCode::NOT_FOUND,
+            message: format!("Unknown cache '{}'", name),
+        });
+    }
+    
+    info!("Cleared cache {}", name);
+    Ok(Json(ApiResponse::success(format!("Cache {} cleared", name))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use monitor_anomaly::external::{IngestGate, IngestReceipt};
+    use monitor_core::{IngestConfig, IngestSourceConfig};
+    use parking_lot::Mutex;
+
+    struct RecordingIngest {
+        gate: IngestGate,
+        ingested: Mutex<Vec<ExternalAnomaly>>,
+    }
+
+    #[async_trait::async_trait]
+    impl ExternalIngest for RecordingIngest {
+        fn gate(&self) -> &IngestGate {
+            &self.gate
+        }
+
+        async fn ingest(
+            &self,
+            anomaly: ExternalAnomaly,
+        ) -> std::result::Result<IngestReceipt, IngestRejection> {
+            let receipt = self.gate.admit(&anomaly, chrono::Utc::now())?;
+            if !receipt.duplicate {
+                self.ingested.lock().push(anomaly);
+            }
+            Ok(receipt)
+        }
+    }
+
+    fn state() -> (AppState, Arc<RecordingIngest>) {
+        let pool = sqlx::PgPool::connect_lazy("postgres://localhost/crypto_monitor").unwrap();
+        let state = AppState::new(pool);
+        let ingest = Arc::new(RecordingIngest {
+            gate: IngestGate::new(IngestConfig {
+                enabled: true,
+                sources: vec![
+                    IngestSourceConfig {
+                        name: "ml-research".to_string(),
+                        api_keys: vec!["ml-key".to_string()],
+                        max_per_minute: 2,
+                    },
+                    IngestSourceConfig {
+                        name: "risk".to_string(),
+                        api_keys: vec!["risk-key".to_string()],
+                        max_per_minute: 100,
+                    },
+                ],
+                dedup_capacity: 100,
+            }),
+            ingested: Mutex::new(Vec::new()),
+        });
+        state.set_external_ingest(ingest.clone());
+        (state, ingest)
+    }
+
+    fn payload(source: &str) -> serde_json::Value {
+        serde_json::json!({
+            "source": source,
+            "timestamp": chrono::Utc::now(),
+            "symbol": "ETH/USDT",
+            "exchange": "okx",
+            "anomaly_type": "PriceSpike",
+            "severity": "Medium",
+            "metrics": {
+                "current_value": 3150.0,
+                "expected_value": 3000.0,
+                "deviation": 150.0,
+                "z_score": null,
+                "percentage_change": 5.0,
+                "historical_avg": null,
+                "historical_std": null
+            },
+            "description": "Forecast residual outside the 99% band"
+        })
+    }
+
+    async fn post(
+        state: &AppState,
+        api_key: Option<&str>,
+        body: &serde_json::Value,
+    ) -> (StatusCode, HeaderMap, serde_json::Value) {
+        let mut headers = HeaderMap::new();
+        if let Some(key) = api_key {
+            headers.insert(API_KEY_HEADER, key.parse().unwrap());
+        }
+        let response = match ingest_anomaly(
+            State(state.clone()),
+            headers,
+            Bytes::from(serde_json::to_vec(body).unwrap()),
+        )
+        .await
+        {
+            Ok(response) => response,
+            Err(error) => error.into_response(),
+        };
+        let (parts, body) = response.into_parts();
+        let body = axum::body::to_bytes(body, usize::MAX).await.unwrap();
+        (parts.status, parts.headers, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_ingest_accepts_and_deduplicates() {
+        let (state, ingest) = state();
+        let mut detection = payload("risk");
+        detection["id"] = uuid::Uuid::new_v4().to_string().into();
+
+        let (status, _, body) = post(&state, Some("risk-key"), &detection).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["duplicate"], false);
+        assert_eq!(body["data"]["id"], detection["id"]);
+
+        let (status, _, body) = post(&state, Some("risk-key"), &detection).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["duplicate"], true);
+        assert_eq!(ingest.ingested.lock().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_ingest_rejects_invalid_payloads_with_field_errors() {
+        let (state, ingest) = state();
+        let mut detection = payload("risk");
+        detection["severity"] = "Severe".into();
+        detection["metrics"]["current_value"] = "high".into();
+        detection.as_object_mut().unwrap().remove("exchange");
+
+        let (status, _, body) = post(&state, Some("risk-key"), &detection).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        let fields: Vec<&str> = body["errors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| e["field"].as_str().unwrap())
+            .collect();
+        assert_eq!(fields, ["exchange", "severity", "metrics"]);
+        assert!(ingest.ingested.lock().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_ingest_checks_api_key_against_source() {
+        let (state, ingest) = state();
+
+        let (status, _, _) = post(&state, None, &payload("risk")).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _, _) = post(&state, Some("ml-key"), &payload("risk")).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _, body) = post(&state, Some("ml-key"), &payload("vendor")).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert!(body["error"].as_str().unwrap().contains("vendor"));
+        assert!(ingest.ingested.lock().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_ingest_rate_limits_each_source() {
+        let (state, ingest) = state();
+
+        for _ in 0..2 {
+            let (status, _, _) = post(&state, Some("ml-key"), &payload("ml-research")).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+        let (status, headers, _) = post(&state, Some("ml-key"), &payload("ml-research")).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(headers[header::RETRY_AFTER], "30");
+
+        let (status, _, _) = post(&state, Some("risk-key"), &payload("risk")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(ingest.ingested.lock().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_detector_stats_filtered_by_exchange() {
+        use monitor_anomaly::{
+            detector::AnomalyDetectorManager, PriceAnomalyConfig, TimeSeriesData,
+            VolumeAnomalyConfig,
+        };
+
+        let (state, _) = state();
+        let query = |exchange: Option<&str>| DetectorStatsQuery {
+            symbol: None,
+            exchange: exchange.map(str::to_string),
+        };
+        let Json(response) = get_detector_stats(Query(query(None)), State(state.clone()))
+            .await
+            .unwrap();
+        assert!(response.data.unwrap().is_empty());
+
+        let manager = Arc::new(AnomalyDetectorManager::new(
+            VolumeAnomalyConfig::default(),
+            PriceAnomalyConfig::default(),
+        ));
+        let data = TimeSeriesData {
+            timestamp: chrono::Utc::now(),
+            value: 10.0,
+        };
+        manager.process_data("BTC/USDT", "binance", &data);
+        manager.process_data("BTC/USDT", "okx", &data);
+        state.set_anomaly_manager(manager);
+
+        let Json(response) = get_detector_stats(Query(query(Some("okx"))), State(state.clone()))
+            .await
+            .unwrap();
+        let stats = response.data.unwrap();
+        assert!(!stats.is_empty());
+        assert!(stats.iter().all(|entry| entry.exchange == "okx"));
+        let volume = stats.iter().find(|entry| entry.detector == "volume").unwrap();
+        assert_eq!(volume.stats.samples_seen, 1);
+    }
+
+    #[tokio::test]
+    async fn test_halt_and_resume() {
+        let (state, _) = state();
+        assert!(get_trading_halt(State(state.clone())).await.is_err());
+
+        let config = monitor_testkit::fixtures::trading_config(serde_json::json!({}));
+        let paper = Arc::new(monitor_trader::paper::PaperExecutionClient::new(
+            Default::default(),
+            "USDT",
+            10_000.0,
+        ));
+        let trader = Arc::new(AutoTrader::new(
+            config.clone(),
+            Box::new(monitor_trader::strategy::AnomalyBasedStrategy::new(config.clone())),
+            Box::new(monitor_trader::risk::SimpleRiskManager::new(config)),
+            paper,
+            10_000.0,
+        ));
+        let breaker = trader.circuit_breaker();
+        state.set_trader(trader);
+        state.set_circuit_breaker(breaker.clone());
+
+        let request = HaltRequest {
+            note: Some("exchange incident".to_string()),
+        };
+        let Json(response) = halt_trading(State(state.clone()), Json(request)).await.unwrap();
+        assert!(response.data.unwrap().halt.is_some());
+        assert!(breaker.is_halted());
+
+        let Json(response) = resume_trading(State(state.clone())).await.unwrap();
+        assert!(response.data.unwrap().halt.is_none());
+        let error = resume_trading(State(state)).await.unwrap_err();
+        assert_eq!(error.status, StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn test_list_and_cancel_dca_plans() {
+        let (state, _) = state();
+        assert!(get_dca_plans(State(state.clone())).await.is_err());
+
+        let plans = Arc::new(DcaPlans::new());
+        state.set_dca_plans(plans.clone());
+        let signal = monitor_trader::TradingSignal {
+            id: uuid::Uuid::new_v4(),
+            timestamp: chrono::Utc::now(),
+            symbol: "BTC/USDT".to_string(),
+            exchange: "binance".to_string(),
+            signal_type: monitor_trader::SignalType::Buy,
+            strength: monitor_trader::SignalStrength::Medium,
+            price: 50_000.0,
+            reason: "test".to_string(),
+            anomaly_id: None,
+        };
+        let config = monitor_core::DcaConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        let plan = DcaPlan::new(
+            signal,
+            "default",
+            monitor_trader::PositionSide::Long,
+            0.4,
+            4,
+            &config,
+            chrono::Utc::now(),
+        );
+        let id = plan.id;
+        plans.start(plan);
+
+        let Json(response) = get_dca_plans(State(state.clone())).await.unwrap();
+        assert_eq!(response.data.unwrap().len(), 1);
+        let Json(response) = cancel_dca_plan(State(state.clone()), Path(id)).await.unwrap();
+        assert_eq!(response.data.unwrap().id, id);
+        assert!(plans.is_empty());
+        let error = cancel_dca_plan(State(state), Path(id)).await.unwrap_err();
+        assert_eq!(error.status, StatusCode::NOT_FOUND);
+    }
+}
@@ -1,7 +1,11 @@
+pub mod cache;
 pub mod handlers;
 pub mod websocket;
 pub mod server;
 pub mod state;
+pub mod filters;
+pub mod market;
+pub mod anomalies;
 
 use axum::{
     extract::{Path, Query, State},
@@ -10,9 +14,9 @@ use axum::{
     Json,
 };
 use chrono::{DateTime, Utc};
-use monitor_core::{MonitorError, Result};
+use monitor_core::{JournalFormat, MonitorError, Result};
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::{collections::BTreeMap, sync::Arc};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ApiResponse<T> {
@@ -51,6 +55,17 @@ pub struct MarketDataQuery {
     pub limit: Option<i64>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BookMetricsQuery {
+    pub symbol: Option<String>,
+    pub exchange: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    // Bucket size such as "1m" or "1h", raw samples when absent
+    pub downsample: Option<String>,
+    pub limit: Option<i64>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AnomalyQuery {
     pub symbol: Option<String>,
@@ -60,16 +75,106 @@ pub struct AnomalyQuery {
     pub from: Option<DateTime<Utc>>,
     pub to: Option<DateTime<Utc>>,
     pub limit: Option<i64>,
+    // `next_cursor` of the previous page
+    pub cursor: Option<String>,
 }
 
+// Newest first. `next_cursor` is set when the page came back full and fetches the one after
 #[derive(Debug, Serialize, Deserialize)]
-pub struct TradingConfig {
-    pub enabled: bool,
-    pub symbol: String,
-    pub exchange: String,
-    pub max_position_size: f64,
-    pub stop_loss_percentage: f64,
-    pub take_profit_percentage: f64,
+pub struct AnomalyPage {
+    pub anomalies: Vec<monitor_core::storage::repository::AnomalyRecord>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AnomalyStatsQuery {
+    // 24h when absent
+    pub range: Option<anomalies::StatsRange>,
+    pub symbol: Option<String>,
+    pub exchange: Option<String>,
+}
+
+// Stored detections over the range, shadows left out. Every bucket of the range is listed
+// oldest first, empty ones with a zero count, the first one starting before `from`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnomalyStats {
+    pub range: anomalies::StatsRange,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub total: i64,
+    pub by_type: BTreeMap<String, i64>,
+    pub by_severity: BTreeMap<String, i64>,
+    pub by_exchange: BTreeMap<String, i64>,
+    pub by_symbol: BTreeMap<String, i64>,
+    pub histogram: Vec<monitor_core::storage::repository::BucketCount>,
+    // Since startup, detections dropped before they were stored
+    pub suppressed: monitor_anomaly::suppression::SuppressionStats,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DetectorStatsQuery {
+    pub symbol: Option<String>,
+    pub exchange: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EvidenceQuery {
+    // Minutes of context on each side of the detection
+    pub window_minutes: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JournalQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    // json when absent
+    pub format: Option<JournalFormat>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AlertHistoryQuery {
+    // true for acknowledged alerts only, false for outstanding ones
+    pub acked: Option<bool>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct HaltRequest {
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AckRequest {
+    pub acked_by: String,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BulkAckRequest {
+    pub acked_by: String,
+    pub note: Option<String>,
+    pub filter: monitor_notifier::ack::AckFilter,
+}
+
+// 422 body for ingest payloads that don't match the ExternalAnomaly contract
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ValidationErrorResponse {
+    pub success: bool,
+    pub error: String,
+    pub errors: Vec<monitor_anomaly::external::FieldError>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl ValidationErrorResponse {
+    pub fn new(errors: Vec<monitor_anomaly::external::FieldError>) -> Self {
+        Self {
+            success: false,
+            error: format!("Detection failed validation ({} errors)", errors.len()),
+            errors,
+            timestamp: Utc::now(),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -93,7 +198,7 @@ pub enum ChannelType {
     SMS,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MarketStats {
     pub symbol: String,
     pub exchange: String,
@@ -104,6 +209,9 @@ pub struct MarketStats {
     pub high_24h: f64,
     pub low_24h: f64,
     pub last_update: DateTime<Utc>,
+    // Where today sits within the trailing 30-day distribution, absent until the first nightly run
+    #[serde(flatten, default, skip_serializing_if = "Option::is_none")]
+    pub percentiles: Option<monitor_core::percentiles::PercentileContext>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -114,6 +222,10 @@ pub struct SystemStatus {
     pub active_monitors: i32,
     pub anomalies_detected_24h: i64,
     pub trades_executed_24h: i64,
+    pub warmup_remaining_seconds: Option<i64>,
+    pub messaging: Option<monitor_core::messaging::MessagingStatus>,
+    // Present when multi-instance coordination is enabled
+    pub leadership: Option<monitor_core::coordination::LeadershipStatus>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
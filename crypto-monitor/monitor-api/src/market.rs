@@ -0,0 +1,72 @@
+use crate::{cache::QueryCache, MarketStats};
+use monitor_core::storage::repository::MarketSummaryRecord;
+use std::{collections::HashMap, time::Duration};
+
+// Dashboards poll the stats every few seconds, this long they are served from memory
+pub const MARKET_STATS_TTL: Duration = Duration::from_secs(5);
+
+// What a market stats query loads: the tick summaries and the trailing 24h realized volatility
+// of their instruments, keyed "exchange:symbol"
+pub struct MarketSnapshot {
+    pub summaries: Vec<MarketSummaryRecord>,
+    pub volatility: HashMap<String, f64>,
+}
+
+// Snapshots per market stats query, so polling doesn't run the aggregates per request
+pub type MarketStatsCache = QueryCache<MarketSnapshot>;
+
+impl Default for MarketStatsCache {
+    fn default() -> Self {
+        Self::new("market_stats", MARKET_STATS_TTL)
+    }
+}
+
+impl From<&MarketSummaryRecord> for MarketStats {
+    fn from(summary: &MarketSummaryRecord) -> Self {
+        let change = summary.last_price - summary.open_price;
+        Self {
+            symbol: summary.symbol.clone(),
+            exchange: summary.exchange.clone(),
+            current_price: summary.last_price,
+            volume_24h: summary.volume,
+            price_change_24h: change,
+            price_change_percentage_24h: if summary.open_price > 0.0 {
+                change / summary.open_price * 100.0
+            } else {
+                0.0
+            },
+            high_24h: summary.high,
+            low_24h: summary.low,
+            last_update: summary.last_update,
+            percentiles: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(last_price: f64) -> MarketSummaryRecord {
+        MarketSummaryRecord {
+            exchange: "binance".to_string(),
+            symbol: "BTC/USDT".to_string(),
+            open_price: 50_000.0,
+            last_price,
+            high: 52_000.0,
+            low: 49_000.0,
+            volume: 12.5,
+            samples: 3,
+            last_update: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_change_is_against_the_first_price() {
+        let stats = MarketStats::from(&summary(51_000.0));
+        assert_eq!(stats.current_price, 51_000.0);
+        assert_eq!(stats.price_change_24h, 1_000.0);
+        assert_eq!(stats.price_change_percentage_24h, 2.0);
+        assert_eq!(stats.high_24h, 52_000.0);
+    }
+}
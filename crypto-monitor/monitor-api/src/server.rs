@@ -15,48 +15,7 @@ pub struct ApiServer {
 
 impl ApiServer {
     pub async fn new(config: MonitorConfig, state: AppState) -> Result<Self> {
-        let app = Router::new()
-            // Health check
-            .route("/health", get(handlers::health_check))
-            
-            // System status
-            .route("/api/v1/status", get(handlers::get_system_status))
-            
-            // Market data endpoints
-            .route("/api/v1/market/stats", get(handlers::get_market_stats))
-            .route("/api/v1/market/history", get(handlers::get_market_history))
-            .route("/api/v1/market/orderbook", get(handlers::get_orderbook))
-            
-            // Anomaly endpoints
-            .route("/api/v1/anomalies", get(handlers::get_anomalies))
-            .route("/api/v1/anomalies/stats", get(handlers::get_anomaly_stats))
-            
-            // Trading endpoints
-            .route("/api/v1/trading/config", get(handlers::get_trading_config))
-            .route("/api/v1/trading/config", post(handlers::update_trading_config))
-            .route("/api/v1/trading/positions", get(handlers::get_positions))
-            .route("/api/v1/trading/orders", get(handlers::get_orders))
-            .route("/api/v1/trading/orders", post(handlers::place_order))
-            .route("/api/v1/trading/orders/:id", delete(handlers::cancel_order))
-            
-            // Alert configuration
-            .route("/api/v1/alerts/config", get(handlers::get_alert_config))
-            .route("/api/v1/alerts/config", post(handlers::update_alert_config))
-            .route("/api/v1/alerts/history", get(handlers::get_alert_history))
-            
-            // WebSocket endpoint for real-time data
-            .route("/ws", get(websocket::websocket_handler))
-            
-            // Add state
-            .with_state(state)
-            
-            // Add CORS middleware
-            .layer(
-                CorsLayer::new()
-                    .allow_origin(Any)
-                    .allow_methods(Any)
-                    .allow_headers(Any),
-            );
+        let app = router(state);
         
         let addr = SocketAddr::from(([0, 0, 0, 0], 8080));
         
@@ -76,4 +35,74 @@ impl ApiServer {
             
         Ok(())
     }
+}
+
+// Every route over `state`, without binding a listener
+pub fn router(state: AppState) -> Router {
+    Router::new()
+        // Health check
+        .route("/health", get(handlers::health_check))
+        .route("/metrics", get(handlers::get_metrics))
+        
+        // System status
+        .route("/api/v1/status", get(handlers::get_system_status))
+        
+        // Market data endpoints
+        .route("/api/v1/market/stats", get(handlers::get_market_stats))
+        .route("/api/v1/market/history", get(handlers::get_market_history))
+        .route("/api/v1/market/orderbook", get(handlers::get_orderbook))
+        .route("/api/v1/market/book-metrics", get(handlers::get_book_metrics))
+        
+        // Anomaly endpoints
+        .route("/api/v1/anomalies", get(handlers::get_anomalies))
+        .route("/api/v1/anomalies/stats", get(handlers::get_anomaly_stats))
+        .route("/api/v1/anomalies/detectors", get(handlers::get_detector_stats))
+        .route("/api/v1/anomalies/ingest", post(handlers::ingest_anomaly))
+        .route("/api/v1/anomalies/:id/evidence", get(handlers::get_anomaly_evidence))
+        
+        // Trading endpoints
+        .route("/api/v1/trading/config", get(handlers::get_trading_config))
+        .route("/api/v1/trading/config", post(handlers::update_trading_config))
+        .route("/api/v1/trading/positions", get(handlers::get_positions))
+        .route("/api/v1/trading/positions/:key/close", post(handlers::close_position))
+        .route("/api/v1/trading/stats", get(handlers::get_trading_stats))
+        .route("/api/v1/trading/journal", get(handlers::get_trading_journal))
+        .route("/api/v1/trading/cooldowns", get(handlers::get_cooldowns))
+        .route("/api/v1/trading/halt", get(handlers::get_trading_halt))
+        .route("/api/v1/trading/halt", post(handlers::halt_trading))
+        .route("/api/v1/trading/resume", post(handlers::resume_trading))
+        .route("/api/v1/trading/dca", get(handlers::get_dca_plans))
+        .route("/api/v1/trading/dca/:id", delete(handlers::cancel_dca_plan))
+        .route("/api/v1/trading/orders", get(handlers::get_orders))
+        .route("/api/v1/trading/orders", post(handlers::place_order))
+        .route("/api/v1/trading/orders/:id", delete(handlers::cancel_order))
+        
+        // Alert configuration
+        .route("/api/v1/alerts/config", get(handlers::get_alert_config))
+        .route("/api/v1/alerts/config", post(handlers::update_alert_config))
+        .route("/api/v1/alerts/config/routing", get(handlers::get_alert_routing))
+        .route("/api/v1/alerts/config/routing", put(handlers::update_alert_routing))
+        .route("/api/v1/alerts/slo", get(handlers::get_alert_slo))
+        .route("/api/v1/alerts/history", get(handlers::get_alert_history))
+        .route("/api/v1/alerts/ack", post(handlers::bulk_ack_alerts))
+        .route("/api/v1/alerts/:id/ack", post(handlers::ack_alert))
+        
+        // Admin endpoints
+        .route("/api/v1/admin/consumers", get(handlers::get_consumers))
+        .route("/api/v1/admin/caches", get(handlers::get_caches))
+        .route("/api/v1/admin/caches/:name/clear", post(handlers::clear_cache))
+        
+        // WebSocket endpoint for real-time data
+        .route("/ws", get(websocket::websocket_handler))
+        
+        // Add state
+        .with_state(state)
+        
+        // Add CORS middleware
+        .layer(
+            CorsLayer::new()
+                .allow_origin(Any)
+                .allow_methods(Any)
+                .allow_headers(Any),
+        )
 }
\ No newline at end of file
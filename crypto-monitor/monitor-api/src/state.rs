@@ -1,35 +1,153 @@
-use crate::websocket::Subscription;
+use crate::{
+    anomalies::AnomalyStatsCache,
+    market::MarketStatsCache,
+    websocket::{Subscription, WsClient, WsEncoding, WsMessage, WsMetrics},
+};
 use dashmap::DashMap;
-use fluvio::Fluvio;
+use monitor_anomaly::{
+    detector::AnomalyDetectorManager, external::ExternalIngest, suppression::AlertSuppressor,
+    warmup::WarmupTracker,
+};
+use monitor_core::{
+    book_metrics::BookMetricsRecorder, cache::CacheRegistry, coordination::LeaderElection, messaging::Messaging, percentiles::PercentileCache, router::TopicRouter,
+};
+use monitor_notifier::manager::NotificationManager;
+use monitor_trader::{
+    circuit_breaker::CircuitBreaker, cooldown::CooldownTracker, dca::DcaPlans, executor::AutoTrader,
+};
+use parking_lot::RwLock;
 use sqlx::PgPool;
 use std::sync::Arc;
-use tokio::sync::mpsc;
 use uuid::Uuid;
 
 #[derive(Clone)]
 pub struct AppState {
     pub db: PgPool,
-    pub fluvio: Arc<Fluvio>,
-    pub websocket_clients: Arc<DashMap<Uuid, mpsc::UnboundedSender<crate::websocket::WsMessage>>>,
+    pub websocket_clients: Arc<DashMap<Uuid, WsClient>>,
+    pub ws_metrics: Arc<WsMetrics>,
+    pub percentiles: Arc<PercentileCache>,
+    pub market_stats: Arc<MarketStatsCache>,
+    pub anomaly_stats: Arc<AnomalyStatsCache>,
+    pub caches: Arc<CacheRegistry>,
     pub subscriptions: Arc<DashMap<Uuid, Vec<Subscription>>>,
+    pub topic_router: Arc<RwLock<Option<Arc<TopicRouter>>>>,
+    pub warmup: Arc<RwLock<Option<Arc<WarmupTracker>>>>,
+    pub suppression: Arc<RwLock<Option<Arc<AlertSuppressor>>>>,
+    pub anomaly_manager: Arc<RwLock<Option<Arc<AnomalyDetectorManager>>>>,
+    // None when running with --no-trading
+    pub trader: Arc<RwLock<Option<Arc<AutoTrader>>>>,
+    pub cooldowns: Arc<RwLock<Option<Arc<CooldownTracker>>>>,
+    pub circuit_breaker: Arc<RwLock<Option<Arc<CircuitBreaker>>>>,
+    pub dca_plans: Arc<RwLock<Option<Arc<DcaPlans>>>>,
+    pub messaging: Arc<RwLock<Option<Arc<Messaging>>>>,
+    pub notifier: Arc<RwLock<Option<Arc<NotificationManager>>>>,
+    pub book_metrics: Arc<RwLock<Option<Arc<BookMetricsRecorder>>>>,
+    pub leadership: Arc<RwLock<Option<Arc<LeaderElection>>>>,
+    pub external_ingest: Arc<RwLock<Option<Arc<dyn ExternalIngest>>>>,
+    // x-api-key values allowed to change trading, empty refuses every change
+    pub trading_api_keys: Arc<RwLock<Vec<String>>>,
 }
 
 impl AppState {
-    pub fn new(db: PgPool, fluvio: Arc<Fluvio>) -> Self {
+    pub fn new(db: PgPool) -> Self {
+        let percentiles = Arc::new(PercentileCache::new());
+        let caches = CacheRegistry::new();
+        caches.register(percentiles.clone());
+        let market_stats = Arc::new(MarketStatsCache::default());
+        caches.register(market_stats.clone());
+        let anomaly_stats = Arc::new(AnomalyStatsCache::default());
+        caches.register(anomaly_stats.clone());
+        
         Self {
             db,
-            fluvio,
             websocket_clients: Arc::new(DashMap::new()),
+            ws_metrics: Arc::new(WsMetrics::default()),
+            percentiles,
+            market_stats,
+            anomaly_stats,
+            caches: Arc::new(caches),
             subscriptions: Arc::new(DashMap::new()),
+            topic_router: Arc::new(RwLock::new(None)),
+            warmup: Arc::new(RwLock::new(None)),
+            suppression: Arc::new(RwLock::new(None)),
+            anomaly_manager: Arc::new(RwLock::new(None)),
+            trader: Arc::new(RwLock::new(None)),
+            cooldowns: Arc::new(RwLock::new(None)),
+            circuit_breaker: Arc::new(RwLock::new(None)),
+            dca_plans: Arc::new(RwLock::new(None)),
+            messaging: Arc::new(RwLock::new(None)),
+            notifier: Arc::new(RwLock::new(None)),
+            book_metrics: Arc::new(RwLock::new(None)),
+            leadership: Arc::new(RwLock::new(None)),
+            external_ingest: Arc::new(RwLock::new(None)),
+            trading_api_keys: Arc::new(RwLock::new(Vec::new())),
         }
     }
     
-    pub fn add_websocket_client(
-        &self,
-        client_id: Uuid,
-        tx: mpsc::UnboundedSender<crate::websocket::WsMessage>,
-    ) {
-        self.websocket_clients.insert(client_id, tx);
+    pub fn set_warmup(&self, warmup: Arc<WarmupTracker>) {
+        *self.warmup.write() = Some(warmup);
+    }
+    
+    pub fn set_suppression(&self, suppressor: Arc<AlertSuppressor>) {
+        *self.suppression.write() = Some(suppressor);
+    }
+    
+    pub fn set_anomaly_manager(&self, manager: Arc<AnomalyDetectorManager>) {
+        *self.anomaly_manager.write() = Some(manager);
+    }
+    
+    pub fn set_trader(&self, trader: Arc<AutoTrader>) {
+        *self.trader.write() = Some(trader);
+    }
+    
+    pub fn set_cooldowns(&self, cooldowns: Arc<CooldownTracker>) {
+        *self.cooldowns.write() = Some(cooldowns);
+    }
+    
+    pub fn set_circuit_breaker(&self, circuit_breaker: Arc<CircuitBreaker>) {
+        *self.circuit_breaker.write() = Some(circuit_breaker);
+    }
+    
+    pub fn set_dca_plans(&self, plans: Arc<DcaPlans>) {
+        *self.dca_plans.write() = Some(plans);
+    }
+    
+    pub fn set_messaging(&self, messaging: Arc<Messaging>) {
+        *self.messaging.write() = Some(messaging);
+    }
+    
+    pub fn set_notifier(&self, notifier: Arc<NotificationManager>) {
+        *self.notifier.write() = Some(notifier);
+    }
+    
+    pub fn set_book_metrics(&self, recorder: Arc<BookMetricsRecorder>) {
+        *self.book_metrics.write() = Some(recorder);
+    }
+    
+    pub fn set_leadership(&self, leadership: Arc<LeaderElection>) {
+        *self.leadership.write() = Some(leadership);
+    }
+    
+    pub fn set_external_ingest(&self, ingest: Arc<dyn ExternalIngest>) {
+        *self.external_ingest.write() = Some(ingest);
+    }
+    
+    pub fn set_trading_api_keys(&self, keys: Vec<String>) {
+        *self.trading_api_keys.write() = keys;
+    }
+    
+    pub fn set_topic_router(&self, router: Arc<TopicRouter>) {
+        *self.topic_router.write() = Some(router);
+    }
+    
+    pub fn add_websocket_client(&self, client_id: Uuid, client: WsClient) {
+        self.websocket_clients.insert(client_id, client);
+    }
+    
+    pub fn set_client_encoding(&self, client_id: Uuid, encoding: WsEncoding) {
+        if let Some(mut client) = self.websocket_clients.get_mut(&client_id) {
+            client.encoding = encoding;
+        }
     }
     
     pub fn remove_websocket_client(&self, client_id: Uuid) {
@@ -37,10 +155,7 @@ impl AppState {
         self.subscriptions.remove(&client_id);
     }
     
-    pub fn get_websocket_client(
-        &self,
-        client_id: Uuid,
-    ) -> Option<mpsc::UnboundedSender<crate::websocket::WsMessage>> {
+    pub fn get_websocket_client(&self, client_id: Uuid) -> Option<WsClient> {
         self.websocket_clients.get(&client_id).map(|c| c.clone())
     }
     
@@ -57,19 +172,17 @@ impl AppState {
         }
     }
     
-    pub fn broadcast_to_subscribers<F>(&self, message: &crate::websocket::WsMessage, filter: F)
+    pub fn broadcast_to_subscribers<F>(&self, message: &WsMessage, filter: F)
     where
         F: Fn(&Subscription) -> bool,
     {
-        for entry in self.subscriptions.iter() {
-            let client_id = entry.key();
-            let subs = entry.value();
-            
-            if subs.iter().any(&filter) {
-                if let Some(tx) = self.websocket_clients.get(client_id) {
-                    let _ = tx.send(message.clone());
-                }
-            }
-        }
+        let targets: Vec<Uuid> = self
+            .subscriptions
+            .iter()
+            .filter(|entry| entry.value().iter().any(&filter))
+            .map(|entry| *entry.key())
+            .collect();
+        
+        crate::websocket::fan_out(&self.websocket_clients, targets, message, &self.ws_metrics);
     }
 }
\ No newline at end of file
@@ -2,24 +2,32 @@ use crate::state::AppState;
 use axum::{
     extract::{
         ws::{Message, WebSocket, WebSocketUpgrade},
-        State,
+        Query, State,
     },
     response::Response,
 };
+use dashmap::DashMap;
 use futures::{sink::SinkExt, stream::StreamExt};
 use monitor_core::MonitorEvent;
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::{
+    cell::OnceCell,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
 use tokio::sync::mpsc;
 use tracing::{error, info, warn};
+use uuid::Uuid;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct WsMessage {
     pub msg_type: WsMessageType,
     pub data: serde_json::Value,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum WsMessageType {
     Subscribe,
     Unsubscribe,
@@ -28,6 +36,147 @@ pub enum WsMessageType {
     Alert,
     Trade,
     Heartbeat,
+    // Switches the encoding of server-pushed messages, `data: {"encoding": "msgpack"}`
+    SetEncoding,
+    // Alerts acknowledged through the API, `data: {"acknowledgements": [...]}`
+    AlertAcked,
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WsEncoding {
+    #[default]
+    Json,
+    MsgPack,
+}
+
+impl WsEncoding {
+    pub fn encode(self, message: &WsMessage) -> Result<WsFrame, String> {
+        match self {
+            WsEncoding::Json => serde_json::to_string(message)
+                .map(|json| WsFrame::Text(json.into()))
+                .map_err(|e| e.to_string()),
+            WsEncoding::MsgPack => rmp_serde::to_vec_named(message)
+                .map(|bytes| WsFrame::Binary(bytes.into()))
+                .map_err(|e| e.to_string()),
+        }
+    }
+}
+
+// A message serialized once and shared between every client of the same encoding
+#[derive(Debug, Clone, PartialEq)]
+pub enum WsFrame {
+    Text(Arc<str>),
+    Binary(Arc<[u8]>),
+}
+
+impl WsFrame {
+    fn into_message(self) -> Message {
+        match self {
+            WsFrame::Text(text) => Message::Text(text.to_string()),
+            WsFrame::Binary(bytes) => Message::Binary(bytes.to_vec()),
+        }
+    }
+}
+
+// Decodes a client frame in whichever encoding it arrived in
+pub fn decode_message(message: &Message) -> Option<Result<WsMessage, String>> {
+    match message {
+        Message::Text(text) => Some(serde_json::from_str(text).map_err(|e| e.to_string())),
+        Message::Binary(bytes) => Some(rmp_serde::from_slice(bytes).map_err(|e| e.to_string())),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct WsMetrics {
+    pub json_serializations: AtomicU64,
+    pub msgpack_serializations: AtomicU64,
+}
+
+impl WsMetrics {
+    fn record(&self, encoding: WsEncoding) {
+        let counter = match encoding {
+            WsEncoding::Json => &self.json_serializations,
+            WsEncoding::MsgPack => &self.msgpack_serializations,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WsClient {
+    pub tx: mpsc::UnboundedSender<WsFrame>,
+    pub encoding: WsEncoding,
+}
+
+impl WsClient {
+    pub fn send(&self, message: &WsMessage, metrics: &WsMetrics) {
+        metrics.record(self.encoding);
+        match self.encoding.encode(message) {
+            Ok(frame) => {
+                let _ = self.tx.send(frame);
+            }
+            Err(e) => error!("Failed to serialize message: {}", e),
+        }
+    }
+}
+
+// Serializes lazily, at most once per encoding, however many clients receive the message
+struct EncodedMessage<'a> {
+    message: &'a WsMessage,
+    metrics: &'a WsMetrics,
+    json: OnceCell<Option<WsFrame>>,
+    msgpack: OnceCell<Option<WsFrame>>,
+}
+
+impl<'a> EncodedMessage<'a> {
+    fn new(message: &'a WsMessage, metrics: &'a WsMetrics) -> Self {
+        Self {
+            message,
+            metrics,
+            json: OnceCell::new(),
+            msgpack: OnceCell::new(),
+        }
+    }
+
+    fn frame(&self, encoding: WsEncoding) -> Option<WsFrame> {
+        let cell = match encoding {
+            WsEncoding::Json => &self.json,
+            WsEncoding::MsgPack => &self.msgpack,
+        };
+        cell.get_or_init(|| {
+            self.metrics.record(encoding);
+            encoding
+                .encode(self.message)
+                .map_err(|e| error!("Failed to serialize message: {}", e))
+                .ok()
+        })
+        .clone()
+    }
+}
+
+pub(crate) fn fan_out(
+    clients: &DashMap<Uuid, WsClient>,
+    targets: impl IntoIterator<Item = Uuid>,
+    message: &WsMessage,
+    metrics: &WsMetrics,
+) {
+    let encoded = EncodedMessage::new(message, metrics);
+    for client_id in targets {
+        if let Some(client) = clients.get(&client_id) {
+            if let Some(frame) = encoded.frame(client.encoding) {
+                let _ = client.tx.send(frame);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct WsParams {
+    #[serde(default)]
+    pub encoding: WsEncoding,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -39,33 +188,26 @@ pub struct Subscription {
 
 pub async fn websocket_handler(
     ws: WebSocketUpgrade,
+    Query(params): Query<WsParams>,
     State(state): State<AppState>,
 ) -> Response {
-    ws.on_upgrade(move |socket| handle_socket(socket, state))
+    ws.on_upgrade(move |socket| handle_socket(socket, state, params.encoding))
 }
 
-async fn handle_socket(socket: WebSocket, state: AppState) {
+async fn handle_socket(socket: WebSocket, state: AppState, encoding: WsEncoding) {
     let (mut sender, mut receiver) = socket.split();
-    let (tx, mut rx) = mpsc::unbounded_channel::<WsMessage>();
+    let (tx, mut rx) = mpsc::unbounded_channel::<WsFrame>();
     
     // Add client to connected clients
     let client_id = uuid::Uuid::new_v4();
-    state.add_websocket_client(client_id, tx.clone());
+    state.add_websocket_client(client_id, WsClient { tx, encoding });
     
-    info!("WebSocket client connected: {}", client_id);
+    info!("WebSocket client connected: {} ({:?})", client_id, encoding);
     
     // Spawn task to handle sending messages to client
     let mut send_task = tokio::spawn(async move {
-        while let Some(msg) = rx.recv().await {
-            let json = match serde_json::to_string(&msg) {
-                Ok(j) => j,
-                Err(e) => {
-                    error!("Failed to serialize message: {}", e);
-                    continue;
-                }
-            };
-            
-            if sender.send(Message::Text(json)).await.is_err() {
+        while let Some(frame) = rx.recv().await {
+            if sender.send(frame.into_message()).await.is_err() {
                 break;
             }
         }
@@ -74,16 +216,19 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
     // Spawn task to handle receiving messages from client
     let mut recv_task = tokio::spawn(async move {
         while let Some(Ok(msg)) = receiver.next().await {
-            match msg {
-                Message::Text(text) => {
-                    if let Ok(ws_msg) = serde_json::from_str::<WsMessage>(&text) {
-                        handle_client_message(ws_msg, &state, client_id).await;
-                    }
-                }
-                Message::Close(_) => {
-                    break;
-                }
-                _ => {}
+            if let Message::Close(_) = msg {
+                break;
+            }
+            
+            match decode_message(&msg) {
+                Some(Ok(ws_msg)) => handle_client_message(ws_msg, &state, client_id).await,
+                Some(Err(e)) => send_to_client(
+                    &state,
+                    client_id,
+                    WsMessageType::Error,
+                    serde_json::json!({ "error": format!("Invalid message: {}", e) }),
+                ),
+                None => {}
             }
         }
     });
@@ -119,11 +264,25 @@ async fn handle_client_message(msg: WsMessage, state: &AppState, client_id: uuid
         }
         WsMessageType::Heartbeat => {
             // Echo heartbeat back
-            if let Some(tx) = state.get_websocket_client(client_id) {
-                let _ = tx.send(WsMessage {
-                    msg_type: WsMessageType::Heartbeat,
-                    data: serde_json::json!({"timestamp": chrono::Utc::now()}),
-                });
+            send_to_client(
+                state,
+                client_id,
+                WsMessageType::Heartbeat,
+                serde_json::json!({"timestamp": chrono::Utc::now()}),
+            );
+        }
+        WsMessageType::SetEncoding => {
+            match serde_json::from_value::<WsParams>(msg.data) {
+                Ok(params) => {
+                    state.set_client_encoding(client_id, params.encoding);
+                    info!("Client {} switched to {:?}", client_id, params.encoding);
+                }
+                Err(e) => send_to_client(
+                    state,
+                    client_id,
+                    WsMessageType::Error,
+                    serde_json::json!({ "error": format!("Invalid encoding: {}", e) }),
+                ),
             }
         }
         _ => {
@@ -132,19 +291,49 @@ async fn handle_client_message(msg: WsMessage, state: &AppState, client_id: uuid
     }
 }
 
+fn send_to_client(
+    state: &AppState,
+    client_id: uuid::Uuid,
+    msg_type: WsMessageType,
+    data: serde_json::Value,
+) {
+    if let Some(client) = state.get_websocket_client(client_id) {
+        client.send(&WsMessage { msg_type, data }, &state.ws_metrics);
+    }
+}
+
+// Market subscribers get the event plus the latest spread, imbalance and microprice of its
+// symbol under `book_metrics`
 pub fn broadcast_market_event(state: &AppState, event: &MonitorEvent) {
+    let field = |name: &str| event.data.get(name).and_then(|v| v.as_str()).map(str::to_string);
+    let (symbol, exchange) = (field("symbol"), field("exchange"));
+    
+    let mut data = serde_json::to_value(event).unwrap_or_default();
+    let latest = match (&exchange, &symbol, state.book_metrics.read().as_ref()) {
+        (Some(exchange), Some(symbol), Some(recorder)) => recorder.latest(exchange, symbol),
+        _ => None,
+    };
+    if let (Some(latest), Some(object)) = (latest, data.as_object_mut()) {
+        object.insert("book_metrics".to_string(), serde_json::to_value(latest).unwrap_or_default());
+    }
+    
     let msg = WsMessage {
         msg_type: WsMessageType::MarketData,
-        data: serde_json::to_value(event).unwrap_or_default(),
+        data,
     };
     
     state.broadcast_to_subscribers(&msg, |sub| {
-        // Check if client is subscribed to this event
-        // This is a simplified version - you'd want more sophisticated filtering
-        true
+        (sub.channel == "market" || sub.channel == "all")
+            && matches_filter(&sub.symbols, symbol.as_deref())
+            && matches_filter(&sub.exchanges, exchange.as_deref())
     });
 }
 
+// An empty subscription list means every value
+fn matches_filter(allowed: &[String], value: Option<&str>) -> bool {
+    allowed.is_empty() || value.is_some_and(|v| allowed.iter().any(|a| a.eq_ignore_ascii_case(v)))
+}
+
 pub fn broadcast_anomaly_event(state: &AppState, anomaly: &monitor_anomaly::AnomalyDetection) {
     let msg = WsMessage {
         msg_type: WsMessageType::Anomaly,
@@ -156,6 +345,17 @@ pub fn broadcast_anomaly_event(state: &AppState, anomaly: &monitor_anomaly::Anom
     });
 }
 
+pub fn broadcast_alert_acked(state: &AppState, acks: &[monitor_notifier::ack::Acknowledgement]) {
+    let msg = WsMessage {
+        msg_type: WsMessageType::AlertAcked,
+        data: serde_json::json!({ "acknowledgements": acks }),
+    };
+    
+    state.broadcast_to_subscribers(&msg, |sub| {
+        sub.channel == "alerts" || sub.channel == "all"
+    });
+}
+
 pub fn broadcast_alert(state: &AppState, alert: serde_json::Value) {
     let msg = WsMessage {
         msg_type: WsMessageType::Alert,
@@ -165,4 +365,84 @@ pub fn broadcast_alert(state: &AppState, alert: serde_json::Value) {
     state.broadcast_to_subscribers(&msg, |sub| {
         sub.channel == "alerts" || sub.channel == "all"
     });
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages() -> Vec<WsMessage> {
+        [
+            WsMessageType::Subscribe,
+            WsMessageType::Unsubscribe,
+            WsMessageType::MarketData,
+            WsMessageType::Anomaly,
+            WsMessageType::Alert,
+            WsMessageType::Trade,
+            WsMessageType::Heartbeat,
+            WsMessageType::SetEncoding,
+            WsMessageType::AlertAcked,
+            WsMessageType::Error,
+        ]
+        .into_iter()
+        .map(|msg_type| WsMessage {
+            msg_type,
+            data: serde_json::json!({ "symbol": "BTC/USDT", "price": 50000.5, "tags": ["a", "b"] }),
+        })
+        .collect()
+    }
+
+    fn decode(frame: WsFrame) -> WsMessage {
+        decode_message(&frame.into_message()).unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_every_message_type_round_trips_in_both_encodings() {
+        for message in messages() {
+            for encoding in [WsEncoding::Json, WsEncoding::MsgPack] {
+                let frame = encoding.encode(&message).unwrap();
+                match (encoding, &frame) {
+                    (WsEncoding::Json, WsFrame::Text(_)) | (WsEncoding::MsgPack, WsFrame::Binary(_)) => {}
+                    other => panic!("unexpected frame {:?}", other),
+                }
+                assert_eq!(decode(frame), message);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mixed_clients_serialize_once_per_encoding() {
+        let clients = DashMap::new();
+        let mut receivers = Vec::new();
+        for encoding in [WsEncoding::Json, WsEncoding::MsgPack, WsEncoding::Json, WsEncoding::MsgPack] {
+            let (tx, rx) = mpsc::unbounded_channel();
+            let id = Uuid::new_v4();
+            clients.insert(id, WsClient { tx, encoding });
+            receivers.push((id, rx));
+        }
+
+        let metrics = WsMetrics::default();
+        let message = messages().remove(2);
+        fan_out(&clients, receivers.iter().map(|(id, _)| *id), &message, &metrics);
+
+        assert_eq!(metrics.json_serializations.load(Ordering::Relaxed), 1);
+        assert_eq!(metrics.msgpack_serializations.load(Ordering::Relaxed), 1);
+        for (_, rx) in &mut receivers {
+            assert_eq!(decode(rx.try_recv().unwrap()), message);
+        }
+    }
+
+    #[test]
+    fn test_no_serialization_without_clients_of_an_encoding() {
+        let clients = DashMap::new();
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let id = Uuid::new_v4();
+        clients.insert(id, WsClient { tx, encoding: WsEncoding::MsgPack });
+
+        let metrics = WsMetrics::default();
+        fan_out(&clients, [id], &messages().remove(0), &metrics);
+
+        assert_eq!(metrics.json_serializations.load(Ordering::Relaxed), 0);
+        assert_eq!(metrics.msgpack_serializations.load(Ordering::Relaxed), 1);
+    }
+}
@@ -0,0 +1,246 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use monitor_api::{server::router, state::AppState};
+use monitor_core::storage::repository::{AnomalyRecord, AnomalyRepository, BucketCount};
+use monitor_testkit::db::migrated_pool;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
+use tower::ServiceExt;
+
+fn anomaly(
+    exchange: &str,
+    symbol: &str,
+    anomaly_type: &str,
+    severity: &str,
+    detected_at: DateTime<Utc>,
+) -> AnomalyRecord {
+    AnomalyRecord {
+        id: uuid::Uuid::new_v4(),
+        exchange: exchange.to_string(),
+        symbol: symbol.to_string(),
+        anomaly_type: anomaly_type.to_string(),
+        severity: severity.to_string(),
+        confidence: Some(0.8),
+        current_value: 120.0,
+        expected_value: 100.0,
+        deviation: 20.0,
+        z_score: Some(3.5),
+        percentage_change: Some(20.0),
+        description: None,
+        reason: None,
+        metadata: None,
+        detected_at,
+        instance_id: None,
+        shadow: false,
+        source: None,
+    }
+}
+
+async fn seed(pool: &PgPool) -> DateTime<Utc> {
+    let repository = AnomalyRepository::new(pool.clone());
+    let now = Utc::now();
+    let hours_ago = |hours| now - Duration::hours(hours);
+    for record in [
+        anomaly("binance", "BTC/USDT", "VolumeSpike", "High", hours_ago(1)),
+        anomaly("binance", "BTC/USDT", "PriceSpike", "Critical", hours_ago(2)),
+        anomaly("binance", "ETH/USDT", "VolumeSpike", "Low", hours_ago(3)),
+        anomaly("okx", "BTC/USDT", "DepthImbalance", "Medium", hours_ago(4)),
+        anomaly("okx", "ETH/USDT", "VolumeSpike", "High", hours_ago(30)),
+    ] {
+        repository.insert(&record).await.unwrap();
+    }
+    now
+}
+
+async fn get(app: &Router, uri: &str) -> (StatusCode, Value) {
+    let request = Request::get(uri).body(Body::empty()).unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+}
+
+fn field<'a>(body: &'a Value, name: &str) -> Vec<&'a str> {
+    body["data"]["anomalies"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|anomaly| anomaly[name].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn test_filters_narrow_the_list() {
+    let pool = migrated_pool().await;
+    let now = seed(&pool).await;
+    // Recorded by a follower, never listed
+    let mut shadow = anomaly("binance", "BTC/USDT", "LargeOrder", "Critical", now);
+    shadow.shadow = true;
+    AnomalyRepository::new(pool.clone()).insert(&shadow).await.unwrap();
+    let app = router(AppState::new(pool));
+
+    let (status, body) = get(&app, "/api/v1/anomalies").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        field(&body, "anomaly_type"),
+        vec!["VolumeSpike", "PriceSpike", "VolumeSpike", "DepthImbalance", "VolumeSpike"]
+    );
+    assert!(body["data"]["next_cursor"].is_null());
+    let (_, body) = get(&app, "/api/v1/anomalies?anomaly_type=LargeOrder").await;
+    assert!(field(&body, "id").is_empty());
+
+    let (_, body) = get(&app, "/api/v1/anomalies?symbol=BTC%2FUSDT").await;
+    assert_eq!(field(&body, "anomaly_type"), vec!["VolumeSpike", "PriceSpike", "DepthImbalance"]);
+    let (_, body) = get(&app, "/api/v1/anomalies?exchange=OKX").await;
+    assert_eq!(field(&body, "exchange"), vec!["okx", "okx"]);
+    let (_, body) = get(&app, "/api/v1/anomalies?anomaly_type=VolumeSpike&exchange=binance").await;
+    assert_eq!(field(&body, "symbol"), vec!["BTC/USDT", "ETH/USDT"]);
+    let (_, body) = get(&app, "/api/v1/anomalies?severity=high,critical").await;
+    assert_eq!(field(&body, "severity"), vec!["High", "Critical", "High"]);
+
+    let from = (now - Duration::hours(24)).format("%Y-%m-%dT%H:%M:%SZ");
+    let to = (now - Duration::minutes(150)).format("%Y-%m-%dT%H:%M:%SZ");
+    let (_, body) = get(&app, &format!("/api/v1/anomalies?from={}&to={}", from, to)).await;
+    assert_eq!(field(&body, "anomaly_type"), vec!["VolumeSpike", "DepthImbalance"]);
+
+    let (status, body) = get(&app, "/api/v1/anomalies?anomaly_type=Meteor").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"].as_str().unwrap().contains("anomaly_type"));
+    let (status, _) = get(&app, "/api/v1/anomalies?severity=Severe").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = get(&app, "/api/v1/anomalies?cursor=page-two").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn test_cursor_pages_without_overlap() {
+    let pool = migrated_pool().await;
+    let repository = AnomalyRepository::new(pool.clone());
+    // Detections sharing an instant are ordered by id, a page boundary can fall between them
+    let at = Utc::now() - Duration::minutes(5);
+    for _ in 0..3 {
+        let record = anomaly("binance", "BTC/USDT", "LargeOrder", "High", at);
+        repository.insert(&record).await.unwrap();
+    }
+    seed(&pool).await;
+    let app = router(AppState::new(pool));
+
+    let mut seen = Vec::new();
+    let mut uri = "/api/v1/anomalies?limit=3".to_string();
+    loop {
+        let (status, body) = get(&app, &uri).await;
+        assert_eq!(status, StatusCode::OK);
+        let ids = field(&body, "id");
+        assert!(ids.len() <= 3);
+        seen.extend(ids.into_iter().map(str::to_string));
+        match body["data"]["next_cursor"].as_str() {
+            Some(cursor) => uri = format!("/api/v1/anomalies?limit=3&cursor={}", cursor),
+            None => break,
+        }
+    }
+    assert_eq!(seen.len(), 8);
+    assert_eq!(seen.iter().collect::<HashSet<_>>().len(), 8);
+
+    // The cursor composes with the filters
+    let (_, first) = get(&app, "/api/v1/anomalies?anomaly_type=VolumeSpike&limit=2").await;
+    assert_eq!(field(&first, "symbol"), vec!["BTC/USDT", "ETH/USDT"]);
+    let cursor = first["data"]["next_cursor"].as_str().unwrap();
+    let uri = format!("/api/v1/anomalies?anomaly_type=VolumeSpike&limit=2&cursor={}", cursor);
+    let (_, rest) = get(&app, &uri).await;
+    assert_eq!(field(&rest, "exchange"), vec!["okx"]);
+    assert!(rest["data"]["next_cursor"].is_null());
+}
+
+fn histogram(body: &Value) -> HashMap<DateTime<Utc>, i64> {
+    let buckets: Vec<BucketCount> =
+        serde_json::from_value(body["data"]["histogram"].clone()).unwrap();
+    buckets.into_iter().map(|bucket| (bucket.bucket, bucket.count)).collect()
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn test_stats_count_detections_per_hour_and_day() {
+    let pool = migrated_pool().await;
+    let repository = AnomalyRepository::new(pool.clone());
+    let now = Utc::now();
+    let hour = Utc.timestamp_opt(now.timestamp() / 3600 * 3600, 0).unwrap();
+    // Five minutes into an earlier hour, clear of the bucket boundaries
+    let at = |hours_back| hour - Duration::hours(hours_back) + Duration::minutes(5);
+    let mut shadow = anomaly("binance", "BTC/USDT", "VolumeSpike", "High", at(1));
+    shadow.shadow = true;
+    let seeded = [
+        anomaly("binance", "BTC/USDT", "VolumeSpike", "High", at(1)),
+        anomaly("binance", "ETH/USDT", "PriceSpike", "Critical", at(1)),
+        anomaly("okx", "BTC/USDT", "VolumeSpike", "Low", at(3)),
+        anomaly("okx", "BTC/USDT", "VolumeSpike", "Low", at(3)),
+        anomaly("binance", "BTC/USDT", "VolumeSpike", "Medium", at(3)),
+        anomaly("binance", "BTC/USDT", "LargeOrder", "High", at(5)),
+        // Only in the 30 day range
+        anomaly("binance", "BTC/USDT", "VolumeSpike", "High", at(72)),
+        // In neither
+        anomaly("binance", "BTC/USDT", "VolumeSpike", "High", at(24 * 40)),
+        // A follower's copy of the first, never counted
+        shadow,
+    ];
+    for record in &seeded {
+        repository.insert(record).await.unwrap();
+    }
+    let app = router(AppState::new(pool));
+
+    let (status, body) = get(&app, "/api/v1/anomalies/stats").await;
+    assert_eq!(status, StatusCode::OK);
+    let stats = &body["data"];
+    assert_eq!(stats["range"], "24h");
+    assert_eq!(stats["total"], 6);
+    assert_eq!(stats["by_type"], json!({ "LargeOrder": 1, "PriceSpike": 1, "VolumeSpike": 4 }));
+    assert_eq!(
+        stats["by_severity"],
+        json!({ "Critical": 1, "High": 2, "Low": 2, "Medium": 1 })
+    );
+    assert_eq!(stats["by_exchange"], json!({ "binance": 4, "okx": 2 }));
+    assert_eq!(stats["by_symbol"], json!({ "BTC/USDT": 5, "ETH/USDT": 1 }));
+    let hourly = histogram(&body);
+    assert_eq!(hourly.len(), 25);
+    assert_eq!(hourly[&(hour - Duration::hours(1))], 2);
+    assert_eq!(hourly[&(hour - Duration::hours(2))], 0);
+    assert_eq!(hourly[&(hour - Duration::hours(3))], 3);
+    assert_eq!(hourly[&(hour - Duration::hours(5))], 1);
+    assert_eq!(hourly.values().sum::<i64>(), 6);
+
+    let (status, body) = get(&app, "/api/v1/anomalies/stats?range=30d").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["total"], 7);
+    let daily = histogram(&body);
+    assert_eq!(daily.len(), 31);
+    let mut expected: HashMap<DateTime<Utc>, i64> = HashMap::new();
+    for record in seeded.iter().take(7) {
+        let day = record.detected_at.timestamp() / 86_400 * 86_400;
+        *expected.entry(Utc.timestamp_opt(day, 0).unwrap()).or_default() += 1;
+    }
+    for (day, count) in expected {
+        assert_eq!(daily[&day], count);
+    }
+    assert_eq!(daily.values().sum::<i64>(), 7);
+
+    let (_, body) = get(&app, "/api/v1/anomalies/stats?symbol=ETH%2FUSDT&exchange=Binance").await;
+    assert_eq!(body["data"]["total"], 1);
+    assert_eq!(body["data"]["by_type"], json!({ "PriceSpike": 1 }));
+    let (status, _) = get(&app, "/api/v1/anomalies/stats?range=7d").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Repeated queries are answered from the cache until it expires
+    repository
+        .insert(&anomaly("okx", "ETH/USDT", "PriceSpike", "High", at(1)))
+        .await
+        .unwrap();
+    assert_eq!(get(&app, "/api/v1/anomalies/stats").await.1["data"]["total"], 6);
+    tokio::time::sleep(monitor_api::anomalies::ANOMALY_STATS_TTL).await;
+    assert_eq!(get(&app, "/api/v1/anomalies/stats").await.1["data"]["total"], 7);
+}
@@ -0,0 +1,123 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use chrono::{Duration, Utc};
+use monitor_api::{server::router, state::AppState};
+use monitor_testkit::db::migrated_pool;
+use serde_json::Value;
+use sqlx::PgPool;
+use tower::ServiceExt;
+
+async fn tick(
+    pool: &PgPool,
+    exchange: &str,
+    symbol: &str,
+    hours_ago: i64,
+    price: f64,
+    volume: f64,
+) {
+    sqlx::query(
+        "INSERT INTO market_data (id, exchange, symbol, price, volume, timestamp) \
+         VALUES ($1, $2, $3, $4, $5, $6)",
+    )
+    .bind(uuid::Uuid::new_v4())
+    .bind(exchange)
+    .bind(symbol)
+    .bind(price)
+    .bind(volume)
+    .bind(Utc::now() - Duration::hours(hours_ago))
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+async fn seed(pool: &PgPool) {
+    // Older than a day, outside the default window
+    tick(pool, "binance", "BTC/USDT", 30, 40_000.0, 9.0).await;
+    tick(pool, "binance", "BTC/USDT", 20, 50_000.0, 1.0).await;
+    tick(pool, "binance", "BTC/USDT", 10, 53_000.0, 2.0).await;
+    tick(pool, "binance", "BTC/USDT", 5, 48_000.0, 1.5).await;
+    tick(pool, "binance", "BTC/USDT", 1, 51_000.0, 0.5).await;
+    tick(pool, "binance", "ETH/USDT", 2, 3_000.0, 10.0).await;
+    tick(pool, "okx", "BTC/USDT", 3, 50_100.0, 4.0).await;
+}
+
+async fn get(app: &Router, uri: &str) -> (StatusCode, Value) {
+    let request = Request::get(uri).body(Body::empty()).unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn test_stats_summarize_the_last_day_of_ticks() {
+    let pool = migrated_pool().await;
+    seed(&pool).await;
+    let app = router(AppState::new(pool));
+
+    let uri = "/api/v1/market/stats?symbol=BTC%2FUSDT&exchange=Binance";
+    let (status, body) = get(&app, uri).await;
+    assert_eq!(status, StatusCode::OK);
+    let stats = body["data"].as_array().unwrap();
+    assert_eq!(stats.len(), 1);
+    let btc = &stats[0];
+    assert_eq!(btc["exchange"], "binance");
+    assert_eq!(btc["current_price"], 51_000.0);
+    assert_eq!(btc["volume_24h"], 5.0);
+    assert_eq!(btc["high_24h"], 53_000.0);
+    assert_eq!(btc["low_24h"], 48_000.0);
+    assert_eq!(btc["price_change_24h"], 1_000.0);
+    assert_eq!(btc["price_change_percentage_24h"], 2.0);
+
+    let (status, body) = get(&app, "/api/v1/market/stats").await;
+    assert_eq!(status, StatusCode::OK);
+    let markets: Vec<(&str, &str)> = body["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| (s["exchange"].as_str().unwrap(), s["symbol"].as_str().unwrap()))
+        .collect();
+    assert_eq!(
+        markets,
+        vec![("binance", "BTC/USDT"), ("binance", "ETH/USDT"), ("okx", "BTC/USDT")]
+    );
+
+    let (_, body) = get(&app, "/api/v1/market/stats?symbol=BTC%2FUSDT").await;
+    assert_eq!(body["data"].as_array().unwrap().len(), 2);
+    let (_, body) = get(&app, "/api/v1/market/stats?limit=1").await;
+    assert_eq!(body["data"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn test_explicit_window_unknown_symbol_and_cache() {
+    let pool = migrated_pool().await;
+    seed(&pool).await;
+    let app = router(AppState::new(pool.clone()));
+
+    // The window from the query replaces the last day
+    let from = (Utc::now() - Duration::hours(48)).format("%Y-%m-%dT%H:%M:%SZ");
+    let uri = format!("/api/v1/market/stats?symbol=BTC%2FUSDT&exchange=binance&from={}", from);
+    let (status, body) = get(&app, &uri).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"][0]["volume_24h"], 14.0);
+    assert_eq!(body["data"][0]["low_24h"], 40_000.0);
+
+    let (status, body) = get(&app, "/api/v1/market/stats?symbol=DOGE%2FUSDT").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["error"], "No market data for DOGE/USDT");
+    let (status, _) = get(&app, "/api/v1/market/stats?limit=0").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // A new tick shows once the cached summary expires
+    let uri = "/api/v1/market/stats?symbol=ETH%2FUSDT";
+    assert_eq!(get(&app, uri).await.1["data"][0]["current_price"], 3_000.0);
+    tick(&pool, "binance", "ETH/USDT", 0, 3_100.0, 1.0).await;
+    assert_eq!(get(&app, uri).await.1["data"][0]["current_price"], 3_000.0);
+    tokio::time::sleep(monitor_api::market::MARKET_STATS_TTL).await;
+    assert_eq!(get(&app, uri).await.1["data"][0]["current_price"], 3_100.0);
+}
@@ -0,0 +1,302 @@
+// The trading routes served by the router against an AutoTrader on a paper account, with one
+// long position restored from a store.
+
+use async_trait::async_trait;
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use monitor_api::{server::router, state::AppState};
+use monitor_core::Result;
+use monitor_testkit::fixtures::{position, trading_config};
+use monitor_trader::{
+    executor::AutoTrader,
+    journal::CSV_COLUMNS,
+    paper::PaperExecutionClient,
+    persistence::{ExecutedTrade, PositionStore},
+    risk::SimpleRiskManager,
+    strategy::AnomalyBasedStrategy,
+    Position, PositionSide,
+};
+use serde_json::Value;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+struct OpenPosition(Position);
+
+#[async_trait]
+impl PositionStore for OpenPosition {
+    async fn open(&self, _: &Position) -> Result<()> {
+        Ok(())
+    }
+
+    async fn update(&self, _: &Position) -> Result<()> {
+        Ok(())
+    }
+
+    async fn close(&self, _: &Position) -> Result<()> {
+        Ok(())
+    }
+
+    async fn record_trade(&self, _: &Position, _: &ExecutedTrade) -> Result<()> {
+        Ok(())
+    }
+
+    async fn load_open(&self) -> Result<Vec<Position>> {
+        Ok(vec![self.0.clone()])
+    }
+}
+
+fn long() -> Position {
+    Position {
+        stop_loss: Some(48_500.0),
+        take_profit: Some(53_000.0),
+        ..position("BTC/USDT", PositionSide::Long, 0.01, 50_000.0)
+    }
+}
+
+async fn trader(position: Position) -> Arc<AutoTrader> {
+    let config = trading_config(serde_json::json!({}));
+    let paper = Arc::new(PaperExecutionClient::new(Default::default(), "USDT", 10_000.0));
+    let trader = AutoTrader::new(
+        config.clone(),
+        Box::new(AnomalyBasedStrategy::new(config.clone())),
+        Box::new(SimpleRiskManager::new(config)),
+        paper.clone(),
+        10_000.0,
+    )
+    .with_paper_execution(paper)
+    .with_position_store(Arc::new(OpenPosition(position)));
+    assert_eq!(trader.restore_from_db().await.unwrap(), 1);
+    // Gives the paper account a price to close at
+    trader.update_positions("BTC/USDT", "binance", 51_000.0).await.unwrap();
+    Arc::new(trader)
+}
+
+const API_KEY: &str = "desk-key";
+
+fn state() -> AppState {
+    let state =
+        AppState::new(sqlx::PgPool::connect_lazy("postgres://localhost/crypto_monitor").unwrap());
+    state.set_trading_api_keys(vec![API_KEY.to_string()]);
+    state
+}
+
+async fn call(app: &Router, method: &str, uri: &str, body: Option<Value>) -> (StatusCode, Value) {
+    call_as(app, Some(API_KEY), method, uri, body).await
+}
+
+async fn call_as(
+    app: &Router,
+    api_key: Option<&str>,
+    method: &str,
+    uri: &str,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let mut request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json");
+    if let Some(key) = api_key {
+        request = request.header("x-api-key", key);
+    }
+    let request = request
+        .body(body.map_or_else(Body::empty, |body| Body::from(body.to_string())))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    // Extractor rejections answer in plain text
+    (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn test_trading_routes_need_a_trader() {
+    let app = router(state());
+    for uri in [
+        "/api/v1/trading/positions",
+        "/api/v1/trading/stats",
+        "/api/v1/trading/config",
+        "/api/v1/trading/journal",
+    ] {
+        let (status, body) = call(&app, "GET", uri, None).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["error"], "Auto trading is disabled");
+    }
+}
+
+#[tokio::test]
+async fn test_positions_stats_and_manual_close() {
+    let opened = long();
+    let state = state();
+    let trader = trader(opened.clone()).await;
+    state.set_trader(trader.clone());
+    let app = router(state);
+
+    let (status, body) = call(&app, "GET", "/api/v1/trading/positions", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let positions = body["data"].as_array().unwrap();
+    assert_eq!(positions.len(), 1);
+    assert_eq!(positions[0]["id"], opened.id.to_string());
+    assert_eq!(positions[0]["current_price"], 51_000.0);
+
+    let (status, body) = call(&app, "GET", "/api/v1/trading/orders", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["data"].as_array().unwrap().is_empty());
+
+    // The slash in the symbol is encoded to stay inside the path segment
+    let close = "/api/v1/trading/positions/binance:BTC%2FUSDT/close";
+    let (status, body) = call(&app, "POST", close, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["id"], opened.id.to_string());
+    assert!(!body["data"]["closed_at"].is_null());
+    assert!(body["data"]["realized_pnl"].as_f64().unwrap() > 0.0);
+    assert!(trader.get_positions().is_empty());
+
+    let (status, _) = call(&app, "POST", close, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = call(&app, "POST", "/api/v1/trading/positions/btc/close", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, body) = call(&app, "GET", "/api/v1/trading/stats", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["total_trades"], 1);
+    assert_eq!(body["data"]["winning_trades"], 1);
+}
+
+#[tokio::test]
+async fn test_config_updates_reach_the_trader() {
+    let state = state();
+    let trader = trader(long()).await;
+    state.set_trader(trader.clone());
+    let app = router(state);
+
+    let (status, body) = call(&app, "GET", "/api/v1/trading/config", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["max_position_size"], 1000.0);
+
+    let mut update = body["data"].clone();
+    update["max_position_size"] = 250.0.into();
+    update["auto_trading_enabled"] = false.into();
+    let (status, body) = call(&app, "POST", "/api/v1/trading/config", Some(update)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["max_position_size"], 250.0);
+    assert_eq!(trader.get_config().max_position_size, 250.0);
+    assert!(!trader.get_config().auto_trading_enabled);
+
+    // Unknown fields are refused like in the config file
+    let (status, _) = call(
+        &app,
+        "POST",
+        "/api/v1/trading/config",
+        Some(serde_json::json!({ "enabled": true })),
+    )
+    .await;
+    assert!(status.is_client_error());
+    assert_eq!(trader.get_config().max_position_size, 250.0);
+}
+
+#[tokio::test]
+async fn test_trading_changes_need_an_api_key() {
+    let state = state();
+    let trader = trader(long()).await;
+    state.set_trader(trader.clone());
+    let app = router(state.clone());
+    let config = serde_json::to_value(trader.get_config()).unwrap();
+    let changes = [
+        ("POST", "/api/v1/trading/config", Some(config)),
+        ("POST", "/api/v1/trading/positions/binance:BTC%2FUSDT/close", None),
+        ("POST", "/api/v1/trading/halt", Some(serde_json::json!({}))),
+        ("POST", "/api/v1/trading/resume", None),
+    ];
+
+    for (method, uri, body) in &changes {
+        let (status, response) = call_as(&app, None, method, uri, body.clone()).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "{}", uri);
+        assert_eq!(response["error"], "Missing x-api-key header");
+        let (status, _) = call_as(&app, Some("guess"), method, uri, body.clone()).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "{}", uri);
+    }
+    // Nothing went through
+    assert_eq!(trader.get_positions().len(), 1);
+    assert!(trader.get_breaker_status().halt.is_none());
+
+    // Without configured keys every change is refused, reads still work
+    state.set_trading_api_keys(Vec::new());
+    for (method, uri, body) in &changes {
+        let (status, _) = call(&app, method, uri, body.clone()).await;
+        assert_eq!(status, StatusCode::FORBIDDEN, "{}", uri);
+    }
+    assert_eq!(call(&app, "GET", "/api/v1/trading/positions", None).await.0, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_config_updates_are_validated() {
+    let state = state();
+    let trader = trader(long()).await;
+    state.set_trader(trader.clone());
+    let app = router(state);
+    let config = serde_json::to_value(trader.get_config()).unwrap();
+
+    for (field, value) in [
+        ("max_position_size", 0.0),
+        ("max_position_size", -100.0),
+        ("risk_percentage", 150.0),
+        ("stop_loss_percentage", -1.0),
+        ("take_profit_percentage", 101.0),
+    ] {
+        let mut update = config.clone();
+        update[field] = value.into();
+        let (status, body) = call(&app, "POST", "/api/v1/trading/config", Some(update)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{} = {}", field, value);
+        assert!(body["error"].as_str().unwrap().starts_with(field));
+    }
+    assert_eq!(trader.get_config().max_position_size, 1000.0);
+    assert_eq!(trader.get_config().risk_percentage, 2.0);
+}
+
+#[tokio::test]
+async fn test_manual_orders_are_not_implemented() {
+    let app = router(state());
+    let order = serde_json::json!({ "symbol": "BTC/USDT", "side": "buy", "quantity": 0.01 });
+    let (status, _) = call(&app, "POST", "/api/v1/trading/orders", Some(order)).await;
+    assert_eq!(status, StatusCode::NOT_IMPLEMENTED);
+    let (status, _) = call(&app, "DELETE", "/api/v1/trading/orders/abc", None).await;
+    assert_eq!(status, StatusCode::NOT_IMPLEMENTED);
+}
+
+#[tokio::test]
+async fn test_journal_exports_as_csv_and_json() {
+    let state = state();
+    let trader = trader(long()).await;
+    state.set_trader(trader.clone());
+    let app = router(state);
+    let close = "/api/v1/trading/positions/binance:BTC%2FUSDT/close";
+    assert_eq!(call(&app, "POST", close, None).await.0, StatusCode::OK);
+
+    let request = Request::get("/api/v1/trading/journal?format=csv").body(Body::empty()).unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "text/csv");
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let csv = String::from_utf8(bytes.to_vec()).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], CSV_COLUMNS.join(","));
+    // The close order, its fill and the closed position
+    assert_eq!(lines.len(), 4);
+    assert!(lines[3].contains(",lifecycle,position_closed,binance,BTC/USDT,long,"));
+
+    let (status, body) = call(&app, "GET", "/api/v1/trading/journal", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let records = body.as_array().unwrap();
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[1]["type"], "lifecycle");
+    assert_eq!(records[1]["kind"], "order_filled");
+
+    let later = "/api/v1/trading/journal?from=2100-01-01T00:00:00Z";
+    let (_, body) = call(&app, "GET", later, None).await;
+    assert!(body.as_array().unwrap().is_empty());
+    let (status, _) = call(&app, "GET", "/api/v1/trading/journal?format=xml", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
@@ -1,28 +1,50 @@
 use anyhow::Result;
-use clap::Parser;
-use fluvio::{Fluvio, FluvioConfig, Offset};
-use futures::StreamExt;
-use monitor_anomaly::{
-    detector::AnomalyDetectorManager, PriceAnomalyConfig, TimeSeriesData, VolumeAnomalyConfig,
-};
+use barter_execution::UnindexedAccountEvent;
+use clap::{Parser, Subcommand};
+use crypto_monitor::{EventType, Monitor, MonitorConfig, MonitorEvent};
+use monitor_anomaly::snapshot::{self, DetectorSnapshotWriter};
 use monitor_api::{server::ApiServer, state::AppState};
+use monitor_config::ConfigManager;
 use monitor_core::{
-    engine::MonitorEngine, EventType, MarketDataType, MonitorConfig, MonitorEvent,
+    book_metrics::BookMetricsWriter,
+    coordination::LeaderElection,
+    engine::MonitorEngine,
+    percentiles::SymbolPercentileJob,
+    ratelimit::RestRateLimiters,
+    scheduler::{PeriodicTask, Schedule, Scheduler},
+    storage::repository::{
+        BookMetricsRepository, DetectorStateRepository, PositionRepository, TradeJournalRepository,
+        TradeRepository, TraderStateRepository,
+    },
+    ExecutionVenueKind,
 };
 use monitor_notifier::{
-    manager::NotificationManager, telegram::TelegramNotifier, email::EmailNotifier,
+    ack::AlertUpkeepJob, manager::NotificationManager, routing::RoutingConfig, telegram::TelegramNotifier, email::EmailNotifier,
     Notification, NotificationConfig,
 };
 use monitor_trader::{
+    client::{self, BarterOrderClient, MockVenue},
+    ensemble::StrategyEnsemble,
     executor::AutoTrader,
-    risk::SimpleRiskManager,
-    strategy::AnomalyBasedStrategy,
+    paper::PaperExecutionClient,
+    journal::PgJournalStore,
+    persistence::PgPositionStore,
+    risk,
+    stale::RestPriceSource,
+    strategy::{self, AnomalyBasedStrategy, ConfigurableStrategy},
+    TradingStrategy,
 };
 use std::{path::PathBuf, sync::Arc};
-use tokio::{signal, sync::mpsc};
+use tokio::{
+    signal,
+    sync::{mpsc, watch},
+};
 use tracing::{error, info, warn};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+// How long the consumers get to finish the events they are on once asked to stop
+const CONSUMER_STOP_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(15);
+
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
@@ -42,9 +64,38 @@ struct Args {
     #[arg(long)]
     no_trading: bool,
     
+    /// Trade against a simulated account instead of the exchanges
+    #[arg(long)]
+    dry_run: bool,
+    
     /// Disable notifications
     #[arg(long)]
     no_notifications: bool,
+    
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Replay a captured NDJSON file (or directory of captures) through the pipeline in dry-run
+    ReplayFile {
+        path: PathBuf,
+    },
+    /// Inspect the resolved configuration
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigCommand {
+    /// Print every config path with the CRYPTO_MONITOR_* variable that overrides it
+    EnvVars {
+        /// Only explain a single path, e.g. exchanges[0].symbols
+        key: Option<String>,
+    },
 }
 
 #[tokio::main]
@@ -57,41 +108,274 @@ async fn main() -> Result<()> {
     info!("Starting Crypto Monitor Application");
     
     // Load configuration
-    let config = load_config(&args.config).await?;
+    let config_manager = load_config(&args.config)?;
+    
+    if let Some(Command::Config { action: ConfigCommand::EnvVars { key } }) = &args.command {
+        let mappings = match key {
+            Some(key) => vec![config_manager.explain_env(key)?],
+            None => config_manager.env_vars(),
+        };
+        for mapping in mappings {
+            println!(
+                "{:<60} {:<80} {:<8}{}",
+                mapping.path,
+                mapping.env_var,
+                mapping.kind,
+                if mapping.overridden { " (set)" } else { "" }
+            );
+        }
+        return Ok(());
+    }
     
-    // Initialize database
-    let db_pool = init_database(&config).await?;
+    let config = config_manager.get_config().clone();
     
-    // Initialize Fluvio
-    let fluvio = init_fluvio(&config).await?;
+    if let Some(Command::ReplayFile { path }) = &args.command {
+        let summary = crypto_monitor::replay::replay_file(config, path).await?;
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+        return Ok(());
+    }
     
-    // Create shared application state
-    let app_state = AppState::new(db_pool.clone(), fluvio.clone());
+    // Initialize database
+    let db_pool = init_database(&config).await?;
     
-    // Initialize monitor engine
-    let mut monitor_engine = MonitorEngine::new(config.clone()).await?;
+    // Initialize monitor engine, which connects to Fluvio or starts degraded when
+    // messaging.required is false
+    // Every REST caller hitting an exchange draws from the same limiter
+    let rest_limiters = RestRateLimiters::new();
+    let mut monitor_engine = MonitorEngine::new(config.clone())
+        .await?
+        .with_rest_limiters(rest_limiters.clone());
     monitor_engine.start().await?;
+    let messaging = monitor_engine.messaging().clone();
     
-    // Initialize anomaly detector
-    let anomaly_manager = Arc::new(AnomalyDetectorManager::new(
-        VolumeAnomalyConfig::default(),
-        PriceAnomalyConfig::default(),
-    ));
+    // Create shared application state
+    let app_state = AppState::new(db_pool.clone());
+    app_state.set_messaging(messaging.clone());
+    app_state.set_trading_api_keys(config.api.trading_api_keys.clone());
+    
+    // With coordination only the elected leader notifies and trades, the monitor renews the
+    // lease once started
+    let leadership = if config.coordination.enabled {
+        let election = Arc::new(LeaderElection::new(config.coordination.clone(), db_pool.clone()));
+        match election.heartbeat().await {
+            Ok(status) => info!("Instance {} started as {:?}", election.instance_id(), status),
+            Err(e) => warn!("Initial leader election failed: {}", e),
+        }
+        app_state.set_leadership(election.clone());
+        Some(election)
+    } else {
+        None
+    };
+    let is_leader = {
+        let leadership = leadership.clone();
+        move || leadership.as_ref().is_none_or(|l| l.is_leader())
+    };
     
     // Initialize notification manager if enabled
     let notification_manager = if !args.no_notifications {
-        Some(Arc::new(init_notifications(&config.notification).await?))
+        let manager = Arc::new(init_notifications(&config.notification, &db_pool).await?);
+        // Rules saved through the API override the config file
+        if let Some(routing) = RoutingConfig::load(&db_pool).await? {
+            manager.set_routing(routing).await?;
+        }
+        // Recent alerts and their acks survive restarts for history, digests and bulk acks
+        match manager.alert_log().load(&db_pool).await {
+            Ok(count) => info!("Restored {} recent alerts", count),
+            Err(e) => warn!("Failed to restore recent alerts: {}", e),
+        }
+        app_state.set_notifier(manager.clone());
+        Some(manager)
     } else {
         None
     };
     
     // Initialize auto trader if enabled
-    let auto_trader = if !args.no_trading {
-        Some(Arc::new(init_auto_trader(&config).await?))
+    let (trading_alert_tx, mut trading_alert_rx) = mpsc::unbounded_channel::<MonitorEvent>();
+    let (auto_trader, account_events) = if !args.no_trading {
+        let (trader, account_events) =
+            init_auto_trader(&config, &db_pool, &rest_limiters, args.dry_run).await?;
+        (Some(Arc::new(trader.with_alert_sender(trading_alert_tx))), account_events)
     } else {
-        None
+        (None, None)
     };
     
+    // Forward trading alerts to notification channels
+    let alert_notifier = notification_manager.clone();
+    let alert_leader = is_leader.clone();
+    tokio::spawn(async move {
+        while let Some(event) = trading_alert_rx.recv().await {
+            if let (Some(notifier), Some(notification), true) =
+                (&alert_notifier, Notification::from_event(&event), alert_leader())
+            {
+                if let Err(e) = notifier.send_all(&notification).await {
+                    error!("Failed to send trading alert: {}", e);
+                }
+            }
+        }
+    });
+    
+    // Report bus outages and recovery
+    let mut messaging_status = messaging.subscribe_status();
+    let status_notifier = notification_manager.clone();
+    let status_state = app_state.clone();
+    tokio::spawn(async move {
+        let mut was_degraded = false;
+        loop {
+            let status = messaging_status.borrow_and_update().clone();
+            // Retry attempts only update the status, notify on transitions
+            if status.is_degraded() != was_degraded {
+                was_degraded = status.is_degraded();
+                if let Some(event) = status.to_event() {
+                    if let (Some(notifier), Some(notification), true) =
+                        (&status_notifier, Notification::from_event(&event), is_leader())
+                    {
+                        if let Err(e) = notifier.send_all(&notification).await {
+                            error!("Failed to send messaging status notification: {}", e);
+                        }
+                    }
+                    monitor_api::websocket::broadcast_alert(
+                        &status_state,
+                        serde_json::to_value(&event).unwrap_or_default(),
+                    );
+                }
+            }
+            if messaging_status.changed().await.is_err() {
+                break;
+            }
+        }
+    });
+    
+    // Build the monitor pipeline, detecting on the in-process stream if the bus is down
+    let mut builder = Monitor::builder()
+        .config(config.clone())
+        .with_storage(db_pool.clone());
+    builder = match monitor_engine.fluvio() {
+        Some(fluvio) => builder.with_bus(fluvio),
+        None => builder.with_messaging(messaging.clone()),
+    };
+    if let Some(notifier) = notification_manager.clone() {
+        builder = builder.with_notifier(notifier);
+    }
+    if let Some(trader) = auto_trader.clone() {
+        builder = builder.with_trader(trader);
+    }
+    if let Some(election) = leadership.clone() {
+        builder = builder.with_leadership(election);
+    }
+    let monitor = builder.build()?;
+    app_state.set_warmup(monitor.warmup().clone());
+    app_state.set_suppression(monitor.suppressor().clone());
+    app_state.set_anomaly_manager(monitor.anomaly_manager().clone());
+    app_state.set_book_metrics(monitor.book_metrics().clone());
+    if monitor.ingest_gate().is_enabled() {
+        app_state.set_external_ingest(Arc::new(monitor.clone()));
+    }
+    if let Some(trader) = monitor.trader() {
+        app_state.set_trader(trader.clone());
+        app_state.set_cooldowns(trader.cooldowns());
+        app_state.set_circuit_breaker(trader.circuit_breaker());
+        app_state.set_dca_plans(trader.dca_plans());
+        app_state.caches.register(trader.market_snapshots());
+    }
+    
+    // Resume detection from the last saved detector windows instead of a fresh warm-up
+    let detector_state = &config.monitoring.detector_state;
+    let lookback = chrono::Duration::minutes(
+        config.monitoring.anomaly_detection.lookback_window_minutes as i64,
+    );
+    let snapshot_writer = detector_state.enabled.then(|| {
+        let writer = DetectorSnapshotWriter::new(
+            DetectorStateRepository::new(db_pool.clone()),
+            monitor.anomaly_manager().clone(),
+        );
+        Arc::new(match leadership.clone() {
+            Some(election) => writer.with_leadership(election),
+            None => writer,
+        })
+    });
+    if detector_state.enabled {
+        match snapshot::restore_saved(
+            &DetectorStateRepository::new(db_pool.clone()),
+            monitor.anomaly_manager(),
+            lookback,
+            chrono::Utc::now(),
+        )
+        .await
+        {
+            Ok(pairs) => info!("Restored detector windows for {} pairs", pairs),
+            Err(e) => warn!("Failed to restore detector windows: {}", e),
+        }
+    }
+    
+    // Nightly trailing percentiles for market stats, computed now if no previous run exists
+    if let Err(e) = app_state.percentiles.load(&db_pool).await {
+        warn!("Failed to load symbol percentiles: {}", e);
+    }
+    let (scheduler_tx, scheduler_rx) = watch::channel(false);
+    let mut scheduler = Scheduler::new();
+    let percentile_job = Arc::new(SymbolPercentileJob::new(db_pool.clone(), app_state.percentiles.clone()));
+    let nightly = Schedule::DailyAt(chrono::NaiveTime::from_hms_opt(0, 15, 0).unwrap());
+    if app_state.percentiles.is_empty() {
+        scheduler.add_and_run(percentile_job, nightly);
+    } else {
+        scheduler.add(percentile_job, nightly);
+    }
+    let book_metrics = &config.monitoring.book_metrics;
+    if book_metrics.enabled {
+        scheduler.add(
+            Arc::new(BookMetricsWriter::new(
+                BookMetricsRepository::new(db_pool.clone()),
+                monitor.book_metrics().clone(),
+            )),
+            Schedule::Every(chrono::Duration::seconds(book_metrics.interval_secs.max(1) as i64)),
+        );
+    }
+    if let Some(writer) = snapshot_writer.clone() {
+        scheduler.add(
+            writer,
+            Schedule::Every(chrono::Duration::seconds(detector_state.save_interval_secs.max(1) as i64)),
+        );
+    }
+    if let Some(notifier) = notification_manager.clone() {
+        let acks = notifier.alert_log().config().clone();
+        if acks.escalate_after_secs > 0 || acks.digest_interval_secs > 0 {
+            scheduler.add(Arc::new(AlertUpkeepJob::new(notifier)), Schedule::Every(chrono::Duration::seconds(30)));
+        }
+    }
+    // Fills and balances of the venue account, applied as they arrive
+    let account_task = auto_trader
+        .as_ref()
+        .zip(account_events)
+        .map(|(trader, events)| trader.follow_account(events, scheduler_rx.clone()));
+    let scheduled_tasks = scheduler.start(scheduler_rx);
+    
+    // Broadcast detections and system events to WebSocket clients
+    let mut detections = monitor.subscribe_detections();
+    let detection_state = app_state.clone();
+    tokio::spawn(async move {
+        while let Ok(anomaly) = detections.recv().await {
+            monitor_api::websocket::broadcast_anomaly_event(&detection_state, &anomaly);
+        }
+    });
+    
+    let mut events = monitor.subscribe_events();
+    let event_state = app_state.clone();
+    let engine_tx = monitor_engine.get_event_sender();
+    tokio::spawn(async move {
+        while let Ok(event) = events.recv().await {
+            if let EventType::MarketData(_) = event.event_type {
+                monitor_api::websocket::broadcast_market_event(&event_state, &event);
+            }
+            if let EventType::System(_) | EventType::Alert(_) = event.event_type {
+                monitor_api::websocket::broadcast_alert(
+                    &event_state,
+                    serde_json::to_value(&event).unwrap_or_default(),
+                );
+                let _ = engine_tx.send(event);
+            }
+        }
+    });
+    
     // Start API server if enabled
     if !args.no_api {
         let api_state = app_state.clone();
@@ -104,17 +388,9 @@ async fn main() -> Result<()> {
     }
     
     // Start event processing
-    let (shutdown_tx, mut shutdown_rx) = mpsc::channel(1);
-    
-    // Spawn Fluvio consumer task
-    let consumer_handle = tokio::spawn(process_events(
-        fluvio.clone(),
-        config.clone(),
-        anomaly_manager.clone(),
-        notification_manager.clone(),
-        auto_trader.clone(),
-        app_state.clone(),
-    ));
+    let (shutdown_tx, mut shutdown_rx) = mpsc::channel::<()>(1);
+    let monitor_handle = monitor.start().await?;
+    app_state.set_topic_router(monitor_handle.router().clone());
     
     // Set up graceful shutdown
     let ctrl_c = async {
@@ -149,8 +425,51 @@ async fn main() -> Result<()> {
     // Graceful shutdown
     info!("Initiating graceful shutdown...");
     
+    // Consumers finish the event in hand before returning, so none is left half processed.
+    // Those still busy are aborted, nothing may open a position while the trader shuts down
+    if !monitor_handle.stop_within(CONSUMER_STOP_TIMEOUT).await {
+        warn!("Consumers still busy after {:?} were aborted", CONSUMER_STOP_TIMEOUT);
+    }
+    let _ = scheduler_tx.send(true);
+    for task in scheduled_tasks.into_iter().chain(account_task) {
+        let _ = task.await;
+    }
+    // Detector windows as of shutdown, so the next start loses nothing since the last save
+    if let Some(writer) = &snapshot_writer {
+        if let Err(e) = writer.run().await {
+            warn!("Failed to save detector windows: {}", e);
+        }
+    }
+    // The trading tasks have stopped, orders and positions are left as the policy says. The
+    // journal of this run follows, for review after it
+    if let Some(trader) = &auto_trader {
+        let policy = trader.get_config().shutdown.policy;
+        let report = trader.shutdown(policy).await;
+        info!(
+            "Trading shutdown under {:?}: {} orders cancelled, {} still working, {} positions",
+            policy,
+            report.cancelled_orders.len(),
+            report.working_orders.len(),
+            report.positions.len()
+        );
+        for position in &report.positions {
+            info!(
+                "Position {} on {}:{}: {:?}",
+                position.position_id, position.exchange, position.symbol, position.outcome
+            );
+        }
+        if !report.is_complete() {
+            warn!("Trading shutdown incomplete, timed out: {}", report.timed_out);
+        }
+        match trader.flush_journal().await {
+            Ok(count) => info!("Flushed {} journal records to the database", count),
+            Err(e) => warn!("Failed to flush the trade journal: {}", e),
+        }
+        if let Err(e) = trader.write_journal().await {
+            warn!("Failed to export the trade journal: {}", e);
+        }
+    }
     monitor_engine.stop().await?;
-    consumer_handle.abort();
     
     info!("Crypto Monitor Application stopped");
     
@@ -173,10 +492,19 @@ fn init_logging(debug: bool) {
         .init();
 }
 
-async fn load_config(path: &PathBuf) -> Result<MonitorConfig> {
-    let config_str = tokio::fs::read_to_string(path).await?;
-    let config: MonitorConfig = serde_yaml::from_str(&config_str)?;
-    Ok(config)
+// CRYPTO_MONITOR_* variables override the file; without a file they are the whole config
+fn load_config(path: &PathBuf) -> Result<ConfigManager> {
+    let manager = if path.exists() {
+        ConfigManager::from_file(path)?
+    } else {
+        warn!("{} not found, loading configuration from environment only", path.display());
+        ConfigManager::from_env()?
+    };
+    
+    for overridden in manager.env_overrides() {
+        info!("Config {} set from environment", overridden);
+    }
+    Ok(manager)
 }
 
 async fn init_database(config: &MonitorConfig) -> Result<sqlx::PgPool> {
@@ -193,15 +521,12 @@ async fn init_database(config: &MonitorConfig) -> Result<sqlx::PgPool> {
     Ok(pool)
 }
 
-async fn init_fluvio(config: &MonitorConfig) -> Result<Arc<Fluvio>> {
-    let fluvio_config = FluvioConfig::new(&config.fluvio.endpoint);
-    let fluvio = Fluvio::connect_with_config(&fluvio_config).await?;
-    info!("Connected to Fluvio at {}", config.fluvio.endpoint);
-    Ok(Arc::new(fluvio))
-}
-
-async fn init_notifications(config: &NotificationConfig) -> Result<NotificationManager> {
-    let mut manager = NotificationManager::new();
+async fn init_notifications(config: &NotificationConfig, db_pool: &sqlx::PgPool) -> Result<NotificationManager> {
+    let mut manager = NotificationManager::new()
+        .with_routing(config.routing.clone())
+        .with_slo(config.slo.clone())
+        .with_acks(config.acks.clone())
+        .with_storage(db_pool.clone());
     
     if config.telegram.enabled {
         manager.add_channel(Box::new(TelegramNotifier::new(config.telegram.clone())));
@@ -215,138 +540,123 @@ async fn init_notifications(config: &NotificationConfig) -> Result<NotificationM
     Ok(manager)
 }
 
-async fn init_auto_trader(config: &MonitorConfig) -> Result<AutoTrader> {
+async fn init_auto_trader(
+    config: &MonitorConfig,
+    db_pool: &sqlx::PgPool,
+    rest_limiters: &RestRateLimiters,
+    dry_run: bool,
+) -> Result<(AutoTrader, Option<AccountEvents>)> {
     // This is a simplified initialization - in production you'd configure properly
-    let strategy = Box::new(AnomalyBasedStrategy::new(config.monitoring.trading.clone()));
-    let risk_manager = Box::new(SimpleRiskManager::new(config.monitoring.trading.clone()));
-    
-    // Create execution client based on config
-    // This would need proper initialization with exchange credentials
-    let execution_client = create_execution_client(config).await?;
-    
-    let trader = AutoTrader::new(
-        config.monitoring.trading.clone(),
-        strategy,
-        risk_manager,
-        execution_client,
-        10000.0, // Initial portfolio value
-    );
+    let trading = &config.monitoring.trading;
+    let strategy: Box<dyn TradingStrategy> = if !trading.ensemble.strategies.is_empty() {
+        Box::new(StrategyEnsemble::from_config(trading)?)
+    } else if let Some(name) = &trading.strategy {
+        strategy::build(name, trading)?
+    } else if trading.rules.is_empty() {
+        Box::new(AnomalyBasedStrategy::new(trading.clone()))
+    } else {
+        Box::new(ConfigurableStrategy::new(trading.clone())?)
+    };
+    let risk_manager = risk::build(trading.risk_manager, trading);
+    
+    let mut account_events = None;
+    let mut trader = match create_venue_client(config, dry_run)? {
+        // Positions are reconciled against what the venue's trades add up to
+        Some(venue) => {
+            // Subscribed before any order goes out so no fill is missed
+            account_events = Some(venue.account_stream().await?);
+            AutoTrader::new(
+                trading.clone(),
+                strategy,
+                risk_manager,
+                venue.clone(),
+                trading.portfolio.initial_value,
+            )
+            .with_account_source(venue)
+        }
+        None => {
+            let paper = create_paper_client(config);
+            AutoTrader::new(
+                trading.clone(),
+                strategy,
+                risk_manager,
+                paper.clone(),
+                trading.portfolio.initial_value,
+            )
+            .with_paper_execution(paper)
+        }
+    }
+    // Reprices positions whose stream stalled under the `rest` stale position fallback
+    .with_price_source(Arc::new(RestPriceSource::new(rest_limiters.clone())));
+    
+    if trading.persistence.enabled {
+        trader = trader.with_position_store(Arc::new(PgPositionStore::new(
+            PositionRepository::new(db_pool.clone()),
+            TradeRepository::new(db_pool.clone()),
+            TraderStateRepository::new(db_pool.clone()),
+        )));
+        trader = trader.with_journal_store(Arc::new(PgJournalStore::new(
+            TradeJournalRepository::new(db_pool.clone()),
+        )));
+        // Positions left open by the previous run are managed again before new signals arrive,
+        // once checked against the exchange account
+        match trader.restore_from_db().await {
+            Ok(count) => info!("Restored {} open positions", count),
+            Err(e) => error!("Failed to restore open positions: {}", e),
+        }
+    } else {
+        // Orders the previous run left unresolved are settled before new signals arrive
+        if let Err(e) = trader.reconcile_intents().await {
+            error!("Failed to reconcile in-flight orders: {}", e);
+        }
+        
+        // Then the positions themselves are checked against the exchange account, a no-op on
+        // the paper account
+        if let Err(e) = trader.reconcile_positions().await {
+            error!("Failed to reconcile positions with the exchange: {}", e);
+        }
+    }
     
     info!("Auto trader initialized");
-    Ok(trader)
+    Ok((trader, account_events))
 }
 
-async fn create_execution_client(
-    config: &MonitorConfig,
-) -> Result<Arc<dyn barter_execution::ExecutionClient>> {
-    // This is a placeholder - you'd create actual execution clients here
-    // based on the exchange configuration
-    unimplemented!("Execution client creation not implemented")
-}
+type AccountEvents = futures::stream::BoxStream<'static, UnindexedAccountEvent>;
 
-async fn process_events(
-    fluvio: Arc<Fluvio>,
-    config: MonitorConfig,
-    anomaly_manager: Arc<AnomalyDetectorManager>,
-    notification_manager: Option<Arc<NotificationManager>>,
-    auto_trader: Option<Arc<AutoTrader>>,
-    app_state: AppState,
-) {
-    let topic = format!("{}.market.trades", config.fluvio.topic_prefix);
-    
-    let consumer = fluvio
-        .partition_consumer(&topic, 0)
-        .await
-        .expect("Failed to create consumer");
-    
-    let mut stream = consumer
-        .stream(Offset::end())
-        .await
-        .expect("Failed to create stream");
-    
-    info!("Started processing events from topic: {}", topic);
-    
-    while let Some(Ok(record)) = stream.next().await {
-        let value = record.get_value().to_vec();
-        
-        match serde_json::from_slice::<MonitorEvent>(&value) {
-            Ok(event) => {
-                process_single_event(
-                    event,
-                    &anomaly_manager,
-                    notification_manager.as_ref(),
-                    auto_trader.as_ref(),
-                    &app_state,
-                )
-                .await;
-            }
-            Err(e) => {
-                error!("Failed to deserialize event: {}", e);
-            }
-        }
+// The client of `trading.venue`, `None` for the paper account. Dry runs always trade on paper
+fn create_venue_client(
+    config: &MonitorConfig,
+    dry_run: bool,
+) -> Result<Option<Arc<BarterOrderClient<MockVenue>>>> {
+    let trading = &config.monitoring.trading;
+    if dry_run || trading.venue.kind == ExecutionVenueKind::Paper {
+        return Ok(None);
     }
+    
+    let Some(exchange) = &trading.venue.exchange else {
+        anyhow::bail!("trading.venue.exchange is required for a {:?} venue", trading.venue.kind);
+    };
+    let symbols: Vec<String> = config
+        .exchanges
+        .iter()
+        .filter(|e| e.enabled && e.name.eq_ignore_ascii_case(exchange))
+        .flat_map(|e| e.symbols.iter().cloned())
+        .collect();
+    warn!("Orders for {} go to barter's mock exchange, market orders only", exchange);
+    let venue = client::mock_exchange(
+        &symbols,
+        &trading.portfolio.quote_asset,
+        trading.portfolio.initial_value,
+        trading.paper.fee_bps,
+    );
+    Ok(Some(Arc::new(BarterOrderClient::new(venue, exchange).with_symbols(&symbols))))
 }
 
-async fn process_single_event(
-    event: MonitorEvent,
-    anomaly_manager: &Arc<AnomalyDetectorManager>,
-    notification_manager: Option<&Arc<NotificationManager>>,
-    auto_trader: Option<&Arc<AutoTrader>>,
-    app_state: &AppState,
-) {
-    // Process market data for anomaly detection
-    if let EventType::MarketData(MarketDataType::Trade) = &event.event_type {
-        if let Ok(trade_data) = serde_json::from_value::<MarketTradeData>(event.data.clone()) {
-            let ts_data = TimeSeriesData {
-                timestamp: event.timestamp,
-                value: trade_data.price,
-            };
-            
-            let anomalies = anomaly_manager.process_data(
-                &trade_data.symbol,
-                &trade_data.exchange,
-                &ts_data,
-            );
-            
-            for anomaly in anomalies {
-                info!("Anomaly detected: {:?}", anomaly);
-                
-                // Send notification
-                if let Some(notifier) = notification_manager {
-                    let notification = Notification::from_anomaly(&anomaly);
-                    if let Err(e) = notifier.send_all(&notification).await {
-                        error!("Failed to send notification: {}", e);
-                    }
-                }
-                
-                // Process for auto trading
-                if let Some(trader) = auto_trader {
-                    if let Err(e) = trader.process_anomaly(&anomaly).await {
-                        error!("Failed to process anomaly for trading: {}", e);
-                    }
-                }
-                
-                // Broadcast to WebSocket clients
-                monitor_api::websocket::broadcast_anomaly_event(app_state, &anomaly);
-            }
-            
-            // Update positions with current price
-            if let Some(trader) = auto_trader {
-                if let Err(e) = trader
-                    .update_positions(&trade_data.symbol, &trade_data.exchange, trade_data.price)
-                    .await
-                {
-                    error!("Failed to update positions: {}", e);
-                }
-            }
-        }
-    }
+fn create_paper_client(config: &MonitorConfig) -> Arc<PaperExecutionClient> {
+    let trading = &config.monitoring.trading;
+    Arc::new(PaperExecutionClient::new(
+        trading.paper.clone(),
+        &trading.portfolio.quote_asset,
+        trading.portfolio.initial_value,
+    ))
 }
-
-#[derive(serde::Deserialize)]
-struct MarketTradeData {
-    symbol: String,
-    exchange: String,
-    price: f64,
-    volume: f64,
-}
\ No newline at end of file
@@ -0,0 +1,381 @@
+use monitor_core::{MonitorError, Result};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::cmp::Ordering;
+
+pub const ENV_PREFIX: &str = "CRYPTO_MONITOR";
+pub const ENV_SEPARATOR: &str = "__";
+pub const LIST_SEPARATOR: char = ',';
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EnvVarMapping {
+    pub path: String,
+    pub env_var: String,
+    pub kind: &'static str,
+    pub overridden: bool,
+}
+
+// "exchanges[0].symbols" and "exchanges.0.symbols" both become ["exchanges", "0", "symbols"]
+pub fn parse_path(path: &str) -> Result<Vec<String>> {
+    let mut segments = Vec::new();
+
+    for part in path.split('.') {
+        let (name, mut indexes) = match part.find('[') {
+            Some(pos) => (&part[..pos], &part[pos..]),
+            None => (part, ""),
+        };
+        if !name.is_empty() {
+            segments.push(name.to_lowercase());
+        }
+
+        while let Some(rest) = indexes.strip_prefix('[') {
+            let end = rest.find(']').ok_or_else(|| invalid_path(path))?;
+            let index = &rest[..end];
+            if index.parse::<usize>().is_err() {
+                return Err(invalid_path(path));
+            }
+            segments.push(index.to_string());
+            indexes = &rest[end + 1..];
+        }
+
+        if !indexes.is_empty() || part.is_empty() {
+            return Err(invalid_path(path));
+        }
+    }
+
+    if segments.is_empty() {
+        return Err(invalid_path(path));
+    }
+    Ok(segments)
+}
+
+pub fn env_var_name(path: &str) -> Result<String> {
+    parse_path(path).map(|segments| env_var_for(&segments))
+}
+
+fn env_var_for(segments: &[String]) -> String {
+    format!("{}_{}", ENV_PREFIX, segments.join(ENV_SEPARATOR).to_uppercase())
+}
+
+fn invalid_path(path: &str) -> MonitorError {
+    MonitorError::Configuration(format!("Invalid config path '{}'", path))
+}
+
+fn env_segments(var: &str) -> Option<Vec<String>> {
+    let rest = var.strip_prefix(ENV_PREFIX)?.strip_prefix('_')?;
+    let segments: Vec<String> = rest.split(ENV_SEPARATOR).map(|s| s.to_lowercase()).collect();
+    segments.iter().all(|s| !s.is_empty()).then_some(segments)
+}
+
+fn display_path(segments: &[String]) -> String {
+    let mut path = String::new();
+    for segment in segments {
+        if segment.parse::<usize>().is_ok() {
+            path.push_str(&format!("[{}]", segment));
+        } else {
+            if !path.is_empty() {
+                path.push('.');
+            }
+            path.push_str(segment);
+        }
+    }
+    path
+}
+
+// Applies every CRYPTO_MONITOR_* variable on top of `root` and returns the overridden paths.
+// Values are coerced to the type already present at that path, so a base file doubles as the
+// schema; paths absent from the base fall back to YAML scalar inference.
+pub fn apply_overrides<I>(root: &mut Value, vars: I) -> Result<Vec<String>>
+where
+    I: IntoIterator<Item = (String, String)>,
+{
+    let mut vars: Vec<(Vec<String>, String, String)> = vars
+        .into_iter()
+        .filter_map(|(var, raw)| env_segments(&var).map(|segments| (segments, var, raw)))
+        .collect();
+    vars.sort_by(|a, b| precedence(&a.0, &b.0).then_with(|| a.1.cmp(&b.1)));
+
+    let mut applied = Vec::new();
+    for (segments, var, raw) in vars {
+        set_path(root, &segments, &raw)
+            .map_err(|e| MonitorError::Configuration(format!("{}: {}", var, e)))?;
+        applied.push(display_path(&segments));
+    }
+
+    Ok(applied)
+}
+
+// A path applies before the paths below it, so the more specific variable wins, and list
+// indexes apply in numeric order, so "[2]" exists before "[10]" is appended
+fn precedence(a: &[String], b: &[String]) -> Ordering {
+    for (a, b) in a.iter().zip(b) {
+        let order = match (a.parse::<usize>(), b.parse::<usize>()) {
+            (Ok(a), Ok(b)) => a.cmp(&b),
+            _ => a.cmp(b),
+        };
+        if order != Ordering::Equal {
+            return order;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+fn set_path(root: &mut Value, segments: &[String], raw: &str) -> std::result::Result<(), String> {
+    let mut node = root;
+    for segment in segments {
+        node = child_mut(node, segment)?;
+    }
+    *node = coerce(raw, node)?;
+    Ok(())
+}
+
+fn child_mut<'a>(node: &'a mut Value, segment: &str) -> std::result::Result<&'a mut Value, String> {
+    let index = segment.parse::<usize>().ok();
+
+    if node.is_null() {
+        *node = match index {
+            Some(_) => Value::Array(Vec::new()),
+            None => Value::Object(Map::new()),
+        };
+    }
+
+    match (node, index) {
+        (Value::Array(items), Some(index)) => {
+            if index == items.len() {
+                items.push(Value::Null);
+            }
+            let len = items.len();
+            items
+                .get_mut(index)
+                .ok_or_else(|| format!("index {} out of range for list of {}", index, len))
+        }
+        (Value::Object(map), _) => {
+            // Env names are upper-cased, so match existing keys case-insensitively
+            let key = map
+                .keys()
+                .find(|k| k.eq_ignore_ascii_case(segment))
+                .cloned()
+                .unwrap_or_else(|| segment.to_string());
+            Ok(map.entry(key).or_insert(Value::Null))
+        }
+        (_, _) => Err(format!("cannot descend into '{}'", segment)),
+    }
+}
+
+fn coerce(raw: &str, existing: &Value) -> std::result::Result<Value, String> {
+    let trimmed = raw.trim();
+
+    match existing {
+        Value::Bool(_) => match trimmed.to_lowercase().as_str() {
+            "true" | "1" | "yes" | "on" => Ok(Value::Bool(true)),
+            "false" | "0" | "no" | "off" => Ok(Value::Bool(false)),
+            _ => Err(format!("expected a boolean, got '{}'", raw)),
+        },
+        Value::Number(n) => {
+            // Integers in the base may back float fields, so fall through to f64
+            let number = if n.is_f64() {
+                None
+            } else {
+                trimmed
+                    .parse::<i64>()
+                    .map(Value::from)
+                    .or_else(|_| trimmed.parse::<u64>().map(Value::from))
+                    .ok()
+            };
+            number
+                .or_else(|| trimmed.parse::<f64>().ok().and_then(|f| {
+                    serde_json::Number::from_f64(f).map(Value::Number)
+                }))
+                .ok_or_else(|| format!("expected a number, got '{}'", raw))
+        }
+        Value::String(_) => Ok(Value::String(raw.to_string())),
+        Value::Array(items) => {
+            if trimmed.starts_with('[') {
+                return serde_yaml::from_str(trimmed).map_err(|e| e.to_string());
+            }
+            let element = items.first().cloned().unwrap_or(Value::Null);
+            trimmed
+                .split(LIST_SEPARATOR)
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| coerce(item, &element))
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map(Value::Array)
+        }
+        _ => Ok(infer(raw)),
+    }
+}
+
+fn infer(raw: &str) -> Value {
+    match serde_yaml::from_str::<Value>(raw.trim()) {
+        Ok(value @ (Value::Bool(_) | Value::Number(_) | Value::Array(_))) => value,
+        _ => Value::String(raw.to_string()),
+    }
+}
+
+// Every leaf of `config` with the variable that sets it
+pub fn mappings(config: &Value, overridden: &[String]) -> Vec<EnvVarMapping> {
+    let mut leaves = Vec::new();
+    collect_leaves(config, &mut Vec::new(), &mut leaves);
+
+    leaves
+        .into_iter()
+        // Map keys such as topic names can't be spelled as an env var name
+        .filter(|(segments, _)| {
+            segments
+                .iter()
+                .all(|s| s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'))
+        })
+        .map(|(segments, kind)| {
+            let path = display_path(&segments);
+            EnvVarMapping {
+                env_var: env_var_for(&segments),
+                overridden: overridden.contains(&path),
+                path,
+                kind,
+            }
+        })
+        .collect()
+}
+
+pub fn kind_of(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "optional",
+        Value::Bool(_) => "bool",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "list",
+        Value::Object(_) => "map",
+    }
+}
+
+fn collect_leaves(value: &Value, prefix: &mut Vec<String>, out: &mut Vec<(Vec<String>, &'static str)>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, child) in map {
+                prefix.push(key.clone());
+                collect_leaves(child, prefix, out);
+                prefix.pop();
+            }
+        }
+        Value::Array(items) if items.iter().any(|i| i.is_object() || i.is_array()) => {
+            for (index, child) in items.iter().enumerate() {
+                prefix.push(index.to_string());
+                collect_leaves(child, prefix, out);
+                prefix.pop();
+            }
+        }
+        _ => out.push((prefix.clone(), kind_of(value))),
+    }
+}
+
+pub fn lookup<'a>(root: &'a Value, segments: &[String]) -> Option<&'a Value> {
+    segments.iter().try_fold(root, |node, segment| match node {
+        Value::Array(items) => items.get(segment.parse::<usize>().ok()?),
+        Value::Object(map) => map
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(segment))
+            .map(|(_, v)| v),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_env_var_names() {
+        assert_eq!(
+            env_var_name("monitoring.anomaly_detection.volume_threshold_multiplier").unwrap(),
+            "CRYPTO_MONITOR_MONITORING__ANOMALY_DETECTION__VOLUME_THRESHOLD_MULTIPLIER"
+        );
+        assert_eq!(
+            env_var_name("exchanges[0].symbols").unwrap(),
+            "CRYPTO_MONITOR_EXCHANGES__0__SYMBOLS"
+        );
+        assert!(env_var_name("exchanges[x]").is_err());
+    }
+
+    #[test]
+    fn test_coerces_to_base_types() {
+        let mut root = json!({
+            "database": { "max_connections": 10, "url": "postgres://a" },
+            "telegram": { "enabled": false, "chat_id": "123" },
+            "threshold": 3.0,
+        });
+
+        let applied = apply_overrides(
+            &mut root,
+            vars(&[
+                ("CRYPTO_MONITOR_DATABASE__MAX_CONNECTIONS", "25"),
+                ("CRYPTO_MONITOR_TELEGRAM__ENABLED", "true"),
+                ("CRYPTO_MONITOR_TELEGRAM__CHAT_ID", "456"),
+                ("CRYPTO_MONITOR_THRESHOLD", "4"),
+                ("UNRELATED", "1"),
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(applied.len(), 4);
+        assert_eq!(root["database"]["max_connections"], json!(25));
+        assert_eq!(root["telegram"]["enabled"], json!(true));
+        // Stays a string because the base says so
+        assert_eq!(root["telegram"]["chat_id"], json!("456"));
+        assert_eq!(root["threshold"], json!(4.0));
+    }
+
+    #[test]
+    fn test_rejects_mistyped_values() {
+        let mut root = json!({ "enabled": true });
+        let err = apply_overrides(&mut root, vars(&[("CRYPTO_MONITOR_ENABLED", "maybe")]));
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn test_applies_in_precedence_order() {
+        let mut root = json!({ "exchanges": [{ "name": "binance", "symbols": ["BTC/USDT"] }] });
+        // Indexes past 9 sort before 2 as text
+        let mut overrides: Vec<(String, String)> = (1..=10)
+            .rev()
+            .map(|i| (format!("CRYPTO_MONITOR_EXCHANGES__{}__NAME", i), format!("venue{}", i)))
+            .collect();
+        // The element override lands on top of the whole list
+        overrides.push(("CRYPTO_MONITOR_EXCHANGES__0__SYMBOLS__1".into(), "SOL/USDT".into()));
+        overrides.push(("CRYPTO_MONITOR_EXCHANGES__0__SYMBOLS".into(), "BTC/USDT,ETH/USDT".into()));
+
+        apply_overrides(&mut root, overrides).unwrap();
+
+        assert_eq!(root["exchanges"].as_array().unwrap().len(), 11);
+        assert_eq!(root["exchanges"][10]["name"], json!("venue10"));
+        assert_eq!(root["exchanges"][0]["symbols"], json!(["BTC/USDT", "SOL/USDT"]));
+    }
+
+    #[test]
+    fn test_list_index_out_of_range() {
+        let mut root = json!({ "exchanges": [{ "name": "binance" }] });
+        let err = apply_overrides(&mut root, vars(&[("CRYPTO_MONITOR_EXCHANGES__3__NAME", "okx")]));
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn test_mappings_list_leaves() {
+        let root = json!({
+            "exchanges": [{ "name": "binance", "symbols": ["BTC/USDT"] }],
+            "file_sink": null,
+        });
+
+        let mappings = mappings(&root, &["exchanges[0].symbols".to_string()]);
+        let paths: Vec<_> = mappings.iter().map(|m| m.path.as_str()).collect();
+        assert_eq!(paths, ["exchanges[0].name", "exchanges[0].symbols", "file_sink"]);
+        assert_eq!(mappings[1].env_var, "CRYPTO_MONITOR_EXCHANGES__0__SYMBOLS");
+        assert_eq!(mappings[1].kind, "list");
+        assert!(mappings[1].overridden);
+        assert_eq!(mappings[2].kind, "optional");
+    }
+}
@@ -1,4 +1,7 @@
-use config::{Config, ConfigError, Environment, File};
+pub mod env;
+
+use config::{Config, File};
+use env::EnvVarMapping;
 use monitor_core::{MonitorConfig, MonitorError, Result};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
@@ -7,6 +10,8 @@ use tracing::info;
 pub struct ConfigManager {
     config: Config,
     monitor_config: MonitorConfig,
+    resolved: serde_json::Value,
+    env_overrides: Vec<String>,
 }
 
 impl ConfigManager {
@@ -17,37 +22,47 @@ impl ConfigManager {
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
         let config = Config::builder()
             .add_source(File::from(path.as_ref()))
-            .add_source(Environment::with_prefix("CRYPTO_MONITOR"))
             .build()
             .map_err(|e| MonitorError::Configuration(e.to_string()))?;
         
-        let monitor_config: MonitorConfig = config
-            .try_deserialize()
-            .map_err(|e| MonitorError::Configuration(e.to_string()))?;
-        
-        info!("Configuration loaded successfully");
-        
-        Ok(Self {
-            config,
-            monitor_config,
-        })
+        let manager = Self::load(config, std::env::vars())?;
+        info!(
+            "Configuration loaded successfully ({} env overrides)",
+            manager.env_overrides.len()
+        );
+        Ok(manager)
     }
     
     pub fn from_env() -> Result<Self> {
-        let config = Config::builder()
-            .add_source(Environment::with_prefix("CRYPTO_MONITOR"))
-            .build()
-            .map_err(|e| MonitorError::Configuration(e.to_string()))?;
-        
-        let monitor_config: MonitorConfig = config
+        let manager = Self::load(Config::default(), std::env::vars())?;
+        info!("Configuration loaded from environment");
+        Ok(manager)
+    }
+    
+    // config-rs's Environment source can't index into lists, so CRYPTO_MONITOR_* variables are
+    // layered over the file here instead (see env::apply_overrides)
+    fn load<I>(config: Config, vars: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        let mut resolved: serde_json::Value = config
+            .clone()
             .try_deserialize()
             .map_err(|e| MonitorError::Configuration(e.to_string()))?;
+        if resolved.is_null() {
+            resolved = serde_json::Value::Object(Default::default());
+        }
         
-        info!("Configuration loaded from environment");
+        let env_overrides = env::apply_overrides(&mut resolved, vars)?;
+        
+        let monitor_config: MonitorConfig = serde_json::from_value(resolved.clone())
+            .map_err(|e| MonitorError::Configuration(e.to_string()))?;
         
         Ok(Self {
             config,
             monitor_config,
+            resolved,
+            env_overrides,
         })
     }
     
@@ -60,14 +75,39 @@ impl ConfigManager {
     }
     
     pub fn reload(&mut self) -> Result<()> {
-        self.monitor_config = self.config
-            .try_deserialize()
-            .map_err(|e| MonitorError::Configuration(e.to_string()))?;
+        *self = Self::load(self.config.clone(), std::env::vars())?;
         
         info!("Configuration reloaded");
         Ok(())
     }
     
+    // Paths set from CRYPTO_MONITOR_* variables, e.g. "exchanges[0].symbols"
+    pub fn env_overrides(&self) -> &[String] {
+        &self.env_overrides
+    }
+    
+    pub fn env_vars(&self) -> Vec<EnvVarMapping> {
+        env::mappings(&self.resolved, &self.env_overrides)
+    }
+    
+    pub fn explain_env(&self, key: &str) -> Result<EnvVarMapping> {
+        let segments = env::parse_path(key)?;
+        let known = self.env_vars()
+            .into_iter()
+            .find(|m| env::parse_path(&m.path).ok().as_deref() == Some(&segments[..]));
+        
+        Ok(match known {
+            Some(mapping) => mapping,
+            // Not in the loaded config (e.g. inside an unset optional section) but still settable
+            None => EnvVarMapping {
+                path: key.to_string(),
+                env_var: env::env_var_name(key)?,
+                kind: env::lookup(&self.resolved, &segments).map(env::kind_of).unwrap_or("unset"),
+                overridden: false,
+            },
+        })
+    }
+    
     pub fn validate(&self) -> Result<()> {
         // Validate exchanges
         if self.monitor_config.exchanges.is_empty() {
@@ -90,6 +130,73 @@ impl ConfigManager {
             ));
         }
         
+        // Validate optional detectors
+        let detection = &self.monitor_config.monitoring.anomaly_detection;
+        if let Some(update_rate) = &detection.update_rate {
+            if update_rate.min_baseline_secs > update_rate.baseline_secs
+                || update_rate.spike_multiple <= 1.0
+            {
+                return Err(MonitorError::Configuration(
+                    "update_rate needs min_baseline_secs within baseline_secs and a spike_multiple \
+                     above 1"
+                        .to_string(),
+                ));
+            }
+        }
+        if let Some(ewma) = &detection.ewma {
+            if ewma.half_life_samples <= 0.0 || ewma.k_sigma <= 0.0 {
+                return Err(MonitorError::Configuration(
+                    "ewma needs a positive half_life_samples and k_sigma".to_string(),
+                ));
+            }
+        }
+        if let Some(volatility) = &detection.volatility {
+            if volatility.window_size < 2
+                || volatility.min_baseline > volatility.baseline_size
+                || volatility.change_multiple <= 1.0
+            {
+                return Err(MonitorError::Configuration(
+                    "volatility needs a window_size of at least 2, min_baseline within \
+                     baseline_size and a change_multiple above 1"
+                        .to_string(),
+                ));
+            }
+        }
+        if let Some(rate_of_change) = &detection.rate_of_change {
+            if rate_of_change.horizons_secs.is_empty()
+                || rate_of_change.horizons_secs.contains(&0)
+                || rate_of_change.percentage_threshold <= 0.0
+                || rate_of_change.max_samples < 2
+            {
+                return Err(MonitorError::Configuration(
+                    "rate_of_change needs non-zero horizons, a positive percentage_threshold and \
+                     max_samples of at least 2"
+                        .to_string(),
+                ));
+            }
+        }
+        if let Some(candles) = &detection.candles {
+            if candles.intervals_secs.is_empty()
+                || candles.intervals_secs.contains(&0)
+                || candles.volume.volume_multiple <= 1.0
+                || candles.range.range_multiple <= 1.0
+            {
+                return Err(MonitorError::Configuration(
+                    "candles needs non-zero intervals, and volume and range multiples above 1"
+                        .to_string(),
+                ));
+            }
+        }
+        if let Some(bollinger) = &detection.bollinger {
+            if bollinger.period < 2 || bollinger.multiplier <= 0.0 || bollinger.consecutive == 0 {
+                return Err(MonitorError::Configuration(
+                    "bollinger needs a period of at least 2, a positive multiplier and a \
+                     consecutive of at least 1"
+                        .to_string(),
+                ));
+            }
+        }
+        
         info!("Configuration validation passed");
         Ok(())
     }
@@ -130,6 +237,8 @@ impl Default for RuntimeConfig {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use config::FileFormat;
+    use monitor_core::WindowEstimator;
     
     #[test]
     fn test_default_runtime_config() {
@@ -138,4 +247,316 @@ mod tests {
         assert!(!config.dry_run);
         assert!(!config.backtest_mode);
     }
+    
+    fn example() -> Config {
+        Config::builder()
+            .add_source(File::from_str(
+                include_str!("../../config.example.yaml"),
+                FileFormat::Yaml,
+            ))
+            .build()
+            .unwrap()
+    }
+    
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+    
+    #[test]
+    fn test_nested_and_list_env_overrides() {
+        let manager = ConfigManager::load(
+            example(),
+            vars(&[
+                ("CRYPTO_MONITOR_MONITORING__ANOMALY_DETECTION__VOLUME_THRESHOLD_MULTIPLIER", "4.5"),
+                ("CRYPTO_MONITOR_EXCHANGES__0__SYMBOLS", "BTC/USDT, DOGE/USDT"),
+                ("CRYPTO_MONITOR_EXCHANGES__1__ENABLED", "false"),
+                ("CRYPTO_MONITOR_DATABASE__MAX_CONNECTIONS", "32"),
+            ]),
+        )
+        .unwrap();
+        let config = manager.get_config();
+        
+        assert_eq!(config.monitoring.anomaly_detection.volume_threshold_multiplier, 4.5);
+        assert_eq!(config.exchanges[0].symbols, vec!["BTC/USDT", "DOGE/USDT"]);
+        assert!(!config.exchanges[1].enabled);
+        assert_eq!(config.exchanges[1].symbols.len(), 3);
+        assert_eq!(config.database.max_connections, 32);
+        assert_eq!(manager.env_overrides().len(), 4);
+    }
+    
+    #[test]
+    fn test_env_appends_list_entries() {
+        let mut base = serde_json::to_value(
+            ConfigManager::load(example(), Vec::new()).unwrap().get_config(),
+        )
+        .unwrap();
+        base["exchanges"] = serde_json::json!([]);
+        let config = Config::builder()
+            .add_source(File::from_str(&base.to_string(), FileFormat::Json))
+            .build()
+            .unwrap();
+        
+        let manager = ConfigManager::load(
+            config,
+            vars(&[
+                ("CRYPTO_MONITOR_EXCHANGES__0__NAME", "kraken"),
+                ("CRYPTO_MONITOR_EXCHANGES__0__ENABLED", "true"),
+                ("CRYPTO_MONITOR_EXCHANGES__0__SYMBOLS", "[XBT/USD]"),
+                ("CRYPTO_MONITOR_EXCHANGES__0__SUBSCRIPTIONS", "[trades, orderbook]"),
+            ]),
+        )
+        .unwrap();
+        
+        let exchange = &manager.get_config().exchanges[0];
+        assert_eq!(exchange.name, "kraken");
+        assert!(exchange.enabled);
+        assert_eq!(exchange.symbols, vec!["XBT/USD"]);
+        assert_eq!(exchange.subscriptions, vec!["trades", "orderbook"]);
+    }
+    
+    #[test]
+    fn test_unknown_keys_are_rejected_outside_extensions() {
+        let err = ConfigManager::load(
+            example(),
+            vars(&[("CRYPTO_MONITOR_MONITORING__TRADING__STOP_LOS_PERCENTAGE", "2.0")]),
+        )
+        .err()
+        .unwrap();
+        assert!(err.to_string().contains("unknown field `stop_los_percentage`"));
+        
+        let err = ConfigManager::load(
+            example(),
+            vars(&[("CRYPTO_MONITOR_NOTIFICATION__TELEGRAM__CHAT_ID", "42")]),
+        )
+        .err()
+        .unwrap();
+        assert!(err.to_string().contains("unknown field `chat_id`"));
+        
+        let manager = ConfigManager::load(
+            example(),
+            vars(&[("CRYPTO_MONITOR_EXTENSIONS__DESK", "asia")]),
+        )
+        .unwrap();
+        assert_eq!(manager.get_config().extensions["desk"], "asia");
+        assert!(manager.get_config().notification.telegram.enabled);
+    }
+    
+    #[test]
+    fn test_severity_bands_override_and_validate() {
+        let prefix = "CRYPTO_MONITOR_MONITORING__ANOMALY_DETECTION";
+        let manager = ConfigManager::load(
+            example(),
+            vars(&[(format!("{}__VOLUME_Z_SCORE_SEVERITY__HIGH", prefix).as_str(), "4.5")]),
+        )
+        .unwrap();
+        let detection = &manager.get_config().monitoring.anomaly_detection;
+        let bands = detection.volume_z_score_severity.clone().unwrap();
+        assert_eq!((bands.medium(), bands.high(), bands.critical()), (3.0, 4.5, 5.0));
+        
+        // High at or below medium
+        for high in ["3.0", "2.0"] {
+            let result = ConfigManager::load(
+                example(),
+                vars(&[(format!("{}__PRICE_PERCENTAGE_SEVERITY__HIGH", prefix).as_str(), high)]),
+            );
+            assert!(result.is_err(), "{}", high);
+        }
+    }
+    
+    #[test]
+    fn test_update_rate_section_is_read_and_validated() {
+        let prefix = "CRYPTO_MONITOR_MONITORING__ANOMALY_DETECTION__UPDATE_RATE";
+        let section = |min_baseline_secs: &str| {
+            ConfigManager::load(
+                example(),
+                vars(&[
+                    (format!("{}__BASELINE_SECS", prefix).as_str(), "120"),
+                    (format!("{}__MIN_BASELINE_SECS", prefix).as_str(), min_baseline_secs),
+                    (format!("{}__SPIKE_MULTIPLE", prefix).as_str(), "4.0"),
+                    (format!("{}__MIN_UPDATES_PER_SEC", prefix).as_str(), "10.0"),
+                ]),
+            )
+            .unwrap()
+        };
+        
+        // Off unless set
+        let manager = ConfigManager::load(example(), Vec::new()).unwrap();
+        assert!(manager.get_config().monitoring.anomaly_detection.update_rate.is_none());
+        
+        let manager = section("60");
+        let update_rate = manager.get_config().monitoring.anomaly_detection.update_rate.clone();
+        assert_eq!(update_rate.unwrap().spike_multiple, 4.0);
+        assert!(manager.validate().is_ok());
+        assert!(section("600").validate().is_err());
+    }
+    
+    #[test]
+    fn test_window_estimators_are_read() {
+        let prefix = "CRYPTO_MONITOR_MONITORING__ANOMALY_DETECTION";
+        let manager = ConfigManager::load(example(), Vec::new()).unwrap();
+        assert!(manager.get_config().monitoring.anomaly_detection.volume_estimator.is_none());
+        
+        let manager = ConfigManager::load(
+            example(),
+            vars(&[(format!("{}__PRICE_ESTIMATOR", prefix).as_str(), "median_mad")]),
+        )
+        .unwrap();
+        let detection = &manager.get_config().monitoring.anomaly_detection;
+        assert_eq!(detection.price_estimator, Some(WindowEstimator::MedianMad));
+        assert!(detection.volume_estimator.is_none());
+        
+        let result = ConfigManager::load(
+            example(),
+            vars(&[(format!("{}__VOLUME_ESTIMATOR", prefix).as_str(), "median")]),
+        );
+        assert!(result.is_err());
+    }
+    
+    #[test]
+    fn test_ewma_section_is_read_and_validated() {
+        let prefix = "CRYPTO_MONITOR_MONITORING__ANOMALY_DETECTION__EWMA";
+        let section = |half_life_samples: &str| {
+            ConfigManager::load(
+                example(),
+                vars(&[
+                    (format!("{}__HALF_LIFE_SAMPLES", prefix).as_str(), half_life_samples),
+                    (format!("{}__K_SIGMA", prefix).as_str(), "4.0"),
+                    (format!("{}__MIN_SAMPLES", prefix).as_str(), "50"),
+                ]),
+            )
+            .unwrap()
+        };
+        
+        let manager = section("40.0");
+        let ewma = manager.get_config().monitoring.anomaly_detection.ewma.clone().unwrap();
+        assert_eq!((ewma.half_life_samples, ewma.k_sigma, ewma.min_samples), (40.0, 4.0, 50));
+        assert_eq!(ewma.severity.critical(), 5.0);
+        assert!(manager.validate().is_ok());
+        assert!(section("0.0").validate().is_err());
+    }
+    
+    #[test]
+    fn test_volatility_section_is_read_and_validated() {
+        let prefix = "CRYPTO_MONITOR_MONITORING__ANOMALY_DETECTION__VOLATILITY";
+        let section = |change_multiple: &str| {
+            ConfigManager::load(
+                example(),
+                vars(&[
+                    (format!("{}__WINDOW_SIZE", prefix).as_str(), "20"),
+                    (format!("{}__BASELINE_SIZE", prefix).as_str(), "200"),
+                    (format!("{}__MIN_BASELINE", prefix).as_str(), "40"),
+                    (format!("{}__CHANGE_MULTIPLE", prefix).as_str(), change_multiple),
+                ]),
+            )
+            .unwrap()
+        };
+        
+        let manager = ConfigManager::load(example(), Vec::new()).unwrap();
+        assert!(manager.get_config().monitoring.anomaly_detection.volatility.is_none());
+        
+        let manager = section("2.5");
+        let volatility = manager.get_config().monitoring.anomaly_detection.volatility.clone();
+        assert_eq!(volatility.unwrap().change_multiple, 2.5);
+        assert!(manager.validate().is_ok());
+        assert!(section("1.0").validate().is_err());
+    }
+    
+    #[test]
+    fn test_rate_of_change_section_is_read_and_validated() {
+        let prefix = "CRYPTO_MONITOR_MONITORING__ANOMALY_DETECTION__RATE_OF_CHANGE";
+        let section = |horizons: &str| {
+            ConfigManager::load(
+                example(),
+                vars(&[
+                    (format!("{}__HORIZONS_SECS", prefix).as_str(), horizons),
+                    (format!("{}__PERCENTAGE_THRESHOLD", prefix).as_str(), "2.5"),
+                    (format!("{}__REFERENCE_TOLERANCE_PCT", prefix).as_str(), "10.0"),
+                ]),
+            )
+            .unwrap()
+        };
+        
+        let manager = ConfigManager::load(example(), Vec::new()).unwrap();
+        assert!(manager.get_config().monitoring.anomaly_detection.rate_of_change.is_none());
+        
+        let manager = section("[60, 300]");
+        let config = manager.get_config().monitoring.anomaly_detection.rate_of_change.clone();
+        let config = config.unwrap();
+        assert_eq!(config.horizons_secs, vec![60, 300]);
+        assert_eq!(config.percentage_threshold, 2.5);
+        assert_eq!(config.max_samples, 1_000);
+        assert!(manager.validate().is_ok());
+        assert!(section("[60, 0]").validate().is_err());
+    }
+    
+    #[test]
+    fn test_candles_section_is_read_and_validated() {
+        let prefix = "CRYPTO_MONITOR_MONITORING__ANOMALY_DETECTION__CANDLES";
+        let section = |intervals: &str| {
+            ConfigManager::load(
+                example(),
+                vars(&[
+                    (format!("{}__INTERVALS_SECS", prefix).as_str(), intervals),
+                    (format!("{}__LATE_TOLERANCE_SECS", prefix).as_str(), "5"),
+                ]),
+            )
+            .unwrap()
+        };
+        
+        let manager = ConfigManager::load(example(), Vec::new()).unwrap();
+        assert!(manager.get_config().monitoring.anomaly_detection.candles.is_none());
+        
+        // The volume and range checks keep their defaults unless set
+        let manager = section("[60, 300]");
+        let config = manager.get_config().monitoring.anomaly_detection.candles.clone();
+        let config = config.unwrap();
+        assert_eq!(config.intervals_secs, vec![60, 300]);
+        assert_eq!(config.late_tolerance_secs, 5);
+        assert_eq!(config.volume.volume_multiple, 5.0);
+        assert_eq!(config.range.range_multiple, 3.0);
+        assert!(manager.validate().is_ok());
+        assert!(section("[]").validate().is_err());
+    }
+    
+    #[test]
+    fn test_bollinger_section_is_read_and_validated() {
+        let prefix = "CRYPTO_MONITOR_MONITORING__ANOMALY_DETECTION__BOLLINGER";
+        let section = |multiplier: &str| {
+            ConfigManager::load(
+                example(),
+                vars(&[
+                    (format!("{}__PERIOD", prefix).as_str(), "30"),
+                    (format!("{}__MULTIPLIER", prefix).as_str(), multiplier),
+                    (format!("{}__CONSECUTIVE", prefix).as_str(), "2"),
+                    (format!("{}__WIDTH_HISTORY", prefix).as_str(), "200"),
+                ]),
+            )
+            .unwrap()
+        };
+        
+        let manager = ConfigManager::load(example(), Vec::new()).unwrap();
+        assert!(manager.get_config().monitoring.anomaly_detection.bollinger.is_none());
+        
+        let manager = section("2.5");
+        let config = manager.get_config().monitoring.anomaly_detection.bollinger.clone();
+        let config = config.unwrap();
+        assert_eq!(config.period, 30);
+        assert_eq!(config.multiplier, 2.5);
+        assert!(manager.validate().is_ok());
+        assert!(section("0.0").validate().is_err());
+    }
+    
+    #[test]
+    fn test_explain_env() {
+        let manager = ConfigManager::load(example(), Vec::new()).unwrap();
+        
+        let mapping = manager.explain_env("exchanges[0].symbols").unwrap();
+        assert_eq!(mapping.env_var, "CRYPTO_MONITOR_EXCHANGES__0__SYMBOLS");
+        assert_eq!(mapping.kind, "list");
+        assert!(!mapping.overridden);
+        
+        let mapping = manager.explain_env("monitoring.anomaly_detection.volume_threshold_multiplier").unwrap();
+        assert_eq!(mapping.kind, "number");
+        assert!(manager.explain_env("exchanges[").is_err());
+    }
 }
\ No newline at end of file
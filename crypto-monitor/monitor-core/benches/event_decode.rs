@@ -0,0 +1,75 @@
+// Owned vs borrowed decoding of trade records as the bus consumer sees them.
+// Run with `cargo bench -p monitor-core --bench event_decode`.
+use monitor_core::{
+    event::{EventBuilder, MonitorEventRef},
+    EventSource, EventType, MarketDataType, MonitorEvent,
+};
+use std::{hint::black_box, time::Instant};
+
+const EVENTS: usize = 100_000;
+const ROUNDS: usize = 5;
+
+fn records() -> Vec<Vec<u8>> {
+    let symbols = ["BTC/USDT", "ETH/USDT", "SOL/USDT", "XRP/USDT"];
+    let exchanges = ["binance", "okx"];
+    (0..EVENTS)
+        .map(|i| {
+            let (symbol, exchange) = (symbols[i % symbols.len()], exchanges[i % exchanges.len()]);
+            let event = EventBuilder::new()
+                .with_source(EventSource::Exchange(exchange.to_string()))
+                .with_type(EventType::MarketData(MarketDataType::Trade))
+                .with_data(serde_json::json!({
+                    "exchange": exchange,
+                    "symbol": symbol,
+                    "price": 50_000.0 + i as f64 * 0.01,
+                    "volume": 0.001 * (i % 100) as f64,
+                    "side": if i % 2 == 0 { "buy" } else { "sell" },
+                    "trade_id": i.to_string(),
+                }))
+                .build()
+                .unwrap();
+            serde_json::to_vec(&event).unwrap()
+        })
+        .collect()
+}
+
+// Best of several rounds, in events per second
+fn measure(records: &[Vec<u8>], decode: impl Fn(&[u8]) -> f64) -> f64 {
+    (0..ROUNDS)
+        .map(|_| {
+            let started = Instant::now();
+            let mut total = 0.0;
+            for record in records {
+                total += decode(record);
+            }
+            black_box(total);
+            records.len() as f64 / started.elapsed().as_secs_f64()
+        })
+        .fold(0.0, f64::max)
+}
+
+fn main() {
+    let records = records();
+
+    // What the consumer did before: a full MonitorEvent, then the trade fields out of `data`
+    let owned = measure(&records, |bytes| {
+        let event: MonitorEvent = serde_json::from_slice(bytes).unwrap();
+        let trade = &event.data;
+        black_box(trade["symbol"].as_str().unwrap().len());
+        black_box(trade["exchange"].as_str().unwrap().len());
+        trade["price"].as_f64().unwrap() * trade["volume"].as_f64().unwrap()
+    });
+
+    let borrowed = measure(&records, |bytes| {
+        let event = MonitorEventRef::from_slice(bytes).unwrap();
+        let trade = event.trade().unwrap();
+        black_box(trade.symbol.len());
+        black_box(trade.exchange.len());
+        trade.price * trade.volume
+    });
+
+    println!("{} trade records, best of {} rounds", EVENTS, ROUNDS);
+    println!("owned MonitorEvent:       {:>12.0} events/s", owned);
+    println!("borrowed MonitorEventRef: {:>12.0} events/s", borrowed);
+    println!("speedup: {:.2}x", borrowed / owned);
+}
@@ -0,0 +1,96 @@
+// Per-trade state lookups keyed by format!("{}:{}") strings vs interned InstrumentKeys, the
+// way the detector manager and trader find a pair's state on every trade.
+// Run with `cargo bench -p monitor-core --bench instrument_key`.
+use monitor_core::instrument::InstrumentKey;
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    collections::HashMap,
+    hint::black_box,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+const EVENTS: usize = 1_000_000;
+const ROUNDS: usize = 5;
+
+// Counts every allocation the benchmark makes
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn trades() -> Vec<(String, String, f64)> {
+    let symbols = ["BTC/USDT", "ETH/USDT", "SOL/USDT", "XRP/USDT"];
+    let exchanges = ["binance", "okx", "bybit"];
+    (0..EVENTS)
+        .map(|i| {
+            let (exchange, symbol) = (exchanges[i % exchanges.len()], symbols[i % symbols.len()]);
+            (exchange.to_string(), symbol.to_string(), 50_000.0 + i as f64 * 0.01)
+        })
+        .collect()
+}
+
+// Best of several rounds, in events per second, and allocations per event in the last round
+fn measure(trades: &[(String, String, f64)], mut apply: impl FnMut(&str, &str, f64)) -> (f64, f64) {
+    let mut best = 0.0_f64;
+    let mut allocations = 0;
+    for _ in 0..ROUNDS {
+        let before = ALLOCATIONS.load(Ordering::Relaxed);
+        let started = Instant::now();
+        for (exchange, symbol, price) in trades {
+            apply(exchange, symbol, *price);
+        }
+        best = best.max(trades.len() as f64 / started.elapsed().as_secs_f64());
+        allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    }
+    (best, allocations as f64 / trades.len() as f64)
+}
+
+fn main() {
+    let trades = trades();
+
+    // What the hot paths did before: a fresh key per trade, cloned again into `entry`
+    let mut by_string: HashMap<String, f64> = HashMap::new();
+    let (string_rate, string_allocs) = measure(&trades, |exchange, symbol, price| {
+        let key = format!("{}:{}", exchange, symbol);
+        *by_string.entry(key.clone()).or_default() += price;
+        black_box(&key);
+    });
+
+    let mut by_key: HashMap<InstrumentKey, f64> = HashMap::new();
+    let (key_rate, key_allocs) = measure(&trades, |exchange, symbol, price| {
+        let key = InstrumentKey::new(exchange, symbol);
+        match by_key.get_mut(&key) {
+            Some(total) => *total += price,
+            None => {
+                by_key.insert(key.clone(), price);
+            }
+        }
+        black_box(&key);
+    });
+    assert_eq!(by_string.len(), by_key.len());
+
+    println!("{} trades over {} pairs, best of {} rounds", EVENTS, by_key.len(), ROUNDS);
+    println!(
+        "format! String keys: {:>12.0} events/s, {:.2} allocations/event",
+        string_rate, string_allocs
+    );
+    println!(
+        "InstrumentKey:       {:>12.0} events/s, {:.2} allocations/event",
+        key_rate, key_allocs
+    );
+    println!("speedup: {:.2}x", key_rate / string_rate);
+}
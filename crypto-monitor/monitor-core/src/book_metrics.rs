@@ -0,0 +1,272 @@
+use crate::{
+    scheduler::PeriodicTask,
+    storage::repository::{BookMetricsRecord, BookMetricsRepository},
+    MonitorError, Result,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::debug;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BookMetricsConfig {
+    pub enabled: bool,
+    // The latest sample per symbol is written once per interval
+    pub interval_secs: u64,
+}
+
+impl Default for BookMetricsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BookMetrics {
+    pub timestamp: DateTime<Utc>,
+    pub spread_bps: f64,
+    // (bid size - ask size) / (bid size + ask size), positive when the bid is heavier
+    pub imbalance: f64,
+    // Mid weighted towards the thinner side, where the next trade is more likely to print
+    pub microprice: f64,
+}
+
+impl BookMetrics {
+    // `None` for empty or crossed books, which would only pollute the series
+    pub fn from_l1(
+        bid_price: f64,
+        bid_size: f64,
+        ask_price: f64,
+        ask_size: f64,
+        timestamp: DateTime<Utc>,
+    ) -> Option<Self> {
+        let depth = bid_size + ask_size;
+        if bid_price <= 0.0 || ask_price < bid_price || depth <= 0.0 {
+            return None;
+        }
+
+        let mid = (bid_price + ask_price) / 2.0;
+        Some(Self {
+            timestamp,
+            spread_bps: (ask_price - bid_price) / mid * 10_000.0,
+            imbalance: (bid_size - ask_size) / depth,
+            microprice: (bid_price * ask_size + ask_price * bid_size) / depth,
+        })
+    }
+}
+
+// Latest metrics per "exchange:symbol" for live consumers, plus the samples not yet written
+#[derive(Default)]
+pub struct BookMetricsRecorder {
+    latest: DashMap<String, BookMetrics>,
+    pending: DashMap<(String, String), BookMetrics>,
+}
+
+impl BookMetricsRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn observe(&self, exchange: &str, symbol: &str, metrics: BookMetrics) {
+        self.latest.insert(format!("{}:{}", exchange, symbol), metrics);
+        self.pending
+            .insert((exchange.to_string(), symbol.to_string()), metrics);
+    }
+
+    pub fn latest(&self, exchange: &str, symbol: &str) -> Option<BookMetrics> {
+        self.latest
+            .get(&format!("{}:{}", exchange, symbol))
+            .map(|m| *m)
+    }
+
+    // Drains the pending samples, keeping only the newest per symbol since the last call
+    pub fn take_pending(&self) -> Vec<BookMetricsRecord> {
+        let keys: Vec<(String, String)> = self.pending.iter().map(|e| e.key().clone()).collect();
+
+        keys.into_iter()
+            .filter_map(|key| self.pending.remove(&key))
+            .map(|((exchange, symbol), metrics)| BookMetricsRecord {
+                exchange,
+                symbol,
+                timestamp: metrics.timestamp,
+                spread_bps: metrics.spread_bps,
+                imbalance: metrics.imbalance,
+                microprice: metrics.microprice,
+            })
+            .collect()
+    }
+}
+
+// Scheduled every `interval_secs`, which is what throttles the stored series
+pub struct BookMetricsWriter {
+    repository: BookMetricsRepository,
+    recorder: Arc<BookMetricsRecorder>,
+}
+
+impl BookMetricsWriter {
+    pub fn new(repository: BookMetricsRepository, recorder: Arc<BookMetricsRecorder>) -> Self {
+        Self {
+            repository,
+            recorder,
+        }
+    }
+}
+
+#[async_trait]
+impl PeriodicTask for BookMetricsWriter {
+    fn name(&self) -> &str {
+        "book_metrics"
+    }
+
+    async fn run(&self) -> Result<()> {
+        let records = self.recorder.take_pending();
+        let written = self.repository.insert_batch(&records).await?;
+        debug!("Wrote {} book metric samples", written);
+        Ok(())
+    }
+}
+
+// "300", "30s", "5m", "1h" or "1d" to seconds
+pub fn parse_resolution(value: &str) -> Result<i64> {
+    let value = value.trim();
+    let (number, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(pos) => value.split_at(pos),
+        None => (value, "s"),
+    };
+    let multiplier = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86_400,
+        _ => 0,
+    };
+
+    match number.parse::<i64>() {
+        Ok(n) if n > 0 && multiplier > 0 => Ok(n * multiplier),
+        _ => Err(MonitorError::Configuration(format!(
+            "Invalid resolution '{}', expected e.g. 30s, 5m, 1h",
+            value
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::query::BookMetricsFilter;
+    use chrono::{Duration, TimeZone};
+
+    #[test]
+    fn test_metrics_from_l1() {
+        let now = Utc::now();
+        let metrics = BookMetrics::from_l1(99.0, 3.0, 101.0, 1.0, now).unwrap();
+
+        assert!((metrics.spread_bps - 200.0).abs() < 1e-9);
+        assert!((metrics.imbalance - 0.5).abs() < 1e-9);
+        // Heavy bid pulls the microprice towards the ask
+        assert!((metrics.microprice - 100.5).abs() < 1e-9);
+
+        assert!(BookMetrics::from_l1(101.0, 1.0, 99.0, 1.0, now).is_none());
+        assert!(BookMetrics::from_l1(99.0, 0.0, 101.0, 0.0, now).is_none());
+    }
+
+    #[test]
+    fn test_pending_keeps_latest_per_symbol() {
+        let recorder = BookMetricsRecorder::new();
+        let start = Utc::now();
+        for i in 0..5 {
+            let at = start + Duration::milliseconds(i * 100);
+            let metrics = BookMetrics::from_l1(99.0, 1.0, 101.0, 1.0 + i as f64, at).unwrap();
+            recorder.observe("binance", "BTC/USDT", metrics);
+        }
+
+        let pending = recorder.take_pending();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].timestamp, start + Duration::milliseconds(400));
+        assert!(recorder.take_pending().is_empty());
+        assert!(recorder.latest("binance", "BTC/USDT").is_some());
+    }
+
+    #[test]
+    fn test_parse_resolution() {
+        assert_eq!(parse_resolution("300").unwrap(), 300);
+        assert_eq!(parse_resolution("5m").unwrap(), 300);
+        assert_eq!(parse_resolution("1h").unwrap(), 3600);
+        assert!(parse_resolution("0m").is_err());
+        assert!(parse_resolution("5w").is_err());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn test_downsample_buckets_and_extremes() {
+        let pool = monitor_testkit::db::migrated_pool().await;
+
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let sample = |offset_ms: i64, spread_bps: f64, symbol: &str| BookMetricsRecord {
+            exchange: "binance".to_string(),
+            symbol: symbol.to_string(),
+            timestamp: start + Duration::milliseconds(offset_ms),
+            spread_bps,
+            imbalance: -spread_bps / 10.0,
+            microprice: 100.0 + spread_bps,
+        };
+        let repository = BookMetricsRepository::new(pool);
+        repository
+            .insert_batch(&[
+                sample(0, 2.0, "BTC/USDT"),
+                sample(120_000, 6.0, "BTC/USDT"),
+                sample(299_999, 4.0, "BTC/USDT"),
+                // Exactly on the boundary, opens the second bucket
+                sample(300_000, 9.0, "BTC/USDT"),
+                sample(599_000, 1.0, "BTC/USDT"),
+                sample(60_000, 50.0, "ETH/USDT"),
+            ])
+            .await
+            .unwrap();
+
+        let buckets = repository
+            .downsample(
+                &[
+                    BookMetricsFilter::ExchangeEq("binance".to_string()),
+                    BookMetricsFilter::SymbolEq("BTC/USDT".to_string()),
+                ],
+                300,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(buckets.len(), 2);
+        assert_eq!(buckets[0].bucket, start);
+        assert_eq!(buckets[0].samples, 3);
+        assert_eq!((buckets[0].spread_bps_min, buckets[0].spread_bps_max), (2.0, 6.0));
+        assert!((buckets[0].spread_bps_avg - 4.0).abs() < 1e-9);
+        // Imbalance is negated, so its extremes come from the opposite samples
+        assert_eq!((buckets[0].imbalance_min, buckets[0].imbalance_max), (-0.6, -0.2));
+        assert_eq!(buckets[1].bucket, start + Duration::seconds(300));
+        assert_eq!(buckets[1].samples, 2);
+        assert_eq!((buckets[1].microprice_min, buckets[1].microprice_max), (101.0, 109.0));
+
+        // The time range is half-open like the bucket boundaries
+        let buckets = repository
+            .downsample(
+                &[
+                    BookMetricsFilter::SymbolEq("BTC/USDT".to_string()),
+                    BookMetricsFilter::TimeRange(None, Some(start + Duration::seconds(300))),
+                ],
+                60,
+            )
+            .await
+            .unwrap();
+        let starts: Vec<_> = buckets.iter().map(|b| b.bucket - start).collect();
+        assert_eq!(
+            starts,
+            [Duration::zero(), Duration::seconds(120), Duration::seconds(240)]
+        );
+    }
+}
@@ -0,0 +1,1144 @@
+use crate::{
+    event::EventBuilder,
+    model::{OrderBook, OrderBookLevel},
+    ratelimit::{EndpointClass, RestRateLimiters},
+    AlertType, EventSource, EventType, MonitorError, MonitorEvent, Result,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::sync::mpsc;
+use tracing::{error, info, warn};
+
+// Clean deltas after a snapshot before the resync counts as successful
+const CONFIRM_AFTER_DELTAS: u32 = 20;
+// Levels per side covered by the OKX checksum
+const OKX_CHECKSUM_DEPTH: usize = 25;
+const MAX_SNAPSHOT_BACKOFF: Duration = Duration::from_secs(30);
+// A hung snapshot request fails and goes back through the backoff
+const SNAPSHOT_CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+const SNAPSHOT_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BookSyncConfig {
+    // Consecutive failed websocket resyncs before a REST snapshot is fetched instead
+    pub resyncs_before_snapshot: u32,
+    // Consecutive failed REST snapshot recoveries before the book feed is marked degraded
+    pub snapshot_budget: u32,
+    // Clamped to what each exchange serves
+    pub snapshot_depth: u32,
+    // Deltas kept while waiting for a snapshot, oldest dropped first
+    pub max_buffered_deltas: usize,
+}
+
+impl Default for BookSyncConfig {
+    fn default() -> Self {
+        Self {
+            resyncs_before_snapshot: 3,
+            snapshot_budget: 3,
+            snapshot_depth: 1000,
+            max_buffered_deltas: 5000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriceLevel {
+    pub price: f64,
+    pub size: f64,
+    // Vendor strings, checksums are computed over them
+    pub raw_price: String,
+    pub raw_size: String,
+}
+
+impl PriceLevel {
+    pub fn parse(price: &str, size: &str) -> Result<Self> {
+        let parse = |value: &str| {
+            value
+                .parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite() && *v >= 0.0)
+                .ok_or_else(|| MonitorError::Stream(format!("Invalid book level value {}", value)))
+        };
+        Ok(Self {
+            price: parse(price)?,
+            size: parse(size)?,
+            raw_price: price.to_string(),
+            raw_size: size.to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct L2Snapshot {
+    // Binance lastUpdateId or OKX seqId, OKX REST snapshots carry none
+    pub update_id: Option<u64>,
+    pub timestamp_ms: Option<i64>,
+    pub bids: Vec<PriceLevel>,
+    pub asks: Vec<PriceLevel>,
+    pub checksum: Option<i32>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct L2Delta {
+    // Binance sequences on first (U) and last (u) update ids, OKX on prevSeqId and seqId
+    pub first_update_id: u64,
+    pub last_update_id: u64,
+    pub prev_update_id: Option<u64>,
+    pub timestamp_ms: Option<i64>,
+    pub bids: Vec<PriceLevel>,
+    pub asks: Vec<PriceLevel>,
+    pub checksum: Option<i32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BookMessage {
+    Snapshot(L2Snapshot),
+    Delta(L2Delta),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BookSequencing {
+    Binance,
+    Okx,
+}
+
+impl BookSequencing {
+    pub fn for_exchange(exchange: &str) -> Option<Self> {
+        match exchange.to_lowercase().as_str() {
+            "binance" => Some(BookSequencing::Binance),
+            "okx" => Some(BookSequencing::Okx),
+            _ => None,
+        }
+    }
+
+    pub fn exchange(&self) -> &'static str {
+        match self {
+            BookSequencing::Binance => "binance",
+            BookSequencing::Okx => "okx",
+        }
+    }
+
+    // Binance only streams diffs, so every resync starts from a REST snapshot
+    fn has_ws_snapshots(&self) -> bool {
+        matches!(self, BookSequencing::Okx)
+    }
+
+    // REST url, endpoint class and request weight for a snapshot of `symbol` ("BTC/USDT")
+    fn snapshot_request(&self, symbol: &str, depth: u32) -> (String, EndpointClass, u32) {
+        match self {
+            // https://binance-docs.github.io/apidocs/spot/en/#order-book
+            BookSequencing::Binance => {
+                let depth = depth.clamp(1, 5000);
+                let weight = match depth {
+                    1..=100 => 5,
+                    101..=500 => 25,
+                    501..=1000 => 50,
+                    _ => 250,
+                };
+                (
+                    format!(
+                        "https://api.binance.com/api/v3/depth?symbol={}&limit={}",
+                        symbol.replace('/', "").to_uppercase(),
+                        depth
+                    ),
+                    EndpointClass::MarketData,
+                    weight,
+                )
+            }
+            // https://www.okx.com/docs-v5/en/#order-book-trading-market-data-get-order-book
+            BookSequencing::Okx => (
+                format!(
+                    "https://www.okx.com/api/v5/market/books?instId={}&sz={}",
+                    symbol.replace('/', "-").to_uppercase(),
+                    depth.clamp(1, 400)
+                ),
+                EndpointClass::MarketData,
+                1,
+            ),
+        }
+    }
+
+    pub fn parse_snapshot(&self, body: &str) -> Result<L2Snapshot> {
+        match self {
+            BookSequencing::Binance => {
+                let snapshot: BinanceDepthSnapshot = serde_json::from_str(body)?;
+                Ok(L2Snapshot {
+                    update_id: Some(snapshot.last_update_id),
+                    timestamp_ms: None,
+                    bids: parse_levels(&snapshot.bids)?,
+                    asks: parse_levels(&snapshot.asks)?,
+                    checksum: None,
+                })
+            }
+            BookSequencing::Okx => {
+                let response: OkxBooksResponse = serde_json::from_str(body)?;
+                if response.code != "0" {
+                    return Err(MonitorError::Stream(format!(
+                        "OKX books returned code {}: {}",
+                        response.code, response.msg
+                    )));
+                }
+                let book = response
+                    .data
+                    .into_iter()
+                    .next()
+                    .ok_or_else(|| MonitorError::Stream("OKX books returned no data".to_string()))?;
+                book.into_snapshot()
+            }
+        }
+    }
+
+    // One websocket message, Binance depthUpdate or an OKX books push
+    pub fn parse_message(&self, body: &str) -> Result<BookMessage> {
+        match self {
+            BookSequencing::Binance => {
+                let update: BinanceDepthUpdate = serde_json::from_str(body)?;
+                Ok(BookMessage::Delta(L2Delta {
+                    first_update_id: update.first_update_id,
+                    last_update_id: update.last_update_id,
+                    prev_update_id: None,
+                    timestamp_ms: update.event_time,
+                    bids: parse_levels(&update.bids)?,
+                    asks: parse_levels(&update.asks)?,
+                    checksum: None,
+                }))
+            }
+            BookSequencing::Okx => {
+                let push: OkxBooksPush = serde_json::from_str(body)?;
+                let book = push
+                    .data
+                    .into_iter()
+                    .next()
+                    .ok_or_else(|| MonitorError::Stream("OKX books push had no data".to_string()))?;
+                match push.action.as_str() {
+                    "snapshot" => Ok(BookMessage::Snapshot(book.into_snapshot()?)),
+                    "update" => Ok(BookMessage::Delta(book.into_delta()?)),
+                    other => Err(MonitorError::Stream(format!("Unknown OKX books action {}", other))),
+                }
+            }
+        }
+    }
+
+    fn checksum(&self, book: &LocalBook) -> Option<i32> {
+        match self {
+            BookSequencing::Binance => None,
+            BookSequencing::Okx => Some(book.okx_checksum()),
+        }
+    }
+}
+
+fn parse_levels(levels: &[Vec<String>]) -> Result<Vec<PriceLevel>> {
+    levels
+        .iter()
+        .map(|level| match level.as_slice() {
+            [price, size, ..] => PriceLevel::parse(price, size),
+            _ => Err(MonitorError::Stream(format!("Malformed book level {:?}", level))),
+        })
+        .collect()
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BinanceDepthSnapshot {
+    last_update_id: u64,
+    bids: Vec<Vec<String>>,
+    asks: Vec<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+struct BinanceDepthUpdate {
+    #[serde(rename = "E")]
+    event_time: Option<i64>,
+    #[serde(rename = "U")]
+    first_update_id: u64,
+    #[serde(rename = "u")]
+    last_update_id: u64,
+    #[serde(rename = "b")]
+    bids: Vec<Vec<String>>,
+    #[serde(rename = "a")]
+    asks: Vec<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+struct OkxBooksResponse {
+    code: String,
+    #[serde(default)]
+    msg: String,
+    #[serde(default)]
+    data: Vec<OkxBook>,
+}
+
+#[derive(Deserialize)]
+struct OkxBooksPush {
+    action: String,
+    data: Vec<OkxBook>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OkxBook {
+    asks: Vec<Vec<String>>,
+    bids: Vec<Vec<String>>,
+    ts: String,
+    #[serde(default)]
+    checksum: Option<i32>,
+    // -1 on snapshots
+    #[serde(default)]
+    prev_seq_id: Option<i64>,
+    #[serde(default)]
+    seq_id: Option<i64>,
+}
+
+impl OkxBook {
+    fn timestamp_ms(&self) -> Result<i64> {
+        self.ts
+            .parse()
+            .map_err(|_| MonitorError::Stream(format!("Invalid OKX book timestamp {}", self.ts)))
+    }
+
+    fn into_snapshot(self) -> Result<L2Snapshot> {
+        Ok(L2Snapshot {
+            update_id: self.seq_id.filter(|id| *id >= 0).map(|id| id as u64),
+            timestamp_ms: Some(self.timestamp_ms()?),
+            bids: parse_levels(&self.bids)?,
+            asks: parse_levels(&self.asks)?,
+            checksum: self.checksum,
+        })
+    }
+
+    fn into_delta(self) -> Result<L2Delta> {
+        let seq_id = self
+            .seq_id
+            .filter(|id| *id >= 0)
+            .ok_or_else(|| MonitorError::Stream("OKX books update without seqId".to_string()))?;
+        Ok(L2Delta {
+            first_update_id: seq_id as u64,
+            last_update_id: seq_id as u64,
+            prev_update_id: self.prev_seq_id.filter(|id| *id >= 0).map(|id| id as u64),
+            timestamp_ms: Some(self.timestamp_ms()?),
+            bids: parse_levels(&self.bids)?,
+            asks: parse_levels(&self.asks)?,
+            checksum: self.checksum,
+        })
+    }
+}
+
+// Levels keyed by the bit pattern of the price, which orders like the price itself for
+// non-negative floats
+#[derive(Debug, Default)]
+pub struct LocalBook {
+    bids: BTreeMap<u64, PriceLevel>,
+    asks: BTreeMap<u64, PriceLevel>,
+}
+
+impl LocalBook {
+    fn rebuild(&mut self, bids: Vec<PriceLevel>, asks: Vec<PriceLevel>) {
+        self.bids.clear();
+        self.asks.clear();
+        Self::apply_side(&mut self.bids, bids);
+        Self::apply_side(&mut self.asks, asks);
+    }
+
+    fn apply(&mut self, delta: &L2Delta) {
+        Self::apply_side(&mut self.bids, delta.bids.clone());
+        Self::apply_side(&mut self.asks, delta.asks.clone());
+    }
+
+    // Sizes are absolute, zero removes the level
+    fn apply_side(side: &mut BTreeMap<u64, PriceLevel>, levels: Vec<PriceLevel>) {
+        for level in levels {
+            let key = level.price.to_bits();
+            if level.size == 0.0 {
+                side.remove(&key);
+            } else {
+                side.insert(key, level);
+            }
+        }
+    }
+
+    // Best first
+    pub fn bids(&self, depth: usize) -> Vec<&PriceLevel> {
+        self.bids.values().rev().take(depth).collect()
+    }
+
+    pub fn asks(&self, depth: usize) -> Vec<&PriceLevel> {
+        self.asks.values().take(depth).collect()
+    }
+
+    pub fn best_bid(&self) -> Option<&PriceLevel> {
+        self.bids.values().next_back()
+    }
+
+    pub fn best_ask(&self) -> Option<&PriceLevel> {
+        self.asks.values().next()
+    }
+
+    // The best `depth` levels of each side, as the book detectors take them
+    pub fn to_order_book(
+        &self,
+        exchange: &str,
+        symbol: &str,
+        depth: usize,
+        timestamp: DateTime<Utc>,
+    ) -> OrderBook {
+        let levels = |side: Vec<&PriceLevel>| {
+            side.into_iter()
+                .map(|level| OrderBookLevel {
+                    price: level.price,
+                    quantity: level.size,
+                })
+                .collect()
+        };
+        OrderBook {
+            exchange: exchange.to_string(),
+            symbol: symbol.to_string(),
+            timestamp,
+            bids: levels(self.bids(depth)),
+            asks: levels(self.asks(depth)),
+        }
+    }
+
+    // CRC32 over the top levels interleaved as "bid:size:ask:size", as a signed integer
+    fn okx_checksum(&self) -> i32 {
+        let bids = self.bids(OKX_CHECKSUM_DEPTH);
+        let asks = self.asks(OKX_CHECKSUM_DEPTH);
+        let mut parts = Vec::with_capacity(bids.len() + asks.len());
+        for i in 0..bids.len().max(asks.len()) {
+            for level in [bids.get(i), asks.get(i)].into_iter().flatten() {
+                parts.push(format!("{}:{}", level.raw_price, level.raw_size));
+            }
+        }
+        crc32(parts.join(":").as_bytes()) as i32
+    }
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum BookFeedHealth {
+    Synced,
+    Resyncing,
+    // Snapshot budget exhausted, book-dependent detectors should not trust this book
+    Degraded,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BookFeedStatus {
+    pub exchange: String,
+    pub symbol: String,
+    pub health: BookFeedHealth,
+    pub failed_resyncs: u32,
+    pub failed_snapshots: u32,
+    pub last_error: Option<String>,
+}
+
+impl BookFeedStatus {
+    pub fn to_event(&self) -> Option<MonitorEvent> {
+        let (alert_type, message) = match self.health {
+            BookFeedHealth::Degraded => (
+                AlertType::Warning,
+                format!(
+                    "Order book for {} on {} cannot be kept in sync after {} REST snapshots: {}",
+                    self.symbol,
+                    self.exchange,
+                    self.failed_snapshots,
+                    self.last_error.as_deref().unwrap_or("unknown error")
+                ),
+            ),
+            BookFeedHealth::Synced | BookFeedHealth::Resyncing => (
+                AlertType::Info,
+                format!("Order book for {} on {} is in sync again", self.symbol, self.exchange),
+            ),
+        };
+
+        let mut data = serde_json::to_value(self).ok()?;
+        data["message"] = serde_json::Value::String(message);
+
+        EventBuilder::new()
+            .with_source(EventSource::Exchange(self.exchange.clone()))
+            .with_type(EventType::Alert(alert_type))
+            .with_data(data)
+            .build()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncAction {
+    None,
+    // Resubscribe the websocket channel to receive a fresh snapshot
+    Resubscribe,
+    // Fetch a REST snapshot and pass it to `apply_snapshot`
+    FetchSnapshot,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotSource {
+    Websocket,
+    Rest,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Anchor {
+    AwaitingSnapshot,
+    // Next delta must continue from `last_id`, the first one after a snapshot may overlap it
+    Sequenced { last_id: u64, first_applied: bool },
+    // OKX REST snapshots have no seqId, deltas up to their timestamp are already included and
+    // the first later delta is confirmed by its checksum
+    Timestamp { after_ms: i64 },
+}
+
+enum Check {
+    Valid,
+    Stale,
+    Gap(String),
+}
+
+// Local L2 book for one symbol, rebuilt from websocket snapshots and, after repeated failures,
+// from REST snapshots. Not thread safe on its own, `BookSyncManager` serializes access.
+pub struct L2BookSync {
+    exchange: String,
+    symbol: String,
+    sequencing: BookSequencing,
+    config: BookSyncConfig,
+    book: LocalBook,
+    anchor: Anchor,
+    buffer: VecDeque<L2Delta>,
+    snapshot_source: Option<SnapshotSource>,
+    snapshot_requested: bool,
+    clean_deltas: u32,
+    failed_resyncs: u32,
+    failed_snapshots: u32,
+    health: BookFeedHealth,
+    last_error: Option<String>,
+    // Exchange time of the last snapshot or delta applied
+    last_update_ms: Option<i64>,
+}
+
+impl L2BookSync {
+    pub fn new(symbol: &str, sequencing: BookSequencing, config: BookSyncConfig) -> Self {
+        Self {
+            exchange: sequencing.exchange().to_string(),
+            symbol: symbol.to_string(),
+            sequencing,
+            config,
+            book: LocalBook::default(),
+            anchor: Anchor::AwaitingSnapshot,
+            buffer: VecDeque::new(),
+            snapshot_source: None,
+            snapshot_requested: false,
+            clean_deltas: 0,
+            failed_resyncs: 0,
+            failed_snapshots: 0,
+            health: BookFeedHealth::Resyncing,
+            last_error: None,
+            last_update_ms: None,
+        }
+    }
+
+    // `None` while the book is being rebuilt
+    pub fn book(&self) -> Option<&LocalBook> {
+        (self.anchor != Anchor::AwaitingSnapshot).then_some(&self.book)
+    }
+
+    pub fn health(&self) -> BookFeedHealth {
+        self.health
+    }
+
+    pub fn last_update(&self) -> Option<DateTime<Utc>> {
+        self.last_update_ms
+            .and_then(|ms| Utc.timestamp_millis_opt(ms).single())
+    }
+
+    pub fn status(&self) -> BookFeedStatus {
+        BookFeedStatus {
+            exchange: self.exchange.clone(),
+            symbol: self.symbol.clone(),
+            health: self.health,
+            failed_resyncs: self.failed_resyncs,
+            failed_snapshots: self.failed_snapshots,
+            last_error: self.last_error.clone(),
+        }
+    }
+
+    pub fn apply_snapshot(&mut self, snapshot: L2Snapshot, source: SnapshotSource) -> SyncAction {
+        if source == SnapshotSource::Rest {
+            self.snapshot_requested = false;
+        }
+        self.book.rebuild(snapshot.bids, snapshot.asks);
+        self.anchor = match snapshot.update_id {
+            Some(last_id) => Anchor::Sequenced { last_id, first_applied: false },
+            None => Anchor::Timestamp { after_ms: snapshot.timestamp_ms.unwrap_or(0) },
+        };
+        self.snapshot_source = Some(source);
+        self.clean_deltas = 0;
+        self.last_update_ms = snapshot.timestamp_ms.or(self.last_update_ms);
+
+        if let Some(expected) = snapshot.checksum {
+            if self.sequencing.checksum(&self.book) != Some(expected) {
+                return self.fail("Snapshot checksum mismatch".to_string());
+            }
+        }
+
+        // Deltas received while waiting, the ones already in the snapshot are dropped as stale
+        let buffered = std::mem::take(&mut self.buffer);
+        for delta in buffered {
+            let action = self.apply_delta(delta);
+            if action != SyncAction::None {
+                return action;
+            }
+        }
+        SyncAction::None
+    }
+
+    pub fn apply_delta(&mut self, delta: L2Delta) -> SyncAction {
+        let check = match self.anchor {
+            Anchor::AwaitingSnapshot => return self.buffer_delta(delta),
+            Anchor::Sequenced { last_id, first_applied } => {
+                self.check_sequence(last_id, first_applied, &delta)
+            }
+            Anchor::Timestamp { after_ms } => match delta.timestamp_ms {
+                Some(ts) if ts <= after_ms => Check::Stale,
+                _ => Check::Valid,
+            },
+        };
+
+        match check {
+            Check::Valid => {}
+            Check::Stale => return SyncAction::None,
+            Check::Gap(reason) => return self.fail(reason),
+        }
+
+        self.book.apply(&delta);
+        self.anchor = Anchor::Sequenced { last_id: delta.last_update_id, first_applied: true };
+        self.last_update_ms = delta.timestamp_ms.or(self.last_update_ms);
+
+        if let Some(expected) = delta.checksum {
+            if self.sequencing.checksum(&self.book) != Some(expected) {
+                return self.fail(format!("Checksum mismatch at update {}", delta.last_update_id));
+            }
+        }
+
+        self.clean_deltas += 1;
+        if self.clean_deltas == CONFIRM_AFTER_DELTAS {
+            if self.health != BookFeedHealth::Synced {
+                info!("{} {} order book in sync", self.exchange, self.symbol);
+            }
+            self.health = BookFeedHealth::Synced;
+            self.failed_resyncs = 0;
+            self.failed_snapshots = 0;
+            self.last_error = None;
+        }
+        SyncAction::None
+    }
+
+    // A REST snapshot could not be fetched or parsed
+    pub fn snapshot_failed(&mut self, reason: String) -> SyncAction {
+        self.snapshot_requested = false;
+        self.failed_snapshots += 1;
+        self.record_failure(reason);
+        self.next_action()
+    }
+
+    fn check_sequence(&self, last_id: u64, first_applied: bool, delta: &L2Delta) -> Check {
+        match self.sequencing {
+            // https://binance-docs.github.io/apidocs/spot/en/#how-to-manage-a-local-order-book-correctly
+            BookSequencing::Binance => {
+                if delta.last_update_id <= last_id {
+                    Check::Stale
+                } else if !first_applied && delta.first_update_id <= last_id + 1 {
+                    // The first delta after a snapshot straddles its lastUpdateId
+                    Check::Valid
+                } else if first_applied && delta.first_update_id == last_id + 1 {
+                    Check::Valid
+                } else {
+                    Check::Gap(format!(
+                        "Expected update {} but got {}..{}",
+                        last_id + 1,
+                        delta.first_update_id,
+                        delta.last_update_id
+                    ))
+                }
+            }
+            // https://www.okx.com/docs-v5/en/#order-book-trading-market-data-ws-order-book-channel
+            BookSequencing::Okx => match delta.prev_update_id {
+                // seqId can go backwards after exchange maintenance, prevSeqId still chains
+                Some(prev) if prev == last_id => Check::Valid,
+                _ if delta.last_update_id <= last_id => Check::Stale,
+                prev => Check::Gap(format!(
+                    "Expected prevSeqId {} but got {:?}",
+                    last_id, prev
+                )),
+            },
+        }
+    }
+
+    fn buffer_delta(&mut self, delta: L2Delta) -> SyncAction {
+        if self.buffer.len() >= self.config.max_buffered_deltas {
+            self.buffer.pop_front();
+        }
+        self.buffer.push_back(delta);
+
+        // Nothing but a REST snapshot will ever arrive for diff-only feeds
+        if !self.sequencing.has_ws_snapshots() && !self.snapshot_requested {
+            self.snapshot_requested = true;
+            return SyncAction::FetchSnapshot;
+        }
+        SyncAction::None
+    }
+
+    fn fail(&mut self, reason: String) -> SyncAction {
+        match self.snapshot_source {
+            Some(SnapshotSource::Rest) => self.failed_snapshots += 1,
+            _ => self.failed_resyncs += 1,
+        }
+        self.anchor = Anchor::AwaitingSnapshot;
+        self.buffer.clear();
+        self.clean_deltas = 0;
+        self.record_failure(reason);
+        self.next_action()
+    }
+
+    fn record_failure(&mut self, reason: String) {
+        warn!("{} {} order book out of sync: {}", self.exchange, self.symbol, reason);
+        self.last_error = Some(reason);
+        // Only a confirmed resync clears degraded
+        if self.failed_snapshots >= self.config.snapshot_budget {
+            self.health = BookFeedHealth::Degraded;
+        } else if self.health != BookFeedHealth::Degraded {
+            self.health = BookFeedHealth::Resyncing;
+        }
+    }
+
+    fn next_action(&mut self) -> SyncAction {
+        if self.snapshot_requested {
+            SyncAction::None
+        } else if self.sequencing.has_ws_snapshots()
+            && self.failed_resyncs < self.config.resyncs_before_snapshot
+        {
+            SyncAction::Resubscribe
+        } else {
+            self.snapshot_requested = true;
+            SyncAction::FetchSnapshot
+        }
+    }
+}
+
+#[async_trait]
+pub trait SnapshotFetcher: Send + Sync {
+    async fn fetch_snapshot(
+        &self,
+        sequencing: BookSequencing,
+        symbol: &str,
+        depth: u32,
+    ) -> Result<L2Snapshot>;
+}
+
+// Public REST depth endpoints, charged to the shared per-exchange limiter
+pub struct RestSnapshotFetcher {
+    client: reqwest::Client,
+    limiters: RestRateLimiters,
+}
+
+impl RestSnapshotFetcher {
+    pub fn new(limiters: RestRateLimiters) -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .connect_timeout(SNAPSHOT_CONNECT_TIMEOUT)
+                .timeout(SNAPSHOT_REQUEST_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            limiters,
+        }
+    }
+}
+
+#[async_trait]
+impl SnapshotFetcher for RestSnapshotFetcher {
+    async fn fetch_snapshot(
+        &self,
+        sequencing: BookSequencing,
+        symbol: &str,
+        depth: u32,
+    ) -> Result<L2Snapshot> {
+        let (url, class, weight) = sequencing.snapshot_request(symbol, depth);
+        let limiter = self.limiters.get(sequencing.exchange());
+        limiter.acquire(class, weight).await?;
+
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| MonitorError::Stream(format!("Snapshot request failed: {}", e)))?;
+        let status = response.status();
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        limiter.record_response(status.as_u16(), retry_after.as_deref());
+        if !status.is_success() {
+            return Err(MonitorError::Stream(format!("Snapshot request returned {}", status)));
+        }
+
+        let body = response
+            .text()
+            .await
+            .map_err(|e| MonitorError::Stream(format!("Snapshot read failed: {}", e)))?;
+        sequencing.parse_snapshot(&body)
+    }
+}
+
+// Owns the books of every synced symbol and runs REST snapshot recoveries in the background,
+// so the websocket consumer never waits on them
+pub struct BookSyncManager {
+    config: BookSyncConfig,
+    books: DashMap<String, Arc<Mutex<L2BookSync>>>,
+    fetcher: Arc<dyn SnapshotFetcher>,
+    alert_tx: Option<mpsc::UnboundedSender<MonitorEvent>>,
+}
+
+impl BookSyncManager {
+    pub fn new(config: BookSyncConfig, fetcher: Arc<dyn SnapshotFetcher>) -> Self {
+        Self {
+            config,
+            books: DashMap::new(),
+            fetcher,
+            alert_tx: None,
+        }
+    }
+
+    // Degraded and recovered book feeds are reported once per transition
+    pub fn with_alert_sender(mut self, alert_tx: mpsc::UnboundedSender<MonitorEvent>) -> Self {
+        self.alert_tx = Some(alert_tx);
+        self
+    }
+
+    pub fn book(&self, exchange: &str, symbol: &str) -> Option<Arc<Mutex<L2BookSync>>> {
+        self.books
+            .get(&format!("{}:{}", exchange.to_lowercase(), symbol))
+            .map(|b| b.clone())
+    }
+
+    pub fn statuses(&self) -> Vec<BookFeedStatus> {
+        self.books.iter().map(|b| b.lock().status()).collect()
+    }
+
+    // Top levels of a book that is in sync, `None` while it is rebuilt or once it is degraded
+    // so book detectors never judge a book that can't be trusted
+    pub fn order_book(&self, exchange: &str, symbol: &str, depth: usize) -> Option<OrderBook> {
+        let book = self.book(exchange, symbol)?;
+        let sync = book.lock();
+        if sync.health() == BookFeedHealth::Degraded {
+            return None;
+        }
+        let timestamp = sync.last_update().unwrap_or_else(Utc::now);
+        Some(sync.book()?.to_order_book(&sync.exchange, &sync.symbol, depth, timestamp))
+    }
+
+    // Returns what the websocket consumer has to do, REST snapshots are handled here
+    pub fn handle_message(
+        self: &Arc<Self>,
+        exchange: &str,
+        symbol: &str,
+        message: BookMessage,
+    ) -> Result<SyncAction> {
+        let sequencing = BookSequencing::for_exchange(exchange).ok_or_else(|| {
+            MonitorError::Configuration(format!("No order book sequencing for {}", exchange))
+        })?;
+        let book = self
+            .books
+            .entry(format!("{}:{}", exchange.to_lowercase(), symbol))
+            .or_insert_with(|| {
+                Arc::new(Mutex::new(L2BookSync::new(symbol, sequencing, self.config.clone())))
+            })
+            .clone();
+
+        let action = self.update(&book, |sync| match message {
+            BookMessage::Snapshot(snapshot) => sync.apply_snapshot(snapshot, SnapshotSource::Websocket),
+            BookMessage::Delta(delta) => sync.apply_delta(delta),
+        });
+        if action == SyncAction::FetchSnapshot {
+            self.spawn_snapshot_fetch(book);
+            return Ok(SyncAction::None);
+        }
+        Ok(action)
+    }
+
+    fn update(
+        &self,
+        book: &Mutex<L2BookSync>,
+        apply: impl FnOnce(&mut L2BookSync) -> SyncAction,
+    ) -> SyncAction {
+        let (before, action, status) = {
+            let mut sync = book.lock();
+            let before = sync.health();
+            let action = apply(&mut sync);
+            (before, action, sync.status())
+        };
+
+        let reported = |h: BookFeedHealth| h == BookFeedHealth::Degraded;
+        if reported(before) != reported(status.health) {
+            if let (Some(tx), Some(event)) = (&self.alert_tx, status.to_event()) {
+                if let Err(e) = tx.send(event) {
+                    error!("Failed to send book feed alert: {}", e);
+                }
+            }
+        }
+        action
+    }
+
+    // Retries with backoff until a snapshot is applied, each failure counts against the budget
+    fn spawn_snapshot_fetch(self: &Arc<Self>, book: Arc<Mutex<L2BookSync>>) {
+        let manager = self.clone();
+        tokio::spawn(async move {
+            let (sequencing, symbol) = {
+                let sync = book.lock();
+                (sync.sequencing, sync.symbol.clone())
+            };
+            let mut backoff = Duration::from_millis(500);
+            loop {
+                let fetched = manager
+                    .fetcher
+                    .fetch_snapshot(sequencing, &symbol, manager.config.snapshot_depth)
+                    .await;
+                let action = manager.update(&book, |sync| match fetched {
+                    Ok(snapshot) => sync.apply_snapshot(snapshot, SnapshotSource::Rest),
+                    Err(e) => sync.snapshot_failed(e.to_string()),
+                });
+                if action != SyncAction::FetchSnapshot {
+                    break;
+                }
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_SNAPSHOT_BACKOFF);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> String {
+        std::fs::read_to_string(format!(
+            "{}/tests/fixtures/book_sync/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            name
+        ))
+        .unwrap()
+    }
+
+    fn messages(sequencing: BookSequencing, name: &str) -> Vec<BookMessage> {
+        fixture(name)
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| sequencing.parse_message(line).unwrap())
+            .collect()
+    }
+
+    fn delta(message: &BookMessage) -> L2Delta {
+        match message {
+            BookMessage::Delta(delta) => delta.clone(),
+            other => panic!("expected a delta, got {:?}", other),
+        }
+    }
+
+    fn sync(sequencing: BookSequencing) -> L2BookSync {
+        L2BookSync::new("BTC/USDT", sequencing, BookSyncConfig::default())
+    }
+
+    fn top(sync: &L2BookSync) -> (f64, f64, f64, f64) {
+        let book = sync.book().unwrap();
+        let (bid, ask) = (book.best_bid().unwrap(), book.best_ask().unwrap());
+        (bid.price, bid.size, ask.price, ask.size)
+    }
+
+    #[test]
+    fn test_binance_snapshot_overlap_and_buffered_deltas() {
+        let binance = BookSequencing::Binance;
+        let mut sync = sync(binance);
+        let deltas = messages(binance, "binance_deltas.ndjson");
+
+        // Deltas before the snapshot are buffered, the first one triggers the fetch
+        assert_eq!(sync.apply_delta(delta(&deltas[0])), SyncAction::FetchSnapshot);
+        for message in &deltas[1..3] {
+            assert_eq!(sync.apply_delta(delta(message)), SyncAction::None);
+        }
+        assert!(sync.book().is_none());
+
+        // lastUpdateId 100: 95..99 is stale, 98..103 overlaps it, 104..106 follows on
+        let snapshot = binance.parse_snapshot(&fixture("binance_snapshot.json")).unwrap();
+        assert_eq!(sync.apply_snapshot(snapshot, SnapshotSource::Rest), SyncAction::None);
+        assert_eq!(top(&sync), (50000.0, 0.8, 50001.0, 2.5));
+
+        assert_eq!(sync.apply_delta(delta(&deltas[3])), SyncAction::None);
+        assert_eq!(top(&sync), (50000.5, 0.3, 50001.0, 2.5));
+        assert_eq!(sync.book().unwrap().bids(10).len(), 3);
+    }
+
+    #[test]
+    fn test_binance_gap_refetches_snapshot_once() {
+        let binance = BookSequencing::Binance;
+        let mut sync = sync(binance);
+        let snapshot = binance.parse_snapshot(&fixture("binance_snapshot.json")).unwrap();
+        sync.apply_snapshot(snapshot.clone(), SnapshotSource::Rest);
+
+        let deltas = messages(binance, "binance_deltas.ndjson");
+        for message in &deltas[1..4] {
+            sync.apply_delta(delta(message));
+        }
+        // 110..112 skips 108 and 109
+        assert_eq!(sync.apply_delta(delta(&deltas[4])), SyncAction::FetchSnapshot);
+        assert_eq!(sync.status().failed_snapshots, 1);
+        assert_eq!(sync.apply_delta(delta(&deltas[4])), SyncAction::None);
+
+        // A first delta that starts past the snapshot does not overlap it
+        let mut sync = self::sync(binance);
+        sync.apply_snapshot(snapshot, SnapshotSource::Rest);
+        let late = L2Delta { first_update_id: 102, last_update_id: 104, ..Default::default() };
+        assert_eq!(sync.apply_delta(late), SyncAction::FetchSnapshot);
+    }
+
+    #[test]
+    fn test_okx_sequence_and_checksum() {
+        let okx = BookSequencing::Okx;
+        let mut sync = sync(okx);
+        let messages = messages(okx, "okx_books.ndjson");
+
+        let BookMessage::Snapshot(snapshot) = messages[0].clone() else { panic!("expected snapshot") };
+        assert_eq!(sync.apply_snapshot(snapshot.clone(), SnapshotSource::Websocket), SyncAction::None);
+        assert_eq!(sync.apply_delta(delta(&messages[1])), SyncAction::None);
+        assert_eq!(sync.apply_delta(delta(&messages[2])), SyncAction::None);
+        assert_eq!(top(&sync), (50000.2, 1.0, 50000.9, 0.4));
+        // A replayed update is stale, not a gap
+        assert_eq!(sync.apply_delta(delta(&messages[2])), SyncAction::None);
+
+        // A lost level update fails the checksum, repeated failures fall back to REST
+        assert_eq!(sync.apply_delta(delta(&messages[3])), SyncAction::Resubscribe);
+        let mut reordered = delta(&messages[1]);
+        reordered.prev_update_id = Some(9);
+        sync.apply_snapshot(snapshot.clone(), SnapshotSource::Websocket);
+        assert_eq!(sync.apply_delta(reordered.clone()), SyncAction::Resubscribe);
+        sync.apply_snapshot(snapshot, SnapshotSource::Websocket);
+        assert_eq!(sync.apply_delta(reordered), SyncAction::FetchSnapshot);
+        assert_eq!(sync.status().failed_resyncs, 3);
+        assert_eq!(sync.health(), BookFeedHealth::Resyncing);
+    }
+
+    #[test]
+    fn test_okx_rest_snapshot_is_anchored_by_timestamp_and_checksum() {
+        let okx = BookSequencing::Okx;
+        let mut sync = sync(okx);
+        let messages = messages(okx, "okx_books.ndjson");
+        let snapshot = okx.parse_snapshot(&fixture("okx_rest_snapshot.json")).unwrap();
+        assert_eq!(snapshot.update_id, None);
+
+        // Taken after the first update: that update is stale, the next anchors the sequence
+        sync.apply_snapshot(snapshot, SnapshotSource::Rest);
+        assert_eq!(sync.apply_delta(delta(&messages[1])), SyncAction::None);
+        assert_eq!(sync.apply_delta(delta(&messages[2])), SyncAction::None);
+        assert_eq!(top(&sync), (50000.2, 1.0, 50000.9, 0.4));
+        assert_eq!(sync.status().failed_snapshots, 0);
+    }
+
+    #[test]
+    fn test_snapshot_budget_marks_feed_degraded_until_confirmed() {
+        let binance = BookSequencing::Binance;
+        let mut sync = sync(binance);
+        let snapshot = binance.parse_snapshot(&fixture("binance_snapshot.json")).unwrap();
+
+        assert_eq!(
+            sync.apply_delta(L2Delta { first_update_id: 1, last_update_id: 2, ..Default::default() }),
+            SyncAction::FetchSnapshot
+        );
+        for attempt in 1..=3 {
+            assert_eq!(sync.snapshot_failed("timeout".to_string()), SyncAction::FetchSnapshot);
+            assert_eq!(sync.status().failed_snapshots, attempt);
+        }
+        assert_eq!(sync.health(), BookFeedHealth::Degraded);
+        let event = sync.status().to_event().unwrap();
+        assert!(matches!(event.event_type, EventType::Alert(AlertType::Warning)));
+        assert!(event.data["message"].as_str().unwrap().contains("timeout"));
+
+        sync.apply_snapshot(snapshot, SnapshotSource::Rest);
+        for id in 101..(101 + CONFIRM_AFTER_DELTAS as u64) {
+            let next = L2Delta { first_update_id: id, last_update_id: id, ..Default::default() };
+            assert_eq!(sync.health(), BookFeedHealth::Degraded);
+            sync.apply_delta(next);
+        }
+        assert_eq!(sync.health(), BookFeedHealth::Synced);
+        assert_eq!(sync.status().failed_snapshots, 0);
+    }
+
+    // Serves the Binance fixture snapshot
+    struct FixtureFetcher;
+
+    #[async_trait]
+    impl SnapshotFetcher for FixtureFetcher {
+        async fn fetch_snapshot(
+            &self,
+            sequencing: BookSequencing,
+            _symbol: &str,
+            _depth: u32,
+        ) -> Result<L2Snapshot> {
+            sequencing.parse_snapshot(&fixture("binance_snapshot.json"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_manager_serves_the_book_once_the_rest_snapshot_is_in() {
+        let binance = BookSequencing::Binance;
+        let manager = Arc::new(BookSyncManager::new(
+            BookSyncConfig::default(),
+            Arc::new(FixtureFetcher),
+        ));
+        let deltas = messages(binance, "binance_deltas.ndjson");
+
+        // The first delta starts the snapshot fetch in the background
+        for message in &deltas[..3] {
+            let action = manager.handle_message("Binance", "BTC/USDT", message.clone()).unwrap();
+            assert_eq!(action, SyncAction::None);
+        }
+        for _ in 0..50 {
+            if manager.order_book("binance", "BTC/USDT", 10).is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        manager.handle_message("binance", "BTC/USDT", deltas[3].clone()).unwrap();
+        let book = manager.order_book("binance", "BTC/USDT", 2).unwrap();
+        assert_eq!((book.exchange.as_str(), book.symbol.as_str()), ("binance", "BTC/USDT"));
+        assert_eq!((book.bids[0].price, book.bids[0].quantity), (50000.5, 0.3));
+        assert_eq!((book.asks[0].price, book.asks[0].quantity), (50001.0, 2.5));
+        assert_eq!(book.bids.len(), 2);
+        assert!(manager.order_book("okx", "BTC/USDT", 2).is_none());
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+}
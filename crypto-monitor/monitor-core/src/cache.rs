@@ -0,0 +1,191 @@
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CacheStats {
+    pub name: String,
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub hit_rate: Option<f64>,
+    // Rough estimate from entry count and per-entry size, not an allocator measurement
+    pub memory_bytes: usize,
+    pub ttl_secs: Option<u64>,
+}
+
+// Uniform view over the in-process caches, for the admin endpoints
+pub trait InstrumentedCache: Send + Sync {
+    fn name(&self) -> &str;
+    fn stats(&self) -> CacheStats;
+    fn clear(&self);
+}
+
+#[derive(Debug, Default)]
+pub struct CacheCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl CacheCounters {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Records the lookup outcome and passes the value through
+    pub fn observe<T>(&self, value: Option<T>) -> Option<T> {
+        let counter = if value.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        value
+    }
+
+    pub fn stats(
+        &self,
+        name: &str,
+        entries: usize,
+        entry_bytes: usize,
+        ttl_secs: Option<u64>,
+    ) -> CacheStats {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        let lookups = hits + misses;
+
+        CacheStats {
+            name: name.to_string(),
+            entries,
+            hits,
+            misses,
+            hit_rate: (lookups > 0).then(|| hits as f64 / lookups as f64),
+            memory_bytes: entries * entry_bytes,
+            ttl_secs,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct CacheRegistry {
+    caches: DashMap<String, Arc<dyn InstrumentedCache>>,
+}
+
+impl CacheRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&self, cache: Arc<dyn InstrumentedCache>) {
+        self.caches.insert(cache.name().to_string(), cache);
+    }
+
+    pub fn stats(&self) -> Vec<CacheStats> {
+        let mut stats: Vec<CacheStats> = self.caches.iter().map(|c| c.stats()).collect();
+        stats.sort_by(|a, b| a.name.cmp(&b.name));
+        stats
+    }
+
+    // Returns false when no cache is registered under `name`
+    pub fn clear(&self, name: &str) -> bool {
+        // Clone out of the map so the cache clears without holding the registry shard lock
+        let cache = self.caches.get(name).map(|c| c.clone());
+        match cache {
+            Some(cache) => {
+                cache.clear();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FakeCache {
+        name: &'static str,
+        entries: DashMap<u64, u64>,
+        counters: CacheCounters,
+    }
+
+    impl FakeCache {
+        fn new(name: &'static str, entries: u64) -> Arc<Self> {
+            Arc::new(Self {
+                name,
+                entries: (0..entries).map(|i| (i, i)).collect(),
+                counters: CacheCounters::new(),
+            })
+        }
+
+        fn get(&self, key: u64) -> Option<u64> {
+            self.counters.observe(self.entries.get(&key).map(|v| *v))
+        }
+    }
+
+    impl InstrumentedCache for FakeCache {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn stats(&self) -> CacheStats {
+            self.counters.stats(self.name, self.entries.len(), 16, Some(60))
+        }
+
+        fn clear(&self) {
+            self.entries.clear();
+        }
+    }
+
+    #[test]
+    fn test_reports_stats_and_clears_only_target() {
+        let registry = CacheRegistry::new();
+        let prices = FakeCache::new("prices", 10);
+        let books = FakeCache::new("books", 4);
+        registry.register(prices.clone());
+        registry.register(books.clone());
+
+        prices.get(1);
+        prices.get(2);
+        prices.get(99);
+
+        let stats = registry.stats();
+        assert_eq!(stats.iter().map(|s| s.name.as_str()).collect::<Vec<_>>(), ["books", "prices"]);
+        assert_eq!(stats[1].entries, 10);
+        assert_eq!(stats[1].memory_bytes, 160);
+        assert_eq!(stats[1].hit_rate, Some(2.0 / 3.0));
+        assert_eq!(stats[0].hit_rate, None);
+
+        assert!(registry.clear("prices"));
+        assert!(!registry.clear("unknown"));
+        assert_eq!(prices.entries.len(), 0);
+        assert_eq!(books.entries.len(), 4);
+    }
+
+    #[test]
+    fn test_clear_is_safe_under_concurrent_reads() {
+        let registry = Arc::new(CacheRegistry::new());
+        let cache = FakeCache::new("prices", 1_000);
+        registry.register(cache.clone());
+
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let cache = cache.clone();
+                std::thread::spawn(move || {
+                    for i in 0..10_000 {
+                        cache.get(i % 1_000);
+                    }
+                })
+            })
+            .collect();
+
+        assert!(registry.clear("prices"));
+        for reader in readers {
+            reader.join().unwrap();
+        }
+
+        let stats = &registry.stats()[0];
+        assert_eq!(stats.entries, 0);
+        assert_eq!(stats.hits + stats.misses, 40_000);
+    }
+}
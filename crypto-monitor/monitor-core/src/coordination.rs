@@ -0,0 +1,357 @@
+use crate::{event::EventBuilder, EventSource, EventType, MonitorEvent, Result, SystemEventType};
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::{sync::watch, task::JoinHandle};
+use tracing::{info, warn};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CoordinationConfig {
+    pub enabled: bool,
+    // Defaults to a random id per process
+    #[serde(default)]
+    pub instance_id: Option<String>,
+    // Instances with the same group compete for one lease
+    pub group: String,
+    pub heartbeat_secs: u64,
+    // A lease not renewed for this long lets a follower take over
+    pub failover_timeout_secs: u64,
+}
+
+impl Default for CoordinationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            instance_id: None,
+            group: "trading".to_string(),
+            heartbeat_secs: 2,
+            failover_timeout_secs: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "role", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum LeadershipStatus {
+    Leader {
+        instance_id: String,
+        since: DateTime<Utc>,
+        lease_expires_at: DateTime<Utc>,
+    },
+    Follower {
+        instance_id: String,
+        // `None` when no instance holds a live lease, or the database is unreachable
+        leader_id: Option<String>,
+        lease_expires_at: Option<DateTime<Utc>>,
+    },
+}
+
+impl LeadershipStatus {
+    pub fn is_leader(&self) -> bool {
+        matches!(self, LeadershipStatus::Leader { .. })
+    }
+
+    pub fn to_event(&self) -> Option<MonitorEvent> {
+        let (event_type, message) = match self {
+            LeadershipStatus::Leader { instance_id, .. } => (
+                SystemEventType::LeadershipAcquired,
+                format!("Instance {} is now the leader", instance_id),
+            ),
+            LeadershipStatus::Follower { instance_id, leader_id, .. } => (
+                SystemEventType::LeadershipLost,
+                format!(
+                    "Instance {} is following {}",
+                    instance_id,
+                    leader_id.as_deref().unwrap_or("no leader")
+                ),
+            ),
+        };
+
+        let mut data = serde_json::to_value(self).ok()?;
+        data["message"] = serde_json::Value::String(message);
+
+        EventBuilder::new()
+            .with_source(EventSource::Monitor)
+            .with_type(EventType::System(event_type))
+            .with_data(data)
+            .build()
+    }
+}
+
+// Lease-based leader election. Each heartbeat takes a transaction-scoped advisory lock on the
+// group, so acquisitions are serialized, then renews or claims the group's lease row. Lease
+// expiry is judged by the database clock; locally the leader fences itself from when the
+// renewal started, which is never later than the expiry other instances see.
+pub struct LeaderElection {
+    config: CoordinationConfig,
+    instance_id: String,
+    pool: PgPool,
+    deadline: Mutex<Option<Instant>>,
+    status: watch::Sender<LeadershipStatus>,
+}
+
+impl LeaderElection {
+    pub fn new(config: CoordinationConfig, pool: PgPool) -> Self {
+        let instance_id = config
+            .instance_id
+            .clone()
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let status = LeadershipStatus::Follower {
+            instance_id: instance_id.clone(),
+            leader_id: None,
+            lease_expires_at: None,
+        };
+
+        Self {
+            config,
+            instance_id,
+            pool,
+            deadline: Mutex::new(None),
+            status: watch::channel(status).0,
+        }
+    }
+
+    pub fn instance_id(&self) -> &str {
+        &self.instance_id
+    }
+
+    // False as soon as the local lease runs out, even if no heartbeat noticed yet
+    pub fn is_leader(&self) -> bool {
+        self.deadline.lock().is_some_and(|deadline| Instant::now() < deadline)
+    }
+
+    pub fn status(&self) -> LeadershipStatus {
+        self.status.borrow().clone()
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<LeadershipStatus> {
+        self.status.subscribe()
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(self.config.failover_timeout_secs.max(1))
+    }
+
+    // One election round: renew or claim the lease, or learn who holds it
+    pub async fn heartbeat(&self) -> Result<LeadershipStatus> {
+        let started = Instant::now();
+        let result = self.try_acquire().await;
+
+        let status = match result {
+            Ok((true, _, expires_at)) => {
+                *self.deadline.lock() = Some(started + self.timeout());
+                let since = match self.status.borrow().clone() {
+                    LeadershipStatus::Leader { since, .. } => since,
+                    LeadershipStatus::Follower { .. } => Utc::now(),
+                };
+                LeadershipStatus::Leader {
+                    instance_id: self.instance_id.clone(),
+                    since,
+                    lease_expires_at: expires_at,
+                }
+            }
+            Ok((false, holder, expires_at)) => {
+                *self.deadline.lock() = None;
+                LeadershipStatus::Follower {
+                    instance_id: self.instance_id.clone(),
+                    leader_id: Some(holder),
+                    lease_expires_at: Some(expires_at),
+                }
+            }
+            Err(e) => {
+                // Keep leading on the lease we already have, step down once it has run out
+                if !self.is_leader() {
+                    *self.deadline.lock() = None;
+                    self.set_status(LeadershipStatus::Follower {
+                        instance_id: self.instance_id.clone(),
+                        leader_id: None,
+                        lease_expires_at: None,
+                    });
+                }
+                return Err(e);
+            }
+        };
+
+        self.set_status(status.clone());
+        Ok(status)
+    }
+
+    // (acquired, holder, lease expiry)
+    async fn try_acquire(&self) -> Result<(bool, String, DateTime<Utc>)> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+            .bind(&self.config.group)
+            .execute(&mut *tx)
+            .await?;
+
+        let current: Option<(String, DateTime<Utc>, bool)> = sqlx::query_as(
+            "SELECT holder, expires_at, expires_at > now() FROM coordination_leases WHERE name = $1",
+        )
+        .bind(&self.config.group)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if let Some((holder, expires_at, true)) = &current {
+            if holder != &self.instance_id {
+                tx.commit().await?;
+                return Ok((false, holder.clone(), *expires_at));
+            }
+        }
+
+        let (expires_at,): (DateTime<Utc>,) = sqlx::query_as(
+            "INSERT INTO coordination_leases (name, holder, acquired_at, renewed_at, expires_at) \
+             VALUES ($1, $2, now(), now(), now() + make_interval(secs => $3)) \
+             ON CONFLICT (name) DO UPDATE SET holder = EXCLUDED.holder, \
+             acquired_at = CASE WHEN coordination_leases.holder = EXCLUDED.holder \
+             THEN coordination_leases.acquired_at ELSE now() END, \
+             renewed_at = now(), expires_at = EXCLUDED.expires_at \
+             RETURNING expires_at",
+        )
+        .bind(&self.config.group)
+        .bind(&self.instance_id)
+        .bind(self.timeout().as_secs_f64())
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok((true, self.instance_id.clone(), expires_at))
+    }
+
+    fn set_status(&self, status: LeadershipStatus) {
+        let changed = self.status.borrow().is_leader() != status.is_leader();
+        if changed {
+            match &status {
+                LeadershipStatus::Leader { .. } => info!("Acquired leadership as {}", self.instance_id),
+                LeadershipStatus::Follower { leader_id, .. } => {
+                    warn!("Following {:?} as {}", leader_id, self.instance_id)
+                }
+            }
+        }
+        self.status.send_replace(status);
+    }
+
+    // Gives the lease up so a follower can take over without waiting for the timeout
+    pub async fn resign(&self) -> Result<()> {
+        *self.deadline.lock() = None;
+        sqlx::query("DELETE FROM coordination_leases WHERE name = $1 AND holder = $2")
+            .bind(&self.config.group)
+            .bind(&self.instance_id)
+            .execute(&self.pool)
+            .await?;
+        self.set_status(LeadershipStatus::Follower {
+            instance_id: self.instance_id.clone(),
+            leader_id: None,
+            lease_expires_at: None,
+        });
+        Ok(())
+    }
+
+    pub fn spawn(self: &Arc<Self>, mut shutdown: watch::Receiver<bool>) -> JoinHandle<()> {
+        let election = self.clone();
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(election.config.heartbeat_secs.max(1)));
+            loop {
+                tokio::select! {
+                    _ = shutdown.changed() => break,
+                    _ = interval.tick() => {
+                        if let Err(e) = election.heartbeat().await {
+                            warn!("Leader election heartbeat failed: {}", e);
+                        }
+                    }
+                }
+            }
+
+            if election.is_leader() {
+                if let Err(e) = election.resign().await {
+                    warn!("Failed to resign leadership: {}", e);
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn election(pool: &PgPool, instance_id: &str) -> LeaderElection {
+        LeaderElection::new(
+            CoordinationConfig {
+                enabled: true,
+                instance_id: Some(instance_id.to_string()),
+                group: "test".to_string(),
+                heartbeat_secs: 1,
+                failover_timeout_secs: 1,
+            },
+            pool.clone(),
+        )
+    }
+
+    #[test]
+    fn test_transition_events() {
+        let leader = LeadershipStatus::Leader {
+            instance_id: "a".to_string(),
+            since: Utc::now(),
+            lease_expires_at: Utc::now(),
+        };
+        let event = leader.to_event().unwrap();
+        assert!(matches!(
+            event.event_type,
+            EventType::System(SystemEventType::LeadershipAcquired)
+        ));
+        assert_eq!(event.data["role"], "leader");
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn test_single_leader_and_failover() {
+        let pool = monitor_testkit::db::migrated_pool().await;
+
+        let a = election(&pool, "a");
+        let b = election(&pool, "b");
+        assert!(a.heartbeat().await.unwrap().is_leader());
+        for _ in 0..3 {
+            let status = b.heartbeat().await.unwrap();
+            assert!(matches!(
+                status,
+                LeadershipStatus::Follower { leader_id: Some(ref id), .. } if id == "a"
+            ));
+            assert!(a.is_leader() && !b.is_leader());
+            a.heartbeat().await.unwrap();
+        }
+
+        // `a` stops renewing, as if it hung. It fences itself before `b` can take over
+        let stalled_at = Instant::now();
+        a.heartbeat().await.unwrap();
+        let mut took_over = None;
+        while took_over.is_none() && stalled_at.elapsed() < Duration::from_secs(3) {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            if b.heartbeat().await.unwrap().is_leader() {
+                took_over = Some(stalled_at.elapsed());
+            }
+            assert!(!(a.is_leader() && b.is_leader()));
+        }
+        let took_over = took_over.expect("follower did not take over");
+        assert!(took_over >= Duration::from_millis(900));
+        assert!(took_over < Duration::from_millis(1500));
+
+        // The old leader learns it lost on its next heartbeat
+        let status = a.heartbeat().await.unwrap();
+        assert!(matches!(
+            status,
+            LeadershipStatus::Follower { leader_id: Some(ref id), .. } if id == "b"
+        ));
+
+        // Resigning hands over immediately
+        b.resign().await.unwrap();
+        assert!(a.heartbeat().await.unwrap().is_leader());
+    }
+}
@@ -1,4 +1,13 @@
-use crate::{MonitorConfig, MonitorError, MonitorEvent, Result, ExchangeConfig};
+use crate::{
+    book_sync::{BookSequencing, BookSyncManager, RestSnapshotFetcher, SyncAction},
+    event::EventBuilder,
+    messaging::{FluvioConnector, Messaging},
+    open_interest::{OpenInterestCollector, RestOpenInterestFetcher},
+    ratelimit::RestRateLimiters,
+    sink::FileSink,
+    EventSource, EventType, ExchangeConfig, MarketDataType, MonitorConfig, MonitorError,
+    MonitorEvent, Result,
+};
 use barter::{
     engine::{Engine, EngineConfig},
     EngineEvent,
@@ -13,44 +22,123 @@ use barter_data::{
 };
 use barter_execution::ExecutionClient;
 use barter_instrument::InstrumentIndex;
-use fluvio::{Fluvio, FluvioConfig, Offset, RecordKey, TopicProducer};
+use fluvio::Fluvio;
 use futures::{Stream, StreamExt};
-use parking_lot::RwLock;
-use std::{collections::HashMap, sync::Arc};
-use tokio::sync::mpsc;
+use std::sync::Arc;
+use tokio::sync::{mpsc, watch};
 use tracing::{error, info, warn};
 
+// How long `stop` waits for the events already queued to be published
+const EVENT_DRAIN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+// Levels per side of the synced books handed to the book detectors
+const BOOK_EVENT_DEPTH: usize = 20;
+
 pub struct MonitorEngine {
     config: Arc<MonitorConfig>,
-    fluvio: Arc<Fluvio>,
-    producers: Arc<RwLock<HashMap<String, Arc<TopicProducer>>>>,
+    connector: Arc<FluvioConnector>,
+    messaging: Arc<Messaging>,
     engine_handle: Option<tokio::task::JoinHandle<()>>,
+    reconnect_handle: Option<tokio::task::JoinHandle<()>>,
+    open_interest_handle: Option<tokio::task::JoinHandle<()>>,
+    shutdown: watch::Sender<bool>,
     event_tx: mpsc::UnboundedSender<MonitorEvent>,
     event_rx: Option<mpsc::UnboundedReceiver<MonitorEvent>>,
+    file_sink: Option<Arc<FileSink>>,
+    rest_limiters: RestRateLimiters,
+    book_sync: Arc<BookSyncManager>,
 }
 
 impl MonitorEngine {
+    // Fails when Fluvio is unreachable unless `messaging.required` is false, in which case the
+    // engine starts degraded and keeps reconnecting in the background
     pub async fn new(config: MonitorConfig) -> Result<Self> {
-        let fluvio_config = FluvioConfig::new(&config.fluvio.endpoint);
-        let fluvio = Fluvio::connect_with_config(&fluvio_config).await?;
+        let connector = Arc::new(FluvioConnector::new(config.fluvio.clone()));
+        let messaging = Messaging::connect(config.messaging.clone(), connector.clone()).await?;
         
         let (event_tx, event_rx) = mpsc::unbounded_channel();
         
+        let file_sink = match &config.file_sink {
+            Some(sink_config) => Some(Arc::new(FileSink::spawn(sink_config.clone())?)),
+            None => None,
+        };
+        
+        let rest_limiters = RestRateLimiters::new();
+        let book_sync = Self::book_sync_manager(&config, &rest_limiters, &event_tx);
+        
         Ok(Self {
             config: Arc::new(config),
-            fluvio: Arc::new(fluvio),
-            producers: Arc::new(RwLock::new(HashMap::new())),
+            connector,
+            messaging,
             engine_handle: None,
+            reconnect_handle: None,
+            open_interest_handle: None,
+            shutdown: watch::channel(false).0,
             event_tx,
             event_rx: Some(event_rx),
+            file_sink,
+            rest_limiters,
+            book_sync,
         })
     }
     
+    // REST pollers started by the engine share these limiters with the rest of the process
+    pub fn with_rest_limiters(mut self, rest_limiters: RestRateLimiters) -> Self {
+        self.book_sync = Self::book_sync_manager(&self.config, &rest_limiters, &self.event_tx);
+        self.rest_limiters = rest_limiters;
+        self
+    }
+    
+    fn book_sync_manager(
+        config: &MonitorConfig,
+        rest_limiters: &RestRateLimiters,
+        event_tx: &mpsc::UnboundedSender<MonitorEvent>,
+    ) -> Arc<BookSyncManager> {
+        let fetcher = Arc::new(RestSnapshotFetcher::new(rest_limiters.clone()));
+        Arc::new(
+            BookSyncManager::new(config.monitoring.book_sync.clone(), fetcher)
+                .with_alert_sender(event_tx.clone()),
+        )
+    }
+    
+    pub fn book_sync(&self) -> &Arc<BookSyncManager> {
+        &self.book_sync
+    }
+    
+    // Feeds one raw L2 websocket message to its local book. Once the book is in sync its top
+    // levels are published on the orderbook topic, books that are rebuilt publish nothing
+    pub fn handle_book_message(
+        &self,
+        exchange: &str,
+        symbol: &str,
+        raw: &str,
+    ) -> Result<SyncAction> {
+        let sequencing = BookSequencing::for_exchange(exchange).ok_or_else(|| {
+            MonitorError::Configuration(format!("No order book sequencing for {}", exchange))
+        })?;
+        let message = sequencing.parse_message(raw)?;
+        let action = self.book_sync.handle_message(exchange, symbol, message)?;
+        
+        if let Some(book) = self.book_sync.order_book(exchange, symbol, BOOK_EVENT_DEPTH) {
+            let event = EventBuilder::new()
+                .with_source(EventSource::Exchange(book.exchange.clone()))
+                .with_type(EventType::MarketData(MarketDataType::OrderBook))
+                .with_data(serde_json::to_value(&book)?)
+                .build();
+            if let Some(event) = event {
+                if let Err(e) = self.event_tx.send(event) {
+                    error!("Failed to send order book event: {}", e);
+                }
+            }
+        }
+        Ok(action)
+    }
+    
     pub async fn start(&mut self) -> Result<()> {
         info!("Starting monitor engine...");
         
-        // Initialize Fluvio topics
-        self.initialize_topics().await?;
+        if self.messaging.status().is_degraded() {
+            self.reconnect_handle = Some(self.messaging.spawn_reconnect(self.shutdown.subscribe()));
+        }
         
         // Start market data collection
         self.start_market_data_collection().await?;
@@ -65,53 +153,27 @@ impl MonitorEngine {
     pub async fn stop(&mut self) -> Result<()> {
         info!("Stopping monitor engine...");
         
-        if let Some(handle) = self.engine_handle.take() {
-            handle.abort();
+        let _ = self.shutdown.send(true);
+        // Events are published whole, the processing task stops between two of them
+        if let Some(mut handle) = self.engine_handle.take() {
+            if tokio::time::timeout(EVENT_DRAIN_TIMEOUT, &mut handle).await.is_err() {
+                warn!("Event processing still busy after {:?}, aborting it", EVENT_DRAIN_TIMEOUT);
+                handle.abort();
+            }
+        }
+        if let Some(handle) = self.reconnect_handle.take() {
+            let _ = handle.await;
         }
+        if let Some(handle) = self.open_interest_handle.take() {
+            let _ = handle.await;
+        }
+        self.messaging.shutdown().await;
         
-        info!("Monitor engine stopped");
-        Ok(())
-    }
-    
-    async fn initialize_topics(&self) -> Result<()> {
-        let admin = fluvio::FluvioAdmin::connect().await?;
-        
-        let topics = vec![
-            format!("{}.market.trades", self.config.fluvio.topic_prefix),
-            format!("{}.market.orderbook", self.config.fluvio.topic_prefix),
-            format!("{}.market.candles", self.config.fluvio.topic_prefix),
-            format!("{}.anomalies", self.config.fluvio.topic_prefix),
-            format!("{}.alerts", self.config.fluvio.topic_prefix),
-            format!("{}.trades", self.config.fluvio.topic_prefix),
-        ];
-        
-        for topic in topics {
-            match admin
-                .create(
-                    topic.clone(),
-                    false,
-                    fluvio::metadata::topic::TopicSpec::new_computed(
-                        self.config.fluvio.partitions as i32,
-                        self.config.fluvio.replication_factor as i32,
-                        None,
-                    ),
-                )
-                .await
-            {
-                Ok(_) => info!("Created topic: {}", topic),
-                Err(e) => {
-                    if e.to_string().contains("already exists") {
-                        info!("Topic already exists: {}", topic);
-                    } else {
-                        return Err(MonitorError::Fluvio(e));
-                    }
-                }
-            }
-            
-            let producer = self.fluvio.topic_producer(&topic).await?;
-            self.producers.write().insert(topic, Arc::new(producer));
+        if let Some(sink) = &self.file_sink {
+            sink.shutdown().await;
         }
         
+        info!("Monitor engine stopped");
         Ok(())
     }
     
@@ -138,6 +200,19 @@ impl MonitorEngine {
             });
         }
         
+        // Open interest has no public stream on every exchange, so it is polled instead
+        if config.monitoring.open_interest.enabled {
+            let collector = OpenInterestCollector::new(
+                config.monitoring.open_interest.clone(),
+                Arc::new(RestOpenInterestFetcher::new(self.rest_limiters.clone())),
+                &config.exchanges,
+            );
+            if !collector.is_empty() {
+                self.open_interest_handle =
+                    Some(collector.spawn(event_tx.clone(), self.shutdown.subscribe()));
+            }
+        }
+        
         Ok(())
     }
     
@@ -179,66 +254,186 @@ impl MonitorEngine {
             MonitorError::Other("Event receiver already taken".to_string())
         })?;
         
-        let producers = self.producers.clone();
-        let config = self.config.clone();
-        
-        self.engine_handle = Some(tokio::spawn(async move {
-            while let Some(event) = event_rx.recv().await {
-                Self::process_event(event, &producers, &config).await;
-            }
-        }));
+        self.engine_handle = Some(tokio::spawn(Self::process_events(
+            event_rx,
+            self.messaging.clone(),
+            self.config.fluvio.topic_prefix.clone(),
+            self.file_sink.clone(),
+            self.shutdown.subscribe(),
+        )));
         
         Ok(())
     }
     
+    // Publishes events until `shutdown`, then whatever was already queued, and returns. The
+    // shutdown is only looked at between events so none is ever left half published
+    async fn process_events(
+        mut events: mpsc::UnboundedReceiver<MonitorEvent>,
+        messaging: Arc<Messaging>,
+        prefix: String,
+        file_sink: Option<Arc<FileSink>>,
+        mut shutdown: watch::Receiver<bool>,
+    ) {
+        loop {
+            let event = tokio::select! {
+                biased;
+                _ = shutdown.changed() => break,
+                event = events.recv() => match event {
+                    Some(event) => event,
+                    None => return,
+                },
+            };
+            Self::process_event(event, &messaging, &prefix, file_sink.as_deref()).await;
+        }
+        
+        // The senders outlive the engine, so this takes only what was sent before the stop
+        let mut drained = 0;
+        while let Ok(event) = events.try_recv() {
+            Self::process_event(event, &messaging, &prefix, file_sink.as_deref()).await;
+            drained += 1;
+        }
+        info!("Event processing stopped, {} queued events published", drained);
+    }
+    
     async fn process_event(
         event: MonitorEvent,
-        producers: &Arc<RwLock<HashMap<String, Arc<TopicProducer>>>>,
-        config: &MonitorConfig,
+        messaging: &Messaging,
+        prefix: &str,
+        file_sink: Option<&FileSink>,
     ) {
         // Determine topic based on event type
         let topic = match &event.event_type {
             crate::EventType::MarketData(data_type) => match data_type {
                 crate::MarketDataType::Trade => {
-                    format!("{}.market.trades", config.fluvio.topic_prefix)
+                    format!("{}.market.trades", prefix)
                 }
                 crate::MarketDataType::OrderBook => {
-                    format!("{}.market.orderbook", config.fluvio.topic_prefix)
+                    format!("{}.market.orderbook", prefix)
                 }
                 crate::MarketDataType::Candle => {
-                    format!("{}.market.candles", config.fluvio.topic_prefix)
+                    format!("{}.market.candles", prefix)
+                }
+                crate::MarketDataType::Liquidation => {
+                    format!("{}.market.liquidations", prefix)
+                }
+                crate::MarketDataType::FundingRate => {
+                    format!("{}.market.funding", prefix)
+                }
+                crate::MarketDataType::OpenInterest => {
+                    format!("{}.market.open_interest", prefix)
                 }
                 _ => return,
             },
             crate::EventType::Anomaly(_) => {
-                format!("{}.anomalies", config.fluvio.topic_prefix)
+                format!("{}.anomalies", prefix)
             }
             crate::EventType::Alert(_) => {
-                format!("{}.alerts", config.fluvio.topic_prefix)
+                format!("{}.alerts", prefix)
             }
             crate::EventType::Trade(_) => {
-                format!("{}.trades", config.fluvio.topic_prefix)
+                format!("{}.trades", prefix)
             }
             _ => return,
         };
         
-        // Send to Fluvio
-        if let Some(producer) = producers.read().get(&topic) {
-            let data = match serde_json::to_string(&event) {
-                Ok(d) => d,
-                Err(e) => {
-                    error!("Failed to serialize event: {}", e);
-                    return;
-                }
-            };
-            
-            if let Err(e) = producer.send(RecordKey::NULL, data).await {
-                error!("Failed to send event to Fluvio: {}", e);
-            }
+        if let Some(sink) = file_sink {
+            sink.publish(&topic, &event);
         }
+        
+        // Send to Fluvio, or the in-process fallback while degraded
+        messaging.publish(&topic, &event).await;
     }
     
     pub fn get_event_sender(&self) -> mpsc::UnboundedSender<MonitorEvent> {
         self.event_tx.clone()
     }
-}
\ No newline at end of file
+    
+    pub fn messaging(&self) -> &Arc<Messaging> {
+        &self.messaging
+    }
+    
+    // `None` until the bus has been reached
+    pub fn fluvio(&self) -> Option<Arc<Fluvio>> {
+        self.connector.client()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messaging::{BusConnector, EventBus, MessagingConfig};
+    use async_trait::async_trait;
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    // Takes a while over every event, counting those it started and those it finished
+    #[derive(Default)]
+    struct SlowBus {
+        started: AtomicUsize,
+        finished: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl EventBus for SlowBus {
+        async fn publish(&self, _topic: &str, _event: &MonitorEvent) -> Result<()> {
+            self.started.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            self.finished.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    struct SlowConnector(Arc<SlowBus>);
+
+    #[async_trait]
+    impl BusConnector for SlowConnector {
+        async fn connect(&self) -> Result<Arc<dyn EventBus>> {
+            Ok(self.0.clone())
+        }
+    }
+
+    fn trade(i: usize) -> MonitorEvent {
+        EventBuilder::new()
+            .with_source(EventSource::Exchange("binance".to_string()))
+            .with_type(EventType::MarketData(MarketDataType::Trade))
+            .with_data(serde_json::json!({ "seq": i }))
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_stop_mid_stream_publishes_whole_events() {
+        let bus = Arc::new(SlowBus::default());
+        let connector = Arc::new(SlowConnector(bus.clone()));
+        let messaging = Messaging::connect(MessagingConfig::default(), connector).await.unwrap();
+        let (event_tx, event_rx) = mpsc::unbounded_channel();
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let handle = tokio::spawn(MonitorEngine::process_events(
+            event_rx,
+            messaging,
+            "crypto-monitor".to_string(),
+            None,
+            shutdown_rx,
+        ));
+
+        for i in 0..5 {
+            event_tx.send(trade(i)).unwrap();
+        }
+        // Lands while the second event is being published
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(bus.started.load(Ordering::SeqCst) < 5);
+        shutdown_tx.send(true).unwrap();
+        tokio::time::timeout(Duration::from_secs(2), handle)
+            .await
+            .unwrap()
+            .unwrap();
+
+        // The one in flight was finished and the queued ones published, nothing half done
+        assert_eq!(bus.started.load(Ordering::SeqCst), 5);
+        assert_eq!(bus.finished.load(Ordering::SeqCst), 5);
+        // Returned, nothing sent from here on is picked up
+        assert!(event_tx.send(trade(5)).is_err());
+    }
+}
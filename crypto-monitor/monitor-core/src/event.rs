@@ -1,6 +1,8 @@
-use crate::{EventSource, EventType, MonitorEvent};
+use crate::{model::TradeSide, EventSource, EventType, MonitorEvent, Result};
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
+use serde_json::value::RawValue;
+use std::borrow::Cow;
 use uuid::Uuid;
 
 pub struct EventBuilder {
@@ -46,4 +48,150 @@ impl EventBuilder {
             data: self.data.unwrap_or(serde_json::Value::Null),
         })
     }
-}
\ No newline at end of file
+}
+// Borrowed view of a serialized MonitorEvent. Strings point into the record buffer unless
+// they contained escapes, and `data` stays raw JSON until a consumer asks for fields
+#[derive(Debug, Deserialize)]
+pub struct MonitorEventRef<'a> {
+    pub id: Uuid,
+    pub timestamp: DateTime<Utc>,
+    #[serde(borrow)]
+    pub source: EventSourceRef<'a>,
+    pub event_type: EventType,
+    #[serde(borrow)]
+    pub data: &'a RawValue,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub enum EventSourceRef<'a> {
+    Exchange(#[serde(borrow)] Cow<'a, str>),
+    Monitor,
+    Anomaly,
+    Trading,
+    User,
+}
+
+// The fields detection needs from a trade record's data
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TradeRef<'a> {
+    #[serde(borrow)]
+    pub symbol: Cow<'a, str>,
+    #[serde(borrow)]
+    pub exchange: Cow<'a, str>,
+    pub price: f64,
+    pub volume: f64,
+    #[serde(default)]
+    pub side: Option<TradeSide>,
+}
+
+impl<'a> MonitorEventRef<'a> {
+    // Invalid UTF-8 and malformed JSON come back as errors, never panics
+    pub fn from_slice(bytes: &'a [u8]) -> Result<Self> {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+
+    pub fn exchange(&self) -> Option<&str> {
+        match &self.source {
+            EventSourceRef::Exchange(exchange) => Some(exchange),
+            _ => None,
+        }
+    }
+
+    pub fn parse_data<T: Deserialize<'a>>(&self) -> Result<T> {
+        Ok(serde_json::from_str(self.data.get())?)
+    }
+
+    pub fn trade(&self) -> Result<TradeRef<'a>> {
+        self.parse_data()
+    }
+
+    // Only for consumers that persist or re-publish, detection works on the borrowed view
+    pub fn to_owned(&self) -> Result<MonitorEvent> {
+        Ok(MonitorEvent {
+            id: self.id,
+            timestamp: self.timestamp,
+            source: match &self.source {
+                EventSourceRef::Exchange(exchange) => EventSource::Exchange(exchange.to_string()),
+                EventSourceRef::Monitor => EventSource::Monitor,
+                EventSourceRef::Anomaly => EventSource::Anomaly,
+                EventSourceRef::Trading => EventSource::Trading,
+                EventSourceRef::User => EventSource::User,
+            },
+            event_type: self.event_type.clone(),
+            data: serde_json::from_str(self.data.get())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MarketDataType;
+
+    fn trade_event(exchange: &str, symbol: &str) -> Vec<u8> {
+        let event = EventBuilder::new()
+            .with_source(EventSource::Exchange(exchange.to_string()))
+            .with_type(EventType::MarketData(MarketDataType::Trade))
+            .with_data(serde_json::json!({
+                "exchange": exchange,
+                "symbol": symbol,
+                "price": 50000.5,
+                "volume": 0.25,
+                "side": "buy",
+            }))
+            .build()
+            .unwrap();
+        serde_json::to_vec(&event).unwrap()
+    }
+
+    #[test]
+    fn test_borrowed_event_matches_owned() {
+        let bytes = trade_event("binance", "BTC/USDT");
+        let event = MonitorEventRef::from_slice(&bytes).unwrap();
+        assert_eq!(event.exchange(), Some("binance"));
+
+        let trade = event.trade().unwrap();
+        assert!(matches!(trade.symbol, Cow::Borrowed("BTC/USDT")));
+        assert!(matches!(trade.exchange, Cow::Borrowed("binance")));
+        assert_eq!((trade.price, trade.volume), (50000.5, 0.25));
+
+        let owned = event.to_owned().unwrap();
+        let expected: MonitorEvent = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(serde_json::to_value(&owned).unwrap(), serde_json::to_value(&expected).unwrap());
+    }
+
+    #[test]
+    fn test_escaped_strings_are_unescaped() {
+        // serde_json writes control characters and quotes escaped, which can't be borrowed
+        let bytes = trade_event("bin\"ance", "BTC\n/USDT");
+        let event = MonitorEventRef::from_slice(&bytes).unwrap();
+        assert_eq!(event.exchange(), Some("bin\"ance"));
+
+        let trade = event.trade().unwrap();
+        assert!(matches!(trade.symbol, Cow::Owned(_)));
+        assert_eq!(trade.symbol, "BTC\n/USDT");
+
+        let bytes = String::from_utf8(trade_event("binance", "BTC/USDT"))
+            .unwrap()
+            .replace("\"BTC/USDT\"", "\"\\u0042TC\\/USDT\"");
+        let trade = MonitorEventRef::from_slice(bytes.as_bytes()).unwrap().trade().unwrap();
+        assert_eq!(trade.symbol, "BTC/USDT");
+    }
+
+    #[test]
+    fn test_invalid_records_are_rejected() {
+        let mut bytes = trade_event("binance", "BTC/USDT");
+        let at = bytes.windows(3).position(|w| w == b"BTC").unwrap();
+        bytes[at] = 0xff;
+        assert!(MonitorEventRef::from_slice(&bytes).is_err());
+
+        // Lone surrogate escapes are invalid UTF-16 as well
+        let bytes = String::from_utf8(trade_event("binance", "BTC/USDT"))
+            .unwrap()
+            .replace("\"binance\"", "\"\\ud800\"");
+        assert!(MonitorEventRef::from_slice(bytes.as_bytes()).is_err());
+
+        assert!(MonitorEventRef::from_slice(b"").is_err());
+        assert!(MonitorEventRef::from_slice(&trade_event("binance", "BTC/USDT")[..40]).is_err());
+    }
+}
@@ -0,0 +1,396 @@
+use crate::{
+    event::EventBuilder, AlertType, EventSource, EventType, MonitorError, MonitorEvent, Result,
+};
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::{
+    sync::{broadcast, watch},
+    task::JoinHandle,
+};
+use tracing::{info, warn};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExchangeStatusConfig {
+    pub enabled: bool,
+    pub interval_secs: u64,
+    pub timeout_secs: u64,
+    // Only exchanges with a known status feed can be polled
+    pub exchanges: Vec<String>,
+    // Stop opening positions on a venue while it reports itself degraded or down
+    pub pause_trading: bool,
+    // Base URL overrides keyed by exchange, e.g. for a proxy
+    #[serde(default)]
+    pub endpoints: HashMap<String, String>,
+}
+
+impl Default for ExchangeStatusConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: 60,
+            timeout_secs: 10,
+            exchanges: vec!["binance".to_string(), "okx".to_string()],
+            pause_trading: false,
+            endpoints: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum ExchangeHealth {
+    Healthy,
+    Degraded,
+    Down,
+}
+
+// What an exchange says about itself, as of the last successful poll
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExchangeStatus {
+    pub exchange: String,
+    pub health: ExchangeHealth,
+    // The vendor's own state, Binance's status code or OKX's maintenance state
+    pub vendor_state: String,
+    pub message: Option<String>,
+    pub checked_at: DateTime<Utc>,
+}
+
+impl ExchangeStatus {
+    pub fn is_incident(&self) -> bool {
+        self.health != ExchangeHealth::Healthy
+    }
+
+    pub fn to_event(&self) -> Option<MonitorEvent> {
+        let vendor_message = self.message.as_deref().unwrap_or(&self.vendor_state);
+        let (alert_type, message) = match self.health {
+            ExchangeHealth::Healthy => (
+                AlertType::Info,
+                format!("{} reports normal service", self.exchange),
+            ),
+            ExchangeHealth::Degraded => (
+                AlertType::Warning,
+                format!("{} reports degraded service: {}", self.exchange, vendor_message),
+            ),
+            ExchangeHealth::Down => (
+                AlertType::Critical,
+                format!("{} reports no service: {}", self.exchange, vendor_message),
+            ),
+        };
+
+        let mut data = serde_json::to_value(self).ok()?;
+        data["message"] = serde_json::Value::String(message);
+
+        EventBuilder::new()
+            .with_source(EventSource::Exchange(self.exchange.clone()))
+            .with_type(EventType::Alert(alert_type))
+            .with_data(data)
+            .build()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusFeed {
+    Binance,
+    Okx,
+}
+
+impl StatusFeed {
+    pub fn for_exchange(exchange: &str) -> Option<Self> {
+        match exchange.to_lowercase().as_str() {
+            "binance" => Some(StatusFeed::Binance),
+            "okx" => Some(StatusFeed::Okx),
+            _ => None,
+        }
+    }
+
+    fn default_base_url(&self) -> &'static str {
+        match self {
+            StatusFeed::Binance => "https://api.binance.com",
+            StatusFeed::Okx => "https://www.okx.com",
+        }
+    }
+
+    fn path(&self) -> &'static str {
+        match self {
+            StatusFeed::Binance => "/sapi/v1/system/status",
+            StatusFeed::Okx => "/api/v5/system/status",
+        }
+    }
+
+    pub fn parse(&self, exchange: &str, body: &str, now: DateTime<Utc>) -> Result<ExchangeStatus> {
+        let (health, vendor_state, message) = match self {
+            StatusFeed::Binance => {
+                let status: BinanceSystemStatus = serde_json::from_str(body)?;
+                let health = match status.status {
+                    0 => ExchangeHealth::Healthy,
+                    // 1 is system maintenance
+                    1 => ExchangeHealth::Down,
+                    _ => ExchangeHealth::Degraded,
+                };
+                (health, status.status.to_string(), Some(status.msg).filter(|m| !m.is_empty()))
+            }
+            StatusFeed::Okx => {
+                let response: OkxSystemStatus = serde_json::from_str(body)?;
+                // An API error says nothing about the exchange itself
+                if response.code != "0" {
+                    return Err(MonitorError::Other(format!(
+                        "OKX status API returned code {}: {}",
+                        response.code, response.msg
+                    )));
+                }
+                // Several maintenances can be listed at once, the worst one decides
+                response
+                    .data
+                    .iter()
+                    .map(|m| (m.health(), m))
+                    .filter(|(health, _)| *health != ExchangeHealth::Healthy)
+                    .max_by_key(|(health, _)| *health)
+                    .map(|(health, m)| (health, m.state.clone(), Some(m.describe())))
+                    .unwrap_or((ExchangeHealth::Healthy, "normal".to_string(), None))
+            }
+        };
+
+        Ok(ExchangeStatus {
+            exchange: exchange.to_lowercase(),
+            health,
+            vendor_state,
+            message,
+            checked_at: now,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct BinanceSystemStatus {
+    status: i64,
+    #[serde(default)]
+    msg: String,
+}
+
+#[derive(Deserialize)]
+struct OkxSystemStatus {
+    code: String,
+    #[serde(default)]
+    msg: String,
+    #[serde(default)]
+    data: Vec<OkxMaintenance>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OkxMaintenance {
+    title: String,
+    // scheduled, ongoing, pre_open, completed or canceled
+    state: String,
+    #[serde(default)]
+    service_type: String,
+    #[serde(default)]
+    sche_desc: String,
+}
+
+impl OkxMaintenance {
+    fn health(&self) -> ExchangeHealth {
+        match self.state.as_str() {
+            // 0 is WebSocket and 5 the whole trading service, the rest are single products or
+            // batches of accounts
+            "ongoing" if matches!(self.service_type.as_str(), "0" | "5") => ExchangeHealth::Down,
+            "ongoing" | "pre_open" => ExchangeHealth::Degraded,
+            _ => ExchangeHealth::Healthy,
+        }
+    }
+
+    fn describe(&self) -> String {
+        if self.sche_desc.is_empty() {
+            self.title.clone()
+        } else {
+            format!("{} ({})", self.title, self.sche_desc)
+        }
+    }
+}
+
+// Polls the public system status endpoints of the configured exchanges. A failed poll leaves
+// the last known status in place, it is never taken as the exchange being down; consumers
+// should stop trusting a status once it is older than `valid_for`.
+pub struct ExchangeStatusPoller {
+    config: ExchangeStatusConfig,
+    feeds: Vec<(String, StatusFeed, String)>,
+    client: reqwest::Client,
+    statuses: DashMap<String, ExchangeStatus>,
+    updates: broadcast::Sender<ExchangeStatus>,
+}
+
+impl ExchangeStatusPoller {
+    pub fn new(config: ExchangeStatusConfig) -> Result<Self> {
+        let feeds = config
+            .exchanges
+            .iter()
+            .map(|exchange| {
+                let feed = StatusFeed::for_exchange(exchange).ok_or_else(|| {
+                    MonitorError::Configuration(format!("No status feed for exchange {}", exchange))
+                })?;
+                let base_url = config
+                    .endpoints
+                    .get(exchange)
+                    .map(|url| url.trim_end_matches('/').to_string())
+                    .unwrap_or_else(|| feed.default_base_url().to_string());
+                Ok((exchange.to_lowercase(), feed, format!("{}{}", base_url, feed.path())))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs.max(1)))
+            .build()
+            .map_err(|e| MonitorError::Other(format!("Failed to build status client: {}", e)))?;
+
+        Ok(Self {
+            config,
+            feeds,
+            client,
+            statuses: DashMap::new(),
+            updates: broadcast::channel(64).0,
+        })
+    }
+
+    pub fn config(&self) -> &ExchangeStatusConfig {
+        &self.config
+    }
+
+    pub fn status(&self, exchange: &str) -> Option<ExchangeStatus> {
+        self.statuses.get(&exchange.to_lowercase()).map(|s| s.clone())
+    }
+
+    pub fn statuses(&self) -> Vec<ExchangeStatus> {
+        self.statuses.iter().map(|s| s.value().clone()).collect()
+    }
+
+    // Every successful poll, changed or not, so consumers can keep their status fresh
+    pub fn subscribe(&self) -> broadcast::Receiver<ExchangeStatus> {
+        self.updates.subscribe()
+    }
+
+    // How long a status stays authoritative without a newer successful poll
+    pub fn valid_for(&self) -> chrono::Duration {
+        chrono::Duration::seconds(self.config.interval_secs.max(1) as i64 * 3)
+    }
+
+    pub async fn poll_all(&self) {
+        for (exchange, feed, url) in &self.feeds {
+            match self.fetch(exchange, *feed, url).await {
+                Ok(status) => self.record(status),
+                Err(e) => warn!("Failed to poll {} system status, keeping last known: {}", exchange, e),
+            }
+        }
+    }
+
+    async fn fetch(&self, exchange: &str, feed: StatusFeed, url: &str) -> Result<ExchangeStatus> {
+        let body = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| MonitorError::Other(e.to_string()))?
+            .text()
+            .await
+            .map_err(|e| MonitorError::Other(e.to_string()))?;
+        feed.parse(exchange, &body, Utc::now())
+    }
+
+    fn record(&self, status: ExchangeStatus) {
+        let previous = self.statuses.insert(status.exchange.clone(), status.clone());
+        if previous.map(|p| p.health) != Some(status.health) {
+            info!("{} reports {:?} ({})", status.exchange, status.health, status.vendor_state);
+        }
+        let _ = self.updates.send(status);
+    }
+
+    pub fn spawn(self: &Arc<Self>, mut shutdown: watch::Receiver<bool>) -> JoinHandle<()> {
+        let poller = self.clone();
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(poller.config.interval_secs.max(1)));
+            loop {
+                tokio::select! {
+                    _ = shutdown.changed() => break,
+                    _ = interval.tick() => poller.poll_all().await,
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(feed: StatusFeed, fixture: &str) -> Result<ExchangeStatus> {
+        let path = format!(
+            "{}/tests/fixtures/exchange_status/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            fixture
+        );
+        let exchange = match feed {
+            StatusFeed::Binance => "binance",
+            StatusFeed::Okx => "okx",
+        };
+        feed.parse(exchange, &std::fs::read_to_string(path).unwrap(), Utc::now())
+    }
+
+    #[test]
+    fn test_binance_status_mapping() {
+        let normal = parse(StatusFeed::Binance, "binance_normal.json").unwrap();
+        assert_eq!(normal.health, ExchangeHealth::Healthy);
+        assert_eq!(normal.vendor_state, "0");
+
+        let maintenance = parse(StatusFeed::Binance, "binance_maintenance.json").unwrap();
+        assert_eq!(maintenance.health, ExchangeHealth::Down);
+        assert_eq!(maintenance.message.as_deref(), Some("system maintenance"));
+    }
+
+    #[test]
+    fn test_okx_status_mapping() {
+        let normal = parse(StatusFeed::Okx, "okx_normal.json").unwrap();
+        assert_eq!(normal.health, ExchangeHealth::Healthy);
+        assert!(normal.message.is_none());
+
+        // Upcoming and finished maintenances do not affect the exchange yet
+        let scheduled = parse(StatusFeed::Okx, "okx_scheduled.json").unwrap();
+        assert_eq!(scheduled.health, ExchangeHealth::Healthy);
+
+        let partial = parse(StatusFeed::Okx, "okx_partial.json").unwrap();
+        assert_eq!(partial.health, ExchangeHealth::Degraded);
+        assert_eq!(partial.vendor_state, "ongoing");
+
+        let down = parse(StatusFeed::Okx, "okx_trading_down.json").unwrap();
+        assert_eq!(down.health, ExchangeHealth::Down);
+        assert!(down.message.unwrap().contains("Trading service upgrade"));
+    }
+
+    #[test]
+    fn test_vendor_errors_are_not_outages() {
+        assert!(parse(StatusFeed::Okx, "okx_error.json").is_err());
+        assert!(StatusFeed::Binance.parse("binance", "<html>502</html>", Utc::now()).is_err());
+    }
+
+    #[test]
+    fn test_incident_event_carries_vendor_message() {
+        let down = parse(StatusFeed::Binance, "binance_maintenance.json").unwrap();
+        let event = down.to_event().unwrap();
+        assert!(matches!(event.event_type, EventType::Alert(AlertType::Critical)));
+        assert_eq!(event.data["message"], "binance reports no service: system maintenance");
+        assert_eq!(event.data["health"], "down");
+    }
+
+    #[test]
+    fn test_unknown_exchange_is_rejected() {
+        let config = ExchangeStatusConfig {
+            exchanges: vec!["kraken".to_string()],
+            ..Default::default()
+        };
+        assert!(ExchangeStatusPoller::new(config).is_err());
+    }
+}
@@ -0,0 +1,242 @@
+use parking_lot::RwLock;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    borrow::Borrow,
+    cell::RefCell,
+    collections::HashMap,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::Deref,
+    sync::{Arc, OnceLock},
+};
+
+// An exchange and symbol pair as a map key, the "exchange:symbol" string shared behind an
+// `Arc`. Keys are interned: the first `new` for a pair allocates, every later one for the same
+// pair is a lookup and a reference count bump, and clones never copy the string. Each thread
+// caches the keys it has asked for, so the shared interner's lock is only taken the first time
+// a thread sees a pair, not per trade. Borrows as the joined `str`, so maps keyed by it still
+// answer `get("binance:BTC/USDT")`.
+#[derive(Clone)]
+pub struct InstrumentKey {
+    joined: Arc<str>,
+    // Byte offset of the ':' between exchange and symbol
+    split: usize,
+}
+
+impl InstrumentKey {
+    pub fn new(exchange: &str, symbol: &str) -> Self {
+        LOCAL_KEYS.with(|local| local.borrow_mut().get_or_intern(exchange, symbol))
+    }
+
+    // Splits an "exchange:symbol" string at its first ':', `None` without one
+    pub fn parse(joined: &str) -> Option<Self> {
+        let (exchange, symbol) = joined.split_once(':')?;
+        Some(Self::new(exchange, symbol))
+    }
+
+    pub fn exchange(&self) -> &str {
+        &self.joined[..self.split]
+    }
+
+    pub fn symbol(&self) -> &str {
+        &self.joined[self.split + 1..]
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.joined
+    }
+}
+
+impl PartialEq for InstrumentKey {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.joined, &other.joined) || self.joined == other.joined
+    }
+}
+
+impl Eq for InstrumentKey {}
+
+// Hashes as the joined string, which `Borrow<str>` requires
+impl Hash for InstrumentKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl PartialOrd for InstrumentKey {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for InstrumentKey {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.exchange(), self.symbol()).cmp(&(other.exchange(), other.symbol()))
+    }
+}
+
+impl Borrow<str> for InstrumentKey {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+// Lets a key go wherever the `&str` APIs take "exchange:symbol"
+impl Deref for InstrumentKey {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for InstrumentKey {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Display for InstrumentKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for InstrumentKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.as_str())
+    }
+}
+
+impl Serialize for InstrumentKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for InstrumentKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let joined = String::deserialize(deserializer)?;
+        Self::parse(&joined).ok_or_else(|| {
+            serde::de::Error::custom(format!("expected \"exchange:symbol\", got {:?}", joined))
+        })
+    }
+}
+
+// Exchange, then symbol, so a lookup borrows both halves instead of joining them first
+#[derive(Default)]
+struct KeyInterner {
+    keys: RwLock<HashMap<Arc<str>, HashMap<Arc<str>, InstrumentKey>>>,
+}
+
+impl KeyInterner {
+    fn intern(&self, exchange: &str, symbol: &str) -> InstrumentKey {
+        if let Some(key) = self.keys.read().get(exchange).and_then(|s| s.get(symbol)) {
+            return key.clone();
+        }
+
+        let mut keys = self.keys.write();
+        if !keys.contains_key(exchange) {
+            keys.insert(Arc::from(exchange), HashMap::new());
+        }
+        let symbols = keys.get_mut(exchange).expect("exchange inserted above");
+        if let Some(key) = symbols.get(symbol) {
+            return key.clone();
+        }
+        let key = InstrumentKey {
+            joined: Arc::from(format!("{}:{}", exchange, symbol)),
+            split: exchange.len(),
+        };
+        symbols.insert(Arc::from(symbol), key.clone());
+        key
+    }
+
+    fn len(&self) -> usize {
+        self.keys.read().values().map(HashMap::len).sum()
+    }
+}
+
+// One thread's view of the interner, filled on the thread's first `new` for each pair. Bounded
+// by the pairs the thread handles, the same keys the interner already keeps for good
+#[derive(Default)]
+struct LocalKeys {
+    keys: HashMap<Arc<str>, HashMap<Arc<str>, InstrumentKey>>,
+}
+
+impl LocalKeys {
+    fn get_or_intern(&mut self, exchange: &str, symbol: &str) -> InstrumentKey {
+        if let Some(key) = self.keys.get(exchange).and_then(|s| s.get(symbol)) {
+            return key.clone();
+        }
+
+        let key = interner().intern(exchange, symbol);
+        if !self.keys.contains_key(exchange) {
+            self.keys.insert(Arc::from(exchange), HashMap::new());
+        }
+        let symbols = self.keys.get_mut(exchange).expect("exchange inserted above");
+        symbols.insert(Arc::from(symbol), key.clone());
+        key
+    }
+}
+
+thread_local! {
+    static LOCAL_KEYS: RefCell<LocalKeys> = RefCell::new(LocalKeys::default());
+}
+
+fn interner() -> &'static KeyInterner {
+    static INTERNER: OnceLock<KeyInterner> = OnceLock::new();
+    INTERNER.get_or_init(KeyInterner::default)
+}
+
+// Distinct pairs interned so far, the keys live for the rest of the process
+pub fn interned_keys() -> usize {
+    interner().len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys_are_interned_and_borrow_as_joined_str() {
+        let key = InstrumentKey::new("binance", "BTC/USDT");
+        let again = InstrumentKey::new("binance", "BTC/USDT");
+        assert!(Arc::ptr_eq(&key.joined, &again.joined));
+        assert_eq!(key.exchange(), "binance");
+        assert_eq!(key.symbol(), "BTC/USDT");
+        assert_eq!(key.to_string(), "binance:BTC/USDT");
+
+        let mut map = HashMap::new();
+        map.insert(key.clone(), 1);
+        assert_eq!(map.get("binance:BTC/USDT"), Some(&1));
+        assert_eq!(map.get(&InstrumentKey::new("okx", "BTC/USDT")), None);
+
+        // The split is at the first ':', symbols may hold more
+        let perp = InstrumentKey::parse("okx:BTC-USDT:SWAP").unwrap();
+        assert_eq!((perp.exchange(), perp.symbol()), ("okx", "BTC-USDT:SWAP"));
+        assert!(InstrumentKey::parse("BTC/USDT").is_none());
+    }
+
+    #[test]
+    fn test_threads_share_the_interned_key() {
+        let here = InstrumentKey::new("kraken", "BTC/USD");
+        let there = std::thread::spawn(|| {
+            // The second lookup on that thread comes from its own cache
+            let first = InstrumentKey::new("kraken", "BTC/USD");
+            let cached = InstrumentKey::new("kraken", "BTC/USD");
+            assert!(Arc::ptr_eq(&first.joined, &cached.joined));
+            first
+        })
+        .join()
+        .unwrap();
+        assert!(Arc::ptr_eq(&here.joined, &there.joined));
+    }
+
+    #[test]
+    fn test_key_round_trips_through_json() {
+        let key = InstrumentKey::new("bybit", "ETH/USDT");
+        let json = serde_json::to_string(&key).unwrap();
+        assert_eq!(json, "\"bybit:ETH/USDT\"");
+        assert_eq!(serde_json::from_str::<InstrumentKey>(&json).unwrap(), key);
+        assert!(serde_json::from_str::<InstrumentKey>("\"bybit\"").is_err());
+    }
+}
@@ -1,6 +1,20 @@
+pub mod book_metrics;
+pub mod book_sync;
+pub mod cache;
+pub mod coordination;
 pub mod engine;
 pub mod event;
+pub mod exchange_status;
+pub mod instrument;
+pub mod messaging;
 pub mod model;
+pub mod notification;
+pub mod open_interest;
+pub mod percentiles;
+pub mod ratelimit;
+pub mod router;
+pub mod scheduler;
+pub mod sink;
 pub mod storage;
 pub mod stream;
 
@@ -10,10 +24,11 @@ use barter_execution::AccountEventKind;
 use barter_instrument::InstrumentIndex;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::fmt::Debug;
+use std::{collections::HashMap, fmt::Debug};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum MonitorError {
     #[error("Fluvio error: {0}")]
     Fluvio(#[from] fluvio::FluvioError),
@@ -49,6 +64,7 @@ pub struct MonitorEvent {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub enum EventSource {
     Exchange(String),
     Monitor,
@@ -58,6 +74,7 @@ pub enum EventSource {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub enum EventType {
     MarketData(MarketDataType),
     Anomaly(AnomalyType),
@@ -67,23 +84,127 @@ pub enum EventType {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub enum MarketDataType {
     Trade,
     OrderBook,
     Candle,
     Volume,
     Liquidation,
+    FundingRate,
+    OpenInterest,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+// The default is the catch-all, for detections built from defaults
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[non_exhaustive]
 pub enum AnomalyType {
     VolumeSpike,
     PriceSpike,
     DepthImbalance,
     LargeOrder,
+    #[default]
     UnusualActivity,
+    PriceDivergence,
+    SpreadWidening,
+    LiquidationCascade,
+    FundingRate,
+    VolatilityRegime,
+    OpenInterest,
+    CorrelationBreak,
+    RateOfChange,
+    BollingerBreach,
 }
 
+impl AnomalyType {
+    // Every variant in declaration order, for validating and listing names
+    pub const ALL: &'static [AnomalyType] = &[
+        AnomalyType::VolumeSpike,
+        AnomalyType::PriceSpike,
+        AnomalyType::DepthImbalance,
+        AnomalyType::LargeOrder,
+        AnomalyType::UnusualActivity,
+        AnomalyType::PriceDivergence,
+        AnomalyType::SpreadWidening,
+        AnomalyType::LiquidationCascade,
+        AnomalyType::FundingRate,
+        AnomalyType::VolatilityRegime,
+        AnomalyType::OpenInterest,
+        AnomalyType::CorrelationBreak,
+        AnomalyType::RateOfChange,
+        AnomalyType::BollingerBreach,
+    ];
+
+    // The name it serializes to
+    pub fn name(&self) -> &'static str {
+        match self {
+            AnomalyType::VolumeSpike => "VolumeSpike",
+            AnomalyType::PriceSpike => "PriceSpike",
+            AnomalyType::DepthImbalance => "DepthImbalance",
+            AnomalyType::LargeOrder => "LargeOrder",
+            AnomalyType::UnusualActivity => "UnusualActivity",
+            AnomalyType::PriceDivergence => "PriceDivergence",
+            AnomalyType::SpreadWidening => "SpreadWidening",
+            AnomalyType::LiquidationCascade => "LiquidationCascade",
+            AnomalyType::FundingRate => "FundingRate",
+            AnomalyType::VolatilityRegime => "VolatilityRegime",
+            AnomalyType::OpenInterest => "OpenInterest",
+            AnomalyType::CorrelationBreak => "CorrelationBreak",
+            AnomalyType::RateOfChange => "RateOfChange",
+            AnomalyType::BollingerBreach => "BollingerBreach",
+        }
+    }
+
+    // Looks a variant up by name, ignoring case
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().find(|t| t.name().eq_ignore_ascii_case(name)).cloned()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum AnomalySeverity {
+    #[default]
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl AnomalySeverity {
+    // Raised by `tiers` levels, capped at Critical
+    pub fn escalated(&self, tiers: usize) -> Self {
+        const ORDER: [AnomalySeverity; 4] = [
+            AnomalySeverity::Low,
+            AnomalySeverity::Medium,
+            AnomalySeverity::High,
+            AnomalySeverity::Critical,
+        ];
+        let index = ORDER.iter().position(|s| s == self).unwrap_or(0);
+        ORDER[(index + tiers).min(ORDER.len() - 1)].clone()
+    }
+
+    // Lowest confidence that maps to this severity, for detectors that grade severity directly
+    pub fn confidence_floor(&self) -> f64 {
+        match self {
+            AnomalySeverity::Low => 0.0,
+            AnomalySeverity::Medium => MEDIUM_CONFIDENCE,
+            AnomalySeverity::High => HIGH_CONFIDENCE,
+            AnomalySeverity::Critical => CRITICAL_CONFIDENCE,
+        }
+    }
+
+    // Confidence raised to this severity's floor, for detectors graded on their own bands so a
+    // High detection never reports the confidence of a Medium one
+    pub fn floor_confidence(&self, confidence: f64) -> f64 {
+        confidence.max(self.confidence_floor())
+    }
+}
+
+const MEDIUM_CONFIDENCE: f64 = 0.5;
+const HIGH_CONFIDENCE: f64 = 0.75;
+const CRITICAL_CONFIDENCE: f64 = 0.9;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum TradeEventType {
     OrderPlaced,
@@ -93,7 +214,8 @@ pub enum TradeEventType {
     PositionClosed,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
 pub enum AlertType {
     Info,
     Warning,
@@ -101,23 +223,43 @@ pub enum AlertType {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub enum SystemEventType {
     Started,
     Stopped,
     Connected,
     Disconnected,
     Error,
+    WarmupCompleted,
+    LeadershipAcquired,
+    LeadershipLost,
+    // Local positions disagreed with the exchange account and were brought in line
+    PositionDiscrepancy,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct MonitorConfig {
     pub exchanges: Vec<ExchangeConfig>,
     pub fluvio: FluvioConfig,
     pub database: DatabaseConfig,
     pub monitoring: MonitoringConfig,
+    #[serde(default)]
+    pub file_sink: Option<sink::FileSinkConfig>,
+    #[serde(default)]
+    pub messaging: messaging::MessagingConfig,
+    #[serde(default)]
+    pub coordination: coordination::CoordinationConfig,
+    #[serde(default)]
+    pub api: ApiConfig,
+    pub notification: notification::NotificationConfig,
+    // Free-form settings for embedders, the only place unknown keys are accepted
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub extensions: HashMap<String, serde_json::Value>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct ExchangeConfig {
     pub name: String,
     pub enabled: bool,
@@ -126,14 +268,23 @@ pub struct ExchangeConfig {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct FluvioConfig {
     pub endpoint: String,
     pub topic_prefix: String,
     pub partitions: u32,
     pub replication_factor: u32,
+    // Overrides of the default delivery semantics, keyed by full topic name
+    #[serde(default)]
+    pub topic_semantics: HashMap<String, router::DeliverySemantics>,
+    // File the consumers' offsets are persisted to, so a restart resumes where they stopped
+    // instead of at the end of each topic
+    #[serde(default)]
+    pub checkpoint_path: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct DatabaseConfig {
     pub url: String,
     pub max_connections: u32,
@@ -141,21 +292,758 @@ pub struct DatabaseConfig {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct MonitoringConfig {
     pub anomaly_detection: AnomalyConfig,
     pub alerting: AlertConfig,
     pub trading: TradingConfig,
+    #[serde(default)]
+    pub warmup: WarmupConfig,
+    #[serde(default)]
+    pub activity: ActivityConfig,
+    #[serde(default)]
+    pub iceberg: IcebergConfig,
+    #[serde(default)]
+    pub analyzer: MarketAnalyzerConfig,
+    #[serde(default)]
+    pub liquidation: LiquidationConfig,
+    #[serde(default)]
+    pub funding: FundingRateConfig,
+    #[serde(default)]
+    pub open_interest: OpenInterestConfig,
+    #[serde(default)]
+    pub correlation_break: CorrelationBreakConfig,
+    #[serde(default)]
+    pub escalation: EscalationConfig,
+    #[serde(default)]
+    pub correlation: CorrelationConfig,
+    #[serde(default)]
+    pub suppression: AlertSuppressionConfig,
+    #[serde(default)]
+    pub detector_state: DetectorStateConfig,
+    #[serde(default)]
+    pub book_metrics: book_metrics::BookMetricsConfig,
+    #[serde(default)]
+    pub book_sync: book_sync::BookSyncConfig,
+    #[serde(default)]
+    pub ingest: IngestConfig,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiConfig {
+    // Keys sent as x-api-key that may change the trading config, close positions and halt or
+    // resume trading. Without any those routes refuse every request
+    #[serde(default)]
+    pub trading_api_keys: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IngestConfig {
+    // Accept detections published by other systems over the API and the anomalies topic
+    pub enabled: bool,
+    // Only these sources are accepted, each with the API keys allowed to publish as it
+    #[serde(default)]
+    pub sources: Vec<IngestSourceConfig>,
+    // Recently ingested detection ids remembered to drop redeliveries
+    pub dedup_capacity: usize,
+}
+
+impl Default for IngestConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sources: Vec::new(),
+            dedup_capacity: 10_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IngestSourceConfig {
+    pub name: String,
+    #[serde(default)]
+    pub api_keys: Vec<String>,
+    // Detections accepted from the source per minute, over both paths
+    pub max_per_minute: u32,
+}
+
+// Flash crash, pump and dump and manipulation checks on each pair's trades
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MarketAnalyzerConfig {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IcebergConfig {
+    pub enabled: bool,
+    // Fills within this many bps of a level count as the same level, unless the symbol has a tick size
+    pub price_tolerance_bps: f64,
+    #[serde(default)]
+    pub tick_sizes: HashMap<String, f64>,
+    pub min_fills: usize,
+    // Executed size at the level must exceed this multiple of the largest displayed size
+    pub hidden_size_multiple: f64,
+    pub window_secs: u64,
+}
+
+impl Default for IcebergConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            price_tolerance_bps: 1.0,
+            tick_sizes: HashMap::new(),
+            min_fills: 5,
+            hidden_size_multiple: 3.0,
+            window_secs: 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LiquidationConfig {
+    pub enabled: bool,
+    // Liquidation notional is summed per exchange and symbol over buckets of this length
+    pub bucket_secs: u64,
+    // A bucket at or above this notional alerts regardless of history
+    pub notional_threshold: f64,
+    // Z-score of a bucket against the completed buckets before it
+    pub z_score_threshold: f64,
+    pub history_buckets: usize,
+    // Completed buckets, quiet ones included, needed before the z-score applies
+    pub min_buckets: usize,
+    // Severity by multiples of whichever threshold fired
+    #[serde(default = "default_liquidation_severity")]
+    pub severity: SeverityThresholds,
+}
+
+fn default_liquidation_severity() -> SeverityThresholds {
+    SeverityThresholds::new(1.0, 2.0, 3.0).expect("liquidation bands increase")
+}
+
+impl Default for LiquidationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            bucket_secs: 60,
+            notional_threshold: 5_000_000.0,
+            z_score_threshold: 4.0,
+            history_buckets: 60,
+            min_buckets: 10,
+            severity: default_liquidation_severity(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FundingRateConfig {
+    pub enabled: bool,
+    // Bounds on the predicted rate per funding interval, as a fraction (0.001 is 0.1%)
+    pub max_rate: f64,
+    pub min_rate: f64,
+    // Change in the predicted rate between two updates that alerts on its own
+    pub max_change: f64,
+    // Severity by the annualized rate, in percent
+    #[serde(default = "default_funding_severity")]
+    pub severity: SeverityThresholds,
+}
+
+fn default_funding_severity() -> SeverityThresholds {
+    SeverityThresholds::new(20.0, 50.0, 100.0).expect("funding bands increase")
+}
+
+impl Default for FundingRateConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_rate: 0.001,
+            min_rate: -0.001,
+            max_change: 0.0005,
+            severity: default_funding_severity(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OpenInterestConfig {
+    pub enabled: bool,
+    // How often each instrument subscribed to `open_interest` is polled
+    pub poll_interval_secs: u64,
+    // Change in open interest over `window_secs` that alerts, in percent
+    pub window_secs: u64,
+    pub change_pct: f64,
+    // Price moves under this over the same window count as flat, in percent
+    pub price_change_pct: f64,
+}
+
+impl Default for OpenInterestConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            poll_interval_secs: 60,
+            window_secs: 900,
+            change_pct: 5.0,
+            price_change_pct: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CorrelationBreakConfig {
+    pub enabled: bool,
+    #[serde(default)]
+    pub pairs: Vec<CorrelationPairConfig>,
+    // Trades of the two series at most this far apart are paired
+    pub alignment_tolerance_ms: u64,
+    // Trades kept per series
+    pub max_samples: usize,
+    // Returns of consecutive aligned pairs in each correlation reading
+    pub window: usize,
+    // Minimum time between readings of a pair
+    pub reading_interval_secs: u64,
+    // Readings outside a break whose mean is the historical correlation
+    pub baseline_readings: usize,
+    pub min_baseline_readings: usize,
+    // Only pairs whose historical correlation is at least this can break
+    pub min_historical_correlation: f64,
+    // Readings below this are a break, which alerts once it has lasted `min_duration_secs`
+    pub break_below: f64,
+    pub min_duration_secs: u64,
+}
+
+impl Default for CorrelationBreakConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            pairs: Vec::new(),
+            alignment_tolerance_ms: 1000,
+            max_samples: 5000,
+            window: 120,
+            reading_interval_secs: 10,
+            baseline_readings: 360,
+            min_baseline_readings: 30,
+            min_historical_correlation: 0.6,
+            break_below: 0.2,
+            min_duration_secs: 300,
+        }
+    }
+}
+
+// Two series as "exchange:symbol". Breaks are reported on the follower
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CorrelationPairConfig {
+    pub leader: String,
+    pub follower: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EscalationConfig {
+    pub enabled: bool,
+    // Detections of one type on one symbol are counted over this window
+    pub window_secs: u64,
+    // Counts, including the current detection, that raise severity by one and two tiers
+    pub first_tier_count: usize,
+    pub second_tier_count: usize,
+    // Symbol and type pairs tracked at once, the least recently seen is dropped beyond it
+    pub max_tracked: usize,
+}
+
+impl Default for EscalationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window_secs: 600,
+            first_tier_count: 3,
+            second_tier_count: 5,
+            max_tracked: 10_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CorrelationConfig {
+    pub enabled: bool,
+    // The first detection on an exchange and symbol is acted on as it arrives, those arriving
+    // within this long after it are notified and traded on together with it as one group
+    pub window_secs: u64,
+}
+
+impl Default for CorrelationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window_secs: 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AlertSuppressionConfig {
+    pub enabled: bool,
+    // Minimum time between detections of one type on one exchange and symbol
+    pub min_interval_secs: u64,
+    // Past the interval, detections no more severe than the last one sent and whose current
+    // value moved less than this percentage from it are still dropped, 0 disables
+    pub min_metric_delta_pct: f64,
+    // How long after the last detection sent the metric comparison applies
+    pub dedup_window_secs: u64,
+    // Critical detections skip both checks
+    pub critical_bypass: bool,
+    // Exchange, symbol and type triples tracked at once, the least recently sent is dropped
+    pub max_tracked: usize,
+}
+
+impl Default for AlertSuppressionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_interval_secs: 60,
+            min_metric_delta_pct: 5.0,
+            dedup_window_secs: 300,
+            critical_bypass: true,
+            max_tracked: 10_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DetectorStateConfig {
+    // Save detector windows to the database and restore them on startup. Saves older than
+    // `anomaly_detection.lookback_window_minutes` are discarded
+    pub enabled: bool,
+    pub save_interval_secs: u64,
+}
+
+impl Default for DetectorStateConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            save_interval_secs: 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ActivityConfig {
+    // Seconds without any message from an exchange before its feed counts as stale
+    pub feed_stale_secs: u64,
+    #[serde(default)]
+    pub expected: Vec<ExpectedActivityConfig>,
+    #[serde(default)]
+    pub maintenance_windows: Vec<MaintenanceWindow>,
+    // Incidents the exchanges report themselves are treated like maintenance windows
+    #[serde(default)]
+    pub exchange_status: exchange_status::ExchangeStatusConfig,
+}
+
+impl Default for ActivityConfig {
+    fn default() -> Self {
+        Self {
+            feed_stale_secs: 30,
+            expected: Vec::new(),
+            maintenance_windows: Vec::new(),
+            exchange_status: exchange_status::ExchangeStatusConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExpectedActivityConfig {
+    pub exchange: String,
+    pub symbol: String,
+    pub min_trades_per_hour: usize,
+    pub max_silent_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MaintenanceWindow {
+    // `None` applies the window to every exchange
+    #[serde(default)]
+    pub exchange: Option<String>,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WarmupConfig {
+    // Seconds after startup during which detections are flagged and not acted on
+    pub duration_secs: u64,
+    // Samples each symbol must have seen before its detections are acted on
+    pub min_samples_per_symbol: usize,
+}
+
+impl Default for WarmupConfig {
+    fn default() -> Self {
+        Self {
+            duration_secs: 300,
+            min_samples_per_symbol: 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct AnomalyConfig {
     pub volume_threshold_multiplier: f64,
     pub price_change_percentage: f64,
     pub lookback_window_minutes: u32,
     pub min_samples: usize,
+    // Baseline each series is judged against
+    #[serde(default)]
+    pub volume_detector: BaselineKind,
+    #[serde(default)]
+    pub price_detector: BaselineKind,
+    // How each "window" baseline measures centre and spread, replacing the detector default
+    // when set
+    #[serde(default)]
+    pub volume_estimator: Option<WindowEstimator>,
+    #[serde(default)]
+    pub price_estimator: Option<WindowEstimator>,
+    // Per-deployment severity bands, replacing the detector defaults when set
+    #[serde(default)]
+    pub volume_z_score_severity: Option<SeverityThresholds>,
+    #[serde(default)]
+    pub price_percentage_severity: Option<SeverityThresholds>,
+    #[serde(default)]
+    pub price_z_score_severity: Option<SeverityThresholds>,
+    // Judges volume against the same UTC hour on previous days, replacing the detector default
+    // when set
+    #[serde(default)]
+    pub volume_seasonality: Option<SeasonalityConfig>,
+    // Settings for the metrics set to the "ewma" baseline, replacing the detector default when set
+    #[serde(default)]
+    pub ewma: Option<EwmaAnomalyConfig>,
+    // Order book update rate spikes, off unless set
+    #[serde(default)]
+    pub update_rate: Option<UpdateRateConfig>,
+    // Price change over fixed horizons, off unless set
+    #[serde(default)]
+    pub rate_of_change: Option<RateOfChangeConfig>,
+    // Realized volatility regime changes, off unless set
+    #[serde(default)]
+    pub volatility: Option<VolatilityRegimeConfig>,
+    // Volume and range of candles built from trades, off unless set
+    #[serde(default)]
+    pub candles: Option<CandleConfig>,
+    // Prices closing outside the Bollinger bands, off unless set
+    #[serde(default)]
+    pub bollinger: Option<BollingerConfig>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SeasonalityConfig {
+    pub enabled: bool,
+    // Previous days an hour needs samples from before its own baseline replaces the window
+    pub min_days: usize,
+    // Days of history kept for each hour
+    pub max_days: usize,
+}
+
+impl Default for SeasonalityConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_days: 7,
+            max_days: 28,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateRateConfig {
+    // Per-second update counts whose mean is the baseline rate
+    pub baseline_secs: usize,
+    // Seconds of history needed before the baseline is trusted
+    pub min_baseline_secs: usize,
+    // Updates within the current second above the baseline rate times this are a spike
+    pub spike_multiple: f64,
+    // Rates below this never alert, however quiet the baseline
+    pub min_updates_per_sec: f64,
+}
+
+impl Default for UpdateRateConfig {
+    fn default() -> Self {
+        Self {
+            baseline_secs: 300,
+            min_baseline_secs: 60,
+            spike_multiple: 5.0,
+            min_updates_per_sec: 20.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EwmaAnomalyConfig {
+    // Samples after which an observation's weight in the baseline has halved
+    pub half_life_samples: f64,
+    // Deviation from the baseline, in baseline standard deviations, that's flagged
+    pub k_sigma: f64,
+    pub min_samples: usize,
+    // Severity by z-score against the baseline
+    #[serde(default = "default_ewma_severity")]
+    pub severity: SeverityThresholds,
+}
+
+fn default_ewma_severity() -> SeverityThresholds {
+    SeverityThresholds::new(3.0, 4.0, 5.0).expect("ewma bands increase")
+}
+
+impl Default for EwmaAnomalyConfig {
+    fn default() -> Self {
+        Self {
+            half_life_samples: 20.0,
+            k_sigma: 3.0,
+            min_samples: 30,
+            severity: default_ewma_severity(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VolatilityRegimeConfig {
+    // Log returns in each realized volatility reading
+    pub window_size: usize,
+    // Past readings whose median is the baseline
+    pub baseline_size: usize,
+    // Readings needed before the median is trusted
+    pub min_baseline: usize,
+    // Volatility above the median times this is an expansion, below the median over it a
+    // contraction
+    pub change_multiple: f64,
+}
+
+impl Default for VolatilityRegimeConfig {
+    fn default() -> Self {
+        Self {
+            window_size: 30,
+            baseline_size: 300,
+            min_baseline: 60,
+            change_multiple: 2.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateOfChangeConfig {
+    // Each horizon gets its own detector and alerts independently
+    pub horizons_secs: Vec<u64>,
+    // Absolute percentage change over a horizon that alerts
+    pub percentage_threshold: f64,
+    // How much older than the horizon the reference price may be, as a percentage of it
+    pub reference_tolerance_pct: f64,
+    // Prices kept per horizon. Ticks closer together than the horizon over this are thinned to
+    // the latest, so busy symbols don't grow the window
+    #[serde(default = "default_rate_of_change_max_samples")]
+    pub max_samples: usize,
+}
+
+fn default_rate_of_change_max_samples() -> usize {
+    1_000
+}
+
+impl Default for RateOfChangeConfig {
+    fn default() -> Self {
+        Self {
+            horizons_secs: vec![60, 300, 900],
+            percentage_threshold: 3.0,
+            reference_tolerance_pct: 10.0,
+            max_samples: default_rate_of_change_max_samples(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CandleConfig {
+    // Candle lengths built from trades, each judged separately
+    pub intervals_secs: Vec<u64>,
+    // How long after a candle ends trades for it are still merged in
+    pub late_tolerance_secs: u64,
+    #[serde(default)]
+    pub volume: CandleVolumeConfig,
+    #[serde(default)]
+    pub range: CandleRangeConfig,
+}
+
+impl Default for CandleConfig {
+    fn default() -> Self {
+        Self {
+            intervals_secs: vec![60],
+            late_tolerance_secs: 2,
+            volume: CandleVolumeConfig::default(),
+            range: CandleRangeConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CandleVolumeConfig {
+    // Volume as a multiple of the average over the previous candles that alerts
+    pub volume_multiple: f64,
+    pub lookback: usize,
+    pub min_candles: usize,
+}
+
+impl Default for CandleVolumeConfig {
+    fn default() -> Self {
+        Self {
+            volume_multiple: 5.0,
+            lookback: 20,
+            min_candles: 10,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CandleRangeConfig {
+    // High to low range, relative to the open, as a multiple of the average that alerts
+    pub range_multiple: f64,
+    pub lookback: usize,
+    pub min_candles: usize,
+}
+
+impl Default for CandleRangeConfig {
+    fn default() -> Self {
+        Self {
+            range_multiple: 3.0,
+            lookback: 20,
+            min_candles: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BollingerConfig {
+    // Prices in the window the bands are drawn from
+    pub period: usize,
+    // Band distance from the mean, in standard deviations
+    pub multiplier: f64,
+    // Prices in a row outside the same band before it alerts
+    pub consecutive: usize,
+    // Past band widths the current one is ranked against
+    pub width_history: usize,
+}
+
+impl Default for BollingerConfig {
+    fn default() -> Self {
+        Self {
+            period: 20,
+            multiplier: 2.0,
+            consecutive: 3,
+            width_history: 500,
+        }
+    }
+}
+
+// Values of a detector's measure (a z-score, a percent move) from which a detection is Medium,
+// High and Critical, anything under `medium` being Low. Rejected unless strictly increasing
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "SeverityBands")]
+pub struct SeverityThresholds {
+    medium: f64,
+    high: f64,
+    critical: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SeverityBands {
+    medium: f64,
+    high: f64,
+    critical: f64,
+}
+
+impl TryFrom<SeverityBands> for SeverityThresholds {
+    type Error = MonitorError;
+
+    fn try_from(bands: SeverityBands) -> Result<Self> {
+        Self::new(bands.medium, bands.high, bands.critical)
+    }
+}
+
+impl SeverityThresholds {
+    pub fn new(medium: f64, high: f64, critical: f64) -> Result<Self> {
+        if ![medium, high, critical].iter().all(|v| v.is_finite() && *v >= 0.0) {
+            return Err(MonitorError::Configuration(format!(
+                "Severity thresholds must be finite and non-negative, got {}/{}/{}",
+                medium, high, critical
+            )));
+        }
+        if !(medium < high && high < critical) {
+            return Err(MonitorError::Configuration(format!(
+                "Severity thresholds must increase from medium to critical, got {}/{}/{}",
+                medium, high, critical
+            )));
+        }
+        Ok(Self {
+            medium,
+            high,
+            critical,
+        })
+    }
+
+    pub fn medium(&self) -> f64 {
+        self.medium
+    }
+
+    pub fn high(&self) -> f64 {
+        self.high
+    }
+
+    pub fn critical(&self) -> f64 {
+        self.critical
+    }
+}
+
+// How a window baseline measures centre and spread
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowEstimator {
+    // Mean and standard deviation, judged by z-score
+    #[default]
+    MeanStd,
+    // Median and median absolute deviation, judged by modified z-score, so outliers already in
+    // the window barely move the baseline
+    MedianMad,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BaselineKind {
+    // Mean and standard deviation over a fixed window of recent samples
+    #[default]
+    Window,
+    // Exponentially weighted mean and variance that adapt to a new regime
+    Ewma,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct AlertConfig {
     pub telegram_enabled: bool,
     pub wechat_enabled: bool,
@@ -164,10 +1052,944 @@ pub struct AlertConfig {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct TradingConfig {
     pub auto_trading_enabled: bool,
     pub max_position_size: f64,
     pub risk_percentage: f64,
     pub stop_loss_percentage: f64,
     pub take_profit_percentage: f64,
+    // Portfolio-wide caps, exposures are notional in the quote currency
+    #[serde(default)]
+    pub max_open_positions: Option<usize>,
+    #[serde(default)]
+    pub max_exposure_per_symbol: Option<f64>,
+    #[serde(default)]
+    pub max_total_exposure: Option<f64>,
+    #[serde(default = "default_stale_position_threshold_secs")]
+    pub stale_position_threshold_secs: u64,
+    #[serde(default)]
+    pub stale_position_fallback: StalePositionFallback,
+    // Positions still open this long after entry are closed at market
+    #[serde(default)]
+    pub max_holding_minutes: Option<u64>,
+    // Whether a sell signal may open a short, usually set per exchange or symbol in `overrides`
+    #[serde(default)]
+    pub instrument_kind: InstrumentKind,
+    // Also rest the stop-loss and take-profit on the exchange, a stop-market and a limit
+    // order that cancel each other, so positions stay protected while the monitor is down
+    #[serde(default)]
+    pub exchange_exit_orders: bool,
+    // How long the price may stay through a resting exit's level without its fill before the
+    // exit is taken locally instead
+    #[serde(default = "default_exit_fill_grace_secs")]
+    pub exit_fill_grace_secs: u64,
+    #[serde(default)]
+    pub cooldown: CooldownConfig,
+    #[serde(default)]
+    pub liquidity: LiquidityConfig,
+    #[serde(default)]
+    pub order_placement: OrderPlacementConfig,
+    #[serde(default)]
+    pub dca: DcaConfig,
+    #[serde(default)]
+    pub retry: OrderRetryConfig,
+    #[serde(default)]
+    pub sizing: PositionSizingConfig,
+    #[serde(default)]
+    pub risk_manager: RiskManagerKind,
+    #[serde(default)]
+    pub volatility_target: VolatilityTargetConfig,
+    #[serde(default)]
+    pub scaling: PositionScalingConfig,
+    #[serde(default)]
+    pub circuit_breaker: CircuitBreakerConfig,
+    #[serde(default)]
+    pub persistence: TradingPersistenceConfig,
+    #[serde(default)]
+    pub reconciliation: ReconciliationConfig,
+    #[serde(default)]
+    pub journal: TradeJournalConfig,
+    #[serde(default)]
+    pub shutdown: TradingShutdownConfig,
+    #[serde(default)]
+    pub portfolio: PortfolioConfig,
+    #[serde(default)]
+    pub paper: PaperTradingConfig,
+    // Where orders go outside dry runs
+    #[serde(default)]
+    pub venue: ExecutionVenueConfig,
+    // Expected fees and slippage, charged in sizing and realized PnL
+    #[serde(default)]
+    pub costs: TradingCostsConfig,
+    // Lookback of the market context's price change, as seen by momentum and the rules
+    #[serde(default = "default_price_change_window_secs")]
+    pub price_change_window_secs: u64,
+    // Built-in strategy by name (anomaly | rules | mean_reversion | momentum), overridden by `ensemble`
+    #[serde(default)]
+    pub strategy: Option<String>,
+    #[serde(default)]
+    pub mean_reversion: MeanReversionConfig,
+    #[serde(default)]
+    pub momentum: MomentumConfig,
+    // Runs several strategies side by side when `strategies` is not empty
+    #[serde(default)]
+    pub ensemble: EnsembleConfig,
+    // Declarative entry rules, used instead of the built-in anomaly strategy when present
+    #[serde(default)]
+    pub rules: Vec<StrategyRuleConfig>,
+    // Per-exchange and per-market values over the global ones above
+    #[serde(default)]
+    pub overrides: TradingOverrides,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TradingOverrides {
+    // Keyed by exchange, e.g. "bybit"
+    #[serde(default)]
+    pub exchanges: HashMap<String, TradingOverride>,
+    // Keyed by "exchange:symbol", e.g. "binance:BTC/USDT", applied over the exchange's
+    #[serde(default)]
+    pub symbols: HashMap<String, TradingOverride>,
+}
+
+// Unset fields fall through to the next layer down
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TradingOverride {
+    #[serde(default)]
+    pub auto_trading_enabled: Option<bool>,
+    #[serde(default)]
+    pub max_position_size: Option<f64>,
+    #[serde(default)]
+    pub risk_percentage: Option<f64>,
+    #[serde(default)]
+    pub stop_loss_percentage: Option<f64>,
+    #[serde(default)]
+    pub take_profit_percentage: Option<f64>,
+    #[serde(default)]
+    pub max_holding_minutes: Option<u64>,
+    #[serde(default)]
+    pub instrument_kind: Option<InstrumentKind>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskManagerKind {
+    // Fixed-percentage sizing and exits
+    #[default]
+    Simple,
+    // Sized and exited by each market's realized volatility, see `volatility_target`
+    VolatilityTarget,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstrumentKind {
+    // Sell signals only close an open long, there is no inventory to short
+    Spot,
+    // Sell signals open shorts
+    #[default]
+    Perpetual,
+}
+
+// What a signal does on a symbol that already has an open position
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PositionScalingConfig {
+    #[serde(default)]
+    pub mode: ScalingMode,
+    // Adds allowed on top of the initial entry when pyramiding
+    #[serde(default = "default_max_adds")]
+    pub max_adds: u32,
+    // Each add is this fraction of a regular entry's size
+    #[serde(default = "default_add_size_fraction")]
+    pub add_size_fraction: f64,
+    // Only add to positions showing an unrealized profit
+    #[serde(default = "default_add_to_winners_only")]
+    pub winners_only: bool,
+}
+
+impl Default for PositionScalingConfig {
+    fn default() -> Self {
+        Self {
+            mode: ScalingMode::default(),
+            max_adds: default_max_adds(),
+            add_size_fraction: default_add_size_fraction(),
+            winners_only: default_add_to_winners_only(),
+        }
+    }
+}
+
+fn default_max_adds() -> u32 {
+    2
+}
+
+fn default_add_size_fraction() -> f64 {
+    0.5
+}
+
+fn default_add_to_winners_only() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScalingMode {
+    // Ignore the signal and keep the open position
+    #[default]
+    Reject,
+    // Close the open position, then enter on the signal
+    Replace,
+    // Add to the open position when the signal is on its side
+    Pyramid,
+}
+
+// Halts new entries until an explicit resume once any limit is hit. Unset limits are not checked
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CircuitBreakerConfig {
+    // Realized loss over the trailing 24 hours, in the quote currency
+    #[serde(default)]
+    pub max_daily_loss: Option<f64>,
+    // Same loss as a percentage of realized equity before the window's trades
+    #[serde(default)]
+    pub max_daily_loss_pct: Option<f64>,
+    // Closed trades in a row that lost money
+    #[serde(default)]
+    pub max_consecutive_losses: Option<u32>,
+    // Positions opened over the trailing hour
+    #[serde(default)]
+    pub max_positions_per_hour: Option<u32>,
+}
+
+// Positions and executed orders written to Postgres, open positions reloaded at startup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TradingPersistenceConfig {
+    #[serde(default = "default_trading_persistence_enabled")]
+    pub enabled: bool,
+    // Price-driven updates of an open position are written at most this often
+    #[serde(default = "default_position_update_interval_secs")]
+    pub update_interval_secs: u64,
+}
+
+impl Default for TradingPersistenceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_trading_persistence_enabled(),
+            update_interval_secs: default_position_update_interval_secs(),
+        }
+    }
+}
+
+fn default_trading_persistence_enabled() -> bool {
+    true
+}
+
+fn default_position_update_interval_secs() -> u64 {
+    5
+}
+
+// Open positions checked against what the exchange account holds, at startup and then every
+// `interval_secs`. Runs only when the trader has an account source to ask
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReconciliationConfig {
+    #[serde(default = "default_reconciliation_interval_secs")]
+    pub interval_secs: u64,
+    // Sizes within this many percent of each other are the same position
+    #[serde(default = "default_reconciliation_quantity_tolerance_pct")]
+    pub quantity_tolerance_pct: f64,
+}
+
+impl Default for ReconciliationConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: default_reconciliation_interval_secs(),
+            quantity_tolerance_pct: default_reconciliation_quantity_tolerance_pct(),
+        }
+    }
+}
+
+fn default_reconciliation_interval_secs() -> u64 {
+    300
+}
+
+fn default_reconciliation_quantity_tolerance_pct() -> f64 {
+    0.5
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JournalFormat {
+    Csv,
+    #[default]
+    Json,
+}
+
+// Signals, orders, fills and position changes kept in memory for export. Older records spill
+// to Postgres when trading persistence is enabled, otherwise they are dropped
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TradeJournalConfig {
+    #[serde(default = "default_journal_max_entries")]
+    pub max_entries: usize,
+    // Whole journal written here on shutdown
+    #[serde(default)]
+    pub export_path: Option<String>,
+    #[serde(default)]
+    pub export_format: JournalFormat,
+}
+
+impl Default for TradeJournalConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: default_journal_max_entries(),
+            export_path: None,
+            export_format: JournalFormat::default(),
+        }
+    }
+}
+
+fn default_journal_max_entries() -> usize {
+    10_000
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShutdownPolicy {
+    // Orders and positions stay on the exchange as they are
+    #[default]
+    Keep,
+    // Working entry orders are cancelled, positions keep their stops and targets
+    CancelOrders,
+    // Working orders are cancelled and every position closed at market
+    Flatten,
+}
+
+// What the trader leaves on the exchange when the monitor stops. Whatever is not done within
+// `timeout_secs` is left as it is
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TradingShutdownConfig {
+    #[serde(default)]
+    pub policy: ShutdownPolicy,
+    #[serde(default = "default_shutdown_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+impl Default for TradingShutdownConfig {
+    fn default() -> Self {
+        Self {
+            policy: ShutdownPolicy::default(),
+            timeout_secs: default_shutdown_timeout_secs(),
+        }
+    }
+}
+
+fn default_shutdown_timeout_secs() -> u64 {
+    30
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OrderRetryConfig {
+    // Including the first attempt
+    #[serde(default = "default_order_max_attempts")]
+    pub max_attempts: u32,
+    // Backoff before the second attempt, doubling up to `max_delay_ms`
+    #[serde(default = "default_order_base_delay_ms")]
+    pub base_delay_ms: u64,
+    #[serde(default = "default_order_max_delay_ms")]
+    pub max_delay_ms: u64,
+    // File in-flight order intents are persisted to, so a restart can reconcile them
+    #[serde(default)]
+    pub intent_path: Option<String>,
+    // Entry intents older than this are dropped at restart instead of resubmitted
+    #[serde(default = "default_max_intent_age_secs")]
+    pub max_intent_age_secs: u64,
+}
+
+impl Default for OrderRetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: default_order_max_attempts(),
+            base_delay_ms: default_order_base_delay_ms(),
+            max_delay_ms: default_order_max_delay_ms(),
+            intent_path: None,
+            max_intent_age_secs: default_max_intent_age_secs(),
+        }
+    }
+}
+
+fn default_order_max_attempts() -> u32 {
+    3
+}
+
+fn default_order_base_delay_ms() -> u64 {
+    250
+}
+
+fn default_order_max_delay_ms() -> u64 {
+    5_000
+}
+
+fn default_max_intent_age_secs() -> u64 {
+    60
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PortfolioConfig {
+    // Starting value until the exchange reports a balance
+    #[serde(default = "default_initial_portfolio_value")]
+    pub initial_value: f64,
+    // Asset whose exchange balance resynchronizes the portfolio value
+    #[serde(default = "default_quote_asset")]
+    pub quote_asset: String,
+    // Include unrealized PnL of open positions in the value used for sizing
+    #[serde(default)]
+    pub mark_to_market: bool,
+    // Per-trade return the Sharpe ratio of the trading stats is measured against
+    #[serde(default)]
+    pub risk_free_rate: f64,
+}
+
+impl Default for PortfolioConfig {
+    fn default() -> Self {
+        Self {
+            initial_value: default_initial_portfolio_value(),
+            quote_asset: default_quote_asset(),
+            mark_to_market: false,
+            risk_free_rate: 0.0,
+        }
+    }
+}
+
+fn default_initial_portfolio_value() -> f64 {
+    10_000.0
+}
+
+fn default_quote_asset() -> String {
+    "USDT".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MeanReversionConfig {
+    // Minimum |z| of a price spike worth fading
+    #[serde(default = "default_mean_reversion_entry_z")]
+    pub entry_z: f64,
+    // Beyond this |z| the move is treated as a crash and not faded
+    #[serde(default = "default_mean_reversion_max_z")]
+    pub max_z: f64,
+    // Only fade spikes preceded by a volume spike within `volume_window_secs`
+    #[serde(default = "default_require_volume")]
+    pub require_volume: bool,
+    #[serde(default = "default_mean_reversion_volume_window_secs")]
+    pub volume_window_secs: u64,
+}
+
+impl Default for MeanReversionConfig {
+    fn default() -> Self {
+        Self {
+            entry_z: default_mean_reversion_entry_z(),
+            max_z: default_mean_reversion_max_z(),
+            require_volume: true,
+            volume_window_secs: default_mean_reversion_volume_window_secs(),
+        }
+    }
+}
+
+fn default_mean_reversion_entry_z() -> f64 {
+    2.5
+}
+
+fn default_mean_reversion_max_z() -> f64 {
+    6.0
+}
+
+fn default_mean_reversion_volume_window_secs() -> u64 {
+    300
+}
+
+fn default_require_volume() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MomentumConfig {
+    // Price move over `price_change_window_secs` a volume spike needs to count
+    #[serde(default = "default_momentum_min_price_change_pct")]
+    pub min_price_change_pct: f64,
+    // Same-direction volume spikes within `window_secs` before trading
+    #[serde(default = "default_momentum_min_spikes")]
+    pub min_spikes: usize,
+    #[serde(default = "default_momentum_window_secs")]
+    pub window_secs: u64,
+    // Volume multiple of expected for a Strong signal, half of it for Medium
+    #[serde(default = "default_momentum_strong_volume_multiple")]
+    pub strong_volume_multiple: f64,
+}
+
+impl Default for MomentumConfig {
+    fn default() -> Self {
+        Self {
+            min_price_change_pct: default_momentum_min_price_change_pct(),
+            min_spikes: default_momentum_min_spikes(),
+            window_secs: default_momentum_window_secs(),
+            strong_volume_multiple: default_momentum_strong_volume_multiple(),
+        }
+    }
+}
+
+fn default_momentum_min_price_change_pct() -> f64 {
+    1.0
+}
+
+fn default_momentum_min_spikes() -> usize {
+    2
+}
+
+fn default_momentum_window_secs() -> u64 {
+    600
+}
+
+fn default_momentum_strong_volume_multiple() -> f64 {
+    6.0
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EnsembleConfig {
+    // Strategy names, e.g. anomaly | rules
+    #[serde(default)]
+    pub strategies: Vec<String>,
+    #[serde(default)]
+    pub policy: CombinationPolicy,
+    // Per-strategy weights for `weighted_score`, unlisted strategies weigh 1
+    #[serde(default)]
+    pub weights: HashMap<String, f64>,
+    // Net weighted score, as a fraction of the total weight, needed to trade
+    #[serde(default = "default_ensemble_min_score")]
+    pub min_score: f64,
+}
+
+impl Default for EnsembleConfig {
+    fn default() -> Self {
+        Self {
+            strategies: Vec::new(),
+            policy: CombinationPolicy::default(),
+            weights: HashMap::new(),
+            min_score: default_ensemble_min_score(),
+        }
+    }
+}
+
+fn default_ensemble_min_score() -> f64 {
+    0.5
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CombinationPolicy {
+    Any,
+    #[default]
+    Majority,
+    All,
+    WeightedScore,
+}
+
+// Simulated execution used for dry runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PaperTradingConfig {
+    #[serde(default = "default_paper_fee_bps")]
+    pub fee_bps: f64,
+    // Market orders fill this far past the last price
+    #[serde(default = "default_paper_slippage_bps")]
+    pub slippage_bps: f64,
+}
+
+impl Default for PaperTradingConfig {
+    fn default() -> Self {
+        Self {
+            fee_bps: default_paper_fee_bps(),
+            slippage_bps: default_paper_slippage_bps(),
+        }
+    }
+}
+
+fn default_paper_fee_bps() -> f64 {
+    10.0
+}
+
+fn default_paper_slippage_bps() -> f64 {
+    5.0
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionVenueKind {
+    // The simulated account under `paper`
+    #[default]
+    Paper,
+    // barter's mock exchange run in process, behind the client a live exchange would use.
+    // Fills market orders only
+    Mock,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExecutionVenueConfig {
+    #[serde(default)]
+    pub kind: ExecutionVenueKind,
+    // The exchange whose orders a `mock` venue takes, orders for the others are rejected
+    #[serde(default)]
+    pub exchange: Option<String>,
+}
+
+// Entries split into clips sent `interval_secs` apart instead of one order, for books too
+// thin to take the whole size at once
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DcaConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_dca_clips")]
+    pub clips: u32,
+    #[serde(default = "default_dca_interval_secs")]
+    pub interval_secs: u64,
+    // Clips still to go are abandoned once the price has moved this far against the entry,
+    // in percent of the signal price
+    #[serde(default)]
+    pub abort_threshold_pct: Option<f64>,
+}
+
+impl Default for DcaConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            clips: default_dca_clips(),
+            interval_secs: default_dca_interval_secs(),
+            abort_threshold_pct: None,
+        }
+    }
+}
+
+fn default_dca_clips() -> u32 {
+    4
+}
+
+fn default_dca_interval_secs() -> u64 {
+    60
+}
+
+// Risk per trade scaled by signal strength, as multiples of `risk_percentage`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PositionSizingConfig {
+    #[serde(default = "default_weak_multiplier")]
+    pub weak_multiplier: f64,
+    #[serde(default = "default_medium_multiplier")]
+    pub medium_multiplier: f64,
+    #[serde(default = "default_strong_multiplier")]
+    pub strong_multiplier: f64,
+    // Smallest order notional each exchange accepts, in the quote currency. Smaller
+    // orders are skipped rather than sent to be rejected
+    #[serde(default)]
+    pub min_notional: HashMap<String, f64>,
+    #[serde(default)]
+    pub default_min_notional: Option<f64>,
+}
+
+impl PositionSizingConfig {
+    pub fn min_notional_for(&self, exchange: &str) -> Option<f64> {
+        self.min_notional.get(exchange).copied().or(self.default_min_notional)
+    }
+}
+
+impl Default for PositionSizingConfig {
+    fn default() -> Self {
+        Self {
+            weak_multiplier: default_weak_multiplier(),
+            medium_multiplier: default_medium_multiplier(),
+            strong_multiplier: default_strong_multiplier(),
+            min_notional: HashMap::new(),
+            default_min_notional: None,
+        }
+    }
+}
+
+fn default_weak_multiplier() -> f64 {
+    0.5
+}
+
+fn default_medium_multiplier() -> f64 {
+    1.0
+}
+
+fn default_strong_multiplier() -> f64 {
+    1.5
+}
+
+// Sizing of the volatility_target risk manager: each position is expected to move the
+// portfolio by `target_bps` a day. Markets with too little price history are sized and
+// protected like under the simple risk manager
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VolatilityTargetConfig {
+    #[serde(default = "default_volatility_target_bps")]
+    pub target_bps: f64,
+    // Returns kept per market for the realized volatility
+    #[serde(default = "default_volatility_window")]
+    pub window: usize,
+    // Returns needed before the realized volatility is used
+    #[serde(default = "default_volatility_min_samples")]
+    pub min_samples: usize,
+    // Exit distances in daily volatilities from the entry
+    #[serde(default = "default_volatility_stop_loss_multiplier")]
+    pub stop_loss_multiplier: f64,
+    #[serde(default = "default_volatility_take_profit_multiplier")]
+    pub take_profit_multiplier: f64,
+}
+
+impl Default for VolatilityTargetConfig {
+    fn default() -> Self {
+        Self {
+            target_bps: default_volatility_target_bps(),
+            window: default_volatility_window(),
+            min_samples: default_volatility_min_samples(),
+            stop_loss_multiplier: default_volatility_stop_loss_multiplier(),
+            take_profit_multiplier: default_volatility_take_profit_multiplier(),
+        }
+    }
+}
+
+fn default_volatility_target_bps() -> f64 {
+    50.0
+}
+
+fn default_volatility_window() -> usize {
+    500
+}
+
+fn default_volatility_min_samples() -> usize {
+    30
+}
+
+fn default_volatility_stop_loss_multiplier() -> f64 {
+    1.0
+}
+
+fn default_volatility_take_profit_multiplier() -> f64 {
+    2.0
+}
+
+// Fees in basis points of notional, maker for resting limit orders, taker for the rest
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FeeScheduleConfig {
+    #[serde(default)]
+    pub maker_bps: f64,
+    #[serde(default)]
+    pub taker_bps: f64,
+}
+
+// How far market fills are expected to land past the quoted price
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case", deny_unknown_fields)]
+pub enum SlippageConfig {
+    Fixed {
+        bps: f64,
+    },
+    // Stronger signals chase faster moves and fill further away
+    ByStrength {
+        weak_bps: f64,
+        medium_bps: f64,
+        strong_bps: f64,
+    },
+}
+
+impl Default for SlippageConfig {
+    fn default() -> Self {
+        SlippageConfig::Fixed { bps: 0.0 }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TradingCostsConfig {
+    // Per exchange, falling back to `default_fees`
+    #[serde(default)]
+    pub fees: HashMap<String, FeeScheduleConfig>,
+    #[serde(default)]
+    pub default_fees: FeeScheduleConfig,
+    #[serde(default)]
+    pub slippage: SlippageConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StrategyRuleConfig {
+    pub name: String,
+    // e.g. "anomaly_type == VolumeSpike AND severity >= High AND rsi < 35"
+    pub when: String,
+    // buy | sell
+    pub side: String,
+    #[serde(default)]
+    pub strength: StrengthConfig,
+    // Minimum seconds between signals of this rule on a symbol
+    #[serde(default)]
+    pub cooldown_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum StrengthConfig {
+    // Weak | Medium | Strong
+    Fixed(String),
+    // Severity to strength, a "default" entry covers unlisted severities
+    BySeverity(HashMap<String, String>),
+}
+
+impl Default for StrengthConfig {
+    fn default() -> Self {
+        StrengthConfig::Fixed("Medium".to_string())
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LiquidityConfig {
+    #[serde(default)]
+    pub tiers: HashMap<String, LiquidityRequirements>,
+    // Symbol to tier name, symbols without an entry use `default_tier`
+    #[serde(default)]
+    pub symbols: HashMap<String, String>,
+    #[serde(default)]
+    pub default_tier: Option<String>,
+}
+
+impl LiquidityConfig {
+    pub fn requirements_for(&self, symbol: &str) -> Option<&LiquidityRequirements> {
+        let tier = self.symbols.get(symbol).or(self.default_tier.as_ref())?;
+        self.tiers.get(tier)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LiquidityRequirements {
+    // Quote volume traded over the last 24h
+    pub min_volume_24h: f64,
+    pub max_spread_bps: f64,
+    // Top-of-book size on the side we take, as a multiple of the order quantity
+    pub min_depth_ratio: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OrderPlacementConfig {
+    #[serde(default)]
+    pub mode: OrderPlacementMode,
+    // Limit price distance from the signal price, on the passive side
+    #[serde(default)]
+    pub offset_bps: f64,
+    // Seconds a limit order may rest unfilled before `on_timeout` applies
+    #[serde(default = "default_limit_timeout_secs")]
+    pub timeout_secs: u64,
+    #[serde(default)]
+    pub on_timeout: UnfilledOrderAction,
+    // Cancel-and-replace rounds before falling back to a market order
+    #[serde(default = "default_max_replacements")]
+    pub max_replacements: u32,
+    // Cancel what is left of a partially filled entry instead of leaving it working
+    #[serde(default)]
+    pub cancel_partial_remainder: bool,
+}
+
+impl Default for OrderPlacementConfig {
+    fn default() -> Self {
+        Self {
+            mode: OrderPlacementMode::default(),
+            offset_bps: 0.0,
+            timeout_secs: default_limit_timeout_secs(),
+            on_timeout: UnfilledOrderAction::default(),
+            max_replacements: default_max_replacements(),
+            cancel_partial_remainder: false,
+        }
+    }
+}
+
+fn default_limit_timeout_secs() -> u64 {
+    10
+}
+
+fn default_max_replacements() -> u32 {
+    3
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderPlacementMode {
+    #[default]
+    Market,
+    Limit,
+    // Limit order the venue rejects rather than letting it take liquidity
+    PostOnly,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnfilledOrderAction {
+    // Cancel and re-quote at the latest price, up to `max_replacements` times
+    Replace,
+    // Cancel and take the rest at market
+    #[default]
+    Market,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CooldownConfig {
+    // Minimum seconds between executed trades of the same strategy on a symbol
+    #[serde(default)]
+    pub per_strategy_symbol_secs: u64,
+    // Minimum seconds between executed trades on a symbol, across strategies
+    #[serde(default)]
+    pub per_symbol_secs: u64,
+    // Blocks re-entry on a symbol for this many minutes after a stop-loss
+    #[serde(default)]
+    pub stop_loss_reentry_minutes: Option<u64>,
+    // File the last-trade timestamps are persisted to, so cooldowns survive restarts
+    #[serde(default)]
+    pub state_path: Option<String>,
+}
+
+fn default_stale_position_threshold_secs() -> u64 {
+    30
+}
+
+fn default_exit_fill_grace_secs() -> u64 {
+    30
+}
+
+fn default_price_change_window_secs() -> u64 {
+    300
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StalePositionFallback {
+    // Fetch a REST ticker and feed it into the stop-loss/take-profit checks
+    Rest,
+    // Only alert
+    #[default]
+    None,
+    // Flatten the position defensively
+    Close,
 }
\ No newline at end of file
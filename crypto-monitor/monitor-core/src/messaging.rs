@@ -0,0 +1,463 @@
+use crate::{
+    event::EventBuilder,
+    sink::{FileSink, FileSinkConfig},
+    AlertType, EventSource, EventType, FluvioConfig as FluvioSettings, MonitorError, MonitorEvent,
+    Result,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use fluvio::{Fluvio, FluvioConfig, RecordKey, TopicProducer};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc};
+use tokio::{
+    sync::{broadcast, watch},
+    task::JoinHandle,
+};
+use tracing::{error, info, warn};
+
+const LOCAL_CHANNEL_CAPACITY: usize = 4096;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MessagingConfig {
+    // When false a failed initial bus connection starts the process degraded instead of exiting
+    pub required: bool,
+    pub retry_interval_secs: u64,
+    // Captures events published while degraded, replay them with `crypto-monitor replay-file`
+    #[serde(default)]
+    pub degraded_capture: Option<FileSinkConfig>,
+}
+
+impl Default for MessagingConfig {
+    fn default() -> Self {
+        Self {
+            required: true,
+            retry_interval_secs: 10,
+            degraded_capture: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum MessagingStatus {
+    Connected {
+        since: DateTime<Utc>,
+    },
+    Degraded {
+        since: DateTime<Utc>,
+        attempts: u32,
+        last_error: String,
+    },
+}
+
+impl MessagingStatus {
+    pub fn is_degraded(&self) -> bool {
+        matches!(self, MessagingStatus::Degraded { .. })
+    }
+
+    pub fn to_event(&self) -> Option<MonitorEvent> {
+        let (event_type, message) = match self {
+            MessagingStatus::Connected { .. } => (
+                EventType::Alert(AlertType::Info),
+                "Message bus connected, publishing to Fluvio".to_string(),
+            ),
+            MessagingStatus::Degraded { last_error, .. } => (
+                EventType::Alert(AlertType::Warning),
+                format!("Message bus unavailable, running degraded: {}", last_error),
+            ),
+        };
+
+        let mut data = serde_json::to_value(self).ok()?;
+        data["message"] = serde_json::Value::String(message);
+
+        EventBuilder::new()
+            .with_source(EventSource::Monitor)
+            .with_type(event_type)
+            .with_data(data)
+            .build()
+    }
+}
+
+#[async_trait]
+pub trait EventBus: Send + Sync {
+    async fn publish(&self, topic: &str, event: &MonitorEvent) -> Result<()>;
+}
+
+#[async_trait]
+pub trait BusConnector: Send + Sync {
+    async fn connect(&self) -> Result<Arc<dyn EventBus>>;
+}
+
+pub struct FluvioBus {
+    producers: HashMap<String, TopicProducer>,
+}
+
+#[async_trait]
+impl EventBus for FluvioBus {
+    async fn publish(&self, topic: &str, event: &MonitorEvent) -> Result<()> {
+        let Some(producer) = self.producers.get(topic) else {
+            return Ok(());
+        };
+        let data = serde_json::to_string(event)?;
+        producer
+            .send(RecordKey::NULL, data)
+            .await
+            .map_err(|e| MonitorError::Stream(e.to_string()))?;
+        Ok(())
+    }
+}
+
+// Connects to Fluvio, creates the monitor topics and keeps the client for consumers
+pub struct FluvioConnector {
+    config: FluvioSettings,
+    client: parking_lot::RwLock<Option<Arc<Fluvio>>>,
+}
+
+impl FluvioConnector {
+    pub fn new(config: FluvioSettings) -> Self {
+        Self {
+            config,
+            client: parking_lot::RwLock::new(None),
+        }
+    }
+
+    pub fn client(&self) -> Option<Arc<Fluvio>> {
+        self.client.read().clone()
+    }
+
+    pub fn topics(&self) -> Vec<String> {
+        [
+            "market.trades",
+            "market.orderbook",
+            "market.candles",
+            "market.liquidations",
+            "market.funding",
+            "market.open_interest",
+            "anomalies",
+            "alerts",
+            "trades",
+        ]
+        .iter()
+        .map(|suffix| format!("{}.{}", self.config.topic_prefix, suffix))
+        .collect()
+    }
+}
+
+#[async_trait]
+impl BusConnector for FluvioConnector {
+    async fn connect(&self) -> Result<Arc<dyn EventBus>> {
+        let fluvio = Fluvio::connect_with_config(&FluvioConfig::new(&self.config.endpoint)).await?;
+        let admin = fluvio::FluvioAdmin::connect().await?;
+
+        let mut producers = HashMap::new();
+        for topic in self.topics() {
+            match admin
+                .create(
+                    topic.clone(),
+                    false,
+                    fluvio::metadata::topic::TopicSpec::new_computed(
+                        self.config.partitions as i32,
+                        self.config.replication_factor as i32,
+                        None,
+                    ),
+                )
+                .await
+            {
+                Ok(_) => info!("Created topic: {}", topic),
+                Err(e) => {
+                    if e.to_string().contains("already exists") {
+                        info!("Topic already exists: {}", topic);
+                    } else {
+                        return Err(MonitorError::Fluvio(e));
+                    }
+                }
+            }
+
+            let producer = fluvio.topic_producer(&topic).await?;
+            producers.insert(topic, producer);
+        }
+
+        *self.client.write() = Some(Arc::new(fluvio));
+        Ok(Arc::new(FluvioBus { producers }))
+    }
+}
+
+// Publication front for the engine. Every event is mirrored to in-process subscribers; the bus
+// only receives events once it is connected, anything published before that is optionally
+// captured to disk.
+pub struct Messaging {
+    config: MessagingConfig,
+    connector: Arc<dyn BusConnector>,
+    bus: parking_lot::RwLock<Option<Arc<dyn EventBus>>>,
+    capture: parking_lot::RwLock<Option<Arc<FileSink>>>,
+    status: watch::Sender<MessagingStatus>,
+    local: broadcast::Sender<(String, MonitorEvent)>,
+}
+
+impl Messaging {
+    pub async fn connect(
+        config: MessagingConfig,
+        connector: Arc<dyn BusConnector>,
+    ) -> Result<Arc<Self>> {
+        let now = Utc::now();
+        let (bus, status, capture) = match connector.connect().await {
+            Ok(bus) => (Some(bus), MessagingStatus::Connected { since: now }, None),
+            Err(e) if config.required => return Err(e),
+            Err(e) => {
+                warn!("Message bus unavailable, starting degraded: {}", e);
+                let capture = match &config.degraded_capture {
+                    Some(sink) => Some(Arc::new(FileSink::spawn(sink.clone())?)),
+                    None => None,
+                };
+                let status = MessagingStatus::Degraded {
+                    since: now,
+                    attempts: 1,
+                    last_error: e.to_string(),
+                };
+                (None, status, capture)
+            }
+        };
+
+        let (local, _) = broadcast::channel(LOCAL_CHANNEL_CAPACITY);
+        Ok(Arc::new(Self {
+            config,
+            connector,
+            bus: parking_lot::RwLock::new(bus),
+            capture: parking_lot::RwLock::new(capture),
+            status: watch::channel(status).0,
+            local,
+        }))
+    }
+
+    pub fn status(&self) -> MessagingStatus {
+        self.status.borrow().clone()
+    }
+
+    pub fn subscribe_status(&self) -> watch::Receiver<MessagingStatus> {
+        self.status.subscribe()
+    }
+
+    // In-process stream of every published (topic, event), used when consumers can't reach the bus
+    pub fn subscribe_local(&self) -> broadcast::Receiver<(String, MonitorEvent)> {
+        self.local.subscribe()
+    }
+
+    pub async fn publish(&self, topic: &str, event: &MonitorEvent) {
+        if self.local.receiver_count() > 0 {
+            let _ = self.local.send((topic.to_string(), event.clone()));
+        }
+
+        // The capture lock is held across the bus check so a concurrent reconnect can't close
+        // the capture between the two
+        let bus = {
+            let capture = self.capture.read();
+            let bus = self.bus.read().clone();
+            if bus.is_none() {
+                if let Some(capture) = capture.as_ref() {
+                    capture.publish(topic, event);
+                }
+            }
+            bus
+        };
+
+        if let Some(bus) = bus {
+            if let Err(e) = bus.publish(topic, event).await {
+                error!("Failed to send event to Fluvio: {}", e);
+            }
+        }
+    }
+
+    // One reconnection attempt, returns whether the bus is connected afterwards
+    pub async fn try_reconnect(&self) -> bool {
+        if self.bus.read().is_some() {
+            return true;
+        }
+
+        match self.connector.connect().await {
+            Ok(bus) => {
+                let capture = {
+                    let mut capture = self.capture.write();
+                    *self.bus.write() = Some(bus);
+                    capture.take()
+                };
+                info!("Message bus connected, leaving degraded mode");
+
+                if let Some(capture) = capture {
+                    capture.shutdown().await;
+                }
+                self.status.send_replace(MessagingStatus::Connected { since: Utc::now() });
+                true
+            }
+            Err(e) => {
+                self.status.send_modify(|status| {
+                    if let MessagingStatus::Degraded { attempts, last_error, .. } = status {
+                        *attempts += 1;
+                        *last_error = e.to_string();
+                    }
+                });
+                warn!("Message bus still unavailable: {}", e);
+                false
+            }
+        }
+    }
+
+    pub fn spawn_reconnect(self: &Arc<Self>, mut shutdown: watch::Receiver<bool>) -> JoinHandle<()> {
+        let messaging = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+                messaging.config.retry_interval_secs.max(1),
+            ));
+            interval.tick().await;
+            while !messaging.try_reconnect().await {
+                tokio::select! {
+                    _ = shutdown.changed() => break,
+                    _ = interval.tick() => {}
+                }
+            }
+        })
+    }
+
+    // Flushes the degraded capture, if one is still open
+    pub async fn shutdown(&self) {
+        let capture = self.capture.write().take();
+        if let Some(capture) = capture {
+            capture.shutdown().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        sink::{capture_files, open_capture, CapturedEvent, FsyncPolicy, SinkCompression},
+        MarketDataType,
+    };
+    use std::{io::BufRead, sync::atomic::{AtomicU32, Ordering}};
+
+    // Fails the first `failures` connection attempts, then records everything published
+    struct DelayedBus {
+        failures: AtomicU32,
+        received: Arc<parking_lot::Mutex<Vec<MonitorEvent>>>,
+    }
+
+    struct RecordingBus {
+        received: Arc<parking_lot::Mutex<Vec<MonitorEvent>>>,
+    }
+
+    #[async_trait]
+    impl EventBus for RecordingBus {
+        async fn publish(&self, _topic: &str, event: &MonitorEvent) -> Result<()> {
+            self.received.lock().push(event.clone());
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl BusConnector for DelayedBus {
+        async fn connect(&self) -> Result<Arc<dyn EventBus>> {
+            let remaining = self.failures.load(Ordering::SeqCst);
+            if remaining > 0 {
+                self.failures.store(remaining - 1, Ordering::SeqCst);
+                return Err(MonitorError::Stream("connection refused".to_string()));
+            }
+            Ok(Arc::new(RecordingBus {
+                received: self.received.clone(),
+            }))
+        }
+    }
+
+    fn delayed(failures: u32) -> Arc<DelayedBus> {
+        Arc::new(DelayedBus {
+            failures: AtomicU32::new(failures),
+            received: Arc::new(parking_lot::Mutex::new(Vec::new())),
+        })
+    }
+
+    fn event(i: usize) -> MonitorEvent {
+        EventBuilder::new()
+            .with_source(EventSource::Exchange("binance".to_string()))
+            .with_type(EventType::MarketData(MarketDataType::Trade))
+            .with_data(serde_json::json!({ "seq": i }))
+            .build()
+            .unwrap()
+    }
+
+    fn config(directory: std::path::PathBuf) -> MessagingConfig {
+        MessagingConfig {
+            required: false,
+            retry_interval_secs: 1,
+            degraded_capture: Some(FileSinkConfig {
+                directory,
+                max_file_bytes: 1024 * 1024,
+                max_file_age_secs: 3600,
+                compression: SinkCompression::None,
+                fsync: FsyncPolicy::OnRotate,
+                topics: Vec::new(),
+                queue_capacity: 1_000,
+            }),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_required_bus_fails_startup() {
+        let config = MessagingConfig::default();
+        assert!(Messaging::connect(config, delayed(1)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_degraded_until_bus_comes_up_without_losing_events() {
+        let directory = std::env::temp_dir().join(format!("degraded-{}", uuid::Uuid::new_v4()));
+        let connector = delayed(2);
+        let messaging = Messaging::connect(config(directory.clone()), connector.clone())
+            .await
+            .unwrap();
+        let mut local = messaging.subscribe_local();
+
+        assert!(messaging.status().is_degraded());
+        for i in 0..3 {
+            messaging.publish("crypto-monitor.market.trades", &event(i)).await;
+        }
+
+        assert!(!messaging.try_reconnect().await);
+        assert!(matches!(
+            messaging.status(),
+            MessagingStatus::Degraded { attempts: 2, .. }
+        ));
+        messaging.publish("crypto-monitor.market.trades", &event(3)).await;
+
+        assert!(messaging.try_reconnect().await);
+        assert!(!messaging.status().is_degraded());
+        for i in 4..6 {
+            messaging.publish("crypto-monitor.market.trades", &event(i)).await;
+        }
+
+        let captured: Vec<usize> = capture_files(&directory)
+            .unwrap()
+            .iter()
+            .flat_map(|path| open_capture(path).unwrap().lines().collect::<Vec<_>>())
+            .map(|line| serde_json::from_str::<CapturedEvent>(&line.unwrap()).unwrap())
+            .map(|c| c.event.data["seq"].as_u64().unwrap() as usize)
+            .collect();
+        let delivered: Vec<usize> = connector
+            .received
+            .lock()
+            .iter()
+            .map(|e| e.data["seq"].as_u64().unwrap() as usize)
+            .collect();
+
+        assert_eq!(captured, vec![0, 1, 2, 3]);
+        assert_eq!(delivered, vec![4, 5]);
+
+        // The in-process stream saw everything regardless of bus state
+        let mut seen = Vec::new();
+        while let Ok((_, event)) = local.try_recv() {
+            seen.push(event.data["seq"].as_u64().unwrap() as usize);
+        }
+        assert_eq!(seen, (0..6).collect::<Vec<_>>());
+
+        let _ = std::fs::remove_dir_all(&directory);
+    }
+}
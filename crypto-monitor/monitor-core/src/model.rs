@@ -31,6 +31,74 @@ pub struct OrderBookLevel {
     pub quantity: f64,
 }
 
+// The aggressor side of a trade, when the venue reports it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TradeSide {
+    Buy,
+    Sell,
+}
+
+// Long positions are liquidated by forced sells, shorts by forced buys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LiquidationSide {
+    Long,
+    Short,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Liquidation {
+    pub exchange: String,
+    pub symbol: String,
+    pub timestamp: DateTime<Utc>,
+    pub side: LiquidationSide,
+    pub price: f64,
+    pub quantity: f64,
+}
+
+impl Liquidation {
+    pub fn notional(&self) -> f64 {
+        self.price * self.quantity
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FundingRateUpdate {
+    pub exchange: String,
+    pub symbol: String,
+    pub timestamp: DateTime<Utc>,
+    // Rate for the coming interval as a fraction, positive when longs pay shorts
+    pub predicted_rate: f64,
+    #[serde(default = "default_funding_interval_hours")]
+    pub interval_hours: f64,
+    #[serde(default)]
+    pub next_funding_at: Option<DateTime<Utc>>,
+}
+
+fn default_funding_interval_hours() -> f64 {
+    8.0
+}
+
+impl FundingRateUpdate {
+    // Rate over a year of intervals without compounding, in percent
+    pub fn annualized_pct(&self) -> f64 {
+        self.predicted_rate * (24.0 / self.interval_hours) * 365.0 * 100.0
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenInterestUpdate {
+    pub exchange: String,
+    pub symbol: String,
+    pub timestamp: DateTime<Utc>,
+    // Outstanding contracts as the exchange reports them
+    pub open_interest: f64,
+    // Price at the time of the reading, when the source carries one
+    #[serde(default)]
+    pub price: Option<f64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Candle {
     pub exchange: String,
@@ -0,0 +1,208 @@
+// The `notification` section of MonitorConfig. Delivery, routing and acknowledgement live in
+// monitor-notifier, which re-exports these
+use crate::{AlertType, AnomalySeverity, AnomalyType, MonitorError, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+pub const DEFAULT_RULE: &str = "default";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NotificationConfig {
+    pub telegram: TelegramConfig,
+    pub wechat: WeChatConfig,
+    pub email: EmailConfig,
+    pub sms: SmsConfig,
+    #[serde(default)]
+    pub routing: RoutingConfig,
+    #[serde(default)]
+    pub slo: SloConfig,
+    #[serde(default)]
+    pub acks: AckConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TelegramConfig {
+    pub enabled: bool,
+    pub bot_token: String,
+    pub chat_ids: Vec<String>,
+    pub send_images: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WeChatConfig {
+    pub enabled: bool,
+    pub corp_id: String,
+    pub agent_id: String,
+    pub secret: String,
+    pub to_user: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EmailConfig {
+    pub enabled: bool,
+    pub smtp_host: String,
+    pub smtp_port: u16,
+    pub username: String,
+    pub password: String,
+    pub from_address: String,
+    pub to_addresses: Vec<String>,
+    pub use_tls: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SmsConfig {
+    pub enabled: bool,
+    pub provider: SmsProvider,
+    pub from_number: String,
+    pub to_numbers: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub enum SmsProvider {
+    Twilio {
+        account_sid: String,
+        auth_token: String,
+    },
+    Aliyun {
+        access_key_id: String,
+        access_key_secret: String,
+        sign_name: String,
+        template_code: String,
+    },
+}
+
+// Empty criteria match anything; anomaly criteria never match non-anomaly notifications
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoutingRule {
+    pub name: String,
+    #[serde(default)]
+    pub anomaly_types: Vec<AnomalyType>,
+    #[serde(default)]
+    pub min_severity: Option<AnomalySeverity>,
+    #[serde(default)]
+    pub exchanges: Vec<String>,
+    // Glob over the symbol, `*` and `?` wildcards, case-insensitive
+    #[serde(default)]
+    pub symbol_pattern: Option<String>,
+    pub channels: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RoutingConfig {
+    // Evaluated in order, the first matching rule decides the channels
+    #[serde(default)]
+    pub rules: Vec<RoutingRule>,
+    // Channels when no rule matches, `None` sends to every enabled channel
+    #[serde(default)]
+    pub default_channels: Option<Vec<String>>,
+}
+
+impl RoutingConfig {
+    pub fn validate(&self) -> Result<()> {
+        let mut names = std::collections::HashSet::new();
+        for rule in &self.rules {
+            if rule.name.is_empty() || rule.name == DEFAULT_RULE {
+                return Err(MonitorError::Configuration(format!(
+                    "Routing rule name '{}' is reserved or empty",
+                    rule.name
+                )));
+            }
+            if !names.insert(rule.name.as_str()) {
+                return Err(MonitorError::Configuration(format!(
+                    "Duplicate routing rule '{}'",
+                    rule.name
+                )));
+            }
+            if rule.channels.is_empty() {
+                return Err(MonitorError::Configuration(format!(
+                    "Routing rule '{}' has no channels",
+                    rule.name
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    // Routing saved through the API, takes precedence over the config file
+    pub async fn load(pool: &PgPool) -> Result<Option<Self>> {
+        let row: Option<(String,)> =
+            sqlx::query_as("SELECT config::text FROM notification_routing WHERE id = 1")
+                .fetch_optional(pool)
+                .await?;
+
+        row.map(|(config,)| serde_json::from_str(&config).map_err(MonitorError::from))
+            .transpose()
+    }
+
+    pub async fn save(&self, pool: &PgPool) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO notification_routing (id, config, updated_at) VALUES (1, $1::jsonb, NOW()) \
+             ON CONFLICT (id) DO UPDATE SET config = EXCLUDED.config, updated_at = EXCLUDED.updated_at",
+        )
+        .bind(serde_json::to_string(self)?)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SloConfig {
+    // Notifications of this alert type are held to the target
+    pub alert_type: AlertType,
+    // Detection to first successful channel delivery
+    pub target_secs: f64,
+    // Percentage of notifications that must meet the target over the window
+    pub objective_pct: f64,
+    pub window_hours: u64,
+    // Compliance is not judged on fewer notifications than this
+    pub min_samples: usize,
+    // Minimum interval between breach meta-alerts
+    pub alert_interval_secs: u64,
+}
+
+impl Default for SloConfig {
+    fn default() -> Self {
+        Self {
+            alert_type: AlertType::Critical,
+            target_secs: 30.0,
+            objective_pct: 99.0,
+            window_hours: 24,
+            min_samples: 5,
+            alert_interval_secs: 3600,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AckConfig {
+    // Recent notifications kept for history, bulk acks, digests and escalation
+    pub history_size: usize,
+    // Most alerts a single bulk ack clears
+    pub bulk_limit: usize,
+    // Unacknowledged critical alerts are re-sent once after this long, 0 disables
+    pub escalate_after_secs: u64,
+    // 0 disables digests
+    pub digest_interval_secs: u64,
+    pub digest_exclude_acked: bool,
+}
+
+impl Default for AckConfig {
+    fn default() -> Self {
+        Self {
+            history_size: 1000,
+            bulk_limit: 500,
+            escalate_after_secs: 0,
+            digest_interval_secs: 0,
+            digest_exclude_acked: true,
+        }
+    }
+}
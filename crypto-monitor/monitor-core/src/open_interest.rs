@@ -0,0 +1,410 @@
+use crate::{
+    event::EventBuilder,
+    model::OpenInterestUpdate,
+    ratelimit::{EndpointClass, RestRateLimiters},
+    EventSource, EventType, ExchangeConfig, MarketDataType, MonitorError, MonitorEvent,
+    OpenInterestConfig, Result,
+};
+use async_trait::async_trait;
+use chrono::{TimeZone, Utc};
+use serde::Deserialize;
+use std::{sync::Arc, time::Duration};
+use tokio::{
+    sync::{mpsc, watch},
+    task::JoinHandle,
+};
+use tracing::{debug, info, warn};
+
+// Exchange subscription that turns on open interest polling for its symbols
+pub const OPEN_INTEREST_SUBSCRIPTION: &str = "open_interest";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenInterestVenue {
+    BinanceFutures,
+    Okx,
+}
+
+impl OpenInterestVenue {
+    pub fn for_exchange(exchange: &str) -> Option<Self> {
+        match exchange.to_lowercase().as_str() {
+            "binance" | "binance_futures_usd" => Some(OpenInterestVenue::BinanceFutures),
+            "okx" => Some(OpenInterestVenue::Okx),
+            _ => None,
+        }
+    }
+
+    // Rate limiter the requests are charged to
+    pub fn limiter(&self) -> &'static str {
+        match self {
+            OpenInterestVenue::BinanceFutures => "binance_futures_usd",
+            OpenInterestVenue::Okx => "okx",
+        }
+    }
+
+    // REST url, endpoint class and request weight for the perpetual on `symbol` ("BTC/USDT",
+    // or "BTC/USDT:USDT" with its settle currency)
+    fn request(&self, symbol: &str) -> (String, EndpointClass, u32) {
+        let pair = symbol.split(':').next().unwrap_or(symbol).to_uppercase();
+        match self {
+            // https://binance-docs.github.io/apidocs/futures/en/#open-interest
+            OpenInterestVenue::BinanceFutures => (
+                format!(
+                    "https://fapi.binance.com/fapi/v1/openInterest?symbol={}",
+                    pair.replace('/', "")
+                ),
+                EndpointClass::OpenInterest,
+                1,
+            ),
+            // https://www.okx.com/docs-v5/en/#public-data-rest-api-get-open-interest
+            OpenInterestVenue::Okx => (
+                format!(
+                    "https://www.okx.com/api/v5/public/open-interest?instType=SWAP&instId={}-SWAP",
+                    pair.replace('/', "-")
+                ),
+                EndpointClass::OpenInterest,
+                1,
+            ),
+        }
+    }
+
+    pub fn parse(&self, exchange: &str, symbol: &str, body: &str) -> Result<OpenInterestUpdate> {
+        let (open_interest, time_ms) = match self {
+            OpenInterestVenue::BinanceFutures => {
+                let response: BinanceOpenInterest = serde_json::from_str(body)?;
+                (response.open_interest, response.time)
+            }
+            OpenInterestVenue::Okx => {
+                let response: OkxOpenInterestResponse = serde_json::from_str(body)?;
+                if response.code != "0" {
+                    return Err(MonitorError::Stream(format!(
+                        "OKX open interest returned code {}: {}",
+                        response.code, response.msg
+                    )));
+                }
+                let entry = response.data.into_iter().next().ok_or_else(|| {
+                    MonitorError::Stream("OKX open interest returned no data".to_string())
+                })?;
+                let ts = entry.ts.parse::<i64>().map_err(|_| {
+                    MonitorError::Stream(format!("Invalid open interest time {}", entry.ts))
+                })?;
+                (entry.oi, ts)
+            }
+        };
+
+        let open_interest = open_interest
+            .parse::<f64>()
+            .ok()
+            .filter(|v| v.is_finite() && *v >= 0.0)
+            .ok_or_else(|| {
+                MonitorError::Stream(format!("Invalid open interest {}", open_interest))
+            })?;
+        let timestamp = Utc.timestamp_millis_opt(time_ms).single().ok_or_else(|| {
+            MonitorError::Stream(format!("Invalid open interest time {}", time_ms))
+        })?;
+        Ok(OpenInterestUpdate {
+            exchange: exchange.to_string(),
+            symbol: symbol.to_string(),
+            timestamp,
+            open_interest,
+            price: None,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BinanceOpenInterest {
+    open_interest: String,
+    time: i64,
+}
+
+#[derive(Deserialize)]
+struct OkxOpenInterestResponse {
+    code: String,
+    #[serde(default)]
+    msg: String,
+    #[serde(default)]
+    data: Vec<OkxOpenInterest>,
+}
+
+#[derive(Deserialize)]
+struct OkxOpenInterest {
+    oi: String,
+    ts: String,
+}
+
+#[async_trait]
+pub trait OpenInterestFetcher: Send + Sync {
+    async fn fetch_open_interest(
+        &self,
+        venue: OpenInterestVenue,
+        exchange: &str,
+        symbol: &str,
+    ) -> Result<OpenInterestUpdate>;
+}
+
+// Public REST open interest endpoints, charged to the shared per-exchange limiter
+pub struct RestOpenInterestFetcher {
+    client: reqwest::Client,
+    limiters: RestRateLimiters,
+}
+
+impl RestOpenInterestFetcher {
+    pub fn new(limiters: RestRateLimiters) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            limiters,
+        }
+    }
+}
+
+#[async_trait]
+impl OpenInterestFetcher for RestOpenInterestFetcher {
+    async fn fetch_open_interest(
+        &self,
+        venue: OpenInterestVenue,
+        exchange: &str,
+        symbol: &str,
+    ) -> Result<OpenInterestUpdate> {
+        let (url, class, weight) = venue.request(symbol);
+        let limiter = self.limiters.get(venue.limiter());
+        limiter.acquire(class, weight).await?;
+
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| MonitorError::Stream(format!("Open interest request failed: {}", e)))?;
+        let status = response.status();
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        limiter.record_response(status.as_u16(), retry_after.as_deref());
+        if !status.is_success() {
+            return Err(MonitorError::Stream(format!(
+                "Open interest request returned {}",
+                status
+            )));
+        }
+
+        let body = response
+            .text()
+            .await
+            .map_err(|e| MonitorError::Stream(format!("Open interest read failed: {}", e)))?;
+        venue.parse(exchange, symbol, &body)
+    }
+}
+
+struct Instrument {
+    venue: OpenInterestVenue,
+    exchange: String,
+    symbol: String,
+}
+
+// Polls open interest for every symbol of the exchanges subscribed to `open_interest` and
+// publishes each reading as a `MarketDataType::OpenInterest` event
+pub struct OpenInterestCollector {
+    config: OpenInterestConfig,
+    fetcher: Arc<dyn OpenInterestFetcher>,
+    instruments: Vec<Instrument>,
+}
+
+impl OpenInterestCollector {
+    pub fn new(
+        config: OpenInterestConfig,
+        fetcher: Arc<dyn OpenInterestFetcher>,
+        exchanges: &[ExchangeConfig],
+    ) -> Self {
+        let mut instruments = Vec::new();
+        for exchange in exchanges {
+            let subscribed = exchange.subscriptions.iter().any(|s| s == OPEN_INTEREST_SUBSCRIPTION);
+            if !exchange.enabled || !subscribed {
+                continue;
+            }
+            let Some(venue) = OpenInterestVenue::for_exchange(&exchange.name) else {
+                warn!("Open interest is not available for {}", exchange.name);
+                continue;
+            };
+            instruments.extend(exchange.symbols.iter().map(|symbol| Instrument {
+                venue,
+                exchange: exchange.name.clone(),
+                symbol: symbol.clone(),
+            }));
+        }
+        Self {
+            config,
+            fetcher,
+            instruments,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instruments.is_empty()
+    }
+
+    // One reading per instrument, returns how many were published. Failures are logged and
+    // left for the next round
+    pub async fn poll_once(&self, tx: &mpsc::UnboundedSender<MonitorEvent>) -> usize {
+        let mut published = 0;
+        for instrument in &self.instruments {
+            let update = match self
+                .fetcher
+                .fetch_open_interest(instrument.venue, &instrument.exchange, &instrument.symbol)
+                .await
+            {
+                Ok(update) => update,
+                Err(e) => {
+                    warn!(
+                        "Open interest poll failed for {}/{}: {}",
+                        instrument.exchange, instrument.symbol, e
+                    );
+                    continue;
+                }
+            };
+            let event = EventBuilder::new()
+                .with_source(EventSource::Exchange(update.exchange.clone()))
+                .with_type(EventType::MarketData(MarketDataType::OpenInterest))
+                .with_data(serde_json::to_value(&update).unwrap_or_default())
+                .build();
+            if let Some(event) = event {
+                if tx.send(event).is_err() {
+                    debug!("Open interest receiver dropped");
+                    return published;
+                }
+                published += 1;
+            }
+        }
+        published
+    }
+
+    pub fn spawn(
+        self,
+        tx: mpsc::UnboundedSender<MonitorEvent>,
+        mut shutdown: watch::Receiver<bool>,
+    ) -> JoinHandle<()> {
+        let period = Duration::from_secs(self.config.poll_interval_secs.max(1));
+        info!(
+            "Polling open interest for {} instruments every {:?}",
+            self.instruments.len(),
+            period
+        );
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(period);
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {
+                        self.poll_once(&tx).await;
+                    }
+                    _ = shutdown.changed() => break,
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+
+    fn exchange(name: &str, subscriptions: &[&str]) -> ExchangeConfig {
+        ExchangeConfig {
+            name: name.to_string(),
+            enabled: true,
+            symbols: vec!["BTC/USDT".to_string(), "ETH/USDT".to_string()],
+            subscriptions: subscriptions.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    // Records every request and fails the ETH ones
+    struct ScriptedFetcher {
+        calls: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl OpenInterestFetcher for ScriptedFetcher {
+        async fn fetch_open_interest(
+            &self,
+            _venue: OpenInterestVenue,
+            exchange: &str,
+            symbol: &str,
+        ) -> Result<OpenInterestUpdate> {
+            self.calls.lock().push(format!("{}:{}", exchange, symbol));
+            if symbol.starts_with("ETH") {
+                return Err(MonitorError::Stream("unavailable".to_string()));
+            }
+            Ok(OpenInterestUpdate {
+                exchange: exchange.to_string(),
+                symbol: symbol.to_string(),
+                timestamp: Utc::now(),
+                open_interest: 10_000.0,
+                price: None,
+            })
+        }
+    }
+
+    #[test]
+    fn test_requests_per_venue() {
+        let (url, class, _) = OpenInterestVenue::BinanceFutures.request("BTC/USDT:USDT");
+        assert_eq!(url, "https://fapi.binance.com/fapi/v1/openInterest?symbol=BTCUSDT");
+        assert_eq!(class, EndpointClass::OpenInterest);
+
+        let (url, _, _) = OpenInterestVenue::Okx.request("eth/usdt");
+        assert!(url.ends_with("instType=SWAP&instId=ETH-USDT-SWAP"));
+        assert_eq!(OpenInterestVenue::for_exchange("Bybit"), None);
+    }
+
+    #[test]
+    fn test_parse_responses() {
+        let binance = r#"{"openInterest":"10659.509","symbol":"BTCUSDT","time":1589437530011}"#;
+        let update =
+            OpenInterestVenue::BinanceFutures.parse("binance", "BTC/USDT", binance).unwrap();
+        assert_eq!(update.open_interest, 10659.509);
+        assert_eq!(update.timestamp.timestamp_millis(), 1589437530011);
+        assert_eq!((update.exchange.as_str(), update.price), ("binance", None));
+
+        let okx = r#"{"code":"0","msg":"","data":[{"instId":"BTC-USDT-SWAP","instType":"SWAP",
+            "oi":"5000","oiCcy":"50","ts":"1597026383085"}]}"#;
+        let update = OpenInterestVenue::Okx.parse("okx", "BTC/USDT", okx).unwrap();
+        assert_eq!(update.open_interest, 5000.0);
+
+        let error = r#"{"code":"51001","msg":"Instrument ID does not exist","data":[]}"#;
+        assert!(OpenInterestVenue::Okx.parse("okx", "BTC/USDT", error).is_err());
+        let negative = r#"{"openInterest":"-1","symbol":"BTCUSDT","time":1589437530011}"#;
+        assert!(OpenInterestVenue::BinanceFutures.parse("binance", "BTC/USDT", negative).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_polls_subscribed_exchanges_only() {
+        let fetcher = Arc::new(ScriptedFetcher {
+            calls: Mutex::new(Vec::new()),
+        });
+        let mut disabled = exchange("okx", &["trades", OPEN_INTEREST_SUBSCRIPTION]);
+        disabled.enabled = false;
+        let exchanges = [
+            exchange("binance", &["trades", OPEN_INTEREST_SUBSCRIPTION]),
+            exchange("bybit", &[OPEN_INTEREST_SUBSCRIPTION]),
+            exchange("okx", &["trades"]),
+            disabled,
+        ];
+        let collector =
+            OpenInterestCollector::new(OpenInterestConfig::default(), fetcher.clone(), &exchanges);
+        let (tx, mut rx) = mpsc::unbounded_channel();
+
+        // ETH fails and is skipped, BTC still goes out
+        assert_eq!(collector.poll_once(&tx).await, 1);
+        assert_eq!(*fetcher.calls.lock(), vec!["binance:BTC/USDT", "binance:ETH/USDT"]);
+
+        let event = rx.try_recv().unwrap();
+        assert!(matches!(
+            event.event_type,
+            EventType::MarketData(MarketDataType::OpenInterest)
+        ));
+        let update: OpenInterestUpdate = serde_json::from_value(event.data).unwrap();
+        assert_eq!((update.symbol.as_str(), update.open_interest), ("BTC/USDT", 10_000.0));
+        assert!(rx.try_recv().is_err());
+    }
+}
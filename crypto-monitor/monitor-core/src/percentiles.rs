@@ -0,0 +1,461 @@
+use crate::{
+    cache::{CacheCounters, CacheStats, InstrumentedCache},
+    scheduler::PeriodicTask,
+    storage::repository::CandleRecord,
+    Result,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+use tracing::info;
+
+pub const TRAILING_DAYS: i64 = 30;
+
+// Candle interval daily metrics are built from, only one is read to avoid double counting
+pub const DAILY_METRICS_INTERVAL: &str = "1m";
+
+// Percentile data older than this is reported as stale
+const FRESHNESS_HOURS: i64 = 36;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyMetrics {
+    pub day: NaiveDate,
+    // Square root of the summed squared log returns between consecutive closes
+    pub realized_volatility: f64,
+    pub volume: f64,
+    // High-low range as a fraction of the day's open
+    pub range: f64,
+}
+
+// Candles must be sorted by open time
+pub fn daily_metrics(candles: &[CandleRecord]) -> Vec<DailyMetrics> {
+    let mut days: BTreeMap<NaiveDate, Vec<&CandleRecord>> = BTreeMap::new();
+    for candle in candles {
+        days.entry(candle.open_time.date_naive()).or_default().push(candle);
+    }
+
+    days.into_iter()
+        .map(|(day, candles)| {
+            let open = candles[0].open;
+            let high = candles.iter().map(|c| c.high).fold(f64::MIN, f64::max);
+            let low = candles.iter().map(|c| c.low).fold(f64::MAX, f64::min);
+            DailyMetrics {
+                day,
+                realized_volatility: realized_volatility(candles.iter().map(|c| c.close)),
+                volume: candles.iter().map(|c| c.volume).sum(),
+                range: if open > 0.0 { (high - low) / open } else { 0.0 },
+            }
+        })
+        .collect()
+}
+
+// Square root of the summed squared log returns between consecutive closes, oldest first.
+// Returns next to a non-positive close are skipped
+pub fn realized_volatility(closes: impl IntoIterator<Item = f64>) -> f64 {
+    let mut previous: Option<f64> = None;
+    let mut squared_returns = 0.0;
+    for close in closes {
+        if let Some(previous) = previous.filter(|p| *p > 0.0 && close > 0.0) {
+            squared_returns += (close / previous).ln().powi(2);
+        }
+        previous = Some(close);
+    }
+    squared_returns.sqrt()
+}
+
+// Realized volatility per "exchange:symbol" over the 24 hours to `now`, from the candles the
+// nightly job reads, so a trailing day places within the daily distribution. Instruments with
+// fewer than two candles are left out
+pub async fn trailing_volatility(
+    pool: &PgPool,
+    instruments: &[(String, String)],
+    now: DateTime<Utc>,
+) -> Result<HashMap<String, f64>> {
+    if instruments.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let (exchanges, symbols): (Vec<String>, Vec<String>) = instruments.iter().cloned().unzip();
+    let closes: Vec<(String, String, f64)> = sqlx::query_as(
+        "SELECT exchange, symbol, close::float8 FROM candles \
+         WHERE interval = $1 AND open_time >= $2 \
+         AND (exchange, symbol) IN (SELECT * FROM UNNEST($3::text[], $4::text[])) \
+         ORDER BY exchange, symbol, open_time",
+    )
+    .bind(DAILY_METRICS_INTERVAL)
+    .bind(now - Duration::hours(24))
+    .bind(&exchanges)
+    .bind(&symbols)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(closes
+        .chunk_by(|a, b| a.0 == b.0 && a.1 == b.1)
+        .filter(|group| group.len() > 1)
+        .map(|group| {
+            let key = format!("{}:{}", group[0].0, group[0].1);
+            (key, realized_volatility(group.iter().map(|c| c.2)))
+        })
+        .collect())
+}
+
+// Fraction of the distribution at or below `value`, `None` for an empty distribution
+pub fn percentile_rank(sorted: &[f64], value: f64) -> Option<f64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let at_or_below = sorted.partition_point(|v| *v <= value);
+    Some(at_or_below as f64 / sorted.len() as f64)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct SymbolPercentiles {
+    pub exchange: String,
+    pub symbol: String,
+    // Sorted ascending
+    pub volatility: Vec<f64>,
+    pub volume: Vec<f64>,
+    pub range: Vec<f64>,
+    pub days_available: i32,
+    pub span_start: NaiveDate,
+    pub span_end: NaiveDate,
+    pub computed_at: DateTime<Utc>,
+}
+
+impl SymbolPercentiles {
+    // Distribution of the completed days in `metrics`, at most the trailing 30
+    pub fn from_daily(
+        exchange: &str,
+        symbol: &str,
+        metrics: &[DailyMetrics],
+        computed_at: DateTime<Utc>,
+    ) -> Option<Self> {
+        let today = computed_at.date_naive();
+        let completed: Vec<&DailyMetrics> = metrics.iter().filter(|m| m.day < today).collect();
+        let window = &completed[completed.len().saturating_sub(TRAILING_DAYS as usize)..];
+        let (first, last) = (window.first()?, window.last()?);
+
+        let sorted = |f: fn(&DailyMetrics) -> f64| {
+            let mut values: Vec<f64> = window.iter().map(|m| f(m)).collect();
+            values.sort_by(|a, b| a.total_cmp(b));
+            values
+        };
+
+        Some(Self {
+            exchange: exchange.to_string(),
+            symbol: symbol.to_string(),
+            volatility: sorted(|m| m.realized_volatility),
+            volume: sorted(|m| m.volume),
+            range: sorted(|m| m.range),
+            days_available: window.len() as i32,
+            span_start: first.day,
+            span_end: last.day,
+            computed_at,
+        })
+    }
+
+    pub fn context(&self, today: &TodayMetrics, now: DateTime<Utc>) -> PercentileContext {
+        PercentileContext {
+            volatility_percentile_30d: today
+                .realized_volatility
+                .and_then(|v| percentile_rank(&self.volatility, v)),
+            volume_percentile_30d: percentile_rank(&self.volume, today.volume),
+            range_percentile_30d: percentile_rank(&self.range, today.range),
+            percentile_days_available: self.days_available,
+            percentile_span_start: self.span_start,
+            percentile_span_end: self.span_end,
+            percentiles_computed_at: self.computed_at,
+            percentiles_stale: now - self.computed_at > Duration::hours(FRESHNESS_HOURS),
+        }
+    }
+}
+
+// The current day's values to place within the trailing distribution
+#[derive(Debug, Clone, PartialEq)]
+pub struct TodayMetrics {
+    pub realized_volatility: Option<f64>,
+    pub volume: f64,
+    pub range: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PercentileContext {
+    pub volatility_percentile_30d: Option<f64>,
+    pub volume_percentile_30d: Option<f64>,
+    pub range_percentile_30d: Option<f64>,
+    // Fewer than 30 when the symbol has a shorter history
+    pub percentile_days_available: i32,
+    pub percentile_span_start: NaiveDate,
+    pub percentile_span_end: NaiveDate,
+    pub percentiles_computed_at: DateTime<Utc>,
+    pub percentiles_stale: bool,
+}
+
+#[derive(Default)]
+pub struct PercentileCache {
+    entries: DashMap<String, Arc<SymbolPercentiles>>,
+    counters: CacheCounters,
+}
+
+impl PercentileCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, exchange: &str, symbol: &str) -> Option<Arc<SymbolPercentiles>> {
+        let entry = self.entries.get(&format!("{}:{}", exchange, symbol)).map(|e| e.clone());
+        self.counters.observe(entry)
+    }
+
+    pub fn insert(&self, percentiles: SymbolPercentiles) {
+        let key = format!("{}:{}", percentiles.exchange, percentiles.symbol);
+        self.entries.insert(key, Arc::new(percentiles));
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // Warms the cache from the last nightly run
+    pub async fn load(&self, pool: &PgPool) -> Result<()> {
+        let rows: Vec<SymbolPercentiles> = sqlx::query_as(
+            "SELECT exchange, symbol, volatility, volume, range, days_available, span_start, \
+             span_end, computed_at FROM symbol_percentiles",
+        )
+        .fetch_all(pool)
+        .await?;
+
+        for row in rows {
+            self.insert(row);
+        }
+        Ok(())
+    }
+}
+
+impl InstrumentedCache for PercentileCache {
+    fn name(&self) -> &str {
+        "percentiles"
+    }
+
+    fn stats(&self) -> CacheStats {
+        // Three 30-day distributions plus the key and span
+        let entry_bytes = std::mem::size_of::<SymbolPercentiles>() + 3 * 30 * 8 + 32;
+        self.counters.stats(self.name(), self.entries.len(), entry_bytes, Some(24 * 3600))
+    }
+
+    fn clear(&self) {
+        self.entries.clear();
+    }
+}
+
+// Nightly job recomputing every symbol's trailing distribution from stored candles
+pub struct SymbolPercentileJob {
+    pool: PgPool,
+    cache: Arc<PercentileCache>,
+    interval: String,
+}
+
+impl SymbolPercentileJob {
+    pub fn new(pool: PgPool, cache: Arc<PercentileCache>) -> Self {
+        Self {
+            pool,
+            cache,
+            interval: DAILY_METRICS_INTERVAL.to_string(),
+        }
+    }
+
+    // Candle interval the daily metrics are built from, `DAILY_METRICS_INTERVAL` by default
+    pub fn with_interval(mut self, interval: impl Into<String>) -> Self {
+        self.interval = interval.into();
+        self
+    }
+}
+
+#[async_trait]
+impl PeriodicTask for SymbolPercentileJob {
+    fn name(&self) -> &str {
+        "symbol_percentiles"
+    }
+
+    async fn run(&self) -> Result<()> {
+        let now = Utc::now();
+        let from = (now.date_naive() - Duration::days(TRAILING_DAYS)).and_hms_opt(0, 0, 0).unwrap().and_utc();
+
+        let candles: Vec<CandleRecord> = sqlx::query_as(
+            "SELECT exchange, symbol, interval, open_time, open::float8, high::float8, \
+             low::float8, close::float8, volume::float8, trades FROM candles \
+             WHERE open_time >= $1 AND interval = $2 ORDER BY exchange, symbol, open_time",
+        )
+        .bind(from)
+        .bind(&self.interval)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut computed = 0;
+        for group in candles.chunk_by(|a, b| a.exchange == b.exchange && a.symbol == b.symbol) {
+            let (exchange, symbol) = (&group[0].exchange, &group[0].symbol);
+            let Some(percentiles) =
+                SymbolPercentiles::from_daily(exchange, symbol, &daily_metrics(group), now)
+            else {
+                continue;
+            };
+
+            sqlx::query(
+                "INSERT INTO symbol_percentiles (exchange, symbol, volatility, volume, range, \
+                 days_available, span_start, span_end, computed_at) \
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) \
+                 ON CONFLICT (exchange, symbol) DO UPDATE SET volatility = EXCLUDED.volatility, \
+                 volume = EXCLUDED.volume, range = EXCLUDED.range, \
+                 days_available = EXCLUDED.days_available, span_start = EXCLUDED.span_start, \
+                 span_end = EXCLUDED.span_end, computed_at = EXCLUDED.computed_at",
+            )
+            .bind(&percentiles.exchange)
+            .bind(&percentiles.symbol)
+            .bind(&percentiles.volatility)
+            .bind(&percentiles.volume)
+            .bind(&percentiles.range)
+            .bind(percentiles.days_available)
+            .bind(percentiles.span_start)
+            .bind(percentiles.span_end)
+            .bind(percentiles.computed_at)
+            .execute(&self.pool)
+            .await?;
+
+            self.cache.insert(percentiles);
+            computed += 1;
+        }
+
+        info!("Computed trailing percentiles for {} symbols", computed);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    // Hourly candles for `days` days ending yesterday; day `i` trades `i + 1` volume per hour
+    // and swings `i + 1`% around 100
+    fn fixture(days: i64, now: DateTime<Utc>) -> Vec<CandleRecord> {
+        let start = now.date_naive() - Duration::days(days);
+        (0..days)
+            .flat_map(|d| {
+                let swing = (d + 1) as f64;
+                (0..24).map(move |h| {
+                    let open_time = (start + Duration::days(d)).and_hms_opt(h, 0, 0).unwrap().and_utc();
+                    let close = if h % 2 == 0 { 100.0 } else { 100.0 + swing };
+                    CandleRecord {
+                        exchange: "binance".to_string(),
+                        symbol: "BTC/USDT".to_string(),
+                        interval: "1h".to_string(),
+                        open_time,
+                        open: 100.0,
+                        high: 100.0 + swing,
+                        low: 100.0,
+                        close,
+                        volume: swing,
+                        trades: 10,
+                    }
+                })
+            })
+            .collect()
+    }
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 5, 1, 6, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_daily_metrics_from_candles() {
+        let metrics = daily_metrics(&fixture(2, now()));
+
+        assert_eq!(metrics.len(), 2);
+        assert_eq!(metrics[0].volume, 24.0);
+        assert!((metrics[1].range - 0.02).abs() < 1e-12);
+        // 23 alternating moves between 100 and 101
+        let expected = (23.0 * (101.0f64 / 100.0).ln().powi(2)).sqrt();
+        assert!((metrics[0].realized_volatility - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_realized_volatility_skips_non_positive_closes() {
+        let expected = 2.0f64.sqrt() * 2.0f64.ln();
+        assert!((realized_volatility([100.0, 200.0, 100.0]) - expected).abs() < 1e-12);
+        assert_eq!(realized_volatility([100.0, 0.0, 100.0]), 0.0);
+        assert_eq!(realized_volatility([100.0]), 0.0);
+    }
+
+    #[test]
+    fn test_percentile_rank() {
+        let sorted = [1.0, 2.0, 3.0, 4.0];
+        assert_eq!(percentile_rank(&sorted, 0.5), Some(0.0));
+        assert_eq!(percentile_rank(&sorted, 2.0), Some(0.5));
+        assert_eq!(percentile_rank(&sorted, 10.0), Some(1.0));
+        assert_eq!(percentile_rank(&[], 1.0), None);
+    }
+
+    #[test]
+    fn test_uses_trailing_thirty_completed_days() {
+        let now = now();
+        let mut candles = fixture(40, now);
+        // Today's partial candle is not part of the distribution
+        let mut partial = candles.last().unwrap().clone();
+        partial.open_time = now;
+        partial.volume = 1_000.0;
+        candles.push(partial);
+
+        let percentiles =
+            SymbolPercentiles::from_daily("binance", "BTC/USDT", &daily_metrics(&candles), now).unwrap();
+
+        assert_eq!(percentiles.days_available, 30);
+        assert_eq!(percentiles.span_end, now.date_naive() - Duration::days(1));
+        // Days 11..=40 trade 11..=40 per hour
+        assert_eq!(percentiles.volume.first(), Some(&(11.0 * 24.0)));
+        assert_eq!(percentiles.volume.last(), Some(&(40.0 * 24.0)));
+
+        let context = percentiles.context(
+            &TodayMetrics {
+                realized_volatility: None,
+                volume: 35.0 * 24.0,
+                range: 0.5,
+            },
+            now,
+        );
+        assert_eq!(context.volume_percentile_30d, Some(25.0 / 30.0));
+        assert_eq!(context.range_percentile_30d, Some(1.0));
+        assert_eq!(context.volatility_percentile_30d, None);
+        assert!(!context.percentiles_stale);
+    }
+
+    #[test]
+    fn test_short_history_reports_available_span() {
+        let now = now();
+        let percentiles =
+            SymbolPercentiles::from_daily("binance", "BTC/USDT", &daily_metrics(&fixture(5, now)), now)
+                .unwrap();
+
+        assert_eq!(percentiles.days_available, 5);
+        assert_eq!(percentiles.span_start, now.date_naive() - Duration::days(5));
+
+        let stale = percentiles.context(
+            &TodayMetrics {
+                realized_volatility: Some(0.0),
+                volume: 0.0,
+                range: 0.0,
+            },
+            now + Duration::hours(48),
+        );
+        assert_eq!(stale.volatility_percentile_30d, Some(0.0));
+        assert!(stale.percentiles_stale);
+    }
+}
@@ -0,0 +1,517 @@
+use crate::{MonitorError, Result};
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tracing::{debug, error, warn};
+
+// Fraction of a bucket that must be consumed before a near-limit warning is logged
+const NEAR_LIMIT_RATIO: f64 = 0.8;
+
+// A poller running close to the limit would otherwise log on every request
+const NEAR_LIMIT_WARN_INTERVAL: Duration = Duration::from_secs(60);
+
+// Pause applied on 429/418 when the exchange does not send a Retry-After header
+const DEFAULT_BACKOFF: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EndpointClass {
+    General,
+    MarketData,
+    Funding,
+    OpenInterest,
+    Instruments,
+    Orders,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct RateLimitRule {
+    pub capacity: u32,
+    pub interval: Duration,
+}
+
+impl RateLimitRule {
+    pub fn new(capacity: u32, interval: Duration) -> Self {
+        Self { capacity, interval }
+    }
+
+    fn refill_per_sec(&self) -> f64 {
+        self.capacity as f64 / self.interval.as_secs_f64()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    pub exchange: String,
+    // Shared bucket charged by every request (eg/ Binance IP request weight)
+    pub global: Option<RateLimitRule>,
+    // Additional per-endpoint buckets (eg/ OKX "20 requests per 2 seconds" limits)
+    pub endpoints: HashMap<EndpointClass, RateLimitRule>,
+}
+
+impl RateLimitConfig {
+    pub fn for_exchange(exchange: &str) -> Self {
+        let exchange = exchange.to_lowercase();
+
+        match exchange.as_str() {
+            // https://binance-docs.github.io/apidocs/spot/en/#limits
+            "binance" | "binance_spot" | "binance_futures_usd" => Self {
+                exchange,
+                global: Some(RateLimitRule::new(6000, Duration::from_secs(60))),
+                endpoints: HashMap::from([
+                    (EndpointClass::Orders, RateLimitRule::new(100, Duration::from_secs(10))),
+                ]),
+            },
+            // https://www.okx.com/docs-v5/en/#rest-api-rate-limit
+            "okx" => Self {
+                exchange,
+                global: None,
+                endpoints: HashMap::from([
+                    (EndpointClass::General, RateLimitRule::new(20, Duration::from_secs(2))),
+                    (EndpointClass::MarketData, RateLimitRule::new(40, Duration::from_secs(2))),
+                    (EndpointClass::Funding, RateLimitRule::new(20, Duration::from_secs(2))),
+                    (EndpointClass::OpenInterest, RateLimitRule::new(20, Duration::from_secs(2))),
+                    (EndpointClass::Instruments, RateLimitRule::new(20, Duration::from_secs(2))),
+                    (EndpointClass::Orders, RateLimitRule::new(60, Duration::from_secs(2))),
+                ]),
+            },
+            "bybit" => Self {
+                exchange,
+                global: Some(RateLimitRule::new(600, Duration::from_secs(5))),
+                endpoints: HashMap::new(),
+            },
+            _ => Self {
+                exchange,
+                global: Some(RateLimitRule::new(10, Duration::from_secs(1))),
+                endpoints: HashMap::new(),
+            },
+        }
+    }
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    rule: RateLimitRule,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(rule: RateLimitRule) -> Self {
+        Self {
+            rule,
+            tokens: rule.capacity as f64,
+            last_refill: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rule.refill_per_sec())
+            .min(self.rule.capacity as f64);
+        self.last_refill = now;
+    }
+
+    fn wait_for(&self, weight: u32) -> Duration {
+        let missing = weight as f64 - self.tokens;
+        if missing <= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(missing / self.rule.refill_per_sec())
+        }
+    }
+
+    fn used_ratio(&self) -> f64 {
+        1.0 - self.tokens / self.rule.capacity as f64
+    }
+}
+
+#[derive(Debug)]
+struct LimiterState {
+    global: Option<TokenBucket>,
+    endpoints: HashMap<EndpointClass, TokenBucket>,
+    paused_until: Option<Instant>,
+    last_near_limit_warning: Option<Instant>,
+}
+
+#[derive(Debug, Default)]
+pub struct RateLimiterMetrics {
+    pub requests: AtomicU64,
+    pub throttled: AtomicU64,
+    pub total_wait_ms: AtomicU64,
+    pub near_limit_warnings: AtomicU64,
+    pub backoffs: AtomicU64,
+    pub bans: AtomicU64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimiterSnapshot {
+    pub exchange: String,
+    pub requests: u64,
+    pub throttled: u64,
+    pub total_wait_ms: u64,
+    pub near_limit_warnings: u64,
+    pub backoffs: u64,
+    pub bans: u64,
+    pub paused_for_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackoffSignal {
+    // 429: request rate exceeded, back off for Retry-After
+    RateLimited(Duration),
+    // 418: Binance auto-banned the IP after repeated 429s
+    IpBanned(Duration),
+}
+
+impl BackoffSignal {
+    // Interprets an HTTP response from an exchange REST API. `retry_after` is the raw
+    // value of the Retry-After header (seconds) when present.
+    pub fn from_response(status: u16, retry_after: Option<&str>) -> Option<Self> {
+        let retry_after = retry_after
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .map(Duration::from_secs);
+
+        match status {
+            429 => Some(Self::RateLimited(retry_after.unwrap_or(DEFAULT_BACKOFF))),
+            418 => Some(Self::IpBanned(retry_after.unwrap_or(DEFAULT_BACKOFF * 2))),
+            _ => None,
+        }
+    }
+
+    pub fn duration(&self) -> Duration {
+        match self {
+            Self::RateLimited(duration) | Self::IpBanned(duration) => *duration,
+        }
+    }
+}
+
+pub struct RestRateLimiter {
+    exchange: String,
+    state: Mutex<LimiterState>,
+    metrics: RateLimiterMetrics,
+}
+
+impl RestRateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            exchange: config.exchange,
+            state: Mutex::new(LimiterState {
+                global: config.global.map(TokenBucket::new),
+                endpoints: config
+                    .endpoints
+                    .into_iter()
+                    .map(|(class, rule)| (class, TokenBucket::new(rule)))
+                    .collect(),
+                paused_until: None,
+                last_near_limit_warning: None,
+            }),
+            metrics: RateLimiterMetrics::default(),
+        }
+    }
+
+    pub fn exchange(&self) -> &str {
+        &self.exchange
+    }
+
+    pub async fn acquire(&self, class: EndpointClass, weight: u32) -> Result<()> {
+        let started = Instant::now();
+        let mut throttled = false;
+
+        loop {
+            let wait = {
+                let mut state = self.state.lock();
+                let now = Instant::now();
+
+                match state.paused_until {
+                    Some(until) if until > now => until - now,
+                    _ => {
+                        state.paused_until = None;
+                        self.try_take(&mut state, class, weight, now)?
+                    }
+                }
+            };
+
+            if wait.is_zero() {
+                break;
+            }
+
+            throttled = true;
+            debug!("{} rate limiter waiting {:?} for {:?}", self.exchange, wait, class);
+            tokio::time::sleep(wait).await;
+        }
+
+        self.metrics.requests.fetch_add(1, Ordering::Relaxed);
+        if throttled {
+            self.metrics.throttled.fetch_add(1, Ordering::Relaxed);
+            self.metrics
+                .total_wait_ms
+                .fetch_add(started.elapsed().as_millis() as u64, Ordering::Relaxed);
+        }
+
+        Ok(())
+    }
+
+    // Takes `weight` tokens from every bucket that applies to `class`, or returns how long
+    // the caller must wait before all of them can cover the request.
+    fn try_take(
+        &self,
+        state: &mut LimiterState,
+        class: EndpointClass,
+        weight: u32,
+        now: Instant,
+    ) -> Result<Duration> {
+        let LimiterState {
+            global,
+            endpoints,
+            last_near_limit_warning,
+            ..
+        } = state;
+        let mut buckets: Vec<&mut TokenBucket> = global
+            .iter_mut()
+            .chain(endpoints.get_mut(&class))
+            .collect();
+
+        let mut wait = Duration::ZERO;
+        for bucket in buckets.iter_mut() {
+            if weight > bucket.rule.capacity {
+                return Err(MonitorError::Configuration(format!(
+                    "Request weight {} exceeds {} rate limit capacity {}",
+                    weight, self.exchange, bucket.rule.capacity
+                )));
+            }
+            bucket.refill(now);
+            wait = wait.max(bucket.wait_for(weight));
+        }
+
+        if !wait.is_zero() {
+            return Ok(wait);
+        }
+
+        for bucket in buckets {
+            bucket.tokens -= weight as f64;
+            let warn_due = last_near_limit_warning
+                .is_none_or(|at| now.saturating_duration_since(at) >= NEAR_LIMIT_WARN_INTERVAL);
+            if bucket.used_ratio() >= NEAR_LIMIT_RATIO && warn_due {
+                *last_near_limit_warning = Some(now);
+                self.metrics.near_limit_warnings.fetch_add(1, Ordering::Relaxed);
+                warn!(
+                    "{} REST usage at {:.0}% of {} per {:?}",
+                    self.exchange,
+                    bucket.used_ratio() * 100.0,
+                    bucket.rule.capacity,
+                    bucket.rule.interval
+                );
+            }
+        }
+
+        Ok(Duration::ZERO)
+    }
+
+    // Must be called by REST callers with every response status so that 429/418 pause
+    // all other callers for this exchange.
+    pub fn record_response(&self, status: u16, retry_after: Option<&str>) -> Option<BackoffSignal> {
+        let signal = BackoffSignal::from_response(status, retry_after)?;
+        self.pause_for(signal.duration());
+
+        match signal {
+            BackoffSignal::RateLimited(duration) => {
+                self.metrics.backoffs.fetch_add(1, Ordering::Relaxed);
+                warn!("{} returned 429, pausing REST calls for {:?}", self.exchange, duration);
+            }
+            BackoffSignal::IpBanned(duration) => {
+                self.metrics.bans.fetch_add(1, Ordering::Relaxed);
+                error!("{} banned this IP (418), pausing REST calls for {:?}", self.exchange, duration);
+            }
+        }
+
+        Some(signal)
+    }
+
+    pub fn pause_for(&self, duration: Duration) {
+        let until = Instant::now() + duration;
+        let mut state = self.state.lock();
+        state.paused_until = Some(state.paused_until.map_or(until, |current| current.max(until)));
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.state
+            .lock()
+            .paused_until
+            .is_some_and(|until| until > Instant::now())
+    }
+
+    pub fn metrics(&self) -> &RateLimiterMetrics {
+        &self.metrics
+    }
+
+    pub fn snapshot(&self) -> RateLimiterSnapshot {
+        let paused_for_ms = self.state.lock().paused_until.and_then(|until| {
+            let remaining = until.saturating_duration_since(Instant::now());
+            (!remaining.is_zero()).then(|| remaining.as_millis() as u64)
+        });
+
+        RateLimiterSnapshot {
+            exchange: self.exchange.clone(),
+            requests: self.metrics.requests.load(Ordering::Relaxed),
+            throttled: self.metrics.throttled.load(Ordering::Relaxed),
+            total_wait_ms: self.metrics.total_wait_ms.load(Ordering::Relaxed),
+            near_limit_warnings: self.metrics.near_limit_warnings.load(Ordering::Relaxed),
+            backoffs: self.metrics.backoffs.load(Ordering::Relaxed),
+            bans: self.metrics.bans.load(Ordering::Relaxed),
+            paused_for_ms,
+        }
+    }
+}
+
+// Process-wide registry so every poller hitting the same exchange shares one limiter
+#[derive(Clone, Default)]
+pub struct RestRateLimiters {
+    limiters: Arc<DashMap<String, Arc<RestRateLimiter>>>,
+}
+
+impl RestRateLimiters {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_config(&self, mut config: RateLimitConfig) -> Arc<RestRateLimiter> {
+        // `get` looks exchanges up lowercased
+        config.exchange = config.exchange.to_lowercase();
+        let limiter = Arc::new(RestRateLimiter::new(config));
+        self.limiters.insert(limiter.exchange().to_string(), limiter.clone());
+        limiter
+    }
+
+    pub fn get(&self, exchange: &str) -> Arc<RestRateLimiter> {
+        let exchange = exchange.to_lowercase();
+        self.limiters
+            .entry(exchange.clone())
+            .or_insert_with(|| Arc::new(RestRateLimiter::new(RateLimitConfig::for_exchange(&exchange))))
+            .clone()
+    }
+
+    pub fn snapshots(&self) -> Vec<RateLimiterSnapshot> {
+        self.limiters.iter().map(|l| l.snapshot()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config(global: u32, per: Duration) -> RateLimitConfig {
+        RateLimitConfig {
+            exchange: "test".to_string(),
+            global: Some(RateLimitRule::new(global, per)),
+            endpoints: HashMap::from([
+                (EndpointClass::Funding, RateLimitRule::new(4, per)),
+            ]),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_pollers_stay_within_limit() {
+        let per = Duration::from_millis(200);
+        let limiter = Arc::new(RestRateLimiter::new(test_config(10, per)));
+        let started = Instant::now();
+        let run_for = Duration::from_millis(600);
+
+        let pollers = (0..4).map(|_| {
+            let limiter = limiter.clone();
+            tokio::spawn(async move {
+                let mut weight = 0;
+                while started.elapsed() < run_for {
+                    limiter.acquire(EndpointClass::General, 2).await.unwrap();
+                    weight += 2;
+                }
+                weight
+            })
+        });
+
+        let mut total = 0;
+        for poller in pollers {
+            total += poller.await.unwrap();
+        }
+
+        // Initial burst of one full bucket plus refill over the run (with one request of slack)
+        let elapsed = started.elapsed().as_secs_f64();
+        let allowed = 10.0 + 10.0 * elapsed / per.as_secs_f64() + 2.0;
+        assert!(total as f64 <= allowed, "used {} weight, allowed {}", total, allowed);
+        assert!(limiter.snapshot().throttled > 0);
+    }
+
+    #[tokio::test]
+    async fn test_endpoint_bucket_limits_independently() {
+        let per = Duration::from_millis(200);
+        let limiter = RestRateLimiter::new(test_config(100, per));
+
+        let started = Instant::now();
+        for _ in 0..8 {
+            limiter.acquire(EndpointClass::Funding, 1).await.unwrap();
+        }
+
+        // 4 from the initial bucket, the next 4 need a full refill interval
+        assert!(started.elapsed() >= per.mul_f64(0.9));
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_pauses_all_callers() {
+        let limiter = RestRateLimiter::new(test_config(100, Duration::from_secs(1)));
+
+        let signal = limiter.record_response(429, Some("1"));
+        assert_eq!(signal, Some(BackoffSignal::RateLimited(Duration::from_secs(1))));
+        assert!(limiter.is_paused());
+
+        let started = Instant::now();
+        limiter.acquire(EndpointClass::MarketData, 1).await.unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(900));
+        assert_eq!(limiter.snapshot().backoffs, 1);
+    }
+
+    #[test]
+    fn test_backoff_signal_parsing() {
+        assert_eq!(
+            BackoffSignal::from_response(418, Some("120")),
+            Some(BackoffSignal::IpBanned(Duration::from_secs(120)))
+        );
+        assert_eq!(
+            BackoffSignal::from_response(429, None),
+            Some(BackoffSignal::RateLimited(DEFAULT_BACKOFF))
+        );
+        assert_eq!(BackoffSignal::from_response(200, None), None);
+    }
+
+    #[tokio::test]
+    async fn test_near_limit_warning_is_rate_limited() {
+        let limiter = RestRateLimiter::new(test_config(10, Duration::from_secs(60)));
+        // The last two requests both leave the bucket more than 80% used
+        for _ in 0..10 {
+            limiter.acquire(EndpointClass::General, 1).await.unwrap();
+        }
+        assert_eq!(limiter.snapshot().near_limit_warnings, 1);
+    }
+
+    #[test]
+    fn test_configured_limiter_is_found_case_insensitively() {
+        let limiters = RestRateLimiters::new();
+        let configured = limiters.with_config(RateLimitConfig {
+            exchange: "OKX".to_string(),
+            ..test_config(10, Duration::from_secs(1))
+        });
+        assert!(Arc::ptr_eq(&configured, &limiters.get("okx")));
+    }
+
+    #[tokio::test]
+    async fn test_weight_above_capacity_is_rejected() {
+        let limiter = RestRateLimiter::new(test_config(5, Duration::from_secs(1)));
+        assert!(limiter.acquire(EndpointClass::General, 6).await.is_err());
+    }
+}
@@ -0,0 +1,520 @@
+use crate::{event::MonitorEventRef, MonitorError, MonitorEvent, Result};
+use async_trait::async_trait;
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+use tracing::{debug, warn};
+
+// Processing guarantee for a consumed topic.
+//
+// `AtMostOnce` commits the checkpoint before the handler runs: a crash mid-handler loses that
+// record but it is never reprocessed. Used for broadcast-style consumers where a duplicate is
+// worse than a gap (websocket fan-out, market data mirrors).
+//
+// `AtLeastOnce` commits only after the handler succeeded: a crash mid-handler replays the record
+// on restart, so handlers must be idempotent. Handlers on these topics are expected to claim the
+// event id (`MonitorEvent::id`) before acting, which turns the replay into a no-op for anything
+// that already completed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum DeliverySemantics {
+    AtMostOnce,
+    AtLeastOnce,
+}
+
+impl DeliverySemantics {
+    pub fn default_for(topic: &str) -> Self {
+        if topic.contains(".market.") {
+            DeliverySemantics::AtMostOnce
+        } else {
+            // Trading, anomaly and alert topics must never skip
+            DeliverySemantics::AtLeastOnce
+        }
+    }
+}
+
+#[async_trait]
+pub trait TopicHandler: Send + Sync {
+    async fn handle(&self, event: &MonitorEvent) -> Result<()>;
+
+    // Handlers that can work on the borrowed record override this to skip the owned copy
+    async fn handle_ref(&self, event: &MonitorEventRef<'_>) -> Result<()> {
+        self.handle(&event.to_owned()?).await
+    }
+}
+
+pub trait CheckpointStore: Send + Sync {
+    fn load(&self, topic: &str) -> Option<i64>;
+    fn commit(&self, topic: &str, offset: i64);
+
+    // Makes the commits so far durable, stores that keep nothing have nothing to do
+    fn flush(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct InMemoryCheckpointStore {
+    offsets: DashMap<String, i64>,
+}
+
+impl InMemoryCheckpointStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl CheckpointStore for InMemoryCheckpointStore {
+    fn load(&self, topic: &str) -> Option<i64> {
+        self.offsets.get(topic).map(|o| *o)
+    }
+
+    fn commit(&self, topic: &str, offset: i64) {
+        self.offsets.insert(topic.to_string(), offset);
+    }
+}
+
+// Offsets kept in a JSON file keyed by topic. Commits stay in memory until `flush`, which the
+// monitor calls every second and on stop, so the per-record path never touches the disk. A
+// crash replays at most the records committed since the last flush
+pub struct FileCheckpointStore {
+    path: PathBuf,
+    offsets: DashMap<String, i64>,
+    dirty: AtomicBool,
+}
+
+impl FileCheckpointStore {
+    // Resumes from the offsets in `path`, a missing file is a first start
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let offsets: HashMap<String, i64> = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => {
+                return Err(MonitorError::Other(format!(
+                    "Failed to read consumer checkpoints {}: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+
+        Ok(Self {
+            path,
+            offsets: offsets.into_iter().collect(),
+            dirty: AtomicBool::new(false),
+        })
+    }
+}
+
+impl CheckpointStore for FileCheckpointStore {
+    fn load(&self, topic: &str) -> Option<i64> {
+        self.offsets.get(topic).map(|o| *o)
+    }
+
+    fn commit(&self, topic: &str, offset: i64) {
+        self.offsets.insert(topic.to_string(), offset);
+        self.dirty.store(true, Ordering::Release);
+    }
+
+    fn flush(&self) -> Result<()> {
+        if !self.dirty.swap(false, Ordering::AcqRel) {
+            return Ok(());
+        }
+        let offsets: HashMap<String, i64> =
+            self.offsets.iter().map(|e| (e.key().clone(), *e.value())).collect();
+
+        // Write then rename so a crash never leaves a truncated file behind
+        let tmp = self.path.with_extension("tmp");
+        let write = || -> std::io::Result<()> {
+            if let Some(parent) = self.path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&tmp, serde_json::to_vec(&offsets)?)?;
+            std::fs::rename(&tmp, &self.path)
+        };
+        write().map_err(|e| {
+            // Left for the next flush to retry
+            self.dirty.store(true, Ordering::Release);
+            MonitorError::Other(format!(
+                "Failed to write consumer checkpoints {}: {}",
+                self.path.display(),
+                e
+            ))
+        })
+    }
+}
+
+// The most recent record a topic's handler failed on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsumerFailure {
+    pub offset: i64,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsumerStatus {
+    pub topic: String,
+    pub semantics: DeliverySemantics,
+    pub checkpoint: Option<i64>,
+    // Records that failed to parse or whose handler returned an error
+    pub failed: u64,
+    pub last_failure: Option<ConsumerFailure>,
+}
+
+struct Route {
+    semantics: DeliverySemantics,
+    handler: Arc<dyn TopicHandler>,
+}
+
+pub struct TopicRouter {
+    routes: HashMap<String, Route>,
+    overrides: HashMap<String, DeliverySemantics>,
+    checkpoints: Arc<dyn CheckpointStore>,
+    failures: DashMap<String, (u64, ConsumerFailure)>,
+}
+
+impl TopicRouter {
+    pub fn new(checkpoints: Arc<dyn CheckpointStore>) -> Self {
+        Self {
+            routes: HashMap::new(),
+            overrides: HashMap::new(),
+            checkpoints,
+            failures: DashMap::new(),
+        }
+    }
+
+    // Per-topic semantics from configuration, applied to routes registered afterwards
+    pub fn with_overrides(mut self, overrides: HashMap<String, DeliverySemantics>) -> Self {
+        self.overrides = overrides;
+        self
+    }
+
+    pub fn route(&mut self, topic: impl Into<String>, handler: Arc<dyn TopicHandler>) -> &mut Self {
+        let topic = topic.into();
+        let semantics = self
+            .overrides
+            .get(&topic)
+            .copied()
+            .unwrap_or_else(|| DeliverySemantics::default_for(&topic));
+
+        self.routes.insert(topic, Route { semantics, handler });
+        self
+    }
+
+    pub fn semantics(&self, topic: &str) -> Option<DeliverySemantics> {
+        self.routes.get(topic).map(|r| r.semantics)
+    }
+
+    // Offset the consumer should resume from, `None` means start at the end of the topic
+    pub fn resume_offset(&self, topic: &str) -> Option<i64> {
+        self.checkpoints.load(topic).map(|o| o + 1)
+    }
+
+    // The route for a record that still needs processing
+    fn pending_route(&self, topic: &str, offset: i64) -> Option<&Route> {
+        let Some(route) = self.routes.get(topic) else {
+            warn!("No route registered for topic {}", topic);
+            return None;
+        };
+
+        if let Some(committed) = self.checkpoints.load(topic) {
+            if offset <= committed {
+                debug!("Skipping already checkpointed offset {} on {}", offset, topic);
+                return None;
+            }
+        }
+
+        Some(route)
+    }
+
+    pub async fn dispatch(&self, topic: &str, offset: i64, event: &MonitorEvent) -> Result<()> {
+        let result = self.dispatch_owned(topic, offset, event).await;
+        self.record_result(topic, offset, result)
+    }
+
+    async fn dispatch_owned(&self, topic: &str, offset: i64, event: &MonitorEvent) -> Result<()> {
+        let Some(route) = self.pending_route(topic, offset) else {
+            return Ok(());
+        };
+
+        match route.semantics {
+            DeliverySemantics::AtMostOnce => {
+                self.checkpoints.commit(topic, offset);
+                route.handler.handle(event).await
+            }
+            DeliverySemantics::AtLeastOnce => {
+                route.handler.handle(event).await?;
+                self.checkpoints.commit(topic, offset);
+                Ok(())
+            }
+        }
+    }
+
+    // Dispatches a serialized record without building the owned event, a record that fails to
+    // parse is an error and leaves the checkpoint alone
+    pub async fn dispatch_raw(&self, topic: &str, offset: i64, record: &[u8]) -> Result<()> {
+        let result = self.dispatch_borrowed(topic, offset, record).await;
+        self.record_result(topic, offset, result)
+    }
+
+    async fn dispatch_borrowed(&self, topic: &str, offset: i64, record: &[u8]) -> Result<()> {
+        let event = MonitorEventRef::from_slice(record)?;
+        let Some(route) = self.pending_route(topic, offset) else {
+            return Ok(());
+        };
+
+        match route.semantics {
+            DeliverySemantics::AtMostOnce => {
+                self.checkpoints.commit(topic, offset);
+                route.handler.handle_ref(&event).await
+            }
+            DeliverySemantics::AtLeastOnce => {
+                route.handler.handle_ref(&event).await?;
+                self.checkpoints.commit(topic, offset);
+                Ok(())
+            }
+        }
+    }
+
+    // Counts a failed record against its topic, so the consumer can carry on past it without
+    // the failure going unnoticed
+    fn record_result(&self, topic: &str, offset: i64, result: Result<()>) -> Result<()> {
+        if let Err(e) = &result {
+            let failure = ConsumerFailure {
+                offset,
+                error: e.to_string(),
+            };
+            let mut entry = self.failures.entry(topic.to_string()).or_insert((0, failure.clone()));
+            entry.0 += 1;
+            entry.1 = failure;
+        }
+        result
+    }
+
+    pub fn flush_checkpoints(&self) -> Result<()> {
+        self.checkpoints.flush()
+    }
+
+    pub fn consumer_statuses(&self) -> Vec<ConsumerStatus> {
+        let mut statuses: Vec<ConsumerStatus> = self
+            .routes
+            .iter()
+            .map(|(topic, route)| {
+                let failures = self.failures.get(topic);
+                ConsumerStatus {
+                    topic: topic.clone(),
+                    semantics: route.semantics,
+                    checkpoint: self.checkpoints.load(topic),
+                    failed: failures.as_ref().map_or(0, |f| f.0),
+                    last_failure: failures.map(|f| f.1.clone()),
+                }
+            })
+            .collect();
+        statuses.sort_by(|a, b| a.topic.cmp(&b.topic));
+        statuses
+    }
+}
+
+// Append-only in-process bus, used to exercise router guarantees without a Fluvio cluster
+#[derive(Default)]
+pub struct InMemoryBus {
+    topics: DashMap<String, Vec<MonitorEvent>>,
+}
+
+impl InMemoryBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn publish(&self, topic: &str, event: MonitorEvent) -> i64 {
+        let mut log = self.topics.entry(topic.to_string()).or_default();
+        log.push(event);
+        log.len() as i64 - 1
+    }
+
+    // Feeds every record from the router's resume point; stops at the first handler error
+    // the way a crashed consumer would.
+    pub async fn consume(&self, topic: &str, router: &TopicRouter) -> Result<()> {
+        let records: Vec<MonitorEvent> = self
+            .topics
+            .get(topic)
+            .map(|log| log.clone())
+            .unwrap_or_default();
+        let start = router.resume_offset(topic).unwrap_or(0);
+
+        for (offset, event) in records.iter().enumerate().skip(start as usize) {
+            router.dispatch(topic, offset as i64, event).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{event::EventBuilder, EventSource, EventType, MonitorError, SystemEventType};
+    use parking_lot::Mutex;
+
+    // Records handled events and "crashes" once on the configured offset
+    struct CrashingHandler {
+        handled: Mutex<Vec<uuid::Uuid>>,
+        crash_on: uuid::Uuid,
+        crashed: AtomicBool,
+    }
+
+    #[async_trait]
+    impl TopicHandler for CrashingHandler {
+        async fn handle(&self, event: &MonitorEvent) -> Result<()> {
+            if event.id == self.crash_on && !self.crashed.swap(true, Ordering::SeqCst) {
+                return Err(MonitorError::Other("simulated crash".to_string()));
+            }
+            self.handled.lock().push(event.id);
+            Ok(())
+        }
+    }
+
+    fn event() -> MonitorEvent {
+        EventBuilder::new()
+            .with_source(EventSource::Monitor)
+            .with_type(EventType::System(SystemEventType::Started))
+            .build()
+            .unwrap()
+    }
+
+    async fn run_with_crash(topic: &str) -> (Vec<uuid::Uuid>, Vec<uuid::Uuid>, DeliverySemantics) {
+        let bus = InMemoryBus::new();
+        let events: Vec<MonitorEvent> = (0..5).map(|_| event()).collect();
+        for event in &events {
+            bus.publish(topic, event.clone());
+        }
+
+        let handler = Arc::new(CrashingHandler {
+            handled: Mutex::new(Vec::new()),
+            crash_on: events[2].id,
+            crashed: AtomicBool::new(false),
+        });
+
+        let mut router = TopicRouter::new(Arc::new(InMemoryCheckpointStore::new()));
+        router.route(topic, handler.clone());
+
+        // First run crashes on the third record, the restart resumes from the checkpoint
+        assert!(bus.consume(topic, &router).await.is_err());
+        bus.consume(topic, &router).await.unwrap();
+
+        let handled = handler.handled.lock().clone();
+        let ids = events.iter().map(|e| e.id).collect();
+        (handled, ids, router.semantics(topic).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_at_least_once_replays_crashed_record() {
+        let (handled, ids, semantics) = run_with_crash("crypto-monitor.trades").await;
+
+        assert_eq!(semantics, DeliverySemantics::AtLeastOnce);
+        assert_eq!(handled, ids);
+    }
+
+    #[tokio::test]
+    async fn test_at_most_once_never_reprocesses() {
+        let (handled, ids, semantics) = run_with_crash("crypto-monitor.market.trades").await;
+
+        assert_eq!(semantics, DeliverySemantics::AtMostOnce);
+        assert_eq!(handled.len(), 4);
+        assert!(!handled.contains(&ids[2]));
+    }
+
+    #[tokio::test]
+    async fn test_raw_records_fall_back_to_owned_handler() {
+        let topic = "crypto-monitor.trades";
+        let handler = Arc::new(CrashingHandler {
+            handled: Mutex::new(Vec::new()),
+            crash_on: uuid::Uuid::nil(),
+            crashed: AtomicBool::new(false),
+        });
+        let mut router = TopicRouter::new(Arc::new(InMemoryCheckpointStore::new()));
+        router.route(topic, handler.clone());
+
+        let event = event();
+        router
+            .dispatch_raw(topic, 0, &serde_json::to_vec(&event).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(*handler.handled.lock(), vec![event.id]);
+
+        // A record that doesn't parse is reported and not checkpointed
+        assert!(router.dispatch_raw(topic, 1, b"{\"id\": \xff}").await.is_err());
+        assert_eq!(router.resume_offset(topic), Some(1));
+        let status = &router.consumer_statuses()[0];
+        assert_eq!(status.failed, 1);
+        assert_eq!(status.last_failure.as_ref().unwrap().offset, 1);
+    }
+
+    #[tokio::test]
+    async fn test_file_checkpoints_resume_after_restart() {
+        let topic = "crypto-monitor.anomalies";
+        let path = std::env::temp_dir().join(format!("checkpoints-{}.json", uuid::Uuid::new_v4()));
+        let bus = InMemoryBus::new();
+        let events: Vec<MonitorEvent> = (0..5).map(|_| event()).collect();
+        let handler = || {
+            Arc::new(CrashingHandler {
+                handled: Mutex::new(Vec::new()),
+                crash_on: events[3].id,
+                crashed: AtomicBool::new(false),
+            })
+        };
+        for event in &events[..3] {
+            bus.publish(topic, event.clone());
+        }
+
+        let store = Arc::new(FileCheckpointStore::open(&path).unwrap());
+        let mut router = TopicRouter::new(store.clone());
+        router.route(topic, handler());
+        bus.consume(topic, &router).await.unwrap();
+        // Nothing reaches the file before a flush
+        assert!(FileCheckpointStore::open(&path).unwrap().load(topic).is_none());
+        router.flush_checkpoints().unwrap();
+        drop(router);
+
+        // The restarted consumer picks up after the last flushed record
+        for event in &events[3..] {
+            bus.publish(topic, event.clone());
+        }
+        let handler = handler();
+        let mut router = TopicRouter::new(Arc::new(FileCheckpointStore::open(&path).unwrap()));
+        router.route(topic, handler.clone());
+        assert_eq!(router.resume_offset(topic), Some(3));
+        assert!(bus.consume(topic, &router).await.is_err());
+        bus.consume(topic, &router).await.unwrap();
+        assert_eq!(*handler.handled.lock(), [events[3].id, events[4].id]);
+        assert_eq!(router.consumer_statuses()[0].failed, 1);
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_overrides_take_precedence() {
+        let mut router = TopicRouter::new(Arc::new(InMemoryCheckpointStore::new())).with_overrides(
+            HashMap::from([("crypto-monitor.anomalies".to_string(), DeliverySemantics::AtMostOnce)]),
+        );
+        router.route(
+            "crypto-monitor.anomalies",
+            Arc::new(CrashingHandler {
+                handled: Mutex::new(Vec::new()),
+                crash_on: uuid::Uuid::nil(),
+                crashed: AtomicBool::new(false),
+            }),
+        );
+
+        assert_eq!(router.consumer_statuses()[0].semantics, DeliverySemantics::AtMostOnce);
+    }
+}
@@ -0,0 +1,126 @@
+use crate::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+use std::sync::Arc;
+use tokio::{sync::watch, task::JoinHandle};
+use tracing::{error, info};
+
+#[async_trait]
+pub trait PeriodicTask: Send + Sync {
+    fn name(&self) -> &str;
+    async fn run(&self) -> Result<()>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Schedule {
+    Every(Duration),
+    // Once a day at the given UTC time
+    DailyAt(NaiveTime),
+}
+
+impl Schedule {
+    pub fn next_run(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        match self {
+            Schedule::Every(interval) => now + *interval,
+            Schedule::DailyAt(time) => {
+                let today = now.date_naive().and_time(*time).and_utc();
+                if today > now {
+                    today
+                } else {
+                    today + Duration::days(1)
+                }
+            }
+        }
+    }
+}
+
+struct ScheduledTask {
+    task: Arc<dyn PeriodicTask>,
+    schedule: Schedule,
+    run_on_start: bool,
+}
+
+#[derive(Default)]
+pub struct Scheduler {
+    tasks: Vec<ScheduledTask>,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, task: Arc<dyn PeriodicTask>, schedule: Schedule) -> &mut Self {
+        self.tasks.push(ScheduledTask {
+            task,
+            schedule,
+            run_on_start: false,
+        });
+        self
+    }
+
+    // Also runs the task once immediately, for jobs whose output is needed before the first slot
+    pub fn add_and_run(&mut self, task: Arc<dyn PeriodicTask>, schedule: Schedule) -> &mut Self {
+        self.tasks.push(ScheduledTask {
+            task,
+            schedule,
+            run_on_start: true,
+        });
+        self
+    }
+
+    pub fn start(self, shutdown: watch::Receiver<bool>) -> Vec<JoinHandle<()>> {
+        self.tasks
+            .into_iter()
+            .map(|scheduled| {
+                let mut shutdown = shutdown.clone();
+                tokio::spawn(async move {
+                    let ScheduledTask {
+                        task,
+                        schedule,
+                        mut run_on_start,
+                    } = scheduled;
+
+                    loop {
+                        if !run_on_start {
+                            let now = Utc::now();
+                            let wait = (schedule.next_run(now) - now).to_std().unwrap_or_default();
+                            tokio::select! {
+                                _ = shutdown.changed() => break,
+                                _ = tokio::time::sleep(wait) => {}
+                            }
+                        }
+                        run_on_start = false;
+
+                        let started = Utc::now();
+                        match task.run().await {
+                            Ok(()) => info!(
+                                "Periodic task {} finished in {}ms",
+                                task.name(),
+                                (Utc::now() - started).num_milliseconds()
+                            ),
+                            Err(e) => error!("Periodic task {} failed: {}", task.name(), e),
+                        }
+                    }
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_daily_schedule_rolls_to_next_day() {
+        let schedule = Schedule::DailyAt(NaiveTime::from_hms_opt(2, 0, 0).unwrap());
+
+        let before = Utc.with_ymd_and_hms(2024, 5, 1, 1, 0, 0).unwrap();
+        assert_eq!(schedule.next_run(before), Utc.with_ymd_and_hms(2024, 5, 1, 2, 0, 0).unwrap());
+
+        let after = Utc.with_ymd_and_hms(2024, 5, 1, 2, 0, 0).unwrap();
+        assert_eq!(schedule.next_run(after), Utc.with_ymd_and_hms(2024, 5, 2, 2, 0, 0).unwrap());
+    }
+}
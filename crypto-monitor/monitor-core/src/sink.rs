@@ -0,0 +1,408 @@
+use crate::{MonitorError, MonitorEvent, Result};
+use chrono::{DateTime, Duration, Utc};
+use flate2::{write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+use tokio::{
+    sync::{mpsc, oneshot},
+    task::JoinHandle,
+};
+use tracing::{error, info, warn};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FileSinkConfig {
+    pub directory: PathBuf,
+    #[serde(default = "default_max_file_bytes")]
+    pub max_file_bytes: u64,
+    #[serde(default = "default_max_file_age_secs")]
+    pub max_file_age_secs: u64,
+    #[serde(default)]
+    pub compression: SinkCompression,
+    #[serde(default)]
+    pub fsync: FsyncPolicy,
+    // Topics to capture, matched exactly or by suffix (`market.trades`); empty captures all
+    #[serde(default)]
+    pub topics: Vec<String>,
+    #[serde(default = "default_queue_capacity")]
+    pub queue_capacity: usize,
+}
+
+fn default_max_file_bytes() -> u64 {
+    64 * 1024 * 1024
+}
+
+fn default_max_file_age_secs() -> u64 {
+    3600
+}
+
+fn default_queue_capacity() -> usize {
+    10_000
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SinkCompression {
+    #[default]
+    None,
+    Gzip,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FsyncPolicy {
+    Never,
+    // Sync when a file is closed, on rotation or shutdown
+    #[default]
+    OnRotate,
+    // Sync after every line
+    Always,
+}
+
+// One NDJSON line of a capture file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapturedEvent {
+    pub topic: String,
+    pub captured_at: DateTime<Utc>,
+    pub event: MonitorEvent,
+}
+
+enum SinkCommand {
+    Write(String),
+    Shutdown(oneshot::Sender<()>),
+}
+
+pub struct FileSink {
+    tx: mpsc::Sender<SinkCommand>,
+    topics: Vec<String>,
+    dropped: AtomicU64,
+    writer: parking_lot::Mutex<Option<JoinHandle<()>>>,
+}
+
+impl FileSink {
+    pub fn spawn(config: FileSinkConfig) -> Result<Self> {
+        std::fs::create_dir_all(&config.directory).map_err(|e| {
+            MonitorError::Configuration(format!(
+                "Cannot create capture directory {}: {}",
+                config.directory.display(),
+                e
+            ))
+        })?;
+
+        let (tx, rx) = mpsc::channel(config.queue_capacity.max(1));
+        let topics = config.topics.clone();
+        info!("Capturing events to {}", config.directory.display());
+
+        // File IO stays off the runtime workers
+        let writer = tokio::task::spawn_blocking(move || run_writer(RotatingWriter::new(config), rx));
+
+        Ok(Self {
+            tx,
+            topics,
+            dropped: AtomicU64::new(0),
+            writer: parking_lot::Mutex::new(Some(writer)),
+        })
+    }
+
+    // Never blocks the publishing path: events are dropped and counted when the queue is full
+    pub fn publish(&self, topic: &str, event: &MonitorEvent) {
+        if !self.captures(topic) {
+            return;
+        }
+
+        let record = CapturedEvent {
+            topic: topic.to_string(),
+            captured_at: Utc::now(),
+            event: event.clone(),
+        };
+        let line = match serde_json::to_string(&record) {
+            Ok(line) => line,
+            Err(e) => {
+                error!("Failed to serialize captured event: {}", e);
+                return;
+            }
+        };
+
+        if self.tx.try_send(SinkCommand::Write(line)).is_err() {
+            let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+            if dropped.is_power_of_two() {
+                warn!("File sink queue full, {} events dropped so far", dropped);
+            }
+        }
+    }
+
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    // Drains everything queued so far, then closes and syncs the current file
+    pub async fn shutdown(&self) {
+        let (done_tx, done_rx) = oneshot::channel();
+        if self.tx.send(SinkCommand::Shutdown(done_tx)).await.is_ok() {
+            let _ = done_rx.await;
+        }
+        if let Some(writer) = self.writer.lock().take() {
+            if let Err(e) = writer.await {
+                error!("File sink writer failed: {}", e);
+            }
+        }
+    }
+
+    fn captures(&self, topic: &str) -> bool {
+        self.topics.is_empty()
+            || self
+                .topics
+                .iter()
+                .any(|t| topic == t || topic.ends_with(&format!(".{}", t)))
+    }
+}
+
+fn run_writer(mut writer: RotatingWriter, mut rx: mpsc::Receiver<SinkCommand>) {
+    while let Some(command) = rx.blocking_recv() {
+        match command {
+            SinkCommand::Write(line) => {
+                if let Err(e) = writer.write_line(&line, Utc::now()) {
+                    error!("Failed to write captured event: {}", e);
+                }
+            }
+            SinkCommand::Shutdown(done) => {
+                if let Err(e) = writer.close() {
+                    error!("Failed to close capture file: {}", e);
+                }
+                let _ = done.send(());
+                return;
+            }
+        }
+    }
+
+    // All senders dropped without an explicit shutdown
+    if let Err(e) = writer.close() {
+        error!("Failed to close capture file: {}", e);
+    }
+}
+
+enum Output {
+    Plain(BufWriter<File>),
+    Gzip(GzEncoder<BufWriter<File>>),
+}
+
+impl Output {
+    fn writer(&mut self) -> &mut dyn Write {
+        match self {
+            Output::Plain(w) => w,
+            Output::Gzip(w) => w,
+        }
+    }
+
+    fn finish(self, sync: bool) -> std::io::Result<()> {
+        let mut inner = match self {
+            Output::Plain(w) => w,
+            Output::Gzip(w) => w.finish()?,
+        };
+        inner.flush()?;
+        if sync {
+            inner.get_ref().sync_all()?;
+        }
+        Ok(())
+    }
+
+    fn sync(&mut self) -> std::io::Result<()> {
+        self.writer().flush()?;
+        match self {
+            Output::Plain(w) => w.get_ref().sync_data(),
+            Output::Gzip(w) => w.get_ref().get_ref().sync_data(),
+        }
+    }
+}
+
+struct RotatingWriter {
+    config: FileSinkConfig,
+    current: Option<(Output, DateTime<Utc>, u64)>,
+    sequence: u64,
+}
+
+impl RotatingWriter {
+    fn new(config: FileSinkConfig) -> Self {
+        Self {
+            config,
+            current: None,
+            sequence: 0,
+        }
+    }
+
+    // Rotation is only ever decided between lines, so a line always lands whole in one file
+    fn write_line(&mut self, line: &str, now: DateTime<Utc>) -> std::io::Result<()> {
+        let line_bytes = line.len() as u64 + 1;
+        let rotate = match &self.current {
+            Some((_, opened_at, written)) => {
+                (*written > 0 && written + line_bytes > self.config.max_file_bytes)
+                    || now - *opened_at >= Duration::seconds(self.config.max_file_age_secs as i64)
+            }
+            None => true,
+        };
+        if rotate {
+            self.close()?;
+            self.open(now)?;
+        }
+
+        let (output, _, written) = self.current.as_mut().expect("capture file opened above");
+        let w = output.writer();
+        w.write_all(line.as_bytes())?;
+        w.write_all(b"\n")?;
+        *written += line_bytes;
+
+        if self.config.fsync == FsyncPolicy::Always {
+            output.sync()?;
+        }
+        Ok(())
+    }
+
+    fn open(&mut self, now: DateTime<Utc>) -> std::io::Result<()> {
+        self.sequence += 1;
+        let extension = match self.config.compression {
+            SinkCompression::None => "ndjson",
+            SinkCompression::Gzip => "ndjson.gz",
+        };
+        let path = self.config.directory.join(format!(
+            "events-{}-{:06}.{}",
+            now.format("%Y%m%dT%H%M%S"),
+            self.sequence,
+            extension
+        ));
+
+        let file = BufWriter::new(File::create(&path)?);
+        let output = match self.config.compression {
+            SinkCompression::None => Output::Plain(file),
+            SinkCompression::Gzip => Output::Gzip(GzEncoder::new(file, Compression::default())),
+        };
+        self.current = Some((output, now, 0));
+        Ok(())
+    }
+
+    fn close(&mut self) -> std::io::Result<()> {
+        match self.current.take() {
+            Some((output, _, _)) => output.finish(self.config.fsync != FsyncPolicy::Never),
+            None => Ok(()),
+        }
+    }
+}
+
+// Capture files in `dir` in the order they were written
+pub fn capture_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let entries = std::fs::read_dir(dir)
+        .map_err(|e| MonitorError::Other(format!("Cannot read {}: {}", dir.display(), e)))?;
+
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with("events-") && n.contains(".ndjson"))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+// Opens a capture file for line reading, transparently decompressing `.gz` files
+pub fn open_capture(path: &Path) -> Result<Box<dyn std::io::BufRead>> {
+    let file = File::open(path)
+        .map_err(|e| MonitorError::Other(format!("Cannot open {}: {}", path.display(), e)))?;
+
+    if path.extension().is_some_and(|e| e == "gz") {
+        Ok(Box::new(std::io::BufReader::new(flate2::read::GzDecoder::new(file))))
+    } else {
+        Ok(Box::new(std::io::BufReader::new(file)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{event::EventBuilder, EventSource, EventType, MarketDataType};
+    use std::io::BufRead;
+
+    fn event(i: usize) -> MonitorEvent {
+        EventBuilder::new()
+            .with_source(EventSource::Exchange("binance".to_string()))
+            .with_type(EventType::MarketData(MarketDataType::Trade))
+            .with_data(serde_json::json!({
+                "symbol": "BTC/USDT",
+                "exchange": "binance",
+                "price": 50_000.0 + i as f64,
+                "volume": 0.1,
+            }))
+            .build()
+            .unwrap()
+    }
+
+    fn config(compression: SinkCompression) -> FileSinkConfig {
+        FileSinkConfig {
+            directory: std::env::temp_dir().join(format!("capture-{}", uuid::Uuid::new_v4())),
+            max_file_bytes: 2_000,
+            max_file_age_secs: 3600,
+            compression,
+            fsync: FsyncPolicy::OnRotate,
+            topics: vec!["market.trades".to_string()],
+            queue_capacity: 1_000,
+        }
+    }
+
+    async fn capture(config: &FileSinkConfig, events: usize) -> Vec<Vec<CapturedEvent>> {
+        let sink = FileSink::spawn(config.clone()).unwrap();
+        for i in 0..events {
+            sink.publish("crypto-monitor.market.trades", &event(i));
+            sink.publish("crypto-monitor.alerts", &event(i));
+        }
+        sink.shutdown().await;
+        assert_eq!(sink.dropped(), 0);
+
+        capture_files(&config.directory)
+            .unwrap()
+            .iter()
+            .map(|path| {
+                open_capture(path)
+                    .unwrap()
+                    .lines()
+                    .map(|line| serde_json::from_str(&line.unwrap()).unwrap())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_rotation_never_splits_lines() {
+        let config = config(SinkCompression::None);
+        let files = capture(&config, 100).await;
+
+        assert!(files.len() > 1);
+        let events: Vec<&CapturedEvent> = files.iter().flatten().collect();
+        assert_eq!(events.len(), 100);
+        assert!(events.iter().all(|e| e.topic == "crypto-monitor.market.trades"));
+
+        // Files come back in write order
+        let prices: Vec<f64> = events.iter().map(|e| e.event.data["price"].as_f64().unwrap()).collect();
+        assert!(prices.windows(2).all(|w| w[0] < w[1]));
+
+        for path in capture_files(&config.directory).unwrap() {
+            let len = std::fs::metadata(&path).unwrap().len();
+            // A file only exceeds the limit when a single line does
+            assert!(len <= config.max_file_bytes + 500);
+        }
+        let _ = std::fs::remove_dir_all(&config.directory);
+    }
+
+    #[tokio::test]
+    async fn test_gzip_capture_round_trips() {
+        let config = config(SinkCompression::Gzip);
+        let files = capture(&config, 50).await;
+
+        assert_eq!(files.iter().map(|f| f.len()).sum::<usize>(), 50);
+        let _ = std::fs::remove_dir_all(&config.directory);
+    }
+}
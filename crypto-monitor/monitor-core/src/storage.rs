@@ -1,4 +1,14 @@
+pub mod evidence;
+pub mod query;
+pub mod repository;
+
 use crate::{MonitorError, Result};
+use chrono::{DateTime, Utc};
+use repository::{
+    AnomalyRepository, BookMetricsRepository, CandleRepository, DetectorStateRepository,
+    NotificationRepository, PositionRepository, SignalRepository, TickRecord, TickRepository,
+    TradeJournalRepository, TradeRepository, TraderStateRepository,
+};
 use sqlx::{PgPool, postgres::PgPoolOptions};
 use tracing::info;
 
@@ -30,4 +40,58 @@ impl StorageManager {
     pub fn get_pool(&self) -> &PgPool {
         &self.pool
     }
-}
\ No newline at end of file
+    
+    pub fn anomalies(&self) -> AnomalyRepository {
+        AnomalyRepository::new(self.pool.clone())
+    }
+    
+    pub fn ticks(&self) -> TickRepository {
+        TickRepository::new(self.pool.clone())
+    }
+    
+    pub fn candles(&self) -> CandleRepository {
+        CandleRepository::new(self.pool.clone())
+    }
+    
+    pub fn signals(&self) -> SignalRepository {
+        SignalRepository::new(self.pool.clone())
+    }
+    
+    pub fn notifications(&self) -> NotificationRepository {
+        NotificationRepository::new(self.pool.clone())
+    }
+    
+    pub fn book_metrics(&self) -> BookMetricsRepository {
+        BookMetricsRepository::new(self.pool.clone())
+    }
+    
+    pub fn detector_state(&self) -> DetectorStateRepository {
+        DetectorStateRepository::new(self.pool.clone())
+    }
+    
+    pub fn positions(&self) -> PositionRepository {
+        PositionRepository::new(self.pool.clone())
+    }
+    
+    pub fn trades(&self) -> TradeRepository {
+        TradeRepository::new(self.pool.clone())
+    }
+    
+    pub fn trade_journal(&self) -> TradeJournalRepository {
+        TradeJournalRepository::new(self.pool.clone())
+    }
+    
+    pub fn trader_state(&self) -> TraderStateRepository {
+        TraderStateRepository::new(self.pool.clone())
+    }
+    
+    // Recent history for warming up a pair's detectors, oldest first
+    pub async fn fetch_recent_ticks(
+        &self,
+        exchange: &str,
+        symbol: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<TickRecord>> {
+        self.ticks().fetch_recent_ticks(exchange, symbol, since).await
+    }
+}
@@ -0,0 +1,461 @@
+use super::{
+    query::{AnomalyFilter, BookMetricsFilter, FilterSet, NotificationFilter, Page, Table, TickFilter, MAX_LIMIT},
+    repository::{
+        AnomalyRecord, AnomalyRepository, BookMetricsRecord, BookMetricsRepository, NotificationRecord,
+        NotificationRepository, Repository, TickRecord, TickRepository,
+    },
+};
+use crate::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{postgres::PgRow, FromRow, PgPool};
+use uuid::Uuid;
+
+pub const DEFAULT_WINDOW_MINUTES: i64 = 15;
+pub const MAX_WINDOW_MINUTES: i64 = 240;
+pub const MAX_BUNDLE_BYTES: usize = 8 * 1024 * 1024;
+// Rows read per side of the incident and section, the byte cap decides what is kept
+const MAX_SECTION_ROWS: i64 = 20_000;
+// Serialized rows gathered before a chunk of the response goes out
+const STREAM_CHUNK_BYTES: usize = 64 * 1024;
+
+// Everything stored around an anomaly, for offline investigation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvidenceBundle {
+    pub anomaly: AnomalyRecord,
+    pub window_start: DateTime<Utc>,
+    pub window_end: DateTime<Utc>,
+    // Detector-specific details persisted with the anomaly
+    pub detector_snapshot: Option<serde_json::Value>,
+    // Other detections on the same instrument within the window
+    pub related_anomalies: Vec<AnomalyRecord>,
+    pub ticks: Vec<TickRecord>,
+    pub book_metrics: Vec<BookMetricsRecord>,
+    // Alerts sent within the window, including system alerts
+    pub system_events: Vec<NotificationRecord>,
+    pub truncated: bool,
+    pub omitted: EvidenceOmitted,
+    pub generated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EvidenceOmitted {
+    pub related_anomalies: usize,
+    pub ticks: usize,
+    pub book_metrics: usize,
+    pub system_events: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct EvidenceParts {
+    pub related_anomalies: Vec<AnomalyRecord>,
+    pub ticks: Vec<TickRecord>,
+    pub book_metrics: Vec<BookMetricsRecord>,
+    pub system_events: Vec<NotificationRecord>,
+}
+
+impl EvidenceBundle {
+    // Keeps the rows closest to the detection until the serialized bundle would exceed
+    // `max_bytes`. The anomaly itself is always included, sections come back oldest first.
+    pub fn assemble(
+        anomaly: AnomalyRecord,
+        window: Duration,
+        parts: EvidenceParts,
+        max_bytes: usize,
+        generated_at: DateTime<Utc>,
+    ) -> Self {
+        let at = anomaly.detected_at;
+        let EvidenceParts {
+            mut related_anomalies,
+            mut ticks,
+            mut book_metrics,
+            mut system_events,
+        } = parts;
+        related_anomalies.retain(|r| r.id != anomaly.id);
+        related_anomalies.sort_by_key(|r| r.detected_at);
+        ticks.sort_by_key(|t| t.timestamp);
+        book_metrics.sort_by_key(|b| b.timestamp);
+        system_events.sort_by_key(|e| e.created_at);
+
+        // (distance from the detection, section, index, serialized size)
+        let mut candidates = Vec::new();
+        let mut push = |section: usize, rows: Vec<(DateTime<Utc>, usize)>| {
+            for (index, (timestamp, size)) in rows.into_iter().enumerate() {
+                candidates.push(((timestamp - at).abs(), section, index, size));
+            }
+        };
+        push(0, related_anomalies.iter().map(|r| (r.detected_at, json_len(r))).collect());
+        push(1, ticks.iter().map(|t| (t.timestamp, json_len(t))).collect());
+        push(2, book_metrics.iter().map(|b| (b.timestamp, json_len(b))).collect());
+        push(3, system_events.iter().map(|e| (e.created_at, json_len(e))).collect());
+        candidates.sort_by_key(|(distance, section, index, _)| (*distance, *section, *index));
+
+        let mut bundle = Self {
+            detector_snapshot: anomaly.metadata.clone(),
+            anomaly,
+            window_start: at - window,
+            window_end: at + window,
+            related_anomalies: Vec::new(),
+            ticks: Vec::new(),
+            book_metrics: Vec::new(),
+            system_events: Vec::new(),
+            truncated: false,
+            // Sized for the largest counts so the final numbers never grow the estimate
+            omitted: EvidenceOmitted {
+                related_anomalies: usize::MAX,
+                ticks: usize::MAX,
+                book_metrics: usize::MAX,
+                system_events: usize::MAX,
+            },
+            generated_at,
+        };
+        let mut used = json_len(&bundle);
+
+        let mut keep = [
+            vec![false; related_anomalies.len()],
+            vec![false; ticks.len()],
+            vec![false; book_metrics.len()],
+            vec![false; system_events.len()],
+        ];
+        for (_, section, index, size) in candidates {
+            // Separating comma
+            let size = size + 1;
+            if used + size > max_bytes {
+                bundle.truncated = true;
+                break;
+            }
+            used += size;
+            keep[section][index] = true;
+        }
+
+        bundle.related_anomalies = retain_kept(related_anomalies, &keep[0]);
+        bundle.ticks = retain_kept(ticks, &keep[1]);
+        bundle.book_metrics = retain_kept(book_metrics, &keep[2]);
+        bundle.system_events = retain_kept(system_events, &keep[3]);
+        let omitted = |kept: &[bool]| kept.iter().filter(|k| !**k).count();
+        bundle.omitted = EvidenceOmitted {
+            related_anomalies: omitted(&keep[0]),
+            ticks: omitted(&keep[1]),
+            book_metrics: omitted(&keep[2]),
+            system_events: omitted(&keep[3]),
+        };
+        bundle
+    }
+
+    // The bundle's JSON in chunks of about STREAM_CHUNK_BYTES, rows serialized as each chunk is
+    // pulled so a download never holds a second copy of the bundle. Fields come in declaration
+    // order, the concatenation is what `serde_json::to_vec` would give.
+    pub fn into_json_chunks(self) -> impl Iterator<Item = serde_json::Result<Vec<u8>>> + Send {
+        let head = (|| -> serde_json::Result<Vec<u8>> {
+            let mut out = vec![b'{'];
+            write_field(&mut out, "anomaly", &self.anomaly)?;
+            write_field(&mut out, "window_start", &self.window_start)?;
+            write_field(&mut out, "window_end", &self.window_end)?;
+            write_field(&mut out, "detector_snapshot", &self.detector_snapshot)?;
+            Ok(out)
+        })();
+        let tail = (|| -> serde_json::Result<Vec<u8>> {
+            let mut out = Vec::new();
+            write_field(&mut out, "truncated", &self.truncated)?;
+            write_field(&mut out, "omitted", &self.omitted)?;
+            write_field(&mut out, "generated_at", &self.generated_at)?;
+            // Closes the object over the last field's comma
+            out.pop();
+            out.push(b'}');
+            Ok(out)
+        })();
+
+        std::iter::once(head)
+            .chain(section_chunks("related_anomalies", self.related_anomalies))
+            .chain(section_chunks("ticks", self.ticks))
+            .chain(section_chunks("book_metrics", self.book_metrics))
+            .chain(section_chunks("system_events", self.system_events))
+            .chain(std::iter::once(tail))
+    }
+}
+
+// `"name":value,`
+fn write_field<T: Serialize>(out: &mut Vec<u8>, name: &str, value: &T) -> serde_json::Result<()> {
+    out.extend_from_slice(format!("\"{}\":", name).as_bytes());
+    serde_json::to_writer(&mut *out, value)?;
+    out.push(b',');
+    Ok(())
+}
+
+// `"name":[rows],` split wherever a chunk reaches STREAM_CHUNK_BYTES
+fn section_chunks<T: Serialize + Send>(
+    name: &'static str,
+    rows: Vec<T>,
+) -> impl Iterator<Item = serde_json::Result<Vec<u8>>> + Send {
+    let mut buffer = format!("\"{}\":[", name).into_bytes();
+    let mut rows = rows.into_iter().enumerate();
+    let mut done = false;
+
+    std::iter::from_fn(move || {
+        if done {
+            return None;
+        }
+        for (i, row) in rows.by_ref() {
+            if i > 0 {
+                buffer.push(b',');
+            }
+            if let Err(e) = serde_json::to_writer(&mut buffer, &row) {
+                done = true;
+                return Some(Err(e));
+            }
+            if buffer.len() >= STREAM_CHUNK_BYTES {
+                return Some(Ok(std::mem::take(&mut buffer)));
+            }
+        }
+        buffer.extend_from_slice(b"],");
+        done = true;
+        Some(Ok(std::mem::take(&mut buffer)))
+    })
+}
+
+fn json_len<T: Serialize>(value: &T) -> usize {
+    serde_json::to_vec(value).map_or(0, |v| v.len())
+}
+
+fn retain_kept<T>(rows: Vec<T>, keep: &[bool]) -> Vec<T> {
+    rows.into_iter().zip(keep).filter(|(_, k)| **k).map(|(row, _)| row).collect()
+}
+
+pub struct EvidenceCollector {
+    anomalies: AnomalyRepository,
+    ticks: TickRepository,
+    book_metrics: BookMetricsRepository,
+    notifications: NotificationRepository,
+}
+
+impl EvidenceCollector {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            anomalies: AnomalyRepository::new(pool.clone()),
+            ticks: TickRepository::new(pool.clone()),
+            book_metrics: BookMetricsRepository::new(pool.clone()),
+            notifications: NotificationRepository::new(pool),
+        }
+    }
+
+    // `None` when no anomaly with this id is stored
+    pub async fn collect(&self, id: Uuid, window: Duration, max_bytes: usize) -> Result<Option<EvidenceBundle>> {
+        let Some(anomaly) = self.anomalies.find_by_id(id).await? else {
+            return Ok(None);
+        };
+        let at = anomaly.detected_at;
+        let (exchange, symbol) = (anomaly.exchange.clone(), anomaly.symbol.clone());
+
+        let mut parts = EvidenceParts::default();
+        for (from, to, nearest_first) in [(at - window, at, true), (at, at + window, false)] {
+            parts.related_anomalies.extend(
+                fetch_nearest(
+                    &self.anomalies,
+                    vec![
+                        AnomalyFilter::ExchangeEq(exchange.clone()),
+                        AnomalyFilter::SymbolEq(symbol.clone()),
+                        AnomalyFilter::TimeRange(Some(from), Some(to)),
+                    ],
+                    nearest_first,
+                )
+                .await?,
+            );
+            parts.ticks.extend(
+                fetch_nearest(
+                    &self.ticks,
+                    vec![
+                        TickFilter::ExchangeEq(exchange.clone()),
+                        TickFilter::SymbolEq(symbol.clone()),
+                        TickFilter::TimeRange(Some(from), Some(to)),
+                    ],
+                    nearest_first,
+                )
+                .await?,
+            );
+            parts.book_metrics.extend(
+                fetch_nearest(
+                    &self.book_metrics,
+                    vec![
+                        BookMetricsFilter::ExchangeEq(exchange.clone()),
+                        BookMetricsFilter::SymbolEq(symbol.clone()),
+                        BookMetricsFilter::TimeRange(Some(from), Some(to)),
+                    ],
+                    nearest_first,
+                )
+                .await?,
+            );
+            parts.system_events.extend(
+                fetch_nearest(
+                    &self.notifications,
+                    vec![NotificationFilter::TimeRange(Some(from), Some(to))],
+                    nearest_first,
+                )
+                .await?,
+            );
+        }
+
+        Ok(Some(EvidenceBundle::assemble(anomaly, window, parts, max_bytes, Utc::now())))
+    }
+}
+
+// Rows come back newest first, so before the detection the nearest rows lead and after it
+// they trail. Reads at most MAX_SECTION_ROWS of the rows nearest the detection.
+async fn fetch_nearest<T, R>(repository: &Repository<T>, filters: Vec<T::Filter>, nearest_first: bool) -> Result<Vec<R>>
+where
+    T: Table,
+    T::Filter: Clone,
+    R: for<'r> FromRow<'r, PgRow> + Send + Unpin,
+{
+    let mut offset = if nearest_first {
+        0
+    } else {
+        (repository.count(&filters).await? - MAX_SECTION_ROWS).max(0)
+    };
+
+    let mut rows = Vec::new();
+    while (rows.len() as i64) < MAX_SECTION_ROWS {
+        let limit = MAX_LIMIT.min(MAX_SECTION_ROWS - rows.len() as i64);
+        let page: Vec<R> = repository
+            .find(&FilterSet::new(filters.clone(), Page { limit, offset }))
+            .await?;
+        let done = (page.len() as i64) < limit;
+        offset += page.len() as i64;
+        rows.extend(page);
+        if done {
+            break;
+        }
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap()
+    }
+
+    fn anomaly(id: Uuid, detected_at: DateTime<Utc>) -> AnomalyRecord {
+        AnomalyRecord {
+            id,
+            exchange: "binance".to_string(),
+            symbol: "BTC/USDT".to_string(),
+            anomaly_type: "VolumeSpike".to_string(),
+            severity: "High".to_string(),
+            confidence: Some(0.8),
+            current_value: 120.0,
+            expected_value: 20.0,
+            deviation: 100.0,
+            z_score: Some(6.0),
+            percentage_change: None,
+            description: Some("Volume spike".to_string()),
+            reason: None,
+            metadata: Some(serde_json::json!({ "kind": "iceberg", "refills": 4 })),
+            detected_at,
+            instance_id: None,
+            shadow: false,
+            source: None,
+        }
+    }
+
+    // Ticks and book samples every 10s over +/-5 minutes, one related anomaly and one alert
+    fn fixture() -> (AnomalyRecord, EvidenceParts) {
+        let incident = anomaly(Uuid::new_v4(), at());
+        let offsets = (-30..=30).map(|i| Duration::seconds(i * 10));
+        let parts = EvidenceParts {
+            // The incident itself shows up in the related query and is dropped
+            related_anomalies: vec![incident.clone(), anomaly(Uuid::new_v4(), at() + Duration::seconds(45))],
+            ticks: offsets
+                .clone()
+                .rev()
+                .map(|offset| TickRecord {
+                    id: Uuid::new_v4(),
+                    exchange: "binance".to_string(),
+                    symbol: "BTC/USDT".to_string(),
+                    price: 50_000.0,
+                    volume: 1.5,
+                    bid: None,
+                    ask: None,
+                    timestamp: at() + offset,
+                })
+                .collect(),
+            book_metrics: offsets
+                .map(|offset| BookMetricsRecord {
+                    exchange: "binance".to_string(),
+                    symbol: "BTC/USDT".to_string(),
+                    timestamp: at() + offset,
+                    spread_bps: 1.2,
+                    imbalance: 0.1,
+                    microprice: 50_000.5,
+                })
+                .collect(),
+            system_events: vec![NotificationRecord {
+                id: Uuid::new_v4(),
+                alert_type: "Critical".to_string(),
+                severity: "High".to_string(),
+                title: "Volume spike".to_string(),
+                message: "binance BTC/USDT".to_string(),
+                metadata: None,
+                channels: Some(vec!["Telegram".to_string()]),
+                status: "sent".to_string(),
+                sent_at: Some(at() + Duration::seconds(2)),
+                created_at: at() + Duration::seconds(1),
+            }],
+        };
+        (incident, parts)
+    }
+
+    #[test]
+    fn test_bundle_is_complete_within_cap() {
+        let (incident, parts) = fixture();
+        let bundle = EvidenceBundle::assemble(incident.clone(), Duration::minutes(5), parts, MAX_BUNDLE_BYTES, at());
+
+        assert!(!bundle.truncated);
+        assert_eq!(bundle.omitted, EvidenceOmitted::default());
+        assert_eq!(bundle.anomaly.id, incident.id);
+        assert_eq!(bundle.detector_snapshot, incident.metadata);
+        assert_eq!(bundle.related_anomalies.len(), 1);
+        assert_eq!((bundle.ticks.len(), bundle.book_metrics.len(), bundle.system_events.len()), (61, 61, 1));
+        assert_eq!(bundle.window_start, at() - Duration::minutes(5));
+        assert!(bundle.ticks.windows(2).all(|w| w[0].timestamp < w[1].timestamp));
+    }
+
+    #[test]
+    fn test_cap_keeps_rows_nearest_the_detection() {
+        let (incident, parts) = fixture();
+        let cap = 6 * 1024;
+        let bundle = EvidenceBundle::assemble(incident, Duration::minutes(5), parts, cap, at());
+
+        assert!(bundle.truncated);
+        assert!(serde_json::to_vec(&bundle).unwrap().len() <= cap);
+        assert!(!bundle.ticks.is_empty() && bundle.ticks.len() < 61);
+        assert_eq!(bundle.ticks.len() + bundle.omitted.ticks, 61);
+        assert_eq!(bundle.book_metrics.len() + bundle.omitted.book_metrics, 61);
+
+        // Kept ticks are a contiguous run around the detection, the last step may only fit one side
+        let furthest = bundle.ticks.iter().map(|t| (t.timestamp - at()).abs()).max().unwrap();
+        let steps = furthest.num_seconds() as usize / 10;
+        assert!(bundle.ticks.len() >= 2 * steps);
+        assert!(bundle.ticks.iter().any(|t| t.timestamp == at()));
+        assert_eq!(bundle.system_events.len(), 1);
+    }
+
+    #[test]
+    fn test_streamed_chunks_make_up_the_bundle() {
+        let (incident, mut parts) = fixture();
+        let ticks = parts.ticks.clone();
+        for _ in 0..20 {
+            parts.ticks.extend(ticks.iter().cloned());
+        }
+        let bundle =
+            EvidenceBundle::assemble(incident, Duration::minutes(5), parts, MAX_BUNDLE_BYTES, at());
+        let expected = serde_json::to_vec(&bundle).unwrap();
+
+        let chunks: Vec<Vec<u8>> = bundle.into_json_chunks().map(|chunk| chunk.unwrap()).collect();
+        assert!(chunks.len() > 2);
+        assert!(chunks.iter().all(|chunk| chunk.len() < STREAM_CHUNK_BYTES + 1024));
+        assert_eq!(chunks.concat(), expected);
+    }
+}
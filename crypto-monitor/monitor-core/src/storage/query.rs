@@ -0,0 +1,699 @@
+use chrono::{DateTime, Utc};
+use sqlx::{
+    postgres::{PgArguments, PgRow},
+    query::QueryAs,
+    FromRow, Postgres,
+};
+use uuid::Uuid;
+
+pub const DEFAULT_LIMIT: i64 = 100;
+pub const MAX_LIMIT: i64 = 1000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BindValue {
+    Text(String),
+    TextArray(Vec<String>),
+    Timestamp(DateTime<Utc>),
+    Int(i64),
+    Uuid(Uuid),
+    Bool(bool),
+}
+
+// A single rendered predicate. `rank` keeps equality columns covered by the composite
+// (exchange, symbol) indexes ahead of set membership and range predicates so that the
+// generated SQL is identical regardless of the order filters were supplied in.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    Eq { column: &'static str, value: BindValue },
+    In { column: &'static str, values: Vec<String> },
+    Range {
+        column: &'static str,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    },
+    // Rows ordered after (`at`, `id`) newest first, the keyset a page ended on
+    Before {
+        column: &'static str,
+        id_column: &'static str,
+        at: DateTime<Utc>,
+        id: Uuid,
+    },
+}
+
+impl Condition {
+    fn rank(&self) -> (u8, &'static str) {
+        match self {
+            Condition::Eq { column, .. } => (0, column),
+            Condition::In { column, .. } => (1, column),
+            Condition::Range { column, .. } | Condition::Before { column, .. } => (2, column),
+        }
+    }
+}
+
+pub trait Filter {
+    fn condition(&self) -> Condition;
+}
+
+pub trait Table {
+    type Filter: Filter;
+    
+    const NAME: &'static str;
+    const COLUMNS: &'static str;
+    const TIME_COLUMN: &'static str;
+    // Orders rows sharing a timestamp, needed for keyset pagination
+    const TIEBREAK_COLUMN: Option<&'static str> = None;
+}
+
+// Tables whose numeric columns can be downsampled to avg/min/max per time bucket
+pub trait Bucketed: Table {
+    const METRICS: &'static [&'static str];
+}
+
+// Tables of trades or quotes per (exchange, symbol) that can be summarized over a window
+pub trait Priced: Table {
+    const PRICE_COLUMN: &'static str;
+    const VOLUME_COLUMN: &'static str;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Page {
+    pub limit: i64,
+    pub offset: i64,
+}
+
+impl Default for Page {
+    fn default() -> Self {
+        Self {
+            limit: DEFAULT_LIMIT,
+            offset: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterSet<F> {
+    pub filters: Vec<F>,
+    pub page: Page,
+}
+
+impl<F> Default for FilterSet<F> {
+    fn default() -> Self {
+        Self {
+            filters: Vec::new(),
+            page: Page::default(),
+        }
+    }
+}
+
+impl<F: Filter> FilterSet<F> {
+    pub fn new(filters: Vec<F>, page: Page) -> Self {
+        Self { filters, page }
+    }
+    
+    pub fn with(mut self, filter: F) -> Self {
+        self.filters.push(filter);
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompiledQuery {
+    pub sql: String,
+    pub binds: Vec<BindValue>,
+}
+
+impl CompiledQuery {
+    pub fn select<T: Table>(filters: &FilterSet<T::Filter>) -> Self {
+        let (where_clause, mut binds) = Self::where_clause(&filters.filters);
+        
+        let tiebreak = T::TIEBREAK_COLUMN.map_or(String::new(), |c| format!(", {} DESC", c));
+        let sql = format!(
+            "SELECT {} FROM {}{} ORDER BY {} DESC{} LIMIT ${} OFFSET ${}",
+            T::COLUMNS,
+            T::NAME,
+            where_clause,
+            T::TIME_COLUMN,
+            tiebreak,
+            binds.len() + 1,
+            binds.len() + 2,
+        );
+        
+        binds.push(BindValue::Int(filters.page.limit.clamp(1, MAX_LIMIT)));
+        binds.push(BindValue::Int(filters.page.offset.max(0)));
+        
+        Self { sql, binds }
+    }
+    
+    pub fn count<T: Table>(filters: &[T::Filter]) -> Self {
+        let (where_clause, binds) = Self::where_clause(filters);
+        
+        Self {
+            sql: format!("SELECT COUNT(*) FROM {}{}", T::NAME, where_clause),
+            binds,
+        }
+    }
+    
+    // Row counts per value of `column`, largest first. `column` is spliced into the SQL, so only
+    // a literal from code is accepted, never a request value
+    pub fn count_by<T: Table>(filters: &[T::Filter], column: &'static str) -> Self {
+        let (where_clause, binds) = Self::where_clause(filters);
+        
+        Self {
+            sql: format!(
+                "SELECT {} AS key, COUNT(*) AS count FROM {}{} GROUP BY 1 ORDER BY 2 DESC, 1",
+                column,
+                T::NAME,
+                where_clause
+            ),
+            binds,
+        }
+    }
+    
+    // Buckets are aligned to multiples of `bucket_secs` since the epoch and half-open, so a
+    // sample exactly on a boundary opens the next bucket. Returned oldest first for charting.
+    pub fn downsample<T: Bucketed>(filters: &[T::Filter], bucket_secs: i64) -> Self {
+        let (where_clause, mut binds) = Self::where_clause(filters);
+        binds.push(BindValue::Int(bucket_secs.max(1)));
+        
+        let aggregates: Vec<String> = T::METRICS
+            .iter()
+            .map(|m| format!("AVG({m}) AS {m}_avg, MIN({m}) AS {m}_min, MAX({m}) AS {m}_max"))
+            .collect();
+        
+        let sql = format!(
+            "SELECT {} AS bucket, COUNT(*) AS samples, {} FROM {}{} GROUP BY 1 ORDER BY 1",
+            Self::bucket::<T>(binds.len()),
+            aggregates.join(", "),
+            T::NAME,
+            where_clause,
+        );
+        
+        Self { sql, binds }
+    }
+    
+    // Row counts over the same aligned buckets as `downsample`. Empty buckets are left out
+    pub fn histogram<T: Table>(filters: &[T::Filter], bucket_secs: i64) -> Self {
+        let (where_clause, mut binds) = Self::where_clause(filters);
+        binds.push(BindValue::Int(bucket_secs.max(1)));
+        
+        let sql = format!(
+            "SELECT {} AS bucket, COUNT(*) AS count FROM {}{} GROUP BY 1 ORDER BY 1",
+            Self::bucket::<T>(binds.len()),
+            T::NAME,
+            where_clause,
+        );
+        
+        Self { sql, binds }
+    }
+    
+    // Start of the bucket a row falls in, the width bound as parameter `n`
+    fn bucket<T: Table>(n: usize) -> String {
+        format!(
+            "to_timestamp(floor(extract(epoch FROM {time})::float8 / ${n}) * ${n})",
+            time = T::TIME_COLUMN,
+        )
+    }
+    
+    // Per instrument over the filtered rows: the first and last price by time, the price
+    // extremes and the summed volume. Instruments come back in (exchange, symbol) order, paged.
+    // The first and last price are each one ordered LIMIT 1 on the (exchange, symbol, time)
+    // index rather than an aggregate of every price in the window
+    pub fn summarize<T: Priced>(filters: &FilterSet<T::Filter>) -> Self {
+        let (where_clause, mut binds) = Self::where_clause(&filters.filters);
+        let instrument = if where_clause.is_empty() {
+            " WHERE exchange = s.exchange AND symbol = s.symbol".to_string()
+        } else {
+            format!("{} AND exchange = s.exchange AND symbol = s.symbol", where_clause)
+        };
+        
+        let sql = format!(
+            "SELECT s.exchange, s.symbol, o.{price}::float8 AS open_price, \
+             l.{price}::float8 AS last_price, s.high, s.low, s.volume, s.samples, s.last_update \
+             FROM (SELECT exchange, symbol, MAX({price})::float8 AS high, \
+             MIN({price})::float8 AS low, COALESCE(SUM({volume}), 0)::float8 AS volume, \
+             COUNT(*) AS samples, MAX({time}) AS last_update \
+             FROM {table}{where_clause} GROUP BY exchange, symbol \
+             ORDER BY exchange, symbol LIMIT ${limit} OFFSET ${offset}) s \
+             CROSS JOIN LATERAL (SELECT {price} FROM {table}{instrument} \
+             ORDER BY {time} LIMIT 1) o \
+             CROSS JOIN LATERAL (SELECT {price} FROM {table}{instrument} \
+             ORDER BY {time} DESC LIMIT 1) l \
+             ORDER BY s.exchange, s.symbol",
+            table = T::NAME,
+            where_clause = where_clause,
+            instrument = instrument,
+            limit = binds.len() + 1,
+            offset = binds.len() + 2,
+            price = T::PRICE_COLUMN,
+            volume = T::VOLUME_COLUMN,
+            time = T::TIME_COLUMN,
+        );
+        binds.push(BindValue::Int(filters.page.limit.clamp(1, MAX_LIMIT)));
+        binds.push(BindValue::Int(filters.page.offset.max(0)));
+        
+        Self { sql, binds }
+    }
+    
+    fn where_clause<F: Filter>(filters: &[F]) -> (String, Vec<BindValue>) {
+        let mut conditions: Vec<Condition> = filters.iter().map(Filter::condition).collect();
+        conditions.sort_by_key(Condition::rank);
+        
+        let mut clauses = Vec::new();
+        let mut binds = Vec::new();
+        
+        for condition in conditions {
+            match condition {
+                Condition::Eq { column, value } => {
+                    binds.push(value);
+                    clauses.push(format!("{} = ${}", column, binds.len()));
+                }
+                Condition::In { column, values } => {
+                    binds.push(BindValue::TextArray(values));
+                    clauses.push(format!("{} = ANY(${})", column, binds.len()));
+                }
+                Condition::Range { column, from, to } => {
+                    if let Some(from) = from {
+                        binds.push(BindValue::Timestamp(from));
+                        clauses.push(format!("{} >= ${}", column, binds.len()));
+                    }
+                    if let Some(to) = to {
+                        binds.push(BindValue::Timestamp(to));
+                        clauses.push(format!("{} < ${}", column, binds.len()));
+                    }
+                }
+                Condition::Before { column, id_column, at, id } => {
+                    binds.push(BindValue::Timestamp(at));
+                    binds.push(BindValue::Uuid(id));
+                    clauses.push(format!(
+                        "({}, {}) < (${}, ${})",
+                        column,
+                        id_column,
+                        binds.len() - 1,
+                        binds.len()
+                    ));
+                }
+            }
+        }
+        
+        if clauses.is_empty() {
+            (String::new(), binds)
+        } else {
+            (format!(" WHERE {}", clauses.join(" AND ")), binds)
+        }
+    }
+    
+    pub fn query_as<'q, O>(&'q self) -> QueryAs<'q, Postgres, O, PgArguments>
+    where
+        O: for<'r> FromRow<'r, PgRow>,
+    {
+        let mut query = sqlx::query_as::<_, O>(&self.sql);
+        
+        for bind in &self.binds {
+            query = match bind {
+                BindValue::Text(v) => query.bind(v),
+                BindValue::TextArray(v) => query.bind(v),
+                BindValue::Timestamp(v) => query.bind(v),
+                BindValue::Int(v) => query.bind(v),
+                BindValue::Uuid(v) => query.bind(v),
+                BindValue::Bool(v) => query.bind(v),
+            };
+        }
+        
+        query
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnomalyFilter {
+    SymbolEq(String),
+    ExchangeEq(String),
+    TimeRange(Option<DateTime<Utc>>, Option<DateTime<Utc>>),
+    SeverityIn(Vec<String>),
+    TypeIn(Vec<String>),
+    // After the anomaly detected at the time with the id, in list order
+    Before(DateTime<Utc>, Uuid),
+    // Detections made while following are shadows of the leader's
+    ShadowEq(bool),
+}
+
+impl Filter for AnomalyFilter {
+    fn condition(&self) -> Condition {
+        match self {
+            AnomalyFilter::SymbolEq(symbol) => Condition::Eq {
+                column: "symbol",
+                value: BindValue::Text(symbol.clone()),
+            },
+            AnomalyFilter::ExchangeEq(exchange) => Condition::Eq {
+                column: "exchange",
+                value: BindValue::Text(exchange.clone()),
+            },
+            AnomalyFilter::TimeRange(from, to) => Condition::Range {
+                column: "detected_at",
+                from: *from,
+                to: *to,
+            },
+            AnomalyFilter::SeverityIn(severities) => Condition::In {
+                column: "severity",
+                values: severities.clone(),
+            },
+            AnomalyFilter::TypeIn(types) => Condition::In {
+                column: "anomaly_type",
+                values: types.clone(),
+            },
+            AnomalyFilter::Before(at, id) => Condition::Before {
+                column: "detected_at",
+                id_column: "id",
+                at: *at,
+                id: *id,
+            },
+            AnomalyFilter::ShadowEq(shadow) => Condition::Eq {
+                column: "shadow",
+                value: BindValue::Bool(*shadow),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TickFilter {
+    SymbolEq(String),
+    ExchangeEq(String),
+    TimeRange(Option<DateTime<Utc>>, Option<DateTime<Utc>>),
+}
+
+impl Filter for TickFilter {
+    fn condition(&self) -> Condition {
+        match self {
+            TickFilter::SymbolEq(symbol) => Condition::Eq {
+                column: "symbol",
+                value: BindValue::Text(symbol.clone()),
+            },
+            TickFilter::ExchangeEq(exchange) => Condition::Eq {
+                column: "exchange",
+                value: BindValue::Text(exchange.clone()),
+            },
+            TickFilter::TimeRange(from, to) => Condition::Range {
+                column: "timestamp",
+                from: *from,
+                to: *to,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CandleFilter {
+    SymbolEq(String),
+    ExchangeEq(String),
+    IntervalEq(String),
+    TimeRange(Option<DateTime<Utc>>, Option<DateTime<Utc>>),
+}
+
+impl Filter for CandleFilter {
+    fn condition(&self) -> Condition {
+        match self {
+            CandleFilter::SymbolEq(symbol) => Condition::Eq {
+                column: "symbol",
+                value: BindValue::Text(symbol.clone()),
+            },
+            CandleFilter::ExchangeEq(exchange) => Condition::Eq {
+                column: "exchange",
+                value: BindValue::Text(exchange.clone()),
+            },
+            CandleFilter::IntervalEq(interval) => Condition::Eq {
+                column: "interval",
+                value: BindValue::Text(interval.clone()),
+            },
+            CandleFilter::TimeRange(from, to) => Condition::Range {
+                column: "open_time",
+                from: *from,
+                to: *to,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SignalFilter {
+    SymbolEq(String),
+    ExchangeEq(String),
+    TimeRange(Option<DateTime<Utc>>, Option<DateTime<Utc>>),
+    PatternKind(String),
+    Status(String),
+}
+
+impl Filter for SignalFilter {
+    fn condition(&self) -> Condition {
+        match self {
+            SignalFilter::SymbolEq(symbol) => Condition::Eq {
+                column: "symbol",
+                value: BindValue::Text(symbol.clone()),
+            },
+            SignalFilter::ExchangeEq(exchange) => Condition::Eq {
+                column: "exchange",
+                value: BindValue::Text(exchange.clone()),
+            },
+            SignalFilter::TimeRange(from, to) => Condition::Range {
+                column: "created_at",
+                from: *from,
+                to: *to,
+            },
+            SignalFilter::PatternKind(kind) => Condition::Eq {
+                column: "pattern_kind",
+                value: BindValue::Text(kind.clone()),
+            },
+            SignalFilter::Status(status) => Condition::Eq {
+                column: "status",
+                value: BindValue::Text(status.clone()),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NotificationFilter {
+    TimeRange(Option<DateTime<Utc>>, Option<DateTime<Utc>>),
+    SeverityIn(Vec<String>),
+    TypeIn(Vec<String>),
+    Status(String),
+}
+
+impl Filter for NotificationFilter {
+    fn condition(&self) -> Condition {
+        match self {
+            NotificationFilter::TimeRange(from, to) => Condition::Range {
+                column: "created_at",
+                from: *from,
+                to: *to,
+            },
+            NotificationFilter::SeverityIn(severities) => Condition::In {
+                column: "severity",
+                values: severities.clone(),
+            },
+            NotificationFilter::TypeIn(types) => Condition::In {
+                column: "alert_type",
+                values: types.clone(),
+            },
+            NotificationFilter::Status(status) => Condition::Eq {
+                column: "status",
+                value: BindValue::Text(status.clone()),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BookMetricsFilter {
+    SymbolEq(String),
+    ExchangeEq(String),
+    TimeRange(Option<DateTime<Utc>>, Option<DateTime<Utc>>),
+}
+
+impl Filter for BookMetricsFilter {
+    fn condition(&self) -> Condition {
+        match self {
+            BookMetricsFilter::SymbolEq(symbol) => Condition::Eq {
+                column: "symbol",
+                value: BindValue::Text(symbol.clone()),
+            },
+            BookMetricsFilter::ExchangeEq(exchange) => Condition::Eq {
+                column: "exchange",
+                value: BindValue::Text(exchange.clone()),
+            },
+            BookMetricsFilter::TimeRange(from, to) => Condition::Range {
+                column: "timestamp",
+                from: *from,
+                to: *to,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::repository::{AnomaliesTable, BookMetricsTable, TicksTable};
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_empty_filters_only_bind_paging() {
+        let query = CompiledQuery::select::<AnomaliesTable>(&FilterSet::default());
+        
+        assert!(query
+            .sql
+            .ends_with("FROM anomalies ORDER BY detected_at DESC, id DESC LIMIT $1 OFFSET $2"));
+        assert_eq!(query.binds, vec![BindValue::Int(DEFAULT_LIMIT), BindValue::Int(0)]);
+    }
+    
+    #[test]
+    fn test_filters_are_rendered_in_canonical_order() {
+        let from = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let filters = FilterSet::new(
+            vec![
+                AnomalyFilter::TimeRange(Some(from), None),
+                AnomalyFilter::SeverityIn(vec!["High".to_string(), "Critical".to_string()]),
+                AnomalyFilter::SymbolEq("BTC/USDT".to_string()),
+                AnomalyFilter::ExchangeEq("binance".to_string()),
+            ],
+            Page { limit: 5000, offset: -1 },
+        );
+        
+        let query = CompiledQuery::select::<AnomaliesTable>(&filters);
+        
+        assert!(query.sql.ends_with(
+            "FROM anomalies WHERE exchange = $1 AND symbol = $2 AND severity = ANY($3) \
+             AND detected_at >= $4 ORDER BY detected_at DESC, id DESC LIMIT $5 OFFSET $6"
+        ));
+        assert_eq!(
+            query.binds,
+            vec![
+                BindValue::Text("binance".to_string()),
+                BindValue::Text("BTC/USDT".to_string()),
+                BindValue::TextArray(vec!["High".to_string(), "Critical".to_string()]),
+                BindValue::Timestamp(from),
+                BindValue::Int(MAX_LIMIT),
+                BindValue::Int(0),
+            ]
+        );
+    }
+    
+    #[test]
+    fn test_count_has_no_paging_binds() {
+        let to = Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap();
+        let query = CompiledQuery::count::<AnomaliesTable>(&[
+            AnomalyFilter::TimeRange(None, Some(to)),
+            AnomalyFilter::TypeIn(vec!["VolumeSpike".to_string()]),
+        ]);
+        
+        assert_eq!(
+            query.sql,
+            "SELECT COUNT(*) FROM anomalies WHERE anomaly_type = ANY($1) AND detected_at < $2"
+        );
+        assert_eq!(query.binds.len(), 2);
+    }
+    
+    #[test]
+    fn test_user_input_is_never_interpolated() {
+        let query = CompiledQuery::select::<AnomaliesTable>(
+            &FilterSet::default().with(AnomalyFilter::SymbolEq("x'; DROP TABLE anomalies; --".to_string())),
+        );
+        
+        assert!(!query.sql.contains("DROP"));
+        assert_eq!(query.binds[0], BindValue::Text("x'; DROP TABLE anomalies; --".to_string()));
+    }
+    
+    #[test]
+    fn test_keyset_follows_the_list_order() {
+        let at = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        let id = Uuid::new_v4();
+        let query = CompiledQuery::select::<AnomaliesTable>(&FilterSet::new(
+            vec![
+                AnomalyFilter::Before(at, id),
+                AnomalyFilter::SymbolEq("BTC/USDT".to_string()),
+            ],
+            Page { limit: 50, offset: 0 },
+        ));
+        
+        assert!(query.sql.ends_with(
+            "FROM anomalies WHERE symbol = $1 AND (detected_at, id) < ($2, $3) \
+             ORDER BY detected_at DESC, id DESC LIMIT $4 OFFSET $5"
+        ));
+        assert_eq!(query.binds[1], BindValue::Timestamp(at));
+        assert_eq!(query.binds[2], BindValue::Uuid(id));
+    }
+    
+    #[test]
+    fn test_summarize_groups_by_instrument() {
+        let from = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let filters = FilterSet::new(
+            vec![
+                TickFilter::TimeRange(Some(from), None),
+                TickFilter::SymbolEq("BTC/USDT".to_string()),
+            ],
+            Page { limit: 10, offset: 0 },
+        );
+        
+        let query = CompiledQuery::summarize::<TicksTable>(&filters);
+        
+        assert!(!query.sql.contains("array_agg"));
+        assert!(query.sql.contains(
+            "FROM market_data WHERE symbol = $1 AND timestamp >= $2 GROUP BY exchange, symbol \
+             ORDER BY exchange, symbol LIMIT $3 OFFSET $4) s"
+        ));
+        // The first and last price read one row each, under the same filters
+        assert!(query.sql.contains(
+            "CROSS JOIN LATERAL (SELECT price FROM market_data WHERE symbol = $1 AND \
+             timestamp >= $2 AND exchange = s.exchange AND symbol = s.symbol \
+             ORDER BY timestamp DESC LIMIT 1) l"
+        ));
+        assert_eq!(query.binds.len(), 4);
+    }
+    
+    #[test]
+    fn test_counts_and_histogram_share_the_filters() {
+        let from = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let filters = [
+            AnomalyFilter::TimeRange(Some(from), None),
+            AnomalyFilter::ShadowEq(false),
+        ];
+        
+        let query = CompiledQuery::count_by::<AnomaliesTable>(&filters, "severity");
+        assert_eq!(
+            query.sql,
+            "SELECT severity AS key, COUNT(*) AS count FROM anomalies \
+             WHERE shadow = $1 AND detected_at >= $2 GROUP BY 1 ORDER BY 2 DESC, 1"
+        );
+        assert_eq!(query.binds, vec![BindValue::Bool(false), BindValue::Timestamp(from)]);
+        
+        let query = CompiledQuery::histogram::<AnomaliesTable>(&filters, 3600);
+        assert_eq!(
+            query.sql,
+            "SELECT to_timestamp(floor(extract(epoch FROM detected_at)::float8 / $3) * $3) \
+             AS bucket, COUNT(*) AS count FROM anomalies \
+             WHERE shadow = $1 AND detected_at >= $2 GROUP BY 1 ORDER BY 1"
+        );
+        assert_eq!(query.binds[2], BindValue::Int(3600));
+    }
+    
+    #[test]
+    fn test_downsample_groups_by_aligned_bucket() {
+        let query = CompiledQuery::downsample::<BookMetricsTable>(
+            &[
+                BookMetricsFilter::SymbolEq("BTC/USDT".to_string()),
+                BookMetricsFilter::ExchangeEq("binance".to_string()),
+            ],
+            300,
+        );
+        
+        assert_eq!(
+            query.sql,
+            "SELECT to_timestamp(floor(extract(epoch FROM timestamp)::float8 / $3) * $3) AS bucket, \
+             COUNT(*) AS samples, \
+             AVG(spread_bps) AS spread_bps_avg, MIN(spread_bps) AS spread_bps_min, MAX(spread_bps) AS spread_bps_max, \
+             AVG(imbalance) AS imbalance_avg, MIN(imbalance) AS imbalance_min, MAX(imbalance) AS imbalance_max, \
+             AVG(microprice) AS microprice_avg, MIN(microprice) AS microprice_min, MAX(microprice) AS microprice_max \
+             FROM book_metrics WHERE exchange = $1 AND symbol = $2 GROUP BY 1 ORDER BY 1"
+        );
+        assert_eq!(query.binds[2], BindValue::Int(300));
+    }
+}
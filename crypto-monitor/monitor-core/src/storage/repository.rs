@@ -0,0 +1,911 @@
+use super::query::{
+    AnomalyFilter, BookMetricsFilter, Bucketed, CandleFilter, CompiledQuery, FilterSet,
+    NotificationFilter, Page, Priced, SignalFilter, Table, TickFilter, MAX_LIMIT,
+};
+use crate::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+pub struct AnomaliesTable;
+
+impl Table for AnomaliesTable {
+    type Filter = AnomalyFilter;
+    
+    const NAME: &'static str = "anomalies";
+    const COLUMNS: &'static str = "id, exchange, symbol, anomaly_type, severity, confidence, \
+        current_value::float8 AS current_value, expected_value::float8 AS expected_value, \
+        deviation::float8 AS deviation, z_score::float8 AS z_score, \
+        percentage_change::float8 AS percentage_change, description, reason, metadata, \
+        detected_at, instance_id, shadow, source";
+    const TIME_COLUMN: &'static str = "detected_at";
+    const TIEBREAK_COLUMN: Option<&'static str> = Some("id");
+}
+
+pub struct TicksTable;
+
+impl Table for TicksTable {
+    type Filter = TickFilter;
+    
+    const NAME: &'static str = "market_data";
+    const COLUMNS: &'static str = "id, exchange, symbol, price::float8 AS price, \
+        volume::float8 AS volume, bid::float8 AS bid, ask::float8 AS ask, timestamp";
+    const TIME_COLUMN: &'static str = "timestamp";
+}
+
+impl Priced for TicksTable {
+    const PRICE_COLUMN: &'static str = "price";
+    const VOLUME_COLUMN: &'static str = "volume";
+}
+
+pub struct CandlesTable;
+
+impl Table for CandlesTable {
+    type Filter = CandleFilter;
+    
+    const NAME: &'static str = "candles";
+    const COLUMNS: &'static str = "exchange, symbol, interval, open_time, \
+        open::float8 AS open, high::float8 AS high, low::float8 AS low, \
+        close::float8 AS close, volume::float8 AS volume, trades";
+    const TIME_COLUMN: &'static str = "open_time";
+}
+
+pub struct SignalsTable;
+
+impl Table for SignalsTable {
+    type Filter = SignalFilter;
+    
+    const NAME: &'static str = "trading_signals";
+    const COLUMNS: &'static str = "id, exchange, symbol, signal_type, pattern_kind, status, \
+        price::float8 AS price, reason, anomaly_id, created_at";
+    const TIME_COLUMN: &'static str = "created_at";
+}
+
+pub struct NotificationsTable;
+
+impl Table for NotificationsTable {
+    type Filter = NotificationFilter;
+    
+    const NAME: &'static str = "alerts";
+    const COLUMNS: &'static str = "id, alert_type, severity, title, message, metadata, \
+        channels, status, sent_at, created_at";
+    const TIME_COLUMN: &'static str = "created_at";
+}
+
+pub struct BookMetricsTable;
+
+impl Table for BookMetricsTable {
+    type Filter = BookMetricsFilter;
+    
+    const NAME: &'static str = "book_metrics";
+    const COLUMNS: &'static str = "exchange, symbol, timestamp, spread_bps, imbalance, microprice";
+    const TIME_COLUMN: &'static str = "timestamp";
+}
+
+impl Bucketed for BookMetricsTable {
+    const METRICS: &'static [&'static str] = &["spread_bps", "imbalance", "microprice"];
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AnomalyRecord {
+    pub id: Uuid,
+    pub exchange: String,
+    pub symbol: String,
+    pub anomaly_type: String,
+    pub severity: String,
+    // 0 to 1, `None` for anomalies recorded before detectors reported it
+    pub confidence: Option<f64>,
+    pub current_value: f64,
+    pub expected_value: f64,
+    pub deviation: f64,
+    pub z_score: Option<f64>,
+    pub percentage_change: Option<f64>,
+    pub description: Option<String>,
+    // What the description was rendered from, `None` for external and older anomalies
+    pub reason: Option<serde_json::Value>,
+    pub metadata: Option<serde_json::Value>,
+    pub detected_at: DateTime<Utc>,
+    // Set when coordination is enabled, `shadow` for detections made while following
+    pub instance_id: Option<String>,
+    pub shadow: bool,
+    // External system that published the detection, `None` for the monitor's own
+    pub source: Option<String>,
+}
+
+// Saved detector state, `state` is the serialized JSON document
+#[derive(Debug, Clone, FromRow)]
+pub struct DetectorStateRecord {
+    pub name: String,
+    pub saved_at: DateTime<Utc>,
+    pub state: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct PositionRecord {
+    pub id: Uuid,
+    pub exchange: String,
+    pub symbol: String,
+    // long | short
+    pub side: String,
+    pub quantity: f64,
+    pub entry_price: f64,
+    pub current_price: f64,
+    pub unrealized_pnl: f64,
+    pub realized_pnl: f64,
+    pub stop_loss: Option<f64>,
+    pub take_profit: Option<f64>,
+    // open | closed
+    pub status: String,
+    pub opened_at: DateTime<Utc>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub adds: i32,
+    pub stop_order_id: Option<String>,
+    pub take_profit_order_id: Option<String>,
+    pub fees: f64,
+}
+
+// One executed order against a position
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct TradeRecord {
+    pub id: Uuid,
+    pub position_id: Uuid,
+    pub order_id: Option<String>,
+    pub exchange: String,
+    pub symbol: String,
+    // buy | sell
+    pub side: String,
+    // entry | add | close
+    pub kind: String,
+    pub quantity: f64,
+    pub price: f64,
+    pub realized_pnl: Option<f64>,
+    pub fee: f64,
+    pub executed_at: DateTime<Utc>,
+}
+
+// A spilled trade journal record, `record` is its serialized JSON document
+#[derive(Debug, Clone, FromRow)]
+pub struct TradeJournalRecord {
+    pub id: Uuid,
+    pub recorded_at: DateTime<Utc>,
+    pub exchange: String,
+    pub symbol: String,
+    pub event: String,
+    pub record: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct TickRecord {
+    pub id: Uuid,
+    pub exchange: String,
+    pub symbol: String,
+    pub price: f64,
+    pub volume: f64,
+    pub bid: Option<f64>,
+    pub ask: Option<f64>,
+    pub timestamp: DateTime<Utc>,
+}
+
+// One instrument's ticks over a window, see `CompiledQuery::summarize`
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct MarketSummaryRecord {
+    pub exchange: String,
+    pub symbol: String,
+    pub open_price: f64,
+    pub last_price: f64,
+    pub high: f64,
+    pub low: f64,
+    pub volume: f64,
+    pub samples: i64,
+    pub last_update: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CandleRecord {
+    pub exchange: String,
+    pub symbol: String,
+    pub interval: String,
+    pub open_time: DateTime<Utc>,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+    pub trades: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SignalRecord {
+    pub id: Uuid,
+    pub exchange: String,
+    pub symbol: String,
+    pub signal_type: String,
+    pub pattern_kind: Option<String>,
+    pub status: String,
+    pub price: f64,
+    pub reason: Option<String>,
+    pub anomaly_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct NotificationRecord {
+    pub id: Uuid,
+    pub alert_type: String,
+    pub severity: String,
+    pub title: String,
+    pub message: String,
+    pub metadata: Option<serde_json::Value>,
+    pub channels: Option<Vec<String>>,
+    pub status: String,
+    pub sent_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct BookMetricsRecord {
+    pub exchange: String,
+    pub symbol: String,
+    pub timestamp: DateTime<Utc>,
+    pub spread_bps: f64,
+    pub imbalance: f64,
+    pub microprice: f64,
+}
+
+// Rows sharing a value of the grouped column, see `CompiledQuery::count_by`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct GroupCount {
+    pub key: String,
+    pub count: i64,
+}
+
+// Rows in one bucket, see `CompiledQuery::histogram`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct BucketCount {
+    pub bucket: DateTime<Utc>,
+    pub count: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct BookMetricsBucket {
+    pub bucket: DateTime<Utc>,
+    pub samples: i64,
+    pub spread_bps_avg: f64,
+    pub spread_bps_min: f64,
+    pub spread_bps_max: f64,
+    pub imbalance_avg: f64,
+    pub imbalance_min: f64,
+    pub imbalance_max: f64,
+    pub microprice_avg: f64,
+    pub microprice_min: f64,
+    pub microprice_max: f64,
+}
+
+// A raw sample is a bucket of one, so both resolutions share a response shape
+impl From<BookMetricsRecord> for BookMetricsBucket {
+    fn from(record: BookMetricsRecord) -> Self {
+        Self {
+            bucket: record.timestamp,
+            samples: 1,
+            spread_bps_avg: record.spread_bps,
+            spread_bps_min: record.spread_bps,
+            spread_bps_max: record.spread_bps,
+            imbalance_avg: record.imbalance,
+            imbalance_min: record.imbalance,
+            imbalance_max: record.imbalance,
+            microprice_avg: record.microprice,
+            microprice_min: record.microprice,
+            microprice_max: record.microprice,
+        }
+    }
+}
+
+pub struct Repository<T: Table> {
+    pool: PgPool,
+    _table: std::marker::PhantomData<T>,
+}
+
+impl<T: Table> Clone for Repository<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.pool.clone())
+    }
+}
+
+impl<T: Table> Repository<T> {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            _table: std::marker::PhantomData,
+        }
+    }
+    
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+    
+    pub async fn find<R>(&self, filters: &FilterSet<T::Filter>) -> Result<Vec<R>>
+    where
+        R: for<'r> FromRow<'r, sqlx::postgres::PgRow> + Send + Unpin,
+    {
+        let query = CompiledQuery::select::<T>(filters);
+        Ok(query.query_as::<R>().fetch_all(&self.pool).await?)
+    }
+    
+    pub async fn count(&self, filters: &[T::Filter]) -> Result<i64> {
+        let query = CompiledQuery::count::<T>(filters);
+        let (count,): (i64,) = query.query_as().fetch_one(&self.pool).await?;
+        Ok(count)
+    }
+    
+    pub async fn count_by(
+        &self,
+        filters: &[T::Filter],
+        column: &'static str,
+    ) -> Result<Vec<GroupCount>> {
+        let query = CompiledQuery::count_by::<T>(filters, column);
+        Ok(query.query_as().fetch_all(&self.pool).await?)
+    }
+    
+    pub async fn histogram(
+        &self,
+        filters: &[T::Filter],
+        bucket_secs: i64,
+    ) -> Result<Vec<BucketCount>> {
+        let query = CompiledQuery::histogram::<T>(filters, bucket_secs);
+        Ok(query.query_as().fetch_all(&self.pool).await?)
+    }
+}
+
+pub type AnomalyRepository = Repository<AnomaliesTable>;
+pub type TickRepository = Repository<TicksTable>;
+pub type CandleRepository = Repository<CandlesTable>;
+pub type SignalRepository = Repository<SignalsTable>;
+pub type NotificationRepository = Repository<NotificationsTable>;
+pub type BookMetricsRepository = Repository<BookMetricsTable>;
+
+// Single documents by name rather than a filtered series, so not a `Repository<T>`
+#[derive(Clone)]
+pub struct DetectorStateRepository {
+    pool: PgPool,
+}
+
+// Positions are written row by row as they change, not queried as a filtered series
+#[derive(Clone)]
+pub struct PositionRepository {
+    pool: PgPool,
+}
+
+#[derive(Clone)]
+pub struct TradeRepository {
+    pool: PgPool,
+}
+
+// Append-only, read back by time range for journal exports
+#[derive(Clone)]
+pub struct TradeJournalRepository {
+    pool: PgPool,
+}
+
+// Named trader documents, one row each like `DetectorStateRepository`
+#[derive(Clone)]
+pub struct TraderStateRepository {
+    pool: PgPool,
+}
+
+const POSITION_COLUMNS: &str = "id, exchange, symbol, side, quantity::float8 AS quantity, \
+    entry_price::float8 AS entry_price, current_price::float8 AS current_price, \
+    unrealized_pnl::float8 AS unrealized_pnl, realized_pnl::float8 AS realized_pnl, \
+    stop_loss::float8 AS stop_loss, take_profit::float8 AS take_profit, status, opened_at, \
+    closed_at, adds, stop_order_id, take_profit_order_id, fees::float8 AS fees";
+
+impl AnomalyRepository {
+    pub async fn find_anomalies(&self, filters: &FilterSet<AnomalyFilter>) -> Result<Vec<AnomalyRecord>> {
+        self.find(filters).await
+    }
+    
+    pub async fn find_by_id(&self, id: Uuid) -> Result<Option<AnomalyRecord>> {
+        let sql = format!(
+            "SELECT {} FROM {} WHERE id = $1",
+            AnomaliesTable::COLUMNS,
+            AnomaliesTable::NAME
+        );
+        Ok(sqlx::query_as(&sql).bind(id).fetch_optional(&self.pool).await?)
+    }
+    
+    pub async fn insert(&self, record: &AnomalyRecord) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO anomalies (id, exchange, symbol, anomaly_type, severity, current_value, \
+             expected_value, deviation, z_score, percentage_change, description, metadata, \
+             detected_at, instance_id, shadow, source, confidence, reason) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12::text::jsonb, $13, $14, $15, \
+             $16, $17, $18::text::jsonb) \
+             ON CONFLICT (id) DO NOTHING",
+        )
+        .bind(record.id)
+        .bind(&record.exchange)
+        .bind(&record.symbol)
+        .bind(&record.anomaly_type)
+        .bind(&record.severity)
+        .bind(record.current_value)
+        .bind(record.expected_value)
+        .bind(record.deviation)
+        .bind(record.z_score)
+        .bind(record.percentage_change)
+        .bind(&record.description)
+        .bind(record.metadata.as_ref().map(|m| m.to_string()))
+        .bind(record.detected_at)
+        .bind(&record.instance_id)
+        .bind(record.shadow)
+        .bind(&record.source)
+        .bind(record.confidence)
+        .bind(record.reason.as_ref().map(|r| r.to_string()))
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
+
+impl TickRepository {
+    pub async fn find_ticks(&self, filters: &FilterSet<TickFilter>) -> Result<Vec<TickRecord>> {
+        self.find(filters).await
+    }
+    
+    // One pair's ticks since `since`, oldest first, capped to the newest `MAX_LIMIT`
+    pub async fn fetch_recent_ticks(
+        &self,
+        exchange: &str,
+        symbol: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<TickRecord>> {
+        let filters = FilterSet::new(
+            vec![
+                TickFilter::ExchangeEq(exchange.to_string()),
+                TickFilter::SymbolEq(symbol.to_string()),
+                TickFilter::TimeRange(Some(since), None),
+            ],
+            Page {
+                limit: MAX_LIMIT,
+                offset: 0,
+            },
+        );
+        let mut ticks = self.find_ticks(&filters).await?;
+        ticks.reverse();
+        Ok(ticks)
+    }
+    
+    pub async fn summarize(
+        &self,
+        filters: &FilterSet<TickFilter>,
+    ) -> Result<Vec<MarketSummaryRecord>> {
+        let query = CompiledQuery::summarize::<TicksTable>(filters);
+        Ok(query.query_as().fetch_all(&self.pool).await?)
+    }
+}
+
+impl CandleRepository {
+    pub async fn find_candles(&self, filters: &FilterSet<CandleFilter>) -> Result<Vec<CandleRecord>> {
+        self.find(filters).await
+    }
+}
+
+impl SignalRepository {
+    pub async fn find_signals(&self, filters: &FilterSet<SignalFilter>) -> Result<Vec<SignalRecord>> {
+        self.find(filters).await
+    }
+}
+
+impl NotificationRepository {
+    pub async fn find_notifications(
+        &self,
+        filters: &FilterSet<NotificationFilter>,
+    ) -> Result<Vec<NotificationRecord>> {
+        self.find(filters).await
+    }
+}
+
+impl BookMetricsRepository {
+    pub async fn find_book_metrics(
+        &self,
+        filters: &FilterSet<BookMetricsFilter>,
+    ) -> Result<Vec<BookMetricsRecord>> {
+        self.find(filters).await
+    }
+    
+    pub async fn downsample(
+        &self,
+        filters: &[BookMetricsFilter],
+        bucket_secs: i64,
+    ) -> Result<Vec<BookMetricsBucket>> {
+        let query = CompiledQuery::downsample::<BookMetricsTable>(filters, bucket_secs);
+        Ok(query.query_as().fetch_all(&self.pool).await?)
+    }
+    
+    pub async fn insert_batch(&self, records: &[BookMetricsRecord]) -> Result<u64> {
+        if records.is_empty() {
+            return Ok(0);
+        }
+        
+        let mut exchanges = Vec::with_capacity(records.len());
+        let mut symbols = Vec::with_capacity(records.len());
+        let mut timestamps = Vec::with_capacity(records.len());
+        let mut spreads = Vec::with_capacity(records.len());
+        let mut imbalances = Vec::with_capacity(records.len());
+        let mut microprices = Vec::with_capacity(records.len());
+        for record in records {
+            exchanges.push(record.exchange.clone());
+            symbols.push(record.symbol.clone());
+            timestamps.push(record.timestamp);
+            spreads.push(record.spread_bps);
+            imbalances.push(record.imbalance);
+            microprices.push(record.microprice);
+        }
+        
+        let result = sqlx::query(
+            "INSERT INTO book_metrics (exchange, symbol, timestamp, spread_bps, imbalance, microprice) \
+             SELECT * FROM UNNEST($1::varchar[], $2::varchar[], $3::timestamptz[], $4::float8[], \
+             $5::float8[], $6::float8[]) ON CONFLICT DO NOTHING",
+        )
+        .bind(&exchanges)
+        .bind(&symbols)
+        .bind(&timestamps)
+        .bind(&spreads)
+        .bind(&imbalances)
+        .bind(&microprices)
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(result.rows_affected())
+    }
+}
+
+impl DetectorStateRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+    
+    pub async fn save(&self, name: &str, saved_at: DateTime<Utc>, state: &str) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO detector_state (name, saved_at, state) VALUES ($1, $2, $3::text::jsonb) \
+             ON CONFLICT (name) DO UPDATE SET saved_at = EXCLUDED.saved_at, state = EXCLUDED.state",
+        )
+        .bind(name)
+        .bind(saved_at)
+        .bind(state)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+    
+    pub async fn load(&self, name: &str) -> Result<Option<DetectorStateRecord>> {
+        Ok(sqlx::query_as(
+            "SELECT name, saved_at, state::text AS state FROM detector_state WHERE name = $1",
+        )
+        .bind(name)
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+}
+
+impl PositionRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+    
+    // Idempotent, a position written twice keeps its first row
+    pub async fn insert(&self, record: &PositionRecord) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO positions (id, exchange, symbol, side, quantity, entry_price, \
+             current_price, unrealized_pnl, realized_pnl, stop_loss, take_profit, status, \
+             opened_at, closed_at, adds, stop_order_id, take_profit_order_id, fees) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, \
+             $17, $18) \
+             ON CONFLICT (id) DO NOTHING",
+        )
+        .bind(record.id)
+        .bind(&record.exchange)
+        .bind(&record.symbol)
+        .bind(&record.side)
+        .bind(record.quantity)
+        .bind(record.entry_price)
+        .bind(record.current_price)
+        .bind(record.unrealized_pnl)
+        .bind(record.realized_pnl)
+        .bind(record.stop_loss)
+        .bind(record.take_profit)
+        .bind(&record.status)
+        .bind(record.opened_at)
+        .bind(record.closed_at)
+        .bind(record.adds)
+        .bind(&record.stop_order_id)
+        .bind(&record.take_profit_order_id)
+        .bind(record.fees)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+    
+    // Writes everything that changes over a position's life, closing it when `status` says so.
+    // A closed row is never reopened by a late update
+    pub async fn update(&self, record: &PositionRecord) -> Result<u64> {
+        let result = sqlx::query(
+            "UPDATE positions SET quantity = $2, entry_price = $3, current_price = $4, \
+             unrealized_pnl = $5, realized_pnl = $6, stop_loss = $7, take_profit = $8, \
+             status = $9, closed_at = $10, adds = $11, stop_order_id = $12, \
+             take_profit_order_id = $13, fees = $14 \
+             WHERE id = $1 AND status = 'open'",
+        )
+        .bind(record.id)
+        .bind(record.quantity)
+        .bind(record.entry_price)
+        .bind(record.current_price)
+        .bind(record.unrealized_pnl)
+        .bind(record.realized_pnl)
+        .bind(record.stop_loss)
+        .bind(record.take_profit)
+        .bind(&record.status)
+        .bind(record.closed_at)
+        .bind(record.adds)
+        .bind(&record.stop_order_id)
+        .bind(&record.take_profit_order_id)
+        .bind(record.fees)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+    
+    pub async fn find_by_id(&self, id: Uuid) -> Result<Option<PositionRecord>> {
+        let sql = format!("SELECT {} FROM positions WHERE id = $1", POSITION_COLUMNS);
+        Ok(sqlx::query_as(&sql).bind(id).fetch_optional(&self.pool).await?)
+    }
+    
+    // Oldest first
+    pub async fn find_open(&self) -> Result<Vec<PositionRecord>> {
+        let sql = format!(
+            "SELECT {} FROM positions WHERE status = 'open' ORDER BY opened_at",
+            POSITION_COLUMNS
+        );
+        Ok(sqlx::query_as(&sql).fetch_all(&self.pool).await?)
+    }
+}
+
+impl TradeRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+    
+    pub async fn insert(&self, record: &TradeRecord) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO trades (id, position_id, order_id, exchange, symbol, side, kind, \
+             quantity, price, realized_pnl, fee, executed_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12) \
+             ON CONFLICT (id) DO NOTHING",
+        )
+        .bind(record.id)
+        .bind(record.position_id)
+        .bind(&record.order_id)
+        .bind(&record.exchange)
+        .bind(&record.symbol)
+        .bind(&record.side)
+        .bind(&record.kind)
+        .bind(record.quantity)
+        .bind(record.price)
+        .bind(record.realized_pnl)
+        .bind(record.fee)
+        .bind(record.executed_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+    
+    // Oldest first
+    pub async fn find_by_position(&self, position_id: Uuid) -> Result<Vec<TradeRecord>> {
+        Ok(sqlx::query_as(
+            "SELECT id, position_id, order_id, exchange, symbol, side, kind, \
+             quantity::float8 AS quantity, price::float8 AS price, \
+             realized_pnl::float8 AS realized_pnl, fee::float8 AS fee, executed_at \
+             FROM trades WHERE position_id = $1 ORDER BY executed_at",
+        )
+        .bind(position_id)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+}
+
+impl TradeJournalRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+    
+    pub async fn insert(&self, record: &TradeJournalRecord) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO trade_journal (id, recorded_at, exchange, symbol, event, record) \
+             VALUES ($1, $2, $3, $4, $5, $6::text::jsonb) ON CONFLICT (id) DO NOTHING",
+        )
+        .bind(record.id)
+        .bind(record.recorded_at)
+        .bind(&record.exchange)
+        .bind(&record.symbol)
+        .bind(&record.event)
+        .bind(&record.record)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+    
+    // Oldest first, either bound left open when `None`
+    pub async fn find_between(
+        &self,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<Vec<TradeJournalRecord>> {
+        Ok(sqlx::query_as(
+            "SELECT id, recorded_at, exchange, symbol, event, record::text AS record \
+             FROM trade_journal \
+             WHERE ($1::timestamptz IS NULL OR recorded_at >= $1) \
+             AND ($2::timestamptz IS NULL OR recorded_at <= $2) \
+             ORDER BY recorded_at",
+        )
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+    
+    // Up to `limit` records after the `(recorded_at, id)` of `after`, in that order
+    pub async fn find_page(
+        &self,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        after: Option<(DateTime<Utc>, Uuid)>,
+        limit: i64,
+    ) -> Result<Vec<TradeJournalRecord>> {
+        Ok(sqlx::query_as(
+            "SELECT id, recorded_at, exchange, symbol, event, record::text AS record \
+             FROM trade_journal \
+             WHERE ($1::timestamptz IS NULL OR recorded_at >= $1) \
+             AND ($2::timestamptz IS NULL OR recorded_at <= $2) \
+             AND ($3::timestamptz IS NULL OR (recorded_at, id) > ($3, $4)) \
+             ORDER BY recorded_at, id LIMIT $5",
+        )
+        .bind(from)
+        .bind(to)
+        .bind(after.map(|(at, _)| at))
+        .bind(after.map(|(_, id)| id))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+}
+
+impl TraderStateRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+    
+    pub async fn save(&self, name: &str, saved_at: DateTime<Utc>, state: &str) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO trader_state (name, saved_at, state) VALUES ($1, $2, $3::text::jsonb) \
+             ON CONFLICT (name) DO UPDATE SET saved_at = EXCLUDED.saved_at, state = EXCLUDED.state",
+        )
+        .bind(name)
+        .bind(saved_at)
+        .bind(state)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+    
+    // The saved JSON document
+    pub async fn load(&self, name: &str) -> Result<Option<String>> {
+        Ok(sqlx::query_scalar("SELECT state::text FROM trader_state WHERE name = $1")
+            .bind(name)
+            .fetch_optional(&self.pool)
+            .await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(position: &PositionRecord, kind: &str, side: &str, price: f64) -> TradeRecord {
+        TradeRecord {
+            id: Uuid::new_v4(),
+            position_id: position.id,
+            order_id: Some(format!("{}-{}", kind, position.id.simple())),
+            exchange: position.exchange.clone(),
+            symbol: position.symbol.clone(),
+            side: side.to_string(),
+            kind: kind.to_string(),
+            quantity: position.quantity,
+            price,
+            realized_pnl: None,
+            fee: 0.0,
+            executed_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn test_position_open_update_close_round_trip() {
+        let pool = monitor_testkit::db::migrated_pool().await;
+        let positions = PositionRepository::new(pool.clone());
+        let trades = TradeRepository::new(pool);
+
+        let opened_at = Utc::now();
+        let mut record = PositionRecord {
+            id: Uuid::new_v4(),
+            exchange: "binance".to_string(),
+            symbol: "BTC/USDT".to_string(),
+            side: "long".to_string(),
+            quantity: 0.002,
+            entry_price: 50_000.0,
+            current_price: 50_000.0,
+            unrealized_pnl: 0.0,
+            realized_pnl: 0.0,
+            stop_loss: Some(48_500.0),
+            take_profit: Some(53_000.0),
+            status: "open".to_string(),
+            opened_at,
+            closed_at: None,
+            adds: 0,
+            stop_order_id: Some("s-1".to_string()),
+            take_profit_order_id: Some("t-1".to_string()),
+            fees: 0.05,
+        };
+        positions.insert(&record).await.unwrap();
+        trades.insert(&trade(&record, "entry", "buy", 50_000.0)).await.unwrap();
+        assert_eq!(positions.find_open().await.unwrap().len(), 1);
+
+        record.current_price = 51_000.0;
+        record.unrealized_pnl = 2.0;
+        record.stop_loss = Some(49_470.0);
+        record.take_profit_order_id = None;
+        assert_eq!(positions.update(&record).await.unwrap(), 1);
+        let loaded = positions.find_by_id(record.id).await.unwrap().unwrap();
+        assert_eq!(loaded.current_price, 51_000.0);
+        assert_eq!(loaded.stop_loss, Some(49_470.0));
+        assert_eq!(loaded.stop_order_id.as_deref(), Some("s-1"));
+        assert_eq!(loaded.take_profit_order_id, None);
+
+        record.status = "closed".to_string();
+        record.realized_pnl = 2.0;
+        record.unrealized_pnl = 0.0;
+        record.closed_at = Some(Utc::now());
+        record.fees = 0.1;
+        assert_eq!(positions.update(&record).await.unwrap(), 1);
+        let mut close = trade(&record, "close", "sell", 51_000.0);
+        close.realized_pnl = Some(2.0);
+        close.fee = 0.05;
+        trades.insert(&close).await.unwrap();
+
+        assert!(positions.find_open().await.unwrap().is_empty());
+        // A late price update does not reopen it
+        record.status = "open".to_string();
+        assert_eq!(positions.update(&record).await.unwrap(), 0);
+        let loaded = positions.find_by_id(record.id).await.unwrap().unwrap();
+        assert_eq!(loaded.status, "closed");
+        assert_eq!(loaded.realized_pnl, 2.0);
+        assert_eq!(loaded.fees, 0.1);
+
+        let history = trades.find_by_position(record.id).await.unwrap();
+        let kinds: Vec<&str> = history.iter().map(|t| t.kind.as_str()).collect();
+        assert_eq!(kinds, vec!["entry", "close"]);
+        assert_eq!(history[1].realized_pnl, Some(2.0));
+        assert_eq!(history[1].fee, 0.05);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn test_trader_state_is_replaced_on_save() {
+        let state = TraderStateRepository::new(monitor_testkit::db::migrated_pool().await);
+        assert_eq!(state.load("ledger").await.unwrap(), None);
+
+        state.save("ledger", Utc::now(), r#"{"balance": 1.0}"#).await.unwrap();
+        state.save("ledger", Utc::now(), r#"{"balance": 2.0}"#).await.unwrap();
+        let saved: serde_json::Value =
+            serde_json::from_str(&state.load("ledger").await.unwrap().unwrap()).unwrap();
+        assert_eq!(saved["balance"], 2.0);
+    }
+}
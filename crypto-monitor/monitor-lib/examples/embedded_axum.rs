@@ -0,0 +1,73 @@
+//! Embeds the monitor in a minimal axum service that exposes the latest detections.
+//!
+//! ```sh
+//! cargo run -p crypto-monitor --example embedded_axum -- config.yaml
+//! ```
+
+use axum::{extract::State, routing::get, Json, Router};
+use crypto_monitor::prelude::*;
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+#[derive(Clone)]
+struct AppState {
+    monitor: Monitor,
+    recent: Arc<Mutex<VecDeque<AnomalyDetection>>>,
+}
+
+async fn recent_detections(State(state): State<AppState>) -> Json<Vec<AnomalyDetection>> {
+    Json(state.recent.lock().unwrap().iter().cloned().collect())
+}
+
+async fn warmup_remaining(State(state): State<AppState>) -> Json<i64> {
+    Json(state.monitor.warmup().remaining(chrono::Utc::now()).num_seconds())
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let path = std::env::args().nth(1).unwrap_or_else(|| "config.yaml".to_string());
+    let config: MonitorConfig = serde_yaml::from_str(&std::fs::read_to_string(path)?)?;
+
+    let bus = fluvio::Fluvio::connect().await?;
+    let monitor = Monitor::builder()
+        .config(config)
+        .with_bus(Arc::new(bus))
+        .build()?;
+
+    let state = AppState {
+        monitor: monitor.clone(),
+        recent: Arc::new(Mutex::new(VecDeque::new())),
+    };
+
+    // Keep the last 100 detections for the HTTP endpoint
+    let mut detections = monitor.subscribe_detections();
+    let recent = state.recent.clone();
+    tokio::spawn(async move {
+        while let Ok(detection) = detections.recv().await {
+            let mut recent = recent.lock().unwrap();
+            if recent.len() >= 100 {
+                recent.pop_front();
+            }
+            recent.push_back(detection);
+        }
+    });
+
+    let handle = monitor.start().await?;
+
+    let app = Router::new()
+        .route("/detections", get(recent_detections))
+        .route("/warmup", get(warmup_remaining))
+        .with_state(state);
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:3000").await?;
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+
+    handle.stop().await;
+    Ok(())
+}
@@ -0,0 +1,1550 @@
+//! Embeddable facade over the crypto-monitor detection, notification and trading pipeline.
+//!
+//! Every type needed to configure and drive the monitor is re-exported from this crate, so
+//! embedding services only need a single dependency.
+//!
+//! ```no_run
+//! use crypto_monitor::prelude::*;
+//!
+//! # async fn run(config: MonitorConfig) -> crypto_monitor::Result<()> {
+//! let monitor = Monitor::builder().config(config).build()?;
+//!
+//! let mut detections = monitor.subscribe_detections();
+//! tokio::spawn(async move {
+//!     while let Ok(detection) = detections.recv().await {
+//!         println!("{}", detection.description);
+//!     }
+//! });
+//!
+//! let handle = monitor.start().await?;
+//! // ...
+//! handle.stop().await;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! See `examples/embedded_axum.rs` for a monitor embedded in a minimal axum service.
+
+pub mod replay;
+
+pub use monitor_anomaly as anomaly;
+pub use monitor_core as core;
+pub use monitor_notifier as notifier;
+pub use monitor_trader as trader;
+
+pub use monitor_anomaly::{
+    activity::{ActivityAlert, ActivityMonitor},
+    analyzer::{AnalyzerManager, MarketAnalyzer, TradeSide},
+    candles::{CandleAggregator, CandleRangeDetector, CandleVolumeDetector},
+    correlation_break::{CorrelationBreakDetails, CorrelationBreakDetector},
+    correlator::{AnomalyCorrelator, AnomalyGroup, Correlated},
+    detector::{AnomalyDetectorManager, BandSide, BollingerBreachDetector, BollingerDetails},
+    escalation::{Escalation, SeverityEscalator},
+    external::{ExternalAnomaly, ExternalIngest, IngestGate, IngestReceipt, IngestRejection},
+    funding::{FundingDetails, FundingRateDetector, FundingTrigger},
+    iceberg::{IcebergDetector, TopOfBook},
+    liquidation::{LiquidationAnomalyDetector, LiquidationDetails},
+    momentum::RateOfChangeDetector,
+    open_interest::{OpenInterestDetails, OpenInterestDetector, OpenInterestPattern},
+    reason::{AnomalyReason, ReasonKind},
+    stats::{DetectorStats, DetectorStatsEntry},
+    suppression::{AlertSuppressor, SuppressionStats},
+    update_rate::UpdateRateDetector,
+    volatility::{VolatilityRegime, VolatilityRegimeDetector},
+    warmup::WarmupTracker,
+    AnomalyDetection, AnomalyMetrics, AnomalySeverity, BollingerConfig, CandleAnomalyDetector,
+    CandleConfig, CandleRangeConfig, CandleVolumeConfig, CrossExchangeDivergenceConfig,
+    DepthImbalanceConfig, DetectionDetails, EwmaAnomalyConfig, LargeTradeConfig,
+    PriceAnomalyConfig, QuoteSample, RateOfChangeConfig, SpreadAnomalyConfig, TimeSeriesData,
+    TimeframeZScore, TradeSample, UpdateRateConfig, VolatilityRegimeConfig, VolumeAnomalyConfig,
+    WindowEstimator,
+};
+pub use monitor_core::{
+    book_metrics::{BookMetrics, BookMetricsRecorder},
+    book_sync::{BookFeedHealth, BookFeedStatus, BookSyncConfig, BookSyncManager, RestSnapshotFetcher},
+    coordination::{CoordinationConfig, LeaderElection, LeadershipStatus},
+    exchange_status::{ExchangeHealth, ExchangeStatus, ExchangeStatusConfig, ExchangeStatusPoller},
+    instrument::InstrumentKey,
+    messaging::{Messaging, MessagingStatus},
+    router::{
+        CheckpointStore, DeliverySemantics, FileCheckpointStore, InMemoryCheckpointStore,
+        TopicHandler, TopicRouter,
+    },
+    AnomalyType, EventSource, EventType, MarketDataType, MonitorConfig, MonitorError,
+    MonitorEvent, Result, SeasonalityConfig, SeverityThresholds, SystemEventType,
+};
+pub use monitor_notifier::{manager::NotificationManager, Notification};
+pub use monitor_trader::{
+    context::MarketContext, executor::AutoTrader, liquidity::L1Snapshot,
+    strategy::ConfigurableStrategy, Position, TradingSignal, TradingStats,
+};
+
+pub mod prelude {
+    pub use crate::{
+        AnomalyDetection, AnomalySeverity, AnomalyType, AutoTrader, Monitor, MonitorBuilder,
+        MonitorConfig, MonitorEvent, MonitorHandle, NotificationManager,
+    };
+}
+
+use fluvio::{Fluvio, Offset};
+use futures::StreamExt;
+use monitor_core::{
+    event::MonitorEventRef,
+    model::{FundingRateUpdate, Liquidation, OpenInterestUpdate, OrderBook},
+    storage::repository::{AnomalyRecord, AnomalyRepository, TickRepository},
+};
+use serde::Deserialize;
+use sqlx::PgPool;
+use std::{collections::HashMap, sync::Arc};
+use tokio::{
+    sync::{broadcast, watch},
+    task::JoinHandle,
+};
+use tracing::{error, info, warn};
+
+const CHANNEL_CAPACITY: usize = 1024;
+const BUS_API_KEY_FIELD: &str = "api_key";
+
+#[derive(Default)]
+pub struct MonitorBuilder {
+    config: Option<MonitorConfig>,
+    storage: Option<PgPool>,
+    bus: Option<Arc<Fluvio>>,
+    messaging: Option<Arc<Messaging>>,
+    notifier: Option<Arc<NotificationManager>>,
+    trader: Option<Arc<AutoTrader>>,
+    leadership: Option<Arc<LeaderElection>>,
+    volume_config: Option<VolumeAnomalyConfig>,
+    price_config: Option<PriceAnomalyConfig>,
+    ewma_config: Option<EwmaAnomalyConfig>,
+    depth_config: Option<DepthImbalanceConfig>,
+    large_trade_config: Option<LargeTradeConfig>,
+    divergence_config: Option<CrossExchangeDivergenceConfig>,
+    spread_config: Option<SpreadAnomalyConfig>,
+    volatility_config: Option<VolatilityRegimeConfig>,
+    rate_of_change_config: Option<RateOfChangeConfig>,
+    candle_config: Option<CandleConfig>,
+    update_rate_config: Option<UpdateRateConfig>,
+    bollinger_config: Option<BollingerConfig>,
+}
+
+impl MonitorBuilder {
+    pub fn config(mut self, config: MonitorConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    pub fn with_storage(mut self, storage: PgPool) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    pub fn with_bus(mut self, bus: Arc<Fluvio>) -> Self {
+        self.bus = Some(bus);
+        self
+    }
+
+    // Consumes the engine's in-process stream instead of the bus, for degraded starts
+    pub fn with_messaging(mut self, messaging: Arc<Messaging>) -> Self {
+        self.messaging = Some(messaging);
+        self
+    }
+
+    pub fn with_notifier(mut self, notifier: Arc<NotificationManager>) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    pub fn with_trader(mut self, trader: Arc<AutoTrader>) -> Self {
+        self.trader = Some(trader);
+        self
+    }
+
+    // Only the leader notifies and trades, followers detect in shadow and persist their
+    // detections when storage is set
+    pub fn with_leadership(mut self, leadership: Arc<LeaderElection>) -> Self {
+        self.leadership = Some(leadership);
+        self
+    }
+
+    pub fn with_volume_config(mut self, config: VolumeAnomalyConfig) -> Self {
+        self.volume_config = Some(config);
+        self
+    }
+
+    pub fn with_price_config(mut self, config: PriceAnomalyConfig) -> Self {
+        self.price_config = Some(config);
+        self
+    }
+
+    // Used for the metrics `anomaly_detection` sets to the "ewma" baseline, in place of
+    // `anomaly_detection.ewma`
+    pub fn with_ewma_config(mut self, config: EwmaAnomalyConfig) -> Self {
+        self.ewma_config = Some(config);
+        self
+    }
+
+    pub fn with_depth_config(mut self, config: DepthImbalanceConfig) -> Self {
+        self.depth_config = Some(config);
+        self
+    }
+
+    pub fn with_large_trade_config(mut self, config: LargeTradeConfig) -> Self {
+        self.large_trade_config = Some(config);
+        self
+    }
+
+    // Compares each symbol's price across every exchange it trades on
+    pub fn with_divergence_config(mut self, config: CrossExchangeDivergenceConfig) -> Self {
+        self.divergence_config = Some(config);
+        self
+    }
+
+    pub fn with_spread_config(mut self, config: SpreadAnomalyConfig) -> Self {
+        self.spread_config = Some(config);
+        self
+    }
+
+    // Watches each pair's realized volatility for regime changes, off unless set here or under
+    // `anomaly_detection.volatility`
+    pub fn with_volatility_config(mut self, config: VolatilityRegimeConfig) -> Self {
+        self.volatility_config = Some(config);
+        self
+    }
+
+    // Alerts on the price change over each configured horizon, off unless set here or under
+    // `anomaly_detection.rate_of_change`
+    pub fn with_rate_of_change_config(mut self, config: RateOfChangeConfig) -> Self {
+        self.rate_of_change_config = Some(config);
+        self
+    }
+
+    // Builds candles from each pair's trades and checks their volume and range, off unless set
+    // here or under `anomaly_detection.candles`
+    pub fn with_candle_config(mut self, config: CandleConfig) -> Self {
+        self.candle_config = Some(config);
+        self
+    }
+
+    // Watches how many order book updates each pair gets per second, off unless set here or
+    // under `anomaly_detection.update_rate`
+    pub fn with_update_rate_config(mut self, config: UpdateRateConfig) -> Self {
+        self.update_rate_config = Some(config);
+        self
+    }
+
+    // Watches for prices closing outside the Bollinger bands, off unless set here or under
+    // `anomaly_detection.bollinger`
+    pub fn with_bollinger_config(mut self, config: BollingerConfig) -> Self {
+        self.bollinger_config = Some(config);
+        self
+    }
+
+    pub fn build(self) -> Result<Monitor> {
+        let config = self
+            .config
+            .ok_or_else(|| MonitorError::Configuration("Monitor config not set".to_string()))?;
+
+        let warmup = Arc::new(WarmupTracker::new(config.monitoring.warmup.clone()));
+        let suppressor = Arc::new(AlertSuppressor::new(config.monitoring.suppression.clone()));
+        let anomaly_detection = &config.monitoring.anomaly_detection;
+        let mut volume_config = self.volume_config.unwrap_or_default();
+        if let Some(bands) = &anomaly_detection.volume_z_score_severity {
+            volume_config.z_score_severity = bands.clone();
+        }
+        if let Some(seasonality) = &anomaly_detection.volume_seasonality {
+            volume_config.seasonality = seasonality.clone();
+        }
+        if let Some(estimator) = anomaly_detection.volume_estimator {
+            volume_config.estimator = estimator;
+        }
+        let mut price_config = self.price_config.unwrap_or_default();
+        if let Some(estimator) = anomaly_detection.price_estimator {
+            price_config.estimator = estimator;
+        }
+        if let Some(bands) = &anomaly_detection.price_percentage_severity {
+            price_config.percentage_severity = bands.clone();
+        }
+        if let Some(bands) = &anomaly_detection.price_z_score_severity {
+            price_config.z_score_severity = bands.clone();
+        }
+        let mut anomaly_manager = AnomalyDetectorManager::new(volume_config, price_config)
+            .with_baselines(anomaly_detection.volume_detector, anomaly_detection.price_detector)
+            .with_ewma_config(
+                self.ewma_config.or_else(|| anomaly_detection.ewma.clone()).unwrap_or_default(),
+            )
+            .with_depth_config(self.depth_config.unwrap_or_default())
+            .with_large_trade_config(self.large_trade_config.unwrap_or_default())
+            .with_divergence_config(self.divergence_config.unwrap_or_default())
+            .with_spread_config(self.spread_config.unwrap_or_default())
+            .with_warmup(warmup.clone());
+        let volatility_config =
+            self.volatility_config.or_else(|| anomaly_detection.volatility.clone());
+        if let Some(volatility_config) = volatility_config {
+            anomaly_manager = anomaly_manager.with_volatility_config(volatility_config);
+        }
+        let rate_of_change_config =
+            self.rate_of_change_config.or_else(|| anomaly_detection.rate_of_change.clone());
+        if let Some(rate_of_change_config) = rate_of_change_config {
+            anomaly_manager = anomaly_manager.with_rate_of_change_config(rate_of_change_config);
+        }
+        let candle_config = self.candle_config.or_else(|| anomaly_detection.candles.clone());
+        if let Some(candle_config) = candle_config {
+            anomaly_manager = anomaly_manager.with_candle_config(candle_config);
+        }
+        let update_rate_config =
+            self.update_rate_config.or_else(|| anomaly_detection.update_rate.clone());
+        if let Some(update_rate_config) = update_rate_config {
+            anomaly_manager = anomaly_manager.with_update_rate_config(update_rate_config);
+        }
+        let bollinger_config =
+            self.bollinger_config.or_else(|| anomaly_detection.bollinger.clone());
+        if let Some(bollinger_config) = bollinger_config {
+            anomaly_manager = anomaly_manager.with_bollinger_config(bollinger_config);
+        }
+        let anomaly_manager = Arc::new(anomaly_manager);
+        let activity = &config.monitoring.activity;
+        let activity = Arc::new(
+            ActivityMonitor::new(
+                activity.expected.clone(),
+                activity.maintenance_windows.clone(),
+                chrono::Duration::seconds(activity.feed_stale_secs as i64),
+            )
+            .with_warmup(warmup.clone()),
+        );
+        let exchange_status = match &config.monitoring.activity.exchange_status {
+            status if status.enabled => Some(Arc::new(ExchangeStatusPoller::new(status.clone())?)),
+            _ => None,
+        };
+        let iceberg = Arc::new(
+            IcebergDetector::new(config.monitoring.iceberg.clone()).with_warmup(warmup.clone()),
+        );
+        let analyzer = Arc::new(
+            AnalyzerManager::new(config.monitoring.analyzer.clone()).with_warmup(warmup.clone()),
+        );
+        let liquidation = Arc::new(
+            LiquidationAnomalyDetector::new(config.monitoring.liquidation.clone())
+                .with_warmup(warmup.clone()),
+        );
+        let funding = Arc::new(
+            FundingRateDetector::new(config.monitoring.funding.clone()).with_warmup(warmup.clone()),
+        );
+        let open_interest = Arc::new(
+            OpenInterestDetector::new(config.monitoring.open_interest.clone())
+                .with_warmup(warmup.clone()),
+        );
+        let correlation_break = Arc::new(
+            CorrelationBreakDetector::new(config.monitoring.correlation_break.clone())
+                .with_warmup(warmup.clone()),
+        );
+        let escalation = Arc::new(SeverityEscalator::new(config.monitoring.escalation.clone()));
+        let correlator = Arc::new(AnomalyCorrelator::new(config.monitoring.correlation.clone()));
+        let ingest = Arc::new(IngestGate::new(config.monitoring.ingest.clone()));
+
+        let book_metrics = Arc::new(BookMetricsRecorder::new());
+
+        let (detections, _) = broadcast::channel(CHANNEL_CAPACITY);
+        let (events, _) = broadcast::channel(CHANNEL_CAPACITY);
+
+        Ok(Monitor {
+            inner: Arc::new(Inner {
+                config,
+                storage: self.storage,
+                bus: self.bus,
+                messaging: self.messaging,
+                notifier: self.notifier,
+                trader: self.trader,
+                leadership: self.leadership,
+                anomaly_manager,
+                warmup,
+                suppressor,
+                activity,
+                exchange_status,
+                iceberg,
+                analyzer,
+                liquidation,
+                funding,
+                open_interest,
+                correlation_break,
+                escalation,
+                correlator,
+                ingest,
+                book_metrics,
+                detections,
+                events,
+            }),
+        })
+    }
+}
+
+struct Inner {
+    config: MonitorConfig,
+    storage: Option<PgPool>,
+    bus: Option<Arc<Fluvio>>,
+    messaging: Option<Arc<Messaging>>,
+    notifier: Option<Arc<NotificationManager>>,
+    trader: Option<Arc<AutoTrader>>,
+    leadership: Option<Arc<LeaderElection>>,
+    anomaly_manager: Arc<AnomalyDetectorManager>,
+    warmup: Arc<WarmupTracker>,
+    suppressor: Arc<AlertSuppressor>,
+    activity: Arc<ActivityMonitor>,
+    exchange_status: Option<Arc<ExchangeStatusPoller>>,
+    iceberg: Arc<IcebergDetector>,
+    analyzer: Arc<AnalyzerManager>,
+    liquidation: Arc<LiquidationAnomalyDetector>,
+    funding: Arc<FundingRateDetector>,
+    open_interest: Arc<OpenInterestDetector>,
+    correlation_break: Arc<CorrelationBreakDetector>,
+    escalation: Arc<SeverityEscalator>,
+    correlator: Arc<AnomalyCorrelator>,
+    ingest: Arc<IngestGate>,
+    book_metrics: Arc<BookMetricsRecorder>,
+    detections: broadcast::Sender<AnomalyDetection>,
+    events: broadcast::Sender<MonitorEvent>,
+}
+
+#[derive(Clone)]
+pub struct Monitor {
+    inner: Arc<Inner>,
+}
+
+impl Monitor {
+    pub fn builder() -> MonitorBuilder {
+        MonitorBuilder::default()
+    }
+
+    pub fn config(&self) -> &MonitorConfig {
+        &self.inner.config
+    }
+
+    pub fn storage(&self) -> Option<&PgPool> {
+        self.inner.storage.as_ref()
+    }
+
+    pub fn anomaly_manager(&self) -> &Arc<AnomalyDetectorManager> {
+        &self.inner.anomaly_manager
+    }
+
+    pub fn warmup(&self) -> &Arc<WarmupTracker> {
+        &self.inner.warmup
+    }
+
+    pub fn suppressor(&self) -> &Arc<AlertSuppressor> {
+        &self.inner.suppressor
+    }
+
+    pub fn activity(&self) -> &Arc<ActivityMonitor> {
+        &self.inner.activity
+    }
+
+    pub fn exchange_status(&self) -> Option<&Arc<ExchangeStatusPoller>> {
+        self.inner.exchange_status.as_ref()
+    }
+
+    pub fn iceberg(&self) -> &Arc<IcebergDetector> {
+        &self.inner.iceberg
+    }
+
+    pub fn analyzer(&self) -> &Arc<AnalyzerManager> {
+        &self.inner.analyzer
+    }
+
+    pub fn liquidation(&self) -> &Arc<LiquidationAnomalyDetector> {
+        &self.inner.liquidation
+    }
+
+    pub fn funding(&self) -> &Arc<FundingRateDetector> {
+        &self.inner.funding
+    }
+
+    pub fn open_interest(&self) -> &Arc<OpenInterestDetector> {
+        &self.inner.open_interest
+    }
+
+    pub fn correlation_break(&self) -> &Arc<CorrelationBreakDetector> {
+        &self.inner.correlation_break
+    }
+
+    pub fn escalation(&self) -> &Arc<SeverityEscalator> {
+        &self.inner.escalation
+    }
+
+    pub fn correlator(&self) -> &Arc<AnomalyCorrelator> {
+        &self.inner.correlator
+    }
+
+    pub fn book_metrics(&self) -> &Arc<BookMetricsRecorder> {
+        &self.inner.book_metrics
+    }
+
+    pub fn ingest_gate(&self) -> &Arc<IngestGate> {
+        &self.inner.ingest
+    }
+
+    pub fn trader(&self) -> Option<&Arc<AutoTrader>> {
+        self.inner.trader.as_ref()
+    }
+
+    pub fn notifier(&self) -> Option<&Arc<NotificationManager>> {
+        self.inner.notifier.as_ref()
+    }
+
+    pub fn leadership(&self) -> Option<&Arc<LeaderElection>> {
+        self.inner.leadership.as_ref()
+    }
+
+    // Always true without coordination
+    pub fn is_leader(&self) -> bool {
+        self.inner.leadership.as_ref().is_none_or(|l| l.is_leader())
+    }
+
+    pub fn subscribe_detections(&self) -> broadcast::Receiver<AnomalyDetection> {
+        self.inner.detections.subscribe()
+    }
+
+    pub fn subscribe_events(&self) -> broadcast::Receiver<MonitorEvent> {
+        self.inner.events.subscribe()
+    }
+
+    // Runs a single event through detection, notification and trading
+    pub async fn process(&self, event: &MonitorEvent) -> Result<Vec<AnomalyDetection>> {
+        let _ = self.inner.events.send(event.clone());
+
+        let now = chrono::Utc::now();
+        if let EventSource::Exchange(exchange) = &event.source {
+            self.inner.activity.record_feed_message(exchange, now);
+        }
+
+        if let EventType::MarketData(MarketDataType::OrderBook) = &event.event_type {
+            // Full depth snapshots go to the book detectors, everything else is top of book
+            if let Ok(book) = serde_json::from_value::<OrderBook>(event.data.clone()) {
+                let mut anomalies = self.inner.anomaly_manager.process_order_book(&book);
+                self.dispatch(&mut anomalies).await;
+                return Ok(anomalies);
+            }
+            let mut anomalies = self.update_l1(event);
+            self.dispatch(&mut anomalies).await;
+            return Ok(anomalies);
+        }
+
+        if let EventType::MarketData(MarketDataType::Liquidation) = &event.event_type {
+            let liquidation = match serde_json::from_value::<Liquidation>(event.data.clone()) {
+                Ok(liquidation) => liquidation,
+                Err(e) => {
+                    warn!("Ignoring malformed liquidation event {}: {}", event.id, e);
+                    return Ok(Vec::new());
+                }
+            };
+            let mut anomalies: Vec<_> =
+                self.inner.liquidation.record(&liquidation).into_iter().collect();
+            self.dispatch(&mut anomalies).await;
+            return Ok(anomalies);
+        }
+
+        if let EventType::MarketData(MarketDataType::FundingRate) = &event.event_type {
+            let update = match serde_json::from_value::<FundingRateUpdate>(event.data.clone()) {
+                Ok(update) => update,
+                Err(e) => {
+                    warn!("Ignoring malformed funding event {}: {}", event.id, e);
+                    return Ok(Vec::new());
+                }
+            };
+            let mut anomalies: Vec<_> = self.inner.funding.record(&update).into_iter().collect();
+            self.dispatch(&mut anomalies).await;
+            return Ok(anomalies);
+        }
+
+        if let EventType::MarketData(MarketDataType::OpenInterest) = &event.event_type {
+            let update = match serde_json::from_value::<OpenInterestUpdate>(event.data.clone()) {
+                Ok(update) => update,
+                Err(e) => {
+                    warn!("Ignoring malformed open interest event {}: {}", event.id, e);
+                    return Ok(Vec::new());
+                }
+            };
+            let mut anomalies: Vec<_> =
+                self.inner.open_interest.record(&update).into_iter().collect();
+            self.dispatch(&mut anomalies).await;
+            return Ok(anomalies);
+        }
+
+        let EventType::MarketData(MarketDataType::Trade) = &event.event_type else {
+            return Ok(Vec::new());
+        };
+
+        let trade = match serde_json::from_value::<MarketTradeData>(event.data.clone()) {
+            Ok(trade) => trade,
+            Err(e) => {
+                warn!("Ignoring malformed trade event {}: {}", event.id, e);
+                return Ok(Vec::new());
+            }
+        };
+
+        Ok(self
+            .handle_trade(
+                &trade.exchange,
+                &trade.symbol,
+                trade.price,
+                trade.volume,
+                trade.side,
+                event.timestamp,
+                now,
+            )
+            .await)
+    }
+
+    // Same as `process` for a record still in its serialized form. Trades are detected from
+    // the borrowed fields, the owned event is only built for subscribers and other event types
+    pub async fn process_ref(&self, event: &MonitorEventRef<'_>) -> Result<Vec<AnomalyDetection>> {
+        let EventType::MarketData(MarketDataType::Trade) = &event.event_type else {
+            return self.process(&event.to_owned()?).await;
+        };
+        if self.inner.events.receiver_count() > 0 {
+            let _ = self.inner.events.send(event.to_owned()?);
+        }
+
+        let now = chrono::Utc::now();
+        if let Some(exchange) = event.exchange() {
+            self.inner.activity.record_feed_message(exchange, now);
+        }
+
+        let trade = match event.trade() {
+            Ok(trade) => trade,
+            Err(e) => {
+                warn!("Ignoring malformed trade event {}: {}", event.id, e);
+                return Ok(Vec::new());
+            }
+        };
+
+        Ok(self
+            .handle_trade(
+                &trade.exchange,
+                &trade.symbol,
+                trade.price,
+                trade.volume,
+                trade.side,
+                event.timestamp,
+                now,
+            )
+            .await)
+    }
+
+    async fn handle_trade(
+        &self,
+        exchange: &str,
+        symbol: &str,
+        price: f64,
+        volume: f64,
+        side: Option<TradeSide>,
+        timestamp: chrono::DateTime<chrono::Utc>,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Vec<AnomalyDetection> {
+        self.inner.activity.record_trade(exchange, symbol, timestamp, now);
+        self.inner.open_interest.observe_price(exchange, symbol, price);
+        // Interned, only the pair's first trade allocates it
+        let key = InstrumentKey::new(exchange, symbol);
+
+        if let Some(trader) = &self.inner.trader {
+            trader.market_snapshots().record_trade(&key, price * volume, timestamp);
+        }
+
+        let sample = TradeSample {
+            timestamp,
+            price,
+            volume,
+        };
+
+        self.warm_up_pair(&key).await;
+        let mut anomalies = self.inner.anomaly_manager.process_trade_keyed(&key, &sample);
+        anomalies.extend(self.inner.iceberg.record_trade(exchange, symbol, price, volume, timestamp));
+        anomalies.extend(self.inner.analyzer.process(symbol, exchange, &sample, side));
+        anomalies.extend(
+            self.inner
+                .correlation_break
+                .record_trade(exchange, symbol, price, timestamp),
+        );
+        self.dispatch(&mut anomalies).await;
+
+        // Update positions with current price
+        if let Some(trader) = self.inner.trader.as_ref().filter(|_| self.is_leader()) {
+            if let Err(e) = trader.update_positions_keyed(&key, price).await {
+                error!("Failed to update positions: {}", e);
+            }
+        }
+
+        anomalies
+    }
+
+    // A pair's first trade warms its detectors from the stored ticks of the lookback window,
+    // unless its windows were restored from a snapshot. On an empty or failed load the pair
+    // starts cold, its first sample creates the detectors and it isn't retried
+    async fn warm_up_pair(&self, key: &InstrumentKey) {
+        let Some(storage) = &self.inner.storage else {
+            return;
+        };
+        if self.inner.anomaly_manager.has_detectors(key) {
+            return;
+        }
+
+        let lookback = chrono::Duration::minutes(
+            self.inner.config.monitoring.anomaly_detection.lookback_window_minutes as i64,
+        );
+        let since = chrono::Utc::now() - lookback;
+        let history: Vec<TradeSample> = match TickRepository::new(storage.clone())
+            .fetch_recent_ticks(key.exchange(), key.symbol(), since)
+            .await
+        {
+            Ok(ticks) => ticks
+                .iter()
+                .map(|t| TradeSample {
+                    timestamp: t.timestamp,
+                    price: t.price,
+                    volume: t.volume,
+                })
+                .collect(),
+            Err(e) => {
+                warn!("Failed to load recent ticks for {}: {}", key, e);
+                return;
+            }
+        };
+
+        let warmed = self
+            .inner
+            .anomaly_manager
+            .warm_up(key.symbol(), key.exchange(), &history);
+        if warmed > 0 {
+            info!("Warmed up {} detectors from {} stored ticks", key, warmed);
+        }
+    }
+
+    // Records on the anomalies topic from other producers. Detections without a `source` are
+    // our own and skipped, invalid or rejected records are logged and dropped
+    pub async fn ingest_event(&self, event: &MonitorEvent) -> Option<IngestReceipt> {
+        let EventType::Anomaly(_) = &event.event_type else {
+            return None;
+        };
+        let source = event.data.get("source")?;
+
+        // Producers on the topic authenticate like API callers, with one of their source's keys
+        // carried as `api_key`, which is dropped before the record is parsed or stored
+        let mut data = event.data.clone();
+        let api_key = data.as_object_mut().and_then(|fields| fields.remove(BUS_API_KEY_FIELD));
+        let api_key = api_key.as_ref().and_then(|key| key.as_str());
+        if let Err(rejection) = self.gate().authorize(api_key, source.as_str().unwrap_or_default()) {
+            warn!("Dropping external detection {}: {}", event.id, rejection);
+            return None;
+        }
+
+        let anomaly = match ExternalAnomaly::parse(&data, chrono::Utc::now()) {
+            Ok(anomaly) => anomaly,
+            Err(errors) => {
+                let errors: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+                warn!("Dropping invalid external detection {}: {}", event.id, errors.join("; "));
+                return None;
+            }
+        };
+        match self.ingest(anomaly).await {
+            Ok(receipt) => Some(receipt),
+            Err(rejection) => {
+                warn!("Dropping external detection {}: {}", event.id, rejection);
+                None
+            }
+        }
+    }
+
+    async fn dispatch(&self, anomalies: &mut Vec<AnomalyDetection>) {
+        // Repeats on a symbol raise severity before anything is notified, traded or stored, and
+        // before suppression, so a repeat escalated past the last one sent still goes out.
+        // External detections are deduplicated by id and limited per source at ingest instead
+        for anomaly in anomalies.iter_mut() {
+            self.inner.escalation.escalate(anomaly);
+        }
+        anomalies.retain(|anomaly| {
+            anomaly.source.is_some() || self.inner.suppressor.admit(anomaly)
+        });
+
+        let leading = self.is_leader();
+        for anomaly in anomalies.iter() {
+            info!("Anomaly detected: {:?}", anomaly);
+
+            // Followers store theirs as shadows, for comparison with the leader's
+            if let Some(storage) = &self.inner.storage {
+                let instance_id = self.inner.leadership.as_ref().map(|l| l.instance_id());
+                let record = anomaly_record(anomaly, instance_id, !leading);
+                if let Err(e) = AnomalyRepository::new(storage.clone()).insert(&record).await {
+                    error!("Failed to persist detection {}: {}", anomaly.id, e);
+                }
+            }
+
+            // The first on the pair is acted on at once, others within its correlation window
+            // together with it once the window closes
+            if leading {
+                for correlated in self.inner.correlator.push(anomaly.clone(), chrono::Utc::now()) {
+                    self.act_on(correlated).await;
+                }
+            }
+
+            let _ = self.inner.detections.send(anomaly.clone());
+        }
+    }
+
+    async fn act_on(&self, correlated: Correlated) {
+        let (anomaly, group) = match correlated {
+            Correlated::Single(anomaly) => (anomaly, None),
+            Correlated::Group(group) => (group.primary(), Some(group)),
+        };
+
+        // Detections during warm-up are not alerted on
+        if let Some(notifier) = self.inner.notifier.as_ref().filter(|_| !anomaly.warmup) {
+            let notification = match &group {
+                Some(group) => Notification::from_group(group),
+                None => Notification::from_anomaly(&anomaly),
+            };
+            if let Err(e) = notifier.send_all(&notification).await {
+                error!("Failed to send notification: {}", e);
+            }
+        }
+
+        // A group's leader was traded on when it arrived, so the group only is when a later
+        // member outranks it
+        let traded = group.as_ref().is_some_and(|group| group.leader().id == anomaly.id);
+        if let Some(trader) = self.inner.trader.as_ref().filter(|_| !traded) {
+            if let Err(e) = trader.process_anomaly(&anomaly).await {
+                error!("Failed to process anomaly for trading: {}", e);
+            }
+        }
+    }
+
+    // Releases correlation windows as they close, and whatever is still held on shutdown
+    fn spawn_correlation_flush(&self, mut shutdown: watch::Receiver<bool>) -> JoinHandle<()> {
+        let monitor = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_millis(250));
+            loop {
+                let released = tokio::select! {
+                    _ = shutdown.changed() => break,
+                    _ = interval.tick() => monitor.inner.correlator.flush(chrono::Utc::now()),
+                };
+                for correlated in released {
+                    monitor.act_on(correlated).await;
+                }
+            }
+            for correlated in monitor.inner.correlator.drain() {
+                monitor.act_on(correlated).await;
+            }
+        })
+    }
+
+    // Closes candles of pairs that stopped trading, which no trade would otherwise close
+    fn spawn_candle_flush(&self, mut shutdown: watch::Receiver<bool>) -> JoinHandle<()> {
+        let monitor = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(1));
+            loop {
+                tokio::select! {
+                    _ = shutdown.changed() => break,
+                    _ = interval.tick() => {}
+                }
+                let mut detections =
+                    monitor.inner.anomaly_manager.flush_candles(chrono::Utc::now());
+                if !detections.is_empty() {
+                    monitor.dispatch(&mut detections).await;
+                }
+            }
+        })
+    }
+
+    // Top-of-book updates feed spread and iceberg detection, the book metric series and the
+    // trader's liquidity checks
+    fn update_l1(&self, event: &MonitorEvent) -> Vec<AnomalyDetection> {
+        let book = match serde_json::from_value::<MarketL1Data>(event.data.clone()) {
+            Ok(book) => book,
+            Err(e) => {
+                warn!("Ignoring malformed order book event {}: {}", event.id, e);
+                return Vec::new();
+            }
+        };
+
+        self.inner.iceberg.update_book(
+            &book.exchange,
+            &book.symbol,
+            TopOfBook {
+                bid_price: book.bid_price,
+                bid_size: book.bid_size,
+                ask_price: book.ask_price,
+                ask_size: book.ask_size,
+            },
+        );
+
+        if self.inner.config.monitoring.book_metrics.enabled {
+            if let Some(metrics) = BookMetrics::from_l1(
+                book.bid_price,
+                book.bid_size,
+                book.ask_price,
+                book.ask_size,
+                event.timestamp,
+            ) {
+                self.inner.book_metrics.observe(&book.exchange, &book.symbol, metrics);
+            }
+        }
+
+        let key = InstrumentKey::new(&book.exchange, &book.symbol);
+        if let Some(trader) = &self.inner.trader {
+            trader.market_snapshots().update_l1(
+                &key,
+                L1Snapshot {
+                    bid_price: book.bid_price,
+                    bid_size: book.bid_size,
+                    ask_price: book.ask_price,
+                    ask_size: book.ask_size,
+                    timestamp: event.timestamp,
+                },
+            );
+        }
+
+        self.inner.anomaly_manager.process_quote_keyed(
+            &key,
+            &QuoteSample {
+                timestamp: event.timestamp,
+                bid_price: book.bid_price,
+                bid_size: book.bid_size,
+                ask_price: book.ask_price,
+                ask_size: book.ask_size,
+            },
+        )
+    }
+
+    // Starts consuming the market trade, order book, liquidation, funding and open interest
+    // topics, and the anomalies topic when external ingest is enabled, from the configured bus (or
+    // the in-process stream when only messaging is set), along with the warm-up and
+    // stale-position background tasks
+    pub async fn start(&self) -> Result<MonitorHandle> {
+        let fluvio = &self.inner.config.fluvio;
+        let prefix = &fluvio.topic_prefix;
+        // In-process offsets restart at zero with the process, only the bus's are worth keeping
+        let checkpoints: Arc<dyn CheckpointStore> = match &fluvio.checkpoint_path {
+            Some(path) if self.inner.bus.is_some() => Arc::new(FileCheckpointStore::open(path)?),
+            _ => Arc::new(InMemoryCheckpointStore::new()),
+        };
+        let mut router =
+            TopicRouter::new(checkpoints).with_overrides(fluvio.topic_semantics.clone());
+        let mut topics = vec![
+            format!("{}.market.trades", prefix),
+            format!("{}.market.orderbook", prefix),
+            format!("{}.market.liquidations", prefix),
+            format!("{}.market.funding", prefix),
+            format!("{}.market.open_interest", prefix),
+        ];
+        for topic in &topics {
+            router.route(topic.clone(), Arc::new(MonitorTopicHandler { monitor: self.clone() }));
+        }
+
+        // Other systems' detections, never run through our own detectors
+        if self.inner.ingest.is_enabled() {
+            let anomalies = format!("{}.anomalies", prefix);
+            router.route(
+                anomalies.clone(),
+                Arc::new(ExternalAnomalyTopicHandler { monitor: self.clone() }),
+            );
+            topics.push(anomalies);
+        }
+        let router = Arc::new(router);
+
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let mut tasks = Vec::new();
+
+        match (&self.inner.bus, &self.inner.messaging) {
+            (Some(bus), _) => {
+                for topic in topics {
+                    tasks.push(
+                        spawn_bus_consumer(bus, topic, router.clone(), shutdown_rx.clone()).await?,
+                    );
+                }
+            }
+            (None, Some(messaging)) => {
+                for topic in topics {
+                    tasks.push(spawn_local_consumer(
+                        messaging.subscribe_local(),
+                        topic,
+                        router.clone(),
+                        shutdown_rx.clone(),
+                    ));
+                }
+            }
+            (None, None) => {
+                return Err(MonitorError::Configuration(
+                    "Monitor has no bus configured".to_string(),
+                ))
+            }
+        }
+
+        if let Some(leadership) = &self.inner.leadership {
+            tasks.push(leadership.spawn(shutdown_rx.clone()));
+            tasks.push(self.spawn_leadership_events(leadership, shutdown_rx.clone()));
+        }
+        tasks.push(spawn_checkpoint_flush(router.clone(), shutdown_rx.clone()));
+        tasks.push(self.spawn_warmup_watch(shutdown_rx.clone()));
+        if self.inner.correlator.is_enabled() {
+            tasks.push(self.spawn_correlation_flush(shutdown_rx.clone()));
+        }
+        if self.inner.anomaly_manager.has_candles() {
+            tasks.push(self.spawn_candle_flush(shutdown_rx.clone()));
+        }
+        tasks.push(self.spawn_activity_check(shutdown_rx.clone()));
+        if let Some(poller) = &self.inner.exchange_status {
+            tasks.push(self.spawn_exchange_status(poller, shutdown_rx.clone()));
+            tasks.push(poller.spawn(shutdown_rx.clone()));
+        }
+        if let Some(trader) = self.inner.trader.clone() {
+            tasks.push(spawn_pending_order_check(trader.clone(), shutdown_rx.clone()));
+            tasks.push(trader.start(shutdown_rx.clone()));
+            tasks.push(spawn_stale_position_check(trader, shutdown_rx));
+        }
+
+        Ok(MonitorHandle {
+            router,
+            shutdown: shutdown_tx,
+            tasks,
+        })
+    }
+
+    fn spawn_warmup_watch(&self, mut shutdown: watch::Receiver<bool>) -> JoinHandle<()> {
+        let monitor = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(1));
+            while !monitor.inner.warmup.is_complete() {
+                tokio::select! {
+                    _ = shutdown.changed() => break,
+                    _ = interval.tick() => {}
+                }
+                if let Some(event) = monitor.inner.warmup.poll_completion(chrono::Utc::now()) {
+                    let _ = monitor.inner.events.send(event);
+                }
+            }
+        })
+    }
+
+    // Publishes a system event whenever this instance gains or loses leadership
+    fn spawn_leadership_events(
+        &self,
+        leadership: &LeaderElection,
+        mut shutdown: watch::Receiver<bool>,
+    ) -> JoinHandle<()> {
+        let monitor = self.clone();
+        let mut status = leadership.subscribe();
+        tokio::spawn(async move {
+            let mut was_leader = status.borrow().is_leader();
+            loop {
+                tokio::select! {
+                    _ = shutdown.changed() => break,
+                    changed = status.changed() => if changed.is_err() { break },
+                }
+                let current = status.borrow_and_update().clone();
+                if current.is_leader() != was_leader {
+                    was_leader = current.is_leader();
+                    if let Some(event) = current.to_event() {
+                        let _ = monitor.inner.events.send(event);
+                    }
+                }
+            }
+        })
+    }
+
+    fn spawn_activity_check(&self, mut shutdown: watch::Receiver<bool>) -> JoinHandle<()> {
+        let monitor = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(1));
+            loop {
+                tokio::select! {
+                    _ = shutdown.changed() => break,
+                    _ = interval.tick() => {}
+                }
+                for alert in monitor.inner.activity.evaluate(chrono::Utc::now()) {
+                    let Some(event) = alert.to_event() else { continue };
+                    if let Some(notifier) = monitor.inner.notifier.as_ref().filter(|_| monitor.is_leader()) {
+                        if let Some(notification) = Notification::from_event(&event) {
+                            if let Err(e) = notifier.send_all(&notification).await {
+                                error!("Failed to send activity alert: {}", e);
+                            }
+                        }
+                    }
+                    let _ = monitor.inner.events.send(event);
+                }
+            }
+        })
+    }
+
+    // Applies every polled exchange status to alert suppression and, when configured, to the
+    // trader, and notifies when an exchange reports an incident or recovers from one
+    fn spawn_exchange_status(
+        &self,
+        poller: &ExchangeStatusPoller,
+        mut shutdown: watch::Receiver<bool>,
+    ) -> JoinHandle<()> {
+        let monitor = self.clone();
+        let mut updates = poller.subscribe();
+        let valid_for = poller.valid_for();
+        let pause_trading = poller.config().pause_trading;
+        tokio::spawn(async move {
+            let mut reported: HashMap<String, ExchangeHealth> = HashMap::new();
+            loop {
+                let status = tokio::select! {
+                    _ = shutdown.changed() => break,
+                    update = updates.recv() => match update {
+                        Ok(status) => status,
+                        Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    },
+                };
+
+                monitor.inner.activity.set_exchange_status(&status, valid_for);
+                if let Some(trader) = monitor.inner.trader.as_ref().filter(|_| pause_trading) {
+                    if status.is_incident() {
+                        let reason = status.message.as_deref().unwrap_or(&status.vendor_state);
+                        trader.pause_venue(&status.exchange, status.checked_at + valid_for, reason);
+                    } else {
+                        trader.resume_venue(&status.exchange);
+                    }
+                }
+
+                // A healthy first report is not news
+                let previous = reported.insert(status.exchange.clone(), status.health);
+                if previous.unwrap_or(ExchangeHealth::Healthy) == status.health {
+                    continue;
+                }
+                let Some(event) = status.to_event() else { continue };
+                if let Some(notifier) = monitor.inner.notifier.as_ref().filter(|_| monitor.is_leader()) {
+                    if let Some(notification) = Notification::from_event(&event) {
+                        if let Err(e) = notifier.send_all(&notification).await {
+                            error!("Failed to send exchange status notification: {}", e);
+                        }
+                    }
+                }
+                let _ = monitor.inner.events.send(event);
+            }
+        })
+    }
+}
+
+async fn spawn_bus_consumer(
+    bus: &Fluvio,
+    topic: String,
+    router: Arc<TopicRouter>,
+    mut shutdown: watch::Receiver<bool>,
+) -> Result<JoinHandle<()>> {
+    let consumer = bus.partition_consumer(&topic, 0).await?;
+    let offset = match router.resume_offset(&topic) {
+        Some(offset) => Offset::absolute(offset)
+            .map_err(|e| MonitorError::Stream(e.to_string()))?,
+        None => Offset::end(),
+    };
+    let mut stream = consumer.stream(offset).await?;
+
+    info!("Started processing events from topic: {} ({:?})", topic, router.semantics(&topic));
+
+    Ok(tokio::spawn(async move {
+        loop {
+            // Shutdown is only looked at between records, the one in hand is handled whole
+            let record = tokio::select! {
+                biased;
+                _ = shutdown.changed() => break,
+                record = stream.next() => match record {
+                    Some(Ok(record)) => record,
+                    Some(Err(e)) => {
+                        error!("Consumer error on {}: {}", topic, e);
+                        continue;
+                    }
+                    None => break,
+                },
+            };
+
+            // Parsed in place, most records never need an owned MonitorEvent. A failed record is
+            // counted in the topic's consumer status and the stream moves on
+            if let Err(e) = router.dispatch_raw(&topic, record.offset(), record.get_value()).await {
+                error!("Failed to process event at offset {}: {}", record.offset(), e);
+            }
+        }
+        info!("Consumer for {} stopped", topic);
+    }))
+}
+
+// The engine mirrors everything it publishes in-process, so this keeps detection running for
+// the life of the process even after the bus comes back, without replaying or duplicating
+fn spawn_local_consumer(
+    mut events: broadcast::Receiver<(String, MonitorEvent)>,
+    topic: String,
+    router: Arc<TopicRouter>,
+    mut shutdown: watch::Receiver<bool>,
+) -> JoinHandle<()> {
+    info!("Started processing events from in-process stream: {}", topic);
+
+    tokio::spawn(async move {
+        let mut offset = 0;
+        loop {
+            let (event_topic, event) = tokio::select! {
+                biased;
+                _ = shutdown.changed() => break,
+                received = events.recv() => match received {
+                    Ok(received) => received,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("In-process consumer for {} lagged, {} events skipped", topic, skipped);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+            };
+            if event_topic != topic {
+                continue;
+            }
+
+            if let Err(e) = router.dispatch(&topic, offset, &event).await {
+                error!("Failed to process event at offset {}: {}", offset, e);
+            }
+            offset += 1;
+        }
+        info!("In-process consumer for {} stopped", topic);
+    })
+}
+
+fn spawn_stale_position_check(
+    trader: Arc<AutoTrader>,
+    mut shutdown: watch::Receiver<bool>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(5));
+        loop {
+            tokio::select! {
+                _ = shutdown.changed() => break,
+                _ = interval.tick() => {}
+            }
+            if let Err(e) = trader.check_stale_positions().await {
+                error!("Failed to check stale positions: {}", e);
+            }
+        }
+    })
+}
+
+fn spawn_pending_order_check(
+    trader: Arc<AutoTrader>,
+    mut shutdown: watch::Receiver<bool>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(1));
+        loop {
+            tokio::select! {
+                _ = shutdown.changed() => break,
+                _ = interval.tick() => {}
+            }
+            if let Err(e) = trader.check_pending_orders().await {
+                error!("Failed to check pending orders: {}", e);
+            }
+        }
+    })
+}
+
+// Offsets are committed in memory per record and written out here, off the consumers' path
+fn spawn_checkpoint_flush(
+    router: Arc<TopicRouter>,
+    mut shutdown: watch::Receiver<bool>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(1));
+        loop {
+            tokio::select! {
+                _ = shutdown.changed() => break,
+                _ = interval.tick() => {}
+            }
+            let router = router.clone();
+            match tokio::task::spawn_blocking(move || router.flush_checkpoints()).await {
+                Ok(Err(e)) => error!("Failed to save consumer checkpoints: {}", e),
+                Err(e) => error!("Checkpoint flush failed: {}", e),
+                Ok(Ok(())) => {}
+            }
+        }
+    })
+}
+
+pub struct MonitorHandle {
+    router: Arc<TopicRouter>,
+    shutdown: watch::Sender<bool>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl MonitorHandle {
+    pub fn router(&self) -> &Arc<TopicRouter> {
+        &self.router
+    }
+
+    pub async fn stop(mut self) {
+        let _ = self.shutdown.send(true);
+        for task in std::mem::take(&mut self.tasks) {
+            if let Err(e) = task.await {
+                error!("Monitor task failed during shutdown: {}", e);
+            }
+        }
+        self.flush_checkpoints();
+        info!("Monitor stopped");
+    }
+
+    // The consumers are done committing by now, this saves what they got through
+    fn flush_checkpoints(&self) {
+        if let Err(e) = self.router.flush_checkpoints() {
+            error!("Failed to save consumer checkpoints: {}", e);
+        }
+    }
+
+    // Like `stop`, but tasks still running after `timeout` are aborted and awaited, so none
+    // keeps trading once this returns. Returns false if any had to be aborted
+    pub async fn stop_within(mut self, timeout: std::time::Duration) -> bool {
+        let _ = self.shutdown.send(true);
+        let deadline = tokio::time::Instant::now() + timeout;
+        let mut finished = true;
+        for mut task in std::mem::take(&mut self.tasks) {
+            match tokio::time::timeout_at(deadline, &mut task).await {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => error!("Monitor task failed during shutdown: {}", e),
+                Err(_) => {
+                    task.abort();
+                    let _ = task.await;
+                    finished = false;
+                }
+            }
+        }
+        self.flush_checkpoints();
+        if finished {
+            info!("Monitor stopped");
+        } else {
+            warn!("Monitor stopped, tasks still busy after {:?} were aborted", timeout);
+        }
+        finished
+    }
+}
+
+struct MonitorTopicHandler {
+    monitor: Monitor,
+}
+
+#[async_trait::async_trait]
+impl TopicHandler for MonitorTopicHandler {
+    async fn handle(&self, event: &MonitorEvent) -> Result<()> {
+        self.monitor.process(event).await.map(|_| ())
+    }
+
+    async fn handle_ref(&self, event: &MonitorEventRef<'_>) -> Result<()> {
+        self.monitor.process_ref(event).await.map(|_| ())
+    }
+}
+
+struct ExternalAnomalyTopicHandler {
+    monitor: Monitor,
+}
+
+#[async_trait::async_trait]
+impl TopicHandler for ExternalAnomalyTopicHandler {
+    async fn handle(&self, event: &MonitorEvent) -> Result<()> {
+        self.monitor.ingest_event(event).await;
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl ExternalIngest for Monitor {
+    fn gate(&self) -> &IngestGate {
+        &self.inner.ingest
+    }
+
+    // Same escalation, persistence, notification and trading as our own detections
+    async fn ingest(
+        &self,
+        anomaly: ExternalAnomaly,
+    ) -> std::result::Result<IngestReceipt, IngestRejection> {
+        let receipt = self.inner.ingest.admit(&anomaly, chrono::Utc::now())?;
+        if !receipt.duplicate {
+            info!("Ingesting external detection {} from {}", receipt.id, receipt.source);
+            self.dispatch(&mut vec![anomaly.into_detection()]).await;
+        }
+        Ok(receipt)
+    }
+}
+
+fn anomaly_record(
+    anomaly: &AnomalyDetection,
+    instance_id: Option<&str>,
+    shadow: bool,
+) -> AnomalyRecord {
+    AnomalyRecord {
+        id: anomaly.id,
+        exchange: anomaly.exchange.clone(),
+        symbol: anomaly.symbol.clone(),
+        anomaly_type: format!("{:?}", anomaly.anomaly_type),
+        severity: format!("{:?}", anomaly.severity),
+        confidence: Some(anomaly.confidence),
+        current_value: anomaly.metrics.current_value,
+        expected_value: anomaly.metrics.expected_value,
+        deviation: anomaly.metrics.deviation,
+        z_score: anomaly.metrics.z_score,
+        percentage_change: anomaly.metrics.percentage_change,
+        description: Some(anomaly.description.clone()),
+        reason: anomaly.reason.as_ref().and_then(|r| serde_json::to_value(r).ok()),
+        metadata: anomaly.details.as_ref().and_then(|d| serde_json::to_value(d).ok()),
+        detected_at: anomaly.timestamp,
+        instance_id: instance_id.map(str::to_string),
+        shadow,
+        source: anomaly.source.clone(),
+    }
+}
+
+#[derive(Deserialize)]
+struct MarketTradeData {
+    symbol: String,
+    exchange: String,
+    price: f64,
+    volume: f64,
+    #[serde(default)]
+    side: Option<TradeSide>,
+}
+
+#[derive(Deserialize)]
+struct MarketL1Data {
+    symbol: String,
+    exchange: String,
+    bid_price: f64,
+    bid_size: f64,
+    ask_price: f64,
+    ask_size: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use monitor_core::event::EventBuilder;
+    use monitor_testkit::fixtures::monitor_config;
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    const TOPIC: &str = "crypto-monitor.market.trades";
+
+    // Takes `delay` over every event, counting those it started and those it finished
+    struct SlowHandler {
+        delay: Duration,
+        started: AtomicUsize,
+        finished: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl TopicHandler for SlowHandler {
+        async fn handle(&self, _event: &MonitorEvent) -> Result<()> {
+            self.started.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(self.delay).await;
+            self.finished.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    fn trade() -> MonitorEvent {
+        EventBuilder::new()
+            .with_source(EventSource::Exchange("binance".to_string()))
+            .with_type(EventType::MarketData(MarketDataType::Trade))
+            .with_data(serde_json::json!({ "symbol": "BTC/USDT" }))
+            .build()
+            .unwrap()
+    }
+
+    // A local consumer fed three events, stopped while the first is being handled
+    async fn consume(delay: Duration, timeout: Duration) -> (Arc<SlowHandler>, bool) {
+        let handler = Arc::new(SlowHandler {
+            delay,
+            started: AtomicUsize::new(0),
+            finished: AtomicUsize::new(0),
+        });
+        let mut router = TopicRouter::new(Arc::new(InMemoryCheckpointStore::new()));
+        router.route(TOPIC, handler.clone());
+        let router = Arc::new(router);
+
+        let (events, receiver) = broadcast::channel(16);
+        let (shutdown, shutdown_rx) = watch::channel(false);
+        let task = spawn_local_consumer(receiver, TOPIC.to_string(), router.clone(), shutdown_rx);
+        for _ in 0..3 {
+            events.send((TOPIC.to_string(), trade())).unwrap();
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        let handle = MonitorHandle {
+            router,
+            shutdown,
+            tasks: vec![task],
+        };
+        let finished = handle.stop_within(timeout).await;
+        (handler, finished)
+    }
+
+    fn volume_spike(at: chrono::DateTime<chrono::Utc>) -> AnomalyDetection {
+        AnomalyDetection {
+            id: uuid::Uuid::new_v4(),
+            timestamp: at,
+            symbol: "BTC/USDT".to_string(),
+            exchange: "binance".to_string(),
+            anomaly_type: AnomalyType::VolumeSpike,
+            severity: AnomalySeverity::Medium,
+            confidence: 0.6,
+            metrics: AnomalyMetrics {
+                current_value: 100.0,
+                expected_value: 10.0,
+                deviation: 90.0,
+                z_score: Some(4.0),
+                percentage_change: Some(900.0),
+                historical_avg: Some(10.0),
+                historical_std: Some(20.0),
+                ..Default::default()
+            },
+            description: "Volume spike".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_repeats_are_escalated_before_they_are_suppressed() {
+        let config = monitor_config();
+        let monitor = Monitor::builder().config(config).build().unwrap();
+
+        // Five Medium spikes within the minute's cooldown. The fifth is escalated two tiers
+        // to Critical, which bypasses the cooldown the repeats before it were held by
+        let start = chrono::Utc::now();
+        let mut sent = Vec::new();
+        for i in 0..5 {
+            let mut anomalies = vec![volume_spike(start + chrono::Duration::seconds(i))];
+            monitor.dispatch(&mut anomalies).await;
+            sent.extend(anomalies);
+        }
+
+        let severities: Vec<AnomalySeverity> = sent.iter().map(|a| a.severity.clone()).collect();
+        assert_eq!(severities, [AnomalySeverity::Medium, AnomalySeverity::Critical]);
+        assert_eq!(sent[1].escalation.as_ref().map(|e| e.recent_count), Some(5));
+        assert_eq!(monitor.suppressor().stats().cooldown, 3);
+    }
+
+    #[tokio::test]
+    async fn test_consumer_finishes_the_event_in_hand_on_stop() {
+        let (handler, finished) = consume(Duration::from_millis(50), Duration::from_secs(2)).await;
+
+        // The event in flight was handled whole, the consumer stopped before the next
+        assert!(finished);
+        assert_eq!(handler.started.load(Ordering::SeqCst), 1);
+        assert_eq!(handler.finished.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_busy_consumer_is_aborted_after_the_timeout() {
+        let started = std::time::Instant::now();
+        let (handler, finished) =
+            consume(Duration::from_secs(60), Duration::from_millis(100)).await;
+
+        assert!(!finished);
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(handler.started.load(Ordering::SeqCst), 1);
+        assert_eq!(handler.finished.load(Ordering::SeqCst), 0);
+    }
+}
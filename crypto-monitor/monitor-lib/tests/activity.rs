@@ -0,0 +1,79 @@
+// Expected activity through a started monitor fed by the market data simulator: a feed that
+// stops altogether is reported as stale, a feed that keeps quoting while none of a pair's
+// trades reach the pipeline is reported as missing activity, even for a pair that never traded.
+
+use crypto_monitor::{
+    core::{AlertType, ExpectedActivityConfig},
+    EventType, Monitor, MonitorConfig, MonitorEvent,
+};
+use monitor_testkit::{
+    fixtures::monitor_config,
+    simulator::{local_messaging, FeedSimulator},
+};
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+fn config() -> MonitorConfig {
+    let mut config = monitor_config();
+    config.monitoring.warmup.duration_secs = 0;
+    config.monitoring.activity.feed_stale_secs = 1;
+    config.monitoring.activity.expected = vec![ExpectedActivityConfig {
+        exchange: "binance".to_string(),
+        symbol: "BTC/USDT".to_string(),
+        min_trades_per_hour: 10,
+        max_silent_secs: 1,
+    }];
+    config
+}
+
+// The `kind` of every activity alert raised within `secs`
+async fn alerts(events: &mut broadcast::Receiver<MonitorEvent>, secs: u64) -> Vec<String> {
+    let mut kinds = Vec::new();
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(secs);
+    while let Ok(received) = tokio::time::timeout_at(deadline, events.recv()).await {
+        let Ok(event) = received else { continue };
+        if let EventType::Alert(AlertType::Warning) = event.event_type {
+            if let Some(kind) = event.data["kind"].as_str() {
+                kinds.push(kind.to_string());
+            }
+        }
+    }
+    kinds
+}
+
+// Runs the monitor for three seconds with the simulator quoting BTC/USDT when `feed` is set,
+// with trades when `trades` is also set
+async fn run(feed: bool, trades: bool) -> Vec<String> {
+    let messaging = local_messaging().await;
+    let monitor = Monitor::builder()
+        .config(config())
+        .with_messaging(messaging.clone())
+        .build()
+        .unwrap();
+    let mut events = monitor.subscribe_events();
+    let handle = monitor.start().await.unwrap();
+
+    let simulator = FeedSimulator::new(messaging, "crypto-monitor", "binance");
+    let feed = feed.then(|| simulator.run("BTC/USDT", Duration::from_millis(100), trades));
+    let kinds = alerts(&mut events, 3).await;
+    if let Some(feed) = feed {
+        feed.abort();
+    }
+    handle.stop().await;
+    kinds
+}
+
+#[tokio::test]
+async fn test_feed_down_is_reported_as_stale() {
+    assert_eq!(run(false, false).await, ["feed_stale"]);
+}
+
+#[tokio::test]
+async fn test_feed_up_without_trades_is_reported_as_missing_activity() {
+    assert_eq!(run(true, false).await, ["no_activity"]);
+}
+
+#[tokio::test]
+async fn test_routed_trades_raise_nothing() {
+    assert!(run(true, true).await.is_empty());
+}
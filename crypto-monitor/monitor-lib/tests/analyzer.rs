@@ -0,0 +1,106 @@
+// Trades routed through `Monitor::process` reach the per-pair market analyzers when enabled.
+
+use crypto_monitor::{
+    core::event::EventBuilder, AnomalyDetection, AnomalyType, EventSource, EventType,
+    MarketDataType, Monitor, MonitorConfig, MonitorEvent,
+};
+use monitor_testkit::fixtures::monitor_config;
+
+fn config(analyzer: bool) -> MonitorConfig {
+    let mut config = monitor_config();
+    config.monitoring.analyzer.enabled = analyzer;
+    config
+}
+
+fn trade(exchange: &str, price: f64) -> MonitorEvent {
+    sided_trade(exchange, price, None)
+}
+
+fn sided_trade(exchange: &str, price: f64, side: Option<&str>) -> MonitorEvent {
+    let mut data = serde_json::json!({
+        "exchange": exchange,
+        "symbol": "PEPE/USDT",
+        "price": price,
+        "volume": 1000.0,
+    });
+    if let Some(side) = side {
+        data["side"] = serde_json::json!(side);
+    }
+    EventBuilder::new()
+        .with_source(EventSource::Exchange(exchange.to_string()))
+        .with_type(EventType::MarketData(MarketDataType::Trade))
+        .with_data(data)
+        .build()
+        .unwrap()
+}
+
+// Flat at 100, pumped 40% to 140 over eight trades, then dumped back five points a trade
+fn pump_and_dump() -> Vec<f64> {
+    let mut prices = vec![100.0; 5];
+    prices.extend((1..=8).map(|i| 100.0 + 5.0 * i as f64));
+    prices.extend((1..=8).map(|i| 140.0 - 5.0 * i as f64));
+    prices
+}
+
+async fn run(
+    monitor: &Monitor,
+    exchange: &str,
+    prices: &[f64],
+) -> Vec<(usize, AnomalyDetection)> {
+    let mut detections = Vec::new();
+    for (i, price) in prices.iter().enumerate() {
+        let found = monitor.process(&trade(exchange, *price)).await.unwrap();
+        detections.extend(found.into_iter().map(|d| (i, d)));
+    }
+    detections
+        .into_iter()
+        .filter(|(_, d)| d.description.starts_with("Pump and dump"))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_pump_and_dump_detected_end_to_end() {
+    let monitor = Monitor::builder().config(config(true)).build().unwrap();
+
+    let detections = run(&monitor, "binance", &pump_and_dump()).await;
+
+    // First judged once 20 trades are in, 35% off the peak by then
+    let (at, anomaly) = &detections[0];
+    assert_eq!(*at, 19);
+    assert_eq!(anomaly.anomaly_type, AnomalyType::UnusualActivity);
+    assert_eq!(anomaly.exchange, "binance");
+    assert_eq!(anomaly.symbol, "PEPE/USDT");
+    assert!(anomaly.description.contains("+40.0% pump, -25.0% dump"));
+
+    // Another exchange's analyzer starts from its own empty history
+    assert!(run(&monitor, "okx", &pump_and_dump()[..19]).await.is_empty());
+}
+
+// The aggressor side carried on the trade record reaches the wash trading check
+#[tokio::test]
+async fn test_wash_trading_detected_from_trade_sides() {
+    let monitor = Monitor::builder().config(config(true)).build().unwrap();
+
+    let mut wash = Vec::new();
+    for i in 0..6 {
+        let side = if i % 2 == 0 { "buy" } else { "sell" };
+        let found = monitor.process(&sided_trade("binance", 100.0, Some(side))).await.unwrap();
+        wash.extend(found.into_iter().filter(|d| d.metrics.repetitions.is_some()));
+    }
+    assert_eq!(wash.len(), 1);
+    assert_eq!(wash[0].metrics.repetitions, Some(6));
+
+    // Without sides the same trades say nothing about who traded with whom
+    for _ in 0..6 {
+        let found = monitor.process(&trade("okx", 100.0)).await.unwrap();
+        assert!(found.iter().all(|d| d.metrics.repetitions.is_none()));
+    }
+}
+
+#[tokio::test]
+async fn test_analyzer_off_by_default() {
+    let monitor = Monitor::builder().config(config(false)).build().unwrap();
+
+    assert!(run(&monitor, "binance", &pump_and_dump()).await.is_empty());
+    assert!(!monitor.analyzer().is_enabled());
+}
@@ -0,0 +1,94 @@
+// Correlation through a running monitor: the first detection on a pair is notified as it
+// arrives and the ones following it within the window as one group once it closes, timed by
+// arrival rather than by the detections' own timestamps.
+
+use chrono::{Duration as ChronoDuration, Utc};
+use crypto_monitor::{
+    core::{CorrelationConfig, IngestConfig, IngestSourceConfig},
+    ExternalAnomaly, ExternalIngest, Monitor, MonitorConfig, NotificationManager,
+};
+use monitor_testkit::{
+    fixtures::monitor_config, notifier::RecordingChannel, simulator::local_messaging,
+};
+use std::{sync::Arc, time::Duration};
+use uuid::Uuid;
+
+fn config() -> MonitorConfig {
+    let mut config = monitor_config();
+    config.monitoring.correlation = CorrelationConfig {
+        enabled: true,
+        window_secs: 1,
+    };
+    config.monitoring.ingest = IngestConfig {
+        enabled: true,
+        sources: vec![IngestSourceConfig {
+            name: "risk".to_string(),
+            api_keys: vec!["risk-key".to_string()],
+            max_per_minute: 100,
+        }],
+        dedup_capacity: 100,
+    };
+    config
+}
+
+// Timestamped an hour back, as from a feed running behind
+fn lagging(symbol: &str, severity: &str) -> (Uuid, ExternalAnomaly) {
+    let id = Uuid::new_v4();
+    let data = serde_json::json!({
+        "id": id,
+        "source": "risk",
+        "timestamp": Utc::now() - ChronoDuration::hours(1),
+        "symbol": symbol,
+        "exchange": "binance",
+        "anomaly_type": "PriceSpike",
+        "severity": severity,
+        "metrics": {
+            "current_value": 9.0,
+            "expected_value": 2.0,
+            "deviation": 7.0,
+            "z_score": 3.5,
+            "percentage_change": null,
+            "historical_avg": null,
+            "historical_std": null
+        },
+        "description": "Model flagged a price move"
+    });
+    (id, ExternalAnomaly::parse(&data, Utc::now()).unwrap())
+}
+
+#[tokio::test]
+async fn test_leader_is_notified_at_once_and_followers_as_a_group() {
+    let channel = RecordingChannel::default();
+    let mut notifier = NotificationManager::new();
+    notifier.add_channel(Box::new(channel.clone()));
+    let monitor = Monitor::builder()
+        .config(config())
+        .with_messaging(local_messaging().await)
+        .with_notifier(Arc::new(notifier))
+        .build()
+        .unwrap();
+    let handle = monitor.start().await.unwrap();
+
+    let (leader, anomaly) = lagging("BTC/USDT", "Medium");
+    monitor.ingest(anomaly).await.unwrap();
+    let (_, anomaly) = lagging("BTC/USDT", "High");
+    monitor.ingest(anomaly).await.unwrap();
+    let (alone, anomaly) = lagging("ETH/USDT", "Low");
+    monitor.ingest(anomaly).await.unwrap();
+
+    // Both leaders went out without waiting on the window, despite their stale timestamps
+    let notified: Vec<Uuid> = channel
+        .sent()
+        .iter()
+        .map(|n| serde_json::from_value(n.data.as_ref().unwrap()["id"].clone()).unwrap())
+        .collect();
+    assert_eq!(notified, [leader, alone]);
+
+    tokio::time::sleep(Duration::from_millis(1_500)).await;
+    handle.stop().await;
+
+    let titles: Vec<String> = channel.sent().iter().map(|n| n.title.clone()).collect();
+    assert_eq!(titles.len(), 3);
+    assert_eq!(titles[2], "2 correlated anomalies on binance/BTC/USDT");
+    assert_eq!(monitor.correlator().pending(), 0);
+}
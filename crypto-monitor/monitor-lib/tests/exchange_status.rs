@@ -0,0 +1,124 @@
+// Exchange status polled by a started monitor from a local stand-in for Binance's status
+// endpoint: a reported incident pauses trading on the venue and is notified, a failed poll
+// leaves the pause in place, and a normal report resumes trading and is notified as recovery.
+
+use axum::{extract::State, http::StatusCode, routing::get, Router};
+use crypto_monitor::{
+    trader::{
+        paper::PaperExecutionClient, risk::SimpleRiskManager, strategy::AnomalyBasedStrategy,
+    },
+    AutoTrader, ExchangeStatusConfig, Monitor, MonitorConfig, NotificationManager,
+};
+use monitor_testkit::{
+    fixtures::{monitor_config, trading_config},
+    notifier::RecordingChannel,
+    simulator::local_messaging,
+};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+// What the endpoint answers with, `None` for a 502
+type Response = Arc<Mutex<Option<&'static str>>>;
+
+const MAINTENANCE: &str = r#"{"status":1,"msg":"system maintenance"}"#;
+const NORMAL: &str = r#"{"status":0,"msg":"normal"}"#;
+
+async fn status(State(response): State<Response>) -> Result<&'static str, StatusCode> {
+    response.lock().unwrap().ok_or(StatusCode::BAD_GATEWAY)
+}
+
+// Serves Binance's system status path on a local port, returning its base URL
+async fn serve(response: Response) -> String {
+    let app = Router::new().route("/sapi/v1/system/status", get(status)).with_state(response);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}", addr)
+}
+
+fn config(base_url: String) -> MonitorConfig {
+    let mut config = monitor_config();
+    config.monitoring.activity.exchange_status = ExchangeStatusConfig {
+        enabled: true,
+        interval_secs: 1,
+        timeout_secs: 1,
+        exchanges: vec!["binance".to_string()],
+        pause_trading: true,
+        endpoints: HashMap::from([("binance".to_string(), base_url)]),
+    };
+    config
+}
+
+fn trader() -> Arc<AutoTrader> {
+    let config = trading_config(serde_json::json!({}));
+    let paper = Arc::new(PaperExecutionClient::new(Default::default(), "USDT", 10_000.0));
+    Arc::new(
+        AutoTrader::new(
+            config.clone(),
+            Box::new(AnomalyBasedStrategy::new(config.clone())),
+            Box::new(SimpleRiskManager::new(config)),
+            paper.clone(),
+            10_000.0,
+        )
+        .with_paper_execution(paper),
+    )
+}
+
+// Polls `condition` until it holds, for at most three poll intervals
+async fn eventually(condition: impl Fn() -> bool) -> bool {
+    for _ in 0..30 {
+        if condition() {
+            return true;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    false
+}
+
+#[tokio::test]
+async fn test_reported_incident_pauses_trading_until_the_exchange_recovers() {
+    let response: Response = Arc::new(Mutex::new(Some(MAINTENANCE)));
+    let base_url = serve(response.clone()).await;
+    let channel = RecordingChannel::default();
+    let mut notifier = NotificationManager::new();
+    notifier.add_channel(Box::new(channel.clone()));
+    let trader = trader();
+    let monitor = Monitor::builder()
+        .config(config(base_url))
+        .with_messaging(local_messaging().await)
+        .with_notifier(Arc::new(notifier))
+        .with_trader(trader.clone())
+        .build()
+        .unwrap();
+    let handle = monitor.start().await.unwrap();
+
+    let paused = || trader.venue_pause("binance", chrono::Utc::now());
+    assert!(eventually(|| paused().is_some()).await);
+    assert_eq!(paused().as_deref(), Some("system maintenance"));
+
+    // The endpoint failing is not the exchange coming back
+    *response.lock().unwrap() = None;
+    tokio::time::sleep(Duration::from_millis(1_500)).await;
+    assert!(paused().is_some());
+
+    *response.lock().unwrap() = Some(NORMAL);
+    assert!(eventually(|| paused().is_none()).await);
+    handle.stop().await;
+
+    let reported: Vec<String> = channel
+        .sent()
+        .iter()
+        .filter(|n| n.data.as_ref().is_some_and(|data| data.get("health").is_some()))
+        .map(|n| n.message.clone())
+        .collect();
+    assert_eq!(
+        reported,
+        [
+            "binance reports no service: system maintenance",
+            "binance reports normal service",
+        ]
+    );
+}
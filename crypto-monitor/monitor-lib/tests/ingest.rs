@@ -0,0 +1,187 @@
+// External detections published on the anomalies topic by other producers, consumed through
+// the in-process stream the same way the bus consumer routes them.
+
+use chrono::Utc;
+use crypto_monitor::{
+    core::{
+        event::EventBuilder,
+        messaging::{BusConnector, EventBus, MessagingConfig},
+        IngestConfig, IngestSourceConfig,
+    },
+    AnomalyDetection, AnomalyType, DetectionDetails, EventSource, EventType, ExternalAnomaly,
+    ExternalIngest, Messaging, Monitor, MonitorConfig, MonitorEvent, Result,
+};
+use monitor_testkit::fixtures::monitor_config;
+use std::{sync::Arc, time::Duration};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+const TOPIC: &str = "crypto-monitor.anomalies";
+
+struct NullBus;
+
+#[async_trait::async_trait]
+impl EventBus for NullBus {
+    async fn publish(&self, _topic: &str, _event: &MonitorEvent) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl BusConnector for NullBus {
+    async fn connect(&self) -> Result<Arc<dyn EventBus>> {
+        Ok(Arc::new(NullBus))
+    }
+}
+
+fn config() -> MonitorConfig {
+    let mut config = monitor_config();
+    config.monitoring.ingest = IngestConfig {
+        enabled: true,
+        sources: vec![
+            IngestSourceConfig {
+                name: "ml-research".to_string(),
+                api_keys: vec!["research-key".to_string()],
+                max_per_minute: 2,
+            },
+            IngestSourceConfig {
+                name: "risk".to_string(),
+                api_keys: vec!["risk-key".to_string()],
+                max_per_minute: 10,
+            },
+        ],
+        dedup_capacity: 100,
+    };
+    config
+}
+
+fn record(data: serde_json::Value) -> MonitorEvent {
+    EventBuilder::new()
+        .with_source(EventSource::Anomaly)
+        .with_type(EventType::Anomaly(AnomalyType::VolumeSpike))
+        .with_data(data)
+        .build()
+        .unwrap()
+}
+
+fn external(id: Uuid, source: &str) -> MonitorEvent {
+    let api_key = match source {
+        "ml-research" => "research-key",
+        _ => "risk-key",
+    };
+    signed(id, source, Some(api_key))
+}
+
+fn signed(id: Uuid, source: &str, api_key: Option<&str>) -> MonitorEvent {
+    let mut event = unsigned(id, source);
+    if let Some(api_key) = api_key {
+        event.data["api_key"] = serde_json::json!(api_key);
+    }
+    event
+}
+
+fn unsigned(id: Uuid, source: &str) -> MonitorEvent {
+    record(serde_json::json!({
+        "id": id,
+        "source": source,
+        "timestamp": Utc::now(),
+        "symbol": "SOL/USDT",
+        "exchange": "binance",
+        "anomaly_type": "VolumeSpike",
+        "severity": "Medium",
+        "metrics": {
+            "current_value": 9.0,
+            "expected_value": 2.0,
+            "deviation": 7.0,
+            "z_score": 3.5,
+            "percentage_change": null,
+            "historical_avg": null,
+            "historical_std": null
+        },
+        "description": "Model flagged unusual volume",
+        "context": { "model": "volume-v2" }
+    }))
+}
+
+async fn next(detections: &mut broadcast::Receiver<AnomalyDetection>) -> Option<AnomalyDetection> {
+    tokio::time::timeout(Duration::from_millis(500), detections.recv())
+        .await
+        .ok()
+        .map(|received| received.unwrap())
+}
+
+#[tokio::test]
+async fn test_bus_records_from_external_sources_are_dispatched() {
+    let messaging = Messaging::connect(MessagingConfig::default(), Arc::new(NullBus))
+        .await
+        .unwrap();
+    let monitor = Monitor::builder()
+        .config(config())
+        .with_messaging(messaging.clone())
+        .build()
+        .unwrap();
+    let mut detections = monitor.subscribe_detections();
+    let handle = monitor.start().await.unwrap();
+
+    // Our own detections on the topic carry no source and aren't taken back in
+    let own = record(serde_json::json!({ "id": Uuid::new_v4(), "symbol": "SOL/USDT" }));
+    let invalid = record(serde_json::json!({
+        "source": "risk",
+        "severity": "Huge",
+        "api_key": "risk-key"
+    }));
+    let unknown = external(Uuid::new_v4(), "vendor");
+    // A key only publishes as its own source
+    let (unkeyed, borrowed) = (Uuid::new_v4(), Uuid::new_v4());
+    let (first, second, limited, other) =
+        (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+    for event in [
+        own,
+        invalid,
+        unknown,
+        signed(unkeyed, "risk", None),
+        signed(borrowed, "risk", Some("research-key")),
+        signed(Uuid::new_v4(), "risk", Some("wrong-key")),
+        external(first, "ml-research"),
+        external(first, "ml-research"),
+        external(second, "ml-research"),
+        external(limited, "ml-research"),
+        external(other, "risk"),
+    ] {
+        messaging.publish(TOPIC, &event).await;
+    }
+
+    let mut dispatched = Vec::new();
+    while let Some(detection) = next(&mut detections).await {
+        dispatched.push(detection);
+    }
+    handle.stop().await;
+
+    // The redelivery of `first` is dropped without using quota, the third ml-research
+    // detection is over its two per minute while risk is unaffected
+    let ids: Vec<Uuid> = dispatched.iter().map(|d| d.id).collect();
+    assert_eq!(ids, [first, second, other]);
+    assert_eq!(dispatched[0].source.as_deref(), Some("ml-research"));
+    assert_eq!(dispatched[2].source.as_deref(), Some("risk"));
+    assert!(!dispatched[0].warmup);
+    assert!(!ids.contains(&limited));
+    assert!(!ids.contains(&unkeyed) && !ids.contains(&borrowed));
+}
+
+// The path the ingest API takes
+#[tokio::test]
+async fn test_direct_ingest_dispatches_once() {
+    let monitor = Monitor::builder().config(config()).build().unwrap();
+    let mut detections = monitor.subscribe_detections();
+
+    let id = Uuid::new_v4();
+    let anomaly = ExternalAnomaly::parse(&unsigned(id, "risk").data, Utc::now()).unwrap();
+    let receipt = monitor.ingest(anomaly.clone()).await.unwrap();
+    assert!(!receipt.duplicate);
+    assert!(monitor.ingest(anomaly).await.unwrap().duplicate);
+
+    let detection = next(&mut detections).await.unwrap();
+    assert_eq!((detection.id, detection.source.as_deref()), (id, Some("risk")));
+    assert!(matches!(detection.details, Some(DetectionDetails::External(_))));
+    assert!(next(&mut detections).await.is_none());
+}
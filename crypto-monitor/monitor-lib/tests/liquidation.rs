@@ -0,0 +1,56 @@
+// Liquidation events routed through `Monitor::process` into the liquidation detector.
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use crypto_monitor::{
+    core::event::EventBuilder, AnomalyType, DetectionDetails, EventSource, EventType,
+    MarketDataType, Monitor, MonitorEvent,
+};
+use monitor_testkit::fixtures::monitor_config;
+
+fn liquidation(side: &str, quantity: f64, at: DateTime<Utc>) -> MonitorEvent {
+    EventBuilder::new()
+        .with_source(EventSource::Exchange("binance".to_string()))
+        .with_type(EventType::MarketData(MarketDataType::Liquidation))
+        .with_data(serde_json::json!({
+            "exchange": "binance",
+            "symbol": "BTC/USDT",
+            "timestamp": at,
+            "side": side,
+            "price": 50_000.0,
+            "quantity": quantity,
+        }))
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_liquidation_burst_is_detected() {
+    let monitor = Monitor::builder().config(monitor_config()).build().unwrap();
+    let start = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+
+    // 40 BTC of shorts is 2m notional, under the default 5m threshold
+    let quiet = monitor.process(&liquidation("short", 40.0, start)).await.unwrap();
+    assert!(quiet.is_empty());
+
+    let mut detections = Vec::new();
+    for i in 1..4 {
+        let at = start + Duration::seconds(i * 5);
+        detections.extend(monitor.process(&liquidation("short", 40.0, at)).await.unwrap());
+    }
+
+    assert_eq!(detections.len(), 1);
+    assert_eq!(detections[0].anomaly_type, AnomalyType::LiquidationCascade);
+    let Some(DetectionDetails::Liquidation(details)) = &detections[0].details else {
+        panic!("missing liquidation details");
+    };
+    assert_eq!(details.liquidations, 3);
+}
+
+#[tokio::test]
+async fn test_malformed_liquidation_is_ignored() {
+    let monitor = Monitor::builder().config(monitor_config()).build().unwrap();
+    let mut event = liquidation("long", 1.0, Utc::now());
+    event.data["side"] = serde_json::json!("sideways");
+
+    assert!(monitor.process(&event).await.unwrap().is_empty());
+}
@@ -0,0 +1,71 @@
+// Open interest events routed through `Monitor::process`, with trades supplying the price.
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use crypto_monitor::{
+    core::event::EventBuilder, AnomalyType, DetectionDetails, EventSource, EventType,
+    MarketDataType, Monitor, MonitorEvent, OpenInterestPattern,
+};
+use monitor_testkit::fixtures::monitor_config;
+
+fn event(data_type: MarketDataType, data: serde_json::Value) -> MonitorEvent {
+    EventBuilder::new()
+        .with_source(EventSource::Exchange("binance".to_string()))
+        .with_type(EventType::MarketData(data_type))
+        .with_data(data)
+        .build()
+        .unwrap()
+}
+
+fn open_interest(open_interest: f64, at: DateTime<Utc>) -> MonitorEvent {
+    event(
+        MarketDataType::OpenInterest,
+        serde_json::json!({
+            "exchange": "binance",
+            "symbol": "BTC/USDT",
+            "timestamp": at,
+            "open_interest": open_interest,
+        }),
+    )
+}
+
+fn trade(price: f64) -> MonitorEvent {
+    event(
+        MarketDataType::Trade,
+        serde_json::json!({
+            "exchange": "binance",
+            "symbol": "BTC/USDT",
+            "price": price,
+            "volume": 0.1,
+        }),
+    )
+}
+
+#[tokio::test]
+async fn test_short_build_up_from_trades_and_open_interest() {
+    let monitor = Monitor::builder().config(monitor_config()).build().unwrap();
+    let start = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+
+    monitor.process(&trade(50_000.0)).await.unwrap();
+    assert!(monitor.process(&open_interest(20_000.0, start)).await.unwrap().is_empty());
+
+    // 8% more contracts five minutes later while the price dropped 3%
+    monitor.process(&trade(48_500.0)).await.unwrap();
+    let at = start + Duration::minutes(5);
+    let detections = monitor.process(&open_interest(21_600.0, at)).await.unwrap();
+
+    assert_eq!(detections.len(), 1);
+    assert_eq!(detections[0].anomaly_type, AnomalyType::OpenInterest);
+    let Some(DetectionDetails::OpenInterest(details)) = &detections[0].details else {
+        panic!("missing open interest details");
+    };
+    assert_eq!(details.pattern, OpenInterestPattern::ShortBuildUp);
+}
+
+#[tokio::test]
+async fn test_malformed_open_interest_is_ignored() {
+    let monitor = Monitor::builder().config(monitor_config()).build().unwrap();
+    let mut event = open_interest(1.0, Utc::now());
+    event.data["open_interest"] = serde_json::json!("lots");
+
+    assert!(monitor.process(&event).await.unwrap().is_empty());
+}
@@ -0,0 +1,269 @@
+// Order book records published on the orderbook topic, consumed through the in-process stream
+// by a started monitor the same way the bus consumer routes them.
+
+use chrono::{DateTime, Utc};
+use crypto_monitor::{
+    core::{
+        event::EventBuilder,
+        messaging::{BusConnector, EventBus, MessagingConfig},
+    },
+    trader::{
+        paper::PaperExecutionClient, risk::SimpleRiskManager, strategy::AnomalyBasedStrategy,
+    },
+    AnomalyDetection, AnomalySeverity, AnomalyType, AutoTrader, DetectionDetails, EventSource,
+    EventType, InstrumentKey, MarketDataType, Messaging, Monitor, MonitorBuilder, MonitorEvent,
+    MonitorHandle, Result, UpdateRateConfig,
+};
+use monitor_testkit::fixtures::{monitor_config, trading_config};
+use std::{sync::Arc, time::Duration};
+use tokio::sync::broadcast;
+
+const TOPIC: &str = "crypto-monitor.market.orderbook";
+const TRADES: &str = "crypto-monitor.market.trades";
+
+struct NullBus;
+
+#[async_trait::async_trait]
+impl EventBus for NullBus {
+    async fn publish(&self, _topic: &str, _event: &MonitorEvent) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl BusConnector for NullBus {
+    async fn connect(&self) -> Result<Arc<dyn EventBus>> {
+        Ok(Arc::new(NullBus))
+    }
+}
+
+fn trader() -> Arc<AutoTrader> {
+    let config = trading_config(serde_json::json!({ "auto_trading_enabled": false }));
+    let paper = Arc::new(PaperExecutionClient::new(Default::default(), "USDT", 10_000.0));
+    Arc::new(
+        AutoTrader::new(
+            config.clone(),
+            Box::new(AnomalyBasedStrategy::new(config.clone())),
+            Box::new(SimpleRiskManager::new(config)),
+            paper.clone(),
+            10_000.0,
+        )
+        .with_paper_execution(paper),
+    )
+}
+
+fn l1(at: DateTime<Utc>, bid_price: f64, ask_price: f64) -> MonitorEvent {
+    let mut event = EventBuilder::new()
+        .with_source(EventSource::Exchange("binance".to_string()))
+        .with_type(EventType::MarketData(MarketDataType::OrderBook))
+        .with_data(serde_json::json!({
+            "exchange": "binance",
+            "symbol": "BTC/USDT",
+            "bid_price": bid_price,
+            "bid_size": 2.0,
+            "ask_price": ask_price,
+            "ask_size": 3.0,
+        }))
+        .build()
+        .unwrap();
+    // Top of book carries no time of its own
+    event.timestamp = at;
+    event
+}
+
+// Full depth, `bid` and `ask` units resting at one level on each side
+fn book(at: DateTime<Utc>, bid: f64, ask: f64) -> MonitorEvent {
+    EventBuilder::new()
+        .with_source(EventSource::Exchange("binance".to_string()))
+        .with_type(EventType::MarketData(MarketDataType::OrderBook))
+        .with_data(serde_json::json!({
+            "exchange": "binance",
+            "symbol": "BTC/USDT",
+            "timestamp": at,
+            "bids": [{ "price": 50_000.0, "quantity": bid }],
+            "asks": [{ "price": 50_010.0, "quantity": ask }],
+        }))
+        .build()
+        .unwrap()
+}
+
+fn trade(at: DateTime<Utc>, price: f64, volume: f64) -> MonitorEvent {
+    let mut event = EventBuilder::new()
+        .with_source(EventSource::Exchange("binance".to_string()))
+        .with_type(EventType::MarketData(MarketDataType::Trade))
+        .with_data(serde_json::json!({
+            "exchange": "binance",
+            "symbol": "BTC/USDT",
+            "price": price,
+            "volume": volume,
+        }))
+        .build()
+        .unwrap();
+    event.timestamp = at;
+    event
+}
+
+async fn start(builder: MonitorBuilder) -> (Arc<Messaging>, Monitor, MonitorHandle) {
+    let messaging = Messaging::connect(MessagingConfig::default(), Arc::new(NullBus))
+        .await
+        .unwrap();
+    let monitor = builder.with_messaging(messaging.clone()).build().unwrap();
+    let handle = monitor.start().await.unwrap();
+    (messaging, monitor, handle)
+}
+
+// Polls until the consumer has handled what was published
+async fn eventually<T>(mut probe: impl FnMut() -> Option<T>) -> T {
+    for _ in 0..50 {
+        if let Some(value) = probe() {
+            return value;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("nothing consumed within 500ms");
+}
+
+// Every detection of `anomaly_type` until none arrives for 500ms
+async fn detected(
+    detections: &mut broadcast::Receiver<AnomalyDetection>,
+    anomaly_type: AnomalyType,
+) -> Vec<AnomalyDetection> {
+    let mut found = Vec::new();
+    loop {
+        let wait = tokio::time::timeout(Duration::from_millis(500), detections.recv());
+        let Ok(received) = wait.await else {
+            break;
+        };
+        let detection = received.unwrap();
+        if detection.anomaly_type == anomaly_type {
+            found.push(detection);
+        }
+    }
+    found
+}
+
+#[tokio::test]
+async fn test_top_of_book_reaches_the_trader_liquidity_cache() {
+    let trader = trader();
+    let (messaging, _monitor, handle) =
+        start(Monitor::builder().config(monitor_config()).with_trader(trader.clone())).await;
+
+    messaging.publish(TOPIC, &l1(Utc::now(), 99.9, 100.1)).await;
+    let key = InstrumentKey::new("binance", "BTC/USDT");
+    let snapshot = eventually(|| trader.market_snapshots().l1(&key)).await;
+    handle.stop().await;
+
+    assert_eq!((snapshot.bid_price, snapshot.bid_size), (99.9, 2.0));
+    assert_eq!((snapshot.ask_price, snapshot.ask_size), (100.1, 3.0));
+    assert!((snapshot.spread_bps().unwrap() - 20.0).abs() < 1e-6);
+}
+
+#[tokio::test]
+async fn test_depth_imbalance_is_detected_from_published_books() {
+    let (messaging, monitor, handle) = start(Monitor::builder().config(monitor_config())).await;
+    let mut detections = monitor.subscribe_detections();
+
+    let now = Utc::now();
+    messaging.publish(TOPIC, &book(now, 2.0, 2.0)).await;
+    messaging.publish(TOPIC, &book(now, 10.0, 1.0)).await;
+    let found = detected(&mut detections, AnomalyType::DepthImbalance).await;
+    handle.stop().await;
+
+    // Ten times more bid than ask is over three times the default ratio of 3
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].severity, AnomalySeverity::Critical);
+    assert_eq!(found[0].metrics.current_value, 10.0);
+}
+
+#[tokio::test]
+async fn test_spread_widening_is_detected_from_published_quotes() {
+    let (messaging, monitor, handle) = start(Monitor::builder().config(monitor_config())).await;
+    let mut detections = monitor.subscribe_detections();
+
+    // The default 60 quotes of history around 2 bps, then one twelve times as wide
+    let start = Utc::now();
+    for i in 0..60 {
+        let ask = if i % 2 == 0 { 50_009.0 } else { 50_011.0 };
+        let at = start + chrono::Duration::milliseconds(100 * i);
+        messaging.publish(TOPIC, &l1(at, 50_000.0, ask)).await;
+    }
+    let wide = l1(start + chrono::Duration::seconds(6), 50_000.0, 50_120.0);
+    messaging.publish(TOPIC, &wide).await;
+    let found = detected(&mut detections, AnomalyType::SpreadWidening).await;
+    handle.stop().await;
+
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].severity, AnomalySeverity::Critical);
+    assert!(found[0].metrics.z_score.is_some());
+    assert!((found[0].metrics.historical_avg.unwrap() - 2.0).abs() < 0.01);
+}
+
+#[tokio::test]
+async fn test_iceberg_is_detected_from_published_books_and_trades() {
+    let (messaging, monitor, handle) = start(Monitor::builder().config(monitor_config())).await;
+    let mut detections = monitor.subscribe_detections();
+
+    // Trades and books come in on separate consumers, the book has to be in first
+    let start = Utc::now();
+    messaging.publish(TOPIC, &l1(start, 99.99, 100.0)).await;
+    eventually(|| monitor.book_metrics().latest("binance", "BTC/USDT")).await;
+
+    // The ask keeps showing 3.0 at 100.0 while buyers lift it again and again
+    for i in 0..12 {
+        let at = start + chrono::Duration::seconds(i);
+        messaging.publish(TRADES, &trade(at, 100.0, 2.0)).await;
+    }
+    let found = detected(&mut detections, AnomalyType::LargeOrder).await;
+    handle.stop().await;
+
+    // Five fills of 2.0 are the first over three times the 3.0 on display
+    assert_eq!(found.len(), 1);
+    let Some(DetectionDetails::Iceberg(details)) = &found[0].details else {
+        panic!("missing iceberg details");
+    };
+    assert_eq!((details.level_price, details.fills), (100.0, 5));
+    assert_eq!(details.hidden_size_estimate, 7.0);
+}
+
+#[tokio::test]
+async fn test_published_quotes_feed_the_book_metric_series() {
+    let (messaging, monitor, handle) = start(Monitor::builder().config(monitor_config())).await;
+
+    let at = Utc::now();
+    messaging.publish(TOPIC, &l1(at, 99.9, 100.1)).await;
+    let latest = eventually(|| monitor.book_metrics().latest("binance", "BTC/USDT")).await;
+    let pending = monitor.book_metrics().take_pending();
+    handle.stop().await;
+
+    assert_eq!(latest.timestamp, at);
+    assert!((latest.spread_bps - 20.0).abs() < 1e-6);
+    // Two bid against three ask
+    assert!((latest.imbalance + 0.2).abs() < 1e-9);
+    assert_eq!(pending.len(), 1);
+}
+
+#[tokio::test]
+async fn test_update_rate_spike_is_detected_from_published_books() {
+    let mut config = monitor_config();
+    config.monitoring.anomaly_detection.update_rate = Some(UpdateRateConfig::default());
+    let (messaging, monitor, handle) = start(Monitor::builder().config(config)).await;
+    let mut detections = monitor.subscribe_detections();
+
+    // A minute and a half at 5 updates a second, then a second at 50
+    let start = Utc::now();
+    for i in 0..450 {
+        let at = start + chrono::Duration::milliseconds(200 * i);
+        messaging.publish(TOPIC, &book(at, 1.0, 1.0)).await;
+    }
+    let burst = start + chrono::Duration::seconds(90);
+    for i in 0..50 {
+        let at = burst + chrono::Duration::milliseconds(20 * i);
+        messaging.publish(TOPIC, &book(at, 1.0, 1.0)).await;
+    }
+    let found = detected(&mut detections, AnomalyType::UnusualActivity).await;
+    handle.stop().await;
+
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].metrics.current_value, 25.0);
+    assert_eq!(found[0].metrics.expected_value, 5.0);
+}
@@ -0,0 +1,226 @@
+// Schema goldens for every type written to the bus or the database. A failure here means
+// records already stored would read differently; see monitor-testkit for updating goldens.
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use crypto_monitor::{
+    anomaly::iceberg::{BookSide, IcebergDetails},
+    core::{
+        model::{Candle, MarketTick},
+        AlertType,
+    },
+    notifier::AnomalyContext,
+    trader::{PositionSide, SignalStrength, SignalType},
+    AnomalyDetection, AnomalyMetrics, AnomalyReason, AnomalySeverity, AnomalyType, DetectionDetails,
+    EventSource, EventType, MarketDataType, MonitorEvent, Notification, Position, ReasonKind,
+    TradingSignal,
+};
+use monitor_testkit::Goldens;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+fn goldens() -> Goldens {
+    Goldens::new(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/schema"))
+}
+
+fn at() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap()
+}
+
+fn monitor_event() -> MonitorEvent {
+    MonitorEvent {
+        id: Uuid::from_u128(1),
+        timestamp: at(),
+        source: EventSource::Exchange("binance".to_string()),
+        event_type: EventType::MarketData(MarketDataType::Trade),
+        data: serde_json::json!({
+            "exchange": "binance",
+            "price": 50000.5,
+            "symbol": "BTC/USDT",
+            "volume": 0.25,
+        }),
+    }
+}
+
+fn anomaly_detection() -> AnomalyDetection {
+    AnomalyDetection {
+        id: Uuid::from_u128(2),
+        timestamp: at(),
+        symbol: "BTC/USDT".to_string(),
+        exchange: "binance".to_string(),
+        anomaly_type: AnomalyType::LargeOrder,
+        severity: AnomalySeverity::High,
+        confidence: 0.78,
+        metrics: AnomalyMetrics {
+            current_value: 12.5,
+            expected_value: 2.5,
+            deviation: 10.0,
+            z_score: Some(4.2),
+            percentage_change: None,
+            historical_avg: Some(2.5),
+            historical_std: Some(2.38),
+            ..Default::default()
+        },
+        description: "Iceberg bid at 50000".to_string(),
+        reason: Some(
+            AnomalyReason::new(
+                "binance",
+                "BTC/USDT",
+                12.5,
+                ReasonKind::Iceberg {
+                    side: BookSide::Bid,
+                    level_price: 50000.0,
+                    fills: 7,
+                    displayed_size: 2.0,
+                    hidden_size_estimate: 10.5,
+                },
+            )
+            .with_baseline(2.0)
+            .with_threshold(6.0)
+            .with_window_secs(42.0),
+        ),
+        details: Some(DetectionDetails::Iceberg(IcebergDetails {
+            side: BookSide::Bid,
+            level_price: 50000.0,
+            executed_size: 12.5,
+            displayed_size: 2.0,
+            hidden_size_estimate: 10.5,
+            fills: 7,
+            duration_secs: 42.0,
+        })),
+        ..Default::default()
+    }
+}
+
+fn position() -> Position {
+    Position {
+        id: Uuid::from_u128(3),
+        symbol: "BTC/USDT".to_string(),
+        exchange: "binance".to_string(),
+        side: PositionSide::Long,
+        quantity: 0.02,
+        entry_price: 50000.0,
+        current_price: 50500.0,
+        unrealized_pnl: 10.0,
+        realized_pnl: 0.0,
+        stop_loss: Some(48500.0),
+        take_profit: Some(53000.0),
+        opened_at: at(),
+        closed_at: None,
+        stale: false,
+        adds: 0,
+        stop_order_id: None,
+        take_profit_order_id: None,
+        fees: 0.0,
+    }
+}
+
+fn trading_signal() -> TradingSignal {
+    TradingSignal {
+        id: Uuid::from_u128(4),
+        timestamp: at(),
+        symbol: "BTC/USDT".to_string(),
+        exchange: "binance".to_string(),
+        signal_type: SignalType::Buy,
+        strength: SignalStrength::Strong,
+        price: 50000.0,
+        reason: "Volume spike".to_string(),
+        anomaly_id: Some(Uuid::from_u128(2)),
+    }
+}
+
+fn notification() -> Notification {
+    Notification {
+        id: Uuid::from_u128(5),
+        timestamp: at(),
+        enqueued_at: at() + Duration::seconds(1),
+        alert_type: AlertType::Critical,
+        title: "Volume spike on BTC/USDT".to_string(),
+        message: "Volume 4.2 standard deviations above average".to_string(),
+        data: Some(serde_json::json!({ "z_score": 4.2 })),
+        anomaly: Some(AnomalyContext {
+            anomaly_type: AnomalyType::VolumeSpike,
+            severity: AnomalySeverity::High,
+            confidence: 0.78,
+            exchange: "binance".to_string(),
+            symbol: "BTC/USDT".to_string(),
+            reason: None,
+        }),
+    }
+}
+
+fn candle() -> Candle {
+    Candle {
+        exchange: "binance".to_string(),
+        symbol: "BTC/USDT".to_string(),
+        timestamp: at(),
+        interval: "1m".to_string(),
+        open: 50000.0,
+        high: 50100.0,
+        low: 49950.0,
+        close: 50050.0,
+        volume: 12.75,
+        trades: 321,
+    }
+}
+
+fn market_tick() -> MarketTick {
+    MarketTick {
+        id: Uuid::from_u128(6),
+        exchange: "binance".to_string(),
+        symbol: "BTC/USDT".to_string(),
+        timestamp: at(),
+        price: 50000.5,
+        volume: 0.25,
+        bid: Some(50000.0),
+        ask: Some(50001.0),
+        bid_volume: Some(1.5),
+        ask_volume: None,
+    }
+}
+
+#[test]
+fn test_serialized_schema_matches_goldens() {
+    let goldens = goldens();
+    goldens.assert_current("monitor_event", &monitor_event());
+    goldens.assert_current("anomaly_detection", &anomaly_detection());
+    goldens.assert_current("position", &position());
+    goldens.assert_current("trading_signal", &trading_signal());
+    goldens.assert_current("notification", &notification());
+    goldens.assert_current("candle", &candle());
+    goldens.assert_current("market_tick", &market_tick());
+}
+
+// Fields added since v1 are filled by their serde defaults
+#[test]
+fn test_previous_schema_versions_still_deserialize() {
+    let goldens = goldens();
+    goldens.assert_readable::<MonitorEvent>("monitor_event");
+    goldens.assert_readable::<TradingSignal>("trading_signal");
+    goldens.assert_readable::<Candle>("candle");
+    goldens.assert_readable::<MarketTick>("market_tick");
+
+    let anomalies = goldens.assert_readable::<AnomalyDetection>("anomaly_detection");
+    let (version, v1) = &anomalies[0];
+    assert_eq!(version, "v1");
+    assert!(!v1.warmup);
+    assert!(v1.details.is_none());
+    // v1 and v2 predate `confidence`, v3 predates `reason`
+    assert!(anomalies[..2].iter().all(|(_, a)| a.confidence == 0.0));
+    assert!(anomalies[..3].iter().all(|(_, a)| a.reason.is_none()));
+
+    let positions = goldens.assert_readable::<Position>("position");
+    assert!(!positions[0].1.stale);
+    // v1 and v2 predate `adds`
+    assert!(positions[..2].iter().all(|(_, p)| p.adds == 0));
+    // v1 to v3 predate the exchange exit orders
+    assert!(positions[..3].iter().all(|(_, p)| p.stop_order_id.is_none()));
+    // v1 to v4 predate fee tracking
+    assert!(positions[..4].iter().all(|(_, p)| p.fees == 0.0));
+
+    let notifications = goldens.assert_readable::<Notification>("notification");
+    let (_, v1) = &notifications[0];
+    assert!(v1.anomaly.is_none());
+    assert!(v1.enqueued_at >= v1.timestamp);
+    let (_, v2) = &notifications[1];
+    assert_eq!(v2.anomaly.as_ref().unwrap().confidence, 0.0);
+}
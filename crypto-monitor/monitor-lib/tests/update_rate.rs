@@ -0,0 +1,52 @@
+// Order book snapshots routed through `Monitor::process` feed the update rate detector.
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use crypto_monitor::{
+    core::event::EventBuilder, AnomalyType, EventSource, EventType, MarketDataType, Monitor,
+    MonitorEvent, UpdateRateConfig,
+};
+use monitor_testkit::fixtures::monitor_config;
+
+fn book(at: DateTime<Utc>) -> MonitorEvent {
+    EventBuilder::new()
+        .with_source(EventSource::Exchange("binance".to_string()))
+        .with_type(EventType::MarketData(MarketDataType::OrderBook))
+        .with_data(serde_json::json!({
+            "exchange": "binance",
+            "symbol": "BTC/USDT",
+            "timestamp": at,
+            "bids": [],
+            "asks": [],
+        }))
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_book_update_burst_is_unusual_activity() {
+    let monitor = Monitor::builder()
+        .config(monitor_config())
+        .with_update_rate_config(UpdateRateConfig::default())
+        .build()
+        .unwrap();
+    let start = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+
+    // A minute and a half at 5 updates a second
+    for i in 0..450 {
+        let at = start + Duration::milliseconds(200 * i);
+        assert!(monitor.process(&book(at)).await.unwrap().is_empty());
+    }
+
+    // Then a second at 50
+    let burst = start + Duration::seconds(90);
+    let mut detections = Vec::new();
+    for i in 0..50 {
+        let at = burst + Duration::milliseconds(20 * i);
+        detections.extend(monitor.process(&book(at)).await.unwrap());
+    }
+
+    assert_eq!(detections.len(), 1);
+    assert_eq!(detections[0].anomaly_type, AnomalyType::UnusualActivity);
+    assert_eq!(detections[0].metrics.current_value, 25.0);
+    assert_eq!(detections[0].metrics.expected_value, 5.0);
+}
@@ -0,0 +1,114 @@
+// Warm-up through a running monitor: detections made while it lasts are flagged and held back
+// from the notifier, feed-health alerts still go out, and once it is over the same detection is
+// notified.
+
+use chrono::{DateTime, Duration, Utc};
+use crypto_monitor::{
+    core::{event::EventBuilder, ExpectedActivityConfig},
+    AnomalyDetection, EventSource, EventType, MarketDataType, Monitor, MonitorConfig,
+    MonitorEvent, NotificationManager,
+};
+use monitor_testkit::{
+    fixtures::monitor_config, notifier::RecordingChannel, simulator::local_messaging,
+};
+use std::sync::Arc;
+
+fn config(warmup_secs: u64) -> MonitorConfig {
+    let mut config = monitor_config();
+    config.monitoring.warmup.duration_secs = warmup_secs;
+    config.monitoring.warmup.min_samples_per_symbol = 0;
+    config.monitoring.activity.feed_stale_secs = 1;
+    config.monitoring.activity.expected = vec![ExpectedActivityConfig {
+        exchange: "binance".to_string(),
+        symbol: "BTC/USDT".to_string(),
+        min_trades_per_hour: 10,
+        max_silent_secs: 1,
+    }];
+    config
+}
+
+fn liquidation(quantity: f64, at: DateTime<Utc>) -> MonitorEvent {
+    EventBuilder::new()
+        .with_source(EventSource::Exchange("binance".to_string()))
+        .with_type(EventType::MarketData(MarketDataType::Liquidation))
+        .with_data(serde_json::json!({
+            "exchange": "binance",
+            "symbol": "BTC/USDT",
+            "timestamp": at,
+            "side": "short",
+            "price": 50_000.0,
+            "quantity": quantity,
+        }))
+        .build()
+        .unwrap()
+}
+
+// 160 BTC of shorts within 15s, past the default 5m notional threshold
+async fn liquidation_burst(monitor: &Monitor) -> Vec<AnomalyDetection> {
+    let start = Utc::now();
+    let mut detections = Vec::new();
+    for i in 0..4 {
+        let at = start + Duration::seconds(i * 5);
+        detections.extend(monitor.process(&liquidation(40.0, at)).await.unwrap());
+    }
+    detections
+}
+
+fn monitor(warmup_secs: u64, channel: &RecordingChannel) -> Monitor {
+    let mut notifier = NotificationManager::new();
+    notifier.add_channel(Box::new(channel.clone()));
+    Monitor::builder()
+        .config(config(warmup_secs))
+        .with_notifier(Arc::new(notifier))
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_detections_during_warmup_are_flagged_and_not_notified() {
+    let channel = RecordingChannel::default();
+    let monitor = monitor(3_600, &channel);
+    let detections = liquidation_burst(&monitor).await;
+
+    assert_eq!(detections.len(), 1);
+    assert!(detections[0].warmup);
+    assert!(channel.sent().is_empty());
+    assert!(monitor.warmup().remaining(Utc::now()) > Duration::minutes(59));
+}
+
+#[tokio::test]
+async fn test_feed_health_alerts_are_notified_during_warmup() {
+    let channel = RecordingChannel::default();
+    let mut notifier = NotificationManager::new();
+    notifier.add_channel(Box::new(channel.clone()));
+    let monitor = Monitor::builder()
+        .config(config(3_600))
+        .with_messaging(local_messaging().await)
+        .with_notifier(Arc::new(notifier))
+        .build()
+        .unwrap();
+    let handle = monitor.start().await.unwrap();
+
+    liquidation_burst(&monitor).await;
+    tokio::time::sleep(std::time::Duration::from_millis(2_500)).await;
+    handle.stop().await;
+
+    // The binance feed never delivered anything, only its staleness is reported
+    let sent = channel.sent();
+    assert!(!sent.is_empty());
+    assert!(sent.iter().all(|n| n.anomaly.is_none()));
+    assert!(sent.iter().any(|n| n.data.as_ref().unwrap()["kind"] == "feed_stale"));
+}
+
+#[tokio::test]
+async fn test_detections_after_warmup_are_notified() {
+    let channel = RecordingChannel::default();
+    let monitor = monitor(0, &channel);
+    let detections = liquidation_burst(&monitor).await;
+
+    assert_eq!(detections.len(), 1);
+    assert!(!detections[0].warmup);
+    let sent = channel.sent();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].anomaly.as_ref().unwrap().symbol, "BTC/USDT");
+}
@@ -0,0 +1,760 @@
+use crate::{manager::NotificationManager, routing::glob_match, DeliveryReport, Notification};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use monitor_anomaly::AnomalySeverity;
+use monitor_core::{scheduler::PeriodicTask, AlertType, AnomalyType, MonitorError, Result};
+pub use monitor_core::notification::AckConfig;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::{collections::VecDeque, sync::Arc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Acknowledgement {
+    pub alert_id: Uuid,
+    pub acked_by: String,
+    pub acked_at: DateTime<Utc>,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertHistoryEntry {
+    #[serde(flatten)]
+    pub notification: Notification,
+    pub acked: bool,
+    pub ack: Option<Acknowledgement>,
+    pub escalated: bool,
+}
+
+// Criteria are combined, anomaly criteria never match non-anomaly alerts
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AckFilter {
+    #[serde(default)]
+    pub ids: Vec<Uuid>,
+    #[serde(default)]
+    pub alert_types: Vec<AlertType>,
+    #[serde(default)]
+    pub anomaly_types: Vec<AnomalyType>,
+    #[serde(default)]
+    pub min_severity: Option<AnomalySeverity>,
+    #[serde(default)]
+    pub exchanges: Vec<String>,
+    #[serde(default)]
+    pub symbol_pattern: Option<String>,
+    #[serde(default)]
+    pub from: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl AckFilter {
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+            && self.alert_types.is_empty()
+            && self.from.is_none()
+            && self.to.is_none()
+            && !self.needs_anomaly()
+    }
+
+    fn needs_anomaly(&self) -> bool {
+        !self.anomaly_types.is_empty()
+            || self.min_severity.is_some()
+            || !self.exchanges.is_empty()
+            || self.symbol_pattern.is_some()
+    }
+
+    pub fn matches(&self, notification: &Notification) -> bool {
+        let common = (self.ids.is_empty() || self.ids.contains(&notification.id))
+            && (self.alert_types.is_empty() || self.alert_types.contains(&notification.alert_type))
+            && self.from.is_none_or(|from| notification.timestamp >= from)
+            && self.to.is_none_or(|to| notification.timestamp <= to);
+        if !common {
+            return false;
+        }
+
+        let Some(anomaly) = &notification.anomaly else {
+            return !self.needs_anomaly();
+        };
+
+        (self.anomaly_types.is_empty() || self.anomaly_types.contains(&anomaly.anomaly_type))
+            && self
+                .min_severity
+                .as_ref()
+                .is_none_or(|min| anomaly.severity >= *min)
+            && (self.exchanges.is_empty()
+                || self
+                    .exchanges
+                    .iter()
+                    .any(|e| e.eq_ignore_ascii_case(&anomaly.exchange)))
+            && self
+                .symbol_pattern
+                .as_deref()
+                .is_none_or(|pattern| glob_match(pattern, &anomaly.symbol))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkAck {
+    pub acknowledgements: Vec<Acknowledgement>,
+    // Unacknowledged alerts matching the filter, including any left over by the cap
+    pub matched: usize,
+    pub truncated: bool,
+}
+
+struct AlertEntry {
+    notification: Notification,
+    ack: Option<Acknowledgement>,
+    escalated: bool,
+}
+
+impl AlertEntry {
+    fn history(&self) -> AlertHistoryEntry {
+        AlertHistoryEntry {
+            notification: self.notification.clone(),
+            acked: self.ack.is_some(),
+            ack: self.ack.clone(),
+            escalated: self.escalated,
+        }
+    }
+}
+
+// Recent notifications with their acknowledgement state, newest last
+pub struct AlertLog {
+    config: AckConfig,
+    entries: RwLock<VecDeque<AlertEntry>>,
+    last_digest: Mutex<Option<DateTime<Utc>>>,
+}
+
+impl AlertLog {
+    pub fn new(config: AckConfig) -> Self {
+        Self {
+            config,
+            entries: RwLock::new(VecDeque::new()),
+            last_digest: Mutex::new(None),
+        }
+    }
+
+    pub fn config(&self) -> &AckConfig {
+        &self.config
+    }
+
+    pub fn record(&self, notification: &Notification) {
+        self.push(AlertEntry {
+            notification: notification.clone(),
+            ack: None,
+            escalated: false,
+        });
+    }
+
+    fn push(&self, entry: AlertEntry) {
+        let mut entries = self.entries.write();
+        if entries.iter().any(|e| e.notification.id == entry.notification.id) {
+            return;
+        }
+        entries.push_back(entry);
+        while entries.len() > self.config.history_size.max(1) {
+            entries.pop_front();
+        }
+    }
+
+    pub fn ack(
+        &self,
+        id: Uuid,
+        acked_by: &str,
+        note: Option<String>,
+        now: DateTime<Utc>,
+    ) -> Option<Acknowledgement> {
+        let ack = self.pending_ack(id, acked_by, note, now)?;
+        self.apply(std::slice::from_ref(&ack));
+        Some(ack)
+    }
+
+    // The ack `ack` would record, without recording it: the existing one when the alert is
+    // already acknowledged, since repeated acks keep the first acknowledger
+    pub fn pending_ack(
+        &self,
+        id: Uuid,
+        acked_by: &str,
+        note: Option<String>,
+        now: DateTime<Utc>,
+    ) -> Option<Acknowledgement> {
+        let entries = self.entries.read();
+        let entry = entries.iter().find(|e| e.notification.id == id)?;
+        Some(entry.ack.clone().unwrap_or_else(|| Acknowledgement {
+            alert_id: id,
+            acked_by: acked_by.to_string(),
+            acked_at: now,
+            note,
+        }))
+    }
+
+    // Acks the newest unacknowledged matches first, up to `bulk_limit`
+    pub fn bulk_ack(
+        &self,
+        filter: &AckFilter,
+        acked_by: &str,
+        note: Option<String>,
+        now: DateTime<Utc>,
+    ) -> Result<BulkAck> {
+        let result = self.bulk_matches(filter, acked_by, note, now)?;
+        self.apply(&result.acknowledgements);
+        Ok(result)
+    }
+
+    // What `bulk_ack` would record, without recording it
+    pub fn bulk_matches(
+        &self,
+        filter: &AckFilter,
+        acked_by: &str,
+        note: Option<String>,
+        now: DateTime<Utc>,
+    ) -> Result<BulkAck> {
+        if filter.is_empty() {
+            return Err(MonitorError::Configuration(
+                "Bulk ack needs at least one filter criterion".to_string(),
+            ));
+        }
+
+        let entries = self.entries.read();
+        let mut result = BulkAck {
+            acknowledgements: Vec::new(),
+            matched: 0,
+            truncated: false,
+        };
+        for entry in entries.iter().rev() {
+            if entry.ack.is_some() || !filter.matches(&entry.notification) {
+                continue;
+            }
+            result.matched += 1;
+            if result.acknowledgements.len() >= self.config.bulk_limit {
+                result.truncated = true;
+                continue;
+            }
+            result.acknowledgements.push(Acknowledgement {
+                alert_id: entry.notification.id,
+                acked_by: acked_by.to_string(),
+                acked_at: now,
+                note: note.clone(),
+            });
+        }
+        Ok(result)
+    }
+
+    // Records acks already persisted, an alert acknowledged meanwhile keeps its first ack
+    pub fn apply(&self, acks: &[Acknowledgement]) {
+        let mut entries = self.entries.write();
+        for ack in acks {
+            if let Some(entry) = entries.iter_mut().find(|e| e.notification.id == ack.alert_id) {
+                entry.ack.get_or_insert_with(|| ack.clone());
+            }
+        }
+    }
+
+    pub fn is_acked(&self, id: Uuid) -> bool {
+        self.entries
+            .read()
+            .iter()
+            .any(|e| e.notification.id == id && e.ack.is_some())
+    }
+
+    // Newest first, `acked` narrows to acknowledged or outstanding alerts
+    pub fn history(&self, limit: usize, acked: Option<bool>) -> Vec<AlertHistoryEntry> {
+        self.entries
+            .read()
+            .iter()
+            .rev()
+            .filter(|e| acked.is_none_or(|acked| e.ack.is_some() == acked))
+            .take(limit)
+            .map(AlertEntry::history)
+            .collect()
+    }
+
+    // Critical alerts still unacknowledged after `escalate_after_secs`, each escalated once
+    pub fn due_escalations(&self, now: DateTime<Utc>) -> Vec<Notification> {
+        if self.config.escalate_after_secs == 0 {
+            return Vec::new();
+        }
+        let after = Duration::seconds(self.config.escalate_after_secs as i64);
+
+        let mut entries = self.entries.write();
+        entries
+            .iter_mut()
+            .filter(|e| {
+                !e.escalated
+                    && e.ack.is_none()
+                    && e.notification.alert_type == AlertType::Critical
+                    && now - e.notification.enqueued_at >= after
+            })
+            .map(|e| {
+                e.escalated = true;
+                Notification {
+                    id: Uuid::new_v4(),
+                    timestamp: e.notification.timestamp,
+                    enqueued_at: now,
+                    alert_type: AlertType::Critical,
+                    title: format!(
+                        "Unacknowledged for {}m: {}",
+                        (now - e.notification.enqueued_at).num_minutes(),
+                        e.notification.title
+                    ),
+                    message: e.notification.message.clone(),
+                    data: Some(serde_json::json!({ "escalated_from": e.notification.id })),
+                    anomaly: e.notification.anomaly.clone(),
+                }
+            })
+            .collect()
+    }
+
+    // Summary of the alerts since the previous digest, `None` until the interval has passed
+    // or when nothing is left to report
+    pub fn digest(&self, now: DateTime<Utc>) -> Option<Notification> {
+        if self.config.digest_interval_secs == 0 {
+            return None;
+        }
+        let interval = Duration::seconds(self.config.digest_interval_secs as i64);
+
+        let since = {
+            let mut last = self.last_digest.lock();
+            let since = last.unwrap_or(now - interval);
+            if now - since < interval {
+                return None;
+            }
+            *last = Some(now);
+            since
+        };
+
+        let entries = self.entries.read();
+        let window: Vec<&AlertEntry> = entries
+            .iter()
+            .filter(|e| e.notification.enqueued_at > since && e.notification.enqueued_at <= now)
+            .collect();
+        let (shown, omitted): (Vec<&AlertEntry>, Vec<&AlertEntry>) = window
+            .into_iter()
+            .partition(|e| e.ack.is_none() || !self.config.digest_exclude_acked);
+        if shown.is_empty() {
+            return None;
+        }
+
+        let mut lines: Vec<String> = shown
+            .iter()
+            .map(|e| {
+                let mut line = format!(
+                    "• [{:?}] {} ({})",
+                    e.notification.alert_type,
+                    e.notification.title,
+                    e.notification.timestamp.format("%H:%M:%S")
+                );
+                if let Some(ack) = &e.ack {
+                    line.push_str(&format!(" — acked by {}", ack.acked_by));
+                }
+                line
+            })
+            .collect();
+        if !omitted.is_empty() {
+            lines.push(format!("{} acknowledged alerts omitted", omitted.len()));
+        }
+
+        Some(Notification {
+            id: Uuid::new_v4(),
+            timestamp: now,
+            enqueued_at: now,
+            alert_type: AlertType::Info,
+            title: format!(
+                "Alert digest: {} alerts since {}",
+                shown.len(),
+                since.format("%Y-%m-%d %H:%M UTC")
+            ),
+            message: lines.join("\n"),
+            data: None,
+            anomaly: None,
+        })
+    }
+
+    // Restores the most recent alerts and their acks after a restart
+    pub async fn load(&self, pool: &PgPool) -> Result<usize> {
+        type Row = (String, Option<String>, Option<DateTime<Utc>>, Option<String>);
+        let rows: Vec<Row> = sqlx::query_as(
+            "SELECT metadata::text, acked_by, acked_at, ack_note FROM alerts \
+             WHERE metadata IS NOT NULL ORDER BY created_at DESC LIMIT $1",
+        )
+        .bind(self.config.history_size as i64)
+        .fetch_all(pool)
+        .await?;
+
+        let count = rows.len();
+        for (metadata, acked_by, acked_at, note) in rows.into_iter().rev() {
+            let notification: Notification = serde_json::from_str(&metadata)?;
+            let ack = acked_by.zip(acked_at).map(|(acked_by, acked_at)| Acknowledgement {
+                alert_id: notification.id,
+                acked_by,
+                acked_at,
+                note,
+            });
+            self.push(AlertEntry {
+                notification,
+                ack,
+                escalated: false,
+            });
+        }
+        Ok(count)
+    }
+
+    pub async fn save_alert(
+        pool: &PgPool,
+        notification: &Notification,
+        report: &DeliveryReport,
+    ) -> Result<()> {
+        let severity = notification
+            .anomaly
+            .as_ref()
+            .map(|a| format!("{:?}", a.severity))
+            .unwrap_or_else(|| format!("{:?}", notification.alert_type));
+        let channels: Vec<String> = report
+            .deliveries
+            .iter()
+            .filter(|d| d.success)
+            .map(|d| d.channel.clone())
+            .collect();
+
+        sqlx::query(
+            "INSERT INTO alerts (id, alert_type, severity, title, message, metadata, channels, sent_at) \
+             VALUES ($1, $2, $3, $4, $5, $6::jsonb, $7, $8) ON CONFLICT (id) DO NOTHING",
+        )
+        .bind(notification.id)
+        .bind(format!("{:?}", notification.alert_type))
+        .bind(severity)
+        .bind(&notification.title)
+        .bind(&notification.message)
+        .bind(serde_json::to_string(notification)?)
+        .bind(channels)
+        .bind(report.deliveries.iter().map(|d| d.completed_at).max())
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
+
+impl Acknowledgement {
+    pub async fn save(&self, pool: &PgPool) -> Result<()> {
+        self.save_with(pool).await
+    }
+
+    // Every ack or none of them
+    pub async fn save_all(acks: &[Acknowledgement], pool: &PgPool) -> Result<()> {
+        let mut tx = pool.begin().await?;
+        for ack in acks {
+            ack.save_with(&mut *tx).await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    // Acks a stored alert, also one that has left the in-memory history. Returns the ack the
+    // alert ends up with, the earlier one when it was already acknowledged, and `None` when no
+    // alert with this id was ever stored
+    pub async fn save_stored(&self, pool: &PgPool) -> Result<Option<Acknowledgement>> {
+        self.save_with(pool).await?;
+        let row: Option<(Option<String>, Option<DateTime<Utc>>, Option<String>)> =
+            sqlx::query_as("SELECT acked_by, acked_at, ack_note FROM alerts WHERE id = $1")
+                .bind(self.alert_id)
+                .fetch_optional(pool)
+                .await?;
+        Ok(row.and_then(|(acked_by, acked_at, note)| {
+            acked_by.zip(acked_at).map(|(acked_by, acked_at)| Acknowledgement {
+                alert_id: self.alert_id,
+                acked_by,
+                acked_at,
+                note,
+            })
+        }))
+    }
+
+    async fn save_with<'e, E>(&self, executor: E) -> Result<()>
+    where
+        E: sqlx::PgExecutor<'e>,
+    {
+        sqlx::query(
+            "UPDATE alerts SET acked_by = $2, acked_at = $3, ack_note = $4 \
+             WHERE id = $1 AND acked_at IS NULL",
+        )
+        .bind(self.alert_id)
+        .bind(&self.acked_by)
+        .bind(self.acked_at)
+        .bind(&self.note)
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+}
+
+impl BulkAck {
+    pub async fn audit(&self, pool: &PgPool, filter: &AckFilter, acked_by: &str) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO audit_logs (action, entity_type, metadata, user_id) \
+             VALUES ('alerts.bulk_ack', 'alert', $1::jsonb, $2)",
+        )
+        .bind(serde_json::to_string(&serde_json::json!({
+            "filter": filter,
+            "matched": self.matched,
+            "truncated": self.truncated,
+            "alert_ids": self.acknowledgements.iter().map(|a| a.alert_id).collect::<Vec<_>>(),
+        }))?)
+        .bind(acked_by)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
+
+// Re-sends overdue critical alerts and the periodic digest, on the leader only since
+// followers never send and so never record alerts
+pub struct AlertUpkeepJob {
+    manager: Arc<NotificationManager>,
+}
+
+impl AlertUpkeepJob {
+    pub fn new(manager: Arc<NotificationManager>) -> Self {
+        Self { manager }
+    }
+}
+
+#[async_trait]
+impl PeriodicTask for AlertUpkeepJob {
+    fn name(&self) -> &str {
+        "alert_upkeep"
+    }
+
+    async fn run(&self) -> Result<()> {
+        let now = Utc::now();
+        self.manager.escalate_unacked(now).await;
+        self.manager.send_digest(now).await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AnomalyContext;
+
+    fn alert(symbol: &str, severity: AnomalySeverity, at: DateTime<Utc>) -> Notification {
+        Notification {
+            id: Uuid::new_v4(),
+            timestamp: at,
+            enqueued_at: at,
+            alert_type: match severity {
+                AnomalySeverity::Critical => AlertType::Critical,
+                _ => AlertType::Warning,
+            },
+            title: format!("VolumeSpike detected on binance/{}", symbol),
+            message: "spike".to_string(),
+            data: None,
+            anomaly: Some(AnomalyContext {
+                anomaly_type: AnomalyType::VolumeSpike,
+                confidence: severity.confidence_floor(),
+                severity,
+                exchange: "binance".to_string(),
+                symbol: symbol.to_string(),
+                reason: None,
+            }),
+        }
+    }
+
+    fn log(config: AckConfig) -> AlertLog {
+        AlertLog::new(config)
+    }
+
+    #[test]
+    fn test_bulk_ack_filters_and_cap() {
+        let log = log(AckConfig {
+            bulk_limit: 2,
+            ..AckConfig::default()
+        });
+        let start = Utc::now();
+        for i in 0..4 {
+            log.record(&alert("BTC/USDT", AnomalySeverity::High, start + Duration::seconds(i)));
+        }
+        log.record(&alert("ETH/USDT", AnomalySeverity::Critical, start));
+        log.record(&alert("BTC/USDT", AnomalySeverity::Low, start));
+        let mut system = alert("BTC/USDT", AnomalySeverity::High, start);
+        system.anomaly = None;
+        log.record(&system);
+
+        assert!(log.bulk_ack(&AckFilter::default(), "ops", None, start).is_err());
+
+        let filter = AckFilter {
+            symbol_pattern: Some("btc/*".to_string()),
+            min_severity: Some(AnomalySeverity::High),
+            ..AckFilter::default()
+        };
+        let first = log.bulk_ack(&filter, "ops", Some("storm".to_string()), start).unwrap();
+        assert_eq!((first.acknowledgements.len(), first.matched, first.truncated), (2, 4, true));
+        // Newest first
+        assert_eq!(first.acknowledgements[0].alert_id, log.history(10, None)[3].notification.id);
+
+        let second = log.bulk_ack(&filter, "ops", None, start).unwrap();
+        assert_eq!((second.acknowledgements.len(), second.matched, second.truncated), (2, 2, false));
+        assert!(log.bulk_ack(&filter, "ops", None, start).unwrap().acknowledgements.is_empty());
+
+        let outstanding = log.history(10, Some(false));
+        assert_eq!(outstanding.len(), 3);
+        assert!(outstanding.iter().all(|e| e.ack.is_none()));
+
+        // Time and type criteria also reach alerts without anomaly context
+        let by_type = AckFilter {
+            alert_types: vec![AlertType::Warning],
+            to: Some(start),
+            ..AckFilter::default()
+        };
+        let acked = log.bulk_ack(&by_type, "oncall", None, start).unwrap();
+        assert_eq!(acked.acknowledgements.len(), 2);
+        assert!(acked.acknowledgements.iter().any(|a| a.alert_id == system.id));
+        assert!(log.is_acked(system.id));
+    }
+
+    #[test]
+    fn test_digest_excludes_acked_alerts() {
+        let start = Utc::now();
+        let config = AckConfig {
+            digest_interval_secs: 3600,
+            ..AckConfig::default()
+        };
+        let alerts: Vec<Notification> = ["BTC/USDT", "ETH/USDT", "SOL/USDT"]
+            .iter()
+            .enumerate()
+            .map(|(i, symbol)| alert(symbol, AnomalySeverity::High, start + Duration::minutes(i as i64)))
+            .collect();
+
+        let excluding = log(config.clone());
+        let including = log(AckConfig {
+            digest_exclude_acked: false,
+            ..config
+        });
+        for log in [&excluding, &including] {
+            for alert in &alerts {
+                log.record(alert);
+            }
+            log.ack(alerts[1].id, "ops", None, start).unwrap();
+        }
+
+        let now = start + Duration::minutes(5);
+        let digest = excluding.digest(now).unwrap();
+        assert!(digest.title.starts_with("Alert digest: 2 alerts"));
+        assert!(!digest.message.contains("ETH/USDT"));
+        assert!(digest.message.contains("1 acknowledged alerts omitted"));
+        // Not due again until a full interval has passed
+        assert!(excluding.digest(now + Duration::minutes(1)).is_none());
+
+        let digest = including.digest(now).unwrap();
+        assert!(digest.title.starts_with("Alert digest: 3 alerts"));
+        assert!(digest.message.contains("ETH/USDT (") && digest.message.contains("acked by ops"));
+
+        // A window holding only acknowledged alerts sends nothing
+        let later = alert("XRP/USDT", AnomalySeverity::High, now + Duration::minutes(20));
+        excluding.record(&later);
+        excluding.ack(later.id, "ops", None, now).unwrap();
+        assert!(excluding.digest(now + Duration::hours(1)).is_none());
+    }
+
+    #[test]
+    fn test_escalation_skips_acked_alerts() {
+        let log = log(AckConfig {
+            escalate_after_secs: 600,
+            ..AckConfig::default()
+        });
+        let start = Utc::now();
+        let acked = alert("BTC/USDT", AnomalySeverity::Critical, start);
+        let pending = alert("ETH/USDT", AnomalySeverity::Critical, start);
+        log.record(&acked);
+        log.record(&pending);
+        log.record(&alert("SOL/USDT", AnomalySeverity::High, start));
+        log.ack(acked.id, "ops", None, start + Duration::minutes(1)).unwrap();
+
+        assert!(log.due_escalations(start + Duration::minutes(5)).is_empty());
+        let escalated = log.due_escalations(start + Duration::minutes(10));
+        assert_eq!(escalated.len(), 1);
+        assert!(escalated[0].title.starts_with("Unacknowledged for 10m"));
+        assert_eq!(escalated[0].anomaly, pending.anomaly);
+        assert!(log.due_escalations(start + Duration::minutes(20)).is_empty());
+        assert!(log.history(3, None)[1].escalated);
+    }
+
+    #[test]
+    fn test_bulk_matches_leave_the_log_until_applied() {
+        let log = log(AckConfig::default());
+        let start = Utc::now();
+        let first = alert("BTC/USDT", AnomalySeverity::High, start);
+        let second = alert("ETH/USDT", AnomalySeverity::High, start);
+        log.record(&first);
+        log.record(&second);
+
+        let filter = AckFilter {
+            min_severity: Some(AnomalySeverity::High),
+            ..AckFilter::default()
+        };
+        let planned = log.bulk_matches(&filter, "ops", None, start).unwrap();
+        assert_eq!(planned.acknowledgements.len(), 2);
+        assert!(!log.is_acked(first.id) && !log.is_acked(second.id));
+        assert_eq!(log.pending_ack(first.id, "ops", None, start).unwrap().acked_by, "ops");
+        assert!(!log.is_acked(first.id));
+
+        // Acknowledged by someone else meanwhile, the first ack stays
+        log.ack(first.id, "oncall", None, start).unwrap();
+        log.apply(&planned.acknowledgements);
+        assert_eq!(log.history(2, None)[1].ack.as_ref().unwrap().acked_by, "oncall");
+        assert_eq!(log.history(2, None)[0].ack.as_ref().unwrap().acked_by, "ops");
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn test_acks_reach_alerts_past_the_history() {
+        let pool = monitor_testkit::db::migrated_pool().await;
+        let manager = NotificationManager::new()
+            .with_acks(AckConfig {
+                history_size: 1,
+                ..AckConfig::default()
+            })
+            .with_storage(pool.clone());
+        let start = Utc::now();
+        let old = alert("BTC/USDT", AnomalySeverity::High, start);
+        let recent = alert("ETH/USDT", AnomalySeverity::High, start);
+        for notification in [&old, &recent] {
+            let report = DeliveryReport {
+                notification_id: notification.id,
+                rule: "default".to_string(),
+                alert_type: notification.alert_type.clone(),
+                detected_at: start,
+                enqueued_at: start,
+                deliveries: Vec::new(),
+            };
+            AlertLog::save_alert(&pool, notification, &report).await.unwrap();
+            manager.alert_log().record(notification);
+        }
+        // Only the recent one is left in memory
+        assert_eq!(manager.alert_log().history(10, None).len(), 1);
+
+        let ack = manager.acknowledge(old.id, "ops", Some("late".to_string())).await.unwrap();
+        assert_eq!(ack.unwrap().acked_by, "ops");
+        let again = manager.acknowledge(old.id, "oncall", None).await.unwrap().unwrap();
+        assert_eq!((again.acked_by.as_str(), again.note.as_deref()), ("ops", Some("late")));
+        assert!(manager.acknowledge(Uuid::new_v4(), "ops", None).await.unwrap().is_none());
+
+        let filter = AckFilter {
+            ids: vec![recent.id],
+            ..AckFilter::default()
+        };
+        let result = manager.bulk_acknowledge(&filter, "ops", None).await.unwrap();
+        assert_eq!(result.acknowledgements.len(), 1);
+        assert!(manager.alert_log().is_acked(recent.id));
+        let (acked_by,): (Option<String>,) =
+            sqlx::query_as("SELECT acked_by FROM alerts WHERE id = $1")
+                .bind(recent.id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(acked_by.as_deref(), Some("ops"));
+    }
+}
@@ -39,12 +39,16 @@ impl EmailNotifier {
     }
     
     fn build_email_body(&self, notification: &Notification) -> String {
+        let confidence = notification.anomaly.as_ref().map_or(String::new(), |a| {
+            format!("<p><strong>Confidence:</strong> {:.0}%</p>", a.confidence * 100.0)
+        });
         format!(
             r#"
             <html>
             <body>
                 <h2>{}</h2>
                 <p><strong>Alert Type:</strong> {:?}</p>
+                {}
                 <p><strong>Time:</strong> {}</p>
                 <hr>
                 <p>{}</p>
@@ -54,6 +58,7 @@ impl EmailNotifier {
             "#,
             notification.title,
             notification.alert_type,
+            confidence,
             notification.timestamp.format("%Y-%m-%d %H:%M:%S UTC"),
             notification.message,
             if let Some(data) = &notification.data {
@@ -3,21 +3,69 @@ pub mod wechat;
 pub mod email;
 pub mod sms;
 pub mod manager;
+pub mod routing;
+pub mod slo;
+pub mod ack;
 
 use async_trait::async_trait;
-use monitor_anomaly::AnomalyDetection;
-use monitor_core::{AlertType, MonitorError, Result};
+use chrono::{DateTime, Utc};
+use monitor_anomaly::{
+    correlator::AnomalyGroup, reason::AnomalyReason, AnomalyDetection, AnomalySeverity,
+};
+use monitor_core::{AlertType, AnomalyType, EventType, MonitorEvent, Result};
+// Channel settings read from the monitor config
+pub use monitor_core::notification::{
+    EmailConfig, NotificationConfig, SmsConfig, SmsProvider, TelegramConfig, WeChatConfig,
+};
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Notification {
     pub id: uuid::Uuid,
-    pub timestamp: chrono::DateTime<chrono::Utc>,
+    // When the underlying anomaly or event was detected
+    pub timestamp: DateTime<Utc>,
+    #[serde(default = "Utc::now")]
+    pub enqueued_at: DateTime<Utc>,
     pub alert_type: AlertType,
     pub title: String,
     pub message: String,
     pub data: Option<serde_json::Value>,
+    // Set for anomaly notifications, used by channel routing
+    #[serde(default)]
+    pub anomaly: Option<AnomalyContext>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnomalyContext {
+    pub anomaly_type: AnomalyType,
+    pub severity: AnomalySeverity,
+    #[serde(default)]
+    pub confidence: f64,
+    pub exchange: String,
+    pub symbol: String,
+    // The figures behind the message, laid out separately by the channel formatters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<AnomalyReason>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelDelivery {
+    pub channel: String,
+    pub success: bool,
+    pub error: Option<String>,
+    pub completed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeliveryReport {
+    pub notification_id: uuid::Uuid,
+    // Routing rule that selected the channels, "default" when none matched
+    pub rule: String,
+    pub alert_type: AlertType,
+    pub detected_at: DateTime<Utc>,
+    pub enqueued_at: DateTime<Utc>,
+    pub deliveries: Vec<ChannelDelivery>,
 }
 
 impl Notification {
@@ -28,19 +76,71 @@ impl Notification {
             _ => AlertType::Info,
         };
         
+        let mut title = format!("{:?} detected on {}/{}", 
+            anomaly.anomaly_type, 
+            anomaly.exchange, 
+            anomaly.symbol
+        );
+        if let Some(source) = &anomaly.source {
+            title.push_str(&format!(" (reported by {})", source));
+        }
+        
         Self {
             id: uuid::Uuid::new_v4(),
             timestamp: anomaly.timestamp,
+            enqueued_at: Utc::now(),
             alert_type,
-            title: format!("{:?} detected on {}/{}", 
-                anomaly.anomaly_type, 
-                anomaly.exchange, 
-                anomaly.symbol
-            ),
+            title,
             message: anomaly.description.clone(),
             data: Some(serde_json::to_value(anomaly).unwrap_or_default()),
+            anomaly: Some(AnomalyContext {
+                anomaly_type: anomaly.anomaly_type.clone(),
+                severity: anomaly.severity.clone(),
+                confidence: anomaly.confidence,
+                exchange: anomaly.exchange.clone(),
+                symbol: anomaly.symbol.clone(),
+                reason: anomaly.reason.clone(),
+            }),
         }
     }
+    
+    // One notification for the whole group, routed by its most severe member
+    pub fn from_group(group: &AnomalyGroup) -> Self {
+        let mut notification = Self::from_anomaly(&group.primary());
+        notification.title = format!(
+            "{} correlated anomalies on {}/{}",
+            group.members.len(),
+            group.exchange,
+            group.symbol
+        );
+        notification.data = Some(serde_json::to_value(group).unwrap_or_default());
+        notification
+    }
+    
+    pub fn from_event(event: &MonitorEvent) -> Option<Self> {
+        let alert_type = match &event.event_type {
+            EventType::Alert(alert_type) => alert_type.clone(),
+            _ => return None,
+        };
+        
+        let message = event
+            .data
+            .get("message")
+            .and_then(|m| m.as_str())
+            .unwrap_or_default()
+            .to_string();
+        
+        Some(Self {
+            id: event.id,
+            timestamp: event.timestamp,
+            enqueued_at: Utc::now(),
+            title: format!("{:?} alert from {:?}", alert_type, event.source),
+            alert_type,
+            message,
+            data: Some(event.data.clone()),
+            anomaly: None,
+        })
+    }
 }
 
 #[async_trait]
@@ -50,77 +150,117 @@ pub trait NotificationChannel: Send + Sync + Debug {
     fn is_enabled(&self) -> bool;
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct NotificationConfig {
-    pub telegram: TelegramConfig,
-    pub wechat: WeChatConfig,
-    pub email: EmailConfig,
-    pub sms: SmsConfig,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TelegramConfig {
-    pub enabled: bool,
-    pub bot_token: String,
-    pub chat_ids: Vec<String>,
-    pub send_images: bool,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct WeChatConfig {
-    pub enabled: bool,
-    pub corp_id: String,
-    pub agent_id: String,
-    pub secret: String,
-    pub to_user: Vec<String>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct EmailConfig {
-    pub enabled: bool,
-    pub smtp_host: String,
-    pub smtp_port: u16,
-    pub username: String,
-    pub password: String,
-    pub from_address: String,
-    pub to_addresses: Vec<String>,
-    pub use_tls: bool,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SmsConfig {
-    pub enabled: bool,
-    pub provider: SmsProvider,
-    pub from_number: String,
-    pub to_numbers: Vec<String>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum SmsProvider {
-    Twilio {
-        account_sid: String,
-        auth_token: String,
-    },
-    Aliyun {
-        access_key_id: String,
-        access_key_secret: String,
-        sign_name: String,
-        template_code: String,
-    },
-}
-
 pub fn format_notification_message(notification: &Notification) -> String {
     let emoji = match notification.alert_type {
         AlertType::Critical => "🚨",
         AlertType::Warning => "⚠️",
-        AlertType::Info => "ℹ️",
+        _ => "ℹ️",
     };
     
+    let severity = notification
+        .anomaly
+        .as_ref()
+        .map(|a| {
+            format!("_Severity: {:?}, confidence {:.0}%_\n", a.severity, a.confidence * 100.0)
+        })
+        .unwrap_or_default();
+    let figures = notification
+        .anomaly
+        .as_ref()
+        .and_then(|a| a.reason.as_ref())
+        .map(format_reason_figures)
+        .unwrap_or_default();
+    
     format!(
-        "{} *{}*\n\n{}\n\n_Time: {}_",
+        "{} *{}*\n\n{}\n\n{}{}_Time: {}_",
         emoji,
         notification.title,
         notification.message,
+        figures,
+        severity,
         notification.timestamp.format("%Y-%m-%d %H:%M:%S UTC")
     )
-}
\ No newline at end of file
+}
+
+// Read from the structured reason rather than the description, so every detector's figures
+// come out in the same layout
+fn format_reason_figures(reason: &AnomalyReason) -> String {
+    let mut figures = format!("_Observed {:.4}", reason.observed);
+    if let Some(baseline) = reason.baseline {
+        figures.push_str(&format!(" against {:.4}", baseline));
+    }
+    if let Some(threshold) = reason.threshold {
+        figures.push_str(&format!(", threshold {:.4}", threshold));
+    }
+    if let Some(window_secs) = reason.window_secs {
+        figures.push_str(&format!(" over {:.0}s", window_secs));
+    }
+    figures.push_str("_\n");
+    figures
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use monitor_anomaly::{reason::ReasonKind, AnomalyMetrics};
+
+    fn anomaly() -> AnomalyDetection {
+        AnomalyDetection {
+            id: uuid::Uuid::new_v4(),
+            timestamp: Utc::now(),
+            symbol: "BTC/USDT".to_string(),
+            exchange: "binance".to_string(),
+            anomaly_type: AnomalyType::VolumeSpike,
+            severity: AnomalySeverity::High,
+            confidence: 0.834,
+            metrics: AnomalyMetrics {
+                current_value: 60.0,
+                expected_value: 15.0,
+                deviation: 45.0,
+                z_score: Some(9.0),
+                percentage_change: Some(300.0),
+                historical_avg: Some(15.0),
+                historical_std: Some(5.0),
+                ..Default::default()
+            },
+            description: "Volume spike".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_anomaly_message_shows_confidence() {
+        let notification = Notification::from_anomaly(&anomaly());
+        assert_eq!(notification.anomaly.as_ref().unwrap().confidence, 0.834);
+        let message = format_notification_message(&notification);
+        assert!(message.contains("_Severity: High, confidence 83%_"));
+        assert!(!message.contains("_Observed"));
+
+        let mut plain = notification;
+        plain.anomaly = None;
+        assert!(!format_notification_message(&plain).contains("confidence"));
+    }
+
+    #[test]
+    fn test_anomaly_message_lays_out_reason_figures() {
+        let mut anomaly = anomaly();
+        let reason = AnomalyReason::new(
+            "binance",
+            "BTC/USDT",
+            60.0,
+            ReasonKind::CandleVolume { interval: "5m".to_string(), multiple: 4.0 },
+        )
+        .with_baseline(15.0)
+        .with_threshold(3.0)
+        .with_window_secs(300.0);
+        anomaly.description = reason.to_string();
+        anomaly.reason = Some(reason.clone());
+
+        let notification = Notification::from_anomaly(&anomaly);
+        assert_eq!(notification.anomaly.as_ref().unwrap().reason, Some(reason));
+        let message = format_notification_message(&notification);
+        assert!(message.contains("volume 60.00 is 4.0x the average 15.00"));
+        assert!(message.contains(
+            "_Observed 60.0000 against 15.0000, threshold 3.0000 over 300s_\n_Severity: High"
+        ));
+    }
+}
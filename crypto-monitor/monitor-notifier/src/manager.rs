@@ -1,40 +1,221 @@
-use crate::{Notification, NotificationChannel, NotificationConfig};
+use crate::{
+    ack::{AckConfig, AckFilter, Acknowledgement, AlertLog, BulkAck},
+    routing::{self, Route, RoutingConfig},
+    slo::{DeliveryTracker, SloConfig},
+    ChannelDelivery, DeliveryReport, Notification, NotificationChannel, NotificationConfig,
+};
+use chrono::{DateTime, Utc};
 use monitor_core::Result;
+use sqlx::PgPool;
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 pub struct NotificationManager {
     channels: Arc<RwLock<Vec<Box<dyn NotificationChannel>>>>,
+    routing: RwLock<RoutingConfig>,
+    tracker: Arc<DeliveryTracker>,
+    alerts: Arc<AlertLog>,
+    storage: Option<PgPool>,
 }
 
 impl NotificationManager {
     pub fn new() -> Self {
         Self {
             channels: Arc::new(RwLock::new(Vec::new())),
+            routing: RwLock::new(RoutingConfig::default()),
+            tracker: Arc::new(DeliveryTracker::new(SloConfig::default())),
+            alerts: Arc::new(AlertLog::new(AckConfig::default())),
+            storage: None,
         }
     }
     
+    pub fn with_routing(mut self, routing: RoutingConfig) -> Self {
+        self.routing = RwLock::new(routing);
+        self
+    }
+    
+    pub fn with_slo(mut self, slo: SloConfig) -> Self {
+        self.tracker = Arc::new(DeliveryTracker::new(slo));
+        self
+    }
+    
+    pub fn with_acks(mut self, config: AckConfig) -> Self {
+        self.alerts = Arc::new(AlertLog::new(config));
+        self
+    }
+    
+    // Sent alerts and their acknowledgements are persisted to the alerts table
+    pub fn with_storage(mut self, storage: PgPool) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+    
+    pub fn delivery_tracker(&self) -> Arc<DeliveryTracker> {
+        self.tracker.clone()
+    }
+    
+    pub fn alert_log(&self) -> Arc<AlertLog> {
+        self.alerts.clone()
+    }
+    
     pub fn add_channel(&mut self, channel: Box<dyn NotificationChannel>) {
-        let channels = self.channels.clone();
-        tokio::spawn(async move {
-            channels.write().await.push(channel);
-        });
+        match self.channels.try_write() {
+            Ok(mut channels) => channels.push(channel),
+            Err(_) => {
+                let channels = self.channels.clone();
+                tokio::spawn(async move {
+                    channels.write().await.push(channel);
+                });
+            }
+        }
+    }
+    
+    pub async fn routing(&self) -> RoutingConfig {
+        self.routing.read().await.clone()
+    }
+    
+    pub async fn set_routing(&self, routing: RoutingConfig) -> Result<()> {
+        routing.validate()?;
+        info!("Notification routing updated ({} rules)", routing.rules.len());
+        *self.routing.write().await = routing;
+        Ok(())
+    }
+    
+    pub async fn route(&self, notification: &Notification) -> Route {
+        routing::route(&*self.routing.read().await, notification)
     }
     
-    pub async fn send_all(&self, notification: &Notification) -> Result<()> {
+    pub async fn send_all(&self, notification: &Notification) -> Result<DeliveryReport> {
+        self.alerts.record(notification);
+        let report = self.dispatch(notification).await;
+        self.tracker.record(&report);
+        
+        if let Some(storage) = &self.storage {
+            if let Err(e) = AlertLog::save_alert(storage, notification, &report).await {
+                error!("Failed to persist alert {}: {}", notification.id, e);
+            }
+        }
+        
+        // The meta-alert itself is left out of the tracked deliveries
+        if let Some(alert) = self.tracker.check_breach(Utc::now()) {
+            warn!("{}", alert.message);
+            self.dispatch(&alert).await;
+        }
+        
+        Ok(report)
+    }
+    
+    // `None` when the alert is not among the recent alerts kept in the log
+    pub async fn acknowledge(
+        &self,
+        id: uuid::Uuid,
+        acked_by: &str,
+        note: Option<String>,
+    ) -> Result<Option<Acknowledgement>> {
+        let now = Utc::now();
+        let pending = self.alerts.pending_ack(id, acked_by, note.clone(), now);
+        // Persisted before the history changes, so a failed write leaves the alert outstanding.
+        // Alerts older than the history are still acknowledged in the database
+        let ack = match &self.storage {
+            Some(storage) => {
+                let ack = pending.clone().unwrap_or_else(|| Acknowledgement {
+                    alert_id: id,
+                    acked_by: acked_by.to_string(),
+                    acked_at: now,
+                    note,
+                });
+                // Alerts are stored once delivered, one still being delivered is only in memory
+                ack.save_stored(storage).await?.or(pending)
+            }
+            None => pending,
+        };
+        let Some(ack) = ack else {
+            return Ok(None);
+        };
+        self.alerts.apply(std::slice::from_ref(&ack));
+        info!("Alert {} acknowledged by {}", id, ack.acked_by);
+        Ok(Some(ack))
+    }
+    
+    pub async fn bulk_acknowledge(
+        &self,
+        filter: &AckFilter,
+        acked_by: &str,
+        note: Option<String>,
+    ) -> Result<BulkAck> {
+        let result = self.alerts.bulk_matches(filter, acked_by, note, Utc::now())?;
+        // All persisted before any is shown as acknowledged
+        if let Some(storage) = &self.storage {
+            Acknowledgement::save_all(&result.acknowledgements, storage).await?;
+            result.audit(storage, filter, acked_by).await?;
+        }
+        self.alerts.apply(&result.acknowledgements);
+        info!(
+            "Bulk ack by {} cleared {} of {} matching alerts",
+            acked_by,
+            result.acknowledgements.len(),
+            result.matched
+        );
+        Ok(result)
+    }
+    
+    // Re-sends critical alerts nobody acknowledged in time, acknowledged ones are never escalated
+    pub async fn escalate_unacked(&self, now: DateTime<Utc>) -> usize {
+        let escalations = self.alerts.due_escalations(now);
+        for escalation in &escalations {
+            warn!("{}", escalation.title);
+            self.dispatch(escalation).await;
+        }
+        escalations.len()
+    }
+    
+    pub async fn send_digest(&self, now: DateTime<Utc>) -> Option<DeliveryReport> {
+        let digest = self.alerts.digest(now)?;
+        Some(self.dispatch(&digest).await)
+    }
+    
+    async fn dispatch(&self, notification: &Notification) -> DeliveryReport {
+        let route = self.route(notification).await;
         let channels = self.channels.read().await;
         
-        for channel in channels.iter() {
-            if channel.is_enabled() {
-                info!("Sending notification via {}", channel.name());
-                if let Err(e) = channel.send(notification).await {
-                    error!("Failed to send via {}: {}", channel.name(), e);
+        let mut report = DeliveryReport {
+            notification_id: notification.id,
+            rule: route.rule,
+            alert_type: notification.alert_type.clone(),
+            detected_at: notification.timestamp,
+            enqueued_at: notification.enqueued_at,
+            deliveries: Vec::new(),
+        };
+        
+        match &route.channels {
+            None => {
+                for channel in channels.iter().filter(|c| c.is_enabled()) {
+                    report.deliveries.push(deliver(channel.as_ref(), notification).await);
+                }
+            }
+            Some(names) => {
+                for name in names {
+                    match channels.iter().find(|c| c.name().eq_ignore_ascii_case(name)) {
+                        Some(channel) if channel.is_enabled() => {
+                            report.deliveries.push(deliver(channel.as_ref(), notification).await)
+                        }
+                        found => {
+                            let reason = if found.is_some() { "disabled" } else { "not configured" };
+                            warn!("Routing rule {} targets {} channel {}", report.rule, reason, name);
+                            report.deliveries.push(ChannelDelivery {
+                                channel: name.clone(),
+                                success: false,
+                                error: Some(format!("Channel {}", reason)),
+                                completed_at: Utc::now(),
+                            });
+                        }
+                    }
                 }
             }
         }
         
-        Ok(())
+        report
     }
     
     pub async fn send_to_channel(
@@ -63,4 +244,164 @@ impl NotificationManager {
             .map(|c| c.name().to_string())
             .collect()
     }
-}
\ No newline at end of file
+}
+
+async fn deliver(channel: &dyn NotificationChannel, notification: &Notification) -> ChannelDelivery {
+    info!("Sending notification via {}", channel.name());
+    let result = channel.send(notification).await;
+    if let Err(e) = &result {
+        error!("Failed to send via {}: {}", channel.name(), e);
+    }
+
+    ChannelDelivery {
+        channel: channel.name().to_string(),
+        success: result.is_ok(),
+        error: result.err().map(|e| e.to_string()),
+        completed_at: Utc::now(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{routing::RoutingRule, AnomalyContext};
+    use async_trait::async_trait;
+    use monitor_anomaly::AnomalySeverity;
+    use monitor_core::{AlertType, AnomalyType};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Debug)]
+    struct CountingChannel {
+        name: &'static str,
+        sent: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl NotificationChannel for CountingChannel {
+        async fn send(&self, _notification: &Notification) -> Result<()> {
+            self.sent.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn is_enabled(&self) -> bool {
+            true
+        }
+    }
+
+    #[tokio::test]
+    async fn test_report_records_matched_rule() {
+        let telegram = Arc::new(AtomicUsize::new(0));
+        let email = Arc::new(AtomicUsize::new(0));
+        let mut manager = NotificationManager::new().with_routing(RoutingConfig {
+            rules: vec![RoutingRule {
+                name: "desk".to_string(),
+                anomaly_types: vec![AnomalyType::VolumeSpike],
+                min_severity: None,
+                exchanges: Vec::new(),
+                symbol_pattern: None,
+                channels: vec!["telegram".to_string()],
+            }],
+            default_channels: None,
+        });
+        manager.add_channel(Box::new(CountingChannel { name: "Telegram", sent: telegram.clone() }));
+        manager.add_channel(Box::new(CountingChannel { name: "Email", sent: email.clone() }));
+
+        let mut notification = Notification {
+            id: uuid::Uuid::new_v4(),
+            timestamp: chrono::Utc::now(),
+            enqueued_at: chrono::Utc::now(),
+            alert_type: AlertType::Warning,
+            title: "spike".to_string(),
+            message: "spike".to_string(),
+            data: None,
+            anomaly: Some(AnomalyContext {
+                anomaly_type: AnomalyType::VolumeSpike,
+                severity: AnomalySeverity::High,
+                confidence: 0.8,
+                exchange: "binance".to_string(),
+                symbol: "BTC/USDT".to_string(),
+                reason: None,
+            }),
+        };
+
+        let report = manager.send_all(&notification).await.unwrap();
+        assert_eq!(report.rule, "desk");
+        assert_eq!(report.deliveries.len(), 1);
+        assert_eq!((telegram.load(Ordering::SeqCst), email.load(Ordering::SeqCst)), (1, 0));
+
+        // No rule matches, every enabled channel receives it
+        notification.anomaly = None;
+        let report = manager.send_all(&notification).await.unwrap();
+        assert_eq!(report.rule, "default");
+        assert_eq!((telegram.load(Ordering::SeqCst), email.load(Ordering::SeqCst)), (2, 1));
+    }
+
+    #[derive(Debug)]
+    struct DelayedChannel {
+        delay: std::time::Duration,
+        titles: Arc<parking_lot::Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl NotificationChannel for DelayedChannel {
+        async fn send(&self, notification: &Notification) -> Result<()> {
+            tokio::time::sleep(self.delay).await;
+            self.titles.lock().push(notification.title.clone());
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            "Telegram"
+        }
+
+        fn is_enabled(&self) -> bool {
+            true
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slo_breach_sends_rate_limited_meta_alert() {
+        let titles = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let mut manager = NotificationManager::new().with_slo(SloConfig {
+            min_samples: 2,
+            ..SloConfig::default()
+        });
+        manager.add_channel(Box::new(DelayedChannel {
+            delay: std::time::Duration::from_millis(150),
+            titles: titles.clone(),
+        }));
+
+        let critical = |detected_ago_ms: i64| Notification {
+            id: uuid::Uuid::new_v4(),
+            timestamp: chrono::Utc::now() - chrono::Duration::milliseconds(detected_ago_ms),
+            enqueued_at: chrono::Utc::now(),
+            alert_type: AlertType::Critical,
+            title: "critical".to_string(),
+            message: "critical".to_string(),
+            data: None,
+            anomaly: None,
+        };
+
+        let report = manager.send_all(&critical(0)).await.unwrap();
+        assert!(report.deliveries[0].completed_at - report.detected_at >= chrono::Duration::milliseconds(150));
+        assert_eq!(manager.delivery_tracker().status(chrono::Utc::now()).compliance_pct, Some(100.0));
+
+        // Detected just under the target, the channel delay pushes it over
+        manager.send_all(&critical(29_900)).await.unwrap();
+        let status = manager.delivery_tracker().status(chrono::Utc::now());
+        assert_eq!((status.notifications, status.within_target), (2, 1));
+        assert!(status.breached);
+        assert!(status.last_alert_at.is_some());
+
+        manager.send_all(&critical(29_900)).await.unwrap();
+        let titles = titles.lock().clone();
+        let meta: Vec<_> = titles.iter().filter(|t| t.contains("SLO breached")).collect();
+        assert_eq!(titles.len(), 4);
+        assert_eq!(meta.len(), 1);
+        assert_eq!(manager.delivery_tracker().status(chrono::Utc::now()).notifications, 3);
+    }
+}
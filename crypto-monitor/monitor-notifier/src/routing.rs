@@ -0,0 +1,187 @@
+use crate::Notification;
+// Rules read from the monitor config and saved through the API
+pub use monitor_core::notification::{RoutingConfig, RoutingRule, DEFAULT_RULE};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Route {
+    pub rule: String,
+    // `None` means every enabled channel
+    pub channels: Option<Vec<String>>,
+}
+
+// Whether the notification meets every criterion the rule sets
+pub fn rule_matches(rule: &RoutingRule, notification: &Notification) -> bool {
+    let needs_anomaly = !rule.anomaly_types.is_empty()
+        || rule.min_severity.is_some()
+        || !rule.exchanges.is_empty()
+        || rule.symbol_pattern.is_some();
+
+    let Some(anomaly) = &notification.anomaly else {
+        return !needs_anomaly;
+    };
+
+    (rule.anomaly_types.is_empty() || rule.anomaly_types.contains(&anomaly.anomaly_type))
+        && rule
+            .min_severity
+            .as_ref()
+            .is_none_or(|min| anomaly.severity >= *min)
+        && (rule.exchanges.is_empty()
+            || rule
+                .exchanges
+                .iter()
+                .any(|e| e.eq_ignore_ascii_case(&anomaly.exchange)))
+        && rule
+            .symbol_pattern
+            .as_deref()
+            .is_none_or(|pattern| glob_match(pattern, &anomaly.symbol))
+}
+
+// The first matching rule decides the channels, the config's defaults apply when none does
+pub fn route(config: &RoutingConfig, notification: &Notification) -> Route {
+    match config.rules.iter().find(|rule| rule_matches(rule, notification)) {
+        Some(rule) => Route {
+            rule: rule.name.clone(),
+            channels: Some(rule.channels.clone()),
+        },
+        None => Route {
+            rule: DEFAULT_RULE.to_string(),
+            channels: config.default_channels.clone(),
+        },
+    }
+}
+
+pub fn glob_match(pattern: &str, value: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let value: Vec<char> = value.to_lowercase().chars().collect();
+
+    // Iterative wildcard match with backtracking to the last `*`
+    let (mut p, mut v) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while v < value.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == value[v]) {
+            p += 1;
+            v += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, v));
+            p += 1;
+        } else if let Some((star_p, star_v)) = star {
+            p = star_p + 1;
+            v = star_v + 1;
+            star = Some((star_p, star_v + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AnomalyContext;
+    use monitor_anomaly::AnomalySeverity;
+    use monitor_core::{AlertType, AnomalyType};
+
+    fn notification(anomaly_type: AnomalyType, severity: AnomalySeverity, symbol: &str) -> Notification {
+        Notification {
+            id: uuid::Uuid::new_v4(),
+            timestamp: chrono::Utc::now(),
+            enqueued_at: chrono::Utc::now(),
+            alert_type: AlertType::Warning,
+            title: "test".to_string(),
+            message: "test".to_string(),
+            data: None,
+            anomaly: Some(AnomalyContext {
+                anomaly_type,
+                confidence: severity.confidence_floor(),
+                severity,
+                exchange: "binance".to_string(),
+                symbol: symbol.to_string(),
+                reason: None,
+            }),
+        }
+    }
+
+    fn rule(name: &str, channels: &[&str]) -> RoutingRule {
+        RoutingRule {
+            name: name.to_string(),
+            anomaly_types: Vec::new(),
+            min_severity: None,
+            exchanges: Vec::new(),
+            symbol_pattern: None,
+            channels: channels.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+
+    fn config() -> RoutingConfig {
+        RoutingConfig {
+            rules: vec![
+                RoutingRule {
+                    anomaly_types: vec![AnomalyType::VolumeSpike],
+                    symbol_pattern: Some("BTC/*".to_string()),
+                    ..rule("desk-btc", &["Telegram"])
+                },
+                RoutingRule {
+                    anomaly_types: vec![AnomalyType::VolumeSpike],
+                    ..rule("desk", &["Telegram", "SMS"])
+                },
+                RoutingRule {
+                    anomaly_types: vec![AnomalyType::UnusualActivity],
+                    min_severity: Some(AnomalySeverity::High),
+                    ..rule("compliance", &["Email"])
+                },
+            ],
+            default_channels: None,
+        }
+    }
+
+    #[test]
+    fn test_first_matching_rule_wins() {
+        let config = config();
+
+        let btc = notification(AnomalyType::VolumeSpike, AnomalySeverity::Low, "BTC/USDT");
+        let routed = route(&config, &btc);
+        assert_eq!(routed.rule, "desk-btc");
+        assert_eq!(routed.channels, Some(vec!["Telegram".to_string()]));
+
+        let eth = notification(AnomalyType::VolumeSpike, AnomalySeverity::Low, "ETH/USDT");
+        assert_eq!(route(&config, &eth).rule, "desk");
+    }
+
+    #[test]
+    fn test_severity_threshold_and_default_fallback() {
+        let config = config();
+
+        let high = notification(AnomalyType::UnusualActivity, AnomalySeverity::Critical, "ETH/USDT");
+        assert_eq!(route(&config, &high).rule, "compliance");
+
+        let medium = notification(AnomalyType::UnusualActivity, AnomalySeverity::Medium, "ETH/USDT");
+        let routed = route(&config, &medium);
+        assert_eq!(routed.rule, DEFAULT_RULE);
+        assert_eq!(routed.channels, None);
+
+        // System alerts carry no anomaly and skip anomaly-specific rules
+        let mut system = medium.clone();
+        system.anomaly = None;
+        assert_eq!(route(&config, &system).rule, DEFAULT_RULE);
+    }
+
+    #[test]
+    fn test_glob_patterns() {
+        assert!(glob_match("BTC/*", "btc/usdt"));
+        assert!(glob_match("*/USD?", "ETH/USDT"));
+        assert!(glob_match("*", "anything"));
+        assert!(!glob_match("BTC/*", "ETH/BTC"));
+        assert!(!glob_match("*/USD", "ETH/USDT"));
+        assert!(glob_match("*USD*T", "BTC-USDT"));
+    }
+
+    #[test]
+    fn test_validation_rejects_duplicates() {
+        let mut config = config();
+        config.rules.push(rule("desk", &["Email"]));
+        assert!(config.validate().is_err());
+        assert!(self::config().validate().is_ok());
+    }
+}
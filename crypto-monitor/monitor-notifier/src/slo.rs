@@ -0,0 +1,353 @@
+use crate::{DeliveryReport, Notification};
+use chrono::{DateTime, Duration, Utc};
+use monitor_core::AlertType;
+pub use monitor_core::notification::SloConfig;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// Upper bounds in seconds, deliveries slower than the last bound only show up in `count`
+const LATENCY_BUCKETS_SECS: [f64; 10] = [0.5, 1.0, 2.0, 5.0, 10.0, 15.0, 30.0, 60.0, 120.0, 300.0];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LatencyHistogram {
+    // Cumulative counts per upper bound, like a Prometheus histogram
+    pub buckets: Vec<(f64, u64)>,
+    pub count: u64,
+    pub sum_secs: f64,
+}
+
+impl LatencyHistogram {
+    fn observe(&mut self, secs: f64) {
+        if self.buckets.is_empty() {
+            self.buckets = LATENCY_BUCKETS_SECS.iter().map(|le| (*le, 0)).collect();
+        }
+        for (le, count) in self.buckets.iter_mut() {
+            if secs <= *le {
+                *count += 1;
+            }
+        }
+        self.count += 1;
+        self.sum_secs += secs;
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SloStatus {
+    pub alert_type: AlertType,
+    pub target_secs: f64,
+    pub objective_pct: f64,
+    pub window_hours: u64,
+    pub notifications: usize,
+    pub within_target: usize,
+    // `None` until the window holds any notification
+    pub compliance_pct: Option<f64>,
+    pub breached: bool,
+    pub last_alert_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeliveryMetrics {
+    // Detection to channel completion, successful deliveries only
+    pub channels: HashMap<String, LatencyHistogram>,
+    pub channel_failures: HashMap<String, u64>,
+    // Detection to first successful delivery, keyed by alert type
+    pub severities: HashMap<String, LatencyHistogram>,
+    pub slo: SloStatus,
+}
+
+impl DeliveryMetrics {
+    // Prometheus text exposition of the histograms, failure counters and SLO gauges, labels in
+    // sorted order so scrapes diff cleanly
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        write_histograms(
+            &mut out,
+            "notification_delivery_latency_seconds",
+            "Detection to channel delivery, successful deliveries only",
+            "channel",
+            &self.channels,
+        );
+        write_histograms(
+            &mut out,
+            "notification_first_delivery_latency_seconds",
+            "Detection to the first successful delivery of a notification",
+            "alert_type",
+            &self.severities,
+        );
+
+        out.push_str("# HELP notification_channel_failures_total Failed channel deliveries\n");
+        out.push_str("# TYPE notification_channel_failures_total counter\n");
+        let mut failures: Vec<_> = self.channel_failures.iter().collect();
+        failures.sort();
+        for (channel, count) in failures {
+            out.push_str(&format!(
+                "notification_channel_failures_total{{channel=\"{}\"}} {}\n",
+                escape_label(channel),
+                count
+            ));
+        }
+
+        let alert_type = format!("alert_type=\"{:?}\"", self.slo.alert_type);
+        let mut gauge = |name: &str, help: &str, value: f64| {
+            out.push_str(&format!("# HELP {} {}\n# TYPE {} gauge\n", name, help, name));
+            out.push_str(&format!("{}{{{}}} {}\n", name, alert_type, value));
+        };
+        gauge(
+            "notification_slo_notifications",
+            "Notifications held to the SLO in its window",
+            self.slo.notifications as f64,
+        );
+        if let Some(compliance) = self.slo.compliance_pct {
+            gauge(
+                "notification_slo_compliance_percent",
+                "Share of notifications delivered within the SLO target",
+                compliance,
+            );
+        }
+        gauge(
+            "notification_slo_breached",
+            "1 while compliance is below the objective",
+            if self.slo.breached { 1.0 } else { 0.0 },
+        );
+        out
+    }
+}
+
+fn write_histograms(
+    out: &mut String,
+    name: &str,
+    help: &str,
+    label: &str,
+    histograms: &HashMap<String, LatencyHistogram>,
+) {
+    out.push_str(&format!("# HELP {} {}\n# TYPE {} histogram\n", name, help, name));
+    let mut histograms: Vec<_> = histograms.iter().collect();
+    histograms.sort_by(|a, b| a.0.cmp(b.0));
+    for (key, histogram) in histograms {
+        let labels = format!("{}=\"{}\"", label, escape_label(key));
+        for (le, count) in &histogram.buckets {
+            out.push_str(&format!("{}_bucket{{{},le=\"{}\"}} {}\n", name, labels, le, count));
+        }
+        out.push_str(&format!(
+            "{}_bucket{{{},le=\"+Inf\"}} {}\n",
+            name, labels, histogram.count
+        ));
+        out.push_str(&format!("{}_sum{{{}}} {}\n", name, labels, histogram.sum_secs));
+        out.push_str(&format!("{}_count{{{}}} {}\n", name, labels, histogram.count));
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[derive(Default)]
+struct TrackerState {
+    channels: HashMap<String, LatencyHistogram>,
+    channel_failures: HashMap<String, u64>,
+    severities: HashMap<String, LatencyHistogram>,
+    // (detected_at, seconds to first delivery or `None` if every channel failed)
+    samples: Vec<(DateTime<Utc>, Option<f64>)>,
+    last_alert_at: Option<DateTime<Utc>>,
+}
+
+pub struct DeliveryTracker {
+    config: SloConfig,
+    state: Mutex<TrackerState>,
+}
+
+impl DeliveryTracker {
+    pub fn new(config: SloConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(TrackerState::default()),
+        }
+    }
+
+    pub fn config(&self) -> &SloConfig {
+        &self.config
+    }
+
+    pub fn record(&self, report: &DeliveryReport) {
+        let latency = |at: DateTime<Utc>| (at - report.detected_at).num_milliseconds().max(0) as f64 / 1000.0;
+        let mut state = self.state.lock();
+
+        let mut first = None::<f64>;
+        for delivery in &report.deliveries {
+            if !delivery.success {
+                *state.channel_failures.entry(delivery.channel.clone()).or_default() += 1;
+                continue;
+            }
+            let secs = latency(delivery.completed_at);
+            state.channels.entry(delivery.channel.clone()).or_default().observe(secs);
+            first = Some(first.map_or(secs, |f| f.min(secs)));
+        }
+
+        if let Some(secs) = first {
+            state
+                .severities
+                .entry(format!("{:?}", report.alert_type))
+                .or_default()
+                .observe(secs);
+        }
+        if report.alert_type == self.config.alert_type {
+            state.samples.push((report.detected_at, first));
+        }
+    }
+
+    pub fn status(&self, now: DateTime<Utc>) -> SloStatus {
+        let mut state = self.state.lock();
+        self.status_locked(&mut state, now)
+    }
+
+    pub fn metrics(&self, now: DateTime<Utc>) -> DeliveryMetrics {
+        let mut state = self.state.lock();
+        let slo = self.status_locked(&mut state, now);
+
+        DeliveryMetrics {
+            channels: state.channels.clone(),
+            channel_failures: state.channel_failures.clone(),
+            severities: state.severities.clone(),
+            slo,
+        }
+    }
+
+    // A meta-alert when the SLO is breached, at most once per `alert_interval_secs`
+    pub fn check_breach(&self, now: DateTime<Utc>) -> Option<Notification> {
+        let mut state = self.state.lock();
+        let status = self.status_locked(&mut state, now);
+        if !status.breached {
+            return None;
+        }
+        let interval = Duration::seconds(self.config.alert_interval_secs as i64);
+        if state.last_alert_at.is_some_and(|last| now - last < interval) {
+            return None;
+        }
+        state.last_alert_at = Some(now);
+
+        let compliance = status.compliance_pct.unwrap_or_default();
+        Some(Notification {
+            id: uuid::Uuid::new_v4(),
+            timestamp: now,
+            enqueued_at: now,
+            alert_type: AlertType::Warning,
+            title: format!("Notification SLO breached for {:?} alerts", status.alert_type),
+            message: format!(
+                "{:.1}% of {} {:?} notifications delivered within {}s over {}h, objective {}%",
+                compliance,
+                status.notifications,
+                status.alert_type,
+                status.target_secs,
+                status.window_hours,
+                status.objective_pct
+            ),
+            data: serde_json::to_value(&status).ok(),
+            anomaly: None,
+        })
+    }
+
+    fn status_locked(&self, state: &mut TrackerState, now: DateTime<Utc>) -> SloStatus {
+        let cutoff = now - Duration::hours(self.config.window_hours as i64);
+        // Reports can arrive out of detection order, so the whole window is scanned
+        state.samples.retain(|(at, _)| *at >= cutoff);
+
+        let notifications = state.samples.len();
+        let within_target = state
+            .samples
+            .iter()
+            .filter(|(_, secs)| secs.is_some_and(|s| s < self.config.target_secs))
+            .count();
+        let compliance_pct =
+            (notifications > 0).then(|| within_target as f64 / notifications as f64 * 100.0);
+
+        SloStatus {
+            alert_type: self.config.alert_type.clone(),
+            target_secs: self.config.target_secs,
+            objective_pct: self.config.objective_pct,
+            window_hours: self.config.window_hours,
+            notifications,
+            within_target,
+            compliance_pct,
+            breached: notifications >= self.config.min_samples.max(1)
+                && compliance_pct.is_some_and(|pct| pct < self.config.objective_pct),
+            last_alert_at: state.last_alert_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ChannelDelivery;
+
+    fn report(alert_type: AlertType, detected_at: DateTime<Utc>, delays: &[(&str, Option<i64>)]) -> DeliveryReport {
+        DeliveryReport {
+            notification_id: uuid::Uuid::new_v4(),
+            rule: "default".to_string(),
+            alert_type,
+            detected_at,
+            enqueued_at: detected_at,
+            deliveries: delays
+                .iter()
+                .map(|(channel, delay)| ChannelDelivery {
+                    channel: channel.to_string(),
+                    success: delay.is_some(),
+                    error: None,
+                    completed_at: detected_at + Duration::seconds(delay.unwrap_or(0)),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_compliance_uses_first_delivery_within_window() {
+        let tracker = DeliveryTracker::new(SloConfig::default());
+        let now = Utc::now();
+
+        // Email is slow but Telegram got there in time
+        tracker.record(&report(AlertType::Critical, now, &[("Telegram", Some(3)), ("Email", Some(45))]));
+        tracker.record(&report(AlertType::Critical, now, &[("Telegram", Some(31))]));
+        tracker.record(&report(AlertType::Critical, now, &[("Telegram", None)]));
+        // Outside the SLO's alert type or window
+        tracker.record(&report(AlertType::Info, now, &[("Telegram", Some(90))]));
+        tracker.record(&report(AlertType::Critical, now - Duration::hours(25), &[("Telegram", None)]));
+
+        let metrics = tracker.metrics(now);
+        assert_eq!((metrics.slo.notifications, metrics.slo.within_target), (3, 1));
+        assert!((metrics.slo.compliance_pct.unwrap() - 100.0 / 3.0).abs() < 1e-9);
+        // Fewer notifications than min_samples
+        assert!(!metrics.slo.breached);
+
+        let telegram = &metrics.channels["Telegram"];
+        assert_eq!((telegram.count, telegram.sum_secs), (3, 124.0));
+        assert_eq!(telegram.buckets.iter().find(|(le, _)| *le == 5.0).unwrap().1, 1);
+        assert_eq!(metrics.channel_failures["Telegram"], 2);
+        assert_eq!(metrics.severities["Critical"].count, 2);
+    }
+
+    #[test]
+    fn test_metrics_export_in_prometheus_text_format() {
+        let tracker = DeliveryTracker::new(SloConfig::default());
+        let now = Utc::now();
+        let delays = [("Telegram", Some(3)), ("Email", None)];
+        tracker.record(&report(AlertType::Critical, now, &delays));
+
+        let text = tracker.metrics(now).to_prometheus();
+        let lines: Vec<&str> = text.lines().collect();
+        for line in [
+            "# TYPE notification_delivery_latency_seconds histogram",
+            "notification_delivery_latency_seconds_bucket{channel=\"Telegram\",le=\"2\"} 0",
+            "notification_delivery_latency_seconds_bucket{channel=\"Telegram\",le=\"5\"} 1",
+            "notification_delivery_latency_seconds_bucket{channel=\"Telegram\",le=\"+Inf\"} 1",
+            "notification_delivery_latency_seconds_sum{channel=\"Telegram\"} 3",
+            "notification_first_delivery_latency_seconds_count{alert_type=\"Critical\"} 1",
+            "notification_channel_failures_total{channel=\"Email\"} 1",
+            "notification_slo_compliance_percent{alert_type=\"Critical\"} 100",
+            "notification_slo_breached{alert_type=\"Critical\"} 0",
+        ] {
+            assert!(lines.contains(&line), "missing {}", line);
+        }
+        assert_eq!(escape_label("a\"b\\c"), "a\\\"b\\\\c");
+    }
+}
@@ -0,0 +1,37 @@
+use sqlx::{migrate::Migrator, postgres::PgPoolOptions, Executor, PgPool};
+
+// Postgres the database tests run against. They are marked #[ignore] and run with
+// `cargo test -- --ignored` once it is set
+pub const DATABASE_URL_ENV: &str = "TEST_DATABASE_URL";
+
+static MIGRATOR: Migrator = sqlx::migrate!("../migrations");
+
+// A pool on a schema of its own with every migration applied, so tests run against the
+// tables the app creates and never see each other's rows. Panics when TEST_DATABASE_URL is
+// not set: a database test that was asked for does not pass without a database
+pub async fn migrated_pool() -> PgPool {
+    let url = std::env::var(DATABASE_URL_ENV)
+        .unwrap_or_else(|_| panic!("{} must be set to run the database tests", DATABASE_URL_ENV));
+    let schema = format!("test_{}", uuid::Uuid::new_v4().simple());
+
+    let admin = PgPoolOptions::new().max_connections(1).connect(&url).await.unwrap();
+    admin.execute(format!("CREATE SCHEMA {}", schema).as_str()).await.unwrap();
+    admin.close().await;
+
+    // Extensions already installed stay reachable in public
+    let search_path = format!("SET search_path TO {}, public", schema);
+    let pool = PgPoolOptions::new()
+        .max_connections(4)
+        .after_connect(move |conn, _| {
+            let search_path = search_path.clone();
+            Box::pin(async move {
+                conn.execute(search_path.as_str()).await?;
+                Ok(())
+            })
+        })
+        .connect(&url)
+        .await
+        .unwrap();
+    MIGRATOR.run(&pool).await.unwrap();
+    pool
+}
@@ -0,0 +1,8 @@
+use monitor_core::MonitorConfig;
+
+pub use monitor_trader::fixtures::{position, signal, trading_config};
+
+// The shipped config.example.yaml, which tests adjust per section
+pub fn monitor_config() -> MonitorConfig {
+    serde_yaml::from_str(include_str!("../../config.example.yaml")).unwrap()
+}
@@ -0,0 +1,149 @@
+pub mod db;
+pub mod fixtures;
+pub mod notifier;
+pub mod simulator;
+
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+// Set to rewrite `current.json` goldens from the values under test
+pub const UPDATE_ENV: &str = "UPDATE_GOLDENS";
+pub const CURRENT: &str = "current.json";
+
+// Golden JSON files for a persisted type live in `<dir>/<type>/`: `current.json` is what the
+// code serializes today, `v<N>.json` are records written by earlier schema versions that must
+// keep deserializing. Before an intentional schema change, copy `current.json` to the next
+// `v<N>.json`, then rerun the tests with UPDATE_GOLDENS=1.
+pub struct Goldens {
+    dir: PathBuf,
+    update: bool,
+}
+
+impl Goldens {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            update: std::env::var_os(UPDATE_ENV).is_some_and(|v| !v.is_empty()),
+        }
+    }
+
+    pub fn with_update(mut self, update: bool) -> Self {
+        self.update = update;
+        self
+    }
+
+    pub fn dir(&self, type_name: &str) -> PathBuf {
+        self.dir.join(type_name)
+    }
+
+    // The serialized value must match `current.json` byte for byte, and the golden must read
+    // back into the same JSON
+    pub fn assert_current<T>(&self, type_name: &str, value: &T)
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        let path = self.dir(type_name).join(CURRENT);
+        let actual = to_golden(value);
+
+        if self.update {
+            fs::create_dir_all(self.dir(type_name)).unwrap();
+            fs::write(&path, &actual).unwrap();
+            return;
+        }
+
+        let expected = fs::read_to_string(&path).unwrap_or_else(|e| {
+            panic!("Missing golden {}: {}, run with {}=1 to create it", path.display(), e, UPDATE_ENV)
+        });
+        assert!(
+            expected == actual,
+            "{} no longer matches the serialized value, rerun with {}=1 if the schema change is \
+             intentional and add the previous golden as a v<N>.json fixture\n--- golden\n{}\n--- actual\n{}",
+            path.display(),
+            UPDATE_ENV,
+            expected,
+            actual
+        );
+
+        let parsed: T = read(&path);
+        assert_eq!(to_golden(&parsed), actual, "{} does not round-trip", path.display());
+    }
+
+    // Every golden of the type, current and previous versions, must deserialize. Returns the
+    // versions read, oldest first.
+    pub fn assert_readable<T: DeserializeOwned>(&self, type_name: &str) -> Vec<(String, T)> {
+        let dir = self.dir(type_name);
+        let mut files: Vec<PathBuf> = fs::read_dir(&dir)
+            .unwrap_or_else(|e| panic!("Missing golden directory {}: {}", dir.display(), e))
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        files.sort_by_key(|path| version_key(path));
+        assert!(!files.is_empty(), "No goldens in {}", dir.display());
+
+        files
+            .iter()
+            .map(|path| (path.file_stem().unwrap().to_string_lossy().into_owned(), read(path)))
+            .collect()
+    }
+}
+
+fn to_golden<T: Serialize>(value: &T) -> String {
+    let mut json = serde_json::to_string_pretty(value).unwrap();
+    json.push('\n');
+    json
+}
+
+fn read<T: DeserializeOwned>(path: &Path) -> T {
+    let json = fs::read_to_string(path).unwrap();
+    serde_json::from_str(&json)
+        .unwrap_or_else(|e| panic!("{} no longer deserializes: {}", path.display(), e))
+}
+
+// v1 < v2 < v10 < current
+fn version_key(path: &Path) -> (u64, String) {
+    let stem = path.file_stem().unwrap().to_string_lossy().into_owned();
+    let version = stem
+        .strip_prefix('v')
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(u64::MAX);
+    (version, stem)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Record {
+        id: u32,
+        #[serde(default)]
+        note: Option<String>,
+    }
+
+    #[test]
+    fn test_goldens_detect_changes_and_read_old_versions() {
+        let dir = std::env::temp_dir().join(format!("monitor-testkit-{}", uuid::Uuid::new_v4()));
+        let record = Record { id: 1, note: None };
+        Goldens::new(&dir).with_update(true).assert_current("record", &record);
+
+        let goldens = Goldens::new(&dir).with_update(false);
+        goldens.assert_current("record", &record);
+
+        // A record from before `note` existed
+        fs::write(dir.join("record/v1.json"), r#"{ "id": 7 }"#).unwrap();
+        let versions = goldens.assert_readable::<Record>("record");
+        let names: Vec<&str> = versions.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["v1", "current"]);
+        assert_eq!(versions[0].1.id, 7);
+
+        let changed = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            goldens.assert_current("record", &Record { id: 1, note: Some("x".to_string()) })
+        }));
+        assert!(changed.is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
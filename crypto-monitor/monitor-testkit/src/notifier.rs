@@ -0,0 +1,33 @@
+use async_trait::async_trait;
+use monitor_core::Result;
+use monitor_notifier::{Notification, NotificationChannel};
+use std::sync::{Arc, Mutex};
+
+// An enabled channel keeping everything it is sent. Clones share the record, so a test keeps
+// one and hands the other to the manager
+#[derive(Debug, Clone, Default)]
+pub struct RecordingChannel {
+    sent: Arc<Mutex<Vec<Notification>>>,
+}
+
+impl RecordingChannel {
+    pub fn sent(&self) -> Vec<Notification> {
+        self.sent.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for RecordingChannel {
+    async fn send(&self, notification: &Notification) -> Result<()> {
+        self.sent.lock().unwrap().push(notification.clone());
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "recording"
+    }
+
+    fn is_enabled(&self) -> bool {
+        true
+    }
+}
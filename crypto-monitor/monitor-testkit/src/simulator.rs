@@ -0,0 +1,98 @@
+use async_trait::async_trait;
+use monitor_core::{
+    event::EventBuilder,
+    messaging::{BusConnector, EventBus, Messaging, MessagingConfig},
+    EventSource, EventType, MarketDataType, MonitorEvent, Result,
+};
+use std::{sync::Arc, time::Duration};
+use tokio::task::JoinHandle;
+
+// A bus that takes everything and keeps nothing, the in-process stream is left as the only
+// consumer
+pub struct NullBus;
+
+#[async_trait]
+impl EventBus for NullBus {
+    async fn publish(&self, _topic: &str, _event: &MonitorEvent) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl BusConnector for NullBus {
+    async fn connect(&self) -> Result<Arc<dyn EventBus>> {
+        Ok(Arc::new(NullBus))
+    }
+}
+
+pub async fn local_messaging() -> Arc<Messaging> {
+    Messaging::connect(MessagingConfig::default(), Arc::new(NullBus)).await.unwrap()
+}
+
+// One exchange's market data, published on the topics the engine uses for a started monitor to
+// consume. Quotes keep the feed alive and trades are what reaches the detectors, so the two can
+// be switched apart to tell a dead feed from one whose trades are not routed
+pub struct FeedSimulator {
+    messaging: Arc<Messaging>,
+    prefix: String,
+    exchange: String,
+}
+
+impl FeedSimulator {
+    pub fn new(messaging: Arc<Messaging>, prefix: &str, exchange: &str) -> Self {
+        Self {
+            messaging,
+            prefix: prefix.to_string(),
+            exchange: exchange.to_string(),
+        }
+    }
+
+    pub async fn quote(&self, symbol: &str, bid_price: f64, ask_price: f64) {
+        let data = serde_json::json!({
+            "exchange": self.exchange,
+            "symbol": symbol,
+            "bid_price": bid_price,
+            "bid_size": 1.0,
+            "ask_price": ask_price,
+            "ask_size": 1.0,
+        });
+        self.publish("orderbook", MarketDataType::OrderBook, data).await;
+    }
+
+    pub async fn trade(&self, symbol: &str, price: f64, volume: f64) {
+        let data = serde_json::json!({
+            "exchange": self.exchange,
+            "symbol": symbol,
+            "price": price,
+            "volume": volume,
+        });
+        self.publish("trades", MarketDataType::Trade, data).await;
+    }
+
+    // Quotes `symbol` every `every`, with a trade alongside each quote when `trades` is set,
+    // until the returned task is aborted
+    pub fn run(self, symbol: &str, every: Duration, trades: bool) -> JoinHandle<()> {
+        let symbol = symbol.to_string();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(every);
+            loop {
+                interval.tick().await;
+                self.quote(&symbol, 99.9, 100.1).await;
+                if trades {
+                    self.trade(&symbol, 100.0, 1.0).await;
+                }
+            }
+        })
+    }
+
+    async fn publish(&self, topic: &str, data_type: MarketDataType, data: serde_json::Value) {
+        let event = EventBuilder::new()
+            .with_source(EventSource::Exchange(self.exchange.clone()))
+            .with_type(EventType::MarketData(data_type))
+            .with_data(data)
+            .build()
+            .unwrap();
+        let topic = format!("{}.market.{}", self.prefix, topic);
+        self.messaging.publish(&topic, &event).await;
+    }
+}
@@ -0,0 +1,323 @@
+use chrono::{DateTime, Duration, Utc};
+use monitor_core::CircuitBreakerConfig;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use tracing::{info, warn};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum HaltReason {
+    DailyLoss { loss: f64, limit: f64 },
+    DailyLossPct { loss_pct: f64, limit: f64 },
+    ConsecutiveLosses { losses: u32, limit: u32 },
+    OpenRate { opened: u32, limit: u32 },
+    Manual { note: Option<String> },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Halt {
+    #[serde(flatten)]
+    pub reason: HaltReason,
+    pub since: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BreakerStatus {
+    // None while trading
+    pub halt: Option<Halt>,
+    // Realized PnL over the trailing 24 hours
+    pub daily_pnl: f64,
+    pub consecutive_losses: u32,
+    pub opened_last_hour: u32,
+}
+
+// What the breaker counts towards its limits, saved so a restart keeps its windows
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BreakerWindows {
+    pub trades: VecDeque<(DateTime<Utc>, f64)>,
+    pub opens: VecDeque<DateTime<Utc>>,
+    pub consecutive_losses: u32,
+    pub equity: f64,
+}
+
+struct BreakerState {
+    // Closed trades over the trailing 24 hours, oldest first
+    trades: VecDeque<(DateTime<Utc>, f64)>,
+    // Positions opened over the trailing hour, oldest first
+    opens: VecDeque<DateTime<Utc>>,
+    consecutive_losses: u32,
+    // Realized equity after the last trade
+    equity: f64,
+    halt: Option<Halt>,
+}
+
+impl BreakerState {
+    // Drops what has rolled out of the windows, an entry exactly one window old is out
+    fn expire(&mut self, now: DateTime<Utc>) {
+        while self.trades.front().is_some_and(|(at, _)| now - *at >= Duration::hours(24)) {
+            self.trades.pop_front();
+        }
+        while self.opens.front().is_some_and(|at| now - *at >= Duration::hours(1)) {
+            self.opens.pop_front();
+        }
+    }
+
+    fn daily_pnl(&self) -> f64 {
+        self.trades.iter().map(|(_, pnl)| pnl).sum()
+    }
+}
+
+// Kill switch over new entries. Once a limit is hit it stays halted until `resume`, even
+// after the windows roll past the trades that tripped it. Closing positions is never blocked
+pub struct CircuitBreaker {
+    config: RwLock<CircuitBreakerConfig>,
+    state: Mutex<BreakerState>,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: CircuitBreakerConfig, initial_equity: f64) -> Self {
+        Self {
+            config: RwLock::new(config),
+            state: Mutex::new(BreakerState {
+                trades: VecDeque::new(),
+                opens: VecDeque::new(),
+                consecutive_losses: 0,
+                equity: initial_equity,
+                halt: None,
+            }),
+        }
+    }
+
+    pub fn set_config(&self, config: CircuitBreakerConfig) {
+        *self.config.write() = config;
+    }
+
+    // The halt blocking a new entry, tripping the breaker first when a limit is reached
+    pub fn check(&self, now: DateTime<Utc>) -> Option<Halt> {
+        let config = self.config.read().clone();
+        let mut state = self.state.lock();
+        if let Some(halt) = &state.halt {
+            return Some(halt.clone());
+        }
+        state.expire(now);
+
+        let loss = -state.daily_pnl();
+        let start_equity = state.equity + loss;
+        let loss_pct = if start_equity > 0.0 {
+            loss / start_equity * 100.0
+        } else {
+            0.0
+        };
+        let opened = state.opens.len() as u32;
+        let reason = if let Some(limit) = config.max_daily_loss.filter(|limit| loss >= *limit) {
+            HaltReason::DailyLoss { loss, limit }
+        } else if let Some(limit) = config.max_daily_loss_pct.filter(|limit| loss_pct >= *limit) {
+            HaltReason::DailyLossPct { loss_pct, limit }
+        } else if let Some(limit) = config
+            .max_consecutive_losses
+            .filter(|limit| state.consecutive_losses >= *limit)
+        {
+            HaltReason::ConsecutiveLosses {
+                losses: state.consecutive_losses,
+                limit,
+            }
+        } else if let Some(limit) = config.max_positions_per_hour.filter(|limit| opened >= *limit) {
+            HaltReason::OpenRate { opened, limit }
+        } else {
+            return None;
+        };
+
+        warn!("Circuit breaker tripped: {:?}", reason);
+        let halt = Halt { reason, since: now };
+        state.halt = Some(halt.clone());
+        Some(halt)
+    }
+
+    pub fn record_trade(&self, pnl: f64, at: DateTime<Utc>) {
+        let mut state = self.state.lock();
+        state.trades.push_back((at, pnl));
+        state.equity += pnl;
+        if pnl < 0.0 {
+            state.consecutive_losses += 1;
+        } else {
+            state.consecutive_losses = 0;
+        }
+        drop(state);
+        self.check(at);
+    }
+
+    pub fn record_open(&self, at: DateTime<Utc>) {
+        self.state.lock().opens.push_back(at);
+    }
+
+    pub fn halt(&self, note: Option<String>) -> Halt {
+        let mut state = self.state.lock();
+        if let Some(halt) = &state.halt {
+            return halt.clone();
+        }
+        warn!("Trading halted manually: {:?}", note);
+        let halt = Halt {
+            reason: HaltReason::Manual { note },
+            since: Utc::now(),
+        };
+        state.halt = Some(halt.clone());
+        halt
+    }
+
+    // A halt saved by a previous run, kept until `resume` like any other
+    pub fn restore(&self, halt: Halt) {
+        warn!("Trading still halted from the previous run: {:?}", halt.reason);
+        self.state.lock().halt = Some(halt);
+    }
+
+    // Resuming is a decision to trade on from here, so the windows that tripped the breaker
+    // start over rather than tripping it again on the next signal
+    pub fn resume(&self) -> bool {
+        let mut state = self.state.lock();
+        let Some(halt) = state.halt.take() else {
+            return false;
+        };
+        info!("Trading resumed after halt: {:?}", halt.reason);
+        state.trades.clear();
+        state.opens.clear();
+        state.consecutive_losses = 0;
+        true
+    }
+
+    pub fn windows(&self) -> BreakerWindows {
+        let state = self.state.lock();
+        BreakerWindows {
+            trades: state.trades.clone(),
+            opens: state.opens.clone(),
+            consecutive_losses: state.consecutive_losses,
+            equity: state.equity,
+        }
+    }
+
+    // Windows saved by a previous run, the halt is restored on its own by `restore`
+    pub fn restore_windows(&self, windows: BreakerWindows) {
+        let mut state = self.state.lock();
+        state.trades = windows.trades;
+        state.opens = windows.opens;
+        state.consecutive_losses = windows.consecutive_losses;
+        state.equity = windows.equity;
+    }
+
+    pub fn status(&self, now: DateTime<Utc>) -> BreakerStatus {
+        let mut state = self.state.lock();
+        state.expire(now);
+        BreakerStatus {
+            halt: state.halt.clone(),
+            daily_pnl: state.daily_pnl(),
+            consecutive_losses: state.consecutive_losses,
+            opened_last_hour: state.opens.len() as u32,
+        }
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.state.lock().halt.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn breaker(daily: Option<f64>, losses: Option<u32>, per_hour: Option<u32>) -> CircuitBreaker {
+        CircuitBreaker::new(
+            CircuitBreakerConfig {
+                max_daily_loss: daily,
+                max_daily_loss_pct: None,
+                max_consecutive_losses: losses,
+                max_positions_per_hour: per_hour,
+            },
+            10_000.0,
+        )
+    }
+
+    fn t0() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_daily_loss_rolls_off_at_the_24h_boundary() {
+        // Still inside the window a second before the boundary
+        let inside = breaker(Some(100.0), None, None);
+        inside.record_trade(-60.0, t0());
+        inside.record_trade(-50.0, t0() + Duration::hours(24) - Duration::seconds(1));
+        assert!(matches!(
+            inside.check(t0() + Duration::hours(24) - Duration::seconds(1)),
+            Some(Halt { reason: HaltReason::DailyLoss { .. }, .. })
+        ));
+
+        // Out of it exactly at the boundary
+        let outside = breaker(Some(100.0), None, None);
+        outside.record_trade(-60.0, t0());
+        outside.record_trade(-50.0, t0() + Duration::hours(24));
+        assert_eq!(outside.check(t0() + Duration::hours(24)), None);
+        assert_eq!(outside.status(t0() + Duration::hours(24)).daily_pnl, -50.0);
+    }
+
+    #[test]
+    fn test_daily_loss_pct_is_against_equity_before_the_window() {
+        let breaker = CircuitBreaker::new(
+            CircuitBreakerConfig {
+                max_daily_loss_pct: Some(5.0),
+                ..Default::default()
+            },
+            10_000.0,
+        );
+        // Gains from before the window raise the base the loss is measured against
+        breaker.record_trade(2_000.0, t0());
+        breaker.record_trade(-590.0, t0() + Duration::hours(24));
+        assert_eq!(breaker.check(t0() + Duration::hours(24)), None);
+        breaker.record_trade(-10.0, t0() + Duration::hours(24));
+        assert!(matches!(
+            breaker.check(t0() + Duration::hours(24)).map(|halt| halt.reason),
+            Some(HaltReason::DailyLossPct { loss_pct, .. }) if (loss_pct - 5.0).abs() < 1e-9
+        ));
+    }
+
+    #[test]
+    fn test_halt_persists_until_resume() {
+        let breaker = breaker(Some(100.0), None, None);
+        breaker.record_trade(-150.0, t0());
+        assert!(breaker.is_halted());
+
+        // The losing trade has long rolled off, the halt has not
+        assert!(breaker.check(t0() + Duration::days(3)).is_some());
+
+        assert!(breaker.resume());
+        assert_eq!(breaker.check(t0() + Duration::days(3)), None);
+        assert!(!breaker.resume());
+    }
+
+    #[test]
+    fn test_consecutive_losses_and_open_rate() {
+        let losses = breaker(None, Some(3), None);
+        for pnl in [-1.0, -1.0, 5.0, -1.0, -1.0] {
+            losses.record_trade(pnl, t0());
+        }
+        assert_eq!(losses.check(t0()), None);
+        losses.record_trade(-1.0, t0());
+        assert_eq!(
+            losses.check(t0()).map(|halt| halt.reason),
+            Some(HaltReason::ConsecutiveLosses { losses: 3, limit: 3 })
+        );
+
+        let opens = breaker(None, None, Some(2));
+        opens.record_open(t0());
+        opens.record_open(t0() + Duration::minutes(30));
+        assert_eq!(
+            opens.check(t0() + Duration::minutes(59)).map(|halt| halt.reason),
+            Some(HaltReason::OpenRate { opened: 2, limit: 2 })
+        );
+
+        let rolled = breaker(None, None, Some(2));
+        rolled.record_open(t0());
+        rolled.record_open(t0() + Duration::minutes(30));
+        assert_eq!(rolled.check(t0() + Duration::hours(1)), None);
+    }
+}
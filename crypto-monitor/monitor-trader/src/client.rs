@@ -0,0 +1,649 @@
+use crate::{
+    reconcile::{AccountSource, VenuePosition},
+    PositionSide,
+};
+use async_trait::async_trait;
+use barter_execution::{
+    balance::{AssetBalance, Balance},
+    client::mock::{MockExecution, MockExecutionConfig},
+    error::{ApiError, OrderError, UnindexedClientError, UnindexedOrderError},
+    exchange::mock::MockExchange,
+    order::{
+        self as venue,
+        id::{ClientOrderId, StrategyId},
+        request::{OrderRequestCancel, OrderRequestOpen},
+        state::Open,
+        OrderKey,
+    },
+    trade::Trade,
+    ExecutionClient, UnindexedAccountSnapshot,
+};
+use barter_instrument::{
+    asset::name::AssetNameExchange,
+    exchange::ExchangeId,
+    instrument::{name::InstrumentNameExchange, Instrument},
+    Side, Underlying,
+};
+use chrono::{DateTime, Utc};
+use monitor_core::{MonitorError, Result as MonitorResult};
+use parking_lot::RwLock;
+use rust_decimal::{
+    prelude::{FromPrimitive, ToPrimitive},
+    Decimal,
+};
+use std::{collections::HashMap, fmt};
+use tokio::sync::{broadcast, mpsc};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OrderId(String);
+
+impl OrderId {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self(id.into())
+    }
+}
+
+impl fmt::Display for OrderId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderKind {
+    Buy,
+    Sell,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderType {
+    Market,
+    Limit,
+    // Rests until the price trades through `price`, then executes at market
+    StopMarket,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeInForce {
+    GoodUntilCancelled,
+    ImmediateOrCancel,
+    FillOrKill,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderState {
+    Open,
+    Filled,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestOpen {
+    pub instrument: String,
+    pub exchange: String,
+    // Reused across retries so the venue can deduplicate them
+    pub client_order_id: Option<String>,
+    pub kind: OrderKind,
+    pub order_type: OrderType,
+    pub quantity: f64,
+    // Limit price, stop trigger, or the reference price of a market order
+    pub price: Option<f64>,
+    pub time_in_force: Option<TimeInForce>,
+    pub post_only: bool,
+    pub reduce_only: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestCancel {
+    pub id: OrderId,
+    pub instrument: String,
+    pub exchange: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Order {
+    pub id: OrderId,
+    pub instrument: String,
+    pub exchange: String,
+    pub kind: OrderKind,
+    pub order_type: OrderType,
+    // What has filled once any of it has, the ordered size until then
+    pub quantity: f64,
+    // The venue's fill price once filled, the order's price until then
+    pub price: Option<f64>,
+    pub state: OrderState,
+}
+
+// Where the trader sends its orders. barter's ExecutionClient can't sit behind a `dyn` (it is
+// Clone, has an associated const and returns `impl Future`), venues built on it are wrapped
+// in a `BarterOrderClient`
+#[async_trait]
+pub trait OrderClient: Send + Sync {
+    async fn open_order(
+        &self,
+        request: RequestOpen,
+    ) -> Result<Option<Order>, UnindexedClientError>;
+
+    async fn cancel_order(
+        &self,
+        request: RequestCancel,
+    ) -> Result<Option<Order>, UnindexedClientError>;
+
+    // The order submitted under `client_order_id` as the venue has it now, `None` when the
+    // venue never received it. Venues that can't look orders up this way refuse
+    async fn find_order(
+        &self,
+        _client_order_id: &str,
+        _instrument: &str,
+        _exchange: &str,
+    ) -> Result<Option<Order>, UnindexedClientError> {
+        Err(UnindexedClientError::Api(ApiError::OrderRejected(
+            "orders can't be looked up by client order id".to_string(),
+        )))
+    }
+}
+
+// "BTC/USDT" -> "BTCUSDT", the form most venues name spot pairs by
+fn concatenated(symbol: &str) -> InstrumentNameExchange {
+    InstrumentNameExchange::new(symbol.replace(['/', '-'], "").to_uppercase())
+}
+
+// OrderClient over a barter ExecutionClient, taking the orders of one exchange as the monitor
+// names it. Orders for any other exchange are rejected
+pub struct BarterOrderClient<C> {
+    client: C,
+    exchange: String,
+    strategy: StrategyId,
+    instrument_name: fn(&str) -> InstrumentNameExchange,
+    // The monitor's symbol of each venue instrument, to report the account back by
+    symbols: RwLock<HashMap<InstrumentNameExchange, String>>,
+}
+
+impl<C: ExecutionClient> BarterOrderClient<C> {
+    pub fn new(client: C, exchange: &str) -> Self {
+        Self {
+            client,
+            exchange: exchange.to_lowercase(),
+            strategy: StrategyId::new("monitor-trader"),
+            instrument_name: concatenated,
+            symbols: RwLock::new(HashMap::new()),
+        }
+    }
+
+    // The pairs the venue lists, so positions held before the first order can be named
+    pub fn with_symbols(self, symbols: &[String]) -> Self {
+        for symbol in symbols {
+            self.instrument(symbol);
+        }
+        self
+    }
+
+    // For venues that don't name pairs by concatenating base and quote
+    pub fn with_instrument_name(
+        mut self,
+        instrument_name: fn(&str) -> InstrumentNameExchange,
+    ) -> Self {
+        self.instrument_name = instrument_name;
+        self
+    }
+
+    // The venue's account events, balances and fills of every instrument, for
+    // `AutoTrader::follow_account`
+    pub async fn account_stream(&self) -> Result<C::AccountStream, UnindexedClientError> {
+        self.client.account_stream(&[], &[]).await
+    }
+
+    fn instrument(&self, symbol: &str) -> InstrumentNameExchange {
+        let instrument = (self.instrument_name)(symbol);
+        if !self.symbols.read().contains_key(&instrument) {
+            self.symbols.write().insert(instrument.clone(), symbol.to_string());
+        }
+        instrument
+    }
+
+    fn key<'a>(
+        &self,
+        instrument: &'a InstrumentNameExchange,
+        cid: &str,
+    ) -> OrderKey<ExchangeId, &'a InstrumentNameExchange> {
+        OrderKey {
+            exchange: C::EXCHANGE,
+            instrument,
+            strategy: self.strategy.clone(),
+            cid: ClientOrderId::new(cid),
+        }
+    }
+}
+
+fn decimal(value: f64, what: &str) -> Result<Decimal, UnindexedClientError> {
+    Decimal::from_f64(value).ok_or_else(|| {
+        UnindexedClientError::Api(ApiError::OrderRejected(format!(
+            "{} {} is not a number",
+            what, value
+        )))
+    })
+}
+
+// barter's side of an open request. It has no stop orders and no reduce-only flag, exits are
+// sized to the position so they can't flip it
+fn venue_request(
+    request: &RequestOpen,
+) -> Result<venue::request::RequestOpen, UnindexedClientError> {
+    let kind = match request.order_type {
+        OrderType::Market => venue::OrderKind::Market,
+        OrderType::Limit => venue::OrderKind::Limit,
+        OrderType::StopMarket => {
+            return Err(UnindexedClientError::Api(ApiError::OrderRejected(
+                "stop orders are not supported by the venue client".to_string(),
+            )))
+        }
+    };
+    let time_in_force = match (request.time_in_force, request.order_type) {
+        (Some(TimeInForce::ImmediateOrCancel), _) | (None, OrderType::Market) => {
+            venue::TimeInForce::ImmediateOrCancel
+        }
+        (Some(TimeInForce::FillOrKill), _) => venue::TimeInForce::FillOrKill,
+        _ => venue::TimeInForce::GoodUntilCancelled {
+            post_only: request.post_only,
+        },
+    };
+
+    Ok(venue::request::RequestOpen {
+        side: match request.kind {
+            OrderKind::Buy => Side::Buy,
+            OrderKind::Sell => Side::Sell,
+        },
+        price: decimal(request.price.unwrap_or_default(), "price")?,
+        quantity: decimal(request.quantity, "quantity")?,
+        kind,
+        time_in_force,
+    })
+}
+
+// The order the venue opened, or the error it refused it with. Quantity and price are the
+// venue's, what filled at what price, so PnL is booked from the execution and not the request
+fn opened(
+    request: RequestOpen,
+    response: venue::Order<ExchangeId, InstrumentNameExchange, Result<Open, UnindexedOrderError>>,
+) -> Result<Order, UnindexedClientError> {
+    let open = match response.state {
+        Ok(open) => open,
+        Err(OrderError::Connectivity(e)) => return Err(UnindexedClientError::Connectivity(e)),
+        Err(OrderError::Rejected(e)) => return Err(UnindexedClientError::Api(e)),
+    };
+    let quantity = if open.filled_quantity > Decimal::ZERO {
+        open.filled_quantity
+    } else {
+        response.quantity
+    };
+
+    Ok(Order {
+        id: OrderId::new(open.id.to_string()),
+        instrument: request.instrument,
+        exchange: request.exchange,
+        kind: request.kind,
+        order_type: request.order_type,
+        quantity: quantity.to_f64().unwrap_or(request.quantity),
+        price: response.price.to_f64().filter(|price| *price > 0.0).or(request.price),
+        state: if open.filled_quantity >= response.quantity {
+            OrderState::Filled
+        } else {
+            OrderState::Open
+        },
+    })
+}
+
+fn other_exchange(exchange: &str, venue: &str) -> UnindexedClientError {
+    UnindexedClientError::Api(ApiError::OrderRejected(format!(
+        "orders for {} are not routed to the {} venue",
+        exchange, venue
+    )))
+}
+
+pub type MockVenue = MockExecution<fn() -> DateTime<Utc>>;
+
+// barter's mock exchange run in process, for `venue.kind: mock`. It takes the same requests a
+// live venue would through a BarterOrderClient, but fills market orders only, at their
+// reference price: limit entries and resting exits are rejected and left to the trader's own
+// fallbacks. `symbols` are the pairs it lists, its account starts with `initial_balance` of
+// `quote_asset`
+pub fn mock_exchange(
+    symbols: &[String],
+    quote_asset: &str,
+    initial_balance: f64,
+    fee_bps: f64,
+) -> MockVenue {
+    const ACCOUNT_STREAM_CAPACITY: usize = 256;
+
+    let instruments = symbols
+        .iter()
+        .filter_map(|symbol| {
+            let (base, quote) = symbol.split_once('/')?;
+            let name = concatenated(symbol);
+            let instrument = Instrument::spot(
+                ExchangeId::Mock,
+                symbol.as_str(),
+                name.clone(),
+                Underlying::new(
+                    AssetNameExchange::new(base.to_uppercase()),
+                    AssetNameExchange::new(quote.to_uppercase()),
+                ),
+                None,
+            );
+            Some((name, instrument))
+        })
+        .collect();
+    let balance = Decimal::from_f64(initial_balance).unwrap_or_default();
+    let account = UnindexedAccountSnapshot {
+        exchange: ExchangeId::Mock,
+        balances: vec![AssetBalance::new(
+            AssetNameExchange::new(quote_asset.to_uppercase()),
+            Balance::new(balance, balance),
+            Utc::now(),
+        )],
+        instruments: Vec::new(),
+    };
+    let config = MockExecutionConfig::new(
+        ExchangeId::Mock,
+        account,
+        0,
+        Decimal::from_f64(fee_bps / 10_000.0).unwrap_or_default(),
+    );
+
+    let (request_tx, request_rx) = mpsc::unbounded_channel();
+    let (event_tx, event_rx) = broadcast::channel(ACCOUNT_STREAM_CAPACITY);
+    tokio::spawn(MockExchange::new(config, request_rx, event_tx, instruments).run());
+    MockExecution::new(ExchangeId::Mock, Utc::now as fn() -> DateTime<Utc>, request_tx, event_rx)
+}
+
+#[async_trait]
+impl<C> OrderClient for BarterOrderClient<C>
+where
+    C: ExecutionClient + Send + Sync,
+{
+    async fn open_order(
+        &self,
+        request: RequestOpen,
+    ) -> Result<Option<Order>, UnindexedClientError> {
+        if !request.exchange.eq_ignore_ascii_case(&self.exchange) {
+            return Err(other_exchange(&request.exchange, &self.exchange));
+        }
+        let state = venue_request(&request)?;
+        let instrument = self.instrument(&request.instrument);
+        let cid = request
+            .client_order_id
+            .clone()
+            .unwrap_or_else(|| ClientOrderId::random().to_string());
+
+        let response = self
+            .client
+            .open_order(OrderRequestOpen {
+                key: self.key(&instrument, &cid),
+                state,
+            })
+            .await;
+
+        response.map(|order| opened(request, order)).transpose()
+    }
+
+    async fn cancel_order(
+        &self,
+        request: RequestCancel,
+    ) -> Result<Option<Order>, UnindexedClientError> {
+        if !request.exchange.eq_ignore_ascii_case(&self.exchange) {
+            return Err(other_exchange(&request.exchange, &self.exchange));
+        }
+        let instrument = (self.instrument_name)(&request.instrument);
+        let id = request.id.to_string();
+
+        // The trader only tracks the venue's order id, which the venue cancels by
+        let response = self
+            .client
+            .cancel_order(OrderRequestCancel {
+                key: self.key(&instrument, &id),
+                state: venue::request::RequestCancel {
+                    id: Some(venue::id::OrderId::new(&id)),
+                },
+            })
+            .await;
+
+        match response.map(|response| response.state) {
+            None => Ok(None),
+            Some(Ok(_)) => Ok(Some(Order {
+                id: request.id,
+                instrument: request.instrument,
+                exchange: request.exchange,
+                kind: OrderKind::Buy,
+                order_type: OrderType::Limit,
+                quantity: 0.0,
+                price: None,
+                state: OrderState::Cancelled,
+            })),
+            Some(Err(OrderError::Connectivity(e))) => Err(UnindexedClientError::Connectivity(e)),
+            Some(Err(OrderError::Rejected(e))) => Err(UnindexedClientError::Api(e)),
+        }
+    }
+}
+
+// The positions the venue's trades add up to, per instrument: what was bought less what was
+// sold, entered at the average price of the side held
+fn net_positions<Q>(
+    exchange: &str,
+    trades: &[Trade<Q, InstrumentNameExchange>],
+    symbol: impl Fn(&InstrumentNameExchange) -> String,
+) -> Vec<VenuePosition> {
+    #[derive(Default)]
+    struct Net {
+        quantity: f64,
+        bought: (f64, f64),
+        sold: (f64, f64),
+    }
+
+    let mut nets: HashMap<&InstrumentNameExchange, Net> = HashMap::new();
+    for trade in trades {
+        let quantity = trade.quantity.abs().to_f64().unwrap_or_default();
+        let value = trade.value_quote().to_f64().unwrap_or_default();
+        let net = nets.entry(&trade.instrument).or_default();
+        let side = match trade.side {
+            Side::Buy => {
+                net.quantity += quantity;
+                &mut net.bought
+            }
+            Side::Sell => {
+                net.quantity -= quantity;
+                &mut net.sold
+            }
+        };
+        side.0 += quantity;
+        side.1 += value;
+    }
+
+    nets.into_iter()
+        .filter(|(_, net)| net.quantity.abs() > f64::EPSILON)
+        .map(|(instrument, net)| {
+            let (side, (quantity, value)) = if net.quantity > 0.0 {
+                (PositionSide::Long, net.bought)
+            } else {
+                (PositionSide::Short, net.sold)
+            };
+            VenuePosition {
+                exchange: exchange.to_string(),
+                symbol: symbol(instrument),
+                side,
+                quantity: net.quantity.abs(),
+                entry_price: if quantity > 0.0 { value / quantity } else { 0.0 },
+            }
+        })
+        .collect()
+}
+
+// barter's ExecutionClient doesn't require `fetch_trades` to be Send, so the account can only
+// be read through venues whose future is known to be, the mock one for now
+#[async_trait]
+impl AccountSource for BarterOrderClient<MockVenue> {
+    async fn fetch_positions(&self) -> MonitorResult<Vec<VenuePosition>> {
+        let trades = self
+            .client
+            .fetch_trades(DateTime::<Utc>::MIN_UTC)
+            .await
+            .map_err(|e| MonitorError::Barter(e.to_string()))?;
+        let symbols = self.symbols.read();
+        Ok(net_positions(&self.exchange, &trades, |instrument| {
+            symbols
+                .get(instrument)
+                .cloned()
+                .unwrap_or_else(|| instrument.to_string())
+        }))
+    }
+
+    fn covers(&self, exchange: &str) -> bool {
+        exchange.eq_ignore_ascii_case(&self.exchange)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use barter_execution::error::ConnectivityError;
+    use chrono::Utc;
+
+    fn request(order_type: OrderType) -> RequestOpen {
+        RequestOpen {
+            instrument: "BTC/USDT".to_string(),
+            exchange: "binance".to_string(),
+            client_order_id: Some("cid-1".to_string()),
+            kind: OrderKind::Sell,
+            order_type,
+            quantity: 0.5,
+            price: Some(50_000.0),
+            time_in_force: None,
+            post_only: true,
+            reduce_only: true,
+        }
+    }
+
+    #[test]
+    fn test_requests_map_onto_barter_orders() {
+        let limit = venue_request(&request(OrderType::Limit)).unwrap();
+        assert_eq!(limit.side, Side::Sell);
+        assert_eq!(limit.kind, venue::OrderKind::Limit);
+        assert_eq!(limit.price, Decimal::from(50_000));
+        assert_eq!(limit.quantity, Decimal::new(5, 1));
+        assert_eq!(
+            limit.time_in_force,
+            venue::TimeInForce::GoodUntilCancelled { post_only: true }
+        );
+
+        let market = venue_request(&request(OrderType::Market)).unwrap();
+        assert_eq!(market.kind, venue::OrderKind::Market);
+        assert_eq!(market.time_in_force, venue::TimeInForce::ImmediateOrCancel);
+
+        assert!(matches!(
+            venue_request(&request(OrderType::StopMarket)),
+            Err(UnindexedClientError::Api(ApiError::OrderRejected(_)))
+        ));
+        assert_eq!(concatenated("btc/usdt"), InstrumentNameExchange::new("BTCUSDT"));
+    }
+
+    #[test]
+    fn test_venue_responses_map_back() {
+        let response = |filled: Decimal, state: Result<(), UnindexedOrderError>| venue::Order {
+            key: OrderKey {
+                exchange: ExchangeId::Mock,
+                instrument: InstrumentNameExchange::new("BTCUSDT"),
+                strategy: StrategyId::new("test"),
+                cid: ClientOrderId::new("cid-1"),
+            },
+            side: Side::Sell,
+            price: Decimal::from(49_950),
+            quantity: Decimal::new(5, 1),
+            kind: venue::OrderKind::Market,
+            time_in_force: venue::TimeInForce::ImmediateOrCancel,
+            state: state.map(|_| Open {
+                id: venue::id::OrderId::new("venue-7"),
+                time_exchange: Utc::now(),
+                filled_quantity: filled,
+            }),
+        };
+
+        let order = opened(request(OrderType::Limit), response(Decimal::ZERO, Ok(()))).unwrap();
+        assert_eq!(order.id, OrderId::new("venue-7"));
+        assert_eq!(order.state, OrderState::Open);
+        assert_eq!(order.quantity, 0.5);
+        // What the venue filled at what price, not what was asked for
+        let filled = response(Decimal::new(5, 1), Ok(()));
+        let order = opened(request(OrderType::Market), filled).unwrap();
+        assert_eq!(order.state, OrderState::Filled);
+        assert_eq!(order.price, Some(49_950.0));
+        let partial = response(Decimal::new(2, 1), Ok(()));
+        let order = opened(request(OrderType::Limit), partial).unwrap();
+        assert_eq!(order.state, OrderState::Open);
+        assert_eq!(order.quantity, 0.2);
+
+        // Retried as a timeout, final as a rejection
+        let timeout = opened(
+            request(OrderType::Market),
+            response(Decimal::ZERO, Err(OrderError::Connectivity(ConnectivityError::Timeout))),
+        );
+        assert!(matches!(timeout, Err(UnindexedClientError::Connectivity(_))));
+        let rejected = opened(
+            request(OrderType::Market),
+            response(Decimal::ZERO, Err(OrderError::Rejected(ApiError::RateLimit))),
+        );
+        assert!(matches!(rejected, Err(UnindexedClientError::Api(ApiError::RateLimit))));
+    }
+
+    #[tokio::test]
+    async fn test_mock_exchange_fills_market_orders_of_its_exchange() {
+        let exchange = mock_exchange(&["BTC/USDT".to_string()], "USDT", 10_000.0, 10.0);
+        let client = BarterOrderClient::new(exchange, "binance");
+        let mut buy = request(OrderType::Market);
+        buy.kind = OrderKind::Buy;
+        buy.quantity = 0.1;
+
+        let order = client.open_order(buy.clone()).await.unwrap().unwrap();
+        assert_eq!(order.state, OrderState::Filled);
+        assert_eq!(order.quantity, 0.1);
+        assert_eq!(order.price, Some(50_000.0));
+
+        // Market orders only, and only for the exchange it stands in for
+        buy.order_type = OrderType::Limit;
+        assert!(matches!(
+            client.open_order(buy.clone()).await,
+            Err(UnindexedClientError::Api(ApiError::OrderRejected(_)))
+        ));
+        buy.order_type = OrderType::Market;
+        buy.exchange = "okx".to_string();
+        assert!(matches!(
+            client.open_order(buy).await,
+            Err(UnindexedClientError::Api(ApiError::OrderRejected(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_mock_exchange_reports_positions_from_its_trades() {
+        let symbols = ["BTC/USDT".to_string(), "ETH/USDT".to_string()];
+        let exchange = mock_exchange(&symbols, "USDT", 10_000.0, 0.0);
+        let client = BarterOrderClient::new(exchange, "Binance").with_symbols(&symbols);
+        assert!(client.fetch_positions().await.unwrap().is_empty());
+
+        let mut order = request(OrderType::Market);
+        order.kind = OrderKind::Buy;
+        order.quantity = 0.15;
+        client.open_order(order.clone()).await.unwrap();
+        order.kind = OrderKind::Sell;
+        order.quantity = 0.05;
+        client.open_order(order).await.unwrap();
+
+        let held = client.fetch_positions().await.unwrap();
+        assert_eq!(held.len(), 1);
+        assert_eq!(held[0].exchange, "binance");
+        assert_eq!(held[0].symbol, "BTC/USDT");
+        assert_eq!(held[0].side, PositionSide::Long);
+        assert!((held[0].quantity - 0.1).abs() < 1e-9);
+        assert_eq!(held[0].entry_price, 50_000.0);
+
+        assert!(client.covers("binance"));
+        assert!(!client.covers("okx"));
+    }
+}
@@ -0,0 +1,244 @@
+use crate::liquidity::MarketSnapshotCache;
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+pub const RSI_PERIOD: usize = 14;
+// Trade prices kept per symbol for the SMA and volatility
+const PRICE_WINDOW: usize = 50;
+// Lookback of `price_change_pct` until a config sets it
+pub const DEFAULT_PRICE_CHANGE_SECS: u64 = 300;
+// Spacing of the prices kept for `price_change_pct`, so a busy symbol holds at most one per
+// second of the lookback rather than every trade
+const PRICE_CHANGE_RESOLUTION_MS: i64 = 1_000;
+
+// Indicators available to strategies alongside the anomaly, `None` until enough data arrived
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MarketContext {
+    pub last_price: Option<f64>,
+    pub rsi: Option<f64>,
+    pub sma: Option<f64>,
+    // Standard deviation of log returns over the price window
+    pub volatility: Option<f64>,
+    // Percentage move from the oldest price within the configured lookback
+    pub price_change_pct: Option<f64>,
+    pub spread_bps: Option<f64>,
+    pub volume_24h: Option<f64>,
+}
+
+#[derive(Default)]
+struct PriceSeries {
+    // The latest trades, for the indicators
+    recent: VecDeque<f64>,
+    // Prices sampled across the lookback, for the price change
+    sampled: VecDeque<(DateTime<Utc>, f64)>,
+}
+
+pub struct MarketContextCache {
+    prices: DashMap<String, PriceSeries>,
+    market: Arc<MarketSnapshotCache>,
+    price_change_secs: AtomicU64,
+}
+
+impl MarketContextCache {
+    pub fn new(market: Arc<MarketSnapshotCache>) -> Self {
+        Self {
+            prices: DashMap::new(),
+            market,
+            price_change_secs: AtomicU64::new(DEFAULT_PRICE_CHANGE_SECS),
+        }
+    }
+
+    pub fn set_price_change_window(&self, secs: u64) {
+        self.price_change_secs.store(secs, Ordering::Relaxed);
+    }
+
+    fn price_change_window(&self) -> Duration {
+        Duration::seconds(self.price_change_secs.load(Ordering::Relaxed) as i64)
+    }
+
+    pub fn record_price(&self, key: &str, price: f64) {
+        self.record_price_at(key, price, Utc::now());
+    }
+
+    pub fn record_price_at(&self, key: &str, price: f64, at: DateTime<Utc>) {
+        let window = self.price_change_window();
+        // Only a key's first price allocates it
+        let mut series = match self.prices.get_mut(key) {
+            Some(series) => series,
+            None => self.prices.entry(key.to_string()).or_default(),
+        };
+        series.recent.push_back(price);
+        if series.recent.len() > PRICE_WINDOW {
+            series.recent.pop_front();
+        }
+
+        let due = series.sampled.back().is_none_or(|(last, _)| {
+            (at - *last).num_milliseconds() >= PRICE_CHANGE_RESOLUTION_MS
+        });
+        if due {
+            series.sampled.push_back((at, price));
+        } else if let Some(last) = series.sampled.back_mut() {
+            // The latest price stands in for its slot so the change always ends at it
+            last.1 = price;
+        }
+        while series.sampled.front().is_some_and(|(first, _)| at - *first > window) {
+            series.sampled.pop_front();
+        }
+    }
+
+    pub fn last_price(&self, key: &str) -> Option<f64> {
+        self.prices.get(key).and_then(|series| series.recent.back().copied())
+    }
+
+    pub fn context(&self, key: &str, now: DateTime<Utc>) -> MarketContext {
+        let (last_price, rsi, sma, volatility, price_change_pct) = match self.prices.get(key) {
+            Some(series) => {
+                let prices: Vec<f64> = series.recent.iter().copied().collect();
+                let since = now - self.price_change_window();
+                let change = series
+                    .sampled
+                    .iter()
+                    .find(|(at, _)| *at >= since)
+                    .zip(prices.last())
+                    .filter(|((_, first), _)| *first > 0.0)
+                    .map(|((_, first), last)| (last - first) / first * 100.0);
+                (
+                    prices.last().copied(),
+                    rsi(&prices, RSI_PERIOD),
+                    sma(&prices),
+                    volatility(&prices),
+                    change,
+                )
+            }
+            None => (None, None, None, None, None),
+        };
+
+        MarketContext {
+            last_price,
+            rsi,
+            sma,
+            volatility,
+            price_change_pct,
+            spread_bps: self.market.l1(key).and_then(|l1| l1.spread_bps()),
+            volume_24h: self.market.volume_24h(key, now),
+        }
+    }
+}
+
+// Simple-average RSI over the last `period` price changes
+pub fn rsi(prices: &[f64], period: usize) -> Option<f64> {
+    if period == 0 || prices.len() < period + 1 {
+        return None;
+    }
+
+    let (gains, losses) = prices[prices.len() - period - 1..]
+        .windows(2)
+        .map(|w| w[1] - w[0])
+        .fold((0.0, 0.0), |(gains, losses), change| {
+            if change > 0.0 {
+                (gains + change, losses)
+            } else {
+                (gains, losses - change)
+            }
+        });
+
+    if losses == 0.0 {
+        return Some(if gains == 0.0 { 50.0 } else { 100.0 });
+    }
+    Some(100.0 - 100.0 / (1.0 + gains / losses))
+}
+
+fn sma(prices: &[f64]) -> Option<f64> {
+    (!prices.is_empty()).then(|| prices.iter().sum::<f64>() / prices.len() as f64)
+}
+
+fn volatility(prices: &[f64]) -> Option<f64> {
+    let returns: Vec<f64> = prices
+        .windows(2)
+        .filter(|w| w[0] > 0.0 && w[1] > 0.0)
+        .map(|w| (w[1] / w[0]).ln())
+        .collect();
+    if returns.len() < 2 {
+        return None;
+    }
+
+    let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+    let variance =
+        returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (returns.len() - 1) as f64;
+    Some(variance.sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rsi_uses_latest_changes() {
+        // Early rally followed by a steady decline, only the decline is in the period
+        let mut prices: Vec<f64> = (0..10).map(|i| 100.0 + i as f64).collect();
+        prices.extend((0..=RSI_PERIOD).map(|i| 109.0 - i as f64));
+
+        assert_eq!(rsi(&prices, RSI_PERIOD), Some(0.0));
+        assert_eq!(rsi(&prices[..5], RSI_PERIOD), None);
+
+        let alternating = [100.0, 101.0, 100.0, 101.0, 100.0];
+        assert_eq!(rsi(&alternating, 4), Some(50.0));
+    }
+
+    #[test]
+    fn test_context_combines_prices_and_snapshots() {
+        let cache = MarketContextCache::new(Arc::new(MarketSnapshotCache::new()));
+        for i in 0..20 {
+            cache.record_price("binance:BTC/USDT", 100.0 + (i % 3) as f64);
+        }
+
+        let context = cache.context("binance:BTC/USDT", Utc::now());
+        assert!(context.rsi.is_some());
+        assert!(context.volatility.unwrap() > 0.0);
+        assert_eq!(context.spread_bps, None);
+        assert_eq!(cache.context("binance:ETH/USDT", Utc::now()), MarketContext::default());
+    }
+
+    #[test]
+    fn test_price_change_only_looks_back_the_lookback() {
+        let cache = MarketContextCache::new(Arc::new(MarketSnapshotCache::new()));
+        let now = Utc::now();
+        let key = "binance:BTC/USDT";
+        let lookback = DEFAULT_PRICE_CHANGE_SECS as i64;
+        cache.record_price_at(key, 50.0, now - Duration::seconds(lookback + 60));
+        cache.record_price_at(key, 100.0, now - Duration::seconds(120));
+        cache.record_price_at(key, 103.0, now);
+
+        let change = cache.context(key, now).price_change_pct.unwrap();
+        assert!((change - 3.0).abs() < 1e-9);
+        assert_eq!(cache.context(key, now).last_price, Some(103.0));
+
+        // A shorter window starts after the 100.0 trade
+        cache.set_price_change_window(60);
+        assert_eq!(cache.context(key, now).price_change_pct, Some(0.0));
+    }
+
+    #[test]
+    fn test_price_change_reaches_past_the_indicator_window() {
+        let cache = MarketContextCache::new(Arc::new(MarketSnapshotCache::new()));
+        let now = Utc::now();
+        let key = "binance:BTC/USDT";
+        // Ten trades a second for four minutes, far more than the indicators keep
+        for i in 0..2_400 {
+            let at = now - Duration::milliseconds(240_000 - i * 100);
+            cache.record_price_at(key, 100.0 + i as f64 / 100.0, at);
+        }
+
+        let change = cache.context(key, now).price_change_pct.unwrap();
+        assert!((change - 23.99).abs() < 1e-6);
+        assert!(cache.prices.get(key).unwrap().sampled.len() <= 241);
+    }
+}
@@ -0,0 +1,295 @@
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use monitor_core::{CooldownConfig, MonitorError, Result};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+use tracing::{error, info};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CooldownKind {
+    StrategySymbol,
+    Symbol,
+    StopLossReentry,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CooldownStatus {
+    pub kind: CooldownKind,
+    pub symbol: String,
+    pub strategy: Option<String>,
+    pub until: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CooldownState {
+    // Keyed by "strategy|exchange:symbol"
+    pub strategy_trades: Vec<(String, DateTime<Utc>)>,
+    // Keyed by "exchange:symbol"
+    pub symbol_trades: Vec<(String, DateTime<Utc>)>,
+    pub stop_losses: Vec<(String, DateTime<Utc>)>,
+}
+
+pub trait CooldownStore: Send + Sync {
+    fn load(&self) -> Result<CooldownState>;
+    fn save(&self, state: &CooldownState) -> Result<()>;
+}
+
+pub struct FileCooldownStore {
+    path: PathBuf,
+}
+
+impl FileCooldownStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl CooldownStore for FileCooldownStore {
+    fn load(&self) -> Result<CooldownState> {
+        match std::fs::read(&self.path) {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(CooldownState::default()),
+            Err(e) => Err(MonitorError::Other(format!(
+                "Failed to read cooldown state {}: {}",
+                self.path.display(),
+                e
+            ))),
+        }
+    }
+
+    fn save(&self, state: &CooldownState) -> Result<()> {
+        // Write then rename so a crash never leaves a truncated file behind
+        let tmp = self.path.with_extension("tmp");
+        let write = || -> std::io::Result<()> {
+            if let Some(parent) = self.path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&tmp, serde_json::to_vec(state)?)?;
+            std::fs::rename(&tmp, &self.path)
+        };
+        write().map_err(|e| {
+            MonitorError::Other(format!(
+                "Failed to write cooldown state {}: {}",
+                self.path.display(),
+                e
+            ))
+        })
+    }
+}
+
+pub struct CooldownTracker {
+    config: RwLock<CooldownConfig>,
+    strategy_trades: DashMap<String, DateTime<Utc>>,
+    symbol_trades: DashMap<String, DateTime<Utc>>,
+    stop_losses: DashMap<String, DateTime<Utc>>,
+    store: Option<Arc<dyn CooldownStore>>,
+    // Set by every change since the last `flush`
+    dirty: AtomicBool,
+}
+
+impl CooldownTracker {
+    pub fn new(config: CooldownConfig) -> Self {
+        Self {
+            config: RwLock::new(config),
+            strategy_trades: DashMap::new(),
+            symbol_trades: DashMap::new(),
+            stop_losses: DashMap::new(),
+            store: None,
+            dirty: AtomicBool::new(false),
+        }
+    }
+
+    // Restores persisted timestamps. Changes reach the store on the next `flush`
+    pub fn with_store(mut self, store: Arc<dyn CooldownStore>) -> Result<Self> {
+        let state = store.load()?;
+        self.strategy_trades.extend(state.strategy_trades);
+        self.symbol_trades.extend(state.symbol_trades);
+        self.stop_losses.extend(state.stop_losses);
+        info!(
+            "Restored cooldown state for {} symbols",
+            self.symbol_trades.len() + self.stop_losses.len()
+        );
+
+        self.store = Some(store);
+        Ok(self)
+    }
+
+    pub fn set_config(&self, config: CooldownConfig) {
+        *self.config.write() = config;
+    }
+
+    // Returns the cooldown that blocks a new trade, if any
+    pub fn check(&self, strategy: &str, key: &str, now: DateTime<Utc>) -> Option<CooldownStatus> {
+        self.active(now)
+            .into_iter()
+            .filter(|c| c.symbol == key)
+            .find(|c| c.strategy.as_deref().is_none_or(|s| s == strategy))
+    }
+
+    pub fn record_trade(&self, strategy: &str, key: &str, at: DateTime<Utc>) {
+        self.strategy_trades.insert(Self::strategy_key(strategy, key), at);
+        self.symbol_trades.insert(key.to_string(), at);
+        self.dirty.store(true, Ordering::Release);
+    }
+
+    pub fn record_stop_loss(&self, key: &str, at: DateTime<Utc>) {
+        self.stop_losses.insert(key.to_string(), at);
+        self.dirty.store(true, Ordering::Release);
+    }
+
+    // Every cooldown still in force at `now`
+    pub fn active(&self, now: DateTime<Utc>) -> Vec<CooldownStatus> {
+        let config = self.config.read().clone();
+        let mut active = Vec::new();
+
+        let per_strategy = Duration::seconds(config.per_strategy_symbol_secs as i64);
+        for entry in self.strategy_trades.iter() {
+            let until = *entry.value() + per_strategy;
+            if until > now {
+                let (strategy, symbol) = entry.key().split_once('|').unwrap_or(("", entry.key()));
+                active.push(CooldownStatus {
+                    kind: CooldownKind::StrategySymbol,
+                    symbol: symbol.to_string(),
+                    strategy: Some(strategy.to_string()),
+                    until,
+                });
+            }
+        }
+
+        let per_symbol = Duration::seconds(config.per_symbol_secs as i64);
+        for entry in self.symbol_trades.iter() {
+            let until = *entry.value() + per_symbol;
+            if until > now {
+                active.push(CooldownStatus {
+                    kind: CooldownKind::Symbol,
+                    symbol: entry.key().clone(),
+                    strategy: None,
+                    until,
+                });
+            }
+        }
+
+        if let Some(minutes) = config.stop_loss_reentry_minutes {
+            for entry in self.stop_losses.iter() {
+                let until = *entry.value() + Duration::minutes(minutes as i64);
+                if until > now {
+                    active.push(CooldownStatus {
+                        kind: CooldownKind::StopLossReentry,
+                        symbol: entry.key().clone(),
+                        strategy: None,
+                        until,
+                    });
+                }
+            }
+        }
+
+        // Longest-lasting block first, so `check` reports when trading can resume
+        active.sort_by(|a, b| b.until.cmp(&a.until));
+        active
+    }
+
+    // Writes the timestamps to the store if anything changed since the last call. Blocks on
+    // the store, so it runs off the trading path: `AutoTrader::start` calls it periodically
+    // and once more on shutdown
+    pub fn flush(&self) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        if !self.dirty.swap(false, Ordering::AcqRel) {
+            return;
+        }
+
+        let collect = |map: &DashMap<String, DateTime<Utc>>| {
+            map.iter().map(|e| (e.key().clone(), *e.value())).collect()
+        };
+        let state = CooldownState {
+            strategy_trades: collect(&self.strategy_trades),
+            symbol_trades: collect(&self.symbol_trades),
+            stop_losses: collect(&self.stop_losses),
+        };
+
+        if let Err(e) = store.save(&state) {
+            error!("Failed to persist cooldown state: {}", e);
+            // Retried on the next flush
+            self.dirty.store(true, Ordering::Release);
+        }
+    }
+
+    fn strategy_key(strategy: &str, key: &str) -> String {
+        format!("{}|{}", strategy, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "binance:BTC/USDT";
+
+    fn tracker() -> CooldownTracker {
+        CooldownTracker::new(CooldownConfig {
+            per_strategy_symbol_secs: 300,
+            per_symbol_secs: 60,
+            stop_loss_reentry_minutes: Some(30),
+            state_path: None,
+        })
+    }
+
+    #[test]
+    fn test_back_to_back_signals_inside_and_outside_windows() {
+        let tracker = tracker();
+        let start = Utc::now();
+        tracker.record_trade("anomaly", KEY, start);
+
+        // Inside both windows the longer strategy cooldown is reported
+        let block = tracker.check("anomaly", KEY, start + Duration::seconds(5)).unwrap();
+        assert_eq!(block.kind, CooldownKind::StrategySymbol);
+
+        // Another strategy is only held by the per-symbol window
+        let block = tracker.check("momentum", KEY, start + Duration::seconds(5)).unwrap();
+        assert_eq!(block.kind, CooldownKind::Symbol);
+        assert!(tracker.check("momentum", KEY, start + Duration::seconds(61)).is_none());
+
+        assert!(tracker.check("anomaly", KEY, start + Duration::seconds(301)).is_none());
+        assert!(tracker.check("anomaly", "okx:BTC/USDT", start).is_none());
+    }
+
+    #[test]
+    fn test_stop_loss_blocks_reentry() {
+        let tracker = tracker();
+        let start = Utc::now();
+        tracker.record_stop_loss(KEY, start);
+
+        let block = tracker.check("anomaly", KEY, start + Duration::minutes(29)).unwrap();
+        assert_eq!(block.kind, CooldownKind::StopLossReentry);
+        assert!(tracker.check("anomaly", KEY, start + Duration::minutes(31)).is_none());
+    }
+
+    #[test]
+    fn test_cooldowns_survive_restart() {
+        let path = std::env::temp_dir().join(format!("cooldowns-{}.json", uuid::Uuid::new_v4()));
+        let start = Utc::now();
+
+        let tracker = tracker()
+            .with_store(Arc::new(FileCooldownStore::new(&path)))
+            .unwrap();
+        tracker.record_trade("anomaly", KEY, start);
+        tracker.flush();
+        drop(tracker);
+
+        let restarted = self::tracker()
+            .with_store(Arc::new(FileCooldownStore::new(&path)))
+            .unwrap();
+        assert!(restarted.check("anomaly", KEY, start + Duration::seconds(5)).is_some());
+
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,124 @@
+use crate::SignalStrength;
+use monitor_core::{FeeScheduleConfig, SlippageConfig, TradingCostsConfig};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Liquidity {
+    // Resting limit orders
+    Maker,
+    // Market orders, and stops once triggered
+    Taker,
+}
+
+// Maker and taker fees per exchange, charged on the notional of each fill
+#[derive(Debug, Clone, Default)]
+pub struct FeeModel {
+    schedules: HashMap<String, FeeScheduleConfig>,
+    default: FeeScheduleConfig,
+}
+
+impl FeeModel {
+    pub fn from_config(config: &TradingCostsConfig) -> Self {
+        Self {
+            schedules: config.fees.clone(),
+            default: config.default_fees,
+        }
+    }
+
+    // Fraction of notional, 10 bps is 0.001
+    pub fn rate(&self, exchange: &str, liquidity: Liquidity) -> f64 {
+        let schedule = self.schedules.get(exchange).unwrap_or(&self.default);
+        let bps = match liquidity {
+            Liquidity::Maker => schedule.maker_bps,
+            Liquidity::Taker => schedule.taker_bps,
+        };
+        bps / 10_000.0
+    }
+
+    pub fn fee(&self, exchange: &str, notional: f64, liquidity: Liquidity) -> f64 {
+        notional.abs() * self.rate(exchange, liquidity)
+    }
+}
+
+// Expected distance between the quoted price and a market fill, always against the order
+#[derive(Debug, Clone, Default)]
+pub struct SlippageModel {
+    config: SlippageConfig,
+}
+
+impl SlippageModel {
+    pub fn from_config(config: &TradingCostsConfig) -> Self {
+        Self {
+            config: config.slippage.clone(),
+        }
+    }
+
+    pub fn bps(&self, strength: &SignalStrength) -> f64 {
+        match &self.config {
+            SlippageConfig::Fixed { bps } => *bps,
+            SlippageConfig::ByStrength {
+                weak_bps,
+                medium_bps,
+                strong_bps,
+            } => match strength {
+                SignalStrength::Weak => *weak_bps,
+                SignalStrength::Medium => *medium_bps,
+                SignalStrength::Strong => *strong_bps,
+            },
+        }
+    }
+
+    // Where a market order quoted at `price` is expected to fill, buys above and sells below
+    pub fn apply(&self, price: f64, buy: bool, strength: &SignalStrength) -> f64 {
+        let slip = price * self.bps(strength) / 10_000.0;
+        if buy {
+            price + slip
+        } else {
+            price - slip
+        }
+    }
+
+    // Exits carry no signal, they slip as a medium one would
+    pub fn exit_price(&self, price: f64, buy: bool) -> f64 {
+        self.apply(price, buy, &SignalStrength::Medium)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn costs() -> TradingCostsConfig {
+        serde_json::from_value(serde_json::json!({
+            "fees": { "binance": { "maker_bps": 2.0, "taker_bps": 10.0 } },
+            "default_fees": { "taker_bps": 5.0 },
+            "slippage": { "mode": "by_strength", "weak_bps": 2.0, "medium_bps": 5.0, "strong_bps": 20.0 },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_fees_per_exchange_fall_back_to_the_default() {
+        let fees = FeeModel::from_config(&costs());
+        assert!((fees.fee("binance", 50_000.0, Liquidity::Taker) - 50.0).abs() < 1e-9);
+        assert!((fees.fee("binance", -50_000.0, Liquidity::Maker) - 10.0).abs() < 1e-9);
+        assert!((fees.fee("okx", 50_000.0, Liquidity::Taker) - 25.0).abs() < 1e-9);
+        assert_eq!(fees.fee("okx", 50_000.0, Liquidity::Maker), 0.0);
+
+        // Nothing configured charges nothing
+        assert_eq!(FeeModel::default().fee("binance", 50_000.0, Liquidity::Taker), 0.0);
+    }
+
+    #[test]
+    fn test_slippage_moves_against_the_order() {
+        let slippage = SlippageModel::from_config(&costs());
+        assert!((slippage.apply(10_000.0, true, &SignalStrength::Strong) - 10_020.0).abs() < 1e-9);
+        assert!((slippage.apply(10_000.0, false, &SignalStrength::Weak) - 9_998.0).abs() < 1e-9);
+        assert!((slippage.exit_price(10_000.0, false) - 9_995.0).abs() < 1e-9);
+
+        let fixed = SlippageModel::default();
+        assert_eq!(fixed.apply(10_000.0, true, &SignalStrength::Strong), 10_000.0);
+    }
+}
@@ -0,0 +1,242 @@
+use crate::{PositionSide, TradingSignal};
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use monitor_core::DcaConfig;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// A signal being entered as `clips` market orders `interval_secs` apart
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DcaPlan {
+    pub id: uuid::Uuid,
+    pub signal: TradingSignal,
+    pub strategy: String,
+    pub side: PositionSide,
+    pub total_quantity: f64,
+    pub clip_quantity: f64,
+    pub clips: u32,
+    pub clips_sent: u32,
+    pub sent_quantity: f64,
+    pub interval_secs: u64,
+    pub next_clip_at: DateTime<Utc>,
+    pub abort_threshold_pct: Option<f64>,
+    pub started_at: DateTime<Utc>,
+}
+
+impl DcaPlan {
+    // The first clip is due at `now`
+    pub fn new(
+        signal: TradingSignal,
+        strategy: &str,
+        side: PositionSide,
+        quantity: f64,
+        clips: u32,
+        config: &DcaConfig,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let clips = clips.max(1);
+        Self {
+            id: uuid::Uuid::new_v4(),
+            signal,
+            strategy: strategy.to_string(),
+            side,
+            total_quantity: quantity,
+            clip_quantity: quantity / clips as f64,
+            clips,
+            clips_sent: 0,
+            sent_quantity: 0.0,
+            interval_secs: config.interval_secs,
+            next_clip_at: now,
+            abort_threshold_pct: config.abort_threshold_pct,
+            started_at: now,
+        }
+    }
+
+    // The last clip takes whatever rounding left over
+    pub fn next_clip_quantity(&self) -> f64 {
+        if self.clips_sent + 1 >= self.clips {
+            (self.total_quantity - self.sent_quantity).max(0.0)
+        } else {
+            self.clip_quantity
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.clips_sent >= self.clips
+    }
+
+    // How far `price` has moved against the entry since the signal, in percent
+    pub fn adverse_move_pct(&self, price: f64) -> f64 {
+        if self.signal.price <= 0.0 {
+            return 0.0;
+        }
+        let change = (price - self.signal.price) / self.signal.price * 100.0;
+        match self.side {
+            PositionSide::Long => -change,
+            PositionSide::Short => change,
+        }
+    }
+
+    pub fn should_abort(&self, price: f64) -> bool {
+        self.abort_threshold_pct
+            .is_some_and(|threshold| self.adverse_move_pct(price) >= threshold)
+    }
+}
+
+// Plans in progress, a plan is dropped once its last clip is sent or it is cancelled
+#[derive(Debug, Default)]
+pub struct DcaPlans {
+    plans: DashMap<uuid::Uuid, DcaPlan>,
+    // Set by every change, cleared by `take_changed` once the plans are saved
+    changed: AtomicBool,
+}
+
+impl DcaPlans {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn start(&self, plan: DcaPlan) {
+        self.plans.insert(plan.id, plan);
+        self.changed.store(true, Ordering::SeqCst);
+    }
+
+    // Oldest first
+    pub fn list(&self) -> Vec<DcaPlan> {
+        let mut plans: Vec<DcaPlan> = self.plans.iter().map(|plan| plan.clone()).collect();
+        plans.sort_by_key(|plan| plan.started_at);
+        plans
+    }
+
+    pub fn get(&self, id: uuid::Uuid) -> Option<DcaPlan> {
+        self.plans.get(&id).map(|plan| plan.clone())
+    }
+
+    // Clips already sent stay in the position
+    pub fn cancel(&self, id: uuid::Uuid) -> Option<DcaPlan> {
+        let plan = self.plans.remove(&id).map(|(_, plan)| plan);
+        if plan.is_some() {
+            self.changed.store(true, Ordering::SeqCst);
+        }
+        plan
+    }
+
+    // Ends the plans entering a market, e.g. once its position has closed
+    pub fn cancel_for(&self, exchange: &str, symbol: &str) -> Vec<DcaPlan> {
+        let ids: Vec<uuid::Uuid> = self
+            .plans
+            .iter()
+            .filter(|plan| plan.signal.exchange == exchange && plan.signal.symbol == symbol)
+            .map(|plan| plan.id)
+            .collect();
+        ids.into_iter().filter_map(|id| self.cancel(id)).collect()
+    }
+
+    pub fn has_active(&self, exchange: &str, symbol: &str) -> bool {
+        self.plans
+            .iter()
+            .any(|plan| plan.signal.exchange == exchange && plan.signal.symbol == symbol)
+    }
+
+    pub fn due(&self, now: DateTime<Utc>) -> Vec<DcaPlan> {
+        self.plans
+            .iter()
+            .filter(|plan| plan.next_clip_at <= now)
+            .map(|plan| plan.clone())
+            .collect()
+    }
+
+    // Claims the next clip of a due plan, handing back its quantity and the plan as it stands
+    // after it. `None` when the plan was cancelled or the clip already claimed
+    pub fn claim_clip(&self, id: uuid::Uuid, now: DateTime<Utc>) -> Option<(f64, DcaPlan)> {
+        let mut plan = self.plans.get_mut(&id)?;
+        if plan.next_clip_at > now || plan.is_complete() {
+            return None;
+        }
+        let quantity = plan.next_clip_quantity();
+        plan.clips_sent += 1;
+        plan.sent_quantity += quantity;
+        plan.next_clip_at = now + Duration::seconds(plan.interval_secs as i64);
+        let after = plan.clone();
+        drop(plan);
+        if after.is_complete() {
+            self.plans.remove(&id);
+        }
+        self.changed.store(true, Ordering::SeqCst);
+        Some((quantity, after))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plans.is_empty()
+    }
+
+    // Whether the plans changed since the last call
+    pub fn take_changed(&self) -> bool {
+        self.changed.swap(false, Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    fn plan(side: PositionSide, now: DateTime<Utc>) -> DcaPlan {
+        let signal = TradingSignal {
+            timestamp: now,
+            ..fixtures::signal("BTC/USDT", 50_000.0)
+        };
+        let config = DcaConfig {
+            enabled: true,
+            clips: 3,
+            interval_secs: 30,
+            abort_threshold_pct: Some(1.0),
+        };
+        DcaPlan::new(signal, "default", side, 1.0, 3, &config, now)
+    }
+
+    #[test]
+    fn test_clips_add_up_to_the_total_and_the_plan_ends() {
+        let now = Utc::now();
+        let plans = DcaPlans::new();
+        let plan = plan(PositionSide::Long, now);
+        let id = plan.id;
+        plans.start(plan);
+        assert!(plans.take_changed());
+        assert!(!plans.take_changed());
+
+        let (first, after) = plans.claim_clip(id, now).unwrap();
+        assert!(plans.take_changed());
+        assert!((first - 1.0 / 3.0).abs() < 1e-12);
+        assert_eq!(after.next_clip_at, now + Duration::seconds(30));
+        // Not due again until the interval has passed
+        assert!(plans.claim_clip(id, now + Duration::seconds(29)).is_none());
+        assert!(plans.due(now + Duration::seconds(29)).is_empty());
+
+        let (second, _) = plans.claim_clip(id, now + Duration::seconds(30)).unwrap();
+        let (last, after) = plans.claim_clip(id, now + Duration::seconds(60)).unwrap();
+        assert!(after.is_complete());
+        assert!((first + second + last - 1.0).abs() < 1e-12);
+        assert!(plans.is_empty());
+        assert!(plans.claim_clip(id, now + Duration::seconds(90)).is_none());
+    }
+
+    #[test]
+    fn test_abort_threshold_is_against_the_side() {
+        let now = Utc::now();
+        let long = plan(PositionSide::Long, now);
+        assert!(!long.should_abort(49_600.0));
+        assert!(long.should_abort(49_450.0));
+        assert!(!long.should_abort(52_000.0));
+
+        let short = plan(PositionSide::Short, now);
+        assert!(short.should_abort(50_500.0));
+        assert!(!short.should_abort(49_000.0));
+
+        let plans = DcaPlans::new();
+        plans.start(long);
+        assert!(plans.has_active("binance", "BTC/USDT"));
+        assert_eq!(plans.cancel_for("binance", "BTC/USDT").len(), 1);
+        assert!(!plans.has_active("binance", "BTC/USDT"));
+    }
+}
@@ -0,0 +1,376 @@
+use crate::{
+    context::MarketContext, strategy, SignalStrength, SignalType, TradingSignal, TradingStrategy,
+};
+use monitor_anomaly::AnomalyDetection;
+use monitor_core::{CombinationPolicy, EnsembleConfig, MonitorError, Result, TradingConfig};
+use std::collections::HashMap;
+use tracing::{debug, info};
+
+fn strength_score(strength: &SignalStrength) -> f64 {
+    match strength {
+        SignalStrength::Weak => 1.0 / 3.0,
+        SignalStrength::Medium => 2.0 / 3.0,
+        SignalStrength::Strong => 1.0,
+    }
+}
+
+fn strength_from_score(score: f64) -> SignalStrength {
+    if score >= 0.9 {
+        SignalStrength::Strong
+    } else if score >= 0.5 {
+        SignalStrength::Medium
+    } else {
+        SignalStrength::Weak
+    }
+}
+
+// +1 for buys, -1 for sells, holds abstain
+fn direction(signal_type: &SignalType) -> f64 {
+    match signal_type {
+        SignalType::Buy => 1.0,
+        SignalType::Sell => -1.0,
+        SignalType::Hold => 0.0,
+    }
+}
+
+// Runs every member on each anomaly and combines their signals under `policy`. Buys and
+// sells that cannot be settled by the policy merge into a Hold.
+pub struct StrategyEnsemble {
+    members: Vec<Box<dyn TradingStrategy>>,
+    policy: CombinationPolicy,
+    weights: HashMap<String, f64>,
+    min_score: f64,
+}
+
+impl StrategyEnsemble {
+    pub fn new(members: Vec<Box<dyn TradingStrategy>>, config: &EnsembleConfig) -> Self {
+        Self {
+            members,
+            policy: config.policy,
+            weights: config.weights.clone(),
+            min_score: config.min_score,
+        }
+    }
+
+    // Builds the members listed in `trading.ensemble.strategies`
+    pub fn from_config(config: &TradingConfig) -> Result<Self> {
+        if config.ensemble.strategies.is_empty() {
+            return Err(MonitorError::Configuration(
+                "Strategy ensemble needs at least one strategy".to_string(),
+            ));
+        }
+        let members = config
+            .ensemble
+            .strategies
+            .iter()
+            .map(|name| strategy::build(name, config))
+            .collect::<Result<Vec<_>>>()?;
+        info!(
+            "Strategy ensemble of {} with {:?} policy",
+            config.ensemble.strategies.join(", "),
+            config.ensemble.policy
+        );
+        Ok(Self::new(members, &config.ensemble))
+    }
+
+    fn weight(&self, name: &str) -> f64 {
+        self.weights.get(name).copied().unwrap_or(1.0)
+    }
+
+    // Side the policy settles on from the members' votes; Hold when buys and sells conflict
+    fn decide(&self, votes: &[(String, TradingSignal)]) -> Option<(SignalType, SignalStrength)> {
+        let buys = votes.iter().filter(|(_, s)| matches!(s.signal_type, SignalType::Buy)).count();
+        let sells = votes.iter().filter(|(_, s)| matches!(s.signal_type, SignalType::Sell)).count();
+        let conflict = buys > 0 && sells > 0;
+        let hold = || conflict.then_some((SignalType::Hold, SignalStrength::Weak));
+        let members = self.members.len();
+
+        let side = match self.policy {
+            CombinationPolicy::Any if conflict => return hold(),
+            CombinationPolicy::Any if buys > 0 => SignalType::Buy,
+            CombinationPolicy::Any if sells > 0 => SignalType::Sell,
+            CombinationPolicy::Majority if buys * 2 > members => SignalType::Buy,
+            CombinationPolicy::Majority if sells * 2 > members => SignalType::Sell,
+            CombinationPolicy::All if buys == members => SignalType::Buy,
+            CombinationPolicy::All if sells == members => SignalType::Sell,
+            CombinationPolicy::WeightedScore => {
+                let total: f64 = self.members.iter().map(|m| self.weight(m.name())).sum();
+                if total <= 0.0 {
+                    return None;
+                }
+                let score = votes
+                    .iter()
+                    .map(|(name, s)| {
+                        self.weight(name) * direction(&s.signal_type) * strength_score(&s.strength)
+                    })
+                    .sum::<f64>()
+                    / total;
+                debug!("Ensemble weighted score {:.3}", score);
+                if score.abs() < self.min_score {
+                    return hold();
+                }
+                let side = if score > 0.0 { SignalType::Buy } else { SignalType::Sell };
+                return Some((side, strength_from_score(score.abs())));
+            }
+            _ => return hold(),
+        };
+
+        // The strongest of the members that agree with the outcome
+        let strength = votes
+            .iter()
+            .filter(|(_, s)| direction(&s.signal_type) == direction(&side))
+            .map(|(_, s)| s.strength.clone())
+            .max_by(|a, b| strength_score(a).total_cmp(&strength_score(b)))?;
+        Some((side, strength))
+    }
+}
+
+impl TradingStrategy for StrategyEnsemble {
+    fn analyze(&mut self, anomaly: &AnomalyDetection, context: &MarketContext) -> Option<TradingSignal> {
+        // Every member sees every anomaly so stateful members stay in sync
+        let votes: Vec<(String, TradingSignal)> = self
+            .members
+            .iter_mut()
+            .filter_map(|member| {
+                let signal = member.analyze(anomaly, context)?;
+                Some((member.name().to_string(), signal))
+            })
+            .collect();
+        if votes.is_empty() {
+            return None;
+        }
+
+        let (signal_type, strength) = self.decide(&votes)?;
+        let reasons: Vec<String> = votes
+            .iter()
+            .map(|(name, s)| format!("{} {:?}: {}", name, s.signal_type, s.reason))
+            .collect();
+
+        Some(TradingSignal {
+            id: uuid::Uuid::new_v4(),
+            timestamp: anomaly.timestamp,
+            symbol: anomaly.symbol.clone(),
+            exchange: anomaly.exchange.clone(),
+            signal_type,
+            strength,
+            price: votes[0].1.price,
+            reason: format!(
+                "Ensemble {:?} ({}/{}): {}",
+                self.policy,
+                votes.len(),
+                self.members.len(),
+                reasons.join("; ")
+            ),
+            anomaly_id: Some(anomaly.id),
+        })
+    }
+
+    fn update_config(&mut self, config: TradingConfig) {
+        self.policy = config.ensemble.policy;
+        self.weights = config.ensemble.weights.clone();
+        self.min_score = config.ensemble.min_score;
+        for member in &mut self.members {
+            member.update_config(config.clone());
+        }
+    }
+
+    fn name(&self) -> &str {
+        "ensemble"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use monitor_anomaly::{AnomalyMetrics, AnomalySeverity};
+    use monitor_core::AnomalyType;
+
+    struct Fixed {
+        name: &'static str,
+        signal: Option<(SignalType, SignalStrength)>,
+    }
+
+    impl TradingStrategy for Fixed {
+        fn analyze(&mut self, anomaly: &AnomalyDetection, _context: &MarketContext) -> Option<TradingSignal> {
+            let (signal_type, strength) = self.signal.clone()?;
+            Some(TradingSignal {
+                id: uuid::Uuid::new_v4(),
+                timestamp: anomaly.timestamp,
+                symbol: anomaly.symbol.clone(),
+                exchange: anomaly.exchange.clone(),
+                signal_type,
+                strength,
+                price: anomaly.metrics.current_value,
+                reason: "fixed".to_string(),
+                anomaly_id: Some(anomaly.id),
+            })
+        }
+
+        fn update_config(&mut self, _config: TradingConfig) {}
+
+        fn name(&self) -> &str {
+            self.name
+        }
+    }
+
+    const NAMES: [&str; 4] = ["a", "b", "c", "d"];
+
+    fn ensemble(
+        policy: CombinationPolicy,
+        signals: Vec<Option<(SignalType, SignalStrength)>>,
+    ) -> StrategyEnsemble {
+        let members = signals
+            .into_iter()
+            .zip(NAMES)
+            .map(|(signal, name)| Box::new(Fixed { name, signal }) as Box<dyn TradingStrategy>)
+            .collect();
+        let config = EnsembleConfig {
+            policy,
+            ..Default::default()
+        };
+        StrategyEnsemble::new(members, &config)
+    }
+
+    fn anomaly() -> AnomalyDetection {
+        AnomalyDetection {
+            id: uuid::Uuid::new_v4(),
+            timestamp: Utc::now(),
+            symbol: "BTC/USDT".to_string(),
+            exchange: "binance".to_string(),
+            anomaly_type: AnomalyType::PriceSpike,
+            confidence: AnomalySeverity::High.confidence_floor(),
+            severity: AnomalySeverity::High,
+            metrics: AnomalyMetrics {
+                current_value: 42_000.0,
+                expected_value: 40_000.0,
+                deviation: 2_000.0,
+                z_score: Some(3.5),
+                percentage_change: Some(5.0),
+                historical_avg: None,
+                historical_std: None,
+                ..Default::default()
+            },
+            description: "spike".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn run(ensemble: &mut StrategyEnsemble) -> Option<(SignalType, SignalStrength)> {
+        ensemble
+            .analyze(&anomaly(), &MarketContext::default())
+            .map(|signal| (signal.signal_type, signal.strength))
+    }
+
+    fn buy(strength: SignalStrength) -> Option<(SignalType, SignalStrength)> {
+        Some((SignalType::Buy, strength))
+    }
+
+    fn sell(strength: SignalStrength) -> Option<(SignalType, SignalStrength)> {
+        Some((SignalType::Sell, strength))
+    }
+
+    #[test]
+    fn test_any() {
+        let mut single = ensemble(CombinationPolicy::Any, vec![None, buy(SignalStrength::Weak), None]);
+        assert!(matches!(run(&mut single), Some((SignalType::Buy, SignalStrength::Weak))));
+
+        let mut conflict = ensemble(
+            CombinationPolicy::Any,
+            vec![buy(SignalStrength::Strong), sell(SignalStrength::Weak)],
+        );
+        assert!(matches!(run(&mut conflict), Some((SignalType::Hold, _))));
+
+        let mut silent = ensemble(CombinationPolicy::Any, vec![None, None]);
+        assert!(run(&mut silent).is_none());
+    }
+
+    #[test]
+    fn test_majority() {
+        let mut two_of_three = ensemble(
+            CombinationPolicy::Majority,
+            vec![sell(SignalStrength::Medium), sell(SignalStrength::Strong), buy(SignalStrength::Strong)],
+        );
+        assert!(matches!(run(&mut two_of_three), Some((SignalType::Sell, SignalStrength::Strong))));
+
+        // Half is not a majority
+        let mut tie = ensemble(
+            CombinationPolicy::Majority,
+            vec![buy(SignalStrength::Medium), buy(SignalStrength::Medium), None, None],
+        );
+        assert!(run(&mut tie).is_none());
+
+        let mut split = ensemble(
+            CombinationPolicy::Majority,
+            vec![buy(SignalStrength::Medium), sell(SignalStrength::Medium), None],
+        );
+        assert!(matches!(run(&mut split), Some((SignalType::Hold, _))));
+    }
+
+    #[test]
+    fn test_all() {
+        let mut agree = ensemble(
+            CombinationPolicy::All,
+            vec![buy(SignalStrength::Weak), buy(SignalStrength::Medium)],
+        );
+        assert!(matches!(run(&mut agree), Some((SignalType::Buy, SignalStrength::Medium))));
+
+        let mut missing = ensemble(CombinationPolicy::All, vec![buy(SignalStrength::Strong), None]);
+        assert!(run(&mut missing).is_none());
+
+        let mut conflict = ensemble(
+            CombinationPolicy::All,
+            vec![buy(SignalStrength::Strong), sell(SignalStrength::Strong)],
+        );
+        assert!(matches!(run(&mut conflict), Some((SignalType::Hold, _))));
+    }
+
+    #[test]
+    fn test_weighted_score() {
+        let members: Vec<Box<dyn TradingStrategy>> = vec![
+            Box::new(Fixed { name: "a", signal: buy(SignalStrength::Strong) }),
+            Box::new(Fixed { name: "b", signal: sell(SignalStrength::Strong) }),
+            Box::new(Fixed { name: "c", signal: None }),
+        ];
+        let mut config = EnsembleConfig {
+            policy: CombinationPolicy::WeightedScore,
+            weights: HashMap::from([("a".to_string(), 4.0), ("b".to_string(), 1.0)]),
+            ..Default::default()
+        };
+        // (4 - 1) / 6 = 0.5 reaches the default threshold
+        let mut weighted = StrategyEnsemble::new(members, &config);
+        assert!(matches!(run(&mut weighted), Some((SignalType::Buy, SignalStrength::Medium))));
+
+        // Raising the threshold turns the disagreement into a Hold
+        config.min_score = 0.6;
+        let members: Vec<Box<dyn TradingStrategy>> = vec![
+            Box::new(Fixed { name: "a", signal: buy(SignalStrength::Strong) }),
+            Box::new(Fixed { name: "b", signal: sell(SignalStrength::Strong) }),
+            Box::new(Fixed { name: "c", signal: None }),
+        ];
+        let mut strict = StrategyEnsemble::new(members, &config);
+        assert!(matches!(run(&mut strict), Some((SignalType::Hold, _))));
+
+        // One weak voter out of three stays below the threshold without a conflict
+        let mut quiet = ensemble(
+            CombinationPolicy::WeightedScore,
+            vec![buy(SignalStrength::Weak), None, None],
+        );
+        assert!(run(&mut quiet).is_none());
+    }
+
+    #[test]
+    fn test_unknown_member_fails_to_build() {
+        let config: TradingConfig = serde_json::from_value(serde_json::json!({
+            "auto_trading_enabled": true,
+            "max_position_size": 1000.0,
+            "risk_percentage": 2.0,
+            "stop_loss_percentage": 3.0,
+            "take_profit_percentage": 6.0,
+            "ensemble": { "strategies": ["anomaly", "astrology"] },
+        }))
+        .unwrap();
+        let err = StrategyEnsemble::from_config(&config).err().unwrap().to_string();
+        assert!(err.contains("astrology"));
+    }
+}
@@ -1,25 +1,145 @@
 use crate::{
-    Position, PositionSide, RiskManager, TradingSignal, TradingStats, TradingStrategy,
+    circuit_breaker::{BreakerStatus, BreakerWindows, CircuitBreaker, Halt},
+    client::{
+        Order, OrderClient, OrderId, OrderKind, OrderState, OrderType, RequestCancel, RequestOpen,
+    },
+    clock::{Clock, SystemClock},
+    context::MarketContextCache,
+    cooldown::{CooldownStatus, CooldownTracker, FileCooldownStore},
+    costs::{FeeModel, Liquidity, SlippageModel},
+    dca::{DcaPlan, DcaPlans},
+    exits::{self, ExitFills, ExitLeg},
+    journal::{
+        self, JournalRecord, JournalStore, LifecycleEvent, LifecycleKind, SignalOutcome,
+        TradeJournal,
+    },
+    liquidity::{check_liquidity, MarketSnapshotCache},
+    orders::{self, Fill, OrderStatus, PendingAction, PendingOrder, PendingOrders},
+    overrides,
+    paper::PaperExecutionClient,
+    persistence::{ExecutedTrade, PositionStore, TradeKind},
+    portfolio::{self, PortfolioAccount},
+    reconcile::{self, AccountSource, Discrepancy, VenuePosition},
+    retry::{self, IntentKind, OrderIntent, OrderIntents, RetryPolicy},
+    risk::PendingEntry,
+    scaling::{self, ScalingDecision},
+    shutdown::{PositionShutdown, ShutdownOutcome, ShutdownReport},
+    stale::{PriceSource, StaleAction, StalePriceGuard},
+    stats::{EquityPoint, StatsEngine},
+    Position, PositionSide, RiskManager, SignalStrength, SignalType, TradingSignal, TradingStats,
+    TradingStrategy,
 };
-use barter_execution::{
-    ExecutionClient,
-    order::{Order, OrderId, OrderKind, OrderState, OrderType, RequestCancel, RequestOpen},
-};
-use dashmap::DashMap;
+use barter_execution::{error::UnindexedClientError, UnindexedAccountEvent};
+use dashmap::{mapref::entry::Entry, DashMap, DashSet};
+use futures::{Stream, StreamExt};
 use monitor_anomaly::AnomalyDetection;
-use monitor_core::{MonitorError, Result, TradingConfig};
+use monitor_core::{
+    event::EventBuilder, instrument::InstrumentKey, AlertType, EventSource, EventType,
+    InstrumentKind, MonitorError, MonitorEvent, OrderPlacementMode, Result, ShutdownPolicy,
+    SystemEventType, TradingConfig,
+};
 use parking_lot::RwLock;
-use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use std::{collections::VecDeque, sync::Arc};
+use tokio::{
+    sync::{mpsc, watch},
+    task::JoinHandle,
+};
 use tracing::{error, info, warn};
 
+// How often `start` looks for positions past `max_holding_minutes`
+const HOLDING_SWEEP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+// How often `start` sends the DCA clips that have come due
+const DCA_CLIP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+// How often `start` writes changed cooldowns to their state file
+const COOLDOWN_FLUSH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+// Closed positions kept in memory, the store keeps every one
+const MAX_CLOSED_POSITIONS: usize = 1_000;
+// Evicted journal records waiting for the store, more are dropped
+const JOURNAL_SPILL_CAPACITY: usize = 10_000;
+// Stored journal records read per query by `write_journal`
+const JOURNAL_EXPORT_PAGE: usize = 1_000;
+// Refused cancels of a timed-out entry before it is given up on
+const MAX_CANCEL_ATTEMPTS: u32 = 3;
+// Names of the circuit breaker's halt, the DCA plans and the ledger in the store's state
+// documents
+const HALT_STATE: &str = "circuit_breaker";
+const DCA_STATE: &str = "dca_plans";
+const LEDGER_STATE: &str = "ledger";
+
+// What realized trades have built up, saved with every entry and close so a restart carries
+// on from it rather than from `initial_portfolio`
+#[derive(Serialize, Deserialize)]
+struct Ledger {
+    stats: StatsEngine,
+    breaker: BreakerWindows,
+    balance: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CloseReason {
+    StopLoss,
+    TakeProfit,
+    MaxHoldingPeriod,
+    Stale,
+    // Closed to make way for a signal under `ScalingMode::Replace`
+    Replaced,
+    // A close whose outcome was unknown, resubmitted at startup
+    Reconciled,
+    // A sell signal on a spot market, which exits a long instead of shorting
+    SellSignal,
+    // Requested from outside the trading loop, e.g. through the API
+    Manual,
+    // Flattened as the monitor stops, under `ShutdownPolicy::Flatten`
+    Shutdown,
+}
+
 pub struct AutoTrader {
     config: Arc<RwLock<TradingConfig>>,
     strategy: Arc<RwLock<Box<dyn TradingStrategy>>>,
     risk_manager: Arc<Box<dyn RiskManager>>,
-    execution_client: Arc<dyn ExecutionClient>,
-    positions: Arc<DashMap<String, Position>>,
-    stats: Arc<RwLock<TradingStats>>,
-    portfolio_value: Arc<RwLock<f64>>,
+    execution_client: Arc<dyn OrderClient>,
+    positions: Arc<DashMap<InstrumentKey, Position>>,
+    stats: Arc<RwLock<StatsEngine>>,
+    portfolio: Arc<RwLock<PortfolioAccount>>,
+    stale_guard: Arc<StalePriceGuard>,
+    price_source: Option<Arc<dyn PriceSource>>,
+    // What the exchange account holds, compared against `positions` by `reconcile_positions`
+    account_source: Option<Arc<dyn AccountSource>>,
+    alert_tx: Option<mpsc::UnboundedSender<MonitorEvent>>,
+    journal: Arc<TradeJournal>,
+    // Where records evicted from `journal` are kept, read back by exports
+    journal_store: Option<Arc<dyn JournalStore>>,
+    // The task writing evicted records to `journal_store`, awaited by `flush_journal`
+    journal_writer: Arc<parking_lot::Mutex<Option<tokio::task::JoinHandle<()>>>>,
+    cooldowns: Arc<CooldownTracker>,
+    circuit_breaker: Arc<CircuitBreaker>,
+    market: Arc<MarketSnapshotCache>,
+    context: Arc<MarketContextCache>,
+    // Exchanges not taking new positions, with when the pause lapses and why
+    paused_venues: Arc<DashMap<String, (chrono::DateTime<chrono::Utc>, String)>>,
+    // Entry limit orders waiting for their fill, keyed by order id
+    pending_orders: Arc<PendingOrders>,
+    // Entries going out in clips under `dca`
+    dca_plans: Arc<DcaPlans>,
+    // Positions grown by clips whose exits wait for the plan to end, keyed by position id
+    exits_deferred: Arc<DashSet<uuid::Uuid>>,
+    // Partial fills of exit orders resting on the exchange
+    exit_fills: Arc<ExitFills>,
+    // When the price first went through the level of a resting exit that has not filled since,
+    // keyed by position id
+    exit_crossings: Arc<DashMap<uuid::Uuid, chrono::DateTime<chrono::Utc>>>,
+    // Submissions whose outcome is not known yet, keyed by client order id
+    intents: Arc<OrderIntents>,
+    // The latest closed positions with their realized PnL, oldest first
+    closed_positions: Arc<RwLock<VecDeque<Position>>>,
+    // Set in dry-run mode, fed every price so it can fill orders
+    paper: Option<Arc<PaperExecutionClient>>,
+    store: Option<Arc<dyn PositionStore>>,
+    // When each open position was last written to the store
+    persisted_at: Arc<DashMap<InstrumentKey, chrono::DateTime<chrono::Utc>>>,
+    clock: Arc<dyn Clock>,
 }
 
 impl AutoTrader {
@@ -27,63 +147,546 @@ impl AutoTrader {
         config: TradingConfig,
         strategy: Box<dyn TradingStrategy>,
         risk_manager: Box<dyn RiskManager>,
-        execution_client: Arc<dyn ExecutionClient>,
+        execution_client: Arc<dyn OrderClient>,
         initial_portfolio: f64,
     ) -> Self {
+        let stale_guard = StalePriceGuard::new(
+            config.stale_position_threshold_secs,
+            config.stale_position_fallback,
+        );
+        
+        let cooldowns = match &config.cooldown.state_path {
+            Some(path) => CooldownTracker::new(config.cooldown.clone())
+                .with_store(Arc::new(FileCooldownStore::new(path)))
+                .unwrap_or_else(|e| {
+                    error!("Failed to restore cooldowns, starting empty: {}", e);
+                    CooldownTracker::new(config.cooldown.clone())
+                }),
+            None => CooldownTracker::new(config.cooldown.clone()),
+        };
+        
+        let intents = match &config.retry.intent_path {
+            Some(path) => OrderIntents::with_path(path).unwrap_or_else(|e| {
+                error!("Failed to restore order intents, starting empty: {}", e);
+                OrderIntents::new()
+            }),
+            None => OrderIntents::new(),
+        };
+        
+        let stats = StatsEngine::new(initial_portfolio, config.portfolio.risk_free_rate);
+        let circuit_breaker = CircuitBreaker::new(config.circuit_breaker.clone(), initial_portfolio);
+        let journal = TradeJournal::new(config.journal.max_entries);
+        let market = Arc::new(MarketSnapshotCache::new());
+        let context = MarketContextCache::new(market.clone());
+        context.set_price_change_window(config.price_change_window_secs);
+        
         Self {
             config: Arc::new(RwLock::new(config)),
             strategy: Arc::new(RwLock::new(strategy)),
             risk_manager: Arc::new(risk_manager),
             execution_client,
             positions: Arc::new(DashMap::new()),
-            stats: Arc::new(RwLock::new(TradingStats::default())),
-            portfolio_value: Arc::new(RwLock::new(initial_portfolio)),
+            stats: Arc::new(RwLock::new(stats)),
+            portfolio: Arc::new(RwLock::new(PortfolioAccount::new(initial_portfolio))),
+            stale_guard: Arc::new(stale_guard),
+            price_source: None,
+            account_source: None,
+            alert_tx: None,
+            journal: Arc::new(journal),
+            journal_store: None,
+            journal_writer: Arc::new(parking_lot::Mutex::new(None)),
+            cooldowns: Arc::new(cooldowns),
+            circuit_breaker: Arc::new(circuit_breaker),
+            context: Arc::new(context),
+            market,
+            paused_venues: Arc::new(DashMap::new()),
+            pending_orders: Arc::new(PendingOrders::new()),
+            dca_plans: Arc::new(DcaPlans::new()),
+            exits_deferred: Arc::new(DashSet::new()),
+            exit_fills: Arc::new(ExitFills::new()),
+            exit_crossings: Arc::new(DashMap::new()),
+            intents: Arc::new(intents),
+            closed_positions: Arc::new(RwLock::new(VecDeque::new())),
+            paper: None,
+            store: None,
+            persisted_at: Arc::new(DashMap::new()),
+            clock: Arc::new(SystemClock),
         }
     }
     
+    pub fn with_price_source(mut self, price_source: Arc<dyn PriceSource>) -> Self {
+        self.price_source = Some(price_source);
+        self
+    }
+    
+    pub fn with_account_source(mut self, account_source: Arc<dyn AccountSource>) -> Self {
+        self.account_source = Some(account_source);
+        self
+    }
+    
+    pub fn with_market_snapshots(mut self, market: Arc<MarketSnapshotCache>) -> Self {
+        let context = MarketContextCache::new(market.clone());
+        context.set_price_change_window(self.config.read().price_change_window_secs);
+        self.context = Arc::new(context);
+        self.market = market;
+        self
+    }
+    
+    // Routes all orders to the simulated account instead of the configured client
+    pub fn with_paper_execution(mut self, paper: Arc<PaperExecutionClient>) -> Self {
+        self.execution_client = paper.clone();
+        self.paper = Some(paper);
+        self
+    }
+    
+    // Time source for position ages, the system clock unless replaced
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+    
+    // Writes positions and executed orders through `store`, see `restore_from_db`
+    pub fn with_position_store(mut self, store: Arc<dyn PositionStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+    
+    // Spills records evicted from the journal to `store` in the background, `flush_journal`
+    // writes the rest on shutdown
+    pub fn with_journal_store(mut self, store: Arc<dyn JournalStore>) -> Self {
+        let (spill_tx, mut spill_rx) = mpsc::channel::<JournalRecord>(JOURNAL_SPILL_CAPACITY);
+        let writer = store.clone();
+        let handle = tokio::spawn(async move {
+            while let Some(record) = spill_rx.recv().await {
+                if let Err(e) = writer.insert(&record).await {
+                    error!("Failed to spill a journal record: {}", e);
+                }
+            }
+        });
+        let max_entries = self.config.read().journal.max_entries;
+        self.journal = Arc::new(TradeJournal::new(max_entries).with_spill(spill_tx));
+        self.journal_store = Some(store);
+        self.journal_writer = Arc::new(parking_lot::Mutex::new(Some(handle)));
+        self
+    }
+    
+    pub fn with_alert_sender(mut self, alert_tx: mpsc::UnboundedSender<MonitorEvent>) -> Self {
+        self.alert_tx = Some(alert_tx);
+        self
+    }
+    
     pub async fn process_anomaly(&self, anomaly: &AnomalyDetection) -> Result<()> {
-        if !self.config.read().auto_trading_enabled {
+        if !overrides::trading_enabled(&self.config.read(), &anomaly.exchange, &anomaly.symbol) {
             return Ok(());
         }
         
         // Generate trading signal from anomaly
-        let signal = {
+        let context = self.context.context(
+            &InstrumentKey::new(&anomaly.exchange, &anomaly.symbol),
+            chrono::Utc::now(),
+        );
+        let (signal, strategy_name) = {
             let mut strategy = self.strategy.write();
-            strategy.analyze(anomaly)
+            (strategy.analyze(anomaly, &context), strategy.name().to_string())
         };
         
         if let Some(signal) = signal {
+            // An ensemble whose members disagree holds
+            if matches!(signal.signal_type, crate::SignalType::Hold) {
+                info!("Holding on {}: {}", signal.symbol, signal.reason);
+                return Ok(());
+            }
+            
+            if anomaly.warmup {
+                info!("Ignoring signal during warm-up: {:?}", signal);
+                self.journal.record(&signal, SignalOutcome::WarmingUp, serde_json::Value::Null);
+                return Ok(());
+            }
+            
             info!("Trading signal generated: {:?}", signal);
-            self.execute_signal(signal).await?;
+            self.execute_signal(signal, &strategy_name).await?;
         }
         
         Ok(())
     }
     
-    async fn execute_signal(&self, signal: TradingSignal) -> Result<()> {
-        let portfolio_value = *self.portfolio_value.read();
+    async fn execute_signal(&self, signal: TradingSignal, strategy: &str) -> Result<()> {
+        let portfolio_value = self.get_portfolio_value();
+        let position_key = InstrumentKey::new(&signal.exchange, &signal.symbol);
+        
+        // Nothing is held on a spot market to sell short, a sell there can only exit
+        if matches!(signal.signal_type, crate::SignalType::Sell)
+            && self.resolve_config(&signal.exchange, &signal.symbol).instrument_kind
+                == InstrumentKind::Spot
+        {
+            return self.exit_spot_long(signal, position_key).await;
+        }
+        
+        let halted = self.circuit_breaker.is_halted();
+        if let Some(halt) = self.circuit_breaker.check(chrono::Utc::now()) {
+            if !halted {
+                self.on_halt(&halt).await;
+            }
+            info!("Signal for {} suppressed, trading is halted: {:?}", position_key, halt.reason);
+            self.journal.record(&signal, SignalOutcome::Halted, serde_json::to_value(&halt)?);
+            return Ok(());
+        }
+        
+        // Cooldowns are enforced before the risk manager sees the signal
+        if let Some(cooldown) = self.cooldowns.check(strategy, &position_key, chrono::Utc::now()) {
+            info!("Signal for {} suppressed by {:?} cooldown", position_key, cooldown.kind);
+            self.journal.record(
+                &signal,
+                SignalOutcome::CooldownActive,
+                serde_json::json!({ "kind": cooldown.kind, "until": cooldown.until }),
+            );
+            return Ok(());
+        }
+        
+        if let Some(reason) = self.venue_pause(&signal.exchange, chrono::Utc::now()) {
+            info!("Signal for {} suppressed, {} is paused: {}", position_key, signal.exchange, reason);
+            self.journal.record(
+                &signal,
+                SignalOutcome::VenuePaused,
+                serde_json::json!({ "reason": reason }),
+            );
+            return Ok(());
+        }
         
         // Validate order with risk manager
-        if !self.risk_manager.validate_order(&signal, portfolio_value) {
-            warn!("Order rejected by risk manager: {:?}", signal);
+        if let Some(rejection) = self.risk_manager.validate_order(&signal, portfolio_value) {
+            warn!("Order rejected by risk manager: {:?} {:?}", rejection, signal);
+            self.journal.record(
+                &signal,
+                SignalOutcome::RejectedByRisk,
+                serde_json::to_value(&rejection)?,
+            );
+            return Ok(());
+        }
+        
+        let open_positions: Vec<Position> = self.positions.iter().map(|p| p.clone()).collect();
+        let rejection = self.risk_manager.validate_portfolio(
+            &signal,
+            &open_positions,
+            &self.pending_entries(),
+            portfolio_value,
+        );
+        if let Some(rejection) = rejection {
+            warn!("Order for {} rejected by portfolio limits: {:?}", position_key, rejection);
+            self.journal.record(
+                &signal,
+                SignalOutcome::RejectedByRisk,
+                serde_json::to_value(&rejection)?,
+            );
             return Ok(());
         }
         
         // Calculate position size
         let quantity = self.risk_manager.calculate_position_size(&signal, portfolio_value);
         
+        // Liquidity prerequisites, the symbol stays monitored even when it cannot be traded
+        let requirements = self.config.read().liquidity.requirements_for(&signal.symbol).cloned();
+        if let Some(requirements) = requirements {
+            let report = check_liquidity(
+                &requirements,
+                &self.market,
+                &position_key,
+                &signal.signal_type,
+                quantity,
+                chrono::Utc::now(),
+            );
+            if !report.passed() {
+                warn!("Liquidity prerequisites not met for {}: {:?}", position_key, report.rejections);
+                self.journal.record(
+                    &signal,
+                    SignalOutcome::RejectedByLiquidity,
+                    serde_json::to_value(&report)?,
+                );
+                return Ok(());
+            }
+        }
+        
         // Determine order side
-        let (side, position_side) = match signal.signal_type {
-            crate::SignalType::Buy => (OrderKind::Buy, PositionSide::Long),
-            crate::SignalType::Sell => (OrderKind::Sell, PositionSide::Short),
+        let position_side = match signal.signal_type {
+            crate::SignalType::Buy => PositionSide::Long,
+            crate::SignalType::Sell => PositionSide::Short,
             crate::SignalType::Hold => return Ok(()),
         };
         
+        // One resting entry per instrument, a second signal must not stack another order
+        if self.pending_orders.has_pending(&position_key)
+            || self.intents.has_pending(&position_key)
+            || self.dca_plans.has_active(&signal.exchange, &signal.symbol)
+        {
+            info!("Signal for {} ignored, an entry order is already pending", position_key);
+            return Ok(());
+        }
+        
+        let scaling = self.config.read().scaling.clone();
+        let open_position = self.positions.get(&position_key).map(|p| p.clone());
+        let quantity = match scaling::decide(&scaling, open_position.as_ref(), &position_side) {
+            ScalingDecision::Open => quantity,
+            ScalingDecision::Add { size_fraction } => {
+                info!("Adding to the open position on {}", position_key);
+                quantity * size_fraction
+            }
+            ScalingDecision::Replace => {
+                info!("Replacing the open position on {}", position_key);
+                // The new entry waits for the old position to be gone, or both would be held
+                if !self.close_position(&position_key, None, CloseReason::Replaced).await? {
+                    warn!(
+                        "Signal for {} dropped, the position to replace is still open",
+                        position_key
+                    );
+                    self.journal.record(
+                        &signal,
+                        SignalOutcome::ExecutionFailed,
+                        serde_json::json!({ "error": "the position to replace was not closed" }),
+                    );
+                    return Ok(());
+                }
+                quantity
+            }
+            ScalingDecision::Reject(rejection) => {
+                info!("Signal for {} ignored, a position is open: {:?}", position_key, rejection);
+                self.journal.record(
+                    &signal,
+                    SignalOutcome::RejectedByScaling,
+                    serde_json::to_value(&rejection)?,
+                );
+                return Ok(());
+            }
+        };
+        
+        // Weak signals and pyramid adds can size below the exchange minimum, skip them here
+        // rather than have the order rejected
+        let notional = quantity * signal.price;
+        let min_notional = self.config.read().sizing.min_notional_for(&signal.exchange);
+        if let Some(min_notional) = min_notional.filter(|min| notional < *min) {
+            warn!(
+                "Order for {} skipped, notional {:.2} is below the {} minimum of {:.2}",
+                position_key, notional, signal.exchange, min_notional
+            );
+            self.journal.record(
+                &signal,
+                SignalOutcome::BelowMinNotional,
+                serde_json::json!({ "notional": notional, "min_notional": min_notional }),
+            );
+            return Ok(());
+        }
+        
+        let dca = self.config.read().dca.clone();
+        if dca.enabled {
+            // Clips stay above the exchange minimum, a small order goes out in fewer of them
+            let clips = min_notional
+                .filter(|min| *min > 0.0)
+                .map_or(dca.clips, |min| dca.clips.min((notional / min) as u32))
+                .max(1);
+            if clips > 1 {
+                let plan = DcaPlan::new(
+                    signal,
+                    strategy,
+                    position_side,
+                    quantity,
+                    clips,
+                    &dca,
+                    self.clock.now(),
+                );
+                info!(
+                    "Entering {} in {} clips of {} every {}s",
+                    position_key, plan.clips, plan.clip_quantity, plan.interval_secs
+                );
+                self.journal.record(
+                    &plan.signal,
+                    SignalOutcome::DcaStarted,
+                    serde_json::json!({ "plan": plan.id, "clips": plan.clips, "quantity": quantity }),
+                );
+                let id = plan.id;
+                self.dca_plans.start(plan);
+                self.send_clip(id).await?;
+                return Ok(());
+            }
+        }
+        
+        let placement = self.config.read().order_placement.clone();
+        if placement.mode == OrderPlacementMode::Market {
+            return self
+                .execute_market_entry(signal, strategy, position_side, quantity, None)
+                .await;
+        }
+        
+        let limit_price = orders::limit_price(&signal.signal_type, signal.price, placement.offset_bps);
+        let pending =
+            self.limit_entry(signal, strategy.to_string(), position_side, quantity, limit_price, 0);
+        self.submit_limit_entry(pending, placement.mode).await
+    }
+    
+    // Closes the position on `key` at market on request, `None` when nothing is open there or
+    // another close got to it first
+    pub async fn close_position_manually(&self, key: &InstrumentKey) -> Result<Option<Position>> {
+        let Some(id) = self.positions.get(key).map(|p| p.id) else {
+            return Ok(None);
+        };
+        if !self.close_position(key, Some(id), CloseReason::Manual).await? {
+            return Ok(None);
+        }
+        Ok(self.closed_positions.read().iter().rev().find(|p| p.id == id).cloned())
+    }
+    
+    // Leaves the exchange as `policy` asks once the trading tasks have stopped: DCA plans and
+    // working entry orders are cancelled, then under `Flatten` each position is closed at
+    // market after its resting exits. Bounded by `shutdown.timeout_secs`, whatever is not
+    // reached by then is reported and left as it is
+    pub async fn shutdown(&self, policy: ShutdownPolicy) -> ShutdownReport {
+        let timeout = std::time::Duration::from_secs(self.config.read().shutdown.timeout_secs);
+        let deadline = tokio::time::Instant::now() + timeout;
+        let mut report = ShutdownReport::new(policy);
+        
+        if policy != ShutdownPolicy::Keep {
+            for plan in self.dca_plans.list() {
+                if self.dca_plans.cancel(plan.id).is_some() {
+                    report.cancelled_dca_plans += 1;
+                }
+            }
+        
+            for order in self.pending_orders.all() {
+                if order.status.is_terminal() {
+                    continue;
+                }
+                if report.timed_out {
+                    report.working_orders.push(order.order_id);
+                    continue;
+                }
+                let cancel_request = RequestCancel {
+                    id: OrderId::new(order.order_id.clone()),
+                    instrument: order.signal.symbol.clone(),
+                    exchange: order.signal.exchange.clone(),
+                };
+        
+                let cancel = self.execution_client.cancel_order(cancel_request);
+                match tokio::time::timeout_at(deadline, cancel).await {
+                    Ok(Ok(_)) => {
+                        self.pending_orders.update(&order.order_id, OrderStatus::Cancelled);
+                        report.cancelled_orders.push(order.order_id);
+                    }
+                    Ok(Err(e)) => {
+                        warn!(
+                            "Failed to cancel order {} for {} on shutdown: {}",
+                            order.order_id, order.key, e
+                        );
+                        report.working_orders.push(order.order_id);
+                    }
+                    Err(_) => {
+                        report.timed_out = true;
+                        report.working_orders.push(order.order_id);
+                    }
+                }
+            }
+        }
+        
+        // Plans kept under `Keep` carry on after the restart
+        self.save_dca_plans().await;
+        
+        for position in self.get_positions() {
+            let outcome = if policy != ShutdownPolicy::Flatten {
+                ShutdownOutcome::Kept
+            } else if report.timed_out {
+                ShutdownOutcome::TimedOut
+            } else {
+                let key = InstrumentKey::new(&position.exchange, &position.symbol);
+                let close = self.close_position(&key, Some(position.id), CloseReason::Shutdown);
+                match tokio::time::timeout_at(deadline, close).await {
+                    Ok(Ok(true)) => {
+                        let closed = self.closed_positions.read();
+                        let closed = closed.iter().rev().find(|p| p.id == position.id);
+                        ShutdownOutcome::Closed {
+                            realized_pnl: closed.map_or(0.0, |p| p.realized_pnl),
+                        }
+                    }
+                    Ok(Ok(false)) => ShutdownOutcome::Failed {
+                        error: "exit orders still resting or already closing".to_string(),
+                    },
+                    Ok(Err(e)) => ShutdownOutcome::Failed { error: e.to_string() },
+                    Err(_) => {
+                        report.timed_out = true;
+                        ShutdownOutcome::TimedOut
+                    }
+                }
+            };
+            report.positions.push(PositionShutdown {
+                position_id: position.id,
+                exchange: position.exchange,
+                symbol: position.symbol,
+                outcome,
+            });
+        }
+        
+        report
+    }
+    
+    // Closes the long held on a spot market and cancels the entries working there, a sell with
+    // nothing to close does nothing else. Not held back by halts or cooldowns, which only stop
+    // new entries
+    async fn exit_spot_long(&self, signal: TradingSignal, position_key: InstrumentKey) -> Result<()> {
+        // A sell the risk manager rejects acts on nothing, not even the long it would close
+        let rejection = self.risk_manager.validate_order(&signal, self.get_portfolio_value());
+        if let Some(rejection) = rejection {
+            warn!("Sell signal for {} rejected by risk manager: {:?}", position_key, rejection);
+            self.journal.record(
+                &signal,
+                SignalOutcome::RejectedByRisk,
+                serde_json::to_value(&rejection)?,
+            );
+            return Ok(());
+        }
+        
+        // Entries still working on the market would buy back into what the sell exits
+        self.cancel_entry_orders(Some(&position_key), "a sell signal arrived").await;
+        for plan in self.dca_plans.cancel_for(&signal.exchange, &signal.symbol) {
+            info!("DCA plan {} cancelled, a sell signal arrived on {}", plan.id, position_key);
+        }
+        
+        let long = self
+            .positions
+            .get(&position_key)
+            .filter(|p| p.side == PositionSide::Long)
+            .map(|p| p.id);
+        let Some(id) = long else {
+            info!("Sell signal for {} ignored, spot markets cannot be shorted", position_key);
+            self.journal.record(&signal, SignalOutcome::ShortNotAllowed, serde_json::Value::Null);
+            return Ok(());
+        };
+        
+        let closed = self.close_position(&position_key, Some(id), CloseReason::SellSignal).await?;
+        self.journal.record(
+            &signal,
+            SignalOutcome::ClosedLong,
+            serde_json::json!({ "position": id, "closed": closed }),
+        );
+        Ok(())
+    }
+    
+    // `clip` numbers the clips of a DCA entry, the clips after the first add to its position
+    async fn execute_market_entry(
+        &self,
+        signal: TradingSignal,
+        strategy: &str,
+        position_side: PositionSide,
+        quantity: f64,
+        clip: Option<u32>,
+    ) -> Result<()> {
+        let position_key = InstrumentKey::new(&signal.exchange, &signal.symbol);
+        let client_order_id = match clip {
+            Some(clip) => retry::clip_order_id(&signal, clip),
+            None => retry::entry_order_id(&signal),
+        };
+        
         // Create order request
         let order_request = RequestOpen {
             instrument: signal.symbol.clone(),
             exchange: signal.exchange.clone(),
-            kind: side,
+            client_order_id: Some(client_order_id.clone()),
+            kind: entry_kind(&position_side),
             order_type: OrderType::Market,
             quantity,
             price: Some(signal.price),
@@ -92,17 +695,38 @@ impl AutoTrader {
             reduce_only: false,
         };
         
+        self.intents.record(OrderIntent {
+            client_order_id: client_order_id.clone(),
+            key: position_key.to_string(),
+            kind: IntentKind::Entry {
+                signal: signal.clone(),
+                strategy: strategy.to_string(),
+                side: position_side.clone(),
+                quantity,
+                limit_price: None,
+                replacements: 0,
+                clip,
+            },
+            created_at: chrono::Utc::now(),
+        });
+        
         // Execute order
-        match self.execution_client.open_order(order_request).await {
+        let result = self.open_order_with_retry(&client_order_id, order_request).await;
+        match result {
             Ok(Some(order)) => {
-                info!("Order executed: {:?}", order);
-                self.create_position(order, signal, position_side).await?;
+                self.on_market_entry(signal, strategy, position_side, quantity, clip, order)
+                    .await?;
             }
             Ok(None) => {
                 warn!("Order execution returned no order");
             }
             Err(e) => {
-                error!("Failed to execute order: {}", e);
+                error!("Failed to execute order {}: {}", client_order_id, e);
+                self.journal.record(
+                    &signal,
+                    SignalOutcome::ExecutionFailed,
+                    serde_json::json!({ "error": e.to_string() }),
+                );
                 return Err(MonitorError::Other(format!("Order execution failed: {}", e)));
             }
         }
@@ -110,92 +734,350 @@ impl AutoTrader {
         Ok(())
     }
     
-    async fn create_position(
+    // Books the venue's answer to a market entry: what filled opens or grows the position, an
+    // order accepted without executing is tracked until its fills arrive
+    async fn on_market_entry(
         &self,
-        order: Order,
         signal: TradingSignal,
-        side: PositionSide,
+        strategy: &str,
+        position_side: PositionSide,
+        quantity: f64,
+        clip: Option<u32>,
+        order: Order,
     ) -> Result<()> {
-        let stop_loss = self.risk_manager.get_stop_loss(signal.price, side.clone());
-        let take_profit = self.risk_manager.get_take_profit(signal.price, side.clone());
-        
-        let position = Position {
-            id: uuid::Uuid::new_v4(),
-            symbol: signal.symbol.clone(),
-            exchange: signal.exchange.clone(),
-            side,
-            quantity: order.quantity,
-            entry_price: signal.price,
-            current_price: signal.price,
-            unrealized_pnl: 0.0,
-            realized_pnl: 0.0,
-            stop_loss: Some(stop_loss),
-            take_profit: Some(take_profit),
-            opened_at: chrono::Utc::now(),
-            closed_at: None,
+        let position_key = InstrumentKey::new(&signal.exchange, &signal.symbol);
+        let new_entry = clip.is_none_or(|clip| clip == 1);
+        match order.state {
+            OrderState::Cancelled => {
+                warn!("Order {} for {} cancelled without a fill", order.id, position_key);
+                self.journal.record(
+                    &signal,
+                    SignalOutcome::ExecutionFailed,
+                    serde_json::json!({ "order_id": order.id.to_string(), "error": "cancelled" }),
+                );
+            }
+            OrderState::Open => {
+                // Accepted without executing yet, the fills arrive as account events
+                info!("Order {} for {} accepted, awaiting fills", order.id, position_key);
+                self.journal.record(
+                    &signal,
+                    SignalOutcome::OrderPending,
+                    serde_json::json!({ "order_id": order.id.to_string(), "quantity": quantity }),
+                );
+                self.pending_orders.track(PendingOrder {
+                    order_id: order.id.to_string(),
+                    key: position_key,
+                    limit_price: signal.price,
+                    signal,
+                    side: position_side,
+                    strategy: strategy.to_string(),
+                    quantity,
+                    status: OrderStatus::Open,
+                    submitted_at: self.clock.now(),
+                    replacements: 0,
+                    filled_quantity: 0.0,
+                    average_fill_price: 0.0,
+                    liquidity: Liquidity::Taker,
+                });
+            }
+            OrderState::Filled => {
+                info!("Order executed: {:?}", order);
+                // A market order's unfilled remainder expires, the position holds what filled
+                if order.quantity < quantity {
+                    warn!(
+                        "Market order for {} filled {} of {}",
+                        position_key, order.quantity, quantity
+                    );
+                }
+                self.journal.record(
+                    &signal,
+                    SignalOutcome::Executed,
+                    serde_json::json!({ "quantity": order.quantity, "requested": quantity }),
+                );
+                self.cooldowns.record_trade(strategy, &position_key, chrono::Utc::now());
+                let buy = position_side == PositionSide::Long;
+                let price = fill_price(
+                    &order,
+                    self.slippage().apply(signal.price, buy, &signal.strength),
+                );
+                let fill = Fill {
+                    order_id: order.id.to_string(),
+                    quantity: order.quantity,
+                    price,
+                };
+                self.add_to_position(fill, Liquidity::Taker, signal, position_side, new_entry)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+    
+    // Places `pending` as a resting limit order and tracks it until it fills or times out
+    async fn submit_limit_entry(
+        &self,
+        pending: PendingOrder,
+        mode: OrderPlacementMode,
+    ) -> Result<()> {
+        let client_order_id = retry::limit_order_id(&pending.signal, pending.replacements);
+        let order_request = RequestOpen {
+            instrument: pending.signal.symbol.clone(),
+            exchange: pending.signal.exchange.clone(),
+            client_order_id: Some(client_order_id.clone()),
+            kind: entry_kind(&pending.side),
+            order_type: OrderType::Limit,
+            quantity: pending.quantity,
+            price: Some(pending.limit_price),
+            time_in_force: None,
+            post_only: mode == OrderPlacementMode::PostOnly,
+            reduce_only: false,
         };
         
-        let position_key = format!("{}:{}", signal.exchange, signal.symbol);
-        self.positions.insert(position_key, position);
+        self.intents.record(OrderIntent {
+            client_order_id: client_order_id.clone(),
+            key: pending.key.to_string(),
+            kind: IntentKind::Entry {
+                signal: pending.signal.clone(),
+                strategy: pending.strategy.clone(),
+                side: pending.side.clone(),
+                quantity: pending.quantity,
+                limit_price: Some(pending.limit_price),
+                replacements: pending.replacements,
+                clip: None,
+            },
+            created_at: chrono::Utc::now(),
+        });
         
-        info!("Position created: {}/{} @ {}", signal.exchange, signal.symbol, signal.price);
+        let result = self.open_order_with_retry(&client_order_id, order_request).await;
+        match result {
+            Ok(Some(order)) => self.on_limit_entry(pending, order).await?,
+            Ok(None) => {
+                warn!("Limit order submission returned no order");
+            }
+            Err(e) => {
+                error!("Failed to submit limit order {}: {}", client_order_id, e);
+                self.journal.record(
+                    &pending.signal,
+                    SignalOutcome::ExecutionFailed,
+                    serde_json::json!({ "error": e.to_string() }),
+                );
+                return Err(MonitorError::Other(format!("Limit order submission failed: {}", e)));
+            }
+        }
         
         Ok(())
     }
     
-    pub async fn update_positions(&self, symbol: &str, exchange: &str, price: f64) -> Result<()> {
-        let position_key = format!("{}:{}", exchange, symbol);
+    // Books the venue's answer to a limit entry: resting, it is tracked until it fills or times
+    // out, marketable on arrival, it opens the position straight away
+    async fn on_limit_entry(&self, mut pending: PendingOrder, order: Order) -> Result<()> {
+        pending.order_id = order.id.to_string();
+        match order.state {
+            OrderState::Filled => {
+                let price = fill_price(&order, pending.limit_price);
+                let quantity = order.quantity.min(pending.quantity);
+                info!(
+                    "Limit order {} for {} filled on arrival @ {}",
+                    pending.order_id, pending.key, price
+                );
+                let first = pending.filled_quantity <= 0.0;
+                self.apply_entry_fill(&pending, quantity, price, first).await?;
+            }
+            OrderState::Cancelled => {
+                // A post-only order that would have taken, or one cancelled while unresolved
+                warn!("Limit order {} for {} cancelled unfilled", pending.order_id, pending.key);
+                self.journal.record(
+                    &pending.signal,
+                    SignalOutcome::ExecutionFailed,
+                    serde_json::json!({ "order_id": pending.order_id, "error": "cancelled" }),
+                );
+            }
+            OrderState::Open => {
+                pending.submitted_at = self.clock.now();
+                self.journal.record(
+                    &pending.signal,
+                    SignalOutcome::OrderPending,
+                    serde_json::json!({
+                        "order_id": pending.order_id,
+                        "limit_price": pending.limit_price,
+                        "quantity": pending.quantity,
+                        "replacements": pending.replacements,
+                    }),
+                );
+                self.pending_orders.track(pending);
+            }
+        }
+        Ok(())
+    }
+    
+    // Feeds a venue order update into the pending-order lifecycle. A status-only fill
+    // completes whatever part of the order had not been reported filled yet
+    pub async fn on_order_update(&self, order_id: &str, status: OrderStatus) -> Result<()> {
+        let Some(order) = self.pending_orders.update(order_id, status) else {
+            return self.on_exit_update(order_id, status).await;
+        };
         
-        if let Some(mut position) = self.positions.get_mut(&position_key) {
-            position.update_price(price);
-            
-            // Check stop loss
-            if position.should_stop_loss() {
-                info!("Stop loss triggered for {}/{}", exchange, symbol);
-                self.close_position(&position_key).await?;
+        match status {
+            OrderStatus::Filled => {
+                info!("Limit order {} filled for {} @ {}", order.order_id, order.key, order.limit_price);
+                let first = order.filled_quantity <= 0.0;
+                self.apply_entry_fill(&order, order.remaining(), order.limit_price, first)
+                    .await?;
             }
-            // Check take profit
-            else if position.should_take_profit() {
-                info!("Take profit triggered for {}/{}", exchange, symbol);
-                self.close_position(&position_key).await?;
+            OrderStatus::Cancelled => {
+                // Cancelled by the venue (or a post-only reject), not by the timeout path
+                warn!("Limit order {} for {} cancelled by the venue", order.order_id, order.key);
             }
+            OrderStatus::Submitted | OrderStatus::Open | OrderStatus::PartiallyFilled => {}
         }
         
         Ok(())
     }
     
-    async fn close_position(&self, position_key: &str) -> Result<()> {
-        if let Some((_, position)) = self.positions.remove(position_key) {
-            let side = match position.side {
-                PositionSide::Long => OrderKind::Sell,
-                PositionSide::Short => OrderKind::Buy,
+    // Feeds one execution against an entry order, the position opens or grows by exactly
+    // the filled amount
+    pub async fn on_fill(&self, fill: &Fill) -> Result<()> {
+        let Some((order, applied)) =
+            self.pending_orders.fill(&fill.order_id, fill.quantity, fill.price)
+        else {
+            return self
+                .on_exit_fill(&fill.order_id, Some((fill.quantity, fill.price)))
+                .await;
+        };
+        if applied <= 0.0 {
+            return Ok(());
+        }
+        
+        info!(
+            "Order {} for {} filled {} @ {} ({} of {})",
+            order.order_id, order.key, applied, fill.price, order.filled_quantity, order.quantity
+        );
+        let first = order.filled_quantity - applied <= 0.0;
+        self.apply_entry_fill(&order, applied, fill.price, first).await?;
+        
+        let cancel_remainder = self.config.read().order_placement.cancel_partial_remainder;
+        if order.status == OrderStatus::PartiallyFilled && cancel_remainder {
+            let cancel_request = RequestCancel {
+                id: OrderId::new(order.order_id.clone()),
+                instrument: order.signal.symbol.clone(),
+                exchange: order.signal.exchange.clone(),
             };
-            
-            let order_request = RequestOpen {
-                instrument: position.symbol.clone(),
-                exchange: position.exchange.clone(),
-                kind: side,
-                order_type: OrderType::Market,
-                quantity: position.quantity,
-                price: None,
-                time_in_force: None,
-                post_only: false,
-                reduce_only: true,
+            match self.execution_client.cancel_order(cancel_request).await {
+                Ok(_) => {
+                    self.pending_orders.remove(&order.order_id);
+                    info!("Cancelled the unfilled {} of order {}", order.remaining(), order.order_id);
+                }
+                // Most likely filled in the meantime, the fill update settles it
+                Err(e) => warn!("Failed to cancel the remainder of order {}: {}", order.order_id, e),
+            }
+        }
+        
+        Ok(())
+    }
+    
+    // The first fill of an entry is what executes the signal, later ones only resize
+    async fn apply_entry_fill(
+        &self,
+        order: &PendingOrder,
+        quantity: f64,
+        price: f64,
+        first: bool,
+    ) -> Result<()> {
+        if first {
+            self.journal.record(
+                &order.signal,
+                SignalOutcome::Executed,
+                serde_json::json!({
+                    "quantity": quantity,
+                    "order_id": order.order_id,
+                    "limit_price": order.limit_price,
+                }),
+            );
+            self.cooldowns.record_trade(&order.strategy, &order.key, chrono::Utc::now());
+        }
+        let fill = Fill {
+            order_id: order.order_id.clone(),
+            quantity,
+            price,
+        };
+        self.add_to_position(fill, order.liquidity, order.signal.clone(), order.side.clone(), first)
+            .await
+    }
+    
+    // Should be called periodically; cancels entries resting past the timeout and then
+    // re-quotes them or takes them at market
+    pub async fn check_pending_orders(&self) -> Result<()> {
+        if self.pending_orders.is_empty() {
+            return Ok(());
+        }
+        
+        let placement = self.config.read().order_placement.clone();
+        let timeout = chrono::Duration::seconds(placement.timeout_secs as i64);
+        
+        for order in self.pending_orders.expired(self.clock.now(), timeout) {
+            let cancel_request = RequestCancel {
+                id: OrderId::new(order.order_id.clone()),
+                instrument: order.signal.symbol.clone(),
+                exchange: order.signal.exchange.clone(),
             };
             
-            match self.execution_client.open_order(order_request).await {
-                Ok(Some(order)) => {
-                    info!("Position closed: {:?}", order);
-                    self.update_stats(position.unrealized_pnl);
+            if let Err(e) = self.execution_client.cancel_order(cancel_request).await {
+                let failures = self.pending_orders.cancel_failed(&order.order_id);
+                if failures < MAX_CANCEL_ATTEMPTS {
+                    // Most likely filled in the meantime, the fill update settles it
+                    warn!(
+                        "Failed to cancel limit order {} for {}: {}",
+                        order.order_id, order.key, e
+                    );
+                    continue;
                 }
-                Ok(None) => {
-                    warn!("Close position returned no order");
+                // Neither cancelled nor filled. Tracking stops so the market takes entries
+                // again, whatever the order still does shows in the next reconciliation
+                error!(
+                    "Giving up on limit order {} for {} after {} refused cancels: {}",
+                    order.order_id, order.key, failures, e
+                );
+                self.pending_orders.remove(&order.order_id);
+                self.send_alert(
+                    AlertType::Critical,
+                    serde_json::json!({
+                        "order_id": order.order_id,
+                        "exchange": order.signal.exchange,
+                        "symbol": order.signal.symbol,
+                        "filled_quantity": order.filled_quantity,
+                        "message": "Timed-out entry could not be cancelled, check the exchange",
+                    }),
+                );
+                continue;
+            }
+            
+            let Some(order) = self.pending_orders.update(&order.order_id, OrderStatus::Cancelled) else {
+                continue;
+            };
+            info!("Limit order {} for {} timed out unfilled", order.order_id, order.key);
+            if self.circuit_breaker.is_halted() {
+                info!("Not re-entering {}, trading is halted", order.key);
+                continue;
+            }
+            
+            match orders::on_timeout(order, &placement) {
+                PendingAction::Replace(mut order) => {
+                    let reference = self.context.last_price(&order.key).unwrap_or(order.signal.price);
+                    order.limit_price =
+                        orders::limit_price(&order.signal.signal_type, reference, placement.offset_bps);
+                    order.replacements += 1;
+                    order.status = OrderStatus::Submitted;
+                    self.submit_limit_entry(order, placement.mode).await?;
                 }
-                Err(e) => {
-                    error!("Failed to close position: {}", e);
-                    // Re-insert position if close failed
-                    self.positions.insert(position_key.to_string(), position);
-                    return Err(MonitorError::Other(format!("Position close failed: {}", e)));
+                PendingAction::MarketFallback(order) => {
+                    info!("Falling back to a market entry for {}", order.key);
+                    self.execute_market_entry(
+                        order.signal,
+                        &order.strategy,
+                        order.side,
+                        order.quantity,
+                        None,
+                    )
+                    .await?;
                 }
             }
         }
@@ -203,34 +1085,3817 @@ impl AutoTrader {
         Ok(())
     }
     
-    fn update_stats(&self, pnl: f64) {
-        let mut stats = self.stats.write();
-        stats.total_trades += 1;
-        stats.total_pnl += pnl;
+    // Submits with retries under one client order id. The intent is settled unless the last
+    // attempt left the outcome unknown, in which case `reconcile_intents` resolves it later
+    async fn open_order_with_retry(
+        &self,
+        client_order_id: &str,
+        request: RequestOpen,
+    ) -> std::result::Result<Option<Order>, UnindexedClientError> {
+        let policy = RetryPolicy::from_config(&self.config.read().retry);
+        let result = retry::with_retry(&policy, client_order_id, || {
+            self.execution_client.open_order(request.clone())
+        })
+        .await;
         
-        if pnl > 0.0 {
-            stats.winning_trades += 1;
-        } else {
-            stats.losing_trades += 1;
+        match &result {
+            Err(e) if retry::is_retryable(e) => {
+                warn!("Order {} outcome unknown, kept for reconciliation", client_order_id);
+            }
+            _ => self.intents.complete(client_order_id),
+        }
+        if let Ok(Some(order)) = &result {
+            self.journal_order(&request, order, None);
+        }
+        result
+    }
+    
+    fn journal_order(
+        &self,
+        request: &RequestOpen,
+        order: &Order,
+        position_id: Option<uuid::Uuid>,
+    ) {
+        self.journal.record_event(LifecycleEvent {
+            timestamp: chrono::Utc::now(),
+            kind: LifecycleKind::OrderPlaced,
+            exchange: request.exchange.clone(),
+            symbol: request.instrument.clone(),
+            side: format!("{:?}", request.kind).to_lowercase(),
+            order_id: Some(order.id.to_string()),
+            position_id,
+            quantity: request.quantity,
+            price: request.price,
+            details: serde_json::json!({
+                "order_type": format!("{:?}", request.order_type).to_lowercase(),
+            }),
+        });
+    }
+    
+    // Should be called at startup, `start` repeats it with the position reconciliation. Each
+    // intent whose outcome is unknown is looked up on the venue by its client order id: an
+    // order found is booked as the venue has it, one the venue never received is resubmitted
+    // under the same id. Intents the venue can't answer for are kept for the next run
+    pub async fn reconcile_intents(&self) -> Result<()> {
+        if self.intents.is_empty() {
+            return Ok(());
         }
         
-        stats.win_rate = if stats.total_trades > 0 {
-            stats.winning_trades as f64 / stats.total_trades as f64
-        } else {
-            0.0
-        };
+        let max_age =
+            chrono::Duration::seconds(self.config.read().retry.max_intent_age_secs as i64);
+        for intent in self.intents.all() {
+            info!("Reconciling order {} for {}", intent.client_order_id, intent.key);
+            if let IntentKind::Entry { signal, .. } = &intent.kind {
+                if chrono::Utc::now() - intent.created_at > max_age {
+                    // Too old to act on, but it may have filled: check the venue by hand
+                    warn!(
+                        "Dropping entry {} for {} from {}, verify it on the venue",
+                        intent.client_order_id, intent.key, signal.timestamp
+                    );
+                    self.intents.complete(&intent.client_order_id);
+                    continue;
+                }
+            }
+            
+            let (symbol, exchange) = match &intent.kind {
+                IntentKind::Entry { signal, .. } => (&signal.symbol, &signal.exchange),
+                IntentKind::Close { position } => (&position.symbol, &position.exchange),
+            };
+            let found = self
+                .execution_client
+                .find_order(&intent.client_order_id, symbol, exchange)
+                .await;
+            match found {
+                Ok(found) => self.resolve_intent(intent, found).await?,
+                Err(e) => warn!(
+                    "Order {} for {} is still unresolved, the venue did not answer: {}",
+                    intent.client_order_id, intent.key, e
+                ),
+            }
+        }
+        
+        Ok(())
     }
     
-    pub fn get_positions(&self) -> Vec<Position> {
-        self.positions.iter().map(|p| p.clone()).collect()
+    // Settles `intent` with the order the venue has under its client order id, or resubmits it
+    // when there is none
+    async fn resolve_intent(&self, intent: OrderIntent, found: Option<Order>) -> Result<()> {
+        let Some(order) = found else {
+            info!("Order {} never reached the venue, resubmitting it", intent.client_order_id);
+            return self.resubmit_intent(intent).await;
+        };
+        if order.state == OrderState::Open && matches!(intent.kind, IntentKind::Close { .. }) {
+            // A market close still working, its outcome is known once it is done
+            info!("Close {} for {} still working on the venue", intent.client_order_id, intent.key);
+            return Ok(());
+        }
+        
+        info!("Order {} found on the venue as {:?}", intent.client_order_id, order.state);
+        self.intents.complete(&intent.client_order_id);
+        match intent.kind {
+            IntentKind::Entry {
+                signal,
+                strategy,
+                side,
+                quantity,
+                limit_price: None,
+                clip,
+                ..
+            } => self.on_market_entry(signal, &strategy, side, quantity, clip, order).await,
+            IntentKind::Entry {
+                signal,
+                strategy,
+                side,
+                quantity,
+                limit_price: Some(limit_price),
+                replacements,
+                ..
+            } => {
+                let pending =
+                    self.limit_entry(signal, strategy, side, quantity, limit_price, replacements);
+                self.on_limit_entry(pending, order).await
+            }
+            IntentKind::Close { position } => {
+                let key = InstrumentKey::new(&position.exchange, &position.symbol);
+                if order.state == OrderState::Cancelled {
+                    // Never executed, the position is still held and managed as before
+                    warn!("Close {} of {} was cancelled, position kept", order.id, key);
+                    self.positions.insert(key, position);
+                    return Ok(());
+                }
+                let expected = self
+                    .slippage()
+                    .exit_price(position.current_price, position.side == PositionSide::Short);
+                let exit_price = fill_price(&order, expected);
+                let order_id = order.id.to_string();
+                self.settle_close(&key, position, exit_price, order_id, Liquidity::Taker).await;
+                Ok(())
+            }
+        }
     }
     
-    pub fn get_stats(&self) -> TradingStats {
-        self.stats.read().clone()
+    // A limit entry before the venue has answered for it
+    fn limit_entry(
+        &self,
+        signal: TradingSignal,
+        strategy: String,
+        side: PositionSide,
+        quantity: f64,
+        limit_price: f64,
+        replacements: u32,
+    ) -> PendingOrder {
+        PendingOrder {
+            order_id: String::new(),
+            key: InstrumentKey::new(&signal.exchange, &signal.symbol),
+            signal,
+            side,
+            strategy,
+            quantity,
+            limit_price,
+            status: OrderStatus::Submitted,
+            submitted_at: self.clock.now(),
+            replacements,
+            filled_quantity: 0.0,
+            average_fill_price: 0.0,
+            liquidity: Liquidity::Maker,
+        }
     }
     
-    pub fn update_config(&self, config: TradingConfig) {
-        *self.config.write() = config.clone();
-        self.strategy.write().update_config(config);
+    // Sends `intent` again under its client order id
+    async fn resubmit_intent(&self, intent: OrderIntent) -> Result<()> {
+        match intent.kind {
+            IntentKind::Entry {
+                signal,
+                strategy,
+                side,
+                quantity,
+                limit_price: None,
+                clip,
+                ..
+            } => {
+                self.execute_market_entry(signal, &strategy, side, quantity, clip).await?;
+            }
+            IntentKind::Entry {
+                signal,
+                strategy,
+                side,
+                quantity,
+                limit_price: Some(limit_price),
+                replacements,
+                ..
+            } => {
+                let pending =
+                    self.limit_entry(signal, strategy, side, quantity, limit_price, replacements);
+                let mode = match self.config.read().order_placement.mode {
+                    OrderPlacementMode::Market => OrderPlacementMode::Limit,
+                    mode => mode,
+                };
+                self.submit_limit_entry(pending, mode).await?;
+            }
+            IntentKind::Close { position } => {
+                let key = InstrumentKey::new(&position.exchange, &position.symbol);
+                let id = position.id;
+                self.positions.insert(key.clone(), position);
+                self.close_position(&key, Some(id), CloseReason::Reconciled).await?;
+            }
+        }
+        Ok(())
     }
-}
\ No newline at end of file
+    
+    // Opens the position, or grows the open one on the same side by a further fill.
+    // `new_entry` marks the first fill of a pyramid add rather than more of the same entry
+    async fn add_to_position(
+        &self,
+        fill: Fill,
+        liquidity: Liquidity,
+        signal: TradingSignal,
+        side: PositionSide,
+        new_entry: bool,
+    ) -> Result<()> {
+        let position_key = InstrumentKey::new(&signal.exchange, &signal.symbol);
+        let fee = self.fees().fee(&signal.exchange, fill.quantity * fill.price, liquidity);
+        let grown = match self.positions.get_mut(&position_key) {
+            Some(mut position) if position.side == side => {
+                position.add_fill(fill.quantity, fill.price);
+                position.fees += fee;
+                if new_entry {
+                    position.adds += 1;
+                }
+                position.stop_loss = Some(self.risk_manager.get_stop_loss(
+                    &signal,
+                    position.entry_price,
+                    side.clone(),
+                ));
+                position.take_profit = Some(self.risk_manager.get_take_profit(
+                    &signal,
+                    position.entry_price,
+                    side.clone(),
+                ));
+                info!(
+                    "Position {} now {} @ {}",
+                    position_key, position.quantity, position.entry_price
+                );
+                Some(position.clone())
+            }
+            _ => None,
+        };
+        
+        let (position, kind) = match grown {
+            Some(position) => (position, TradeKind::Add),
+            None => (
+                self.create_position(fill.quantity, fill.price, fee, signal, side).await?,
+                TradeKind::Entry,
+            ),
+        };
+        // A new position is protected from its first fill. Clips only grow it, its exits are
+        // resized once when the plan ends rather than cancelled and replaced on every clip
+        let position = if kind == TradeKind::Add
+            && self.dca_plans.has_active(&position.exchange, &position.symbol)
+        {
+            self.exits_deferred.insert(position.id);
+            position
+        } else {
+            self.exits_deferred.remove(&position.id);
+            self.update_exit_orders(position).await
+        };
+        let trade = ExecutedTrade {
+            position_id: position.id,
+            order_id: Some(fill.order_id),
+            kind,
+            quantity: fill.quantity,
+            price: fill.price,
+            realized_pnl: None,
+            fee,
+            executed_at: chrono::Utc::now(),
+        };
+        self.persist(&position, Some(trade)).await;
+        Ok(())
+    }
+    
+    async fn create_position(
+        &self,
+        quantity: f64,
+        entry_price: f64,
+        fees: f64,
+        signal: TradingSignal,
+        side: PositionSide,
+    ) -> Result<Position> {
+        let stop_loss = self.risk_manager.get_stop_loss(&signal, entry_price, side.clone());
+        let take_profit = self.risk_manager.get_take_profit(&signal, entry_price, side.clone());
+        
+        let position = Position {
+            id: uuid::Uuid::new_v4(),
+            symbol: signal.symbol.clone(),
+            exchange: signal.exchange.clone(),
+            side,
+            quantity,
+            entry_price,
+            current_price: entry_price,
+            unrealized_pnl: 0.0,
+            realized_pnl: 0.0,
+            stop_loss: Some(stop_loss),
+            take_profit: Some(take_profit),
+            opened_at: self.clock.now(),
+            closed_at: None,
+            stale: false,
+            adds: 0,
+            stop_order_id: None,
+            take_profit_order_id: None,
+            fees,
+        };
+        
+        let position_key = InstrumentKey::new(&signal.exchange, &signal.symbol);
+        // Never replace an open position silently, its fills would lose their tracking
+        match self.positions.entry(position_key.clone()) {
+            Entry::Occupied(_) => {
+                error!("Fill for {} left untracked, a position is already open", position_key);
+                return Err(MonitorError::Other(format!(
+                    "Position already open for {}",
+                    position_key
+                )));
+            }
+            Entry::Vacant(vacant) => {
+                self.stale_guard.record_update(&position_key, position.opened_at);
+                self.circuit_breaker.record_open(position.opened_at);
+                vacant.insert(position.clone());
+            }
+        }
+        
+        info!("Position created: {}/{} @ {}", signal.exchange, signal.symbol, entry_price);
+        
+        Ok(position)
+    }
+    
+    // Exits follow the position: placed when it opens, replaced at the new size and levels
+    // when it grows. The old ones are kept when they could not all be cancelled
+    async fn update_exit_orders(&self, mut position: Position) -> Position {
+        let resting = ExitLeg::BOTH.iter().any(|leg| leg.order_id(&position).is_some());
+        if resting && !self.cancel_exit_orders(&mut position).await {
+            return position;
+        }
+        self.place_exit_orders(&mut position).await;
+        
+        let key = InstrumentKey::new(&position.exchange, &position.symbol);
+        let recorded = match self.positions.get_mut(&key) {
+            Some(mut open) if open.id == position.id => {
+                open.stop_order_id = position.stop_order_id.clone();
+                open.take_profit_order_id = position.take_profit_order_id.clone();
+                true
+            }
+            _ => false,
+        };
+        // Closed while its exits were being placed, they would act on nothing
+        if !recorded && !self.cancel_exit_orders(&mut position).await {
+            warn!("Exit orders of closed position {} left resting on {}", position.id, key);
+        }
+        position
+    }
+    
+    // Rests the stop and target of `position` on the exchange under `exchange_exit_orders`.
+    // A leg that fails to place is left to the local price checks
+    async fn place_exit_orders(&self, position: &mut Position) {
+        let policy = {
+            let config = self.config.read();
+            if !config.exchange_exit_orders {
+                return;
+            }
+            RetryPolicy::from_config(&config.retry)
+        };
+        
+        for (leg, request) in exits::exit_requests(position) {
+            let client_order_id = request.client_order_id.clone().unwrap_or_default();
+            let result = retry::with_retry(&policy, &client_order_id, || {
+                self.execution_client.open_order(request.clone())
+            })
+            .await;
+            match result {
+                Ok(Some(order)) => {
+                    info!(
+                        "{:?} order {} resting for position {} @ {:?}",
+                        leg, order.id, position.id, request.price
+                    );
+                    self.journal_order(&request, &order, Some(position.id));
+                    leg.set_order_id(position, Some(order.id.to_string()));
+                }
+                Ok(None) => warn!("{:?} order for position {} returned no order", leg, position.id),
+                Err(e) => warn!(
+                    "Failed to place {:?} order for position {}, checked locally instead: {}",
+                    leg, position.id, e
+                ),
+            }
+        }
+    }
+    
+    // Cancels the exits resting for `position`, clearing each cancelled one on it and on the
+    // book. False when one could not be cancelled, most likely because it filled; its fill
+    // then settles the position
+    async fn cancel_exit_orders(&self, position: &mut Position) -> bool {
+        let mut cancelled = true;
+        for leg in ExitLeg::BOTH {
+            let Some(order_id) = leg.order_id(position).map(str::to_string) else {
+                continue;
+            };
+            let cancel_request = RequestCancel {
+                id: OrderId::new(order_id.clone()),
+                instrument: position.symbol.clone(),
+                exchange: position.exchange.clone(),
+            };
+            match self.execution_client.cancel_order(cancel_request).await {
+                Ok(_) => {
+                    leg.set_order_id(position, None);
+                    self.forget_exit_order(position, &order_id);
+                }
+                Err(e) => {
+                    warn!("Failed to cancel {:?} order {} of position {}: {}", leg, order_id, position.id, e);
+                    cancelled = false;
+                }
+            }
+        }
+        cancelled
+    }
+    
+    // Stops tracking an exit order on the book's copy of `position`
+    fn forget_exit_order(&self, position: &Position, order_id: &str) {
+        self.exit_fills.remove(order_id);
+        let key = InstrumentKey::new(&position.exchange, &position.symbol);
+        if let Some(mut open) = self.positions.get_mut(&key) {
+            if open.id == position.id {
+                if let Some(leg) = ExitLeg::of(&open, order_id) {
+                    leg.set_order_id(&mut open, None);
+                }
+            }
+        }
+    }
+    
+    // Status-only venue updates of exit orders. Without a price, a filled leg is taken at
+    // its level; a leg the venue cancelled hands its exit back to the local checks
+    async fn on_exit_update(&self, order_id: &str, status: OrderStatus) -> Result<()> {
+        match status {
+            OrderStatus::Filled => self.on_exit_fill(order_id, None).await,
+            OrderStatus::Cancelled => {
+                let open = self
+                    .positions
+                    .iter()
+                    .find(|p| ExitLeg::of(p, order_id).is_some())
+                    .map(|p| p.clone());
+                if let Some(position) = open {
+                    warn!("Exit order {} of position {} cancelled by the venue", order_id, position.id);
+                    self.forget_exit_order(&position, order_id);
+                }
+                Ok(())
+            }
+            OrderStatus::Submitted | OrderStatus::Open | OrderStatus::PartiallyFilled => Ok(()),
+        }
+    }
+    
+    // Settles a fill, `(quantity, price)`, against an exit resting on the exchange. The leg
+    // that covers the position first closes it and cancels its sibling. A fill on an exit of
+    // a position already closed means both legs executed
+    async fn on_exit_fill(&self, order_id: &str, fill: Option<(f64, f64)>) -> Result<()> {
+        let open = self
+            .positions
+            .iter()
+            .find_map(|p| ExitLeg::of(&p, order_id).map(|leg| (p.key().clone(), p.clone(), leg)));
+        let Some((key, position, leg)) = open else {
+            self.report_stray_exit_fill(order_id);
+            return Ok(());
+        };
+        
+        let price = match fill {
+            Some((quantity, price)) => {
+                match self.exit_fills.fill(order_id, quantity, price, position.quantity) {
+                    Some(average) => average,
+                    None => {
+                        info!("{:?} order {} of position {} partially filled", leg, order_id, position.id);
+                        return Ok(());
+                    }
+                }
+            }
+            None => {
+                self.exit_fills.remove(order_id);
+                let level = leg.level(&position).unwrap_or(position.current_price);
+                // A triggered stop goes out at market, the target rests at its price
+                match leg {
+                    ExitLeg::StopLoss => {
+                        self.slippage().exit_price(level, position.side == PositionSide::Short)
+                    }
+                    ExitLeg::TakeProfit => level,
+                }
+            }
+        };
+        
+        let Some(mut position) = self.claim_position(&key, Some(position.id)) else {
+            self.report_stray_exit_fill(order_id);
+            return Ok(());
+        };
+        info!("{:?} order {} filled for position {} @ {}", leg, order_id, position.id, price);
+        if leg == ExitLeg::StopLoss {
+            self.cooldowns.record_stop_loss(&key, chrono::Utc::now());
+        }
+        
+        if let Some(sibling_id) = leg.sibling().order_id(&position).map(str::to_string) {
+            let cancel_request = RequestCancel {
+                id: OrderId::new(sibling_id.clone()),
+                instrument: position.symbol.clone(),
+                exchange: position.exchange.clone(),
+            };
+            match self.execution_client.cancel_order(cancel_request).await {
+                Ok(_) => leg.sibling().set_order_id(&mut position, None),
+                // Kept on the closed position, so a fill of it is recognized as a double exit
+                Err(e) => warn!(
+                    "Failed to cancel {:?} order {} of position {}, it may fill as well: {}",
+                    leg.sibling(),
+                    sibling_id,
+                    position.id,
+                    e
+                ),
+            }
+        }
+        
+        let liquidity = match leg {
+            ExitLeg::StopLoss => Liquidity::Taker,
+            ExitLeg::TakeProfit => Liquidity::Maker,
+        };
+        self.settle_close(&key, position, price, order_id.to_string(), liquidity).await;
+        Ok(())
+    }
+    
+    // Both legs executed: the second exit traded against a closed position and left the
+    // exchange holding the opposite side, which needs a hand
+    fn report_stray_exit_fill(&self, order_id: &str) {
+        let closed = self
+            .closed_positions
+            .read()
+            .iter()
+            .rev()
+            .find(|p| ExitLeg::of(p, order_id).is_some())
+            .cloned();
+        let Some(position) = closed else {
+            return;
+        };
+        error!(
+            "Exit order {} filled after position {} on {}/{} was closed",
+            order_id, position.id, position.exchange, position.symbol
+        );
+        self.send_alert(
+            AlertType::Critical,
+            serde_json::json!({
+                "position": position.id,
+                "exchange": position.exchange,
+                "symbol": position.symbol,
+                "order_id": order_id,
+                "message": "Both exit orders filled, the exchange position is reversed",
+            }),
+        );
+    }
+    
+    // Journals and writes `position` and the order that changed it. A failed write is logged
+    // rather than returned, the position is managed in memory either way
+    async fn persist(&self, position: &Position, trade: Option<ExecutedTrade>) {
+        if let Some(trade) = &trade {
+            self.journal_trade(position, trade);
+        }
+        let Some(store) = &self.store else {
+            return;
+        };
+        
+        let key = InstrumentKey::new(&position.exchange, &position.symbol);
+        let written = match trade.as_ref().map(|trade| trade.kind) {
+            Some(TradeKind::Entry) => store.open(position).await,
+            Some(TradeKind::Close) => store.close(position).await,
+            Some(TradeKind::Add) | None => store.update(position).await,
+        };
+        if let Err(e) = written {
+            error!("Failed to persist position {} on {}: {}", position.id, key, e);
+            return;
+        }
+        if position.closed_at.is_some() {
+            self.persisted_at.remove(&key);
+        } else {
+            self.persisted_at.insert(key, chrono::Utc::now());
+        }
+        
+        if let Some(trade) = trade {
+            if let Err(e) = store.record_trade(position, &trade).await {
+                error!("Failed to record {:?} trade on position {}: {}", trade.kind, position.id, e);
+            }
+            // Entries count towards the breaker's open rate, closes move everything
+            if trade.kind != TradeKind::Add {
+                self.save_ledger(store.as_ref()).await;
+            }
+        }
+    }
+    
+    async fn save_ledger(&self, store: &dyn PositionStore) {
+        let ledger = Ledger {
+            stats: self.stats.read().clone(),
+            breaker: self.circuit_breaker.windows(),
+            balance: self.portfolio.read().balance(),
+        };
+        let saved = match serde_json::to_value(&ledger) {
+            Ok(state) => store.save_state(LEDGER_STATE, &state).await,
+            Err(e) => Err(e.into()),
+        };
+        if let Err(e) = saved {
+            error!("Failed to save the ledger: {}", e);
+        }
+    }
+    
+    // The fill behind `trade`, and the open or close it made. Positions taken over from the
+    // exchange account were not filled here and only open
+    fn journal_trade(&self, position: &Position, trade: &ExecutedTrade) {
+        // Entries buy a long, closes sell it
+        let buy = (position.side == PositionSide::Long) != (trade.kind == TradeKind::Close);
+        let event = |kind, side: &str, details| LifecycleEvent {
+            timestamp: trade.executed_at,
+            kind,
+            exchange: position.exchange.clone(),
+            symbol: position.symbol.clone(),
+            side: side.to_string(),
+            order_id: trade.order_id.clone(),
+            position_id: Some(position.id),
+            quantity: trade.quantity,
+            price: Some(trade.price),
+            details,
+        };
+        if trade.order_id.is_some() {
+            self.journal.record_event(event(
+                LifecycleKind::OrderFilled,
+                if buy { "buy" } else { "sell" },
+                serde_json::json!({ "trade": trade.kind, "fee": trade.fee }),
+            ));
+        }
+        
+        let side = match position.side {
+            PositionSide::Long => "long",
+            PositionSide::Short => "short",
+        };
+        match trade.kind {
+            TradeKind::Entry => self.journal.record_event(event(
+                LifecycleKind::PositionOpened,
+                side,
+                serde_json::json!({
+                    "stop_loss": position.stop_loss,
+                    "take_profit": position.take_profit,
+                }),
+            )),
+            TradeKind::Close => self.journal.record_event(event(
+                LifecycleKind::PositionClosed,
+                side,
+                serde_json::json!({
+                    "realized_pnl": position.realized_pnl,
+                    "fees": position.fees,
+                }),
+            )),
+            TradeKind::Add => {}
+        }
+    }
+    
+    // Price-driven changes are written at most once per `update_interval_secs`
+    async fn persist_if_due(&self, position: &Position) {
+        if self.store.is_none() {
+            return;
+        }
+        let interval = chrono::Duration::seconds(
+            self.config.read().persistence.update_interval_secs as i64,
+        );
+        let key = InstrumentKey::new(&position.exchange, &position.symbol);
+        let due = self
+            .persisted_at
+            .get(&key)
+            .is_none_or(|at| chrono::Utc::now() - *at >= interval);
+        if due {
+            self.persist(position, None).await;
+        }
+    }
+    
+    // Reloads what a previous run left: the ledger of stats, breaker windows and portfolio
+    // balance, a halt, DCA plans and the open positions, whose stop-loss and take-profit are
+    // evaluated again from the next price. The restored positions are then checked against the
+    // exchange account, after the orders left in flight are resolved. Should be called before
+    // the first signal, returns the positions restored
+    pub async fn restore_from_db(&self) -> Result<usize> {
+        let Some(store) = &self.store else {
+            return Ok(0);
+        };
+        
+        if let Some(state) = store.load_state(LEDGER_STATE).await? {
+            let ledger: Ledger = serde_json::from_value(state)?;
+            let mut stats = ledger.stats;
+            // The rate comes from the config in effect, not the one saved with the stats
+            stats.set_risk_free_rate(self.config.read().portfolio.risk_free_rate);
+            info!(
+                "Restored the ledger: {} trades, portfolio balance {:.2}",
+                stats.stats().total_trades,
+                ledger.balance
+            );
+            *self.stats.write() = stats;
+            self.circuit_breaker.restore_windows(ledger.breaker);
+            *self.portfolio.write() = PortfolioAccount::new(ledger.balance);
+        }
+        
+        // A halt outlives the restart, only `resume_trading` ends it
+        if let Some(state) = store.load_state(HALT_STATE).await? {
+            if let Some(halt) = serde_json::from_value::<Option<Halt>>(state)? {
+                self.circuit_breaker.restore(halt);
+            }
+        }
+        
+        // Plans carry on with their next clip
+        if let Some(state) = store.load_state(DCA_STATE).await? {
+            for plan in serde_json::from_value::<Vec<DcaPlan>>(state)? {
+                info!(
+                    "Restored DCA plan {} on {}/{}, {} of {} clips sent",
+                    plan.id, plan.signal.exchange, plan.signal.symbol, plan.clips_sent, plan.clips
+                );
+                self.dca_plans.start(plan);
+            }
+            self.dca_plans.take_changed();
+        }
+        
+        let mut restored = 0;
+        for position in store.load_open().await? {
+            let key = InstrumentKey::new(&position.exchange, &position.symbol);
+            match self.positions.entry(key.clone()) {
+                Entry::Occupied(open) => {
+                    warn!(
+                        "Not restoring position {} on {}, {} is already open",
+                        position.id,
+                        key,
+                        open.get().id
+                    );
+                }
+                Entry::Vacant(vacant) => {
+                    info!(
+                        "Restored {:?} position {} on {}: {} @ {}",
+                        position.side, position.id, key, position.quantity, position.entry_price
+                    );
+                    // Staleness is measured from the restart, not from the last saved price
+                    self.stale_guard.record_update(&key, chrono::Utc::now());
+                    // Its exits may lag the clips already sent, they are resized as the plan ends
+                    if self.dca_plans.has_active(&position.exchange, &position.symbol) {
+                        self.exits_deferred.insert(position.id);
+                    }
+                    self.persisted_at.insert(key, chrono::Utc::now());
+                    vacant.insert(position);
+                    restored += 1;
+                }
+            }
+        }
+        
+        // The exchange may have closed or changed positions while the monitor was down
+        if let Err(e) = self.reconcile_intents().await {
+            error!("Failed to reconcile in-flight orders: {}", e);
+        }
+        if let Err(e) = self.reconcile_positions().await {
+            error!("Failed to reconcile restored positions with the exchange: {}", e);
+        }
+        
+        Ok(restored)
+    }
+    
+    pub async fn update_positions(&self, symbol: &str, exchange: &str, price: f64) -> Result<()> {
+        self.update_positions_keyed(&InstrumentKey::new(exchange, symbol), price).await
+    }
+    
+    // Same as `update_positions` with the key built once per subscription
+    pub async fn update_positions_keyed(&self, key: &InstrumentKey, price: f64) -> Result<()> {
+        self.context.record_price(key, price);
+        self.risk_manager.record_price(key, price, self.clock.now());
+        
+        if let Some(paper) = &self.paper {
+            for order_id in paper.record_price(key, price) {
+                self.on_order_update(&order_id, OrderStatus::Filled).await?;
+            }
+        }
+        
+        if self.positions.contains_key(key) {
+            self.stale_guard.record_update(key, chrono::Utc::now());
+        }
+        
+        self.apply_price(key, price).await
+    }
+    
+    async fn apply_price(&self, position_key: &str, price: f64) -> Result<()> {
+        let (stop_loss, take_profit, position) = match self.positions.get_mut(position_key) {
+            Some(mut position) => {
+                position.update_price(price);
+                (position.should_stop_loss(), position.should_take_profit(), position.clone())
+            }
+            None => return Ok(()),
+        };
+        let (stop_loss, take_profit) = self.local_exits(&position, stop_loss, take_profit);
+        
+        if !stop_loss && !take_profit {
+            self.persist_if_due(&position).await;
+        }
+        
+        // Check stop loss
+        if stop_loss {
+            info!("Stop loss triggered for {}", position_key);
+            self.close_position(position_key, Some(position.id), CloseReason::StopLoss).await?;
+        }
+        // Check take profit
+        else if take_profit {
+            info!("Take profit triggered for {}", position_key);
+            self.close_position(position_key, Some(position.id), CloseReason::TakeProfit).await?;
+        }
+        
+        Ok(())
+    }
+    
+    // Which of the crossed exits to take locally. Exits resting on the exchange execute there
+    // and their fills close the position, until the price has been through the level for
+    // `exit_fill_grace_secs` without one: the order may be lost or the fill missed, and the
+    // local close cancels it before going out at market
+    fn local_exits(&self, position: &Position, stop_loss: bool, take_profit: bool) -> (bool, bool) {
+        let resting = |leg: ExitLeg| leg.order_id(position).is_some();
+        let waiting = (stop_loss && resting(ExitLeg::StopLoss))
+            || (take_profit && resting(ExitLeg::TakeProfit));
+        if !waiting {
+            self.exit_crossings.remove(&position.id);
+            return (stop_loss, take_profit);
+        }
+        
+        let now = self.clock.now();
+        let since = *self.exit_crossings.entry(position.id).or_insert(now);
+        let grace = chrono::Duration::seconds(self.config.read().exit_fill_grace_secs as i64);
+        if now - since < grace {
+            return (
+                stop_loss && !resting(ExitLeg::StopLoss),
+                take_profit && !resting(ExitLeg::TakeProfit),
+            );
+        }
+        warn!(
+            "Resting exit of position {} on {} unfilled {}s past its level, closing locally",
+            position.id,
+            position.exchange,
+            (now - since).num_seconds()
+        );
+        self.exit_crossings.remove(&position.id);
+        (stop_loss, take_profit)
+    }
+    
+    // Should be called periodically; protects open positions whose price feed has stalled
+    pub async fn check_stale_positions(&self) -> Result<()> {
+        let keys: Vec<(String, String, String, InstrumentKind)> = self
+            .positions
+            .iter()
+            .map(|p| {
+                let kind = self.resolve_config(&p.exchange, &p.symbol).instrument_kind;
+                (p.key().to_string(), p.symbol.clone(), p.exchange.clone(), kind)
+            })
+            .collect();
+        
+        if keys.is_empty() {
+            return Ok(());
+        }
+        
+        let actions = self
+            .stale_guard
+            .evaluate(&keys, chrono::Utc::now(), self.price_source.as_deref())
+            .await;
+        
+        for action in actions {
+            match action {
+                StaleAction::Alert { key, stale_for } => {
+                    self.send_alert(
+                        AlertType::Critical,
+                        serde_json::json!({
+                            "position": key,
+                            "stale_for_secs": stale_for.num_seconds(),
+                            "message": "No price update for open position, stop-loss is not being evaluated",
+                        }),
+                    );
+                }
+                // A failed position is retried on the next check, the others are still handled
+                StaleAction::Reprice { key, price } => {
+                    info!("Applying REST fallback price {} to {}", price, key);
+                    if let Err(e) = self.apply_price(&key, price).await {
+                        error!("Failed to apply fallback price to {}: {}", key, e);
+                    }
+                }
+                StaleAction::Close { key } => {
+                    warn!("Defensively closing stale position {}", key);
+                    if let Err(e) = self.close_position(&key, None, CloseReason::Stale).await {
+                        error!("Failed to close stale position {}: {}", key, e);
+                    }
+                }
+            }
+        }
+        
+        Ok(())
+    }
+    
+    fn send_alert(&self, alert_type: AlertType, data: serde_json::Value) {
+        self.send_event(EventType::Alert(alert_type), data);
+    }
+    
+    fn send_event(&self, event_type: EventType, data: serde_json::Value) {
+        let Some(tx) = &self.alert_tx else {
+            return;
+        };
+        
+        let event = EventBuilder::new()
+            .with_source(EventSource::Trading)
+            .with_type(event_type)
+            .with_data(data)
+            .build();
+        
+        if let Some(event) = event {
+            if let Err(e) = tx.send(event) {
+                error!("Failed to send trading alert: {}", e);
+            }
+        }
+    }
+    
+    // Spawns the periodic sweep closing positions held past `max_holding_minutes`, the
+    // scheduler sending DCA clips as they come due and the periodic reconciliation of in-flight
+    // orders and positions against the exchange. Cooldown changes are written out here too,
+    // keeping file writes off the trading path. Work in progress completes and cooldowns are
+    // flushed once more before the task stops on `shutdown`
+    pub fn start(self: &Arc<Self>, mut shutdown: watch::Receiver<bool>) -> JoinHandle<()> {
+        let trader = self.clone();
+        tokio::spawn(async move {
+            let mut sweep = tokio::time::interval(HOLDING_SWEEP_INTERVAL);
+            let mut clips = tokio::time::interval(DCA_CLIP_INTERVAL);
+            let mut cooldowns = tokio::time::interval(COOLDOWN_FLUSH_INTERVAL);
+            // Startup reconciles before the trader starts, the first run here is one period on.
+            // The period is read again after each run so config updates apply to the next
+            let reconciliation = tokio::time::sleep(trader.reconciliation_interval());
+            tokio::pin!(reconciliation);
+            loop {
+                tokio::select! {
+                    _ = shutdown.changed() => break,
+                    _ = sweep.tick() => {
+                        if let Err(e) = trader.close_expired_positions().await {
+                            error!("Failed to close expired positions: {}", e);
+                        }
+                    }
+                    _ = clips.tick() => {
+                        if let Err(e) = trader.execute_due_clips().await {
+                            error!("Failed to send DCA clips: {}", e);
+                        }
+                    }
+                    _ = cooldowns.tick() => trader.flush_cooldowns().await,
+                    _ = &mut reconciliation => {
+                        if let Err(e) = trader.reconcile_intents().await {
+                            error!("Failed to reconcile in-flight orders: {}", e);
+                        }
+                        if let Err(e) = trader.reconcile_positions().await {
+                            error!("Failed to reconcile positions with the exchange: {}", e);
+                        }
+                        let next = tokio::time::Instant::now() + trader.reconciliation_interval();
+                        reconciliation.as_mut().reset(next);
+                    }
+                }
+            }
+            trader.flush_cooldowns().await;
+            info!("Position maintenance stopped");
+        })
+    }
+    
+    async fn flush_cooldowns(&self) {
+        let cooldowns = self.cooldowns.clone();
+        if let Err(e) = tokio::task::spawn_blocking(move || cooldowns.flush()).await {
+            error!("Cooldown flush task failed: {}", e);
+        }
+    }
+    
+    fn reconciliation_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.config.read().reconciliation.interval_secs.max(1))
+    }
+    
+    // Should be called at startup, `start` repeats it every `reconciliation.interval_secs`.
+    // Brings the open positions in line with the exchange account: phantoms are booked closed
+    // without an order, unknown positions adopted and sizes resynced, each reported as a
+    // `PositionDiscrepancy` event. Markets with an order in flight wait for the next run.
+    // Returns what was resolved, nothing without an account source
+    pub async fn reconcile_positions(&self) -> Result<Vec<Discrepancy>> {
+        let Some(source) = &self.account_source else {
+            return Ok(Vec::new());
+        };
+        let held = source.fetch_positions().await?;
+        let tolerance = self.config.read().reconciliation.quantity_tolerance_pct;
+        let local: Vec<Position> = self
+            .positions
+            .iter()
+            .filter(|p| source.covers(&p.exchange))
+            .map(|p| p.clone())
+            .collect();
+        
+        let mut resolved = Vec::new();
+        for discrepancy in reconcile::diff(&local, &held, tolerance) {
+            let key = discrepancy.key();
+            if self.pending_orders.has_pending(&key)
+                || self.intents.has_pending(&key)
+                || self.dca_plans.has_active(key.exchange(), key.symbol())
+            {
+                info!("Reconciliation of {} deferred, an order is in flight", key);
+                continue;
+            }
+            
+            let message = match &discrepancy {
+                Discrepancy::Phantom { position } => {
+                    self.close_phantom(position).await;
+                    format!(
+                        "Position {} on {} is gone from the exchange, closed locally",
+                        position.id, key
+                    )
+                }
+                Discrepancy::Unknown { venue } => {
+                    let adopted = self.adopt_position(venue).await?;
+                    format!(
+                        "Adopted a {:?} position of {} on {} opened outside the monitor as {}",
+                        venue.side, venue.quantity, key, adopted.id
+                    )
+                }
+                Discrepancy::Mismatch { position, venue } => {
+                    self.resize_position(position, venue).await;
+                    format!(
+                        "Position {} on {} resized from {} to the exchange's {}",
+                        position.id, key, position.quantity, venue.quantity
+                    )
+                }
+            };
+            warn!("{}", message);
+            let mut data = serde_json::to_value(&discrepancy)?;
+            data["key"] = key.to_string().into();
+            data["resolution"] = discrepancy.resolution().into();
+            data["message"] = message.into();
+            self.send_event(EventType::System(SystemEventType::PositionDiscrepancy), data);
+            resolved.push(discrepancy);
+        }
+        Ok(resolved)
+    }
+    
+    // Books a position the exchange no longer holds closed at the last price. No order is
+    // sent, an exit order still resting is cancelled
+    async fn close_phantom(&self, position: &Position) {
+        let key = InstrumentKey::new(&position.exchange, &position.symbol);
+        let mut open = position.clone();
+        if ExitLeg::BOTH.iter().any(|leg| leg.order_id(&open).is_some()) {
+            // The leg that filled refuses the cancel, which is expected here
+            self.cancel_exit_orders(&mut open).await;
+        }
+        let Some(claimed) = self.claim_position(&key, Some(position.id)) else {
+            return;
+        };
+        let price = self.context.last_price(&key).unwrap_or(claimed.current_price);
+        self.settle_close(&key, claimed, price, "reconciled".to_string(), Liquidity::Taker)
+            .await;
+    }
+    
+    // Takes over a position opened outside the monitor, with the stop-loss and take-profit a
+    // signal at its entry would have had
+    async fn adopt_position(&self, venue: &VenuePosition) -> Result<Position> {
+        let signal = TradingSignal {
+            id: uuid::Uuid::new_v4(),
+            timestamp: self.clock.now(),
+            symbol: venue.symbol.clone(),
+            exchange: venue.exchange.clone(),
+            signal_type: match venue.side {
+                PositionSide::Long => SignalType::Buy,
+                PositionSide::Short => SignalType::Sell,
+            },
+            strength: SignalStrength::Medium,
+            price: venue.entry_price,
+            reason: "Adopted from the exchange account".to_string(),
+            anomaly_id: None,
+        };
+        let position = self
+            .create_position(venue.quantity, venue.entry_price, 0.0, signal, venue.side.clone())
+            .await?;
+        let position = self.update_exit_orders(position).await;
+        let trade = ExecutedTrade {
+            position_id: position.id,
+            order_id: None,
+            kind: TradeKind::Entry,
+            quantity: position.quantity,
+            price: position.entry_price,
+            realized_pnl: None,
+            fee: 0.0,
+            executed_at: position.opened_at,
+        };
+        self.persist(&position, Some(trade)).await;
+        self.send_alert(
+            AlertType::Warning,
+            serde_json::json!({
+                "position": position.id,
+                "exchange": position.exchange,
+                "symbol": position.symbol,
+                "quantity": position.quantity,
+                "message": "Adopted a position opened outside the monitor",
+            }),
+        );
+        Ok(position)
+    }
+    
+    // Takes the exchange's size, and its entry when reported, resizing resting exits to match
+    async fn resize_position(&self, position: &Position, venue: &VenuePosition) {
+        let key = InstrumentKey::new(&position.exchange, &position.symbol);
+        let resized = match self.positions.get_mut(&key) {
+            Some(mut open) if open.id == position.id => {
+                open.quantity = venue.quantity;
+                if venue.entry_price > 0.0 {
+                    open.entry_price = venue.entry_price;
+                }
+                Some(open.clone())
+            }
+            _ => None,
+        };
+        if let Some(resized) = resized {
+            let resized = self.update_exit_orders(resized).await;
+            self.persist(&resized, None).await;
+        }
+    }
+    
+    // Sends every DCA clip that has come due, returning how many went out. Positions whose
+    // plan has ended since get their exits resized, and the plans are saved when they changed
+    pub async fn execute_due_clips(&self) -> Result<usize> {
+        let mut sent = 0;
+        for plan in self.dca_plans.due(self.clock.now()) {
+            match self.send_clip(plan.id).await {
+                Ok(true) => sent += 1,
+                Ok(false) => {}
+                Err(e) => error!("Failed to send a clip of DCA plan {}: {}", plan.id, e),
+            }
+        }
+        
+        if !self.exits_deferred.is_empty() {
+            let ended: Vec<Position> = self
+                .positions
+                .iter()
+                .filter(|p| {
+                    self.exits_deferred.contains(&p.id)
+                        && !self.dca_plans.has_active(&p.exchange, &p.symbol)
+                })
+                .map(|p| p.clone())
+                .collect();
+            for position in ended {
+                self.exits_deferred.remove(&position.id);
+                let position = self.update_exit_orders(position).await;
+                self.persist(&position, None).await;
+            }
+        }
+        
+        self.save_dca_plans().await;
+        Ok(sent)
+    }
+    
+    async fn save_dca_plans(&self) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        if !self.dca_plans.take_changed() {
+            return;
+        }
+        let plans = serde_json::json!(self.dca_plans.list());
+        if let Err(e) = store.save_state(DCA_STATE, &plans).await {
+            error!("Failed to save the DCA plans: {}", e);
+        }
+    }
+    
+    // What stops the next clip of `plan` at `price`: the halt, venue pause and portfolio checks
+    // a signal goes through are run again for every clip. The portfolio is checked for the
+    // whole plan, so the position its clips are building is left out
+    async fn clip_rejection(
+        &self,
+        plan: &DcaPlan,
+        price: f64,
+    ) -> Result<Option<(SignalOutcome, serde_json::Value)>> {
+        let halted = self.circuit_breaker.is_halted();
+        if let Some(halt) = self.circuit_breaker.check(chrono::Utc::now()) {
+            if !halted {
+                self.on_halt(&halt).await;
+            }
+            return Ok(Some((SignalOutcome::Halted, serde_json::to_value(&halt)?)));
+        }
+        
+        if let Some(reason) = self.venue_pause(&plan.signal.exchange, chrono::Utc::now()) {
+            return Ok(Some((SignalOutcome::VenuePaused, serde_json::json!({ "reason": reason }))));
+        }
+        
+        let mut signal = plan.signal.clone();
+        signal.price = price;
+        let key = InstrumentKey::new(&signal.exchange, &signal.symbol);
+        let others: Vec<Position> = self
+            .positions
+            .iter()
+            .filter(|p| *p.key() != key)
+            .map(|p| p.clone())
+            .collect();
+        let pending: Vec<PendingEntry> = self
+            .pending_entries()
+            .into_iter()
+            .filter(|entry| entry.exchange != signal.exchange || entry.symbol != signal.symbol)
+            .collect();
+        let portfolio_value = self.get_portfolio_value();
+        let rejection =
+            self.risk_manager.validate_portfolio(&signal, &others, &pending, portfolio_value);
+        if let Some(rejection) = rejection {
+            return Ok(Some((SignalOutcome::RejectedByRisk, serde_json::to_value(&rejection)?)));
+        }
+        Ok(None)
+    }
+    
+    // Entries submitted and not filled yet: what working limit orders still have open, and
+    // entries whose submission has no known outcome
+    fn pending_entries(&self) -> Vec<PendingEntry> {
+        let orders = self
+            .pending_orders
+            .all()
+            .into_iter()
+            .filter(|order| !order.status.is_terminal())
+            .map(|order| PendingEntry {
+                notional: order.remaining() * order.limit_price,
+                exchange: order.signal.exchange,
+                symbol: order.signal.symbol,
+            });
+        let intents = self.intents.all().into_iter().filter_map(|intent| match intent.kind {
+            IntentKind::Entry {
+                signal,
+                quantity,
+                limit_price,
+                ..
+            } => Some(PendingEntry {
+                notional: quantity * limit_price.unwrap_or(signal.price),
+                exchange: signal.exchange,
+                symbol: signal.symbol,
+            }),
+            IntentKind::Close { .. } => None,
+        });
+        orders.chain(intents).collect()
+    }
+    
+    // Sends the next clip of plan `id` at the last price, or drops the plan once the price has
+    // moved past its abort threshold. Returns whether a clip went out
+    async fn send_clip(&self, id: uuid::Uuid) -> Result<bool> {
+        let Some(plan) = self.dca_plans.get(id) else {
+            return Ok(false);
+        };
+        let key = InstrumentKey::new(&plan.signal.exchange, &plan.signal.symbol);
+        let price = self.context.last_price(&key).unwrap_or(plan.signal.price);
+        if plan.should_abort(price) {
+            if self.dca_plans.cancel(id).is_some() {
+                warn!(
+                    "DCA entry on {} aborted at {}, {:.2}% against the signal after {} of {} clips",
+                    key,
+                    price,
+                    plan.adverse_move_pct(price),
+                    plan.clips_sent,
+                    plan.clips
+                );
+                self.journal.record(
+                    &plan.signal,
+                    SignalOutcome::DcaAborted,
+                    serde_json::json!({
+                        "plan": plan.id,
+                        "price": price,
+                        "clips_sent": plan.clips_sent,
+                        "sent_quantity": plan.sent_quantity,
+                    }),
+                );
+            }
+            return Ok(false);
+        }
+        
+        if let Some((outcome, reason)) = self.clip_rejection(&plan, price).await? {
+            if self.dca_plans.cancel(id).is_some() {
+                warn!(
+                    "DCA entry on {} stopped after {} of {} clips: {:?} {}",
+                    key, plan.clips_sent, plan.clips, outcome, reason
+                );
+                self.journal.record(
+                    &plan.signal,
+                    outcome,
+                    serde_json::json!({
+                        "plan": plan.id,
+                        "reason": reason,
+                        "clips_sent": plan.clips_sent,
+                        "sent_quantity": plan.sent_quantity,
+                    }),
+                );
+            }
+            return Ok(false);
+        }
+        
+        // Claimed before it is sent, so a cancel or a concurrent run cannot send it twice
+        let Some((quantity, plan)) = self.dca_plans.claim_clip(id, self.clock.now()) else {
+            return Ok(false);
+        };
+        info!("Sending clip {} of {} for {}: {}", plan.clips_sent, plan.clips, key, quantity);
+        let mut signal = plan.signal.clone();
+        signal.price = price;
+        let clip = Some(plan.clips_sent);
+        self.execute_market_entry(signal, &plan.strategy, plan.side, quantity, clip)
+            .await?;
+        Ok(true)
+    }
+    
+    // Closes at market every position open for its market's `max_holding_minutes` or longer
+    pub async fn close_expired_positions(&self) -> Result<usize> {
+        let config = self.config.read().clone();
+        let now = self.clock.now();
+        
+        let expired: Vec<(InstrumentKey, uuid::Uuid, u64)> = self
+            .positions
+            .iter()
+            .filter_map(|p| {
+                let minutes = overrides::resolve_config(&config, &p.exchange, &p.symbol)
+                    .max_holding_minutes?;
+                (now - p.opened_at >= chrono::Duration::minutes(minutes as i64))
+                    .then(|| (p.key().clone(), p.id, minutes))
+            })
+            .collect();
+        
+        let mut closed = 0;
+        for (key, id, minutes) in expired {
+            info!("Position {} on {} held past {} minutes", id, key, minutes);
+            match self.close_position(&key, Some(id), CloseReason::MaxHoldingPeriod).await {
+                Ok(true) => closed += 1,
+                Ok(false) => {}
+                Err(e) => error!("Failed to close expired position {}: {}", id, e),
+            }
+        }
+        Ok(closed)
+    }
+    
+    // Takes the position out of the book, the one atomic step every close goes through, so of
+    // racing closes only the first gets it. With `position_id` only that position is taken,
+    // not one opened on the symbol since the caller looked
+    fn claim_position(&self, position_key: &str, position_id: Option<uuid::Uuid>) -> Option<Position> {
+        self.positions
+            .remove_if(position_key, |_, position| position_id.is_none_or(|id| position.id == id))
+            .map(|(_, position)| position)
+    }
+    
+    // The single close path. Returns false when there was nothing left to close, another
+    // close having claimed the position first
+    async fn close_position(
+        &self,
+        position_key: &str,
+        position_id: Option<uuid::Uuid>,
+        reason: CloseReason,
+    ) -> Result<bool> {
+        // Resting exits go first, or they would still execute after the market close
+        let open = self
+            .positions
+            .get(position_key)
+            .filter(|p| position_id.is_none_or(|id| p.id == id))
+            .map(|p| p.clone());
+        if let Some(mut open) = open {
+            let resting = ExitLeg::BOTH.iter().any(|leg| leg.order_id(&open).is_some());
+            if resting && !self.cancel_exit_orders(&mut open).await {
+                return Ok(false);
+            }
+        }
+        
+        if let Some(position) = self.claim_position(position_key, position_id) {
+            info!("Closing position {} on {}: {:?}", position.id, position_key, reason);
+            if reason == CloseReason::StopLoss {
+                self.cooldowns.record_stop_loss(position_key, chrono::Utc::now());
+            }
+            let side = match position.side {
+                PositionSide::Long => OrderKind::Sell,
+                PositionSide::Short => OrderKind::Buy,
+            };
+            
+            let client_order_id = retry::close_order_id(&position);
+            let order_request = RequestOpen {
+                instrument: position.symbol.clone(),
+                exchange: position.exchange.clone(),
+                client_order_id: Some(client_order_id.clone()),
+                kind: side,
+                order_type: OrderType::Market,
+                quantity: position.quantity,
+                price: None,
+                time_in_force: None,
+                post_only: false,
+                reduce_only: true,
+            };
+            
+            self.intents.record(OrderIntent {
+                client_order_id: client_order_id.clone(),
+                key: position_key.to_string(),
+                kind: IntentKind::Close {
+                    position: position.clone(),
+                },
+                created_at: chrono::Utc::now(),
+            });
+            
+            let result = self.open_order_with_retry(&client_order_id, order_request).await;
+            match result {
+                Ok(Some(order)) => {
+                    info!("Position closed: {:?}", order);
+                    // Realized at the closing fill, which can differ from the last mark
+                    let expected = self
+                        .slippage()
+                        .exit_price(position.current_price, position.side == PositionSide::Short);
+                    let exit_price = fill_price(&order, expected);
+                    self.settle_close(
+                        position_key,
+                        position,
+                        exit_price,
+                        order.id.to_string(),
+                        Liquidity::Taker,
+                    )
+                    .await;
+                }
+                Ok(None) => {
+                    // Nothing executed, the position is still held
+                    warn!("Close of position {} returned no order, kept open", position.id);
+                    let key = InstrumentKey::new(&position.exchange, &position.symbol);
+                    self.positions.insert(key, position);
+                    return Ok(false);
+                }
+                Err(e) => {
+                    error!("Failed to close position {}: {}", client_order_id, e);
+                    // Re-insert position if close failed
+                    let key = InstrumentKey::new(&position.exchange, &position.symbol);
+                    self.positions.insert(key, position);
+                    return Err(MonitorError::Other(format!("Position close failed: {}", e)));
+                }
+            }
+            return Ok(true);
+        }
+        
+        Ok(false)
+    }
+    
+    // Books a position closed by `order_id` at `exit_price`: its realized PnL, net of the
+    // entry and exit fees, goes into the stats, the breaker and the portfolio, the exit into
+    // the store
+    async fn settle_close(
+        &self,
+        position_key: &str,
+        mut position: Position,
+        exit_price: f64,
+        order_id: String,
+        liquidity: Liquidity,
+    ) {
+        self.exit_crossings.remove(&position.id);
+        self.exits_deferred.remove(&position.id);
+        let closed_at = chrono::Utc::now();
+        let fee = self
+            .fees()
+            .fee(&position.exchange, position.quantity * exit_price, liquidity);
+        position.fees += fee;
+        let pnl = position.close(exit_price, closed_at);
+        info!(
+            "Position {} realized PnL {:.2} @ {} after {:.2} in fees",
+            position.id, pnl, exit_price, position.fees
+        );
+        self.stale_guard.remove(position_key);
+        self.stats.write().record_trade(pnl, position.fees, closed_at);
+        let halted = self.circuit_breaker.is_halted();
+        self.circuit_breaker.record_trade(pnl, closed_at);
+        self.portfolio.write().apply_realized(pnl);
+        let trade = ExecutedTrade {
+            position_id: position.id,
+            order_id: Some(order_id),
+            kind: TradeKind::Close,
+            quantity: position.quantity,
+            price: exit_price,
+            realized_pnl: Some(pnl),
+            fee,
+            executed_at: closed_at,
+        };
+        self.persist(&position, Some(trade)).await;
+        // A closed position ends the entry still building it
+        for plan in self.dca_plans.cancel_for(&position.exchange, &position.symbol) {
+            info!("DCA plan {} cancelled, position {} closed", plan.id, position.id);
+        }
+        {
+            let mut closed = self.closed_positions.write();
+            closed.push_back(position);
+            if closed.len() > MAX_CLOSED_POSITIONS {
+                closed.pop_front();
+            }
+        }
+        
+        if !halted {
+            if let Some(halt) = self.circuit_breaker.status(closed_at).halt {
+                self.on_halt(&halt).await;
+            }
+        }
+    }
+    
+    pub fn get_positions(&self) -> Vec<Position> {
+        self.positions
+            .iter()
+            .map(|p| {
+                let mut position = p.clone();
+                position.stale = self.stale_guard.is_stale(p.key());
+                position
+            })
+            .collect()
+    }
+    
+    // Realized balance, plus unrealized PnL of open positions when `mark_to_market` is set
+    pub fn get_portfolio_value(&self) -> f64 {
+        let mark_to_market = self.config.read().portfolio.mark_to_market;
+        let open: Vec<Position> = if mark_to_market {
+            self.positions.iter().map(|p| p.clone()).collect()
+        } else {
+            Vec::new()
+        };
+        self.portfolio.read().value(&open, mark_to_market)
+    }
+    
+    // Applies fills and resynchronizes the portfolio value from balances reported on the
+    // account stream
+    pub async fn on_account_event(&self, event: &UnindexedAccountEvent) -> Result<()> {
+        if let Some(fill) = orders::fill_of(event) {
+            return self.on_fill(&fill).await;
+        }
+        
+        let quote_asset = self.config.read().portfolio.quote_asset.clone();
+        if let Some(balance) = portfolio::quote_balance(event, &quote_asset) {
+            self.portfolio.write().sync(balance);
+        }
+        Ok(())
+    }
+    
+    // Spawns the task applying the venue's account stream through `on_account_event` until it
+    // ends or `shutdown` fires
+    pub fn follow_account<S>(
+        self: &Arc<Self>,
+        events: S,
+        mut shutdown: watch::Receiver<bool>,
+    ) -> JoinHandle<()>
+    where
+        S: Stream<Item = UnindexedAccountEvent> + Send + 'static,
+    {
+        let trader = self.clone();
+        tokio::spawn(async move {
+            let mut events = std::pin::pin!(events);
+            loop {
+                let event = tokio::select! {
+                    _ = shutdown.changed() => break,
+                    event = events.next() => event,
+                };
+                let Some(event) = event else {
+                    warn!("Account stream ended, fills are no longer applied as they arrive");
+                    break;
+                };
+                if let Err(e) = trader.on_account_event(&event).await {
+                    error!("Failed to apply account event: {}", e);
+                }
+            }
+            info!("Account stream stopped");
+        })
+    }
+    
+    pub fn get_closed_positions(&self) -> Vec<Position> {
+        self.closed_positions.read().iter().cloned().collect()
+    }
+    
+    pub fn get_pending_orders(&self) -> Vec<PendingOrder> {
+        self.pending_orders.all()
+    }
+    
+    pub fn get_order_intents(&self) -> Vec<OrderIntent> {
+        self.intents.all()
+    }
+    
+    pub fn get_journal(&self) -> Vec<crate::journal::JournalEntry> {
+        self.journal.entries()
+    }
+    
+    // Every journal record between `from` and `to`, oldest first, spilled ones included
+    pub async fn export_journal(
+        &self,
+        from: Option<chrono::DateTime<chrono::Utc>>,
+        to: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<Vec<JournalRecord>> {
+        let mut records = match &self.journal_store {
+            Some(store) => store.load(from, to).await?,
+            None => Vec::new(),
+        };
+        records.extend(self.journal.records(from, to));
+        Ok(records)
+    }
+    
+    // Should be called on shutdown, after `shutdown`. Waits for the spilled records to be
+    // written, then writes the ones still in memory, so the store holds the whole journal.
+    // Records made afterwards stay in memory only. Returns the records written from memory
+    pub async fn flush_journal(&self) -> Result<usize> {
+        let Some(store) = &self.journal_store else {
+            return Ok(0);
+        };
+        self.journal.close_spill();
+        let writer = self.journal_writer.lock().take();
+        if let Some(writer) = writer {
+            if let Err(e) = writer.await {
+                error!("Journal spill writer failed: {}", e);
+            }
+        }
+        
+        let records = self.journal.drain();
+        for record in &records {
+            store.insert(record).await?;
+        }
+        Ok(records.len())
+    }
+    
+    // Should be called on shutdown, after `flush_journal`; writes the whole journal to
+    // `journal.export_path`, reading the store a page at a time. Returns the path written,
+    // `None` when no path is configured
+    pub async fn write_journal(&self) -> Result<Option<String>> {
+        use tokio::io::AsyncWriteExt;
+        
+        let (path, format) = {
+            let config = self.config.read();
+            (config.journal.export_path.clone(), config.journal.export_format)
+        };
+        let Some(path) = path else {
+            return Ok(None);
+        };
+        let failed = |e: std::io::Error| {
+            MonitorError::Other(format!("Failed to write the trade journal to {}: {}", path, e))
+        };
+        
+        let file = tokio::fs::File::create(&path).await.map_err(failed)?;
+        let mut file = tokio::io::BufWriter::new(file);
+        let mut export = journal::ExportWriter::new(format);
+        file.write_all(export.start().as_bytes()).await.map_err(failed)?;
+        if let Some(store) = &self.journal_store {
+            let mut after = None;
+            loop {
+                let (records, next) =
+                    store.load_page(None, None, after, JOURNAL_EXPORT_PAGE).await?;
+                file.write_all(export.page(&records)?.as_bytes()).await.map_err(failed)?;
+                match next {
+                    Some(next) => after = Some(next),
+                    None => break,
+                }
+            }
+        }
+        let records = self.journal.records(None, None);
+        file.write_all(export.page(&records)?.as_bytes()).await.map_err(failed)?;
+        file.write_all(export.finish().as_bytes()).await.map_err(failed)?;
+        file.flush().await.map_err(failed)?;
+        
+        info!("Wrote {} journal records to {}", export.written(), path);
+        Ok(Some(path))
+    }
+    
+    pub fn get_cooldowns(&self) -> Vec<CooldownStatus> {
+        self.cooldowns.active(chrono::Utc::now())
+    }
+    
+    pub fn market_snapshots(&self) -> Arc<MarketSnapshotCache> {
+        self.market.clone()
+    }
+    
+    pub fn market_context(&self) -> Arc<MarketContextCache> {
+        self.context.clone()
+    }
+    
+    pub fn cooldowns(&self) -> Arc<CooldownTracker> {
+        self.cooldowns.clone()
+    }
+    
+    pub fn circuit_breaker(&self) -> Arc<CircuitBreaker> {
+        self.circuit_breaker.clone()
+    }
+    
+    // In-progress DCA entries, shared with the API to list and cancel them
+    pub fn dca_plans(&self) -> Arc<DcaPlans> {
+        self.dca_plans.clone()
+    }
+    
+    pub fn get_breaker_status(&self) -> BreakerStatus {
+        self.circuit_breaker.status(chrono::Utc::now())
+    }
+    
+    // Halts new entries until `resume_trading`, open positions keep being managed
+    pub async fn halt_trading(&self, note: Option<String>) -> Halt {
+        let halt = self.circuit_breaker.halt(note);
+        self.on_halt(&halt).await;
+        halt
+    }
+    
+    pub async fn resume_trading(&self) -> bool {
+        if !self.circuit_breaker.resume() {
+            return false;
+        }
+        self.save_halt(None).await;
+        true
+    }
+    
+    // Whatever would still open a position while halted is stopped: DCA plans are cancelled
+    // and so are working entry orders. The halt is saved so a restart stays halted
+    async fn on_halt(&self, halt: &Halt) {
+        for plan in self.dca_plans.list() {
+            if self.dca_plans.cancel(plan.id).is_some() {
+                info!("DCA plan {} cancelled, trading is halted", plan.id);
+            }
+        }
+        
+        // One the venue does not cancel now is cancelled again once it times out, and not
+        // replaced while halted
+        self.cancel_entry_orders(None, "trading is halted").await;
+        self.save_halt(Some(halt)).await;
+    }
+    
+    // Cancels the working entry orders, only those on `key` when given
+    async fn cancel_entry_orders(&self, key: Option<&InstrumentKey>, reason: &str) {
+        for order in self.pending_orders.all() {
+            if order.status.is_terminal() || key.is_some_and(|key| order.key != *key) {
+                continue;
+            }
+            let cancel_request = RequestCancel {
+                id: OrderId::new(order.order_id.clone()),
+                instrument: order.signal.symbol.clone(),
+                exchange: order.signal.exchange.clone(),
+            };
+            match self.execution_client.cancel_order(cancel_request).await {
+                Ok(_) => {
+                    self.pending_orders.update(&order.order_id, OrderStatus::Cancelled);
+                    info!("Entry order {} for {} cancelled, {}", order.order_id, order.key, reason);
+                }
+                Err(e) => warn!(
+                    "Failed to cancel order {} for {}: {}",
+                    order.order_id, order.key, e
+                ),
+            }
+        }
+    }
+    
+    async fn save_halt(&self, halt: Option<&Halt>) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        if let Err(e) = store.save_state(HALT_STATE, &serde_json::json!(halt)).await {
+            error!("Failed to save the circuit breaker halt: {}", e);
+        }
+    }
+    
+    // Blocks new positions on the exchange until `until`, open positions keep being managed
+    pub fn pause_venue(&self, exchange: &str, until: chrono::DateTime<chrono::Utc>, reason: &str) {
+        let previous = self
+            .paused_venues
+            .insert(exchange.to_lowercase(), (until, reason.to_string()));
+        if previous.is_none() {
+            warn!("Trading paused on {}: {}", exchange, reason);
+        }
+    }
+    
+    pub fn resume_venue(&self, exchange: &str) {
+        if self.paused_venues.remove(&exchange.to_lowercase()).is_some() {
+            info!("Trading resumed on {}", exchange);
+        }
+    }
+    
+    pub fn venue_pause(&self, exchange: &str, now: chrono::DateTime<chrono::Utc>) -> Option<String> {
+        self.paused_venues
+            .get(&exchange.to_lowercase())
+            .filter(|pause| now < pause.0)
+            .map(|pause| pause.1.clone())
+    }
+    
+    pub fn get_stats(&self) -> TradingStats {
+        self.stats.read().stats()
+    }
+    
+    // Realized equity after every closed trade, oldest first
+    pub fn get_equity_curve(&self) -> Vec<EquityPoint> {
+        self.stats.read().equity_curve()
+    }
+    
+    pub fn get_config(&self) -> TradingConfig {
+        self.config.read().clone()
+    }
+    
+    pub fn update_config(&self, config: TradingConfig) {
+        self.stale_guard
+            .set_policy(config.stale_position_threshold_secs, config.stale_position_fallback);
+        self.cooldowns.set_config(config.cooldown.clone());
+        self.circuit_breaker.set_config(config.circuit_breaker.clone());
+        self.context.set_price_change_window(config.price_change_window_secs);
+        self.stats.write().set_risk_free_rate(config.portfolio.risk_free_rate);
+        *self.config.write() = config.clone();
+        self.risk_manager.update_config(config.clone());
+        self.strategy.write().update_config(config);
+    }
+    
+    fn fees(&self) -> FeeModel {
+        FeeModel::from_config(&self.config.read().costs)
+    }
+    
+    fn slippage(&self) -> SlippageModel {
+        SlippageModel::from_config(&self.config.read().costs)
+    }
+    
+    // The config in effect for one market, its overrides applied
+    pub fn resolve_config(&self, exchange: &str, symbol: &str) -> TradingConfig {
+        overrides::resolve_config(&self.config.read(), exchange, symbol)
+    }
+}
+
+// Average fill price of `order`, `fallback` when the venue did not report one
+fn fill_price(order: &Order, fallback: f64) -> f64 {
+    order.price.filter(|price| *price > 0.0).unwrap_or(fallback)
+}
+
+fn entry_kind(side: &PositionSide) -> OrderKind {
+    match side {
+        PositionSide::Long => OrderKind::Buy,
+        PositionSide::Short => OrderKind::Sell,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        clock::ManualClock, fixtures, risk::SimpleRiskManager, strategy::AnomalyBasedStrategy,
+    };
+    use async_trait::async_trait;
+    use barter_execution::error::{ApiError, ConnectivityError};
+    use monitor_anomaly::{AnomalyMetrics, AnomalySeverity};
+    use monitor_core::{AnomalyType, DcaConfig, StalePositionFallback, TradingOverride};
+    use parking_lot::Mutex;
+    use std::collections::{HashMap, HashSet};
+    
+    // Venue deduplicating by client order id. The first attempt times out before the order
+    // lands, the second lands it and then times out, leaving the outcome ambiguous
+    #[derive(Default)]
+    struct FlakyVenue {
+        attempts: Mutex<u32>,
+        orders: Mutex<HashMap<String, Order>>,
+        reject: bool,
+    }
+    
+    #[async_trait]
+    impl OrderClient for FlakyVenue {
+        async fn open_order(
+            &self,
+            request: RequestOpen,
+        ) -> std::result::Result<Option<Order>, UnindexedClientError> {
+            let attempt = {
+                let mut attempts = self.attempts.lock();
+                *attempts += 1;
+                *attempts
+            };
+            if self.reject {
+                return Err(UnindexedClientError::Api(ApiError::OrderRejected("halted".to_string())));
+            }
+            
+            let cid = request.client_order_id.clone().unwrap();
+            if let Some(order) = self.orders.lock().get(&cid) {
+                return Ok(Some(order.clone()));
+            }
+            if attempt == 1 {
+                return Err(UnindexedClientError::Connectivity(ConnectivityError::Timeout));
+            }
+            
+            let order = Order {
+                id: OrderId::new(format!("order-{}", attempt)),
+                instrument: request.instrument,
+                exchange: request.exchange,
+                kind: request.kind,
+                order_type: request.order_type,
+                quantity: request.quantity,
+                price: request.price,
+                state: OrderState::Filled,
+            };
+            self.orders.lock().insert(cid, order);
+            Err(UnindexedClientError::Connectivity(ConnectivityError::Timeout))
+        }
+        
+        async fn cancel_order(
+            &self,
+            _request: RequestCancel,
+        ) -> std::result::Result<Option<Order>, UnindexedClientError> {
+            Ok(None)
+        }
+        
+        async fn find_order(
+            &self,
+            client_order_id: &str,
+            _instrument: &str,
+            _exchange: &str,
+        ) -> std::result::Result<Option<Order>, UnindexedClientError> {
+            Ok(self.orders.lock().get(client_order_id).cloned())
+        }
+    }
+    
+    // Venue resting every order, fills are delivered separately
+    #[derive(Default)]
+    struct RestingVenue {
+        cancels: Mutex<Vec<String>>,
+    }
+    
+    #[async_trait]
+    impl OrderClient for RestingVenue {
+        async fn open_order(
+            &self,
+            request: RequestOpen,
+        ) -> std::result::Result<Option<Order>, UnindexedClientError> {
+            Ok(Some(Order {
+                id: OrderId::new(request.client_order_id.unwrap()),
+                instrument: request.instrument,
+                exchange: request.exchange,
+                kind: request.kind,
+                order_type: request.order_type,
+                quantity: request.quantity,
+                price: request.price,
+                state: OrderState::Open,
+            }))
+        }
+        
+        async fn cancel_order(
+            &self,
+            request: RequestCancel,
+        ) -> std::result::Result<Option<Order>, UnindexedClientError> {
+            self.cancels.lock().push(request.id.to_string());
+            Ok(None)
+        }
+    }
+    
+    // Venue filling every order in full at its request price
+    struct FillingVenue;
+    
+    #[async_trait]
+    impl OrderClient for FillingVenue {
+        async fn open_order(
+            &self,
+            request: RequestOpen,
+        ) -> std::result::Result<Option<Order>, UnindexedClientError> {
+            Ok(Some(Order {
+                id: OrderId::new(request.client_order_id.unwrap()),
+                instrument: request.instrument,
+                exchange: request.exchange,
+                kind: request.kind,
+                order_type: request.order_type,
+                quantity: request.quantity,
+                price: request.price,
+                state: OrderState::Filled,
+            }))
+        }
+        
+        async fn cancel_order(
+            &self,
+            _request: RequestCancel,
+        ) -> std::result::Result<Option<Order>, UnindexedClientError> {
+            Ok(None)
+        }
+    }
+    
+    // Venue filling market orders at their request price and resting everything else. Cancels
+    // of the order ids in `filled` fail, as those orders already executed
+    #[derive(Default)]
+    struct ExitVenue {
+        opened: Mutex<Vec<RequestOpen>>,
+        cancels: Mutex<Vec<String>>,
+        filled: Mutex<Vec<String>>,
+    }
+    
+    #[async_trait]
+    impl OrderClient for ExitVenue {
+        async fn open_order(
+            &self,
+            request: RequestOpen,
+        ) -> std::result::Result<Option<Order>, UnindexedClientError> {
+            self.opened.lock().push(request.clone());
+            let state = match request.order_type {
+                OrderType::Market => OrderState::Filled,
+                _ => OrderState::Open,
+            };
+            Ok(Some(Order {
+                id: OrderId::new(request.client_order_id.unwrap()),
+                instrument: request.instrument,
+                exchange: request.exchange,
+                kind: request.kind,
+                order_type: request.order_type,
+                quantity: request.quantity,
+                price: request.price,
+                state,
+            }))
+        }
+        
+        async fn cancel_order(
+            &self,
+            request: RequestCancel,
+        ) -> std::result::Result<Option<Order>, UnindexedClientError> {
+            let id = request.id.to_string();
+            if self.filled.lock().contains(&id) {
+                return Err(UnindexedClientError::Api(ApiError::OrderRejected(format!(
+                    "order {} already filled",
+                    id
+                ))));
+            }
+            self.cancels.lock().push(id);
+            Ok(None)
+        }
+    }
+    
+    // Venue resting limit orders until `fill_after` has passed on `clock`, `poll` fills them at
+    // their price. Market orders fill on arrival. Cancels of filled orders are refused, as are
+    // all of them under `refuse_cancels`
+    struct DelayedVenue {
+        clock: Arc<ManualClock>,
+        fill_after: chrono::Duration,
+        resting: Mutex<Vec<(RequestOpen, chrono::DateTime<chrono::Utc>)>>,
+        opened: Mutex<Vec<RequestOpen>>,
+        cancels: Mutex<Vec<String>>,
+        refuse_cancels: std::sync::atomic::AtomicBool,
+    }
+    
+    impl DelayedVenue {
+        fn new(clock: Arc<ManualClock>, fill_after_secs: i64) -> Self {
+            Self {
+                clock,
+                fill_after: chrono::Duration::seconds(fill_after_secs),
+                resting: Mutex::new(Vec::new()),
+                opened: Mutex::new(Vec::new()),
+                cancels: Mutex::new(Vec::new()),
+                refuse_cancels: std::sync::atomic::AtomicBool::new(false),
+            }
+        }
+        
+        // Fills of the orders that have rested long enough by now
+        fn poll(&self) -> Vec<Fill> {
+            let now = self.clock.now();
+            let mut resting = self.resting.lock();
+            let (filled, waiting) = resting
+                .drain(..)
+                .partition::<Vec<_>, _>(|(_, placed)| now - *placed >= self.fill_after);
+            *resting = waiting;
+            filled
+                .into_iter()
+                .map(|(request, _)| Fill {
+                    order_id: request.client_order_id.unwrap(),
+                    quantity: request.quantity,
+                    price: request.price.unwrap(),
+                })
+                .collect()
+        }
+    }
+    
+    #[async_trait]
+    impl OrderClient for DelayedVenue {
+        async fn open_order(
+            &self,
+            request: RequestOpen,
+        ) -> std::result::Result<Option<Order>, UnindexedClientError> {
+            self.opened.lock().push(request.clone());
+            let (state, price) = match request.order_type {
+                OrderType::Market => (OrderState::Filled, Some(50_000.0)),
+                _ => {
+                    self.resting.lock().push((request.clone(), self.clock.now()));
+                    (OrderState::Open, request.price)
+                }
+            };
+            Ok(Some(Order {
+                id: OrderId::new(request.client_order_id.unwrap()),
+                instrument: request.instrument,
+                exchange: request.exchange,
+                kind: request.kind,
+                order_type: request.order_type,
+                quantity: request.quantity,
+                price,
+                state,
+            }))
+        }
+        
+        async fn cancel_order(
+            &self,
+            request: RequestCancel,
+        ) -> std::result::Result<Option<Order>, UnindexedClientError> {
+            let id = request.id.to_string();
+            let mut resting = self.resting.lock();
+            let refused = self.refuse_cancels.load(std::sync::atomic::Ordering::SeqCst);
+            if refused || !resting.iter().any(|(r, _)| r.client_order_id.as_ref() == Some(&id)) {
+                return Err(UnindexedClientError::Api(ApiError::OrderRejected(format!(
+                    "order {} cannot be cancelled",
+                    id
+                ))));
+            }
+            resting.retain(|(r, _)| r.client_order_id.as_ref() != Some(&id));
+            self.cancels.lock().push(id);
+            Ok(None)
+        }
+    }
+    
+    fn delayed_trader(fill_after_secs: i64) -> (AutoTrader, Arc<DelayedVenue>, Arc<ManualClock>) {
+        let clock = Arc::new(ManualClock::new(chrono::Utc::now()));
+        let venue = Arc::new(DelayedVenue::new(clock.clone(), fill_after_secs));
+        let trader = trader(venue.clone()).with_clock(clock.clone());
+        trader.update_config(limit_config(false));
+        (trader, venue, clock)
+    }
+    
+    // Venue filling every order in full until `stalled` is set, after which no call returns
+    #[derive(Default)]
+    struct StallingVenue {
+        stalled: std::sync::atomic::AtomicBool,
+    }
+    
+    impl StallingVenue {
+        async fn stall(&self) {
+            if self.stalled.load(std::sync::atomic::Ordering::SeqCst) {
+                std::future::pending::<()>().await;
+            }
+        }
+    }
+    
+    #[async_trait]
+    impl OrderClient for StallingVenue {
+        async fn open_order(
+            &self,
+            request: RequestOpen,
+        ) -> std::result::Result<Option<Order>, UnindexedClientError> {
+            self.stall().await;
+            Ok(Some(Order {
+                id: OrderId::new(request.client_order_id.unwrap()),
+                instrument: request.instrument,
+                exchange: request.exchange,
+                kind: request.kind,
+                order_type: request.order_type,
+                quantity: request.quantity,
+                price: request.price,
+                state: OrderState::Filled,
+            }))
+        }
+        
+        async fn cancel_order(
+            &self,
+            _request: RequestCancel,
+        ) -> std::result::Result<Option<Order>, UnindexedClientError> {
+            self.stall().await;
+            Ok(None)
+        }
+    }
+    
+    // Venue filling every order in full, except the closes of `symbol` which it rejects
+    struct RejectingCloseVenue {
+        symbol: &'static str,
+    }
+    
+    #[async_trait]
+    impl OrderClient for RejectingCloseVenue {
+        async fn open_order(
+            &self,
+            request: RequestOpen,
+        ) -> std::result::Result<Option<Order>, UnindexedClientError> {
+            if request.reduce_only && request.instrument == self.symbol {
+                return Err(UnindexedClientError::Api(ApiError::OrderRejected("halted".to_string())));
+            }
+            FillingVenue.open_order(request).await
+        }
+        
+        async fn cancel_order(
+            &self,
+            _request: RequestCancel,
+        ) -> std::result::Result<Option<Order>, UnindexedClientError> {
+            Ok(None)
+        }
+    }
+    
+    // Venue filling entries and answering closes with no order at all
+    struct SilentCloseVenue;
+    
+    #[async_trait]
+    impl OrderClient for SilentCloseVenue {
+        async fn open_order(
+            &self,
+            request: RequestOpen,
+        ) -> std::result::Result<Option<Order>, UnindexedClientError> {
+            if request.reduce_only {
+                return Ok(None);
+            }
+            FillingVenue.open_order(request).await
+        }
+        
+        async fn cancel_order(
+            &self,
+            _request: RequestCancel,
+        ) -> std::result::Result<Option<Order>, UnindexedClientError> {
+            Ok(None)
+        }
+    }
+    
+    // Venue filling entries at their request price and closes at its own, whatever the mark
+    struct SlippedCloseVenue(f64);
+    
+    #[async_trait]
+    impl OrderClient for SlippedCloseVenue {
+        async fn open_order(
+            &self,
+            mut request: RequestOpen,
+        ) -> std::result::Result<Option<Order>, UnindexedClientError> {
+            if request.reduce_only {
+                request.price = Some(self.0);
+            }
+            FillingVenue.open_order(request).await
+        }
+        
+        async fn cancel_order(
+            &self,
+            _request: RequestCancel,
+        ) -> std::result::Result<Option<Order>, UnindexedClientError> {
+            Ok(None)
+        }
+    }
+    
+    // REST ticker quoting the same price for every pair
+    struct FixedPrice(f64);
+    
+    #[async_trait]
+    impl PriceSource for FixedPrice {
+        async fn fetch_price(
+            &self,
+            _symbol: &str,
+            _exchange: &str,
+            _kind: InstrumentKind,
+        ) -> Result<f64> {
+            Ok(self.0)
+        }
+    }
+    
+    // Sized by the max position cap, which keeps the notional within the risk budget
+    fn config() -> TradingConfig {
+        fixtures::trading_config(serde_json::json!({
+            "max_position_size": 100.0,
+            "retry": { "max_attempts": 3, "base_delay_ms": 1, "max_delay_ms": 2 },
+        }))
+    }
+    
+    fn trader(venue: Arc<dyn OrderClient>) -> AutoTrader {
+        let config = config();
+        AutoTrader::new(
+            config.clone(),
+            Box::new(AnomalyBasedStrategy::new(config.clone())),
+            Box::new(SimpleRiskManager::new(config)),
+            venue,
+            10_000.0,
+        )
+    }
+    
+    fn limit_config(cancel_partial_remainder: bool) -> TradingConfig {
+        let mut config = config();
+        config.order_placement.mode = OrderPlacementMode::Limit;
+        config.order_placement.cancel_partial_remainder = cancel_partial_remainder;
+        config
+    }
+    
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+    
+    fn signal() -> TradingSignal {
+        signal_at(50_000.0)
+    }
+    
+    fn signal_at(price: f64) -> TradingSignal {
+        fixtures::signal("BTC/USDT", price)
+    }
+    
+    #[tokio::test]
+    async fn test_retries_after_ambiguous_timeouts_open_one_position() {
+        let venue = Arc::new(FlakyVenue::default());
+        let trader = trader(venue.clone());
+        
+        trader.execute_signal(signal(), "default").await.unwrap();
+        
+        assert_eq!(*venue.attempts.lock(), 3);
+        assert_eq!(venue.orders.lock().len(), 1);
+        assert_eq!(trader.get_positions().len(), 1);
+        assert!(trader.get_order_intents().is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_rejection_is_not_retried() {
+        let venue = Arc::new(FlakyVenue {
+            reject: true,
+            ..Default::default()
+        });
+        let trader = trader(venue.clone());
+        
+        assert!(trader.execute_signal(signal(), "default").await.is_err());
+        assert_eq!(*venue.attempts.lock(), 1);
+        assert!(trader.get_positions().is_empty());
+        assert!(trader.get_order_intents().is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_unresolved_entry_is_reconciled_under_the_same_id() {
+        let venue = Arc::new(FlakyVenue::default());
+        let trader = trader(venue.clone());
+        trader.update_config(TradingConfig {
+            retry: monitor_core::OrderRetryConfig {
+                max_attempts: 2,
+                base_delay_ms: 1,
+                ..Default::default()
+            },
+            ..config()
+        });
+        
+        // Both attempts time out, the second after the order landed
+        assert!(trader.execute_signal(signal(), "default").await.is_err());
+        assert!(trader.get_positions().is_empty());
+        assert_eq!(trader.get_order_intents().len(), 1);
+        // A second signal must not stack another entry on top of the unresolved one
+        trader.execute_signal(signal(), "default").await.unwrap();
+        assert_eq!(*venue.attempts.lock(), 2);
+        
+        // Found on the venue by its client order id, nothing is sent again
+        trader.reconcile_intents().await.unwrap();
+        assert_eq!(*venue.attempts.lock(), 2);
+        assert_eq!(venue.orders.lock().len(), 1);
+        assert_eq!(trader.get_positions().len(), 1);
+        assert!(trader.get_order_intents().is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_unresolved_entry_waits_for_a_venue_that_cannot_look_it_up() {
+        let venue = Arc::new(StallingVenue::default());
+        let trader = trader(venue.clone());
+        trader.update_config(TradingConfig {
+            retry: monitor_core::OrderRetryConfig {
+                max_attempts: 1,
+                ..Default::default()
+            },
+            ..config()
+        });
+        venue.stalled.store(true, std::sync::atomic::Ordering::SeqCst);
+        let entry = tokio::time::timeout(
+            std::time::Duration::from_millis(50),
+            trader.execute_signal(signal(), "default"),
+        );
+        assert!(entry.await.is_err());
+        assert_eq!(trader.get_order_intents().len(), 1);
+        
+        // Neither resubmitted nor dropped
+        venue.stalled.store(false, std::sync::atomic::Ordering::SeqCst);
+        trader.reconcile_intents().await.unwrap();
+        assert_eq!(trader.get_order_intents().len(), 1);
+        assert!(trader.get_positions().is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_partial_fill_opens_then_grows_the_position() {
+        let trader = trader(Arc::new(RestingVenue::default()));
+        trader.update_config(limit_config(false));
+        
+        trader.execute_signal(signal(), "default").await.unwrap();
+        let order = trader.get_pending_orders().pop().unwrap();
+        let quantity = order.quantity;
+        
+        // 40% fills first, the position holds exactly that and the rest keeps working
+        let first = Fill {
+            order_id: order.order_id.clone(),
+            quantity: quantity * 0.4,
+            price: 49_900.0,
+        };
+        trader.on_fill(&first).await.unwrap();
+        let position = trader.get_positions().pop().unwrap();
+        assert!(close(position.quantity, quantity * 0.4));
+        assert_eq!(position.entry_price, 49_900.0);
+        assert_eq!(trader.get_pending_orders().len(), 1);
+        
+        let rest = Fill {
+            order_id: order.order_id.clone(),
+            quantity: quantity * 0.6,
+            price: 50_000.0,
+        };
+        trader.on_fill(&rest).await.unwrap();
+        let position = trader.get_positions().pop().unwrap();
+        assert!(close(position.quantity, quantity));
+        assert!(close(position.entry_price, 49_960.0));
+        assert!(trader.get_pending_orders().is_empty());
+        
+        trader.update_positions("BTC/USDT", "binance", 50_960.0).await.unwrap();
+        let position = trader.get_positions().pop().unwrap();
+        assert!((position.unrealized_pnl - 1_000.0 * quantity).abs() < 1e-6);
+    }
+    
+    fn trade_event(order_id: &str, quantity: f64, price: f64) -> UnindexedAccountEvent {
+        UnindexedAccountEvent {
+            exchange: barter_instrument::exchange::ExchangeId::Mock,
+            kind: barter_execution::AccountEventKind::Trade(barter_execution::trade::Trade {
+                id: barter_execution::trade::TradeId::new(format!("{}-{}", order_id, quantity)),
+                order_id: barter_execution::order::id::OrderId::new(order_id),
+                instrument: "BTCUSDT".into(),
+                strategy: barter_execution::order::id::StrategyId::new("monitor-trader"),
+                time_exchange: chrono::Utc::now(),
+                side: barter_instrument::Side::Buy,
+                price: rust_decimal::Decimal::from_f64_retain(price).unwrap(),
+                quantity: rust_decimal::Decimal::from_f64_retain(quantity).unwrap(),
+                fees: Default::default(),
+            }),
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_account_stream_applies_fills_and_balances() {
+        let trader = Arc::new(trader(Arc::new(RestingVenue::default())));
+        trader.update_config(limit_config(false));
+        trader.execute_signal(signal(), "default").await.unwrap();
+        let order = trader.get_pending_orders().pop().unwrap();
+        
+        let balance = barter_execution::UnindexedAccountSnapshot {
+            exchange: barter_instrument::exchange::ExchangeId::Mock,
+            balances: vec![barter_execution::balance::AssetBalance::new(
+                "USDT".into(),
+                barter_execution::balance::Balance::new(
+                    rust_decimal::Decimal::new(12_000, 0),
+                    rust_decimal::Decimal::new(12_000, 0),
+                ),
+                chrono::Utc::now(),
+            )],
+            instruments: Vec::new(),
+        };
+        let events = futures::stream::iter(vec![
+            trade_event(&order.order_id, order.quantity, 49_900.0),
+            UnindexedAccountEvent {
+                exchange: barter_instrument::exchange::ExchangeId::Mock,
+                kind: barter_execution::AccountEventKind::Snapshot(balance),
+            },
+        ]);
+        let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+        // The task stops by itself once the stream ends
+        trader.follow_account(events, shutdown_rx).await.unwrap();
+        
+        let position = trader.get_positions().pop().unwrap();
+        assert!(close(position.quantity, order.quantity));
+        assert_eq!(position.entry_price, 49_900.0);
+        assert!(trader.get_pending_orders().is_empty());
+        assert_eq!(trader.get_portfolio_value(), 12_000.0);
+    }
+    
+    #[tokio::test]
+    async fn test_partial_fills_arrive_on_the_account_stream() {
+        let trader = trader(Arc::new(RestingVenue::default()));
+        trader.update_config(limit_config(false));
+        trader.execute_signal(signal(), "default").await.unwrap();
+        let order = trader.get_pending_orders().pop().unwrap();
+        
+        let first = trade_event(&order.order_id, order.quantity * 0.4, 49_900.0);
+        trader.on_account_event(&first).await.unwrap();
+        assert!(close(trader.get_positions()[0].quantity, order.quantity * 0.4));
+        assert_eq!(trader.get_pending_orders().len(), 1);
+        
+        let rest = trade_event(&order.order_id, order.quantity * 0.6, 50_000.0);
+        trader.on_account_event(&rest).await.unwrap();
+        let position = trader.get_positions().pop().unwrap();
+        assert!(close(position.quantity, order.quantity));
+        assert!(close(position.entry_price, 49_960.0));
+        assert!(trader.get_pending_orders().is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_limit_entry_rests_then_fills() {
+        let (trader, venue, clock) = delayed_trader(5);
+        trader.execute_signal(signal(), "default").await.unwrap();
+        let order = trader.get_pending_orders().pop().unwrap();
+        assert_eq!(order.status, OrderStatus::Submitted);
+        assert!(trader.get_positions().is_empty());
+        
+        trader.on_order_update(&order.order_id, OrderStatus::Open).await.unwrap();
+        assert_eq!(trader.get_pending_orders()[0].status, OrderStatus::Open);
+        clock.advance(chrono::Duration::seconds(3));
+        assert!(venue.poll().is_empty());
+        trader.check_pending_orders().await.unwrap();
+        assert_eq!(trader.get_pending_orders().len(), 1);
+        
+        clock.advance(chrono::Duration::seconds(2));
+        for fill in venue.poll() {
+            trader.on_fill(&fill).await.unwrap();
+        }
+        assert!(trader.get_pending_orders().is_empty());
+        let position = trader.get_positions().pop().unwrap();
+        assert!(close(position.quantity, order.quantity));
+        assert_eq!(position.entry_price, order.limit_price);
+        assert!(venue.cancels.lock().is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_working_limit_entries_count_against_max_open_positions() {
+        let (trader, venue, _) = delayed_trader(60);
+        let mut config = trader.get_config();
+        config.max_open_positions = Some(1);
+        trader.update_config(config);
+        trader.execute_signal(signal(), "default").await.unwrap();
+        assert_eq!(trader.get_pending_orders().len(), 1);
+        
+        let mut eth = signal();
+        eth.symbol = "ETH/USDT".to_string();
+        trader.execute_signal(eth, "default").await.unwrap();
+        assert_eq!(trader.get_journal().pop().unwrap().outcome, SignalOutcome::RejectedByRisk);
+        assert_eq!(venue.opened.lock().len(), 1);
+    }
+    
+    #[tokio::test]
+    async fn test_limit_entry_times_out_into_a_market_order() {
+        let (trader, venue, clock) = delayed_trader(60);
+        trader.execute_signal(signal(), "default").await.unwrap();
+        let order = trader.get_pending_orders().pop().unwrap();
+        
+        // Past the 10s timeout, the order is cancelled and the entry taken at market
+        clock.advance(chrono::Duration::seconds(10));
+        trader.check_pending_orders().await.unwrap();
+        assert_eq!(*venue.cancels.lock(), vec![order.order_id]);
+        assert!(matches!(venue.opened.lock().last().unwrap().order_type, OrderType::Market));
+        assert!(trader.get_pending_orders().is_empty());
+        let position = trader.get_positions().pop().unwrap();
+        assert!(close(position.quantity, order.quantity));
+    }
+    
+    #[tokio::test]
+    async fn test_limit_entry_filled_on_arrival_opens_the_position() {
+        let trader = trader(Arc::new(FillingVenue));
+        trader.update_config(limit_config(false));
+        trader.execute_signal(signal(), "default").await.unwrap();
+        
+        assert!(trader.get_pending_orders().is_empty());
+        assert_eq!(trader.get_positions().len(), 1);
+        let outcomes: Vec<SignalOutcome> =
+            trader.get_journal().into_iter().map(|entry| entry.outcome).collect();
+        assert!(outcomes.contains(&SignalOutcome::Executed));
+        assert!(!outcomes.contains(&SignalOutcome::OrderPending));
+    }
+    
+    #[tokio::test]
+    async fn test_uncancellable_limit_entry_is_given_up_with_an_alert() {
+        let (trader, venue, clock) = delayed_trader(60);
+        trader.execute_signal(signal(), "default").await.unwrap();
+        let order = trader.get_pending_orders().pop().unwrap();
+        let (alert_tx, mut alert_rx) = mpsc::unbounded_channel();
+        let trader = trader.with_alert_sender(alert_tx);
+        venue.refuse_cancels.store(true, std::sync::atomic::Ordering::SeqCst);
+        
+        clock.advance(chrono::Duration::seconds(10));
+        for _ in 1..MAX_CANCEL_ATTEMPTS {
+            trader.check_pending_orders().await.unwrap();
+            assert_eq!(trader.get_pending_orders().len(), 1);
+        }
+        trader.check_pending_orders().await.unwrap();
+        
+        // The market is free for new entries, no market order went out
+        assert!(trader.get_pending_orders().is_empty());
+        assert_eq!(venue.opened.lock().len(), 1);
+        let alert = alert_rx.try_recv().unwrap();
+        assert_eq!(alert.data["order_id"], order.order_id.as_str());
+    }
+    
+    #[tokio::test]
+    async fn test_partial_fill_can_cancel_the_remainder() {
+        let venue = Arc::new(RestingVenue::default());
+        let trader = trader(venue.clone());
+        trader.update_config(limit_config(true));
+        
+        trader.execute_signal(signal(), "default").await.unwrap();
+        let order = trader.get_pending_orders().pop().unwrap();
+        let fill = Fill {
+            order_id: order.order_id.clone(),
+            quantity: order.quantity * 0.4,
+            price: 49_900.0,
+        };
+        trader.on_fill(&fill).await.unwrap();
+        
+        assert_eq!(*venue.cancels.lock(), vec![order.order_id]);
+        assert!(trader.get_pending_orders().is_empty());
+        assert!(close(trader.get_positions()[0].quantity, order.quantity * 0.4));
+    }
+    
+    #[tokio::test]
+    async fn test_second_signal_does_not_replace_the_open_position() {
+        let trader = trader(Arc::new(FillingVenue));
+        trader.execute_signal(signal(), "default").await.unwrap();
+        let opened = trader.get_positions().pop().unwrap();
+        
+        trader.execute_signal(signal(), "default").await.unwrap();
+        let positions = trader.get_positions();
+        assert_eq!(positions.len(), 1);
+        assert_eq!(positions[0].id, opened.id);
+        assert_eq!(positions[0].entry_price, 50_000.0);
+        let last = trader.get_journal().pop().unwrap();
+        assert_eq!(last.outcome, SignalOutcome::RejectedByScaling);
+    }
+    
+    #[tokio::test]
+    async fn test_realized_pnl_is_taken_at_the_closing_fill_not_the_mark() {
+        let trader = trader(Arc::new(SlippedCloseVenue(50_500.0)));
+        trader.execute_signal(signal(), "default").await.unwrap();
+        let opened = trader.get_positions().pop().unwrap();
+        trader.update_positions("BTC/USDT", "binance", 51_000.0).await.unwrap();
+        assert!(close(trader.get_positions()[0].unrealized_pnl, 1_000.0 * opened.quantity));
+        
+        let key = InstrumentKey::new("binance", "BTC/USDT");
+        let closed = trader.close_position_manually(&key).await.unwrap().unwrap();
+        let pnl = 500.0 * opened.quantity;
+        assert!(close(closed.realized_pnl, pnl));
+        let stats = trader.get_stats();
+        assert!(close(stats.total_pnl, pnl));
+        assert!(close(stats.average_win, pnl));
+    }
+    
+    #[tokio::test]
+    async fn test_replace_keeps_the_position_when_the_close_does_not_execute() {
+        let trader = trader(Arc::new(SilentCloseVenue));
+        let mut config = config();
+        config.scaling.mode = monitor_core::ScalingMode::Replace;
+        trader.update_config(config);
+        trader.execute_signal(signal(), "default").await.unwrap();
+        let opened = trader.get_positions().pop().unwrap();
+        
+        trader.execute_signal(signal_at(51_000.0), "default").await.unwrap();
+        let positions = trader.get_positions();
+        assert_eq!(positions.len(), 1);
+        assert_eq!(positions[0].id, opened.id);
+        assert!(trader.get_closed_positions().is_empty());
+        assert_eq!(trader.get_journal().pop().unwrap().outcome, SignalOutcome::ExecutionFailed);
+        
+        // Nor does a manual close report one that did not happen
+        let key = InstrumentKey::new("binance", "BTC/USDT");
+        assert!(trader.close_position_manually(&key).await.unwrap().is_none());
+        assert_eq!(trader.get_positions().len(), 1);
+    }
+    
+    #[tokio::test]
+    async fn test_pyramid_adds_average_the_entry() {
+        let trader = trader(Arc::new(FillingVenue));
+        let mut config = config();
+        config.scaling.mode = monitor_core::ScalingMode::Pyramid;
+        trader.update_config(config);
+        
+        let mut fills = Vec::new();
+        for price in [50_000.0, 51_000.0, 52_000.0] {
+            trader.update_positions("BTC/USDT", "binance", price).await.unwrap();
+            trader.execute_signal(signal_at(price), "default").await.unwrap();
+            let position = trader.get_positions().pop().unwrap();
+            let filled: f64 = fills.iter().map(|(quantity, _)| quantity).sum();
+            fills.push((position.quantity - filled, price));
+        }
+        
+        // Adds are half a regular entry
+        assert!(close(fills[1].0, fills[0].0 * 0.5 * 50_000.0 / 51_000.0));
+        let quantity: f64 = fills.iter().map(|(quantity, _)| quantity).sum();
+        let notional: f64 = fills.iter().map(|(quantity, price)| quantity * price).sum();
+        let position = trader.get_positions().pop().unwrap();
+        assert_eq!(position.adds, 2);
+        assert!(close(position.quantity, quantity));
+        assert!(close(position.entry_price, notional / quantity));
+        assert!(position.entry_price > 50_000.0 && position.entry_price < 51_000.0);
+        
+        // A third add is past `max_adds`
+        trader.update_positions("BTC/USDT", "binance", 53_000.0).await.unwrap();
+        trader.execute_signal(signal_at(53_000.0), "default").await.unwrap();
+        assert!(close(trader.get_positions()[0].quantity, quantity));
+    }
+    
+    #[tokio::test]
+    async fn test_halt_blocks_entries_but_not_stop_losses() {
+        let trader = trader(Arc::new(FillingVenue));
+        trader.execute_signal(signal(), "default").await.unwrap();
+        assert_eq!(trader.get_positions().len(), 1);
+        
+        trader.circuit_breaker().halt(Some("test".to_string()));
+        trader.execute_signal(signal_at(50_100.0), "default").await.unwrap();
+        assert_eq!(trader.get_journal().pop().unwrap().outcome, SignalOutcome::Halted);
+        
+        // 3% below the entry, the stop-loss still closes the position
+        trader.update_positions("BTC/USDT", "binance", 48_400.0).await.unwrap();
+        assert!(trader.get_positions().is_empty());
+        assert_eq!(trader.get_closed_positions().len(), 1);
+        assert!(trader.get_breaker_status().halt.is_some());
+        
+        assert!(trader.circuit_breaker().resume());
+        trader.execute_signal(signal_at(48_400.0), "default").await.unwrap();
+        assert_eq!(trader.get_positions().len(), 1);
+    }
+    
+    #[tokio::test]
+    async fn test_venue_pause_blocks_entries_until_resumed_or_lapsed() {
+        let trader = trader(Arc::new(FillingVenue));
+        let now = chrono::Utc::now();
+        trader.pause_venue("Binance", now + chrono::Duration::hours(1), "system maintenance");
+        assert!(trader.venue_pause("okx", now).is_none());
+        
+        trader.execute_signal(signal(), "default").await.unwrap();
+        assert!(trader.get_positions().is_empty());
+        let paused = trader.get_journal().pop().unwrap();
+        assert_eq!(paused.outcome, SignalOutcome::VenuePaused);
+        assert_eq!(paused.details["reason"], "system maintenance");
+        
+        trader.resume_venue("binance");
+        trader.execute_signal(signal(), "default").await.unwrap();
+        assert_eq!(trader.get_positions().len(), 1);
+        
+        // A pause nobody renewed stops applying on its own
+        trader.pause_venue("binance", now - chrono::Duration::seconds(1), "system maintenance");
+        assert!(trader.venue_pause("binance", now).is_none());
+    }
+    
+    fn cooldown_config(cooldown: serde_json::Value) -> TradingConfig {
+        TradingConfig {
+            cooldown: serde_json::from_value(cooldown).unwrap(),
+            ..config()
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_cooldown_suppresses_back_to_back_signals() {
+        let trader = trader(Arc::new(FillingVenue));
+        trader.update_config(cooldown_config(serde_json::json!({ "per_symbol_secs": 60 })));
+        trader.execute_signal(signal(), "default").await.unwrap();
+        let key = InstrumentKey::new("binance", "BTC/USDT");
+        trader.close_position(&key, None, CloseReason::Manual).await.unwrap();
+        
+        // Flat again, but inside the window the next signal is journaled and dropped
+        trader.execute_signal(signal(), "default").await.unwrap();
+        assert!(trader.get_positions().is_empty());
+        let suppressed = trader.get_journal().pop().unwrap();
+        assert_eq!(suppressed.outcome, SignalOutcome::CooldownActive);
+        assert_eq!(suppressed.details["kind"], "symbol");
+        
+        // Once the window has passed it executes
+        trader.update_config(cooldown_config(serde_json::json!({ "per_symbol_secs": 0 })));
+        trader.execute_signal(signal(), "default").await.unwrap();
+        assert_eq!(trader.get_positions().len(), 1);
+    }
+    
+    #[tokio::test]
+    async fn test_stop_loss_blocks_reentry() {
+        let trader = trader(Arc::new(FillingVenue));
+        trader.update_config(cooldown_config(serde_json::json!({
+            "stop_loss_reentry_minutes": 30,
+        })));
+        trader.execute_signal(signal(), "default").await.unwrap();
+        trader.update_positions("BTC/USDT", "binance", 48_400.0).await.unwrap();
+        assert!(trader.get_positions().is_empty());
+        
+        trader.execute_signal(signal_at(48_400.0), "default").await.unwrap();
+        assert!(trader.get_positions().is_empty());
+        let suppressed = trader.get_journal().pop().unwrap();
+        assert_eq!(suppressed.outcome, SignalOutcome::CooldownActive);
+        assert_eq!(suppressed.details["kind"], "stop_loss_reentry");
+    }
+    
+    #[tokio::test]
+    async fn test_signals_during_warmup_are_journaled_and_not_traded() {
+        let trader = trader(Arc::new(FillingVenue));
+        let mut anomaly = AnomalyDetection {
+            timestamp: chrono::Utc::now(),
+            symbol: "BTC/USDT".to_string(),
+            exchange: "binance".to_string(),
+            anomaly_type: AnomalyType::VolumeSpike,
+            severity: AnomalySeverity::High,
+            metrics: AnomalyMetrics { current_value: 50_000.0, ..Default::default() },
+            warmup: true,
+            ..Default::default()
+        };
+        trader.process_anomaly(&anomaly).await.unwrap();
+        assert!(trader.get_positions().is_empty());
+        let held = trader.get_journal().pop().unwrap();
+        assert_eq!(held.outcome, SignalOutcome::WarmingUp);
+        assert_eq!(held.signal.anomaly_id, Some(anomaly.id));
+        
+        // The same detection once warmed up is traded
+        anomaly.warmup = false;
+        trader.process_anomaly(&anomaly).await.unwrap();
+        assert_eq!(trader.get_positions().len(), 1);
+    }
+    
+    #[tokio::test]
+    async fn test_cooldowns_are_written_by_the_maintenance_task_and_outlive_a_restart() {
+        let path = std::env::temp_dir().join(format!("cooldowns-{}.json", uuid::Uuid::new_v4()));
+        let config = cooldown_config(serde_json::json!({
+            "per_symbol_secs": 60,
+            "state_path": path.display().to_string(),
+        }));
+        let trader = |config: &TradingConfig| {
+            AutoTrader::new(
+                config.clone(),
+                Box::new(AnomalyBasedStrategy::new(config.clone())),
+                Box::new(SimpleRiskManager::new(config.clone())),
+                Arc::new(FillingVenue),
+                10_000.0,
+            )
+        };
+        
+        let before = Arc::new(trader(&config));
+        before.execute_signal(signal(), "default").await.unwrap();
+        // Executing the signal does not touch the file, the maintenance task writes it
+        assert!(!path.exists());
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let handle = before.start(shutdown_rx);
+        shutdown_tx.send(true).unwrap();
+        handle.await.unwrap();
+        assert!(path.exists());
+        
+        let after = trader(&config);
+        after.execute_signal(signal(), "default").await.unwrap();
+        assert!(after.get_positions().is_empty());
+        assert_eq!(after.get_journal().pop().unwrap().outcome, SignalOutcome::CooldownActive);
+        
+        let _ = std::fs::remove_file(path);
+    }
+    
+    // Positions by id plus the trades recorded against them, shared between trader instances
+    // the way a database outlives a restart
+    #[derive(Default)]
+    struct MemoryStore {
+        positions: Mutex<std::collections::HashMap<uuid::Uuid, Position>>,
+        trades: Mutex<Vec<ExecutedTrade>>,
+        updates: Mutex<usize>,
+        states: Mutex<std::collections::HashMap<String, serde_json::Value>>,
+    }
+    
+    #[async_trait]
+    impl PositionStore for MemoryStore {
+        async fn open(&self, position: &Position) -> Result<()> {
+            self.positions.lock().entry(position.id).or_insert_with(|| position.clone());
+            Ok(())
+        }
+        
+        async fn update(&self, position: &Position) -> Result<()> {
+            *self.updates.lock() += 1;
+            self.positions.lock().insert(position.id, position.clone());
+            Ok(())
+        }
+        
+        async fn close(&self, position: &Position) -> Result<()> {
+            self.positions.lock().insert(position.id, position.clone());
+            Ok(())
+        }
+        
+        async fn record_trade(&self, _position: &Position, trade: &ExecutedTrade) -> Result<()> {
+            self.trades.lock().push(trade.clone());
+            Ok(())
+        }
+        
+        async fn load_open(&self) -> Result<Vec<Position>> {
+            Ok(self
+                .positions
+                .lock()
+                .values()
+                .filter(|p| p.closed_at.is_none())
+                .cloned()
+                .collect())
+        }
+        
+        async fn save_state(&self, name: &str, state: &serde_json::Value) -> Result<()> {
+            self.states.lock().insert(name.to_string(), state.clone());
+            Ok(())
+        }
+        
+        async fn load_state(&self, name: &str) -> Result<Option<serde_json::Value>> {
+            Ok(self.states.lock().get(name).cloned())
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_halt_cancels_entries_and_outlives_a_restart() {
+        let store = Arc::new(MemoryStore::default());
+        let (trader, venue, _) = delayed_trader(60);
+        let trader = trader.with_position_store(store.clone());
+        trader.execute_signal(signal(), "default").await.unwrap();
+        let order = trader.get_pending_orders().pop().unwrap();
+        let config = monitor_core::DcaConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        let plan = DcaPlan::new(
+            signal_at(49_000.0),
+            "default",
+            PositionSide::Long,
+            0.4,
+            4,
+            &config,
+            chrono::Utc::now(),
+        );
+        trader.dca_plans().start(plan);
+        
+        trader.halt_trading(Some("test".to_string())).await;
+        assert_eq!(*venue.cancels.lock(), vec![order.order_id]);
+        assert!(trader.get_pending_orders().is_empty());
+        assert!(trader.dca_plans().is_empty());
+        drop(trader);
+        
+        let after = delayed_trader(60).0.with_position_store(store.clone());
+        after.restore_from_db().await.unwrap();
+        assert!(after.get_breaker_status().halt.is_some());
+        after.execute_signal(signal(), "default").await.unwrap();
+        assert_eq!(after.get_journal().pop().unwrap().outcome, SignalOutcome::Halted);
+        
+        // Resuming is saved as well
+        assert!(after.resume_trading().await);
+        let again = delayed_trader(60).0.with_position_store(store);
+        again.restore_from_db().await.unwrap();
+        assert!(again.get_breaker_status().halt.is_none());
+    }
+    
+    #[tokio::test]
+    async fn test_restored_positions_keep_their_stop_loss() {
+        let store = Arc::new(MemoryStore::default());
+        let before = trader(Arc::new(FillingVenue)).with_position_store(store.clone());
+        before.execute_signal(signal(), "default").await.unwrap();
+        let opened = before.get_positions().pop().unwrap();
+        
+        // Price updates inside the interval are not written
+        before.update_positions("BTC/USDT", "binance", 50_500.0).await.unwrap();
+        before.update_positions("BTC/USDT", "binance", 50_600.0).await.unwrap();
+        assert_eq!(*store.updates.lock(), 0);
+        assert_eq!(store.positions.lock()[&opened.id].current_price, 50_000.0);
+        drop(before);
+        
+        let after = trader(Arc::new(FillingVenue)).with_position_store(store.clone());
+        assert_eq!(after.restore_from_db().await.unwrap(), 1);
+        let restored = after.get_positions().pop().unwrap();
+        assert_eq!(restored.id, opened.id);
+        assert_eq!(restored.stop_loss, opened.stop_loss);
+        
+        after.update_positions("BTC/USDT", "binance", 48_400.0).await.unwrap();
+        assert!(after.get_positions().is_empty());
+        let closed = store.positions.lock()[&opened.id].clone();
+        assert!(closed.closed_at.is_some());
+        assert!(close(closed.realized_pnl, (48_400.0 - 50_000.0) * opened.quantity));
+        
+        let kinds: Vec<TradeKind> = store.trades.lock().iter().map(|t| t.kind).collect();
+        assert_eq!(kinds, vec![TradeKind::Entry, TradeKind::Close]);
+        assert!(store.load_open().await.unwrap().is_empty());
+        // Nothing left to restore
+        let again = trader(Arc::new(FillingVenue)).with_position_store(store);
+        assert_eq!(again.restore_from_db().await.unwrap(), 0);
+    }
+    
+    #[tokio::test]
+    async fn test_restart_restores_the_ledger_and_checks_positions_with_the_exchange() {
+        let store = Arc::new(MemoryStore::default());
+        let before = trader(Arc::new(FillingVenue)).with_position_store(store.clone());
+        before.execute_signal(signal(), "default").await.unwrap();
+        // Stopped out, then in again
+        before.update_positions("BTC/USDT", "binance", 48_400.0).await.unwrap();
+        before.execute_signal(signal(), "default").await.unwrap();
+        let opened = before.get_positions().pop().unwrap();
+        let stats = before.get_stats();
+        let breaker = before.get_breaker_status();
+        let value = before.get_portfolio_value();
+        assert!(value < 10_000.0);
+        drop(before);
+        
+        let account = Arc::new(Account::default());
+        account
+            .held
+            .lock()
+            .push(held("BTC/USDT", PositionSide::Long, opened.quantity, opened.entry_price));
+        let after = trader(Arc::new(FillingVenue))
+            .with_position_store(store.clone())
+            .with_account_source(account.clone());
+        assert_eq!(after.restore_from_db().await.unwrap(), 1);
+        assert_eq!(after.get_positions().len(), 1);
+        assert_eq!(after.get_stats().total_trades, stats.total_trades);
+        assert_eq!(after.get_stats().losing_trades, 1);
+        assert!(close(after.get_portfolio_value(), value));
+        assert_eq!(after.get_breaker_status(), breaker);
+        assert_eq!(after.get_equity_curve().len(), 1);
+        drop(after);
+        
+        // The exchange closed the position while the monitor was down
+        account.held.lock().clear();
+        let again = trader(Arc::new(FillingVenue))
+            .with_position_store(store)
+            .with_account_source(account);
+        assert_eq!(again.restore_from_db().await.unwrap(), 1);
+        assert!(again.get_positions().is_empty());
+        assert_eq!(again.get_stats().total_trades, 2);
+    }
+    
+    // Every open position counts as stalled at the first check
+    fn stale_trader(
+        venue: Arc<dyn OrderClient>,
+        price: f64,
+        fallback: StalePositionFallback,
+    ) -> AutoTrader {
+        let trader = trader(venue).with_price_source(Arc::new(FixedPrice(price)));
+        trader.update_config(TradingConfig {
+            stale_position_threshold_secs: 0,
+            stale_position_fallback: fallback,
+            ..config()
+        });
+        trader
+    }
+    
+    #[tokio::test]
+    async fn test_stalled_position_is_repriced_into_its_stop_loss() {
+        let trader = stale_trader(Arc::new(FillingVenue), 48_400.0, StalePositionFallback::Rest);
+        trader.execute_signal(signal(), "default").await.unwrap();
+        let opened = trader.get_positions().pop().unwrap();
+        
+        trader.check_stale_positions().await.unwrap();
+        assert!(trader.get_positions().is_empty());
+        let closed = trader.get_closed_positions().pop().unwrap();
+        assert_eq!(closed.id, opened.id);
+        assert!(close(closed.realized_pnl, (48_400.0 - 50_000.0) * opened.quantity));
+    }
+    
+    #[tokio::test]
+    async fn test_stalled_positions_close_under_the_close_policy() {
+        let venue = Arc::new(RejectingCloseVenue { symbol: "BTC/USDT" });
+        let trader = stale_trader(venue, 50_000.0, StalePositionFallback::Close);
+        let mut eth = signal_at(3_000.0);
+        eth.symbol = "ETH/USDT".to_string();
+        trader.execute_signal(signal(), "default").await.unwrap();
+        trader.execute_signal(eth, "default").await.unwrap();
+        assert_eq!(trader.get_positions().len(), 2);
+        
+        // The rejected close of BTC/USDT doesn't keep ETH/USDT open
+        trader.check_stale_positions().await.unwrap();
+        let open = trader.get_positions();
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].symbol, "BTC/USDT");
+        assert_eq!(trader.get_closed_positions()[0].symbol, "ETH/USDT");
+    }
+    
+    fn holding_trader(clock: Arc<ManualClock>) -> AutoTrader {
+        let trader = trader(Arc::new(FillingVenue)).with_clock(clock);
+        trader.update_config(TradingConfig {
+            max_holding_minutes: Some(1),
+            ..config()
+        });
+        trader
+    }
+    
+    #[tokio::test]
+    async fn test_positions_close_at_the_holding_horizon() {
+        let clock = Arc::new(ManualClock::new(chrono::Utc::now()));
+        let trader = holding_trader(clock.clone());
+        trader.execute_signal(signal(), "default").await.unwrap();
+        
+        clock.advance(chrono::Duration::seconds(59));
+        assert_eq!(trader.close_expired_positions().await.unwrap(), 0);
+        assert_eq!(trader.get_positions().len(), 1);
+        
+        clock.advance(chrono::Duration::seconds(1));
+        assert_eq!(trader.close_expired_positions().await.unwrap(), 1);
+        assert!(trader.get_positions().is_empty());
+        assert_eq!(trader.get_closed_positions().len(), 1);
+        assert_eq!(trader.close_expired_positions().await.unwrap(), 0);
+    }
+    
+    #[tokio::test]
+    async fn test_sweep_and_stop_loss_close_a_position_once() {
+        let clock = Arc::new(ManualClock::new(chrono::Utc::now()));
+        let trader = holding_trader(clock.clone());
+        trader.execute_signal(signal(), "default").await.unwrap();
+        clock.advance(chrono::Duration::minutes(5));
+        
+        let (swept, priced) = tokio::join!(
+            trader.close_expired_positions(),
+            trader.update_positions("BTC/USDT", "binance", 48_400.0),
+        );
+        priced.unwrap();
+        assert!(swept.unwrap() <= 1);
+        assert_eq!(trader.get_closed_positions().len(), 1);
+        assert_eq!(trader.get_stats().total_trades, 1);
+    }
+    
+    #[tokio::test]
+    async fn test_claim_skips_a_position_reopened_since() {
+        let trader = trader(Arc::new(FillingVenue));
+        trader.execute_signal(signal(), "default").await.unwrap();
+        let key = InstrumentKey::new("binance", "BTC/USDT");
+        
+        // The id a sweep saw before the position was replaced no longer matches
+        let stale_id = uuid::Uuid::new_v4();
+        let closed = trader
+            .close_position(&key, Some(stale_id), CloseReason::MaxHoldingPeriod)
+            .await
+            .unwrap();
+        assert!(!closed);
+        assert_eq!(trader.get_positions().len(), 1);
+    }
+    
+    #[tokio::test]
+    async fn test_sweep_task_stops_on_shutdown() {
+        let clock = Arc::new(ManualClock::new(chrono::Utc::now()));
+        let trader = Arc::new(holding_trader(clock.clone()));
+        trader.execute_signal(signal(), "default").await.unwrap();
+        clock.advance(chrono::Duration::minutes(2));
+        
+        // The first sweep runs as soon as the task starts
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let handle = trader.start(shutdown_rx);
+        for _ in 0..100 {
+            if trader.get_positions().is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert!(trader.get_positions().is_empty());
+        
+        shutdown_tx.send(true).unwrap();
+        tokio::time::timeout(std::time::Duration::from_secs(1), handle)
+            .await
+            .expect("sweep task did not stop")
+            .unwrap();
+    }
+    
+    #[tokio::test]
+    async fn test_symbol_overrides_apply_to_its_positions_only() {
+        let clock = Arc::new(ManualClock::new(chrono::Utc::now()));
+        let trader = trader(Arc::new(FillingVenue)).with_clock(clock.clone());
+        let mut layered = config();
+        layered.overrides.symbols.insert(
+            "binance:BTC/USDT".to_string(),
+            TradingOverride {
+                stop_loss_percentage: Some(5.0),
+                max_holding_minutes: Some(1),
+                ..Default::default()
+            },
+        );
+        trader.update_config(layered);
+        
+        trader.execute_signal(signal(), "default").await.unwrap();
+        let mut eth = signal_at(3_000.0);
+        eth.symbol = "ETH/USDT".to_string();
+        trader.execute_signal(eth, "default").await.unwrap();
+        
+        let stops: HashMap<String, Option<f64>> = trader
+            .get_positions()
+            .into_iter()
+            .map(|p| (p.symbol, p.stop_loss))
+            .collect();
+        assert_eq!(stops["BTC/USDT"], Some(47_500.0));
+        assert_eq!(stops["ETH/USDT"], Some(2_910.0));
+        
+        // Only BTC/USDT has a holding horizon
+        clock.advance(chrono::Duration::minutes(5));
+        assert_eq!(trader.close_expired_positions().await.unwrap(), 1);
+        let open = trader.get_positions();
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].symbol, "ETH/USDT");
+        assert_eq!(trader.resolve_config("binance", "BTC/USDT").stop_loss_percentage, 5.0);
+    }
+    
+    #[tokio::test]
+    async fn test_round_trip_pays_taker_fees_both_ways() {
+        let store = Arc::new(MemoryStore::default());
+        let trader = trader(Arc::new(FillingVenue)).with_position_store(store.clone());
+        trader.update_config(TradingConfig {
+            costs: serde_json::from_value(serde_json::json!({
+                "fees": { "binance": { "maker_bps": 2.0, "taker_bps": 10.0 } },
+            }))
+            .unwrap(),
+            ..config()
+        });
+        
+        trader.execute_signal(signal(), "default").await.unwrap();
+        let opened = trader.get_positions().pop().unwrap();
+        let quantity = opened.quantity;
+        assert!(close(opened.fees, 50.0 * quantity));
+        
+        // Taken at the 6% target, a market close paying the taker rate again
+        trader.update_positions("BTC/USDT", "binance", 53_000.0).await.unwrap();
+        let closed = trader.get_closed_positions().pop().unwrap();
+        assert!(close(closed.fees, (50.0 + 53.0) * quantity));
+        assert!(close(closed.realized_pnl, (3_000.0 - 103.0) * quantity));
+        
+        let stats = trader.get_stats();
+        assert!(close(stats.gross_pnl, 3_000.0 * quantity));
+        assert!(close(stats.total_fees, 103.0 * quantity));
+        assert!(close(stats.total_pnl, 2_897.0 * quantity));
+        
+        let fees: Vec<f64> = store.trades.lock().iter().map(|t| t.fee).collect();
+        assert_eq!(fees.len(), 2);
+        assert!(close(fees[0], 50.0 * quantity) && close(fees[1], 53.0 * quantity));
+    }
+    
+    #[tokio::test]
+    async fn test_orders_below_the_exchange_minimum_are_skipped() {
+        let venue = Arc::new(ExitVenue::default());
+        let trader = trader(venue.clone());
+        let mut config = config();
+        config.sizing.min_notional.insert("binance".to_string(), 150.0);
+        trader.update_config(config);
+        
+        // Capped at 100 of notional, under what binance accepts
+        let mut weak = signal();
+        weak.strength = SignalStrength::Weak;
+        trader.execute_signal(weak.clone(), "default").await.unwrap();
+        let entry = trader.get_journal().pop().unwrap();
+        assert_eq!(entry.outcome, SignalOutcome::BelowMinNotional);
+        assert_eq!(entry.details["min_notional"], 150.0);
+        assert!(venue.opened.lock().is_empty());
+        assert!(trader.get_positions().is_empty());
+        
+        // No minimum configured for okx
+        weak.exchange = "okx".to_string();
+        trader.execute_signal(weak, "default").await.unwrap();
+        assert_eq!(venue.opened.lock().len(), 1);
+        assert_eq!(trader.get_positions().len(), 1);
+    }
+    
+    #[tokio::test]
+    async fn test_sell_on_spot_only_closes_a_long() {
+        let venue = Arc::new(ExitVenue::default());
+        let trader = trader(venue.clone());
+        trader.update_config(TradingConfig {
+            instrument_kind: InstrumentKind::Spot,
+            ..config()
+        });
+        let mut sell = signal();
+        sell.signal_type = SignalType::Sell;
+        
+        // No inventory, no phantom short
+        trader.execute_signal(sell.clone(), "default").await.unwrap();
+        assert!(venue.opened.lock().is_empty());
+        assert!(trader.get_positions().is_empty());
+        assert_eq!(trader.get_journal().pop().unwrap().outcome, SignalOutcome::ShortNotAllowed);
+        
+        trader.execute_signal(signal(), "default").await.unwrap();
+        let long = trader.get_positions().pop().unwrap();
+        trader.execute_signal(sell, "default").await.unwrap();
+        assert!(trader.get_positions().is_empty());
+        assert_eq!(trader.get_closed_positions().pop().unwrap().id, long.id);
+        {
+            let opened = venue.opened.lock();
+            assert_eq!(opened.len(), 2);
+            assert!(matches!(opened[1].kind, OrderKind::Sell));
+            assert!(close(opened[1].quantity, long.quantity));
+        }
+        assert_eq!(trader.get_journal().pop().unwrap().outcome, SignalOutcome::ClosedLong);
+        
+        // Perpetuals still go short
+        trader.update_config(config());
+        let mut sell = signal();
+        sell.signal_type = SignalType::Sell;
+        trader.execute_signal(sell, "default").await.unwrap();
+        assert_eq!(trader.get_positions().pop().unwrap().side, PositionSide::Short);
+    }
+    
+    #[tokio::test]
+    async fn test_sell_on_spot_cancels_working_entries() {
+        let (trader, venue, _) = delayed_trader(60);
+        let mut config = trader.get_config();
+        config.instrument_kind = InstrumentKind::Spot;
+        trader.update_config(config);
+        trader.execute_signal(signal(), "default").await.unwrap();
+        let order = trader.get_pending_orders().pop().unwrap();
+        
+        let mut sell = signal();
+        sell.signal_type = SignalType::Sell;
+        trader.execute_signal(sell, "default").await.unwrap();
+        assert_eq!(*venue.cancels.lock(), vec![order.order_id]);
+        assert!(trader.get_pending_orders().is_empty());
+        assert!(trader.get_positions().is_empty());
+        assert_eq!(trader.get_journal().pop().unwrap().outcome, SignalOutcome::ShortNotAllowed);
+    }
+    
+    fn dca_trader(venue: Arc<ExitVenue>, clock: Arc<ManualClock>) -> AutoTrader {
+        let trader = trader(venue).with_clock(clock);
+        let mut config = config();
+        config.dca = DcaConfig {
+            enabled: true,
+            clips: 4,
+            interval_secs: 60,
+            abort_threshold_pct: Some(1.0),
+        };
+        trader.update_config(config);
+        trader
+    }
+    
+    #[tokio::test]
+    async fn test_dca_clips_add_up_to_the_signal_size() {
+        let venue = Arc::new(ExitVenue::default());
+        let clock = Arc::new(ManualClock::new(chrono::Utc::now()));
+        let trader = dca_trader(venue.clone(), clock.clone());
+        trader.execute_signal(signal(), "default").await.unwrap();
+        assert_eq!(venue.opened.lock().len(), 1);
+        assert_eq!(trader.dca_plans().list()[0].clips_sent, 1);
+        // The next clip waits for the interval
+        assert_eq!(trader.execute_due_clips().await.unwrap(), 0);
+        
+        for _ in 0..3 {
+            clock.advance(chrono::Duration::seconds(60));
+            assert_eq!(trader.execute_due_clips().await.unwrap(), 1);
+        }
+        assert!(trader.dca_plans().is_empty());
+        
+        let opened = venue.opened.lock();
+        assert_eq!(opened.len(), 4);
+        let ids: HashSet<_> = opened.iter().map(|r| r.client_order_id.clone()).collect();
+        assert_eq!(ids.len(), 4);
+        // Same size as a single order, one position
+        let position = trader.get_positions().pop().unwrap();
+        assert!(close(position.quantity, 100.0 / 50_000.0));
+        assert_eq!(position.adds, 0);
+        assert_eq!(trader.get_positions().len(), 1);
+    }
+    
+    #[tokio::test]
+    async fn test_dca_aborts_when_the_price_moves_against_it() {
+        let venue = Arc::new(ExitVenue::default());
+        let clock = Arc::new(ManualClock::new(chrono::Utc::now()));
+        let trader = dca_trader(venue.clone(), clock.clone());
+        trader.execute_signal(signal(), "default").await.unwrap();
+        
+        // Still inside the threshold
+        trader.update_positions("BTC/USDT", "binance", 49_700.0).await.unwrap();
+        clock.advance(chrono::Duration::seconds(60));
+        assert_eq!(trader.execute_due_clips().await.unwrap(), 1);
+        
+        trader.update_positions("BTC/USDT", "binance", 49_400.0).await.unwrap();
+        clock.advance(chrono::Duration::seconds(60));
+        assert_eq!(trader.execute_due_clips().await.unwrap(), 0);
+        assert!(trader.dca_plans().is_empty());
+        assert_eq!(venue.opened.lock().len(), 2);
+        
+        let entry = trader.get_journal().pop().unwrap();
+        assert_eq!(entry.outcome, SignalOutcome::DcaAborted);
+        assert_eq!(entry.details["clips_sent"], 2);
+        // The clips already sent stay in the position
+        let position = trader.get_positions().pop().unwrap();
+        assert!(close(position.quantity, 100.0 / 50_000.0 / 2.0));
+    }
+    
+    #[tokio::test]
+    async fn test_dca_plan_stops_on_a_venue_pause_or_a_halt() {
+        let venue = Arc::new(ExitVenue::default());
+        let clock = Arc::new(ManualClock::new(chrono::Utc::now()));
+        let trader = dca_trader(venue.clone(), clock.clone());
+        trader.execute_signal(signal(), "default").await.unwrap();
+        let until = chrono::Utc::now() + chrono::Duration::hours(1);
+        trader.pause_venue("binance", until, "maintenance");
+        
+        clock.advance(chrono::Duration::seconds(60));
+        assert_eq!(trader.execute_due_clips().await.unwrap(), 0);
+        assert!(trader.dca_plans().is_empty());
+        assert_eq!(venue.opened.lock().len(), 1);
+        let entry = trader.get_journal().pop().unwrap();
+        assert_eq!(entry.outcome, SignalOutcome::VenuePaused);
+        assert_eq!(entry.details["clips_sent"], 1);
+        
+        let venue = Arc::new(ExitVenue::default());
+        let trader = dca_trader(venue.clone(), clock.clone());
+        trader.execute_signal(signal(), "default").await.unwrap();
+        trader.circuit_breaker().halt(None);
+        clock.advance(chrono::Duration::seconds(60));
+        assert_eq!(trader.execute_due_clips().await.unwrap(), 0);
+        assert!(trader.dca_plans().is_empty());
+        assert_eq!(trader.get_journal().pop().unwrap().outcome, SignalOutcome::Halted);
+    }
+    
+    #[tokio::test]
+    async fn test_dca_exits_follow_the_plan_and_plans_survive_a_restart() {
+        let venue = Arc::new(ExitVenue::default());
+        let clock = Arc::new(ManualClock::new(chrono::Utc::now()));
+        let store = Arc::new(MemoryStore::default());
+        let trader = dca_trader(venue.clone(), clock.clone()).with_position_store(store.clone());
+        let mut config = trader.get_config();
+        config.exchange_exit_orders = true;
+        trader.update_config(config.clone());
+        trader.execute_signal(signal(), "default").await.unwrap();
+        // The first clip and the exits protecting it
+        assert_eq!(venue.opened.lock().len(), 3);
+        
+        // Later clips leave the exits alone
+        clock.advance(chrono::Duration::seconds(60));
+        assert_eq!(trader.execute_due_clips().await.unwrap(), 1);
+        assert_eq!(venue.opened.lock().len(), 4);
+        assert!(venue.cancels.lock().is_empty());
+        drop(trader);
+        
+        let after = dca_trader(venue.clone(), clock.clone()).with_position_store(store);
+        after.update_config(config);
+        after.restore_from_db().await.unwrap();
+        let plan = after.dca_plans().list().pop().unwrap();
+        assert_eq!(plan.clips_sent, 2);
+        
+        // Once the plan ends the exits are replaced at the size the clips reached
+        after.dca_plans().cancel(plan.id);
+        assert_eq!(after.execute_due_clips().await.unwrap(), 0);
+        assert_eq!(venue.cancels.lock().len(), 2);
+        let position = after.get_positions().pop().unwrap();
+        let opened = venue.opened.lock();
+        assert_eq!(opened.len(), 6);
+        assert!(opened[4..].iter().all(|exit| close(exit.quantity, position.quantity)));
+        assert!(close(position.quantity, 100.0 / 50_000.0 / 2.0));
+    }
+    
+    async fn exit_trader(venue: Arc<ExitVenue>) -> (AutoTrader, Position) {
+        let trader = trader(venue);
+        trader.update_config(TradingConfig {
+            exchange_exit_orders: true,
+            ..config()
+        });
+        trader.execute_signal(signal(), "default").await.unwrap();
+        let position = trader.get_positions().pop().unwrap();
+        (trader, position)
+    }
+    
+    #[tokio::test]
+    async fn test_exit_fill_cancels_its_sibling() {
+        let venue = Arc::new(ExitVenue::default());
+        let (trader, opened) = exit_trader(venue.clone()).await;
+        let stop_id = opened.stop_order_id.clone().unwrap();
+        let target_id = opened.take_profit_order_id.clone().unwrap();
+        {
+            let opened_orders = venue.opened.lock();
+            let exits: Vec<(&OrderType, Option<f64>, bool)> = opened_orders[1..]
+                .iter()
+                .map(|r| (&r.order_type, r.price, r.reduce_only))
+                .collect();
+            assert!(matches!(exits[0], (OrderType::StopMarket, Some(p), true) if p == 48_500.0));
+            assert!(matches!(exits[1], (OrderType::Limit, Some(p), true) if p == 53_000.0));
+        }
+        
+        // Through the stop, which is left to the exchange
+        trader.update_positions("BTC/USDT", "binance", 48_400.0).await.unwrap();
+        assert_eq!(trader.get_positions().len(), 1);
+        
+        let half = opened.quantity / 2.0;
+        let fill = |price| Fill {
+            order_id: stop_id.clone(),
+            quantity: half,
+            price,
+        };
+        trader.on_fill(&fill(48_450.0)).await.unwrap();
+        assert_eq!(trader.get_positions().len(), 1);
+        assert!(venue.cancels.lock().is_empty());
+        trader.on_fill(&fill(48_350.0)).await.unwrap();
+        
+        assert!(trader.get_positions().is_empty());
+        assert_eq!(*venue.cancels.lock(), vec![target_id]);
+        let closed = trader.get_closed_positions().pop().unwrap();
+        assert!(close(closed.realized_pnl, (48_400.0 - 50_000.0) * opened.quantity));
+        assert_eq!(closed.take_profit_order_id, None);
+        assert_eq!(trader.get_stats().total_trades, 1);
+        // No market close was sent
+        assert_eq!(venue.opened.lock().len(), 3);
+    }
+    
+    #[tokio::test]
+    async fn test_both_exits_filling_closes_once_and_alerts() {
+        let venue = Arc::new(ExitVenue::default());
+        let (trader, opened) = exit_trader(venue.clone()).await;
+        let (alert_tx, mut alert_rx) = mpsc::unbounded_channel();
+        let trader = trader.with_alert_sender(alert_tx);
+        let stop_id = opened.stop_order_id.clone().unwrap();
+        let target_id = opened.take_profit_order_id.clone().unwrap();
+        // The target executed before the stop's fill came in, so it cannot be cancelled
+        venue.filled.lock().push(target_id.clone());
+        
+        trader.on_order_update(&stop_id, OrderStatus::Filled).await.unwrap();
+        assert!(trader.get_positions().is_empty());
+        assert!(alert_rx.try_recv().is_err());
+        
+        trader
+            .on_fill(&Fill {
+                order_id: target_id.clone(),
+                quantity: opened.quantity,
+                price: 53_000.0,
+            })
+            .await
+            .unwrap();
+        let closed = trader.get_closed_positions();
+        assert_eq!(closed.len(), 1);
+        // Booked once, at the stop
+        assert!(close(closed[0].realized_pnl, (48_500.0 - 50_000.0) * opened.quantity));
+        assert_eq!(trader.get_stats().total_trades, 1);
+        
+        let alert = alert_rx.try_recv().unwrap();
+        assert_eq!(alert.data["order_id"], target_id.as_str());
+    }
+    
+    #[tokio::test]
+    async fn test_unfilled_resting_exit_is_taken_locally_after_the_grace() {
+        let venue = Arc::new(ExitVenue::default());
+        let (trader, opened) = exit_trader(venue.clone()).await;
+        let clock = Arc::new(ManualClock::new(chrono::Utc::now()));
+        let trader = trader.with_clock(clock.clone());
+        
+        // Through the stop, its fill is waited for
+        trader.update_positions("BTC/USDT", "binance", 48_400.0).await.unwrap();
+        clock.advance(chrono::Duration::seconds(20));
+        trader.update_positions("BTC/USDT", "binance", 48_300.0).await.unwrap();
+        assert_eq!(trader.get_positions().len(), 1);
+        
+        // Back above the stop restarts the wait
+        trader.update_positions("BTC/USDT", "binance", 49_000.0).await.unwrap();
+        trader.update_positions("BTC/USDT", "binance", 48_400.0).await.unwrap();
+        clock.advance(chrono::Duration::seconds(20));
+        trader.update_positions("BTC/USDT", "binance", 48_400.0).await.unwrap();
+        assert_eq!(trader.get_positions().len(), 1);
+        
+        // No fill 30s on, both legs are cancelled and the stop goes out at market
+        clock.advance(chrono::Duration::seconds(10));
+        trader.update_positions("BTC/USDT", "binance", 48_400.0).await.unwrap();
+        assert!(trader.get_positions().is_empty());
+        assert_eq!(venue.cancels.lock().len(), 2);
+        assert!(matches!(venue.opened.lock().last().unwrap().order_type, OrderType::Market));
+        let closed = trader.get_closed_positions().pop().unwrap();
+        assert_eq!(closed.id, opened.id);
+    }
+    
+    #[tokio::test]
+    async fn test_local_close_cancels_resting_exits_first() {
+        let venue = Arc::new(ExitVenue::default());
+        let (trader, opened) = exit_trader(venue.clone()).await;
+        let key = InstrumentKey::new("binance", "BTC/USDT");
+        
+        // A leg that cannot be cancelled has filled, its fill settles the position instead
+        venue.filled.lock().push(opened.take_profit_order_id.clone().unwrap());
+        let closed = trader
+            .close_position(&key, Some(opened.id), CloseReason::MaxHoldingPeriod)
+            .await
+            .unwrap();
+        assert!(!closed);
+        let open = trader.get_positions().pop().unwrap();
+        // The stop was cancelled and is watched locally again
+        assert_eq!(open.stop_order_id, None);
+        assert!(open.take_profit_order_id.is_some());
+        
+        venue.filled.lock().clear();
+        assert!(trader
+            .close_position(&key, Some(opened.id), CloseReason::MaxHoldingPeriod)
+            .await
+            .unwrap());
+        assert_eq!(venue.cancels.lock().len(), 2);
+        assert!(matches!(venue.opened.lock().last().unwrap().order_type, OrderType::Market));
+    }
+    
+    #[tokio::test]
+    async fn test_journal_keeps_rejections_and_the_position_lifecycle() {
+        let venue = Arc::new(ExitVenue::default());
+        let trader = trader(venue.clone());
+        trader.update_config(TradingConfig {
+            risk_percentage: 0.5,
+            ..config()
+        });
+        
+        // Capped at 100 notional, twice what 0.5% of the account may risk
+        trader.execute_signal(signal(), "default").await.unwrap();
+        let rejected = trader.get_journal().pop().unwrap();
+        assert_eq!(rejected.outcome, SignalOutcome::RejectedByRisk);
+        assert_eq!(rejected.details["limit"], "risk_amount");
+        assert!(close(rejected.details["max"].as_f64().unwrap(), 50.0));
+        assert!(close(rejected.details["value"].as_f64().unwrap(), 100.0));
+        
+        trader.update_config(config());
+        trader.execute_signal(signal(), "default").await.unwrap();
+        let key = InstrumentKey::new("binance", "BTC/USDT");
+        trader.close_position(&key, None, CloseReason::Manual).await.unwrap();
+        
+        let records = trader.export_journal(None, None).await.unwrap();
+        let events: Vec<String> = records.iter().map(|record| record.event()).collect();
+        assert_eq!(
+            events,
+            [
+                "rejected_by_risk",
+                "order_placed",
+                "executed",
+                "order_filled",
+                "position_opened",
+                "order_placed",
+                "order_filled",
+                "position_closed",
+            ]
+        );
+        let csv = journal::to_csv(&records);
+        assert_eq!(csv.lines().count(), records.len() + 1);
+        assert!(csv.lines().nth(1).unwrap().contains("\"\"limit\"\":\"\"risk_amount\"\""));
+        assert!(trader.write_journal().await.unwrap().is_none());
+    }
+    
+    #[derive(Default)]
+    struct MemoryJournal {
+        records: Mutex<Vec<JournalRecord>>,
+    }
+    
+    #[async_trait]
+    impl JournalStore for MemoryJournal {
+        async fn insert(&self, record: &JournalRecord) -> Result<()> {
+            self.records.lock().push(record.clone());
+            Ok(())
+        }
+        
+        // Unbounded, the tests export everything
+        async fn load(
+            &self,
+            _from: Option<chrono::DateTime<chrono::Utc>>,
+            _to: Option<chrono::DateTime<chrono::Utc>>,
+        ) -> Result<Vec<JournalRecord>> {
+            Ok(self.records.lock().clone())
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_journal_is_flushed_on_shutdown_and_exported_from_the_store() {
+        let path = std::env::temp_dir().join(format!("journal-{}.csv", uuid::Uuid::new_v4()));
+        let store = Arc::new(MemoryJournal::default());
+        let trader = trader(Arc::new(FillingVenue));
+        trader.update_config(TradingConfig {
+            journal: monitor_core::TradeJournalConfig {
+                max_entries: 2,
+                export_path: Some(path.display().to_string()),
+                export_format: monitor_core::JournalFormat::Csv,
+            },
+            ..config()
+        });
+        let trader = trader.with_journal_store(store.clone());
+        
+        // The signal, the order, its fill and the position, two of them spilled
+        trader.execute_signal(signal(), "default").await.unwrap();
+        assert_eq!(trader.flush_journal().await.unwrap(), 2);
+        assert_eq!(store.records.lock().len(), 4);
+        assert_eq!(trader.export_journal(None, None).await.unwrap().len(), 4);
+        
+        trader.write_journal().await.unwrap();
+        let csv = tokio::fs::read_to_string(&path).await.unwrap();
+        assert_eq!(csv, journal::to_csv(&store.records.lock()));
+        assert_eq!(csv.lines().count(), 5);
+        tokio::fs::remove_file(&path).await.unwrap();
+    }
+    
+    // Exchange account reporting whatever the test put in `held`
+    #[derive(Default)]
+    struct Account {
+        held: Mutex<Vec<VenuePosition>>,
+    }
+    
+    #[async_trait]
+    impl AccountSource for Account {
+        async fn fetch_positions(&self) -> Result<Vec<VenuePosition>> {
+            Ok(self.held.lock().clone())
+        }
+    }
+    
+    fn held(symbol: &str, side: PositionSide, quantity: f64, entry_price: f64) -> VenuePosition {
+        VenuePosition {
+            exchange: "binance".to_string(),
+            symbol: symbol.to_string(),
+            side,
+            quantity,
+            entry_price,
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_reconciliation_closes_phantom_positions() {
+        let account = Arc::new(Account::default());
+        let (alert_tx, mut alert_rx) = mpsc::unbounded_channel();
+        let trader = trader(Arc::new(ExitVenue::default()))
+            .with_account_source(account.clone())
+            .with_alert_sender(alert_tx);
+        trader.execute_signal(signal(), "default").await.unwrap();
+        let opened = trader.get_positions().pop().unwrap();
+        trader.update_positions("BTC/USDT", "binance", 50_500.0).await.unwrap();
+        
+        // The exchange holds nothing
+        let resolved = trader.reconcile_positions().await.unwrap();
+        assert_eq!(resolved.len(), 1);
+        assert!(trader.get_positions().is_empty());
+        let closed = trader.get_closed_positions().pop().unwrap();
+        assert_eq!(closed.id, opened.id);
+        // Booked at the last price seen
+        assert!(closed.realized_pnl > 0.0);
+        assert_eq!(trader.get_stats().total_trades, 1);
+        
+        let event = alert_rx.try_recv().unwrap();
+        assert!(matches!(
+            event.event_type,
+            EventType::System(SystemEventType::PositionDiscrepancy)
+        ));
+        assert_eq!(event.data["kind"], "phantom");
+        assert_eq!(event.data["resolution"], "closed_locally");
+        assert!(trader.reconcile_positions().await.unwrap().is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_reconciliation_adopts_unknown_positions() {
+        let account = Arc::new(Account::default());
+        account.held.lock().push(held("ETH/USDT", PositionSide::Short, 0.5, 3_000.0));
+        let (alert_tx, mut alert_rx) = mpsc::unbounded_channel();
+        let trader = trader(Arc::new(ExitVenue::default()))
+            .with_account_source(account.clone())
+            .with_alert_sender(alert_tx);
+        
+        trader.reconcile_positions().await.unwrap();
+        let adopted = trader.get_positions().pop().unwrap();
+        assert_eq!(adopted.symbol, "ETH/USDT");
+        assert_eq!(adopted.side, PositionSide::Short);
+        assert_eq!(adopted.quantity, 0.5);
+        assert_eq!(adopted.entry_price, 3_000.0);
+        // Protected like any other short
+        assert!(close(adopted.stop_loss.unwrap(), 3_090.0));
+        assert!(close(adopted.take_profit.unwrap(), 2_820.0));
+        
+        let warning = alert_rx.try_recv().unwrap();
+        assert!(matches!(warning.event_type, EventType::Alert(AlertType::Warning)));
+        let event = alert_rx.try_recv().unwrap();
+        assert_eq!(event.data["kind"], "unknown");
+        assert_eq!(event.data["resolution"], "adopted");
+        // Tracked from now on
+        assert!(trader.reconcile_positions().await.unwrap().is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_reconciliation_resizes_to_the_exchange() {
+        let account = Arc::new(Account::default());
+        let trader = trader(Arc::new(ExitVenue::default())).with_account_source(account.clone());
+        trader.execute_signal(signal(), "default").await.unwrap();
+        let opened = trader.get_positions().pop().unwrap();
+        
+        // Within the tolerance nothing changes
+        let nearly = opened.quantity * 1.001;
+        account.held.lock().push(held("BTC/USDT", PositionSide::Long, nearly, 0.0));
+        assert!(trader.reconcile_positions().await.unwrap().is_empty());
+        
+        account.held.lock()[0].quantity = opened.quantity / 2.0;
+        let resolved = trader.reconcile_positions().await.unwrap();
+        assert_eq!(resolved[0].resolution(), "resized");
+        let resized = trader.get_positions().pop().unwrap();
+        assert_eq!(resized.id, opened.id);
+        assert!(close(resized.quantity, opened.quantity / 2.0));
+        // No entry reported, the local one stands
+        assert_eq!(resized.entry_price, opened.entry_price);
+    }
+    
+    #[tokio::test]
+    async fn test_flatten_cancels_working_orders_before_closing() {
+        let venue = Arc::new(ExitVenue::default());
+        let (trader, opened) = exit_trader(venue.clone()).await;
+        let stop_id = opened.stop_order_id.clone().unwrap();
+        let target_id = opened.take_profit_order_id.clone().unwrap();
+        trader.update_config(TradingConfig {
+            exchange_exit_orders: true,
+            ..limit_config(false)
+        });
+        let mut eth = signal_at(3_000.0);
+        eth.symbol = "ETH/USDT".to_string();
+        trader.execute_signal(eth, "default").await.unwrap();
+        let entry_id = trader.get_pending_orders().pop().unwrap().order_id;
+        trader.update_positions("BTC/USDT", "binance", 51_000.0).await.unwrap();
+        
+        // Keep touches nothing
+        let report = trader.shutdown(ShutdownPolicy::Keep).await;
+        assert!(report.is_complete());
+        assert_eq!(report.positions[0].outcome, ShutdownOutcome::Kept);
+        assert!(venue.cancels.lock().is_empty());
+        assert_eq!(trader.get_pending_orders().len(), 1);
+        
+        let opened_before = venue.opened.lock().len();
+        let report = trader.shutdown(ShutdownPolicy::Flatten).await;
+        assert!(report.is_complete());
+        assert_eq!(report.cancelled_orders, vec![entry_id.clone()]);
+        assert_eq!(report.positions.len(), 1);
+        assert_eq!(report.positions[0].position_id, opened.id);
+        assert!(matches!(
+            report.positions[0].outcome,
+            ShutdownOutcome::Closed { realized_pnl } if realized_pnl > 0.0
+        ));
+        
+        // The entry, then the exits, then the market close
+        assert_eq!(*venue.cancels.lock(), vec![entry_id, stop_id, target_id]);
+        let opened_orders = venue.opened.lock();
+        assert_eq!(opened_orders.len(), opened_before + 1);
+        let close_order = opened_orders.last().unwrap();
+        assert!(matches!(close_order.order_type, OrderType::Market));
+        assert!(matches!(close_order.kind, OrderKind::Sell));
+        assert_eq!(close_order.instrument, "BTC/USDT");
+        assert!(trader.get_positions().is_empty());
+        assert!(trader.get_pending_orders().is_empty());
+        assert_eq!(trader.get_closed_positions()[0].id, opened.id);
+    }
+    
+    #[tokio::test]
+    async fn test_flatten_gives_up_at_the_timeout() {
+        let venue = Arc::new(StallingVenue::default());
+        let trader = trader(venue.clone());
+        let mut eth = signal_at(3_000.0);
+        eth.symbol = "ETH/USDT".to_string();
+        trader.execute_signal(signal(), "default").await.unwrap();
+        trader.execute_signal(eth, "default").await.unwrap();
+        assert_eq!(trader.get_positions().len(), 2);
+        trader.update_config(TradingConfig {
+            shutdown: monitor_core::TradingShutdownConfig {
+                policy: ShutdownPolicy::Flatten,
+                timeout_secs: 1,
+            },
+            ..config()
+        });
+        
+        venue.stalled.store(true, std::sync::atomic::Ordering::SeqCst);
+        let started = std::time::Instant::now();
+        let report = trader.shutdown(ShutdownPolicy::Flatten).await;
+        assert!(started.elapsed() < std::time::Duration::from_secs(3));
+        
+        assert!(report.timed_out);
+        assert!(!report.is_complete());
+        assert_eq!(report.positions.len(), 2);
+        // The first close hung, the second was never sent
+        assert!(report
+            .positions
+            .iter()
+            .all(|position| position.outcome == ShutdownOutcome::TimedOut));
+        // The hung close stays on record for the next startup to resolve
+        assert_eq!(trader.get_order_intents().len(), 1);
+    }
+}
@@ -0,0 +1,170 @@
+use crate::{
+    client::{OrderKind, OrderType, RequestOpen},
+    retry, Position, PositionSide,
+};
+use dashmap::DashMap;
+
+// Below this the unfilled part of an exit counts as filled, guards against float dust
+const QUANTITY_EPSILON: f64 = 1e-12;
+
+// One side of the emulated OCO pair resting on the exchange for a position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitLeg {
+    StopLoss,
+    TakeProfit,
+}
+
+impl ExitLeg {
+    pub const BOTH: [ExitLeg; 2] = [ExitLeg::StopLoss, ExitLeg::TakeProfit];
+
+    // The leg of `position` that `order_id` is, if any
+    pub fn of(position: &Position, order_id: &str) -> Option<Self> {
+        Self::BOTH
+            .into_iter()
+            .find(|leg| leg.order_id(position) == Some(order_id))
+    }
+
+    pub fn sibling(self) -> Self {
+        match self {
+            ExitLeg::StopLoss => ExitLeg::TakeProfit,
+            ExitLeg::TakeProfit => ExitLeg::StopLoss,
+        }
+    }
+
+    pub fn order_id(self, position: &Position) -> Option<&str> {
+        match self {
+            ExitLeg::StopLoss => position.stop_order_id.as_deref(),
+            ExitLeg::TakeProfit => position.take_profit_order_id.as_deref(),
+        }
+    }
+
+    pub fn set_order_id(self, position: &mut Position, order_id: Option<String>) {
+        match self {
+            ExitLeg::StopLoss => position.stop_order_id = order_id,
+            ExitLeg::TakeProfit => position.take_profit_order_id = order_id,
+        }
+    }
+
+    // Stop trigger or target price
+    pub fn level(self, position: &Position) -> Option<f64> {
+        match self {
+            ExitLeg::StopLoss => position.stop_loss,
+            ExitLeg::TakeProfit => position.take_profit,
+        }
+    }
+}
+
+// Reduce-only orders for the legs of `position` that have a level: a stop-market triggered at
+// the stop and a limit at the target, both for the whole position
+pub fn exit_requests(position: &Position) -> Vec<(ExitLeg, RequestOpen)> {
+    ExitLeg::BOTH
+        .into_iter()
+        .filter_map(|leg| {
+            let level = leg.level(position)?;
+            let (prefix, order_type) = match leg {
+                ExitLeg::StopLoss => ("s", OrderType::StopMarket),
+                ExitLeg::TakeProfit => ("t", OrderType::Limit),
+            };
+            Some((
+                leg,
+                RequestOpen {
+                    instrument: position.symbol.clone(),
+                    exchange: position.exchange.clone(),
+                    client_order_id: Some(retry::exit_order_id(prefix)),
+                    kind: match position.side {
+                        PositionSide::Long => OrderKind::Sell,
+                        PositionSide::Short => OrderKind::Buy,
+                    },
+                    order_type,
+                    quantity: position.quantity,
+                    price: Some(level),
+                    time_in_force: None,
+                    post_only: false,
+                    reduce_only: true,
+                },
+            ))
+        })
+        .collect()
+}
+
+// Fills against exit orders, by order id, until one covers its position
+#[derive(Default)]
+pub struct ExitFills {
+    fills: DashMap<String, (f64, f64)>,
+}
+
+impl ExitFills {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Folds a fill in. Returns the average fill price once `quantity` of the position has
+    // filled, the order is then forgotten
+    pub fn fill(&self, order_id: &str, filled: f64, price: f64, quantity: f64) -> Option<f64> {
+        let (total, notional) = {
+            let mut entry = self.fills.entry(order_id.to_string()).or_insert((0.0, 0.0));
+            entry.0 += filled;
+            entry.1 += filled * price;
+            *entry
+        };
+        if total + QUANTITY_EPSILON < quantity || total <= 0.0 {
+            return None;
+        }
+        self.fills.remove(order_id);
+        Some(notional / total)
+    }
+
+    pub fn remove(&self, order_id: &str) {
+        self.fills.remove(order_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    // Protected by a stop and a target, both resting on the venue
+    fn position(side: PositionSide) -> Position {
+        Position {
+            stop_loss: Some(51_500.0),
+            take_profit: Some(47_000.0),
+            stop_order_id: Some("s-1".to_string()),
+            take_profit_order_id: Some("t-1".to_string()),
+            ..fixtures::position("BTC/USDT", side, 0.2, 50_000.0)
+        }
+    }
+
+    #[test]
+    fn test_short_exits_buy_back_reduce_only() {
+        let requests = exit_requests(&position(PositionSide::Short));
+        assert_eq!(requests.len(), 2);
+        let (leg, stop) = &requests[0];
+        assert_eq!(*leg, ExitLeg::StopLoss);
+        assert!(matches!(stop.kind, OrderKind::Buy));
+        assert!(matches!(stop.order_type, OrderType::StopMarket));
+        assert_eq!(stop.price, Some(51_500.0));
+        assert!(stop.reduce_only);
+        assert!(matches!(requests[1].1.order_type, OrderType::Limit));
+        assert_ne!(stop.client_order_id, requests[1].1.client_order_id);
+
+        let mut unprotected = position(PositionSide::Short);
+        unprotected.take_profit = None;
+        assert_eq!(exit_requests(&unprotected).len(), 1);
+    }
+
+    #[test]
+    fn test_legs_and_partial_fills() {
+        let position = position(PositionSide::Long);
+        assert_eq!(ExitLeg::of(&position, "t-1"), Some(ExitLeg::TakeProfit));
+        assert_eq!(ExitLeg::of(&position, "e-1"), None);
+        assert_eq!(ExitLeg::TakeProfit.sibling().order_id(&position), Some("s-1"));
+
+        let fills = ExitFills::new();
+        assert_eq!(fills.fill("t-1", 0.05, 47_000.0, 0.2), None);
+        let average = fills.fill("t-1", 0.15, 47_100.0, 0.2).unwrap();
+        assert!((average - 47_075.0).abs() < 1e-9);
+        // Forgotten once complete
+        assert_eq!(fills.fill("t-1", 0.05, 47_000.0, 0.2), None);
+    }
+}
@@ -0,0 +1,62 @@
+// Signals, positions and configs for tests. The trader's own unit tests need them as this
+// crate's types, so they are defined here and reach the other crates' tests through
+// monitor-testkit, which enables the `testkit` feature.
+use crate::{Position, PositionSide, SignalStrength, SignalType, TradingSignal};
+use chrono::Utc;
+use monitor_core::TradingConfig;
+
+// Auto trading on, 1000 quote per position with a 3% stop and a 6% target. `extra` adds keys
+// or replaces these
+pub fn trading_config(extra: serde_json::Value) -> TradingConfig {
+    let mut config = serde_json::json!({
+        "auto_trading_enabled": true,
+        "max_position_size": 1000.0,
+        "risk_percentage": 2.0,
+        "stop_loss_percentage": 3.0,
+        "take_profit_percentage": 6.0,
+    });
+    if let serde_json::Value::Object(extra) = extra {
+        config.as_object_mut().unwrap().extend(extra);
+    }
+    serde_json::from_value(config).unwrap()
+}
+
+// A medium strength buy on binance
+pub fn signal(symbol: &str, price: f64) -> TradingSignal {
+    TradingSignal {
+        id: uuid::Uuid::new_v4(),
+        timestamp: Utc::now(),
+        symbol: symbol.to_string(),
+        exchange: "binance".to_string(),
+        signal_type: SignalType::Buy,
+        strength: SignalStrength::Medium,
+        price,
+        reason: "test".to_string(),
+        anomaly_id: None,
+    }
+}
+
+// An open position on binance entered and marked at `price`, with no stop, target or resting
+// exit orders
+pub fn position(symbol: &str, side: PositionSide, quantity: f64, price: f64) -> Position {
+    Position {
+        id: uuid::Uuid::new_v4(),
+        symbol: symbol.to_string(),
+        exchange: "binance".to_string(),
+        side,
+        quantity,
+        entry_price: price,
+        current_price: price,
+        unrealized_pnl: 0.0,
+        realized_pnl: 0.0,
+        stop_loss: None,
+        take_profit: None,
+        opened_at: Utc::now(),
+        closed_at: None,
+        stale: false,
+        adds: 0,
+        stop_order_id: None,
+        take_profit_order_id: None,
+        fees: 0.0,
+    }
+}
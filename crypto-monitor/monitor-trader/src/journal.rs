@@ -0,0 +1,603 @@
+use crate::TradingSignal;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use monitor_core::{
+    storage::repository::{TradeJournalRecord, TradeJournalRepository},
+    JournalFormat, Result,
+};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use tokio::sync::mpsc;
+use tracing::warn;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SignalOutcome {
+    Executed,
+    RejectedByRisk,
+    RejectedByLiquidity,
+    // A position is already open on the symbol and the scaling mode does not allow another
+    RejectedByScaling,
+    // Sized below the smallest order the exchange accepts
+    BelowMinNotional,
+    WarmingUp,
+    CooldownActive,
+    VenuePaused,
+    // The circuit breaker has halted new entries
+    Halted,
+    // Entry limit order resting, the position opens on its fill
+    OrderPending,
+    // Entered in clips, the first one sent
+    DcaStarted,
+    // Clips left unsent after the price moved past the abort threshold
+    DcaAborted,
+    // A sell on a spot market with no long to close
+    ShortNotAllowed,
+    // A sell on a spot market, closing the long instead of opening a short
+    ClosedLong,
+    ExecutionFailed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub timestamp: DateTime<Utc>,
+    pub signal: TradingSignal,
+    pub outcome: SignalOutcome,
+    pub details: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LifecycleKind {
+    OrderPlaced,
+    OrderFilled,
+    PositionOpened,
+    PositionClosed,
+}
+
+// An order, fill or position change. `side` is buy or sell for orders and fills, long or short
+// for positions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LifecycleEvent {
+    pub timestamp: DateTime<Utc>,
+    pub kind: LifecycleKind,
+    pub exchange: String,
+    pub symbol: String,
+    pub side: String,
+    pub order_id: Option<String>,
+    pub position_id: Option<uuid::Uuid>,
+    pub quantity: f64,
+    pub price: Option<f64>,
+    pub details: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JournalRecord {
+    Signal(JournalEntry),
+    Lifecycle(LifecycleEvent),
+}
+
+impl JournalRecord {
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        match self {
+            JournalRecord::Signal(entry) => entry.timestamp,
+            JournalRecord::Lifecycle(event) => event.timestamp,
+        }
+    }
+    
+    pub fn exchange(&self) -> &str {
+        match self {
+            JournalRecord::Signal(entry) => &entry.signal.exchange,
+            JournalRecord::Lifecycle(event) => &event.exchange,
+        }
+    }
+    
+    pub fn symbol(&self) -> &str {
+        match self {
+            JournalRecord::Signal(entry) => &entry.signal.symbol,
+            JournalRecord::Lifecycle(event) => &event.symbol,
+        }
+    }
+    
+    // The signal outcome or lifecycle kind in snake case, e.g. rejected_by_risk or
+    // order_filled
+    pub fn event(&self) -> String {
+        match self {
+            JournalRecord::Signal(entry) => snake_case(&format!("{:?}", entry.outcome)),
+            JournalRecord::Lifecycle(event) => serde_json::to_value(event.kind)
+                .ok()
+                .and_then(|value| value.as_str().map(str::to_string))
+                .unwrap_or_default(),
+        }
+    }
+    
+    fn within(&self, from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>) -> bool {
+        let at = self.timestamp();
+        from.is_none_or(|from| at >= from) && to.is_none_or(|to| at <= to)
+    }
+}
+
+fn snake_case(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len() + 4);
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
+// Where a stored record sits in the journal's order, a page of `JournalStore::load_page`
+// starts after it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JournalCursor {
+    pub recorded_at: DateTime<Utc>,
+    pub id: uuid::Uuid,
+}
+
+// Where records evicted from the in-memory journal go
+#[async_trait]
+pub trait JournalStore: Send + Sync {
+    async fn insert(&self, record: &JournalRecord) -> Result<()>;
+    // Oldest first
+    async fn load(
+        &self,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<Vec<JournalRecord>>;
+    
+    // Up to `limit` records after `after`, oldest first, with the cursor of the next page.
+    // Stores that can't page return everything at once
+    async fn load_page(
+        &self,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        _after: Option<JournalCursor>,
+        _limit: usize,
+    ) -> Result<(Vec<JournalRecord>, Option<JournalCursor>)> {
+        Ok((self.load(from, to).await?, None))
+    }
+}
+
+pub struct PgJournalStore {
+    journal: TradeJournalRepository,
+}
+
+impl PgJournalStore {
+    pub fn new(journal: TradeJournalRepository) -> Self {
+        Self { journal }
+    }
+}
+
+#[async_trait]
+impl JournalStore for PgJournalStore {
+    async fn insert(&self, record: &JournalRecord) -> Result<()> {
+        self.journal
+            .insert(&TradeJournalRecord {
+                id: uuid::Uuid::new_v4(),
+                recorded_at: record.timestamp(),
+                exchange: record.exchange().to_string(),
+                symbol: record.symbol().to_string(),
+                event: record.event(),
+                record: serde_json::to_string(record)?,
+            })
+            .await
+    }
+    
+    async fn load(
+        &self,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<Vec<JournalRecord>> {
+        self.journal
+            .find_between(from, to)
+            .await?
+            .into_iter()
+            .map(|row| serde_json::from_str(&row.record).map_err(Into::into))
+            .collect()
+    }
+    
+    async fn load_page(
+        &self,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        after: Option<JournalCursor>,
+        limit: usize,
+    ) -> Result<(Vec<JournalRecord>, Option<JournalCursor>)> {
+        let after = after.map(|after| (after.recorded_at, after.id));
+        let rows = self.journal.find_page(from, to, after, limit as i64).await?;
+        let next = rows
+            .last()
+            .filter(|_| rows.len() == limit)
+            .map(|row| JournalCursor {
+                recorded_at: row.recorded_at,
+                id: row.id,
+            });
+        let records = rows
+            .into_iter()
+            .map(|row| serde_json::from_str(&row.record).map_err(Into::into))
+            .collect::<Result<Vec<JournalRecord>>>()?;
+        Ok((records, next))
+    }
+}
+
+// The newest `max_entries` records, each evicted one handed to the spill channel when set.
+// A full channel drops the record rather than block the trading path
+pub struct TradeJournal {
+    entries: RwLock<VecDeque<JournalRecord>>,
+    max_entries: usize,
+    spill: RwLock<Option<mpsc::Sender<JournalRecord>>>,
+}
+
+impl TradeJournal {
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            entries: RwLock::new(VecDeque::with_capacity(max_entries)),
+            max_entries,
+            spill: RwLock::new(None),
+        }
+    }
+    
+    pub fn with_spill(self, spill: mpsc::Sender<JournalRecord>) -> Self {
+        *self.spill.write() = Some(spill);
+        self
+    }
+    
+    // Closes the spill channel, its reader ends once it has taken what was already sent.
+    // Records evicted from here on are dropped
+    pub fn close_spill(&self) {
+        self.spill.write().take();
+    }
+    
+    // Takes every record still in memory, oldest first
+    pub fn drain(&self) -> Vec<JournalRecord> {
+        self.entries.write().drain(..).collect()
+    }
+    
+    pub fn record(&self, signal: &TradingSignal, outcome: SignalOutcome, details: serde_json::Value) {
+        self.push(JournalRecord::Signal(JournalEntry {
+            timestamp: Utc::now(),
+            signal: signal.clone(),
+            outcome,
+            details,
+        }));
+    }
+    
+    pub fn record_event(&self, event: LifecycleEvent) {
+        self.push(JournalRecord::Lifecycle(event));
+    }
+    
+    fn push(&self, record: JournalRecord) {
+        let evicted = {
+            let mut entries = self.entries.write();
+            let evicted = if entries.len() >= self.max_entries {
+                entries.pop_front()
+            } else {
+                None
+            };
+            entries.push_back(record);
+            evicted
+        };
+        
+        let Some(evicted) = evicted else {
+            return;
+        };
+        if let Some(spill) = &*self.spill.read() {
+            if let Err(mpsc::error::TrySendError::Full(record)) = spill.try_send(evicted) {
+                warn!(
+                    "Journal spill channel full, dropped the {} record of {}",
+                    record.event(),
+                    record.timestamp()
+                );
+            }
+        }
+    }
+    
+    // Signal entries only
+    pub fn entries(&self) -> Vec<JournalEntry> {
+        self.entries
+            .read()
+            .iter()
+            .filter_map(|record| match record {
+                JournalRecord::Signal(entry) => Some(entry.clone()),
+                JournalRecord::Lifecycle(_) => None,
+            })
+            .collect()
+    }
+    
+    // Every record still in memory between `from` and `to`, oldest first
+    pub fn records(
+        &self,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Vec<JournalRecord> {
+        self.entries
+            .read()
+            .iter()
+            .filter(|record| record.within(from, to))
+            .cloned()
+            .collect()
+    }
+    
+    pub fn len(&self) -> usize {
+        self.entries.read().len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.entries.read().is_empty()
+    }
+}
+
+impl Default for TradeJournal {
+    fn default() -> Self {
+        Self::new(10_000)
+    }
+}
+
+// Column order of CSV exports. Columns are only ever appended so existing readers keep working
+pub const CSV_COLUMNS: [&str; 13] = [
+    "timestamp",
+    "type",
+    "event",
+    "exchange",
+    "symbol",
+    "side",
+    "signal_id",
+    "strength",
+    "order_id",
+    "position_id",
+    "quantity",
+    "price",
+    "details",
+];
+
+pub fn export(records: &[JournalRecord], format: JournalFormat) -> Result<String> {
+    match format {
+        JournalFormat::Csv => Ok(to_csv(records)),
+        JournalFormat::Json => Ok(serde_json::to_string_pretty(records)?),
+    }
+}
+
+// An export written a page of records at a time: `start`, then `page` for each page, then
+// `finish`. Together they make the same CSV as `to_csv`, or a JSON array of the records
+pub struct ExportWriter {
+    format: JournalFormat,
+    written: usize,
+}
+
+impl ExportWriter {
+    pub fn new(format: JournalFormat) -> Self {
+        Self { format, written: 0 }
+    }
+    
+    pub fn start(&self) -> String {
+        match self.format {
+            JournalFormat::Csv => format!("{}\n", CSV_COLUMNS.join(",")),
+            JournalFormat::Json => "[".to_string(),
+        }
+    }
+    
+    pub fn page(&mut self, records: &[JournalRecord]) -> Result<String> {
+        let chunk = match self.format {
+            JournalFormat::Csv => csv_rows(records),
+            JournalFormat::Json => {
+                let mut json = String::new();
+                for (i, record) in records.iter().enumerate() {
+                    if self.written + i > 0 {
+                        json.push(',');
+                    }
+                    json.push_str("\n  ");
+                    json.push_str(&serde_json::to_string(record)?);
+                }
+                json
+            }
+        };
+        self.written += records.len();
+        Ok(chunk)
+    }
+    
+    pub fn finish(&self) -> String {
+        match self.format {
+            JournalFormat::Csv => String::new(),
+            JournalFormat::Json if self.written == 0 => "]\n".to_string(),
+            JournalFormat::Json => "\n]\n".to_string(),
+        }
+    }
+    
+    pub fn written(&self) -> usize {
+        self.written
+    }
+}
+
+// One row per record under a `CSV_COLUMNS` header. `details` holds the JSON details, e.g. the
+// limit a rejected signal breached
+pub fn to_csv(records: &[JournalRecord]) -> String {
+    let mut csv = CSV_COLUMNS.join(",");
+    csv.push('\n');
+    csv.push_str(&csv_rows(records));
+    csv
+}
+
+fn csv_rows(records: &[JournalRecord]) -> String {
+    let mut csv = String::new();
+    for record in records {
+        let row = match record {
+            JournalRecord::Signal(entry) => [
+                entry.timestamp.to_rfc3339(),
+                "signal".to_string(),
+                record.event(),
+                entry.signal.exchange.clone(),
+                entry.signal.symbol.clone(),
+                format!("{:?}", entry.signal.signal_type).to_lowercase(),
+                entry.signal.id.to_string(),
+                format!("{:?}", entry.signal.strength).to_lowercase(),
+                String::new(),
+                String::new(),
+                String::new(),
+                entry.signal.price.to_string(),
+                details(&entry.details),
+            ],
+            JournalRecord::Lifecycle(event) => [
+                event.timestamp.to_rfc3339(),
+                "lifecycle".to_string(),
+                record.event(),
+                event.exchange.clone(),
+                event.symbol.clone(),
+                event.side.clone(),
+                String::new(),
+                String::new(),
+                event.order_id.clone().unwrap_or_default(),
+                event.position_id.map(|id| id.to_string()).unwrap_or_default(),
+                event.quantity.to_string(),
+                event.price.map(|price| price.to_string()).unwrap_or_default(),
+                details(&event.details),
+            ],
+        };
+        let fields: Vec<String> = row.iter().map(|field| escape(field)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+fn details(details: &serde_json::Value) -> String {
+    if details.is_null() {
+        String::new()
+    } else {
+        details.to_string()
+    }
+}
+
+// Quotes fields holding a separator, quote or line break, doubling the quotes inside
+fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::signal;
+
+    fn fill(quantity: f64) -> LifecycleEvent {
+        LifecycleEvent {
+            timestamp: Utc::now(),
+            kind: LifecycleKind::OrderFilled,
+            exchange: "binance".to_string(),
+            symbol: "BTC/USDT".to_string(),
+            side: "buy".to_string(),
+            order_id: Some("e-1".to_string()),
+            position_id: None,
+            quantity,
+            price: Some(50_000.0),
+            details: serde_json::Value::Null,
+        }
+    }
+
+    #[test]
+    fn test_csv_schema_is_stable() {
+        let journal = TradeJournal::new(10);
+        let signal = signal("BTC/USDT", 50_000.0);
+        journal.record(
+            &signal,
+            SignalOutcome::RejectedByRisk,
+            serde_json::json!({ "limit": "max_position_size", "value": 150.0, "max": 100.0 }),
+        );
+        journal.record_event(fill(0.002));
+
+        let csv = to_csv(&journal.records(None, None));
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "timestamp,type,event,exchange,symbol,side,signal_id,strength,order_id,position_id,\
+             quantity,price,details"
+        );
+        assert_eq!(lines.len(), 3);
+        // The JSON details are quoted whole, so every row has the header's columns
+        let rejected = lines[1];
+        assert!(rejected.contains(&format!(
+            ",signal,rejected_by_risk,binance,BTC/USDT,buy,{},strong,,,,50000,",
+            signal.id
+        )));
+        assert!(rejected.ends_with(
+            r#""{""limit"":""max_position_size"",""max"":100.0,""value"":150.0}""#
+        ));
+        assert!(lines[2].contains(",lifecycle,order_filled,binance,BTC/USDT,buy,,,e-1,,0.002,50000,"));
+        assert!(lines[2].ends_with(','));
+    }
+
+    #[test]
+    fn test_evicted_records_spill() {
+        let (spill_tx, mut spill_rx) = mpsc::channel(1);
+        let journal = TradeJournal::new(2).with_spill(spill_tx);
+        let signal = signal("BTC/USDT", 50_000.0);
+        journal.record(&signal, SignalOutcome::Executed, serde_json::Value::Null);
+        journal.record_event(fill(1.0));
+        assert!(spill_rx.try_recv().is_err());
+
+        journal.record_event(fill(2.0));
+        assert_eq!(journal.len(), 2);
+        assert!(matches!(spill_rx.try_recv().unwrap(), JournalRecord::Signal(_)));
+        // Lifecycle records stay out of the signal entries
+        assert!(journal.entries().is_empty());
+
+        let json = export(&journal.records(None, None), JournalFormat::Json).unwrap();
+        let restored: Vec<JournalRecord> = serde_json::from_str(&json).unwrap();
+        assert!(matches!(&restored[1], JournalRecord::Lifecycle(event) if event.quantity == 2.0));
+        assert!(journal.records(Some(Utc::now() + chrono::Duration::seconds(1)), None).is_empty());
+
+        // Over the channel's capacity the record is dropped, not queued
+        journal.record_event(fill(3.0));
+        journal.record_event(fill(4.0));
+        assert!(matches!(spill_rx.try_recv().unwrap(), JournalRecord::Lifecycle(_)));
+        assert!(spill_rx.try_recv().is_err());
+
+        journal.close_spill();
+        assert_eq!(journal.drain().len(), 2);
+        assert!(journal.is_empty());
+        assert!(spill_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_paged_export_matches_the_whole_one() {
+        let records: Vec<JournalRecord> = (0..5)
+            .map(|i| JournalRecord::Lifecycle(fill(i as f64)))
+            .chain([JournalRecord::Signal(JournalEntry {
+                timestamp: Utc::now(),
+                signal: signal("BTC/USDT", 50_000.0),
+                outcome: SignalOutcome::BelowMinNotional,
+                details: serde_json::Value::Null,
+            })])
+            .collect();
+
+        let mut csv = ExportWriter::new(JournalFormat::Csv);
+        let mut json = ExportWriter::new(JournalFormat::Json);
+        let (mut csv_out, mut json_out) = (csv.start(), json.start());
+        for page in records.chunks(2) {
+            csv_out.push_str(&csv.page(page).unwrap());
+            json_out.push_str(&json.page(page).unwrap());
+        }
+        csv_out.push_str(&csv.finish());
+        json_out.push_str(&json.finish());
+
+        assert_eq!(csv_out, to_csv(&records));
+        assert!(csv_out.contains(",signal,below_min_notional,"));
+        let restored: Vec<JournalRecord> = serde_json::from_str(&json_out).unwrap();
+        assert_eq!(restored.len(), 6);
+        assert_eq!(json.written(), 6);
+
+        let empty = ExportWriter::new(JournalFormat::Json);
+        let restored: Vec<JournalRecord> =
+            serde_json::from_str(&(empty.start() + &empty.finish())).unwrap();
+        assert!(restored.is_empty());
+    }
+}
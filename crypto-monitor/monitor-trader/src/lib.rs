@@ -1,15 +1,38 @@
+pub mod circuit_breaker;
+pub mod client;
+pub mod clock;
+pub mod context;
+pub mod cooldown;
+pub mod costs;
+pub mod dca;
+pub mod ensemble;
 pub mod executor;
+pub mod exits;
+#[cfg(any(test, feature = "testkit"))]
+pub mod fixtures;
+pub mod journal;
+pub mod liquidity;
+pub mod orders;
+pub mod overrides;
+pub mod paper;
+pub mod persistence;
+pub mod portfolio;
+pub mod reconcile;
+pub mod retry;
+pub mod rules;
+pub mod scaling;
+pub mod shutdown;
 pub mod strategy;
 pub mod risk;
+pub mod stale;
+pub mod stats;
+pub mod volatility;
 
-use barter_execution::{
-    order::{Order, OrderId, OrderKind, OrderState, OrderType},
-    trade::Trade,
-};
 use barter_instrument::InstrumentIndex;
 use chrono::{DateTime, Utc};
+use context::MarketContext;
 use monitor_anomaly::AnomalyDetection;
-use monitor_core::{MonitorError, Result, TradingConfig};
+use monitor_core::{instrument::InstrumentKey, MonitorError, Result, TradingConfig};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -55,9 +78,22 @@ pub struct Position {
     pub take_profit: Option<f64>,
     pub opened_at: DateTime<Utc>,
     pub closed_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub stale: bool,
+    // Pyramid adds made on top of the initial entry
+    #[serde(default)]
+    pub adds: u32,
+    // Exit orders resting on the exchange under `exchange_exit_orders`
+    #[serde(default)]
+    pub stop_order_id: Option<String>,
+    #[serde(default)]
+    pub take_profit_order_id: Option<String>,
+    // Fees paid on the fills so far, taken out of the realized PnL at close
+    #[serde(default)]
+    pub fees: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum PositionSide {
     Long,
     Short,
@@ -66,10 +102,36 @@ pub enum PositionSide {
 impl Position {
     pub fn update_price(&mut self, price: f64) {
         self.current_price = price;
-        self.unrealized_pnl = match self.side {
+        self.unrealized_pnl = self.pnl_at(price);
+    }
+    
+    // Grows the position by a further fill, the entry becomes the quantity-weighted average
+    pub fn add_fill(&mut self, quantity: f64, price: f64) {
+        let combined = self.quantity + quantity;
+        if combined <= 0.0 {
+            return;
+        }
+        self.entry_price = (self.entry_price * self.quantity + price * quantity) / combined;
+        self.quantity = combined;
+        self.update_price(self.current_price);
+    }
+    
+    // PnL of exiting the whole position at `price`
+    pub fn pnl_at(&self, price: f64) -> f64 {
+        match self.side {
             PositionSide::Long => (price - self.entry_price) * self.quantity,
             PositionSide::Short => (self.entry_price - price) * self.quantity,
-        };
+        }
+    }
+    
+    // Marks the position closed at its exit fill and returns the realized PnL, net of `fees`.
+    // The exit fee is expected to be in `fees` already
+    pub fn close(&mut self, exit_price: f64, at: DateTime<Utc>) -> f64 {
+        self.current_price = exit_price;
+        self.realized_pnl = self.pnl_at(exit_price) - self.fees;
+        self.unrealized_pnl = 0.0;
+        self.closed_at = Some(at);
+        self.realized_pnl
     }
     
     pub fn should_stop_loss(&self) -> bool {
@@ -101,12 +163,60 @@ pub struct TradingStats {
     pub winning_trades: u64,
     pub losing_trades: u64,
     pub win_rate: f64,
+    // Net of fees, as are the averages and streaks below
     pub total_pnl: f64,
+    // Before fees, `total_pnl` + `total_fees`
+    #[serde(default)]
+    pub gross_pnl: f64,
+    #[serde(default)]
+    pub total_fees: f64,
     pub average_win: f64,
     pub average_loss: f64,
     pub profit_factor: f64,
+    // Largest fall of realized equity from its peak, as a fraction of the peak
     pub max_drawdown: f64,
+    // Mean excess per-trade return over its standard deviation, not annualized
     pub sharpe_ratio: f64,
+    // Positive while winning, negative while losing
+    pub current_streak: i64,
+    pub max_win_streak: u64,
+    pub max_loss_streak: u64,
+}
+
+impl TradingStats {
+    // Folds one closed trade in; break-even trades count as losses. `average_loss` is
+    // negative and `profit_factor` stays 0 until there is a losing trade to divide by.
+    // `pnl` is net of the `fees` the trade paid
+    pub fn record_trade(&mut self, pnl: f64, fees: f64) {
+        self.total_trades += 1;
+        self.total_pnl += pnl;
+        self.gross_pnl += pnl + fees;
+        self.total_fees += fees;
+        
+        if pnl > 0.0 {
+            let gross_profit = self.average_win * self.winning_trades as f64 + pnl;
+            self.winning_trades += 1;
+            self.average_win = gross_profit / self.winning_trades as f64;
+            self.current_streak = self.current_streak.max(0) + 1;
+            self.max_win_streak = self.max_win_streak.max(self.current_streak as u64);
+        } else {
+            let gross_loss = self.average_loss * self.losing_trades as f64 + pnl;
+            self.losing_trades += 1;
+            self.average_loss = gross_loss / self.losing_trades as f64;
+            self.current_streak = self.current_streak.min(0) - 1;
+            self.max_loss_streak = self.max_loss_streak.max(self.current_streak.unsigned_abs());
+        }
+        
+        self.win_rate = self.winning_trades as f64 / self.total_trades as f64;
+        
+        let gross_profit = self.average_win * self.winning_trades as f64;
+        let gross_loss = -self.average_loss * self.losing_trades as f64;
+        self.profit_factor = if gross_loss > 0.0 {
+            gross_profit / gross_loss
+        } else {
+            0.0
+        };
+    }
 }
 
 impl Default for TradingStats {
@@ -117,23 +227,118 @@ impl Default for TradingStats {
             losing_trades: 0,
             win_rate: 0.0,
             total_pnl: 0.0,
+            gross_pnl: 0.0,
+            total_fees: 0.0,
             average_win: 0.0,
             average_loss: 0.0,
             profit_factor: 0.0,
             max_drawdown: 0.0,
             sharpe_ratio: 0.0,
+            current_streak: 0,
+            max_win_streak: 0,
+            max_loss_streak: 0,
         }
     }
 }
 
 pub trait TradingStrategy: Send + Sync {
-    fn analyze(&mut self, anomaly: &AnomalyDetection) -> Option<TradingSignal>;
+    fn analyze(&mut self, anomaly: &AnomalyDetection, context: &MarketContext) -> Option<TradingSignal>;
     fn update_config(&mut self, config: TradingConfig);
+    
+    // Identifies the strategy for per-strategy cooldowns and journaling
+    fn name(&self) -> &str {
+        "default"
+    }
 }
 
 pub trait RiskManager: Send + Sync {
-    fn validate_order(&self, signal: &TradingSignal, portfolio_value: f64) -> bool;
+    // The limit the order breaches on its own, `None` when it may go ahead
+    fn validate_order(
+        &self,
+        signal: &TradingSignal,
+        portfolio_value: f64,
+    ) -> Option<risk::OrderRejection>;
+    
+    // Checks the order against what is already open or pending, `validate_order` only sees it
+    // in isolation
+    fn validate_portfolio(
+        &self,
+        _signal: &TradingSignal,
+        _open_positions: &[Position],
+        _pending: &[risk::PendingEntry],
+        _portfolio_value: f64,
+    ) -> Option<risk::ExposureRejection> {
+        None
+    }
+    
     fn calculate_position_size(&self, signal: &TradingSignal, portfolio_value: f64) -> f64;
-    fn get_stop_loss(&self, entry_price: f64, side: PositionSide) -> f64;
-    fn get_take_profit(&self, entry_price: f64, side: PositionSide) -> f64;
-}
\ No newline at end of file
+    // Exit levels for a position entered on `signal`'s market
+    fn get_stop_loss(&self, signal: &TradingSignal, entry_price: f64, side: PositionSide) -> f64;
+    fn get_take_profit(&self, signal: &TradingSignal, entry_price: f64, side: PositionSide) -> f64;
+    
+    fn update_config(&self, _config: TradingConfig) {}
+    
+    // Every trade price as it arrives, for managers sizing from the market's own history
+    fn record_price(&self, _key: &InstrumentKey, _price: f64, _at: DateTime<Utc>) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fixtures::position;
+
+    #[test]
+    fn test_close_realizes_at_the_fill_not_the_mark() {
+        let mut long = position("BTC/USDT", PositionSide::Long, 0.5, 50_000.0);
+        long.update_price(51_000.0);
+        assert_eq!(long.unrealized_pnl, 500.0);
+
+        // The closing market order slips below the last mark
+        let pnl = long.close(50_800.0, Utc::now());
+        assert_eq!(pnl, 400.0);
+        assert_eq!(long.realized_pnl, 400.0);
+        assert_eq!(long.unrealized_pnl, 0.0);
+        assert_eq!(long.current_price, 50_800.0);
+        assert!(long.closed_at.is_some());
+
+        let mut short = position("BTC/USDT", PositionSide::Short, 0.5, 50_000.0);
+        short.update_price(49_000.0);
+        assert_eq!(short.close(49_200.0, Utc::now()), 400.0);
+    }
+
+    #[test]
+    fn test_pnl_follows_the_filled_quantity() {
+        let mut long = position("BTC/USDT", PositionSide::Long, 0.2, 50_000.0);
+        long.update_price(51_000.0);
+        assert_eq!(long.unrealized_pnl, 200.0);
+
+        // The remaining 60% fills higher, lifting the average entry
+        long.add_fill(0.3, 50_500.0);
+        assert_eq!(long.quantity, 0.5);
+        assert!((long.entry_price - 50_300.0).abs() < 1e-6);
+        assert!((long.unrealized_pnl - 350.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_stats_track_averages_and_profit_factor() {
+        let mut stats = TradingStats::default();
+        stats.record_trade(300.0, 0.0);
+        assert_eq!(stats.profit_factor, 0.0);
+
+        stats.record_trade(100.0, 0.0);
+        stats.record_trade(-50.0, 0.0);
+        stats.record_trade(-150.0, 0.0);
+
+        assert_eq!(stats.total_trades, 4);
+        assert_eq!(stats.winning_trades, 2);
+        assert_eq!(stats.losing_trades, 2);
+        assert_eq!(stats.win_rate, 0.5);
+        assert_eq!(stats.total_pnl, 200.0);
+        assert_eq!(stats.average_win, 200.0);
+        assert_eq!(stats.average_loss, -100.0);
+        assert_eq!(stats.profit_factor, 2.0);
+        assert_eq!(stats.current_streak, -2);
+        assert_eq!(stats.max_win_streak, 2);
+        assert_eq!(stats.max_loss_streak, 2);
+    }
+}
@@ -0,0 +1,316 @@
+use crate::SignalType;
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use dashmap::DashMap;
+use monitor_core::{
+    cache::{CacheCounters, CacheStats, InstrumentedCache},
+    LiquidityRequirements,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct L1Snapshot {
+    pub bid_price: f64,
+    pub bid_size: f64,
+    pub ask_price: f64,
+    pub ask_size: f64,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl L1Snapshot {
+    pub fn spread_bps(&self) -> Option<f64> {
+        let mid = (self.bid_price + self.ask_price) / 2.0;
+        (mid > 0.0).then(|| (self.ask_price - self.bid_price) / mid * 10_000.0)
+    }
+}
+
+// Latest top-of-book and rolling 24h quote volume per "exchange:symbol"
+#[derive(Default)]
+pub struct MarketSnapshotCache {
+    l1: DashMap<String, L1Snapshot>,
+    hourly_volume: DashMap<String, BTreeMap<DateTime<Utc>, f64>>,
+    counters: CacheCounters,
+}
+
+impl MarketSnapshotCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update_l1(&self, key: &str, snapshot: L1Snapshot) {
+        match self.l1.get_mut(key) {
+            Some(mut l1) => *l1 = snapshot,
+            None => {
+                self.l1.insert(key.to_string(), snapshot);
+            }
+        }
+    }
+
+    pub fn l1(&self, key: &str) -> Option<L1Snapshot> {
+        self.counters.observe(self.l1.get(key).map(|s| *s))
+    }
+
+    pub fn record_trade(&self, key: &str, quote_volume: f64, at: DateTime<Utc>) {
+        let hour = at.duration_trunc(Duration::hours(1)).unwrap_or(at);
+        let mut buckets = match self.hourly_volume.get_mut(key) {
+            Some(buckets) => buckets,
+            None => self.hourly_volume.entry(key.to_string()).or_default(),
+        };
+        *buckets.entry(hour).or_insert(0.0) += quote_volume;
+
+        // Keep one spare bucket so the partial oldest hour is still covered
+        let cutoff = hour - Duration::hours(24);
+        buckets.retain(|h, _| *h >= cutoff);
+    }
+
+    pub fn volume_24h(&self, key: &str, now: DateTime<Utc>) -> Option<f64> {
+        let buckets = self.hourly_volume.get(key)?;
+        let cutoff = now - Duration::hours(24);
+        Some(
+            buckets
+                .iter()
+                .filter(|(hour, _)| **hour + Duration::hours(1) > cutoff)
+                .map(|(_, volume)| volume)
+                .sum(),
+        )
+    }
+}
+
+impl InstrumentedCache for MarketSnapshotCache {
+    fn name(&self) -> &str {
+        "orderbook_snapshots"
+    }
+
+    fn stats(&self) -> CacheStats {
+        // Hourly volume maps hold up to 25 buckets per symbol
+        let entry_bytes = std::mem::size_of::<L1Snapshot>() + 25 * 16 + 32;
+        self.counters.stats(self.name(), self.l1.len(), entry_bytes, None)
+    }
+
+    // Drops the snapshots only, the next quote replaces them. The volume history took a day
+    // of trades to build and clearing it would fail the volume check until it refilled
+    fn clear(&self) {
+        self.l1.clear();
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum LiquidityRejection {
+    InsufficientVolume { volume_24h: f64, required: f64 },
+    SpreadTooWide { spread_bps: f64, max: f64 },
+    InsufficientDepth { depth: f64, required: f64 },
+    MissingData { data: String },
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LiquidityMeasurements {
+    pub volume_24h: Option<f64>,
+    pub spread_bps: Option<f64>,
+    pub depth: Option<f64>,
+    pub order_quantity: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LiquidityReport {
+    pub measurements: LiquidityMeasurements,
+    pub rejections: Vec<LiquidityRejection>,
+}
+
+impl LiquidityReport {
+    pub fn passed(&self) -> bool {
+        self.rejections.is_empty()
+    }
+}
+
+// Evaluates every prerequisite so the journal records all failing measurements at once
+pub fn check_liquidity(
+    requirements: &LiquidityRequirements,
+    market: &MarketSnapshotCache,
+    key: &str,
+    signal_type: &SignalType,
+    quantity: f64,
+    now: DateTime<Utc>,
+) -> LiquidityReport {
+    let l1 = market.l1(key);
+    let measurements = LiquidityMeasurements {
+        volume_24h: market.volume_24h(key, now),
+        spread_bps: l1.and_then(|s| s.spread_bps()),
+        // Buys take the ask, sells hit the bid
+        depth: l1.map(|s| match signal_type {
+            SignalType::Sell => s.bid_size,
+            _ => s.ask_size,
+        }),
+        order_quantity: quantity,
+    };
+
+    let mut rejections = Vec::new();
+
+    match measurements.volume_24h {
+        Some(volume_24h) if volume_24h < requirements.min_volume_24h => {
+            rejections.push(LiquidityRejection::InsufficientVolume {
+                volume_24h,
+                required: requirements.min_volume_24h,
+            })
+        }
+        Some(_) => {}
+        None => rejections.push(LiquidityRejection::MissingData {
+            data: "volume_24h".to_string(),
+        }),
+    }
+
+    match measurements.spread_bps {
+        Some(spread_bps) if spread_bps > requirements.max_spread_bps => {
+            rejections.push(LiquidityRejection::SpreadTooWide {
+                spread_bps,
+                max: requirements.max_spread_bps,
+            })
+        }
+        Some(_) => {}
+        None => rejections.push(LiquidityRejection::MissingData {
+            data: "l1_snapshot".to_string(),
+        }),
+    }
+
+    // Without a snapshot the missing-data rejection above already applies
+    if let Some(depth) = measurements.depth {
+        let required = quantity * requirements.min_depth_ratio;
+        if depth < required {
+            rejections.push(LiquidityRejection::InsufficientDepth { depth, required });
+        }
+    }
+
+    LiquidityReport {
+        measurements,
+        rejections,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "binance:BTC/USDT";
+
+    fn requirements() -> LiquidityRequirements {
+        LiquidityRequirements {
+            min_volume_24h: 1_000_000.0,
+            max_spread_bps: 5.0,
+            min_depth_ratio: 3.0,
+        }
+    }
+
+    // A liquid market: 2M volume, 2 bps spread, 10 units on each side
+    fn market(now: DateTime<Utc>) -> MarketSnapshotCache {
+        let market = MarketSnapshotCache::new();
+        market.record_trade(KEY, 2_000_000.0, now - Duration::hours(2));
+        market.update_l1(
+            KEY,
+            L1Snapshot {
+                bid_price: 49_995.0,
+                bid_size: 10.0,
+                ask_price: 50_005.0,
+                ask_size: 10.0,
+                timestamp: now,
+            },
+        );
+        market
+    }
+
+    fn check(market: &MarketSnapshotCache, quantity: f64, now: DateTime<Utc>) -> LiquidityReport {
+        check_liquidity(&requirements(), market, KEY, &SignalType::Buy, quantity, now)
+    }
+
+    #[test]
+    fn test_all_prerequisites_pass() {
+        let now = Utc::now();
+        let report = check(&market(now), 1.0, now);
+
+        assert!(report.passed());
+        assert_eq!(report.measurements.volume_24h, Some(2_000_000.0));
+        assert_eq!(report.measurements.depth, Some(10.0));
+    }
+
+    #[test]
+    fn test_low_volume_rejected() {
+        let now = Utc::now();
+        let market = market(now);
+        // Volume from before the 24h window does not count
+        let quiet = MarketSnapshotCache::new();
+        quiet.record_trade(KEY, 5_000_000.0, now - Duration::hours(30));
+        quiet.update_l1(KEY, market.l1(KEY).unwrap());
+
+        let report = check(&quiet, 1.0, now);
+        assert_eq!(
+            report.rejections,
+            vec![LiquidityRejection::InsufficientVolume {
+                volume_24h: 0.0,
+                required: 1_000_000.0
+            }]
+        );
+    }
+
+    #[test]
+    fn test_wide_spread_rejected() {
+        let now = Utc::now();
+        let market = market(now);
+        market.update_l1(
+            KEY,
+            L1Snapshot {
+                bid_price: 49_800.0,
+                bid_size: 10.0,
+                ask_price: 50_200.0,
+                ask_size: 10.0,
+                timestamp: now,
+            },
+        );
+
+        let report = check(&market, 1.0, now);
+        assert_eq!(report.rejections.len(), 1);
+        assert!(matches!(
+            report.rejections[0],
+            LiquidityRejection::SpreadTooWide { spread_bps, .. } if (spread_bps - 80.0).abs() < 1e-9
+        ));
+    }
+
+    #[test]
+    fn test_thin_book_rejected_relative_to_order_size() {
+        let now = Utc::now();
+        let market = market(now);
+
+        assert!(check(&market, 3.0, now).passed());
+        let report = check(&market, 4.0, now);
+        assert_eq!(
+            report.rejections,
+            vec![LiquidityRejection::InsufficientDepth {
+                depth: 10.0,
+                required: 12.0
+            }]
+        );
+    }
+
+    #[test]
+    fn test_clearing_keeps_the_volume_history() {
+        let now = Utc::now();
+        let market = market(now);
+        market.clear();
+
+        assert_eq!(market.l1(KEY), None);
+        assert_eq!(market.volume_24h(KEY, now), Some(2_000_000.0));
+    }
+
+    #[test]
+    fn test_missing_snapshot_rejected() {
+        let now = Utc::now();
+        let market = MarketSnapshotCache::new();
+        market.record_trade(KEY, 2_000_000.0, now);
+
+        let report = check(&market, 1.0, now);
+        assert_eq!(
+            report.rejections,
+            vec![LiquidityRejection::MissingData {
+                data: "l1_snapshot".to_string()
+            }]
+        );
+    }
+}
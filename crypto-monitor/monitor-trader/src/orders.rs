@@ -0,0 +1,433 @@
+use crate::{costs::Liquidity, PositionSide, SignalType, TradingSignal};
+use barter_execution::{AccountEventKind, UnindexedAccountEvent};
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use monitor_core::{instrument::InstrumentKey, OrderPlacementConfig, UnfilledOrderAction};
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderStatus {
+    // Sent, not yet acknowledged as resting on the book
+    Submitted,
+    Open,
+    // Some of the quantity filled, the rest still working
+    PartiallyFilled,
+    Filled,
+    Cancelled,
+}
+
+impl OrderStatus {
+    pub fn is_terminal(self) -> bool {
+        matches!(self, OrderStatus::Filled | OrderStatus::Cancelled)
+    }
+}
+
+// Below this the remainder of an order counts as filled, guards against float dust
+const QUANTITY_EPSILON: f64 = 1e-12;
+
+// An entry order waiting for its fills, with what is needed to open the position
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingOrder {
+    pub order_id: String,
+    pub key: InstrumentKey,
+    pub signal: TradingSignal,
+    pub side: PositionSide,
+    pub strategy: String,
+    pub quantity: f64,
+    pub limit_price: f64,
+    pub status: OrderStatus,
+    pub submitted_at: DateTime<Utc>,
+    // Cancel-and-replace rounds this entry has been through
+    pub replacements: u32,
+    // Cumulative fills so far, the position holds exactly this much
+    pub filled_quantity: f64,
+    pub average_fill_price: f64,
+    // Fee rate its fills pay, maker for resting limits and taker for market orders
+    pub liquidity: Liquidity,
+}
+
+impl PendingOrder {
+    pub fn remaining(&self) -> f64 {
+        (self.quantity - self.filled_quantity).max(0.0)
+    }
+
+    // Folds a fill in, capped at what is still open, and returns the quantity applied
+    pub fn apply_fill(&mut self, quantity: f64, price: f64) -> f64 {
+        let applied = quantity.min(self.remaining()).max(0.0);
+        if applied > 0.0 {
+            let filled = self.filled_quantity + applied;
+            self.average_fill_price =
+                (self.average_fill_price * self.filled_quantity + price * applied) / filled;
+            self.filled_quantity = filled;
+        }
+        applied
+    }
+
+    // The part still to be entered after a cancel, as a fresh order
+    pub fn into_remainder(mut self) -> Self {
+        self.quantity = self.remaining();
+        self.filled_quantity = 0.0;
+        self.average_fill_price = 0.0;
+        self
+    }
+}
+
+// One execution against an order, as reported by the venue
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fill {
+    pub order_id: String,
+    pub quantity: f64,
+    pub price: f64,
+}
+
+// The fill carried by an account event, `None` for every other kind
+pub fn fill_of(event: &UnindexedAccountEvent) -> Option<Fill> {
+    match &event.kind {
+        AccountEventKind::Trade(trade) => Some(Fill {
+            order_id: trade.order_id.to_string(),
+            quantity: trade.quantity.abs().to_f64()?,
+            price: trade.price.to_f64()?,
+        }),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum PendingAction {
+    // Re-quote the same entry as a fresh limit order
+    Replace(PendingOrder),
+    // Take the entry at market
+    MarketFallback(PendingOrder),
+}
+
+// Limit price `offset_bps` away from `reference` on the passive side, below for buys
+pub fn limit_price(signal_type: &SignalType, reference: f64, offset_bps: f64) -> f64 {
+    let offset = reference * offset_bps / 10_000.0;
+    match signal_type {
+        SignalType::Sell => reference + offset,
+        _ => reference - offset,
+    }
+}
+
+// What to do with an entry whose limit order timed out and was cancelled, only the unfilled
+// remainder carries on
+pub fn on_timeout(order: PendingOrder, config: &OrderPlacementConfig) -> PendingAction {
+    let order = order.into_remainder();
+    match config.on_timeout {
+        UnfilledOrderAction::Replace if order.replacements < config.max_replacements => {
+            PendingAction::Replace(order)
+        }
+        _ => PendingAction::MarketFallback(order),
+    }
+}
+
+#[derive(Default)]
+pub struct PendingOrders {
+    orders: DashMap<String, PendingOrder>,
+    // Cancels the venue refused so far, per order id
+    cancel_failures: DashMap<String, u32>,
+}
+
+impl PendingOrders {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn track(&self, order: PendingOrder) {
+        info!(
+            "Tracking {} limit order {} @ {}",
+            order.key, order.order_id, order.limit_price
+        );
+        self.orders.insert(order.order_id.clone(), order);
+    }
+
+    // Applies a venue update, terminal ones hand the order back and stop tracking it
+    pub fn update(&self, order_id: &str, status: OrderStatus) -> Option<PendingOrder> {
+        if status.is_terminal() {
+            self.cancel_failures.remove(order_id);
+            return self.orders.remove(order_id).map(|(_, mut order)| {
+                order.status = status;
+                order
+            });
+        }
+        let mut order = self.orders.get_mut(order_id)?;
+        order.status = status;
+        Some(order.clone())
+    }
+
+    // Applies a fill, handing back the order with the quantity applied. An order filled in
+    // full stops being tracked
+    pub fn fill(&self, order_id: &str, quantity: f64, price: f64) -> Option<(PendingOrder, f64)> {
+        let (order, applied) = {
+            let mut order = self.orders.get_mut(order_id)?;
+            let applied = order.apply_fill(quantity, price);
+            order.status = if order.remaining() <= QUANTITY_EPSILON {
+                OrderStatus::Filled
+            } else {
+                OrderStatus::PartiallyFilled
+            };
+            (order.clone(), applied)
+        };
+        if order.status == OrderStatus::Filled {
+            self.remove(order_id);
+        }
+        Some((order, applied))
+    }
+
+    pub fn remove(&self, order_id: &str) -> Option<PendingOrder> {
+        self.cancel_failures.remove(order_id);
+        self.orders.remove(order_id).map(|(_, order)| order)
+    }
+
+    // Counts a refused cancel of `order_id`, returning how many there have been
+    pub fn cancel_failed(&self, order_id: &str) -> u32 {
+        let mut failures = self.cancel_failures.entry(order_id.to_string()).or_insert(0);
+        *failures += 1;
+        *failures
+    }
+
+    // Orders resting for at least `timeout`, still tracked until their cancel is confirmed
+    pub fn expired(&self, now: DateTime<Utc>, timeout: Duration) -> Vec<PendingOrder> {
+        self.orders
+            .iter()
+            .filter(|order| now - order.submitted_at >= timeout)
+            .map(|order| order.clone())
+            .collect()
+    }
+
+    pub fn has_pending(&self, key: &str) -> bool {
+        self.orders.iter().any(|order| order.key.as_str() == key)
+    }
+
+    pub fn get(&self, order_id: &str) -> Option<PendingOrder> {
+        self.orders.get(order_id).map(|order| order.clone())
+    }
+
+    pub fn all(&self) -> Vec<PendingOrder> {
+        self.orders.iter().map(|order| order.clone()).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.orders.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.orders.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use monitor_core::OrderPlacementMode;
+
+    // A venue that fills each resting order `fill_after` after it was placed, cancels are
+    // refused once the fill has happened
+    struct DelayedFillVenue {
+        fill_after: Duration,
+        resting: Vec<(String, DateTime<Utc>)>,
+        next_id: u32,
+    }
+
+    impl DelayedFillVenue {
+        fn new(fill_after_secs: i64) -> Self {
+            Self {
+                fill_after: Duration::seconds(fill_after_secs),
+                resting: Vec::new(),
+                next_id: 0,
+            }
+        }
+
+        fn place(&mut self, at: DateTime<Utc>) -> String {
+            self.next_id += 1;
+            let id = format!("order-{}", self.next_id);
+            self.resting.push((id.clone(), at));
+            id
+        }
+
+        // Ids filled by `now`
+        fn poll(&mut self, now: DateTime<Utc>) -> Vec<String> {
+            let fill_after = self.fill_after;
+            let (filled, resting) = self
+                .resting
+                .drain(..)
+                .partition(|(_, placed)| now - *placed >= fill_after);
+            self.resting = resting;
+            filled.into_iter().map(|(id, _)| id).collect()
+        }
+
+        fn cancel(&mut self, id: &str) -> bool {
+            let before = self.resting.len();
+            self.resting.retain(|(resting, _)| resting != id);
+            self.resting.len() < before
+        }
+    }
+
+    fn pending(order_id: String, at: DateTime<Utc>, replacements: u32) -> PendingOrder {
+        PendingOrder {
+            order_id,
+            key: InstrumentKey::new("binance", "BTC/USDT"),
+            signal: fixtures::signal("BTC/USDT", 50_000.0),
+            side: PositionSide::Long,
+            strategy: "default".to_string(),
+            quantity: 0.1,
+            limit_price: limit_price(&SignalType::Buy, 50_000.0, 2.0),
+            status: OrderStatus::Submitted,
+            submitted_at: at,
+            replacements,
+            filled_quantity: 0.0,
+            average_fill_price: 0.0,
+            liquidity: Liquidity::Maker,
+        }
+    }
+
+    fn placement(on_timeout: UnfilledOrderAction) -> OrderPlacementConfig {
+        OrderPlacementConfig {
+            mode: OrderPlacementMode::Limit,
+            offset_bps: 2.0,
+            timeout_secs: 10,
+            on_timeout,
+            max_replacements: 2,
+            cancel_partial_remainder: false,
+        }
+    }
+
+    #[test]
+    fn test_limit_price_sits_on_the_passive_side() {
+        assert!((limit_price(&SignalType::Buy, 50_000.0, 2.0) - 49_990.0).abs() < 1e-9);
+        assert!((limit_price(&SignalType::Sell, 50_000.0, 2.0) - 50_010.0).abs() < 1e-9);
+        assert_eq!(limit_price(&SignalType::Buy, 50_000.0, 0.0), 50_000.0);
+    }
+
+    #[test]
+    fn test_fill_before_timeout_hands_back_the_order() {
+        let start = Utc::now();
+        let config = placement(UnfilledOrderAction::Market);
+        let timeout = Duration::seconds(config.timeout_secs as i64);
+        let mut venue = DelayedFillVenue::new(3);
+        let orders = PendingOrders::new();
+
+        let id = venue.place(start);
+        orders.track(pending(id.clone(), start, 0));
+        assert!(orders.has_pending("binance:BTC/USDT"));
+        assert_eq!(orders.update(&id, OrderStatus::Open).unwrap().status, OrderStatus::Open);
+
+        assert!(venue.poll(start + Duration::seconds(1)).is_empty());
+        let filled = venue.poll(start + Duration::seconds(3));
+        assert_eq!(filled, vec![id.clone()]);
+
+        let order = orders.update(&id, OrderStatus::Filled).unwrap();
+        assert_eq!(order.status, OrderStatus::Filled);
+        assert!(orders.is_empty());
+        assert!(orders.expired(start + timeout, timeout).is_empty());
+        // Late duplicates of the fill are ignored
+        assert!(orders.update(&id, OrderStatus::Filled).is_none());
+    }
+
+    #[test]
+    fn test_slow_fills_are_replaced_then_fall_back_to_market() {
+        let start = Utc::now();
+        let config = placement(UnfilledOrderAction::Replace);
+        let timeout = Duration::seconds(config.timeout_secs as i64);
+        let mut venue = DelayedFillVenue::new(60);
+        let orders = PendingOrders::new();
+
+        let mut now = start;
+        orders.track(pending(venue.place(now), now, 0));
+
+        let mut replaced = 0;
+        let fallback = loop {
+            assert!(orders.expired(now + Duration::seconds(5), timeout).is_empty());
+            now += timeout;
+            assert!(venue.poll(now).is_empty());
+
+            let expired = orders.expired(now, timeout);
+            assert_eq!(expired.len(), 1);
+            let order = expired.into_iter().next().unwrap();
+            assert!(venue.cancel(&order.order_id));
+            let order = orders.update(&order.order_id, OrderStatus::Cancelled).unwrap();
+
+            match on_timeout(order, &config) {
+                PendingAction::Replace(mut order) => {
+                    replaced += 1;
+                    order.order_id = venue.place(now);
+                    order.submitted_at = now;
+                    order.replacements += 1;
+                    orders.track(order);
+                }
+                PendingAction::MarketFallback(order) => break order,
+            }
+        };
+
+        assert_eq!(replaced, 2);
+        assert_eq!(fallback.replacements, 2);
+        assert!(orders.is_empty());
+        assert!(venue.resting.is_empty());
+    }
+
+    #[test]
+    fn test_market_policy_falls_back_on_first_timeout() {
+        let start = Utc::now();
+        let config = placement(UnfilledOrderAction::Market);
+        let order = pending("order-1".to_string(), start, 0);
+        assert!(matches!(on_timeout(order, &config), PendingAction::MarketFallback(_)));
+    }
+
+    #[test]
+    fn test_cancel_racing_a_fill_keeps_the_fill() {
+        let start = Utc::now();
+        let timeout = Duration::seconds(10);
+        let mut venue = DelayedFillVenue::new(10);
+        let orders = PendingOrders::new();
+
+        let id = venue.place(start);
+        orders.track(pending(id.clone(), start, 0));
+
+        // The fill lands before the cancel reaches the venue
+        let now = start + timeout;
+        assert_eq!(orders.expired(now, timeout).len(), 1);
+        assert_eq!(venue.poll(now), vec![id.clone()]);
+        assert!(!venue.cancel(&id));
+
+        // Still tracked, so the fill update opens the position instead of a replacement
+        assert!(orders.get(&id).is_some());
+        assert_eq!(orders.update(&id, OrderStatus::Filled).unwrap().quantity, 0.1);
+    }
+
+    #[test]
+    fn test_partial_fills_accumulate_until_complete() {
+        let start = Utc::now();
+        let orders = PendingOrders::new();
+        orders.track(pending("order-1".to_string(), start, 0));
+
+        let (order, applied) = orders.fill("order-1", 0.04, 49_990.0).unwrap();
+        assert_eq!(order.status, OrderStatus::PartiallyFilled);
+        assert!((applied - 0.04).abs() < 1e-12);
+        assert!((order.remaining() - 0.06).abs() < 1e-12);
+        assert!(orders.has_pending("binance:BTC/USDT"));
+
+        // Overfills are capped at what was still open
+        let (order, applied) = orders.fill("order-1", 0.08, 49_980.0).unwrap();
+        assert_eq!(order.status, OrderStatus::Filled);
+        assert!((applied - 0.06).abs() < 1e-12);
+        assert!((order.average_fill_price - 49_984.0).abs() < 1e-6);
+        assert!(orders.is_empty());
+    }
+
+    #[test]
+    fn test_timeout_carries_on_with_the_remainder() {
+        let mut order = pending("order-1".to_string(), Utc::now(), 0);
+        order.apply_fill(0.04, 49_990.0);
+
+        let PendingAction::MarketFallback(rest) =
+            on_timeout(order, &placement(UnfilledOrderAction::Market))
+        else {
+            panic!("expected a market fallback");
+        };
+        assert!((rest.quantity - 0.06).abs() < 1e-12);
+        assert_eq!(rest.filled_quantity, 0.0);
+    }
+}
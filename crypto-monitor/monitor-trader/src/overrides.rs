@@ -0,0 +1,175 @@
+use monitor_core::{TradingConfig, TradingOverride};
+
+// The override in effect for a market: its "exchange:symbol" entry over its exchange's
+pub fn resolve_override(config: &TradingConfig, exchange: &str, symbol: &str) -> TradingOverride {
+    let none = TradingOverride::default();
+    let by_exchange = config.overrides.exchanges.get(exchange).unwrap_or(&none);
+    let by_symbol = config
+        .overrides
+        .symbols
+        .get(&format!("{}:{}", exchange, symbol))
+        .unwrap_or(&none);
+
+    TradingOverride {
+        auto_trading_enabled: by_symbol.auto_trading_enabled.or(by_exchange.auto_trading_enabled),
+        max_position_size: by_symbol.max_position_size.or(by_exchange.max_position_size),
+        risk_percentage: by_symbol.risk_percentage.or(by_exchange.risk_percentage),
+        stop_loss_percentage: by_symbol.stop_loss_percentage.or(by_exchange.stop_loss_percentage),
+        take_profit_percentage: by_symbol
+            .take_profit_percentage
+            .or(by_exchange.take_profit_percentage),
+        max_holding_minutes: by_symbol.max_holding_minutes.or(by_exchange.max_holding_minutes),
+        instrument_kind: by_symbol.instrument_kind.or(by_exchange.instrument_kind),
+    }
+}
+
+// Whether new trades are taken on the market, the check every strategy starts with
+pub fn trading_enabled(config: &TradingConfig, exchange: &str, symbol: &str) -> bool {
+    resolve_override(config, exchange, symbol)
+        .auto_trading_enabled
+        .unwrap_or(config.auto_trading_enabled)
+}
+
+// `config` as it applies to one market, symbol overrides over exchange overrides over the
+// global values. The result carries no overrides of its own
+pub fn resolve_config(config: &TradingConfig, exchange: &str, symbol: &str) -> TradingConfig {
+    let over = resolve_override(config, exchange, symbol);
+    let mut resolved = config.clone();
+    resolved.overrides = Default::default();
+    apply(&mut resolved, &over);
+    resolved
+}
+
+// `config` as it applies to one market, for callers on the trading path that read a few values
+// per signal. Borrows the config rather than resolving a copy of it: the overridable values are
+// looked up through the resolved override, everything else is read from `base`
+pub struct MarketConfig<'a> {
+    pub base: &'a TradingConfig,
+    over: TradingOverride,
+}
+
+impl<'a> MarketConfig<'a> {
+    pub fn new(base: &'a TradingConfig, exchange: &str, symbol: &str) -> Self {
+        Self {
+            base,
+            over: resolve_override(base, exchange, symbol),
+        }
+    }
+
+    pub fn max_position_size(&self) -> f64 {
+        self.over.max_position_size.unwrap_or(self.base.max_position_size)
+    }
+
+    pub fn risk_percentage(&self) -> f64 {
+        self.over.risk_percentage.unwrap_or(self.base.risk_percentage)
+    }
+
+    pub fn stop_loss_percentage(&self) -> f64 {
+        self.over.stop_loss_percentage.unwrap_or(self.base.stop_loss_percentage)
+    }
+
+    pub fn take_profit_percentage(&self) -> f64 {
+        self.over.take_profit_percentage.unwrap_or(self.base.take_profit_percentage)
+    }
+}
+
+fn apply(config: &mut TradingConfig, over: &TradingOverride) {
+    if let Some(enabled) = over.auto_trading_enabled {
+        config.auto_trading_enabled = enabled;
+    }
+    if let Some(size) = over.max_position_size {
+        config.max_position_size = size;
+    }
+    if let Some(risk) = over.risk_percentage {
+        config.risk_percentage = risk;
+    }
+    if let Some(stop) = over.stop_loss_percentage {
+        config.stop_loss_percentage = stop;
+    }
+    if let Some(target) = over.take_profit_percentage {
+        config.take_profit_percentage = target;
+    }
+    if over.max_holding_minutes.is_some() {
+        config.max_holding_minutes = over.max_holding_minutes;
+    }
+    if let Some(kind) = over.instrument_kind {
+        config.instrument_kind = kind;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use monitor_core::InstrumentKind;
+
+    fn config() -> TradingConfig {
+        fixtures::trading_config(serde_json::json!({
+            "overrides": {
+                "exchanges": {
+                    "bybit": { "max_position_size": 200.0, "stop_loss_percentage": 5.0 },
+                    "coinbase": { "instrument_kind": "spot" },
+                },
+                "symbols": {
+                    "bybit:PEPE/USDT": { "stop_loss_percentage": 8.0, "max_holding_minutes": 30 },
+                    "binance:BTC/USDT": { "max_position_size": 5000.0, "stop_loss_percentage": 1.5 },
+                    "bybit:LUNA/USDT": { "auto_trading_enabled": false },
+                    "coinbase:BTC-PERP": { "instrument_kind": "perpetual" },
+                },
+            },
+        }))
+    }
+
+    #[test]
+    fn test_symbol_over_exchange_over_global() {
+        let config = config();
+
+        // Symbol stop, exchange size, global risk and target
+        let pepe = resolve_config(&config, "bybit", "PEPE/USDT");
+        assert_eq!(pepe.stop_loss_percentage, 8.0);
+        assert_eq!(pepe.max_position_size, 200.0);
+        assert_eq!(pepe.risk_percentage, 2.0);
+        assert_eq!(pepe.take_profit_percentage, 6.0);
+        assert_eq!(pepe.max_holding_minutes, Some(30));
+        assert!(pepe.overrides.symbols.is_empty());
+
+        let doge = resolve_config(&config, "bybit", "DOGE/USDT");
+        assert_eq!(doge.stop_loss_percentage, 5.0);
+        assert_eq!(doge.max_position_size, 200.0);
+        assert_eq!(doge.max_holding_minutes, None);
+
+        let btc = resolve_config(&config, "binance", "BTC/USDT");
+        assert_eq!(btc.max_position_size, 5000.0);
+        assert_eq!(btc.stop_loss_percentage, 1.5);
+
+        // Untouched markets get the global values
+        let eth = resolve_config(&config, "binance", "ETH/USDT");
+        assert_eq!(eth.max_position_size, 1000.0);
+        assert_eq!(eth.stop_loss_percentage, 3.0);
+
+        assert!(!resolve_config(&config, "bybit", "LUNA/USDT").auto_trading_enabled);
+        // A spot exchange with a perpetual listed on it
+        let spot = resolve_config(&config, "coinbase", "ETH/USD");
+        assert_eq!(spot.instrument_kind, InstrumentKind::Spot);
+        assert_eq!(
+            resolve_config(&config, "coinbase", "BTC-PERP").instrument_kind,
+            InstrumentKind::Perpetual
+        );
+        assert_eq!(eth.instrument_kind, InstrumentKind::Perpetual);
+        // The same symbol on another exchange is a different market
+        assert_eq!(resolve_override(&config, "okx", "PEPE/USDT"), TradingOverride::default());
+    }
+
+    #[test]
+    fn test_market_config_reads_what_resolve_config_resolves() {
+        let config = config();
+        for (exchange, symbol) in [("bybit", "PEPE/USDT"), ("binance", "BTC/USDT"), ("okx", "X")] {
+            let market = MarketConfig::new(&config, exchange, symbol);
+            let resolved = resolve_config(&config, exchange, symbol);
+            assert_eq!(market.max_position_size(), resolved.max_position_size);
+            assert_eq!(market.risk_percentage(), resolved.risk_percentage);
+            assert_eq!(market.stop_loss_percentage(), resolved.stop_loss_percentage);
+            assert_eq!(market.take_profit_percentage(), resolved.take_profit_percentage);
+        }
+    }
+}
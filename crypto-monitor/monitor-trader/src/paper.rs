@@ -0,0 +1,623 @@
+use crate::client::{
+    Order, OrderClient, OrderId, OrderKind, OrderState, OrderType, RequestCancel, RequestOpen,
+};
+use async_trait::async_trait;
+use barter_execution::error::{ApiError, UnindexedClientError};
+use chrono::{DateTime, Utc};
+use monitor_core::{instrument::InstrumentKey, PaperTradingConfig};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::info;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaperFill {
+    pub order_id: String,
+    pub key: String,
+    pub buy: bool,
+    pub quantity: f64,
+    pub price: f64,
+    // Charged in the quote asset
+    pub fee: f64,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PaperRejection {
+    // No price observed for the instrument and none given on the request
+    NoPrice(String),
+    BalanceInsufficient { asset: String, required: f64, available: f64 },
+    // Post-only limit that would have taken liquidity
+    WouldTake(String),
+    UnknownOrder(String),
+}
+
+#[derive(Debug, Clone)]
+struct RestingOrder {
+    key: String,
+    buy: bool,
+    quantity: f64,
+    // The trigger for stop orders
+    limit_price: f64,
+    stop: bool,
+}
+
+impl RestingOrder {
+    // Limits fill once the price trades through them, stops once it reaches them the other way
+    fn triggered(&self, price: f64) -> bool {
+        match (self.stop, self.buy) {
+            (false, true) | (true, false) => price <= self.limit_price,
+            (false, false) | (true, true) => price >= self.limit_price,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaperOrder {
+    pub id: String,
+    pub quantity: f64,
+    // Fill price for filled orders, the limit for resting ones
+    pub price: f64,
+    pub filled: bool,
+}
+
+// Simulated account: market orders fill at the last observed price moved by the slippage,
+// limit orders rest until the price trades through them and stop orders turn into market
+// orders once it reaches their trigger
+pub struct PaperAccount {
+    config: PaperTradingConfig,
+    balances: HashMap<String, f64>,
+    prices: HashMap<String, f64>,
+    resting: HashMap<String, RestingOrder>,
+    fills: Vec<PaperFill>,
+    next_id: u64,
+}
+
+impl PaperAccount {
+    pub fn new(config: PaperTradingConfig, quote_asset: &str, initial_balance: f64) -> Self {
+        Self {
+            config,
+            balances: HashMap::from([(quote_asset.to_uppercase(), initial_balance)]),
+            prices: HashMap::new(),
+            resting: HashMap::new(),
+            fills: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    // Returns the ids of resting orders the new price filled
+    pub fn record_price(&mut self, key: &str, price: f64, now: DateTime<Utc>) -> Vec<String> {
+        self.prices.insert(key.to_string(), price);
+
+        let crossed: Vec<String> = self
+            .resting
+            .iter()
+            .filter(|(_, order)| order.key == key && order.triggered(price))
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        for id in &crossed {
+            if let Some(order) = self.resting.remove(id) {
+                let fill_price = if order.stop {
+                    self.slipped(order.buy, price)
+                } else {
+                    order.limit_price
+                };
+                self.settle(id, &order.key, order.buy, order.quantity, fill_price, now);
+            }
+        }
+        crossed
+    }
+
+    pub fn open(
+        &mut self,
+        key: &str,
+        buy: bool,
+        quantity: f64,
+        limit_price: Option<f64>,
+        post_only: bool,
+        reference_price: Option<f64>,
+    ) -> Result<PaperOrder, PaperRejection> {
+        let last = self.prices.get(key).copied().or(reference_price);
+        let crosses = |limit: f64, last: f64| if buy { last <= limit } else { last >= limit };
+
+        let (price, rest) = match (limit_price, last) {
+            (Some(limit), Some(last)) if crosses(limit, last) => {
+                if post_only {
+                    return Err(PaperRejection::WouldTake(key.to_string()));
+                }
+                // A marketable limit takes at the touch, never worse than its limit
+                (no_worse_than(buy, self.slipped(buy, last), limit), false)
+            }
+            (Some(limit), _) => (limit, true),
+            (None, Some(last)) => (self.slipped(buy, last), false),
+            (None, None) => return Err(PaperRejection::NoPrice(key.to_string())),
+        };
+
+        if buy {
+            let quote = quote_asset(key);
+            let required = quantity * price * (1.0 + self.config.fee_bps / 10_000.0);
+            let available = self.balance(&quote);
+            if required > available {
+                return Err(PaperRejection::BalanceInsufficient {
+                    asset: quote,
+                    required,
+                    available,
+                });
+            }
+        }
+
+        self.next_id += 1;
+        let id = format!("paper-{}", self.next_id);
+        if rest {
+            self.resting.insert(
+                id.clone(),
+                RestingOrder {
+                    key: key.to_string(),
+                    buy,
+                    quantity,
+                    limit_price: price,
+                    stop: false,
+                },
+            );
+        } else {
+            self.settle(&id, key, buy, quantity, price, Utc::now());
+        }
+
+        Ok(PaperOrder {
+            id,
+            quantity,
+            price,
+            filled: !rest,
+        })
+    }
+
+    // Rests a stop-market order, or fills it right away when the last price is already
+    // beyond the trigger
+    pub fn open_stop(
+        &mut self,
+        key: &str,
+        buy: bool,
+        quantity: f64,
+        trigger: f64,
+    ) -> Result<PaperOrder, PaperRejection> {
+        let order = RestingOrder {
+            key: key.to_string(),
+            buy,
+            quantity,
+            limit_price: trigger,
+            stop: true,
+        };
+        if self.prices.get(key).is_some_and(|last| order.triggered(*last)) {
+            return self.open(key, buy, quantity, None, false, None);
+        }
+
+        self.next_id += 1;
+        let id = format!("paper-{}", self.next_id);
+        self.resting.insert(id.clone(), order);
+        Ok(PaperOrder {
+            id,
+            quantity,
+            price: trigger,
+            filled: false,
+        })
+    }
+
+    pub fn cancel(&mut self, id: &str) -> Result<PaperOrder, PaperRejection> {
+        let order = self
+            .resting
+            .remove(id)
+            .ok_or_else(|| PaperRejection::UnknownOrder(id.to_string()))?;
+        Ok(PaperOrder {
+            id: id.to_string(),
+            quantity: order.quantity,
+            price: order.limit_price,
+            filled: false,
+        })
+    }
+
+    pub fn is_resting(&self, id: &str) -> bool {
+        self.resting.contains_key(id)
+    }
+
+    // Base balances go negative for shorts, the simulation does not model borrowing
+    pub fn balance(&self, asset: &str) -> f64 {
+        self.balances.get(&asset.to_uppercase()).copied().unwrap_or(0.0)
+    }
+
+    pub fn balances(&self) -> HashMap<String, f64> {
+        self.balances.clone()
+    }
+
+    pub fn fills(&self) -> &[PaperFill] {
+        &self.fills
+    }
+
+    fn slipped(&self, buy: bool, price: f64) -> f64 {
+        let slippage = price * self.config.slippage_bps / 10_000.0;
+        if buy {
+            price + slippage
+        } else {
+            price - slippage
+        }
+    }
+
+    fn settle(&mut self, id: &str, key: &str, buy: bool, quantity: f64, price: f64, now: DateTime<Utc>) {
+        let notional = quantity * price;
+        let fee = notional * self.config.fee_bps / 10_000.0;
+        let (base, quote) = (base_asset(key), quote_asset(key));
+
+        let sign = if buy { 1.0 } else { -1.0 };
+        *self.balances.entry(base).or_default() += sign * quantity;
+        *self.balances.entry(quote).or_default() += -sign * notional - fee;
+
+        info!(
+            "Paper fill {} {} {} {} @ {} (fee {:.4})",
+            id,
+            if buy { "buy" } else { "sell" },
+            quantity,
+            key,
+            price,
+            fee
+        );
+        self.fills.push(PaperFill {
+            order_id: id.to_string(),
+            key: key.to_string(),
+            buy,
+            quantity,
+            price,
+            fee,
+            timestamp: now,
+        });
+    }
+}
+
+fn no_worse_than(buy: bool, price: f64, limit: f64) -> f64 {
+    if buy {
+        price.min(limit)
+    } else {
+        price.max(limit)
+    }
+}
+
+// "binance:BTC/USDT" -> "BTC"
+fn base_asset(key: &str) -> String {
+    let symbol = key.split_once(':').map_or(key, |(_, symbol)| symbol);
+    symbol.split('/').next().unwrap_or(symbol).to_uppercase()
+}
+
+fn quote_asset(key: &str) -> String {
+    let symbol = key.split_once(':').map_or(key, |(_, symbol)| symbol);
+    symbol.split('/').nth(1).unwrap_or_default().to_uppercase()
+}
+
+// OrderClient over a PaperAccount, used for dry runs and when no credentials are configured
+pub struct PaperExecutionClient {
+    account: Mutex<PaperAccount>,
+    // Accepted orders by client order id, a resubmission gets the original back
+    submitted: Mutex<HashMap<String, Order>>,
+}
+
+impl PaperExecutionClient {
+    pub fn new(config: PaperTradingConfig, quote_asset: &str, initial_balance: f64) -> Self {
+        Self {
+            account: Mutex::new(PaperAccount::new(config, quote_asset, initial_balance)),
+            submitted: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Price-update hook, returns the ids of resting orders the price filled
+    pub fn record_price(&self, key: &InstrumentKey, price: f64) -> Vec<String> {
+        self.account.lock().record_price(key, price, Utc::now())
+    }
+
+    pub fn balances(&self) -> HashMap<String, f64> {
+        self.account.lock().balances()
+    }
+
+    pub fn fills(&self) -> Vec<PaperFill> {
+        self.account.lock().fills().to_vec()
+    }
+}
+
+impl From<PaperRejection> for UnindexedClientError {
+    fn from(rejection: PaperRejection) -> Self {
+        let error = match rejection {
+            PaperRejection::NoPrice(key) => {
+                ApiError::InstrumentInvalid(key.as_str().into(), "no price observed".to_string())
+            }
+            PaperRejection::BalanceInsufficient {
+                asset,
+                required,
+                available,
+            } => ApiError::BalanceInsufficient(
+                asset.as_str().into(),
+                format!("required {:.8}, available {:.8}", required, available),
+            ),
+            PaperRejection::WouldTake(key) => {
+                ApiError::OrderRejected(format!("post-only order for {} would take", key))
+            }
+            PaperRejection::UnknownOrder(id) => {
+                ApiError::OrderRejected(format!("unknown or already filled order {}", id))
+            }
+        };
+        UnindexedClientError::Api(error)
+    }
+}
+
+#[async_trait]
+impl OrderClient for PaperExecutionClient {
+    async fn open_order(
+        &self,
+        request: RequestOpen,
+    ) -> Result<Option<Order>, UnindexedClientError> {
+        if let Some(order) = request
+            .client_order_id
+            .as_ref()
+            .and_then(|cid| self.submitted.lock().get(cid).cloned())
+        {
+            return Ok(Some(order));
+        }
+
+        let key = InstrumentKey::new(&request.exchange, &request.instrument);
+        let buy = matches!(request.kind, OrderKind::Buy);
+        let order = match (&request.order_type, request.price) {
+            (OrderType::StopMarket, Some(trigger)) => {
+                self.account.lock().open_stop(&key, buy, request.quantity, trigger)?
+            }
+            (order_type, price) => {
+                let limit_price = match order_type {
+                    OrderType::Market => None,
+                    _ => price,
+                };
+                self.account.lock().open(
+                    &key,
+                    buy,
+                    request.quantity,
+                    limit_price,
+                    request.post_only,
+                    request.price,
+                )?
+            }
+        };
+
+        let order = Order {
+            id: OrderId::new(order.id),
+            instrument: request.instrument,
+            exchange: request.exchange,
+            kind: request.kind,
+            order_type: request.order_type,
+            quantity: order.quantity,
+            price: Some(order.price),
+            state: if order.filled {
+                OrderState::Filled
+            } else {
+                OrderState::Open
+            },
+        };
+        if let Some(cid) = request.client_order_id {
+            self.submitted.lock().insert(cid, order.clone());
+        }
+        Ok(Some(order))
+    }
+
+    async fn cancel_order(
+        &self,
+        request: RequestCancel,
+    ) -> Result<Option<Order>, UnindexedClientError> {
+        let order = self.account.lock().cancel(&request.id.to_string())?;
+
+        Ok(Some(Order {
+            id: request.id,
+            instrument: request.instrument,
+            exchange: request.exchange,
+            kind: OrderKind::Buy,
+            order_type: OrderType::Limit,
+            quantity: order.quantity,
+            price: Some(order.price),
+            state: OrderState::Cancelled,
+        }))
+    }
+
+    // A resting order no longer in the book either filled or was cancelled, its fill tells
+    async fn find_order(
+        &self,
+        client_order_id: &str,
+        _instrument: &str,
+        _exchange: &str,
+    ) -> Result<Option<Order>, UnindexedClientError> {
+        let Some(mut order) = self.submitted.lock().get(client_order_id).cloned() else {
+            return Ok(None);
+        };
+        let id = order.id.to_string();
+        let account = self.account.lock();
+        if order.state == OrderState::Open && !account.is_resting(&id) {
+            match account.fills().iter().find(|fill| fill.order_id == id) {
+                Some(fill) => {
+                    order.state = OrderState::Filled;
+                    order.quantity = fill.quantity;
+                    order.price = Some(fill.price);
+                }
+                None => order.state = OrderState::Cancelled,
+            }
+        }
+        Ok(Some(order))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "binance:BTC/USDT";
+
+    fn account() -> PaperAccount {
+        PaperAccount::new(
+            PaperTradingConfig {
+                fee_bps: 10.0,
+                slippage_bps: 5.0,
+            },
+            "USDT",
+            10_000.0,
+        )
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_market_buy_fills_with_slippage_and_fee() {
+        let mut account = account();
+        account.record_price(KEY, 20_000.0, Utc::now());
+
+        let order = account.open(KEY, true, 0.1, None, false, None).unwrap();
+        assert!(order.filled);
+        assert!(close(order.price, 20_010.0));
+
+        // 0.1 * 20010 = 2001 notional, 2.001 fee
+        assert!(close(account.balance("USDT"), 10_000.0 - 2_001.0 - 2.001));
+        assert!(close(account.balance("BTC"), 0.1));
+        let fill = &account.fills()[0];
+        assert!(close(fill.fee, 2.001));
+        assert_eq!(fill.order_id, order.id);
+    }
+
+    #[test]
+    fn test_round_trip_loses_fees_and_slippage() {
+        let mut account = account();
+        account.record_price(KEY, 20_000.0, Utc::now());
+        account.open(KEY, true, 0.1, None, false, None).unwrap();
+        account.record_price(KEY, 20_000.0, Utc::now());
+        let sell = account.open(KEY, false, 0.1, None, false, None).unwrap();
+
+        assert!(close(sell.price, 19_990.0));
+        assert!(close(account.balance("BTC"), 0.0));
+        // 2 slippage on the way in and out, plus 2.001 + 1.999 in fees
+        assert!(close(account.balance("USDT"), 10_000.0 - 2.0 - 4.0));
+    }
+
+    #[test]
+    fn test_market_order_needs_a_price() {
+        let mut account = account();
+        assert_eq!(
+            account.open(KEY, true, 0.1, None, false, None),
+            Err(PaperRejection::NoPrice(KEY.to_string()))
+        );
+        // The signal price stands in until the feed delivers one
+        assert!(account.open(KEY, true, 0.1, None, false, Some(20_000.0)).is_ok());
+    }
+
+    #[test]
+    fn test_buy_beyond_balance_is_rejected() {
+        let mut account = account();
+        account.record_price(KEY, 20_000.0, Utc::now());
+        let result = account.open(KEY, true, 1.0, None, false, None);
+        assert!(matches!(result, Err(PaperRejection::BalanceInsufficient { .. })));
+        assert!(account.fills().is_empty());
+        assert_eq!(account.balance("USDT"), 10_000.0);
+    }
+
+    #[test]
+    fn test_limit_order_rests_until_the_price_trades_through() {
+        let mut account = account();
+        account.record_price(KEY, 20_000.0, Utc::now());
+
+        let order = account.open(KEY, true, 0.1, Some(19_900.0), true, None).unwrap();
+        assert!(!order.filled);
+        assert!(account.record_price(KEY, 19_950.0, Utc::now()).is_empty());
+        assert_eq!(account.balance("USDT"), 10_000.0);
+
+        assert_eq!(account.record_price(KEY, 19_900.0, Utc::now()), vec![order.id.clone()]);
+        // Resting orders fill at their limit without slippage
+        assert!(close(account.fills()[0].price, 19_900.0));
+        assert!(close(account.balance("BTC"), 0.1));
+        assert!(matches!(account.cancel(&order.id), Err(PaperRejection::UnknownOrder(_))));
+    }
+
+    #[test]
+    fn test_post_only_rejects_a_marketable_limit() {
+        let mut account = account();
+        account.record_price(KEY, 20_000.0, Utc::now());
+        assert!(matches!(
+            account.open(KEY, true, 0.1, Some(20_100.0), true, None),
+            Err(PaperRejection::WouldTake(_))
+        ));
+
+        // Without post-only it takes at the touch, capped at the limit
+        let order = account.open(KEY, true, 0.1, Some(20_005.0), false, None).unwrap();
+        assert!(order.filled);
+        assert!(close(order.price, 20_005.0));
+    }
+
+    #[test]
+    fn test_stop_triggers_at_market_once_the_price_reaches_it() {
+        let mut account = account();
+        account.record_price(KEY, 20_000.0, Utc::now());
+        account.open(KEY, true, 0.1, None, false, None).unwrap();
+
+        let stop = account.open_stop(KEY, false, 0.1, 19_500.0).unwrap();
+        assert!(!stop.filled);
+        // A sell limit would have filled on the way up, the stop waits for the way down
+        assert!(account.record_price(KEY, 20_500.0, Utc::now()).is_empty());
+        assert_eq!(account.record_price(KEY, 19_400.0, Utc::now()), vec![stop.id.clone()]);
+        // Filled at the price that triggered it, less slippage
+        assert!(close(account.fills()[1].price, 19_400.0 - 9.7));
+        assert!(close(account.balance("BTC"), 0.0));
+
+        // Already beyond the trigger, it fills right away
+        assert!(account.open_stop(KEY, false, 0.1, 19_500.0).unwrap().filled);
+    }
+
+    #[test]
+    fn test_cancel_releases_a_resting_order() {
+        let mut account = account();
+        account.record_price(KEY, 20_000.0, Utc::now());
+        let order = account.open(KEY, false, 0.1, Some(20_500.0), false, None).unwrap();
+
+        assert_eq!(account.cancel(&order.id).unwrap().price, 20_500.0);
+        assert!(account.record_price(KEY, 21_000.0, Utc::now()).is_empty());
+        assert!(account.fills().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_orders_are_found_by_client_order_id() {
+        let client = PaperExecutionClient::new(PaperTradingConfig::default(), "USDT", 10_000.0);
+        let key = InstrumentKey::new("binance", "BTC/USDT");
+        client.record_price(&key, 20_000.0);
+        let request = |cid: &str| RequestOpen {
+            instrument: "BTC/USDT".to_string(),
+            exchange: "binance".to_string(),
+            client_order_id: Some(cid.to_string()),
+            kind: OrderKind::Buy,
+            order_type: OrderType::Limit,
+            quantity: 0.1,
+            price: Some(19_900.0),
+            time_in_force: None,
+            post_only: true,
+            reduce_only: false,
+        };
+        let find = |cid: &'static str| client.find_order(cid, "BTC/USDT", "binance");
+
+        assert!(find("unknown").await.unwrap().is_none());
+        client.open_order(request("filled")).await.unwrap();
+        client.open_order(request("cancelled")).await.unwrap();
+        assert_eq!(find("filled").await.unwrap().unwrap().state, OrderState::Open);
+
+        let cancelled = find("cancelled").await.unwrap().unwrap();
+        client
+            .cancel_order(RequestCancel {
+                id: cancelled.id,
+                instrument: "BTC/USDT".to_string(),
+                exchange: "binance".to_string(),
+            })
+            .await
+            .unwrap();
+        client.record_price(&key, 19_800.0);
+
+        let filled = find("filled").await.unwrap().unwrap();
+        assert_eq!(filled.state, OrderState::Filled);
+        assert_eq!(filled.price, Some(19_900.0));
+        assert_eq!(find("cancelled").await.unwrap().unwrap().state, OrderState::Cancelled);
+    }
+}
@@ -0,0 +1,253 @@
+use crate::{Position, PositionSide};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use monitor_core::{
+    storage::repository::{
+        PositionRecord, PositionRepository, TradeRecord, TradeRepository, TraderStateRepository,
+    },
+    MonitorError, Result,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TradeKind {
+    Entry,
+    Add,
+    Close,
+}
+
+impl TradeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TradeKind::Entry => "entry",
+            TradeKind::Add => "add",
+            TradeKind::Close => "close",
+        }
+    }
+}
+
+// An executed order, as recorded against the position it opened, grew or closed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExecutedTrade {
+    pub position_id: uuid::Uuid,
+    pub order_id: Option<String>,
+    pub kind: TradeKind,
+    pub quantity: f64,
+    pub price: f64,
+    pub realized_pnl: Option<f64>,
+    // Charged on this fill alone
+    pub fee: f64,
+    pub executed_at: DateTime<Utc>,
+}
+
+// Where positions and their trades are written so a restart can pick up open positions
+#[async_trait]
+pub trait PositionStore: Send + Sync {
+    async fn open(&self, position: &Position) -> Result<()>;
+    // Price, size and stop changes of an open position
+    async fn update(&self, position: &Position) -> Result<()>;
+    // `position` carries its realized PnL and `closed_at`
+    async fn close(&self, position: &Position) -> Result<()>;
+    async fn record_trade(&self, position: &Position, trade: &ExecutedTrade) -> Result<()>;
+    async fn load_open(&self) -> Result<Vec<Position>>;
+
+    // Named state documents, such as the circuit breaker's halt. Stores without them keep
+    // nothing and restore nothing
+    async fn save_state(&self, _name: &str, _state: &serde_json::Value) -> Result<()> {
+        Ok(())
+    }
+
+    async fn load_state(&self, _name: &str) -> Result<Option<serde_json::Value>> {
+        Ok(None)
+    }
+}
+
+pub struct PgPositionStore {
+    positions: PositionRepository,
+    trades: TradeRepository,
+    state: TraderStateRepository,
+}
+
+impl PgPositionStore {
+    pub fn new(
+        positions: PositionRepository,
+        trades: TradeRepository,
+        state: TraderStateRepository,
+    ) -> Self {
+        Self {
+            positions,
+            trades,
+            state,
+        }
+    }
+}
+
+#[async_trait]
+impl PositionStore for PgPositionStore {
+    async fn open(&self, position: &Position) -> Result<()> {
+        self.positions.insert(&to_record(position)).await
+    }
+
+    async fn update(&self, position: &Position) -> Result<()> {
+        self.positions.update(&to_record(position)).await?;
+        Ok(())
+    }
+
+    async fn close(&self, position: &Position) -> Result<()> {
+        if self.positions.update(&to_record(position)).await? == 0 {
+            return Err(MonitorError::Other(format!(
+                "Position {} is not open in the database",
+                position.id
+            )));
+        }
+        Ok(())
+    }
+
+    async fn record_trade(&self, position: &Position, trade: &ExecutedTrade) -> Result<()> {
+        // Entries buy a long, closes sell it
+        let buy = (position.side == PositionSide::Long) != (trade.kind == TradeKind::Close);
+        self.trades
+            .insert(&TradeRecord {
+                id: uuid::Uuid::new_v4(),
+                position_id: trade.position_id,
+                order_id: trade.order_id.clone(),
+                exchange: position.exchange.clone(),
+                symbol: position.symbol.clone(),
+                side: if buy { "buy" } else { "sell" }.to_string(),
+                kind: trade.kind.as_str().to_string(),
+                quantity: trade.quantity,
+                price: trade.price,
+                realized_pnl: trade.realized_pnl,
+                fee: trade.fee,
+                executed_at: trade.executed_at,
+            })
+            .await
+    }
+
+    async fn load_open(&self) -> Result<Vec<Position>> {
+        self.positions
+            .find_open()
+            .await?
+            .into_iter()
+            .map(from_record)
+            .collect()
+    }
+
+    async fn save_state(&self, name: &str, state: &serde_json::Value) -> Result<()> {
+        self.state.save(name, Utc::now(), &state.to_string()).await
+    }
+
+    async fn load_state(&self, name: &str) -> Result<Option<serde_json::Value>> {
+        match self.state.load(name).await? {
+            Some(state) => Ok(Some(serde_json::from_str(&state)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+pub fn to_record(position: &Position) -> PositionRecord {
+    PositionRecord {
+        id: position.id,
+        exchange: position.exchange.clone(),
+        symbol: position.symbol.clone(),
+        side: match position.side {
+            PositionSide::Long => "long",
+            PositionSide::Short => "short",
+        }
+        .to_string(),
+        quantity: position.quantity,
+        entry_price: position.entry_price,
+        current_price: position.current_price,
+        unrealized_pnl: position.unrealized_pnl,
+        realized_pnl: position.realized_pnl,
+        stop_loss: position.stop_loss,
+        take_profit: position.take_profit,
+        status: if position.closed_at.is_some() { "closed" } else { "open" }.to_string(),
+        opened_at: position.opened_at,
+        closed_at: position.closed_at,
+        adds: position.adds as i32,
+        stop_order_id: position.stop_order_id.clone(),
+        take_profit_order_id: position.take_profit_order_id.clone(),
+        fees: position.fees,
+    }
+}
+
+pub fn from_record(record: PositionRecord) -> Result<Position> {
+    let side = match record.side.as_str() {
+        "long" => PositionSide::Long,
+        "short" => PositionSide::Short,
+        other => {
+            return Err(MonitorError::Other(format!(
+                "Position {} has unknown side {}",
+                record.id, other
+            )))
+        }
+    };
+    Ok(Position {
+        id: record.id,
+        symbol: record.symbol,
+        exchange: record.exchange,
+        side,
+        quantity: record.quantity,
+        entry_price: record.entry_price,
+        current_price: record.current_price,
+        unrealized_pnl: record.unrealized_pnl,
+        realized_pnl: record.realized_pnl,
+        stop_loss: record.stop_loss,
+        take_profit: record.take_profit,
+        opened_at: record.opened_at,
+        closed_at: record.closed_at,
+        stale: false,
+        adds: record.adds.max(0) as u32,
+        stop_order_id: record.stop_order_id,
+        take_profit_order_id: record.take_profit_order_id,
+        fees: record.fees,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_round_trip() {
+        let position = Position {
+            id: uuid::Uuid::new_v4(),
+            symbol: "ETH/USDT".to_string(),
+            exchange: "okx".to_string(),
+            side: PositionSide::Short,
+            quantity: 0.5,
+            entry_price: 3_000.0,
+            current_price: 2_950.0,
+            unrealized_pnl: 25.0,
+            realized_pnl: 0.0,
+            stop_loss: Some(3_090.0),
+            take_profit: None,
+            opened_at: Utc::now(),
+            closed_at: None,
+            stale: true,
+            adds: 1,
+            stop_order_id: Some("s-1".to_string()),
+            take_profit_order_id: None,
+            fees: 1.5,
+        };
+
+        let record = to_record(&position);
+        assert_eq!(record.side, "short");
+        assert_eq!(record.status, "open");
+        let restored = from_record(record).unwrap();
+        assert_eq!(restored.id, position.id);
+        assert_eq!(restored.side, PositionSide::Short);
+        assert_eq!(restored.stop_loss, Some(3_090.0));
+        assert_eq!(restored.adds, 1);
+        assert_eq!(restored.stop_order_id.as_deref(), Some("s-1"));
+        assert_eq!(restored.fees, 1.5);
+        // Staleness is rebuilt from the live feed, not restored
+        assert!(!restored.stale);
+
+        let mut record = to_record(&position);
+        record.side = "flat".to_string();
+        assert!(from_record(record).is_err());
+    }
+}
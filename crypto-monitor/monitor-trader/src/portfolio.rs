@@ -0,0 +1,131 @@
+use crate::Position;
+use barter_execution::{balance::AssetBalance, AccountEventKind, UnindexedAccountEvent};
+use barter_instrument::asset::name::AssetNameExchange;
+use rust_decimal::prelude::ToPrimitive;
+use tracing::info;
+
+// Cash balance moved by realized PnL and resynchronized from exchange balances
+#[derive(Debug, Clone)]
+pub struct PortfolioAccount {
+    balance: f64,
+}
+
+impl PortfolioAccount {
+    pub fn new(initial_value: f64) -> Self {
+        Self {
+            balance: initial_value,
+        }
+    }
+
+    pub fn balance(&self) -> f64 {
+        self.balance
+    }
+
+    pub fn apply_realized(&mut self, pnl: f64) {
+        self.balance += pnl;
+    }
+
+    // The exchange is authoritative, its balance replaces whatever was accumulated locally
+    pub fn sync(&mut self, balance: f64) {
+        if (balance - self.balance).abs() > f64::EPSILON {
+            info!("Portfolio value resynchronized from {:.2} to {:.2}", self.balance, balance);
+        }
+        self.balance = balance;
+    }
+
+    // Value used for sizing, optionally including what open positions are worth at their mark
+    pub fn value<'a>(
+        &self,
+        open_positions: impl IntoIterator<Item = &'a Position>,
+        mark_to_market: bool,
+    ) -> f64 {
+        if !mark_to_market {
+            return self.balance;
+        }
+        self.balance
+            + open_positions
+                .into_iter()
+                .map(|position| position.unrealized_pnl)
+                .sum::<f64>()
+    }
+}
+
+// Total balance of `quote_asset` carried by an account event, `None` when it carries none
+pub fn quote_balance(event: &UnindexedAccountEvent, quote_asset: &str) -> Option<f64> {
+    match &event.kind {
+        AccountEventKind::Snapshot(snapshot) => balance_of(&snapshot.balances, quote_asset),
+        AccountEventKind::BalanceSnapshot(snapshot) => {
+            balance_of(std::slice::from_ref(&snapshot.0), quote_asset)
+        }
+        _ => None,
+    }
+}
+
+pub fn balance_of(balances: &[AssetBalance<AssetNameExchange>], quote_asset: &str) -> Option<f64> {
+    balances
+        .iter()
+        .find(|balance| balance.asset.as_ref().eq_ignore_ascii_case(quote_asset))
+        .and_then(|balance| balance.balance.total.to_f64())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fixtures, risk::SimpleRiskManager, PositionSide, RiskManager};
+    use barter_execution::balance::Balance;
+    use chrono::Utc;
+    use rust_decimal::Decimal;
+
+    fn position(unrealized_pnl: f64) -> Position {
+        Position {
+            unrealized_pnl,
+            ..fixtures::position("BTC/USDT", PositionSide::Long, 0.1, 50_000.0)
+        }
+    }
+
+    fn asset_balance(asset: &str, total: i64) -> AssetBalance<AssetNameExchange> {
+        AssetBalance::new(
+            AssetNameExchange::from(asset),
+            Balance::new(Decimal::new(total, 0), Decimal::new(total, 0)),
+            Utc::now(),
+        )
+    }
+
+    #[test]
+    fn test_losing_trade_shrinks_the_next_position() {
+        let config = fixtures::trading_config(serde_json::json!({ "max_position_size": 100000.0 }));
+        let risk = SimpleRiskManager::new(config);
+        let mut account = PortfolioAccount::new(10_000.0);
+        let signal = fixtures::signal("BTC/USDT", 50_000.0);
+
+        let before = risk.calculate_position_size(&signal, account.value([], false));
+        account.apply_realized(-1_000.0);
+        let after = risk.calculate_position_size(&signal, account.value([], false));
+
+        assert_eq!(account.balance(), 9_000.0);
+        assert!(after < before);
+        assert!((after / before - 0.9).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_mark_to_market_adds_unrealized_pnl() {
+        let account = PortfolioAccount::new(10_000.0);
+        let open = [position(250.0), position(-50.0)];
+
+        assert_eq!(account.value(&open, false), 10_000.0);
+        assert_eq!(account.value(&open, true), 10_200.0);
+    }
+
+    #[test]
+    fn test_exchange_balance_replaces_local_accounting() {
+        let mut account = PortfolioAccount::new(10_000.0);
+        account.apply_realized(300.0);
+
+        let balances = [asset_balance("BTC", 2), asset_balance("usdt", 9_800)];
+        let reported = balance_of(&balances, "USDT").unwrap();
+        account.sync(reported);
+
+        assert_eq!(account.balance(), 9_800.0);
+        assert_eq!(balance_of(&balances, "ETH"), None);
+    }
+}
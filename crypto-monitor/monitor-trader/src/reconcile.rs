@@ -0,0 +1,144 @@
+use crate::{Position, PositionSide};
+use async_trait::async_trait;
+use monitor_core::{instrument::InstrumentKey, Result};
+use serde::{Deserialize, Serialize};
+
+// A position as the exchange account reports it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VenuePosition {
+    pub exchange: String,
+    pub symbol: String,
+    pub side: PositionSide,
+    pub quantity: f64,
+    // Average entry, 0 when the exchange does not report one
+    pub entry_price: f64,
+}
+
+// The exchange's view of the open positions, e.g. from an account query or the last account
+// snapshot. The exchange is authoritative when it disagrees with the local positions
+#[async_trait]
+pub trait AccountSource: Send + Sync {
+    async fn fetch_positions(&self) -> Result<Vec<VenuePosition>>;
+
+    // Whether the account holds the positions of `exchange`. Those of any other exchange are
+    // left out of reconciliation rather than taken for phantoms
+    fn covers(&self, _exchange: &str) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Discrepancy {
+    // Open locally but gone from the exchange, e.g. an exit filled while the monitor was down
+    Phantom { position: Position },
+    // Held on the exchange with no local position
+    Unknown { venue: VenuePosition },
+    // Held on both sides in different sizes
+    Mismatch { position: Position, venue: VenuePosition },
+}
+
+impl Discrepancy {
+    pub fn key(&self) -> InstrumentKey {
+        match self {
+            Discrepancy::Phantom { position } | Discrepancy::Mismatch { position, .. } => {
+                InstrumentKey::new(&position.exchange, &position.symbol)
+            }
+            Discrepancy::Unknown { venue } => InstrumentKey::new(&venue.exchange, &venue.symbol),
+        }
+    }
+
+    // What reconciling it does to the local side
+    pub fn resolution(&self) -> &'static str {
+        match self {
+            Discrepancy::Phantom { .. } => "closed_locally",
+            Discrepancy::Unknown { .. } => "adopted",
+            Discrepancy::Mismatch { .. } => "resized",
+        }
+    }
+}
+
+// Where `local` and `venue` disagree, phantoms first. A position held on the other side of
+// the market on the exchange is a phantom plus an unknown one
+pub fn diff(local: &[Position], venue: &[VenuePosition], tolerance_pct: f64) -> Vec<Discrepancy> {
+    let same = |position: &Position, held: &VenuePosition| {
+        position.exchange == held.exchange
+            && position.symbol == held.symbol
+            && position.side == held.side
+    };
+
+    let mut phantoms = Vec::new();
+    let mut others = Vec::new();
+    for position in local {
+        match venue.iter().find(|held| same(position, held)) {
+            None => phantoms.push(Discrepancy::Phantom {
+                position: position.clone(),
+            }),
+            Some(held) if !same_size(position.quantity, held.quantity, tolerance_pct) => {
+                others.push(Discrepancy::Mismatch {
+                    position: position.clone(),
+                    venue: held.clone(),
+                })
+            }
+            Some(_) => {}
+        }
+    }
+    for held in venue {
+        if held.quantity > 0.0 && !local.iter().any(|position| same(position, held)) {
+            others.push(Discrepancy::Unknown { venue: held.clone() });
+        }
+    }
+
+    phantoms.extend(others);
+    phantoms
+}
+
+fn same_size(local: f64, venue: f64, tolerance_pct: f64) -> bool {
+    (local - venue).abs() <= local.abs().max(venue.abs()) * tolerance_pct / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::position;
+
+    fn held(symbol: &str, side: PositionSide, quantity: f64) -> VenuePosition {
+        VenuePosition {
+            exchange: "binance".to_string(),
+            symbol: symbol.to_string(),
+            side,
+            quantity,
+            entry_price: 100.0,
+        }
+    }
+
+    #[test]
+    fn test_diff_finds_each_kind_of_discrepancy() {
+        let local = vec![
+            position("BTC/USDT", PositionSide::Long, 1.0, 100.0),
+            position("ETH/USDT", PositionSide::Long, 2.0, 100.0),
+            position("SOL/USDT", PositionSide::Long, 10.0, 100.0),
+            position("XRP/USDT", PositionSide::Long, 5.0, 100.0),
+        ];
+        let venue = vec![
+            // Within the tolerance
+            held("BTC/USDT", PositionSide::Long, 1.004),
+            held("ETH/USDT", PositionSide::Long, 1.0),
+            held("XRP/USDT", PositionSide::Short, 5.0),
+            held("DOGE/USDT", PositionSide::Short, 100.0),
+            held("ADA/USDT", PositionSide::Long, 0.0),
+        ];
+
+        let found = diff(&local, &venue, 0.5);
+        assert_eq!(found.len(), 5);
+        // SOL is gone and XRP flipped sides, both phantoms ahead of the rest
+        assert!(matches!(&found[0], Discrepancy::Phantom { position } if position.symbol == "SOL/USDT"));
+        assert!(matches!(&found[1], Discrepancy::Phantom { position } if position.symbol == "XRP/USDT"));
+        assert_eq!(found[1].resolution(), "closed_locally");
+        assert!(matches!(&found[2], Discrepancy::Mismatch { venue, .. } if venue.quantity == 1.0));
+        assert!(matches!(&found[3], Discrepancy::Unknown { venue } if venue.symbol == "XRP/USDT"));
+        assert!(matches!(&found[4], Discrepancy::Unknown { venue } if venue.symbol == "DOGE/USDT"));
+
+        assert!(diff(&local[..1], &venue[..1], 0.5).is_empty());
+    }
+}
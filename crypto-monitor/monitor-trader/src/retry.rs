@@ -0,0 +1,317 @@
+use crate::{Position, PositionSide, TradingSignal};
+use barter_execution::error::{ApiError, UnindexedClientError};
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use monitor_core::{MonitorError, OrderRetryConfig, Result};
+use serde::{Deserialize, Serialize};
+use std::{future::Future, path::PathBuf, time::Duration};
+use tracing::{error, info, warn};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    pub fn from_config(config: &OrderRetryConfig) -> Self {
+        Self {
+            max_attempts: config.max_attempts.max(1),
+            base_delay: Duration::from_millis(config.base_delay_ms),
+            max_delay: Duration::from_millis(config.max_delay_ms),
+        }
+    }
+
+    // Backoff after failed attempt `attempt` (1-based), `jitter` in [0, 1) spreads it over
+    // the upper half of the exponential step so concurrent retries do not line up
+    pub fn delay(&self, attempt: u32, jitter: f64) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(16);
+        let step = self.base_delay.saturating_mul(1 << exponent).min(self.max_delay);
+        step.mul_f64(0.5 + 0.5 * jitter.clamp(0.0, 1.0))
+    }
+}
+
+// Failures where the venue may not have seen the request, or asked us to come back later.
+// Rejections are final, resubmitting them would only be rejected again
+pub fn is_retryable(error: &UnindexedClientError) -> bool {
+    matches!(
+        error,
+        UnindexedClientError::Connectivity(_) | UnindexedClientError::Api(ApiError::RateLimit)
+    )
+}
+
+// Runs `attempt` until it succeeds, fails with a non-retryable error or runs out of attempts.
+// Every attempt must reuse the same client order id so the venue can deduplicate them
+pub async fn with_retry<T, F, Fut>(
+    policy: &RetryPolicy,
+    client_order_id: &str,
+    mut attempt: F,
+) -> std::result::Result<T, UnindexedClientError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = std::result::Result<T, UnindexedClientError>>,
+{
+    let mut tried = 0;
+    loop {
+        tried += 1;
+        match attempt().await {
+            Ok(value) => return Ok(value),
+            Err(e) if tried < policy.max_attempts && is_retryable(&e) => {
+                let delay = policy.delay(tried, jitter());
+                warn!(
+                    "Order {} attempt {}/{} failed, retrying in {:?}: {}",
+                    client_order_id, tried, policy.max_attempts, delay, e
+                );
+                tokio::time::sleep(delay).await;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+// No rand dependency, the sub-second clock is random enough to decorrelate retries
+fn jitter() -> f64 {
+    f64::from(Utc::now().timestamp_subsec_nanos() % 1_000) / 1_000.0
+}
+
+// Client order ids are derived from what they submit, so a retry or a resubmission after a
+// restart carries the same id as the original. Kept within the 36 characters venues accept
+pub fn entry_order_id(signal: &TradingSignal) -> String {
+    format!("e-{}", signal.id.simple())
+}
+
+pub fn limit_order_id(signal: &TradingSignal, replacements: u32) -> String {
+    format!("l-{}-{}", signal.id.simple(), replacements)
+}
+
+pub fn clip_order_id(signal: &TradingSignal, clip: u32) -> String {
+    format!("c-{}-{}", signal.id.simple(), clip)
+}
+
+pub fn close_order_id(position: &Position) -> String {
+    format!("x-{}", position.id.simple())
+}
+
+// Exit orders are never resubmitted after a restart, each placement gets an id of its own.
+// Retries of one placement still share it
+pub fn exit_order_id(prefix: &str) -> String {
+    format!("{}-{}", prefix, uuid::Uuid::new_v4().simple())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum IntentKind {
+    Entry {
+        signal: TradingSignal,
+        strategy: String,
+        side: PositionSide,
+        quantity: f64,
+        // Set for resting limit entries
+        limit_price: Option<f64>,
+        replacements: u32,
+        // Set for the clips of a DCA entry, counted from 1
+        #[serde(default)]
+        clip: Option<u32>,
+    },
+    Close {
+        position: Position,
+    },
+}
+
+// An order submitted to the venue whose outcome we have not seen yet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderIntent {
+    pub client_order_id: String,
+    pub key: String,
+    pub kind: IntentKind,
+    pub created_at: DateTime<Utc>,
+}
+
+// In-flight intents keyed by client order id. An intent outlives its submission only when
+// the outcome stayed ambiguous, and is persisted so a restart can reconcile it
+#[derive(Default)]
+pub struct OrderIntents {
+    intents: DashMap<String, OrderIntent>,
+    path: Option<PathBuf>,
+}
+
+impl OrderIntents {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Restores intents left behind by a previous run and persists every change to `path`
+    pub fn with_path(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let intents = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice::<Vec<OrderIntent>>(&bytes)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => {
+                return Err(MonitorError::Other(format!(
+                    "Failed to read order intents {}: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+        if !intents.is_empty() {
+            info!("Restored {} in-flight order intents", intents.len());
+        }
+
+        Ok(Self {
+            intents: intents
+                .into_iter()
+                .map(|intent| (intent.client_order_id.clone(), intent))
+                .collect(),
+            path: Some(path),
+        })
+    }
+
+    pub fn record(&self, intent: OrderIntent) {
+        self.intents.insert(intent.client_order_id.clone(), intent);
+        self.persist();
+    }
+
+    pub fn complete(&self, client_order_id: &str) {
+        if self.intents.remove(client_order_id).is_some() {
+            self.persist();
+        }
+    }
+
+    pub fn get(&self, client_order_id: &str) -> Option<OrderIntent> {
+        self.intents.get(client_order_id).map(|intent| intent.clone())
+    }
+
+    // Oldest first
+    pub fn all(&self) -> Vec<OrderIntent> {
+        let mut intents: Vec<OrderIntent> = self.intents.iter().map(|i| i.clone()).collect();
+        intents.sort_by_key(|intent| intent.created_at);
+        intents
+    }
+
+    pub fn has_pending(&self, key: &str) -> bool {
+        self.intents.iter().any(|intent| intent.key == key)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intents.is_empty()
+    }
+
+    fn persist(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+
+        // Write then rename so a crash never leaves a truncated file behind
+        let tmp = path.with_extension("tmp");
+        let write = || -> std::io::Result<()> {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&tmp, serde_json::to_vec(&self.all())?)?;
+            std::fs::rename(&tmp, path)
+        };
+        if let Err(e) = write() {
+            error!("Failed to write order intents {}: {}", path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::signal;
+    use barter_execution::error::ConnectivityError;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(4),
+        }
+    }
+
+    fn intent(signal: TradingSignal) -> OrderIntent {
+        OrderIntent {
+            client_order_id: entry_order_id(&signal),
+            key: "binance:BTC/USDT".to_string(),
+            kind: IntentKind::Entry {
+                signal,
+                strategy: "default".to_string(),
+                side: PositionSide::Long,
+                quantity: 0.1,
+                limit_price: None,
+                replacements: 0,
+                clip: None,
+            },
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_delay_doubles_up_to_the_cap() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(1_000),
+        };
+
+        assert_eq!(policy.delay(1, 0.999_999).as_millis(), 99);
+        assert_eq!(policy.delay(2, 0.0), Duration::from_millis(100));
+        assert_eq!(policy.delay(3, 0.0), Duration::from_millis(200));
+        assert_eq!(policy.delay(8, 0.0), Duration::from_millis(500));
+        assert_eq!(policy.delay(60, 1.0), Duration::from_millis(1_000));
+    }
+
+    #[tokio::test]
+    async fn test_retries_only_retryable_errors() {
+        let mut calls = 0;
+        let result: std::result::Result<(), _> = with_retry(&policy(), "e-1", || {
+            calls += 1;
+            async { Err(UnindexedClientError::Connectivity(ConnectivityError::Timeout)) }
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(calls, 3);
+
+        let mut calls = 0;
+        let result: std::result::Result<(), _> = with_retry(&policy(), "e-2", || {
+            calls += 1;
+            async { Err(UnindexedClientError::Api(ApiError::OrderRejected("no".to_string()))) }
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_order_ids_are_stable_and_fit_venue_limits() {
+        let signal = signal("BTC/USDT", 50_000.0);
+        assert_eq!(entry_order_id(&signal), entry_order_id(&signal.clone()));
+        assert_ne!(limit_order_id(&signal, 0), limit_order_id(&signal, 1));
+        assert!(limit_order_id(&signal, 9).len() <= 36);
+        assert_ne!(clip_order_id(&signal, 1), clip_order_id(&signal, 2));
+        assert!(clip_order_id(&signal, 99).len() <= 36);
+    }
+
+    #[test]
+    fn test_intents_survive_a_restart() {
+        let path = std::env::temp_dir().join(format!("order-intents-{}.json", uuid::Uuid::new_v4()));
+        let first = intent(signal("BTC/USDT", 50_000.0));
+        let second = intent(signal("BTC/USDT", 50_000.0));
+
+        let intents = OrderIntents::with_path(&path).unwrap();
+        intents.record(first.clone());
+        intents.record(second.clone());
+        intents.complete(&second.client_order_id);
+
+        let restored = OrderIntents::with_path(&path).unwrap();
+        let all = restored.all();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].client_order_id, first.client_order_id);
+        assert!(restored.get(&second.client_order_id).is_none());
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
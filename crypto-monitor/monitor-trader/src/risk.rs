@@ -1,65 +1,456 @@
-use crate::{PositionSide, RiskManager, TradingSignal};
-use monitor_core::TradingConfig;
+use crate::{
+    costs::{FeeModel, Liquidity, SlippageModel},
+    overrides::MarketConfig,
+    volatility::VolatilityTargetRiskManager,
+    Position, PositionSide, RiskManager, SignalStrength, SignalType, TradingSignal,
+};
+use monitor_core::{OrderPlacementMode, PositionSizingConfig, RiskManagerKind, TradingConfig};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+// Which per-order limit an order would breach, journaled with the rejection
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "limit", rename_all = "snake_case")]
+pub enum OrderRejection {
+    MaxPositionSize { value: f64, max: f64 },
+    RiskAmount { value: f64, max: f64 },
+}
+
+// Which portfolio limit an order would breach, journaled with the rejection
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "limit", rename_all = "snake_case")]
+pub enum ExposureRejection {
+    MaxOpenPositions { open: usize, limit: usize },
+    SymbolExposure { symbol: String, exposure: f64, limit: f64 },
+    TotalExposure { exposure: f64, limit: f64 },
+}
+
+// An entry submitted but not filled yet, a working limit order or a submission whose outcome
+// is unknown. It counts against the portfolio limits as if it had filled
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingEntry {
+    pub exchange: String,
+    pub symbol: String,
+    pub notional: f64,
+}
+
+// Notional value of an open position at its last mark
+fn notional(position: &Position) -> f64 {
+    position.quantity * position.current_price
+}
+
+// The share of the base risk a signal of `strength` takes
+pub fn strength_multiplier(config: &PositionSizingConfig, strength: &SignalStrength) -> f64 {
+    match strength {
+        SignalStrength::Weak => config.weak_multiplier,
+        SignalStrength::Medium => config.medium_multiplier,
+        SignalStrength::Strong => config.strong_multiplier,
+    }
+}
+
+// Quote amount a signal may lose at its stop
+fn risk_amount(config: &MarketConfig, signal: &TradingSignal, portfolio_value: f64) -> f64 {
+    portfolio_value
+        * (config.risk_percentage() / 100.0)
+        * strength_multiplier(&config.base.sizing, &signal.strength)
+}
+
+// The portfolio limit an order of `order_value` on `signal`'s market would breach. Pending
+// entries count as open, an add to a position already open does not open another
+pub(crate) fn check_exposure(
+    config: &TradingConfig,
+    signal: &TradingSignal,
+    order_value: f64,
+    open_positions: &[Position],
+    pending: &[PendingEntry],
+) -> Option<ExposureRejection> {
+    let is_open = |exchange: &str, symbol: &str| {
+        open_positions
+            .iter()
+            .any(|position| position.exchange == exchange && position.symbol == symbol)
+    };
+    if let Some(limit) = config.max_open_positions {
+        let pending_markets: HashSet<(&str, &str)> = pending
+            .iter()
+            .filter(|entry| !is_open(&entry.exchange, &entry.symbol))
+            .map(|entry| (entry.exchange.as_str(), entry.symbol.as_str()))
+            .collect();
+        let open = open_positions.len() + pending_markets.len();
+        if open >= limit && !is_open(&signal.exchange, &signal.symbol) {
+            return Some(ExposureRejection::MaxOpenPositions { open, limit });
+        }
+    }
+    
+    if let Some(limit) = config.max_exposure_per_symbol {
+        let exposure = order_value
+            + open_positions
+                .iter()
+                .filter(|position| position.symbol == signal.symbol)
+                .map(notional)
+                .sum::<f64>()
+            + pending
+                .iter()
+                .filter(|entry| entry.symbol == signal.symbol)
+                .map(|entry| entry.notional)
+                .sum::<f64>();
+        if exposure > limit {
+            return Some(ExposureRejection::SymbolExposure {
+                symbol: signal.symbol.clone(),
+                exposure,
+                limit,
+            });
+        }
+    }
+    
+    if let Some(limit) = config.max_total_exposure {
+        let exposure = order_value
+            + open_positions.iter().map(notional).sum::<f64>()
+            + pending.iter().map(|entry| entry.notional).sum::<f64>();
+        if exposure > limit {
+            return Some(ExposureRejection::TotalExposure { exposure, limit });
+        }
+    }
+    
+    None
+}
+
+// The risk manager picked by `trading.risk_manager`
+pub fn build(kind: RiskManagerKind, config: &TradingConfig) -> Box<dyn RiskManager> {
+    match kind {
+        RiskManagerKind::Simple => Box::new(SimpleRiskManager::new(config.clone())),
+        RiskManagerKind::VolatilityTarget => {
+            Box::new(VolatilityTargetRiskManager::new(config.clone()))
+        }
+    }
+}
 
 pub struct SimpleRiskManager {
-    config: TradingConfig,
+    config: RwLock<TradingConfig>,
 }
 
 impl SimpleRiskManager {
     pub fn new(config: TradingConfig) -> Self {
-        Self { config }
+        Self {
+            config: RwLock::new(config),
+        }
+    }
+    
+    // Sizing and exit distances come from the signal's market, portfolio limits stay global
+    fn market_config<'a>(config: &'a TradingConfig, signal: &TradingSignal) -> MarketConfig<'a> {
+        MarketConfig::new(config, &signal.exchange, &signal.symbol)
     }
 }
 
 impl RiskManager for SimpleRiskManager {
-    fn validate_order(&self, signal: &TradingSignal, portfolio_value: f64) -> bool {
+    fn validate_order(
+        &self,
+        signal: &TradingSignal,
+        portfolio_value: f64,
+    ) -> Option<OrderRejection> {
         // Check if position size is within limits
         let position_value = signal.price * self.calculate_position_size(signal, portfolio_value);
+        let config = self.config.read();
+        let config = Self::market_config(&config, signal);
         
-        if position_value > self.config.max_position_size {
-            return false;
+        if position_value > config.max_position_size() {
+            return Some(OrderRejection::MaxPositionSize {
+                value: position_value,
+                max: config.max_position_size(),
+            });
         }
         
         // Check risk percentage
-        let risk_amount = portfolio_value * (self.config.risk_percentage / 100.0);
+        let risk_amount = risk_amount(&config, signal, portfolio_value);
         if position_value > risk_amount {
-            return false;
+            return Some(OrderRejection::RiskAmount {
+                value: position_value,
+                max: risk_amount,
+            });
         }
         
-        true
+        None
+    }
+    
+    fn validate_portfolio(
+        &self,
+        signal: &TradingSignal,
+        open_positions: &[Position],
+        pending: &[PendingEntry],
+        portfolio_value: f64,
+    ) -> Option<ExposureRejection> {
+        let order_value = signal.price * self.calculate_position_size(signal, portfolio_value);
+        check_exposure(&self.config.read(), signal, order_value, open_positions, pending)
     }
     
+    // Sized so that stopping out loses `risk_percentage`, scaled by the signal's strength and
+    // counting the expected slippage on both fills and the fees on both legs
     fn calculate_position_size(&self, signal: &TradingSignal, portfolio_value: f64) -> f64 {
-        let risk_amount = portfolio_value * (self.config.risk_percentage / 100.0);
-        let stop_loss_distance = signal.price * (self.config.stop_loss_percentage / 100.0);
+        let config = self.config.read();
+        let config = Self::market_config(&config, signal);
+        let risk_amount = risk_amount(&config, signal, portfolio_value);
+        let stop_loss = config.stop_loss_percentage() / 100.0;
+        if stop_loss <= 0.0 {
+            return 0.0;
+        }
         
-        if stop_loss_distance > 0.0 {
-            let position_size = risk_amount / stop_loss_distance;
-            position_size.min(self.config.max_position_size / signal.price)
+        let fees = FeeModel::from_config(&config.base.costs);
+        let slippage = SlippageModel::from_config(&config.base.costs);
+        let buy = !matches!(signal.signal_type, SignalType::Sell);
+        let (entry, liquidity) = match config.base.order_placement.mode {
+            OrderPlacementMode::Market => {
+                (slippage.apply(signal.price, buy, &signal.strength), Liquidity::Taker)
+            }
+            OrderPlacementMode::Limit | OrderPlacementMode::PostOnly => {
+                (signal.price, Liquidity::Maker)
+            }
+        };
+        let stop = if buy {
+            entry * (1.0 - stop_loss)
         } else {
-            0.0
-        }
+            entry * (1.0 + stop_loss)
+        };
+        // The stop goes out at market and slips past its level
+        let exit = slippage.exit_price(stop, !buy);
+        let loss_per_unit = (entry - exit).abs()
+            + fees.fee(&signal.exchange, entry, liquidity)
+            + fees.fee(&signal.exchange, exit, Liquidity::Taker);
+        
+        let position_size = risk_amount / loss_per_unit;
+        position_size.min(config.max_position_size() / signal.price)
     }
     
-    fn get_stop_loss(&self, entry_price: f64, side: PositionSide) -> f64 {
+    fn get_stop_loss(&self, signal: &TradingSignal, entry_price: f64, side: PositionSide) -> f64 {
+        let stop_loss_percentage =
+            Self::market_config(&self.config.read(), signal).stop_loss_percentage();
         match side {
             PositionSide::Long => {
-                entry_price * (1.0 - self.config.stop_loss_percentage / 100.0)
+                entry_price * (1.0 - stop_loss_percentage / 100.0)
             }
             PositionSide::Short => {
-                entry_price * (1.0 + self.config.stop_loss_percentage / 100.0)
+                entry_price * (1.0 + stop_loss_percentage / 100.0)
             }
         }
     }
     
-    fn get_take_profit(&self, entry_price: f64, side: PositionSide) -> f64 {
+    fn get_take_profit(&self, signal: &TradingSignal, entry_price: f64, side: PositionSide) -> f64 {
+        let take_profit_percentage =
+            Self::market_config(&self.config.read(), signal).take_profit_percentage();
         match side {
             PositionSide::Long => {
-                entry_price * (1.0 + self.config.take_profit_percentage / 100.0)
+                entry_price * (1.0 + take_profit_percentage / 100.0)
             }
             PositionSide::Short => {
-                entry_price * (1.0 - self.config.take_profit_percentage / 100.0)
+                entry_price * (1.0 - take_profit_percentage / 100.0)
             }
         }
     }
-}
\ No newline at end of file
+    
+    fn update_config(&self, config: TradingConfig) {
+        *self.config.write() = config;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    // 2% of a 10k portfolio with a 3% stop sizes every order at ~6667 notional
+    const ORDER_VALUE: f64 = 10_000.0 * 0.02 / 0.03;
+
+    fn manager(limits: serde_json::Value) -> SimpleRiskManager {
+        let mut extra = serde_json::json!({ "max_position_size": 100000.0 });
+        extra.as_object_mut().unwrap().extend(limits.as_object().unwrap().clone());
+        SimpleRiskManager::new(fixtures::trading_config(extra))
+    }
+
+    fn signal(symbol: &str) -> TradingSignal {
+        fixtures::signal(symbol, 100.0)
+    }
+
+    fn position(symbol: &str, exchange: &str, notional: f64) -> Position {
+        Position {
+            exchange: exchange.to_string(),
+            ..fixtures::position(symbol, PositionSide::Long, notional / 100.0, 100.0)
+        }
+    }
+
+    #[test]
+    fn test_max_open_positions() {
+        let risk = manager(serde_json::json!({ "max_open_positions": 2 }));
+        let one = [position("ETH/USDT", "binance", 100.0)];
+        let two = [one[0].clone(), position("SOL/USDT", "binance", 100.0)];
+
+        assert_eq!(risk.validate_portfolio(&signal("BTC/USDT"), &one, &[], 10_000.0), None);
+        assert_eq!(
+            risk.validate_portfolio(&signal("BTC/USDT"), &two, &[], 10_000.0),
+            Some(ExposureRejection::MaxOpenPositions { open: 2, limit: 2 })
+        );
+    }
+
+    #[test]
+    fn test_pending_entries_count_and_adds_are_exempt() {
+        let risk = manager(serde_json::json!({
+            "max_open_positions": 2,
+            "max_total_exposure": 10_000.0,
+        }));
+        let open = [position("ETH/USDT", "binance", 100.0)];
+        let pending = [PendingEntry {
+            exchange: "binance".to_string(),
+            symbol: "SOL/USDT".to_string(),
+            notional: 100.0,
+        }];
+        assert_eq!(
+            risk.validate_portfolio(&signal("BTC/USDT"), &open, &pending, 10_000.0),
+            Some(ExposureRejection::MaxOpenPositions { open: 2, limit: 2 })
+        );
+        // Adding to ETH opens nothing new, its notional still counts
+        assert_eq!(risk.validate_portfolio(&signal("ETH/USDT"), &open, &pending, 10_000.0), None);
+        let large = [PendingEntry {
+            notional: 10_000.0 - ORDER_VALUE - 99.0,
+            ..pending[0].clone()
+        }];
+        assert!(matches!(
+            risk.validate_portfolio(&signal("ETH/USDT"), &open, &large, 10_000.0),
+            Some(ExposureRejection::TotalExposure { .. })
+        ));
+    }
+
+    #[test]
+    fn test_symbol_exposure_sums_across_exchanges() {
+        let limit = ORDER_VALUE + 1_000.0;
+        let risk = manager(serde_json::json!({ "max_exposure_per_symbol": limit }));
+
+        let below = [
+            position("BTC/USDT", "binance", 600.0),
+            position("BTC/USDT", "okx", 399.0),
+            position("ETH/USDT", "binance", 5_000.0),
+        ];
+        assert_eq!(risk.validate_portfolio(&signal("BTC/USDT"), &below, &[], 10_000.0), None);
+
+        let over = [
+            position("BTC/USDT", "binance", 600.0),
+            position("BTC/USDT", "okx", 401.0),
+        ];
+        assert!(matches!(
+            risk.validate_portfolio(&signal("BTC/USDT"), &over, &[], 10_000.0),
+            Some(ExposureRejection::SymbolExposure { .. })
+        ));
+    }
+
+    #[test]
+    fn test_total_exposure() {
+        let limit = ORDER_VALUE + 2_000.0;
+        let risk = manager(serde_json::json!({ "max_total_exposure": limit }));
+
+        let below = [
+            position("ETH/USDT", "binance", 1_000.0),
+            position("SOL/USDT", "binance", 999.0),
+        ];
+        assert_eq!(risk.validate_portfolio(&signal("BTC/USDT"), &below, &[], 10_000.0), None);
+
+        let over = [
+            position("ETH/USDT", "binance", 1_000.0),
+            position("SOL/USDT", "binance", 1_001.0),
+        ];
+        assert!(matches!(
+            risk.validate_portfolio(&signal("BTC/USDT"), &over, &[], 10_000.0),
+            Some(ExposureRejection::TotalExposure { .. })
+        ));
+    }
+
+    #[test]
+    fn test_no_limits_configured() {
+        let risk = manager(serde_json::json!({}));
+        let open: Vec<Position> = (0..20)
+            .map(|i| position(&format!("COIN{}/USDT", i), "binance", 50_000.0))
+            .collect();
+        assert_eq!(risk.validate_portfolio(&signal("BTC/USDT"), &open, &[], 10_000.0), None);
+    }
+
+    #[test]
+    fn test_market_overrides_size_and_exits() {
+        let risk = manager(serde_json::json!({
+            "overrides": {
+                "exchanges": { "binance": { "stop_loss_percentage": 4.0 } },
+                "symbols": { "binance:BTC/USDT": { "stop_loss_percentage": 1.0 } },
+            },
+        }));
+
+        // Same risk budget, the tighter stop allows the larger position
+        let btc = signal("BTC/USDT");
+        let eth = signal("ETH/USDT");
+        assert!((risk.calculate_position_size(&btc, 10_000.0) - 200.0).abs() < 1e-9);
+        assert!((risk.calculate_position_size(&eth, 10_000.0) - 50.0).abs() < 1e-9);
+        assert!((risk.get_stop_loss(&btc, 100.0, PositionSide::Long) - 99.0).abs() < 1e-9);
+        assert!((risk.get_stop_loss(&eth, 100.0, PositionSide::Short) - 104.0).abs() < 1e-9);
+
+        // A new layered config replaces the old one, overrides included
+        risk.update_config(
+            serde_json::from_value(serde_json::json!({
+                "auto_trading_enabled": true,
+                "max_position_size": 100000.0,
+                "risk_percentage": 2.0,
+                "stop_loss_percentage": 3.0,
+                "take_profit_percentage": 6.0,
+            }))
+            .unwrap(),
+        );
+        assert!((risk.get_stop_loss(&btc, 100.0, PositionSide::Long) - 97.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_size_counts_slippage_and_fees_to_the_stop() {
+        let risk = manager(serde_json::json!({
+            "costs": {
+                "default_fees": { "maker_bps": 2.0, "taker_bps": 10.0 },
+                "slippage": { "mode": "fixed", "bps": 10.0 },
+            },
+        }));
+        let size = risk.calculate_position_size(&signal("BTC/USDT"), 10_000.0);
+
+        // Bought at 100.1, stopped at 97.097 and sold 0.1% lower, taker fees both ways
+        let entry = 100.1;
+        let exit = entry * 0.97 * 0.999;
+        let loss = size * ((entry - exit) + 0.001 * (entry + exit));
+        assert!((loss - 200.0).abs() < 1e-6);
+        assert!(size < 200.0 / 3.0);
+
+        // Resting limit entries do not slip and pay the maker rate
+        let limit = manager(serde_json::json!({
+            "order_placement": { "mode": "limit" },
+            "costs": { "default_fees": { "maker_bps": 2.0, "taker_bps": 10.0 } },
+        }));
+        let size = limit.calculate_position_size(&signal("BTC/USDT"), 10_000.0);
+        assert!((size * (3.0 + 0.0002 * 100.0 + 0.001 * 97.0) - 200.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_strength_scales_the_risk_taken() {
+        let risk = manager(serde_json::json!({ "max_position_size": 10_000.0 }));
+        let sized = |strength: SignalStrength| {
+            let mut signal = signal("BTC/USDT");
+            signal.strength = strength;
+            risk.calculate_position_size(&signal, 10_000.0)
+        };
+
+        // 200 at risk over a 3 unit stop, then half and one and a half times that
+        assert!((sized(SignalStrength::Medium) - 200.0 / 3.0).abs() < 1e-9);
+        assert!((sized(SignalStrength::Weak) - 100.0 / 3.0).abs() < 1e-9);
+        assert!((sized(SignalStrength::Strong) - 100.0).abs() < 1e-9);
+
+        // Strong signals still stop at max_position_size
+        let capped = manager(serde_json::json!({ "max_position_size": 8_000.0 }));
+        let mut strong = signal("BTC/USDT");
+        strong.strength = SignalStrength::Strong;
+        assert!((capped.calculate_position_size(&strong, 10_000.0) - 80.0).abs() < 1e-9);
+
+        let flat = manager(serde_json::json!({
+            "max_position_size": 10_000.0,
+            "sizing": { "weak_multiplier": 1.0, "strong_multiplier": 1.0 },
+        }));
+        strong.strength = SignalStrength::Weak;
+        assert!((flat.calculate_position_size(&strong, 10_000.0) - 200.0 / 3.0).abs() < 1e-9);
+    }
+}
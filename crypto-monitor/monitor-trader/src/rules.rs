@@ -0,0 +1,619 @@
+use crate::context::MarketContext;
+use monitor_anomaly::{AnomalyDetection, AnomalySeverity};
+use monitor_core::{AnomalyType, MonitorError};
+use std::{cmp::Ordering, fmt};
+
+// Entry conditions for rule-based strategies, e.g.
+// "anomaly_type == VolumeSpike AND (severity >= High OR z_score > 4) AND rsi < 35".
+// NOT binds tighter than AND, which binds tighter than OR. Comparisons against a value that is
+// not available (no RSI yet, no z-score on the anomaly) are false.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleError {
+    pub message: String,
+    pub span: Span,
+    pub source: String,
+}
+
+impl RuleError {
+    fn new(message: impl Into<String>, span: Span, source: &str) -> Self {
+        Self {
+            message: message.into(),
+            span,
+            source: source.to_string(),
+        }
+    }
+}
+
+// Points at the offending part of the expression:
+//   unknown field 'rsii', expected one of ... at 21..25
+//     severity >= High AND rsii < 35
+//                          ^^^^
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = self.source[..self.span.start].chars().count();
+        let width = self.source[self.span.start..self.span.end].chars().count().max(1);
+        write!(
+            f,
+            "{} at {}..{}\n  {}\n  {}{}",
+            self.message,
+            self.span.start,
+            self.span.end,
+            self.source,
+            " ".repeat(offset),
+            "^".repeat(width)
+        )
+    }
+}
+
+impl std::error::Error for RuleError {}
+
+impl From<RuleError> for MonitorError {
+    fn from(err: RuleError) -> Self {
+        MonitorError::Configuration(err.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl CmpOp {
+    fn symbol(&self) -> &'static str {
+        match self {
+            CmpOp::Eq => "==",
+            CmpOp::Ne => "!=",
+            CmpOp::Lt => "<",
+            CmpOp::Le => "<=",
+            CmpOp::Gt => ">",
+            CmpOp::Ge => ">=",
+        }
+    }
+
+    fn holds(&self, ordering: Ordering) -> bool {
+        match self {
+            CmpOp::Eq => ordering == Ordering::Equal,
+            CmpOp::Ne => ordering != Ordering::Equal,
+            CmpOp::Lt => ordering == Ordering::Less,
+            CmpOp::Le => ordering != Ordering::Greater,
+            CmpOp::Gt => ordering == Ordering::Greater,
+            CmpOp::Ge => ordering != Ordering::Less,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    AnomalyType,
+    Severity,
+    Exchange,
+    Symbol,
+    CurrentValue,
+    ExpectedValue,
+    Deviation,
+    ZScore,
+    PercentageChange,
+    Rsi,
+    Sma,
+    Volatility,
+    PriceChangePct,
+    SpreadBps,
+    Volume24h,
+}
+
+pub const FIELDS: &[(&str, Field)] = &[
+    ("anomaly_type", Field::AnomalyType),
+    ("severity", Field::Severity),
+    ("exchange", Field::Exchange),
+    ("symbol", Field::Symbol),
+    ("current_value", Field::CurrentValue),
+    ("expected_value", Field::ExpectedValue),
+    ("deviation", Field::Deviation),
+    ("z_score", Field::ZScore),
+    ("percentage_change", Field::PercentageChange),
+    ("rsi", Field::Rsi),
+    ("sma", Field::Sma),
+    ("volatility", Field::Volatility),
+    ("price_change_pct", Field::PriceChangePct),
+    ("spread_bps", Field::SpreadBps),
+    ("volume_24h", Field::Volume24h),
+];
+
+const SEVERITIES: &[(&str, AnomalySeverity)] = &[
+    ("Low", AnomalySeverity::Low),
+    ("Medium", AnomalySeverity::Medium),
+    ("High", AnomalySeverity::High),
+    ("Critical", AnomalySeverity::Critical),
+];
+
+impl Field {
+    fn name(&self) -> &'static str {
+        FIELDS.iter().find(|(_, f)| f == self).map(|(name, _)| *name).unwrap_or("?")
+    }
+
+    fn is_ordered(&self) -> bool {
+        !matches!(self, Field::AnomalyType | Field::Exchange | Field::Symbol)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(f64),
+    Severity(AnomalySeverity),
+    AnomalyType(AnomalyType),
+    Text(String),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Number(n) => write!(f, "{}", n),
+            Value::Severity(s) => write!(f, "{:?}", s),
+            Value::AnomalyType(t) => write!(f, "{:?}", t),
+            Value::Text(s) => write!(f, "'{}'", s),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare { field: Field, op: CmpOp, value: Value },
+}
+
+// Fully parenthesized, so the parsed precedence is visible
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Or(a, b) => write!(f, "({} OR {})", a, b),
+            Expr::And(a, b) => write!(f, "({} AND {})", a, b),
+            Expr::Not(e) => write!(f, "NOT {}", e),
+            Expr::Compare { field, op, value } => {
+                write!(f, "{} {} {}", field.name(), op.symbol(), value)
+            }
+        }
+    }
+}
+
+impl Expr {
+    pub fn evaluate(&self, anomaly: &AnomalyDetection, context: &MarketContext) -> bool {
+        match self {
+            Expr::Or(a, b) => a.evaluate(anomaly, context) || b.evaluate(anomaly, context),
+            Expr::And(a, b) => a.evaluate(anomaly, context) && b.evaluate(anomaly, context),
+            Expr::Not(e) => !e.evaluate(anomaly, context),
+            Expr::Compare { field, op, value } => compare(*field, *op, value, anomaly, context),
+        }
+    }
+}
+
+fn compare(
+    field: Field,
+    op: CmpOp,
+    value: &Value,
+    anomaly: &AnomalyDetection,
+    context: &MarketContext,
+) -> bool {
+    let metrics = &anomaly.metrics;
+    let number = match field {
+        Field::CurrentValue => Some(metrics.current_value),
+        Field::ExpectedValue => Some(metrics.expected_value),
+        Field::Deviation => Some(metrics.deviation),
+        Field::ZScore => metrics.z_score,
+        Field::PercentageChange => metrics.percentage_change,
+        Field::Rsi => context.rsi,
+        Field::Sma => context.sma,
+        Field::Volatility => context.volatility,
+        Field::PriceChangePct => context.price_change_pct,
+        Field::SpreadBps => context.spread_bps,
+        Field::Volume24h => context.volume_24h,
+        Field::AnomalyType | Field::Severity | Field::Exchange | Field::Symbol => None,
+    };
+
+    let ordering = match (field, value) {
+        (Field::AnomalyType, Value::AnomalyType(t)) => {
+            Some(if anomaly.anomaly_type == *t { Ordering::Equal } else { Ordering::Less })
+        }
+        (Field::Severity, Value::Severity(s)) => Some(anomaly.severity.cmp(s)),
+        (Field::Exchange, Value::Text(s)) => Some(anomaly.exchange.to_lowercase().cmp(&s.to_lowercase())),
+        (Field::Symbol, Value::Text(s)) => Some(anomaly.symbol.to_uppercase().cmp(&s.to_uppercase())),
+        (_, Value::Number(n)) => number.and_then(|v| v.partial_cmp(n)),
+        _ => None,
+    };
+
+    ordering.is_some_and(|o| op.holds(o))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(f64),
+    Text(String),
+    Op(CmpOp),
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Ident(name) => format!("'{}'", name),
+            Token::Number(n) => format!("number {}", n),
+            Token::Text(s) => format!("string '{}'", s),
+            Token::Op(op) => format!("'{}'", op.symbol()),
+            Token::And => "AND".to_string(),
+            Token::Or => "OR".to_string(),
+            Token::Not => "NOT".to_string(),
+            Token::LParen => "'('".to_string(),
+            Token::RParen => "')'".to_string(),
+        }
+    }
+}
+
+fn lex(source: &str) -> Result<Vec<(Token, Span)>, RuleError> {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        let start = pos;
+        let c = bytes[pos];
+
+        if c.is_ascii_whitespace() {
+            pos += 1;
+            continue;
+        }
+
+        let token = match c {
+            b'(' => {
+                pos += 1;
+                Token::LParen
+            }
+            b')' => {
+                pos += 1;
+                Token::RParen
+            }
+            b'=' | b'!' | b'<' | b'>' => {
+                let next = bytes.get(pos + 1).copied();
+                let (op, len) = match (c, next) {
+                    (b'=', Some(b'=')) => (CmpOp::Eq, 2),
+                    (b'!', Some(b'=')) => (CmpOp::Ne, 2),
+                    (b'<', Some(b'=')) => (CmpOp::Le, 2),
+                    (b'>', Some(b'=')) => (CmpOp::Ge, 2),
+                    (b'<', _) => (CmpOp::Lt, 1),
+                    (b'>', _) => (CmpOp::Gt, 1),
+                    (b'=', _) => {
+                        return Err(RuleError::new(
+                            "unexpected '=', use '==' to compare",
+                            Span { start, end: start + 1 },
+                            source,
+                        ))
+                    }
+                    _ => {
+                        return Err(RuleError::new(
+                            "unexpected '!', use NOT or '!='",
+                            Span { start, end: start + 1 },
+                            source,
+                        ))
+                    }
+                };
+                pos += len;
+                Token::Op(op)
+            }
+            b'\'' | b'"' => {
+                let end = source[pos + 1..].find(c as char).map(|i| pos + 1 + i).ok_or_else(|| {
+                    RuleError::new(
+                        "unterminated string",
+                        Span { start, end: source.len() },
+                        source,
+                    )
+                })?;
+                pos = end + 1;
+                Token::Text(source[start + 1..end].to_string())
+            }
+            b'0'..=b'9' | b'-' | b'.' => {
+                pos += 1;
+                while pos < bytes.len() && (bytes[pos].is_ascii_digit() || bytes[pos] == b'.') {
+                    pos += 1;
+                }
+                let text = &source[start..pos];
+                let number = text.parse::<f64>().map_err(|_| {
+                    RuleError::new(
+                        format!("invalid number '{}'", text),
+                        Span { start, end: pos },
+                        source,
+                    )
+                })?;
+                Token::Number(number)
+            }
+            c if c.is_ascii_alphabetic() || c == b'_' => {
+                while pos < bytes.len() && (bytes[pos].is_ascii_alphanumeric() || bytes[pos] == b'_') {
+                    pos += 1;
+                }
+                let word = &source[start..pos];
+                match word.to_ascii_uppercase().as_str() {
+                    "AND" => Token::And,
+                    "OR" => Token::Or,
+                    "NOT" => Token::Not,
+                    _ => Token::Ident(word.to_string()),
+                }
+            }
+            _ => {
+                let end = start + source[start..].chars().next().map_or(1, char::len_utf8);
+                return Err(RuleError::new(
+                    format!("unexpected character '{}'", &source[start..end]),
+                    Span { start, end },
+                    source,
+                ));
+            }
+        };
+
+        tokens.push((token, Span { start, end: pos }));
+    }
+
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<(Token, Span)>,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(t, _)| t)
+    }
+
+    fn next(&mut self) -> Result<(Token, Span), RuleError> {
+        let token = self.tokens.get(self.pos).cloned().ok_or_else(|| {
+            let end = self.source.len();
+            RuleError::new("unexpected end of expression", Span { start: end, end }, self.source)
+        })?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn error(&self, message: String, span: Span) -> RuleError {
+        RuleError::new(message, span, self.source)
+    }
+
+    fn or(&mut self) -> Result<Expr, RuleError> {
+        let mut expr = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, RuleError> {
+        let mut expr = self.unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, RuleError> {
+        if self.peek() == Some(&Token::Not) {
+            self.pos += 1;
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        if self.peek() == Some(&Token::LParen) {
+            let (_, open) = self.next()?;
+            let expr = self.or()?;
+            return match self.next() {
+                Ok((Token::RParen, _)) => Ok(expr),
+                Ok((token, span)) => Err(self.error(format!("expected ')', found {}", token.describe()), span)),
+                Err(_) => Err(self.error("unclosed '('".to_string(), open)),
+            };
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, RuleError> {
+        let (token, field_span) = self.next()?;
+        let Token::Ident(name) = token else {
+            return Err(self.error(format!("expected a field, found {}", token.describe()), field_span));
+        };
+        let field = FIELDS
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(&name))
+            .map(|(_, f)| *f)
+            .ok_or_else(|| {
+                let known: Vec<&str> = FIELDS.iter().map(|(n, _)| *n).collect();
+                self.error(
+                    format!("unknown field '{}', expected one of {}", name, known.join(", ")),
+                    field_span,
+                )
+            })?;
+
+        let (token, op_span) = self.next()?;
+        let Token::Op(op) = token else {
+            return Err(self.error(
+                format!("expected a comparison after '{}', found {}", name, token.describe()),
+                op_span,
+            ));
+        };
+        if !field.is_ordered() && !matches!(op, CmpOp::Eq | CmpOp::Ne) {
+            return Err(self.error(
+                format!("'{}' only supports == and !=", field.name()),
+                op_span,
+            ));
+        }
+
+        let (token, value_span) = self.next()?;
+        let value = self.value(field, token, value_span)?;
+        Ok(Expr::Compare { field, op, value })
+    }
+
+    fn value(&self, field: Field, token: Token, span: Span) -> Result<Value, RuleError> {
+        fn variant<T: Clone>(variants: &[(&str, T)], name: &str) -> Option<T> {
+            variants.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, v)| v.clone())
+        }
+        fn names<T>(variants: &[(&str, T)]) -> String {
+            variants.iter().map(|(n, _)| *n).collect::<Vec<_>>().join(", ")
+        }
+
+        match (field, token) {
+            (Field::Severity, Token::Ident(name) | Token::Text(name)) => variant(SEVERITIES, &name)
+                .map(Value::Severity)
+                .ok_or_else(|| {
+                    self.error(
+                        format!("unknown severity '{}', expected one of {}", name, names(SEVERITIES)),
+                        span,
+                    )
+                }),
+            (Field::AnomalyType, Token::Ident(name) | Token::Text(name)) => {
+                AnomalyType::from_name(&name).map(Value::AnomalyType).ok_or_else(|| {
+                    let expected: Vec<&str> =
+                        AnomalyType::ALL.iter().map(AnomalyType::name).collect();
+                    self.error(
+                        format!(
+                            "unknown anomaly type '{}', expected one of {}",
+                            name,
+                            expected.join(", ")
+                        ),
+                        span,
+                    )
+                })
+            }
+            (Field::Exchange | Field::Symbol, Token::Ident(text) | Token::Text(text)) => {
+                Ok(Value::Text(text))
+            }
+            (Field::Severity | Field::AnomalyType | Field::Exchange | Field::Symbol, token) => Err(
+                self.error(format!("'{}' cannot be compared to {}", field.name(), token.describe()), span),
+            ),
+            (_, Token::Number(n)) => Ok(Value::Number(n)),
+            (_, token) => Err(self.error(
+                format!("'{}' is numeric, found {}", field.name(), token.describe()),
+                span,
+            )),
+        }
+    }
+}
+
+pub fn compile(source: &str) -> Result<Expr, RuleError> {
+    let mut parser = Parser {
+        source,
+        tokens: lex(source)?,
+        pos: 0,
+    };
+
+    let expr = parser.or()?;
+    if let Some((token, span)) = parser.tokens.get(parser.pos) {
+        return Err(parser.error(
+            format!("expected AND, OR or end of expression, found {}", token.describe()),
+            *span,
+        ));
+    }
+    Ok(expr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use monitor_anomaly::AnomalyMetrics;
+
+    fn anomaly(anomaly_type: AnomalyType, severity: AnomalySeverity) -> AnomalyDetection {
+        AnomalyDetection {
+            id: uuid::Uuid::new_v4(),
+            timestamp: chrono::Utc::now(),
+            symbol: "BTC/USDT".to_string(),
+            exchange: "binance".to_string(),
+            anomaly_type,
+            confidence: severity.confidence_floor(),
+            severity,
+            metrics: AnomalyMetrics {
+                current_value: 100.0,
+                expected_value: 50.0,
+                deviation: 50.0,
+                z_score: Some(4.5),
+                percentage_change: None,
+                historical_avg: None,
+                historical_std: None,
+                ..Default::default()
+            },
+            description: "test".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_operator_precedence() {
+        let expr = compile("severity >= High OR anomaly_type == PriceSpike AND rsi < 35").unwrap();
+        assert_eq!(
+            expr.to_string(),
+            "(severity >= High OR (anomaly_type == PriceSpike AND rsi < 35))"
+        );
+
+        let expr = compile("NOT (z_score > 3 OR rsi < 30) and symbol == 'BTC/USDT'").unwrap();
+        assert_eq!(
+            expr.to_string(),
+            "(NOT (z_score > 3 OR rsi < 30) AND symbol == 'BTC/USDT')"
+        );
+
+        // AND binds tighter, so a high-severity spike matches without an RSI reading
+        let expr = compile("severity >= High OR anomaly_type == PriceSpike AND rsi < 35").unwrap();
+        let spike = anomaly(AnomalyType::VolumeSpike, AnomalySeverity::Critical);
+        assert!(expr.evaluate(&spike, &MarketContext::default()));
+    }
+
+    #[test]
+    fn test_unknown_field_points_at_it() {
+        let source = "anomaly_type == VolumeSpike AND rsii < 35";
+        let err = compile(source).unwrap_err();
+
+        assert!(err.message.starts_with("unknown field 'rsii'"));
+        assert_eq!(&source[err.span.start..err.span.end], "rsii");
+        assert!(err.to_string().ends_with(&format!("\n  {}\n  {}^^^^", source, " ".repeat(32))));
+    }
+
+    #[test]
+    fn test_validation_errors() {
+        let cases = [
+            ("severity >= Huge", "Huge"),
+            ("anomaly_type > VolumeSpike", ">"),
+            ("rsi < High", "High"),
+            ("rsi = 30", "="),
+            ("(rsi < 30", "("),
+            ("rsi < 30 rsi", "rsi"),
+            ("symbol == 'BTC", "'BTC"),
+        ];
+        for (source, offending) in cases {
+            let err = compile(source).unwrap_err();
+            assert_eq!(&source[err.span.start..err.span.end], offending, "{}", source);
+        }
+
+        let err = compile("rsi <").unwrap_err();
+        assert_eq!(err.span, Span { start: 5, end: 5 });
+    }
+
+    #[test]
+    fn test_missing_values_never_match() {
+        let expr = compile("percentage_change < -5 OR rsi >= 0").unwrap();
+        let spike = anomaly(AnomalyType::PriceSpike, AnomalySeverity::High);
+        assert!(!expr.evaluate(&spike, &MarketContext::default()));
+
+        let negated = compile("NOT rsi >= 0").unwrap();
+        assert!(negated.evaluate(&spike, &MarketContext::default()));
+    }
+}
@@ -0,0 +1,128 @@
+use crate::{Position, PositionSide};
+use monitor_core::{PositionScalingConfig, ScalingMode};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScalingDecision {
+    // Nothing open on the symbol
+    Open,
+    // Close the open position first
+    Replace,
+    // Add to the open position at this fraction of a regular entry
+    Add { size_fraction: f64 },
+    Reject(ScalingRejection),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum ScalingRejection {
+    PositionOpen,
+    OppositeSide,
+    MaxAdds { adds: u32, limit: u32 },
+    NotInProfit { unrealized_pnl: f64 },
+}
+
+// How a signal on `side` interacts with the position already open on its symbol
+pub fn decide(
+    config: &PositionScalingConfig,
+    open: Option<&Position>,
+    side: &PositionSide,
+) -> ScalingDecision {
+    let Some(position) = open else {
+        return ScalingDecision::Open;
+    };
+
+    match config.mode {
+        ScalingMode::Reject => ScalingDecision::Reject(ScalingRejection::PositionOpen),
+        ScalingMode::Replace => ScalingDecision::Replace,
+        // Pyramiding only adds, a signal against the position is no reason to flip it
+        ScalingMode::Pyramid if position.side != *side => {
+            ScalingDecision::Reject(ScalingRejection::OppositeSide)
+        }
+        ScalingMode::Pyramid if position.adds >= config.max_adds => {
+            ScalingDecision::Reject(ScalingRejection::MaxAdds {
+                adds: position.adds,
+                limit: config.max_adds,
+            })
+        }
+        ScalingMode::Pyramid if config.winners_only && position.unrealized_pnl <= 0.0 => {
+            ScalingDecision::Reject(ScalingRejection::NotInProfit {
+                unrealized_pnl: position.unrealized_pnl,
+            })
+        }
+        ScalingMode::Pyramid => ScalingDecision::Add {
+            size_fraction: config.add_size_fraction,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    fn config(mode: ScalingMode) -> PositionScalingConfig {
+        PositionScalingConfig {
+            mode,
+            max_adds: 2,
+            add_size_fraction: 0.5,
+            winners_only: true,
+        }
+    }
+
+    fn position(unrealized_pnl: f64) -> Position {
+        Position {
+            unrealized_pnl,
+            ..fixtures::position("BTC/USDT", PositionSide::Long, 0.2, 50_000.0)
+        }
+    }
+
+    #[test]
+    fn test_rejects_by_default() {
+        let default = PositionScalingConfig::default();
+        let open = position(100.0);
+        assert_eq!(decide(&default, None, &PositionSide::Long), ScalingDecision::Open);
+        assert_eq!(
+            decide(&default, Some(&open), &PositionSide::Long),
+            ScalingDecision::Reject(ScalingRejection::PositionOpen)
+        );
+        assert_eq!(
+            decide(&config(ScalingMode::Replace), Some(&open), &PositionSide::Short),
+            ScalingDecision::Replace
+        );
+    }
+
+    #[test]
+    fn test_pyramid_adds_to_winners_up_to_the_limit() {
+        let config = config(ScalingMode::Pyramid);
+        let mut open = position(100.0);
+
+        // Two adds, each half the size, move the entry to the weighted average
+        for (price, adds) in [(51_000.0, 1), (52_000.0, 2)] {
+            open.update_price(price);
+            let ScalingDecision::Add { size_fraction } =
+                decide(&config, Some(&open), &PositionSide::Long)
+            else {
+                panic!("expected an add");
+            };
+            open.add_fill(0.2 * size_fraction, price);
+            open.adds = adds;
+        }
+        assert!((open.quantity - 0.4).abs() < 1e-12);
+        // (0.2 * 50000 + 0.1 * 51000 + 0.1 * 52000) / 0.4
+        assert!((open.entry_price - 50_750.0).abs() < 1e-6);
+
+        assert_eq!(
+            decide(&config, Some(&open), &PositionSide::Long),
+            ScalingDecision::Reject(ScalingRejection::MaxAdds { adds: 2, limit: 2 })
+        );
+        assert_eq!(
+            decide(&config, Some(&position(100.0)), &PositionSide::Short),
+            ScalingDecision::Reject(ScalingRejection::OppositeSide)
+        );
+        assert_eq!(
+            decide(&config, Some(&position(-5.0)), &PositionSide::Long),
+            ScalingDecision::Reject(ScalingRejection::NotInProfit { unrealized_pnl: -5.0 })
+        );
+    }
+}
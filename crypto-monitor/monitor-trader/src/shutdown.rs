@@ -0,0 +1,60 @@
+use monitor_core::ShutdownPolicy;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum ShutdownOutcome {
+    // Left open by the policy
+    Kept,
+    Closed { realized_pnl: f64 },
+    // Still open, the close was refused or another close got to it first
+    Failed { error: String },
+    // Not closed before the timeout. A close in flight is resolved at the next startup
+    TimedOut,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionShutdown {
+    pub position_id: uuid::Uuid,
+    pub exchange: String,
+    pub symbol: String,
+    #[serde(flatten)]
+    pub outcome: ShutdownOutcome,
+}
+
+// What `AutoTrader::shutdown` did to the orders and positions it found
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShutdownReport {
+    pub policy: ShutdownPolicy,
+    pub cancelled_orders: Vec<String>,
+    // Entry orders still working, their cancel refused or not sent in time
+    pub working_orders: Vec<String>,
+    pub cancelled_dca_plans: usize,
+    pub positions: Vec<PositionShutdown>,
+    pub timed_out: bool,
+}
+
+impl ShutdownReport {
+    pub fn new(policy: ShutdownPolicy) -> Self {
+        Self {
+            policy,
+            cancelled_orders: Vec::new(),
+            working_orders: Vec::new(),
+            cancelled_dca_plans: 0,
+            positions: Vec::new(),
+            timed_out: false,
+        }
+    }
+
+    // Everything the policy asked for was done
+    pub fn is_complete(&self) -> bool {
+        !self.timed_out
+            && self.working_orders.is_empty()
+            && self.positions.iter().all(|position| {
+                matches!(
+                    position.outcome,
+                    ShutdownOutcome::Kept | ShutdownOutcome::Closed { .. }
+                )
+            })
+    }
+}
@@ -0,0 +1,431 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use monitor_core::{
+    ratelimit::{EndpointClass, RestRateLimiters},
+    InstrumentKind, MonitorError, Result, StalePositionFallback,
+};
+use parking_lot::RwLock;
+use serde::Deserialize;
+use tracing::{error, info, warn};
+
+// A stalled feed is what sent us here, a hanging ticker request must not stall the check too
+const TICKER_CONNECT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
+const TICKER_REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+#[async_trait]
+pub trait PriceSource: Send + Sync {
+    async fn fetch_price(
+        &self,
+        symbol: &str,
+        exchange: &str,
+        kind: InstrumentKind,
+    ) -> Result<f64>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TickerVenue {
+    Binance,
+    Okx,
+}
+
+impl TickerVenue {
+    pub fn for_exchange(exchange: &str) -> Option<Self> {
+        match exchange.to_lowercase().as_str() {
+            "binance" => Some(TickerVenue::Binance),
+            "okx" => Some(TickerVenue::Okx),
+            _ => None,
+        }
+    }
+
+    // REST url, endpoint class and request weight of the last price of `symbol`, the
+    // perpetual and spot markets of a pair trade at different prices
+    fn request(&self, symbol: &str, kind: InstrumentKind) -> (String, EndpointClass, u32) {
+        let pair = symbol.to_uppercase();
+        match (self, kind) {
+            // https://binance-docs.github.io/apidocs/spot/en/#symbol-price-ticker
+            (TickerVenue::Binance, InstrumentKind::Spot) => (
+                format!(
+                    "https://api.binance.com/api/v3/ticker/price?symbol={}",
+                    pair.replace('/', "")
+                ),
+                EndpointClass::MarketData,
+                2,
+            ),
+            // https://binance-docs.github.io/apidocs/futures/en/#symbol-price-ticker
+            (TickerVenue::Binance, InstrumentKind::Perpetual) => (
+                format!(
+                    "https://fapi.binance.com/fapi/v1/ticker/price?symbol={}",
+                    pair.replace('/', "")
+                ),
+                EndpointClass::MarketData,
+                2,
+            ),
+            // https://www.okx.com/docs-v5/en/#order-book-trading-market-data-get-ticker
+            (TickerVenue::Okx, InstrumentKind::Spot) => (
+                format!(
+                    "https://www.okx.com/api/v5/market/ticker?instId={}",
+                    pair.replace('/', "-")
+                ),
+                EndpointClass::MarketData,
+                1,
+            ),
+            (TickerVenue::Okx, InstrumentKind::Perpetual) => (
+                format!(
+                    "https://www.okx.com/api/v5/market/ticker?instId={}-SWAP",
+                    pair.replace('/', "-")
+                ),
+                EndpointClass::MarketData,
+                1,
+            ),
+        }
+    }
+
+    pub fn parse(&self, body: &str) -> Result<f64> {
+        let price = match self {
+            TickerVenue::Binance => {
+                let response: BinanceTickerPrice = serde_json::from_str(body)?;
+                response.price
+            }
+            TickerVenue::Okx => {
+                let response: OkxTickerResponse = serde_json::from_str(body)?;
+                if response.code != "0" {
+                    return Err(MonitorError::Stream(format!(
+                        "OKX ticker returned code {}: {}",
+                        response.code, response.msg
+                    )));
+                }
+                response
+                    .data
+                    .into_iter()
+                    .next()
+                    .ok_or_else(|| MonitorError::Stream("OKX ticker returned no data".to_string()))?
+                    .last
+            }
+        };
+
+        price
+            .parse::<f64>()
+            .ok()
+            .filter(|v| v.is_finite() && *v > 0.0)
+            .ok_or_else(|| MonitorError::Stream(format!("Invalid ticker price {}", price)))
+    }
+}
+
+#[derive(Deserialize)]
+struct BinanceTickerPrice {
+    price: String,
+}
+
+#[derive(Deserialize)]
+struct OkxTickerResponse {
+    code: String,
+    #[serde(default)]
+    msg: String,
+    #[serde(default)]
+    data: Vec<OkxTicker>,
+}
+
+#[derive(Deserialize)]
+struct OkxTicker {
+    last: String,
+}
+
+// Last traded price from the public REST ticker, charged to the shared per-exchange limiter
+pub struct RestPriceSource {
+    client: reqwest::Client,
+    limiters: RestRateLimiters,
+}
+
+impl RestPriceSource {
+    pub fn new(limiters: RestRateLimiters) -> Self {
+        let client = reqwest::Client::builder()
+            .connect_timeout(TICKER_CONNECT_TIMEOUT)
+            .timeout(TICKER_REQUEST_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Self { client, limiters }
+    }
+}
+
+#[async_trait]
+impl PriceSource for RestPriceSource {
+    async fn fetch_price(
+        &self,
+        symbol: &str,
+        exchange: &str,
+        kind: InstrumentKind,
+    ) -> Result<f64> {
+        let venue = TickerVenue::for_exchange(exchange).ok_or_else(|| {
+            MonitorError::Configuration(format!("No REST ticker for exchange {}", exchange))
+        })?;
+        let (url, class, weight) = venue.request(symbol, kind);
+        let limiter = self.limiters.get(exchange);
+        limiter.acquire(class, weight).await?;
+
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| MonitorError::Stream(format!("Ticker request failed: {}", e)))?;
+        let status = response.status();
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        limiter.record_response(status.as_u16(), retry_after.as_deref());
+        if !status.is_success() {
+            return Err(MonitorError::Stream(format!("Ticker request returned {}", status)));
+        }
+
+        let body = response
+            .text()
+            .await
+            .map_err(|e| MonitorError::Stream(format!("Ticker read failed: {}", e)))?;
+        venue.parse(&body)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StaleAction {
+    // Position just crossed the staleness threshold
+    Alert { key: String, stale_for: Duration },
+    // REST fallback price to feed into the stop-loss/take-profit checks
+    Reprice { key: String, price: f64 },
+    // Flatten the position defensively
+    Close { key: String },
+}
+
+pub struct StalePriceGuard {
+    policy: RwLock<(Duration, StalePositionFallback)>,
+    last_updates: DashMap<String, DateTime<Utc>>,
+    stale_since: DashMap<String, DateTime<Utc>>,
+}
+
+impl StalePriceGuard {
+    pub fn new(threshold_secs: u64, fallback: StalePositionFallback) -> Self {
+        Self {
+            policy: RwLock::new((Duration::seconds(threshold_secs as i64), fallback)),
+            last_updates: DashMap::new(),
+            stale_since: DashMap::new(),
+        }
+    }
+
+    pub fn set_policy(&self, threshold_secs: u64, fallback: StalePositionFallback) {
+        *self.policy.write() = (Duration::seconds(threshold_secs as i64), fallback);
+    }
+
+    // Records a streaming price update, returns true if this clears a stale condition
+    pub fn record_update(&self, key: &str, at: DateTime<Utc>) -> bool {
+        match self.last_updates.get_mut(key) {
+            Some(mut last) => *last = at,
+            None => {
+                self.last_updates.insert(key.to_string(), at);
+            }
+        }
+        let recovered = self.stale_since.remove(key).is_some();
+        if recovered {
+            info!("Price feed for position {} resumed", key);
+        }
+        recovered
+    }
+
+    pub fn remove(&self, key: &str) {
+        self.last_updates.remove(key);
+        self.stale_since.remove(key);
+    }
+
+    pub fn is_stale(&self, key: &str) -> bool {
+        self.stale_since.contains_key(key)
+    }
+
+    pub fn last_update(&self, key: &str) -> Option<DateTime<Utc>> {
+        self.last_updates.get(key).map(|t| *t)
+    }
+
+    pub async fn evaluate(
+        &self,
+        positions: &[(String, String, String, InstrumentKind)],
+        now: DateTime<Utc>,
+        price_source: Option<&dyn PriceSource>,
+    ) -> Vec<StaleAction> {
+        let (threshold, fallback) = *self.policy.read();
+        let mut actions = Vec::new();
+
+        for (key, symbol, exchange, kind) in positions {
+            let last_update = match self.last_update(key) {
+                Some(t) => t,
+                None => {
+                    // Positions opened before the first tick start the clock now
+                    self.last_updates.insert(key.clone(), now);
+                    continue;
+                }
+            };
+
+            let stale_for = now - last_update;
+            if stale_for < threshold {
+                continue;
+            }
+
+            if !self.stale_since.contains_key(key) {
+                warn!("No price update for position {} in {}s", key, stale_for.num_seconds());
+                self.stale_since.insert(key.clone(), now);
+                actions.push(StaleAction::Alert {
+                    key: key.clone(),
+                    stale_for,
+                });
+            }
+
+            match fallback {
+                StalePositionFallback::None => {}
+                StalePositionFallback::Rest => match price_source {
+                    Some(source) => match source.fetch_price(symbol, exchange, *kind).await {
+                        Ok(price) => actions.push(StaleAction::Reprice {
+                            key: key.clone(),
+                            price,
+                        }),
+                        Err(e) => error!("REST fallback price for {} failed: {}", key, e),
+                    },
+                    None => warn!("REST fallback configured but no price source available"),
+                },
+                StalePositionFallback::Close => actions.push(StaleAction::Close { key: key.clone() }),
+            }
+        }
+
+        actions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct MockPriceSource {
+        price: Option<f64>,
+    }
+
+    #[async_trait]
+    impl PriceSource for MockPriceSource {
+        async fn fetch_price(
+            &self,
+            _symbol: &str,
+            _exchange: &str,
+            _kind: InstrumentKind,
+        ) -> Result<f64> {
+            self.price
+                .ok_or_else(|| MonitorError::Other("ticker unavailable".to_string()))
+        }
+    }
+
+    fn positions() -> Vec<(String, String, String, InstrumentKind)> {
+        vec![(
+            "binance:BTC/USDT".to_string(),
+            "BTC/USDT".to_string(),
+            "binance".to_string(),
+            InstrumentKind::Perpetual,
+        )]
+    }
+
+    async fn stall(guard: &StalePriceGuard, source: &MockPriceSource) -> Vec<StaleAction> {
+        let start = Utc::now();
+        guard.record_update("binance:BTC/USDT", start);
+
+        // Still fresh
+        assert!(guard.evaluate(&positions(), start + Duration::seconds(5), Some(source)).await.is_empty());
+
+        guard.evaluate(&positions(), start + Duration::seconds(31), Some(source)).await
+    }
+
+    #[test]
+    fn test_ticker_requests_and_responses() {
+        let (url, class, _) = TickerVenue::Binance.request("btc/usdt", InstrumentKind::Spot);
+        assert_eq!(url, "https://api.binance.com/api/v3/ticker/price?symbol=BTCUSDT");
+        assert_eq!(class, EndpointClass::MarketData);
+        let (url, _, _) = TickerVenue::Binance.request("BTC/USDT", InstrumentKind::Perpetual);
+        assert_eq!(url, "https://fapi.binance.com/fapi/v1/ticker/price?symbol=BTCUSDT");
+        let (url, _, _) = TickerVenue::Okx.request("ETH/USDT", InstrumentKind::Spot);
+        assert!(url.ends_with("instId=ETH-USDT"));
+        let (url, _, _) = TickerVenue::Okx.request("ETH/USDT", InstrumentKind::Perpetual);
+        assert!(url.ends_with("instId=ETH-USDT-SWAP"));
+        assert_eq!(TickerVenue::for_exchange("Bybit"), None);
+
+        let binance = r#"{"symbol":"BTCUSDT","price":"64123.50000000"}"#;
+        assert_eq!(TickerVenue::Binance.parse(binance).unwrap(), 64123.5);
+        let okx = r#"{"code":"0","msg":"","data":[{"instType":"SPOT","instId":"ETH-USDT",
+            "last":"3150.2","lastSz":"0.1","ts":"1597026383085"}]}"#;
+        assert_eq!(TickerVenue::Okx.parse(okx).unwrap(), 3150.2);
+
+        let error = r#"{"code":"51001","msg":"Instrument ID does not exist","data":[]}"#;
+        assert!(TickerVenue::Okx.parse(error).is_err());
+        let zero = r#"{"symbol":"BTCUSDT","price":"0.00000000"}"#;
+        assert!(TickerVenue::Binance.parse(zero).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_none_policy_only_alerts_once() {
+        let guard = StalePriceGuard::new(30, StalePositionFallback::None);
+        let source = MockPriceSource { price: Some(100.0) };
+
+        let actions = stall(&guard, &source).await;
+        assert_eq!(actions.len(), 1);
+        assert!(matches!(actions[0], StaleAction::Alert { .. }));
+        assert!(guard.is_stale("binance:BTC/USDT"));
+
+        let later = guard
+            .evaluate(&positions(), Utc::now() + Duration::seconds(60), Some(&source))
+            .await;
+        assert!(later.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rest_policy_reprices_from_fallback() {
+        let guard = StalePriceGuard::new(30, StalePositionFallback::Rest);
+        let source = MockPriceSource { price: Some(95.5) };
+
+        let actions = stall(&guard, &source).await;
+        assert_eq!(
+            actions[1],
+            StaleAction::Reprice {
+                key: "binance:BTC/USDT".to_string(),
+                price: 95.5
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rest_policy_tolerates_source_failure() {
+        let guard = StalePriceGuard::new(30, StalePositionFallback::Rest);
+        let source = MockPriceSource { price: None };
+
+        let actions = stall(&guard, &source).await;
+        assert_eq!(actions.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_close_policy_flattens() {
+        let guard = StalePriceGuard::new(30, StalePositionFallback::Close);
+        let source = MockPriceSource { price: Some(100.0) };
+
+        let actions = stall(&guard, &source).await;
+        assert_eq!(
+            actions[1],
+            StaleAction::Close {
+                key: "binance:BTC/USDT".to_string()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_streaming_resume_clears_condition() {
+        let guard = StalePriceGuard::new(30, StalePositionFallback::None);
+        let source = MockPriceSource { price: None };
+
+        stall(&guard, &source).await;
+        assert!(guard.is_stale("binance:BTC/USDT"));
+
+        assert!(guard.record_update("binance:BTC/USDT", Utc::now()));
+        assert!(!guard.is_stale("binance:BTC/USDT"));
+    }
+}
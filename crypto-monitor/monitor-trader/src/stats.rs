@@ -0,0 +1,279 @@
+use crate::TradingStats;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+// Points kept on the equity curve, the oldest are dropped first. Drawdown and Sharpe are
+// accumulated as trades come in and do not need the dropped points
+const MAX_EQUITY_POINTS: usize = 10_000;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EquityPoint {
+    pub timestamp: DateTime<Utc>,
+    // Realized equity after the trade
+    pub equity: f64,
+    pub pnl: f64,
+}
+
+// Maintains TradingStats trade by trade: averages and streaks in `TradingStats::record_trade`,
+// drawdown over the realized equity curve and Sharpe over per-trade returns
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsEngine {
+    risk_free_rate: f64,
+    stats: TradingStats,
+    equity: f64,
+    equity_curve: VecDeque<EquityPoint>,
+    peak: f64,
+    // Welford accumulators over per-trade returns
+    returns: u64,
+    return_mean: f64,
+    return_m2: f64,
+}
+
+impl StatsEngine {
+    // `risk_free_rate` is the per-trade return the Sharpe ratio is measured against
+    pub fn new(initial_equity: f64, risk_free_rate: f64) -> Self {
+        Self {
+            risk_free_rate,
+            stats: TradingStats::default(),
+            equity: initial_equity,
+            equity_curve: VecDeque::new(),
+            peak: initial_equity,
+            returns: 0,
+            return_mean: 0.0,
+            return_m2: 0.0,
+        }
+    }
+
+    // `pnl` is net of `fees`, equity and returns move by what was actually realized
+    pub fn record_trade(&mut self, pnl: f64, fees: f64, at: DateTime<Utc>) {
+        let before = self.equity;
+        let equity = before + pnl;
+        self.equity = equity;
+        self.stats.record_trade(pnl, fees);
+        self.equity_curve.push_back(EquityPoint {
+            timestamp: at,
+            equity,
+            pnl,
+        });
+        if self.equity_curve.len() > MAX_EQUITY_POINTS {
+            self.equity_curve.pop_front();
+        }
+
+        self.peak = self.peak.max(equity);
+        if self.peak > 0.0 {
+            let drawdown = (self.peak - equity) / self.peak;
+            self.stats.max_drawdown = self.stats.max_drawdown.max(drawdown);
+        }
+
+        // A return needs positive equity to be measured against
+        if before > 0.0 {
+            let r = pnl / before;
+            self.returns += 1;
+            let delta = r - self.return_mean;
+            self.return_mean += delta / self.returns as f64;
+            self.return_m2 += delta * (r - self.return_mean);
+            self.stats.sharpe_ratio = sharpe(
+                self.return_mean,
+                self.return_m2,
+                self.returns,
+                self.risk_free_rate,
+            );
+        }
+    }
+
+    pub fn stats(&self) -> TradingStats {
+        self.stats.clone()
+    }
+
+    pub fn equity(&self) -> f64 {
+        self.equity
+    }
+
+    // The latest `MAX_EQUITY_POINTS` trades
+    pub fn equity_curve(&self) -> Vec<EquityPoint> {
+        self.equity_curve.iter().cloned().collect()
+    }
+
+    pub fn set_risk_free_rate(&mut self, risk_free_rate: f64) {
+        self.risk_free_rate = risk_free_rate;
+        self.stats.sharpe_ratio =
+            sharpe(self.return_mean, self.return_m2, self.returns, risk_free_rate);
+    }
+}
+
+fn sharpe(mean: f64, m2: f64, count: u64, risk_free_rate: f64) -> f64 {
+    if count < 2 {
+        return 0.0;
+    }
+    let std_dev = (m2 / (count - 1) as f64).sqrt();
+    if std_dev > 0.0 {
+        (mean - risk_free_rate) / std_dev
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Trade PnLs in [-scale, scale), from a fixed LCG so runs are repeatable
+    fn trades(seed: u64, count: usize, scale: f64) -> Vec<f64> {
+        let mut state = seed;
+        (0..count)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6_364_136_223_846_793_005)
+                    .wrapping_add(1_442_695_040_888_963_407);
+                ((state >> 11) as f64 / (1u64 << 53) as f64 * 2.0 - 1.0) * scale
+            })
+            .collect()
+    }
+
+    // Stats recomputed from the whole trade list in one pass over each measure, the reference
+    // the incremental engine is checked against. The PnLs are taken as fee-free
+    fn compute_stats(initial_equity: f64, risk_free_rate: f64, pnls: &[f64]) -> TradingStats {
+        let wins: Vec<f64> = pnls.iter().copied().filter(|pnl| *pnl > 0.0).collect();
+        let losses: Vec<f64> = pnls.iter().copied().filter(|pnl| *pnl <= 0.0).collect();
+        let mean = |values: &[f64]| {
+            if values.is_empty() {
+                0.0
+            } else {
+                values.iter().sum::<f64>() / values.len() as f64
+            }
+        };
+
+        let gross_profit: f64 = wins.iter().sum();
+        let gross_loss: f64 = -losses.iter().sum::<f64>();
+
+        let mut equity = vec![initial_equity];
+        for pnl in pnls {
+            equity.push(equity[equity.len() - 1] + pnl);
+        }
+        let max_drawdown = (1..equity.len())
+            .filter_map(|i| {
+                let peak = equity[..=i].iter().copied().fold(f64::MIN, f64::max);
+                (peak > 0.0).then(|| (peak - equity[i]) / peak)
+            })
+            .fold(0.0, f64::max);
+
+        let returns: Vec<f64> = pnls
+            .iter()
+            .zip(&equity)
+            .filter(|(_, before)| **before > 0.0)
+            .map(|(pnl, before)| pnl / before)
+            .collect();
+        let sharpe_ratio = if returns.len() < 2 {
+            0.0
+        } else {
+            let average = mean(&returns);
+            let variance = returns.iter().map(|r| (r - average).powi(2)).sum::<f64>()
+                / (returns.len() - 1) as f64;
+            if variance > 0.0 {
+                (average - risk_free_rate) / variance.sqrt()
+            } else {
+                0.0
+            }
+        };
+
+        let streak = |win: bool| {
+            pnls.split(|pnl| (*pnl > 0.0) != win)
+                .map(|run| run.len() as u64)
+                .max()
+                .unwrap_or(0)
+        };
+        let last = pnls.last().is_none_or(|pnl| *pnl > 0.0);
+        let current = pnls.iter().rev().take_while(|pnl| (**pnl > 0.0) == last).count() as i64;
+
+        TradingStats {
+            total_trades: pnls.len() as u64,
+            winning_trades: wins.len() as u64,
+            losing_trades: losses.len() as u64,
+            win_rate: if pnls.is_empty() {
+                0.0
+            } else {
+                wins.len() as f64 / pnls.len() as f64
+            },
+            total_pnl: pnls.iter().sum(),
+            gross_pnl: pnls.iter().sum(),
+            total_fees: 0.0,
+            average_win: mean(&wins),
+            average_loss: mean(&losses),
+            profit_factor: if gross_loss > 0.0 {
+                gross_profit / gross_loss
+            } else {
+                0.0
+            },
+            max_drawdown,
+            sharpe_ratio,
+            current_streak: if last { current } else { -current },
+            max_win_streak: streak(true),
+            max_loss_streak: streak(false),
+        }
+    }
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() <= 1e-9 * (1.0 + a.abs().max(b.abs())), "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_incremental_stats_match_a_full_recompute() {
+        for seed in 0..200u64 {
+            let count = (seed % 40) as usize;
+            // Large scales drive equity to zero and below on some seeds
+            let scale = [50.0, 500.0, 4_000.0][(seed % 3) as usize];
+            let pnls = trades(seed.wrapping_add(0x2545_f491_4f6c_dd1d), count, scale);
+
+            let mut engine = StatsEngine::new(10_000.0, 0.001);
+            for pnl in &pnls {
+                engine.record_trade(*pnl, 0.0, Utc::now());
+            }
+            let incremental = engine.stats();
+            let expected = compute_stats(10_000.0, 0.001, &pnls);
+
+            assert_eq!(incremental.total_trades, expected.total_trades);
+            assert_eq!(incremental.winning_trades, expected.winning_trades);
+            assert_eq!(incremental.losing_trades, expected.losing_trades);
+            assert_eq!(incremental.current_streak, expected.current_streak, "seed {}", seed);
+            assert_eq!(incremental.max_win_streak, expected.max_win_streak);
+            assert_eq!(incremental.max_loss_streak, expected.max_loss_streak);
+            assert_close(incremental.win_rate, expected.win_rate);
+            assert_close(incremental.total_pnl, expected.total_pnl);
+            assert_close(incremental.average_win, expected.average_win);
+            assert_close(incremental.average_loss, expected.average_loss);
+            assert_close(incremental.profit_factor, expected.profit_factor);
+            assert_close(incremental.max_drawdown, expected.max_drawdown);
+            assert_close(incremental.sharpe_ratio, expected.sharpe_ratio);
+            assert_eq!(engine.equity_curve().len(), count);
+        }
+    }
+
+    #[test]
+    fn test_drawdown_is_measured_from_the_peak() {
+        let mut engine = StatsEngine::new(1_000.0, 0.0);
+        for pnl in [200.0, -300.0, 100.0, -100.0] {
+            engine.record_trade(pnl, 0.0, Utc::now());
+        }
+
+        // Peak 1200, trough 900
+        assert_close(engine.stats().max_drawdown, 0.25);
+        assert_eq!(engine.equity(), 900.0);
+        let curve: Vec<f64> = engine.equity_curve().iter().map(|p| p.equity).collect();
+        assert_eq!(curve, vec![1_200.0, 900.0, 1_000.0, 900.0]);
+    }
+
+    #[test]
+    fn test_equity_curve_is_capped() {
+        let mut engine = StatsEngine::new(1_000.0, 0.0);
+        for _ in 0..MAX_EQUITY_POINTS + 5 {
+            engine.record_trade(1.0, 0.0, Utc::now());
+        }
+
+        let curve = engine.equity_curve();
+        assert_eq!(curve.len(), MAX_EQUITY_POINTS);
+        assert_eq!(curve[0].equity, 1_006.0);
+        assert_eq!(engine.equity(), 1_000.0 + (MAX_EQUITY_POINTS + 5) as f64);
+        assert_eq!(engine.stats().total_trades, (MAX_EQUITY_POINTS + 5) as u64);
+    }
+}
@@ -1,6 +1,17 @@
-use crate::{SignalStrength, SignalType, TradingSignal, TradingStrategy};
-use monitor_anomaly::AnomalyDetection;
-use monitor_core::TradingConfig;
+use crate::{
+    context::MarketContext,
+    overrides,
+    rules::{self, Expr},
+    SignalStrength, SignalType, TradingSignal, TradingStrategy,
+};
+use chrono::{DateTime, Duration, Utc};
+use monitor_anomaly::{AnomalyDetection, AnomalySeverity};
+use monitor_core::{
+    AnomalyType, MeanReversionConfig, MonitorError, Result, StrategyRuleConfig, StrengthConfig,
+    TradingConfig,
+};
+use std::collections::{HashMap, VecDeque};
+use tracing::{error, info};
 
 pub struct AnomalyBasedStrategy {
     config: TradingConfig,
@@ -13,8 +24,8 @@ impl AnomalyBasedStrategy {
 }
 
 impl TradingStrategy for AnomalyBasedStrategy {
-    fn analyze(&mut self, anomaly: &AnomalyDetection) -> Option<TradingSignal> {
-        if !self.config.auto_trading_enabled {
+    fn analyze(&mut self, anomaly: &AnomalyDetection, _context: &MarketContext) -> Option<TradingSignal> {
+        if !overrides::trading_enabled(&self.config, &anomaly.exchange, &anomaly.symbol) {
             return None;
         }
         
@@ -65,4 +76,676 @@ impl TradingStrategy for AnomalyBasedStrategy {
     fn update_config(&mut self, config: TradingConfig) {
         self.config = config;
     }
-}
\ No newline at end of file
+    
+    fn name(&self) -> &str {
+        "anomaly"
+    }
+}
+
+// Fades price spikes back toward the historical average. A spike only counts once a volume
+// spike on the same symbol confirms it, and spikes beyond `max_z` are left alone as crashes.
+pub struct MeanReversionStrategy {
+    config: TradingConfig,
+    // Last volume spike per "exchange:symbol"
+    volume_spikes: HashMap<String, DateTime<Utc>>,
+}
+
+impl MeanReversionStrategy {
+    pub fn new(config: TradingConfig) -> Self {
+        Self {
+            config,
+            volume_spikes: HashMap::new(),
+        }
+    }
+
+    fn volume_confirmed(&self, key: &str, at: DateTime<Utc>, settings: &MeanReversionConfig) -> bool {
+        if !settings.require_volume {
+            return true;
+        }
+        let window = Duration::seconds(settings.volume_window_secs as i64);
+        self.volume_spikes
+            .get(key)
+            .is_some_and(|spike| *spike <= at && at - *spike <= window)
+    }
+}
+
+impl TradingStrategy for MeanReversionStrategy {
+    fn analyze(&mut self, anomaly: &AnomalyDetection, _context: &MarketContext) -> Option<TradingSignal> {
+        let key = format!("{}:{}", anomaly.exchange, anomaly.symbol);
+        if anomaly.anomaly_type == AnomalyType::VolumeSpike {
+            self.volume_spikes.insert(key, anomaly.timestamp);
+            return None;
+        }
+        if !overrides::trading_enabled(&self.config, &anomaly.exchange, &anomaly.symbol)
+            || anomaly.anomaly_type != AnomalyType::PriceSpike {
+            return None;
+        }
+
+        let settings = &self.config.mean_reversion;
+        let z = anomaly.metrics.z_score?;
+        if z.abs() < settings.entry_z {
+            return None;
+        }
+        if z.abs() > settings.max_z {
+            info!("Not fading {} at z {:.2}, beyond max {:.2}", key, z, settings.max_z);
+            return None;
+        }
+        if !self.volume_confirmed(&key, anomaly.timestamp, settings) {
+            return None;
+        }
+
+        let price = anomaly.metrics.current_value;
+        let signal_type = if z < 0.0 { SignalType::Buy } else { SignalType::Sell };
+        // The price has to be on the far side of the mean it is expected to revert to
+        if let Some(mean) = anomaly.metrics.historical_avg {
+            let stretched = match signal_type {
+                SignalType::Buy => price < mean,
+                _ => price > mean,
+            };
+            if !stretched {
+                return None;
+            }
+        }
+
+        let excess = z.abs() - settings.entry_z;
+        let strength = if excess >= 2.0 {
+            SignalStrength::Strong
+        } else if excess >= 1.0 {
+            SignalStrength::Medium
+        } else {
+            SignalStrength::Weak
+        };
+
+        Some(TradingSignal {
+            id: uuid::Uuid::new_v4(),
+            timestamp: anomaly.timestamp,
+            symbol: anomaly.symbol.clone(),
+            exchange: anomaly.exchange.clone(),
+            signal_type,
+            strength,
+            price,
+            reason: format!(
+                "Mean reversion at z {:.2} toward {}: {}",
+                z,
+                anomaly
+                    .metrics
+                    .historical_avg
+                    .map_or("n/a".to_string(), |mean| format!("{:.8}", mean)),
+                anomaly.description
+            ),
+            anomaly_id: Some(anomaly.id),
+        })
+    }
+
+    fn update_config(&mut self, config: TradingConfig) {
+        self.config = config;
+    }
+
+    fn name(&self) -> &str {
+        "mean_reversion"
+    }
+}
+
+// A volume spike with the price already moving, as remembered by MomentumStrategy
+#[derive(Debug, Clone)]
+struct MomentumSpike {
+    at: DateTime<Utc>,
+    price_change_pct: f64,
+    volume_multiple: f64,
+}
+
+// Trades with the move once `min_spikes` volume spikes inside `window_secs` all saw the price
+// move the same way by at least `min_price_change_pct`; a lone spike has no follow-through yet
+pub struct MomentumStrategy {
+    config: TradingConfig,
+    spikes: HashMap<String, VecDeque<MomentumSpike>>,
+}
+
+impl MomentumStrategy {
+    pub fn new(config: TradingConfig) -> Self {
+        Self {
+            config,
+            spikes: HashMap::new(),
+        }
+    }
+}
+
+impl TradingStrategy for MomentumStrategy {
+    fn analyze(&mut self, anomaly: &AnomalyDetection, context: &MarketContext) -> Option<TradingSignal> {
+        if !overrides::trading_enabled(&self.config, &anomaly.exchange, &anomaly.symbol)
+            || anomaly.anomaly_type != AnomalyType::VolumeSpike {
+            return None;
+        }
+        // The anomaly carries volumes, the entry price comes from the trade feed
+        let price = context.last_price?;
+
+        let settings = &self.config.momentum;
+        let key = format!("{}:{}", anomaly.exchange, anomaly.symbol);
+        let window = Duration::seconds(settings.window_secs as i64);
+        let spikes = self.spikes.entry(key).or_default();
+        while spikes.front().is_some_and(|s| anomaly.timestamp - s.at > window) {
+            spikes.pop_front();
+        }
+
+        // A spike without a price move, or against the remembered move, restarts the count
+        let price_change_pct = match context.price_change_pct {
+            Some(change) if change.abs() >= settings.min_price_change_pct => change,
+            _ => {
+                spikes.clear();
+                return None;
+            }
+        };
+        if spikes
+            .back()
+            .is_some_and(|last| last.price_change_pct.signum() != price_change_pct.signum())
+        {
+            spikes.clear();
+        }
+
+        let metrics = &anomaly.metrics;
+        let volume_multiple = if metrics.expected_value > 0.0 {
+            metrics.current_value / metrics.expected_value
+        } else {
+            0.0
+        };
+        spikes.push_back(MomentumSpike {
+            at: anomaly.timestamp,
+            price_change_pct,
+            volume_multiple,
+        });
+        if spikes.len() < settings.min_spikes.max(1) {
+            return None;
+        }
+
+        let confirmed: Vec<MomentumSpike> = spikes.drain(..).collect();
+        let multiple = confirmed.iter().map(|s| s.volume_multiple).fold(0.0, f64::max);
+        let strength = if multiple >= settings.strong_volume_multiple {
+            SignalStrength::Strong
+        } else if multiple >= settings.strong_volume_multiple / 2.0 {
+            SignalStrength::Medium
+        } else {
+            SignalStrength::Weak
+        };
+        let signal_type = if price_change_pct > 0.0 { SignalType::Buy } else { SignalType::Sell };
+
+        Some(TradingSignal {
+            id: uuid::Uuid::new_v4(),
+            timestamp: anomaly.timestamp,
+            symbol: anomaly.symbol.clone(),
+            exchange: anomaly.exchange.clone(),
+            signal_type,
+            strength,
+            price,
+            reason: format!(
+                "Momentum: {} volume spikes up to {:.1}x with price {:+.2}%",
+                confirmed.len(),
+                multiple,
+                price_change_pct
+            ),
+            anomaly_id: Some(anomaly.id),
+        })
+    }
+
+    fn update_config(&mut self, config: TradingConfig) {
+        self.config = config;
+    }
+
+    fn name(&self) -> &str {
+        "momentum"
+    }
+}
+
+// Strategy by its configured name, as used by `trading.strategy` and `trading.ensemble.strategies`
+pub fn build(name: &str, config: &TradingConfig) -> Result<Box<dyn TradingStrategy>> {
+    match name {
+        "anomaly" => Ok(Box::new(AnomalyBasedStrategy::new(config.clone()))),
+        "rules" => Ok(Box::new(ConfigurableStrategy::new(config.clone())?)),
+        "mean_reversion" => Ok(Box::new(MeanReversionStrategy::new(config.clone()))),
+        "momentum" => Ok(Box::new(MomentumStrategy::new(config.clone()))),
+        _ => Err(MonitorError::Configuration(format!(
+            "Unknown strategy '{}', expected anomaly, rules, mean_reversion or momentum",
+            name
+        ))),
+    }
+}
+
+struct CompiledRule {
+    name: String,
+    source: String,
+    condition: Expr,
+    side: SignalType,
+    // Exact severity matches, falling back to the "default" entry
+    strengths: Vec<(AnomalySeverity, SignalStrength)>,
+    default_strength: Option<SignalStrength>,
+    cooldown: Duration,
+}
+
+impl CompiledRule {
+    fn strength(&self, severity: &AnomalySeverity) -> Option<SignalStrength> {
+        self.strengths
+            .iter()
+            .find(|(s, _)| s == severity)
+            .map(|(_, strength)| strength.clone())
+            .or_else(|| self.default_strength.clone())
+    }
+}
+
+fn parse_side(value: &str) -> Option<SignalType> {
+    match value.to_ascii_lowercase().as_str() {
+        "buy" => Some(SignalType::Buy),
+        "sell" => Some(SignalType::Sell),
+        _ => None,
+    }
+}
+
+fn parse_strength(value: &str) -> Option<SignalStrength> {
+    match value.to_ascii_lowercase().as_str() {
+        "weak" => Some(SignalStrength::Weak),
+        "medium" => Some(SignalStrength::Medium),
+        "strong" => Some(SignalStrength::Strong),
+        _ => None,
+    }
+}
+
+fn parse_severity(value: &str) -> Option<AnomalySeverity> {
+    match value.to_ascii_lowercase().as_str() {
+        "low" => Some(AnomalySeverity::Low),
+        "medium" => Some(AnomalySeverity::Medium),
+        "high" => Some(AnomalySeverity::High),
+        "critical" => Some(AnomalySeverity::Critical),
+        _ => None,
+    }
+}
+
+fn compile_rule(spec: &StrategyRuleConfig) -> Result<CompiledRule> {
+    let invalid = |field: &str, message: String| {
+        MonitorError::Configuration(format!("Strategy rule '{}' {}: {}", spec.name, field, message))
+    };
+
+    let condition = rules::compile(&spec.when).map_err(|e| invalid("when", e.to_string()))?;
+    let side = parse_side(&spec.side)
+        .ok_or_else(|| invalid("side", format!("expected buy or sell, found '{}'", spec.side)))?;
+    let strength = |value: &str| {
+        parse_strength(value).ok_or_else(|| {
+            invalid("strength", format!("expected Weak, Medium or Strong, found '{}'", value))
+        })
+    };
+
+    let (strengths, default_strength) = match &spec.strength {
+        StrengthConfig::Fixed(value) => (Vec::new(), Some(strength(value)?)),
+        StrengthConfig::BySeverity(map) => {
+            let mut strengths = Vec::new();
+            let mut default = None;
+            for (severity, value) in map {
+                if severity.eq_ignore_ascii_case("default") {
+                    default = Some(strength(value)?);
+                    continue;
+                }
+                let severity = parse_severity(severity).ok_or_else(|| {
+                    invalid("strength", format!("unknown severity '{}'", severity))
+                })?;
+                strengths.push((severity, strength(value)?));
+            }
+            (strengths, default)
+        }
+    };
+
+    Ok(CompiledRule {
+        name: spec.name.clone(),
+        source: spec.when.clone(),
+        condition,
+        side,
+        strengths,
+        default_strength,
+        cooldown: Duration::seconds(spec.cooldown_secs as i64),
+    })
+}
+
+fn compile_rules(specs: &[StrategyRuleConfig]) -> Result<Vec<CompiledRule>> {
+    let mut names = std::collections::HashSet::new();
+    for spec in specs {
+        if !names.insert(spec.name.as_str()) {
+            return Err(MonitorError::Configuration(format!(
+                "Duplicate strategy rule '{}'",
+                spec.name
+            )));
+        }
+    }
+    specs.iter().map(compile_rule).collect()
+}
+
+// Interprets `trading.rules` from config, the first matching rule outside its cooldown decides
+// the signal. Rules are compiled up front so a bad expression fails at load or reload time.
+pub struct ConfigurableStrategy {
+    config: TradingConfig,
+    rules: Vec<CompiledRule>,
+    // Last signal per (rule, "exchange:symbol")
+    last_fired: HashMap<(String, String), DateTime<Utc>>,
+}
+
+impl ConfigurableStrategy {
+    pub fn new(config: TradingConfig) -> Result<Self> {
+        let rules = compile_rules(&config.rules)?;
+        info!("Loaded {} strategy rules", rules.len());
+
+        Ok(Self {
+            config,
+            rules,
+            last_fired: HashMap::new(),
+        })
+    }
+}
+
+impl TradingStrategy for ConfigurableStrategy {
+    fn analyze(&mut self, anomaly: &AnomalyDetection, context: &MarketContext) -> Option<TradingSignal> {
+        if !overrides::trading_enabled(&self.config, &anomaly.exchange, &anomaly.symbol) {
+            return None;
+        }
+
+        let key = format!("{}:{}", anomaly.exchange, anomaly.symbol);
+        for rule in &self.rules {
+            let fired_key = (rule.name.clone(), key.clone());
+            let cooling = self
+                .last_fired
+                .get(&fired_key)
+                .is_some_and(|last| anomaly.timestamp < *last + rule.cooldown);
+            if cooling || !rule.condition.evaluate(anomaly, context) {
+                continue;
+            }
+            let Some(strength) = rule.strength(&anomaly.severity) else {
+                continue;
+            };
+
+            self.last_fired.insert(fired_key, anomaly.timestamp);
+            return Some(TradingSignal {
+                id: uuid::Uuid::new_v4(),
+                timestamp: anomaly.timestamp,
+                symbol: anomaly.symbol.clone(),
+                exchange: anomaly.exchange.clone(),
+                signal_type: rule.side.clone(),
+                strength,
+                price: anomaly.metrics.current_value,
+                reason: format!("Rule {} matched ({}): {}", rule.name, rule.source, anomaly.description),
+                anomaly_id: Some(anomaly.id),
+            });
+        }
+
+        None
+    }
+
+    // A reload with an invalid rule keeps the previous rules running
+    fn update_config(&mut self, config: TradingConfig) {
+        match compile_rules(&config.rules) {
+            Ok(rules) => {
+                info!("Reloaded {} strategy rules", rules.len());
+                self.last_fired
+                    .retain(|(rule, _), _| rules.iter().any(|r| &r.name == rule));
+                self.rules = rules;
+            }
+            Err(e) => error!("Keeping previous strategy rules: {}", e),
+        }
+        self.config = config;
+    }
+
+    fn name(&self) -> &str {
+        "rules"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use monitor_anomaly::AnomalyMetrics;
+
+    fn config(rules: serde_json::Value) -> TradingConfig {
+        fixtures::trading_config(serde_json::json!({ "rules": rules }))
+    }
+
+    fn anomaly(anomaly_type: AnomalyType, severity: AnomalySeverity, at: DateTime<Utc>) -> AnomalyDetection {
+        AnomalyDetection {
+            id: uuid::Uuid::new_v4(),
+            timestamp: at,
+            symbol: "BTC/USDT".to_string(),
+            exchange: "binance".to_string(),
+            anomaly_type,
+            confidence: severity.confidence_floor(),
+            severity,
+            metrics: AnomalyMetrics {
+                current_value: 42_000.0,
+                expected_value: 40_000.0,
+                deviation: 2_000.0,
+                z_score: Some(3.5),
+                percentage_change: Some(5.0),
+                historical_avg: None,
+                historical_std: None,
+                ..Default::default()
+            },
+            description: "spike".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn oversold(rsi: f64) -> MarketContext {
+        MarketContext {
+            rsi: Some(rsi),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_signals_from_spec() {
+        let mut strategy = ConfigurableStrategy::new(config(serde_json::json!([
+            {
+                "name": "oversold-volume",
+                "when": "anomaly_type == VolumeSpike AND severity >= High AND rsi < 35",
+                "side": "buy",
+                "strength": { "Critical": "Strong", "default": "Medium" },
+                "cooldown_secs": 600,
+            },
+            {
+                "name": "fade-spike",
+                "when": "anomaly_type == PriceSpike AND percentage_change > 3",
+                "side": "sell",
+            },
+        ])))
+        .unwrap();
+        let start = Utc::now();
+
+        let signal = strategy
+            .analyze(&anomaly(AnomalyType::VolumeSpike, AnomalySeverity::Critical, start), &oversold(30.0))
+            .unwrap();
+        assert!(matches!(signal.signal_type, SignalType::Buy));
+        assert!(matches!(signal.strength, SignalStrength::Strong));
+        assert_eq!(signal.price, 42_000.0);
+        assert!(signal.reason.starts_with("Rule oversold-volume matched"));
+
+        // Inside the rule's cooldown
+        let later = start + Duration::seconds(60);
+        assert!(strategy
+            .analyze(&anomaly(AnomalyType::VolumeSpike, AnomalySeverity::High, later), &oversold(30.0))
+            .is_none());
+        let after = start + Duration::seconds(601);
+        let signal = strategy
+            .analyze(&anomaly(AnomalyType::VolumeSpike, AnomalySeverity::High, after), &oversold(30.0))
+            .unwrap();
+        assert!(matches!(signal.strength, SignalStrength::Medium));
+
+        // RSI too high, or not known yet
+        let next = start + Duration::seconds(2000);
+        let volume = anomaly(AnomalyType::VolumeSpike, AnomalySeverity::High, next);
+        assert!(strategy.analyze(&volume, &oversold(50.0)).is_none());
+        assert!(strategy.analyze(&volume, &MarketContext::default()).is_none());
+
+        let signal = strategy
+            .analyze(&anomaly(AnomalyType::PriceSpike, AnomalySeverity::Low, start), &MarketContext::default())
+            .unwrap();
+        assert!(matches!(signal.signal_type, SignalType::Sell));
+        assert!(matches!(signal.strength, SignalStrength::Medium));
+
+        // A volatility regime change moves percentage_change as well, but is not a price spike
+        let regime = anomaly(AnomalyType::VolatilityRegime, AnomalySeverity::Low, start);
+        assert!(strategy.analyze(&regime, &MarketContext::default()).is_none());
+    }
+
+    #[test]
+    fn test_invalid_rules_fail_load_and_keep_previous_on_reload() {
+        let bad = config(serde_json::json!([
+            { "name": "typo", "when": "severity >= High AND rsii < 35", "side": "buy" },
+        ]));
+        let err = ConfigurableStrategy::new(bad.clone()).err().unwrap().to_string();
+        assert!(err.contains("Strategy rule 'typo' when: unknown field 'rsii'"));
+        assert!(err.contains("^^^^"));
+
+        let bad_side = config(serde_json::json!([{ "name": "x", "when": "rsi < 30", "side": "long" }]));
+        assert!(ConfigurableStrategy::new(bad_side).is_err());
+
+        let mut strategy = ConfigurableStrategy::new(config(serde_json::json!([
+            { "name": "any-spike", "when": "anomaly_type == PriceSpike", "side": "sell" },
+        ])))
+        .unwrap();
+        strategy.update_config(bad);
+
+        let spike = anomaly(AnomalyType::PriceSpike, AnomalySeverity::Low, Utc::now());
+        assert!(strategy.analyze(&spike, &MarketContext::default()).is_some());
+    }
+
+    fn price_spike(z: f64, current: f64, at: DateTime<Utc>) -> AnomalyDetection {
+        let mut spike = anomaly(AnomalyType::PriceSpike, AnomalySeverity::High, at);
+        spike.metrics.z_score = Some(z);
+        spike.metrics.current_value = current;
+        spike.metrics.historical_avg = Some(40_000.0);
+        spike
+    }
+
+    fn mean_reversion() -> MeanReversionStrategy {
+        MeanReversionStrategy::new(config(serde_json::json!([])))
+    }
+
+    #[test]
+    fn test_mean_reversion_fades_confirmed_spikes() {
+        let mut strategy = mean_reversion();
+        let start = Utc::now();
+        let context = MarketContext::default();
+
+        // Unconfirmed by volume
+        assert!(strategy.analyze(&price_spike(-3.0, 38_000.0, start), &context).is_none());
+
+        let volume = anomaly(AnomalyType::VolumeSpike, AnomalySeverity::High, start);
+        assert!(strategy.analyze(&volume, &context).is_none());
+
+        let later = start + Duration::seconds(10);
+        let signal = strategy.analyze(&price_spike(-3.0, 38_000.0, later), &context).unwrap();
+        assert!(matches!(signal.signal_type, SignalType::Buy));
+        assert!(matches!(signal.strength, SignalStrength::Weak));
+
+        let signal = strategy.analyze(&price_spike(4.0, 42_000.0, later), &context).unwrap();
+        assert!(matches!(signal.signal_type, SignalType::Sell));
+        assert!(matches!(signal.strength, SignalStrength::Medium));
+
+        let signal = strategy.analyze(&price_spike(-5.0, 37_000.0, later), &context).unwrap();
+        assert!(matches!(signal.strength, SignalStrength::Strong));
+
+        // Confirmation expires with the volume window
+        let stale = start + Duration::seconds(301);
+        assert!(strategy.analyze(&price_spike(-3.0, 38_000.0, stale), &context).is_none());
+    }
+
+    #[test]
+    fn test_mean_reversion_refuses_crashes_and_mild_moves() {
+        let mut strategy = mean_reversion();
+        let start = Utc::now();
+        let context = MarketContext::default();
+        strategy.analyze(&anomaly(AnomalyType::VolumeSpike, AnomalySeverity::High, start), &context);
+
+        // Beyond max |z| it looks like a crash, not an overreaction
+        assert!(strategy.analyze(&price_spike(-6.5, 30_000.0, start), &context).is_none());
+        // Below the entry threshold
+        assert!(strategy.analyze(&price_spike(-2.0, 39_000.0, start), &context).is_none());
+        // A negative z with the price above the mean has nothing to revert
+        assert!(strategy.analyze(&price_spike(-3.0, 41_000.0, start), &context).is_none());
+        // Other anomaly types are ignored
+        let mut volatility = anomaly(AnomalyType::VolatilityRegime, AnomalySeverity::High, start);
+        volatility.metrics.z_score = Some(-3.0);
+        assert!(strategy.analyze(&volatility, &context).is_none());
+    }
+
+    fn volume_spike(multiple: f64, at: DateTime<Utc>) -> AnomalyDetection {
+        let mut spike = anomaly(AnomalyType::VolumeSpike, AnomalySeverity::High, at);
+        spike.metrics.current_value = 100.0 * multiple;
+        spike.metrics.expected_value = 100.0;
+        spike
+    }
+
+    fn moving(price_change_pct: f64) -> MarketContext {
+        MarketContext {
+            last_price: Some(42_000.0),
+            price_change_pct: Some(price_change_pct),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_momentum_needs_follow_through() {
+        let mut strategy = MomentumStrategy::new(config(serde_json::json!([])));
+        let start = Utc::now();
+
+        // One spike is not enough
+        assert!(strategy.analyze(&volume_spike(3.0, start), &moving(1.5)).is_none());
+        let signal = strategy
+            .analyze(&volume_spike(4.0, start + Duration::seconds(60)), &moving(2.0))
+            .unwrap();
+        assert!(matches!(signal.signal_type, SignalType::Buy));
+        assert!(matches!(signal.strength, SignalStrength::Medium));
+        assert_eq!(signal.price, 42_000.0);
+
+        // State is consumed by the signal, the next spike starts over
+        assert!(strategy
+            .analyze(&volume_spike(8.0, start + Duration::seconds(120)), &moving(2.0))
+            .is_none());
+        let signal = strategy
+            .analyze(&volume_spike(2.0, start + Duration::seconds(180)), &moving(2.5))
+            .unwrap();
+        assert!(matches!(signal.strength, SignalStrength::Strong));
+    }
+
+    #[test]
+    fn test_momentum_resets_on_reversal_stall_and_expiry() {
+        let mut strategy = MomentumStrategy::new(config(serde_json::json!([])));
+        let start = Utc::now();
+        let at = |secs: i64| start + Duration::seconds(secs);
+
+        // Direction flips between spikes
+        assert!(strategy.analyze(&volume_spike(3.0, at(0)), &moving(1.5)).is_none());
+        assert!(strategy.analyze(&volume_spike(2.0, at(30)), &moving(-1.5)).is_none());
+        let signal = strategy.analyze(&volume_spike(2.0, at(60)), &moving(-2.0)).unwrap();
+        assert!(matches!(signal.signal_type, SignalType::Sell));
+        assert!(matches!(signal.strength, SignalStrength::Weak));
+
+        // A spike while the price is flat breaks the run
+        assert!(strategy.analyze(&volume_spike(3.0, at(100)), &moving(1.5)).is_none());
+        assert!(strategy.analyze(&volume_spike(3.0, at(130)), &moving(0.2)).is_none());
+        assert!(strategy.analyze(&volume_spike(3.0, at(160)), &moving(1.5)).is_none());
+
+        // The earlier spike left the window
+        assert!(strategy.analyze(&volume_spike(3.0, at(900)), &moving(1.5)).is_none());
+
+        // Other anomaly types and unknown prices never trade
+        let price = anomaly(AnomalyType::PriceSpike, AnomalySeverity::High, at(910));
+        assert!(strategy.analyze(&price, &moving(5.0)).is_none());
+        let blind = MarketContext {
+            price_change_pct: Some(5.0),
+            ..Default::default()
+        };
+        assert!(strategy.analyze(&volume_spike(3.0, at(920)), &blind).is_none());
+    }
+
+    #[test]
+    fn test_strategies_build_by_name() {
+        let config = config(serde_json::json!([]));
+        assert_eq!(build("mean_reversion", &config).unwrap().name(), "mean_reversion");
+        assert_eq!(build("anomaly", &config).unwrap().name(), "anomaly");
+        assert_eq!(build("momentum", &config).unwrap().name(), "momentum");
+        assert!(build("momentum?", &config).is_err());
+    }
+}
@@ -0,0 +1,276 @@
+use crate::{
+    overrides::MarketConfig,
+    risk::{
+        check_exposure, strength_multiplier, ExposureRejection, OrderRejection, PendingEntry,
+        SimpleRiskManager,
+    },
+    Position, PositionSide, RiskManager, TradingSignal,
+};
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use monitor_anomaly::{TimeSeriesData, TimeSeriesWindow};
+use monitor_core::{instrument::InstrumentKey, TradingConfig};
+use parking_lot::RwLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const SECS_PER_DAY: f64 = 86_400.0;
+
+// A market's log returns, each stamped with the price that closed it
+#[derive(Debug)]
+struct Returns {
+    last: (DateTime<Utc>, f64),
+    window: TimeSeriesWindow,
+}
+
+// Realized volatility of each market from its trade prices, scaled to a day by the average
+// time between them
+#[derive(Debug, Default)]
+pub struct RealizedVolatility {
+    returns: DashMap<InstrumentKey, Returns>,
+}
+
+impl RealizedVolatility {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Keeps the last `window` returns. Prices older than the last one recorded are dropped
+    pub fn record(&self, key: &InstrumentKey, price: f64, at: DateTime<Utc>, window: usize) {
+        if price <= 0.0 {
+            return;
+        }
+        let Some(mut returns) = self.returns.get_mut(key) else {
+            self.returns.entry(key.clone()).or_insert_with(|| Returns {
+                last: (at, price),
+                window: TimeSeriesWindow::new(window.max(1)),
+            });
+            return;
+        };
+        let (last_at, last_price) = returns.last;
+        if at < last_at {
+            return;
+        }
+        // A new window size starts the history over
+        if returns.window.max_size != window.max(1) {
+            returns.window = TimeSeriesWindow::new(window.max(1));
+        }
+        returns.window.push(TimeSeriesData {
+            timestamp: at,
+            value: (price / last_price).ln(),
+        });
+        returns.last = (at, price);
+    }
+
+    // Standard deviation of daily log returns, `None` with fewer than `min_samples` returns
+    pub fn daily(&self, key: &str, min_samples: usize) -> Option<f64> {
+        let returns = self.returns.get(key)?;
+        let window = &returns.window;
+        if window.len() < min_samples.max(2) {
+            return None;
+        }
+        let (first, last) = (window.data.front()?.timestamp, window.data.back()?.timestamp);
+        let span = (last - first).num_milliseconds() as f64 / 1000.0;
+        if span <= 0.0 {
+            return None;
+        }
+
+        let secs_per_return = span / (window.len() - 1) as f64;
+        Some(window.std_dev() * (SECS_PER_DAY / secs_per_return).sqrt())
+    }
+}
+
+// Sizes each position so its expected daily move is `volatility_target.target_bps` of the
+// portfolio, scaled by signal strength, and places the exits a multiple of that move away.
+// Markets without enough price history get the simple risk manager's sizing and exits
+pub struct VolatilityTargetRiskManager {
+    config: RwLock<TradingConfig>,
+    fallback: SimpleRiskManager,
+    volatility: RealizedVolatility,
+    // `volatility_target.window`, read on every price without taking the config lock
+    window: AtomicUsize,
+}
+
+impl VolatilityTargetRiskManager {
+    pub fn new(config: TradingConfig) -> Self {
+        Self {
+            fallback: SimpleRiskManager::new(config.clone()),
+            window: AtomicUsize::new(config.volatility_target.window),
+            config: RwLock::new(config),
+            volatility: RealizedVolatility::new(),
+        }
+    }
+
+    // Daily volatility of the signal's market, `None` while its history is too short
+    pub fn volatility(&self, signal: &TradingSignal) -> Option<f64> {
+        let min_samples = self.config.read().volatility_target.min_samples;
+        let key = InstrumentKey::new(&signal.exchange, &signal.symbol);
+        self.volatility
+            .daily(&key, min_samples)
+            .filter(|volatility| *volatility > 0.0)
+    }
+
+    fn market_config<'a>(config: &'a TradingConfig, signal: &TradingSignal) -> MarketConfig<'a> {
+        MarketConfig::new(config, &signal.exchange, &signal.symbol)
+    }
+
+    // `distance` away from the entry as a share of it, on the losing side for a stop
+    fn exit_level(entry_price: f64, distance: f64, side: PositionSide, stop: bool) -> f64 {
+        let up = (side == PositionSide::Long) != stop;
+        if up {
+            entry_price * (1.0 + distance)
+        } else {
+            entry_price * (1.0 - distance).max(0.0)
+        }
+    }
+}
+
+impl RiskManager for VolatilityTargetRiskManager {
+    fn validate_order(
+        &self,
+        signal: &TradingSignal,
+        portfolio_value: f64,
+    ) -> Option<OrderRejection> {
+        if self.volatility(signal).is_none() {
+            return self.fallback.validate_order(signal, portfolio_value);
+        }
+        let value = signal.price * self.calculate_position_size(signal, portfolio_value);
+        let max = Self::market_config(&self.config.read(), signal).max_position_size();
+        (value > max).then_some(OrderRejection::MaxPositionSize { value, max })
+    }
+
+    fn validate_portfolio(
+        &self,
+        signal: &TradingSignal,
+        open_positions: &[Position],
+        pending: &[PendingEntry],
+        portfolio_value: f64,
+    ) -> Option<ExposureRejection> {
+        let order_value = signal.price * self.calculate_position_size(signal, portfolio_value);
+        check_exposure(&self.config.read(), signal, order_value, open_positions, pending)
+    }
+
+    fn calculate_position_size(&self, signal: &TradingSignal, portfolio_value: f64) -> f64 {
+        let Some(volatility) = self.volatility(signal) else {
+            return self.fallback.calculate_position_size(signal, portfolio_value);
+        };
+        if signal.price <= 0.0 {
+            return 0.0;
+        }
+
+        let config = self.config.read();
+        let config = Self::market_config(&config, signal);
+        let target = portfolio_value * config.base.volatility_target.target_bps / 10_000.0
+            * strength_multiplier(&config.base.sizing, &signal.strength);
+        (target / volatility / signal.price).min(config.max_position_size() / signal.price)
+    }
+
+    fn get_stop_loss(&self, signal: &TradingSignal, entry_price: f64, side: PositionSide) -> f64 {
+        let Some(volatility) = self.volatility(signal) else {
+            return self.fallback.get_stop_loss(signal, entry_price, side);
+        };
+        let multiplier = self.config.read().volatility_target.stop_loss_multiplier;
+        Self::exit_level(entry_price, volatility * multiplier, side, true)
+    }
+
+    fn get_take_profit(&self, signal: &TradingSignal, entry_price: f64, side: PositionSide) -> f64 {
+        let Some(volatility) = self.volatility(signal) else {
+            return self.fallback.get_take_profit(signal, entry_price, side);
+        };
+        let multiplier = self.config.read().volatility_target.take_profit_multiplier;
+        Self::exit_level(entry_price, volatility * multiplier, side, false)
+    }
+
+    fn update_config(&self, config: TradingConfig) {
+        self.fallback.update_config(config.clone());
+        self.window.store(config.volatility_target.window, Ordering::Relaxed);
+        *self.config.write() = config;
+    }
+
+    fn record_price(&self, key: &InstrumentKey, price: f64, at: DateTime<Utc>) {
+        self.volatility.record(key, price, at, self.window.load(Ordering::Relaxed));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use monitor_core::RiskManagerKind;
+    use chrono::Duration;
+
+    fn config() -> TradingConfig {
+        fixtures::trading_config(serde_json::json!({
+            "max_position_size": 100000.0,
+            "risk_manager": "volatility_target",
+            "volatility_target": { "target_bps": 50.0, "min_samples": 30 },
+        }))
+    }
+
+    fn signal(symbol: &str) -> TradingSignal {
+        fixtures::signal(symbol, 100.0)
+    }
+
+    // A price a minute for an hour, swinging `swing` either way
+    fn feed(manager: &VolatilityTargetRiskManager, symbol: &str, swing: f64) {
+        let start = Utc::now() - Duration::hours(1);
+        for minute in 0..=60 {
+            let price = if minute % 2 == 0 { 100.0 } else { 100.0 * (1.0 + swing) };
+            manager.record_price(
+                &InstrumentKey::new("binance", symbol),
+                price,
+                start + Duration::minutes(minute),
+            );
+        }
+    }
+
+    #[test]
+    fn test_volatile_markets_get_smaller_positions() {
+        let manager = VolatilityTargetRiskManager::new(config());
+        feed(&manager, "BTC/USDT", 0.001);
+        feed(&manager, "DOGE/USDT", 0.01);
+
+        let calm = manager.calculate_position_size(&signal("BTC/USDT"), 10_000.0);
+        let volatile = manager.calculate_position_size(&signal("DOGE/USDT"), 10_000.0);
+        assert!(volatile < calm);
+        // Ten times the swing, about a tenth of the size
+        assert!(volatile * 9.0 < calm && calm < volatile * 11.0);
+
+        // 50 bps of the portfolio is the expected daily move of either position
+        let volatility = manager.volatility(&signal("DOGE/USDT")).unwrap();
+        assert!((volatile * 100.0 * volatility - 50.0).abs() < 1e-6);
+        // Exits one and two daily moves from the entry
+        let stop = manager.get_stop_loss(&signal("DOGE/USDT"), 100.0, PositionSide::Long);
+        assert!((stop - 100.0 * (1.0 - volatility)).abs() < 1e-9);
+        let target = manager.get_take_profit(&signal("DOGE/USDT"), 100.0, PositionSide::Short);
+        assert!((target - 100.0 * (1.0 - 2.0 * volatility)).abs() < 1e-9);
+        assert!(manager.validate_order(&signal("DOGE/USDT"), 10_000.0).is_none());
+    }
+
+    #[test]
+    fn test_short_history_falls_back_to_simple_sizing() {
+        let manager = VolatilityTargetRiskManager::new(config());
+        let simple = SimpleRiskManager::new(config());
+        // 20 returns, under `min_samples`
+        let start = Utc::now();
+        for second in 0..=20 {
+            let price = 100.0 + (second % 2) as f64;
+            let key = InstrumentKey::new("binance", "ETH/USDT");
+            manager.record_price(&key, price, start + Duration::seconds(second));
+        }
+
+        let eth = signal("ETH/USDT");
+        assert!(manager.volatility(&eth).is_none());
+        assert_eq!(
+            manager.calculate_position_size(&eth, 10_000.0),
+            simple.calculate_position_size(&eth, 10_000.0)
+        );
+        assert_eq!(
+            manager.get_stop_loss(&eth, 100.0, PositionSide::Long),
+            simple.get_stop_loss(&eth, 100.0, PositionSide::Long)
+        );
+
+        assert_eq!(config().risk_manager, RiskManagerKind::VolatilityTarget);
+        let unknown = serde_json::from_value::<RiskManagerKind>(serde_json::json!("kelly"));
+        assert!(unknown.is_err());
+    }
+}